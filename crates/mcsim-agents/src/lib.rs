@@ -11,14 +11,29 @@
 //! - [`CliAgent`] - An agent that communicates with MeshCore repeater and room server
 //!   firmware using the text-based CLI protocol. This agent applies configuration
 //!   at node startup (password, CLI commands).
+//!
+//! An [`Agent`] normally stays connected to its companion for the whole run.
+//! With [`PhoneAppConfig`] enabled it instead behaves like a phone app: it
+//! connects and disconnects periodically, syncs queued messages on each
+//! connect and optionally answers received DMs with read receipts.
+//!
+//! Either way the agent tracks its sync state: the messages the companion has
+//! queued for it (announced with a message-waiting push, which the companion
+//! sends even while the app is away) against the messages it has fetched. The
+//! difference is the sync backlog, reported as the `mcsim.sync.*` metrics.
+//!
+//! With [`GpsConfig`] enabled the agent also shares the node's location
+//! with the companion, as a phone app does: it sets the advert location once
+//! ready, and again as position fixes from the Graph show the node moving,
+//! at most once per interval.
 
 pub mod cli_agent;
 
 pub use cli_agent::{CliAgent, CliAgentConfig, CliProtocolState, create_cli_agent};
 
 use mcsim_common::{
-    entity_tracer::TraceEvent, Entity, EntityId, Event, EventPayload, NodeId, SerialRxEvent,
-    SimContext, SimError, SimTime,
+    entity_tracer::TraceEvent, rng::substream, Entity, EntityId, Event, EventPayload, GeoCoord, NodeId,
+    SerialRxEvent, SimContext, SimError, SimTime,
 };
 use mcsim_companion_protocol::{
     Command, ChannelInfo, ContactInfo, Message, ProtocolSession, PushNotification, PublicKey,
@@ -30,6 +45,7 @@ use rand::Rng;
 use rand_chacha::ChaCha8Rng;
 use rand_distr::{Distribution, Normal};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use tracing::{debug, error, info, trace, warn};
 
 // ============================================================================
@@ -227,6 +243,260 @@ impl Default for ChannelMessageConfig {
     }
 }
 
+/// How a scheduled message picks its destination when it fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DestinationStrategy {
+    /// Send to the first (nearest) candidate.
+    Nearest,
+    /// Send to a candidate chosen at random when the message fires.
+    Random,
+}
+
+impl std::str::FromStr for DestinationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "nearest" => Ok(DestinationStrategy::Nearest),
+            "random" => Ok(DestinationStrategy::Random),
+            other => Err(format!(
+                "Unknown destination strategy '{}' (expected nearest or random)",
+                other
+            )),
+        }
+    }
+}
+
+/// A one-off direct message scheduled at an absolute simulation time.
+///
+/// Group actions in the model expand into one of these per sending agent, so a
+/// scenario can address hundreds of nodes without listing individual sends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessage {
+    /// Simulation time (seconds) at which to send, or as soon as the agent
+    /// is ready if that is later.
+    pub at_s: f64,
+    /// Possible destinations, ordered nearest first.
+    pub candidates: Vec<NodeId>,
+    /// How to pick the destination from `candidates`.
+    pub strategy: DestinationStrategy,
+    /// Message text (a default is generated if None).
+    pub text: Option<String>,
+}
+
+impl ScheduledMessage {
+    /// Pick the destination for this message.
+    pub fn select_destination<R: Rng>(&self, rng: &mut R) -> Option<NodeId> {
+        match self.strategy {
+            DestinationStrategy::Nearest => self.candidates.first().copied(),
+            DestinationStrategy::Random if self.candidates.is_empty() => None,
+            DestinationStrategy::Random => {
+                Some(self.candidates[rng.gen_range(0..self.candidates.len())])
+            }
+        }
+    }
+}
+
+/// Where a traffic rule sends its messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TrafficDestination {
+    /// Direct message to a node (must be one of the agent's contacts).
+    Direct(NodeId),
+    /// Post to a channel (must be one of the agent's channels).
+    Channel(ChannelTarget),
+}
+
+/// Which received messages trigger a traffic rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TrafficSource {
+    /// Direct message from a node (must be one of the agent's contacts).
+    Direct(NodeId),
+    /// Post on the named channel (must be one of the agent's channels).
+    Channel(String),
+}
+
+/// Received message that makes a traffic rule send.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrafficTrigger {
+    /// Where the message comes from.
+    pub source: TrafficSource,
+    /// Only messages whose text contains this trigger the rule.
+    pub text_contains: Option<String>,
+}
+
+/// A scripted message, sent once or repeatedly from an absolute time, or
+/// in response to received messages.
+///
+/// Unlike the direct and channel state machines, traffic rules don't wait
+/// for ACKs or rotate targets: each rule fires on its own schedule, so a
+/// scenario can describe exactly what application traffic each node sends.
+/// A rule with a trigger sends one message per matching received message,
+/// to model replies and forwarding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrafficRule {
+    /// Where to send.
+    pub destination: TrafficDestination,
+    /// Simulation time (seconds) of the first message, or from which
+    /// triggers are honored.
+    pub at_s: f64,
+    /// Interval between messages. If None, the message is sent once.
+    pub interval_s: Option<f64>,
+    /// Standard deviation of the randomness in the interval (or in the
+    /// delay of a triggered rule).
+    pub interval_jitter_s: f64,
+    /// Count of messages before the rule stops.
+    /// If None, the rule sends until `until_s` or the end of the run.
+    pub message_count: Option<u32>,
+    /// Simulation time (seconds) after which no more messages are sent.
+    pub until_s: Option<f64>,
+    /// Message text (a default is generated if None).
+    pub text: Option<String>,
+    /// Send in response to received messages instead of from `at_s`.
+    #[serde(default)]
+    pub trigger: Option<TrafficTrigger>,
+    /// Delay (seconds) between a trigger and the message it sends.
+    #[serde(default)]
+    pub delay_s: f64,
+    /// Custom counter (see [`mcsim_metrics::custom`]) incremented for each
+    /// message sent, with a `node` label if the counter declares one.
+    #[serde(default)]
+    pub metric: Option<String>,
+}
+
+impl TrafficRule {
+    /// Whether the rule may still send after `sent` messages at `now_s`.
+    pub fn is_active(&self, sent: u32, now_s: f64) -> bool {
+        self.message_count.is_none_or(|limit| sent < limit)
+            && self.until_s.is_none_or(|until| now_s <= until)
+    }
+
+    /// Whether a message from `source` with `text`, received at `now_s`,
+    /// triggers the rule.
+    fn is_triggered_by(&self, source: &TrafficSource, text: &str, now_s: f64) -> bool {
+        let Some(trigger) = &self.trigger else {
+            return false;
+        };
+        trigger.source == *source
+            && now_s >= self.at_s
+            && trigger.text_contains.as_ref().is_none_or(|needle| text.contains(needle.as_str()))
+    }
+}
+
+/// Encode traffic rules as the script of a
+/// [`ReloadScriptEvent`](mcsim_common::ReloadScriptEvent), which replaces an
+/// agent's rules mid-run (see [`Agent::reload_traffic`]).
+pub fn encode_traffic_script(rules: &[TrafficRule]) -> String {
+    serde_json::to_string(rules).expect("traffic rules always serialize")
+}
+
+/// Text of the DM sent as a read receipt.
+///
+/// Messages with this text never get a receipt of their own, so two phone
+/// agents don't acknowledge each other's receipts forever.
+pub const READ_RECEIPT_TEXT: &str = "[read]";
+
+/// Phone app behavior profile.
+///
+/// A phone app isn't attached to its companion all the time: it connects
+/// over BLE or TCP while the app is in the foreground, pulls the messages the
+/// companion queued while it was away, and drops the connection again when
+/// the app goes to the background. Enabling this profile makes the agent
+/// follow that pattern instead of acting as an always-connected host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhoneAppConfig {
+    /// Whether the agent connects and disconnects like a phone app.
+    pub enabled: bool,
+    /// Time the app stays connected.
+    pub connected_s: f64,
+    /// Standard deviation of randomness in the connected time.
+    pub connected_jitter_s: f64,
+    /// Time the app spends disconnected in the background.
+    pub disconnected_s: f64,
+    /// Standard deviation of randomness in the disconnected time.
+    pub disconnected_jitter_s: f64,
+    /// Whether received DMs are answered with a read receipt.
+    pub read_receipts: bool,
+    /// Time between receiving a DM and the user reading it.
+    pub read_delay_s: f64,
+    /// Standard deviation of randomness in the read delay.
+    pub read_delay_jitter_s: f64,
+}
+
+impl Default for PhoneAppConfig {
+    fn default() -> Self {
+        PhoneAppConfig {
+            enabled: false,
+            connected_s: 300.0,
+            connected_jitter_s: 60.0,
+            disconnected_s: 1800.0,
+            disconnected_jitter_s: 600.0,
+            read_receipts: false,
+            read_delay_s: 30.0,
+            read_delay_jitter_s: 10.0,
+        }
+    }
+}
+
+/// Room server client behavior.
+///
+/// The agent logs in to a room server, posts to it on a schedule and counts
+/// the posts the room pushes back (its history on login, then other
+/// clients' posts as they arrive).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomClientConfig {
+    /// Room server to join (disabled when None). Must also be a contact.
+    pub room: Option<NodeId>,
+    /// Guest password sent with the login.
+    pub password: String,
+    /// Delay after the agent is ready before logging in.
+    pub login_s: f64,
+    /// Interval between posts once logged in.
+    pub post_interval_s: f64,
+    /// Standard deviation of randomness in the post interval.
+    pub post_interval_jitter_s: f64,
+    /// Count of posts before the agent stops posting (and only reads).
+    /// If None, posts until the end of the run.
+    pub post_count: Option<u32>,
+}
+
+impl Default for RoomClientConfig {
+    fn default() -> Self {
+        RoomClientConfig {
+            room: None,
+            password: "hello".to_string(),
+            login_s: 30.0,
+            post_interval_s: 600.0,
+            post_interval_jitter_s: 120.0,
+            post_count: None,
+        }
+    }
+}
+
+/// Location sharing with the companion.
+///
+/// The agent sets the companion's advert location from the node's position:
+/// its configured location at first, then the position fixes the Graph
+/// sends as the node moves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpsConfig {
+    /// Whether the agent shares the node's location.
+    pub enabled: bool,
+    /// Position until the first fix.
+    pub position: Option<GeoCoord>,
+    /// Shortest time between location updates, in seconds.
+    pub interval_s: f64,
+}
+
+impl Default for GpsConfig {
+    fn default() -> Self {
+        GpsConfig {
+            enabled: false,
+            position: None,
+            interval_s: 60.0,
+        }
+    }
+}
+
 /// Unified agent configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentConfig {
@@ -239,6 +509,20 @@ pub struct AgentConfig {
     /// Contacts to add to firmware's contact list at startup.
     /// Required for DM communication - firmware needs contacts to decrypt/ACK messages.
     pub contacts: Vec<ContactTarget>,
+    /// One-off direct messages sent at fixed times (from group actions).
+    pub scheduled: Vec<ScheduledMessage>,
+    /// Phone app connection behavior (always connected when disabled).
+    #[serde(default)]
+    pub phone: PhoneAppConfig,
+    /// Scripted traffic sent on fixed schedules.
+    #[serde(default)]
+    pub traffic: Vec<TrafficRule>,
+    /// Room server client behavior.
+    #[serde(default)]
+    pub room: RoomClientConfig,
+    /// Location sharing with the companion.
+    #[serde(default)]
+    pub gps: GpsConfig,
 }
 
 impl Default for AgentConfig {
@@ -248,6 +532,11 @@ impl Default for AgentConfig {
             direct: DirectMessageConfig::default(),
             channel: ChannelMessageConfig::default(),
             contacts: Vec::new(),
+            scheduled: Vec::new(),
+            phone: PhoneAppConfig::default(),
+            traffic: Vec::new(),
+            room: RoomClientConfig::default(),
+            gps: GpsConfig::default(),
         }
     }
 }
@@ -255,7 +544,7 @@ impl Default for AgentConfig {
 impl AgentConfig {
     /// Check if this agent has any messaging behavior enabled.
     pub fn is_enabled(&self) -> bool {
-        self.direct.enabled || self.channel.enabled
+        self.direct.enabled || self.channel.enabled || !self.traffic.is_empty() || self.room.room.is_some()
     }
 }
 
@@ -338,6 +627,20 @@ const TIMER_CHANNEL_INTERVAL: u64 = 6;
 const TIMER_CHANNEL_SESSION: u64 = 7;
 const TIMER_DIRECT_SHUTDOWN: u64 = 8;
 const TIMER_CHANNEL_SHUTDOWN: u64 = 9;
+const TIMER_PHONE_DISCONNECT: u64 = 10;
+const TIMER_PHONE_CONNECT: u64 = 11;
+const TIMER_READ_RECEIPT: u64 = 12;
+const TIMER_ROOM_LOGIN: u64 = 13;
+const TIMER_ROOM_POST: u64 = 14;
+const TIMER_GPS_UPDATE: u64 = 15;
+/// Time without a login response before the room login is retried.
+const ROOM_LOGIN_RETRY_S: f64 = 120.0;
+/// Scheduled message `i` uses timer ID `TIMER_SCHEDULED_BASE + i`.
+const TIMER_SCHEDULED_BASE: u64 = 100;
+/// Traffic rule `i` of reload generation `g` uses timer ID
+/// `TIMER_TRAFFIC_BASE + g * TRAFFIC_TIMER_STRIDE + i`.
+const TIMER_TRAFFIC_BASE: u64 = 1_000_000;
+const TRAFFIC_TIMER_STRIDE: u64 = 1_000_000;
 
 // ============================================================================
 // Agent Entity
@@ -370,11 +673,43 @@ pub struct Agent {
     channel_target_idx: usize,
     channel_session_count: u32,
     
+    // Traffic rule state (messages sent per rule, bumped on each reload)
+    traffic_sent: Vec<u32>,
+    traffic_generation: u64,
+
     // Message counters
     message_seq: u32,
     direct_messages_sent: u32,
     channel_messages_sent: u32,
     messages_received: u32,
+
+    // Phone app state
+    ever_ready: bool,
+    connected: bool,
+    syncing: bool,
+    deferred_direct: bool,
+    deferred_channel: bool,
+    deferred_scheduled: Vec<usize>,
+    deferred_traffic: Vec<usize>,
+    unread: VecDeque<PublicKeyPrefix>,
+    receipts_due: usize,
+    read_receipts_sent: u32,
+
+    // Sync state: when each message still queued in the companion was announced
+    sync_backlog: VecDeque<SimTime>,
+    messages_fetched: u32,
+    messages_lost: u32,
+
+    // Room client state
+    room_logged_in: bool,
+    room_posts_sent: u32,
+    room_messages_received: u32,
+
+    // Location state: the latest fix, and the one last sent with when
+    gps_position: Option<GeoCoord>,
+    gps_sent: Option<(GeoCoord, SimTime)>,
+    gps_timer_armed: bool,
+    location_updates_sent: u32,
     
     // Metrics labels for this agent
     metrics_labels: MetricLabels,
@@ -404,6 +739,8 @@ impl Agent {
         } else {
             ChannelMessageState::Disabled
         };
+        let traffic_sent = vec![0; config.traffic.len()];
+        let gps_position = config.gps.position;
         
         Agent {
             id,
@@ -420,10 +757,32 @@ impl Agent {
             channel_state,
             channel_target_idx: 0,
             channel_session_count: 0,
+            traffic_sent,
+            traffic_generation: 0,
             message_seq: 0,
             direct_messages_sent: 0,
             channel_messages_sent: 0,
             messages_received: 0,
+            ever_ready: false,
+            connected: true,
+            syncing: false,
+            deferred_direct: false,
+            deferred_channel: false,
+            deferred_scheduled: Vec::new(),
+            deferred_traffic: Vec::new(),
+            unread: VecDeque::new(),
+            receipts_due: 0,
+            read_receipts_sent: 0,
+            sync_backlog: VecDeque::new(),
+            messages_fetched: 0,
+            messages_lost: 0,
+            room_logged_in: false,
+            room_posts_sent: 0,
+            room_messages_received: 0,
+            gps_position,
+            gps_sent: None,
+            gps_timer_armed: false,
+            location_updates_sent: 0,
             metrics_labels,
         }
     }
@@ -448,6 +807,54 @@ impl Agent {
         self.messages_received
     }
 
+    /// Get the total read receipts sent.
+    pub fn read_receipts_sent(&self) -> u32 {
+        self.read_receipts_sent
+    }
+
+    /// Whether the agent has logged in to its room server.
+    pub fn room_logged_in(&self) -> bool {
+        self.room_logged_in
+    }
+
+    /// Get the total posts sent to the room server.
+    pub fn room_posts_sent(&self) -> u32 {
+        self.room_posts_sent
+    }
+
+    /// Get the total room posts received from the room server.
+    pub fn room_messages_received(&self) -> u32 {
+        self.room_messages_received
+    }
+
+    /// Messages queued in the companion that the host hasn't fetched yet.
+    pub fn sync_backlog(&self) -> usize {
+        self.sync_backlog.len()
+    }
+
+    /// Get the total messages fetched from the companion.
+    pub fn messages_fetched(&self) -> u32 {
+        self.messages_fetched
+    }
+
+    /// Get the total messages the companion announced but no longer had
+    /// queued when the host synced.
+    pub fn messages_lost(&self) -> u32 {
+        self.messages_lost
+    }
+
+    /// Get the count of location updates sent to the companion.
+    pub fn location_updates_sent(&self) -> u32 {
+        self.location_updates_sent
+    }
+
+    /// Whether the host is currently connected to the companion.
+    ///
+    /// Always true unless the phone app profile is enabled.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
     /// Get the current protocol state.
     pub fn protocol_state(&self) -> ProtocolState {
         self.protocol_state
@@ -623,10 +1030,16 @@ impl Agent {
 
     /// Called when protocol is ready - start messaging state machines.
     fn on_ready(&mut self, ctx: &mut SimContext) {
+        self.ever_ready = true;
+        if self.config.phone.enabled {
+            self.schedule_disconnect(ctx);
+        }
+        self.share_location(ctx);
+
         // Start direct message state machine
         if self.direct_state == DirectMessageState::WaitingStartup {
             let delay = self.jittered_delay(
-                ctx.rng(),
+                ctx.rng_substream(substream::JITTER),
                 self.config.direct.startup_s,
                 self.config.direct.startup_jitter_s,
             );
@@ -642,7 +1055,7 @@ impl Agent {
         // Start channel message state machine
         if self.channel_state == ChannelMessageState::WaitingStartup {
             let delay = self.jittered_delay(
-                ctx.rng(),
+                ctx.rng_substream(substream::JITTER),
                 self.config.channel.startup_s,
                 self.config.channel.startup_jitter_s,
             );
@@ -654,6 +1067,330 @@ impl Agent {
                 ctx.post_event(shutdown_delay, vec![self.id], EventPayload::Timer { timer_id: TIMER_CHANNEL_SHUTDOWN });
             }
         }
+
+        // One-off messages that came due during setup go out now
+        for idx in std::mem::take(&mut self.deferred_scheduled) {
+            self.send_scheduled_message(idx, ctx);
+        }
+
+        self.schedule_traffic(ctx);
+
+        if self.config.room.room.is_some() {
+            let delay = SimTime::from_secs(self.config.room.login_s);
+            ctx.post_event(delay, vec![self.id], EventPayload::Timer { timer_id: TIMER_ROOM_LOGIN });
+        }
+    }
+
+    // ========================================================================
+    // Room Client
+    // ========================================================================
+
+    /// Key prefix room posts and login responses arrive from.
+    fn room_prefix(&self) -> Option<PublicKeyPrefix> {
+        self.config.room.room.map(|room| PublicKeyPrefix::new(room.public_key_hash()))
+    }
+
+    /// Log in to the room server, retrying until it answers.
+    fn room_login(&mut self, ctx: &mut SimContext) {
+        let Some(room) = self.config.room.room else {
+            return;
+        };
+        if self.room_logged_in {
+            return;
+        }
+        if self.protocol_state == ProtocolState::Ready {
+            debug!("Agent[{}]: Logging in to room {:?}", self.config.name, PublicKeyPrefix::new(room.public_key_hash()).to_hex());
+            self.send_command(
+                ctx,
+                &Command::SendLogin {
+                    public_key: PublicKey::new(room.0),
+                    password: self.config.room.password.clone(),
+                },
+            );
+        }
+        ctx.post_event(
+            SimTime::from_secs(ROOM_LOGIN_RETRY_S),
+            vec![self.id],
+            EventPayload::Timer { timer_id: TIMER_ROOM_LOGIN },
+        );
+    }
+
+    /// Handle the room server accepting the login.
+    fn handle_room_login(&mut self, ctx: &mut SimContext) {
+        if self.room_logged_in {
+            return;
+        }
+        self.room_logged_in = true;
+        ctx.tracer().log(TraceEvent::custom(
+            Some(&self.config.name),
+            self.id,
+            ctx.time(),
+            "Logged in to room server",
+        ));
+        mcsim_metrics::metrics::counter!(
+            metric_defs::ROOM_LOGINS.name,
+            &self.metrics_labels.to_labels()
+        ).increment(1);
+        self.schedule_room_post(ctx);
+    }
+
+    /// Schedule the next post, unless the post count is reached.
+    fn schedule_room_post(&mut self, ctx: &mut SimContext) {
+        if self.config.room.post_count.is_some_and(|limit| self.room_posts_sent >= limit) {
+            return;
+        }
+        let delay = self.jittered_delay(
+            ctx.rng_substream(substream::JITTER),
+            self.config.room.post_interval_s,
+            self.config.room.post_interval_jitter_s,
+        );
+        ctx.post_event(delay, vec![self.id], EventPayload::Timer { timer_id: TIMER_ROOM_POST });
+    }
+
+    /// Post to the room. Posts that come due while away are skipped.
+    fn send_room_post(&mut self, ctx: &mut SimContext) {
+        let Some(recipient) = self.room_prefix() else {
+            return;
+        };
+        if self.protocol_state == ProtocolState::Ready {
+            let content = format!("Room post {} from {}", self.room_posts_sent + 1, self.config.name);
+            debug!("Agent[{}]: Posting to room {:?}: {}", self.config.name, recipient.to_hex(), content);
+
+            mcsim_metrics::metrics::counter!(
+                metric_defs::MESSAGE_SENT.name,
+                &self.metrics_labels.to_labels()
+            ).increment(1);
+
+            self.send_command(
+                ctx,
+                &Command::SendTextMessage {
+                    text_type: TextType::Plain,
+                    attempt: 0,
+                    timestamp: ctx.time().as_secs_f64() as u32,
+                    recipient_prefix: recipient,
+                    text: content,
+                },
+            );
+            self.room_posts_sent += 1;
+        }
+        self.schedule_room_post(ctx);
+    }
+
+    // ========================================================================
+    // Phone App Behavior
+    // ========================================================================
+
+    /// Whether the app is away (disconnected or reconnecting) after having
+    /// been ready. Messaging timers that fire while away are deferred.
+    fn is_away(&self) -> bool {
+        self.ever_ready && self.protocol_state != ProtocolState::Ready
+    }
+
+    /// Schedule the app going to the background.
+    fn schedule_disconnect(&mut self, ctx: &mut SimContext) {
+        let delay = self.jittered_delay(
+            ctx.rng_substream(substream::JITTER),
+            self.config.phone.connected_s,
+            self.config.phone.connected_jitter_s,
+        );
+        ctx.post_event(delay, vec![self.id], EventPayload::Timer { timer_id: TIMER_PHONE_DISCONNECT });
+    }
+
+    /// Drop the connection to the companion until the app returns.
+    fn disconnect(&mut self, ctx: &mut SimContext) {
+        debug!("Agent[{}]: App going to background, disconnecting", self.config.name);
+        ctx.tracer().log(TraceEvent::custom(
+            Some(&self.config.name),
+            self.id,
+            ctx.time(),
+            "Phone app disconnected",
+        ));
+        self.connected = false;
+        self.syncing = false;
+        self.protocol_state = ProtocolState::Uninitialized;
+        self.protocol_session.reset();
+
+        let delay = self.jittered_delay(
+            ctx.rng_substream(substream::JITTER),
+            self.config.phone.disconnected_s,
+            self.config.phone.disconnected_jitter_s,
+        );
+        ctx.post_event(delay, vec![self.id], EventPayload::Timer { timer_id: TIMER_PHONE_CONNECT });
+    }
+
+    /// Reconnect to the companion, repeating the app handshake.
+    fn connect(&mut self, ctx: &mut SimContext) {
+        debug!("Agent[{}]: App returning to foreground, connecting", self.config.name);
+        ctx.tracer().log(TraceEvent::custom(
+            Some(&self.config.name),
+            self.id,
+            ctx.time(),
+            "Phone app connected",
+        ));
+        self.connected = true;
+        // Drop any partial frame left from watching while away
+        self.protocol_session.reset();
+        self.start_initialization(ctx);
+    }
+
+    // ========================================================================
+    // Sync State
+    // ========================================================================
+
+    /// The companion announced a newly queued message.
+    fn note_message_queued(&mut self, ctx: &mut SimContext) {
+        self.sync_backlog.push_back(ctx.time());
+        self.record_sync_backlog();
+    }
+
+    /// The host fetched the oldest queued message.
+    fn note_message_fetched(&mut self, ctx: &mut SimContext) {
+        self.messages_fetched += 1;
+        mcsim_metrics::metrics::counter!(
+            metric_defs::SYNC_FETCHED.name,
+            &self.metrics_labels.to_labels()
+        ).increment(1);
+        // Messages the companion pushes without announcing them first
+        // (nothing queued) didn't wait
+        if let Some(queued) = self.sync_backlog.pop_front() {
+            mcsim_metrics::metrics::histogram!(
+                metric_defs::SYNC_WAIT.name,
+                &self.metrics_labels.to_labels()
+            ).record((ctx.time() - queued).as_secs_f64() * 1000.0);
+        }
+        self.record_sync_backlog();
+    }
+
+    /// The companion reported its queue empty: announced messages not
+    /// fetched by now were dropped.
+    fn note_queue_drained(&mut self) {
+        let lost = self.sync_backlog.len() as u32;
+        if lost > 0 {
+            warn!("Agent[{}]: {} queued message(s) lost before sync", self.config.name, lost);
+            self.messages_lost += lost;
+            mcsim_metrics::metrics::counter!(
+                metric_defs::SYNC_LOST.name,
+                &self.metrics_labels.to_labels()
+            ).increment(lost as u64);
+            self.sync_backlog.clear();
+        }
+        self.record_sync_backlog();
+    }
+
+    fn record_sync_backlog(&self) {
+        mcsim_metrics::metrics::gauge!(
+            metric_defs::SYNC_BACKLOG.name,
+            &self.metrics_labels.to_labels()
+        ).set(self.sync_backlog.len() as f64);
+    }
+
+    /// Called when the handshake completes after a reconnect. Channels and
+    /// contacts are still in the companion, so the app goes straight to
+    /// syncing the messages queued while it was away.
+    fn on_reconnected(&mut self, ctx: &mut SimContext) {
+        self.protocol_state = ProtocolState::Ready;
+        self.syncing = true;
+        self.send_command(ctx, &Command::SyncNextMessage);
+        self.schedule_disconnect(ctx);
+
+        // Catch up on sends that came due while away
+        if std::mem::take(&mut self.deferred_direct)
+            && !matches!(self.direct_state, DirectMessageState::Shutdown | DirectMessageState::Disabled)
+        {
+            self.direct_state = DirectMessageState::Idle;
+            self.send_next_direct_message(ctx);
+        }
+        if std::mem::take(&mut self.deferred_channel)
+            && !matches!(self.channel_state, ChannelMessageState::Shutdown | ChannelMessageState::Disabled)
+        {
+            self.channel_state = ChannelMessageState::Idle;
+            self.send_next_channel_message(ctx);
+        }
+        for idx in std::mem::take(&mut self.deferred_scheduled) {
+            self.send_scheduled_message(idx, ctx);
+        }
+        for idx in std::mem::take(&mut self.deferred_traffic) {
+            self.send_traffic_message(idx, ctx);
+        }
+        self.send_read_receipts(ctx);
+        self.share_location(ctx);
+    }
+
+    /// Set the companion's advert location to the latest fix if it has
+    /// changed, waiting out the rest of the update interval if need be.
+    fn share_location(&mut self, ctx: &mut SimContext) {
+        if !self.config.gps.enabled || self.protocol_state != ProtocolState::Ready {
+            return;
+        }
+        let Some(position) = self.gps_position else {
+            return;
+        };
+        if let Some((sent, sent_at)) = self.gps_sent {
+            if sent == position {
+                return;
+            }
+            let next = sent_at + SimTime::from_secs(self.config.gps.interval_s);
+            if ctx.time() < next {
+                if !self.gps_timer_armed {
+                    self.gps_timer_armed = true;
+                    ctx.post_event(next - ctx.time(), vec![self.id], EventPayload::Timer { timer_id: TIMER_GPS_UPDATE });
+                }
+                return;
+            }
+        }
+        self.send_command(
+            ctx,
+            &Command::SetAdvertLatLon {
+                lat: (position.latitude * 1e6).round() as i32,
+                lon: (position.longitude * 1e6).round() as i32,
+                alt: None,
+            },
+        );
+        self.gps_sent = Some((position, ctx.time()));
+        self.location_updates_sent += 1;
+    }
+
+    /// Queue a read receipt for a DM from `sender`, sent once the user has
+    /// had time to read it.
+    fn queue_read_receipt(&mut self, sender: PublicKeyPrefix, ctx: &mut SimContext) {
+        self.unread.push_back(sender);
+        let delay = self.jittered_delay(
+            ctx.rng_substream(substream::JITTER),
+            self.config.phone.read_delay_s,
+            self.config.phone.read_delay_jitter_s,
+        );
+        ctx.post_event(delay, vec![self.id], EventPayload::Timer { timer_id: TIMER_READ_RECEIPT });
+    }
+
+    /// Send the read receipts that are due, if connected.
+    fn send_read_receipts(&mut self, ctx: &mut SimContext) {
+        if self.protocol_state != ProtocolState::Ready {
+            return;
+        }
+        while self.receipts_due > 0 {
+            let Some(sender) = self.unread.pop_front() else {
+                break;
+            };
+            self.receipts_due -= 1;
+            debug!("Agent[{}]: Sending read receipt to {:?}", self.config.name, sender.to_hex());
+
+            mcsim_metrics::metrics::counter!(
+                metric_defs::MESSAGE_SENT.name,
+                &self.metrics_labels.to_labels()
+            ).increment(1);
+
+            self.send_command(
+                ctx,
+                &Command::SendTextMessage {
+                    text_type: TextType::Plain,
+                    attempt: 0,
+                    timestamp: ctx.time().as_secs_f64() as u32,
+                    recipient_prefix: sender,
+                    text: READ_RECEIPT_TEXT.to_string(),
+                },
+            );
+            self.read_receipts_sent += 1;
+        }
     }
 
     // ========================================================================
@@ -661,7 +1398,7 @@ impl Agent {
     // ========================================================================
 
     /// Calculate a delay with optional jitter (using normal distribution).
-    fn jittered_delay(&self, rng: &mut ChaCha8Rng, base_s: f64, jitter_s: f64) -> SimTime {
+    fn jittered_delay<R: Rng>(&self, rng: &mut R, base_s: f64, jitter_s: f64) -> SimTime {
         let delay = if jitter_s > 0.0 {
             let normal = Normal::new(base_s, jitter_s).unwrap();
             normal.sample(rng).max(0.0)
@@ -767,7 +1504,7 @@ impl Agent {
                 self.direct_session_count = 0;
                 self.direct_state = DirectMessageState::WaitingSession;
                 let delay = self.jittered_delay(
-                    ctx.rng(),
+                    ctx.rng_substream(substream::JITTER),
                     self.config.direct.session_interval_s,
                     self.config.direct.session_interval_jitter_s,
                 );
@@ -779,13 +1516,279 @@ impl Agent {
         // Schedule next message
         self.direct_state = DirectMessageState::WaitingInterval;
         let delay = self.jittered_delay(
-            ctx.rng(),
+            ctx.rng_substream(substream::JITTER),
             self.config.direct.interval_s,
             self.config.direct.interval_jitter_s,
         );
         ctx.post_event(delay, vec![self.id], EventPayload::Timer { timer_id: TIMER_DIRECT_INTERVAL });
     }
 
+    /// Schedule the one-off messages at their absolute times. Those due
+    /// before the agent is ready are held until it is.
+    fn schedule_one_off_messages(&mut self, ctx: &mut SimContext) {
+        for (idx, scheduled) in self.config.scheduled.iter().enumerate() {
+            let delay = SimTime::from_secs((scheduled.at_s - ctx.time().as_secs_f64()).max(0.0));
+            ctx.post_event(
+                delay,
+                vec![self.id],
+                EventPayload::Timer { timer_id: TIMER_SCHEDULED_BASE + idx as u64 },
+            );
+        }
+    }
+
+    /// Send scheduled message `idx`, choosing its destination now.
+    fn send_scheduled_message(&mut self, idx: usize, ctx: &mut SimContext) {
+        let Some(scheduled) = self.config.scheduled.get(idx) else {
+            return;
+        };
+        let Some(target) = scheduled.select_destination(ctx.rng_substream(substream::DESTINATION)) else {
+            warn!("Agent[{}]: Scheduled message {} has no destination", self.config.name, idx);
+            return;
+        };
+
+        self.message_seq += 1;
+        let content = scheduled
+            .text
+            .clone()
+            .unwrap_or_else(|| format!("Scheduled DM {} from {}", idx + 1, self.config.name));
+        let timestamp = ctx.time().as_secs_f64() as u32;
+        let recipient = PublicKeyPrefix::new(target.public_key_hash());
+
+        ctx.tracer().log(TraceEvent::custom(
+            Some(&self.config.name),
+            self.id,
+            ctx.time(),
+            format!("Sending scheduled DM to recipient_prefix={:?} ({:?})",
+                recipient.as_bytes(), scheduled.strategy),
+        ));
+
+        mcsim_metrics::metrics::counter!(
+            metric_defs::MESSAGE_SENT.name,
+            &self.metrics_labels.to_labels()
+        ).increment(1);
+
+        self.send_command(
+            ctx,
+            &Command::SendTextMessage {
+                text_type: TextType::Plain,
+                attempt: 0,
+                timestamp,
+                recipient_prefix: recipient,
+                text: content,
+            },
+        );
+        self.direct_messages_sent += 1;
+    }
+
+    // ========================================================================
+    // Traffic Rules
+    // ========================================================================
+
+    /// Handle traffic rule `idx` coming due: send now (or on reconnect if
+    /// away) and schedule the next message.
+    fn fire_traffic_rule(&mut self, idx: usize, ctx: &mut SimContext) {
+        let Some(rule) = self.config.traffic.get(idx) else {
+            return;
+        };
+        let sent = self.traffic_sent[idx];
+        if !rule.is_active(sent, ctx.time().as_secs_f64()) {
+            return;
+        }
+        self.traffic_sent[idx] += 1;
+
+        if let Some(interval_s) = rule.interval_s {
+            if rule.is_active(sent + 1, ctx.time().as_secs_f64()) {
+                let delay = self.jittered_delay(ctx.rng_substream(substream::JITTER), interval_s, rule.interval_jitter_s);
+                ctx.post_event(
+                    delay,
+                    vec![self.id],
+                    EventPayload::Timer { timer_id: self.traffic_timer(idx) },
+                );
+            }
+        }
+
+        if self.is_away() {
+            // The app can't send while away; catch up on reconnect
+            if !self.deferred_traffic.contains(&idx) {
+                self.deferred_traffic.push(idx);
+            }
+        } else if self.protocol_state == ProtocolState::Ready {
+            self.send_traffic_message(idx, ctx);
+        } else {
+            debug!("Agent[{}]: Traffic rule {} due before ready, skipped", self.config.name, idx);
+        }
+    }
+
+    /// Schedule the triggered traffic rules that a message from `source`
+    /// with `text` sets off.
+    fn trigger_traffic(&mut self, source: TrafficSource, text: &str, ctx: &mut SimContext) {
+        let now_s = ctx.time().as_secs_f64();
+        for idx in 0..self.config.traffic.len() {
+            let rule = &self.config.traffic[idx];
+            if !rule.is_triggered_by(&source, text, now_s) || !rule.is_active(self.traffic_sent[idx], now_s) {
+                continue;
+            }
+            let (delay_s, jitter_s) = (rule.delay_s, rule.interval_jitter_s);
+            debug!("Agent[{}]: Traffic rule {} triggered", self.config.name, idx);
+            let delay = self.jittered_delay(ctx.rng_substream(substream::JITTER), delay_s, jitter_s);
+            ctx.post_event(
+                delay,
+                vec![self.id],
+                EventPayload::Timer { timer_id: self.traffic_timer(idx) },
+            );
+        }
+    }
+
+    /// Timer ID of traffic rule `idx` in the current reload generation.
+    fn traffic_timer(&self, idx: usize) -> u64 {
+        TIMER_TRAFFIC_BASE + self.traffic_generation * TRAFFIC_TIMER_STRIDE + idx as u64
+    }
+
+    /// Start the traffic rules. Times are absolute (late ones fire now);
+    /// triggered rules wait for their messages.
+    fn schedule_traffic(&mut self, ctx: &mut SimContext) {
+        for (idx, rule) in self.config.traffic.iter().enumerate() {
+            if rule.trigger.is_some() {
+                continue;
+            }
+            let delay = SimTime::from_secs((rule.at_s - ctx.time().as_secs_f64()).max(0.0));
+            ctx.post_event(
+                delay,
+                vec![self.id],
+                EventPayload::Timer { timer_id: self.traffic_timer(idx) },
+            );
+        }
+    }
+
+    /// Replace the traffic rules mid-run, returning the number kept.
+    ///
+    /// The new rules start over as if the run had begun with them: sent
+    /// counts are reset, pending and deferred sends of the old rules are
+    /// dropped, and timed rules are scheduled at their `at_s` (at once if it
+    /// has passed). Contacts and channels are set up in the firmware at
+    /// startup only, so rules that message or listen to a node that is not
+    /// a contact, or use a channel the agent has not joined, are dropped.
+    pub fn reload_traffic(&mut self, rules: Vec<TrafficRule>, ctx: &mut SimContext) -> usize {
+        let is_contact = |node: &NodeId| self.config.contacts.iter().any(|c| c.public_key == *node);
+        let is_channel = |name: &str| {
+            self.config.channel.targets.iter().chain(&self.config.channel.subscribe_only).any(|c| c.name == name)
+        };
+        let (kept, dropped): (Vec<TrafficRule>, Vec<TrafficRule>) = rules.into_iter().partition(|rule| {
+            let destination_known = match &rule.destination {
+                TrafficDestination::Direct(node) => is_contact(node),
+                TrafficDestination::Channel(channel) => is_channel(&channel.name),
+            };
+            let trigger_known = rule.trigger.as_ref().is_none_or(|trigger| match &trigger.source {
+                TrafficSource::Direct(node) => is_contact(node),
+                TrafficSource::Channel(name) => is_channel(name),
+            });
+            destination_known && trigger_known
+        });
+        if !dropped.is_empty() {
+            warn!(
+                "Agent[{}]: Dropped {} reloaded traffic rule(s) using contacts or channels not set up at startup",
+                self.config.name,
+                dropped.len()
+            );
+        }
+
+        self.traffic_generation += 1;
+        self.traffic_sent = vec![0; kept.len()];
+        self.deferred_traffic.clear();
+        self.config.traffic = kept;
+        ctx.tracer().log(TraceEvent::custom(
+            Some(&self.config.name),
+            self.id,
+            ctx.time(),
+            format!("Reloaded {} traffic rule(s)", self.config.traffic.len()),
+        ));
+        // Agents that are not ready yet start their rules in on_ready
+        if self.ever_ready {
+            self.schedule_traffic(ctx);
+        }
+        self.config.traffic.len()
+    }
+
+    /// Send one message of traffic rule `idx`.
+    fn send_traffic_message(&mut self, idx: usize, ctx: &mut SimContext) {
+        let Some(rule) = self.config.traffic.get(idx) else {
+            return;
+        };
+        self.message_seq += 1;
+        let text = rule.text.clone().unwrap_or_else(|| {
+            format!("Traffic {}.{} from {}", idx + 1, self.traffic_sent[idx], self.config.name)
+        });
+        let timestamp = ctx.time().as_secs_f64() as u32;
+
+        let command = match &rule.destination {
+            TrafficDestination::Direct(target) => {
+                let recipient = PublicKeyPrefix::new(target.public_key_hash());
+                ctx.tracer().log(TraceEvent::custom(
+                    Some(&self.config.name),
+                    self.id,
+                    ctx.time(),
+                    format!("Sending traffic DM to recipient_prefix={:?}", recipient.as_bytes()),
+                ));
+                self.direct_messages_sent += 1;
+                Command::SendTextMessage {
+                    text_type: TextType::Plain,
+                    attempt: 0,
+                    timestamp,
+                    recipient_prefix: recipient,
+                    text,
+                }
+            }
+            TrafficDestination::Channel(channel) => {
+                let Some(channel_idx) = self
+                    .config
+                    .channel
+                    .targets
+                    .iter()
+                    .chain(&self.config.channel.subscribe_only)
+                    .position(|c| c.name == channel.name)
+                else {
+                    warn!(
+                        "Agent[{}]: Traffic rule {} posts to unknown channel '{}'",
+                        self.config.name, idx, channel.name
+                    );
+                    return;
+                };
+                ctx.tracer().log(TraceEvent::custom(
+                    Some(&self.config.name),
+                    self.id,
+                    ctx.time(),
+                    format!("Posting traffic to channel {}", channel.name),
+                ));
+                self.channel_messages_sent += 1;
+                Command::SendChannelTextMessage {
+                    text_type: TextType::Plain,
+                    channel_idx: channel_idx as u8,
+                    timestamp,
+                    text,
+                }
+            }
+        };
+
+        mcsim_metrics::metrics::counter!(
+            metric_defs::MESSAGE_SENT.name,
+            &self.metrics_labels.to_labels()
+        ).increment(1);
+        if let Some(metric) = &rule.metric {
+            self.emit_custom_counter(metric);
+        }
+
+        self.send_command(ctx, &command);
+    }
+
+    /// Increment a scenario-declared custom counter for this node.
+    fn emit_custom_counter(&self, name: &str) {
+        let has_node_label = mcsim_metrics::custom::get(name).is_some_and(|m| m.labels.iter().any(|l| l == "node"));
+        let labels: &[(&str, &str)] = if has_node_label { &[("node", self.config.name.as_str())] } else { &[] };
+        if let Err(e) = mcsim_metrics::custom::counter(name, labels, 1) {
+            warn!("Agent[{}]: {}", self.config.name, e);
+        }
+    }
+
     // ========================================================================
     // Channel Message State Machine
     // ========================================================================
@@ -854,7 +1857,7 @@ impl Agent {
                 self.channel_session_count = 0;
                 self.channel_state = ChannelMessageState::WaitingSession;
                 let delay = self.jittered_delay(
-                    ctx.rng(),
+                    ctx.rng_substream(substream::JITTER),
                     self.config.channel.session_interval_s,
                     self.config.channel.session_interval_jitter_s,
                 );
@@ -866,7 +1869,7 @@ impl Agent {
         // Schedule next message
         self.channel_state = ChannelMessageState::WaitingInterval;
         let delay = self.jittered_delay(
-            ctx.rng(),
+            ctx.rng_substream(substream::JITTER),
             self.config.channel.interval_s,
             self.config.channel.interval_jitter_s,
         );
@@ -899,8 +1902,12 @@ impl Agent {
                     self.config.name,
                     info.public_key.to_hex()
                 );
-                // After SelfInfo, set up any channels before going Ready
-                self.setup_channels(ctx);
+                if self.ever_ready {
+                    self.on_reconnected(ctx);
+                } else {
+                    // After SelfInfo, set up any channels before going Ready
+                    self.setup_channels(ctx);
+                }
             }
             Response::Ok => {
                 // OK response - if we're setting up channels or contacts, move to next one
@@ -931,10 +1938,25 @@ impl Agent {
                     ctx.time(),
                     format!("Received ContactMessage from {:?}", msg.sender_prefix.to_hex()),
                 ));
+                self.note_message_fetched(ctx);
                 self.handle_contact_message(msg, ctx);
+                if self.syncing {
+                    self.send_command(ctx, &Command::SyncNextMessage);
+                }
             }
             Response::ChannelMessageV2(msg) | Response::ChannelMessageV3(msg) => {
+                self.note_message_fetched(ctx);
                 self.handle_channel_message(msg, ctx);
+                if self.syncing {
+                    self.send_command(ctx, &Command::SyncNextMessage);
+                }
+            }
+            Response::NoMoreMessages => {
+                self.note_queue_drained();
+                if self.syncing {
+                    debug!("Agent[{}]: Message sync complete", self.config.name);
+                    self.syncing = false;
+                }
             }
             Response::Error(code) => {
                 warn!("Agent[{}]: Error response: {:?}", self.config.name, code);
@@ -979,6 +2001,15 @@ impl Agent {
     fn handle_contact_message(&mut self, msg: ReceivedContactMessage, ctx: &mut SimContext) {
         self.messages_received += 1;
 
+        if self.room_prefix() == Some(msg.sender_prefix) {
+            // History and other clients' posts pushed by the room server
+            self.room_messages_received += 1;
+            mcsim_metrics::metrics::counter!(
+                metric_defs::ROOM_MESSAGES_RECEIVED.name,
+                &self.metrics_labels.to_labels()
+            ).increment(1);
+        }
+
         debug!(
             "Agent[{}]: Received DM from {:?}: {}",
             self.config.name,
@@ -1003,10 +2034,30 @@ impl Agent {
             metric_defs::MESSAGE_DELIVERY_LATENCY.name,
             &self.metrics_labels.to_labels()
         ).record(latency_ms);
+
+        if self.config.phone.enabled && self.config.phone.read_receipts && msg.text != READ_RECEIPT_TEXT {
+            self.queue_read_receipt(msg.sender_prefix, ctx);
+        }
+
+        // Replies to the sender's DMs (read receipts don't count)
+        if msg.text != READ_RECEIPT_TEXT {
+            let sender = self
+                .config
+                .traffic
+                .iter()
+                .filter_map(|rule| match rule.trigger.as_ref().map(|t| &t.source) {
+                    Some(TrafficSource::Direct(node)) => Some(*node),
+                    _ => None,
+                })
+                .find(|node| PublicKeyPrefix::new(node.public_key_hash()) == msg.sender_prefix);
+            if let Some(sender) = sender {
+                self.trigger_traffic(TrafficSource::Direct(sender), &msg.text, ctx);
+            }
+        }
     }
 
     /// Handle a received channel message.
-    fn handle_channel_message(&mut self, msg: ReceivedChannelMessage, _ctx: &mut SimContext) {
+    fn handle_channel_message(&mut self, msg: ReceivedChannelMessage, ctx: &mut SimContext) {
         self.messages_received += 1;
 
         debug!(
@@ -1021,6 +2072,18 @@ impl Agent {
             metric_defs::MESSAGE_DELIVERED.name,
             &self.metrics_labels.to_labels()
         ).increment(1);
+
+        let channel = self
+            .config
+            .channel
+            .targets
+            .iter()
+            .chain(&self.config.channel.subscribe_only)
+            .nth(msg.channel_idx as usize)
+            .map(|c| c.name.clone());
+        if let Some(channel) = channel {
+            self.trigger_traffic(TrafficSource::Channel(channel), &msg.text, ctx);
+        }
     }
 
     /// Handle a push notification.
@@ -1047,7 +2110,12 @@ impl Agent {
                 // Handle direct message ACK
                 self.handle_direct_ack(ctx);
             }
+            PushNotification::MessageWaiting if self.syncing => {
+                // Already draining the queue; this message will be synced too
+                self.note_message_queued(ctx);
+            }
             PushNotification::MessageWaiting => {
+                self.note_message_queued(ctx);
                 // A message is waiting - send SyncNextMessage to retrieve it
                 ctx.tracer().log(TraceEvent::custom(
                     Some(&self.config.name),
@@ -1057,6 +2125,13 @@ impl Agent {
                 ));
                 self.send_command(ctx, &Command::SyncNextMessage);
             }
+            PushNotification::LoginSuccess { server_prefix, .. } if self.room_prefix() == Some(server_prefix) => {
+                self.handle_room_login(ctx);
+            }
+            PushNotification::LoginFail { server_prefix } if self.room_prefix() == Some(server_prefix) => {
+                // Retried by the login timer
+                warn!("Agent[{}]: Room server rejected login", self.config.name);
+            }
             PushNotification::Advert { public_key } => {
                 debug!(
                     "Agent[{}]: Advert received from {:?}",
@@ -1079,10 +2154,29 @@ impl Entity for Agent {
     fn handle_event(&mut self, event: &Event, ctx: &mut SimContext) -> Result<(), SimError> {
         match &event.payload {
             EventPayload::Timer { timer_id } => {
+                if self.is_away() {
+                    // The app can't send while away; catch up on reconnect
+                    match *timer_id {
+                        TIMER_DIRECT_STARTUP | TIMER_DIRECT_INTERVAL | TIMER_DIRECT_SESSION => {
+                            self.deferred_direct = true;
+                            return Ok(());
+                        }
+                        TIMER_CHANNEL_STARTUP | TIMER_CHANNEL_INTERVAL | TIMER_CHANNEL_SESSION => {
+                            self.deferred_channel = true;
+                            return Ok(());
+                        }
+                        id if (TIMER_SCHEDULED_BASE..TIMER_TRAFFIC_BASE).contains(&id) => {
+                            self.deferred_scheduled.push((id - TIMER_SCHEDULED_BASE) as usize);
+                            return Ok(());
+                        }
+                        _ => {}
+                    }
+                }
                 match *timer_id {
                     TIMER_PROTOCOL_INIT => {
                         // Initialize protocol on startup timer
                         if self.protocol_state == ProtocolState::Uninitialized {
+                            self.schedule_one_off_messages(ctx);
                             self.start_initialization(ctx);
                         }
                     }
@@ -1158,9 +2252,77 @@ impl Entity for Agent {
                             self.channel_state = ChannelMessageState::Shutdown;
                         }
                     }
+                    TIMER_PHONE_DISCONNECT if self.connected => {
+                        self.disconnect(ctx);
+                    }
+                    TIMER_PHONE_CONNECT if !self.connected => {
+                        self.connect(ctx);
+                    }
+                    TIMER_READ_RECEIPT => {
+                        // The user has read one more message
+                        self.receipts_due += 1;
+                        self.send_read_receipts(ctx);
+                    }
+                    TIMER_ROOM_LOGIN => {
+                        self.room_login(ctx);
+                    }
+                    TIMER_ROOM_POST => {
+                        self.send_room_post(ctx);
+                    }
+                    TIMER_GPS_UPDATE => {
+                        self.gps_timer_armed = false;
+                        self.share_location(ctx);
+                    }
+                    id if id >= TIMER_TRAFFIC_BASE => {
+                        // Scripted traffic (defers itself while away); timers
+                        // of rules replaced by a reload are dropped
+                        let offset = id - TIMER_TRAFFIC_BASE;
+                        if offset / TRAFFIC_TIMER_STRIDE == self.traffic_generation {
+                            self.fire_traffic_rule((offset % TRAFFIC_TIMER_STRIDE) as usize, ctx);
+                        }
+                    }
+                    id if id >= TIMER_SCHEDULED_BASE => {
+                        // Scheduled one-off message (from a group action)
+                        let idx = (id - TIMER_SCHEDULED_BASE) as usize;
+                        if self.protocol_state == ProtocolState::Ready {
+                            self.send_scheduled_message(idx, ctx);
+                        } else {
+                            // Due before the companion is set up; sent once ready
+                            self.deferred_scheduled.push(idx);
+                        }
+                    }
                     _ => {}
                 }
             }
+            EventPayload::PositionFix(fix) => {
+                self.gps_position = Some(fix.position);
+                self.share_location(ctx);
+            }
+            EventPayload::ReloadScript(reload) => {
+                match serde_json::from_str::<Vec<TrafficRule>>(&reload.script) {
+                    Ok(rules) => {
+                        let kept = self.reload_traffic(rules, ctx);
+                        info!("Agent[{}]: Reloaded {} traffic rule(s)", self.config.name, kept);
+                    }
+                    Err(e) => warn!("Agent[{}]: Invalid traffic script, keeping current rules: {}", self.config.name, e),
+                }
+            }
+            EventPayload::SerialTx(serial_event) if !self.connected => {
+                // Nothing is listening while the app is disconnected, but the
+                // messages the companion queues meanwhile still count
+                self.protocol_session.feed(&serial_event.data);
+                loop {
+                    match self.protocol_session.try_decode() {
+                        Ok(Some(Message::Push(PushNotification::MessageWaiting))) => self.note_message_queued(ctx),
+                        Ok(Some(_)) => {}
+                        Ok(None) => break,
+                        Err(_) => {
+                            self.protocol_session.reset();
+                            break;
+                        }
+                    }
+                }
+            }
             EventPayload::SerialTx(serial_event) => {
                 // Feed received serial data into the protocol decoder
                 self.protocol_session.feed(&serial_event.data);
@@ -1342,4 +2504,506 @@ mod tests {
         // Should be different from Public
         assert_ne!(hash_secret, PUBLIC_CHANNEL_KEY);
     }
+
+    #[test]
+    fn test_scheduled_message_destination() {
+        use rand::SeedableRng;
+
+        let near = NodeId::from_bytes([1u8; 32]);
+        let far = NodeId::from_bytes([2u8; 32]);
+        let mut scheduled = ScheduledMessage {
+            at_s: 600.0,
+            candidates: vec![near, far],
+            strategy: DestinationStrategy::Nearest,
+            text: None,
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        assert_eq!(scheduled.select_destination(&mut rng), Some(near));
+
+        scheduled.strategy = DestinationStrategy::Random;
+        for _ in 0..10 {
+            let picked = scheduled.select_destination(&mut rng).unwrap();
+            assert!(picked == near || picked == far);
+        }
+
+        scheduled.candidates.clear();
+        assert_eq!(scheduled.select_destination(&mut rng), None);
+        assert_eq!("Random".parse::<DestinationStrategy>(), Ok(DestinationStrategy::Random));
+        assert!("closest".parse::<DestinationStrategy>().is_err());
+    }
+
+    fn timer(timer_id: u64) -> Event {
+        Event {
+            id: mcsim_common::EventId(0),
+            time: SimTime::ZERO,
+            source: EntityId::new(1),
+            targets: vec![EntityId::new(1)],
+            payload: EventPayload::Timer { timer_id },
+        }
+    }
+
+    fn timer_ids(events: &[Event]) -> Vec<u64> {
+        events
+            .iter()
+            .filter_map(|e| match e.payload {
+                EventPayload::Timer { timer_id } => Some(timer_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_phone_app_disconnects_and_defers_sends() {
+        let config = AgentConfig {
+            direct: DirectMessageConfig { enabled: true, ..Default::default() },
+            phone: PhoneAppConfig { enabled: true, ..Default::default() },
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+
+        agent.protocol_state = ProtocolState::Ready;
+        agent.on_ready(&mut ctx);
+        assert!(timer_ids(&ctx.take_pending_events()).contains(&TIMER_PHONE_DISCONNECT));
+
+        agent.handle_event(&timer(TIMER_PHONE_DISCONNECT), &mut ctx).unwrap();
+        assert!(!agent.is_connected());
+        assert_eq!(agent.protocol_state(), ProtocolState::Uninitialized);
+        assert_eq!(timer_ids(&ctx.take_pending_events()), vec![TIMER_PHONE_CONNECT]);
+
+        // Sends that come due while away are held back
+        agent.handle_event(&timer(TIMER_DIRECT_STARTUP), &mut ctx).unwrap();
+        assert!(agent.deferred_direct);
+        assert_eq!(agent.direct_messages_sent(), 0);
+        assert!(ctx.take_pending_events().is_empty());
+
+        // Reconnecting repeats the handshake
+        agent.handle_event(&timer(TIMER_PHONE_CONNECT), &mut ctx).unwrap();
+        assert!(agent.is_connected());
+        assert_eq!(agent.protocol_state(), ProtocolState::AwaitingDeviceInfo);
+        let events = ctx.take_pending_events();
+        assert!(matches!(events[0].payload, EventPayload::SerialRx(_)));
+    }
+
+    #[test]
+    fn test_scheduled_message_due_before_ready_is_deferred() {
+        let config = AgentConfig {
+            scheduled: vec![ScheduledMessage {
+                at_s: 0.0,
+                candidates: vec![NodeId::from_bytes([2u8; 32])],
+                strategy: DestinationStrategy::Nearest,
+                text: None,
+            }],
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+
+        agent.handle_event(&timer(TIMER_PROTOCOL_INIT), &mut ctx).unwrap();
+        assert!(timer_ids(&ctx.take_pending_events()).contains(&TIMER_SCHEDULED_BASE));
+
+        // Due while the companion is still being set up: held, not dropped
+        agent.handle_event(&timer(TIMER_SCHEDULED_BASE), &mut ctx).unwrap();
+        assert_eq!(agent.direct_messages_sent(), 0);
+        assert_eq!(agent.deferred_scheduled, vec![0]);
+
+        agent.protocol_state = ProtocolState::Ready;
+        agent.on_ready(&mut ctx);
+        assert_eq!(agent.direct_messages_sent(), 1);
+        assert!(agent.deferred_scheduled.is_empty());
+    }
+
+    #[test]
+    fn test_sync_backlog_grows_while_away() {
+        use mcsim_common::SerialTxEvent;
+        use mcsim_companion_protocol::PUSH_CODE_MSG_WAITING;
+
+        let config = AgentConfig {
+            phone: PhoneAppConfig { enabled: true, ..Default::default() },
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+        agent.protocol_state = ProtocolState::Ready;
+        agent.on_ready(&mut ctx);
+        agent.handle_event(&timer(TIMER_PHONE_DISCONNECT), &mut ctx).unwrap();
+        ctx.take_pending_events();
+
+        // The companion announces three messages while the app is away
+        let waiting = Event {
+            payload: EventPayload::SerialTx(SerialTxEvent { data: vec![b'>', 1, 0, PUSH_CODE_MSG_WAITING] }),
+            ..timer(0)
+        };
+        for _ in 0..3 {
+            agent.handle_event(&waiting, &mut ctx).unwrap();
+        }
+        assert_eq!(agent.sync_backlog(), 3);
+        assert!(ctx.take_pending_events().is_empty());
+
+        // Back in the foreground the app fetches two before the queue runs dry
+        agent.handle_event(&timer(TIMER_PHONE_CONNECT), &mut ctx).unwrap();
+        let dm = ReceivedContactMessage {
+            sender_prefix: PublicKeyPrefix::new([7u8; 6]),
+            path_len: 0,
+            text_type: TextType::Plain,
+            timestamp: 0,
+            snr_x4: None,
+            extra: Vec::new(),
+            text: "hi".to_string(),
+        };
+        for _ in 0..2 {
+            agent.handle_message(Message::Response(Response::ContactMessageV3(dm.clone())), &mut ctx);
+        }
+        assert_eq!((agent.sync_backlog(), agent.messages_fetched()), (1, 2));
+
+        agent.handle_message(Message::Response(Response::NoMoreMessages), &mut ctx);
+        assert_eq!((agent.sync_backlog(), agent.messages_lost()), (0, 1));
+    }
+
+    #[test]
+    fn test_traffic_rule_repeats_until_count() {
+        let config = AgentConfig {
+            channel: ChannelMessageConfig {
+                subscribe_only: vec![ChannelTarget::from_name("#ops".to_string())],
+                ..Default::default()
+            },
+            traffic: vec![TrafficRule {
+                destination: TrafficDestination::Channel(ChannelTarget::from_name("#ops".to_string())),
+                at_s: 60.0,
+                interval_s: Some(300.0),
+                interval_jitter_s: 0.0,
+                message_count: Some(2),
+                until_s: None,
+                text: None,
+                trigger: None,
+                delay_s: 0.0,
+                metric: None,
+            }],
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+
+        agent.protocol_state = ProtocolState::Ready;
+        agent.on_ready(&mut ctx);
+        let events = ctx.take_pending_events();
+        let first = events
+            .iter()
+            .find(|e| matches!(e.payload, EventPayload::Timer { timer_id: TIMER_TRAFFIC_BASE }))
+            .unwrap();
+        assert_eq!(first.time, SimTime::from_secs(60.0));
+
+        // First post schedules the next one
+        agent.handle_event(&timer(TIMER_TRAFFIC_BASE), &mut ctx).unwrap();
+        let events = ctx.take_pending_events();
+        assert!(events.iter().any(|e| matches!(e.payload, EventPayload::SerialRx(_))));
+        assert_eq!(timer_ids(&events), vec![TIMER_TRAFFIC_BASE]);
+
+        // The second is the last
+        agent.handle_event(&timer(TIMER_TRAFFIC_BASE), &mut ctx).unwrap();
+        assert!(timer_ids(&ctx.take_pending_events()).is_empty());
+        assert_eq!(agent.channel_messages_sent(), 2);
+
+        agent.handle_event(&timer(TIMER_TRAFFIC_BASE), &mut ctx).unwrap();
+        assert!(ctx.take_pending_events().is_empty());
+    }
+
+    #[test]
+    fn test_triggered_traffic_replies_to_dm() {
+        let alice = NodeId::from_bytes([7u8; 32]);
+        let config = AgentConfig {
+            traffic: vec![TrafficRule {
+                destination: TrafficDestination::Direct(alice),
+                at_s: 0.0,
+                interval_s: None,
+                interval_jitter_s: 0.0,
+                message_count: Some(1),
+                until_s: None,
+                text: Some("pong".to_string()),
+                trigger: Some(TrafficTrigger {
+                    source: TrafficSource::Direct(alice),
+                    text_contains: Some("ping".to_string()),
+                }),
+                delay_s: 20.0,
+                metric: None,
+            }],
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+
+        // Triggered rules aren't scheduled at startup
+        agent.protocol_state = ProtocolState::Ready;
+        agent.on_ready(&mut ctx);
+        assert!(!timer_ids(&ctx.take_pending_events()).contains(&TIMER_TRAFFIC_BASE));
+
+        let dm = |text: &str| ReceivedContactMessage {
+            sender_prefix: PublicKeyPrefix::new(alice.public_key_hash()),
+            path_len: 0,
+            text_type: TextType::Plain,
+            timestamp: 0,
+            snr_x4: None,
+            extra: Vec::new(),
+            text: text.to_string(),
+        };
+        agent.handle_contact_message(dm("hello"), &mut ctx);
+        assert!(ctx.take_pending_events().is_empty());
+
+        agent.handle_contact_message(dm("ping 1"), &mut ctx);
+        let events = ctx.take_pending_events();
+        assert_eq!(timer_ids(&events), vec![TIMER_TRAFFIC_BASE]);
+        assert_eq!(events[0].time, SimTime::from_secs(20.0));
+
+        agent.handle_event(&timer(TIMER_TRAFFIC_BASE), &mut ctx).unwrap();
+        assert_eq!(agent.direct_messages_sent(), 1);
+        ctx.take_pending_events();
+
+        // The count is reached: later pings go unanswered
+        agent.handle_contact_message(dm("ping 2"), &mut ctx);
+        assert!(ctx.take_pending_events().is_empty());
+    }
+
+    #[test]
+    fn test_traffic_rule_counts_custom_metric() {
+        use mcsim_metrics::custom::{self, CustomMetric};
+        use mcsim_metrics::metrics::{
+            Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+        };
+        use mcsim_metrics::MetricKind;
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::{Arc, Mutex};
+
+        /// Keeps the counters registered with it, keyed by name and labels.
+        #[derive(Default)]
+        struct CountingRecorder {
+            counters: Mutex<Vec<(String, Vec<(String, String)>, Arc<AtomicU64>)>>,
+        }
+
+        impl Recorder for CountingRecorder {
+            fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+                let labels: Vec<(String, String)> =
+                    key.labels().map(|l| (l.key().to_string(), l.value().to_string())).collect();
+                let counter = Arc::new(AtomicU64::new(0));
+                self.counters.lock().unwrap().push((key.name().to_string(), labels, counter.clone()));
+                Counter::from_arc(counter)
+            }
+            fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+                Gauge::noop()
+            }
+            fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+                Histogram::noop()
+            }
+        }
+
+        custom::register(CustomMetric::new("test.agents.pings", MetricKind::Counter).with_labels(["node"])).unwrap();
+        let alice = NodeId::from_bytes([7u8; 32]);
+        let config = AgentConfig {
+            name: "Bob".to_string(),
+            traffic: vec![TrafficRule {
+                destination: TrafficDestination::Direct(alice),
+                at_s: 0.0,
+                interval_s: None,
+                interval_jitter_s: 0.0,
+                message_count: Some(1),
+                until_s: None,
+                text: None,
+                trigger: None,
+                delay_s: 0.0,
+                metric: Some("test.agents.pings".to_string()),
+            }],
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+        agent.protocol_state = ProtocolState::Ready;
+
+        let recorder = CountingRecorder::default();
+        mcsim_metrics::metrics::with_local_recorder(&recorder, || {
+            agent.handle_event(&timer(TIMER_TRAFFIC_BASE), &mut ctx).unwrap();
+        });
+        assert_eq!(agent.direct_messages_sent(), 1);
+
+        let counters = recorder.counters.lock().unwrap();
+        let (_, labels, count) = counters.iter().find(|(name, _, _)| name == "test.agents.pings").unwrap();
+        assert_eq!(labels, &vec![("node".to_string(), "Bob".to_string())]);
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_phone_app_read_receipts_wait_for_connection() {
+        let config = AgentConfig {
+            phone: PhoneAppConfig { enabled: true, read_receipts: true, ..Default::default() },
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+        agent.ever_ready = true;
+
+        agent.queue_read_receipt(PublicKeyPrefix::new([7u8; 6]), &mut ctx);
+        assert_eq!(timer_ids(&ctx.take_pending_events()), vec![TIMER_READ_RECEIPT]);
+
+        // Read while disconnected: the receipt goes out once connected again
+        agent.handle_event(&timer(TIMER_READ_RECEIPT), &mut ctx).unwrap();
+        assert_eq!(agent.read_receipts_sent(), 0);
+
+        agent.protocol_state = ProtocolState::Ready;
+        agent.send_read_receipts(&mut ctx);
+        assert_eq!(agent.read_receipts_sent(), 1);
+        assert!(agent.unread.is_empty());
+    }
+
+    #[test]
+    fn test_room_client_logs_in_and_posts() {
+        let room = NodeId::from_bytes([9u8; 32]);
+        let config = AgentConfig {
+            room: RoomClientConfig {
+                room: Some(room),
+                post_interval_jitter_s: 0.0,
+                post_count: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+
+        agent.protocol_state = ProtocolState::Ready;
+        agent.on_ready(&mut ctx);
+        assert_eq!(timer_ids(&ctx.take_pending_events()), vec![TIMER_ROOM_LOGIN]);
+
+        // The login goes out with a retry timer behind it
+        agent.handle_event(&timer(TIMER_ROOM_LOGIN), &mut ctx).unwrap();
+        let events = ctx.take_pending_events();
+        assert!(matches!(events[0].payload, EventPayload::SerialRx(_)));
+        assert_eq!(timer_ids(&events), vec![TIMER_ROOM_LOGIN]);
+
+        // Another server's login is not ours
+        let prefix = PublicKeyPrefix::new(room.public_key_hash());
+        let login = |server_prefix| PushNotification::LoginSuccess {
+            is_admin: false,
+            server_prefix,
+            server_timestamp: None,
+            acl_permissions: None,
+            firmware_ver_level: None,
+        };
+        agent.handle_push(login(PublicKeyPrefix::new([3u8; 6])), &mut ctx);
+        assert!(!agent.room_logged_in());
+        agent.handle_push(login(prefix), &mut ctx);
+        assert!(agent.room_logged_in());
+        assert_eq!(timer_ids(&ctx.take_pending_events()), vec![TIMER_ROOM_POST]);
+
+        // The retry is dropped once logged in
+        agent.handle_event(&timer(TIMER_ROOM_LOGIN), &mut ctx).unwrap();
+        assert!(ctx.take_pending_events().is_empty());
+
+        // One post, then the count is reached
+        agent.handle_event(&timer(TIMER_ROOM_POST), &mut ctx).unwrap();
+        assert_eq!(agent.room_posts_sent(), 1);
+        assert!(timer_ids(&ctx.take_pending_events()).is_empty());
+
+        agent.handle_contact_message(
+            ReceivedContactMessage {
+                sender_prefix: prefix,
+                path_len: 0,
+                text_type: TextType::SignedPlain,
+                timestamp: 0,
+                snr_x4: None,
+                extra: vec![1, 2, 3, 4],
+                text: "hi all".to_string(),
+            },
+            &mut ctx,
+        );
+        assert_eq!(agent.room_messages_received(), 1);
+    }
+
+    #[test]
+    fn test_reload_traffic_restarts_rules() {
+        let ops = || ChannelTarget::from_name("#ops".to_string());
+        let rule = |at_s: f64, destination: TrafficDestination| TrafficRule {
+            destination,
+            at_s,
+            interval_s: Some(300.0),
+            interval_jitter_s: 0.0,
+            message_count: Some(2),
+            until_s: None,
+            text: None,
+            trigger: None,
+            delay_s: 0.0,
+            metric: None,
+        };
+        let config = AgentConfig {
+            channel: ChannelMessageConfig { subscribe_only: vec![ops()], ..Default::default() },
+            traffic: vec![rule(60.0, TrafficDestination::Channel(ops()))],
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+        agent.protocol_state = ProtocolState::Ready;
+        agent.on_ready(&mut ctx);
+        ctx.take_pending_events();
+        agent.handle_event(&timer(TIMER_TRAFFIC_BASE), &mut ctx).unwrap();
+        ctx.take_pending_events();
+
+        // A DM to a node that is not a contact can't be sent
+        let script = encode_traffic_script(&[
+            rule(0.0, TrafficDestination::Channel(ops())),
+            rule(0.0, TrafficDestination::Direct(NodeId::from_bytes([5u8; 32]))),
+        ]);
+        let reload = Event {
+            payload: EventPayload::ReloadScript(mcsim_common::ReloadScriptEvent { script }),
+            ..timer(0)
+        };
+        agent.handle_event(&reload, &mut ctx).unwrap();
+        assert_eq!(agent.config().traffic.len(), 1);
+        let restarted = TIMER_TRAFFIC_BASE + TRAFFIC_TIMER_STRIDE;
+        assert_eq!(timer_ids(&ctx.take_pending_events()), vec![restarted]);
+
+        // The old rule's pending timer is stale; the new rule counts afresh
+        agent.handle_event(&timer(TIMER_TRAFFIC_BASE), &mut ctx).unwrap();
+        assert!(ctx.take_pending_events().is_empty());
+        agent.handle_event(&timer(restarted), &mut ctx).unwrap();
+        agent.handle_event(&timer(restarted), &mut ctx).unwrap();
+        assert_eq!(agent.channel_messages_sent(), 3);
+        assert_eq!(agent.traffic_sent, vec![2]);
+    }
+
+    #[test]
+    fn test_gps_shares_location_at_most_once_per_interval() {
+        let home = GeoCoord::new(47.6, -122.3);
+        let config = AgentConfig {
+            gps: GpsConfig { enabled: true, position: Some(home), interval_s: 60.0 },
+            ..Default::default()
+        };
+        let mut agent = Agent::new(EntityId::new(1), config, NodeId::from_bytes([1u8; 32]), EntityId::new(2));
+        let mut ctx = SimContext::new(1);
+        agent.protocol_state = ProtocolState::Ready;
+        agent.on_ready(&mut ctx);
+        assert_eq!(agent.location_updates_sent(), 1);
+        ctx.take_pending_events();
+
+        let fix = |position: GeoCoord| Event {
+            payload: EventPayload::PositionFix(mcsim_common::PositionFixEvent { position }),
+            ..timer(0)
+        };
+        // Unchanged: nothing to send
+        agent.handle_event(&fix(home), &mut ctx).unwrap();
+        assert!(ctx.take_pending_events().is_empty());
+
+        // Moved within the interval: sent when it ends, with the latest fix
+        ctx.set_time(SimTime::from_secs(10.0));
+        agent.handle_event(&fix(GeoCoord::new(47.7, -122.3)), &mut ctx).unwrap();
+        agent.handle_event(&fix(GeoCoord::new(47.8, -122.3)), &mut ctx).unwrap();
+        let events = ctx.take_pending_events();
+        assert_eq!(timer_ids(&events), vec![TIMER_GPS_UPDATE]);
+        assert_eq!(events[0].time, SimTime::from_secs(60.0));
+        assert_eq!(agent.location_updates_sent(), 1);
+
+        ctx.set_time(SimTime::from_secs(60.0));
+        agent.handle_event(&timer(TIMER_GPS_UPDATE), &mut ctx).unwrap();
+        assert_eq!(agent.location_updates_sent(), 2);
+        assert_eq!(agent.gps_sent.map(|(p, _)| p.latitude), Some(47.8));
+    }
 }
@@ -0,0 +1,367 @@
+//! Golden corpus of encoded CLI lines.
+//!
+//! [`corpus`] produces one representative line for every command and for each
+//! response shape the parser recognizes. The corpus is rendered to a text file
+//! that is checked in; [`verify`] then compares the checked-in file against the
+//! current code:
+//!
+//! - Commands are re-encoded and must produce the stored bytes.
+//! - Responses are run through [`LineCodec`] and [`Response::parse`] from the
+//!   stored bytes and must produce the stored value.
+//!
+//! A mismatch means the CLI format changed. If the change is intended, the
+//! file is regenerated with
+//! `MCSIM_UPDATE_GOLDEN=1 cargo test -p mcsim-cli-protocol --test golden`.
+//!
+//! File format: one block per line, separated by blank lines.
+//!
+//! ```text
+//! command get_name
+//! hex: 676574206e616d650d
+//! value: GetConfig { key: Name }
+//! ```
+
+use crate::codec::LineCodec;
+use crate::commands::{Command, ConfigKey};
+use crate::error::{CliError, CliResult};
+use crate::responses::Response;
+
+/// Direction of a golden line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    /// Host → firmware command.
+    Command,
+    /// Firmware → host response.
+    Response,
+}
+
+impl FrameKind {
+    /// Keyword used in the golden file.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrameKind::Command => "command",
+            FrameKind::Response => "response",
+        }
+    }
+}
+
+impl std::str::FromStr for FrameKind {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "command" => Ok(FrameKind::Command),
+            "response" => Ok(FrameKind::Response),
+            other => Err(CliError::ParseError(format!("unknown frame kind: {}", other))),
+        }
+    }
+}
+
+/// One entry of the golden corpus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenFrame {
+    /// Line direction.
+    pub kind: FrameKind,
+    /// Unique name within its kind (e.g. "get_radio").
+    pub name: String,
+    /// Raw bytes on the wire.
+    pub bytes: Vec<u8>,
+    /// Debug rendering of the command or the parsed response.
+    pub value: String,
+}
+
+impl GoldenFrame {
+    fn command(name: &str, command: Command) -> Self {
+        GoldenFrame {
+            kind: FrameKind::Command,
+            name: name.to_string(),
+            bytes: command.encode(),
+            value: format!("{:?}", command),
+        }
+    }
+
+    fn response(name: &str, text: &str) -> Self {
+        let bytes = format!("  -> {}\r\n", text).into_bytes();
+        GoldenFrame {
+            kind: FrameKind::Response,
+            name: name.to_string(),
+            value: decode_value(&bytes),
+            bytes,
+        }
+    }
+}
+
+/// Decode firmware output into the string stored in the golden file.
+fn decode_value(bytes: &[u8]) -> String {
+    let mut codec = LineCodec::new();
+    codec.push_raw(bytes);
+    match codec.decode_response() {
+        Some(text) => match Response::parse(&text) {
+            Ok(response) => format!("{:?}", response),
+            Err(e) => format!("error: {}", e),
+        },
+        None => "incomplete".to_string(),
+    }
+}
+
+// ============================================================================
+// Corpus Generation
+// ============================================================================
+
+fn command_frames() -> Vec<GoldenFrame> {
+    let mut frames = vec![
+        GoldenFrame::command("reboot", Command::Reboot),
+        GoldenFrame::command("advert", Command::Advert),
+        GoldenFrame::command("clock_sync", Command::ClockSync),
+        GoldenFrame::command("clock", Command::Clock),
+        GoldenFrame::command("set_time", Command::SetTime { epoch_secs: 1_700_000_000 }),
+        GoldenFrame::command("start_ota", Command::StartOta),
+        GoldenFrame::command("erase", Command::Erase),
+        GoldenFrame::command("get_config_raw", Command::GetConfigRaw { key: "owner.info".to_string() }),
+        GoldenFrame::command(
+            "set_config_raw",
+            Command::SetConfigRaw { key: "owner.info".to_string(), value: "Bob".to_string() },
+        ),
+        GoldenFrame::command("set_password", Command::SetPassword { password: "secret".to_string() }),
+        GoldenFrame::command(
+            "temp_radio",
+            Command::TempRadio { freq: 910.525, bw: 62.5, sf: 7, cr: 5, timeout_mins: 30 },
+        ),
+        GoldenFrame::command("neighbors", Command::Neighbors),
+        GoldenFrame::command("remove_neighbor", Command::RemoveNeighbor { pubkey_hex: "a1b2c3".to_string() }),
+        GoldenFrame::command("clear_stats", Command::ClearStats),
+        GoldenFrame::command("stats_core", Command::StatsCore),
+        GoldenFrame::command("stats_radio", Command::StatsRadio),
+        GoldenFrame::command("stats_packets", Command::StatsPackets),
+        GoldenFrame::command("version", Command::Version),
+        GoldenFrame::command("board", Command::Board),
+        GoldenFrame::command("sensor_get", Command::SensorGet { key: "temp".to_string() }),
+        GoldenFrame::command(
+            "sensor_set",
+            Command::SensorSet { key: "temp".to_string(), value: "1".to_string() },
+        ),
+        GoldenFrame::command("sensor_list", Command::SensorList { start: None }),
+        GoldenFrame::command("sensor_list_from", Command::SensorList { start: Some(8) }),
+        GoldenFrame::command("gps_on", Command::GpsOn),
+        GoldenFrame::command("gps_off", Command::GpsOff),
+        GoldenFrame::command("gps_sync", Command::GpsSync),
+        GoldenFrame::command("gps_setloc", Command::GpsSetLoc),
+        GoldenFrame::command("gps_advert", Command::GpsAdvert { policy: None }),
+        GoldenFrame::command("gps_advert_share", Command::GpsAdvert { policy: Some("share".to_string()) }),
+        GoldenFrame::command("gps_status", Command::GpsStatus),
+        GoldenFrame::command("log_start", Command::LogStart),
+        GoldenFrame::command("log_stop", Command::LogStop),
+        GoldenFrame::command("log_erase", Command::LogErase),
+        GoldenFrame::command("log_dump", Command::LogDump),
+        GoldenFrame::command(
+            "set_perm",
+            Command::SetPerm { pubkey_hex: "a1b2c3d4".to_string(), permissions: 3 },
+        ),
+        GoldenFrame::command("get_acl", Command::GetAcl),
+        GoldenFrame::command("raw", Command::Raw { command: "region".to_string() }),
+    ];
+
+    // Every typed config key, as get and set
+    const CONFIG_KEYS: &[(ConfigKey, &str)] = &[
+        (ConfigKey::AirtimeFactor, "1.0"),
+        (ConfigKey::InterferenceThreshold, "14"),
+        (ConfigKey::AgcResetInterval, "4"),
+        (ConfigKey::MultiAcks, "1"),
+        (ConfigKey::AllowReadOnly, "on"),
+        (ConfigKey::FloodAdvertInterval, "12"),
+        (ConfigKey::AdvertInterval, "60"),
+        (ConfigKey::GuestPassword, "guest"),
+        (ConfigKey::PrivateKey, "00112233"),
+        (ConfigKey::Name, "Node-1"),
+        (ConfigKey::Repeat, "on"),
+        (ConfigKey::Latitude, "47.6062"),
+        (ConfigKey::Longitude, "-122.3321"),
+        (ConfigKey::Radio, "910.525,62.5,7,5"),
+        (ConfigKey::RxDelay, "0"),
+        (ConfigKey::TxDelay, "0.5"),
+        (ConfigKey::DirectTxDelay, "0.2"),
+        (ConfigKey::FloodMax, "64"),
+        (ConfigKey::TxPower, "20"),
+        (ConfigKey::Frequency, "910.525"),
+        (ConfigKey::PublicKey, "a1b2c3d4"),
+        (ConfigKey::Role, "repeater"),
+        (ConfigKey::BridgeType, "rs232"),
+        (ConfigKey::BridgeEnabled, "on"),
+        (ConfigKey::BridgeDelay, "500"),
+        (ConfigKey::BridgeSource, "tx"),
+        (ConfigKey::BridgeBaud, "115200"),
+        (ConfigKey::BridgeChannel, "1"),
+        (ConfigKey::BridgeSecret, "secret"),
+        (ConfigKey::AdcMultiplier, "2.0"),
+    ];
+    for &(key, value) in CONFIG_KEYS {
+        let name = key.as_str().replace('.', "_");
+        frames.push(GoldenFrame::command(&format!("get_{}", name), Command::GetConfig { key }));
+        frames.push(GoldenFrame::command(
+            &format!("set_{}", name),
+            Command::SetConfig { key, value: value.to_string() },
+        ));
+    }
+    frames
+}
+
+fn response_frames() -> Vec<GoldenFrame> {
+    vec![
+        GoldenFrame::response("ok", "OK"),
+        GoldenFrame::response("ok_message", "OK - clock set: 12:30 - 14/11/2023 UTC"),
+        GoldenFrame::response("value", "> Node-1"),
+        GoldenFrame::response("value_radio", "> 910.525,62.5,7,5"),
+        GoldenFrame::response("error_colon", "ERR: unknown config"),
+        GoldenFrame::response("error_word", "Error: clock cannot go backwards"),
+        GoldenFrame::response("clock", "12:30 - 14/11/2023 UTC"),
+        GoldenFrame::response("clock_short_suffix", "12:30:05 - 14/11/2023 U"),
+        GoldenFrame::response("version", "v1.11.0 (Build: 13-Jan-2025)"),
+        GoldenFrame::response("unknown", "password now: secret"),
+        GoldenFrame {
+            kind: FrameKind::Response,
+            name: "unterminated".to_string(),
+            bytes: b"  -> OK".to_vec(),
+            value: decode_value(b"  -> OK"),
+        },
+    ]
+}
+
+/// Generate the full corpus from the current protocol code.
+pub fn corpus() -> Vec<GoldenFrame> {
+    let mut frames = command_frames();
+    frames.extend(response_frames());
+    frames
+}
+
+// ============================================================================
+// Golden File Format
+// ============================================================================
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> CliResult<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return Err(CliError::ParseError(format!("odd-length hex: {}", s)));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&s[i..i + 2], 16)
+                .map_err(|_| CliError::ParseError(format!("invalid hex: {}", s)))
+        })
+        .collect()
+}
+
+/// Render frames in the golden file format.
+pub fn render(frames: &[GoldenFrame]) -> String {
+    let mut out = String::from(
+        "# MeshCore CLI protocol golden corpus.\n\
+         # Regenerate with: MCSIM_UPDATE_GOLDEN=1 cargo test -p mcsim-cli-protocol --test golden\n",
+    );
+    for frame in frames {
+        out.push_str(&format!(
+            "\n{} {}\nhex: {}\nvalue: {}\n",
+            frame.kind.as_str(),
+            frame.name,
+            to_hex(&frame.bytes),
+            frame.value
+        ));
+    }
+    out
+}
+
+/// Parse a golden file.
+pub fn parse(text: &str) -> CliResult<Vec<GoldenFrame>> {
+    let invalid = |line: &str| CliError::ParseError(format!("malformed golden entry: {}", line));
+    let mut frames = Vec::new();
+    let mut lines = text.lines().filter(|line| !line.is_empty() && !line.starts_with('#'));
+    while let Some(header) = lines.next() {
+        let Some((kind, name)) = header.split_once(' ') else {
+            return Err(invalid(header));
+        };
+        let hex = lines.next().and_then(|l| l.strip_prefix("hex: ")).ok_or_else(|| invalid(header))?;
+        let value = lines.next().and_then(|l| l.strip_prefix("value: ")).ok_or_else(|| invalid(header))?;
+        frames.push(GoldenFrame {
+            kind: kind.parse()?,
+            name: name.to_string(),
+            bytes: from_hex(hex)?,
+            value: value.to_string(),
+        });
+    }
+    Ok(frames)
+}
+
+/// Check a golden file against the current code.
+///
+/// Returns a description of every difference; an empty list means the CLI
+/// format is unchanged.
+pub fn verify(text: &str) -> Vec<String> {
+    let golden = match parse(text) {
+        Ok(golden) => golden,
+        Err(e) => return vec![e.to_string()],
+    };
+    let current = corpus();
+    let find = |frames: &[GoldenFrame], frame: &GoldenFrame| {
+        frames.iter().position(|f| f.kind == frame.kind && f.name == frame.name)
+    };
+
+    let mut problems = Vec::new();
+    for frame in &golden {
+        let label = format!("{} '{}'", frame.kind.as_str(), frame.name);
+        match frame.kind {
+            FrameKind::Command => match find(&current, frame) {
+                Some(i) if current[i].bytes != frame.bytes => problems.push(format!(
+                    "{} encodes as {:?} (golden {:?})",
+                    label,
+                    String::from_utf8_lossy(&current[i].bytes),
+                    String::from_utf8_lossy(&frame.bytes)
+                )),
+                Some(_) => {}
+                None => problems.push(format!("{} is no longer generated", label)),
+            },
+            FrameKind::Response => {
+                let value = decode_value(&frame.bytes);
+                if value != frame.value {
+                    problems.push(format!("{} decodes as {} (golden {})", label, value, frame.value));
+                }
+            }
+        }
+    }
+    for frame in &current {
+        if find(&golden, frame).is_none() {
+            problems.push(format!(
+                "{} '{}' is missing from the golden corpus",
+                frame.kind.as_str(),
+                frame.name
+            ));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_parse_round_trip() {
+        let frames = corpus();
+        assert_eq!(parse(&render(&frames)).unwrap(), frames);
+        assert!(verify(&render(&frames)).is_empty());
+    }
+
+    #[test]
+    fn test_verify_reports_changes() {
+        let mut frames = corpus();
+        frames[0].bytes = b"restart\r".to_vec();
+        let response = frames.iter().position(|f| f.kind == FrameKind::Response).unwrap();
+        frames[response].value = "Unknown(\"OK\")".to_string();
+
+        let problems = verify(&render(&frames));
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].starts_with("command 'reboot' encodes as \"reboot\\r\""));
+        assert!(problems[1].starts_with("response 'ok' decodes as Ok"));
+    }
+}
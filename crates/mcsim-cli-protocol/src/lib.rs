@@ -34,13 +34,28 @@
 //! // Parse a response
 //! let response = Response::parse("  -> OK")?;
 //! ```
+//!
+//! # Sessions
+//!
+//! [`CliSession`] queues commands, drops their echo, separates log lines from
+//! responses and retries commands that time out, for scripting a node without
+//! handling the raw [`LineCodec`] stream.
+//!
+//! # Format Stability
+//!
+//! The [`golden`] module generates a corpus of every command and response
+//! shape. The checked-in copy under `tests/golden/` is verified by the `golden`
+//! integration test, so an accidental format change fails CI.
 
 mod codec;
 mod commands;
 mod error;
+pub mod golden;
 mod responses;
+mod session;
 
 pub use codec::*;
 pub use commands::*;
 pub use error::*;
 pub use responses::*;
+pub use session::*;
@@ -0,0 +1,264 @@
+//! Command session over the CLI.
+//!
+//! The repeater CLI echoes every character it receives, prints its answer as a
+//! single line prefixed with `  -> `, and may print log lines at any time. A
+//! [`CliSession`] sorts that stream out: commands are queued and sent one at a
+//! time, the echo of the command in flight is dropped, the `  -> ` line (the
+//! CLI's only prompt) completes the command with a parsed [`Response`], and
+//! everything else is kept as an unsolicited log line.
+//!
+//! The session does no I/O and reads no clock. The caller writes the bytes
+//! returned by [`CliSession::poll`], feeds back what it reads, and passes the
+//! current time as a [`Duration`] from any fixed start, so the same session
+//! works over a serial port and inside the simulator.
+//!
+//! ```rust,ignore
+//! use mcsim_cli_protocol::{CliSession, Command, ConfigKey};
+//!
+//! let mut session = CliSession::new();
+//! session.enqueue(Command::GetConfig { key: ConfigKey::Name });
+//! loop {
+//!     if let Some(bytes) = session.poll(start.elapsed()) {
+//!         port.write_all(&bytes)?;
+//!     }
+//!     session.feed(&read_some(&mut port)?);
+//!     while let Some(reply) = session.next_reply() {
+//!         println!("{} -> {:?}", reply.command.to_command_string(), reply.result);
+//!     }
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::codec::{LineCodec, RESPONSE_PREFIX};
+use crate::commands::Command;
+use crate::error::{CliError, CliResult};
+use crate::responses::Response;
+
+/// Timeout and retry settings for a [`CliSession`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CliSessionConfig {
+    /// How long to wait for a command's response before resending it.
+    pub timeout: Duration,
+    /// How many times a command is resent before it fails with
+    /// [`CliError::Timeout`]. Resending repeats the command, so keep this at
+    /// zero for commands that are not safe to run twice.
+    pub max_retries: u32,
+}
+
+impl Default for CliSessionConfig {
+    fn default() -> Self {
+        CliSessionConfig {
+            timeout: Duration::from_secs(2),
+            max_retries: 1,
+        }
+    }
+}
+
+/// Outcome of one queued command.
+#[derive(Debug)]
+pub struct CliReply {
+    /// ID returned by [`CliSession::enqueue`].
+    pub id: u64,
+    /// The command that was sent.
+    pub command: Command,
+    /// The parsed response, or [`CliError::Timeout`] once all retries are used.
+    pub result: CliResult<Response>,
+}
+
+/// Command being answered.
+#[derive(Debug)]
+struct InFlight {
+    id: u64,
+    command: Command,
+    line: String,
+    sent_at: Duration,
+    attempts: u32,
+}
+
+/// Queued, one-at-a-time command exchange with a repeater or room server CLI.
+#[derive(Debug, Default)]
+pub struct CliSession {
+    codec: LineCodec,
+    config: CliSessionConfig,
+    queue: VecDeque<(u64, Command)>,
+    in_flight: Option<InFlight>,
+    next_id: u64,
+    replies: VecDeque<CliReply>,
+    log_lines: VecDeque<String>,
+}
+
+impl CliSession {
+    /// Create a session with the default timeout and retries.
+    pub fn new() -> Self {
+        Self::with_config(CliSessionConfig::default())
+    }
+
+    /// Create a session with the given timeout and retries.
+    pub fn with_config(config: CliSessionConfig) -> Self {
+        CliSession {
+            codec: LineCodec::new(),
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Queue a command, returning the ID its [`CliReply`] will carry.
+    pub fn enqueue(&mut self, command: Command) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.queue.push_back((id, command));
+        id
+    }
+
+    /// Bytes to write to the CLI now, if any: the next queued command once the
+    /// previous one is answered, or a resend of a command that timed out.
+    /// Commands out of retries complete with [`CliError::Timeout`].
+    pub fn poll(&mut self, now: Duration) -> Option<Vec<u8>> {
+        if let Some(in_flight) = &mut self.in_flight {
+            if now.saturating_sub(in_flight.sent_at) < self.config.timeout {
+                return None;
+            }
+            if in_flight.attempts <= self.config.max_retries {
+                log::debug!("CLI command '{}' timed out, resending", in_flight.line);
+                in_flight.attempts += 1;
+                in_flight.sent_at = now;
+                self.codec.set_last_command(&in_flight.line);
+                return Some(LineCodec::encode_command(&in_flight.line));
+            }
+            let in_flight = self.in_flight.take().expect("checked above");
+            self.codec.clear_echo();
+            self.replies.push_back(CliReply {
+                id: in_flight.id,
+                command: in_flight.command,
+                result: Err(CliError::Timeout),
+            });
+        }
+
+        let (id, command) = self.queue.pop_front()?;
+        let line = command.to_command_string();
+        self.codec.set_last_command(&line);
+        let bytes = LineCodec::encode_command(&line);
+        self.in_flight = Some(InFlight {
+            id,
+            command,
+            line,
+            sent_at: now,
+            attempts: 1,
+        });
+        Some(bytes)
+    }
+
+    /// When [`poll`](Self::poll) should next be called if no data arrives,
+    /// for callers that sleep or schedule a timer.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.in_flight.as_ref().map(|f| f.sent_at + self.config.timeout)
+    }
+
+    /// Feed bytes read from the CLI.
+    pub fn feed(&mut self, data: &[u8]) {
+        self.codec.push(data);
+        while let Some(line) = self.codec.decode_line() {
+            if let Some(text) = line.strip_prefix(RESPONSE_PREFIX) {
+                self.complete(text);
+            } else if self.is_echo(&line) {
+                log::trace!("Dropping CLI echo '{}'", line);
+            } else {
+                self.log_lines.push_back(line);
+            }
+        }
+    }
+
+    /// An echo the codec could not drop because a log line interrupted it.
+    fn is_echo(&self, line: &str) -> bool {
+        self.in_flight
+            .as_ref()
+            .is_some_and(|f| !line.trim().is_empty() && f.line.ends_with(line.trim()))
+    }
+
+    fn complete(&mut self, text: &str) {
+        let Some(in_flight) = self.in_flight.take() else {
+            log::debug!("CLI response with no command in flight: '{}'", text);
+            return;
+        };
+        self.codec.clear_echo();
+        self.replies.push_back(CliReply {
+            id: in_flight.id,
+            command: in_flight.command,
+            result: Response::parse(text),
+        });
+    }
+
+    /// Take the next completed command.
+    pub fn next_reply(&mut self) -> Option<CliReply> {
+        self.replies.pop_front()
+    }
+
+    /// Take the next line the firmware printed on its own (logs, banners).
+    pub fn next_log_line(&mut self) -> Option<String> {
+        self.log_lines.pop_front()
+    }
+
+    /// Whether no command is queued or waiting for its response.
+    pub fn is_idle(&self) -> bool {
+        self.in_flight.is_none() && self.queue.is_empty()
+    }
+
+    /// Number of commands queued or in flight.
+    pub fn pending(&self) -> usize {
+        self.queue.len() + usize::from(self.in_flight.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ConfigKey;
+
+    #[test]
+    fn test_session_queues_and_filters() {
+        let mut session = CliSession::new();
+        let get = session.enqueue(Command::GetConfig { key: ConfigKey::Name });
+        let set = session.enqueue(Command::Raw { command: "set rxdelay 0".to_string() });
+        assert_eq!(session.pending(), 2);
+
+        // One command at a time
+        assert_eq!(session.poll(Duration::ZERO), Some(b"get name\r".to_vec()));
+        assert_eq!(session.poll(Duration::ZERO), None);
+
+        // The echo is broken up by a log line; both echo halves are dropped
+        session.feed(b"get n\r\nRX, len=12 (type=4)\r\name\r\n  -> > Repeater1\r\n");
+        let reply = session.next_reply().unwrap();
+        assert_eq!(reply.id, get);
+        assert_eq!(reply.result.unwrap(), Response::Value("Repeater1".to_string()));
+        assert_eq!(session.next_log_line().as_deref(), Some("RX, len=12 (type=4)"));
+        assert!(session.next_log_line().is_none());
+
+        assert_eq!(session.poll(Duration::from_millis(10)), Some(b"set rxdelay 0\r".to_vec()));
+        session.feed(b"set rxdelay 0\r\n  -> OK\r\n");
+        let reply = session.next_reply().unwrap();
+        assert_eq!(reply.id, set);
+        assert_eq!(reply.result.unwrap(), Response::Ok);
+        assert!(session.is_idle());
+    }
+
+    #[test]
+    fn test_session_timeout_and_retry() {
+        let mut session = CliSession::with_config(CliSessionConfig {
+            timeout: Duration::from_secs(1),
+            max_retries: 1,
+        });
+        session.enqueue(Command::Version);
+        assert!(session.poll(Duration::ZERO).is_some());
+        assert_eq!(session.next_deadline(), Some(Duration::from_secs(1)));
+
+        // Resent once, then given up
+        assert_eq!(session.poll(Duration::from_secs(1)), Some(b"ver\r".to_vec()));
+        assert!(session.poll(Duration::from_millis(1500)).is_none());
+        assert!(session.poll(Duration::from_secs(2)).is_none());
+        let reply = session.next_reply().unwrap();
+        assert!(matches!(reply.result, Err(CliError::Timeout)));
+        assert!(session.is_idle());
+    }
+}
@@ -0,0 +1,244 @@
+//! LoRa time-on-air calculation.
+//!
+//! This module implements the airtime formula from the Semtech SX127x/SX126x
+//! datasheets (AN1200.13). It lives in `mcsim-common` so that the radio entity,
+//! agents and offline analysis tooling all share one implementation instead of
+//! relying on the airtime reported by the firmware DLL.
+//!
+//! The payload CRC and low data rate optimisation count towards the airtime,
+//! as they do on the radio. SF5 and SF6 use the SX126x framing (a longer
+//! preamble and no header symbol offset); the SX127x doesn't support SF5.
+//!
+//! ```rust
+//! use mcsim_common::airtime::{AirtimeParams, LoraHeaderMode};
+//!
+//! let params = AirtimeParams::new(7, 125_000, 5)
+//!     .with_preamble_symbols(8)
+//!     .with_header_mode(LoraHeaderMode::Explicit);
+//! let toa = params.time_on_air(10);
+//! assert_eq!(toa.as_micros(), 41_216);
+//! ```
+
+use crate::{RadioParams, SimTime};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Parameters
+// ============================================================================
+
+/// LoRa PHY header mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoraHeaderMode {
+    /// Explicit header: length, coding rate and CRC flag are sent on air.
+    #[default]
+    Explicit,
+    /// Implicit header: both ends agree on the framing out of band.
+    Implicit,
+}
+
+/// Low data rate optimisation setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LowDataRateOptimize {
+    /// Enabled when the symbol time exceeds 16 ms (the radio driver default).
+    #[default]
+    Auto,
+    /// Always enabled.
+    On,
+    /// Always disabled.
+    Off,
+}
+
+/// Inputs to the LoRa time-on-air formula.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AirtimeParams {
+    /// Spreading factor (5-12).
+    pub spreading_factor: u8,
+    /// Bandwidth in Hz.
+    pub bandwidth_hz: u32,
+    /// Coding rate denominator (5-8, representing 4/5 to 4/8).
+    pub coding_rate: u8,
+    /// Number of programmed preamble symbols.
+    pub preamble_symbols: u32,
+    /// Header mode.
+    pub header_mode: LoraHeaderMode,
+    /// Whether the payload CRC is enabled.
+    pub crc_enabled: bool,
+    /// Low data rate optimisation setting.
+    pub low_data_rate_optimize: LowDataRateOptimize,
+}
+
+impl AirtimeParams {
+    /// Default preamble symbol count used by MeshCore.
+    pub const DEFAULT_PREAMBLE_SYMBOLS: u32 = 8;
+
+    /// Create parameters with MeshCore framing defaults (8 preamble symbols,
+    /// explicit header, CRC on, automatic low data rate optimisation).
+    pub fn new(spreading_factor: u8, bandwidth_hz: u32, coding_rate: u8) -> Self {
+        AirtimeParams {
+            spreading_factor,
+            bandwidth_hz,
+            coding_rate,
+            preamble_symbols: Self::DEFAULT_PREAMBLE_SYMBOLS,
+            header_mode: LoraHeaderMode::Explicit,
+            crc_enabled: true,
+            low_data_rate_optimize: LowDataRateOptimize::Auto,
+        }
+    }
+
+    /// Create parameters from radio parameters, using MeshCore framing defaults.
+    pub fn from_radio_params(params: &RadioParams) -> Self {
+        Self::new(params.spreading_factor, params.bandwidth_hz, params.coding_rate)
+    }
+
+    /// Set the number of preamble symbols.
+    pub fn with_preamble_symbols(mut self, preamble_symbols: u32) -> Self {
+        self.preamble_symbols = preamble_symbols;
+        self
+    }
+
+    /// Set the header mode.
+    pub fn with_header_mode(mut self, header_mode: LoraHeaderMode) -> Self {
+        self.header_mode = header_mode;
+        self
+    }
+
+    /// Enable or disable the payload CRC.
+    pub fn with_crc(mut self, crc_enabled: bool) -> Self {
+        self.crc_enabled = crc_enabled;
+        self
+    }
+
+    /// Set the low data rate optimisation mode.
+    pub fn with_low_data_rate_optimize(mut self, mode: LowDataRateOptimize) -> Self {
+        self.low_data_rate_optimize = mode;
+        self
+    }
+
+    /// Duration of one symbol in microseconds.
+    pub fn symbol_time_us(&self) -> f64 {
+        2.0_f64.powi(self.spreading_factor as i32) * 1_000_000.0 / self.bandwidth_hz as f64
+    }
+
+    /// Whether low data rate optimisation is in effect for these parameters.
+    pub fn low_data_rate_optimize_enabled(&self) -> bool {
+        match self.low_data_rate_optimize {
+            LowDataRateOptimize::Auto => self.symbol_time_us() > 16_000.0,
+            LowDataRateOptimize::On => true,
+            LowDataRateOptimize::Off => false,
+        }
+    }
+
+    /// Number of preamble symbols on air, including sync word and SFD.
+    /// SF5 and SF6 send two extra sync symbols.
+    pub fn preamble_symbol_count(&self) -> f64 {
+        if self.spreading_factor < 7 {
+            self.preamble_symbols as f64 + 6.25
+        } else {
+            self.preamble_symbols as f64 + 4.25
+        }
+    }
+
+    /// Number of symbols for the header and payload of a `payload_len` byte packet.
+    pub fn payload_symbol_count(&self, payload_len: usize) -> u32 {
+        let sf = self.spreading_factor as i64;
+        let pl = payload_len as i64;
+        let crc = if self.crc_enabled { 1 } else { 0 };
+        let ih = if self.header_mode == LoraHeaderMode::Implicit { 1 } else { 0 };
+        let de = if self.low_data_rate_optimize_enabled() { 1 } else { 0 };
+        // coding_rate is the denominator (5-8); the formula uses CR = 1..4.
+        let cr = (self.coding_rate.clamp(5, 8) - 4) as i64;
+
+        // SF5 and SF6 (SX126x) drop the 8 bit header offset and never use
+        // low data rate optimisation.
+        let (numerator, denominator) = if sf < 7 {
+            (8 * pl - 4 * sf + 20 + 16 * crc - 20 * ih, 4 * sf)
+        } else {
+            (8 * pl - 4 * sf + 28 + 16 * crc - 20 * ih, 4 * (sf - 2 * de).max(1))
+        };
+        let blocks = if numerator > 0 {
+            (numerator + denominator - 1) / denominator
+        } else {
+            0
+        };
+
+        (8 + blocks * (cr + 4)) as u32
+    }
+
+    /// Total time on air for a `payload_len` byte packet.
+    pub fn time_on_air(&self, payload_len: usize) -> SimTime {
+        time_on_air(self, payload_len)
+    }
+}
+
+// ============================================================================
+// Calculation
+// ============================================================================
+
+/// Calculate the LoRa time on air for a packet, rounded to the nearest microsecond.
+pub fn time_on_air(params: &AirtimeParams, payload_len: usize) -> SimTime {
+    let symbols = params.preamble_symbol_count() + params.payload_symbol_count(payload_len) as f64;
+    SimTime::from_micros((symbols * params.symbol_time_us()).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sf7_bw125_matches_semtech_calculator() {
+        let params = AirtimeParams::new(7, 125_000, 5);
+        assert!(!params.low_data_rate_optimize_enabled());
+        assert_eq!(params.payload_symbol_count(10), 28);
+        assert_eq!(params.time_on_air(10).as_micros(), 41_216);
+    }
+
+    #[test]
+    fn test_matches_semtech_calculator() {
+        // (SF, bandwidth, CR denominator, header, CRC, payload bytes, µs)
+        let cases = [
+            (7, 125_000, 5, LoraHeaderMode::Explicit, true, 20, 56_576),
+            (9, 125_000, 7, LoraHeaderMode::Implicit, false, 32, 283_648),
+            (10, 250_000, 5, LoraHeaderMode::Explicit, true, 100, 513_024),
+            // Low data rate optimisation (symbol time over 16 ms)
+            (11, 125_000, 5, LoraHeaderMode::Explicit, true, 50, 1_314_816),
+            (12, 125_000, 8, LoraHeaderMode::Explicit, true, 255, 14_032_896),
+            // SX126x SF5/SF6 framing
+            (5, 125_000, 5, LoraHeaderMode::Explicit, true, 10, 12_096),
+            (6, 125_000, 5, LoraHeaderMode::Explicit, true, 10, 21_632),
+            (6, 500_000, 6, LoraHeaderMode::Implicit, false, 64, 18_976),
+        ];
+        for (sf, bw, cr, header, crc, len, expected_us) in cases {
+            let params = AirtimeParams::new(sf, bw, cr).with_header_mode(header).with_crc(crc);
+            assert_eq!(params.time_on_air(len).as_micros(), expected_us, "SF{} BW{} {} bytes", sf, bw, len);
+        }
+    }
+
+    #[test]
+    fn test_sf12_uses_low_data_rate_optimize() {
+        let params = AirtimeParams::new(12, 125_000, 5);
+        assert!(params.low_data_rate_optimize_enabled());
+        assert_eq!(params.time_on_air(10).as_micros(), 991_232);
+
+        let forced_off = params.clone().with_low_data_rate_optimize(LowDataRateOptimize::Off);
+        assert!(forced_off.time_on_air(50) < params.time_on_air(50));
+    }
+
+    #[test]
+    fn test_header_crc_and_preamble_options() {
+        let base = AirtimeParams::new(9, 62_500, 8);
+        let implicit = base.clone().with_header_mode(LoraHeaderMode::Implicit).with_crc(false);
+        assert!(implicit.time_on_air(50) < base.time_on_air(50));
+
+        let long_preamble = base.clone().with_preamble_symbols(16);
+        let delta = long_preamble.time_on_air(50).as_micros() - base.time_on_air(50).as_micros();
+        assert_eq!(delta as f64, (8.0 * base.symbol_time_us()).round());
+    }
+
+    #[test]
+    fn test_empty_payload_has_minimum_symbols() {
+        let params = AirtimeParams::new(12, 125_000, 5).with_header_mode(LoraHeaderMode::Implicit).with_crc(false);
+        assert_eq!(params.payload_symbol_count(0), 8);
+    }
+}
@@ -0,0 +1,121 @@
+//! Simulated barometric pressure sensor.
+//!
+//! Nodes with a barometer report air pressure, the altitude the firmware
+//! derives from it, and the sensor's temperature in their environment
+//! telemetry. The true pressure follows the international barometric
+//! formula for the node's altitude:
+//!
+//! ```text
+//! p(h) = p0 · (1 - h / 44330)^5.255
+//! ```
+//!
+//! Each [`Barometer::read`] adds Gaussian noise to the pressure and converts
+//! the noisy value back to an altitude, so the reported altitude scatters
+//! around the node's true one the way a real sensor's does (about 8 m per
+//! hPa near sea level).
+
+use crate::crystal::TemperatureProfile;
+use crate::fading::standard_normal_pair;
+use crate::SimTime;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Standard sea-level pressure, in hPa.
+pub const STANDARD_SEA_LEVEL_HPA: f64 = 1013.25;
+
+/// Temperature reported when no temperature model is configured, in °C.
+pub const STANDARD_TEMPERATURE_C: f64 = 15.0;
+
+/// Scale height of the barometric formula, in meters.
+const BAROMETRIC_SCALE_M: f64 = 44_330.0;
+
+/// Exponent of the barometric formula.
+const BAROMETRIC_EXPONENT: f64 = 5.255;
+
+/// Pressure at an altitude, in hPa.
+pub fn pressure_at_altitude(altitude_m: f64, sea_level_hpa: f64) -> f64 {
+    sea_level_hpa * (1.0 - altitude_m / BAROMETRIC_SCALE_M).max(0.0).powf(BAROMETRIC_EXPONENT)
+}
+
+/// Altitude at which the pressure is `pressure_hpa`, in meters.
+pub fn altitude_from_pressure(pressure_hpa: f64, sea_level_hpa: f64) -> f64 {
+    BAROMETRIC_SCALE_M * (1.0 - (pressure_hpa / sea_level_hpa).max(0.0).powf(1.0 / BAROMETRIC_EXPONENT))
+}
+
+/// One sensor reading.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BarometerReading {
+    /// Measured pressure, in hPa.
+    pub pressure_hpa: f64,
+    /// Altitude derived from the measured pressure, in meters.
+    pub altitude_m: f64,
+    /// Sensor temperature, in °C.
+    pub temperature_c: f64,
+}
+
+/// A node's barometric pressure sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Barometer {
+    /// True altitude of the sensor, in meters.
+    pub altitude_m: f64,
+    /// Sea-level pressure of the weather, in hPa.
+    pub sea_level_hpa: f64,
+    /// Standard deviation of the pressure noise, in hPa.
+    pub noise_std_hpa: f64,
+    /// Temperature at the node (`None` reports [`STANDARD_TEMPERATURE_C`]).
+    pub temperature: Option<TemperatureProfile>,
+}
+
+impl Barometer {
+    /// True pressure at the sensor, in hPa.
+    pub fn true_pressure_hpa(&self) -> f64 {
+        pressure_at_altitude(self.altitude_m, self.sea_level_hpa)
+    }
+
+    /// Take a noisy reading at a simulation time.
+    pub fn read<R: Rng + ?Sized>(&self, time: SimTime, rng: &mut R) -> BarometerReading {
+        let (noise, _) = standard_normal_pair(rng);
+        let pressure_hpa = self.true_pressure_hpa() + self.noise_std_hpa * noise;
+        BarometerReading {
+            pressure_hpa,
+            altitude_m: altitude_from_pressure(pressure_hpa, self.sea_level_hpa),
+            temperature_c: self
+                .temperature
+                .map_or(STANDARD_TEMPERATURE_C, |t| t.temperature_c(time)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_barometric_formula() {
+        assert!((pressure_at_altitude(0.0, STANDARD_SEA_LEVEL_HPA) - STANDARD_SEA_LEVEL_HPA).abs() < 1e-9);
+        // About 899 hPa at 1000 m in the standard atmosphere
+        let p = pressure_at_altitude(1000.0, STANDARD_SEA_LEVEL_HPA);
+        assert!((p - 898.7).abs() < 0.5, "pressure {}", p);
+        assert!((altitude_from_pressure(p, STANDARD_SEA_LEVEL_HPA) - 1000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_noisy_altitude() {
+        let barometer = Barometer {
+            altitude_m: 250.0,
+            sea_level_hpa: STANDARD_SEA_LEVEL_HPA,
+            noise_std_hpa: 0.12,
+            temperature: None,
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let readings: Vec<BarometerReading> = (0..1000).map(|_| barometer.read(SimTime::ZERO, &mut rng)).collect();
+        let mean = readings.iter().map(|r| r.altitude_m).sum::<f64>() / readings.len() as f64;
+        let max_error = readings.iter().map(|r| (r.altitude_m - 250.0).abs()).fold(0.0, f64::max);
+        // 0.12 hPa is about 1 m: the readings scatter a few meters around the true altitude
+        assert!((mean - 250.0).abs() < 0.2, "mean {}", mean);
+        assert!(max_error > 1.0 && max_error < 8.0, "max error {}", max_error);
+        assert_eq!(readings[0].temperature_c, STANDARD_TEMPERATURE_C);
+    }
+}
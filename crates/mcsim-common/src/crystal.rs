@@ -0,0 +1,198 @@
+//! Temperature-dependent crystal drift.
+//!
+//! A node's clock and its radio's carrier are both derived from quartz
+//! crystals, whose frequency depends on temperature. Around its turnover
+//! temperature a crystal's error follows a parabola:
+//!
+//! ```text
+//! ppm(T) = offset_ppm - coefficient · (T - turnover)²
+//! ```
+//!
+//! [`TemperatureProfile`] gives each node a daily temperature cycle, and
+//! [`CrystalDrift`] combines the two into a frequency error over simulated
+//! time: the clock runs fast or slow ([`CrystalDrift::local_time`]) and the
+//! radio carrier shifts ([`CrystalDrift::offset_hz`]).
+
+use crate::SimTime;
+use serde::{Deserialize, Serialize};
+
+/// Turnover temperature of a typical crystal, in °C.
+pub const CRYSTAL_TURNOVER_C: f64 = 25.0;
+
+/// Parabolic coefficient of a 32.768 kHz tuning-fork crystal (RTC clocks).
+pub const TUNING_FORK_COEFFICIENT_PPM_PER_C2: f64 = 0.034;
+
+/// Seconds in a day.
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Daily temperature cycle at a node.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TemperatureProfile {
+    /// Daily mean temperature in °C.
+    pub mean_c: f64,
+    /// Difference between the daily high and low, in °C.
+    pub daily_swing_c: f64,
+    /// Hour of the day (0-24) with the highest temperature.
+    pub warmest_hour: f64,
+    /// Hour of the day (0-24) when the simulation starts.
+    pub start_hour: f64,
+}
+
+impl TemperatureProfile {
+    /// A constant temperature.
+    pub fn constant(temperature_c: f64) -> Self {
+        TemperatureProfile {
+            mean_c: temperature_c,
+            daily_swing_c: 0.0,
+            warmest_hour: 0.0,
+            start_hour: 0.0,
+        }
+    }
+
+    /// Phase of the daily cycle at simulation time zero, in radians.
+    fn start_phase(&self) -> f64 {
+        2.0 * std::f64::consts::PI * (self.start_hour - self.warmest_hour) / 24.0
+    }
+
+    /// Temperature at a simulation time, in °C.
+    pub fn temperature_c(&self, time: SimTime) -> f64 {
+        let phase = self.start_phase() + angular_rate() * time.as_secs_f64();
+        self.mean_c + self.daily_swing_c / 2.0 * phase.cos()
+    }
+}
+
+/// Temperature response of a crystal.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CrystalConfig {
+    /// Frequency error at the turnover temperature, in ppm.
+    pub offset_ppm: f64,
+    /// How fast the frequency falls away from the turnover temperature,
+    /// in ppm/°C².
+    pub coefficient_ppm_per_c2: f64,
+}
+
+impl CrystalConfig {
+    /// Frequency error at a temperature, in ppm.
+    pub fn ppm(&self, temperature_c: f64) -> f64 {
+        self.offset_ppm - self.coefficient_ppm_per_c2 * (temperature_c - CRYSTAL_TURNOVER_C).powi(2)
+    }
+}
+
+/// A crystal's frequency error over simulated time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CrystalDrift {
+    /// The node's temperature.
+    pub temperature: TemperatureProfile,
+    /// The crystal.
+    pub crystal: CrystalConfig,
+}
+
+impl CrystalDrift {
+    /// Frequency error at a simulation time, in ppm.
+    pub fn ppm_at(&self, time: SimTime) -> f64 {
+        self.crystal.ppm(self.temperature.temperature_c(time))
+    }
+
+    /// Offset of a carrier generated from this crystal, in Hz.
+    pub fn offset_hz(&self, frequency_hz: u32, time: SimTime) -> i64 {
+        (frequency_hz as f64 * self.ppm_at(time) * 1e-6).round() as i64
+    }
+
+    /// A carrier frequency shifted by the crystal error at `time`.
+    pub fn apply_to_frequency(&self, frequency_hz: u32, time: SimTime) -> u32 {
+        (frequency_hz as i64 + self.offset_hz(frequency_hz, time)).clamp(0, u32::MAX as i64) as u32
+    }
+
+    /// Time shown by a clock driven by this crystal, started with the
+    /// simulation.
+    pub fn local_time(&self, time: SimTime) -> SimTime {
+        let t = time.as_secs_f64();
+        let error_s = self.integrated_ppm(t) * 1e-6;
+        SimTime::from_micros(((t + error_s) * 1e6).round().max(0.0) as u64)
+    }
+
+    /// Simulation time that passes while the local clock advances by
+    /// `local_delay`, starting at `time`.
+    pub fn sim_delay(&self, local_delay: SimTime, time: SimTime) -> SimTime {
+        let rate = 1.0 + self.ppm_at(time) * 1e-6;
+        SimTime::from_micros((local_delay.as_micros() as f64 / rate).round() as u64)
+    }
+
+    /// Integral of the ppm error from simulation time zero to `t` seconds.
+    ///
+    /// With `T(s) = m + a·cos(θ(s))`, the squared distance from turnover
+    /// expands to `d² + a²/2 + 2da·cos θ + (a²/2)·cos 2θ` (`d = m - turnover`),
+    /// which integrates in closed form.
+    fn integrated_ppm(&self, t: f64) -> f64 {
+        let k = self.crystal.coefficient_ppm_per_c2;
+        let d = self.temperature.mean_c - CRYSTAL_TURNOVER_C;
+        let a = self.temperature.daily_swing_c / 2.0;
+        let omega = angular_rate();
+        let theta0 = self.temperature.start_phase();
+        let theta = theta0 + omega * t;
+
+        let constant = (self.crystal.offset_ppm - k * (d * d + a * a / 2.0)) * t;
+        let first = 2.0 * d * a * (theta.sin() - theta0.sin()) / omega;
+        let second = a * a / 2.0 * ((2.0 * theta).sin() - (2.0 * theta0).sin()) / (2.0 * omega);
+        constant - k * (first + second)
+    }
+}
+
+/// Angular rate of the daily cycle, in radians per second.
+fn angular_rate() -> f64 {
+    2.0 * std::f64::consts::PI / SECONDS_PER_DAY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drift(mean_c: f64, daily_swing_c: f64, offset_ppm: f64) -> CrystalDrift {
+        CrystalDrift {
+            temperature: TemperatureProfile {
+                mean_c,
+                daily_swing_c,
+                warmest_hour: 15.0,
+                start_hour: 6.0,
+            },
+            crystal: CrystalConfig {
+                offset_ppm,
+                coefficient_ppm_per_c2: TUNING_FORK_COEFFICIENT_PPM_PER_C2,
+            },
+        }
+    }
+
+    #[test]
+    fn test_temperature_profile() {
+        let profile = drift(10.0, 20.0, 0.0).temperature;
+        // Starts at 06:00; warmest at 15:00, coldest at 03:00 the next day
+        assert!((profile.temperature_c(SimTime::from_secs(9.0 * 3600.0)) - 20.0).abs() < 1e-9);
+        assert!((profile.temperature_c(SimTime::from_secs(21.0 * 3600.0)) - 0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_constant_drift() {
+        // At the turnover temperature only the offset remains
+        let fast = drift(CRYSTAL_TURNOVER_C, 0.0, 10.0);
+        assert_eq!(fast.local_time(SimTime::from_secs(1000.0)), SimTime::from_micros(1_000_010_000));
+        assert_eq!(fast.offset_hz(910_000_000, SimTime::ZERO), 9_100);
+        assert_eq!(fast.sim_delay(SimTime::from_micros(1_000_010), SimTime::ZERO), SimTime::from_secs(1.0));
+
+        // 20 °C below turnover a tuning-fork crystal is 13.6 ppm slow
+        let cold = drift(5.0, 0.0, 0.0);
+        assert!((cold.ppm_at(SimTime::ZERO) + 13.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_local_time_matches_numeric_integral() {
+        let drift = drift(15.0, 16.0, 2.0);
+        let step_s = 10.0;
+        let end = SimTime::from_secs(2.0 * SECONDS_PER_DAY);
+        let steps = (end.as_secs_f64() / step_s) as u64;
+        let error_s: f64 = (0..steps)
+            .map(|i| drift.ppm_at(SimTime::from_secs((i as f64 + 0.5) * step_s)) * 1e-6 * step_s)
+            .sum();
+        let expected = end.as_secs_f64() + error_s;
+        assert!((drift.local_time(end).as_secs_f64() - expected).abs() < 1e-5);
+    }
+}
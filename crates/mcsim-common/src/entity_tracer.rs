@@ -250,12 +250,31 @@ fn describe_event_payload(payload: &EventPayload) -> (String, Vec<(String, Strin
             ];
             ("ReceiveAir".to_string(), details)
         }
+        EventPayload::TransmitInterference(e) => {
+            let details = vec![
+                ("source_id".to_string(), format!("{:?}", e.source_id)),
+                ("kind".to_string(), format!("{:?}", e.kind)),
+                ("frequency_hz".to_string(), format!("{}", e.frequency_hz)),
+                ("end_time_us".to_string(), format!("{}", e.end_time.as_micros())),
+            ];
+            ("TransmitInterference".to_string(), details)
+        }
+        EventPayload::ReceiveInterference(e) => {
+            let details = vec![
+                ("source_id".to_string(), format!("{:?}", e.source_id)),
+                ("kind".to_string(), format!("{:?}", e.kind)),
+                ("mean_snr_db".to_string(), format!("{:.1}", e.mean_snr_db_at20dbm)),
+                ("end_time_us".to_string(), format!("{}", e.end_time.as_micros())),
+            ];
+            ("ReceiveInterference".to_string(), details)
+        }
         EventPayload::RadioRxPacket(e) => {
             let details = vec![
                 ("packet_len".to_string(), format!("{}", e.packet.payload.len())),
                 ("snr_db".to_string(), format!("{:.1}", e.snr_db)),
                 ("rssi_dbm".to_string(), format!("{:.1}", e.rssi_dbm)),
                 ("collided".to_string(), format!("{}", e.was_collided)),
+                ("corrupted".to_string(), format!("{}", e.was_corrupted)),
             ];
             ("RadioRxPacket".to_string(), details)
         }
@@ -266,6 +285,37 @@ fn describe_event_payload(payload: &EventPayload) -> (String, Vec<(String, Strin
             ];
             ("RadioStateChanged".to_string(), details)
         }
+        EventPayload::ChannelActivity(e) => {
+            let details = vec![
+                ("busy".to_string(), format!("{}", e.busy)),
+            ];
+            ("ChannelActivity".to_string(), details)
+        }
+        EventPayload::BatteryLevel(e) => {
+            let details = vec![
+                ("millivolts".to_string(), format!("{}", e.millivolts)),
+                ("depleted".to_string(), format!("{}", e.depleted)),
+            ];
+            ("BatteryLevel".to_string(), details)
+        }
+        EventPayload::PowerOff(e) => {
+            let details = vec![
+                ("reason".to_string(), e.reason.clone()),
+            ];
+            ("PowerOff".to_string(), details)
+        }
+        EventPayload::PowerOn(e) => {
+            let details = vec![
+                ("reason".to_string(), e.reason.clone()),
+            ];
+            ("PowerOn".to_string(), details)
+        }
+        EventPayload::Reboot(e) => {
+            let details = vec![
+                ("reason".to_string(), e.reason.clone()),
+            ];
+            ("Reboot".to_string(), details)
+        }
         EventPayload::RadioTxRequest(e) => {
             let details = vec![
                 ("packet_len".to_string(), format!("{}", e.packet.payload.len())),
@@ -310,6 +360,35 @@ fn describe_event_payload(payload: &EventPayload) -> (String, Vec<(String, Strin
             ];
             ("Timer".to_string(), details)
         }
+        EventPayload::MoveNode(e) => {
+            let details = vec![
+                ("radio_id".to_string(), format!("{}", e.radio_id.0)),
+                ("lat".to_string(), format!("{:.6}", e.position.latitude)),
+                ("lon".to_string(), format!("{:.6}", e.position.longitude)),
+            ];
+            ("MoveNode".to_string(), details)
+        }
+        EventPayload::AdvertSuppressed(e) => {
+            let details = vec![
+                ("policy".to_string(), e.policy.clone()),
+                ("airtime_us".to_string(), format!("{}", e.airtime.as_micros())),
+                ("utilization".to_string(), format!("{:.3}", e.utilization)),
+            ];
+            ("AdvertSuppressed".to_string(), details)
+        }
+        EventPayload::PositionFix(e) => {
+            let details = vec![
+                ("lat".to_string(), format!("{:.6}", e.position.latitude)),
+                ("lon".to_string(), format!("{:.6}", e.position.longitude)),
+            ];
+            ("PositionFix".to_string(), details)
+        }
+        EventPayload::ReloadScript(e) => {
+            let details = vec![
+                ("script_len".to_string(), format!("{}", e.script.len())),
+            ];
+            ("ReloadScript".to_string(), details)
+        }
         EventPayload::SimulationEnd => {
             ("SimulationEnd".to_string(), Vec::new())
         }
@@ -341,11 +420,13 @@ fn sanitize_for_display(s: &str) -> String {
             // Unicode replacement character (from invalid UTF-8)
             result.push_str("\\u{FFFD}");
         } else if !c.is_ascii() {
-            // Non-ASCII: show as unicode escape if not printable
-            if c.is_alphanumeric() || c.is_whitespace() {
-                result.push(c);
-            } else {
+            // Non-ASCII: names and messages in any script or with emoji are
+            // shown as is; control and invisible formatting characters (such
+            // as bidi overrides) are escaped
+            if c.is_control() || is_invisible_format(c) {
                 result.push_str(&format!("\\u{{{:04x}}}", c as u32));
+            } else {
+                result.push(c);
             }
         } else {
             result.push(c);
@@ -354,6 +435,13 @@ fn sanitize_for_display(s: &str) -> String {
     result
 }
 
+/// Whether a character is an invisible formatting character: zero-width
+/// spaces and joiners, bidi marks, embeddings, overrides and isolates, and
+/// the byte order mark.
+fn is_invisible_format(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{206F}' | '\u{FEFF}')
+}
+
 // ============================================================================
 // Tracer Configuration
 // ============================================================================
@@ -792,7 +880,7 @@ impl EntityTracer {
             if trimmed.len() <= 80 {
                 format!("\"{}\"", trimmed.replace('\n', "\\n").replace('\r', "\\r"))
             } else {
-                format!("\"{}...\" ({} bytes)", &trimmed[..40], data.len())
+                format!("\"{}...\" ({} bytes)", &trimmed[..trimmed.floor_char_boundary(40)], data.len())
             }
         } else {
             format!("{} bytes: {:02x?}", data.len(), &data[..data.len().min(16)])
@@ -913,4 +1001,12 @@ mod tests {
         assert!(tracer.should_trace(None, EntityId::new(42)));
         assert!(!tracer.should_trace(Some("Bob"), EntityId::new(1)));
     }
+
+    #[test]
+    fn test_sanitize_keeps_non_ascii_names() {
+        assert_eq!(sanitize_for_display("Café Ålesund 📡 東京"), "Café Ålesund 📡 東京");
+        assert_eq!(sanitize_for_display("abc\u{202E}def\u{0085}"), "abc\\u{202e}def\\u{0085}");
+        let config = EntityTracerConfig::from_spec("Café📡");
+        assert!(config.should_trace_name("Café📡"));
+    }
 }
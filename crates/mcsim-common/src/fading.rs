@@ -0,0 +1,125 @@
+//! Small-scale fading models.
+//!
+//! A link's mean SNR and its Gaussian standard deviation describe slow
+//! (shadowing) variation. On top of that, multipath makes the received power
+//! of each packet fluctuate. [`FadingModel`] draws that per-packet power gain:
+//!
+//! - **Rayleigh**: no dominant path, as on obstructed (NLOS) links.
+//! - **Rician**: a direct path of `K` times the power of the scattered
+//!   paths, as on line-of-sight links. `K → ∞` is no fading, `K = 0` is
+//!   Rayleigh.
+//!
+//! Gains are normalized to unit mean power, so fading leaves the link's mean
+//! received power unchanged and only widens the spread of per-packet SNR.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Lowest gain a fade can produce, in dB. Keeps a deep null finite.
+pub const MIN_FADING_GAIN_DB: f64 = -60.0;
+
+/// Per-packet fading applied to a link.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum FadingModel {
+    /// No small-scale fading.
+    #[default]
+    None,
+    /// Rayleigh fading (no line-of-sight component).
+    Rayleigh,
+    /// Rician fading with the given K-factor in dB.
+    Rician {
+        /// Ratio of line-of-sight to scattered power, in dB.
+        k_factor_db: f64,
+    },
+}
+
+impl FadingModel {
+    /// Draw the power gain of one packet, in dB.
+    ///
+    /// Draws nothing from `rng` for [`FadingModel::None`], so links without
+    /// fading keep their random sequence.
+    pub fn sample_gain_db<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        let k = match *self {
+            FadingModel::None => return 0.0,
+            FadingModel::Rayleigh => 0.0,
+            FadingModel::Rician { k_factor_db } => 10f64.powf(k_factor_db / 10.0),
+        };
+
+        // Channel gain h = sqrt(K/(K+1)) + CN(0, 1/(K+1)), so E[|h|²] = 1
+        let los = (k / (k + 1.0)).sqrt();
+        let scatter_std = (0.5 / (k + 1.0)).sqrt();
+        let (x, y) = standard_normal_pair(rng);
+        let power = (los + scatter_std * x).powi(2) + (scatter_std * y).powi(2);
+
+        if power > 0.0 {
+            (10.0 * power.log10()).max(MIN_FADING_GAIN_DB)
+        } else {
+            MIN_FADING_GAIN_DB
+        }
+    }
+}
+
+impl std::fmt::Display for FadingModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FadingModel::None => write!(f, "none"),
+            FadingModel::Rayleigh => write!(f, "rayleigh"),
+            FadingModel::Rician { k_factor_db } => write!(f, "rician (K={:.1} dB)", k_factor_db),
+        }
+    }
+}
+
+/// Two independent standard normal samples (Box-Muller).
+pub(crate) fn standard_normal_pair<R: Rng + ?Sized>(rng: &mut R) -> (f64, f64) {
+    let u1: f64 = rng.gen();
+    let u2: f64 = rng.gen();
+
+    // Avoid log(0)
+    let u1 = if u1 == 0.0 { f64::MIN_POSITIVE } else { u1 };
+
+    let r = (-2.0 * u1.ln()).sqrt();
+    let theta = 2.0 * std::f64::consts::PI * u2;
+    (r * theta.cos(), r * theta.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn gains(model: FadingModel) -> Vec<f64> {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        (0..20_000).map(|_| model.sample_gain_db(&mut rng)).collect()
+    }
+
+    fn mean_power(gains_db: &[f64]) -> f64 {
+        gains_db.iter().map(|g| 10f64.powf(g / 10.0)).sum::<f64>() / gains_db.len() as f64
+    }
+
+    #[test]
+    fn test_fading_unit_mean_power() {
+        assert!(gains(FadingModel::None).iter().all(|&g| g == 0.0));
+
+        for model in [
+            FadingModel::Rayleigh,
+            FadingModel::Rician { k_factor_db: 0.0 },
+            FadingModel::Rician { k_factor_db: 10.0 },
+        ] {
+            let mean = mean_power(&gains(model));
+            assert!((mean - 1.0).abs() < 0.05, "{}: mean power {}", model, mean);
+        }
+    }
+
+    #[test]
+    fn test_rician_fades_less_than_rayleigh() {
+        // Fraction of packets faded more than 10 dB: ~10% for Rayleigh,
+        // far fewer with a strong line-of-sight component
+        let deep = |model| gains(model).iter().filter(|&&g| g < -10.0).count() as f64 / 20_000.0;
+        let rayleigh = deep(FadingModel::Rayleigh);
+        let rician = deep(FadingModel::Rician { k_factor_db: 10.0 });
+        assert!((rayleigh - 0.095).abs() < 0.01, "rayleigh deep fades {}", rayleigh);
+        assert!(rician < 0.01, "rician deep fades {}", rician);
+    }
+}
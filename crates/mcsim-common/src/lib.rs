@@ -10,16 +10,26 @@
 //! - Simulation context ([`SimContext`])
 //! - Entity traits ([`Entity`])
 //! - Entity tracing ([`entity_tracer`])
-
+//! - LoRa time-on-air calculation ([`airtime`])
+//! - Per-packet fading ([`fading`])
+//! - Temperature-dependent crystal drift ([`crystal`])
+//! - Random number backends ([`rng`])
+//! - Barometric pressure sensors ([`barometer`])
+
+pub mod airtime;
+pub mod barometer;
+pub mod crystal;
 pub mod entity_tracer;
+pub mod fading;
+pub mod rng;
 
-use rand::SeedableRng;
-use rand_chacha::ChaCha8Rng;
+use rng::{RngBackend, SimRng};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
 
 // Re-export meshcore-packet types
+pub use fading::FadingModel;
 pub use meshcore_packet::{Destination, MeshCorePacket, PublicKeyHash};
 
 // ============================================================================
@@ -64,7 +74,7 @@ impl SimTime {
     pub const ZERO: SimTime = SimTime(0);
 
     /// Create from microseconds.
-    pub fn from_micros(us: u64) -> Self {
+    pub const fn from_micros(us: u64) -> Self {
         SimTime(us)
     }
 
@@ -125,7 +135,7 @@ impl std::ops::Sub for SimTime {
 // ============================================================================
 
 /// Geographic coordinate.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct GeoCoord {
     /// Latitude in degrees.
     pub latitude: f64,
@@ -367,6 +377,25 @@ pub struct RadioParams {
     pub tx_power_dbm: i8,
 }
 
+/// Reference TX power (dBm) at which link SNR and RSSI values are specified.
+///
+/// Edges carry `mean_snr_db_at20dbm`; the transmitter's actual power is applied
+/// as an offset from this reference when a packet is received.
+pub const REFERENCE_TX_POWER_DBM: i8 = 20;
+
+/// LoRa sync word MeshCore radios use (the "private network" word).
+///
+/// Receivers only decode packets sent with their own sync word; packets with
+/// another on the same channel are just interference.
+pub const DEFAULT_SYNC_WORD: u8 = 0x12;
+
+impl RadioParams {
+    /// Offset in dB of this radio's TX power from [`REFERENCE_TX_POWER_DBM`].
+    pub fn tx_power_offset_db(&self) -> f64 {
+        (self.tx_power_dbm as f64) - (REFERENCE_TX_POWER_DBM as f64)
+    }
+}
+
 /// Transmit air event - broadcast when a radio begins transmission.
 /// Directed to a Graph entity which routes to appropriate receivers.
 #[derive(Debug, Clone)]
@@ -377,6 +406,8 @@ pub struct TransmitAirEvent {
     pub packet: LoraPacket,
     /// Radio parameters for this transmission.
     pub params: RadioParams,
+    /// Sync word (network ID) the packet is sent with.
+    pub sync_word: u8,
     /// When transmission will end.
     pub end_time: SimTime,
 }
@@ -391,6 +422,8 @@ pub struct ReceiveAirEvent {
     pub packet: LoraPacket,
     /// Radio parameters for this transmission.
     pub params: RadioParams,
+    /// Sync word (network ID) the packet was sent with.
+    pub sync_word: u8,
     /// When transmission will end.
     pub end_time: SimTime,
     /// Mean signal-to-noise ratio in dB at 20 dBm TX power (from link model).
@@ -399,6 +432,72 @@ pub struct ReceiveAirEvent {
     pub snr_std_dev: f64,
     /// Received signal strength in dBm (from link model).
     pub rssi_dbm: f64,
+    /// Per-packet fading on the link.
+    pub fading: FadingModel,
+    /// Fault injected into this packet on its way to the receiver, if any.
+    pub fault: Option<InjectedFault>,
+}
+
+/// A fault injected into a packet on one link, to test how firmware copes
+/// with targeted loss.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectedFault {
+    /// The receiver never hears the packet.
+    Drop,
+    /// The packet arrives with a bit flipped and is reported as corrupted.
+    Corrupt,
+}
+
+/// Kind of signal an interference source emits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterferenceKind {
+    /// Wideband noise across the source's bandwidth.
+    Noise,
+    /// An unmodulated carrier.
+    ContinuousWave,
+}
+
+/// Transmit interference event - sent when an interference source starts a
+/// burst. Directed to a Graph entity which routes to nearby radios.
+#[derive(Debug, Clone)]
+pub struct TransmitInterferenceEvent {
+    /// Entity emitting the interference.
+    pub source_id: EntityId,
+    /// Kind of signal.
+    pub kind: InterferenceKind,
+    /// Center frequency in Hz.
+    pub frequency_hz: u32,
+    /// Occupied bandwidth in Hz (0 for a carrier).
+    pub bandwidth_hz: u32,
+    /// Transmit power in dBm.
+    pub tx_power_dbm: i8,
+    /// When the burst ends.
+    pub end_time: SimTime,
+}
+
+/// Receive interference event - sent from Graph entity to radios in range of
+/// an interference source. Contains link model parameters for sampling the
+/// interference level.
+#[derive(Debug, Clone)]
+pub struct ReceiveInterferenceEvent {
+    /// Entity emitting the interference.
+    pub source_id: EntityId,
+    /// Kind of signal.
+    pub kind: InterferenceKind,
+    /// Center frequency in Hz.
+    pub frequency_hz: u32,
+    /// Occupied bandwidth in Hz (0 for a carrier).
+    pub bandwidth_hz: u32,
+    /// Transmit power in dBm.
+    pub tx_power_dbm: i8,
+    /// When the burst ends.
+    pub end_time: SimTime,
+    /// Mean signal-to-noise ratio in dB at 20 dBm TX power (from link model).
+    pub mean_snr_db_at20dbm: f64,
+    /// Standard deviation of SNR in dB for Gaussian variation.
+    pub snr_std_dev: f64,
 }
 
 /// States visible to firmware via RadioStateChangedEvent.
@@ -426,6 +525,9 @@ pub struct RadioRxPacketEvent {
     pub was_collided: bool,
     /// Whether the packet was too weak (SNR below threshold).
     pub was_weak_signal: bool,
+    /// Whether the packet was delivered with bit errors (marginal SNR with the
+    /// bit-error channel enabled). The payload contains the corrupted bytes.
+    pub was_corrupted: bool,
     /// When the packet transmission started.
     pub start_time: SimTime,
     /// When the packet transmission ended (reception complete).
@@ -447,12 +549,65 @@ pub struct RadioStateChangedEvent {
     pub state_version: u32,
 }
 
+/// Channel activity sensed by a radio changed.
+/// Radio → Firmware event.
+///
+/// The channel is busy while the radio has at least one reception in progress
+/// strong enough to detect. Firmware uses this to answer channel activity
+/// detection (CAD) queries before transmitting.
+#[derive(Debug, Clone)]
+pub struct ChannelActivityEvent {
+    /// Whether a reception is in progress.
+    pub busy: bool,
+}
+
+/// The node's battery level changed.
+/// Radio → Firmware event, only sent for battery-powered nodes.
+#[derive(Debug, Clone)]
+pub struct BatteryLevelEvent {
+    /// Battery voltage the firmware should report.
+    pub millivolts: u16,
+    /// Whether the battery is exhausted and the node has powered off.
+    pub depleted: bool,
+}
+
+/// The node lost power, e.g. because its failure domain failed.
+/// Scenario → Radio → Firmware event.
+#[derive(Debug, Clone)]
+pub struct PowerOffEvent {
+    /// What cut the power, for reports.
+    pub reason: String,
+}
+
+/// Power came back after a [`PowerOffEvent`], e.g. at the end of an outage.
+/// Scenario → Radio → Firmware event.
+#[derive(Debug, Clone)]
+pub struct PowerOnEvent {
+    /// What restored the power, for reports.
+    pub reason: String,
+}
+
+/// The node restarts: in-flight radio activity is dropped and the firmware
+/// boots afresh from its configuration, losing its RAM state.
+/// Scenario → Radio → Firmware event, or Firmware → Radio when the
+/// firmware asks to restart.
+#[derive(Debug, Clone)]
+pub struct RebootEvent {
+    /// What caused the restart, for reports.
+    pub reason: String,
+}
+
 /// Firmware requests radio to transmit a packet.
 /// Firmware → Radio event.
 #[derive(Debug, Clone)]
 pub struct RadioTxRequestEvent {
     /// The packet to transmit.
     pub packet: LoraPacket,
+    /// Airtime the firmware estimated for the packet in milliseconds, if known.
+    pub reported_airtime_ms: Option<u32>,
+    /// Packets the firmware still has queued behind this one, for firmware
+    /// that keeps an inspectable queue.
+    pub queue_depth: Option<usize>,
 }
 
 /// Message send event data.
@@ -484,6 +639,44 @@ pub struct MessageAckedEvent {
     pub hop_count: u8,
 }
 
+/// Move node event data.
+#[derive(Debug, Clone)]
+pub struct MoveNodeEvent {
+    /// The radio to move.
+    pub radio_id: EntityId,
+    /// Its new position.
+    pub position: GeoCoord,
+}
+
+/// GPS position fix event data.
+#[derive(Debug, Clone)]
+pub struct PositionFixEvent {
+    /// The node's current position.
+    pub position: GeoCoord,
+}
+
+/// Suppressed advert event data.
+#[derive(Debug, Clone)]
+pub struct AdvertSuppressedEvent {
+    /// The radio that skipped its advert.
+    pub radio_id: EntityId,
+    /// Airtime the advert would have taken.
+    pub airtime: SimTime,
+    /// Name of the suppression policy.
+    pub policy: String,
+    /// Channel utilization the radio measured.
+    pub utilization: f64,
+    /// Packets the firmware had queued, if it reports them.
+    pub queue_depth: Option<usize>,
+}
+
+/// Reload script event data.
+#[derive(Debug, Clone)]
+pub struct ReloadScriptEvent {
+    /// The agent's new scripts, in the format the agent defines.
+    pub script: String,
+}
+
 /// Event payload variants.
 #[derive(Debug, Clone)]
 pub enum EventPayload {
@@ -492,16 +685,36 @@ pub enum EventPayload {
     TransmitAir(TransmitAirEvent),
     /// A packet is being received (from Graph entity to receiver).
     ReceiveAir(ReceiveAirEvent),
+    /// An interference source started a burst (directed to Graph entity).
+    TransmitInterference(TransmitInterferenceEvent),
+    /// Interference is reaching a radio (from Graph entity to receiver).
+    ReceiveInterference(ReceiveInterferenceEvent),
 
     // =========== Radio → Firmware Events ===========
     /// Radio has a packet for firmware.
     RadioRxPacket(RadioRxPacketEvent),
     /// Radio state changed (TX start, TX complete, RX ready, etc.).
     RadioStateChanged(RadioStateChangedEvent),
+    /// The radio started or stopped sensing activity on the channel.
+    ChannelActivity(ChannelActivityEvent),
+    /// The battery level changed, or the battery ran out.
+    BatteryLevel(BatteryLevelEvent),
+    /// The node lost power until a [`EventPayload::PowerOn`], if any (sent to
+    /// its radio, which passes it on to the firmware).
+    PowerOff(PowerOffEvent),
+    /// The node's power was restored (sent to its radio, which passes it on
+    /// to the firmware).
+    PowerOn(PowerOnEvent),
+    /// The node restarts (sent to its radio, which passes it on to the
+    /// firmware).
+    Reboot(RebootEvent),
 
     // =========== Firmware → Radio Events ===========
     /// Firmware requests transmission.
     RadioTxRequest(RadioTxRequestEvent),
+    /// A radio's suppression policy skipped an advert the firmware sent
+    /// (from the radio to itself, for observers).
+    AdvertSuppressed(AdvertSuppressedEvent),
 
     // =========== Serial/UART Events ===========
     /// Serial data received from external source (e.g., TCP client).
@@ -525,6 +738,13 @@ pub enum EventPayload {
     },
 
     // =========== Simulation Control ===========
+    /// Move a radio to a new position (directed to Graph entity).
+    MoveNode(MoveNodeEvent),
+    /// A node's current position, as its GPS would report it (from the Graph
+    /// entity to the node's GPS receivers).
+    PositionFix(PositionFixEvent),
+    /// Replace an agent's scripted behavior mid-run (directed to the agent).
+    ReloadScript(ReloadScriptEvent),
     /// End the simulation.
     SimulationEnd,
 }
@@ -552,7 +772,12 @@ use crate::entity_tracer::EntityTracer;
 /// Context passed to entities during event handling.
 pub struct SimContext {
     time: SimTime,
-    rng: ChaCha8Rng,
+    seed: u64,
+    rng_backend: RngBackend,
+    /// Generator shared by all entities (ChaCha backend).
+    shared_rng: SimRng,
+    /// Per-entity, per-substream generators (counter-based backend).
+    entity_rngs: HashMap<(EntityId, u32), SimRng>,
     pending_events: Vec<Event>,
     next_event_id: u64,
     source_entity: EntityId,
@@ -562,21 +787,17 @@ pub struct SimContext {
 impl SimContext {
     /// Create a new simulation context.
     pub fn new(seed: u64) -> Self {
-        SimContext {
-            time: SimTime::ZERO,
-            rng: ChaCha8Rng::seed_from_u64(seed),
-            pending_events: Vec::new(),
-            next_event_id: 0,
-            source_entity: EntityId(0),
-            tracer: EntityTracer::disabled(),
-        }
+        Self::with_tracer(seed, EntityTracer::disabled())
     }
 
     /// Create a new simulation context with a tracer.
     pub fn with_tracer(seed: u64, tracer: EntityTracer) -> Self {
         SimContext {
             time: SimTime::ZERO,
-            rng: ChaCha8Rng::seed_from_u64(seed),
+            seed,
+            rng_backend: RngBackend::default(),
+            shared_rng: SimRng::new(RngBackend::ChaCha, seed, 0, 0),
+            entity_rngs: HashMap::new(),
             pending_events: Vec::new(),
             next_event_id: 0,
             source_entity: EntityId(0),
@@ -584,14 +805,46 @@ impl SimContext {
         }
     }
 
+    /// Select the random number backend.
+    pub fn with_rng_backend(mut self, backend: RngBackend) -> Self {
+        self.rng_backend = backend;
+        self.entity_rngs.clear();
+        self
+    }
+
+    /// Get the random number backend in use.
+    pub fn rng_backend(&self) -> RngBackend {
+        self.rng_backend
+    }
+
     /// Get the current simulation time.
     pub fn time(&self) -> SimTime {
         self.time
     }
 
-    /// Get mutable access to the random number generator.
-    pub fn rng(&mut self) -> &mut ChaCha8Rng {
-        &mut self.rng
+    /// Get the random number generator for the entity handling the event.
+    pub fn rng(&mut self) -> &mut SimRng {
+        self.rng_substream(rng::substream::GENERAL)
+    }
+
+    /// Get a numbered substream of the current entity's generator (see
+    /// [`rng::substream`] for the fixed ones).
+    ///
+    /// With the counter-based backend each `(entity, substream)` pair draws an
+    /// independent sequence. The ChaCha backend has a single shared generator.
+    pub fn rng_substream(&mut self, substream: u32) -> &mut SimRng {
+        match self.rng_backend {
+            RngBackend::ChaCha => &mut self.shared_rng,
+            RngBackend::Philox => {
+                let (seed, backend, entity) = (self.seed, self.rng_backend, self.source_entity);
+                self.entity_rngs.entry((entity, substream)).or_insert_with(|| {
+                    // Entity IDs are the Philox counter's 32-bit stream word
+                    let stream = u32::try_from(entity.0)
+                        .unwrap_or_else(|_| panic!("entity {} is beyond the counter RNG's 32-bit streams", entity.0));
+                    SimRng::new(backend, seed, stream, substream)
+                })
+            }
+        }
     }
 
     /// Set the current time (used by event loop).
@@ -651,6 +904,42 @@ pub trait Entity: Send {
 
     /// Handle an event.
     fn handle_event(&mut self, event: &Event, ctx: &mut SimContext) -> Result<(), SimError>;
+
+    /// Snapshot of the packets the entity has queued for transmission, for
+    /// entities that keep an inspectable queue (repeater firmware).
+    fn outbound_queue(&self) -> Option<OutboundQueue> {
+        None
+    }
+
+    /// Snapshot of the current radio links, for entities that route
+    /// transmissions (the graph).
+    fn links(&self) -> Option<Vec<LinkQuality>> {
+        None
+    }
+}
+
+/// Snapshot of a node's outbound packet queue.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutboundQueue {
+    /// Raw packets in queue (insertion) order. The firmware sends the
+    /// highest-priority packet that is due, so this is not necessarily the
+    /// order they go on the air.
+    pub packets: Vec<Vec<u8>>,
+    /// Number of packets whose scheduled send time has passed.
+    pub due: usize,
+}
+
+/// Current quality of one directed radio link.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkQuality {
+    /// Transmitting radio.
+    pub from: EntityId,
+    /// Receiving radio.
+    pub to: EntityId,
+    /// Mean SNR in dB at 20 dBm transmit power.
+    pub mean_snr_db_at20dbm: f64,
+    /// Received signal strength in dBm.
+    pub rssi_dbm: f64,
 }
 
 // ============================================================================
@@ -750,4 +1039,37 @@ mod tests {
         // SF to LA is approximately 559 km
         assert!(distance > 550_000.0 && distance < 570_000.0);
     }
+
+    #[test]
+    fn test_tx_power_offset() {
+        let mut params = RadioParams {
+            frequency_hz: 910_525_000,
+            bandwidth_hz: 62_500,
+            spreading_factor: 7,
+            coding_rate: 5,
+            tx_power_dbm: REFERENCE_TX_POWER_DBM,
+        };
+        assert_eq!(params.tx_power_offset_db(), 0.0);
+        params.tx_power_dbm = 30;
+        assert_eq!(params.tx_power_offset_db(), 10.0);
+        params.tx_power_dbm = 14;
+        assert_eq!(params.tx_power_offset_db(), -6.0);
+    }
+
+    #[test]
+    fn test_rng_substreams_are_independent() {
+        use rand::Rng;
+
+        let mut ctx = SimContext::new(7).with_rng_backend(RngBackend::Philox);
+        ctx.set_source(EntityId(3));
+        let fading: f64 = ctx.rng_substream(rng::substream::FADING).gen();
+
+        // Extra SNR draws leave the fading sequence where it was
+        let mut other = SimContext::new(7).with_rng_backend(RngBackend::Philox);
+        other.set_source(EntityId(3));
+        for _ in 0..5 {
+            let _: f64 = other.rng_substream(rng::substream::SNR).gen();
+        }
+        assert_eq!(other.rng_substream(rng::substream::FADING).gen::<f64>(), fading);
+    }
 }
@@ -0,0 +1,381 @@
+//! Async client for companion firmware.
+//!
+//! [`CompanionClient`] owns a byte transport to a companion node: a TCP socket
+//! (such as the simulator's UART server), a serial port, or anything else that
+//! is `AsyncRead + AsyncWrite`. It encodes commands, reassembles device frames,
+//! matches each response to the command that caused it, and routes push
+//! notifications to [`PushStream`] subscribers.
+//!
+//! The firmware answers commands strictly in order and its frames carry no
+//! request IDs, so the client keeps one command in flight at a time: concurrent
+//! callers queue behind each other, and every response frame belongs to the
+//! command that is waiting. Replies that span several frames, like the contact
+//! list, are collected with [`CompanionClient::request_until`].
+//!
+//! ```rust,ignore
+//! use mcsim_companion_protocol::CompanionClient;
+//!
+//! let client = CompanionClient::connect_tcp("127.0.0.1:9000").await?;
+//! let mut pushes = client.subscribe();
+//! let info = client.app_start("mcsim-client").await?;
+//! for contact in client.get_contacts(None).await? {
+//!     println!("{}", contact.name);
+//! }
+//! while let Some(push) = pushes.recv().await {
+//!     println!("{:?}", push);
+//! }
+//! ```
+//!
+//! The client spawns its frame reader on the current tokio runtime, so it must
+//! be created from within one.
+
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::commands::Command;
+use crate::error::ProtocolError;
+use crate::frame::FrameCodec;
+use crate::responses::{Message, PushNotification, Response};
+use crate::types::{ContactInfo, DeviceInfo, SelfInfo};
+
+/// How long a command waits for its response by default.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Push notifications buffered per subscriber before the oldest are dropped.
+const PUSH_CHANNEL_CAPACITY: usize = 256;
+
+/// Errors from a [`CompanionClient`].
+#[derive(Error, Debug)]
+pub enum ClientError {
+    /// The transport failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A response could not be decoded, or the firmware reported an error.
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+
+    /// No response arrived in time.
+    #[error("no response within {0:?}")]
+    Timeout(Duration),
+
+    /// The transport closed.
+    #[error("connection closed")]
+    Closed,
+
+    /// The firmware answered with a response the command does not expect.
+    #[error("unexpected response: {0:?}")]
+    UnexpectedResponse(Box<Response>),
+}
+
+/// Write side of the transport plus the responses the reader has decoded.
+struct Link {
+    writer: Box<dyn AsyncWrite + Send + Unpin>,
+    responses: mpsc::UnboundedReceiver<Result<Response, ProtocolError>>,
+}
+
+impl Link {
+    async fn send(&mut self, command: &Command) -> Result<(), ClientError> {
+        // Responses that arrive after their command timed out would otherwise
+        // be taken as the answer to this one
+        while let Ok(stale) = self.responses.try_recv() {
+            log::debug!("Discarding late response: {:?}", stale);
+        }
+        self.writer.write_all(&FrameCodec::encode(&command.encode())).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    async fn next_response(&mut self, timeout: Duration) -> Result<Response, ClientError> {
+        match tokio::time::timeout(timeout, self.responses.recv()).await {
+            Err(_) => Err(ClientError::Timeout(timeout)),
+            Ok(None) => Err(ClientError::Closed),
+            Ok(Some(Err(e))) => Err(e.into()),
+            Ok(Some(Ok(Response::Error(code)))) => Err(ProtocolError::FirmwareError(code).into()),
+            Ok(Some(Ok(Response::Disabled))) => Err(ProtocolError::FeatureDisabled.into()),
+            Ok(Some(Ok(response))) => Ok(response),
+        }
+    }
+}
+
+/// Async client for a companion node.
+pub struct CompanionClient {
+    link: Mutex<Link>,
+    pushes: broadcast::Receiver<PushNotification>,
+    reader: JoinHandle<()>,
+    timeout: Duration,
+}
+
+impl CompanionClient {
+    /// Create a client over `transport` and start reading its frames.
+    pub fn new<T>(transport: T) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::split(transport);
+        let (response_tx, responses) = mpsc::unbounded_channel();
+        let (push_tx, pushes) = broadcast::channel(PUSH_CHANNEL_CAPACITY);
+        CompanionClient {
+            link: Mutex::new(Link {
+                writer: Box::new(writer),
+                responses,
+            }),
+            pushes,
+            reader: tokio::spawn(read_frames(reader, response_tx, push_tx)),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Connect to a companion node over TCP.
+    pub async fn connect_tcp<A: ToSocketAddrs>(addr: A) -> Result<Self, ClientError> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        Ok(Self::new(stream))
+    }
+
+    /// Set how long each response may take (default [`DEFAULT_REQUEST_TIMEOUT`]).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Subscribe to push notifications received from now on. The stream ends
+    /// when the connection closes.
+    pub fn subscribe(&self) -> PushStream {
+        PushStream {
+            receiver: self.pushes.resubscribe(),
+        }
+    }
+
+    /// Send `command` and wait for its response. Firmware errors and
+    /// `Disabled` replies are returned as [`ClientError::Protocol`].
+    pub async fn request(&self, command: &Command) -> Result<Response, ClientError> {
+        let mut link = self.link.lock().await;
+        link.send(command).await?;
+        link.next_response(self.timeout).await
+    }
+
+    /// Send `command` and collect response frames up to and including the
+    /// first for which `is_last` returns true.
+    pub async fn request_until(
+        &self,
+        command: &Command,
+        is_last: impl Fn(&Response) -> bool,
+    ) -> Result<Vec<Response>, ClientError> {
+        let mut link = self.link.lock().await;
+        link.send(command).await?;
+        let mut responses = Vec::new();
+        loop {
+            let response = link.next_response(self.timeout).await?;
+            let done = is_last(&response);
+            responses.push(response);
+            if done {
+                return Ok(responses);
+            }
+        }
+    }
+
+    /// Query the firmware version and capabilities.
+    pub async fn device_query(&self, app_version: u8) -> Result<DeviceInfo, ClientError> {
+        match self.request(&Command::DeviceQuery { app_version }).await? {
+            Response::DeviceInfo(info) => Ok(info),
+            other => Err(ClientError::UnexpectedResponse(Box::new(other))),
+        }
+    }
+
+    /// Start the app session and get the node's own info.
+    pub async fn app_start(&self, app_name: &str) -> Result<SelfInfo, ClientError> {
+        let command = Command::AppStart {
+            reserved: [0; 7],
+            app_name: app_name.to_string(),
+        };
+        match self.request(&command).await? {
+            Response::SelfInfo(info) => Ok(info),
+            other => Err(ClientError::UnexpectedResponse(Box::new(other))),
+        }
+    }
+
+    /// Fetch the contact list, optionally only contacts modified after `since`.
+    pub async fn get_contacts(&self, since: Option<u32>) -> Result<Vec<ContactInfo>, ClientError> {
+        let responses = self
+            .request_until(&Command::GetContacts { since }, |r| {
+                matches!(r, Response::EndOfContacts { .. })
+            })
+            .await?;
+        let mut contacts = Vec::new();
+        for response in responses {
+            match response {
+                Response::Contact(contact) => contacts.push(contact),
+                Response::ContactsStart { .. } | Response::EndOfContacts { .. } => {}
+                other => return Err(ClientError::UnexpectedResponse(Box::new(other))),
+            }
+        }
+        Ok(contacts)
+    }
+
+    /// Take the next message from the node's offline queue, or None once the
+    /// queue is empty.
+    pub async fn sync_next_message(&self) -> Result<Option<Response>, ClientError> {
+        match self.request(&Command::SyncNextMessage).await? {
+            Response::NoMoreMessages => Ok(None),
+            message => Ok(Some(message)),
+        }
+    }
+}
+
+impl Drop for CompanionClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Push notifications from a [`CompanionClient`].
+pub struct PushStream {
+    receiver: broadcast::Receiver<PushNotification>,
+}
+
+impl PushStream {
+    /// Wait for the next push notification, or None once the connection has
+    /// closed. A subscriber that falls behind skips the oldest notifications.
+    pub async fn recv(&mut self) -> Option<PushNotification> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(push) => return Some(push),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Push subscriber fell behind, skipped {} notifications", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Decode device frames from `reader` until the transport closes, passing
+/// responses to the waiting command and pushes to subscribers.
+async fn read_frames<R: AsyncRead + Unpin>(
+    mut reader: R,
+    responses: mpsc::UnboundedSender<Result<Response, ProtocolError>>,
+    pushes: broadcast::Sender<PushNotification>,
+) {
+    let mut codec = FrameCodec::new();
+    let mut buf = [0u8; 512];
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) => return,
+            Ok(n) => n,
+            Err(e) => {
+                log::debug!("Companion transport read failed: {}", e);
+                return;
+            }
+        };
+        codec.push(&buf[..n]);
+        while let Some(frame) = codec.decode() {
+            match Message::decode(&frame) {
+                Ok(Message::Push(push)) => {
+                    // No subscribers is fine
+                    let _ = pushes.send(push);
+                }
+                Ok(Message::Response(response)) => {
+                    if responses.send(Ok(response)).is_err() {
+                        return;
+                    }
+                }
+                // A response that fails to decode still answers the waiting command
+                Err(e) if frame.first().is_some_and(|code| code & 0x80 == 0) => {
+                    if responses.send(Err(e)).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => log::debug!("Dropping undecodable push frame: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use tokio::io::DuplexStream;
+
+    /// Read one host frame ('<' + length + data) from the fake device end.
+    async fn read_command(device: &mut DuplexStream) -> Vec<u8> {
+        let mut header = [0u8; 3];
+        device.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[0], b'<');
+        let mut data = vec![0u8; u16::from_le_bytes([header[1], header[2]]) as usize];
+        device.read_exact(&mut data).await.unwrap();
+        data
+    }
+
+    async fn write_frame(device: &mut DuplexStream, data: &[u8]) {
+        let mut frame = vec![b'>'];
+        frame.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        frame.extend_from_slice(data);
+        device.write_all(&frame).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_response_correlation_and_pushes() {
+        let (host, mut device) = tokio::io::duplex(1024);
+        let client = CompanionClient::new(host);
+        let mut pushes = client.subscribe();
+
+        let fake_device = tokio::spawn(async move {
+            assert_eq!(read_command(&mut device).await, vec![CMD_GET_DEVICE_TIME]);
+            // A push arriving mid-request goes to subscribers, not the caller
+            write_frame(&mut device, &[PUSH_CODE_MSG_WAITING]).await;
+            let mut time = vec![RESP_CODE_CURR_TIME];
+            time.extend_from_slice(&1_700_000_000u32.to_le_bytes());
+            write_frame(&mut device, &time).await;
+
+            assert_eq!(read_command(&mut device).await[0], CMD_GET_CONTACTS);
+            let mut start = vec![RESP_CODE_CONTACTS_START];
+            start.extend_from_slice(&0u32.to_le_bytes());
+            write_frame(&mut device, &start).await;
+            let mut end = vec![RESP_CODE_END_OF_CONTACTS];
+            end.extend_from_slice(&42u32.to_le_bytes());
+            write_frame(&mut device, &end).await;
+
+            read_command(&mut device).await;
+            write_frame(&mut device, &[RESP_CODE_ERR, ERR_CODE_NOT_FOUND]).await;
+        });
+
+        match client.request(&Command::GetDeviceTime).await.unwrap() {
+            Response::CurrentTime { time_secs } => assert_eq!(time_secs, 1_700_000_000),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(pushes.recv().await, Some(PushNotification::MessageWaiting)));
+        assert!(client.get_contacts(None).await.unwrap().is_empty());
+        assert!(matches!(
+            client.request(&Command::SyncNextMessage).await,
+            Err(ClientError::Protocol(ProtocolError::FirmwareError(
+                crate::FirmwareErrorCode::NotFound
+            )))
+        ));
+        fake_device.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_close() {
+        let (host, device) = tokio::io::duplex(1024);
+        let client = CompanionClient::new(host).with_timeout(Duration::from_millis(20));
+        let mut pushes = client.subscribe();
+
+        assert!(matches!(
+            client.request(&Command::GetDeviceTime).await,
+            Err(ClientError::Timeout(_))
+        ));
+        drop(device);
+        assert!(pushes.recv().await.is_none());
+        assert!(matches!(
+            client.request(&Command::GetDeviceTime).await,
+            Err(ClientError::Io(_) | ClientError::Closed)
+        ));
+    }
+}
@@ -1,6 +1,8 @@
 //! Commands that can be sent to the companion firmware.
 
 use crate::constants::*;
+use crate::contact::ExportedContact;
+use crate::error::ProtocolError;
 use crate::types::*;
 
 /// Commands that can be sent to the companion firmware.
@@ -320,6 +322,11 @@ pub enum Command {
 }
 
 impl Command {
+    /// Import command for a contact blob (see [`ExportedContact`]).
+    pub fn import_contact(contact: &ExportedContact) -> Result<Self, ProtocolError> {
+        Ok(Command::ImportContact { data: contact.encode()? })
+    }
+
     /// Get the command code for this command.
     pub fn code(&self) -> u8 {
         match self {
@@ -461,10 +468,10 @@ impl Command {
                 buf.push(contact.flags);
                 buf.push(contact.out_path_len as u8);
                 buf.extend_from_slice(&contact.out_path);
-                // Name is 32 bytes, null-padded
+                // Name is 32 bytes, null-padded, cut at a character boundary
                 let mut name_buf = [0u8; 32];
                 let name_bytes = contact.name.as_bytes();
-                let len = name_bytes.len().min(31);
+                let len = contact.name.floor_char_boundary(31);
                 name_buf[..len].copy_from_slice(&name_bytes[..len]);
                 buf.extend_from_slice(&name_buf);
                 buf.extend_from_slice(&contact.last_advert_timestamp.to_le_bytes());
@@ -606,10 +613,10 @@ impl Command {
             Command::SetChannel { channel } => {
                 buf.push(CMD_SET_CHANNEL);
                 buf.push(channel.index);
-                // Name is 32 bytes, null-padded
+                // Name is 32 bytes, null-padded, cut at a character boundary
                 let mut name_buf = [0u8; 32];
                 let name_bytes = channel.name.as_bytes();
-                let len = name_bytes.len().min(31);
+                let len = channel.name.floor_char_boundary(31);
                 name_buf[..len].copy_from_slice(&name_bytes[..len]);
                 buf.extend_from_slice(&name_buf);
                 buf.extend_from_slice(&channel.secret);
@@ -259,6 +259,10 @@ pub const SIGNATURE_SIZE: usize = 64;
 pub const MAX_PATH_SIZE: usize = 64;
 /// Maximum frame size.
 pub const MAX_FRAME_SIZE: usize = 256;
+/// Maximum size of an on-air packet, such as an exported contact.
+pub const MAX_PACKET_SIZE: usize = 255;
+/// Maximum size of advertisement app data (flags, location, features, name).
+pub const MAX_ADVERT_DATA_SIZE: usize = 32;
 /// Maximum sign data length.
 pub const MAX_SIGN_DATA_LEN: usize = 8 * 1024;
 /// Size of public key prefix used in messages.
@@ -0,0 +1,371 @@
+//! Contact blobs exchanged by `CMD_EXPORT_CONTACT` and `CMD_IMPORT_CONTACT`.
+//!
+//! An exported contact is the contact's advertisement packet in its on-air
+//! encoding:
+//!
+//! ```text
+//! header(1) [transport codes(4)] path_len(1) path(path_len)
+//! public_key(32) timestamp(4, LE) signature(64) app_data(≤32)
+//! ```
+//!
+//! where `app_data` is a flags byte (node type in the low nibble, presence
+//! bits in the high nibble) followed by the optional location, two reserved
+//! feature words and the name. The signature covers the public key, the
+//! timestamp and `app_data`, so a blob only imports if it is byte-exact.
+//!
+//! [`ExportedContact`] decodes and validates such blobs and builds signed ones
+//! from a private key, so tests and agents can share contacts between nodes
+//! without copying bytes from real devices.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+use crate::constants::*;
+use crate::error::ProtocolError;
+use crate::types::PublicKey;
+
+/// Route type bits of a flood packet.
+const ROUTE_TYPE_FLOOD: u8 = 0x01;
+/// Payload type of an advertisement.
+const PAYLOAD_TYPE_ADVERT: u8 = 0x04;
+
+const ADV_LATLON_MASK: u8 = 0x10;
+const ADV_FEAT1_MASK: u8 = 0x20;
+const ADV_FEAT2_MASK: u8 = 0x40;
+const ADV_NAME_MASK: u8 = 0x80;
+
+/// Application data of an advertisement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdvertAppData {
+    /// Node type (`ADV_TYPE_*`).
+    pub adv_type: u8,
+    /// Latitude and longitude in microdegrees.
+    pub location: Option<(i32, i32)>,
+    /// Reserved feature word 1.
+    pub feature1: Option<u16>,
+    /// Reserved feature word 2.
+    pub feature2: Option<u16>,
+    /// Node name.
+    pub name: Option<String>,
+}
+
+impl AdvertAppData {
+    /// App data with a node type and name.
+    pub fn new(adv_type: u8, name: &str) -> Self {
+        AdvertAppData {
+            adv_type,
+            name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Add a location in degrees.
+    pub fn with_location(mut self, latitude: f64, longitude: f64) -> Self {
+        self.location = Some(((latitude * 1_000_000.0) as i32, (longitude * 1_000_000.0) as i32));
+        self
+    }
+
+    /// Encode to bytes.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut flags = self.adv_type & 0x0F;
+        let mut buf = vec![0];
+        if let Some((lat, lon)) = self.location {
+            flags |= ADV_LATLON_MASK;
+            buf.extend_from_slice(&lat.to_le_bytes());
+            buf.extend_from_slice(&lon.to_le_bytes());
+        }
+        if let Some(feature) = self.feature1 {
+            flags |= ADV_FEAT1_MASK;
+            buf.extend_from_slice(&feature.to_le_bytes());
+        }
+        if let Some(feature) = self.feature2 {
+            flags |= ADV_FEAT2_MASK;
+            buf.extend_from_slice(&feature.to_le_bytes());
+        }
+        if let Some(ref name) = self.name {
+            flags |= ADV_NAME_MASK;
+            buf.extend_from_slice(name.as_bytes());
+        }
+        buf[0] = flags;
+        buf
+    }
+
+    /// Decode from bytes. Every field the flags announce must be present,
+    /// and nothing may follow them unless a name is announced.
+    pub fn decode(data: &[u8]) -> Result<Self, ProtocolError> {
+        let Some((&flags, mut rest)) = data.split_first() else {
+            return Err(ProtocolError::InvalidData("advert app data is empty".to_string()));
+        };
+        let mut take = |len: usize, field: &str| -> Result<&[u8], ProtocolError> {
+            if rest.len() < len {
+                return Err(ProtocolError::InvalidData(format!("advert app data is missing its {}", field)));
+            }
+            let (bytes, tail) = rest.split_at(len);
+            rest = tail;
+            Ok(bytes)
+        };
+
+        let location = if flags & ADV_LATLON_MASK != 0 {
+            let bytes = take(8, "location")?;
+            Some((
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+                i32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            ))
+        } else {
+            None
+        };
+        let feature1 = if flags & ADV_FEAT1_MASK != 0 {
+            let bytes = take(2, "feature 1")?;
+            Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+        } else {
+            None
+        };
+        let feature2 = if flags & ADV_FEAT2_MASK != 0 {
+            let bytes = take(2, "feature 2")?;
+            Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+        } else {
+            None
+        };
+        let name = if flags & ADV_NAME_MASK != 0 {
+            let name = std::str::from_utf8(rest)
+                .map_err(|_| ProtocolError::InvalidData("advert name is not valid UTF-8".to_string()))?;
+            Some(name.to_string())
+        } else if !rest.is_empty() {
+            return Err(ProtocolError::InvalidData(format!(
+                "{} unexpected byte(s) after advert app data",
+                rest.len()
+            )));
+        } else {
+            None
+        };
+
+        Ok(AdvertAppData {
+            adv_type: flags & 0x0F,
+            location,
+            feature1,
+            feature2,
+            name,
+        })
+    }
+
+    /// Location in degrees.
+    pub fn location_degrees(&self) -> Option<(f64, f64)> {
+        self.location
+            .map(|(lat, lon)| (lat as f64 / 1_000_000.0, lon as f64 / 1_000_000.0))
+    }
+}
+
+/// A contact as exported by `CMD_EXPORT_CONTACT`: a signed advertisement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedContact {
+    /// Path the advertisement arrived over (empty for a node's own advert).
+    pub path: Vec<u8>,
+    /// Contact's public key.
+    pub public_key: PublicKey,
+    /// Advertisement timestamp (Unix seconds).
+    pub timestamp: u32,
+    /// Ed25519 signature of the public key, timestamp and app data.
+    pub signature: [u8; SIGNATURE_SIZE],
+    /// Node type, location and name.
+    pub app_data: AdvertAppData,
+}
+
+impl ExportedContact {
+    /// Build the advertisement of the node with the Ed25519 `private_key`
+    /// (the 32-byte seed used in model key configuration), signed as the
+    /// firmware would.
+    pub fn sign(private_key: &[u8; 32], timestamp: u32, app_data: AdvertAppData) -> Result<Self, ProtocolError> {
+        let signing_key = SigningKey::from_bytes(private_key);
+        let mut contact = ExportedContact {
+            path: Vec::new(),
+            public_key: PublicKey(signing_key.verifying_key().to_bytes()),
+            timestamp,
+            signature: [0u8; SIGNATURE_SIZE],
+            app_data,
+        };
+        contact.check_app_data_size()?;
+        contact.signature = signing_key.sign(&contact.signed_message()).to_bytes();
+        Ok(contact)
+    }
+
+    /// The bytes covered by the signature.
+    pub fn signed_message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(PUB_KEY_SIZE + 4 + MAX_ADVERT_DATA_SIZE);
+        message.extend_from_slice(self.public_key.as_bytes());
+        message.extend_from_slice(&self.timestamp.to_le_bytes());
+        message.extend_from_slice(&self.app_data.encode());
+        message
+    }
+
+    /// Whether the signature is valid for the public key.
+    pub fn verify_signature(&self) -> bool {
+        let Ok(key) = VerifyingKey::from_bytes(self.public_key.as_bytes()) else {
+            return false;
+        };
+        key.verify(&self.signed_message(), &Signature::from_bytes(&self.signature))
+            .is_ok()
+    }
+
+    /// Encode to the blob accepted by `CMD_IMPORT_CONTACT`.
+    pub fn encode(&self) -> Result<Vec<u8>, ProtocolError> {
+        self.check_app_data_size()?;
+        if self.path.len() > MAX_PATH_SIZE {
+            return Err(ProtocolError::InvalidData(format!(
+                "contact path of {} bytes exceeds {}",
+                self.path.len(),
+                MAX_PATH_SIZE
+            )));
+        }
+        let app_data = self.app_data.encode();
+        let mut buf = Vec::with_capacity(2 + self.path.len() + PUB_KEY_SIZE + 4 + SIGNATURE_SIZE + app_data.len());
+        buf.push((PAYLOAD_TYPE_ADVERT << 2) | ROUTE_TYPE_FLOOD);
+        buf.push(self.path.len() as u8);
+        buf.extend_from_slice(&self.path);
+        buf.extend_from_slice(self.public_key.as_bytes());
+        buf.extend_from_slice(&self.timestamp.to_le_bytes());
+        buf.extend_from_slice(&self.signature);
+        buf.extend_from_slice(&app_data);
+        Ok(buf)
+    }
+
+    /// Decode and validate a blob from `RESP_CODE_EXPORT_CONTACT`. The
+    /// signature is not checked; see [`ExportedContact::verify_signature`].
+    pub fn decode(blob: &[u8]) -> Result<Self, ProtocolError> {
+        if blob.len() > MAX_PACKET_SIZE {
+            return Err(ProtocolError::FrameTooLong {
+                max: MAX_PACKET_SIZE,
+                actual: blob.len(),
+            });
+        }
+        let Some(&header) = blob.first() else {
+            return Err(ProtocolError::FrameTooShort { expected: 2, actual: 0 });
+        };
+        let payload_type = (header >> 2) & 0x0F;
+        if payload_type != PAYLOAD_TYPE_ADVERT {
+            return Err(ProtocolError::InvalidData(format!(
+                "contact blob has payload type {}, expected an advert ({})",
+                payload_type, PAYLOAD_TYPE_ADVERT
+            )));
+        }
+        if header >> 6 != 0 {
+            return Err(ProtocolError::InvalidData(format!(
+                "unsupported packet version {} in contact blob",
+                header >> 6
+            )));
+        }
+
+        // Transport-code route types carry 4 bytes of codes before the path
+        let mut offset = if matches!(header & 0x03, 0x00 | 0x03) { 5 } else { 1 };
+        let path_len = *blob.get(offset).ok_or(ProtocolError::FrameTooShort {
+            expected: offset + 1,
+            actual: blob.len(),
+        })? as usize;
+        offset += 1;
+        if path_len > MAX_PATH_SIZE {
+            return Err(ProtocolError::InvalidData(format!(
+                "contact path of {} bytes exceeds {}",
+                path_len, MAX_PATH_SIZE
+            )));
+        }
+        let min_len = offset + path_len + PUB_KEY_SIZE + 4 + SIGNATURE_SIZE + 1;
+        if blob.len() < min_len {
+            return Err(ProtocolError::FrameTooShort {
+                expected: min_len,
+                actual: blob.len(),
+            });
+        }
+        let path = blob[offset..offset + path_len].to_vec();
+        offset += path_len;
+
+        let public_key = PublicKey::from_slice(&blob[offset..offset + PUB_KEY_SIZE]).unwrap_or_default();
+        offset += PUB_KEY_SIZE;
+        let timestamp = u32::from_le_bytes([blob[offset], blob[offset + 1], blob[offset + 2], blob[offset + 3]]);
+        offset += 4;
+        let mut signature = [0u8; SIGNATURE_SIZE];
+        signature.copy_from_slice(&blob[offset..offset + SIGNATURE_SIZE]);
+        offset += SIGNATURE_SIZE;
+
+        let contact = ExportedContact {
+            path,
+            public_key,
+            timestamp,
+            signature,
+            app_data: AdvertAppData::decode(&blob[offset..])?,
+        };
+        contact.check_app_data_size()?;
+        Ok(contact)
+    }
+
+    /// Contact's name, if advertised.
+    pub fn name(&self) -> Option<&str> {
+        self.app_data.name.as_deref()
+    }
+
+    fn check_app_data_size(&self) -> Result<(), ProtocolError> {
+        let len = self.app_data.encode().len();
+        if len > MAX_ADVERT_DATA_SIZE {
+            return Err(ProtocolError::InvalidData(format!(
+                "advert app data of {} bytes exceeds {} (name too long?)",
+                len, MAX_ADVERT_DATA_SIZE
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, Response};
+
+    const SEED: [u8; 32] = [7u8; 32];
+
+    #[test]
+    fn test_contact_round_trip() {
+        let app_data = AdvertAppData::new(ADV_TYPE_CHAT, "Alice").with_location(47.6062, -122.3321);
+        let contact = ExportedContact::sign(&SEED, 1_700_000_000, app_data).unwrap();
+        assert!(contact.verify_signature());
+
+        // Import command carries the blob; export response decodes back
+        let blob = contact.encode().unwrap();
+        let Command::ImportContact { data } = Command::import_contact(&contact).unwrap() else {
+            panic!("expected an import command");
+        };
+        assert_eq!(data, blob);
+        let mut frame = vec![RESP_CODE_EXPORT_CONTACT];
+        frame.extend_from_slice(&blob);
+        let decoded = Response::decode(&frame).unwrap().exported_contact().unwrap().unwrap();
+        assert_eq!(decoded, contact);
+        assert_eq!(decoded.name(), Some("Alice"));
+        let (lat, lon) = decoded.app_data.location_degrees().unwrap();
+        assert!((lat - 47.6062).abs() < 1e-6 && (lon + 122.3321).abs() < 1e-6);
+
+        // A received advert keeps its path; tampering breaks the signature
+        let mut relayed = decoded.clone();
+        relayed.path = vec![0xAA, 0xBB];
+        assert_eq!(ExportedContact::decode(&relayed.encode().unwrap()).unwrap().path, vec![0xAA, 0xBB]);
+        relayed.app_data.name = Some("Mallory".to_string());
+        assert!(!relayed.verify_signature());
+    }
+
+    #[test]
+    fn test_contact_validation() {
+        let contact = ExportedContact::sign(&SEED, 1, AdvertAppData::new(ADV_TYPE_REPEATER, "R1")).unwrap();
+        let blob = contact.encode().unwrap();
+
+        // Truncated, wrong payload type, bad UTF-8, missing location
+        assert!(ExportedContact::decode(&blob[..100]).is_err());
+        let mut not_advert = blob.clone();
+        not_advert[0] = (0x02 << 2) | ROUTE_TYPE_FLOOD;
+        assert!(ExportedContact::decode(&not_advert).is_err());
+        let mut bad_name = blob.clone();
+        *bad_name.last_mut().unwrap() = 0xFF;
+        assert!(ExportedContact::decode(&bad_name).is_err());
+        let mut no_location = blob.clone();
+        no_location[2 + PUB_KEY_SIZE + 4 + SIGNATURE_SIZE] |= ADV_LATLON_MASK;
+        assert!(ExportedContact::decode(&no_location).is_err());
+
+        // Names that overflow the advert are rejected when building
+        let long = AdvertAppData::new(ADV_TYPE_CHAT, &"x".repeat(MAX_ADVERT_DATA_SIZE));
+        assert!(ExportedContact::sign(&SEED, 1, long).is_err());
+    }
+}
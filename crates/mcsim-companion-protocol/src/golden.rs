@@ -0,0 +1,758 @@
+//! Golden corpus of encoded protocol frames.
+//!
+//! [`corpus`] produces one representative frame for every command, response
+//! and push notification, including the layouts that changed between
+//! protocol versions (v2/v3 message frames, v7 login fields, v8 stats and
+//! control data). The corpus is rendered to a text file that is checked in;
+//! [`verify`] then compares the checked-in file against the current code:
+//!
+//! - Commands are re-encoded and must produce the stored bytes.
+//! - Responses and pushes are decoded from the stored bytes and must produce
+//!   the stored value.
+//!
+//! A mismatch means the wire format changed. If the change is intended, the
+//! file is regenerated with
+//! `MCSIM_UPDATE_GOLDEN=1 cargo test -p mcsim-companion-protocol --test golden`.
+//!
+//! File format: one block per frame, separated by blank lines.
+//!
+//! ```text
+//! command device_query v1
+//! hex: 1603
+//! value: DeviceQuery { app_version: 3 }
+//! ```
+
+use crate::constants::*;
+use crate::error::*;
+use crate::types::*;
+use crate::{Command, Message};
+
+/// Direction/category of a golden frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    /// Host → firmware command.
+    Command,
+    /// Firmware → host response.
+    Response,
+    /// Firmware → host push notification.
+    Push,
+}
+
+impl FrameKind {
+    /// Keyword used in the golden file.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrameKind::Command => "command",
+            FrameKind::Response => "response",
+            FrameKind::Push => "push",
+        }
+    }
+}
+
+impl std::str::FromStr for FrameKind {
+    type Err = ProtocolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "command" => Ok(FrameKind::Command),
+            "response" => Ok(FrameKind::Response),
+            "push" => Ok(FrameKind::Push),
+            other => Err(ProtocolError::InvalidData(format!("unknown frame kind: {}", other))),
+        }
+    }
+}
+
+/// One entry of the golden corpus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenFrame {
+    /// Frame direction.
+    pub kind: FrameKind,
+    /// Unique name within its kind (e.g. "contact_msg_v3").
+    pub name: String,
+    /// Lowest protocol version that uses this frame layout.
+    pub since_version: u8,
+    /// Encoded frame bytes.
+    pub bytes: Vec<u8>,
+    /// Debug rendering of the command or the decoded message.
+    pub value: String,
+}
+
+impl GoldenFrame {
+    fn command(name: &str, since_version: u8, command: Command) -> Self {
+        GoldenFrame {
+            kind: FrameKind::Command,
+            name: name.to_string(),
+            since_version,
+            bytes: command.encode(),
+            value: format!("{:?}", command),
+        }
+    }
+
+    fn incoming(name: &str, since_version: u8, bytes: Vec<u8>) -> Self {
+        let kind = if bytes.first().is_some_and(|code| code & 0x80 != 0) {
+            FrameKind::Push
+        } else {
+            FrameKind::Response
+        };
+        GoldenFrame {
+            kind,
+            name: name.to_string(),
+            since_version,
+            value: decode_value(&bytes),
+            bytes,
+        }
+    }
+}
+
+/// Decode a firmware frame into the string stored in the golden file.
+fn decode_value(bytes: &[u8]) -> String {
+    match Message::decode(bytes) {
+        Ok(message) => format!("{:?}", message),
+        Err(e) => format!("error: {}", e),
+    }
+}
+
+// ============================================================================
+// Corpus Generation
+// ============================================================================
+
+fn key(seed: u8) -> PublicKey {
+    PublicKey::new(std::array::from_fn(|i| seed.wrapping_add(i as u8)))
+}
+
+fn prefix(seed: u8) -> PublicKeyPrefix {
+    PublicKeyPrefix::new(std::array::from_fn(|i| seed.wrapping_add(i as u8)))
+}
+
+/// Null-padded fixed-width string field.
+fn padded(s: &str, len: usize) -> Vec<u8> {
+    let mut buf = s.as_bytes().to_vec();
+    buf.resize(len, 0);
+    buf
+}
+
+/// Concatenate a code byte and fields into a frame.
+fn frame(code: u8, fields: &[&[u8]]) -> Vec<u8> {
+    let mut buf = vec![code];
+    for field in fields {
+        buf.extend_from_slice(field);
+    }
+    buf
+}
+
+fn sample_contact() -> ContactInfo {
+    let mut out_path = [0u8; MAX_PATH_SIZE];
+    out_path[..2].copy_from_slice(&[0x3a, 0x7c]);
+    ContactInfo {
+        public_key: key(0x20),
+        contact_type: ADV_TYPE_CHAT,
+        flags: 0,
+        out_path_len: 2,
+        out_path,
+        name: "Alice".to_string(),
+        last_advert_timestamp: 1_700_000_000,
+        gps_lat: 47_606_200,
+        gps_lon: -122_332_100,
+        lastmod: 1_700_000_100,
+    }
+}
+
+/// Contact record as sent by the firmware (with optional GPS/lastmod fields).
+fn contact_record(contact: &ContactInfo, with_lastmod: bool) -> Vec<u8> {
+    let mut buf = contact.public_key.as_bytes().to_vec();
+    buf.push(contact.contact_type);
+    buf.push(contact.flags);
+    buf.push(contact.out_path_len as u8);
+    buf.extend_from_slice(&contact.out_path);
+    buf.extend_from_slice(&padded(&contact.name, 32));
+    buf.extend_from_slice(&contact.last_advert_timestamp.to_le_bytes());
+    buf.extend_from_slice(&contact.gps_lat.to_le_bytes());
+    buf.extend_from_slice(&contact.gps_lon.to_le_bytes());
+    if with_lastmod {
+        buf.extend_from_slice(&contact.lastmod.to_le_bytes());
+    }
+    buf
+}
+
+fn command_frames() -> Vec<GoldenFrame> {
+    let channel = ChannelInfo {
+        index: 1,
+        name: "Public".to_string(),
+        secret: std::array::from_fn(|i| 0xa0 + i as u8),
+    };
+    vec![
+        GoldenFrame::command("device_query", 1, Command::DeviceQuery { app_version: 3 }),
+        GoldenFrame::command(
+            "app_start",
+            1,
+            Command::AppStart { reserved: [0; 7], app_name: "mcsim".to_string() },
+        ),
+        GoldenFrame::command(
+            "send_txt_msg",
+            1,
+            Command::SendTextMessage {
+                text_type: TextType::Plain,
+                attempt: 0,
+                timestamp: 1_700_000_000,
+                recipient_prefix: prefix(0x20),
+                text: "hello".to_string(),
+            },
+        ),
+        GoldenFrame::command(
+            "send_txt_msg_cli",
+            1,
+            Command::SendTextMessage {
+                text_type: TextType::CliData,
+                attempt: 2,
+                timestamp: 1_700_000_001,
+                recipient_prefix: prefix(0x40),
+                text: "get name".to_string(),
+            },
+        ),
+        GoldenFrame::command(
+            "send_channel_txt_msg",
+            1,
+            Command::SendChannelTextMessage {
+                text_type: TextType::Plain,
+                channel_idx: 0,
+                timestamp: 1_700_000_000,
+                text: "hi all".to_string(),
+            },
+        ),
+        GoldenFrame::command("get_contacts", 1, Command::GetContacts { since: None }),
+        GoldenFrame::command(
+            "get_contacts_since",
+            1,
+            Command::GetContacts { since: Some(1_700_000_000) },
+        ),
+        GoldenFrame::command("get_device_time", 1, Command::GetDeviceTime),
+        GoldenFrame::command("set_device_time", 1, Command::SetDeviceTime { time_secs: 1_700_000_000 }),
+        GoldenFrame::command("send_self_advert_zero_hop", 1, Command::SendSelfAdvert { flood: false }),
+        GoldenFrame::command("send_self_advert_flood", 1, Command::SendSelfAdvert { flood: true }),
+        GoldenFrame::command("set_advert_name", 1, Command::SetAdvertName { name: "Node-1".to_string() }),
+        GoldenFrame::command(
+            "set_advert_latlon",
+            1,
+            Command::SetAdvertLatLon { lat: 47_606_200, lon: -122_332_100, alt: None },
+        ),
+        GoldenFrame::command(
+            "set_advert_latlon_alt",
+            1,
+            Command::SetAdvertLatLon { lat: 47_606_200, lon: -122_332_100, alt: Some(56) },
+        ),
+        GoldenFrame::command("add_update_contact", 1, Command::AddUpdateContact { contact: sample_contact() }),
+        GoldenFrame::command("remove_contact", 1, Command::RemoveContact { public_key: key(0x20) }),
+        GoldenFrame::command("reset_path", 1, Command::ResetPath { public_key: key(0x20) }),
+        GoldenFrame::command("get_contact_by_key", 1, Command::GetContactByKey { public_key: key(0x20) }),
+        GoldenFrame::command("share_contact", 1, Command::ShareContact { public_key: key(0x20) }),
+        GoldenFrame::command("export_self", 1, Command::ExportContact { public_key: None }),
+        GoldenFrame::command("export_contact", 1, Command::ExportContact { public_key: Some(key(0x20)) }),
+        GoldenFrame::command("import_contact", 1, Command::ImportContact { data: vec![0x11, 0x22, 0x33] }),
+        GoldenFrame::command("sync_next_message", 1, Command::SyncNextMessage),
+        GoldenFrame::command(
+            "set_radio_params",
+            1,
+            Command::SetRadioParams {
+                params: RadioParams {
+                    freq_khz: 910_525,
+                    bandwidth_hz: 62_500,
+                    spreading_factor: 7,
+                    coding_rate: 5,
+                },
+            },
+        ),
+        GoldenFrame::command("set_radio_tx_power", 1, Command::SetRadioTxPower { power_dbm: 20 }),
+        GoldenFrame::command(
+            "set_tuning_params",
+            1,
+            Command::SetTuningParams {
+                params: TuningParams { rx_delay_base: 0, airtime_factor: 1000 },
+            },
+        ),
+        GoldenFrame::command("get_tuning_params", 1, Command::GetTuningParams),
+        GoldenFrame::command(
+            "set_other_params_manual_add",
+            1,
+            Command::SetOtherParams {
+                manual_add_contacts: 1,
+                telemetry_modes: None,
+                advert_loc_policy: None,
+                multi_acks: None,
+            },
+        ),
+        GoldenFrame::command(
+            "set_other_params_full",
+            1,
+            Command::SetOtherParams {
+                manual_add_contacts: 0,
+                telemetry_modes: Some(TELEM_MODE_ALLOW_ALL),
+                advert_loc_policy: Some(ADVERT_LOC_INCLUDE),
+                multi_acks: Some(1),
+            },
+        ),
+        GoldenFrame::command("reboot", 1, Command::Reboot),
+        GoldenFrame::command("get_batt_and_storage", 1, Command::GetBatteryAndStorage),
+        GoldenFrame::command("export_private_key", 1, Command::ExportPrivateKey),
+        GoldenFrame::command(
+            "import_private_key",
+            1,
+            Command::ImportPrivateKey { identity: std::array::from_fn(|i| i as u8) },
+        ),
+        GoldenFrame::command(
+            "send_raw_data",
+            1,
+            Command::SendRawData { path: vec![0x3a, 0x7c], payload: vec![0xde, 0xad, 0xbe, 0xef] },
+        ),
+        GoldenFrame::command(
+            "send_login",
+            1,
+            Command::SendLogin { public_key: key(0x40), password: "hello".to_string() },
+        ),
+        GoldenFrame::command("send_status_req", 1, Command::SendStatusRequest { public_key: key(0x40) }),
+        GoldenFrame::command("has_connection", 1, Command::HasConnection { public_key: key(0x40) }),
+        GoldenFrame::command("logout", 1, Command::Logout { public_key: key(0x40) }),
+        GoldenFrame::command("get_channel", 1, Command::GetChannel { index: 1 }),
+        GoldenFrame::command("set_channel", 1, Command::SetChannel { channel }),
+        GoldenFrame::command("sign_start", 1, Command::SignStart),
+        GoldenFrame::command("sign_data", 1, Command::SignData { data: b"payload".to_vec() }),
+        GoldenFrame::command("sign_finish", 1, Command::SignFinish),
+        GoldenFrame::command(
+            "send_trace_path",
+            1,
+            Command::SendTracePath { tag: 0x1234_5678, auth: 0x9abc_def0, flags: 0, path: vec![0x3a, 0x7c] },
+        ),
+        GoldenFrame::command("set_device_pin", 1, Command::SetDevicePin { pin: 123_456 }),
+        GoldenFrame::command(
+            "send_telemetry_req_self",
+            1,
+            Command::SendTelemetryRequest { public_key: None, reserved: [0; 3] },
+        ),
+        GoldenFrame::command(
+            "send_telemetry_req",
+            1,
+            Command::SendTelemetryRequest { public_key: Some(key(0x40)), reserved: [0; 3] },
+        ),
+        GoldenFrame::command("get_custom_vars", 1, Command::GetCustomVars),
+        GoldenFrame::command(
+            "set_custom_var",
+            1,
+            Command::SetCustomVar { name: "gps".to_string(), value: "1".to_string() },
+        ),
+        GoldenFrame::command(
+            "get_advert_path",
+            1,
+            Command::GetAdvertPath { reserved: 0, public_key: key(0x20) },
+        ),
+        GoldenFrame::command(
+            "send_binary_req",
+            1,
+            Command::SendBinaryRequest { public_key: key(0x40), data: vec![0x01, 0x02] },
+        ),
+        GoldenFrame::command("factory_reset", 1, Command::FactoryReset),
+        GoldenFrame::command(
+            "send_path_discovery_req",
+            1,
+            Command::SendPathDiscoveryRequest { reserved: 0, public_key: key(0x20) },
+        ),
+        GoldenFrame::command("set_flood_scope_null", 8, Command::SetFloodScope { reserved: 0, key: None }),
+        GoldenFrame::command(
+            "set_flood_scope",
+            8,
+            Command::SetFloodScope { reserved: 0, key: Some(std::array::from_fn(|i| 0x50 + i as u8)) },
+        ),
+        GoldenFrame::command("send_control_data", 8, Command::SendControlData { data: vec![0x81, 0x00, 0x01] }),
+        GoldenFrame::command("get_stats_core", 8, Command::GetStats { stats_type: STATS_TYPE_CORE }),
+        GoldenFrame::command("get_stats_radio", 8, Command::GetStats { stats_type: STATS_TYPE_RADIO }),
+        GoldenFrame::command("get_stats_packets", 8, Command::GetStats { stats_type: STATS_TYPE_PACKETS }),
+    ]
+}
+
+fn response_frames() -> Vec<GoldenFrame> {
+    let contact = sample_contact();
+    let ts = 1_700_000_000u32.to_le_bytes();
+    let self_info = {
+        let mut buf = vec![ADV_TYPE_CHAT, 20, 22];
+        buf.extend_from_slice(key(0x01).as_bytes());
+        buf.extend_from_slice(&47_606_200i32.to_le_bytes());
+        buf.extend_from_slice(&(-122_332_100i32).to_le_bytes());
+        buf.extend_from_slice(&[1, ADVERT_LOC_NONE, TELEM_MODE_DISABLED, 0]);
+        buf.extend_from_slice(&910_525u32.to_le_bytes());
+        buf.extend_from_slice(&62_500u32.to_le_bytes());
+        buf.extend_from_slice(&[7, 5]);
+        buf.extend_from_slice(b"Node-1");
+        buf
+    };
+    let device_info = {
+        let mut buf = vec![8, 175, 40];
+        buf.extend_from_slice(&123_456u32.to_le_bytes());
+        buf.extend_from_slice(&padded("13 Jan 2025", 12));
+        buf.extend_from_slice(&padded("MCSim Virtual", 40));
+        buf.extend_from_slice(&padded("v1.11.0", 20));
+        buf
+    };
+
+    vec![
+        GoldenFrame::incoming("ok", 1, frame(RESP_CODE_OK, &[])),
+        GoldenFrame::incoming("err_not_found", 1, frame(RESP_CODE_ERR, &[&[ERR_CODE_NOT_FOUND]])),
+        GoldenFrame::incoming("err_unknown_code", 1, frame(RESP_CODE_ERR, &[&[0x7f]])),
+        GoldenFrame::incoming("disabled", 1, frame(RESP_CODE_DISABLED, &[])),
+        GoldenFrame::incoming("contacts_start", 1, frame(RESP_CODE_CONTACTS_START, &[&3u32.to_le_bytes()])),
+        GoldenFrame::incoming("contact", 1, frame(RESP_CODE_CONTACT, &[&contact_record(&contact, true)])),
+        GoldenFrame::incoming(
+            "contact_without_lastmod",
+            1,
+            frame(RESP_CODE_CONTACT, &[&contact_record(&contact, false)]),
+        ),
+        GoldenFrame::incoming("end_of_contacts", 1, frame(RESP_CODE_END_OF_CONTACTS, &[&ts])),
+        GoldenFrame::incoming("self_info", 1, frame(RESP_CODE_SELF_INFO, &[&self_info])),
+        GoldenFrame::incoming(
+            "sent",
+            1,
+            frame(RESP_CODE_SENT, &[&[1], &0xcafe_f00du32.to_le_bytes(), &8_000u32.to_le_bytes()]),
+        ),
+        GoldenFrame::incoming("curr_time", 1, frame(RESP_CODE_CURR_TIME, &[&ts])),
+        GoldenFrame::incoming("no_more_messages", 1, frame(RESP_CODE_NO_MORE_MESSAGES, &[])),
+        GoldenFrame::incoming("export_contact", 1, frame(RESP_CODE_EXPORT_CONTACT, &[&[0x11, 0x22, 0x33]])),
+        GoldenFrame::incoming(
+            "batt_and_storage",
+            1,
+            frame(
+                RESP_CODE_BATT_AND_STORAGE,
+                &[&4_100u16.to_le_bytes(), &128u32.to_le_bytes(), &1_024u32.to_le_bytes()],
+            ),
+        ),
+        GoldenFrame::incoming("device_info", 1, frame(RESP_CODE_DEVICE_INFO, &[&device_info])),
+        GoldenFrame::incoming(
+            "private_key",
+            1,
+            frame(RESP_CODE_PRIVATE_KEY, &[&(0..64).collect::<Vec<u8>>()]),
+        ),
+        GoldenFrame::incoming(
+            "contact_msg_v2",
+            2,
+            frame(RESP_CODE_CONTACT_MSG_RECV, &[prefix(0x20).as_bytes(), &[0xff, TXT_TYPE_PLAIN], &ts, b"hello"]),
+        ),
+        GoldenFrame::incoming(
+            "contact_msg_v2_signed",
+            2,
+            frame(
+                RESP_CODE_CONTACT_MSG_RECV,
+                &[prefix(0x40).as_bytes(), &[1, TXT_TYPE_SIGNED_PLAIN], &ts, &[0xaa, 0xbb, 0xcc, 0xdd], b"post"],
+            ),
+        ),
+        GoldenFrame::incoming(
+            "contact_msg_v3",
+            3,
+            frame(
+                RESP_CODE_CONTACT_MSG_RECV_V3,
+                &[&[(-22i8) as u8, 0, 0], prefix(0x20).as_bytes(), &[2, TXT_TYPE_PLAIN], &ts, b"hello"],
+            ),
+        ),
+        GoldenFrame::incoming(
+            "contact_msg_v3_signed",
+            3,
+            frame(
+                RESP_CODE_CONTACT_MSG_RECV_V3,
+                &[&[40, 0, 0], prefix(0x40).as_bytes(), &[1, TXT_TYPE_SIGNED_PLAIN], &ts, &[0xaa, 0xbb, 0xcc, 0xdd], b"post"],
+            ),
+        ),
+        GoldenFrame::incoming(
+            "channel_msg_v2",
+            2,
+            frame(RESP_CODE_CHANNEL_MSG_RECV, &[&[0, 0xff, TXT_TYPE_PLAIN], &ts, b"Bob: hi all"]),
+        ),
+        GoldenFrame::incoming(
+            "channel_msg_v3",
+            3,
+            frame(RESP_CODE_CHANNEL_MSG_RECV_V3, &[&[12, 0, 0], &[0, 3, TXT_TYPE_PLAIN], &ts, b"Bob: hi all"]),
+        ),
+        GoldenFrame::incoming(
+            "channel_info",
+            1,
+            frame(RESP_CODE_CHANNEL_INFO, &[&[1], &padded("Public", 32), &[0xa5; 16]]),
+        ),
+        GoldenFrame::incoming("sign_start", 1, frame(RESP_CODE_SIGN_START, &[&[0], &8_192u32.to_le_bytes()])),
+        GoldenFrame::incoming("signature", 1, frame(RESP_CODE_SIGNATURE, &[&[0x5a; SIGNATURE_SIZE]])),
+        GoldenFrame::incoming("custom_vars", 1, frame(RESP_CODE_CUSTOM_VARS, &[b"gps:1,gps_interval:60"])),
+        GoldenFrame::incoming("advert_path", 1, frame(RESP_CODE_ADVERT_PATH, &[&ts, &[2, 0x3a, 0x7c]])),
+        GoldenFrame::incoming(
+            "tuning_params",
+            1,
+            frame(RESP_CODE_TUNING_PARAMS, &[&0u32.to_le_bytes(), &1_000u32.to_le_bytes()]),
+        ),
+        GoldenFrame::incoming(
+            "stats_core",
+            8,
+            frame(
+                RESP_CODE_STATS,
+                &[&[STATS_TYPE_CORE], &4_100u16.to_le_bytes(), &3_600u32.to_le_bytes(), &0u16.to_le_bytes(), &[2]],
+            ),
+        ),
+        GoldenFrame::incoming(
+            "stats_radio",
+            8,
+            frame(
+                RESP_CODE_STATS,
+                &[
+                    &[STATS_TYPE_RADIO],
+                    &(-118i16).to_le_bytes(),
+                    &[(-95i8) as u8, 26],
+                    &42u32.to_le_bytes(),
+                    &310u32.to_le_bytes(),
+                ],
+            ),
+        ),
+        GoldenFrame::incoming(
+            "stats_packets",
+            8,
+            frame(
+                RESP_CODE_STATS,
+                &[
+                    &[STATS_TYPE_PACKETS],
+                    &120u32.to_le_bytes(),
+                    &40u32.to_le_bytes(),
+                    &30u32.to_le_bytes(),
+                    &10u32.to_le_bytes(),
+                    &100u32.to_le_bytes(),
+                    &20u32.to_le_bytes(),
+                ],
+            ),
+        ),
+        GoldenFrame::incoming("stats_unknown_type", 8, frame(RESP_CODE_STATS, &[&[9]])),
+        GoldenFrame::incoming("sent_truncated", 1, frame(RESP_CODE_SENT, &[&[1, 2, 3]])),
+    ]
+}
+
+fn push_frames() -> Vec<GoldenFrame> {
+    let contact = sample_contact();
+    vec![
+        GoldenFrame::incoming("advert", 1, frame(PUSH_CODE_ADVERT, &[key(0x20).as_bytes()])),
+        GoldenFrame::incoming("new_advert", 1, frame(PUSH_CODE_NEW_ADVERT, &[&contact_record(&contact, true)])),
+        GoldenFrame::incoming("path_updated", 1, frame(PUSH_CODE_PATH_UPDATED, &[key(0x20).as_bytes()])),
+        GoldenFrame::incoming(
+            "send_confirmed",
+            1,
+            frame(PUSH_CODE_SEND_CONFIRMED, &[&0xcafe_f00du32.to_le_bytes(), &1_250u32.to_le_bytes()]),
+        ),
+        GoldenFrame::incoming("msg_waiting", 1, frame(PUSH_CODE_MSG_WAITING, &[])),
+        GoldenFrame::incoming(
+            "raw_data",
+            1,
+            frame(PUSH_CODE_RAW_DATA, &[&[28, (-90i8) as u8, 0xff], &[0xde, 0xad]]),
+        ),
+        GoldenFrame::incoming(
+            "login_success",
+            1,
+            frame(PUSH_CODE_LOGIN_SUCCESS, &[&[1], prefix(0x40).as_bytes()]),
+        ),
+        GoldenFrame::incoming(
+            "login_success_v7",
+            7,
+            frame(
+                PUSH_CODE_LOGIN_SUCCESS,
+                &[&[0], prefix(0x40).as_bytes(), &1_700_000_000u32.to_le_bytes(), &[0x03, 1]],
+            ),
+        ),
+        GoldenFrame::incoming("login_fail", 1, frame(PUSH_CODE_LOGIN_FAIL, &[&[0], prefix(0x40).as_bytes()])),
+        GoldenFrame::incoming(
+            "status_response",
+            1,
+            frame(PUSH_CODE_STATUS_RESPONSE, &[&[0], prefix(0x40).as_bytes(), &[0x01, 0x02, 0x03]]),
+        ),
+        GoldenFrame::incoming(
+            "log_rx_data",
+            1,
+            frame(PUSH_CODE_LOG_RX_DATA, &[&[(-8i8) as u8, (-110i8) as u8], &[0x11, 0x00, 0x42]]),
+        ),
+        GoldenFrame::incoming(
+            "trace_data",
+            1,
+            frame(
+                PUSH_CODE_TRACE_DATA,
+                &[
+                    &[0, 2, 0],
+                    &0x1234_5678u32.to_le_bytes(),
+                    &0x9abc_def0u32.to_le_bytes(),
+                    &[0x3a, 0x7c],
+                    &[36, 20],
+                    &[24],
+                ],
+            ),
+        ),
+        GoldenFrame::incoming(
+            "telemetry_response",
+            1,
+            frame(PUSH_CODE_TELEMETRY_RESPONSE, &[&[0], prefix(0x40).as_bytes(), &[0x01, 0x74, 0x01, 0x9a]]),
+        ),
+        GoldenFrame::incoming(
+            "binary_response",
+            1,
+            frame(PUSH_CODE_BINARY_RESPONSE, &[&[0], &0x0102_0304u32.to_le_bytes(), &[0xaa, 0xbb]]),
+        ),
+        GoldenFrame::incoming(
+            "path_discovery_response",
+            1,
+            frame(
+                PUSH_CODE_PATH_DISCOVERY_RESPONSE,
+                &[&[0], prefix(0x20).as_bytes(), &[2, 0x3a, 0x7c], &[1, 0x55]],
+            ),
+        ),
+        GoldenFrame::incoming(
+            "control_data",
+            8,
+            frame(PUSH_CODE_CONTROL_DATA, &[&[16, (-100i8) as u8, 0], &[0x81, 0x00, 0x01]]),
+        ),
+    ]
+}
+
+/// Generate the full corpus from the current protocol code.
+pub fn corpus() -> Vec<GoldenFrame> {
+    let mut frames = command_frames();
+    frames.extend(response_frames());
+    frames.extend(push_frames());
+    frames
+}
+
+// ============================================================================
+// Golden File Format
+// ============================================================================
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>, ProtocolError> {
+    if !s.len().is_multiple_of(2) {
+        return Err(ProtocolError::InvalidData(format!("odd-length hex: {}", s)));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&s[i..i + 2], 16)
+                .map_err(|_| ProtocolError::InvalidData(format!("invalid hex: {}", s)))
+        })
+        .collect()
+}
+
+/// Render frames in the golden file format.
+pub fn render(frames: &[GoldenFrame]) -> String {
+    let mut out = String::from(
+        "# MeshCore companion protocol golden corpus.\n\
+         # Regenerate with: MCSIM_UPDATE_GOLDEN=1 cargo test -p mcsim-companion-protocol --test golden\n",
+    );
+    for frame in frames {
+        out.push_str(&format!(
+            "\n{} {} v{}\nhex: {}\nvalue: {}\n",
+            frame.kind.as_str(),
+            frame.name,
+            frame.since_version,
+            to_hex(&frame.bytes),
+            frame.value
+        ));
+    }
+    out
+}
+
+/// Parse a golden file.
+pub fn parse(text: &str) -> Result<Vec<GoldenFrame>, ProtocolError> {
+    let invalid = |line: &str| ProtocolError::InvalidData(format!("malformed golden entry: {}", line));
+    let mut frames = Vec::new();
+    let mut lines = text.lines().filter(|line| !line.is_empty() && !line.starts_with('#'));
+    while let Some(header) = lines.next() {
+        let mut parts = header.split(' ');
+        let (Some(kind), Some(name), Some(version), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid(header));
+        };
+        let since_version = version
+            .strip_prefix('v')
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| invalid(header))?;
+        let hex = lines.next().and_then(|l| l.strip_prefix("hex: ")).ok_or_else(|| invalid(header))?;
+        let value = lines.next().and_then(|l| l.strip_prefix("value: ")).ok_or_else(|| invalid(header))?;
+        frames.push(GoldenFrame {
+            kind: kind.parse()?,
+            name: name.to_string(),
+            since_version,
+            bytes: from_hex(hex)?,
+            value: value.to_string(),
+        });
+    }
+    Ok(frames)
+}
+
+/// Check a golden file against the current code.
+///
+/// Returns a description of every difference; an empty list means the wire
+/// format is unchanged.
+pub fn verify(text: &str) -> Vec<String> {
+    let golden = match parse(text) {
+        Ok(golden) => golden,
+        Err(e) => return vec![e.to_string()],
+    };
+    let current = corpus();
+    let find = |frames: &[GoldenFrame], frame: &GoldenFrame| {
+        frames.iter().position(|f| f.kind == frame.kind && f.name == frame.name)
+    };
+
+    let mut problems = Vec::new();
+    for frame in &golden {
+        let label = format!("{} '{}'", frame.kind.as_str(), frame.name);
+        match frame.kind {
+            FrameKind::Command => match find(&current, frame) {
+                Some(i) if current[i].bytes != frame.bytes => problems.push(format!(
+                    "{} encodes as {} (golden {})",
+                    label,
+                    to_hex(&current[i].bytes),
+                    to_hex(&frame.bytes)
+                )),
+                Some(_) => {}
+                None => problems.push(format!("{} is no longer generated", label)),
+            },
+            FrameKind::Response | FrameKind::Push => {
+                let value = decode_value(&frame.bytes);
+                if value != frame.value {
+                    problems.push(format!("{} decodes as {} (golden {})", label, value, frame.value));
+                }
+            }
+        }
+    }
+    for frame in &current {
+        if find(&golden, frame).is_none() {
+            problems.push(format!(
+                "{} '{}' is missing from the golden corpus",
+                frame.kind.as_str(),
+                frame.name
+            ));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_parse_round_trip() {
+        let frames = corpus();
+        assert_eq!(parse(&render(&frames)).unwrap(), frames);
+        assert!(verify(&render(&frames)).is_empty());
+    }
+
+    #[test]
+    fn test_verify_reports_changes() {
+        let mut frames = corpus();
+        frames[0].bytes.push(0);
+        let incoming = frames.iter().position(|f| f.kind == FrameKind::Response).unwrap();
+        frames[incoming].value = "Ok".to_string();
+        frames.pop();
+
+        let problems = verify(&render(&frames));
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert!(problems[0].starts_with("command 'device_query' encodes as"));
+        assert!(problems[1].starts_with("response 'ok' decodes as"));
+        assert!(problems[2].ends_with("is missing from the golden corpus"));
+    }
+}
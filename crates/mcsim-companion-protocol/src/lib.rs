@@ -25,16 +25,34 @@
 //! // Parse a response
 //! let response = Response::decode(&received_data)?;
 //! ```
+//!
+//! # Async Client
+//!
+//! With the default `client` feature, [`CompanionClient`] handles framing,
+//! response matching and push notifications over a TCP or serial transport.
+//!
+//! # Wire-Format Stability
+//!
+//! The [`golden`] module generates a corpus of every command, response and
+//! push frame. The checked-in copy under `tests/golden/` is verified by the
+//! `golden` integration test, so an accidental wire-format change fails CI.
 
+#[cfg(feature = "client")]
+mod client;
 mod commands;
 mod constants;
+mod contact;
 mod error;
 mod frame;
+pub mod golden;
 mod responses;
 mod types;
 
+#[cfg(feature = "client")]
+pub use client::*;
 pub use commands::*;
 pub use constants::*;
+pub use contact::*;
 pub use error::*;
 pub use frame::*;
 pub use responses::*;
@@ -1,6 +1,7 @@
 //! Responses from the companion firmware.
 
 use crate::constants::*;
+use crate::contact::ExportedContact;
 use crate::error::*;
 use crate::types::*;
 
@@ -296,6 +297,15 @@ impl Message {
 }
 
 impl Response {
+    /// Decode the contact of an [`Response::ExportedContact`], or None for
+    /// other responses.
+    pub fn exported_contact(&self) -> Option<Result<ExportedContact, ProtocolError>> {
+        match self {
+            Response::ExportedContact { data } => Some(ExportedContact::decode(data)),
+            _ => None,
+        }
+    }
+
     /// Decode a response from a frame.
     pub fn decode(frame: &[u8]) -> Result<Self, ProtocolError> {
         if frame.is_empty() {
@@ -890,6 +900,27 @@ impl PushNotification {
 // Helper decode functions
 // ============================================================================
 
+/// Decode a null-terminated UTF-8 name from a fixed-size field.
+///
+/// The firmware copies names into these fields byte-wise, so a name that
+/// didn't fit can end part-way through a multi-byte character; that partial
+/// character is dropped. Other invalid bytes become U+FFFD.
+fn decode_name(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    let mut name = String::with_capacity(end);
+    let mut chunks = field[..end].utf8_chunks().peekable();
+    while let Some(chunk) = chunks.next() {
+        name.push_str(chunk.valid());
+        let invalid = chunk.invalid();
+        let cut_short = chunks.peek().is_none()
+            && std::str::from_utf8(invalid).is_err_and(|e| e.error_len().is_none());
+        if !invalid.is_empty() && !cut_short {
+            name.push(char::REPLACEMENT_CHARACTER);
+        }
+    }
+    name
+}
+
 fn decode_contact(data: &[u8]) -> Result<ContactInfo, ProtocolError> {
     // Minimum size: 32 (pubkey) + 1 (type) + 1 (flags) + 1 (path_len) + 16 (path) + 32 (name) + 4 (timestamp) = 87
     if data.len() < 87 {
@@ -920,9 +951,7 @@ fn decode_contact(data: &[u8]) -> Result<ContactInfo, ProtocolError> {
     i += MAX_PATH_SIZE;
 
     // Name (32 bytes, null-terminated)
-    let name_bytes = &data[i..i + 32];
-    let name_end = name_bytes.iter().position(|&b| b == 0).unwrap_or(32);
-    contact.name = String::from_utf8_lossy(&name_bytes[..name_end]).to_string();
+    contact.name = decode_name(&data[i..i + 32]);
     i += 32;
 
     // Last advert timestamp
@@ -986,9 +1015,7 @@ fn decode_self_info(data: &[u8]) -> Result<SelfInfo, ProtocolError> {
     i += 1;
 
     // Node name is the rest
-    let name_bytes = &data[i..];
-    let name_end = name_bytes.iter().position(|&b| b == 0).unwrap_or(name_bytes.len());
-    info.node_name = String::from_utf8_lossy(&name_bytes[..name_end]).to_string();
+    info.node_name = decode_name(&data[i..]);
 
     Ok(info)
 }
@@ -1048,9 +1075,7 @@ fn decode_channel_info(data: &[u8]) -> Result<ChannelInfo, ProtocolError> {
     info.index = data[0];
 
     // Name (32 bytes)
-    let name = &data[1..33];
-    let end = name.iter().position(|&b| b == 0).unwrap_or(32);
-    info.name = String::from_utf8_lossy(&name[..end]).to_string();
+    info.name = decode_name(&data[1..33]);
 
     // Secret (16 bytes)
     info.secret.copy_from_slice(&data[33..49]);
@@ -112,7 +112,7 @@ pub struct ContactInfo {
     pub out_path_len: i8,
     /// Outbound path data.
     pub out_path: [u8; MAX_PATH_SIZE],
-    /// Contact name (up to 31 chars + null).
+    /// Contact name (up to 31 bytes of UTF-8 + null).
     pub name: String,
     /// Timestamp of last advertisement.
     pub last_advert_timestamp: u32,
@@ -163,7 +163,7 @@ impl ContactInfo {
 pub struct ChannelInfo {
     /// Channel index (0-based).
     pub index: u8,
-    /// Channel name (up to 31 chars).
+    /// Channel name (up to 31 bytes of UTF-8).
     pub name: String,
     /// Channel secret key (16 bytes for 128-bit).
     pub secret: [u8; 16],
@@ -0,0 +1,25 @@
+//! Verifies the checked-in golden frame corpus against the current encoders
+//! and decoders.
+//!
+//! Set `MCSIM_UPDATE_GOLDEN=1` to regenerate the corpus after an intended
+//! protocol change.
+
+use mcsim_companion_protocol::golden;
+
+const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/companion_frames.txt");
+
+#[test]
+fn test_golden_corpus() {
+    if std::env::var_os("MCSIM_UPDATE_GOLDEN").is_some() {
+        std::fs::write(GOLDEN_PATH, golden::render(&golden::corpus())).unwrap();
+    }
+    let text = std::fs::read_to_string(GOLDEN_PATH)
+        .expect("golden corpus missing; run with MCSIM_UPDATE_GOLDEN=1 to generate it");
+    let problems = golden::verify(&text);
+    assert!(
+        problems.is_empty(),
+        "companion protocol wire format changed:\n{}\n\
+         If intended, regenerate with MCSIM_UPDATE_GOLDEN=1",
+        problems.join("\n")
+    );
+}
@@ -0,0 +1,51 @@
+//! Non-ASCII names through the fixed-size name fields of the protocol.
+//!
+//! Community networks name nodes and channels in any script and with
+//! emoji. Names longer than a field are cut at a character boundary when
+//! encoded, and a name the firmware cut part-way through a character decodes
+//! without the partial character.
+
+use mcsim_companion_protocol::{ChannelInfo, Command, Response, RESP_CODE_CHANNEL_INFO, RESP_CODE_SELF_INFO};
+use proptest::prelude::*;
+
+/// Self info frame with the fixed fields zeroed, followed by `name`.
+fn self_info_frame(name: &[u8]) -> Vec<u8> {
+    let mut frame = vec![RESP_CODE_SELF_INFO];
+    frame.extend_from_slice(&[0; 57]);
+    frame.extend_from_slice(name);
+    frame
+}
+
+proptest! {
+    #[test]
+    fn channel_name_round_trips(name in "\\PC{0,40}") {
+        let mut frame = Command::SetChannel {
+            channel: ChannelInfo { index: 1, name: name.clone(), secret: [7; 16] },
+        }
+        .encode();
+        frame[0] = RESP_CODE_CHANNEL_INFO;
+        let Response::ChannelInfo(info) = Response::decode(&frame).unwrap() else {
+            panic!("not channel info");
+        };
+        prop_assert_eq!(info.name.as_str(), &name[..name.floor_char_boundary(31)]);
+    }
+
+    #[test]
+    fn name_cut_by_firmware_drops_partial_character(name in "\\PC{1,20}", cut in 0usize..80) {
+        let cut = cut.min(name.len());
+        let frame = self_info_frame(&name.as_bytes()[..cut]);
+        let Response::SelfInfo(info) = Response::decode(&frame).unwrap() else {
+            panic!("not self info");
+        };
+        prop_assert_eq!(info.node_name.as_str(), &name[..name.floor_char_boundary(cut)]);
+    }
+}
+
+#[test]
+fn test_invalid_name_bytes_replaced() {
+    let frame = self_info_frame(b"Caf\xff\xc3\xa9\xe2\x82");
+    let Response::SelfInfo(info) = Response::decode(&frame).unwrap() else {
+        panic!("not self info");
+    };
+    assert_eq!(info.node_name, "Caf\u{FFFD}é");
+}
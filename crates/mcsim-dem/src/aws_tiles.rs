@@ -24,6 +24,23 @@
 //! - Multiple threads requesting the same tile will coordinate, with only one
 //!   performing the download while others wait
 //! - Cached tiles are served immediately without blocking
+//!
+//! ## Download Failures
+//!
+//! A failed download is retried a few times with exponential backoff (see
+//! [`RetryPolicy`]). If every attempt fails, the tile is put on a cool-down:
+//! requests for it fail immediately until the cool-down expires, and each
+//! further failure doubles it. This keeps a long-running caller that needs a
+//! tile mid-run from stalling on repeated timeouts while the network is down,
+//! and lets it recover on its own once the tile becomes reachable again.
+//!
+//! ## Offline Mode
+//!
+//! On air-gapped machines every download would wait out the HTTP timeout.
+//! With an [`OfflineMode`] other than `Online`, the fetcher never touches the
+//! network: tiles must already be in the cache (see
+//! [`AwsTileFetcher::prefetch_tiles`]), and a missing tile either fails
+//! immediately or reads as a constant fallback elevation.
 
 use crate::{DemError, DemTile, Result};
 use crate::tile::TileBounds;
@@ -34,6 +51,7 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Condvar, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 /// Default maximum number of tiles to cache in memory.
 /// Each tile is 512x512 pixels at ~4 bytes per pixel = ~1MB per tile.
@@ -52,6 +70,35 @@ pub const MAX_ZOOM: u8 = 14;
 /// Default zoom level (good balance of detail and tile count).
 pub const DEFAULT_ZOOM: u8 = 12;
 
+/// Retry behavior for tile downloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Download attempts per request before giving up (at least 1).
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubles after each failed attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound on retry delays and on the cool-down after a failed request.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay after `failures` consecutive failures (1-based), capped at `max_backoff`.
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let factor = 1u32.checked_shl(failures.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
 /// OSM-style tile coordinates (z, x, y).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TileCoord {
@@ -143,11 +190,58 @@ impl TileCoord {
     pub fn aws_url(&self) -> String {
         format!("{}/{}/{}/{}.tif", AWS_TILE_BASE_URL, self.z, self.x, self.y)
     }
+
+    /// All tiles at zoom `z` that overlap a bounding box, row by row from
+    /// the north-west corner. An inverted box covers no tiles.
+    pub fn covering(z: u8, min_lat: f64, max_lat: f64, min_lon: f64, max_lon: f64) -> Result<Vec<Self>> {
+        let tl = Self::from_lat_lon(max_lat, min_lon, z)?;
+        let br = Self::from_lat_lon(min_lat, max_lon, z)?;
+        Ok((tl.y..=br.y)
+            .flat_map(|y| (tl.x..=br.x).map(move |x| Self::new(z, x, y)))
+            .collect())
+    }
 }
 
 /// Callback for tile download progress.
 pub type DownloadCallback = Box<dyn Fn(&str) + Send + Sync>;
 
+/// Progress of a bulk prefetch, reported once per tile as it finishes.
+#[derive(Debug, Clone)]
+pub struct PrefetchProgress {
+    /// Tiles finished so far, including this one.
+    pub completed: usize,
+    /// Tiles in the prefetch.
+    pub total: usize,
+    /// The tile that just finished.
+    pub coord: TileCoord,
+    /// What happened to the tile.
+    pub outcome: PrefetchOutcome,
+}
+
+/// What a bulk prefetch did with one tile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrefetchOutcome {
+    /// The tile was already on disk.
+    Cached,
+    /// The tile was downloaded.
+    Downloaded,
+    /// Every download attempt failed.
+    Failed(String),
+}
+
+/// Result of a bulk prefetch.
+#[derive(Debug, Clone, Default)]
+pub struct PrefetchSummary {
+    /// Tiles in the prefetch.
+    pub total: usize,
+    /// Tiles that were already on disk.
+    pub cached: usize,
+    /// Tiles that were downloaded.
+    pub downloaded: usize,
+    /// Tiles that could not be downloaded, with the reason.
+    pub failed: Vec<(TileCoord, String)>,
+}
+
 /// Status of a tile download in progress.
 #[derive(Clone)]
 enum DownloadStatus {
@@ -159,17 +253,30 @@ enum DownloadStatus {
     Failed(String),
 }
 
+/// Cool-down state of a tile whose download failed.
+struct TileBackoff {
+    /// Consecutive failed requests.
+    failures: u32,
+    /// No download is attempted before this instant.
+    retry_at: Instant,
+    /// Reason for the last failure.
+    reason: String,
+}
+
 /// Tracks in-flight downloads to prevent duplicate requests.
 struct DownloadTracker {
     /// Map of tile coordinates to their download status.
     /// Only contains entries for tiles currently being downloaded.
     in_flight: HashMap<TileCoord, DownloadStatus>,
+    /// Tiles cooling down after failed downloads.
+    backoff: HashMap<TileCoord, TileBackoff>,
 }
 
 impl DownloadTracker {
     fn new() -> Self {
         Self {
             in_flight: HashMap::new(),
+            backoff: HashMap::new(),
         }
     }
 }
@@ -228,6 +335,19 @@ impl TileCache {
     }
 }
 
+/// How a fetcher treats tiles that are not in its cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OfflineMode {
+    /// Download missing tiles.
+    #[default]
+    Online,
+    /// Never download; a missing tile is a [`DemError::TileNotCached`] error.
+    Strict,
+    /// Never download; points on missing tiles have this elevation in
+    /// meters (0 for a flat, sea-level earth).
+    Fallback(f32),
+}
+
 /// Download statistics for the fetcher.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct DownloadStats {
@@ -235,6 +355,10 @@ pub struct DownloadStats {
     pub tiles_downloaded: usize,
     /// Total bytes downloaded this session.
     pub bytes_downloaded: u64,
+    /// Tile requests that failed after all retries, or while cooling down.
+    pub failed_downloads: usize,
+    /// Elevation lookups answered with the offline fallback elevation.
+    pub offline_fallbacks: usize,
 }
 
 /// AWS elevation tile fetcher with local caching.
@@ -261,6 +385,14 @@ pub struct AwsTileFetcher {
     tiles_downloaded: AtomicUsize,
     /// Total bytes downloaded this session (atomic for thread safety).
     bytes_downloaded: AtomicU64,
+    /// Failed tile requests this session (atomic for thread safety).
+    failed_downloads: AtomicUsize,
+    /// Retry and cool-down behavior for failed downloads.
+    retry_policy: RetryPolicy,
+    /// Whether missing tiles may be downloaded.
+    offline_mode: OfflineMode,
+    /// Elevation lookups answered with the offline fallback (atomic for thread safety).
+    offline_fallbacks: AtomicUsize,
 }
 
 impl std::fmt::Debug for AwsTileFetcher {
@@ -302,9 +434,35 @@ impl AwsTileFetcher {
             tile_cache: RwLock::new(TileCache::new(DEFAULT_TILE_CACHE_SIZE)),
             tiles_downloaded: AtomicUsize::new(0),
             bytes_downloaded: AtomicU64::new(0),
+            failed_downloads: AtomicUsize::new(0),
+            retry_policy: RetryPolicy::default(),
+            offline_mode: OfflineMode::Online,
+            offline_fallbacks: AtomicUsize::new(0),
         })
     }
 
+    /// Use a custom retry policy for failed downloads.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Get the retry policy.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Set how tiles missing from the cache are handled.
+    pub fn with_offline_mode(mut self, mode: OfflineMode) -> Self {
+        self.offline_mode = mode;
+        self
+    }
+
+    /// Get the offline mode.
+    pub fn offline_mode(&self) -> OfflineMode {
+        self.offline_mode
+    }
+
     /// Get the zoom level.
     pub fn zoom(&self) -> u8 {
         self.zoom
@@ -315,6 +473,8 @@ impl AwsTileFetcher {
         DownloadStats {
             tiles_downloaded: self.tiles_downloaded.load(Ordering::Relaxed),
             bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+            failed_downloads: self.failed_downloads.load(Ordering::Relaxed),
+            offline_fallbacks: self.offline_fallbacks.load(Ordering::Relaxed),
         }
     }
 
@@ -322,6 +482,8 @@ impl AwsTileFetcher {
     pub fn reset_download_stats(&self) {
         self.tiles_downloaded.store(0, Ordering::Relaxed);
         self.bytes_downloaded.store(0, Ordering::Relaxed);
+        self.failed_downloads.store(0, Ordering::Relaxed);
+        self.offline_fallbacks.store(0, Ordering::Relaxed);
     }
 
     /// Set the zoom level.
@@ -369,6 +531,10 @@ impl AwsTileFetcher {
     /// - Different tiles can be downloaded concurrently
     ///
     /// The callback is called with status messages for user feedback during downloads.
+    ///
+    /// Failed downloads are retried according to the [`RetryPolicy`]; a tile
+    /// that still fails is cooling down and returns an error without touching
+    /// the network until the cool-down expires.
     pub fn fetch_tile_with_callback(
         &self,
         coord: &TileCoord,
@@ -380,6 +546,13 @@ impl AwsTileFetcher {
         if cache_path.exists() {
             return Ok(cache_path);
         }
+        if self.offline_mode != OfflineMode::Online {
+            return Err(DemError::TileNotCached {
+                z: coord.z,
+                x: coord.x,
+                y: coord.y,
+            });
+        }
 
         // Check if another thread is already downloading this tile
         loop {
@@ -413,6 +586,23 @@ impl AwsTileFetcher {
                     if cache_path.exists() {
                         return Ok(cache_path);
                     }
+                    // Fail fast while the tile is cooling down after a failure
+                    if let Some(backoff) = tracker.backoff.get(coord) {
+                        let now = Instant::now();
+                        if now < backoff.retry_at {
+                            self.failed_downloads.fetch_add(1, Ordering::Relaxed);
+                            return Err(DemError::TileDownloadFailed {
+                                z: coord.z,
+                                x: coord.x,
+                                y: coord.y,
+                                reason: format!(
+                                    "{} (retrying in {:.0}s)",
+                                    backoff.reason,
+                                    (backoff.retry_at - now).as_secs_f64()
+                                ),
+                            });
+                        }
+                    }
                     // Mark as in-progress and proceed to download
                     tracker.in_flight.insert(*coord, DownloadStatus::InProgress);
                     break;
@@ -422,7 +612,14 @@ impl AwsTileFetcher {
 
         // We are responsible for downloading this tile
         // Note: Other threads can now download different tiles concurrently
-        let result = self.download_tile_internal(coord, &cache_path, callback);
+        let mut result = self.download_tile_internal(coord, &cache_path, callback);
+        for attempt in 1..self.retry_policy.max_attempts {
+            if result.is_ok() {
+                break;
+            }
+            std::thread::sleep(self.retry_policy.backoff(attempt));
+            result = self.download_tile_internal(coord, &cache_path, callback);
+        }
 
         // Update status and notify waiting threads
         {
@@ -430,9 +627,20 @@ impl AwsTileFetcher {
             match &result {
                 Ok(_) => {
                     tracker.in_flight.insert(*coord, DownloadStatus::Complete);
+                    tracker.backoff.remove(coord);
                 }
                 Err(e) => {
+                    self.failed_downloads.fetch_add(1, Ordering::Relaxed);
                     tracker.in_flight.insert(*coord, DownloadStatus::Failed(e.to_string()));
+                    let failures = tracker.backoff.get(coord).map_or(0, |b| b.failures) + 1;
+                    tracker.backoff.insert(
+                        *coord,
+                        TileBackoff {
+                            failures,
+                            retry_at: Instant::now() + self.retry_policy.backoff(failures),
+                            reason: e.to_string(),
+                        },
+                    );
                 }
             }
         }
@@ -507,7 +715,14 @@ impl AwsTileFetcher {
         }
         
         // Tile not in memory - ensure it's downloaded/cached on disk
-        let tile_path = self.fetch_tile_with_callback(&coord, callback)?;
+        let tile_path = match (self.fetch_tile_with_callback(&coord, callback), self.offline_mode) {
+            (Ok(path), _) => path,
+            (Err(DemError::TileNotCached { .. }), OfflineMode::Fallback(elevation)) => {
+                self.offline_fallbacks.fetch_add(1, Ordering::Relaxed);
+                return Ok(elevation);
+            }
+            (Err(e), _) => return Err(e),
+        };
         
         // Get the tile bounds from the coordinate
         let (min_lat, max_lat, min_lon, max_lon) = coord.bounds();
@@ -572,6 +787,62 @@ impl AwsTileFetcher {
 
         Ok(fetched)
     }
+
+    /// Download every missing tile in `tiles` using `jobs` worker threads.
+    ///
+    /// Each tile is retried according to the [`RetryPolicy`]. A tile that
+    /// still fails does not stop the others; it is listed in the summary so
+    /// the caller can run the prefetch again. `progress` is called from the
+    /// worker threads after each tile.
+    pub fn prefetch_tiles(
+        &self,
+        tiles: &[TileCoord],
+        jobs: usize,
+        progress: Option<&(dyn Fn(&PrefetchProgress) + Sync)>,
+    ) -> PrefetchSummary {
+        let next = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+        let summary = Mutex::new(PrefetchSummary {
+            total: tiles.len(),
+            ..Default::default()
+        });
+
+        let worker = || {
+            while let Some(coord) = tiles.get(next.fetch_add(1, Ordering::Relaxed)) {
+                let outcome = if self.is_cached(coord) {
+                    PrefetchOutcome::Cached
+                } else {
+                    match self.fetch_tile(coord) {
+                        Ok(_) => PrefetchOutcome::Downloaded,
+                        Err(e) => PrefetchOutcome::Failed(e.to_string()),
+                    }
+                };
+                {
+                    let mut summary = summary.lock().unwrap();
+                    match &outcome {
+                        PrefetchOutcome::Cached => summary.cached += 1,
+                        PrefetchOutcome::Downloaded => summary.downloaded += 1,
+                        PrefetchOutcome::Failed(reason) => summary.failed.push((*coord, reason.clone())),
+                    }
+                }
+                if let Some(progress) = progress {
+                    progress(&PrefetchProgress {
+                        completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                        total: tiles.len(),
+                        coord: *coord,
+                        outcome,
+                    });
+                }
+            }
+        };
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.clamp(1, tiles.len().max(1)) {
+                scope.spawn(worker);
+            }
+        });
+
+        summary.into_inner().unwrap()
+    }
 }
 
 #[cfg(test)]
@@ -590,6 +861,21 @@ mod tests {
         assert!(-122.3321 >= min_lon && -122.3321 <= max_lon);
     }
 
+    #[test]
+    fn test_tile_coord_covering() {
+        let tiles = TileCoord::covering(12, 47.5, 47.7, -122.5, -122.2).unwrap();
+        let tl = TileCoord::from_lat_lon(47.7, -122.5, 12).unwrap();
+        let br = TileCoord::from_lat_lon(47.5, -122.2, 12).unwrap();
+        assert_eq!(tiles.len() as u32, (br.x - tl.x + 1) * (br.y - tl.y + 1));
+        assert_eq!(tiles.first(), Some(&tl));
+        assert_eq!(tiles.last(), Some(&br));
+
+        // A point covers one tile; an inverted box covers none
+        assert_eq!(TileCoord::covering(12, 47.6, 47.6, -122.3, -122.3).unwrap().len(), 1);
+        assert!(TileCoord::covering(12, 47.7, 47.5, -122.5, -122.2).unwrap().is_empty());
+        assert!(TileCoord::covering(20, 47.5, 47.7, -122.5, -122.2).is_err());
+    }
+
     #[test]
     fn test_tile_coord_equator() {
         // Equator at prime meridian
@@ -682,4 +968,53 @@ mod tests {
         assert!(TileCoord::from_lat_lon(0.0, 0.0, 0).is_err());
         assert!(TileCoord::from_lat_lon(0.0, 0.0, 15).is_err());
     }
+
+    #[test]
+    fn test_retry_backoff_doubles_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(8));
+        assert_eq!(policy.backoff(5), Duration::from_secs(10));
+        assert_eq!(policy.backoff(40), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_cooling_down_tile_fails_fast() {
+        let cache_dir = std::env::temp_dir().join(format!("mcsim-dem-backoff-{}", std::process::id()));
+        let fetcher = AwsTileFetcher::new(&cache_dir).unwrap();
+        let coord = TileCoord::new(12, 655, 1407);
+        fetcher.download_tracker.lock().unwrap().backoff.insert(
+            coord,
+            TileBackoff {
+                failures: 1,
+                retry_at: Instant::now() + Duration::from_secs(60),
+                reason: "HTTP 503".to_string(),
+            },
+        );
+
+        // Returns without attempting a download
+        let err = fetcher.fetch_tile(&coord).unwrap_err().to_string();
+        assert!(err.contains("HTTP 503") && err.contains("retrying in"), "{}", err);
+        assert_eq!(fetcher.download_stats().failed_downloads, 1);
+        let _ = fs::remove_dir_all(&cache_dir);
+    }
+
+    #[test]
+    fn test_offline_mode_never_downloads() {
+        let cache_dir = std::env::temp_dir().join(format!("mcsim-dem-offline-{}", std::process::id()));
+        let strict = AwsTileFetcher::new(&cache_dir).unwrap().with_offline_mode(OfflineMode::Strict);
+        let err = strict.get_elevation(47.6062, -122.3321).unwrap_err();
+        assert!(matches!(err, DemError::TileNotCached { z: 12, .. }), "{}", err);
+
+        let flat = AwsTileFetcher::new(&cache_dir).unwrap().with_offline_mode(OfflineMode::Fallback(0.0));
+        assert_eq!(flat.get_elevation(47.6062, -122.3321).unwrap(), 0.0);
+        let stats = flat.download_stats();
+        assert_eq!((stats.tiles_downloaded, stats.failed_downloads, stats.offline_fallbacks), (0, 0, 1));
+        let _ = fs::remove_dir_all(&cache_dir);
+    }
 }
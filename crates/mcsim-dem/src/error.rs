@@ -81,6 +81,17 @@ pub enum DemError {
         reason: String,
     },
 
+    /// Tile is not in the cache and offline mode forbids downloading it.
+    #[error("Tile z={z} x={x} y={y} is not cached and downloads are disabled (offline mode)")]
+    TileNotCached {
+        /// Zoom level.
+        z: u8,
+        /// X tile coordinate.
+        x: u32,
+        /// Y tile coordinate.
+        y: u32,
+    },
+
     /// Invalid zoom level.
     #[error("Invalid zoom level {0} (must be 1-14)")]
     InvalidZoomLevel(u8),
@@ -0,0 +1,242 @@
+//! Land-cover rasters (e.g. ESA WorldCover) for clutter modeling.
+//!
+//! A land-cover raster is a GeoTIFF of one byte per pixel, each value a
+//! land-cover class code. [`LandCoverMap`] holds any number of such tiles
+//! and answers the class at a coordinate, nearest pixel, so that link
+//! prediction can add the loss of forests or buildings that bare-terrain
+//! models ignore.
+//!
+//! Class codes follow ESA WorldCover (10 m, 3x3 degree tiles named like
+//! `ESA_WorldCover_10m_2021_v200_N45W123_Map.tif`).
+
+use crate::tile::TileBounds;
+use crate::{DemError, DemTile, Result};
+use std::path::Path;
+use tiff::decoder::{Decoder, DecodingResult, Limits};
+
+/// Land-cover class, with the ESA WorldCover codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LandCoverClass {
+    /// Forest (code 10).
+    TreeCover,
+    /// Shrubs (code 20).
+    Shrubland,
+    /// Grass (code 30).
+    Grassland,
+    /// Fields (code 40).
+    Cropland,
+    /// Buildings and roads (code 50).
+    BuiltUp,
+    /// Bare ground or sparse vegetation (code 60).
+    Bare,
+    /// Snow and ice (code 70).
+    SnowAndIce,
+    /// Open water (code 80).
+    Water,
+    /// Herbaceous wetland (code 90).
+    Wetland,
+    /// Mangroves (code 95).
+    Mangroves,
+    /// Moss and lichen (code 100).
+    MossAndLichen,
+}
+
+impl LandCoverClass {
+    /// Every class, in code order.
+    pub const ALL: [LandCoverClass; 11] = [
+        LandCoverClass::TreeCover,
+        LandCoverClass::Shrubland,
+        LandCoverClass::Grassland,
+        LandCoverClass::Cropland,
+        LandCoverClass::BuiltUp,
+        LandCoverClass::Bare,
+        LandCoverClass::SnowAndIce,
+        LandCoverClass::Water,
+        LandCoverClass::Wetland,
+        LandCoverClass::Mangroves,
+        LandCoverClass::MossAndLichen,
+    ];
+
+    /// Class of a WorldCover pixel value, or None for no data.
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|class| class.code() == code)
+    }
+
+    /// WorldCover pixel value of the class.
+    pub fn code(self) -> u8 {
+        match self {
+            LandCoverClass::TreeCover => 10,
+            LandCoverClass::Shrubland => 20,
+            LandCoverClass::Grassland => 30,
+            LandCoverClass::Cropland => 40,
+            LandCoverClass::BuiltUp => 50,
+            LandCoverClass::Bare => 60,
+            LandCoverClass::SnowAndIce => 70,
+            LandCoverClass::Water => 80,
+            LandCoverClass::Wetland => 90,
+            LandCoverClass::Mangroves => 95,
+            LandCoverClass::MossAndLichen => 100,
+        }
+    }
+
+    /// Lowercase name, e.g. `tree_cover`.
+    pub fn name(self) -> &'static str {
+        match self {
+            LandCoverClass::TreeCover => "tree_cover",
+            LandCoverClass::Shrubland => "shrubland",
+            LandCoverClass::Grassland => "grassland",
+            LandCoverClass::Cropland => "cropland",
+            LandCoverClass::BuiltUp => "built_up",
+            LandCoverClass::Bare => "bare",
+            LandCoverClass::SnowAndIce => "snow_and_ice",
+            LandCoverClass::Water => "water",
+            LandCoverClass::Wetland => "wetland",
+            LandCoverClass::Mangroves => "mangroves",
+            LandCoverClass::MossAndLichen => "moss_and_lichen",
+        }
+    }
+}
+
+impl std::fmt::Display for LandCoverClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A single land-cover raster tile.
+#[derive(Debug)]
+pub struct LandCoverTile {
+    /// Class codes in row-major order (north to south, west to east).
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    bounds: TileBounds,
+}
+
+impl LandCoverTile {
+    /// Load a land-cover tile from a GeoTIFF of 8-bit class codes.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+        // WorldCover tiles are 36000 x 36000 pixels of one byte
+        let mut limits = Limits::default();
+        limits.decoding_buffer_size = 2 * 1024 * 1024 * 1024;
+        limits.intermediate_buffer_size = 2 * 1024 * 1024 * 1024;
+        limits.ifd_value_size = 1024 * 1024 * 1024;
+        let mut decoder = Decoder::new(file)?.with_limits(limits);
+
+        let (width, height) = decoder.dimensions()?;
+        let bounds = DemTile::read_geotransform(&mut decoder, path)?;
+        let data = match decoder.read_image()? {
+            DecodingResult::U8(data) => data,
+            _ => {
+                return Err(DemError::UnsupportedDataType(format!(
+                    "land-cover raster {} is not 8-bit",
+                    path.display()
+                )))
+            }
+        };
+        Self::from_codes(data, width, height, bounds)
+    }
+
+    /// Build a tile from class codes in row-major order, north row first.
+    pub fn from_codes(data: Vec<u8>, width: u32, height: u32, bounds: TileBounds) -> Result<Self> {
+        if width == 0 || height == 0 || data.len() != width as usize * height as usize {
+            return Err(DemError::InvalidGeoTiff(format!(
+                "{} land-cover pixels for a {}x{} raster",
+                data.len(),
+                width,
+                height
+            )));
+        }
+        Ok(Self { data, width, height, bounds })
+    }
+
+    /// Geographic bounds of the tile.
+    pub fn bounds(&self) -> TileBounds {
+        self.bounds
+    }
+
+    /// Class of the pixel containing a coordinate, or None outside the tile
+    /// or on no-data pixels.
+    pub fn class_at(&self, lat: f64, lon: f64) -> Option<LandCoverClass> {
+        if !self.bounds.contains(lat, lon) {
+            return None;
+        }
+        // Pixels are areas here, not sample points as in elevation tiles
+        let fx = (lon - self.bounds.min_lon) / (self.bounds.max_lon - self.bounds.min_lon);
+        let fy = (self.bounds.max_lat - lat) / (self.bounds.max_lat - self.bounds.min_lat);
+        let x = ((fx * self.width as f64) as u32).min(self.width - 1);
+        let y = ((fy * self.height as f64) as u32).min(self.height - 1);
+        LandCoverClass::from_code(self.data[(y as usize) * self.width as usize + x as usize])
+    }
+}
+
+/// Land-cover tiles covering an area.
+#[derive(Debug, Default)]
+pub struct LandCoverMap {
+    tiles: Vec<LandCoverTile>,
+}
+
+impl LandCoverMap {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tile.
+    pub fn add_tile(&mut self, tile: LandCoverTile) {
+        self.tiles.push(tile);
+    }
+
+    /// Load and add a GeoTIFF tile.
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.add_tile(LandCoverTile::from_file(path)?);
+        Ok(())
+    }
+
+    /// Load every `.tif` file of a directory, returning the number of tiles
+    /// added.
+    pub fn add_directory<P: AsRef<Path>>(&mut self, dir: P) -> Result<usize> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("tif")))
+            .collect();
+        paths.sort();
+        for path in &paths {
+            self.add_file(path)?;
+        }
+        Ok(paths.len())
+    }
+
+    /// Number of tiles.
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Class at a coordinate, or None where no tile has data.
+    pub fn class_at(&self, lat: f64, lon: f64) -> Option<LandCoverClass> {
+        self.tiles.iter().find_map(|tile| tile.class_at(lat, lon))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_class_lookup() {
+        let bounds = TileBounds { min_lat: 47.0, max_lat: 48.0, min_lon: -123.0, max_lon: -122.0 };
+        // North row: forest then water; south row: built-up then no data
+        let tile = LandCoverTile::from_codes(vec![10, 80, 50, 0], 2, 2, bounds).unwrap();
+        let mut map = LandCoverMap::new();
+        map.add_tile(tile);
+
+        assert_eq!(map.class_at(47.9, -122.9), Some(LandCoverClass::TreeCover));
+        assert_eq!(map.class_at(47.9, -122.1), Some(LandCoverClass::Water));
+        assert_eq!(map.class_at(47.1, -122.9), Some(LandCoverClass::BuiltUp));
+        assert_eq!(map.class_at(47.0, -122.0), None);
+        assert_eq!(map.class_at(46.5, -122.5), None);
+        assert!(LandCoverTile::from_codes(vec![10; 3], 2, 2, bounds).is_err());
+    }
+}
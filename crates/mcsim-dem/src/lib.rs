@@ -6,6 +6,8 @@
 //! - USGS 3DEP (3D Elevation Program) GeoTIFF tiles (local files)
 //! - AWS Open Data terrain tiles (fetched on demand and cached locally)
 //!
+//! It also reads land-cover rasters (ESA WorldCover) for clutter modeling.
+//!
 //! ## Overview
 //!
 //! ### USGS 3DEP Tiles
@@ -64,18 +66,28 @@
 //!     47.6062, -122.3321,
 //!     Some(&callback)
 //! )?;
+//!
+//! // Download a whole area up front, 8 tiles at a time
+//! let tiles = TileCoord::covering(12, 47.5, 47.7, -122.5, -122.2)?;
+//! let summary = fetcher.prefetch_tiles(&tiles, 8, None);
+//! println!("{} downloaded, {} failed", summary.downloaded, summary.failed.len());
 //! # Ok::<(), mcsim_dem::DemError>(())
 //! ```
 
 mod aws_tiles;
 mod error;
+mod landcover;
 mod manager;
 mod tile;
 
-pub use aws_tiles::{AwsTileFetcher, DownloadCallback, DownloadStats, TileCoord, DEFAULT_ZOOM, MAX_ZOOM, MIN_ZOOM};
+pub use aws_tiles::{
+    AwsTileFetcher, DownloadCallback, DownloadStats, OfflineMode, PrefetchOutcome, PrefetchProgress, PrefetchSummary, RetryPolicy,
+    TileCoord, DEFAULT_ZOOM, MAX_ZOOM, MIN_ZOOM,
+};
 pub use error::DemError;
+pub use landcover::{LandCoverClass, LandCoverMap, LandCoverTile};
 pub use manager::DemManager;
-pub use tile::DemTile;
+pub use tile::{DemTile, TileBounds};
 
 /// Result type for DEM operations.
 pub type Result<T> = std::result::Result<T, DemError>;
@@ -113,7 +113,7 @@ impl DemTile {
     }
 
     /// Read the geotransform (geographic bounds) from GeoTIFF tags.
-    fn read_geotransform<R: std::io::Read + std::io::Seek>(
+    pub(crate) fn read_geotransform<R: std::io::Read + std::io::Seek>(
         decoder: &mut Decoder<R>,
         path: &Path,
     ) -> Result<TileBounds> {
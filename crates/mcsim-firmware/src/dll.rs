@@ -22,6 +22,9 @@
 //! ```
 
 use libloading::Library;
+use mcsim_common::crystal::CrystalDrift;
+use mcsim_common::barometer::{Barometer, BarometerReading};
+use mcsim_common::OutboundQueue;
 use std::ffi::{c_char, CStr, CString};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -49,12 +52,16 @@ pub const MAX_NODE_NAME: usize = 32;
 pub const PUB_KEY_SIZE: usize = 32;
 /// Size of private key in bytes.
 pub const PRV_KEY_SIZE: usize = 64;
+/// Largest flood hop limit the firmware accepts (its `MAX_PATH_SIZE`).
+pub const MAX_FLOOD_HOPS: u8 = 64;
 /// Maximum radio packet size.
 pub const MAX_RADIO_PACKET: usize = 256;
 /// Maximum serial TX buffer size (must match SIM_MAX_SERIAL_TX in sim_api.h).
 pub const MAX_SERIAL_TX: usize = 32768;
 /// Maximum log output buffer size.
 pub const MAX_LOG_OUTPUT: usize = 4096;
+/// Buffer size for an outbound queue snapshot (64 length-prefixed packets).
+pub const MAX_OUTBOUND_QUEUE: usize = 64 * (MAX_RADIO_PACKET + 2);
 
 // ============================================================================
 // Error Types
@@ -110,6 +117,12 @@ pub struct FirmwareSimulationParams {
     pub initial_rtc_secs: u64,
     /// Startup time in microseconds. Events before this time are dropped.
     pub startup_time_us: u64,
+    /// Temperature-dependent error of the node's clock crystal (`None` for
+    /// a clock that keeps simulation time exactly).
+    pub clock_drift: Option<CrystalDrift>,
+    /// Barometric pressure sensor reported in environment telemetry (`None`
+    /// for a node without one).
+    pub barometer: Option<Barometer>,
 }
 
 impl Default for FirmwareSimulationParams {
@@ -121,6 +134,8 @@ impl Default for FirmwareSimulationParams {
             log_loop_iterations: false,
             initial_rtc_secs: DEFAULT_INITIAL_RTC_SECS,
             startup_time_us: 0,
+            clock_drift: None,
+            barometer: None,
         }
     }
 }
@@ -186,8 +201,11 @@ pub struct NodeConfig {
     pub log_spin_detection: u8,
     /// Enable debug logging for loop iterations (bool as u8).
     pub log_loop_iterations: u8,
+
+    /// Hop count of flood packets no longer forwarded (0 = firmware default).
+    pub flood_max: u8,
     /// Alignment padding.
-    _padding: [u8; 2],
+    _padding: [u8; 1],
 
     /// Reserved for future use.
     _reserved: [u8; 56],
@@ -211,7 +229,8 @@ impl Default for NodeConfig {
             idle_loops_before_yield: DEFAULT_IDLE_LOOPS_BEFORE_YIELD,
             log_spin_detection: 0,
             log_loop_iterations: 0,
-            _padding: [0; 2],
+            flood_max: 0,
+            _padding: [0; 1],
             _reserved: [0; 56],
         }
     }
@@ -219,9 +238,12 @@ impl Default for NodeConfig {
 
 impl NodeConfig {
     /// Create a new config with the given name.
+    ///
+    /// A name longer than the field is cut at a character boundary, so the
+    /// firmware never sees part of a multi-byte UTF-8 character.
     pub fn with_name(mut self, name: &str) -> Self {
         let bytes = name.as_bytes();
-        let len = bytes.len().min(MAX_NODE_NAME - 1);
+        let len = name.floor_char_boundary(MAX_NODE_NAME - 1);
         for (i, &b) in bytes[..len].iter().enumerate() {
             self.node_name[i] = b as c_char;
         }
@@ -272,6 +294,12 @@ impl NodeConfig {
         self.log_loop_iterations = log_loops as u8;
         self
     }
+
+    /// Set the flood hop limit (the firmware's `flood.max` setting).
+    pub fn with_flood_max(mut self, flood_max: u8) -> Self {
+        self.flood_max = flood_max;
+        self
+    }
 }
 
 /// Result of a simulation step.
@@ -378,8 +406,12 @@ type FnSimInjectSerialFrame = unsafe extern "C" fn(SimNodeHandle, *const u8, usi
 type FnSimCollectSerialFrame = unsafe extern "C" fn(SimNodeHandle, *mut u8, usize) -> usize;
 type FnSimNotifyTxComplete = unsafe extern "C" fn(SimNodeHandle);
 type FnSimNotifyStateChange = unsafe extern "C" fn(SimNodeHandle, u32);
+type FnSimSetChannelBusy = unsafe extern "C" fn(SimNodeHandle, i32);
+type FnSimSetBatteryMv = unsafe extern "C" fn(SimNodeHandle, u16);
+type FnSimSetEnvironment = unsafe extern "C" fn(SimNodeHandle, f32, f32, f32);
 type FnSimGetNodeType = unsafe extern "C" fn() -> *const c_char;
 type FnSimGetPublicKey = unsafe extern "C" fn(SimNodeHandle, *mut u8);
+type FnSimGetOutboundQueue = unsafe extern "C" fn(SimNodeHandle, *mut u8, usize, *mut i32) -> i32;
 type FnSimFsWrite = unsafe extern "C" fn(SimNodeHandle, *const c_char, *const u8, usize) -> i32;
 type FnSimFsRead = unsafe extern "C" fn(SimNodeHandle, *const c_char, *mut u8, usize) -> i32;
 type FnSimFsExists = unsafe extern "C" fn(SimNodeHandle, *const c_char) -> i32;
@@ -454,8 +486,12 @@ pub struct FirmwareDll {
     sim_collect_serial_frame: FnSimCollectSerialFrame,
     sim_notify_tx_complete: FnSimNotifyTxComplete,
     sim_notify_state_change: FnSimNotifyStateChange,
+    sim_set_channel_busy: FnSimSetChannelBusy,
+    sim_set_battery_mv: FnSimSetBatteryMv,
+    sim_set_environment: FnSimSetEnvironment,
     sim_get_node_type: FnSimGetNodeType,
     sim_get_public_key: FnSimGetPublicKey,
+    sim_get_outbound_queue: FnSimGetOutboundQueue,
     sim_fs_write: FnSimFsWrite,
     sim_fs_read: FnSimFsRead,
     sim_fs_exists: FnSimFsExists,
@@ -498,10 +534,18 @@ impl FirmwareDll {
                 *library.get::<FnSimNotifyTxComplete>(b"sim_notify_tx_complete")?;
             let sim_notify_state_change: FnSimNotifyStateChange =
                 *library.get::<FnSimNotifyStateChange>(b"sim_notify_state_change")?;
+            let sim_set_channel_busy: FnSimSetChannelBusy =
+                *library.get::<FnSimSetChannelBusy>(b"sim_set_channel_busy")?;
+            let sim_set_battery_mv: FnSimSetBatteryMv =
+                *library.get::<FnSimSetBatteryMv>(b"sim_set_battery_mv")?;
+            let sim_set_environment: FnSimSetEnvironment =
+                *library.get::<FnSimSetEnvironment>(b"sim_set_environment")?;
             let sim_get_node_type: FnSimGetNodeType =
                 *library.get::<FnSimGetNodeType>(b"sim_get_node_type")?;
             let sim_get_public_key: FnSimGetPublicKey =
                 *library.get::<FnSimGetPublicKey>(b"sim_get_public_key")?;
+            let sim_get_outbound_queue: FnSimGetOutboundQueue =
+                *library.get::<FnSimGetOutboundQueue>(b"sim_get_outbound_queue")?;
             let sim_fs_write: FnSimFsWrite = *library.get::<FnSimFsWrite>(b"sim_fs_write")?;
             let sim_fs_read: FnSimFsRead = *library.get::<FnSimFsRead>(b"sim_fs_read")?;
             let sim_fs_exists: FnSimFsExists = *library.get::<FnSimFsExists>(b"sim_fs_exists")?;
@@ -522,8 +566,12 @@ impl FirmwareDll {
                 sim_collect_serial_frame,
                 sim_notify_tx_complete,
                 sim_notify_state_change,
+                sim_set_channel_busy,
+                sim_set_battery_mv,
+                sim_set_environment,
                 sim_get_node_type,
                 sim_get_public_key,
+                sim_get_outbound_queue,
                 sim_fs_write,
                 sim_fs_read,
                 sim_fs_exists,
@@ -584,6 +632,12 @@ impl<'a> FirmwareNode<'a> {
         key
     }
 
+    /// Snapshot the node's outbound packet queue. Returns None for firmware
+    /// without an inspectable queue (only repeaters have one).
+    pub fn outbound_queue(&self) -> Option<OutboundQueue> {
+        query_outbound_queue(self.dll, self.handle)
+    }
+
     /// Reboot the node with a new configuration.
     pub fn reboot(&mut self, config: &NodeConfig) {
         unsafe {
@@ -663,6 +717,33 @@ impl<'a> FirmwareNode<'a> {
         }
     }
 
+    /// Set whether the radio senses a reception in progress on the channel.
+    /// Firmware sees this through its channel-activity (CAD) query.
+    pub fn set_channel_busy(&mut self, busy: bool) {
+        unsafe {
+            (self.dll.sim_set_channel_busy)(self.handle, busy as i32);
+        }
+    }
+
+    /// Set the battery voltage the firmware reports.
+    pub fn set_battery_millivolts(&mut self, millivolts: u16) {
+        unsafe {
+            (self.dll.sim_set_battery_mv)(self.handle, millivolts);
+        }
+    }
+
+    /// Set the environment sensor reading the firmware reports in telemetry.
+    pub fn set_environment(&mut self, reading: &BarometerReading) {
+        unsafe {
+            (self.dll.sim_set_environment)(
+                self.handle,
+                reading.pressure_hpa as f32,
+                reading.altitude_m as f32,
+                reading.temperature_c as f32,
+            );
+        }
+    }
+
     /// Write a file to the node's filesystem.
     pub fn fs_write(&mut self, path: &str, data: &[u8]) -> Result<(), DllError> {
         let c_path = CString::new(path).map_err(|_| DllError::InvalidPath(path.to_string()))?;
@@ -754,6 +835,12 @@ impl OwnedFirmwareNode {
         key
     }
 
+    /// Snapshot the node's outbound packet queue. Returns None for firmware
+    /// without an inspectable queue (only repeaters have one).
+    pub fn outbound_queue(&self) -> Option<OutboundQueue> {
+        query_outbound_queue(&self.dll, self.handle)
+    }
+
     /// Reboot the node with a new configuration.
     pub fn reboot(&mut self, config: &NodeConfig) {
         unsafe {
@@ -805,6 +892,33 @@ impl OwnedFirmwareNode {
             (self.dll.sim_notify_state_change)(self.handle, state_version);
         }
     }
+
+    /// Set whether the radio senses a reception in progress on the channel.
+    /// Firmware sees this through its channel-activity (CAD) query.
+    pub fn set_channel_busy(&mut self, busy: bool) {
+        unsafe {
+            (self.dll.sim_set_channel_busy)(self.handle, busy as i32);
+        }
+    }
+
+    /// Set the battery voltage the firmware reports.
+    pub fn set_battery_millivolts(&mut self, millivolts: u16) {
+        unsafe {
+            (self.dll.sim_set_battery_mv)(self.handle, millivolts);
+        }
+    }
+
+    /// Set the environment sensor reading the firmware reports in telemetry.
+    pub fn set_environment(&mut self, reading: &BarometerReading) {
+        unsafe {
+            (self.dll.sim_set_environment)(
+                self.handle,
+                reading.pressure_hpa as f32,
+                reading.altitude_m as f32,
+                reading.temperature_c as f32,
+            );
+        }
+    }
 }
 
 impl Drop for OwnedFirmwareNode {
@@ -823,13 +937,41 @@ unsafe impl Send for OwnedFirmwareNode {}
 // Helper Functions
 // ============================================================================
 
+/// Call `sim_get_outbound_queue` and parse the snapshot.
+fn query_outbound_queue(dll: &FirmwareDll, handle: SimNodeHandle) -> Option<OutboundQueue> {
+    let mut buffer = vec![0u8; MAX_OUTBOUND_QUEUE];
+    let mut due = 0i32;
+    let count = unsafe { (dll.sim_get_outbound_queue)(handle, buffer.as_mut_ptr(), buffer.len(), &mut due) };
+    if count < 0 {
+        return None;
+    }
+    Some(parse_outbound_queue(&buffer, count as usize, due.max(0) as usize))
+}
+
+/// Parse `count` length-prefixed packets (2-byte little-endian length, then
+/// the raw packet) from an outbound queue snapshot.
+fn parse_outbound_queue(buffer: &[u8], count: usize, due: usize) -> OutboundQueue {
+    let mut packets = Vec::with_capacity(count);
+    let mut offset = 0;
+    while packets.len() < count && offset + 2 <= buffer.len() {
+        let len = u16::from_le_bytes([buffer[offset], buffer[offset + 1]]) as usize;
+        offset += 2;
+        let Some(packet) = buffer.get(offset..offset + len) else {
+            break;
+        };
+        packets.push(packet.to_vec());
+        offset += len;
+    }
+    OutboundQueue { packets, due }
+}
+
 /// Find the path to a firmware DLL.
 ///
 /// Searches in:
 /// 1. Current directory
 /// 2. OUT_DIR from build script (embedded at compile time)
 /// 3. target/debug or target/release directories
-fn find_dll_path(firmware_type: FirmwareType) -> Result<PathBuf, DllError> {
+pub fn find_dll_path(firmware_type: FirmwareType) -> Result<PathBuf, DllError> {
     let dll_name = firmware_type.dll_name();
 
     // Check current directory
@@ -946,6 +1088,18 @@ mod tests {
         assert_eq!(config.node_name[MAX_NODE_NAME - 1], 0);
     }
 
+    proptest::proptest! {
+        #[test]
+        fn test_node_config_with_utf8_name(name in "\\PC{0,40}") {
+            let config = NodeConfig::default().with_name(&name);
+            let bytes: Vec<u8> = config.node_name.iter().take_while(|&&c| c != 0).map(|&c| c as u8).collect();
+            // The name, or as many whole characters of it as fit
+            let stored = std::str::from_utf8(&bytes).unwrap();
+            proptest::prop_assert!(name.starts_with(stored));
+            proptest::prop_assert!(stored == name || stored.len() + 4 > MAX_NODE_NAME - 1);
+        }
+    }
+
     #[test]
     fn test_node_config_with_keys() {
         let pub_key = [1u8; 32];
@@ -1322,4 +1476,33 @@ mod tests {
         assert!(result1.current_millis >= 1000);
         assert!(result2.current_millis >= 1000);
     }
+
+    #[test]
+    fn test_parse_outbound_queue() {
+        let buffer = [2, 0, 0xaa, 0xbb, 1, 0, 0xcc, 0, 0];
+        let queue = parse_outbound_queue(&buffer, 2, 1);
+        assert_eq!(queue.packets, vec![vec![0xaa, 0xbb], vec![0xcc]]);
+        assert_eq!(queue.due, 1);
+
+        // A truncated record ends the snapshot
+        let queue = parse_outbound_queue(&[5, 0, 0xaa], 1, 0);
+        assert!(queue.packets.is_empty());
+    }
+
+    #[test]
+    fn test_repeater_outbound_queue() {
+        let dll = match FirmwareDll::load(FirmwareType::Repeater) {
+            Ok(dll) => dll,
+            Err(DllError::NotFound(_)) => {
+                println!("Skipping test: DLL not found");
+                return;
+            }
+            Err(e) => panic!("Unexpected error: {}", e),
+        };
+
+        let mut node = dll.create_node(&NodeConfig::default().with_name("queue")).expect("Failed to create node");
+        node.step(1000, 1700000000);
+        let queue = node.outbound_queue().expect("Repeater should expose its outbound queue");
+        assert!(queue.due <= queue.packets.len());
+    }
 }
@@ -28,10 +28,13 @@ pub mod dll;
 pub mod tracer;
 
 use dll::{DllError, FirmwareDll, FirmwareType, NodeConfig, OwnedFirmwareNode};
-pub use dll::{YieldReason, FirmwareSimulationParams};
+pub use dll::{YieldReason, FirmwareSimulationParams, MAX_FLOOD_HOPS};
 use mcsim_common::{
     entity_tracer::FirmwareYieldReason,
-    Entity, EntityId, Event, EventPayload, NodeId, SimContext, SimError, SimTime,
+    barometer::Barometer,
+    crystal::CrystalDrift,
+    rng::CounterRng,
+    Entity, EntityId, Event, EventPayload, NodeId, OutboundQueue, SimContext, SimError, SimTime,
 };
 use meshcore_packet::EncryptionKey;
 use serde::{Deserialize, Serialize};
@@ -132,6 +135,8 @@ pub struct FirmwareStepResult {
     pub reason: YieldReason,
     /// Wake time in milliseconds.
     pub wake_millis: u64,
+    /// Simulation time until the node's clock reaches `wake_millis`.
+    pub wake_delay: SimTime,
     /// Radio TX data if transmitting.
     pub radio_tx_data: Option<(Vec<u8>, u32)>,
     /// Serial TX data if any.
@@ -142,6 +147,51 @@ pub struct FirmwareStepResult {
     pub error_message: Option<String>,
 }
 
+/// Reading of a node's millisecond clock at a simulation time.
+fn local_millis(clock_drift: Option<&CrystalDrift>, time: SimTime) -> u64 {
+    let local = match clock_drift {
+        Some(drift) => drift.local_time(time),
+        None => time,
+    };
+    local.as_micros() / 1000
+}
+
+/// Simulation time until a node's clock advances from `current_millis` to
+/// `wake_millis`.
+fn wake_delay(clock_drift: Option<&CrystalDrift>, current_millis: u64, wake_millis: u64) -> SimTime {
+    let local_delay = SimTime::from_micros(wake_millis.saturating_sub(current_millis) * 1000);
+    match clock_drift {
+        // The clock rate changes slowly, so its rate now is good enough
+        Some(drift) => drift.sim_delay(local_delay, SimTime::from_millis(current_millis)),
+        None => local_delay,
+    }
+}
+
+/// RNG stream of a node's sensor noise, separate from the firmware's own seed use.
+const SENSOR_RNG_STREAM: u32 = 0x5E45;
+
+/// A node's simulated environment sensor, read before each firmware step.
+#[derive(Debug)]
+struct EnvironmentSensor {
+    barometer: Barometer,
+    rng: CounterRng,
+}
+
+impl EnvironmentSensor {
+    fn new(sim_params: &FirmwareSimulationParams, rng_seed: u32) -> Option<Self> {
+        sim_params.barometer.map(|barometer| EnvironmentSensor {
+            barometer,
+            rng: CounterRng::new(rng_seed as u64, SENSOR_RNG_STREAM, 0),
+        })
+    }
+
+    /// Give the firmware a fresh noisy reading.
+    fn update(&mut self, node: &mut OwnedFirmwareNode, time: SimTime) {
+        let reading = self.barometer.read(time, &mut self.rng);
+        node.set_environment(&reading);
+    }
+}
+
 /// Trait for firmware entities.
 pub trait FirmwareEntity: Entity {
     /// Get the node ID.
@@ -175,12 +225,16 @@ pub trait FirmwareEntity: Entity {
 pub struct RepeaterConfig {
     /// Base firmware configuration.
     pub base: FirmwareConfig,
+    /// Flood packets that have travelled this many hops are not forwarded
+    /// (None keeps the firmware's setting).
+    pub flood_max: Option<u8>,
 }
 
 impl Default for RepeaterConfig {
     fn default() -> Self {
         RepeaterConfig {
             base: FirmwareConfig::default(),
+            flood_max: None,
         }
     }
 }
@@ -207,6 +261,14 @@ pub struct RepeaterFirmware {
     wake_millis: u64,
     // Startup time in microseconds - events before this are dropped
     startup_time_us: u64,
+    // Temperature-dependent error of the node's clock crystal
+    clock_drift: Option<CrystalDrift>,
+    // Battery ran out or power was cut - events are dropped until power returns
+    powered_off: bool,
+    // Configuration the DLL node was created with, to boot it again on reboot
+    node_config: NodeConfig,
+    // Barometer reported in environment telemetry
+    environment: Option<EnvironmentSensor>,
 }
 
 impl RepeaterFirmware {
@@ -258,12 +320,15 @@ impl RepeaterFirmware {
             .with_spin_logging(
                 sim_params.log_spin_detection,
                 sim_params.log_loop_iterations,
-            );
+            )
+            .with_flood_max(config.flood_max.unwrap_or(0));
 
         // Create the persistent node
         let node = OwnedFirmwareNode::new(dll, &node_config)
             .map_err(|e| FirmwareError::Dll(e))?;
 
+        let environment = EnvironmentSensor::new(sim_params, config.base.rng_seed);
+
         Ok(RepeaterFirmware {
             id,
             name,
@@ -277,9 +342,35 @@ impl RepeaterFirmware {
             awaiting_tx_complete: false,
             wake_millis: 0,
             startup_time_us: sim_params.startup_time_us,
+            clock_drift: sim_params.clock_drift,
+            powered_off: false,
+            node_config,
+            environment,
         })
     }
 
+    /// Boot the DLL node afresh at the current time, dropping everything the
+    /// firmware held in RAM.
+    fn reboot(&mut self, ctx: &mut SimContext) {
+        let rtc_secs = self.initial_rtc + (self.current_millis / 1000) as u32;
+        let config = self.node_config.clone().with_initial_time(self.current_millis, rtc_secs);
+        self.node.reboot(&config);
+        self.pending_tx = None;
+        self.awaiting_tx_complete = false;
+        ctx.post_immediate(vec![self.id], EventPayload::Timer { timer_id: 1 });
+    }
+
+    /// Pass a restart or shutdown the firmware asked for to the radio, which
+    /// hands it back to the firmware.
+    fn request_lifecycle(&self, reason: YieldReason, ctx: &mut SimContext) {
+        let payload = if reason == YieldReason::PowerOff {
+            EventPayload::PowerOff(mcsim_common::PowerOffEvent { reason: "firmware shutdown".to_string() })
+        } else {
+            EventPayload::Reboot(mcsim_common::RebootEvent { reason: "firmware reboot".to_string() })
+        };
+        ctx.post_immediate(vec![self.attached_radio], payload);
+    }
+
     /// Get the node name.
     pub fn name(&self) -> &str {
         &self.name
@@ -306,12 +397,59 @@ impl Entity for RepeaterFirmware {
         self.id
     }
 
+    fn outbound_queue(&self) -> Option<OutboundQueue> {
+        self.node.outbound_queue()
+    }
+
     fn handle_event(&mut self, event: &Event, ctx: &mut SimContext) -> Result<(), SimError> {
         // Update current time
-        self.current_millis = event.time.as_micros() / 1000;
+        self.current_millis = local_millis(self.clock_drift.as_ref(), event.time);
         // Clone the tracer to avoid borrow conflict with ctx
         let tracer = ctx.tracer().clone();
 
+        // Battery level is read by the firmware when asked for it; a depleted
+        // battery powers the node off for the rest of the simulation
+        if let EventPayload::BatteryLevel(battery) = &event.payload {
+            self.node.set_battery_millivolts(battery.millivolts);
+            if battery.depleted && !self.powered_off {
+                log::info!("[{}] Battery depleted, powering off", self.name);
+                self.powered_off = true;
+            }
+            return Ok(());
+        }
+        if let EventPayload::PowerOff(power_off) = &event.payload {
+            if !self.powered_off {
+                log::info!("[{}] Powering off: {}", self.name, power_off.reason);
+                self.powered_off = true;
+            }
+            return Ok(());
+        }
+        if let EventPayload::PowerOn(power_on) = &event.payload {
+            if self.powered_off {
+                log::info!("[{}] Powering on: {}", self.name, power_on.reason);
+                self.powered_off = false;
+                // A transmission cut short by the outage never completes
+                if self.awaiting_tx_complete {
+                    self.node.notify_tx_complete();
+                    self.awaiting_tx_complete = false;
+                    self.pending_tx = None;
+                }
+                // Wake timers lapsed while off; resume stepping now
+                ctx.post_immediate(vec![self.id], EventPayload::Timer { timer_id: 1 });
+            }
+            return Ok(());
+        }
+        if let EventPayload::Reboot(reboot) = &event.payload {
+            if !self.powered_off {
+                log::info!("[{}] Rebooting: {}", self.name, reboot.reason);
+                self.reboot(ctx);
+            }
+            return Ok(());
+        }
+        if self.powered_off {
+            return Ok(());
+        }
+
         // Check if we're still in startup delay period
         let event_time_us = event.time.as_micros();
         if event_time_us < self.startup_time_us {
@@ -327,6 +465,13 @@ impl Entity for RepeaterFirmware {
         // Log event received
         tracer.log_event_received(Some(&self.name), self.id, event.time, event);
 
+        // Channel activity only changes what the next CAD query returns; the
+        // firmware polls it on its own schedule, so no step is needed
+        if let EventPayload::ChannelActivity(activity) = &event.payload {
+            self.node.set_channel_busy(activity.busy);
+            return Ok(());
+        }
+
         // Log step begin with trigger event description
         let trigger_desc = describe_event(&event.payload);
         tracer.log_firmware_step_begin(Some(&self.name), self.id, event.time, &trigger_desc);
@@ -364,6 +509,9 @@ impl Entity for RepeaterFirmware {
         }
 
         // Step the firmware
+        if let Some(environment) = &mut self.environment {
+            environment.update(&mut self.node, event.time);
+        }
         let rtc_secs = self.initial_rtc + (self.current_millis / 1000) as u32;
         let result = self.node.step(self.current_millis, rtc_secs);
 
@@ -407,17 +555,19 @@ impl Entity for RepeaterFirmware {
                     vec![self.attached_radio],
                     EventPayload::RadioTxRequest(mcsim_common::RadioTxRequestEvent {
                         packet: mcsim_common::LoraPacket::new(tx_data),
+                        reported_airtime_ms: Some(airtime_ms),
+                        queue_depth: self.node.outbound_queue().map(|queue| queue.packets.len()),
                     }),
                 );
             }
             YieldReason::Idle => {
                 // Schedule wake timer if needed
                 if result.wake_millis > self.current_millis {
-                    let delay_us = (result.wake_millis - self.current_millis) * 1000;
-                    let delay_ms = delay_us / 1000;
+                    let delay = wake_delay(self.clock_drift.as_ref(), self.current_millis, result.wake_millis);
+                    let delay_ms = delay.as_micros() / 1000;
                     tracer.log_timer_scheduled(Some(&self.name), self.id, event.time, 1, delay_ms);
                     ctx.post_event(
-                        SimTime::from_micros(delay_us),
+                        delay,
                         vec![self.id],
                         EventPayload::Timer { timer_id: 1 },
                     );
@@ -433,7 +583,11 @@ impl Entity for RepeaterFirmware {
                     eprintln!("Firmware error: {}", msg);
                 }
             }
-            _ => {}
+            YieldReason::Reboot | YieldReason::PowerOff => {
+                // The firmware asked to restart or shut down (e.g. the
+                // `reboot` CLI command); the radio goes down with it
+                self.request_lifecycle(result.reason, ctx);
+            }
         }
 
         // Emit serial TX data if any
@@ -483,7 +637,7 @@ impl FirmwareEntity for RepeaterFirmware {
     
     fn step_begin(&mut self, event: &Event) {
         // Update current time
-        self.current_millis = event.time.as_micros() / 1000;
+        self.current_millis = local_millis(self.clock_drift.as_ref(), event.time);
         
         // Process event payload to inject data into DLL
         match &event.payload {
@@ -514,6 +668,9 @@ impl FirmwareEntity for RepeaterFirmware {
         }
         
         // Begin async step
+        if let Some(environment) = &mut self.environment {
+            environment.update(&mut self.node, event.time);
+        }
         let rtc_secs = self.initial_rtc + (self.current_millis / 1000) as u32;
         self.node.step_begin(self.current_millis, rtc_secs);
     }
@@ -548,6 +705,7 @@ impl FirmwareEntity for RepeaterFirmware {
         FirmwareStepResult {
             reason: result.reason,
             wake_millis: result.wake_millis,
+            wake_delay: wake_delay(self.clock_drift.as_ref(), self.current_millis, result.wake_millis),
             radio_tx_data,
             serial_tx_data,
             log_output: result.log_output(),
@@ -593,6 +751,14 @@ pub struct CompanionFirmware {
     wake_millis: u64,
     // Startup time in microseconds - events before this are dropped
     startup_time_us: u64,
+    // Temperature-dependent error of the node's clock crystal
+    clock_drift: Option<CrystalDrift>,
+    // Battery ran out or power was cut - events are dropped until power returns
+    powered_off: bool,
+    // Configuration the DLL node was created with, to boot it again on reboot
+    node_config: NodeConfig,
+    // Barometer reported in environment telemetry
+    environment: Option<EnvironmentSensor>,
 }
 
 impl CompanionFirmware {
@@ -653,6 +819,8 @@ impl CompanionFirmware {
         let node = OwnedFirmwareNode::new(dll, &node_config)
             .map_err(|e| FirmwareError::Dll(e))?;
 
+        let environment = EnvironmentSensor::new(sim_params, config.base.rng_seed);
+
         Ok(CompanionFirmware {
             id,
             name,
@@ -666,9 +834,35 @@ impl CompanionFirmware {
             awaiting_tx_complete: false,
             wake_millis: 0,
             startup_time_us: sim_params.startup_time_us,
+            clock_drift: sim_params.clock_drift,
+            powered_off: false,
+            node_config,
+            environment,
         })
     }
 
+    /// Boot the DLL node afresh at the current time, dropping everything the
+    /// firmware held in RAM.
+    fn reboot(&mut self, ctx: &mut SimContext) {
+        let rtc_secs = self.initial_rtc + (self.current_millis / 1000) as u32;
+        let config = self.node_config.clone().with_initial_time(self.current_millis, rtc_secs);
+        self.node.reboot(&config);
+        self.pending_tx = None;
+        self.awaiting_tx_complete = false;
+        ctx.post_immediate(vec![self.id], EventPayload::Timer { timer_id: 1 });
+    }
+
+    /// Pass a restart or shutdown the firmware asked for to the radio, which
+    /// hands it back to the firmware.
+    fn request_lifecycle(&self, reason: YieldReason, ctx: &mut SimContext) {
+        let payload = if reason == YieldReason::PowerOff {
+            EventPayload::PowerOff(mcsim_common::PowerOffEvent { reason: "firmware shutdown".to_string() })
+        } else {
+            EventPayload::Reboot(mcsim_common::RebootEvent { reason: "firmware reboot".to_string() })
+        };
+        ctx.post_immediate(vec![self.attached_radio], payload);
+    }
+
     /// Get the node name.
     pub fn name(&self) -> &str {
         &self.name
@@ -696,10 +890,53 @@ impl Entity for CompanionFirmware {
     }
 
     fn handle_event(&mut self, event: &Event, ctx: &mut SimContext) -> Result<(), SimError> {
-        self.current_millis = event.time.as_micros() / 1000;
+        self.current_millis = local_millis(self.clock_drift.as_ref(), event.time);
         // Clone the tracer to avoid borrow conflict with ctx
         let tracer = ctx.tracer().clone();
 
+        // Battery level is read by the firmware when asked for it; a depleted
+        // battery powers the node off for the rest of the simulation
+        if let EventPayload::BatteryLevel(battery) = &event.payload {
+            self.node.set_battery_millivolts(battery.millivolts);
+            if battery.depleted && !self.powered_off {
+                log::info!("[{}] Battery depleted, powering off", self.name);
+                self.powered_off = true;
+            }
+            return Ok(());
+        }
+        if let EventPayload::PowerOff(power_off) = &event.payload {
+            if !self.powered_off {
+                log::info!("[{}] Powering off: {}", self.name, power_off.reason);
+                self.powered_off = true;
+            }
+            return Ok(());
+        }
+        if let EventPayload::PowerOn(power_on) = &event.payload {
+            if self.powered_off {
+                log::info!("[{}] Powering on: {}", self.name, power_on.reason);
+                self.powered_off = false;
+                // A transmission cut short by the outage never completes
+                if self.awaiting_tx_complete {
+                    self.node.notify_tx_complete();
+                    self.awaiting_tx_complete = false;
+                    self.pending_tx = None;
+                }
+                // Wake timers lapsed while off; resume stepping now
+                ctx.post_immediate(vec![self.id], EventPayload::Timer { timer_id: 1 });
+            }
+            return Ok(());
+        }
+        if let EventPayload::Reboot(reboot) = &event.payload {
+            if !self.powered_off {
+                log::info!("[{}] Rebooting: {}", self.name, reboot.reason);
+                self.reboot(ctx);
+            }
+            return Ok(());
+        }
+        if self.powered_off {
+            return Ok(());
+        }
+
         // Check if we're still in startup delay period
         let event_time_us = event.time.as_micros();
         if event_time_us < self.startup_time_us {
@@ -715,6 +952,13 @@ impl Entity for CompanionFirmware {
         // Log event received
         tracer.log_event_received(Some(&self.name), self.id, event.time, event);
 
+        // Channel activity only changes what the next CAD query returns; the
+        // firmware polls it on its own schedule, so no step is needed
+        if let EventPayload::ChannelActivity(activity) = &event.payload {
+            self.node.set_channel_busy(activity.busy);
+            return Ok(());
+        }
+
         // Log step begin with trigger event description
         let trigger_desc = describe_event(&event.payload);
         tracer.log_firmware_step_begin(Some(&self.name), self.id, event.time, &trigger_desc);
@@ -757,6 +1001,9 @@ impl Entity for CompanionFirmware {
         }
 
         // Step the firmware
+        if let Some(environment) = &mut self.environment {
+            environment.update(&mut self.node, event.time);
+        }
         let rtc_secs = self.initial_rtc + (self.current_millis / 1000) as u32;
         let result = self.node.step(self.current_millis, rtc_secs);
 
@@ -798,16 +1045,18 @@ impl Entity for CompanionFirmware {
                     vec![self.attached_radio],
                     EventPayload::RadioTxRequest(mcsim_common::RadioTxRequestEvent {
                         packet: mcsim_common::LoraPacket::new(tx_data),
+                        reported_airtime_ms: Some(airtime_ms),
+                        queue_depth: None,
                     }),
                 );
             }
             YieldReason::Idle => {
                 if result.wake_millis > self.current_millis {
-                    let delay_us = (result.wake_millis - self.current_millis) * 1000;
-                    let delay_ms = delay_us / 1000;
+                    let delay = wake_delay(self.clock_drift.as_ref(), self.current_millis, result.wake_millis);
+                    let delay_ms = delay.as_micros() / 1000;
                     tracer.log_timer_scheduled(Some(&self.name), self.id, event.time, 1, delay_ms);
                     ctx.post_event(
-                        SimTime::from_micros(delay_us),
+                        delay,
                         vec![self.id],
                         EventPayload::Timer { timer_id: 1 },
                     );
@@ -822,7 +1071,11 @@ impl Entity for CompanionFirmware {
                     eprintln!("Firmware error: {}", msg);
                 }
             }
-            _ => {}
+            YieldReason::Reboot | YieldReason::PowerOff => {
+                // The firmware asked to restart or shut down (e.g. the
+                // `reboot` CLI command); the radio goes down with it
+                self.request_lifecycle(result.reason, ctx);
+            }
         }
 
         // Emit serial TX data if any
@@ -872,7 +1125,7 @@ impl FirmwareEntity for CompanionFirmware {
     
     fn step_begin(&mut self, event: &Event) {
         // Update current time
-        self.current_millis = event.time.as_micros() / 1000;
+        self.current_millis = local_millis(self.clock_drift.as_ref(), event.time);
         
         // Process event payload to inject data into DLL
         match &event.payload {
@@ -903,6 +1156,9 @@ impl FirmwareEntity for CompanionFirmware {
         }
         
         // Begin async step
+        if let Some(environment) = &mut self.environment {
+            environment.update(&mut self.node, event.time);
+        }
         let rtc_secs = self.initial_rtc + (self.current_millis / 1000) as u32;
         self.node.step_begin(self.current_millis, rtc_secs);
     }
@@ -937,6 +1193,7 @@ impl FirmwareEntity for CompanionFirmware {
         FirmwareStepResult {
             reason: result.reason,
             wake_millis: result.wake_millis,
+            wake_delay: wake_delay(self.clock_drift.as_ref(), self.current_millis, result.wake_millis),
             radio_tx_data,
             serial_tx_data,
             log_output: result.log_output(),
@@ -956,6 +1213,9 @@ pub struct RoomServerConfig {
     pub base: FirmwareConfig,
     /// Room identifier.
     pub room_id: [u8; 16],
+    /// Flood packets that have travelled this many hops are not forwarded
+    /// (None keeps the firmware's setting).
+    pub flood_max: Option<u8>,
 }
 
 impl Default for RoomServerConfig {
@@ -963,6 +1223,7 @@ impl Default for RoomServerConfig {
         RoomServerConfig {
             base: FirmwareConfig::default(),
             room_id: [0u8; 16],
+            flood_max: None,
         }
     }
 }
@@ -985,6 +1246,14 @@ pub struct RoomServerFirmware {
     wake_millis: u64,
     // Startup time in microseconds - events before this are dropped
     startup_time_us: u64,
+    // Temperature-dependent error of the node's clock crystal
+    clock_drift: Option<CrystalDrift>,
+    // Battery ran out or power was cut - events are dropped until power returns
+    powered_off: bool,
+    // Configuration the DLL node was created with, to boot it again on reboot
+    node_config: NodeConfig,
+    // Barometer reported in environment telemetry
+    environment: Option<EnvironmentSensor>,
 }
 
 impl RoomServerFirmware {
@@ -1036,12 +1305,15 @@ impl RoomServerFirmware {
             .with_spin_logging(
                 sim_params.log_spin_detection,
                 sim_params.log_loop_iterations,
-            );
+            )
+            .with_flood_max(config.flood_max.unwrap_or(0));
 
         // Create the persistent node
         let node = OwnedFirmwareNode::new(dll, &node_config)
             .map_err(|e| FirmwareError::Dll(e))?;
 
+        let environment = EnvironmentSensor::new(sim_params, config.base.rng_seed);
+
         Ok(RoomServerFirmware {
             id,
             name,
@@ -1055,9 +1327,35 @@ impl RoomServerFirmware {
             awaiting_tx_complete: false,
             wake_millis: 0,
             startup_time_us: sim_params.startup_time_us,
+            clock_drift: sim_params.clock_drift,
+            powered_off: false,
+            node_config,
+            environment,
         })
     }
 
+    /// Boot the DLL node afresh at the current time, dropping everything the
+    /// firmware held in RAM.
+    fn reboot(&mut self, ctx: &mut SimContext) {
+        let rtc_secs = self.initial_rtc + (self.current_millis / 1000) as u32;
+        let config = self.node_config.clone().with_initial_time(self.current_millis, rtc_secs);
+        self.node.reboot(&config);
+        self.pending_tx = None;
+        self.awaiting_tx_complete = false;
+        ctx.post_immediate(vec![self.id], EventPayload::Timer { timer_id: 1 });
+    }
+
+    /// Pass a restart or shutdown the firmware asked for to the radio, which
+    /// hands it back to the firmware.
+    fn request_lifecycle(&self, reason: YieldReason, ctx: &mut SimContext) {
+        let payload = if reason == YieldReason::PowerOff {
+            EventPayload::PowerOff(mcsim_common::PowerOffEvent { reason: "firmware shutdown".to_string() })
+        } else {
+            EventPayload::Reboot(mcsim_common::RebootEvent { reason: "firmware reboot".to_string() })
+        };
+        ctx.post_immediate(vec![self.attached_radio], payload);
+    }
+
     /// Get the node name.
     pub fn name(&self) -> &str {
         &self.name
@@ -1085,10 +1383,53 @@ impl Entity for RoomServerFirmware {
     }
 
     fn handle_event(&mut self, event: &Event, ctx: &mut SimContext) -> Result<(), SimError> {
-        self.current_millis = event.time.as_micros() / 1000;
+        self.current_millis = local_millis(self.clock_drift.as_ref(), event.time);
         // Clone the tracer to avoid borrow conflict with ctx
         let tracer = ctx.tracer().clone();
 
+        // Battery level is read by the firmware when asked for it; a depleted
+        // battery powers the node off for the rest of the simulation
+        if let EventPayload::BatteryLevel(battery) = &event.payload {
+            self.node.set_battery_millivolts(battery.millivolts);
+            if battery.depleted && !self.powered_off {
+                log::info!("[{}] Battery depleted, powering off", self.name);
+                self.powered_off = true;
+            }
+            return Ok(());
+        }
+        if let EventPayload::PowerOff(power_off) = &event.payload {
+            if !self.powered_off {
+                log::info!("[{}] Powering off: {}", self.name, power_off.reason);
+                self.powered_off = true;
+            }
+            return Ok(());
+        }
+        if let EventPayload::PowerOn(power_on) = &event.payload {
+            if self.powered_off {
+                log::info!("[{}] Powering on: {}", self.name, power_on.reason);
+                self.powered_off = false;
+                // A transmission cut short by the outage never completes
+                if self.awaiting_tx_complete {
+                    self.node.notify_tx_complete();
+                    self.awaiting_tx_complete = false;
+                    self.pending_tx = None;
+                }
+                // Wake timers lapsed while off; resume stepping now
+                ctx.post_immediate(vec![self.id], EventPayload::Timer { timer_id: 1 });
+            }
+            return Ok(());
+        }
+        if let EventPayload::Reboot(reboot) = &event.payload {
+            if !self.powered_off {
+                log::info!("[{}] Rebooting: {}", self.name, reboot.reason);
+                self.reboot(ctx);
+            }
+            return Ok(());
+        }
+        if self.powered_off {
+            return Ok(());
+        }
+
         // Check if we're still in startup delay period
         let event_time_us = event.time.as_micros();
         if event_time_us < self.startup_time_us {
@@ -1104,6 +1445,13 @@ impl Entity for RoomServerFirmware {
         // Log event received
         tracer.log_event_received(Some(&self.name), self.id, event.time, event);
 
+        // Channel activity only changes what the next CAD query returns; the
+        // firmware polls it on its own schedule, so no step is needed
+        if let EventPayload::ChannelActivity(activity) = &event.payload {
+            self.node.set_channel_busy(activity.busy);
+            return Ok(());
+        }
+
         // Log step begin with trigger event description
         let trigger_desc = describe_event(&event.payload);
         tracer.log_firmware_step_begin(Some(&self.name), self.id, event.time, &trigger_desc);
@@ -1139,6 +1487,9 @@ impl Entity for RoomServerFirmware {
         }
 
         // Step the firmware
+        if let Some(environment) = &mut self.environment {
+            environment.update(&mut self.node, event.time);
+        }
         let rtc_secs = self.initial_rtc + (self.current_millis / 1000) as u32;
         let result = self.node.step(self.current_millis, rtc_secs);
 
@@ -1180,16 +1531,18 @@ impl Entity for RoomServerFirmware {
                     vec![self.attached_radio],
                     EventPayload::RadioTxRequest(mcsim_common::RadioTxRequestEvent {
                         packet: mcsim_common::LoraPacket::new(tx_data),
+                        reported_airtime_ms: Some(airtime_ms),
+                        queue_depth: None,
                     }),
                 );
             }
             YieldReason::Idle => {
                 if result.wake_millis > self.current_millis {
-                    let delay_us = (result.wake_millis - self.current_millis) * 1000;
-                    let delay_ms = delay_us / 1000;
+                    let delay = wake_delay(self.clock_drift.as_ref(), self.current_millis, result.wake_millis);
+                    let delay_ms = delay.as_micros() / 1000;
                     tracer.log_timer_scheduled(Some(&self.name), self.id, event.time, 1, delay_ms);
                     ctx.post_event(
-                        SimTime::from_micros(delay_us),
+                        delay,
                         vec![self.id],
                         EventPayload::Timer { timer_id: 1 },
                     );
@@ -1204,7 +1557,11 @@ impl Entity for RoomServerFirmware {
                     eprintln!("Firmware error: {}", msg);
                 }
             }
-            _ => {}
+            YieldReason::Reboot | YieldReason::PowerOff => {
+                // The firmware asked to restart or shut down (e.g. the
+                // `reboot` CLI command); the radio goes down with it
+                self.request_lifecycle(result.reason, ctx);
+            }
         }
 
         // Emit serial TX data if any
@@ -1254,7 +1611,7 @@ impl FirmwareEntity for RoomServerFirmware {
     
     fn step_begin(&mut self, event: &Event) {
         // Update current time
-        self.current_millis = event.time.as_micros() / 1000;
+        self.current_millis = local_millis(self.clock_drift.as_ref(), event.time);
         
         // Process event payload to inject data into DLL
         match &event.payload {
@@ -1285,6 +1642,9 @@ impl FirmwareEntity for RoomServerFirmware {
         }
         
         // Begin async step
+        if let Some(environment) = &mut self.environment {
+            environment.update(&mut self.node, event.time);
+        }
         let rtc_secs = self.initial_rtc + (self.current_millis / 1000) as u32;
         self.node.step_begin(self.current_millis, rtc_secs);
     }
@@ -1319,6 +1679,7 @@ impl FirmwareEntity for RoomServerFirmware {
         FirmwareStepResult {
             reason: result.reason,
             wake_millis: result.wake_millis,
+            wake_delay: wake_delay(self.clock_drift.as_ref(), self.current_millis, result.wake_millis),
             radio_tx_data,
             serial_tx_data,
             log_output: result.log_output(),
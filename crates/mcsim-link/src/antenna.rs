@@ -0,0 +1,345 @@
+//! Antenna gain and radiation patterns.
+//!
+//! An [`Antenna`] combines a peak gain, a boresight orientation and an
+//! [`AntennaPattern`] giving the gain relative to the peak in any direction.
+//! Link prediction adds the transmitter's gain towards the receiver and the
+//! receiver's gain towards the transmitter to the link budget, so a sector
+//! antenna pointed away from a neighbor predicts a weaker link than the same
+//! antenna pointed at it.
+//!
+//! Directions are given as an azimuth (degrees clockwise from true north) and
+//! an elevation (degrees above the horizon). Patterns are separable: the gain
+//! in a direction is the sum of the azimuth cut at the horizontal offset from
+//! boresight and the elevation cut at the vertical offset.
+//!
+//! Pattern tables can be exported from NEC or a vendor datasheet as a CSV with
+//! one cut point per line:
+//!
+//! ```text
+//! # plane, angle from boresight (deg), gain relative to peak (dB)
+//! az, 0, 0
+//! az, 60, -3
+//! az, 180, -20
+//! el, 0, 0
+//! el, 15, -3
+//! el, 90, -25
+//! ```
+
+use std::path::Path;
+
+use mcsim_model::properties::{
+    NodeScope, ResolvedProperties, RADIO_ANTENNA_AZIMUTH_DEG, RADIO_ANTENNA_DOWNTILT_DEG,
+    RADIO_ANTENNA_GAIN_DBI, RADIO_ANTENNA_PATTERN,
+};
+
+use crate::predict::LinkPredictionError;
+
+/// Gain of an antenna relative to its peak, as a function of direction.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum AntennaPattern {
+    /// Same gain in every direction.
+    #[default]
+    Isotropic,
+    /// `cos^n` main lobe in each plane, sized by its half-power beamwidth.
+    Cosine {
+        /// Horizontal half-power beamwidth in degrees (360 for omnidirectional).
+        horizontal_beamwidth_deg: f64,
+        /// Vertical half-power beamwidth in degrees.
+        vertical_beamwidth_deg: f64,
+        /// Attenuation outside the main lobe, in dB.
+        front_to_back_db: f64,
+    },
+    /// Measured or simulated azimuth and elevation cuts.
+    Table {
+        /// (degrees from boresight, relative gain in dB), sorted by angle.
+        azimuth: Vec<(f64, f64)>,
+        /// (degrees above boresight, relative gain in dB), sorted by angle.
+        elevation: Vec<(f64, f64)>,
+    },
+}
+
+/// Default front-to-back ratio of a cosine pattern.
+const DEFAULT_FRONT_TO_BACK_DB: f64 = 20.0;
+
+impl AntennaPattern {
+    /// Parse a pattern specification as used by `radio/antenna_pattern`.
+    ///
+    /// Accepts `isotropic`, `cosine:<h_bw>,<v_bw>[,<front_to_back_db>]` or
+    /// `file:<path>`.
+    pub fn parse(spec: &str) -> Result<Self, LinkPredictionError> {
+        let spec = spec.trim();
+        if spec.eq_ignore_ascii_case("isotropic") {
+            return Ok(AntennaPattern::Isotropic);
+        }
+        if let Some(args) = spec.strip_prefix("cosine:") {
+            let values = args
+                .split(',')
+                .map(|v| v.trim().parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| pattern_error(spec, &e.to_string()))?;
+            let (h, v, ftb) = match values[..] {
+                [h, v] => (h, v, DEFAULT_FRONT_TO_BACK_DB),
+                [h, v, ftb] => (h, v, ftb),
+                _ => return Err(pattern_error(spec, "expected 2 or 3 values")),
+            };
+            if !(h > 0.0 && v > 0.0 && ftb >= 0.0) {
+                return Err(pattern_error(spec, "beamwidths must be positive and front-to-back non-negative"));
+            }
+            return Ok(AntennaPattern::Cosine {
+                horizontal_beamwidth_deg: h,
+                vertical_beamwidth_deg: v,
+                front_to_back_db: ftb,
+            });
+        }
+        if let Some(path) = spec.strip_prefix("file:") {
+            return Self::load_table(path.trim());
+        }
+        Err(pattern_error(spec, "expected 'isotropic', 'cosine:...' or 'file:...'"))
+    }
+
+    /// Load a pattern table from a CSV file (see the [module docs](self)).
+    pub fn load_table<P: AsRef<Path>>(path: P) -> Result<Self, LinkPredictionError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            LinkPredictionError::ConfigError(format!("Failed to read antenna pattern {}: {}", path.display(), e))
+        })?;
+        Self::parse_table(&text)
+            .map_err(|e| LinkPredictionError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parse a pattern table from CSV text.
+    pub fn parse_table(text: &str) -> Result<Self, LinkPredictionError> {
+        let mut azimuth = Vec::new();
+        let mut elevation = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let bad_line = || {
+                LinkPredictionError::ConfigError(format!(
+                    "Invalid antenna pattern line {}: '{}' (expected 'az|el,angle_deg,gain_db')",
+                    i + 1,
+                    line
+                ))
+            };
+            let [plane, angle, gain] = fields[..] else {
+                return Err(bad_line());
+            };
+            let point = (
+                angle.parse::<f64>().map_err(|_| bad_line())?,
+                gain.parse::<f64>().map_err(|_| bad_line())?,
+            );
+            match plane.to_ascii_lowercase().as_str() {
+                "az" => azimuth.push((wrap_deg(point.0), point.1)),
+                "el" => elevation.push(point),
+                _ => return Err(bad_line()),
+            }
+        }
+        if azimuth.is_empty() && elevation.is_empty() {
+            return Err(LinkPredictionError::ConfigError("Antenna pattern table is empty".to_string()));
+        }
+        azimuth.sort_by(|a, b| a.0.total_cmp(&b.0));
+        elevation.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(AntennaPattern::Table { azimuth, elevation })
+    }
+
+    /// Gain relative to the peak, in dB (zero or negative), at the given
+    /// offsets from boresight.
+    ///
+    /// `azimuth_offset_deg` is measured clockwise and wraps at ±180;
+    /// `elevation_offset_deg` is positive above boresight.
+    pub fn relative_gain_db(&self, azimuth_offset_deg: f64, elevation_offset_deg: f64) -> f64 {
+        let az = wrap_deg(azimuth_offset_deg);
+        match self {
+            AntennaPattern::Isotropic => 0.0,
+            AntennaPattern::Cosine {
+                horizontal_beamwidth_deg,
+                vertical_beamwidth_deg,
+                front_to_back_db,
+            } => {
+                let gain = cosine_lobe_db(az, *horizontal_beamwidth_deg)
+                    + cosine_lobe_db(elevation_offset_deg, *vertical_beamwidth_deg);
+                gain.max(-front_to_back_db)
+            }
+            AntennaPattern::Table { azimuth, elevation } => {
+                interpolate_wrapped(azimuth, az) + interpolate(elevation, elevation_offset_deg)
+            }
+        }
+    }
+}
+
+fn pattern_error(spec: &str, reason: &str) -> LinkPredictionError {
+    LinkPredictionError::ConfigError(format!("Invalid antenna pattern '{}': {}", spec, reason))
+}
+
+/// Wrap an angle to (-180, 180].
+fn wrap_deg(angle: f64) -> f64 {
+    let wrapped = angle.rem_euclid(360.0);
+    if wrapped > 180.0 {
+        wrapped - 360.0
+    } else {
+        wrapped
+    }
+}
+
+/// Relative gain of a `cos^n` lobe with the given half-power beamwidth.
+fn cosine_lobe_db(offset_deg: f64, beamwidth_deg: f64) -> f64 {
+    if beamwidth_deg >= 360.0 {
+        return 0.0;
+    }
+    let half = (beamwidth_deg / 2.0).min(89.9).to_radians();
+    let n = 0.5f64.ln() / half.cos().ln();
+    let c = offset_deg.to_radians().cos();
+    if c <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    10.0 * n * c.log10()
+}
+
+/// Linear interpolation in a sorted cut, clamped at the ends.
+fn interpolate(points: &[(f64, f64)], x: f64) -> f64 {
+    match points {
+        [] => 0.0,
+        [(_, y)] => *y,
+        _ => {
+            let i = points.partition_point(|p| p.0 < x);
+            if i == 0 {
+                return points[0].1;
+            }
+            if i == points.len() {
+                return points[points.len() - 1].1;
+            }
+            let (x0, y0) = points[i - 1];
+            let (x1, y1) = points[i];
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        }
+    }
+}
+
+/// Linear interpolation in a sorted azimuth cut that wraps around at ±180.
+fn interpolate_wrapped(points: &[(f64, f64)], x: f64) -> f64 {
+    let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+        return 0.0;
+    };
+    if x >= first.0 && x <= last.0 {
+        return interpolate(points, x);
+    }
+    // Between the last point and the first one, crossing ±180
+    let span = first.0 + 360.0 - last.0;
+    if span <= 0.0 {
+        return first.1;
+    }
+    let dx = if x > last.0 { x - last.0 } else { x + 360.0 - last.0 };
+    last.1 + (first.1 - last.1) * dx / span
+}
+
+/// An antenna's peak gain, orientation and pattern.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Antenna {
+    /// Peak gain in dBi.
+    pub gain_dbi: f64,
+    /// Boresight azimuth in degrees clockwise from true north.
+    pub azimuth_deg: f64,
+    /// Boresight downtilt in degrees below the horizon.
+    pub downtilt_deg: f64,
+    /// Radiation pattern relative to the peak.
+    pub pattern: AntennaPattern,
+}
+
+impl Antenna {
+    /// An isotropic antenna with the given gain.
+    pub fn isotropic(gain_dbi: f64) -> Self {
+        Self {
+            gain_dbi,
+            ..Default::default()
+        }
+    }
+
+    /// Read a node's antenna from its `radio/antenna_*` properties.
+    pub fn from_properties(props: &ResolvedProperties<NodeScope>) -> Result<Self, LinkPredictionError> {
+        Ok(Self {
+            gain_dbi: props.get(&RADIO_ANTENNA_GAIN_DBI),
+            azimuth_deg: props.get(&RADIO_ANTENNA_AZIMUTH_DEG),
+            downtilt_deg: props.get(&RADIO_ANTENNA_DOWNTILT_DEG),
+            pattern: AntennaPattern::parse(&props.get(&RADIO_ANTENNA_PATTERN))?,
+        })
+    }
+
+    /// Gain in dBi towards a direction given as azimuth from true north and
+    /// elevation above the horizon.
+    pub fn gain_towards_db(&self, azimuth_deg: f64, elevation_deg: f64) -> f64 {
+        self.gain_dbi
+            + self
+                .pattern
+                .relative_gain_db(azimuth_deg - self.azimuth_deg, elevation_deg + self.downtilt_deg)
+    }
+}
+
+/// Initial great-circle bearing from one point to another, in degrees
+/// clockwise from true north.
+pub(crate) fn bearing_deg(from_lat: f64, from_lon: f64, to_lat: f64, to_lon: f64) -> f64 {
+    let lat1 = from_lat.to_radians();
+    let lat2 = to_lat.to_radians();
+    let dlon = (to_lon - from_lon).to_radians();
+    let y = dlon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_pattern() {
+        let pattern = AntennaPattern::parse("cosine:90,30").unwrap();
+        assert!(pattern.relative_gain_db(0.0, 0.0).abs() < 1e-9);
+        // Half power at half the beamwidth in each plane
+        assert!((pattern.relative_gain_db(45.0, 0.0) + 3.0103).abs() < 1e-3);
+        assert!((pattern.relative_gain_db(0.0, -15.0) + 3.0103).abs() < 1e-3);
+        // Behind the antenna the front-to-back ratio applies
+        assert_eq!(pattern.relative_gain_db(180.0, 0.0), -DEFAULT_FRONT_TO_BACK_DB);
+
+        let omni = AntennaPattern::parse("cosine:360,20,30").unwrap();
+        assert_eq!(omni.relative_gain_db(137.0, 0.0), 0.0);
+        assert!(AntennaPattern::parse("cosine:90").is_err());
+        assert!(AntennaPattern::parse("yagi").is_err());
+    }
+
+    #[test]
+    fn test_table_pattern() {
+        let pattern = AntennaPattern::parse_table(
+            "# sector\naz, 0, 0\naz, 90, -10\naz, -90, -10\naz, 180, -20\nel, 0, 0\nel, 20, -6\n",
+        )
+        .unwrap();
+        assert_eq!(pattern.relative_gain_db(0.0, 0.0), 0.0);
+        assert_eq!(pattern.relative_gain_db(45.0, 0.0), -5.0);
+        assert_eq!(pattern.relative_gain_db(-135.0, 10.0), -18.0);
+        // Elevation clamps beyond the last point
+        assert_eq!(pattern.relative_gain_db(0.0, 45.0), -6.0);
+        assert!(AntennaPattern::parse_table("az, 0\n").is_err());
+    }
+
+    #[test]
+    fn test_antenna_orientation() {
+        let sector = Antenna {
+            gain_dbi: 12.0,
+            azimuth_deg: 90.0,
+            downtilt_deg: 5.0,
+            pattern: AntennaPattern::parse("cosine:60,10,25").unwrap(),
+        };
+        // On boresight: east and 5° below the horizon
+        assert!((sector.gain_towards_db(90.0, -5.0) - 12.0).abs() < 1e-9);
+        assert!(sector.gain_towards_db(90.0, 0.0) < 12.0);
+        assert_eq!(sector.gain_towards_db(270.0, -5.0), 12.0 - 25.0);
+
+        assert!((bearing_deg(47.0, -122.0, 47.0, -121.0) - 89.63).abs() < 0.01);
+        assert!((bearing_deg(47.0, -122.0, 46.0, -122.0) - 180.0).abs() < 1e-9);
+    }
+}
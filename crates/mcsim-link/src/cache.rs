@@ -0,0 +1,454 @@
+//! Persistent cache of link predictions.
+//!
+//! Predicting a link samples a terrain profile and runs ITM, which for a
+//! large scenario dominates the time to build it, and re-running the same
+//! scenario recomputes identical paths. [`LinkCache`] keeps every prediction
+//! keyed by the link's geometry (endpoint coordinates, antenna heights and
+//! frequency) and a hash of the remaining inputs (TX power, spreading factor,
+//! terrain samples, antennas and [`LinkPredictionParams`]), and saves them to
+//! a small binary file, by convention next to the elevation tile cache:
+//!
+//! ```ignore
+//! let path = cache_dir.join(LinkCache::FILE_NAME);
+//! let cache = LinkCache::load(&path)?;
+//! let prediction = cache.predict(&elevation, &itm, &config, &params)?;
+//! cache.save(&path)?;
+//! ```
+//!
+//! The key doesn't cover the elevation data itself: a cache file belongs to
+//! one elevation source. Failed predictions aren't cached.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use mcsim_itm::Itm;
+
+use crate::predict::{
+    predict_link_with_elevation_and_params, AntennaHeightMode, ElevationSource, LinkPrediction,
+    LinkPredictionConfig, LinkPredictionError, LinkPredictionParams, LinkStatus, PathInfo, PredictionMethod,
+    RadioParams, TerrainInfo,
+};
+
+/// File signature.
+const MAGIC: &[u8; 4] = b"MCLC";
+/// Format version; bumped whenever the layout of an entry changes.
+const VERSION: u32 = 2;
+
+/// FNV-1a offset basis.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+/// FNV-1a prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Link geometry and a hash of every other prediction input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CacheKey {
+    /// Bits of from/to latitude and longitude, from/to height and frequency.
+    geometry: [u64; 7],
+    height_mode: u8,
+    params_hash: u64,
+}
+
+impl CacheKey {
+    fn new(config: &LinkPredictionConfig, params: &LinkPredictionParams) -> Self {
+        // FNV-1a rather than the std hasher, whose output may change between
+        // Rust releases and would invalidate saved caches. Debug output of
+        // floats round-trips exactly.
+        let inputs = format!(
+            "{}|{}|{}|{:?}|{:?}|{:?}",
+            config.tx_power_dbm,
+            config.spreading_factor,
+            config.terrain_samples,
+            config.from_antenna,
+            config.to_antenna,
+            params
+        );
+        let params_hash = inputs
+            .bytes()
+            .fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME));
+        Self {
+            geometry: [
+                config.from_lat.to_bits(),
+                config.from_lon.to_bits(),
+                config.to_lat.to_bits(),
+                config.to_lon.to_bits(),
+                config.from_height.to_bits(),
+                config.to_height.to_bits(),
+                config.freq_mhz.to_bits(),
+            ],
+            height_mode: height_mode_code(config.height_mode),
+            params_hash,
+        }
+    }
+}
+
+/// Cache hits and misses since a [`LinkCache`] was loaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinkCacheStats {
+    /// Predictions served from the cache.
+    pub hits: u64,
+    /// Predictions computed and added to the cache.
+    pub misses: u64,
+}
+
+/// Link predictions keyed by geometry, shareable across threads.
+#[derive(Debug, Default)]
+pub struct LinkCache {
+    entries: Mutex<HashMap<CacheKey, LinkPrediction>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl LinkCache {
+    /// Conventional file name of a cache in the elevation cache directory.
+    pub const FILE_NAME: &'static str = "link_predictions.bin";
+
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a cache saved by [`save`](Self::save). A missing file gives an
+    /// empty cache; a file of another format version is an `InvalidData`
+    /// error.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e),
+        };
+        let entries = decode(&bytes).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "not a link prediction cache of this version")
+        })?;
+        Ok(Self { entries: Mutex::new(entries), ..Self::default() })
+    }
+
+    /// Save the cache, replacing `path` atomically.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bytes = encode(&self.entries.lock().unwrap());
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Number of cached predictions.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether the cache holds no prediction.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Cached prediction for `config` and `params`, if any.
+    pub fn get(&self, config: &LinkPredictionConfig, params: &LinkPredictionParams) -> Option<LinkPrediction> {
+        self.entries.lock().unwrap().get(&CacheKey::new(config, params)).cloned()
+    }
+
+    /// Cache `prediction` for `config` and `params`.
+    pub fn insert(&self, config: &LinkPredictionConfig, params: &LinkPredictionParams, prediction: LinkPrediction) {
+        self.entries.lock().unwrap().insert(CacheKey::new(config, params), prediction);
+    }
+
+    /// Predict a link, reusing the cached prediction if there is one.
+    ///
+    /// The cache isn't locked while predicting, so threads predicting the
+    /// same uncached link concurrently both compute it.
+    pub fn predict(
+        &self,
+        elevation: &ElevationSource,
+        itm: &Itm,
+        config: &LinkPredictionConfig,
+        params: &LinkPredictionParams,
+    ) -> Result<LinkPrediction, LinkPredictionError> {
+        if let Some(prediction) = self.get(config, params) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(prediction);
+        }
+        let prediction = predict_link_with_elevation_and_params(elevation, itm, config, params)?;
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.insert(config, params, prediction.clone());
+        Ok(prediction)
+    }
+
+    /// Hits and misses of [`predict`](Self::predict) so far.
+    pub fn stats(&self) -> LinkCacheStats {
+        LinkCacheStats { hits: self.hits.load(Ordering::Relaxed), misses: self.misses.load(Ordering::Relaxed) }
+    }
+}
+
+// ============================================================================
+// Binary Format
+// ============================================================================
+
+fn height_mode_code(mode: AntennaHeightMode) -> u8 {
+    match mode {
+        AntennaHeightMode::Agl => 0,
+        AntennaHeightMode::Msl => 1,
+    }
+}
+
+fn height_mode_from_code(code: u8) -> Option<AntennaHeightMode> {
+    match code {
+        0 => Some(AntennaHeightMode::Agl),
+        1 => Some(AntennaHeightMode::Msl),
+        _ => None,
+    }
+}
+
+const METHODS: [PredictionMethod; 3] = [PredictionMethod::Itm, PredictionMethod::FreeSpace, PredictionMethod::Colocated];
+const STATUSES: [LinkStatus; 4] =
+    [LinkStatus::Excellent, LinkStatus::Good, LinkStatus::Marginal, LinkStatus::Unreliable];
+
+/// Little-endian writer.
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u8(&mut self, v: u8) {
+        self.0.push(v);
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn f64(&mut self, v: f64) {
+        self.u64(v.to_bits());
+    }
+}
+
+/// Little-endian reader; every read returns None past the end.
+struct Decoder<'a>(&'a [u8]);
+
+impl Decoder<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (head, rest) = self.0.split_first_chunk::<N>()?;
+        self.0 = rest;
+        Some(*head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        self.u64().map(f64::from_bits)
+    }
+}
+
+fn encode(entries: &HashMap<CacheKey, LinkPrediction>) -> Vec<u8> {
+    let mut out = Encoder(MAGIC.to_vec());
+    out.u32(VERSION);
+    out.u64(entries.len() as u64);
+    for (key, p) in entries {
+        key.geometry.iter().for_each(|&bits| out.u64(bits));
+        out.u8(key.height_mode);
+        out.u64(key.params_hash);
+
+        for v in [p.path.from_lat, p.path.from_lon, p.path.to_lat, p.path.to_lon, p.path.from_height, p.path.to_height] {
+            out.f64(v);
+        }
+        out.u8(height_mode_code(p.path.height_mode));
+        out.f64(p.path.distance_km);
+
+        out.u64(p.terrain.sample_count as u64);
+        for v in [
+            p.terrain.resolution_m,
+            p.terrain.min_elevation,
+            p.terrain.max_elevation,
+            p.terrain.mean_elevation,
+            p.terrain.delta_h,
+        ] {
+            out.f64(v);
+        }
+        out.u8(p.terrain.line_of_sight as u8);
+
+        out.f64(p.radio.freq_mhz);
+        out.u8(p.radio.tx_power_dbm as u8);
+        out.f64(p.radio.noise_floor_dbm);
+        out.u8(p.radio.spreading_factor);
+        out.f64(p.radio.snr_threshold_db);
+
+        out.f64(p.path_loss_db);
+        out.f64(p.clutter_loss_db);
+        out.u8(METHODS.iter().position(|m| *m == p.prediction_method).unwrap() as u8);
+        out.u32(p.itm_warnings);
+        for v in [p.antenna_gain_db, p.snr_db, p.snr_std_dev_db, p.link_margin_db] {
+            out.f64(v);
+        }
+        out.u8(STATUSES.iter().position(|s| *s == p.status).unwrap() as u8);
+    }
+    out.0
+}
+
+fn decode(bytes: &[u8]) -> Option<HashMap<CacheKey, LinkPrediction>> {
+    let mut input = Decoder(bytes);
+    if &input.take::<4>()? != MAGIC || input.u32()? != VERSION {
+        return None;
+    }
+    let count = input.u64()?;
+    let mut entries = HashMap::new();
+    for _ in 0..count {
+        let mut geometry = [0u64; 7];
+        for bits in &mut geometry {
+            *bits = input.u64()?;
+        }
+        let key = CacheKey { geometry, height_mode: input.u8()?, params_hash: input.u64()? };
+
+        let path = PathInfo {
+            from_lat: input.f64()?,
+            from_lon: input.f64()?,
+            to_lat: input.f64()?,
+            to_lon: input.f64()?,
+            from_height: input.f64()?,
+            to_height: input.f64()?,
+            height_mode: height_mode_from_code(input.u8()?)?,
+            distance_km: input.f64()?,
+        };
+        let terrain = TerrainInfo {
+            sample_count: input.u64()? as usize,
+            resolution_m: input.f64()?,
+            min_elevation: input.f64()?,
+            max_elevation: input.f64()?,
+            mean_elevation: input.f64()?,
+            delta_h: input.f64()?,
+            line_of_sight: input.u8()? != 0,
+        };
+        let radio = RadioParams {
+            freq_mhz: input.f64()?,
+            tx_power_dbm: input.u8()? as i8,
+            noise_floor_dbm: input.f64()?,
+            spreading_factor: input.u8()?,
+            snr_threshold_db: input.f64()?,
+        };
+        let prediction = LinkPrediction {
+            path,
+            terrain,
+            radio,
+            path_loss_db: input.f64()?,
+            clutter_loss_db: input.f64()?,
+            prediction_method: *METHODS.get(input.u8()? as usize)?,
+            itm_warnings: input.u32()?,
+            antenna_gain_db: input.f64()?,
+            snr_db: input.f64()?,
+            snr_std_dev_db: input.f64()?,
+            link_margin_db: input.f64()?,
+            status: *STATUSES.get(input.u8()? as usize)?,
+        };
+        entries.insert(key, prediction);
+    }
+    input.0.is_empty().then_some(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LinkPredictionConfig {
+        LinkPredictionConfig {
+            from_lat: 47.0,
+            from_lon: -122.0,
+            to_lat: 47.1,
+            to_lon: -122.0,
+            ..Default::default()
+        }
+    }
+
+    fn prediction() -> LinkPrediction {
+        LinkPrediction {
+            path: PathInfo {
+                from_lat: 47.0,
+                from_lon: -122.0,
+                to_lat: 47.1,
+                to_lon: -122.0,
+                from_height: 2.0,
+                to_height: 2.0,
+                height_mode: AntennaHeightMode::Agl,
+                distance_km: 11.1,
+            },
+            terrain: TerrainInfo {
+                sample_count: 100,
+                resolution_m: 111.0,
+                min_elevation: 10.0,
+                max_elevation: 80.0,
+                mean_elevation: 35.5,
+                delta_h: 42.0,
+                line_of_sight: false,
+            },
+            radio: RadioParams {
+                freq_mhz: 910.525,
+                tx_power_dbm: -3,
+                noise_floor_dbm: -120.0,
+                spreading_factor: 9,
+                snr_threshold_db: -12.5,
+            },
+            path_loss_db: 131.25,
+            clutter_loss_db: 0.0,
+            prediction_method: PredictionMethod::Itm,
+            itm_warnings: 3,
+            antenna_gain_db: 1.5,
+            snr_db: 4.75,
+            snr_std_dev_db: 2.0,
+            link_margin_db: 17.25,
+            status: LinkStatus::Excellent,
+        }
+    }
+
+    #[test]
+    fn test_key_covers_inputs() {
+        let params = LinkPredictionParams::default();
+        let cache = LinkCache::new();
+        cache.insert(&config(), &params, prediction());
+        assert!(cache.get(&config(), &params).is_some());
+
+        let taller = LinkPredictionConfig { to_height: 10.0, ..config() };
+        let louder = LinkPredictionConfig { tx_power_dbm: 14, ..config() };
+        let noisier = LinkPredictionParams { noise_floor_dbm: -110.0, ..params.clone() };
+        assert!(cache.get(&taller, &params).is_none());
+        assert!(cache.get(&louder, &params).is_none());
+        assert!(cache.get(&config(), &noisier).is_none());
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("mcsim-link-cache-{}.bin", std::process::id()));
+        let params = LinkPredictionParams::default();
+        let cache = LinkCache::new();
+        cache.insert(&config(), &params, prediction());
+        cache.save(&path).unwrap();
+
+        let loaded = LinkCache::load(&path).unwrap();
+        let restored = loaded.get(&config(), &params).unwrap();
+        assert_eq!(restored.radio.tx_power_dbm, -3);
+        assert_eq!(restored.terrain.mean_elevation, 35.5);
+        assert_eq!(restored.link_margin_db, 17.25);
+        assert_eq!(restored.status, LinkStatus::Excellent);
+
+        // Truncated files are rejected rather than half-loaded
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(LinkCache::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+        assert!(LinkCache::load(&path).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,429 @@
+//! Calibrating link predictions against field measurements.
+//!
+//! ITM predictions can be consistently off for an area: foliage, buildings
+//! and local antenna installs the terrain model doesn't see. A
+//! [`LinkCalibration`] learns that bias from real measurements. Each
+//! [`FieldMeasurement`] (endpoints, spreading factor, measured RSSI and/or
+//! SNR) is predicted without calibration, and the residuals (measured minus
+//! predicted SNR) are binned into grid cells by the midpoint of the path.
+//! Every cell with enough measurements gets its own [`CalibrationCorrection`]:
+//! the mean residual as an SNR offset and the residuals' standard deviation
+//! as the link's SNR spread. Paths outside those cells use the correction
+//! fitted from all measurements.
+//!
+//! Set [`LinkPredictionParams::calibration`] and every prediction adds the
+//! offset for its midpoint to the median SNR and takes the fitted spread in
+//! place of the terrain-based estimate. Calibrations are saved as JSON:
+//!
+//! ```ignore
+//! let measurements = FieldMeasurement::from_csv(&std::fs::read_to_string("field.csv")?)?;
+//! let calibration = LinkCalibration::fit(&elevation, &itm, &template, &params, &measurements, &CalibrationFit::default())?;
+//! calibration.save("calibration.json")?;
+//!
+//! let params = LinkPredictionParams { calibration: Some(LinkCalibration::load("calibration.json")?), ..params };
+//! ```
+//!
+//! Measurement CSVs need a header row naming the columns `from_lat`,
+//! `from_lon`, `to_lat`, `to_lon`, `sf` and at least one of `rssi_dbm` and
+//! `snr_db`; an optional `tx_power_dbm` column overrides the transmit power of
+//! the template config per row. The measured SNR is used when a row has
+//! both; with only RSSI, the residual is measured minus predicted RSSI.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use mcsim_itm::Itm;
+use rayon::prelude::*;
+use serde_json::{json, Value};
+
+use crate::coverage::BoundingBox;
+use crate::predict::{
+    predict_link_with_elevation_and_params, ElevationSource, LinkPrediction, LinkPredictionConfig,
+    LinkPredictionError, LinkPredictionParams,
+};
+
+/// One real-world measurement of a link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMeasurement {
+    pub from_lat: f64,
+    pub from_lon: f64,
+    pub to_lat: f64,
+    pub to_lon: f64,
+    pub spreading_factor: u8,
+    /// Transmit power, if it differs from the template config.
+    pub tx_power_dbm: Option<i8>,
+    /// Measured RSSI in dBm.
+    pub rssi_dbm: Option<f64>,
+    /// Measured SNR in dB.
+    pub snr_db: Option<f64>,
+}
+
+impl FieldMeasurement {
+    /// Parse measurements from CSV text with a header row.
+    pub fn from_csv(text: &str) -> Result<Vec<Self>, LinkPredictionError> {
+        let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let (_, header) = lines
+            .next()
+            .ok_or_else(|| LinkPredictionError::ConfigError("Measurement CSV is empty".to_string()))?;
+        let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+        let column = |name: &str| columns.iter().position(|c| *c == name);
+        let required = |name: &str| {
+            column(name).ok_or_else(|| {
+                LinkPredictionError::ConfigError(format!("Measurement CSV has no '{}' column", name))
+            })
+        };
+        let (from_lat, from_lon, to_lat, to_lon, sf) =
+            (required("from_lat")?, required("from_lon")?, required("to_lat")?, required("to_lon")?, required("sf")?);
+        let (rssi, snr, tx_power) = (column("rssi_dbm"), column("snr_db"), column("tx_power_dbm"));
+        if rssi.is_none() && snr.is_none() {
+            return Err(LinkPredictionError::ConfigError(
+                "Measurement CSV needs an 'rssi_dbm' or 'snr_db' column".to_string(),
+            ));
+        }
+
+        lines
+            .map(|(index, line)| {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                let invalid = |name: &str| {
+                    LinkPredictionError::ConfigError(format!(
+                        "Invalid '{}' on line {} of the measurement CSV",
+                        name,
+                        index + 1
+                    ))
+                };
+                let optional = |at: Option<usize>, name: &str| -> Result<Option<f64>, LinkPredictionError> {
+                    match at.and_then(|i| fields.get(i)).filter(|v| !v.is_empty()) {
+                        Some(v) => v.parse().map(Some).map_err(|_| invalid(name)),
+                        None => Ok(None),
+                    }
+                };
+                let value = |at: usize, name: &str| optional(Some(at), name)?.ok_or_else(|| invalid(name));
+                let measurement = FieldMeasurement {
+                    from_lat: value(from_lat, "from_lat")?,
+                    from_lon: value(from_lon, "from_lon")?,
+                    to_lat: value(to_lat, "to_lat")?,
+                    to_lon: value(to_lon, "to_lon")?,
+                    spreading_factor: fields.get(sf).and_then(|v| v.parse().ok()).ok_or_else(|| invalid("sf"))?,
+                    tx_power_dbm: match tx_power.and_then(|i| fields.get(i)).filter(|v| !v.is_empty()) {
+                        Some(v) => Some(v.parse().map_err(|_| invalid("tx_power_dbm"))?),
+                        None => None,
+                    },
+                    rssi_dbm: optional(rssi, "rssi_dbm")?,
+                    snr_db: optional(snr, "snr_db")?,
+                };
+                if measurement.rssi_dbm.is_none() && measurement.snr_db.is_none() {
+                    return Err(LinkPredictionError::ConfigError(format!(
+                        "Line {} of the measurement CSV has neither RSSI nor SNR",
+                        index + 1
+                    )));
+                }
+                Ok(measurement)
+            })
+            .collect()
+    }
+
+    /// Measured minus predicted SNR, or RSSI if no SNR was measured.
+    pub fn residual_db(&self, prediction: &LinkPrediction) -> f64 {
+        match (self.snr_db, self.rssi_dbm) {
+            (Some(snr), _) => snr - prediction.snr_db,
+            (None, Some(rssi)) => rssi - (prediction.snr_db + prediction.radio.noise_floor_dbm),
+            (None, None) => 0.0,
+        }
+    }
+}
+
+/// A measurement's residual, placed at the midpoint of its path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationSample {
+    pub lat: f64,
+    pub lon: f64,
+    /// Measured minus predicted SNR (dB).
+    pub residual_db: f64,
+}
+
+/// How a calibration is fitted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationFit {
+    /// Size of the grid cells measurements are grouped by (degrees).
+    pub cell_size_deg: f64,
+    /// Fewest measurements a cell needs for its own correction (at least 2).
+    pub min_samples: usize,
+}
+
+impl Default for CalibrationFit {
+    fn default() -> Self {
+        Self { cell_size_deg: 0.25, min_samples: 5 }
+    }
+}
+
+/// Correction of predicted SNR fitted from measurements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CalibrationCorrection {
+    /// Added to the predicted median SNR (dB).
+    pub offset_db: f64,
+    /// SNR standard deviation replacing the predicted one (dB).
+    pub std_dev_db: f64,
+    /// Measurements the correction was fitted from.
+    pub samples: usize,
+}
+
+impl CalibrationCorrection {
+    /// Mean and sample standard deviation of residuals; None for fewer than two.
+    fn from_residuals(residuals: &[f64]) -> Option<Self> {
+        if residuals.len() < 2 {
+            return None;
+        }
+        let n = residuals.len() as f64;
+        let mean = residuals.iter().sum::<f64>() / n;
+        let variance = residuals.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        Some(Self { offset_db: mean, std_dev_db: variance.sqrt(), samples: residuals.len() })
+    }
+}
+
+/// Correction of the paths whose midpoint lies in an area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegionCalibration {
+    pub bounds: BoundingBox,
+    pub correction: CalibrationCorrection,
+}
+
+/// Per-region corrections of link predictions.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkCalibration {
+    /// Correction of paths outside every region.
+    pub global: CalibrationCorrection,
+    /// Corrections of areas with enough measurements of their own.
+    pub regions: Vec<RegionCalibration>,
+}
+
+impl LinkCalibration {
+    /// Predict every measurement without calibration and fit corrections to
+    /// the residuals. `template` supplies what the measurements don't:
+    /// antenna heights, frequency, TX power and terrain samples.
+    pub fn fit(
+        elevation: &ElevationSource,
+        itm: &Itm,
+        template: &LinkPredictionConfig,
+        params: &LinkPredictionParams,
+        measurements: &[FieldMeasurement],
+        fit: &CalibrationFit,
+    ) -> Result<Self, LinkPredictionError> {
+        let params = LinkPredictionParams { calibration: None, ..params.clone() };
+        let samples = measurements
+            .par_iter()
+            .map(|m| {
+                let config = LinkPredictionConfig {
+                    from_lat: m.from_lat,
+                    from_lon: m.from_lon,
+                    to_lat: m.to_lat,
+                    to_lon: m.to_lon,
+                    spreading_factor: m.spreading_factor,
+                    tx_power_dbm: m.tx_power_dbm.unwrap_or(template.tx_power_dbm),
+                    ..template.clone()
+                };
+                let prediction = predict_link_with_elevation_and_params(elevation, itm, &config, &params)?;
+                Ok(CalibrationSample {
+                    lat: (m.from_lat + m.to_lat) / 2.0,
+                    lon: (m.from_lon + m.to_lon) / 2.0,
+                    residual_db: m.residual_db(&prediction),
+                })
+            })
+            .collect::<Result<Vec<_>, LinkPredictionError>>()?;
+        Self::from_samples(&samples, fit)
+    }
+
+    /// Fit corrections to residuals already computed.
+    pub fn from_samples(samples: &[CalibrationSample], fit: &CalibrationFit) -> Result<Self, LinkPredictionError> {
+        let all: Vec<f64> = samples.iter().map(|s| s.residual_db).collect();
+        let global = CalibrationCorrection::from_residuals(&all).ok_or_else(|| {
+            LinkPredictionError::ConfigError("Calibration needs at least 2 measurements".to_string())
+        })?;
+        if fit.cell_size_deg.is_nan() || fit.cell_size_deg <= 0.0 {
+            return Err(LinkPredictionError::ConfigError(format!(
+                "Invalid calibration cell size {}",
+                fit.cell_size_deg
+            )));
+        }
+
+        let mut cells: BTreeMap<(i64, i64), Vec<f64>> = BTreeMap::new();
+        for sample in samples {
+            let cell = (
+                (sample.lat / fit.cell_size_deg).floor() as i64,
+                (sample.lon / fit.cell_size_deg).floor() as i64,
+            );
+            cells.entry(cell).or_default().push(sample.residual_db);
+        }
+        let regions = cells
+            .into_iter()
+            .filter(|(_, residuals)| residuals.len() >= fit.min_samples)
+            .filter_map(|((row, col), residuals)| {
+                Some(RegionCalibration {
+                    bounds: BoundingBox {
+                        min_lat: row as f64 * fit.cell_size_deg,
+                        min_lon: col as f64 * fit.cell_size_deg,
+                        max_lat: (row + 1) as f64 * fit.cell_size_deg,
+                        max_lon: (col + 1) as f64 * fit.cell_size_deg,
+                    },
+                    correction: CalibrationCorrection::from_residuals(&residuals)?,
+                })
+            })
+            .collect();
+        Ok(Self { global, regions })
+    }
+
+    /// Correction of a path with its midpoint at the point.
+    pub fn correction_at(&self, lat: f64, lon: f64) -> &CalibrationCorrection {
+        self.regions
+            .iter()
+            .find(|r| {
+                (r.bounds.min_lat..r.bounds.max_lat).contains(&lat)
+                    && (r.bounds.min_lon..r.bounds.max_lon).contains(&lon)
+            })
+            .map_or(&self.global, |r| &r.correction)
+    }
+
+    /// Correction of a link's path.
+    pub fn correction_for(&self, config: &LinkPredictionConfig) -> &CalibrationCorrection {
+        self.correction_at((config.from_lat + config.to_lat) / 2.0, (config.from_lon + config.to_lon) / 2.0)
+    }
+
+    /// Calibrated median SNR and its standard deviation.
+    pub fn apply(&self, config: &LinkPredictionConfig, snr_db: f64) -> (f64, f64) {
+        let correction = self.correction_for(config);
+        (snr_db + correction.offset_db, correction.std_dev_db)
+    }
+
+    /// Serialize to JSON.
+    pub fn to_json_string(&self) -> String {
+        let correction = |c: &CalibrationCorrection| {
+            json!({ "offset_db": c.offset_db, "std_dev_db": c.std_dev_db, "samples": c.samples })
+        };
+        let regions: Vec<Value> = self
+            .regions
+            .iter()
+            .map(|r| {
+                json!({
+                    "min_lat": r.bounds.min_lat,
+                    "min_lon": r.bounds.min_lon,
+                    "max_lat": r.bounds.max_lat,
+                    "max_lon": r.bounds.max_lon,
+                    "correction": correction(&r.correction),
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&json!({ "global": correction(&self.global), "regions": regions }))
+            .unwrap_or_default()
+    }
+
+    /// Parse a calibration saved by [`to_json_string`](Self::to_json_string).
+    pub fn from_json_str(text: &str) -> Result<Self, LinkPredictionError> {
+        let invalid = |what: &str| LinkPredictionError::ConfigError(format!("Invalid calibration: {}", what));
+        let root: Value = serde_json::from_str(text).map_err(|e| invalid(&e.to_string()))?;
+        let number = |value: &Value, key: &str| value[key].as_f64().ok_or_else(|| invalid(&format!("no '{}'", key)));
+        let correction = |value: &Value| -> Result<CalibrationCorrection, LinkPredictionError> {
+            Ok(CalibrationCorrection {
+                offset_db: number(value, "offset_db")?,
+                std_dev_db: number(value, "std_dev_db")?,
+                samples: value["samples"].as_u64().unwrap_or(0) as usize,
+            })
+        };
+        let regions = match &root["regions"] {
+            Value::Null => Vec::new(),
+            Value::Array(regions) => regions
+                .iter()
+                .map(|r| {
+                    Ok(RegionCalibration {
+                        bounds: BoundingBox {
+                            min_lat: number(r, "min_lat")?,
+                            min_lon: number(r, "min_lon")?,
+                            max_lat: number(r, "max_lat")?,
+                            max_lon: number(r, "max_lon")?,
+                        },
+                        correction: correction(&r["correction"])?,
+                    })
+                })
+                .collect::<Result<_, LinkPredictionError>>()?,
+            _ => return Err(invalid("'regions' is not a list")),
+        };
+        Ok(Self { global: correction(&root["global"])?, regions })
+    }
+
+    /// Load a calibration from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LinkPredictionError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            LinkPredictionError::ConfigError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Self::from_json_str(&text)
+    }
+
+    /// Save the calibration to a JSON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), LinkPredictionError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json_string()).map_err(|e| {
+            LinkPredictionError::ConfigError(format!("Failed to write {}: {}", path.display(), e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_measurements() {
+        let csv = "from_lat,from_lon,to_lat,to_lon,sf,rssi_dbm,snr_db,tx_power_dbm\n\
+                   47.60,-122.33,47.65,-122.30,9,-112.5,-4.25,\n\
+                   47.60,-122.33,47.70,-122.20,11,-121,,14\n";
+        let measurements = FieldMeasurement::from_csv(csv).unwrap();
+        assert_eq!(measurements.len(), 2);
+        assert_eq!(measurements[0].snr_db, Some(-4.25));
+        assert_eq!(measurements[0].tx_power_dbm, None);
+        assert_eq!(measurements[1].spreading_factor, 11);
+        assert_eq!(measurements[1].snr_db, None);
+        assert_eq!(measurements[1].tx_power_dbm, Some(14));
+
+        assert!(FieldMeasurement::from_csv("from_lat,from_lon,to_lat,to_lon,sf\n").is_err());
+        assert!(FieldMeasurement::from_csv("from_lat,from_lon,to_lat,to_lon,sf,snr_db\n1,2,3,4,7,\n").is_err());
+    }
+
+    #[test]
+    fn test_fit_regions() {
+        // Six measurements 7 dB weaker than predicted in one cell, two 2 dB
+        // stronger elsewhere
+        let mut samples: Vec<CalibrationSample> = [-6.0, -8.0, -7.5, -6.5, -7.0, -7.0]
+            .iter()
+            .map(|&residual_db| CalibrationSample { lat: 47.6, lon: -122.3, residual_db })
+            .collect();
+        samples.extend([1.0, 3.0].map(|residual_db| CalibrationSample { lat: 45.5, lon: -122.7, residual_db }));
+        let calibration = LinkCalibration::from_samples(&samples, &CalibrationFit::default()).unwrap();
+
+        assert_eq!(calibration.regions.len(), 1);
+        let seattle = calibration.correction_at(47.62, -122.35);
+        assert!((seattle.offset_db + 7.0).abs() < 1e-9);
+        assert_eq!(seattle.samples, 6);
+        let portland = calibration.correction_at(45.5, -122.7);
+        assert_eq!(portland.samples, 8);
+        assert!((portland.offset_db - (-42.0 + 4.0) / 8.0).abs() < 1e-9);
+
+        let config = LinkPredictionConfig {
+            from_lat: 47.55,
+            from_lon: -122.4,
+            to_lat: 47.65,
+            to_lon: -122.3,
+            ..Default::default()
+        };
+        let (snr, std_dev) = calibration.apply(&config, 5.0);
+        assert!((snr + 2.0).abs() < 1e-9);
+        assert_eq!(std_dev, seattle.std_dev_db);
+
+        let reloaded = LinkCalibration::from_json_str(&calibration.to_json_string()).unwrap();
+        assert_eq!(reloaded.regions.len(), 1);
+        assert_eq!(reloaded.regions[0].bounds, calibration.regions[0].bounds);
+        assert!((reloaded.global.std_dev_db - calibration.global.std_dev_db).abs() < 1e-9);
+        assert!(LinkCalibration::from_samples(&samples[..1], &CalibrationFit::default()).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! Clutter loss from land cover.
+//!
+//! ITM predicts propagation over bare terrain, so it over-predicts range
+//! where antennas sit in forests or among buildings. [`ClutterModel`] adds
+//! loss from a [`LandCoverMap`] to a prediction:
+//!
+//! - at each endpoint, the class's endpoint loss when the antenna is below
+//!   the class's clutter height, tapering linearly to nothing at that height;
+//! - along the path, the class's loss per kilometer for the share of the
+//!   path over it, capped at [`ClutterModel::max_path_loss_db`].
+//!
+//! The defaults are rough figures for 900 MHz; set [`ClutterModel::classes`]
+//! to calibrate them.
+
+use std::collections::BTreeMap;
+
+use mcsim_dem::{LandCoverClass, LandCoverMap};
+use mcsim_itm::Itm;
+
+use crate::predict::{
+    predict_link_with_elevation_and_params, ElevationSource, LinkPrediction, LinkPredictionConfig,
+    LinkPredictionError, LinkPredictionParams, PathInfo,
+};
+
+/// Clutter of one land-cover class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClassClutter {
+    /// Height of the clutter above ground (meters).
+    pub height_m: f64,
+    /// Loss for an antenna at ground level in this class (dB).
+    pub endpoint_loss_db: f64,
+    /// Loss per kilometer of path over this class (dB/km).
+    pub path_loss_db_per_km: f64,
+}
+
+/// Per-class clutter loss applied on top of a terrain prediction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClutterModel {
+    /// Clutter of each class; classes not listed add no loss.
+    pub classes: BTreeMap<LandCoverClass, ClassClutter>,
+    /// Maximum loss along the path (dB), since signals eventually go over
+    /// rather than through clutter.
+    pub max_path_loss_db: f64,
+}
+
+impl Default for ClutterModel {
+    fn default() -> Self {
+        let clutter = |height_m, endpoint_loss_db, path_loss_db_per_km| ClassClutter {
+            height_m,
+            endpoint_loss_db,
+            path_loss_db_per_km,
+        };
+        Self {
+            classes: BTreeMap::from([
+                (LandCoverClass::TreeCover, clutter(15.0, 10.0, 3.0)),
+                (LandCoverClass::Mangroves, clutter(10.0, 8.0, 3.0)),
+                (LandCoverClass::Shrubland, clutter(3.0, 3.0, 0.5)),
+                (LandCoverClass::BuiltUp, clutter(15.0, 12.0, 2.0)),
+                (LandCoverClass::Cropland, clutter(1.0, 1.0, 0.0)),
+                (LandCoverClass::Wetland, clutter(1.0, 1.0, 0.0)),
+            ]),
+            max_path_loss_db: 20.0,
+        }
+    }
+}
+
+/// Clutter loss of a path, by where it applies.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClutterLoss {
+    /// Loss at the transmitter (dB).
+    pub from_db: f64,
+    /// Loss at the receiver (dB).
+    pub to_db: f64,
+    /// Loss along the path (dB).
+    pub path_db: f64,
+}
+
+impl ClutterLoss {
+    /// Total loss (dB).
+    pub fn total_db(&self) -> f64 {
+        self.from_db + self.to_db + self.path_db
+    }
+}
+
+impl ClutterModel {
+    /// Clutter loss of `path`, whose antenna heights are above ground,
+    /// sampling the land cover at `samples` points.
+    pub fn loss(&self, land_cover: &LandCoverMap, path: &PathInfo, samples: usize) -> ClutterLoss {
+        let (from_lat, from_lon, to_lat, to_lon) = (path.from_lat, path.from_lon, path.to_lat, path.to_lon);
+        let endpoint = |lat: f64, lon: f64, agl_m: f64| {
+            let Some(clutter) = land_cover.class_at(lat, lon).and_then(|class| self.classes.get(&class)) else {
+                return 0.0;
+            };
+            if clutter.height_m <= 0.0 || agl_m >= clutter.height_m {
+                return 0.0;
+            }
+            clutter.endpoint_loss_db * (1.0 - agl_m.max(0.0) / clutter.height_m)
+        };
+
+        // Interior samples, as the endpoints are covered above
+        let interior = samples.saturating_sub(2);
+        let mut per_km_sum = 0.0;
+        for i in 1..=interior {
+            let t = i as f64 / (interior + 1) as f64;
+            let lat = from_lat + t * (to_lat - from_lat);
+            let lon = from_lon + t * (to_lon - from_lon);
+            if let Some(clutter) = land_cover.class_at(lat, lon).and_then(|class| self.classes.get(&class)) {
+                per_km_sum += clutter.path_loss_db_per_km;
+            }
+        }
+        let path_db = if interior > 0 {
+            (per_km_sum / interior as f64 * path.distance_km).min(self.max_path_loss_db)
+        } else {
+            0.0
+        };
+
+        ClutterLoss {
+            from_db: endpoint(from_lat, from_lon, path.from_height),
+            to_db: endpoint(to_lat, to_lon, path.to_height),
+            path_db,
+        }
+    }
+
+    /// Add the clutter loss of `prediction`'s path to it, updating its SNR,
+    /// margin and status.
+    pub fn apply(&self, land_cover: &LandCoverMap, prediction: &mut LinkPrediction, params: &LinkPredictionParams) {
+        let loss = self.loss(land_cover, &prediction.path, prediction.terrain.sample_count).total_db();
+        prediction.clutter_loss_db += loss;
+        prediction.path_loss_db += loss;
+        prediction.snr_db -= loss;
+        prediction.link_margin_db -= loss;
+        prediction.status = params.classify_link(prediction.link_margin_db);
+    }
+}
+
+/// Predict link quality over terrain, then add clutter loss from land cover.
+pub fn predict_link_with_clutter(
+    elevation: &ElevationSource,
+    itm: &Itm,
+    config: &LinkPredictionConfig,
+    params: &LinkPredictionParams,
+    land_cover: &LandCoverMap,
+    clutter: &ClutterModel,
+) -> Result<LinkPrediction, LinkPredictionError> {
+    let mut prediction = predict_link_with_elevation_and_params(elevation, itm, config, params)?;
+    clutter.apply(land_cover, &mut prediction, params);
+    Ok(prediction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predict::AntennaHeightMode;
+    use mcsim_dem::{LandCoverTile, TileBounds};
+
+    fn path(from: (f64, f64), to: (f64, f64), heights: (f64, f64), distance_km: f64) -> PathInfo {
+        PathInfo {
+            from_lat: from.0,
+            from_lon: from.1,
+            to_lat: to.0,
+            to_lon: to.1,
+            from_height: heights.0,
+            to_height: heights.1,
+            height_mode: AntennaHeightMode::Agl,
+            distance_km,
+        }
+    }
+
+    /// West half forest, east half water.
+    fn land_cover() -> LandCoverMap {
+        let bounds = TileBounds { min_lat: 47.0, max_lat: 48.0, min_lon: -123.0, max_lon: -122.0 };
+        let mut map = LandCoverMap::new();
+        map.add_tile(LandCoverTile::from_codes(vec![10, 80, 10, 80], 2, 2, bounds).unwrap());
+        map
+    }
+
+    #[test]
+    fn test_clutter_loss() {
+        let model = ClutterModel::default();
+        let map = land_cover();
+        let forest = model.classes[&LandCoverClass::TreeCover];
+
+        // Ground-level antenna in the forest, the other over water
+        let loss = model.loss(&map, &path((47.5, -122.9), (47.5, -122.1), (0.0, 2.0), 1.0), 11);
+        assert_eq!(loss.from_db, forest.endpoint_loss_db);
+        assert_eq!(loss.to_db, 0.0);
+        // Half the interior samples are over the forest
+        let expected = forest.path_loss_db_per_km * 0.5 * 1.0;
+        assert!((loss.path_db - expected).abs() < 0.4, "{}", loss.path_db);
+
+        // Antennas above the canopy clear it; long paths are capped
+        let high = model.loss(&map, &path((47.5, -122.9), (47.5, -122.8), (20.0, 20.0), 100.0), 11);
+        assert_eq!(high.from_db + high.to_db, 0.0);
+        assert_eq!(high.path_db, model.max_path_loss_db);
+
+        // Outside the land cover nothing is added
+        let outside = model.loss(&map, &path((40.0, -100.0), (40.1, -100.0), (0.0, 0.0), 10.0), 11);
+        assert_eq!(outside.total_db(), 0.0);
+    }
+}
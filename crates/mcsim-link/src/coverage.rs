@@ -0,0 +1,411 @@
+//! Coverage maps for a single transmitter.
+//!
+//! [`compute_coverage`] predicts the SNR a receiver would see at the center of
+//! every cell of a grid covering a bounding box, using the Longley-Rice (ITM)
+//! area mode. Each cell samples the terrain between the transmitter and the
+//! cell to derive the terrain irregularity (delta h) of that path; cells
+//! closer than the ITM minimum distance fall back to free-space loss, as point
+//! predictions do. Cells are computed in parallel. A [`CoverageRegion`]
+//! limits the sweep to polygon boundaries minus exclusion zones; cells outside
+//! it are skipped.
+//!
+//! The resulting [`CoverageMap`] can be written as a single-band GeoTIFF of SNR
+//! values (EPSG:4326, NaN for cells that couldn't be predicted) or as a PNG
+//! colored with a [`ColorRamp`].
+
+use std::io::{Seek, Write};
+
+use mcsim_itm::{Itm, SitingCriteria, TerrainProfile};
+use rayon::prelude::*;
+
+use crate::matrix::with_thread_itm;
+use crate::predict::{
+    load_itm, path_antenna_gain_db, resolve_antenna_heights, sample_path_profile, ElevationSource,
+    LinkPredictionConfig, LinkPredictionError, LinkPredictionParams, LinkStatus,
+};
+use crate::region::CoverageRegion;
+
+/// Meters per degree of latitude.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Geographic bounding box, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoundingBox {
+    /// Southern edge.
+    pub min_lat: f64,
+    /// Western edge.
+    pub min_lon: f64,
+    /// Northern edge.
+    pub max_lat: f64,
+    /// Eastern edge.
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    /// Parse `min_lat,min_lon,max_lat,max_lon`.
+    pub fn parse(spec: &str) -> Result<Self, LinkPredictionError> {
+        let values: Vec<f64> = spec
+            .split(',')
+            .map(|v| v.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| LinkPredictionError::ConfigError(format!("Invalid bounding box '{}'", spec)))?;
+        let [min_lat, min_lon, max_lat, max_lon] = values[..] else {
+            return Err(LinkPredictionError::ConfigError(format!(
+                "Bounding box '{}' must be min_lat,min_lon,max_lat,max_lon",
+                spec
+            )));
+        };
+        Ok(Self { min_lat, min_lon, max_lat, max_lon })
+    }
+
+    fn validate(&self) -> Result<(), LinkPredictionError> {
+        let valid = self.min_lat < self.max_lat
+            && self.min_lon < self.max_lon
+            && self.min_lat >= -90.0
+            && self.max_lat <= 90.0
+            && self.min_lon >= -180.0
+            && self.max_lon <= 180.0;
+        if valid {
+            Ok(())
+        } else {
+            Err(LinkPredictionError::ConfigError(format!("Invalid bounding box {:?}", self)))
+        }
+    }
+}
+
+/// Settings for a coverage map.
+#[derive(Debug, Clone)]
+pub struct CoverageConfig {
+    /// Transmitter and receiver settings. `from_*` is the transmitter,
+    /// `to_height` and `to_antenna` apply to the receiver in every cell, and
+    /// `to_lat`/`to_lon` are ignored.
+    pub link: LinkPredictionConfig,
+    /// Area to cover.
+    pub bounds: BoundingBox,
+    /// Cell size in meters.
+    pub resolution_m: f64,
+    /// How carefully the transmitter is sited (receivers are sited randomly).
+    pub tx_siting: SitingCriteria,
+    /// Cells to predict within `bounds` (all of them by default).
+    pub region: CoverageRegion,
+}
+
+/// Predicted SNR over a grid of cells.
+#[derive(Debug, Clone)]
+pub struct CoverageMap {
+    /// Area covered by the grid.
+    pub bounds: BoundingBox,
+    /// Number of columns (west to east).
+    pub width: usize,
+    /// Number of rows (north to south).
+    pub height: usize,
+    /// Row-major SNR per cell in dB, NaN where the prediction failed or the
+    /// cell is outside the sweep's region.
+    pub snr_db: Vec<f32>,
+    /// Minimum SNR for the configured spreading factor.
+    pub snr_threshold_db: f64,
+}
+
+impl CoverageMap {
+    /// Cell size in degrees of longitude and latitude.
+    pub fn cell_size_deg(&self) -> (f64, f64) {
+        (
+            (self.bounds.max_lon - self.bounds.min_lon) / self.width as f64,
+            (self.bounds.max_lat - self.bounds.min_lat) / self.height as f64,
+        )
+    }
+
+    /// Latitude and longitude of the center of a cell.
+    pub fn cell_center(&self, col: usize, row: usize) -> (f64, f64) {
+        let (dlon, dlat) = self.cell_size_deg();
+        (
+            self.bounds.max_lat - (row as f64 + 0.5) * dlat,
+            self.bounds.min_lon + (col as f64 + 0.5) * dlon,
+        )
+    }
+
+    /// Predicted SNR of a cell, if it could be predicted.
+    pub fn snr_at(&self, col: usize, row: usize) -> Option<f64> {
+        let snr = *self.snr_db.get(row * self.width + col)?;
+        (!snr.is_nan()).then_some(snr as f64)
+    }
+
+    /// Link status of a cell, classified with `params`' margins.
+    pub fn status_at(&self, col: usize, row: usize, params: &LinkPredictionParams) -> Option<LinkStatus> {
+        self.snr_at(col, row)
+            .map(|snr| params.classify_link(snr - self.snr_threshold_db))
+    }
+
+    /// Fraction of predicted cells at or above the SNR threshold.
+    pub fn covered_fraction(&self) -> f64 {
+        let predicted = self.snr_db.iter().filter(|s| !s.is_nan());
+        let (total, covered) = predicted.fold((0usize, 0usize), |(total, covered), &snr| {
+            (total + 1, covered + (snr as f64 >= self.snr_threshold_db) as usize)
+        });
+        if total == 0 {
+            0.0
+        } else {
+            covered as f64 / total as f64
+        }
+    }
+
+    /// Write the SNR grid as a single-band float GeoTIFF in EPSG:4326.
+    pub fn write_geotiff<W: Write + Seek>(&self, writer: W) -> std::io::Result<()> {
+        use tiff::encoder::{colortype::Gray32Float, TiffEncoder};
+        use tiff::tags::Tag;
+
+        let (dlon, dlat) = self.cell_size_deg();
+        let mut encoder = TiffEncoder::new(writer).map_err(std::io::Error::other)?;
+        let mut image = encoder
+            .new_image::<Gray32Float>(self.width as u32, self.height as u32)
+            .map_err(std::io::Error::other)?;
+        let tags = image.encoder();
+        tags.write_tag(Tag::ModelPixelScaleTag, &[dlon, dlat, 0.0][..])
+            .and_then(|_| {
+                tags.write_tag(
+                    Tag::ModelTiepointTag,
+                    &[0.0, 0.0, 0.0, self.bounds.min_lon, self.bounds.max_lat, 0.0][..],
+                )
+            })
+            .and_then(|_| {
+                // Geographic model, pixel-is-area, WGS 84
+                tags.write_tag(
+                    Tag::GeoKeyDirectoryTag,
+                    &[1u16, 1, 0, 3, 1024, 0, 1, 2, 1025, 0, 1, 1, 2048, 0, 1, 4326][..],
+                )
+            })
+            .and_then(|_| tags.write_tag(Tag::GdalNodata, "nan"))
+            .map_err(std::io::Error::other)?;
+        image.write_data(&self.snr_db).map_err(std::io::Error::other)
+    }
+
+    /// Write the grid as an RGBA PNG, coloring each cell's SNR with `ramp`.
+    pub fn write_png<W: Write>(&self, writer: W, ramp: &ColorRamp) -> std::io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let pixels: Vec<u8> = self.snr_db.iter().flat_map(|&snr| ramp.color(snr as f64)).collect();
+        let mut writer = encoder.write_header().map_err(std::io::Error::other)?;
+        writer.write_image_data(&pixels).map_err(std::io::Error::other)?;
+        writer.finish().map_err(std::io::Error::other)
+    }
+}
+
+/// Piecewise-linear mapping from SNR to RGBA color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp {
+    stops: Vec<(f64, [u8; 4])>,
+}
+
+impl ColorRamp {
+    /// Ramp through `stops`, given as (SNR dB, RGBA) in any order. Values
+    /// below the first stop are transparent; values above the last take its
+    /// color.
+    pub fn new(mut stops: Vec<(f64, [u8; 4])>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// Red at the decode threshold through yellow to green at 10 dB margin
+    /// and blue at 20 dB; cells below the threshold are transparent.
+    pub fn link_margin(snr_threshold_db: f64) -> Self {
+        Self::new(vec![
+            (snr_threshold_db, [215, 48, 39, 200]),
+            (snr_threshold_db + 5.0, [254, 224, 139, 200]),
+            (snr_threshold_db + 10.0, [26, 152, 80, 200]),
+            (snr_threshold_db + 20.0, [49, 54, 149, 200]),
+        ])
+    }
+
+    /// Color for an SNR value; NaN is transparent.
+    pub fn color(&self, snr_db: f64) -> [u8; 4] {
+        let transparent = [0, 0, 0, 0];
+        let Some(&(first, _)) = self.stops.first() else {
+            return transparent;
+        };
+        if snr_db.is_nan() || snr_db < first {
+            return transparent;
+        }
+        for pair in self.stops.windows(2) {
+            let ((lo, lo_color), (hi, hi_color)) = (pair[0], pair[1]);
+            if snr_db <= hi {
+                let t = if hi > lo { (snr_db - lo) / (hi - lo) } else { 1.0 };
+                let mut color = [0u8; 4];
+                for (i, c) in color.iter_mut().enumerate() {
+                    *c = (lo_color[i] as f64 + (hi_color[i] as f64 - lo_color[i] as f64) * t).round() as u8;
+                }
+                return color;
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+/// Predict SNR over `config.bounds` from the transmitter in `config.link`.
+///
+/// Cells whose prediction fails (missing elevation data, receiver below
+/// ground in MSL mode, ITM errors) are left as NaN. Returns an error only for
+/// invalid settings or if the ITM library can't be loaded.
+pub fn compute_coverage(
+    elevation: &ElevationSource,
+    config: &CoverageConfig,
+    params: &LinkPredictionParams,
+) -> Result<CoverageMap, LinkPredictionError> {
+    config.bounds.validate()?;
+    if config.resolution_m.is_nan() || config.resolution_m <= 0.0 {
+        return Err(LinkPredictionError::ConfigError(
+            "Coverage resolution must be positive".to_string(),
+        ));
+    }
+    drop(load_itm()?);
+
+    let bounds = config.bounds;
+    let mid_lat = (bounds.min_lat + bounds.max_lat) / 2.0;
+    let dlat = config.resolution_m / METERS_PER_DEGREE;
+    let dlon = config.resolution_m / (METERS_PER_DEGREE * mid_lat.to_radians().cos());
+    let width = (((bounds.max_lon - bounds.min_lon) / dlon).ceil() as usize).max(1);
+    let height = (((bounds.max_lat - bounds.min_lat) / dlat).ceil() as usize).max(1);
+
+    let mut map = CoverageMap {
+        bounds,
+        width,
+        height,
+        snr_db: Vec::new(),
+        snr_threshold_db: params.snr_threshold_for_sf(config.link.spreading_factor),
+    };
+    let snr_db = (0..width * height)
+        .into_par_iter()
+        .map(|i| {
+            let (lat, lon) = map.cell_center(i % width, i / width);
+            if !config.region.contains(lat, lon) {
+                return f32::NAN;
+            }
+            with_thread_itm(|itm| predict_cell(elevation, itm, config, params, lat, lon))
+                .map_or(f32::NAN, |snr| snr as f32)
+        })
+        .collect();
+    map.snr_db = snr_db;
+    Ok(map)
+}
+
+/// Area-mode SNR at one receiver location.
+fn predict_cell(
+    elevation: &ElevationSource,
+    itm: &Itm,
+    config: &CoverageConfig,
+    params: &LinkPredictionParams,
+    lat: f64,
+    lon: f64,
+) -> Result<f64, LinkPredictionError> {
+    let link = LinkPredictionConfig {
+        to_lat: lat,
+        to_lon: lon,
+        ..config.link.clone()
+    };
+    let profile = sample_path_profile(elevation, &link)?;
+    let elevations = &profile.elevations;
+    let distance_m = profile.distance_m;
+    let (from_ground, to_ground) = (elevations[0], elevations[elevations.len() - 1]);
+    let (tx_height, rx_height) = resolve_antenna_heights(&link, from_ground, to_ground)?;
+    let antenna_gain_db =
+        path_antenna_gain_db(&link, from_ground + tx_height, to_ground + rx_height, distance_m);
+
+    let itm_error = |e: mcsim_itm::ItmError| LinkPredictionError::ItmError(format!("ITM calculation failed: {}", e));
+    let path_loss_db = if distance_m < params.fspl_min_distance_m {
+        params.colocated_path_loss_db
+    } else if distance_m < params.itm_min_distance_m || elevations.len() < 2 {
+        itm.free_space_loss(distance_m, link.freq_mhz).map_err(itm_error)?
+    } else {
+        let resolution_m = distance_m / (elevations.len() - 1) as f64;
+        let pfl = TerrainProfile::from_elevations(resolution_m, elevations).to_pfl();
+        let delta_h = itm.compute_delta_h(&pfl, 0.0, distance_m).map_err(itm_error)?;
+        itm.area_tls(
+            tx_height,
+            rx_height,
+            config.tx_siting,
+            SitingCriteria::Random,
+            distance_m / 1000.0,
+            delta_h,
+            params.parse_climate(),
+            params.itm_surface_refractivity,
+            link.freq_mhz,
+            params.parse_polarization(),
+            params.itm_ground_permittivity,
+            params.itm_ground_conductivity,
+            0, // mdvar: single message mode
+            50.0,
+            50.0,
+            50.0,
+        )
+        .map_err(itm_error)?
+        .loss_db
+    };
+
+    Ok(link.tx_power_dbm as f64 + antenna_gain_db - path_loss_db - params.noise_floor_dbm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> CoverageMap {
+        CoverageMap {
+            bounds: BoundingBox { min_lat: 47.0, min_lon: -122.0, max_lat: 47.2, max_lon: -121.6 },
+            width: 4,
+            height: 2,
+            snr_db: vec![5.0, -3.0, f32::NAN, -20.0, 12.0, 0.0, -7.5, f32::NAN],
+            snr_threshold_db: -7.5,
+        }
+    }
+
+    #[test]
+    fn test_cell_geometry_and_status() {
+        let map = map();
+        let (lat, lon) = map.cell_center(0, 0);
+        assert!((lat - 47.15).abs() < 1e-9 && (lon - -121.95).abs() < 1e-9);
+        assert_eq!(map.snr_at(2, 0), None);
+        assert_eq!(map.snr_at(0, 1), Some(12.0));
+
+        let params = LinkPredictionParams::default();
+        assert_eq!(map.status_at(0, 1, &params), Some(LinkStatus::Excellent));
+        assert_eq!(map.status_at(3, 0, &params), Some(LinkStatus::Unreliable));
+        // 5 of the 6 predicted cells reach the threshold
+        assert!((map.covered_fraction() - 5.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_color_ramp() {
+        let ramp = ColorRamp::link_margin(-7.5);
+        assert_eq!(ramp.color(-8.0), [0, 0, 0, 0]);
+        assert_eq!(ramp.color(f64::NAN), [0, 0, 0, 0]);
+        assert_eq!(ramp.color(-7.5), [215, 48, 39, 200]);
+        assert_eq!(ramp.color(50.0), [49, 54, 149, 200]);
+        let mid = ramp.color(-5.0);
+        assert!(mid[0] > 215 && mid[1] > 48 && mid[1] < 224);
+    }
+
+    #[test]
+    fn test_outputs() {
+        let map = map();
+        let mut png = Vec::new();
+        map.write_png(&mut png, &ColorRamp::link_margin(map.snr_threshold_db)).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        map.write_geotiff(&mut tiff).unwrap();
+        let mut decoder = tiff::decoder::Decoder::new(std::io::Cursor::new(tiff.into_inner())).unwrap();
+        assert_eq!(decoder.dimensions().unwrap(), (4, 2));
+        let scale = decoder.get_tag_f64_vec(tiff::tags::Tag::ModelPixelScaleTag).unwrap();
+        assert!((scale[0] - 0.1).abs() < 1e-9 && (scale[1] - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_bounding_box() {
+        let bounds = BoundingBox::parse("47.0, -122.5, 47.8, -121.9").unwrap();
+        assert_eq!(bounds.max_lon, -121.9);
+        assert!(bounds.validate().is_ok());
+        assert!(BoundingBox::parse("47.0,-122.5,47.8").is_err());
+        assert!(BoundingBox::parse("47.8,-122.5,47.0,-121.9").unwrap().validate().is_err());
+    }
+}
@@ -3,6 +3,19 @@
 //! This module provides Maximum Likelihood Estimation for estimating the true
 //! mean and standard deviation of SNR from observed values that are inherently
 //! truncated (only packets above the sensitivity threshold are received).
+//!
+//! Links whose conditions change over the observation window (a door opening,
+//! foliage, a moving vehicle) can show two distinct SNR clusters. For those,
+//! [`estimate_snr_mixture`] also fits a two-component truncated normal
+//! mixture and reports it when it explains the data clearly better than a
+//! single normal.
+//!
+//! For a whole network, [`estimate_link_snrs`] takes each directed link's
+//! receive log ([`LinkRxLog`]), including the packets the receiver is known
+//! to have missed, and estimates every link in one call; [`write_link_snr_csv`]
+//! tabulates the estimates next to the configured link parameters.
+
+use std::io::Write;
 
 use argmin::core::{CostFunction, Error, Executor, State};
 use argmin::solver::neldermead::NelderMead;
@@ -11,6 +24,7 @@ use mcsim_model::properties::{
     RADIO_SNR_THRESHOLD_SF7_DB, RADIO_SNR_THRESHOLD_SF8_DB, RADIO_SNR_THRESHOLD_SF9_DB,
     RADIO_SNR_THRESHOLD_SF10_DB, RADIO_SNR_THRESHOLD_SF11_DB, RADIO_SNR_THRESHOLD_SF12_DB,
 };
+use rayon::prelude::*;
 use statrs::distribution::{Continuous, ContinuousCDF, Normal};
 use thiserror::Error;
 
@@ -462,6 +476,430 @@ pub fn estimate_snr_with_threshold(
     })
 }
 
+/// Negative log-likelihood of received SNRs plus a count of packets lost
+/// below the threshold (a censored normal).
+struct CensoredSnrCostFunction {
+    observations: Vec<f64>,
+    lost: u64,
+    threshold: f64,
+}
+
+impl CostFunction for CensoredSnrCostFunction {
+    type Param = Vec<f64>; // [mu, sigma]
+    type Output = f64;
+
+    fn cost(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+        let dist = match Normal::new(p[0], p[1].abs().max(0.1)) {
+            Ok(d) => d,
+            Err(_) => return Ok(f64::INFINITY),
+        };
+        let mut nll = -self.observations.iter().map(|&y| dist.ln_pdf(y)).sum::<f64>();
+        if self.lost > 0 {
+            let lost_prob = dist.cdf(self.threshold);
+            if lost_prob < 1e-300 {
+                return Ok(f64::INFINITY);
+            }
+            nll -= self.lost as f64 * lost_prob.ln();
+        }
+        Ok(nll)
+    }
+}
+
+/// Estimates the SNR distribution from received packets and a count of
+/// packets lost below the threshold.
+///
+/// [`estimate_snr_with_threshold`] only sees the packets that were received,
+/// so it has to infer how much of the distribution lies below the threshold.
+/// A receiver that knows how many packets it missed (from sequence gaps, or
+/// the simulator's records) can count each of them as `P(SNR < threshold)`
+/// instead, which pins down the mean of weak links much better. With no
+/// lost packets this is an ordinary normal fit.
+pub fn estimate_snr_censored(
+    observations: Vec<f64>,
+    lost: u64,
+    threshold: f64,
+) -> Result<SnrEstimationResult, SnrEstimationError> {
+    if observations.is_empty() {
+        return Err(SnrEstimationError::NoObservations);
+    }
+
+    let observation_count = observations.len();
+    let (sample_mean, sample_std) = mean_and_std(&observations);
+    // Lost packets pull the starting point below the threshold
+    let lost_fraction = lost as f64 / (lost as f64 + observation_count as f64);
+    let start_mean = sample_mean - lost_fraction * (sample_mean - threshold + sample_std);
+
+    let cost_fn = CensoredSnrCostFunction {
+        observations,
+        lost,
+        threshold,
+    };
+    let solver = NelderMead::new(vec![
+        vec![start_mean, sample_std],
+        vec![start_mean - 2.0, sample_std + 1.0],
+        vec![start_mean + 2.0, sample_std + 0.5],
+    ]);
+    let res = Executor::new(cost_fn, solver)
+        .configure(|state| state.max_iters(200))
+        .run()
+        .map_err(|e| SnrEstimationError::OptimizationFailed(e.to_string()))?;
+    let best_param = res.state().get_best_param().ok_or_else(|| {
+        SnrEstimationError::OptimizationFailed("No solution found".to_string())
+    })?;
+
+    Ok(SnrEstimationResult {
+        mean_snr: best_param[0],
+        std_dev: best_param[1].abs(),
+        threshold,
+        observation_count,
+        sample_mean,
+    })
+}
+
+// ============================================================================
+// Two-Component Mixture
+// ============================================================================
+
+/// Fewer observations than this are always fitted with a single normal.
+pub const MIN_MIXTURE_OBSERVATIONS: usize = 10;
+
+/// Smallest weight either mixture component may have to be reported.
+const MIN_COMPONENT_WEIGHT: f64 = 0.05;
+
+/// Negative log-likelihood of truncated observations under a two-component
+/// normal mixture.
+struct MixtureCostFunction {
+    observations: Vec<f64>,
+    threshold: f64,
+}
+
+impl CostFunction for MixtureCostFunction {
+    type Param = Vec<f64>; // [mu1, sigma1, mu2, sigma2, logit(w)]
+    type Output = f64;
+
+    fn cost(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+        let (first, second) = match (
+            Normal::new(p[0], p[1].abs().max(0.1)),
+            Normal::new(p[2], p[3].abs().max(0.1)),
+        ) {
+            (Ok(a), Ok(b)) => (a, b),
+            _ => return Ok(f64::INFINITY),
+        };
+        let weight = 1.0 / (1.0 + (-p[4]).exp());
+
+        // Probability that a draw from the mixture is received at all
+        let survival_prob = weight * (1.0 - first.cdf(self.threshold))
+            + (1.0 - weight) * (1.0 - second.cdf(self.threshold));
+        if survival_prob < 1e-10 {
+            return Ok(f64::INFINITY);
+        }
+        let log_survival = survival_prob.ln();
+
+        let mut nll = 0.0;
+        for &y in &self.observations {
+            let density = weight * first.pdf(y) + (1.0 - weight) * second.pdf(y);
+            if density <= 0.0 {
+                return Ok(f64::INFINITY);
+            }
+            nll -= density.ln() - log_survival;
+        }
+        Ok(nll)
+    }
+}
+
+/// One component of an SNR mixture.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnrComponent {
+    /// Estimated mean SNR of this component (dB).
+    pub mean_snr: f64,
+    /// Estimated standard deviation of this component (dB).
+    pub std_dev: f64,
+    /// Fraction of transmissions drawn from this component, including ones
+    /// that were not received.
+    pub weight: f64,
+}
+
+/// Result of fitting observed SNR with both a single normal and a
+/// two-component mixture.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnrMixtureResult {
+    /// Single truncated normal fit.
+    pub single: SnrEstimationResult,
+
+    /// Mixture components, lower mean first, when the mixture is a clearly
+    /// better fit than `single` (by BIC). `None` when the data look unimodal
+    /// or there are too few observations to tell.
+    pub components: Option<[SnrComponent; 2]>,
+
+    /// BIC of the single normal minus BIC of the mixture. Positive values
+    /// favour the mixture; above 10 is strong evidence.
+    pub bic_improvement: f64,
+}
+
+impl SnrMixtureResult {
+    /// Whether the single truncated normal is a poor description of the data
+    /// and the mixture should be used instead.
+    pub fn single_fit_poor(&self) -> bool {
+        self.components.is_some()
+    }
+
+    /// Weight of the lower-SNR component, if the data are bimodal.
+    pub fn mixing_weight(&self) -> Option<f64> {
+        self.components.map(|c| c[0].weight)
+    }
+
+    /// Probability that a transmission is received, using the mixture when
+    /// one was selected.
+    pub fn reception_probability(&self) -> f64 {
+        match self.components {
+            Some(components) => components
+                .iter()
+                .map(|c| match Normal::new(c.mean_snr, c.std_dev.max(0.1)) {
+                    Ok(dist) => c.weight * (1.0 - dist.cdf(self.single.threshold)),
+                    Err(_) => 0.0,
+                })
+                .sum(),
+            None => self.single.reception_probability(),
+        }
+    }
+}
+
+/// Estimates the SNR distribution, allowing for two modes.
+///
+/// Fits a single truncated normal (as [`estimate_snr_with_threshold`]) and a
+/// two-component truncated normal mixture, and selects the mixture only when
+/// its Bayesian Information Criterion is lower by more than 10, both
+/// components carry at least 5% of the weight, and there are at least
+/// [`MIN_MIXTURE_OBSERVATIONS`] observations.
+pub fn estimate_snr_mixture(
+    observations: Vec<f64>,
+    threshold: f64,
+) -> Result<SnrMixtureResult, SnrEstimationError> {
+    let single = estimate_snr_with_threshold(observations.clone(), threshold)?;
+    let n = observations.len();
+    let unimodal = SnrMixtureResult {
+        single,
+        components: None,
+        bic_improvement: 0.0,
+    };
+    if n < MIN_MIXTURE_OBSERVATIONS {
+        return Ok(unimodal);
+    }
+
+    let single_nll = SnrCostFunction {
+        observations: observations.clone(),
+        threshold,
+    }
+    .cost(&vec![single.mean_snr, single.std_dev])
+    .map_err(|e| SnrEstimationError::OptimizationFailed(e.to_string()))?;
+
+    // Start from the lower and upper halves of the sorted observations
+    let mut sorted = observations.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let (lower, upper) = sorted.split_at(n / 2);
+    let (mu1, s1) = mean_and_std(lower);
+    let (mu2, s2) = mean_and_std(upper);
+    let start = vec![mu1, s1, mu2, s2, 0.0];
+    let mut simplex = vec![start.clone()];
+    for i in 0..start.len() {
+        let mut point = start.clone();
+        point[i] += if i % 2 == 1 { 0.5 } else { 1.0 };
+        simplex.push(point);
+    }
+
+    let cost_fn = MixtureCostFunction {
+        observations,
+        threshold,
+    };
+    let res = Executor::new(cost_fn, NelderMead::new(simplex))
+        .configure(|state| state.max_iters(1000))
+        .run()
+        .map_err(|e| SnrEstimationError::OptimizationFailed(e.to_string()))?;
+    let best = res.state().get_best_param().ok_or_else(|| {
+        SnrEstimationError::OptimizationFailed("No solution found".to_string())
+    })?;
+    let mixture_nll = res.state().get_best_cost();
+    if !mixture_nll.is_finite() {
+        return Ok(unimodal);
+    }
+
+    let ln_n = (n as f64).ln();
+    let bic_single = 2.0 * ln_n + 2.0 * single_nll;
+    let bic_mixture = 5.0 * ln_n + 2.0 * mixture_nll;
+    let bic_improvement = bic_single - bic_mixture;
+
+    let weight = 1.0 / (1.0 + (-best[4]).exp());
+    let mut components = [
+        SnrComponent {
+            mean_snr: best[0],
+            std_dev: best[1].abs(),
+            weight,
+        },
+        SnrComponent {
+            mean_snr: best[2],
+            std_dev: best[3].abs(),
+            weight: 1.0 - weight,
+        },
+    ];
+    components.sort_by(|a, b| a.mean_snr.total_cmp(&b.mean_snr));
+
+    let selected = bic_improvement > 10.0
+        && components.iter().all(|c| c.weight >= MIN_COMPONENT_WEIGHT);
+    Ok(SnrMixtureResult {
+        single,
+        components: selected.then_some(components),
+        bic_improvement,
+    })
+}
+
+/// Sample mean and standard deviation, with the same floor as the single fit.
+fn mean_and_std(values: &[f64]) -> (f64, f64) {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|&x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, variance.sqrt().max(1.0))
+}
+
+// ============================================================================
+// Network-Wide Estimation
+// ============================================================================
+
+/// A packet received on a link.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkRxRecord {
+    /// Reception time in seconds.
+    pub time_s: f64,
+    /// SNR the receiver measured, in dB.
+    pub snr_db: f64,
+    /// Packets from the same sender missed since the previous record, e.g.
+    /// inferred from a gap in sequence numbers.
+    pub lost_before: u32,
+}
+
+/// Link parameters a scenario configures, for comparison with estimates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfiguredLink {
+    /// Mean SNR at 20 dBm TX power, in dB.
+    pub mean_snr_db_at20dbm: f64,
+    /// SNR standard deviation, in dB.
+    pub snr_std_dev: f64,
+}
+
+/// Receive log of one directed link.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkRxLog {
+    /// Transmitting node.
+    pub from: String,
+    /// Receiving node.
+    pub to: String,
+    /// Sender's TX power in dBm.
+    pub tx_power_dbm: f64,
+    /// Demodulation threshold of the link's spreading factor, in dB.
+    pub threshold_db: f64,
+    /// Received packets in time order.
+    pub records: Vec<LinkRxRecord>,
+    /// Packets missed after the last record.
+    pub lost_after: u32,
+    /// Parameters the scenario configures for the link, if known.
+    pub configured: Option<ConfiguredLink>,
+}
+
+/// SNR estimate of one directed link.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkSnrEstimate {
+    /// Transmitting node.
+    pub from: String,
+    /// Receiving node.
+    pub to: String,
+    /// Packets received.
+    pub received: usize,
+    /// Packets lost.
+    pub lost: u64,
+    /// Sender's TX power in dBm.
+    pub tx_power_dbm: f64,
+    /// Estimated SNR distribution at the sender's TX power, or `None` when
+    /// nothing was received.
+    pub estimate: Option<SnrEstimationResult>,
+    /// Parameters the scenario configures for the link, if known.
+    pub configured: Option<ConfiguredLink>,
+}
+
+impl LinkSnrEstimate {
+    /// Estimated mean SNR scaled to 20 dBm TX power, comparable with
+    /// [`ConfiguredLink::mean_snr_db_at20dbm`].
+    pub fn mean_snr_db_at20dbm(&self) -> Option<f64> {
+        self.estimate.map(|e| e.mean_snr - (self.tx_power_dbm - 20.0))
+    }
+
+    /// Estimated minus configured mean SNR, in dB.
+    pub fn bias_db(&self) -> Option<f64> {
+        Some(self.mean_snr_db_at20dbm()? - self.configured?.mean_snr_db_at20dbm)
+    }
+}
+
+/// Estimates the SNR distribution of every link from its receive log, in
+/// parallel, with [`estimate_snr_censored`]. The estimates are in the order
+/// of the logs.
+pub fn estimate_link_snrs(logs: &[LinkRxLog]) -> Vec<LinkSnrEstimate> {
+    logs.par_iter()
+        .map(|log| {
+            let lost = log.records.iter().map(|r| r.lost_before as u64).sum::<u64>() + log.lost_after as u64;
+            let observations: Vec<f64> = log.records.iter().map(|r| r.snr_db).collect();
+            LinkSnrEstimate {
+                from: log.from.clone(),
+                to: log.to.clone(),
+                received: observations.len(),
+                lost,
+                tx_power_dbm: log.tx_power_dbm,
+                estimate: estimate_snr_censored(observations, lost, log.threshold_db).ok(),
+                configured: log.configured,
+            }
+        })
+        .collect()
+}
+
+/// Writes link estimates as CSV, one row per link, with the estimated and
+/// configured parameters side by side. Empty fields are unknown.
+pub fn write_link_snr_csv<W: Write>(estimates: &[LinkSnrEstimate], writer: &mut W) -> std::io::Result<()> {
+    let opt = |value: Option<f64>| value.map_or_else(String::new, |v| format!("{:.2}", v));
+    writeln!(
+        writer,
+        "from,to,received,lost,tx_power_dbm,estimated_mean_snr_db_at20dbm,estimated_std_dev_db,\
+         configured_mean_snr_db_at20dbm,configured_std_dev_db,bias_db"
+    )?;
+    for link in estimates {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&link.from),
+            csv_field(&link.to),
+            link.received,
+            link.lost,
+            link.tx_power_dbm,
+            opt(link.mean_snr_db_at20dbm()),
+            opt(link.estimate.map(|e| e.std_dev)),
+            opt(link.configured.map(|c| c.mean_snr_db_at20dbm)),
+            opt(link.configured.map(|c| c.snr_std_dev)),
+            opt(link.bias_db())
+        )?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -616,4 +1054,90 @@ mod tests {
         // The estimated mean should be finite
         assert!(result.mean_snr.is_finite());
     }
+
+    /// Evenly spaced quantiles of a normal, as deterministic "samples".
+    fn normal_quantiles(mean: f64, std_dev: f64, count: usize) -> Vec<f64> {
+        let dist = Normal::new(mean, std_dev).unwrap();
+        (1..=count)
+            .map(|i| dist.inverse_cdf(i as f64 / (count + 1) as f64))
+            .collect()
+    }
+
+    #[test]
+    fn test_mixture_detects_two_modes() {
+        let mut data = normal_quantiles(-12.0, 1.0, 30);
+        data.extend(normal_quantiles(2.0, 1.5, 60));
+
+        let result = estimate_snr_mixture(data, -20.0).unwrap();
+        assert!(result.single_fit_poor(), "{:?}", result);
+        let [low, high] = result.components.unwrap();
+        assert!((low.mean_snr + 12.0).abs() < 1.0);
+        assert!((high.mean_snr - 2.0).abs() < 1.0);
+        assert!((result.mixing_weight().unwrap() - 1.0 / 3.0).abs() < 0.05);
+        assert!(result.reception_probability() > 0.99);
+    }
+
+    #[test]
+    fn test_mixture_keeps_unimodal_fit() {
+        let result = estimate_snr_mixture(normal_quantiles(-5.0, 3.0, 60), -20.0).unwrap();
+        assert!(!result.single_fit_poor(), "{:?}", result);
+        assert!(result.mixing_weight().is_none());
+        assert_eq!(result.reception_probability(), result.single.reception_probability());
+
+        // Too few observations to consider a mixture
+        let few = estimate_snr_mixture(vec![-15.0, -14.0, 3.0, 4.0], -20.0).unwrap();
+        assert!(few.components.is_none());
+    }
+
+    #[test]
+    fn test_censored_estimate_uses_lost_packets() {
+        // A weak link: only the upper tail of N(-22, 2) clears -20 dB
+        let data = vec![-19.8, -19.5, -19.0, -18.6, -19.9];
+        let truncated = estimate_snr_with_threshold(data.clone(), -20.0).unwrap();
+        let censored = estimate_snr_censored(data, 15, -20.0).unwrap();
+        assert!(censored.mean_snr < -20.5, "{:?}", censored);
+        assert!((censored.mean_snr + 22.0).abs() < (truncated.mean_snr + 22.0).abs());
+        assert!((censored.reception_probability() - 0.25).abs() < 0.1);
+        assert!(matches!(estimate_snr_censored(vec![], 3, -20.0), Err(SnrEstimationError::NoObservations)));
+    }
+
+    #[test]
+    fn test_link_snrs_estimated_and_tabulated() {
+        let record = |snr_db: f64, lost_before: u32| LinkRxRecord { time_s: 0.0, snr_db, lost_before };
+        let logs = vec![
+            LinkRxLog {
+                from: "A".to_string(),
+                to: "B, relay".to_string(),
+                tx_power_dbm: 22.0,
+                threshold_db: -20.0,
+                records: vec![record(4.0, 0), record(6.0, 0), record(5.0, 0), record(5.5, 0)],
+                lost_after: 0,
+                configured: Some(ConfiguredLink { mean_snr_db_at20dbm: 2.0, snr_std_dev: 1.0 }),
+            },
+            LinkRxLog {
+                from: "B, relay".to_string(),
+                to: "A".to_string(),
+                tx_power_dbm: 20.0,
+                threshold_db: -20.0,
+                records: Vec::new(),
+                lost_after: 12,
+                configured: None,
+            },
+        ];
+        let estimates = estimate_link_snrs(&logs);
+        assert_eq!(estimates[0].received, 4);
+        // 5.1 dB at 22 dBm is 3.1 dB at 20 dBm, about 1 dB above the configured mean
+        let bias = estimates[0].bias_db().unwrap();
+        assert!((bias - 1.1).abs() < 0.3, "{}", bias);
+        assert_eq!(estimates[1].lost, 12);
+        assert!(estimates[1].estimate.is_none());
+
+        let mut csv = Vec::new();
+        write_link_snr_csv(&estimates, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("A,\"B, relay\",4,0,22,3.1"), "{}", lines[1]);
+        assert_eq!(lines[2], "\"B, relay\",A,0,12,20,,,,,");
+    }
 }
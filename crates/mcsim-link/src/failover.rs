@@ -0,0 +1,199 @@
+//! Graceful degradation when elevation data is unavailable.
+//!
+//! A caller that recomputes links while a simulation is running can't stop the
+//! run because one elevation tile failed to download. [`LinkFailover`] keeps
+//! the last successful prediction for every link; when a new prediction fails
+//! for lack of elevation data it returns that prediction marked
+//! [`PredictionFreshness::Stale`] instead of an error. Tile downloads
+//! themselves are retried with backoff by the elevation source, so a later
+//! recomputation picks up fresh terrain once the tile is reachable again.
+//!
+//! Only elevation failures are absorbed. ITM and configuration errors still
+//! propagate, as does an elevation failure for a link that has never been
+//! predicted successfully.
+
+use std::collections::HashMap;
+
+use mcsim_itm::Itm;
+
+use crate::predict::{
+    predict_link_with_elevation_and_params, ElevationSource, LinkPrediction, LinkPredictionConfig,
+    LinkPredictionError, LinkPredictionParams,
+};
+
+/// Whether a prediction reflects current terrain data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredictionFreshness {
+    /// Computed just now.
+    Fresh,
+    /// Last known prediction, reused because elevation data was unavailable.
+    Stale,
+}
+
+/// Counters of predictions served by a [`LinkFailover`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FailoverStats {
+    /// Predictions computed successfully.
+    pub fresh: u64,
+    /// Degraded predictions: last known value reused after an elevation failure.
+    pub stale: u64,
+    /// Elevation failures with no last known value to fall back on.
+    pub failed: u64,
+}
+
+/// Endpoint coordinates identifying a directed link.
+type LinkKey = [u64; 4];
+
+fn link_key(config: &LinkPredictionConfig) -> LinkKey {
+    [
+        config.from_lat.to_bits(),
+        config.from_lon.to_bits(),
+        config.to_lat.to_bits(),
+        config.to_lon.to_bits(),
+    ]
+}
+
+/// Link predictor that falls back to the last known value on elevation failures.
+#[derive(Debug, Default)]
+pub struct LinkFailover {
+    last_known: HashMap<LinkKey, LinkPrediction>,
+    stats: FailoverStats,
+}
+
+impl LinkFailover {
+    /// Create a failover predictor with no history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Predict a link, reusing its last known prediction if elevation data
+    /// can't be fetched.
+    pub fn predict(
+        &mut self,
+        elevation: &ElevationSource,
+        itm: &Itm,
+        config: &LinkPredictionConfig,
+        params: &LinkPredictionParams,
+    ) -> Result<(LinkPrediction, PredictionFreshness), LinkPredictionError> {
+        let result = predict_link_with_elevation_and_params(elevation, itm, config, params);
+        self.resolve(config, result)
+    }
+
+    /// Apply the failover rules to a prediction result for `config`'s link.
+    pub fn resolve(
+        &mut self,
+        config: &LinkPredictionConfig,
+        result: Result<LinkPrediction, LinkPredictionError>,
+    ) -> Result<(LinkPrediction, PredictionFreshness), LinkPredictionError> {
+        let key = link_key(config);
+        match result {
+            Ok(prediction) => {
+                self.stats.fresh += 1;
+                self.last_known.insert(key, prediction.clone());
+                Ok((prediction, PredictionFreshness::Fresh))
+            }
+            Err(LinkPredictionError::DemError(reason)) => match self.last_known.get(&key) {
+                Some(prediction) => {
+                    self.stats.stale += 1;
+                    Ok((prediction.clone(), PredictionFreshness::Stale))
+                }
+                None => {
+                    self.stats.failed += 1;
+                    Err(LinkPredictionError::DemError(reason))
+                }
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Counters of fresh, degraded and failed predictions so far.
+    pub fn stats(&self) -> FailoverStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predict::{
+        AntennaHeightMode, LinkStatus, PathInfo, PredictionMethod, RadioParams, TerrainInfo,
+    };
+
+    fn prediction(snr_db: f64) -> LinkPrediction {
+        LinkPrediction {
+            path: PathInfo {
+                from_lat: 47.0,
+                from_lon: -122.0,
+                to_lat: 47.1,
+                to_lon: -122.0,
+                from_height: 2.0,
+                to_height: 2.0,
+                height_mode: AntennaHeightMode::Agl,
+                distance_km: 11.1,
+            },
+            terrain: TerrainInfo {
+                sample_count: 0,
+                resolution_m: 0.0,
+                min_elevation: 0.0,
+                max_elevation: 0.0,
+                mean_elevation: 0.0,
+                delta_h: 0.0,
+                line_of_sight: true,
+            },
+            radio: RadioParams {
+                freq_mhz: 910.525,
+                tx_power_dbm: 20,
+                noise_floor_dbm: -120.0,
+                spreading_factor: 7,
+                snr_threshold_db: -7.5,
+            },
+            path_loss_db: 130.0,
+            clutter_loss_db: 0.0,
+            prediction_method: PredictionMethod::Itm,
+            itm_warnings: 0,
+            antenna_gain_db: 0.0,
+            snr_db,
+            snr_std_dev_db: 2.0,
+            link_margin_db: snr_db + 7.5,
+            status: LinkStatus::Excellent,
+        }
+    }
+
+    fn tile_error() -> LinkPredictionError {
+        LinkPredictionError::DemError("Failed to download tile z=12 x=655 y=1407: HTTP 503".to_string())
+    }
+
+    #[test]
+    fn test_elevation_failure_reuses_last_known() {
+        let config = LinkPredictionConfig {
+            from_lat: 47.0,
+            from_lon: -122.0,
+            to_lat: 47.1,
+            to_lon: -122.0,
+            ..Default::default()
+        };
+        let mut failover = LinkFailover::new();
+
+        // No history yet: the error propagates
+        assert!(failover.resolve(&config, Err(tile_error())).is_err());
+
+        let (_, freshness) = failover.resolve(&config, Ok(prediction(10.0))).unwrap();
+        assert_eq!(freshness, PredictionFreshness::Fresh);
+
+        let (stale, freshness) = failover.resolve(&config, Err(tile_error())).unwrap();
+        assert_eq!(freshness, PredictionFreshness::Stale);
+        assert_eq!(stale.snr_db, 10.0);
+
+        // Other links and other error kinds aren't covered
+        let reverse = LinkPredictionConfig {
+            from_lat: 47.1,
+            to_lat: 47.0,
+            ..config.clone()
+        };
+        assert!(failover.resolve(&reverse, Err(tile_error())).is_err());
+        let itm_error = LinkPredictionError::ItmError("bad profile".to_string());
+        assert!(failover.resolve(&config, Err(itm_error)).is_err());
+
+        assert_eq!(failover.stats(), FailoverStats { fresh: 1, stale: 1, failed: 2 });
+    }
+}
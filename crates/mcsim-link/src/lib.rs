@@ -9,27 +9,71 @@
 //! ## Features
 //!
 //! - **Link Prediction**: Predict link quality using terrain data and ITM propagation model
-//! - **SNR Estimation**: Estimate true SNR distribution from observed (truncated) measurements
+//! - **SNR Estimation**: Estimate true SNR distribution from observed (truncated) measurements,
+//!   including bimodal links fitted with a two-component mixture, and every link of a network
+//!   at once from per-link receive logs
 //! - **Property-Based Configuration**: Load parameters from simulation properties
+//! - **Antenna Patterns**: Directional gain from per-node orientation and pattern
+//! - **Coverage Maps**: Area-mode SNR rasters around a transmitter, as GeoTIFF or PNG,
+//!   optionally clipped to GeoJSON boundaries and exclusion zones
+//! - **Link Matrix**: Predict all links between a set of nodes in parallel, sharing terrain profiles
+//! - **Clutter Loss**: Per-class loss from land-cover rasters (e.g. ESA WorldCover) at the endpoints
+//!   and along the path
+//! - **Field Calibration**: Fit per-region SNR offsets and spreads from measured links (CSV) and
+//!   apply them to every prediction
+//! - **Prediction Cache**: Persist predictions keyed by link geometry to skip recomputing ITM paths
+//! - **Failover**: Reuse the last known prediction when elevation tiles can't be fetched
+//! - **Synthetic Terrain**: Seeded hills and ridges for deterministic tests without DEM data
+//! - **Network Maps**: Nodes, links colored by status and coverage outlines as KML or GeoJSON
+//! - **Settings Files** (`serde` feature): Save and reload prediction inputs as YAML
 
+mod antenna;
+mod cache;
+mod calibration;
+mod clutter;
+mod coverage;
 mod estimate;
+mod failover;
+mod matrix;
+mod network_map;
 mod predict;
+mod region;
+#[cfg(feature = "serde")]
+mod settings;
+mod synthetic;
 
+pub use antenna::{Antenna, AntennaPattern};
+pub use cache::{LinkCache, LinkCacheStats};
+pub use calibration::{
+    CalibrationCorrection, CalibrationFit, CalibrationSample, FieldMeasurement, LinkCalibration, RegionCalibration,
+};
+pub use clutter::{predict_link_with_clutter, ClassClutter, ClutterLoss, ClutterModel};
+pub use coverage::{compute_coverage, BoundingBox, ColorRamp, CoverageConfig, CoverageMap};
 pub use estimate::{
-    estimate_snr, estimate_snr_with_config, estimate_snr_with_threshold,
-    LoraModulationParams, LoraPhyConfig, SnrEstimationError, SnrEstimationResult,
+    estimate_link_snrs, estimate_snr, estimate_snr_censored, estimate_snr_mixture, estimate_snr_with_config,
+    estimate_snr_with_threshold, write_link_snr_csv, ConfiguredLink, LinkRxLog, LinkRxRecord, LinkSnrEstimate,
+    LoraModulationParams, LoraPhyConfig, SnrComponent, SnrEstimationError, SnrEstimationResult,
+    SnrMixtureResult, MIN_MIXTURE_OBSERVATIONS,
 };
+pub use failover::{FailoverStats, LinkFailover, PredictionFreshness};
+pub use matrix::{predict_link_matrix, LinkMatrix, MatrixNode};
+pub use network_map::{MapFormat, MapLink, MapNode, NetworkMap};
 pub use predict::{
     // Legacy DEM-based functions
-    load_dem, load_itm, predict_link, predict_link_with_params,
+    has_line_of_sight, load_dem, load_itm, predict_link, predict_link_with_params,
     // New elevation source abstraction
-    ElevationSource, load_aws_elevation, load_aws_elevation_with_callback,
+    ElevationSource, load_aws_elevation, load_aws_elevation_with_callback, resolve_offline_mode,
     predict_link_with_elevation, predict_link_with_elevation_and_params,
     // Types
-    LinkPrediction, LinkPredictionConfig, LinkPredictionError, LinkPredictionParams,
+    AntennaHeightMode, LinkPrediction, LinkPredictionConfig, LinkPredictionError, LinkPredictionParams,
     LinkStatus, PathInfo, PredictionMethod, RadioParams, TerrainInfo,
     ITM_MIN_DISTANCE_M, FSPL_MIN_DISTANCE_M, COLOCATED_PATH_LOSS_DB,
 };
+pub use region::{CoverageRegion, Polygon};
+pub use synthetic::{SyntheticTerrain, SyntheticTerrainConfig};
+
+#[cfg(feature = "serde")]
+pub use settings::PredictionSettings;
 
-// Re-export download stats from mcsim-dem
-pub use mcsim_dem::DownloadStats;
+// Re-export download stats and land cover from mcsim-dem
+pub use mcsim_dem::{DownloadStats, LandCoverClass, LandCoverMap, OfflineMode, RetryPolicy};
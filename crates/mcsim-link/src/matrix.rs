@@ -0,0 +1,263 @@
+//! Batch link prediction between every pair of nodes.
+//!
+//! Predicting each directed link on its own samples the terrain twice per
+//! pair, once in each direction. [`predict_link_matrix`] samples the profile
+//! between each pair of distinct positions once and reuses it, reversed, for
+//! the opposite direction and for any other nodes at the same positions. Pairs
+//! are sampled and predicted in parallel, each worker thread with its own ITM
+//! instance.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use mcsim_itm::Itm;
+use rayon::prelude::*;
+
+use crate::antenna::Antenna;
+use crate::predict::{
+    load_itm, predict_link_from_profile, sample_path_profile, ElevationSource, LinkPrediction,
+    LinkPredictionConfig, LinkPredictionError, LinkPredictionParams, PathProfile,
+};
+
+// One ITM instance per worker thread, as in the model builder: the library
+// itself has no global state, but sharing one `libloading::Library` across
+// threads is avoided.
+thread_local! {
+    static THREAD_ITM: RefCell<Option<Itm>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn with_thread_itm<F, R>(f: F) -> Result<R, LinkPredictionError>
+where
+    F: FnOnce(&Itm) -> Result<R, LinkPredictionError>,
+{
+    THREAD_ITM.with(|cell| {
+        let mut borrow = cell.borrow_mut();
+        if borrow.is_none() {
+            *borrow = Some(load_itm()?);
+        }
+        f(borrow.as_ref().unwrap())
+    })
+}
+
+/// A node taking part in a link matrix.
+#[derive(Debug, Clone)]
+pub struct MatrixNode {
+    /// Latitude (degrees).
+    pub lat: f64,
+    /// Longitude (degrees).
+    pub lon: f64,
+    /// Antenna height (meters, interpreted per the base config's `height_mode`).
+    pub height: f64,
+    /// TX power in dBm.
+    pub tx_power_dbm: i8,
+    /// LoRa spreading factor (7-12).
+    pub spreading_factor: u8,
+    /// Antenna used both to transmit and to receive.
+    pub antenna: Antenna,
+}
+
+impl MatrixNode {
+    /// A node at the given position with the default radio settings.
+    pub fn new(lat: f64, lon: f64) -> Self {
+        let defaults = LinkPredictionConfig::default();
+        Self {
+            lat,
+            lon,
+            height: defaults.from_height,
+            tx_power_dbm: defaults.tx_power_dbm,
+            spreading_factor: defaults.spreading_factor,
+            antenna: Antenna::default(),
+        }
+    }
+
+    fn position_key(&self) -> [u64; 2] {
+        [self.lat.to_bits(), self.lon.to_bits()]
+    }
+}
+
+/// Predictions for every directed link between a set of nodes.
+#[derive(Debug)]
+pub struct LinkMatrix {
+    len: usize,
+    /// Row-major `len * len` results; diagonal entries are `None`.
+    links: Vec<Option<Result<LinkPrediction, LinkPredictionError>>>,
+}
+
+impl LinkMatrix {
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the matrix covers no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Prediction for the link from node `from` to node `to`.
+    ///
+    /// Returns `None` when `from == to` or either index is out of range.
+    pub fn get(&self, from: usize, to: usize) -> Option<&Result<LinkPrediction, LinkPredictionError>> {
+        if from >= self.len || to >= self.len {
+            return None;
+        }
+        self.links[from * self.len + to].as_ref()
+    }
+
+    /// Iterate over `(from, to, result)` for every directed link.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &Result<LinkPrediction, LinkPredictionError>)> {
+        self.links
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, link)| link.as_ref().map(|r| (i / self.len, i % self.len, r)))
+    }
+}
+
+/// Predict every directed link between `nodes`.
+///
+/// `base` supplies the settings shared by all links (frequency, height mode
+/// and number of terrain samples); endpoint positions, heights, TX power,
+/// spreading factor and antennas come from the nodes. Failures are recorded
+/// per link. The only error returned for the whole matrix is failing to load
+/// the ITM library.
+pub fn predict_link_matrix(
+    elevation: &ElevationSource,
+    nodes: &[MatrixNode],
+    base: &LinkPredictionConfig,
+    params: &LinkPredictionParams,
+) -> Result<LinkMatrix, LinkPredictionError> {
+    // Fail early if the library can't be loaded; workers load their own copies
+    drop(load_itm()?);
+
+    let n = nodes.len();
+
+    // Distinct positions, so co-located nodes share profiles
+    let mut position_index: HashMap<[u64; 2], usize> = HashMap::new();
+    let mut positions: Vec<usize> = Vec::new();
+    let node_position: Vec<usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            *position_index.entry(node.position_key()).or_insert_with(|| {
+                positions.push(i);
+                positions.len() - 1
+            })
+        })
+        .collect();
+
+    // Unordered position pairs that some link needs
+    let mut needed: HashSet<(usize, usize)> = HashSet::new();
+    for from in 0..n {
+        for to in (from + 1)..n {
+            let (a, b) = (node_position[from], node_position[to]);
+            needed.insert((a.min(b), a.max(b)));
+        }
+    }
+    let needed: Vec<(usize, usize)> = needed.into_iter().collect();
+
+    let profiles: HashMap<(usize, usize), Result<PathProfile, LinkPredictionError>> = needed
+        .par_iter()
+        .map(|&(a, b)| {
+            let (from, to) = (&nodes[positions[a]], &nodes[positions[b]]);
+            let config = LinkPredictionConfig {
+                from_lat: from.lat,
+                from_lon: from.lon,
+                to_lat: to.lat,
+                to_lon: to.lon,
+                ..base.clone()
+            };
+            ((a, b), sample_path_profile(elevation, &config))
+        })
+        .collect();
+
+    let pairs: Vec<(usize, usize)> = (0..n)
+        .flat_map(|from| (0..n).filter(move |&to| to != from).map(move |to| (from, to)))
+        .collect();
+
+    let predictions: Vec<((usize, usize), Result<LinkPrediction, LinkPredictionError>)> = pairs
+        .par_iter()
+        .map(|&(from, to)| {
+            let (a, b) = (node_position[from], node_position[to]);
+            let result = match &profiles[&(a.min(b), a.max(b))] {
+                Ok(profile) => {
+                    let config = link_config(&nodes[from], &nodes[to], base);
+                    if a <= b {
+                        with_thread_itm(|itm| predict_link_from_profile(itm, &config, params, profile))
+                    } else {
+                        let profile = profile.reversed();
+                        with_thread_itm(|itm| predict_link_from_profile(itm, &config, params, &profile))
+                    }
+                }
+                Err(e) => Err(e.clone()),
+            };
+            ((from, to), result)
+        })
+        .collect();
+
+    let mut links: Vec<Option<Result<LinkPrediction, LinkPredictionError>>> = (0..n * n).map(|_| None).collect();
+    for ((from, to), result) in predictions {
+        links[from * n + to] = Some(result);
+    }
+
+    Ok(LinkMatrix { len: n, links })
+}
+
+/// Per-link config for the link from `from` to `to`.
+fn link_config(from: &MatrixNode, to: &MatrixNode, base: &LinkPredictionConfig) -> LinkPredictionConfig {
+    LinkPredictionConfig {
+        from_lat: from.lat,
+        from_lon: from.lon,
+        to_lat: to.lat,
+        to_lon: to.lon,
+        from_height: from.height,
+        to_height: to.height,
+        tx_power_dbm: from.tx_power_dbm,
+        spreading_factor: from.spreading_factor,
+        from_antenna: from.antenna.clone(),
+        to_antenna: to.antenna.clone(),
+        ..base.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_config_takes_endpoint_settings() {
+        let base = LinkPredictionConfig {
+            freq_mhz: 869.525,
+            terrain_samples: 50,
+            ..Default::default()
+        };
+        let from = MatrixNode {
+            height: 10.0,
+            tx_power_dbm: 22,
+            spreading_factor: 10,
+            ..MatrixNode::new(47.0, -122.0)
+        };
+        let to = MatrixNode::new(47.1, -122.1);
+
+        let config = link_config(&from, &to, &base);
+        assert_eq!((config.from_lat, config.to_lon), (47.0, -122.1));
+        assert_eq!((config.from_height, config.to_height), (10.0, 2.0));
+        assert_eq!(config.tx_power_dbm, 22);
+        assert_eq!(config.spreading_factor, 10);
+        assert_eq!(config.freq_mhz, 869.525);
+        assert_eq!(config.terrain_samples, 50);
+    }
+
+    #[test]
+    fn test_matrix_indexing() {
+        let prediction_error = || Err(LinkPredictionError::DemError("no tile".to_string()));
+        let matrix = LinkMatrix {
+            len: 2,
+            links: vec![None, Some(prediction_error()), Some(prediction_error()), None],
+        };
+        assert!(matrix.get(0, 0).is_none());
+        assert!(matrix.get(0, 1).is_some());
+        assert!(matrix.get(2, 0).is_none());
+        let pairs: Vec<(usize, usize)> = matrix.iter().map(|(from, to, _)| (from, to)).collect();
+        assert_eq!(pairs, vec![(0, 1), (1, 0)]);
+    }
+}
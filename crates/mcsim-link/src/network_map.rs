@@ -0,0 +1,391 @@
+//! Network maps for Google Earth and GIS tools.
+//!
+//! A [`NetworkMap`] collects the nodes of a network and the links predicted
+//! between them, and renders them as KML (for Google Earth) or as a GeoJSON
+//! FeatureCollection. Each node becomes a point; each pair of linked nodes a
+//! line colored by the [`LinkStatus`] of its weaker direction, with the SNR and
+//! margin of both directions in its description.
+//!
+//! Each node also gets a coverage polygon: the convex hull of the node and the
+//! nodes its transmissions reach. It is a quick outline of where the node is
+//! heard, not a terrain-aware coverage map (see [`compute_coverage`] for that).
+//!
+//! [`compute_coverage`]: crate::compute_coverage
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::predict::{LinkPredictionError, LinkStatus};
+
+/// A node on the map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapNode {
+    /// Node name.
+    pub name: String,
+    /// Latitude in degrees.
+    pub lat: f64,
+    /// Longitude in degrees.
+    pub lon: f64,
+    /// Antenna altitude above sea level in meters, if known.
+    pub altitude_m: Option<f64>,
+}
+
+/// A directed link between two nodes on the map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapLink {
+    /// Index of the transmitting node.
+    pub from: usize,
+    /// Index of the receiving node.
+    pub to: usize,
+    /// Mean SNR in dB.
+    pub snr_db: f64,
+    /// SNR above the demodulation threshold in dB.
+    pub link_margin_db: f64,
+    /// Link quality.
+    pub status: LinkStatus,
+}
+
+/// Output format of a [`NetworkMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapFormat {
+    /// KML, for Google Earth.
+    Kml,
+    /// GeoJSON FeatureCollection.
+    GeoJson,
+}
+
+impl MapFormat {
+    /// Format for a file name: `.kml` or `.geojson`/`.json`.
+    pub fn from_path(path: &Path) -> Result<Self, LinkPredictionError> {
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("kml") => Ok(MapFormat::Kml),
+            Some("geojson") | Some("json") => Ok(MapFormat::GeoJson),
+            _ => Err(LinkPredictionError::ConfigError(format!(
+                "Unknown map format for '{}'. Use a .kml, .geojson or .json file.",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// Nodes and predicted links of a network.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NetworkMap {
+    /// Nodes, referenced by index from the links.
+    pub nodes: Vec<MapNode>,
+    /// Directed links.
+    pub links: Vec<MapLink>,
+}
+
+/// One line on the map: a pair of nodes and its links in either direction.
+struct MapLine<'a> {
+    a: usize,
+    b: usize,
+    links: Vec<&'a MapLink>,
+}
+
+impl MapLine<'_> {
+    /// Status of the weaker direction.
+    fn status(&self) -> LinkStatus {
+        self.links.iter().map(|l| l.status).max_by_key(|s| status_rank(*s)).unwrap_or(LinkStatus::Unreliable)
+    }
+}
+
+/// Rank of a status, worst highest.
+fn status_rank(status: LinkStatus) -> u8 {
+    match status {
+        LinkStatus::Excellent => 0,
+        LinkStatus::Good => 1,
+        LinkStatus::Marginal => 2,
+        LinkStatus::Unreliable => 3,
+    }
+}
+
+/// Short name of a status, used for styles and properties.
+fn status_name(status: LinkStatus) -> &'static str {
+    match status {
+        LinkStatus::Excellent => "excellent",
+        LinkStatus::Good => "good",
+        LinkStatus::Marginal => "marginal",
+        LinkStatus::Unreliable => "unreliable",
+    }
+}
+
+/// Line color of a status, as `#rrggbb`.
+fn status_color(status: LinkStatus) -> &'static str {
+    match status {
+        LinkStatus::Excellent => "#1a9850",
+        LinkStatus::Good => "#91cf60",
+        LinkStatus::Marginal => "#fc8d59",
+        LinkStatus::Unreliable => "#d73027",
+    }
+}
+
+/// KML color (`aabbggrr`) of a `#rrggbb` color.
+fn kml_color(rgb: &str, alpha: u8) -> String {
+    format!("{:02x}{}{}{}", alpha, &rgb[5..7], &rgb[3..5], &rgb[1..3])
+}
+
+/// Escape text for XML.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl NetworkMap {
+    /// Lines between linked node pairs, in node order.
+    fn lines(&self) -> Vec<MapLine<'_>> {
+        let mut lines: BTreeMap<(usize, usize), Vec<&MapLink>> = BTreeMap::new();
+        for link in &self.links {
+            let key = (link.from.min(link.to), link.from.max(link.to));
+            lines.entry(key).or_default().push(link);
+        }
+        lines.into_iter().map(|((a, b), links)| MapLine { a, b, links }).collect()
+    }
+
+    /// Description of a line: SNR and margin in each direction.
+    fn describe(&self, line: &MapLine) -> String {
+        line.links
+            .iter()
+            .map(|l| {
+                format!(
+                    "{} -> {}: {:.1} dB SNR, {:.1} dB margin",
+                    self.nodes[l.from].name, self.nodes[l.to].name, l.snr_db, l.link_margin_db
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Coverage polygon of a node as a closed `[lon, lat]` ring, or `None`
+    /// if it reaches fewer than two other nodes.
+    pub fn coverage_polygon(&self, node: usize) -> Option<Vec<[f64; 2]>> {
+        let mut points: Vec<[f64; 2]> = self
+            .links
+            .iter()
+            .filter(|l| l.from == node)
+            .map(|l| [self.nodes[l.to].lon, self.nodes[l.to].lat])
+            .collect();
+        points.push([self.nodes[node].lon, self.nodes[node].lat]);
+        let mut hull = convex_hull(points);
+        if hull.len() < 3 {
+            return None;
+        }
+        hull.push(hull[0]);
+        Some(hull)
+    }
+
+    /// Render as a GeoJSON FeatureCollection.
+    pub fn to_geojson(&self) -> Value {
+        let mut features = Vec::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            if let Some(ring) = self.coverage_polygon(i) {
+                features.push(json!({
+                    "type": "Feature",
+                    "geometry": { "type": "Polygon", "coordinates": [ring] },
+                    "properties": { "kind": "coverage", "name": node.name },
+                }));
+            }
+        }
+        for line in self.lines() {
+            let (a, b) = (&self.nodes[line.a], &self.nodes[line.b]);
+            let status = line.status();
+            let directions: Vec<Value> = line
+                .links
+                .iter()
+                .map(|l| {
+                    json!({
+                        "from": self.nodes[l.from].name,
+                        "to": self.nodes[l.to].name,
+                        "snr_db": l.snr_db,
+                        "link_margin_db": l.link_margin_db,
+                        "status": status_name(l.status),
+                    })
+                })
+                .collect();
+            features.push(json!({
+                "type": "Feature",
+                "geometry": { "type": "LineString", "coordinates": [[a.lon, a.lat], [b.lon, b.lat]] },
+                "properties": {
+                    "kind": "link",
+                    "name": format!("{} - {}", a.name, b.name),
+                    "status": status_name(status),
+                    "stroke": status_color(status),
+                    "directions": directions,
+                },
+            }));
+        }
+        for node in &self.nodes {
+            let mut coordinates = vec![node.lon, node.lat];
+            coordinates.extend(node.altitude_m);
+            features.push(json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": coordinates },
+                "properties": { "kind": "node", "name": node.name },
+            }));
+        }
+        json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+    }
+
+    /// Render as a KML document with folders for nodes, links and coverage.
+    pub fn to_kml(&self) -> String {
+        let mut kml = String::new();
+        kml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        kml.push_str("<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n<Document>\n");
+        kml.push_str("<name>MCSim network</name>\n");
+        for status in [LinkStatus::Excellent, LinkStatus::Good, LinkStatus::Marginal, LinkStatus::Unreliable] {
+            let _ = writeln!(
+                kml,
+                "<Style id=\"link-{}\"><LineStyle><color>{}</color><width>3</width></LineStyle></Style>",
+                status_name(status),
+                kml_color(status_color(status), 0xff)
+            );
+        }
+        let _ = writeln!(
+            kml,
+            "<Style id=\"coverage\"><LineStyle><color>{}</color></LineStyle><PolyStyle><color>{}</color></PolyStyle></Style>",
+            kml_color("#4575b4", 0xc0),
+            kml_color("#4575b4", 0x40)
+        );
+
+        kml.push_str("<Folder>\n<name>Nodes</name>\n");
+        for node in &self.nodes {
+            let (altitude, mode) = match node.altitude_m {
+                Some(altitude) => (altitude, "absolute"),
+                None => (0.0, "clampToGround"),
+            };
+            let _ = writeln!(
+                kml,
+                "<Placemark><name>{}</name><Point><altitudeMode>{}</altitudeMode><coordinates>{},{},{}</coordinates></Point></Placemark>",
+                xml_escape(&node.name),
+                mode,
+                node.lon,
+                node.lat,
+                altitude
+            );
+        }
+        kml.push_str("</Folder>\n<Folder>\n<name>Links</name>\n");
+        for line in self.lines() {
+            let (a, b) = (&self.nodes[line.a], &self.nodes[line.b]);
+            let _ = writeln!(
+                kml,
+                "<Placemark><name>{} - {}</name><description>{}</description><styleUrl>#link-{}</styleUrl><LineString><tessellate>1</tessellate><coordinates>{},{} {},{}</coordinates></LineString></Placemark>",
+                xml_escape(&a.name),
+                xml_escape(&b.name),
+                xml_escape(&self.describe(&line)),
+                status_name(line.status()),
+                a.lon,
+                a.lat,
+                b.lon,
+                b.lat
+            );
+        }
+        kml.push_str("</Folder>\n<Folder>\n<name>Coverage</name>\n<visibility>0</visibility>\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let Some(ring) = self.coverage_polygon(i) else {
+                continue;
+            };
+            let coordinates: Vec<String> = ring.iter().map(|[lon, lat]| format!("{},{}", lon, lat)).collect();
+            let _ = writeln!(
+                kml,
+                "<Placemark><name>{}</name><visibility>0</visibility><styleUrl>#coverage</styleUrl><Polygon><tessellate>1</tessellate><outerBoundaryIs><LinearRing><coordinates>{}</coordinates></LinearRing></outerBoundaryIs></Polygon></Placemark>",
+                xml_escape(&node.name),
+                coordinates.join(" ")
+            );
+        }
+        kml.push_str("</Folder>\n</Document>\n</kml>\n");
+        kml
+    }
+
+    /// Write the map to `path` in the format its extension names.
+    pub fn write(&self, path: &Path) -> Result<(), LinkPredictionError> {
+        let text = match MapFormat::from_path(path)? {
+            MapFormat::Kml => self.to_kml(),
+            MapFormat::GeoJson => serde_json::to_string_pretty(&self.to_geojson())
+                .map_err(|e| LinkPredictionError::ConfigError(e.to_string()))?,
+        };
+        std::fs::write(path, text).map_err(|e| {
+            LinkPredictionError::ConfigError(format!("Failed to write map {}: {}", path.display(), e))
+        })
+    }
+}
+
+/// Convex hull of points (Andrew's monotone chain), counter-clockwise and
+/// without repeating the first point.
+fn convex_hull(mut points: Vec<[f64; 2]>) -> Vec<[f64; 2]> {
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: [f64; 2], a: [f64; 2], b: [f64; 2]| (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0]);
+    let mut hull: Vec<[f64; 2]> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, lat: f64, lon: f64) -> MapNode {
+        MapNode { name: name.to_string(), lat, lon, altitude_m: None }
+    }
+
+    fn link(from: usize, to: usize, margin: f64, status: LinkStatus) -> MapLink {
+        MapLink { from, to, snr_db: margin - 7.5, link_margin_db: margin, status }
+    }
+
+    #[test]
+    fn test_network_map_export() {
+        let map = NetworkMap {
+            nodes: vec![node("Hub", 47.0, -122.0), node("East", 47.0, -121.9), node("North", 47.1, -122.0), node("A&B", 47.05, -121.95)],
+            links: vec![
+                link(0, 1, 12.0, LinkStatus::Excellent),
+                link(1, 0, 3.0, LinkStatus::Marginal),
+                link(0, 2, 7.0, LinkStatus::Good),
+                link(0, 3, 11.0, LinkStatus::Excellent),
+            ],
+        };
+
+        // The hub reaches the other three; the inner node isn't on the hull
+        let ring = map.coverage_polygon(0).unwrap();
+        assert_eq!(ring.len(), 4);
+        assert_eq!(ring.first(), ring.last());
+        assert!(map.coverage_polygon(1).is_none());
+
+        let geojson = map.to_geojson();
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 1 + 3 + 4);
+        let hub_east = &features[1]["properties"];
+        assert_eq!(hub_east["name"], "Hub - East");
+        // Colored by the weaker direction
+        assert_eq!(hub_east["status"], "marginal");
+        assert_eq!(hub_east["directions"].as_array().unwrap().len(), 2);
+
+        let kml = map.to_kml();
+        assert!(kml.contains("<name>A&amp;B</name>"));
+        assert!(kml.contains("<styleUrl>#link-marginal</styleUrl>"));
+        assert_eq!(kml.matches("<LineString>").count(), 3);
+        assert!(kml.contains("<color>ff50981a</color>"));
+
+        assert_eq!(MapFormat::from_path(Path::new("net.KML")).unwrap(), MapFormat::Kml);
+        assert_eq!(MapFormat::from_path(Path::new("net.geojson")).unwrap(), MapFormat::GeoJson);
+        assert!(MapFormat::from_path(Path::new("net.yaml")).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 //! Link prediction using DEM and ITM.
 
-use mcsim_dem::{AwsTileFetcher, DemManager, DownloadCallback, DownloadStats};
+use mcsim_dem::{AwsTileFetcher, DemManager, DownloadCallback, DownloadStats, OfflineMode};
 use mcsim_itm::{Climate, Itm, Polarization, TerrainProfile};
 use mcsim_model::properties::{
     ResolvedProperties, SimulationScope,
@@ -19,10 +19,15 @@ use mcsim_model::properties::{
     COLOCATED_PATH_LOSS_DB as COLOCATED_PATH_LOSS_DB_PROP,
     // Elevation tile parameters
     PREDICT_ELEVATION_CACHE_DIR, PREDICT_ELEVATION_ZOOM_LEVEL,
+    PREDICT_ELEVATION_OFFLINE, PREDICT_ELEVATION_OFFLINE_FALLBACK_M,
 };
 use std::path::Path;
 use thiserror::Error;
 
+use crate::antenna::{bearing_deg, Antenna};
+use crate::calibration::LinkCalibration;
+use crate::synthetic::SyntheticTerrain;
+
 /// Minimum path distance in meters required for ITM calculations.
 /// Below this threshold, free-space path loss is used instead.
 ///
@@ -63,7 +68,11 @@ pub const COLOCATED_PATH_LOSS_DB: f64 = 20.0;
 /// let sim_props: ResolvedProperties<SimulationScope> = ResolvedProperties::new();
 /// let params = LinkPredictionParams::from_properties(&sim_props);
 /// ```
-#[derive(Debug, Clone)]
+///
+/// With the `serde` feature, missing fields deserialize to their defaults.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct LinkPredictionParams {
     // Radio parameters
     /// Noise floor in dBm.
@@ -102,6 +111,11 @@ pub struct LinkPredictionParams {
     // Colocated parameters
     /// Fixed near-field path loss for colocated nodes (dB).
     pub colocated_path_loss_db: f64,
+
+    // Field calibration
+    /// Corrections fitted from real measurements, applied to every
+    /// prediction. See [`LinkCalibration`].
+    pub calibration: Option<LinkCalibration>,
 }
 
 impl Default for LinkPredictionParams {
@@ -130,6 +144,9 @@ impl Default for LinkPredictionParams {
 
             // Colocated parameters
             colocated_path_loss_db: 20.0,
+
+            // Field calibration
+            calibration: None,
         }
     }
 }
@@ -171,6 +188,9 @@ impl LinkPredictionParams {
 
             // Colocated parameters
             colocated_path_loss_db: props.get(&COLOCATED_PATH_LOSS_DB_PROP),
+
+            // Field calibration is loaded from its own file
+            calibration: None,
         }
     }
 
@@ -242,7 +262,7 @@ impl LinkPredictionParams {
 }
 
 /// Errors that can occur during link prediction.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum LinkPredictionError {
     #[error("DEM error: {0}")]
     DemError(String),
@@ -254,8 +274,98 @@ pub enum LinkPredictionError {
     ConfigError(String),
 }
 
+/// Reference datum for antenna heights in [`LinkPredictionConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum AntennaHeightMode {
+    /// Heights are above ground level at the endpoint.
+    #[default]
+    Agl,
+    /// Heights are absolute elevations above mean sea level. They are
+    /// converted to AGL using the DEM ground elevation at each endpoint.
+    Msl,
+}
+
+impl std::fmt::Display for AntennaHeightMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AntennaHeightMode::Agl => write!(f, "AGL"),
+            AntennaHeightMode::Msl => write!(f, "MSL"),
+        }
+    }
+}
+
+impl std::str::FromStr for AntennaHeightMode {
+    type Err = LinkPredictionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "agl" => Ok(AntennaHeightMode::Agl),
+            "msl" => Ok(AntennaHeightMode::Msl),
+            other => Err(LinkPredictionError::ConfigError(format!(
+                "Unknown antenna height mode '{}'. Use 'agl' or 'msl'.",
+                other
+            ))),
+        }
+    }
+}
+
+/// Convert configured antenna heights to heights above ground.
+///
+/// `from_ground_m` and `to_ground_m` are the DEM ground elevations at the
+/// transmitter and receiver. Returns an error if a resulting height is
+/// negative, which for MSL mode means the antenna is below the terrain.
+pub(crate) fn resolve_antenna_heights(
+    config: &LinkPredictionConfig,
+    from_ground_m: f64,
+    to_ground_m: f64,
+) -> Result<(f64, f64), LinkPredictionError> {
+    let (from_agl, to_agl) = match config.height_mode {
+        AntennaHeightMode::Agl => (config.from_height, config.to_height),
+        AntennaHeightMode::Msl => (config.from_height - from_ground_m, config.to_height - to_ground_m),
+    };
+
+    for (label, configured, ground, agl) in [
+        ("transmitter", config.from_height, from_ground_m, from_agl),
+        ("receiver", config.to_height, to_ground_m, to_agl),
+    ] {
+        if !agl.is_finite() || agl < 0.0 {
+            return Err(LinkPredictionError::ConfigError(match config.height_mode {
+                AntennaHeightMode::Agl => format!(
+                    "{} antenna height {:.1}m AGL must not be negative",
+                    label, configured
+                ),
+                AntennaHeightMode::Msl => format!(
+                    "{} antenna height {:.1}m MSL is below ground elevation {:.1}m",
+                    label, configured, ground
+                ),
+            }));
+        }
+    }
+
+    Ok((from_agl, to_agl))
+}
+
+/// Gain of the transmitter antenna towards the receiver plus the receiver
+/// antenna towards the transmitter.
+///
+/// `from_alt_m` and `to_alt_m` are the antenna altitudes above sea level; the
+/// elevation angle between them ignores earth curvature.
+pub(crate) fn path_antenna_gain_db(config: &LinkPredictionConfig, from_alt_m: f64, to_alt_m: f64, distance_m: f64) -> f64 {
+    let forward = bearing_deg(config.from_lat, config.from_lon, config.to_lat, config.to_lon);
+    let reverse = bearing_deg(config.to_lat, config.to_lon, config.from_lat, config.from_lon);
+    let elevation = (to_alt_m - from_alt_m).atan2(distance_m).to_degrees();
+    config.from_antenna.gain_towards_db(forward, elevation)
+        + config.to_antenna.gain_towards_db(reverse, -elevation)
+}
+
 /// Configuration for link prediction.
-#[derive(Debug, Clone)]
+///
+/// With the `serde` feature, missing fields deserialize to their defaults.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct LinkPredictionConfig {
     /// Latitude of the transmitter (degrees).
     pub from_lat: f64,
@@ -265,10 +375,12 @@ pub struct LinkPredictionConfig {
     pub to_lat: f64,
     /// Longitude of the receiver (degrees).
     pub to_lon: f64,
-    /// Height of the transmitter above ground (meters).
+    /// Height of the transmitter antenna (meters, interpreted per `height_mode`).
     pub from_height: f64,
-    /// Height of the receiver above ground (meters).
+    /// Height of the receiver antenna (meters, interpreted per `height_mode`).
     pub to_height: f64,
+    /// Whether `from_height`/`to_height` are above ground or above sea level.
+    pub height_mode: AntennaHeightMode,
     /// Frequency in MHz.
     pub freq_mhz: f64,
     /// TX power in dBm.
@@ -277,6 +389,10 @@ pub struct LinkPredictionConfig {
     pub spreading_factor: u8,
     /// Number of terrain samples along the path.
     pub terrain_samples: usize,
+    /// Transmitter antenna.
+    pub from_antenna: Antenna,
+    /// Receiver antenna.
+    pub to_antenna: Antenna,
 }
 
 impl Default for LinkPredictionConfig {
@@ -288,10 +404,13 @@ impl Default for LinkPredictionConfig {
             to_lon: 0.0,
             from_height: 2.0,
             to_height: 2.0,
+            height_mode: AntennaHeightMode::Agl,
             freq_mhz: 910.525, // LoRa default
             tx_power_dbm: 20,
             spreading_factor: 7,
             terrain_samples: 100,
+            from_antenna: Antenna::default(),
+            to_antenna: Antenna::default(),
         }
     }
 }
@@ -312,6 +431,9 @@ pub struct PathInfo {
     pub from_height: f64,
     /// Height of receiver above ground (meters).
     pub to_height: f64,
+    /// Datum the antenna heights were configured in. `from_height` and
+    /// `to_height` above are always resolved to AGL.
+    pub height_mode: AntennaHeightMode,
     /// Path distance in kilometers.
     pub distance_km: f64,
 }
@@ -332,6 +454,9 @@ pub struct TerrainInfo {
     pub mean_elevation: f64,
     /// Terrain irregularity (delta H) in meters.
     pub delta_h: f64,
+    /// Whether the direct path between the antennas clears the terrain.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub line_of_sight: bool,
 }
 
 /// Radio parameters used in the prediction.
@@ -414,12 +539,17 @@ pub struct LinkPrediction {
     pub terrain: TerrainInfo,
     /// Radio parameters used.
     pub radio: RadioParams,
-    /// Median path loss in dB (from ITM or free-space).
+    /// Median path loss in dB (from ITM or free-space), including clutter.
     pub path_loss_db: f64,
+    /// Clutter loss from land cover included in `path_loss_db` (dB).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub clutter_loss_db: f64,
     /// The method used for prediction.
     pub prediction_method: PredictionMethod,
     /// ITM warning flags (0 if none, or if free-space was used).
     pub itm_warnings: u32,
+    /// Combined gain of both antennas along the path, in dB.
+    pub antenna_gain_db: f64,
     /// Predicted mean SNR in dB.
     pub snr_db: f64,
     /// Estimated standard deviation of SNR (dB).
@@ -435,6 +565,73 @@ impl LinkPrediction {
     pub fn is_viable(&self) -> bool {
         self.link_margin_db > 0.0
     }
+
+    /// Predicted mean SNR if the transmitter used `tx_power_dbm` instead of
+    /// the power the prediction was made with.
+    ///
+    /// Path loss is reciprocal, so this also gives the reverse-direction SNR
+    /// when passed the receiving node's TX power.
+    pub fn snr_db_at_tx_power(&self, tx_power_dbm: i8) -> f64 {
+        self.snr_db + (tx_power_dbm as f64 - self.radio.tx_power_dbm as f64)
+    }
+
+    /// Link margin in dB if the transmitter used `tx_power_dbm`.
+    pub fn link_margin_db_at_tx_power(&self, tx_power_dbm: i8) -> f64 {
+        self.snr_db_at_tx_power(tx_power_dbm) - self.radio.snr_threshold_db
+    }
+
+    /// Rician K-factor in dB suggested for per-packet fading on this link,
+    /// or `None` if the path is obstructed and Rayleigh fading applies.
+    ///
+    /// Rougher terrain scatters more power, so the direct path dominates less.
+    pub fn rician_k_factor_db(&self) -> Option<f64> {
+        if !self.terrain.line_of_sight {
+            return None;
+        }
+        Some(match self.prediction_method {
+            PredictionMethod::Colocated | PredictionMethod::FreeSpace => 10.0,
+            PredictionMethod::Itm => {
+                if self.terrain.delta_h < 10.0 {
+                    9.0
+                } else if self.terrain.delta_h < 50.0 {
+                    6.0
+                } else if self.terrain.delta_h < 100.0 {
+                    4.0
+                } else {
+                    3.0
+                }
+            }
+        })
+    }
+}
+
+/// Whether the straight line between two antennas clears the terrain.
+///
+/// `elevations` are equally spaced ground elevations along the path, from
+/// the transmitter to the receiver, and antenna heights are above the ground
+/// at each end. Earth curvature uses the standard 4/3 effective radius.
+pub fn has_line_of_sight(
+    elevations: &[f64],
+    from_height_agl: f64,
+    to_height_agl: f64,
+    path_distance_m: f64,
+) -> bool {
+    const EFFECTIVE_EARTH_RADIUS_M: f64 = 6_371_000.0 * 4.0 / 3.0;
+
+    if elevations.len() < 3 {
+        return true;
+    }
+    let from_tip = elevations[0] + from_height_agl;
+    let to_tip = elevations[elevations.len() - 1] + to_height_agl;
+    let last = (elevations.len() - 1) as f64;
+
+    elevations[1..elevations.len() - 1].iter().enumerate().all(|(i, &ground)| {
+        let t = (i + 1) as f64 / last;
+        let d1 = t * path_distance_m;
+        let d2 = path_distance_m - d1;
+        let bulge = d1 * d2 / (2.0 * EFFECTIVE_EARTH_RADIUS_M);
+        ground + bulge < from_tip + t * (to_tip - from_tip)
+    })
 }
 
 /// Predict link quality between two geographic coordinates.
@@ -561,6 +758,18 @@ pub fn predict_link_with_params(
     let mean_elev = elevations.iter().sum::<f64>() / elevations.len() as f64;
     let delta_h = max_elev - min_elev;
 
+    // Convert antenna heights to AGL using the ground elevation at each endpoint
+    let (from_height_agl, to_height_agl) =
+        resolve_antenna_heights(config, elevations[0], elevations[elevations.len() - 1])?;
+    let antenna_gain_db = path_antenna_gain_db(
+        config,
+        elevations[0] + from_height_agl,
+        elevations[elevations.len() - 1] + to_height_agl,
+        path_distance_m,
+    );
+    let line_of_sight =
+        has_line_of_sight(&elevations, from_height_agl, to_height_agl, path_distance_m);
+
     // Determine prediction method and calculate path loss
     let (path_loss_db, itm_warnings, prediction_method) = if path_distance_m < params.fspl_min_distance_m {
         // Use fixed path loss for co-located nodes where distance ≈ 0
@@ -581,8 +790,8 @@ pub fn predict_link_with_params(
         // Get the median (50/50/50) result - this is the most likely path loss
         let median_result = itm
             .p2p_tls(
-                config.from_height,
-                config.to_height,
+                from_height_agl,
+                to_height_agl,
                 &pfl,
                 climate,
                 n_0,
@@ -627,9 +836,15 @@ pub fn predict_link_with_params(
     };
 
     // Calculate SNR using configurable noise floor
-    // SNR = TX Power - Path Loss - Noise Floor
+    // SNR = TX Power + Antenna Gains - Path Loss - Noise Floor
     let noise_floor_dbm = params.noise_floor_dbm;
-    let median_snr = config.tx_power_dbm as f64 - path_loss_db - noise_floor_dbm;
+    let median_snr = config.tx_power_dbm as f64 + antenna_gain_db - path_loss_db - noise_floor_dbm;
+
+    // Correct the model by the bias and spread fitted from field measurements
+    let (median_snr, std_dev_loss) = match &params.calibration {
+        Some(calibration) => calibration.apply(config, median_snr),
+        None => (median_snr, std_dev_loss),
+    };
 
     // LoRa link budget assessment - sensitivity varies by SF
     let snr_threshold = params.snr_threshold_for_sf(config.spreading_factor);
@@ -645,8 +860,9 @@ pub fn predict_link_with_params(
             from_lon: config.from_lon,
             to_lat: config.to_lat,
             to_lon: config.to_lon,
-            from_height: config.from_height,
-            to_height: config.to_height,
+            from_height: from_height_agl,
+            to_height: to_height_agl,
+            height_mode: config.height_mode,
             distance_km: path_distance_km,
         },
         terrain: TerrainInfo {
@@ -656,6 +872,7 @@ pub fn predict_link_with_params(
             max_elevation: max_elev,
             mean_elevation: mean_elev,
             delta_h,
+            line_of_sight,
         },
         radio: RadioParams {
             freq_mhz: config.freq_mhz,
@@ -665,8 +882,10 @@ pub fn predict_link_with_params(
             snr_threshold_db: snr_threshold,
         },
         path_loss_db,
+        clutter_loss_db: 0.0,
         prediction_method,
         itm_warnings,
+        antenna_gain_db,
         snr_db: median_snr,
         snr_std_dev_db: std_dev_loss,
         link_margin_db: link_margin,
@@ -683,14 +902,17 @@ pub fn predict_link_with_params(
 /// This enum abstracts over different sources of elevation data:
 /// - Local USGS DEM tiles
 /// - AWS terrain tiles (fetched on demand and cached locally)
+/// - Seeded synthetic terrain, for tests
 pub enum ElevationSource {
     /// Local USGS DEM tiles loaded via `DemManager`.
     LocalDem(DemManager),
     /// AWS terrain tiles fetched on demand via `AwsTileFetcher`.
     AwsTiles {
-        fetcher: AwsTileFetcher,
+        fetcher: Box<AwsTileFetcher>,
         callback: Option<DownloadCallback>,
     },
+    /// Analytic terrain that needs no elevation data.
+    Synthetic(SyntheticTerrain),
 }
 
 impl std::fmt::Debug for ElevationSource {
@@ -703,6 +925,7 @@ impl std::fmt::Debug for ElevationSource {
                     .field("callback", &"<callback>")
                     .finish()
             }
+            ElevationSource::Synthetic(terrain) => f.debug_tuple("Synthetic").field(terrain).finish(),
         }
     }
 }
@@ -713,6 +936,11 @@ impl ElevationSource {
         ElevationSource::LocalDem(dem)
     }
 
+    /// Create an elevation source from synthetic terrain.
+    pub fn from_synthetic(terrain: SyntheticTerrain) -> Self {
+        ElevationSource::Synthetic(terrain)
+    }
+
     /// Create an elevation source using AWS terrain tiles.
     ///
     /// # Arguments
@@ -727,27 +955,41 @@ impl ElevationSource {
         let fetcher = AwsTileFetcher::with_zoom(cache_dir, zoom).map_err(|e| {
             LinkPredictionError::DemError(format!("Failed to create AWS tile fetcher: {}", e))
         })?;
-        Ok(ElevationSource::AwsTiles { fetcher, callback })
+        Ok(ElevationSource::AwsTiles { fetcher: Box::new(fetcher), callback })
     }
 
     /// Create an elevation source from simulation properties.
     ///
     /// Uses AWS tiles with settings from `PREDICT_ELEVATION_CACHE_DIR` and
-    /// `PREDICT_ELEVATION_ZOOM_LEVEL` properties.
+    /// `PREDICT_ELEVATION_ZOOM_LEVEL` properties, offline if
+    /// `PREDICT_ELEVATION_OFFLINE` or `PREDICT_ELEVATION_OFFLINE_FALLBACK_M`
+    /// says so.
     pub fn from_properties(
         props: &ResolvedProperties<SimulationScope>,
         callback: Option<DownloadCallback>,
     ) -> Result<Self, LinkPredictionError> {
         let cache_dir: String = props.get(&PREDICT_ELEVATION_CACHE_DIR);
         let zoom: u8 = props.get(&PREDICT_ELEVATION_ZOOM_LEVEL);
-        Self::from_aws_tiles(cache_dir, zoom, callback)
+        Ok(Self::from_aws_tiles(cache_dir, zoom, callback)?.with_offline_mode(resolve_offline_mode(props, false, None)))
+    }
+
+    /// Set how AWS tiles missing from the cache are handled. Has no effect
+    /// on local DEM and synthetic sources, which never download.
+    pub fn with_offline_mode(self, mode: OfflineMode) -> Self {
+        match self {
+            ElevationSource::AwsTiles { fetcher, callback } => ElevationSource::AwsTiles {
+                fetcher: Box::new(fetcher.with_offline_mode(mode)),
+                callback,
+            },
+            other => other,
+        }
     }
 
     /// Get download statistics (for AWS tiles source only).
-    /// Returns None for local DEM sources.
+    /// Returns None for local DEM and synthetic sources.
     pub fn download_stats(&self) -> Option<DownloadStats> {
         match self {
-            ElevationSource::LocalDem(_) => None,
+            ElevationSource::LocalDem(_) | ElevationSource::Synthetic(_) => None,
             ElevationSource::AwsTiles { fetcher, .. } => Some(fetcher.download_stats()),
         }
     }
@@ -765,6 +1007,7 @@ impl ElevationSource {
                         LinkPredictionError::DemError(format!("Failed to get elevation: {}", e))
                     })
             }
+            ElevationSource::Synthetic(terrain) => Ok(terrain.elevation(lat, lon)),
         }
     }
 
@@ -797,7 +1040,7 @@ impl ElevationSource {
                     })
                     .collect()
             }
-            ElevationSource::AwsTiles { fetcher, callback } => {
+            ElevationSource::AwsTiles { .. } | ElevationSource::Synthetic(_) => {
                 // Calculate total distance using haversine formula
                 let total_distance = haversine_distance(start_lat, start_lon, end_lat, end_lon);
 
@@ -808,15 +1051,7 @@ impl ElevationSource {
                     let lon = start_lon + t * (end_lon - start_lon);
                     let distance = t * total_distance;
 
-                    let elevation = fetcher
-                        .get_elevation_with_callback(lat, lon, callback.as_ref())
-                        .map_err(|e| {
-                            LinkPredictionError::DemError(format!(
-                                "Failed to get elevation: {}",
-                                e
-                            ))
-                        });
-                    results.push((distance, elevation));
+                    results.push((distance, self.get_elevation(lat, lon)));
                 }
                 results
             }
@@ -862,6 +1097,23 @@ pub fn load_aws_elevation<P: AsRef<Path>>(
     ElevationSource::from_aws_tiles(cache_dir, zoom, Some(callback))
 }
 
+/// Offline mode for AWS tiles from `PREDICT_ELEVATION_OFFLINE` and
+/// `PREDICT_ELEVATION_OFFLINE_FALLBACK_M`, with command-line overrides: `offline`
+/// forces offline mode and `fallback_m` sets the fallback elevation (which
+/// implies offline).
+pub fn resolve_offline_mode(
+    props: &ResolvedProperties<SimulationScope>,
+    offline: bool,
+    fallback_m: Option<f64>,
+) -> OfflineMode {
+    let fallback_m = fallback_m.or_else(|| props.get(&PREDICT_ELEVATION_OFFLINE_FALLBACK_M));
+    match fallback_m {
+        Some(elevation) => OfflineMode::Fallback(elevation as f32),
+        None if offline || props.get::<bool>(&PREDICT_ELEVATION_OFFLINE) => OfflineMode::Strict,
+        None => OfflineMode::Online,
+    }
+}
+
 /// Load AWS elevation tiles with a custom progress callback.
 ///
 /// # Arguments
@@ -925,6 +1177,34 @@ pub fn predict_link_with_elevation_and_params(
     config: &LinkPredictionConfig,
     params: &LinkPredictionParams,
 ) -> Result<LinkPrediction, LinkPredictionError> {
+    let profile = sample_path_profile(elevation, config)?;
+    predict_link_from_profile(itm, config, params, &profile)
+}
+
+/// Terrain elevations sampled at even spacing along a path.
+#[derive(Debug, Clone)]
+pub(crate) struct PathProfile {
+    /// Path length in meters.
+    pub distance_m: f64,
+    /// Elevations in meters, from the transmitter to the receiver.
+    pub elevations: Vec<f64>,
+}
+
+impl PathProfile {
+    /// The same path seen from the other end.
+    pub fn reversed(&self) -> Self {
+        Self {
+            distance_m: self.distance_m,
+            elevations: self.elevations.iter().rev().copied().collect(),
+        }
+    }
+}
+
+/// Sample the terrain profile between the endpoints of `config`.
+pub(crate) fn sample_path_profile(
+    elevation: &ElevationSource,
+    config: &LinkPredictionConfig,
+) -> Result<PathProfile, LinkPredictionError> {
     // Validate configuration
     if config.terrain_samples < 2 {
         return Err(LinkPredictionError::ConfigError(
@@ -955,14 +1235,25 @@ pub fn predict_link_with_elevation_and_params(
         }
     }
 
+    let distance_m = samples.last().map(|(d, _)| *d).unwrap_or(0.0);
+    Ok(PathProfile { distance_m, elevations })
+}
+
+/// Predict a link from an already sampled terrain profile.
+pub(crate) fn predict_link_from_profile(
+    itm: &Itm,
+    config: &LinkPredictionConfig,
+    params: &LinkPredictionParams,
+    profile: &PathProfile,
+) -> Result<LinkPrediction, LinkPredictionError> {
+    let elevations = &profile.elevations;
     if elevations.len() < 2 {
         return Err(LinkPredictionError::ConfigError(
             "Insufficient elevation samples".to_string(),
         ));
     }
 
-    // Calculate path distance from samples
-    let path_distance_m = samples.last().map(|(d, _)| *d).unwrap_or(0.0);
+    let path_distance_m = profile.distance_m;
     let path_distance_km = path_distance_m / 1000.0;
 
     // Calculate resolution from samples
@@ -991,6 +1282,18 @@ pub fn predict_link_with_elevation_and_params(
     let mean_elev = elevations.iter().sum::<f64>() / elevations.len() as f64;
     let delta_h = max_elev - min_elev;
 
+    // Convert antenna heights to AGL using the ground elevation at each endpoint
+    let (from_height_agl, to_height_agl) =
+        resolve_antenna_heights(config, elevations[0], elevations[elevations.len() - 1])?;
+    let antenna_gain_db = path_antenna_gain_db(
+        config,
+        elevations[0] + from_height_agl,
+        elevations[elevations.len() - 1] + to_height_agl,
+        path_distance_m,
+    );
+    let line_of_sight =
+        has_line_of_sight(elevations, from_height_agl, to_height_agl, path_distance_m);
+
     // Determine prediction method and calculate path loss
     let (path_loss_db, itm_warnings, prediction_method) =
         if path_distance_m < params.fspl_min_distance_m {
@@ -998,7 +1301,7 @@ pub fn predict_link_with_elevation_and_params(
             (params.colocated_path_loss_db, 0, PredictionMethod::Colocated)
         } else if path_distance_m >= params.itm_min_distance_m && resolution_valid {
             // Use ITM for paths >= configured min distance with valid terrain resolution
-            let profile = TerrainProfile::from_elevations(resolution_m, &elevations);
+            let profile = TerrainProfile::from_elevations(resolution_m, elevations);
             let pfl = profile.to_pfl();
 
             // Get terrain parameters from params
@@ -1011,8 +1314,8 @@ pub fn predict_link_with_elevation_and_params(
             // Get the median (50/50/50) result - this is the most likely path loss
             let median_result = itm
                 .p2p_tls(
-                    config.from_height,
-                    config.to_height,
+                    from_height_agl,
+                    to_height_agl,
                     &pfl,
                     climate,
                     n_0,
@@ -1064,7 +1367,13 @@ pub fn predict_link_with_elevation_and_params(
 
     // Calculate SNR using configurable noise floor
     let noise_floor_dbm = params.noise_floor_dbm;
-    let median_snr = config.tx_power_dbm as f64 - path_loss_db - noise_floor_dbm;
+    let median_snr = config.tx_power_dbm as f64 + antenna_gain_db - path_loss_db - noise_floor_dbm;
+
+    // Correct the model by the bias and spread fitted from field measurements
+    let (median_snr, std_dev_loss) = match &params.calibration {
+        Some(calibration) => calibration.apply(config, median_snr),
+        None => (median_snr, std_dev_loss),
+    };
 
     // LoRa link budget assessment - sensitivity varies by SF
     let snr_threshold = params.snr_threshold_for_sf(config.spreading_factor);
@@ -1079,8 +1388,9 @@ pub fn predict_link_with_elevation_and_params(
             from_lon: config.from_lon,
             to_lat: config.to_lat,
             to_lon: config.to_lon,
-            from_height: config.from_height,
-            to_height: config.to_height,
+            from_height: from_height_agl,
+            to_height: to_height_agl,
+            height_mode: config.height_mode,
             distance_km: path_distance_km,
         },
         terrain: TerrainInfo {
@@ -1090,6 +1400,7 @@ pub fn predict_link_with_elevation_and_params(
             max_elevation: max_elev,
             mean_elevation: mean_elev,
             delta_h,
+            line_of_sight,
         },
         radio: RadioParams {
             freq_mhz: config.freq_mhz,
@@ -1099,8 +1410,10 @@ pub fn predict_link_with_elevation_and_params(
             snr_threshold_db: snr_threshold,
         },
         path_loss_db,
+        clutter_loss_db: 0.0,
         prediction_method,
         itm_warnings,
+        antenna_gain_db,
         snr_db: median_snr,
         snr_std_dev_db: std_dev_loss,
         link_margin_db: link_margin,
@@ -1227,6 +1540,103 @@ mod tests {
         assert_eq!(params.classify_link(-5.0), LinkStatus::Unreliable);
     }
 
+    #[test]
+    fn test_snr_at_tx_power() {
+        let pred = LinkPrediction {
+            path: PathInfo {
+                from_lat: 0.0,
+                from_lon: 0.0,
+                to_lat: 0.0,
+                to_lon: 0.0,
+                from_height: 2.0,
+                to_height: 2.0,
+                height_mode: AntennaHeightMode::Agl,
+                distance_km: 5.0,
+            },
+            terrain: TerrainInfo {
+                sample_count: 0,
+                resolution_m: 0.0,
+                min_elevation: 0.0,
+                max_elevation: 0.0,
+                mean_elevation: 0.0,
+                delta_h: 0.0,
+                line_of_sight: true,
+            },
+            radio: RadioParams {
+                freq_mhz: 910.525,
+                tx_power_dbm: 20,
+                noise_floor_dbm: -120.0,
+                spreading_factor: 7,
+                snr_threshold_db: -7.5,
+            },
+            path_loss_db: 130.0,
+            clutter_loss_db: 0.0,
+            prediction_method: PredictionMethod::FreeSpace,
+            itm_warnings: 0,
+            antenna_gain_db: 0.0,
+            snr_db: 10.0,
+            snr_std_dev_db: 2.0,
+            link_margin_db: 17.5,
+            status: LinkStatus::Excellent,
+        };
+
+        assert_eq!(pred.snr_db_at_tx_power(20), 10.0);
+        assert_eq!(pred.snr_db_at_tx_power(30), 20.0);
+        assert_eq!(pred.snr_db_at_tx_power(10), 0.0);
+        assert_eq!(pred.link_margin_db_at_tx_power(10), 7.5);
+
+        // Line-of-sight links get Rician fading, obstructed ones Rayleigh
+        assert_eq!(pred.rician_k_factor_db(), Some(10.0));
+        let mut obstructed = pred.clone();
+        obstructed.terrain.line_of_sight = false;
+        assert_eq!(obstructed.rician_k_factor_db(), None);
+    }
+
+    #[test]
+    fn test_has_line_of_sight() {
+        let flat = vec![100.0; 11];
+        assert!(has_line_of_sight(&flat, 10.0, 10.0, 1_000.0));
+
+        // A 20 m ridge mid-path blocks 10 m masts but not 30 m ones
+        let mut ridge = flat.clone();
+        ridge[5] = 120.0;
+        assert!(!has_line_of_sight(&ridge, 10.0, 10.0, 1_000.0));
+        assert!(has_line_of_sight(&ridge, 30.0, 30.0, 1_000.0));
+
+        // Over 40 km of flat ground the earth bulge (~24 m) blocks 10 m masts
+        assert!(!has_line_of_sight(&flat, 10.0, 10.0, 40_000.0));
+    }
+
+    #[test]
+    fn test_resolve_antenna_heights() {
+        let mut config = LinkPredictionConfig {
+            from_height: 10.0,
+            to_height: 3.0,
+            ..Default::default()
+        };
+        assert_eq!(resolve_antenna_heights(&config, 100.0, 50.0).unwrap(), (10.0, 3.0));
+
+        config.height_mode = AntennaHeightMode::Msl;
+        config.from_height = 130.0;
+        config.to_height = 52.0;
+        assert_eq!(resolve_antenna_heights(&config, 100.0, 50.0).unwrap(), (30.0, 2.0));
+
+        // Antenna below the terrain is rejected
+        config.to_height = 40.0;
+        assert!(resolve_antenna_heights(&config, 100.0, 50.0).is_err());
+
+        config.height_mode = AntennaHeightMode::Agl;
+        config.to_height = -1.0;
+        assert!(resolve_antenna_heights(&config, 100.0, 50.0).is_err());
+    }
+
+    #[test]
+    fn test_antenna_height_mode_parse() {
+        assert_eq!("agl".parse::<AntennaHeightMode>().unwrap(), AntennaHeightMode::Agl);
+        assert_eq!("MSL".parse::<AntennaHeightMode>().unwrap(), AntennaHeightMode::Msl);
+        assert!("asl".parse::<AntennaHeightMode>().is_err());
+    }
+
     #[test]
     fn test_link_prediction_params_parse_climate() {
         let mut params = LinkPredictionParams::default();
@@ -1286,4 +1696,18 @@ mod tests {
         assert_eq!(params.fspl_min_distance_m, 1.0);
         assert_eq!(params.colocated_path_loss_db, 20.0);
     }
+
+    #[test]
+    fn test_resolve_offline_mode() {
+        let mut props: ResolvedProperties<SimulationScope> = ResolvedProperties::new();
+        assert_eq!(resolve_offline_mode(&props, false, None), OfflineMode::Online);
+        assert_eq!(resolve_offline_mode(&props, true, None), OfflineMode::Strict);
+        assert_eq!(resolve_offline_mode(&props, false, Some(0.0)), OfflineMode::Fallback(0.0));
+
+        props.set(&PREDICT_ELEVATION_OFFLINE, true).unwrap();
+        assert_eq!(resolve_offline_mode(&props, false, None), OfflineMode::Strict);
+        props.set(&PREDICT_ELEVATION_OFFLINE_FALLBACK_M, Some(120.0)).unwrap();
+        assert_eq!(resolve_offline_mode(&props, false, None), OfflineMode::Fallback(120.0));
+        assert_eq!(resolve_offline_mode(&props, false, Some(5.0)), OfflineMode::Fallback(5.0));
+    }
 }
@@ -0,0 +1,212 @@
+//! Polygon boundaries for coverage sweeps.
+//!
+//! A [`CoverageRegion`] limits a coverage map to the cells whose centers lie
+//! inside at least one boundary polygon and outside every exclusion polygon
+//! (lakes, the sea, areas nobody cares about). Cells outside the region are
+//! not predicted and come out as nodata, so compute isn't spent on them and
+//! the rendered map follows the region's outline.
+//!
+//! Polygons are read from GeoJSON: a `FeatureCollection`, `Feature` or bare
+//! geometry, with `Polygon` and `MultiPolygon` geometries (other geometry
+//! types are ignored). Holes in a polygon are excluded from it.
+
+use serde_json::Value;
+
+use crate::coverage::BoundingBox;
+use crate::predict::LinkPredictionError;
+
+/// A polygon with optional holes. Rings are lists of `[lon, lat]` points, as
+/// in GeoJSON; the first ring is the outer boundary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    rings: Vec<Vec<[f64; 2]>>,
+}
+
+impl Polygon {
+    /// Polygon from its outer ring followed by any holes. Rings need not be
+    /// closed.
+    pub fn new(rings: Vec<Vec<[f64; 2]>>) -> Self {
+        Self { rings }
+    }
+
+    /// Whether the point lies inside the polygon (and not in a hole), by the
+    /// even-odd rule.
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        let mut inside = false;
+        for ring in &self.rings {
+            let n = ring.len();
+            for i in 0..n {
+                let [x1, y1] = ring[i];
+                let [x2, y2] = ring[(i + 1) % n];
+                if (y1 > lat) != (y2 > lat) && lon < x1 + (lat - y1) * (x2 - x1) / (y2 - y1) {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    /// Bounding box of the outer ring.
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        let outer = self.rings.first().filter(|r| !r.is_empty())?;
+        let mut bounds = BoundingBox {
+            min_lat: f64::INFINITY,
+            min_lon: f64::INFINITY,
+            max_lat: f64::NEG_INFINITY,
+            max_lon: f64::NEG_INFINITY,
+        };
+        for &[lon, lat] in outer {
+            bounds.min_lat = bounds.min_lat.min(lat);
+            bounds.min_lon = bounds.min_lon.min(lon);
+            bounds.max_lat = bounds.max_lat.max(lat);
+            bounds.max_lon = bounds.max_lon.max(lon);
+        }
+        Some(bounds)
+    }
+
+    /// All polygons in a GeoJSON document.
+    pub fn from_geojson(text: &str) -> Result<Vec<Polygon>, LinkPredictionError> {
+        let invalid = |reason: String| LinkPredictionError::ConfigError(format!("Invalid GeoJSON: {}", reason));
+        let value: Value = serde_json::from_str(text).map_err(|e| invalid(e.to_string()))?;
+        let mut polygons = Vec::new();
+        collect_polygons(&value, &mut polygons).map_err(invalid)?;
+        if polygons.is_empty() {
+            return Err(invalid("no Polygon or MultiPolygon geometry".to_string()));
+        }
+        Ok(polygons)
+    }
+}
+
+fn collect_polygons(value: &Value, polygons: &mut Vec<Polygon>) -> Result<(), String> {
+    match value.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            for feature in value.get("features").and_then(Value::as_array).into_iter().flatten() {
+                collect_polygons(feature, polygons)?;
+            }
+        }
+        Some("Feature") => {
+            if let Some(geometry) = value.get("geometry").filter(|g| !g.is_null()) {
+                collect_polygons(geometry, polygons)?;
+            }
+        }
+        Some("GeometryCollection") => {
+            for geometry in value.get("geometries").and_then(Value::as_array).into_iter().flatten() {
+                collect_polygons(geometry, polygons)?;
+            }
+        }
+        Some("Polygon") => polygons.push(parse_polygon(coordinates(value)?)?),
+        Some("MultiPolygon") => {
+            let parts = coordinates(value)?.as_array().ok_or("MultiPolygon coordinates must be an array")?;
+            for part in parts {
+                polygons.push(parse_polygon(part)?);
+            }
+        }
+        Some(_) => {}
+        None => return Err("object without a \"type\"".to_string()),
+    }
+    Ok(())
+}
+
+fn coordinates(geometry: &Value) -> Result<&Value, String> {
+    geometry.get("coordinates").ok_or_else(|| "geometry without coordinates".to_string())
+}
+
+fn parse_polygon(coordinates: &Value) -> Result<Polygon, String> {
+    let rings = coordinates.as_array().ok_or("Polygon coordinates must be an array of rings")?;
+    let rings = rings
+        .iter()
+        .map(|ring| {
+            let points = ring.as_array().ok_or("Polygon ring must be an array of positions")?;
+            let ring = points
+                .iter()
+                .map(|point| match point.as_array().map(Vec::as_slice) {
+                    Some([lon, lat, ..]) => match (lon.as_f64(), lat.as_f64()) {
+                        (Some(lon), Some(lat)) => Ok([lon, lat]),
+                        _ => Err("position must be numeric [lon, lat]".to_string()),
+                    },
+                    _ => Err("position must be [lon, lat]".to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if ring.len() < 3 {
+                return Err("Polygon ring needs at least 3 positions".to_string());
+            }
+            Ok(ring)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if rings.is_empty() {
+        return Err("Polygon without rings".to_string());
+    }
+    Ok(Polygon::new(rings))
+}
+
+/// Area a coverage sweep is limited to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageRegion {
+    /// Boundary polygons; empty means the whole bounding box.
+    pub include: Vec<Polygon>,
+    /// Exclusion zones inside the boundary.
+    pub exclude: Vec<Polygon>,
+}
+
+impl CoverageRegion {
+    /// Whether a cell centered at the point should be predicted.
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.contains(lat, lon)))
+            && !self.exclude.iter().any(|p| p.contains(lat, lon))
+    }
+
+    /// Bounding box of the boundary polygons, if there are any.
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        self.include.iter().filter_map(Polygon::bounds).reduce(|a, b| BoundingBox {
+            min_lat: a.min_lat.min(b.min_lat),
+            min_lon: a.min_lon.min(b.min_lon),
+            max_lat: a.max_lat.max(b.max_lat),
+            max_lon: a.max_lon.max(b.max_lon),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE_WITH_HOLE: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            { "type": "Feature", "properties": {}, "geometry": { "type": "Polygon", "coordinates": [
+                [[-122.0, 47.0], [-121.0, 47.0], [-121.0, 48.0], [-122.0, 48.0], [-122.0, 47.0]],
+                [[-121.6, 47.4], [-121.4, 47.4], [-121.4, 47.6], [-121.6, 47.6], [-121.6, 47.4]]
+            ]}},
+            { "type": "Feature", "properties": {}, "geometry": { "type": "Point", "coordinates": [0, 0] }}
+        ]
+    }"#;
+
+    #[test]
+    fn test_polygon_with_hole() {
+        let polygons = Polygon::from_geojson(SQUARE_WITH_HOLE).unwrap();
+        assert_eq!(polygons.len(), 1);
+        let square = &polygons[0];
+        assert!(square.contains(47.2, -121.8));
+        assert!(!square.contains(47.5, -121.5));
+        assert!(!square.contains(48.5, -121.5));
+        assert_eq!(square.bounds().unwrap().max_lat, 48.0);
+    }
+
+    #[test]
+    fn test_region_with_exclusion() {
+        let square = Polygon::from_geojson(SQUARE_WITH_HOLE).unwrap();
+        let lake = Polygon::new(vec![vec![[-122.0, 47.0], [-121.8, 47.0], [-121.8, 47.2], [-122.0, 47.2]]]);
+        let region = CoverageRegion { include: square, exclude: vec![lake] };
+        assert!(region.contains(47.8, -121.2));
+        assert!(!region.contains(47.1, -121.9));
+        assert!(CoverageRegion::default().contains(0.0, 0.0));
+        assert_eq!(region.bounds().unwrap().min_lon, -122.0);
+    }
+
+    #[test]
+    fn test_invalid_geojson() {
+        assert!(Polygon::from_geojson("{}").is_err());
+        assert!(Polygon::from_geojson(r#"{"type": "Point", "coordinates": [0, 0]}"#).is_err());
+        assert!(Polygon::from_geojson(r#"{"type": "Polygon", "coordinates": [[[0, 0], [1, 1]]]}"#).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Saving and reloading link prediction settings.
+//!
+//! [`PredictionSettings`] bundles the per-link [`LinkPredictionConfig`] with the
+//! model [`LinkPredictionParams`] so the exact inputs of a prediction can be
+//! written next to its results and loaded back later, rather than rebuilt from
+//! simulation properties. Settings are stored as YAML; fields missing from a
+//! file take their defaults.
+//!
+//! ```yaml
+//! config:
+//!   from_lat: 47.6062
+//!   from_lon: -122.3321
+//!   to_lat: 47.6097
+//!   to_lon: -122.3331
+//!   spreading_factor: 9
+//! params:
+//!   noise_floor_dbm: -118.0
+//! ```
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::predict::{LinkPredictionConfig, LinkPredictionError, LinkPredictionParams};
+
+/// Inputs of a link prediction.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PredictionSettings {
+    /// Endpoints, antenna heights and radio settings.
+    pub config: LinkPredictionConfig,
+    /// Propagation model parameters.
+    pub params: LinkPredictionParams,
+}
+
+impl PredictionSettings {
+    /// Bundle a config and parameters.
+    pub fn new(config: LinkPredictionConfig, params: LinkPredictionParams) -> Self {
+        Self { config, params }
+    }
+
+    /// Parse settings from YAML.
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, LinkPredictionError> {
+        serde_yaml::from_str(yaml)
+            .map_err(|e| LinkPredictionError::ConfigError(format!("Invalid prediction settings: {}", e)))
+    }
+
+    /// Serialize settings to YAML.
+    pub fn to_yaml_string(&self) -> Result<String, LinkPredictionError> {
+        serde_yaml::to_string(self).map_err(|e| {
+            LinkPredictionError::ConfigError(format!("Failed to serialize prediction settings: {}", e))
+        })
+    }
+
+    /// Load settings from a YAML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LinkPredictionError> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path).map_err(|e| {
+            LinkPredictionError::ConfigError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Self::from_yaml_str(&yaml)
+    }
+
+    /// Save settings to a YAML file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), LinkPredictionError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_yaml_string()?).map_err(|e| {
+            LinkPredictionError::ConfigError(format!("Failed to write {}: {}", path.display(), e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predict::AntennaHeightMode;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let settings = PredictionSettings::new(
+            LinkPredictionConfig {
+                from_lat: 47.606_209_1,
+                from_lon: -122.332_071_3,
+                to_lat: 47.609_7,
+                to_lon: -122.333_1,
+                height_mode: AntennaHeightMode::Msl,
+                tx_power_dbm: 22,
+                spreading_factor: 9,
+                ..Default::default()
+            },
+            LinkPredictionParams {
+                noise_floor_dbm: -118.3,
+                itm_climate: "maritime_temperate_land".to_string(),
+                ..Default::default()
+            },
+        );
+
+        let yaml = settings.to_yaml_string().unwrap();
+        assert_eq!(PredictionSettings::from_yaml_str(&yaml).unwrap(), settings);
+
+        let path = std::env::temp_dir().join(format!("mcsim-link-settings-{}.yaml", std::process::id()));
+        settings.save(&path).unwrap();
+        assert_eq!(PredictionSettings::load(&path).unwrap(), settings);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings =
+            PredictionSettings::from_yaml_str("config:\n  spreading_factor: 12\nparams:\n  noise_floor_dbm: -110\n")
+                .unwrap();
+        assert_eq!(settings.config.spreading_factor, 12);
+        assert_eq!(settings.config.freq_mhz, LinkPredictionConfig::default().freq_mhz);
+        assert_eq!(settings.params.noise_floor_dbm, -110.0);
+        assert_eq!(settings.params.snr_thresholds, LinkPredictionParams::default().snr_thresholds);
+
+        assert!(PredictionSettings::from_yaml_str("config:\n  height_mode: sideways\n").is_err());
+    }
+}
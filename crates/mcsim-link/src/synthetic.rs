@@ -0,0 +1,245 @@
+//! Seeded synthetic terrain.
+//!
+//! [`SyntheticTerrain`] is an analytic elevation surface made of a flat plain
+//! plus Gaussian hills and ridges. It backs [`ElevationSource::Synthetic`]
+//! so terrain-dependent code (path profiles, coverage sweeps, link
+//! prediction) can be exercised deterministically without DEM files or tile
+//! downloads:
+//!
+//! ```
+//! use mcsim_link::{BoundingBox, ElevationSource, SyntheticTerrain, SyntheticTerrainConfig};
+//!
+//! let bounds = BoundingBox { min_lat: 47.5, min_lon: -122.5, max_lat: 47.7, max_lon: -122.2 };
+//! let terrain = SyntheticTerrain::generate(42, bounds, &SyntheticTerrainConfig::default());
+//! let elevation = ElevationSource::from_synthetic(terrain);
+//! assert!(elevation.get_elevation(47.6, -122.3).is_ok());
+//! ```
+//!
+//! Terrain can also be built by hand for tests that need a specific
+//! obstruction:
+//!
+//! ```
+//! use mcsim_link::SyntheticTerrain;
+//!
+//! let terrain = SyntheticTerrain::flat(100.0).with_ridge((47.0, -122.1), (47.2, -122.1), 300.0, 500.0);
+//! assert!(terrain.elevation(47.1, -122.1) > 350.0);
+//! ```
+
+use crate::coverage::BoundingBox;
+
+/// Meters per degree of latitude.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Parameters for [`SyntheticTerrain::generate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticTerrainConfig {
+    /// Elevation of the plain in meters.
+    pub base_elevation_m: f64,
+    /// Number of hills.
+    pub hills: usize,
+    /// Number of ridges.
+    pub ridges: usize,
+    /// Tallest feature height above the plain in meters.
+    pub max_height_m: f64,
+    /// Largest hill radius (standard deviation) and ridge half-width in meters.
+    pub max_width_m: f64,
+}
+
+impl Default for SyntheticTerrainConfig {
+    fn default() -> Self {
+        Self {
+            base_elevation_m: 50.0,
+            hills: 6,
+            ridges: 2,
+            max_height_m: 400.0,
+            max_width_m: 2000.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Hill {
+    lat: f64,
+    lon: f64,
+    height_m: f64,
+    radius_m: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Ridge {
+    from: (f64, f64),
+    to: (f64, f64),
+    height_m: f64,
+    half_width_m: f64,
+}
+
+/// Analytic terrain: a plain with Gaussian hills and ridges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticTerrain {
+    base_elevation_m: f64,
+    hills: Vec<Hill>,
+    ridges: Vec<Ridge>,
+}
+
+impl SyntheticTerrain {
+    /// A flat plain at `elevation_m`.
+    pub fn flat(elevation_m: f64) -> Self {
+        Self { base_elevation_m: elevation_m, hills: Vec::new(), ridges: Vec::new() }
+    }
+
+    /// Random hills and ridges inside `bounds`. The same seed, bounds and
+    /// config always produce the same terrain.
+    pub fn generate(seed: u64, bounds: BoundingBox, config: &SyntheticTerrainConfig) -> Self {
+        let mut rng = SplitMix64(seed);
+        let point = |rng: &mut SplitMix64| {
+            (
+                bounds.min_lat + rng.next_f64() * (bounds.max_lat - bounds.min_lat),
+                bounds.min_lon + rng.next_f64() * (bounds.max_lon - bounds.min_lon),
+            )
+        };
+        // Features are at least a quarter of the maximum size
+        let scale = |rng: &mut SplitMix64, max: f64| max * (0.25 + 0.75 * rng.next_f64());
+
+        let mut terrain = Self::flat(config.base_elevation_m);
+        for _ in 0..config.hills {
+            let (lat, lon) = point(&mut rng);
+            let height_m = scale(&mut rng, config.max_height_m);
+            let radius_m = scale(&mut rng, config.max_width_m);
+            terrain = terrain.with_hill(lat, lon, height_m, radius_m);
+        }
+        for _ in 0..config.ridges {
+            let from = point(&mut rng);
+            let to = point(&mut rng);
+            let height_m = scale(&mut rng, config.max_height_m);
+            let half_width_m = scale(&mut rng, config.max_width_m);
+            terrain = terrain.with_ridge(from, to, height_m, half_width_m);
+        }
+        terrain
+    }
+
+    /// Add a round hill `height_m` tall whose slope has a standard deviation
+    /// of `radius_m`.
+    pub fn with_hill(mut self, lat: f64, lon: f64, height_m: f64, radius_m: f64) -> Self {
+        self.hills.push(Hill { lat, lon, height_m, radius_m });
+        self
+    }
+
+    /// Add a ridge `height_m` tall along the segment between two (lat, lon)
+    /// points, falling off with a standard deviation of `half_width_m`.
+    pub fn with_ridge(mut self, from: (f64, f64), to: (f64, f64), height_m: f64, half_width_m: f64) -> Self {
+        self.ridges.push(Ridge { from, to, height_m, half_width_m });
+        self
+    }
+
+    /// Elevation in meters at a coordinate.
+    pub fn elevation(&self, lat: f64, lon: f64) -> f32 {
+        let bump = |distance_m: f64, height_m: f64, width_m: f64| {
+            height_m * (-(distance_m * distance_m) / (2.0 * width_m * width_m)).exp()
+        };
+        let hills: f64 = self
+            .hills
+            .iter()
+            .map(|h| bump(local_distance_m(lat, lon, h.lat, h.lon), h.height_m, h.radius_m))
+            .sum();
+        let ridges: f64 = self
+            .ridges
+            .iter()
+            .map(|r| bump(segment_distance_m(lat, lon, r.from, r.to), r.height_m, r.half_width_m))
+            .sum();
+        (self.base_elevation_m + hills + ridges) as f32
+    }
+}
+
+/// Local (x, y) offset in meters from a reference point, on an
+/// equirectangular projection.
+fn local_offset_m(lat: f64, lon: f64, ref_lat: f64, ref_lon: f64) -> (f64, f64) {
+    let x = (lon - ref_lon) * METERS_PER_DEGREE * ref_lat.to_radians().cos();
+    let y = (lat - ref_lat) * METERS_PER_DEGREE;
+    (x, y)
+}
+
+fn local_distance_m(lat: f64, lon: f64, ref_lat: f64, ref_lon: f64) -> f64 {
+    let (x, y) = local_offset_m(lat, lon, ref_lat, ref_lon);
+    x.hypot(y)
+}
+
+/// Distance from a point to the segment between two (lat, lon) points.
+fn segment_distance_m(lat: f64, lon: f64, from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (px, py) = local_offset_m(lat, lon, from.0, from.1);
+    let (sx, sy) = local_offset_m(to.0, to.1, from.0, from.1);
+    let length_sq = sx * sx + sy * sy;
+    let t = if length_sq > 0.0 { ((px * sx + py * sy) / length_sq).clamp(0.0, 1.0) } else { 0.0 };
+    (px - t * sx).hypot(py - t * sy)
+}
+
+/// SplitMix64, so generated terrain doesn't depend on an RNG crate's version.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predict::{sample_path_profile, ElevationSource, LinkPredictionConfig};
+
+    const BOUNDS: BoundingBox = BoundingBox { min_lat: 47.5, min_lon: -122.5, max_lat: 47.7, max_lon: -122.2 };
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let config = SyntheticTerrainConfig::default();
+        let a = SyntheticTerrain::generate(7, BOUNDS, &config);
+        assert_eq!(a, SyntheticTerrain::generate(7, BOUNDS, &config));
+        assert_ne!(a, SyntheticTerrain::generate(8, BOUNDS, &config));
+
+        // Never below the plain, never above every feature stacked up
+        let ceiling = config.base_elevation_m + (config.hills + config.ridges) as f64 * config.max_height_m;
+        for i in 0..100 {
+            let elevation = a.elevation(47.5 + i as f64 * 0.002, -122.5 + i as f64 * 0.003) as f64;
+            assert!(elevation >= config.base_elevation_m - 1e-3 && elevation <= ceiling);
+        }
+    }
+
+    #[test]
+    fn test_hand_built_features() {
+        let terrain = SyntheticTerrain::flat(10.0).with_hill(47.6, -122.3, 200.0, 1000.0);
+        assert!((terrain.elevation(47.6, -122.3) - 210.0).abs() < 1e-3);
+        assert!((terrain.elevation(47.0, -122.3) - 10.0).abs() < 1e-3);
+
+        // Ridge height is constant along its length and falls off across it
+        let ridge = SyntheticTerrain::flat(0.0).with_ridge((47.5, -122.3), (47.7, -122.3), 100.0, 500.0);
+        assert!((ridge.elevation(47.55, -122.3) - ridge.elevation(47.65, -122.3)).abs() < 1e-3);
+        assert!(ridge.elevation(47.6, -122.29) < 50.0);
+    }
+
+    #[test]
+    fn test_path_profile_over_ridge() {
+        let terrain = SyntheticTerrain::flat(20.0).with_ridge((47.5, -122.35), (47.7, -122.35), 300.0, 300.0);
+        let elevation = ElevationSource::from_synthetic(terrain);
+        let config = LinkPredictionConfig {
+            from_lat: 47.6,
+            from_lon: -122.4,
+            to_lat: 47.6,
+            to_lon: -122.3,
+            terrain_samples: 101,
+            ..Default::default()
+        };
+        let profile = sample_path_profile(&elevation, &config).unwrap();
+        assert_eq!(profile.elevations.len(), 101);
+        let peak = profile.elevations.iter().cloned().fold(f64::MIN, f64::max);
+        assert!(peak > 300.0 && profile.elevations[0] < 25.0);
+        assert!((profile.distance_m - 7_500.0).abs() < 100.0);
+    }
+}
@@ -0,0 +1,495 @@
+//! Hardware-in-the-loop radio bridges.
+//!
+//! A [`RadioBridge`] stands in for real LoRa hardware (a gateway, or a
+//! MeshCore device with a packet bridge) placed in the simulated mesh. It has
+//! no firmware: the [`Graph`](crate::Graph) routes packets along its edges as
+//! for any radio, and every packet the hardware could decode at the bridge's
+//! position is handed to the hardware as serial data. Every packet the
+//! hardware reports hearing over the air is transmitted into the simulation
+//! from the bridge's position. The runner carries the serial data over the
+//! node's UART TCP port, the same way it exposes a firmware's console.
+//!
+//! ## Translation
+//!
+//! Simulated packets are judged as a radio at the bridge's position would
+//! judge them: on the bridge's channel, spreading factor and sync word, with
+//! the SNR sampled from the link (scaled by the sender's TX power, with
+//! fading) at or above the sensitivity threshold. Packets a fault injection
+//! rule drops on the link never reach the hardware. Each packet is handed
+//! over when it has finished arriving, along with its sampled SNR and RSSI.
+//! Collisions at the bridge's position are not simulated; the hardware's own
+//! receiver decides what it hears for real.
+//!
+//! Packets from the hardware are transmitted with the bridge's radio
+//! parameters, `tx_power_dbm` standing for the power the real transmitter
+//! reaches the simulated region with, and last their LoRa time on air from
+//! the moment they arrive. They reach the simulation after their real
+//! airtime plus the link's latency, so real hardware should only be attached
+//! to a run paced in real time.
+//!
+//! ## Protocols
+//!
+//! With [`BridgeProtocol::Json`] each packet is one JSON object per line,
+//! for gateway adapters. To the hardware:
+//!
+//! ```text
+//! {"data":"1500a1b2...","snr_db":4.2,"rssi_dbm":-108.5,"frequency_hz":910525000,"bandwidth_hz":62500,"spreading_factor":7,"coding_rate":5,"sim_time_us":81250000}
+//! ```
+//!
+//! From the hardware only `data` (the packet in hex) is required; other
+//! fields, such as the gateway's own SNR and RSSI, are ignored.
+//!
+//! With [`BridgeProtocol::Rs232`] each packet is a binary frame: the magic
+//! `0xC0 0x3E`, the packet length (16 bits), the packet and a Fletcher-16
+//! checksum of the packet, all big-endian. This is the framing of MeshCore's
+//! RS232 bridge, so a device built with it can be attached through a
+//! serial-to-TCP forwarder such as `socat`.
+
+use std::str::FromStr;
+
+use mcsim_common::{
+    rng::substream, Entity, EntityId, Event, EventPayload, InjectedFault, LoraPacket, RadioParams,
+    ReceiveAirEvent, SimContext, SimError,
+};
+use mcsim_metrics::{metric_defs, metrics, MetricLabels};
+use serde::{Deserialize, Serialize};
+
+use crate::{calculate_snr_sensitivity, channel, sample_gaussian, AirtimeParams};
+
+/// First bytes of an RS232 bridge frame.
+pub const RS232_MAGIC: [u8; 2] = [0xC0, 0x3E];
+
+/// Largest packet an RS232 bridge frame may carry (MeshCore's MTU).
+pub const RS232_MAX_PACKET_LEN: usize = 255;
+
+/// Longest JSON line accepted from the hardware before it is discarded.
+const MAX_LINE_LEN: usize = 4096;
+
+/// Wire format spoken with the hardware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BridgeProtocol {
+    /// One JSON object per line, carrying the packet in hex and its
+    /// simulated reception.
+    #[default]
+    Json,
+    /// Binary frames of MeshCore's RS232 bridge.
+    Rs232,
+}
+
+impl FromStr for BridgeProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(BridgeProtocol::Json),
+            "rs232" => Ok(BridgeProtocol::Rs232),
+            other => Err(format!("unknown bridge protocol '{}' (expected json or rs232)", other)),
+        }
+    }
+}
+
+/// Configuration of a radio bridge.
+#[derive(Debug, Clone)]
+pub struct BridgeConfig {
+    /// Channel and spreading factor of the hardware, and the TX power its
+    /// packets are transmitted into the simulation with.
+    pub params: RadioParams,
+    /// Sync word (network ID) the hardware listens for and sends with.
+    pub sync_word: u8,
+    /// Number of preamble symbols used for time on air calculation.
+    pub preamble_symbols: u32,
+    /// Wire format spoken with the hardware.
+    pub protocol: BridgeProtocol,
+    /// Entity ID of the Graph entity (for routing transmissions).
+    pub graph_entity: EntityId,
+}
+
+/// A simulated packet as handed to the hardware in JSON.
+#[derive(Debug, Serialize)]
+struct JsonOutbound {
+    data: String,
+    snr_db: f64,
+    rssi_dbm: f64,
+    frequency_hz: u32,
+    bandwidth_hz: u32,
+    spreading_factor: u8,
+    coding_rate: u8,
+    sim_time_us: u64,
+}
+
+/// A packet heard by the hardware, as reported in JSON.
+#[derive(Debug, Deserialize)]
+struct JsonInbound {
+    data: String,
+}
+
+/// Fletcher-16 checksum, as used by RS232 bridge frames.
+pub fn fletcher16(data: &[u8]) -> u16 {
+    let (mut sum1, mut sum2) = (0u16, 0u16);
+    for &byte in data {
+        sum1 = (sum1 + byte as u16) % 255;
+        sum2 = (sum2 + sum1) % 255;
+    }
+    (sum2 << 8) | sum1
+}
+
+/// Frame `packet` for an RS232 bridge.
+pub fn encode_rs232_frame(packet: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(packet.len() + 6);
+    frame.extend_from_slice(&RS232_MAGIC);
+    frame.extend_from_slice(&(packet.len() as u16).to_be_bytes());
+    frame.extend_from_slice(packet);
+    frame.extend_from_slice(&fletcher16(packet).to_be_bytes());
+    frame
+}
+
+/// Accumulates serial data from the hardware and splits it into packets.
+#[derive(Debug, Default)]
+struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    /// Add `data` and return the complete packets it finished, plus the
+    /// number of malformed frames skipped.
+    fn push(&mut self, protocol: BridgeProtocol, data: &[u8]) -> (Vec<Vec<u8>>, u64) {
+        self.buffer.extend_from_slice(data);
+        match protocol {
+            BridgeProtocol::Json => self.take_lines(),
+            BridgeProtocol::Rs232 => self.take_frames(),
+        }
+    }
+
+    fn take_lines(&mut self) -> (Vec<Vec<u8>>, u64) {
+        let mut packets = Vec::new();
+        let mut errors = 0;
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<JsonInbound>(line).ok().and_then(|frame| hex::decode(frame.data).ok()) {
+                Some(packet) if !packet.is_empty() => packets.push(packet),
+                _ => errors += 1,
+            }
+        }
+        if self.buffer.len() > MAX_LINE_LEN {
+            self.buffer.clear();
+            errors += 1;
+        }
+        (packets, errors)
+    }
+
+    fn take_frames(&mut self) -> (Vec<Vec<u8>>, u64) {
+        let mut packets = Vec::new();
+        let mut errors = 0;
+        loop {
+            // Resynchronize on the magic, dropping anything before it
+            let Some(start) = self.buffer.windows(2).position(|w| w == RS232_MAGIC) else {
+                let keep = usize::from(self.buffer.last() == Some(&RS232_MAGIC[0]));
+                self.buffer.drain(..self.buffer.len() - keep);
+                break;
+            };
+            self.buffer.drain(..start);
+            if self.buffer.len() < 4 {
+                break;
+            }
+            let len = u16::from_be_bytes([self.buffer[2], self.buffer[3]]) as usize;
+            if len == 0 || len > RS232_MAX_PACKET_LEN {
+                errors += 1;
+                self.buffer.drain(..2);
+                continue;
+            }
+            if self.buffer.len() < len + 6 {
+                break;
+            }
+            let packet = &self.buffer[4..4 + len];
+            let checksum = u16::from_be_bytes([self.buffer[4 + len], self.buffer[5 + len]]);
+            if fletcher16(packet) == checksum {
+                packets.push(packet.to_vec());
+                self.buffer.drain(..len + 6);
+            } else {
+                errors += 1;
+                self.buffer.drain(..2);
+            }
+        }
+        (packets, errors)
+    }
+}
+
+/// Entity that connects real LoRa hardware to the simulated mesh.
+///
+/// Receives `ReceiveAir` from the Graph and `SerialRx` from the hardware;
+/// sends `SerialTx` to itself for the hardware and `TransmitAir` to the
+/// Graph.
+pub struct RadioBridge {
+    id: EntityId,
+    config: BridgeConfig,
+    metric_labels: MetricLabels,
+    decoder: FrameDecoder,
+    to_hardware: u64,
+    from_hardware: u64,
+    frame_errors: u64,
+}
+
+impl RadioBridge {
+    /// Create a new radio bridge.
+    pub fn new(id: EntityId, config: BridgeConfig, metric_labels: MetricLabels) -> Self {
+        RadioBridge {
+            id,
+            config,
+            metric_labels,
+            decoder: FrameDecoder::default(),
+            to_hardware: 0,
+            from_hardware: 0,
+            frame_errors: 0,
+        }
+    }
+
+    /// The bridge configuration.
+    pub fn config(&self) -> &BridgeConfig {
+        &self.config
+    }
+
+    /// Simulated packets handed to the hardware so far.
+    pub fn to_hardware(&self) -> u64 {
+        self.to_hardware
+    }
+
+    /// Packets from the hardware transmitted into the simulation so far.
+    pub fn from_hardware(&self) -> u64 {
+        self.from_hardware
+    }
+
+    /// Malformed frames received from the hardware so far.
+    pub fn frame_errors(&self) -> u64 {
+        self.frame_errors
+    }
+
+    /// Hand a simulated packet to the hardware if it could decode it.
+    fn handle_receive_air(&mut self, rx_event: &ReceiveAirEvent, ctx: &mut SimContext) {
+        let params = &self.config.params;
+        let co_channel = matches!(
+            channel::channel_relation(
+                params.frequency_hz,
+                params.bandwidth_hz,
+                rx_event.params.frequency_hz,
+                rx_event.params.bandwidth_hz,
+                channel::ADJACENT_CHANNEL_REJECTION_DB,
+            ),
+            channel::ChannelRelation::CoChannel
+        );
+        if !co_channel
+            || rx_event.params.spreading_factor != params.spreading_factor
+            || rx_event.sync_word != self.config.sync_word
+            || rx_event.fault == Some(InjectedFault::Drop)
+        {
+            return;
+        }
+
+        let tx_power_offset_db = rx_event.params.tx_power_offset_db();
+        let snr_db = sample_gaussian(
+            ctx.rng_substream(substream::SNR),
+            rx_event.mean_snr_db_at20dbm + tx_power_offset_db,
+            rx_event.snr_std_dev,
+        );
+        let fading_gain_db = rx_event.fading.sample_gain_db(ctx.rng_substream(substream::FADING));
+        let snr_db = snr_db + fading_gain_db;
+        if snr_db < calculate_snr_sensitivity(params.spreading_factor) {
+            return;
+        }
+        let rssi_dbm = rx_event.rssi_dbm + tx_power_offset_db + fading_gain_db;
+
+        let payload = &rx_event.packet.payload;
+        let data = match self.config.protocol {
+            BridgeProtocol::Json => {
+                let frame = JsonOutbound {
+                    data: hex::encode(payload),
+                    snr_db,
+                    rssi_dbm,
+                    frequency_hz: rx_event.params.frequency_hz,
+                    bandwidth_hz: rx_event.params.bandwidth_hz,
+                    spreading_factor: rx_event.params.spreading_factor,
+                    coding_rate: rx_event.params.coding_rate,
+                    sim_time_us: rx_event.end_time.as_micros(),
+                };
+                let mut line = serde_json::to_vec(&frame).expect("bridge frames serialize");
+                line.push(b'\n');
+                line
+            }
+            BridgeProtocol::Rs232 if payload.len() > RS232_MAX_PACKET_LEN => return,
+            BridgeProtocol::Rs232 => encode_rs232_frame(payload),
+        };
+
+        self.to_hardware += 1;
+        metrics::counter!(metric_defs::BRIDGE_TO_HARDWARE.name, &self.metric_labels.to_labels()).increment(1);
+        // Handed over once the packet has finished arriving
+        ctx.post_event(
+            rx_event.end_time - ctx.time(),
+            vec![self.id],
+            EventPayload::SerialTx(mcsim_common::SerialTxEvent { data }),
+        );
+    }
+
+    /// Transmit the packets the hardware heard into the simulation.
+    fn handle_serial_rx(&mut self, data: &[u8], ctx: &mut SimContext) {
+        let (packets, errors) = self.decoder.push(self.config.protocol, data);
+        let labels = self.metric_labels.to_labels();
+        if errors > 0 {
+            self.frame_errors += errors;
+            metrics::counter!(metric_defs::BRIDGE_FRAME_ERRORS.name, &labels).increment(errors);
+        }
+        for payload in packets {
+            let airtime = AirtimeParams::from_radio_params(&self.config.params)
+                .with_preamble_symbols(self.config.preamble_symbols)
+                .time_on_air(payload.len());
+            self.from_hardware += 1;
+            metrics::counter!(metric_defs::BRIDGE_FROM_HARDWARE.name, &labels).increment(1);
+            ctx.post_immediate(
+                vec![self.config.graph_entity],
+                EventPayload::TransmitAir(mcsim_common::TransmitAirEvent {
+                    radio_id: self.id,
+                    packet: LoraPacket::new(payload),
+                    params: self.config.params.clone(),
+                    sync_word: self.config.sync_word,
+                    end_time: ctx.time() + airtime,
+                }),
+            );
+        }
+    }
+}
+
+impl Entity for RadioBridge {
+    fn entity_id(&self) -> EntityId {
+        self.id
+    }
+
+    fn handle_event(&mut self, event: &Event, ctx: &mut SimContext) -> Result<(), SimError> {
+        match &event.payload {
+            EventPayload::ReceiveAir(rx_event) => self.handle_receive_air(rx_event, ctx),
+            EventPayload::SerialRx(serial) => self.handle_serial_rx(&serial.data, ctx),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{default_radio_params, FadingModel};
+    use mcsim_common::SimTime;
+
+    fn bridge(protocol: BridgeProtocol) -> RadioBridge {
+        RadioBridge::new(
+            EntityId::new(5),
+            BridgeConfig {
+                params: default_radio_params(),
+                sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+                preamble_symbols: AirtimeParams::DEFAULT_PREAMBLE_SYMBOLS,
+                protocol,
+                graph_entity: EntityId::new(0),
+            },
+            MetricLabels::new("Gateway", "bridge"),
+        )
+    }
+
+    fn event(payload: EventPayload) -> Event {
+        Event {
+            id: mcsim_common::EventId(0),
+            time: SimTime::ZERO,
+            source: EntityId::new(0),
+            targets: vec![EntityId::new(5)],
+            payload,
+        }
+    }
+
+    fn receive_air(mean_snr_db_at20dbm: f64, spreading_factor: u8) -> EventPayload {
+        EventPayload::ReceiveAir(ReceiveAirEvent {
+            source_radio_id: EntityId::new(1),
+            packet: LoraPacket::new(vec![0x11, 0x22, 0x33]),
+            params: RadioParams { spreading_factor, ..default_radio_params() },
+            sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+            end_time: SimTime::from_millis(120),
+            mean_snr_db_at20dbm,
+            snr_std_dev: 0.0,
+            rssi_dbm: -100.0,
+            fading: FadingModel::None,
+            fault: None,
+        })
+    }
+
+    #[test]
+    fn test_decodable_packets_reach_hardware() {
+        let mut bridge = bridge(BridgeProtocol::Json);
+        let mut ctx = SimContext::new(1);
+        let sf = default_radio_params().spreading_factor;
+        bridge.handle_event(&event(receive_air(5.0, sf)), &mut ctx).unwrap();
+        // Too weak, and on another spreading factor
+        bridge.handle_event(&event(receive_air(-30.0, sf)), &mut ctx).unwrap();
+        bridge.handle_event(&event(receive_air(5.0, sf + 1)), &mut ctx).unwrap();
+
+        let events = ctx.take_pending_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(bridge.to_hardware(), 1);
+        assert_eq!(events[0].time, SimTime::from_millis(120));
+        let EventPayload::SerialTx(serial) = &events[0].payload else {
+            panic!("expected serial data for the hardware");
+        };
+        let line: serde_json::Value = serde_json::from_slice(&serial.data).unwrap();
+        assert_eq!(line["data"], "112233");
+        assert_eq!(line["snr_db"], 5.0);
+        assert_eq!(line["spreading_factor"], sf);
+    }
+
+    #[test]
+    fn test_hardware_packets_are_transmitted() {
+        let mut bridge = bridge(BridgeProtocol::Json);
+        let mut ctx = SimContext::new(1);
+        ctx.set_time(SimTime::from_secs(10.0));
+        let serial = |data: &[u8]| {
+            event(EventPayload::SerialRx(mcsim_common::SerialRxEvent { data: data.to_vec() }))
+        };
+        // A line split across reads, then a malformed one
+        bridge.handle_event(&serial(b"{\"data\":\"aabb"), &mut ctx).unwrap();
+        assert!(ctx.take_pending_events().is_empty());
+        bridge.handle_event(&serial(b"cc\",\"rssi_dbm\":-90}\nnot json\n"), &mut ctx).unwrap();
+
+        let events = ctx.take_pending_events();
+        assert_eq!((bridge.from_hardware(), bridge.frame_errors()), (1, 1));
+        let EventPayload::TransmitAir(tx) = &events[0].payload else {
+            panic!("expected a transmission");
+        };
+        assert_eq!(events[0].targets, vec![EntityId::new(0)]);
+        assert_eq!(tx.radio_id, EntityId::new(5));
+        assert_eq!(tx.packet.payload, vec![0xaa, 0xbb, 0xcc]);
+        assert!(tx.end_time > SimTime::from_secs(10.0));
+    }
+
+    #[test]
+    fn test_rs232_frames() {
+        // Reference value of the Fletcher-16 checksum
+        assert_eq!(fletcher16(b"abcde"), 0xC8F0);
+
+        let frame = encode_rs232_frame(&[1, 2, 3]);
+        assert_eq!(&frame[..4], &[0xC0, 0x3E, 0, 3]);
+
+        let mut decoder = FrameDecoder::default();
+        let mut corrupted = frame.clone();
+        corrupted[5] ^= 0xFF;
+        // Line noise, a corrupted frame, then a good one split across reads
+        let mut stream = vec![0x00, 0xC0];
+        stream.extend(&corrupted);
+        stream.extend(&frame);
+        let (first, rest) = stream.split_at(stream.len() - 2);
+        let (packets, errors) = decoder.push(BridgeProtocol::Rs232, first);
+        assert!(packets.is_empty());
+        assert_eq!(errors, 1);
+        let (packets, errors) = decoder.push(BridgeProtocol::Rs232, rest);
+        assert_eq!(packets, vec![vec![1, 2, 3]]);
+        assert_eq!(errors, 0);
+        assert!(decoder.buffer.is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+//! Frequency channels.
+//!
+//! A radio decodes only packets sent on its own channel: the same bandwidth
+//! and a center frequency within [`FREQUENCY_TOLERANCE`] of the bandwidth,
+//! which absorbs crystal error between the two radios. A signal whose band
+//! partly overlaps the channel can't be decoded, but the part of its power
+//! that falls inside the channel still interferes, reduced by the receiver's
+//! adjacent-channel rejection. Signals whose bands don't overlap at all don't
+//! interact.
+
+/// Largest carrier offset, as a fraction of the bandwidth, at which a LoRa
+/// receiver still locks onto a packet.
+pub const FREQUENCY_TOLERANCE: f64 = 0.25;
+
+/// Default rejection of an overlapping signal on a different channel, in dB,
+/// on top of the power that falls outside the receiver's band.
+pub const ADJACENT_CHANNEL_REJECTION_DB: f64 = 10.0;
+
+/// Whether two signals overlap in frequency. A bandwidth of 0 is a carrier.
+pub fn channels_overlap(a_hz: u32, a_bandwidth_hz: u32, b_hz: u32, b_bandwidth_hz: u32) -> bool {
+    let separation = (a_hz as f64 - b_hz as f64).abs();
+    separation * 2.0 < a_bandwidth_hz as f64 + b_bandwidth_hz as f64
+}
+
+/// How a transmission relates to a receiver's channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelRelation {
+    /// Same channel: the packet can be decoded and collides at full power.
+    CoChannel,
+    /// Overlapping channel: the packet can't be decoded and interferes at
+    /// reduced power.
+    Adjacent {
+        /// Attenuation of the interfering signal, in dB.
+        rejection_db: f64,
+    },
+    /// No overlap: the receiver doesn't see the packet.
+    Separate,
+}
+
+/// How a transmission on `tx_hz`/`tx_bandwidth_hz` relates to a receiver
+/// tuned to `rx_hz`/`rx_bandwidth_hz`.
+///
+/// An adjacent signal is attenuated by `adjacent_channel_rejection_db` plus
+/// the fraction of its power that falls outside the receiver's band.
+pub fn channel_relation(
+    rx_hz: u32,
+    rx_bandwidth_hz: u32,
+    tx_hz: u32,
+    tx_bandwidth_hz: u32,
+    adjacent_channel_rejection_db: f64,
+) -> ChannelRelation {
+    let offset_hz = (rx_hz as f64 - tx_hz as f64).abs();
+    if rx_bandwidth_hz == tx_bandwidth_hz && offset_hz <= rx_bandwidth_hz as f64 * FREQUENCY_TOLERANCE {
+        return ChannelRelation::CoChannel;
+    }
+    if !channels_overlap(rx_hz, rx_bandwidth_hz, tx_hz, tx_bandwidth_hz) {
+        return ChannelRelation::Separate;
+    }
+
+    let band = |hz: u32, bandwidth_hz: u32| {
+        let half = bandwidth_hz as f64 / 2.0;
+        (hz as f64 - half, hz as f64 + half)
+    };
+    let (rx_low, rx_high) = band(rx_hz, rx_bandwidth_hz);
+    let (tx_low, tx_high) = band(tx_hz, tx_bandwidth_hz);
+    let overlap_hz = rx_high.min(tx_high) - rx_low.max(tx_low);
+    let in_band_fraction = (overlap_hz / tx_bandwidth_hz.max(1) as f64).clamp(f64::MIN_POSITIVE, 1.0);
+
+    ChannelRelation::Adjacent {
+        rejection_db: adjacent_channel_rejection_db - 10.0 * in_band_fraction.log10(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels_overlap() {
+        // Same channel, adjacent channel, carrier inside and outside the channel
+        assert!(channels_overlap(910_525_000, 62_500, 910_525_000, 62_500));
+        assert!(!channels_overlap(910_525_000, 62_500, 910_600_000, 62_500));
+        assert!(channels_overlap(910_525_000, 62_500, 910_550_000, 0));
+        assert!(!channels_overlap(910_525_000, 62_500, 910_560_000, 0));
+    }
+
+    #[test]
+    fn test_channel_relation() {
+        let relation = |tx_hz, tx_bandwidth_hz| {
+            channel_relation(910_525_000, 62_500, tx_hz, tx_bandwidth_hz, ADJACENT_CHANNEL_REJECTION_DB)
+        };
+        assert_eq!(relation(910_525_000, 62_500), ChannelRelation::CoChannel);
+        // Crystal error within a quarter of the bandwidth is tolerated
+        assert_eq!(relation(910_540_625, 62_500), ChannelRelation::CoChannel);
+        assert!(matches!(relation(910_541_000, 62_500), ChannelRelation::Adjacent { .. }));
+        assert_eq!(relation(910_600_000, 62_500), ChannelRelation::Separate);
+
+        // Half the band overlaps: 3 dB of the power falls outside
+        let ChannelRelation::Adjacent { rejection_db } = relation(910_556_250, 62_500) else {
+            panic!("expected an adjacent channel");
+        };
+        assert!((rejection_db - 13.01).abs() < 0.01);
+
+        // A wider channel on the same center: a quarter of its power is in band
+        let ChannelRelation::Adjacent { rejection_db } = relation(910_525_000, 250_000) else {
+            panic!("expected an adjacent channel");
+        };
+        assert!((rejection_db - 16.02).abs() < 0.01);
+    }
+}
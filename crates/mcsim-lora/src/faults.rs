@@ -0,0 +1,159 @@
+//! Packet-level fault injection.
+//!
+//! Fault rules drop or corrupt selected packets on chosen links, so retry
+//! and path-rebuild logic in firmware can be exercised with targeted loss
+//! rather than whatever the link model happens to produce. The
+//! [`Graph`](crate::Graph) checks the rules for every receiver of a
+//! transmission and marks the [`ReceiveAirEvent`](mcsim_common::ReceiveAirEvent)
+//! with the injected fault:
+//!
+//! - a dropped packet is discarded by the receiving radio, as if it had never
+//!   been sent on that link (it neither occupies the channel nor collides);
+//! - a corrupted packet is received normally, but if it would have been
+//!   decoded one bit of its payload is flipped and it is reported to the
+//!   firmware as corrupted.
+//!
+//! A rule selects packets either at random with a given probability, drawn
+//! from the simulation RNG, or as every Nth packet it matches, and only
+//! within its time window, so runs stay reproducible for a seed.
+
+use mcsim_common::{EntityId, InjectedFault, SimTime};
+use rand::Rng;
+
+/// Which of the packets matching a rule are hit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultSelection {
+    /// Each packet independently, with this probability (0 to 1).
+    Probability(f64),
+    /// Every Nth packet: the Nth, 2Nth, ... matching the rule.
+    Every(u64),
+}
+
+/// A fault injection rule on transmissions between radios.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultRule {
+    /// Transmitting radio, or any radio.
+    pub from: Option<EntityId>,
+    /// Receiving radio, or any radio.
+    pub to: Option<EntityId>,
+    /// Whether the rule also applies from `to` to `from`.
+    pub bidirectional: bool,
+    /// What happens to a selected packet.
+    pub action: InjectedFault,
+    /// Which matching packets are selected.
+    pub selection: FaultSelection,
+    /// When the rule starts applying.
+    pub start: SimTime,
+    /// When it stops applying, or never.
+    pub end: Option<SimTime>,
+}
+
+impl FaultRule {
+    /// Whether a packet from `from` to `to` sent at `time` is covered by
+    /// the rule.
+    pub fn matches(&self, from: EntityId, to: EntityId, time: SimTime) -> bool {
+        let on_link = |a: EntityId, b: EntityId| {
+            self.from.is_none_or(|from| from == a) && self.to.is_none_or(|to| to == b)
+        };
+        time >= self.start
+            && self.end.is_none_or(|end| time < end)
+            && (on_link(from, to) || (self.bidirectional && on_link(to, from)))
+    }
+}
+
+/// The fault rules of a simulation, with the packets each has matched.
+#[derive(Debug, Clone, Default)]
+pub struct PacketFaults {
+    rules: Vec<FaultRule>,
+    matched: Vec<u64>,
+}
+
+impl PacketFaults {
+    /// Apply `rules`, in order.
+    pub fn new(rules: Vec<FaultRule>) -> Self {
+        let matched = vec![0; rules.len()];
+        PacketFaults { rules, matched }
+    }
+
+    /// Get the rules.
+    pub fn rules(&self) -> &[FaultRule] {
+        &self.rules
+    }
+
+    /// Number of packets each rule has matched so far, in rule order.
+    pub fn matched(&self) -> &[u64] {
+        &self.matched
+    }
+
+    /// Decide the fault, if any, injected into a packet from `from` to `to`
+    /// sent at `time`.
+    ///
+    /// Every rule covering the packet counts it, so an `Every` rule keeps its
+    /// schedule when an earlier rule already hit the packet; the first rule
+    /// that selects it decides the fault.
+    pub fn apply<R: Rng>(&mut self, from: EntityId, to: EntityId, time: SimTime, rng: &mut R) -> Option<InjectedFault> {
+        let mut fault = None;
+        for (rule, matched) in self.rules.iter().zip(&mut self.matched) {
+            if !rule.matches(from, to, time) {
+                continue;
+            }
+            *matched += 1;
+            let selected = match rule.selection {
+                FaultSelection::Probability(p) => rng.gen::<f64>() < p,
+                FaultSelection::Every(n) => *matched % n.max(1) == 0,
+            };
+            if selected && fault.is_none() {
+                fault = Some(rule.action);
+            }
+        }
+        fault
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_fault_rules_select_packets() {
+        let (a, b, c) = (EntityId::new(1), EntityId::new(2), EntityId::new(3));
+        let window = FaultRule {
+            from: Some(a),
+            to: Some(b),
+            bidirectional: false,
+            action: InjectedFault::Drop,
+            selection: FaultSelection::Probability(1.0),
+            start: SimTime::from_secs(60.0),
+            end: Some(SimTime::from_secs(120.0)),
+        };
+        let every_third = FaultRule {
+            from: Some(b),
+            to: Some(c),
+            bidirectional: true,
+            action: InjectedFault::Corrupt,
+            selection: FaultSelection::Every(3),
+            start: SimTime::ZERO,
+            end: None,
+        };
+        let mut faults = PacketFaults::new(vec![window, every_third]);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+        // Only inside the window, and only in the rule's direction
+        assert_eq!(faults.apply(a, b, SimTime::from_secs(59.0), &mut rng), None);
+        assert_eq!(faults.apply(a, b, SimTime::from_secs(60.0), &mut rng), Some(InjectedFault::Drop));
+        assert_eq!(faults.apply(b, a, SimTime::from_secs(90.0), &mut rng), None);
+        assert_eq!(faults.apply(a, b, SimTime::from_secs(120.0), &mut rng), None);
+
+        // Both directions count towards every third packet
+        let hits: Vec<_> = (0..6)
+            .map(|i| {
+                let (from, to) = if i % 2 == 0 { (b, c) } else { (c, b) };
+                faults.apply(from, to, SimTime::from_secs(10.0), &mut rng)
+            })
+            .collect();
+        assert_eq!(hits, [None, None, Some(InjectedFault::Corrupt), None, None, Some(InjectedFault::Corrupt)]);
+        assert_eq!(faults.matched(), &[1, 6]);
+    }
+}
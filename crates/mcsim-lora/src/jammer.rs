@@ -0,0 +1,159 @@
+//! Interference sources.
+//!
+//! A [`Jammer`] is a transmitter that carries no packets: on a duty cycle it
+//! emits a burst of wideband noise or an unmodulated carrier, routed by the
+//! [`Graph`](crate::Graph) to every radio it has a link to. While a burst
+//! lasts, the receiving radio's effective noise floor is raised by the
+//! interference power, and every packet it is receiving loses that much SNR.
+//!
+//! LoRa's chirp spread spectrum spreads a narrowband carrier across the
+//! whole channel when the receiver de-chirps, so a carrier is attenuated by
+//! the processing gain (`10·log10(2^SF)` dB) before it adds to the noise.
+//! Wideband noise gets no such rejection.
+//!
+//! Interference is not a LoRa preamble, so it never triggers the firmware's
+//! channel activity detection.
+
+use mcsim_common::{
+    Entity, EntityId, Event, EventPayload, InterferenceKind, SimContext, SimError, SimTime,
+    TransmitInterferenceEvent,
+};
+
+/// Timer ID of a jammer's next burst.
+pub const TIMER_JAMMER_BURST: u64 = 1;
+
+/// Attenuation a LoRa receiver applies to interference of a kind, in dB.
+pub fn interference_rejection_db(kind: InterferenceKind, spreading_factor: u8) -> f64 {
+    match kind {
+        InterferenceKind::Noise => 0.0,
+        InterferenceKind::ContinuousWave => 10.0 * 2f64.powi(spreading_factor as i32).log10(),
+    }
+}
+
+/// Rise of the noise floor in dB from interferers at the given
+/// interference-to-noise ratios (dB).
+pub fn noise_rise_db(interference_to_noise_db: impl IntoIterator<Item = f64>) -> f64 {
+    let interference: f64 = interference_to_noise_db
+        .into_iter()
+        .map(|inr_db| 10f64.powf(inr_db / 10.0))
+        .sum();
+    10.0 * (1.0 + interference).log10()
+}
+
+/// Configuration of an interference source.
+#[derive(Debug, Clone)]
+pub struct JammerConfig {
+    /// Kind of signal emitted.
+    pub kind: InterferenceKind,
+    /// Center frequency in Hz.
+    pub frequency_hz: u32,
+    /// Occupied bandwidth in Hz (ignored for a carrier).
+    pub bandwidth_hz: u32,
+    /// Transmit power in dBm.
+    pub tx_power_dbm: i8,
+    /// Length of each burst.
+    pub on_time: SimTime,
+    /// Time from the start of one burst to the start of the next. Equal to
+    /// `on_time` for a continuous jammer.
+    pub period: SimTime,
+    /// When the jammer stops for good, if it does.
+    pub stop_time: Option<SimTime>,
+    /// Entity ID of the Graph entity (for routing the interference).
+    pub graph_entity: EntityId,
+}
+
+impl JammerConfig {
+    /// Fraction of the time the jammer is on.
+    pub fn duty_cycle(&self) -> f64 {
+        self.on_time.as_micros() as f64 / self.period.as_micros().max(1) as f64
+    }
+}
+
+/// Interference source entity.
+///
+/// The first burst starts on the first [`TIMER_JAMMER_BURST`] timer, which
+/// the caller schedules; later bursts are scheduled by the jammer.
+pub struct Jammer {
+    id: EntityId,
+    config: JammerConfig,
+    bursts: u64,
+}
+
+impl Jammer {
+    /// Create a new interference source.
+    pub fn new(id: EntityId, config: JammerConfig) -> Self {
+        Jammer { id, config, bursts: 0 }
+    }
+
+    /// The jammer configuration.
+    pub fn config(&self) -> &JammerConfig {
+        &self.config
+    }
+
+    /// Number of bursts emitted so far.
+    pub fn bursts(&self) -> u64 {
+        self.bursts
+    }
+
+    fn start_burst(&mut self, ctx: &mut SimContext) {
+        let now = ctx.time();
+        if self.config.stop_time.is_some_and(|stop| now >= stop) {
+            return;
+        }
+        let mut end_time = now + self.config.on_time;
+        if let Some(stop) = self.config.stop_time {
+            end_time = end_time.min(stop);
+        }
+        let bandwidth_hz = match self.config.kind {
+            InterferenceKind::Noise => self.config.bandwidth_hz,
+            InterferenceKind::ContinuousWave => 0,
+        };
+        self.bursts += 1;
+        ctx.post_immediate(
+            vec![self.config.graph_entity],
+            EventPayload::TransmitInterference(TransmitInterferenceEvent {
+                source_id: self.id,
+                kind: self.config.kind,
+                frequency_hz: self.config.frequency_hz,
+                bandwidth_hz,
+                tx_power_dbm: self.config.tx_power_dbm,
+                end_time,
+            }),
+        );
+        ctx.post_event(
+            self.config.period,
+            vec![self.id],
+            EventPayload::Timer { timer_id: TIMER_JAMMER_BURST },
+        );
+    }
+}
+
+impl Entity for Jammer {
+    fn entity_id(&self) -> EntityId {
+        self.id
+    }
+
+    fn handle_event(&mut self, event: &Event, ctx: &mut SimContext) -> Result<(), SimError> {
+        if let EventPayload::Timer { timer_id: TIMER_JAMMER_BURST } = event.payload {
+            self.start_burst(ctx);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_rise() {
+        assert_eq!(noise_rise_db(std::iter::empty()), 0.0);
+        // Interference at the noise floor doubles the noise
+        assert!((noise_rise_db([0.0]) - 3.0103).abs() < 1e-3);
+        // Strong interference dominates
+        assert!((noise_rise_db([20.0, 0.0]) - 20.086).abs() < 1e-2);
+        // A carrier is rejected by the spreading gain
+        assert!((interference_rejection_db(InterferenceKind::ContinuousWave, 11) - 33.11).abs() < 1e-2);
+        assert_eq!(interference_rejection_db(InterferenceKind::Noise, 11), 0.0);
+    }
+}
@@ -8,19 +8,48 @@
 //! - Radio entity simulation ([`Radio`])
 //! - Link model for signal propagation ([`LinkModel`])
 //! - Collision detection ([`check_collision`])
+//! - Frequency channels and adjacent-channel rejection ([`channel`])
+//! - Bit-error channel for marginal receptions ([`BitErrorConfig`], [`corrupt_payload`])
 //! - PHY calculations ([`calculate_time_on_air`], [`calculate_snr_sensitivity`])
 //! - Configurable PHY parameters ([`LoraPhyConfig`])
-
+//! - Node mobility with links recomputed from position ([`mobility`])
+//! - Battery drain from radio activity ([`power`])
+//! - Targeted packet loss and corruption on links ([`faults`])
+//! - TX power derating under sustained transmit duty ([`thermal`])
+//! - Regulatory duty-cycle and dwell-time limits ([`regulatory`])
+//! - Advert suppression policies driven by channel utilization ([`suppression`])
+//! - Hardware-in-the-loop bridges to real LoRa radios ([`bridge`])
+
+pub mod bridge;
+pub mod channel;
+pub mod faults;
+pub mod jammer;
+pub mod mobility;
+pub mod power;
+pub mod regulatory;
+pub mod suppression;
+pub mod thermal;
+
+use mcsim_common::rng::substream;
 use mcsim_common::{
     Entity, EntityId, Event, EventPayload, GeoCoord, SimContext, SimError,
     SimTime,
 };
 use mcsim_metrics::{metric_defs, metrics, MetricLabels};
+use power::{Battery, PowerConfig, PowerState, BATTERY_EMPTY_MV};
+use regulatory::{Compliance, ComplianceTracker, RegulatoryConfig, Violation};
+use suppression::{is_own_advert, AdvertSuppression, AdvertSuppressor};
+use thermal::{DeratingConfig, DutyTracker};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 // Re-export common types
+pub use mcsim_common::airtime::{AirtimeParams, LoraHeaderMode, LowDataRateOptimize};
+pub use mcsim_common::crystal::CrystalDrift;
+pub use mcsim_common::FadingModel;
+pub use mcsim_common::InjectedFault;
+pub use mcsim_common::InterferenceKind;
 pub use mcsim_common::LoraPacket;
 pub use mcsim_common::RadioParams;
 
@@ -150,43 +179,30 @@ impl Default for LoraPhyConfig {
 
 /// Calculate the time on air for a LoRa packet.
 ///
-/// Uses the LoRa time on air formula based on spreading factor,
-/// bandwidth, and coding rate.
+/// Uses the Semtech time on air formula with MeshCore framing (explicit
+/// header, CRC enabled, automatic low data rate optimisation). The CRC and
+/// low data rate optimisation make packets longer than the simplified
+/// formula used before, most of all at SF11 and SF12 on 125 kHz.
 ///
 /// This function uses the default preamble symbols (8). For configurable
-/// preamble, use [`calculate_time_on_air_with_config()`].
+/// preamble, use [`calculate_time_on_air_with_config()`], or build an
+/// [`AirtimeParams`] directly to control header mode and CRC.
 pub fn calculate_time_on_air(params: &RadioParams, payload_len: usize) -> SimTime {
     calculate_time_on_air_with_config(params, payload_len, &LoraPhyConfig::default())
 }
 
 /// Calculate the time on air for a LoRa packet with configurable PHY parameters.
 ///
-/// Uses the LoRa time on air formula based on spreading factor,
-/// bandwidth, coding rate, and preamble symbols from the config.
+/// Same as [`calculate_time_on_air()`] but takes the preamble symbol count
+/// from the config.
 pub fn calculate_time_on_air_with_config(
     params: &RadioParams,
     payload_len: usize,
     config: &LoraPhyConfig,
 ) -> SimTime {
-    // Simplified LoRa time on air calculation
-    let sf = params.spreading_factor as f64;
-    let bw = params.bandwidth_hz as f64;
-    let cr = params.coding_rate as f64;
-
-    // Symbol time in seconds
-    let t_sym = (2.0_f64.powf(sf)) / bw;
-
-    // Preamble symbols + 4.25 (sync word and start frame delimiter)
-    let n_preamble = config.preamble_symbols as f64 + 4.25;
-
-    // Payload symbols (simplified calculation)
-    let pl = payload_len as f64;
-    let payload_symbols = 8.0 + ((8.0 * pl - 4.0 * sf + 28.0).max(0.0) / (4.0 * sf)).ceil() * cr;
-
-    let total_symbols = n_preamble + payload_symbols;
-    let time_seconds = total_symbols * t_sym;
-
-    SimTime::from_secs(time_seconds)
+    AirtimeParams::from_radio_params(params)
+        .with_preamble_symbols(config.preamble_symbols)
+        .time_on_air(payload_len)
 }
 
 /// Calculate the SNR sensitivity threshold for a spreading factor.
@@ -236,6 +252,8 @@ pub struct CollisionContext {
     pub end_time: SimTime,
     /// Frequency in Hz.
     pub frequency_hz: u32,
+    /// Bandwidth in Hz.
+    pub bandwidth_hz: u32,
     /// Unique packet ID.
     pub packet_id: u64,
 }
@@ -259,8 +277,8 @@ pub fn check_collision(
     existing: &[CollisionContext],
 ) -> CollisionResult {
     for other in existing {
-        // Check frequency overlap (simplified: exact match required)
-        if incoming.frequency_hz != other.frequency_hz {
+        // Check frequency overlap
+        if !channel::channels_overlap(incoming.frequency_hz, incoming.bandwidth_hz, other.frequency_hz, other.bandwidth_hz) {
             continue;
         }
 
@@ -289,6 +307,9 @@ pub struct LinkParams {
     pub snr_std_dev: f64,
     /// Received signal strength in dBm.
     pub rssi_dbm: f64,
+    /// Per-packet fading on top of the Gaussian variation.
+    #[serde(default)]
+    pub fading: FadingModel,
 }
 
 /// Model of radio links between nodes.
@@ -312,7 +333,7 @@ impl LinkModel {
     pub fn add_link(&mut self, from: EntityId, to: EntityId, mean_snr_db_at20dbm: f64, snr_std_dev: f64, rssi_dbm: f64) {
         self.edges.insert(
             (from, to),
-            LinkParams { mean_snr_db_at20dbm, snr_std_dev, rssi_dbm },
+            LinkParams { mean_snr_db_at20dbm, snr_std_dev, rssi_dbm, fading: FadingModel::None },
         );
     }
 
@@ -321,6 +342,11 @@ impl LinkModel {
         self.edges.insert((from, to), params);
     }
 
+    /// Remove the directed link between two radios, if present.
+    pub fn remove_link(&mut self, from: EntityId, to: EntityId) {
+        self.edges.remove(&(from, to));
+    }
+
     /// Get link parameters between two radios.
     pub fn get_link(&self, from: EntityId, to: EntityId) -> Option<&LinkParams> {
         self.edges.get(&(from, to))
@@ -346,6 +372,62 @@ impl Default for LinkModel {
     }
 }
 
+// ============================================================================
+// Bit Errors
+// ============================================================================
+
+/// Bit-error channel settings.
+///
+/// When enabled, receptions whose SNR falls just below the demodulation
+/// threshold are delivered to firmware with flipped bits instead of being
+/// dropped, so the firmware's CRC/MAC/decrypt failure paths get exercised.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BitErrorConfig {
+    /// Width of the SNR window below the threshold (dB) in which packets are
+    /// corrupted rather than dropped.
+    pub window_db: f64,
+    /// Per-bit error rate at the bottom of the window. The rate scales
+    /// linearly from zero at the threshold.
+    pub max_bit_error_rate: f64,
+}
+
+impl BitErrorConfig {
+    /// Per-bit error rate for a reception at `snr_db`, or `None` if the packet
+    /// is outside the corruption window (above the threshold or too weak).
+    pub fn bit_error_rate(&self, snr_db: f64, threshold_db: f64) -> Option<f64> {
+        let depth = threshold_db - snr_db;
+        if depth <= 0.0 || depth > self.window_db {
+            return None;
+        }
+        Some(self.max_bit_error_rate * depth / self.window_db)
+    }
+}
+
+/// Flip bits in `payload`, each with probability `bit_error_rate`.
+///
+/// At least one bit is always flipped for a non-empty payload so that a
+/// corrupted reception never arrives intact. Returns the number of flipped bits.
+pub fn corrupt_payload<R: Rng>(rng: &mut R, payload: &mut [u8], bit_error_rate: f64) -> u32 {
+    if payload.is_empty() {
+        return 0;
+    }
+    let mut flipped = 0;
+    for byte in payload.iter_mut() {
+        for bit in 0..8 {
+            if rng.gen::<f64>() < bit_error_rate {
+                *byte ^= 1 << bit;
+                flipped += 1;
+            }
+        }
+    }
+    if flipped == 0 {
+        let bit = rng.gen_range(0..payload.len() * 8);
+        payload[bit / 8] ^= 1 << (bit % 8);
+        flipped = 1;
+    }
+    flipped
+}
+
 // ============================================================================
 // Radio Entity
 // ============================================================================
@@ -355,6 +437,36 @@ impl Default for LinkModel {
 const TIMER_TX_TURNAROUND_COMPLETE: u64 = 1;
 const TIMER_RX_TURNAROUND_COMPLETE: u64 = 2;
 const TIMER_RX_COMPLETE_BASE: u64 = 0x1000; // reception_id is added to this
+const TIMER_TX_QUEUE_RELEASE: u64 = 4;
+
+/// Timer that updates a battery-powered radio's charge and reports it to
+/// firmware. Post it to the radio once at the start of the simulation; the
+/// radio then re-arms it every [`BATTERY_UPDATE_INTERVAL`].
+pub const BATTERY_UPDATE_TIMER_ID: u64 = 3;
+
+/// Relative difference allowed between the airtime firmware reports for a
+/// packet and the computed time on air.
+pub const AIRTIME_TOLERANCE: f64 = 0.05;
+
+/// Absolute slack, since firmware reports airtime in whole milliseconds.
+const AIRTIME_TOLERANCE_MS: f64 = 1.0;
+
+/// Compare the airtime firmware reported for a packet with the computed time
+/// on air. Returns the relative difference when it is outside
+/// [`AIRTIME_TOLERANCE`].
+///
+/// A mismatch usually means the firmware's radio is configured differently
+/// from the simulator's (spreading factor, bandwidth, coding rate or
+/// preamble), so its timing decisions are based on the wrong airtime.
+pub fn check_reported_airtime(reported_ms: u32, expected: SimTime) -> Option<f64> {
+    let expected_ms = expected.as_micros() as f64 / 1000.0;
+    let difference_ms = (reported_ms as f64 - expected_ms).abs();
+    let relative = difference_ms / expected_ms.max(f64::EPSILON);
+    (difference_ms > AIRTIME_TOLERANCE_MS && relative > AIRTIME_TOLERANCE).then_some(relative)
+}
+
+/// How often a battery-powered radio reports its battery level.
+pub const BATTERY_UPDATE_INTERVAL: SimTime = SimTime::from_micros(60_000_000);
 
 /// State of an active reception.
 #[derive(Debug, Clone)]
@@ -373,10 +485,29 @@ struct ActiveReception {
     rssi_dbm: f64,
     /// Whether this packet was damaged by collision.
     collided: bool,
+    /// Whether the packet is on an overlapping channel rather than the
+    /// radio's own. It can only interfere; `snr_db` is after rejection.
+    adjacent_channel: bool,
+    /// Whether the packet was sent with another network's sync word. It
+    /// keeps the channel busy and can collide, but is never decoded.
+    foreign_network: bool,
+    /// Highest noise floor rise from interference during the reception (dB).
+    noise_rise_db: f64,
+    /// Whether a fault injection rule corrupts the packet.
+    injected_corruption: bool,
     /// Unique ID for this reception (for timer tracking).
     reception_id: u64,
 }
 
+/// Interference reaching the radio.
+#[derive(Debug, Clone)]
+struct ActiveInterference {
+    /// When the burst ends.
+    end_time: SimTime,
+    /// Interference-to-noise ratio after the receiver's rejection (dB).
+    interference_to_noise_db: f64,
+}
+
 /// Radio configuration including turnaround times.
 #[derive(Debug, Clone)]
 pub struct RadioConfig {
@@ -386,6 +517,28 @@ pub struct RadioConfig {
     pub rx_to_tx_turnaround: SimTime,
     /// Time to switch from TX to RX mode.
     pub tx_to_rx_turnaround: SimTime,
+    /// Number of preamble symbols used for time on air calculation.
+    pub preamble_symbols: u32,
+    /// Bit-error channel for marginal receptions (`None` drops them).
+    pub bit_errors: Option<BitErrorConfig>,
+    /// Battery and current draw (`None` for mains-powered nodes).
+    pub power: Option<PowerConfig>,
+    /// TX power derating under sustained transmitting (`None` for a
+    /// constant TX power).
+    pub derating: Option<DeratingConfig>,
+    /// Regulatory duty-cycle and dwell-time limits (`None` for no limits).
+    pub regulatory: Option<RegulatoryConfig>,
+    /// Policy that may skip the node's own adverts (`None` to send them
+    /// all).
+    pub advert_suppression: Option<AdvertSuppression>,
+    /// Rejection of packets on partially overlapping channels, in dB
+    /// (see [`channel::channel_relation`]).
+    pub adjacent_channel_rejection_db: f64,
+    /// Temperature-dependent error of the radio's crystal, which shifts its
+    /// carrier (`None` for an exact carrier).
+    pub frequency_drift: Option<CrystalDrift>,
+    /// Sync word (network ID) the radio sends with and listens for.
+    pub sync_word: u8,
     /// Entity ID of the Graph entity (for routing transmissions).
     pub graph_entity: EntityId,
 }
@@ -402,6 +555,15 @@ impl Default for RadioConfig {
             },
             rx_to_tx_turnaround: SimTime::from_micros(100),
             tx_to_rx_turnaround: SimTime::from_micros(100),
+            preamble_symbols: AirtimeParams::DEFAULT_PREAMBLE_SYMBOLS,
+            bit_errors: None,
+            power: None,
+            derating: None,
+            regulatory: None,
+            advert_suppression: None,
+            adjacent_channel_rejection_db: channel::ADJACENT_CHANNEL_REJECTION_DB,
+            frequency_drift: None,
+            sync_word: mcsim_common::DEFAULT_SYNC_WORD,
             graph_entity: EntityId::new(0),
         }
     }
@@ -448,12 +610,40 @@ pub struct Radio {
     active_receptions: Vec<ActiveReception>,
     /// Counter for unique reception IDs.
     next_reception_id: u64,
+    /// Channel activity last reported to firmware.
+    channel_busy: bool,
+    /// Interference bursts currently reaching the radio.
+    active_interference: Vec<ActiveInterference>,
 
     // Metrics
     /// Labels for emitting metrics.
     metric_labels: MetricLabels,
     /// Time of last state change (for turnaround time tracking).
     last_state_change_time: SimTime,
+    /// TX requests whose reported airtime didn't match the computed one.
+    airtime_mismatches: u64,
+
+    // Power
+    /// Battery charge, for battery-powered nodes.
+    battery: Option<Battery>,
+    /// Battery voltage last reported to firmware.
+    reported_battery_mv: Option<u16>,
+    /// Time of the next battery update; earlier update timers are stale.
+    battery_update_at: SimTime,
+    /// The battery ran out or power was cut; the radio ignores all events
+    /// until power is restored.
+    powered_off: bool,
+    /// Recent TX duty cycle, for radios with TX power derating.
+    duty: Option<DutyTracker>,
+    /// Recent transmissions, for radios with regulatory limits.
+    compliance: Option<ComplianceTracker>,
+    /// Packet held back by the regulatory duty-cycle limit.
+    queued_tx: Option<LoraPacket>,
+    /// Channel utilization and suppressed adverts, for radios with an
+    /// advert suppression policy.
+    suppressor: Option<AdvertSuppressor>,
+    /// Packets the firmware had queued at its latest TX request.
+    tx_queue_depth: Option<usize>,
 }
 
 impl Radio {
@@ -465,6 +655,10 @@ impl Radio {
         attached_firmware: EntityId,
         metric_labels: MetricLabels,
     ) -> Self {
+        let battery = config.power.map(|power| Battery::new(power, config.params.tx_power_dbm));
+        let duty = config.derating.clone().map(DutyTracker::new);
+        let compliance = config.regulatory.clone().map(ComplianceTracker::new);
+        let suppressor = config.advert_suppression.clone().map(AdvertSuppressor::new);
         Radio {
             id,
             config,
@@ -476,8 +670,20 @@ impl Radio {
             current_tx: None,
             active_receptions: Vec::new(),
             next_reception_id: 0,
+            channel_busy: false,
+            active_interference: Vec::new(),
             metric_labels,
             last_state_change_time: SimTime::ZERO,
+            airtime_mismatches: 0,
+            battery,
+            reported_battery_mv: None,
+            battery_update_at: SimTime::ZERO,
+            powered_off: false,
+            duty,
+            compliance,
+            queued_tx: None,
+            suppressor,
+            tx_queue_depth: None,
         }
     }
 
@@ -540,6 +746,33 @@ impl Radio {
         self.state_version
     }
 
+    /// Get the battery, for battery-powered nodes.
+    pub fn battery(&self) -> Option<&Battery> {
+        self.battery.as_ref()
+    }
+
+    /// Get the TX duty tracker, for radios with TX power derating.
+    pub fn duty(&self) -> Option<&DutyTracker> {
+        self.duty.as_ref()
+    }
+
+    /// Get the regulatory compliance tracker, for radios with regulatory
+    /// limits.
+    pub fn compliance(&self) -> Option<&ComplianceTracker> {
+        self.compliance.as_ref()
+    }
+
+    /// Get the advert suppressor, for radios with an advert suppression
+    /// policy.
+    pub fn suppressor(&self) -> Option<&AdvertSuppressor> {
+        self.suppressor.as_ref()
+    }
+
+    /// Check if the battery has run out or power is cut.
+    pub fn is_powered_off(&self) -> bool {
+        self.powered_off
+    }
+
     /// Set the graph entity ID for routing transmissions.
     pub fn set_graph_entity(&mut self, graph_entity: EntityId) {
         self.config.graph_entity = graph_entity;
@@ -557,6 +790,36 @@ impl Radio {
         );
     }
 
+    /// Carrier frequency the radio is actually tuned to at `time`, including
+    /// crystal error.
+    pub fn tuned_frequency_hz(&self, time: SimTime) -> u32 {
+        let frequency_hz = self.config.params.frequency_hz;
+        match &self.config.frequency_drift {
+            Some(drift) => drift.apply_to_frequency(frequency_hz, time),
+            None => frequency_hz,
+        }
+    }
+
+    /// Report channel activity to firmware when it changes.
+    ///
+    /// The channel is busy while any reception in progress on the radio's
+    /// channel is strong enough to demodulate, which is what the firmware's
+    /// CAD query would detect.
+    fn update_channel_activity(&mut self, ctx: &mut SimContext) {
+        let threshold = calculate_snr_sensitivity(self.config.params.spreading_factor);
+        let busy = self.active_receptions.iter().any(|r| !r.adjacent_channel && r.snr_db >= threshold);
+        if busy != self.channel_busy {
+            self.channel_busy = busy;
+            if let Some(suppressor) = &mut self.suppressor {
+                suppressor.set_channel_busy(ctx.time(), busy);
+            }
+            ctx.post_immediate(
+                vec![self.attached_firmware],
+                EventPayload::ChannelActivity(mcsim_common::ChannelActivityEvent { busy }),
+            );
+        }
+    }
+
     /// Check for collisions among active receptions with capture effect.
     /// 
     /// When two packets collide in time, we check for capture effect:
@@ -590,6 +853,79 @@ impl Radio {
         }
     }
 
+    /// Current rise of the noise floor from interference, in dB. Bursts that
+    /// have ended are dropped.
+    fn current_noise_rise_db(&mut self, now: SimTime) -> f64 {
+        self.active_interference.retain(|i| i.end_time > now);
+        jammer::noise_rise_db(self.active_interference.iter().map(|i| i.interference_to_noise_db))
+    }
+
+    /// Handle interference routed from the Graph entity.
+    fn handle_receive_interference(&mut self, event: &mcsim_common::ReceiveInterferenceEvent, ctx: &mut SimContext) {
+        let frequency_hz = self.tuned_frequency_hz(ctx.time());
+        let params = &self.config.params;
+        if !channel::channels_overlap(frequency_hz, params.bandwidth_hz, event.frequency_hz, event.bandwidth_hz) {
+            return;
+        }
+        let tx_power_offset_db = event.tx_power_dbm as f64 - mcsim_common::REFERENCE_TX_POWER_DBM as f64;
+        let interference_to_noise_db = sample_gaussian(
+            ctx.rng_substream(substream::SNR),
+            event.mean_snr_db_at20dbm + tx_power_offset_db,
+            event.snr_std_dev,
+        ) - jammer::interference_rejection_db(event.kind, params.spreading_factor);
+        self.active_interference.push(ActiveInterference {
+            end_time: event.end_time,
+            interference_to_noise_db,
+        });
+
+        // Packets already being received lose SNR for the rest of the burst
+        let noise_rise_db = self.current_noise_rise_db(ctx.time());
+        for reception in &mut self.active_receptions {
+            reception.noise_rise_db = reception.noise_rise_db.max(noise_rise_db);
+        }
+    }
+
+    /// Check the airtime firmware reported for a TX against the computed one.
+    fn validate_reported_airtime(&mut self, packet: &LoraPacket, reported_ms: u32) {
+        let expected = AirtimeParams::from_radio_params(&self.config.params)
+            .with_preamble_symbols(self.config.preamble_symbols)
+            .time_on_air(packet.payload.len());
+        let Some(relative) = check_reported_airtime(reported_ms, expected) else {
+            return;
+        };
+        self.airtime_mismatches += 1;
+        let labels = self.metric_labels.to_labels();
+        metrics::counter!(metric_defs::RADIO_AIRTIME_MISMATCH.name, &labels).increment(1);
+
+        let params = &self.config.params;
+        let message = format!(
+            "[{}] Firmware reported {} ms airtime for a {}-byte packet, expected {:.1} ms \
+             ({:.0}% off) for SF{} BW{} CR4/{} with {} preamble symbols; \
+             check the firmware's radio configuration",
+            self.metric_labels.node,
+            reported_ms,
+            packet.payload.len(),
+            expected.as_micros() as f64 / 1000.0,
+            relative * 100.0,
+            params.spreading_factor,
+            params.bandwidth_hz,
+            params.coding_rate,
+            self.config.preamble_symbols,
+        );
+        // Warn once per radio; a misconfiguration affects every packet
+        if self.airtime_mismatches == 1 {
+            log::warn!("{}", message);
+        } else {
+            log::debug!("{}", message);
+        }
+    }
+
+    /// Get the number of TX requests whose reported airtime didn't match the
+    /// computed time on air.
+    pub fn airtime_mismatches(&self) -> u64 {
+        self.airtime_mismatches
+    }
+
     /// Handle TX request from firmware.
     fn handle_tx_request(&mut self, packet: LoraPacket, ctx: &mut SimContext) {
         match self.state {
@@ -623,10 +959,50 @@ impl Radio {
     fn start_transmission(&mut self, ctx: &mut SimContext) {
         if let Some(packet) = self.pending_tx.take() {
             // Calculate airtime
-            let airtime = calculate_time_on_air(&self.config.params, packet.payload.len());
+            let airtime = AirtimeParams::from_radio_params(&self.config.params)
+                .with_preamble_symbols(self.config.preamble_symbols)
+                .time_on_air(packet.payload.len());
             let airtime_us = airtime.as_micros() as u64;
             let end_time = ctx.time() + airtime;
             let packet_size = packet.payload.len();
+
+            // The suppression policy may skip the node's own adverts
+            if is_own_advert(&packet) {
+                let queue_depth = self.tx_queue_depth;
+                if let Some(observation) =
+                    self.suppressor.as_mut().and_then(|s| s.check(ctx.time(), queue_depth, airtime))
+                {
+                    self.record_suppressed_advert(airtime, observation, ctx);
+                    // The firmware sees its transmission end at once
+                    self.state = InternalRadioState::Receiving;
+                    self.notify_state_change(ctx, mcsim_common::RadioState::Receiving);
+                    return;
+                }
+            }
+
+            // Hold back or discard a transmission the band's rules forbid
+            match self.compliance.as_mut().map(|c| c.check(ctx.time(), airtime)) {
+                None | Some(Compliance::Transmit) => {}
+                Some(Compliance::TransmitViolating(violation)) => self.record_violation(violation, "transmitted"),
+                Some(Compliance::QueueUntil(violation, at)) => {
+                    self.record_violation(violation, "queued");
+                    self.queue_transmission(packet, at, ctx);
+                    return;
+                }
+                Some(Compliance::Drop(violation)) => {
+                    self.record_violation(violation, "dropped");
+                    // The firmware sees its transmission end at once
+                    self.state = InternalRadioState::Receiving;
+                    self.notify_state_change(ctx, mcsim_common::RadioState::Receiving);
+                    return;
+                }
+            }
+            if let Some(compliance) = &mut self.compliance {
+                compliance.record(ctx.time(), end_time);
+            }
+            if let Some(suppressor) = &mut self.suppressor {
+                suppressor.record_transmission(ctx.time(), end_time);
+            }
             
             // Build labels with packet breakdown
             // The recorder will filter to only the labels requested in metric specs
@@ -648,6 +1024,10 @@ impl Radio {
             }
             self.last_state_change_time = ctx.time();
             
+            // A radio that has been busy transmitting runs hot and backs
+            // off its TX power
+            let tx_power_dbm = self.derated_tx_power_dbm(ctx.time(), end_time);
+
             // Update state
             self.state = InternalRadioState::Transmitting;
             self.current_tx = Some((packet.clone(), end_time));
@@ -661,7 +1041,12 @@ impl Radio {
                 EventPayload::TransmitAir(mcsim_common::TransmitAirEvent {
                     radio_id: self.id,
                     packet,
-                    params: self.config.params.clone(),
+                    params: RadioParams {
+                        frequency_hz: self.tuned_frequency_hz(ctx.time()),
+                        tx_power_dbm,
+                        ..self.config.params.clone()
+                    },
+                    sync_word: self.config.sync_word,
                     end_time,
                 }),
             );
@@ -678,6 +1063,69 @@ impl Radio {
         }
     }
 
+    /// Count a transmission that broke a regulatory limit.
+    fn record_violation(&self, violation: Violation, action: &'static str) {
+        let mut labels = self.metric_labels.to_labels();
+        labels.push(("limit", violation.label().to_string()));
+        labels.push(("action", action.to_string()));
+        metrics::counter!(metric_defs::RADIO_DUTY_CYCLE_VIOLATIONS.name, &labels).increment(1);
+    }
+
+    /// Count an advert the suppression policy skipped and tell observers.
+    fn record_suppressed_advert(
+        &self,
+        airtime: SimTime,
+        observation: suppression::ChannelObservation,
+        ctx: &mut SimContext,
+    ) {
+        let Some(suppressor) = &self.suppressor else {
+            return;
+        };
+        let policy = suppressor.config().policy_name.clone();
+        let mut labels = self.metric_labels.to_labels();
+        labels.push(("policy", policy.clone()));
+        metrics::counter!(metric_defs::RADIO_ADVERTS_SUPPRESSED.name, &labels).increment(1);
+        metrics::counter!(metric_defs::RADIO_SUPPRESSED_AIRTIME.name, &labels).increment(airtime.as_micros());
+        ctx.post_immediate(
+            vec![self.id],
+            EventPayload::AdvertSuppressed(mcsim_common::AdvertSuppressedEvent {
+                radio_id: self.id,
+                airtime,
+                policy,
+                utilization: observation.utilization,
+                queue_depth: observation.queue_depth,
+            }),
+        );
+    }
+
+    /// Hold a packet back until `at`, receiving meanwhile. The firmware
+    /// still sees it as being sent.
+    fn queue_transmission(&mut self, packet: LoraPacket, at: SimTime, ctx: &mut SimContext) {
+        self.state = InternalRadioState::Receiving;
+        self.queued_tx = Some(packet);
+        ctx.post_event(at - ctx.time(), vec![self.id], EventPayload::Timer { timer_id: TIMER_TX_QUEUE_RELEASE });
+    }
+
+    /// TX power for a transmission from `now` until `end`, after derating for
+    /// the recent duty cycle.
+    fn derated_tx_power_dbm(&mut self, now: SimTime, end: SimTime) -> i8 {
+        let configured = self.config.params.tx_power_dbm;
+        let Some(duty) = &mut self.duty else {
+            return configured;
+        };
+        duty.advance(now);
+        let derating_db = duty.derating_db();
+        duty.start_tx(now, end);
+        let tx_power_dbm = (configured as f64 - derating_db).round().clamp(i8::MIN as f64, configured as f64) as i8;
+
+        let labels = self.metric_labels.to_labels();
+        metrics::gauge!(metric_defs::RADIO_TX_DERATING.name, &labels).set(derating_db);
+        if let Some(battery) = &mut self.battery {
+            battery.set_tx_power_dbm(tx_power_dbm);
+        }
+        tx_power_dbm
+    }
+
     /// Handle reception of a packet (from Graph entity via ReceiveAir).
     fn handle_receive_air(&mut self, rx_event: &mcsim_common::ReceiveAirEvent, ctx: &mut SimContext) {
         // Can only receive when in Receiving state
@@ -686,24 +1134,58 @@ impl Radio {
             return;
         }
 
+        // Packets on other channels are invisible, or only interfere if the
+        // channels partly overlap
+        let (rejection_db, adjacent_channel) = match channel::channel_relation(
+            self.tuned_frequency_hz(ctx.time()),
+            self.config.params.bandwidth_hz,
+            rx_event.params.frequency_hz,
+            rx_event.params.bandwidth_hz,
+            self.config.adjacent_channel_rejection_db,
+        ) {
+            channel::ChannelRelation::CoChannel => (0.0, false),
+            channel::ChannelRelation::Adjacent { rejection_db } => (rejection_db, true),
+            channel::ChannelRelation::Separate => return,
+        };
+
+        // A fault injection rule took the packet off this link
+        if rx_event.fault == Some(InjectedFault::Drop) {
+            let labels = self.metric_labels.to_labels();
+            metrics::counter!(metric_defs::RADIO_RX_FAULT_DROPPED.name, &labels).increment(1);
+            return;
+        }
+
         let reception_id = self.next_reception_id;
         self.next_reception_id += 1;
 
+        // Link values are specified at the reference TX power; scale them by the
+        // transmitting radio's actual power so each direction uses its own TX power.
+        let tx_power_offset_db = rx_event.params.tx_power_offset_db();
+
         // Sample the actual SNR from Gaussian distribution based on mean and std dev
         let snr_db = sample_gaussian(
-            ctx.rng(),
-            rx_event.mean_snr_db_at20dbm,
+            ctx.rng_substream(substream::SNR),
+            rx_event.mean_snr_db_at20dbm + tx_power_offset_db,
             rx_event.snr_std_dev,
         );
 
+        // Multipath fading scales the received power of this packet
+        let fading_gain_db = rx_event.fading.sample_gain_db(ctx.rng_substream(substream::FADING));
+        let snr_db = snr_db + fading_gain_db - rejection_db;
+
         let reception = ActiveReception {
             packet: rx_event.packet.clone(),
             source_radio_id: rx_event.source_radio_id,
             start_time: ctx.time(),
             end_time: rx_event.end_time,
             snr_db,
-            rssi_dbm: rx_event.rssi_dbm,
+            rssi_dbm: rx_event.rssi_dbm + tx_power_offset_db + fading_gain_db - rejection_db,
             collided: false,
+            adjacent_channel,
+            // The receiver locks onto the preamble but drops the packet at the sync word
+            foreign_network: !adjacent_channel && rx_event.sync_word != self.config.sync_word,
+            noise_rise_db: self.current_noise_rise_db(ctx.time()),
+            injected_corruption: rx_event.fault == Some(InjectedFault::Corrupt),
             reception_id,
         };
 
@@ -715,6 +1197,7 @@ impl Radio {
 
         // Check for collisions with existing receptions (including capture effect)
         self.check_collisions_with_capture();
+        self.update_channel_activity(ctx);
 
         // Schedule reception completion
         let delay = rx_event.end_time - ctx.time();
@@ -734,12 +1217,24 @@ impl Radio {
             .position(|r| r.reception_id == reception_id);
 
         if let Some(idx) = idx {
-            let reception = self.active_receptions.remove(idx);
+            let mut reception = self.active_receptions.remove(idx);
+            self.update_channel_activity(ctx);
             
             // Base labels without packet type (for active_receptions gauge)
             let base_labels = self.metric_labels.to_labels();
             metrics::gauge!(metric_defs::RADIO_ACTIVE_RECEPTIONS.name, &base_labels).decrement(1.0);
 
+            // Packets on other channels were only interference
+            if reception.adjacent_channel {
+                return;
+            }
+
+            // Packets from another network are dropped, however strong
+            if reception.foreign_network {
+                metrics::counter!(metric_defs::RADIO_RX_FOREIGN_NETWORK.name, &base_labels).increment(1);
+                return;
+            }
+
             // Build labels with packet breakdown
             // The recorder will filter to only the labels requested in metric specs
             let mut labels = self.metric_labels.to_labels();
@@ -747,6 +1242,13 @@ impl Radio {
             labels.push(("route_type", reception.packet.route_type_label().to_string()));
             labels.push(("payload_hash", reception.packet.payload_hash_label()));
 
+            // Interference raised the noise floor under the packet
+            let interfered = reception.noise_rise_db > 0.0;
+            reception.snr_db -= reception.noise_rise_db;
+            if interfered {
+                metrics::counter!(metric_defs::RADIO_RX_INTERFERED.name, &labels).increment(1);
+            }
+
             // Final collision check
             let survived = !reception.collided;
             
@@ -754,6 +1256,22 @@ impl Radio {
             let snr_threshold = calculate_snr_sensitivity(self.config.params.spreading_factor);
             let snr_ok = reception.snr_db >= snr_threshold;
 
+            // Marginal packets may be delivered with bit errors instead of dropped
+            let bit_error_rate = if survived && !snr_ok {
+                self.config.bit_errors
+                    .and_then(|cfg| cfg.bit_error_rate(reception.snr_db, snr_threshold))
+            } else {
+                None
+            };
+            // A fault injection rule flips one bit of a packet that would have been decoded
+            let bit_error_rate = bit_error_rate.or((reception.injected_corruption && survived && snr_ok).then_some(0.0));
+            let corrupted = bit_error_rate.is_some();
+            if let Some(ber) = bit_error_rate {
+                let mut payload = reception.packet.payload.clone();
+                corrupt_payload(ctx.rng_substream(substream::BIT_ERRORS), &mut payload, ber);
+                reception.packet = LoraPacket::new(payload);
+            }
+
             // Calculate airtime for metrics
             let airtime = reception.end_time - reception.start_time;
             let airtime_us = airtime.as_micros() as u64;
@@ -768,13 +1286,14 @@ impl Radio {
                     snr_db: reception.snr_db,
                     rssi_dbm: reception.rssi_dbm,
                     was_collided: !survived,
-                    was_weak_signal: !snr_ok,
+                    was_weak_signal: !snr_ok && !corrupted,
+                    was_corrupted: corrupted,
                     start_time: reception.start_time,
                     end_time: reception.end_time,
                 }),
             );
 
-            if survived && snr_ok {
+            if survived && snr_ok && !corrupted {
                 // Record RX success metrics
                 metrics::counter!(metric_defs::RADIO_RX_PACKETS.name, &labels).increment(1);
                 metrics::counter!(metric_defs::RADIO_RX_AIRTIME.name, &labels).increment(airtime_us);
@@ -784,6 +1303,9 @@ impl Radio {
             } else if !survived {
                 // Packet lost due to collision
                 metrics::counter!(metric_defs::RADIO_RX_COLLIDED.name, &labels).increment(1);
+            } else if corrupted {
+                // Packet delivered with bit errors (marginal SNR or injected fault)
+                metrics::counter!(metric_defs::RADIO_RX_CORRUPTED.name, &labels).increment(1);
             } else {
                 // Packet lost due to weak signal (SNR below threshold)
                 metrics::counter!(metric_defs::RADIO_RX_WEAK.name, &labels).increment(1);
@@ -792,6 +1314,126 @@ impl Radio {
     }
 }
 
+impl Radio {
+    /// Power state implied by the radio state machine.
+    fn power_state(&self) -> PowerState {
+        match self.state {
+            InternalRadioState::Receiving if self.active_receptions.is_empty() => PowerState::Idle,
+            InternalRadioState::Receiving => PowerState::Rx,
+            InternalRadioState::TxTurnaround | InternalRadioState::Transmitting => PowerState::Tx,
+        }
+    }
+
+    /// Draw charge for the time since the last event.
+    fn account_power(&mut self, now: SimTime) {
+        if let Some(battery) = &mut self.battery {
+            let state = battery.state();
+            let elapsed = battery.advance(now);
+            if elapsed > SimTime::ZERO {
+                let mut labels = self.metric_labels.to_labels();
+                labels.push(("state", state.label().to_string()));
+                metrics::counter!(metric_defs::POWER_STATE_TIME.name, &labels)
+                    .increment(elapsed.as_micros());
+            }
+        }
+    }
+
+    /// Record the power state after handling an event.
+    fn update_power_state(&mut self, now: SimTime) {
+        let state = self.power_state();
+        if let Some(battery) = &mut self.battery {
+            battery.set_state(now, state);
+        }
+    }
+
+    /// Publish battery metrics, tell firmware if the voltage changed, and
+    /// re-arm the update timer.
+    fn report_battery(&mut self, ctx: &mut SimContext) {
+        let Some(battery) = &self.battery else {
+            return;
+        };
+        let labels = self.metric_labels.to_labels();
+        metrics::gauge!(metric_defs::POWER_BATTERY_LEVEL.name, &labels).set(battery.level());
+        metrics::gauge!(metric_defs::POWER_CHARGE_USED.name, &labels).set(battery.used_mah());
+
+        let millivolts = battery.millivolts();
+        // Wake up when the battery would run out at the current draw
+        let next_update = battery
+            .time_to_depletion()
+            .map_or(BATTERY_UPDATE_INTERVAL, |t| t.min(BATTERY_UPDATE_INTERVAL));
+
+        if self.reported_battery_mv != Some(millivolts) {
+            self.reported_battery_mv = Some(millivolts);
+            ctx.post_immediate(
+                vec![self.attached_firmware],
+                EventPayload::BatteryLevel(mcsim_common::BatteryLevelEvent { millivolts, depleted: false }),
+            );
+        }
+        self.battery_update_at = ctx.time() + next_update;
+        ctx.post_event(
+            next_update,
+            vec![self.id],
+            EventPayload::Timer { timer_id: BATTERY_UPDATE_TIMER_ID },
+        );
+    }
+
+    /// Stop transmitting and receiving until power is restored.
+    fn shut_down(&mut self) {
+        self.powered_off = true;
+        self.pending_tx = None;
+        self.queued_tx = None;
+        if !self.active_receptions.is_empty() {
+            let labels = self.metric_labels.to_labels();
+            metrics::gauge!(metric_defs::RADIO_ACTIVE_RECEPTIONS.name, &labels)
+                .decrement(self.active_receptions.len() as f64);
+            self.active_receptions.clear();
+        }
+    }
+
+    /// Power was restored: resume receiving, power the firmware back on and
+    /// resume battery reporting. Nothing is drawn from the battery while off.
+    fn power_on(&mut self, power_on: &mcsim_common::PowerOnEvent, ctx: &mut SimContext) {
+        self.powered_off = false;
+        self.state = InternalRadioState::Receiving;
+        self.current_tx = None;
+        if let Some(battery) = &mut self.battery {
+            battery.set_state(ctx.time(), PowerState::Idle);
+        }
+        ctx.post_immediate(vec![self.attached_firmware], EventPayload::PowerOn(power_on.clone()));
+        self.reported_battery_mv = None;
+        self.report_battery(ctx);
+    }
+
+    /// The node restarts: drop the transmission and receptions in progress,
+    /// go back to receiving and reboot the firmware.
+    fn reboot(&mut self, reboot: &mcsim_common::RebootEvent, ctx: &mut SimContext) {
+        self.shut_down();
+        self.powered_off = false;
+        self.state = InternalRadioState::Receiving;
+        self.current_tx = None;
+        ctx.post_immediate(vec![self.attached_firmware], EventPayload::Reboot(reboot.clone()));
+    }
+
+    /// The battery ran out: stop transmitting and receiving, and power off
+    /// the firmware.
+    fn power_off(&mut self, ctx: &mut SimContext) {
+        self.shut_down();
+        let labels = self.metric_labels.to_labels();
+        if let Some(battery) = &self.battery {
+            metrics::gauge!(metric_defs::POWER_BATTERY_LEVEL.name, &labels).set(0.0);
+            metrics::gauge!(metric_defs::POWER_CHARGE_USED.name, &labels).set(battery.used_mah());
+        }
+        metrics::counter!(metric_defs::POWER_DEPLETED.name, &labels).increment(1);
+        ctx.post_immediate(
+            vec![self.attached_firmware],
+            EventPayload::BatteryLevel(mcsim_common::BatteryLevelEvent {
+                millivolts: BATTERY_EMPTY_MV,
+                depleted: true,
+            }),
+        );
+    }
+}
+
 /// Create a new radio entity (legacy API).
 pub fn create_radio(
     id: EntityId,
@@ -808,18 +1450,60 @@ impl Entity for Radio {
     }
 
     fn handle_event(&mut self, event: &Event, ctx: &mut SimContext) -> Result<(), SimError> {
+        // A depleted battery keeps the radio off even when mains power returns
+        if let EventPayload::PowerOn(power_on) = &event.payload {
+            if self.powered_off && !self.battery.as_ref().is_some_and(Battery::is_depleted) {
+                self.power_on(power_on, ctx);
+            }
+            return Ok(());
+        }
+        if self.powered_off {
+            return Ok(());
+        }
+        self.account_power(ctx.time());
+        if self.battery.as_ref().is_some_and(Battery::is_depleted) {
+            self.power_off(ctx);
+            return Ok(());
+        }
+
         match &event.payload {
+            EventPayload::PowerOff(power_off) => {
+                self.shut_down();
+                ctx.post_immediate(vec![self.attached_firmware], EventPayload::PowerOff(power_off.clone()));
+                return Ok(());
+            }
+            EventPayload::Reboot(reboot) => {
+                self.reboot(reboot, ctx);
+            }
             EventPayload::RadioTxRequest(tx_request) => {
                 // Firmware requests transmission
+                if let Some(reported_ms) = tx_request.reported_airtime_ms {
+                    self.validate_reported_airtime(&tx_request.packet, reported_ms);
+                }
+                self.tx_queue_depth = tx_request.queue_depth;
                 self.handle_tx_request(tx_request.packet.clone(), ctx);
             }
             EventPayload::ReceiveAir(rx_air_event) => {
                 // Handle incoming transmission routed from Graph entity
                 self.handle_receive_air(rx_air_event, ctx);
             }
+            EventPayload::ReceiveInterference(interference) => {
+                // Interference routed from Graph entity
+                self.handle_receive_interference(interference, ctx);
+            }
             EventPayload::Timer { timer_id } => {
                 // Handle internal timers
-                if *timer_id == TIMER_TX_TURNAROUND_COMPLETE {
+                if *timer_id == BATTERY_UPDATE_TIMER_ID {
+                    // Updates scheduled before an outage are superseded
+                    if ctx.time() >= self.battery_update_at {
+                        self.report_battery(ctx);
+                    }
+                } else if *timer_id == TIMER_TX_QUEUE_RELEASE {
+                    // The duty cycle allows the held-back packet now
+                    if let Some(packet) = self.queued_tx.take() {
+                        self.handle_tx_request(packet, ctx);
+                    }
+                } else if *timer_id == TIMER_TX_TURNAROUND_COMPLETE {
                     // TX turnaround complete - start actual transmission
                     self.start_transmission(ctx);
                 } else if *timer_id == TIMER_RX_TURNAROUND_COMPLETE {
@@ -849,6 +1533,7 @@ impl Entity for Radio {
                 // Ignore other events
             }
         }
+        self.update_power_state(ctx.time());
         Ok(())
     }
 }
@@ -889,16 +1574,86 @@ pub fn create_link_model() -> LinkModel {
 /// 
 /// It receives TransmitAir events from Radio entities and routes them
 /// to appropriate receivers based on the LinkModel, sending ReceiveAir
-/// events with SNR/RSSI from the link parameters.
+/// events with SNR/RSSI from the link parameters. Interference from
+/// [`jammer::Jammer`] entities is routed the same way.
 pub struct Graph {
     id: EntityId,
     link_model: LinkModel,
+    mobility: Option<mobility::Mobility>,
+    faults: faults::PacketFaults,
+    /// Entities sent a [`EventPayload::PositionFix`] when a radio moves, by radio.
+    gps_receivers: BTreeMap<EntityId, Vec<EntityId>>,
+    /// Position last sent to each radio's GPS receivers.
+    last_fix: BTreeMap<EntityId, GeoCoord>,
 }
 
+/// Timer ID of the Graph's periodic mobility update.
+pub const TIMER_MOBILITY_UPDATE: u64 = 1;
+
 impl Graph {
     /// Create a new Graph entity with the given link model.
     pub fn new(id: EntityId, link_model: LinkModel) -> Self {
-        Graph { id, link_model }
+        Graph {
+            id,
+            link_model,
+            mobility: None,
+            faults: faults::PacketFaults::default(),
+            gps_receivers: BTreeMap::new(),
+            last_fix: BTreeMap::new(),
+        }
+    }
+
+    /// Move nodes during the simulation, recomputing their links on each
+    /// [`TIMER_MOBILITY_UPDATE`] timer. The first timer must be scheduled by
+    /// the caller; later ones are scheduled by the Graph. Without mobility,
+    /// [`EventPayload::MoveNode`] events are ignored.
+    pub fn with_mobility(mut self, mobility: mobility::Mobility) -> Self {
+        self.mobility = Some(mobility);
+        self
+    }
+
+    /// Drop or corrupt packets on links according to fault injection rules.
+    pub fn with_faults(mut self, faults: faults::PacketFaults) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Send the entities listening to a radio's GPS an
+    /// [`EventPayload::PositionFix`] whenever the radio has moved, after each
+    /// mobility update or [`EventPayload::MoveNode`].
+    pub fn with_gps_receivers(mut self, receivers: BTreeMap<EntityId, Vec<EntityId>>) -> Self {
+        self.gps_receivers = receivers;
+        self
+    }
+
+    /// Send a position fix to the GPS receivers of each radio that moved
+    /// since its last fix.
+    fn send_position_fixes(&mut self, ctx: &mut SimContext) {
+        let Some(mobility) = &self.mobility else {
+            return;
+        };
+        for (radio, receivers) in &self.gps_receivers {
+            let Some(&position) = mobility.position(*radio) else {
+                continue;
+            };
+            if self.last_fix.insert(*radio, position) != Some(position) {
+                ctx.post_immediate(
+                    receivers.clone(),
+                    EventPayload::PositionFix(mcsim_common::PositionFixEvent { position }),
+                );
+            }
+        }
+    }
+
+    /// Get the fault injection rules and how many packets each has matched.
+    pub fn faults(&self) -> &faults::PacketFaults {
+        &self.faults
+    }
+
+    /// Get the mobility state, which also places nodes moved by
+    /// [`EventPayload::MoveNode`].
+    pub fn mobility(&self) -> Option<&mobility::Mobility> {
+        self.mobility.as_ref()
     }
 
     /// Get a mutable reference to the link model for runtime modification.
@@ -917,25 +1672,79 @@ impl Entity for Graph {
         self.id
     }
 
+    fn links(&self) -> Option<Vec<mcsim_common::LinkQuality>> {
+        Some(
+            self.link_model
+                .edges
+                .iter()
+                .map(|(&(from, to), params)| mcsim_common::LinkQuality {
+                    from,
+                    to,
+                    mean_snr_db_at20dbm: params.mean_snr_db_at20dbm,
+                    rssi_dbm: params.rssi_dbm,
+                })
+                .collect(),
+        )
+    }
+
     fn handle_event(&mut self, event: &Event, ctx: &mut SimContext) -> Result<(), SimError> {
         match &event.payload {
             EventPayload::TransmitAir(tx_event) => {
                 // Route to all receivers in range
                 for (receiver_id, link_params) in self.link_model.get_receivers(tx_event.radio_id) {
+                    let fault = self.faults.apply(tx_event.radio_id, receiver_id, ctx.time(), ctx.rng_substream(substream::FAULTS));
                     ctx.post_immediate(
                         vec![receiver_id],
                         EventPayload::ReceiveAir(mcsim_common::ReceiveAirEvent {
                             source_radio_id: tx_event.radio_id,
                             packet: tx_event.packet.clone(),
                             params: tx_event.params.clone(),
+                            sync_word: tx_event.sync_word,
                             end_time: tx_event.end_time,
                             mean_snr_db_at20dbm: link_params.mean_snr_db_at20dbm,
                             snr_std_dev: link_params.snr_std_dev,
                             rssi_dbm: link_params.rssi_dbm,
+                            fading: link_params.fading,
+                            fault,
                         }),
                     );
                 }
             }
+            EventPayload::TransmitInterference(tx_event) => {
+                for (receiver_id, link_params) in self.link_model.get_receivers(tx_event.source_id) {
+                    ctx.post_immediate(
+                        vec![receiver_id],
+                        EventPayload::ReceiveInterference(mcsim_common::ReceiveInterferenceEvent {
+                            source_id: tx_event.source_id,
+                            kind: tx_event.kind,
+                            frequency_hz: tx_event.frequency_hz,
+                            bandwidth_hz: tx_event.bandwidth_hz,
+                            tx_power_dbm: tx_event.tx_power_dbm,
+                            end_time: tx_event.end_time,
+                            mean_snr_db_at20dbm: link_params.mean_snr_db_at20dbm,
+                            snr_std_dev: link_params.snr_std_dev,
+                        }),
+                    );
+                }
+            }
+            EventPayload::Timer { timer_id: TIMER_MOBILITY_UPDATE } => {
+                if let Some(mobility) = &mut self.mobility {
+                    let now_s = ctx.time().as_secs_f64();
+                    mobility.update(now_s, &mut self.link_model, ctx.rng_substream(substream::MOBILITY));
+                    ctx.post_event(
+                        SimTime::from_secs(mobility.update_interval_s()),
+                        vec![self.id],
+                        EventPayload::Timer { timer_id: TIMER_MOBILITY_UPDATE },
+                    );
+                }
+                self.send_position_fixes(ctx);
+            }
+            EventPayload::MoveNode(move_event) => {
+                if let Some(mobility) = &mut self.mobility {
+                    mobility.place(move_event.radio_id, move_event.position, &mut self.link_model);
+                }
+                self.send_position_fixes(ctx);
+            }
             _ => {}
         }
         Ok(())
@@ -955,6 +1764,23 @@ mod tests {
         assert!(toa.as_millis() < 5000);
     }
 
+    #[test]
+    fn test_check_reported_airtime() {
+        let params = RadioParams::default_meshcore();
+        let toa = calculate_time_on_air(&params, 50);
+        let toa_ms = toa.as_millis() as u32;
+
+        // Whole-millisecond rounding is within tolerance
+        assert_eq!(check_reported_airtime(toa_ms, toa), None);
+        assert_eq!(check_reported_airtime(toa_ms + 1, toa), None);
+
+        // Airtime for a different spreading factor is not
+        let other = RadioParams { spreading_factor: params.spreading_factor - 1, ..params.clone() };
+        let other_ms = calculate_time_on_air(&other, 50).as_millis() as u32;
+        let relative = check_reported_airtime(other_ms, toa).unwrap();
+        assert!(relative > 0.4 && relative < 0.6);
+    }
+
     #[test]
     fn test_snr_sensitivity_thresholds() {
         // Verify SF sensitivity thresholds are reasonable
@@ -967,6 +1793,32 @@ mod tests {
         assert!(sf7_sens > sf12_sens, "SF12 should be more sensitive than SF7");
     }
 
+    #[test]
+    fn test_bit_error_window() {
+        let cfg = BitErrorConfig { window_db: 2.0, max_bit_error_rate: 0.01 };
+        assert_eq!(cfg.bit_error_rate(-7.0, -7.5), None);
+        assert_eq!(cfg.bit_error_rate(-10.0, -7.5), None);
+        let ber = cfg.bit_error_rate(-8.5, -7.5).unwrap();
+        assert!((ber - 0.005).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_corrupt_payload_flips_at_least_one_bit() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let original = vec![0u8; 32];
+        let mut payload = original.clone();
+        let flipped = corrupt_payload(&mut rng, &mut payload, 0.0);
+        assert_eq!(flipped, 1);
+        let diff: u32 = original.iter().zip(&payload).map(|(a, b)| (a ^ b).count_ones()).sum();
+        assert_eq!(diff, 1);
+
+        let mut empty: Vec<u8> = Vec::new();
+        assert_eq!(corrupt_payload(&mut rng, &mut empty, 1.0), 0);
+    }
+
     #[test]
     fn test_gaussian_sampling() {
         // Test that Gaussian sampling produces values around the mean
@@ -991,12 +1843,14 @@ mod tests {
             start_time: SimTime::from_millis(1000),
             end_time: SimTime::from_millis(1500),
             frequency_hz: 906_000_000,
+            bandwidth_hz: 62_500,
             packet_id: 1,
         };
         let existing = vec![CollisionContext {
             start_time: SimTime::from_millis(0),
             end_time: SimTime::from_millis(500),
             frequency_hz: 906_000_000,
+            bandwidth_hz: 62_500,
             packet_id: 0,
         }];
 
@@ -1009,15 +1863,672 @@ mod tests {
             start_time: SimTime::from_millis(400),
             end_time: SimTime::from_millis(900),
             frequency_hz: 906_000_000,
+            bandwidth_hz: 62_500,
             packet_id: 1,
         };
         let existing = vec![CollisionContext {
             start_time: SimTime::from_millis(0),
             end_time: SimTime::from_millis(500),
             frequency_hz: 906_000_000,
+            bandwidth_hz: 62_500,
             packet_id: 0,
         }];
 
         assert_eq!(check_collision(&incoming, &existing), CollisionResult::BothDestroyed(0));
     }
+
+    #[test]
+    fn test_battery_depletion_powers_off_radio() {
+        let firmware = EntityId::new(2);
+        let config = RadioConfig {
+            power: Some(PowerConfig {
+                battery_capacity_mah: 0.001,
+                idle_current_ma: 5.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            config,
+            GeoCoord::new(47.0, -122.0),
+            firmware,
+            MetricLabels::new("node", "repeater"),
+        );
+        let mut ctx = SimContext::new(1);
+        let timer = |time: SimTime| Event {
+            id: mcsim_common::EventId(0),
+            time,
+            source: EntityId::new(1),
+            targets: vec![EntityId::new(1)],
+            payload: EventPayload::Timer { timer_id: BATTERY_UPDATE_TIMER_ID },
+        };
+
+        // Full battery reported, next update when it would run out (0.72 s idle)
+        radio.handle_event(&timer(SimTime::ZERO), &mut ctx).unwrap();
+        let events = ctx.take_pending_events();
+        assert!(matches!(
+            &events[0].payload,
+            EventPayload::BatteryLevel(b) if b.millivolts == power::BATTERY_FULL_MV && !b.depleted
+        ));
+        assert_eq!(events[1].time, SimTime::from_micros(720_000));
+
+        ctx.set_time(events[1].time);
+        radio.handle_event(&timer(events[1].time), &mut ctx).unwrap();
+        assert!(radio.is_powered_off());
+        let events = ctx.take_pending_events();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0].payload, EventPayload::BatteryLevel(b) if b.depleted));
+
+        // A dead radio no longer transmits
+        let tx = Event {
+            payload: EventPayload::RadioTxRequest(mcsim_common::RadioTxRequestEvent {
+                packet: LoraPacket::new(vec![1, 2, 3]),
+                reported_airtime_ms: None,
+                queue_depth: None,
+            }),
+            ..timer(events[0].time)
+        };
+        radio.handle_event(&tx, &mut ctx).unwrap();
+        assert!(ctx.take_pending_events().is_empty());
+    }
+
+    #[test]
+    fn test_power_restored_after_outage() {
+        let firmware = EntityId::new(2);
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            RadioConfig::default(),
+            GeoCoord::new(47.0, -122.0),
+            firmware,
+            MetricLabels::new("node", "repeater"),
+        );
+        let mut ctx = SimContext::new(1);
+        let event = |time: SimTime, payload: EventPayload| Event {
+            id: mcsim_common::EventId(0),
+            time,
+            source: EntityId::new(1),
+            targets: vec![EntityId::new(1)],
+            payload,
+        };
+        let reason = "outage storm".to_string();
+
+        let off = EventPayload::PowerOff(mcsim_common::PowerOffEvent { reason: reason.clone() });
+        radio.handle_event(&event(SimTime::ZERO, off), &mut ctx).unwrap();
+        assert!(radio.is_powered_off());
+        ctx.take_pending_events();
+
+        ctx.set_time(SimTime::from_secs(60.0));
+        let on = EventPayload::PowerOn(mcsim_common::PowerOnEvent { reason });
+        radio.handle_event(&event(ctx.time(), on), &mut ctx).unwrap();
+        assert!(!radio.is_powered_off());
+        let events = ctx.take_pending_events();
+        assert!(matches!(&events[0].payload, EventPayload::PowerOn(_)));
+        assert_eq!(events[0].targets, vec![firmware]);
+    }
+
+    #[test]
+    fn test_reboot_drops_pending_transmission() {
+        let firmware = EntityId::new(2);
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            RadioConfig::default(),
+            GeoCoord::new(47.0, -122.0),
+            firmware,
+            MetricLabels::new("node", "repeater"),
+        );
+        let mut ctx = SimContext::new(1);
+        let event = |time: SimTime, payload: EventPayload| Event {
+            id: mcsim_common::EventId(0),
+            time,
+            source: EntityId::new(1),
+            targets: vec![EntityId::new(1)],
+            payload,
+        };
+
+        let request = EventPayload::RadioTxRequest(mcsim_common::RadioTxRequestEvent {
+            packet: LoraPacket::new(vec![1, 2, 3]),
+            reported_airtime_ms: None,
+            queue_depth: None,
+        });
+        radio.handle_event(&event(SimTime::ZERO, request), &mut ctx).unwrap();
+        assert!(radio.is_transmitting());
+        let turnaround = ctx
+            .take_pending_events()
+            .into_iter()
+            .find(|e| matches!(e.payload, EventPayload::Timer { .. }))
+            .unwrap();
+
+        let reboot = EventPayload::Reboot(mcsim_common::RebootEvent { reason: "scheduled".to_string() });
+        radio.handle_event(&event(SimTime::ZERO, reboot), &mut ctx).unwrap();
+        assert!(!radio.is_transmitting() && !radio.is_powered_off());
+        let events = ctx.take_pending_events();
+        assert!(matches!(&events[0].payload, EventPayload::Reboot(_)));
+        assert_eq!(events[0].targets, vec![firmware]);
+
+        // The packet queued before the reboot is never sent
+        ctx.set_time(turnaround.time);
+        radio.handle_event(&turnaround, &mut ctx).unwrap();
+        assert!(!ctx.take_pending_events().iter().any(|e| matches!(e.payload, EventPayload::TransmitAir(_))));
+    }
+
+    #[test]
+    fn test_sustained_tx_derates_power() {
+        let config = RadioConfig {
+            derating: Some(DeratingConfig {
+                time_constant: SimTime::from_secs(10.0),
+                curve: vec![(0.1, 0.0), (0.5, 6.0)],
+            }),
+            ..Default::default()
+        };
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            config,
+            GeoCoord::new(47.0, -122.0),
+            EntityId::new(2),
+            MetricLabels::new("node", "repeater"),
+        );
+        let mut ctx = SimContext::new(1);
+        let event = |time: SimTime, payload: EventPayload| Event {
+            id: mcsim_common::EventId(0),
+            time,
+            source: EntityId::new(1),
+            targets: vec![EntityId::new(1)],
+            payload,
+        };
+
+        // Transmit back to back, following each timer the radio sets
+        let mut tx_powers = Vec::new();
+        for _ in 0..40 {
+            let request = EventPayload::RadioTxRequest(mcsim_common::RadioTxRequestEvent {
+                packet: LoraPacket::new(vec![0; 100]),
+                reported_airtime_ms: None,
+                queue_depth: None,
+            });
+            radio.handle_event(&event(ctx.time(), request), &mut ctx).unwrap();
+            for _ in 0..2 {
+                let events = ctx.take_pending_events();
+                for e in &events {
+                    if let EventPayload::TransmitAir(tx) = &e.payload {
+                        tx_powers.push(tx.params.tx_power_dbm);
+                    }
+                }
+                let timer = events.into_iter().find(|e| matches!(e.payload, EventPayload::Timer { .. })).unwrap();
+                ctx.set_time(timer.time);
+                radio.handle_event(&timer, &mut ctx).unwrap();
+            }
+            for e in ctx.take_pending_events() {
+                if let EventPayload::TransmitAir(tx) = &e.payload {
+                    tx_powers.push(tx.params.tx_power_dbm);
+                }
+            }
+        }
+
+        // A cold radio starts at full power and backs off as it heats up
+        assert_eq!(tx_powers.len(), 40);
+        assert_eq!(tx_powers[0], 20);
+        assert!(tx_powers.windows(2).all(|w| w[1] <= w[0]));
+        assert_eq!(*tx_powers.last().unwrap(), 14);
+
+        // It cools down again when idle
+        ctx.set_time(ctx.time() + SimTime::from_secs(60.0));
+        let duty = radio.duty().unwrap().clone();
+        let mut cooled = duty.clone();
+        cooled.advance(ctx.time());
+        assert!(cooled.duty() < 0.01 && duty.duty() > 0.5);
+    }
+
+    #[test]
+    fn test_duty_cycle_limit_enforcement() {
+        use regulatory::Enforcement;
+
+        let airtime = AirtimeParams::from_radio_params(&RadioConfig::default().params).time_on_air(100);
+        // Only one packet fits the budget of a 10 s window
+        let transmit_twice = |enforcement: Enforcement| {
+            let config = RadioConfig {
+                regulatory: Some(RegulatoryConfig {
+                    duty_cycle_limit: Some(1.5 * airtime.as_secs_f64() / 10.0),
+                    window: SimTime::from_secs(10.0),
+                    max_dwell: None,
+                    enforcement,
+                }),
+                ..Default::default()
+            };
+            let mut radio = Radio::new(
+                EntityId::new(1),
+                config,
+                GeoCoord::new(47.0, -122.0),
+                EntityId::new(2),
+                MetricLabels::new("node", "repeater"),
+            );
+            let mut ctx = SimContext::new(1);
+            let mut tx_times = Vec::new();
+            let mut tx_complete = 0;
+            for _ in 0..2 {
+                let mut events = vec![Event {
+                    id: mcsim_common::EventId(0),
+                    time: ctx.time(),
+                    source: EntityId::new(2),
+                    targets: vec![EntityId::new(1)],
+                    payload: EventPayload::RadioTxRequest(mcsim_common::RadioTxRequestEvent {
+                        packet: LoraPacket::new(vec![0; 100]),
+                        reported_airtime_ms: None,
+                        queue_depth: None,
+                    }),
+                }];
+                // Follow the radio's own events until the transmission ends
+                while let Some(event) = events.pop() {
+                    ctx.set_time(event.time);
+                    radio.handle_event(&event, &mut ctx).unwrap();
+                    for e in ctx.take_pending_events() {
+                        match &e.payload {
+                            EventPayload::TransmitAir(_) => tx_times.push(e.time),
+                            EventPayload::RadioStateChanged(state)
+                                if state.new_state == mcsim_common::RadioState::Receiving =>
+                            {
+                                tx_complete += 1
+                            }
+                            EventPayload::Timer { .. } => events.push(e),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            (tx_times, tx_complete, radio.compliance().unwrap().violations())
+        };
+
+        let (tx_times, tx_complete, violations) = transmit_twice(Enforcement::Record);
+        assert_eq!((tx_times.len(), tx_complete, violations), (2, 2, 1));
+
+        // The second packet waits until the first has left the window
+        let (tx_times, tx_complete, violations) = transmit_twice(Enforcement::Queue);
+        assert_eq!((tx_times.len(), tx_complete, violations), (2, 2, 1));
+        assert!(tx_times[1] - tx_times[0] >= SimTime::from_secs(10.0) - airtime);
+
+        // The firmware sees a dropped packet's transmission end at once
+        let (tx_times, tx_complete, violations) = transmit_twice(Enforcement::Drop);
+        assert_eq!((tx_times.len(), tx_complete, violations), (1, 2, 1));
+    }
+
+    #[test]
+    fn test_advert_suppression() {
+        use meshcore_packet::{AdvertPayload, MeshCorePacket};
+        use suppression::{AdvertSuppression, QueueDepthThreshold};
+
+        let config = RadioConfig {
+            advert_suppression: Some(AdvertSuppression {
+                policy_name: "queue_depth".to_string(),
+                policy: std::sync::Arc::new(QueueDepthThreshold { max_queue_depth: 2 }),
+                window: suppression::DEFAULT_UTILIZATION_WINDOW,
+                max_consecutive: 1,
+            }),
+            ..Default::default()
+        };
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            config,
+            GeoCoord::new(47.0, -122.0),
+            EntityId::new(2),
+            MetricLabels::new("node", "repeater"),
+        );
+        let mut advert = MeshCorePacket::advert(AdvertPayload::new([0u8; 32], 1, [0u8; 64], "node"));
+        let own = advert.encode();
+        advert.path = vec![0x12];
+        let relayed = advert.encode();
+
+        let mut ctx = SimContext::new(1);
+        let mut send = |payload: &[u8], queue_depth: usize| {
+            let mut events = vec![Event {
+                id: mcsim_common::EventId(0),
+                time: ctx.time(),
+                source: EntityId::new(2),
+                targets: vec![EntityId::new(1)],
+                payload: EventPayload::RadioTxRequest(mcsim_common::RadioTxRequestEvent {
+                    packet: LoraPacket::new(payload.to_vec()),
+                    reported_airtime_ms: None,
+                    queue_depth: Some(queue_depth),
+                }),
+            }];
+            let (mut transmitted, mut suppressed) = (false, false);
+            while let Some(event) = events.pop() {
+                ctx.set_time(event.time);
+                radio.handle_event(&event, &mut ctx).unwrap();
+                for e in ctx.take_pending_events() {
+                    match &e.payload {
+                        EventPayload::TransmitAir(_) => transmitted = true,
+                        EventPayload::AdvertSuppressed(_) => suppressed = true,
+                        EventPayload::Timer { .. } => events.push(e),
+                        _ => {}
+                    }
+                }
+            }
+            (transmitted, suppressed)
+        };
+
+        // Backlogged: the first advert is skipped, the next sent regardless
+        assert_eq!(send(&own, 3), (false, true));
+        assert_eq!(send(&own, 3), (true, false));
+        assert_eq!(send(&own, 0), (true, false));
+        // Relayed adverts and other packets always go out
+        assert_eq!(send(&relayed, 3), (true, false));
+        assert_eq!(send(&[0u8; 20], 3), (true, false));
+        assert_eq!(radio.suppressor().unwrap().suppressed(), 1);
+    }
+
+    #[test]
+    fn test_interference_degrades_reception() {
+        let firmware = EntityId::new(2);
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            RadioConfig::default(),
+            GeoCoord::new(47.0, -122.0),
+            firmware,
+            MetricLabels::new("node", "repeater"),
+        );
+        let params = radio.params().clone();
+        let mut ctx = SimContext::new(1);
+        let event = |payload| Event {
+            id: mcsim_common::EventId(0),
+            time: SimTime::ZERO,
+            source: EntityId::new(0),
+            targets: vec![EntityId::new(1)],
+            payload,
+        };
+
+        // A strong packet, then a noise burst 20 dB above the noise floor
+        let end_time = SimTime::from_millis(100);
+        radio.handle_event(&event(EventPayload::ReceiveAir(mcsim_common::ReceiveAirEvent {
+            source_radio_id: EntityId::new(3),
+            packet: LoraPacket::new(vec![1, 2, 3]),
+            params: params.clone(),
+            sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+            end_time,
+            mean_snr_db_at20dbm: 10.0,
+            snr_std_dev: 0.0,
+            rssi_dbm: -100.0,
+            fading: FadingModel::None,
+            fault: None,
+        })), &mut ctx).unwrap();
+        radio.handle_event(&event(EventPayload::ReceiveInterference(mcsim_common::ReceiveInterferenceEvent {
+            source_id: EntityId::new(4),
+            kind: InterferenceKind::Noise,
+            frequency_hz: params.frequency_hz,
+            bandwidth_hz: params.bandwidth_hz,
+            tx_power_dbm: 20,
+            end_time: SimTime::from_millis(50),
+            mean_snr_db_at20dbm: 20.0,
+            snr_std_dev: 0.0,
+        })), &mut ctx).unwrap();
+        ctx.take_pending_events();
+
+        ctx.set_time(end_time);
+        radio.handle_event(&event(EventPayload::Timer { timer_id: TIMER_RX_COMPLETE_BASE }), &mut ctx).unwrap();
+        let rx = ctx
+            .take_pending_events()
+            .into_iter()
+            .find_map(|e| match e.payload {
+                EventPayload::RadioRxPacket(rx) => Some(rx),
+                _ => None,
+            })
+            .unwrap();
+        assert!((rx.snr_db - (10.0 - 20.043)).abs() < 1e-2);
+        assert!(rx.was_weak_signal);
+    }
+
+    #[test]
+    fn test_injected_faults() {
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            RadioConfig::default(),
+            GeoCoord::new(47.0, -122.0),
+            EntityId::new(2),
+            MetricLabels::new("node", "repeater"),
+        );
+        let params = radio.params().clone();
+        let mut ctx = SimContext::new(1);
+        let event = |payload| Event {
+            id: mcsim_common::EventId(0),
+            time: SimTime::ZERO,
+            source: EntityId::new(0),
+            targets: vec![EntityId::new(1)],
+            payload,
+        };
+        let receive = |fault| EventPayload::ReceiveAir(mcsim_common::ReceiveAirEvent {
+            source_radio_id: EntityId::new(3),
+            packet: LoraPacket::new(vec![0; 8]),
+            params: params.clone(),
+            sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+            end_time: SimTime::from_millis(100),
+            mean_snr_db_at20dbm: 10.0,
+            snr_std_dev: 0.0,
+            rssi_dbm: -100.0,
+            fading: FadingModel::None,
+            fault: Some(fault),
+        });
+
+        // A dropped packet is never received
+        radio.handle_event(&event(receive(InjectedFault::Drop)), &mut ctx).unwrap();
+        assert!(ctx.take_pending_events().is_empty());
+
+        // A corrupted one arrives with one bit flipped, however strong
+        radio.handle_event(&event(receive(InjectedFault::Corrupt)), &mut ctx).unwrap();
+        ctx.take_pending_events();
+        ctx.set_time(SimTime::from_millis(100));
+        radio.handle_event(&event(EventPayload::Timer { timer_id: TIMER_RX_COMPLETE_BASE }), &mut ctx).unwrap();
+        let rx = ctx
+            .take_pending_events()
+            .into_iter()
+            .find_map(|e| match e.payload {
+                EventPayload::RadioRxPacket(rx) => Some(rx),
+                _ => None,
+            })
+            .unwrap();
+        assert!(rx.was_corrupted && !rx.was_weak_signal && !rx.was_collided);
+        assert_eq!(rx.packet.payload.iter().map(|b| b.count_ones()).sum::<u32>(), 1);
+    }
+
+    #[test]
+    fn test_channel_separation() {
+        let firmware = EntityId::new(2);
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            RadioConfig::default(),
+            GeoCoord::new(47.0, -122.0),
+            firmware,
+            MetricLabels::new("node", "repeater"),
+        );
+        let params = radio.params().clone();
+        let mut ctx = SimContext::new(1);
+        let end_time = SimTime::from_millis(100);
+        let mut receive = |source: u64, frequency_hz: u32, mean_snr_db_at20dbm: f64| {
+            let event = Event {
+                id: mcsim_common::EventId(0),
+                time: SimTime::ZERO,
+                source: EntityId::new(0),
+                targets: vec![EntityId::new(1)],
+                payload: EventPayload::ReceiveAir(mcsim_common::ReceiveAirEvent {
+                    source_radio_id: EntityId::new(source),
+                    packet: LoraPacket::new(vec![1, 2, 3]),
+                    params: RadioParams { frequency_hz, ..params.clone() },
+                    sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+                    end_time,
+                    mean_snr_db_at20dbm,
+                    snr_std_dev: 0.0,
+                    rssi_dbm: -100.0,
+                    fading: FadingModel::None,
+                    fault: None,
+                }),
+            };
+            radio.handle_event(&event, &mut ctx).unwrap();
+        };
+
+        // A wanted packet, a much stronger one on a separate channel, and one
+        // on a half-overlapping channel 7 dB stronger after rejection
+        receive(3, params.frequency_hz, 10.0);
+        receive(4, params.frequency_hz + 100_000, 40.0);
+        receive(5, params.frequency_hz + 31_250, 30.0);
+        assert_eq!(radio.active_receptions.len(), 2);
+        assert!(radio.active_receptions[1].adjacent_channel);
+        ctx.take_pending_events();
+
+        ctx.set_time(end_time);
+        for reception_id in 0..2 {
+            let timer = EventPayload::Timer { timer_id: TIMER_RX_COMPLETE_BASE + reception_id };
+            radio.handle_event(&Event {
+                id: mcsim_common::EventId(0),
+                time: end_time,
+                source: EntityId::new(1),
+                targets: vec![EntityId::new(1)],
+                payload: timer,
+            }, &mut ctx).unwrap();
+        }
+        let delivered: Vec<_> = ctx
+            .take_pending_events()
+            .into_iter()
+            .filter_map(|e| match e.payload {
+                EventPayload::RadioRxPacket(rx) => Some(rx),
+                _ => None,
+            })
+            .collect();
+
+        // Only the wanted packet reaches firmware, destroyed by the overlap
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].source_radio_id, EntityId::new(3));
+        assert!(delivered[0].was_collided);
+    }
+
+    #[test]
+    fn test_foreign_sync_word() {
+        let firmware = EntityId::new(2);
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            RadioConfig::default(),
+            GeoCoord::new(47.0, -122.0),
+            firmware,
+            MetricLabels::new("node", "repeater"),
+        );
+        let params = radio.params().clone();
+        let mut ctx = SimContext::new(1);
+        let end_time = SimTime::from_millis(100);
+        let mut receive = |source: u64, sync_word: u8, mean_snr_db_at20dbm: f64| {
+            let event = Event {
+                id: mcsim_common::EventId(0),
+                time: SimTime::ZERO,
+                source: EntityId::new(0),
+                targets: vec![EntityId::new(1)],
+                payload: EventPayload::ReceiveAir(mcsim_common::ReceiveAirEvent {
+                    source_radio_id: EntityId::new(source),
+                    packet: LoraPacket::new(vec![1, 2, 3]),
+                    params: params.clone(),
+                    sync_word,
+                    end_time,
+                    mean_snr_db_at20dbm,
+                    snr_std_dev: 0.0,
+                    rssi_dbm: -100.0,
+                    fading: FadingModel::None,
+                    fault: None,
+                }),
+            };
+            radio.handle_event(&event, &mut ctx).unwrap();
+        };
+
+        // A strong packet from another network keeps the channel busy and
+        // destroys a weaker one from our own network
+        receive(3, 0x34, 20.0);
+        receive(4, mcsim_common::DEFAULT_SYNC_WORD, 10.0);
+        assert!(radio.active_receptions[0].foreign_network);
+        assert!(!radio.active_receptions[1].foreign_network);
+        assert!(radio.channel_busy);
+        ctx.take_pending_events();
+
+        ctx.set_time(end_time);
+        for reception_id in 0..2 {
+            let timer = EventPayload::Timer { timer_id: TIMER_RX_COMPLETE_BASE + reception_id };
+            radio.handle_event(&Event {
+                id: mcsim_common::EventId(0),
+                time: end_time,
+                source: EntityId::new(1),
+                targets: vec![EntityId::new(1)],
+                payload: timer,
+            }, &mut ctx).unwrap();
+        }
+        let delivered: Vec<_> = ctx
+            .take_pending_events()
+            .into_iter()
+            .filter_map(|e| match e.payload {
+                EventPayload::RadioRxPacket(rx) => Some(rx),
+                _ => None,
+            })
+            .collect();
+
+        // The foreign packet is never delivered, however strong
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].source_radio_id, EntityId::new(4));
+        assert!(delivered[0].was_collided);
+    }
+
+    #[test]
+    fn test_channel_activity_events() {
+        let firmware = EntityId::new(2);
+        let mut radio = Radio::new(
+            EntityId::new(1),
+            RadioConfig::default(),
+            GeoCoord::new(47.0, -122.0),
+            firmware,
+            MetricLabels::new("node", "repeater"),
+        );
+        let params = radio.params().clone();
+        let mut ctx = SimContext::new(1);
+        let end_time = SimTime::from_millis(100);
+        let event = |time: SimTime, payload| Event {
+            id: mcsim_common::EventId(0),
+            time,
+            source: EntityId::new(0),
+            targets: vec![EntityId::new(1)],
+            payload,
+        };
+        let receive = |source: u64, mean_snr_db_at20dbm: f64| {
+            EventPayload::ReceiveAir(mcsim_common::ReceiveAirEvent {
+                source_radio_id: EntityId::new(source),
+                packet: LoraPacket::new(vec![1, 2, 3]),
+                params: params.clone(),
+                sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+                end_time,
+                mean_snr_db_at20dbm,
+                snr_std_dev: 0.0,
+                rssi_dbm: -100.0,
+                fading: FadingModel::None,
+                fault: None,
+            })
+        };
+        let complete = |reception_id: u64| EventPayload::Timer { timer_id: TIMER_RX_COMPLETE_BASE + reception_id };
+        let activity = |ctx: &mut SimContext| -> Vec<bool> {
+            ctx.take_pending_events()
+                .into_iter()
+                .filter_map(|e| match e.payload {
+                    EventPayload::ChannelActivity(activity) => {
+                        assert_eq!(e.targets, vec![firmware]);
+                        Some(activity.busy)
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // A reception below the demodulation threshold isn't detectable
+        radio.handle_event(&event(SimTime::ZERO, receive(3, -40.0)), &mut ctx).unwrap();
+        assert!(activity(&mut ctx).is_empty());
+
+        // A detectable one makes the channel busy
+        radio.handle_event(&event(SimTime::ZERO, receive(4, 10.0)), &mut ctx).unwrap();
+        assert_eq!(activity(&mut ctx), vec![true]);
+
+        // The weak one ending changes nothing; the detectable one ending frees the channel
+        ctx.set_time(end_time);
+        radio.handle_event(&event(end_time, complete(0)), &mut ctx).unwrap();
+        assert!(activity(&mut ctx).is_empty());
+        radio.handle_event(&event(end_time, complete(1)), &mut ctx).unwrap();
+        assert_eq!(activity(&mut ctx), vec![false]);
+        assert!(!radio.channel_busy);
+    }
 }
@@ -0,0 +1,428 @@
+//! Node mobility.
+//!
+//! Static links come from the model's edges. A mobile node's links are instead
+//! recomputed by the [`Graph`](crate::Graph) at a fixed interval from the
+//! node's current position, using a log-distance path loss model
+//! ([`PathLossModel`]). Each mobile node follows a [`MobilityModel`]:
+//! a timed list of waypoints, a constant velocity, or the random waypoint
+//! model (pick a point in an area, walk there, pause, repeat).
+//!
+//! Only links involving at least one mobile node are recomputed; links
+//! between two static nodes keep their model values for the whole run.
+
+use std::collections::BTreeMap;
+
+use mcsim_common::{EntityId, FadingModel, GeoCoord};
+use rand::Rng;
+
+use crate::{LinkModel, LinkParams};
+
+/// Mean Earth radius in meters (for dead reckoning).
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// Reference TX power of link SNRs in the link model.
+const REFERENCE_TX_POWER_DBM: f64 = 20.0;
+
+/// A point on a waypoint path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waypoint {
+    /// Simulation time (seconds) at which the node is at `position`.
+    pub at_s: f64,
+    /// Position at that time.
+    pub position: GeoCoord,
+}
+
+/// How a mobile node moves.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MobilityModel {
+    /// Move in straight lines between timed waypoints (sorted by time),
+    /// holding the first position before the first waypoint and the last
+    /// position after the last one.
+    Waypoints(Vec<Waypoint>),
+    /// Move from the start position at a constant speed and heading.
+    ConstantVelocity {
+        /// Speed in meters per second.
+        speed_mps: f64,
+        /// Heading in degrees clockwise from north.
+        heading_deg: f64,
+    },
+    /// Repeatedly walk to a random point in an area and pause there.
+    RandomWaypoint {
+        /// Southern edge of the area (degrees).
+        min_lat: f64,
+        /// Western edge of the area (degrees).
+        min_lon: f64,
+        /// Northern edge of the area (degrees).
+        max_lat: f64,
+        /// Eastern edge of the area (degrees).
+        max_lon: f64,
+        /// Lowest speed of a leg in meters per second.
+        min_speed_mps: f64,
+        /// Highest speed of a leg in meters per second.
+        max_speed_mps: f64,
+        /// Pause at each waypoint in seconds.
+        pause_s: f64,
+    },
+}
+
+/// Progress of a node under the random waypoint model.
+#[derive(Debug, Clone)]
+enum Leg {
+    /// Paused at the current position until the given time.
+    Paused { until_s: f64 },
+    /// Walking from `from` to `to`.
+    Moving { from: GeoCoord, to: GeoCoord, depart_s: f64, arrive_s: f64 },
+}
+
+/// A node that moves during the simulation.
+#[derive(Debug, Clone)]
+pub struct MobileNode {
+    radio: EntityId,
+    model: MobilityModel,
+    start: GeoCoord,
+    leg: Leg,
+}
+
+impl MobileNode {
+    /// A node whose radio `radio` starts at `start` and moves per `model`.
+    pub fn new(radio: EntityId, start: GeoCoord, model: MobilityModel) -> Self {
+        MobileNode {
+            radio,
+            model,
+            start,
+            leg: Leg::Paused { until_s: 0.0 },
+        }
+    }
+
+    /// The node's radio entity.
+    pub fn radio(&self) -> EntityId {
+        self.radio
+    }
+
+    /// Position at `now_s`, given the position at the previous call.
+    ///
+    /// Calls must be made with non-decreasing times; the random waypoint
+    /// model draws its next leg from `rng` as each one completes.
+    pub fn advance<R: Rng>(&mut self, now_s: f64, current: &GeoCoord, rng: &mut R) -> GeoCoord {
+        match &self.model {
+            MobilityModel::Waypoints(waypoints) => interpolate_waypoints(waypoints, now_s).unwrap_or(self.start),
+            MobilityModel::ConstantVelocity { speed_mps, heading_deg } => {
+                offset(&self.start, speed_mps * now_s, *heading_deg)
+            }
+            &MobilityModel::RandomWaypoint {
+                min_lat,
+                min_lon,
+                max_lat,
+                max_lon,
+                min_speed_mps,
+                max_speed_mps,
+                pause_s,
+            } => {
+                let mut position = *current;
+                loop {
+                    match &self.leg {
+                        Leg::Paused { until_s } if now_s < *until_s => return position,
+                        &Leg::Paused { until_s } => {
+                            let to = GeoCoord::new(
+                                sample_range(rng, min_lat, max_lat),
+                                sample_range(rng, min_lon, max_lon),
+                            );
+                            let speed = sample_range(rng, min_speed_mps, max_speed_mps).max(f64::EPSILON);
+                            let arrive_s = until_s + position.distance_to(&to) / speed;
+                            self.leg = Leg::Moving { from: position, to, depart_s: until_s, arrive_s };
+                        }
+                        Leg::Moving { from, to, depart_s, arrive_s } => {
+                            if now_s < *arrive_s {
+                                let fraction = (now_s - depart_s) / (arrive_s - depart_s);
+                                return lerp(from, to, fraction);
+                            }
+                            position = *to;
+                            self.leg = Leg::Paused { until_s: arrive_s + pause_s };
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn sample_range<R: Rng>(rng: &mut R, low: f64, high: f64) -> f64 {
+    if high > low {
+        rng.gen_range(low..high)
+    } else {
+        low
+    }
+}
+
+fn lerp(from: &GeoCoord, to: &GeoCoord, fraction: f64) -> GeoCoord {
+    let fraction = fraction.clamp(0.0, 1.0);
+    GeoCoord::new(
+        from.latitude + (to.latitude - from.latitude) * fraction,
+        from.longitude + (to.longitude - from.longitude) * fraction,
+    )
+}
+
+fn interpolate_waypoints(waypoints: &[Waypoint], now_s: f64) -> Option<GeoCoord> {
+    let first = waypoints.first()?;
+    if now_s <= first.at_s {
+        return Some(first.position);
+    }
+    for pair in waypoints.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        if now_s < b.at_s {
+            let fraction = (now_s - a.at_s) / (b.at_s - a.at_s);
+            return Some(lerp(&a.position, &b.position, fraction));
+        }
+    }
+    waypoints.last().map(|w| w.position)
+}
+
+/// Position `distance_m` from `start` along `heading_deg` (flat-earth
+/// approximation, fine for the distances a node covers in a run).
+fn offset(start: &GeoCoord, distance_m: f64, heading_deg: f64) -> GeoCoord {
+    let heading = heading_deg.to_radians();
+    let dlat = distance_m * heading.cos() / EARTH_RADIUS_M;
+    let dlon = distance_m * heading.sin() / (EARTH_RADIUS_M * start.latitude.to_radians().cos());
+    GeoCoord::new(start.latitude + dlat.to_degrees(), start.longitude + dlon.to_degrees())
+}
+
+/// Log-distance path loss model for links of mobile nodes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathLossModel {
+    /// Path loss exponent (2.0 is free space).
+    pub exponent: f64,
+    /// Noise floor in dBm.
+    pub noise_floor_dbm: f64,
+    /// SNR standard deviation given to recomputed links.
+    pub snr_std_dev: f64,
+    /// Links with a mean SNR (at 20 dBm) below this are removed.
+    pub min_snr_db: f64,
+}
+
+impl PathLossModel {
+    /// Path loss in dB over `distance_m` at `frequency_hz`.
+    pub fn path_loss_db(&self, distance_m: f64, frequency_hz: u32) -> f64 {
+        let fspl_1m = 20.0 * (frequency_hz as f64 / 1e6).log10() - 27.55;
+        fspl_1m + 10.0 * self.exponent * distance_m.max(1.0).log10()
+    }
+
+    /// Link parameters over `distance_m`, or `None` if the link is too weak.
+    pub fn link(&self, distance_m: f64, frequency_hz: u32) -> Option<LinkParams> {
+        let rssi_dbm = REFERENCE_TX_POWER_DBM - self.path_loss_db(distance_m, frequency_hz);
+        let mean_snr_db_at20dbm = rssi_dbm - self.noise_floor_dbm;
+        (mean_snr_db_at20dbm >= self.min_snr_db).then_some(LinkParams {
+            mean_snr_db_at20dbm,
+            snr_std_dev: self.snr_std_dev,
+            rssi_dbm,
+            fading: FadingModel::None,
+        })
+    }
+}
+
+/// A radio's position and carrier frequency.
+#[derive(Debug, Clone)]
+pub struct RadioPosition {
+    /// Current position.
+    pub position: GeoCoord,
+    /// Carrier frequency in Hz.
+    pub frequency_hz: u32,
+}
+
+/// Mobility state owned by the [`Graph`](crate::Graph).
+#[derive(Debug, Clone)]
+pub struct Mobility {
+    nodes: Vec<MobileNode>,
+    radios: BTreeMap<EntityId, RadioPosition>,
+    path_loss: PathLossModel,
+    update_interval_s: f64,
+}
+
+impl Mobility {
+    /// Mobility for `nodes` among all `radios`, updating links every
+    /// `update_interval_s` seconds.
+    pub fn new(
+        nodes: Vec<MobileNode>,
+        radios: BTreeMap<EntityId, RadioPosition>,
+        path_loss: PathLossModel,
+        update_interval_s: f64,
+    ) -> Self {
+        Mobility { nodes, radios, path_loss, update_interval_s }
+    }
+
+    /// Seconds between link updates.
+    pub fn update_interval_s(&self) -> f64 {
+        self.update_interval_s
+    }
+
+    /// Current position of a radio.
+    pub fn position(&self, radio: EntityId) -> Option<&GeoCoord> {
+        self.radios.get(&radio).map(|r| &r.position)
+    }
+
+    /// Move every mobile node to its position at `now_s` and recompute the
+    /// links that involve it.
+    pub fn update<R: Rng>(&mut self, now_s: f64, links: &mut LinkModel, rng: &mut R) {
+        for node in &mut self.nodes {
+            let Some(radio) = self.radios.get(&node.radio) else {
+                continue;
+            };
+            let position = node.advance(now_s, &radio.position, rng);
+            if let Some(radio) = self.radios.get_mut(&node.radio) {
+                radio.position = position;
+            }
+        }
+
+        for node in &self.nodes {
+            self.relink(node.radio, links);
+        }
+    }
+
+    /// Move a radio to `position` and recompute its links, as for a mobile
+    /// node. A mobile node carries on along its model at the next update.
+    /// Returns `false` if the radio is unknown.
+    pub fn place(&mut self, radio: EntityId, position: GeoCoord, links: &mut LinkModel) -> bool {
+        let Some(entry) = self.radios.get_mut(&radio) else {
+            return false;
+        };
+        entry.position = position;
+        self.relink(radio, links);
+        true
+    }
+
+    /// Recompute the links between `radio` and every other radio from
+    /// their distance.
+    fn relink(&self, radio: EntityId, links: &mut LinkModel) {
+        let Some(mobile) = self.radios.get(&radio) else {
+            return;
+        };
+        for (&other, other_radio) in &self.radios {
+            if other == radio {
+                continue;
+            }
+            let distance_m = mobile.position.distance_to(&other_radio.position);
+            // Each direction uses the transmitter's frequency
+            for (from, to, frequency_hz) in [
+                (radio, other, mobile.frequency_hz),
+                (other, radio, other_radio.frequency_hz),
+            ] {
+                match self.path_loss.link(distance_m, frequency_hz) {
+                    Some(params) => links.add_edge(from, to, params),
+                    None => links.remove_link(from, to),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn path_loss() -> PathLossModel {
+        PathLossModel { exponent: 3.0, noise_floor_dbm: -120.0, snr_std_dev: 1.8, min_snr_db: -25.0 }
+    }
+
+    #[test]
+    fn test_waypoints_interpolate_and_hold() {
+        let waypoints = vec![
+            Waypoint { at_s: 10.0, position: GeoCoord::new(47.0, -122.0) },
+            Waypoint { at_s: 20.0, position: GeoCoord::new(47.1, -122.2) },
+        ];
+        let mut node = MobileNode::new(EntityId::new(1), GeoCoord::new(0.0, 0.0), MobilityModel::Waypoints(waypoints));
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let here = GeoCoord::new(0.0, 0.0);
+
+        assert_eq!(node.advance(0.0, &here, &mut rng), GeoCoord::new(47.0, -122.0));
+        let mid = node.advance(15.0, &here, &mut rng);
+        assert!((mid.latitude - 47.05).abs() < 1e-9);
+        assert!((mid.longitude + 122.1).abs() < 1e-9);
+        assert_eq!(node.advance(100.0, &here, &mut rng), GeoCoord::new(47.1, -122.2));
+    }
+
+    #[test]
+    fn test_constant_velocity_heading() {
+        let start = GeoCoord::new(47.0, -122.0);
+        let model = MobilityModel::ConstantVelocity { speed_mps: 10.0, heading_deg: 90.0 };
+        let mut node = MobileNode::new(EntityId::new(1), start, model);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+        let east = node.advance(100.0, &start, &mut rng);
+        assert!((east.latitude - 47.0).abs() < 1e-9);
+        assert!(east.longitude > -122.0);
+        assert!((start.distance_to(&east) - 1000.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_random_waypoint_stays_in_bounds() {
+        let model = MobilityModel::RandomWaypoint {
+            min_lat: 47.0,
+            min_lon: -122.1,
+            max_lat: 47.1,
+            max_lon: -122.0,
+            min_speed_mps: 1.0,
+            max_speed_mps: 2.0,
+            pause_s: 30.0,
+        };
+        let start = GeoCoord::new(47.05, -122.05);
+        let mut node = MobileNode::new(EntityId::new(1), start, model);
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+
+        let mut position = start;
+        let mut moved = false;
+        for step in 1..=500 {
+            let next = node.advance(step as f64 * 60.0, &position, &mut rng);
+            assert!((47.0..=47.1).contains(&next.latitude));
+            assert!((-122.1..=-122.0).contains(&next.longitude));
+            // Walking speed bounds how far a node gets in a minute
+            assert!(position.distance_to(&next) <= 2.0 * 60.0 + 1.0);
+            moved |= next != position;
+            position = next;
+        }
+        assert!(moved);
+    }
+
+    #[test]
+    fn test_update_recomputes_links_of_mobile_nodes() {
+        let (walker, near, far) = (EntityId::new(1), EntityId::new(2), EntityId::new(3));
+        let radio = |lat: f64| RadioPosition { position: GeoCoord::new(lat, -122.0), frequency_hz: 910_525_000 };
+        let radios = BTreeMap::from([(walker, radio(47.0)), (near, radio(47.001)), (far, radio(48.0))]);
+        let waypoints = vec![
+            Waypoint { at_s: 0.0, position: GeoCoord::new(47.0, -122.0) },
+            Waypoint { at_s: 100.0, position: GeoCoord::new(48.0, -122.0) },
+        ];
+        let nodes = vec![MobileNode::new(walker, GeoCoord::new(47.0, -122.0), MobilityModel::Waypoints(waypoints))];
+        let mut mobility = Mobility::new(nodes, radios, path_loss(), 10.0);
+        let mut links = LinkModel::new();
+        links.add_link(near, far, 5.0, 1.0, -110.0);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+        mobility.update(0.0, &mut links, &mut rng);
+        assert!(links.get_link(walker, near).is_some());
+        assert!(links.get_link(near, walker).is_some());
+        assert!(links.get_link(walker, far).is_none());
+
+        mobility.update(100.0, &mut links, &mut rng);
+        assert!(links.get_link(walker, near).is_none());
+        assert!(links.get_link(far, walker).is_some());
+        // Static links are untouched
+        assert_eq!(links.get_link(near, far).unwrap().mean_snr_db_at20dbm, 5.0);
+    }
+
+    #[test]
+    fn test_place_moves_static_radio() {
+        let (a, b) = (EntityId::new(1), EntityId::new(2));
+        let radio = |lat: f64| RadioPosition { position: GeoCoord::new(lat, -122.0), frequency_hz: 910_525_000 };
+        let radios = BTreeMap::from([(a, radio(47.0)), (b, radio(48.0))]);
+        let mut mobility = Mobility::new(Vec::new(), radios, path_loss(), 10.0);
+        let mut links = LinkModel::new();
+
+        assert!(mobility.place(a, GeoCoord::new(47.999, -122.0), &mut links));
+        assert_eq!(mobility.position(a), Some(&GeoCoord::new(47.999, -122.0)));
+        assert!(links.get_link(a, b).is_some());
+        assert!(links.get_link(b, a).is_some());
+
+        assert!(!mobility.place(EntityId::new(9), GeoCoord::new(0.0, 0.0), &mut links));
+    }
+}
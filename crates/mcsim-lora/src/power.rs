@@ -0,0 +1,226 @@
+//! Battery and power consumption.
+//!
+//! A battery-powered [`Radio`](crate::Radio) draws current according to its
+//! power state: transmitting, receiving a packet, or idle listening. The
+//! drawn charge is integrated over simulated time, and when the battery is
+//! empty the node powers off for the rest of the simulation.
+//!
+//! The battery voltage reported to firmware falls linearly from
+//! [`BATTERY_FULL_MV`] to [`BATTERY_EMPTY_MV`] with the remaining charge.
+
+use mcsim_common::SimTime;
+
+/// Battery voltage at full charge, in millivolts.
+pub const BATTERY_FULL_MV: u16 = 4200;
+
+/// Battery voltage at which the node browns out, in millivolts.
+pub const BATTERY_EMPTY_MV: u16 = 3300;
+
+/// TX power the configured TX current is specified at.
+pub const TX_CURRENT_REFERENCE_DBM: i8 = 20;
+
+/// Current draw and battery capacity of a node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerConfig {
+    /// Battery capacity in mAh.
+    pub battery_capacity_mah: f64,
+    /// Charge at the start of the simulation, as a fraction of capacity.
+    pub initial_charge: f64,
+    /// Current while transmitting at [`TX_CURRENT_REFERENCE_DBM`], in mA.
+    pub tx_current_ma: f64,
+    /// Current while receiving a packet, in mA.
+    pub rx_current_ma: f64,
+    /// Current while listening with nothing on the air, in mA.
+    pub idle_current_ma: f64,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        // SX1262-class radio with an nRF52-class MCU
+        Self {
+            battery_capacity_mah: 2000.0,
+            initial_charge: 1.0,
+            tx_current_ma: 120.0,
+            rx_current_ma: 12.0,
+            idle_current_ma: 8.0,
+        }
+    }
+}
+
+impl PowerConfig {
+    /// Current drawn in a power state, in mA.
+    ///
+    /// TX current above the receive current is taken to be proportional to
+    /// the radiated power, so it is scaled from the reference power to
+    /// `tx_power_dbm`.
+    pub fn current_ma(&self, state: PowerState, tx_power_dbm: i8) -> f64 {
+        match state {
+            PowerState::Idle => self.idle_current_ma,
+            PowerState::Rx => self.rx_current_ma,
+            PowerState::Tx => {
+                let scale = 10f64.powf((tx_power_dbm - TX_CURRENT_REFERENCE_DBM) as f64 / 10.0);
+                self.rx_current_ma + (self.tx_current_ma - self.rx_current_ma).max(0.0) * scale
+            }
+        }
+    }
+}
+
+/// Power state of a radio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerState {
+    /// Listening with nothing on the air.
+    Idle,
+    /// Receiving a packet.
+    Rx,
+    /// Transmitting, including the RX/TX turnarounds.
+    Tx,
+}
+
+impl PowerState {
+    /// Label value used for the `state` metric label.
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerState::Idle => "idle",
+            PowerState::Rx => "rx",
+            PowerState::Tx => "tx",
+        }
+    }
+}
+
+/// Charge drawn from a node's battery over simulated time.
+#[derive(Debug, Clone)]
+pub struct Battery {
+    config: PowerConfig,
+    tx_power_dbm: i8,
+    state: PowerState,
+    since: SimTime,
+    used_mah: f64,
+}
+
+impl Battery {
+    /// A battery at its initial charge, idle from time zero.
+    pub fn new(config: PowerConfig, tx_power_dbm: i8) -> Self {
+        Self {
+            config,
+            tx_power_dbm,
+            state: PowerState::Idle,
+            since: SimTime::ZERO,
+            used_mah: 0.0,
+        }
+    }
+
+    /// The battery configuration.
+    pub fn config(&self) -> &PowerConfig {
+        &self.config
+    }
+
+    /// Current power state.
+    pub fn state(&self) -> PowerState {
+        self.state
+    }
+
+    /// Draw charge for the time spent in the current state up to `now`.
+    /// Returns that time.
+    pub fn advance(&mut self, now: SimTime) -> SimTime {
+        if now <= self.since {
+            return SimTime::ZERO;
+        }
+        let elapsed = now - self.since;
+        let hours = elapsed.as_micros() as f64 / 3.6e9;
+        self.used_mah += self.config.current_ma(self.state, self.tx_power_dbm) * hours;
+        self.since = now;
+        elapsed
+    }
+
+    /// Switch power state at `now`. Call [`advance`](Self::advance) first to
+    /// account for the previous state.
+    pub fn set_state(&mut self, now: SimTime, state: PowerState) {
+        self.since = self.since.max(now);
+        self.state = state;
+    }
+
+    /// Change the TX power the transmit current is drawn at, e.g. when the
+    /// radio derates. Call [`advance`](Self::advance) first.
+    pub fn set_tx_power_dbm(&mut self, tx_power_dbm: i8) {
+        self.tx_power_dbm = tx_power_dbm;
+    }
+
+    /// Charge drawn so far, in mAh.
+    pub fn used_mah(&self) -> f64 {
+        self.used_mah
+    }
+
+    /// Remaining charge as a fraction of capacity.
+    pub fn level(&self) -> f64 {
+        if self.config.battery_capacity_mah <= 0.0 {
+            return 0.0;
+        }
+        let remaining = self.config.battery_capacity_mah * self.config.initial_charge - self.used_mah;
+        (remaining / self.config.battery_capacity_mah).clamp(0.0, 1.0)
+    }
+
+    /// Whether the battery is empty.
+    pub fn is_depleted(&self) -> bool {
+        self.level() <= 0.0
+    }
+
+    /// Battery voltage for the remaining charge.
+    pub fn millivolts(&self) -> u16 {
+        let span = (BATTERY_FULL_MV - BATTERY_EMPTY_MV) as f64;
+        BATTERY_EMPTY_MV + (span * self.level()).round() as u16
+    }
+
+    /// Time until the battery is empty if the current state persists, or
+    /// `None` if the state draws no current.
+    pub fn time_to_depletion(&self) -> Option<SimTime> {
+        let current_ma = self.config.current_ma(self.state, self.tx_power_dbm);
+        if current_ma <= 0.0 {
+            return None;
+        }
+        let remaining_mah = self.level() * self.config.battery_capacity_mah;
+        // Round up so the battery is empty when the time is reached
+        Some(SimTime::from_micros((remaining_mah / current_ma * 3.6e9).ceil() as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PowerConfig {
+        PowerConfig {
+            battery_capacity_mah: 100.0,
+            initial_charge: 1.0,
+            tx_current_ma: 100.0,
+            rx_current_ma: 10.0,
+            idle_current_ma: 5.0,
+        }
+    }
+
+    #[test]
+    fn test_tx_current_scales_with_power() {
+        let config = config();
+        assert_eq!(config.current_ma(PowerState::Tx, 20), 100.0);
+        assert!((config.current_ma(PowerState::Tx, 10) - 19.0).abs() < 1e-9);
+        assert_eq!(config.current_ma(PowerState::Idle, 20), 5.0);
+    }
+
+    #[test]
+    fn test_battery_drain() {
+        let mut battery = Battery::new(config(), 20);
+        assert_eq!(battery.millivolts(), BATTERY_FULL_MV);
+
+        // 2 hours idle at 5 mA = 10 mAh
+        battery.advance(SimTime::from_secs(7200.0));
+        assert!((battery.used_mah() - 10.0).abs() < 1e-9);
+        assert!((battery.level() - 0.9).abs() < 1e-9);
+
+        // 90 mAh left at 100 mA
+        battery.set_state(SimTime::from_secs(7200.0), PowerState::Tx);
+        let remaining = battery.time_to_depletion().unwrap();
+        assert!((remaining.as_secs_f64() - 3240.0).abs() < 1e-3);
+        battery.advance(SimTime::from_secs(7200.0) + remaining);
+        assert!(battery.is_depleted());
+        assert_eq!(battery.millivolts(), BATTERY_EMPTY_MV);
+    }
+}
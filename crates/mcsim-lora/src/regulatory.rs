@@ -0,0 +1,290 @@
+//! Regulatory duty-cycle and dwell-time limits.
+//!
+//! Licence-exempt bands limit how much a radio may transmit. In EU868 each
+//! sub-band has a duty-cycle limit (1% in most of the band, 10% in the
+//! 869.4-869.65 MHz sub-band MeshCore uses), evaluated over a sliding
+//! window; in US915 a narrowband transmission may not dwell on a channel
+//! for more than 400 ms. A [`Radio`](crate::Radio) with a
+//! [`RegulatoryConfig`] checks every transmission against the limits of its
+//! band and, on a violation, depending on the [`Enforcement`]:
+//!
+//! - `record`: transmits anyway and only counts the violation, to check
+//!   whether a deployment stays legal under load;
+//! - `queue`: holds the packet until the duty cycle allows it, as radio
+//!   drivers that enforce the limit do;
+//! - `drop`: discards the packet. The firmware sees its transmission end at
+//!   once.
+//!
+//! A packet that can never be sent legally (longer than the dwell limit, or
+//! than the window's whole duty-cycle budget) is dropped when queueing.
+//! Every violation counts towards `mcsim.radio.duty_cycle_violations`.
+
+use std::collections::VecDeque;
+
+use mcsim_common::SimTime;
+use serde::{Deserialize, Serialize};
+
+/// Default window over which the duty cycle is evaluated (one hour).
+pub const DEFAULT_DUTY_CYCLE_WINDOW: SimTime = SimTime::from_micros(3_600_000_000);
+
+/// Dwell-time limit of narrowband transmissions in US915.
+pub const US915_MAX_DWELL: SimTime = SimTime::from_micros(400_000);
+
+/// Regulatory region whose band plan sets the limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Region {
+    /// Europe, 863-870 MHz: duty-cycle limits per sub-band.
+    Eu868,
+    /// United States, 902-928 MHz: 400 ms dwell time.
+    Us915,
+}
+
+impl Region {
+    /// Duty-cycle limit (0 to 1) at a frequency, if the region has one.
+    ///
+    /// EU868 sub-bands follow ERC Recommendation 70-03 annex 1; frequencies
+    /// outside them get the strictest limit, 0.1%.
+    pub fn duty_cycle_limit(self, frequency_hz: u32) -> Option<f64> {
+        match self {
+            Region::Eu868 => Some(match frequency_hz {
+                865_000_000..868_000_000 => 0.01,
+                868_000_000..868_600_000 => 0.01,
+                868_700_000..869_200_000 => 0.001,
+                869_400_000..869_650_000 => 0.1,
+                869_700_000..870_000_000 => 0.01,
+                _ => 0.001,
+            }),
+            Region::Us915 => None,
+        }
+    }
+
+    /// Dwell-time limit, if the region has one.
+    pub fn max_dwell(self) -> Option<SimTime> {
+        match self {
+            Region::Eu868 => None,
+            Region::Us915 => Some(US915_MAX_DWELL),
+        }
+    }
+}
+
+/// What the radio does with a transmission that breaks a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Enforcement {
+    /// Transmit anyway and count the violation.
+    #[default]
+    Record,
+    /// Hold the packet until it can be sent legally.
+    Queue,
+    /// Discard the packet.
+    Drop,
+}
+
+/// Which limit a transmission breaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// Too much airtime within the duty-cycle window.
+    DutyCycle,
+    /// The packet is longer than the dwell-time limit.
+    Dwell,
+}
+
+impl Violation {
+    /// Label value for metrics.
+    pub fn label(self) -> &'static str {
+        match self {
+            Violation::DutyCycle => "duty_cycle",
+            Violation::Dwell => "dwell",
+        }
+    }
+}
+
+/// Regulatory limits of a radio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegulatoryConfig {
+    /// Largest fraction (0 to 1) of the window the radio may transmit, if
+    /// limited.
+    pub duty_cycle_limit: Option<f64>,
+    /// Sliding window over which the duty cycle is evaluated.
+    pub window: SimTime,
+    /// Longest single transmission, if limited.
+    pub max_dwell: Option<SimTime>,
+    /// What happens to a transmission that breaks a limit.
+    pub enforcement: Enforcement,
+}
+
+/// Decision on a transmission about to start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compliance {
+    /// Transmit now.
+    Transmit,
+    /// Transmit now, though it breaks a limit (recording only).
+    TransmitViolating(Violation),
+    /// Try again at this time.
+    QueueUntil(Violation, SimTime),
+    /// Discard the packet.
+    Drop(Violation),
+}
+
+/// Transmissions of a radio within the duty-cycle window.
+#[derive(Debug, Clone)]
+pub struct ComplianceTracker {
+    config: RegulatoryConfig,
+    /// (start, end) of recent transmissions, oldest first.
+    transmissions: VecDeque<(SimTime, SimTime)>,
+    violations: u64,
+}
+
+impl ComplianceTracker {
+    /// A radio that hasn't transmitted yet.
+    pub fn new(config: RegulatoryConfig) -> Self {
+        Self {
+            config,
+            transmissions: VecDeque::new(),
+            violations: 0,
+        }
+    }
+
+    /// The limits.
+    pub fn config(&self) -> &RegulatoryConfig {
+        &self.config
+    }
+
+    /// Violations so far.
+    pub fn violations(&self) -> u64 {
+        self.violations
+    }
+
+    /// Decide on a transmission of `airtime` starting at `now`. A
+    /// transmission that goes ahead must be [`record`](Self::record)ed.
+    pub fn check(&mut self, now: SimTime, airtime: SimTime) -> Compliance {
+        let violation = if self.config.max_dwell.is_some_and(|max| airtime > max) {
+            Some(Violation::Dwell)
+        } else if self.airtime_in_window(now + airtime) + airtime.as_micros() > self.budget_us() {
+            Some(Violation::DutyCycle)
+        } else {
+            None
+        };
+        let Some(violation) = violation else {
+            return Compliance::Transmit;
+        };
+        self.violations += 1;
+        match self.config.enforcement {
+            Enforcement::Record => Compliance::TransmitViolating(violation),
+            Enforcement::Drop => Compliance::Drop(violation),
+            Enforcement::Queue => match violation {
+                Violation::DutyCycle => self
+                    .ready_at(now, airtime)
+                    .map_or(Compliance::Drop(violation), |at| Compliance::QueueUntil(violation, at)),
+                Violation::Dwell => Compliance::Drop(violation),
+            },
+        }
+    }
+
+    /// Record a transmission from `start` until `end`.
+    pub fn record(&mut self, start: SimTime, end: SimTime) {
+        let window_start = end - self.config.window;
+        while self.transmissions.front().is_some_and(|&(_, e)| e <= window_start) {
+            self.transmissions.pop_front();
+        }
+        self.transmissions.push_back((start, end));
+    }
+
+    /// Airtime the duty-cycle limit allows within a window, in microseconds.
+    fn budget_us(&self) -> u64 {
+        self.config
+            .duty_cycle_limit
+            .map_or(u64::MAX, |limit| (self.config.window.as_micros() as f64 * limit) as u64)
+    }
+
+    /// Airtime of recorded transmissions within the window ending at `end`,
+    /// in microseconds.
+    fn airtime_in_window(&self, end: SimTime) -> u64 {
+        let window_start = end - self.config.window;
+        self.transmissions
+            .iter()
+            .map(|&(s, e)| e.as_micros().saturating_sub(s.max(window_start).as_micros()))
+            .sum()
+    }
+
+    /// Earliest time from `now` at which a transmission of `airtime` fits
+    /// the duty-cycle budget, or None if it never does.
+    fn ready_at(&self, now: SimTime, airtime: SimTime) -> Option<SimTime> {
+        let budget = self.budget_us().checked_sub(airtime.as_micros())?;
+        let fits = |at: SimTime| self.airtime_in_window(at + airtime) <= budget;
+        // The airtime in the window only falls as its start slides through
+        // the recorded transmissions, oldest first
+        let mut at = now;
+        for &(start, end) in &self.transmissions {
+            if fits(at) {
+                return Some(at);
+            }
+            at = at.max(start + self.config.window - airtime);
+            if fits(at) {
+                return Some(at);
+            }
+            // Slide until the excess or this transmission has left the window
+            let excess = self.airtime_in_window(at + airtime) - budget;
+            let window_start = at + airtime - self.config.window;
+            let remaining = (end - start.max(window_start)).as_micros();
+            at = at + SimTime::from_micros(excess.min(remaining));
+        }
+        fits(at).then_some(at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(enforcement: Enforcement) -> ComplianceTracker {
+        ComplianceTracker::new(RegulatoryConfig {
+            duty_cycle_limit: Some(0.1),
+            window: SimTime::from_secs(100.0),
+            max_dwell: Some(SimTime::from_secs(5.0)),
+            enforcement,
+        })
+    }
+
+    #[test]
+    fn test_region_limits() {
+        assert_eq!(Region::Eu868.duty_cycle_limit(869_525_000), Some(0.1));
+        assert_eq!(Region::Eu868.duty_cycle_limit(868_100_000), Some(0.01));
+        assert_eq!(Region::Eu868.duty_cycle_limit(869_300_000), Some(0.001));
+        assert_eq!(Region::Us915.duty_cycle_limit(910_525_000), None);
+        assert_eq!(Region::Us915.max_dwell(), Some(US915_MAX_DWELL));
+    }
+
+    #[test]
+    fn test_duty_cycle_enforcement() {
+        let secs = SimTime::from_secs;
+        let mut record = tracker(Enforcement::Record);
+        let mut queue = tracker(Enforcement::Queue);
+        for t in [&mut record, &mut queue] {
+            // 10 s of the 100 s window's 10% budget used in two packets
+            for start in [0.0, 20.0] {
+                assert_eq!(t.check(secs(start), secs(5.0)), Compliance::Transmit);
+                t.record(secs(start), secs(start + 5.0));
+            }
+        }
+
+        assert_eq!(
+            record.check(secs(50.0), secs(2.0)),
+            Compliance::TransmitViolating(Violation::DutyCycle)
+        );
+        assert_eq!(record.violations(), 1);
+
+        // Fits once 2 s of the first packet have left the window
+        assert_eq!(
+            queue.check(secs(50.0), secs(2.0)),
+            Compliance::QueueUntil(Violation::DutyCycle, secs(100.0))
+        );
+        assert_eq!(queue.check(secs(100.0), secs(2.0)), Compliance::Transmit);
+
+        // Too long to ever send
+        assert_eq!(queue.check(secs(50.0), secs(6.0)), Compliance::Drop(Violation::Dwell));
+        let mut drop = tracker(Enforcement::Drop);
+        assert_eq!(drop.check(SimTime::ZERO, secs(6.0)), Compliance::Drop(Violation::Dwell));
+    }
+}
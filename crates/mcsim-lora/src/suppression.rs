@@ -0,0 +1,393 @@
+//! Adaptive advert suppression experiments.
+//!
+//! MeshCore nodes advertise on a fixed schedule whether or not the channel
+//! is congested. To experiment with policies that skip adverts when it is, a
+//! [`Radio`](crate::Radio) with an [`AdvertSuppression`] config asks its
+//! [`SuppressionPolicy`] before sending each advert the node originates
+//! (relayed adverts, which carry a path, are never touched). The policy sees
+//! what the radio observes ([`ChannelObservation`]):
+//!
+//! - the channel utilization: the fraction of the recent window the channel
+//!   was busy with receptions the radio could demodulate or with its own
+//!   transmissions;
+//! - the number of packets the firmware still has queued, for firmware that
+//!   keeps an inspectable queue (repeaters);
+//! - how many adverts in a row were already suppressed.
+//!
+//! A suppressed advert never goes on air; the firmware sees its transmission
+//! end at once. At most `max_consecutive` adverts in a row are suppressed,
+//! which bounds how much later neighbours discover the node.
+//!
+//! Built-in policies are `none`, `utilization` (suppress while the
+//! utilization exceeds `max_utilization`), `queue_depth` (suppress while at
+//! least `max_queue_depth` packets wait) and `adaptive` (either). Experiments
+//! add their own with [`register_policy`]; nodes pick one by name.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use mcsim_common::{LoraPacket, SimTime};
+use meshcore_packet::PayloadType;
+
+/// Default window over which the channel utilization is measured.
+pub const DEFAULT_UTILIZATION_WINDOW: SimTime = SimTime::from_micros(60_000_000);
+
+/// Whether a packet is an advert its sender originated rather than relays.
+pub fn is_own_advert(packet: &LoraPacket) -> bool {
+    packet
+        .decoded()
+        .is_some_and(|p| p.payload_type() == PayloadType::Advert && p.path_len() == 0)
+}
+
+/// What a radio observed when one of its adverts is about to be sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelObservation {
+    /// Fraction (0 to 1) of the utilization window the channel was busy.
+    pub utilization: f64,
+    /// Packets the firmware has queued behind the advert, if it reports them.
+    pub queue_depth: Option<usize>,
+    /// Adverts suppressed since the last one sent.
+    pub suppressed_in_row: u32,
+}
+
+/// Decides whether a node skips an advert.
+pub trait SuppressionPolicy: fmt::Debug + Send + Sync {
+    /// Whether to skip the advert about to be sent.
+    fn suppress(&self, observation: &ChannelObservation) -> bool;
+}
+
+/// Tuning of a policy, from the node's configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolicyParams {
+    /// Utilization above which the channel counts as congested.
+    pub max_utilization: f64,
+    /// Queued packets at which the node counts as backlogged.
+    pub max_queue_depth: usize,
+}
+
+impl Default for PolicyParams {
+    fn default() -> Self {
+        Self { max_utilization: 0.3, max_queue_depth: 2 }
+    }
+}
+
+/// Builds a policy from its tuning.
+pub type PolicyFactory = fn(&PolicyParams) -> Arc<dyn SuppressionPolicy>;
+
+/// Never suppresses (the firmware's own schedule).
+#[derive(Debug, Clone, Copy)]
+pub struct NeverSuppress;
+
+impl SuppressionPolicy for NeverSuppress {
+    fn suppress(&self, _observation: &ChannelObservation) -> bool {
+        false
+    }
+}
+
+/// Suppresses while the channel utilization exceeds a limit.
+#[derive(Debug, Clone, Copy)]
+pub struct UtilizationThreshold {
+    /// Utilization above which adverts are skipped.
+    pub max_utilization: f64,
+}
+
+impl SuppressionPolicy for UtilizationThreshold {
+    fn suppress(&self, observation: &ChannelObservation) -> bool {
+        observation.utilization > self.max_utilization
+    }
+}
+
+/// Suppresses while the firmware has a backlog of queued packets.
+#[derive(Debug, Clone, Copy)]
+pub struct QueueDepthThreshold {
+    /// Queued packets at which adverts are skipped.
+    pub max_queue_depth: usize,
+}
+
+impl SuppressionPolicy for QueueDepthThreshold {
+    fn suppress(&self, observation: &ChannelObservation) -> bool {
+        observation.queue_depth.is_some_and(|depth| depth >= self.max_queue_depth)
+    }
+}
+
+/// Suppresses when the channel is congested or the firmware is backlogged.
+#[derive(Debug, Clone, Copy)]
+pub struct Adaptive {
+    /// Congestion test.
+    pub utilization: UtilizationThreshold,
+    /// Backlog test.
+    pub queue_depth: QueueDepthThreshold,
+}
+
+impl SuppressionPolicy for Adaptive {
+    fn suppress(&self, observation: &ChannelObservation) -> bool {
+        self.utilization.suppress(observation) || self.queue_depth.suppress(observation)
+    }
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Names of the built-in policies.
+pub const BUILTIN_POLICIES: &[&str] = &["none", "utilization", "queue_depth", "adaptive"];
+
+static POLICIES: RwLock<BTreeMap<String, PolicyFactory>> = RwLock::new(BTreeMap::new());
+
+fn builtin_policy(name: &str, params: &PolicyParams) -> Option<Arc<dyn SuppressionPolicy>> {
+    let utilization = UtilizationThreshold { max_utilization: params.max_utilization };
+    let queue_depth = QueueDepthThreshold { max_queue_depth: params.max_queue_depth };
+    Some(match name {
+        "none" => Arc::new(NeverSuppress),
+        "utilization" => Arc::new(utilization),
+        "queue_depth" => Arc::new(queue_depth),
+        "adaptive" => Arc::new(Adaptive { utilization, queue_depth }),
+        _ => return None,
+    })
+}
+
+/// Make a policy available to nodes under `name`.
+///
+/// Returns false, leaving the registry unchanged, if the name is taken by a
+/// built-in or another registered policy.
+pub fn register_policy(name: &str, factory: PolicyFactory) -> bool {
+    if BUILTIN_POLICIES.contains(&name) {
+        return false;
+    }
+    let mut policies = POLICIES.write().unwrap_or_else(|e| e.into_inner());
+    if policies.contains_key(name) {
+        return false;
+    }
+    policies.insert(name.to_string(), factory);
+    true
+}
+
+/// Build the policy registered as `name`, if any.
+pub fn policy(name: &str, params: &PolicyParams) -> Option<Arc<dyn SuppressionPolicy>> {
+    builtin_policy(name, params).or_else(|| {
+        let policies = POLICIES.read().unwrap_or_else(|e| e.into_inner());
+        policies.get(name).map(|factory| factory(params))
+    })
+}
+
+/// Names of all available policies, built-ins first.
+pub fn policy_names() -> Vec<String> {
+    let policies = POLICIES.read().unwrap_or_else(|e| e.into_inner());
+    BUILTIN_POLICIES
+        .iter()
+        .map(|name| name.to_string())
+        .chain(policies.keys().cloned())
+        .collect()
+}
+
+// ============================================================================
+// Radio State
+// ============================================================================
+
+/// Advert suppression of a radio.
+#[derive(Debug, Clone)]
+pub struct AdvertSuppression {
+    /// Name the policy was picked by, for reporting.
+    pub policy_name: String,
+    /// The policy.
+    pub policy: Arc<dyn SuppressionPolicy>,
+    /// Window over which the channel utilization is measured.
+    pub window: SimTime,
+    /// Most adverts suppressed in a row.
+    pub max_consecutive: u32,
+}
+
+/// Channel utilization over a sliding window.
+#[derive(Debug, Clone)]
+pub struct ChannelUtilization {
+    window: SimTime,
+    /// Busy (start, end) intervals, by end time.
+    busy: VecDeque<(SimTime, SimTime)>,
+    /// Start of the current busy period, if the channel is busy.
+    busy_since: Option<SimTime>,
+}
+
+impl ChannelUtilization {
+    /// A channel that has been idle.
+    pub fn new(window: SimTime) -> Self {
+        Self { window, busy: VecDeque::new(), busy_since: None }
+    }
+
+    /// The channel became busy or idle at `now`.
+    pub fn set_busy(&mut self, now: SimTime, busy: bool) {
+        match (busy, self.busy_since) {
+            (true, None) => self.busy_since = Some(now),
+            (false, Some(start)) => {
+                self.busy_since = None;
+                self.record(start, now);
+            }
+            _ => {}
+        }
+    }
+
+    /// Record the channel as busy from `start` until `end`.
+    pub fn record(&mut self, start: SimTime, end: SimTime) {
+        let window_start = end - self.window;
+        while self.busy.front().is_some_and(|&(_, e)| e <= window_start) {
+            self.busy.pop_front();
+        }
+        self.busy.push_back((start, end));
+    }
+
+    /// Fraction (0 to 1) of the window ending at `now` the channel was busy.
+    /// Overlapping periods (a reception during the radio's own
+    /// transmission) count once.
+    pub fn utilization(&self, now: SimTime) -> f64 {
+        let window_start = now - self.window;
+        let mut periods: Vec<(SimTime, SimTime)> = self
+            .busy
+            .iter()
+            .copied()
+            .chain(self.busy_since.map(|start| (start, now)))
+            .map(|(s, e)| (s.max(window_start), e.min(now)))
+            .filter(|&(s, e)| e > s)
+            .collect();
+        periods.sort();
+        let mut busy_us = 0;
+        let mut covered_until = window_start;
+        for (start, end) in periods {
+            if end > covered_until {
+                busy_us += end.as_micros() - start.max(covered_until).as_micros();
+                covered_until = end;
+            }
+        }
+        (busy_us as f64 / self.window.as_micros().max(1) as f64).min(1.0)
+    }
+}
+
+/// Applies a radio's suppression policy to its adverts.
+#[derive(Debug, Clone)]
+pub struct AdvertSuppressor {
+    config: AdvertSuppression,
+    channel: ChannelUtilization,
+    suppressed_in_row: u32,
+    suppressed: u64,
+    airtime_saved: SimTime,
+}
+
+impl AdvertSuppressor {
+    /// A radio that hasn't sent or suppressed an advert yet.
+    pub fn new(config: AdvertSuppression) -> Self {
+        let channel = ChannelUtilization::new(config.window);
+        Self { config, channel, suppressed_in_row: 0, suppressed: 0, airtime_saved: SimTime::ZERO }
+    }
+
+    /// The configuration.
+    pub fn config(&self) -> &AdvertSuppression {
+        &self.config
+    }
+
+    /// The channel's utilization as the radio measures it.
+    pub fn channel(&self) -> &ChannelUtilization {
+        &self.channel
+    }
+
+    /// The channel became busy or idle.
+    pub fn set_channel_busy(&mut self, now: SimTime, busy: bool) {
+        self.channel.set_busy(now, busy);
+    }
+
+    /// The radio transmitted from `start` until `end`.
+    pub fn record_transmission(&mut self, start: SimTime, end: SimTime) {
+        self.channel.record(start, end);
+    }
+
+    /// Decide on an advert of `airtime` about to be sent at `now`. Returns
+    /// what the policy saw if the advert is suppressed.
+    pub fn check(&mut self, now: SimTime, queue_depth: Option<usize>, airtime: SimTime) -> Option<ChannelObservation> {
+        let observation = ChannelObservation {
+            utilization: self.channel.utilization(now),
+            queue_depth,
+            suppressed_in_row: self.suppressed_in_row,
+        };
+        if self.suppressed_in_row >= self.config.max_consecutive || !self.config.policy.suppress(&observation) {
+            self.suppressed_in_row = 0;
+            return None;
+        }
+        self.suppressed_in_row += 1;
+        self.suppressed += 1;
+        self.airtime_saved = self.airtime_saved + airtime;
+        Some(observation)
+    }
+
+    /// Adverts suppressed so far.
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+
+    /// Airtime of the suppressed adverts.
+    pub fn airtime_saved(&self) -> SimTime {
+        self.airtime_saved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_utilization() {
+        let secs = SimTime::from_secs;
+        let mut channel = ChannelUtilization::new(secs(10.0));
+        channel.set_busy(secs(1.0), true);
+        channel.set_busy(secs(3.0), false);
+        // Own transmission overlapping the reception counts once
+        channel.record(secs(2.0), secs(4.0));
+        assert!((channel.utilization(secs(10.0)) - 0.3).abs() < 1e-9);
+        // Still busy at 13 s; the 1-2 s part has left the window
+        channel.set_busy(secs(12.0), true);
+        assert!((channel.utilization(secs(13.0)) - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_suppression_is_bounded() {
+        let mut suppressor = AdvertSuppressor::new(AdvertSuppression {
+            policy_name: "adaptive".to_string(),
+            policy: policy("adaptive", &PolicyParams::default()).unwrap(),
+            window: SimTime::from_secs(10.0),
+            max_consecutive: 2,
+        });
+        let airtime = SimTime::from_millis(100);
+        suppressor.record_transmission(SimTime::ZERO, SimTime::from_secs(5.0));
+        let now = SimTime::from_secs(5.0);
+
+        // Congested: two adverts suppressed, then one goes out regardless
+        assert!(suppressor.check(now, None, airtime).is_some());
+        assert_eq!(suppressor.check(now, None, airtime).unwrap().suppressed_in_row, 1);
+        assert!(suppressor.check(now, None, airtime).is_none());
+
+        // Backlogged firmware on a quiet channel
+        let later = SimTime::from_secs(100.0);
+        assert!(suppressor.check(later, Some(1), airtime).is_none());
+        assert!(suppressor.check(later, Some(2), airtime).is_some());
+        assert_eq!(suppressor.suppressed(), 3);
+        assert_eq!(suppressor.airtime_saved(), SimTime::from_millis(300));
+    }
+
+    #[test]
+    fn test_custom_policy_registry() {
+        #[derive(Debug)]
+        struct EveryOther;
+        impl SuppressionPolicy for EveryOther {
+            fn suppress(&self, observation: &ChannelObservation) -> bool {
+                observation.suppressed_in_row == 0
+            }
+        }
+
+        assert!(register_policy("every_other", |_| Arc::new(EveryOther)));
+        assert!(!register_policy("every_other", |_| Arc::new(NeverSuppress)));
+        assert!(!register_policy("utilization", |_| Arc::new(NeverSuppress)));
+        assert!(policy_names().contains(&"every_other".to_string()));
+
+        let observation = ChannelObservation { utilization: 0.0, queue_depth: None, suppressed_in_row: 0 };
+        assert!(policy("every_other", &PolicyParams::default()).unwrap().suppress(&observation));
+        assert!(!policy("none", &PolicyParams::default()).unwrap().suppress(&observation));
+        assert!(policy("bogus", &PolicyParams::default()).is_none());
+    }
+}
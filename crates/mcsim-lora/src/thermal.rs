@@ -0,0 +1,166 @@
+//! Thermal derating of transmit power.
+//!
+//! High-power repeaters, particularly solar units in sealed enclosures, cut
+//! their TX power when sustained traffic heats the power amplifier. A
+//! [`Radio`](crate::Radio) with a [`DeratingConfig`] tracks its recent TX
+//! duty cycle as an exponentially weighted average over the configured time
+//! constant, a stand-in for the PA temperature, and transmits each packet
+//! with its configured power reduced by the derating the curve gives for
+//! that duty cycle. Receivers see the lower power in their link budgets.
+//!
+//! The curve is piecewise linear between its points and flat beyond its
+//! ends, so a curve starting at `(0.1, 0.0)` leaves the power untouched
+//! below 10% duty.
+
+use mcsim_common::SimTime;
+
+/// How sustained transmitting reduces a radio's TX power.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeratingConfig {
+    /// Time constant of the duty-cycle average, i.e. how quickly the radio
+    /// heats up and cools down.
+    pub time_constant: SimTime,
+    /// Derating curve as `(duty_fraction, derating_db)` points, sorted by
+    /// duty fraction (0 to 1).
+    pub curve: Vec<(f64, f64)>,
+}
+
+impl DeratingConfig {
+    /// TX power reduction in dB at an average duty cycle (0 to 1).
+    pub fn derating_db(&self, duty: f64) -> f64 {
+        let (Some(&(first_duty, first_db)), Some(&(last_duty, last_db))) = (self.curve.first(), self.curve.last()) else {
+            return 0.0;
+        };
+        if duty <= first_duty {
+            return first_db;
+        }
+        if duty >= last_duty {
+            return last_db;
+        }
+        self.curve
+            .windows(2)
+            .find(|w| duty <= w[1].0)
+            .map(|w| {
+                let ((d0, db0), (d1, db1)) = (w[0], w[1]);
+                if d1 > d0 {
+                    db0 + (db1 - db0) * (duty - d0) / (d1 - d0)
+                } else {
+                    db1
+                }
+            })
+            .unwrap_or(last_db)
+    }
+}
+
+/// Average TX duty cycle of a radio over simulated time.
+#[derive(Debug, Clone)]
+pub struct DutyTracker {
+    config: DeratingConfig,
+    /// Average duty cycle at `since`.
+    duty: f64,
+    since: SimTime,
+    /// End of the transmission in progress, if any.
+    busy_until: SimTime,
+}
+
+impl DutyTracker {
+    /// A cold radio that hasn't transmitted yet.
+    pub fn new(config: DeratingConfig) -> Self {
+        Self {
+            config,
+            duty: 0.0,
+            since: SimTime::ZERO,
+            busy_until: SimTime::ZERO,
+        }
+    }
+
+    /// The derating configuration.
+    pub fn config(&self) -> &DeratingConfig {
+        &self.config
+    }
+
+    /// Bring the average up to `now`, counting time before `busy_until` as
+    /// transmitting and the rest as idle.
+    pub fn advance(&mut self, now: SimTime) {
+        if now <= self.since {
+            return;
+        }
+        if self.busy_until > self.since {
+            let tx_end = self.busy_until.min(now);
+            self.decay_towards(1.0, tx_end - self.since);
+            self.since = tx_end;
+        }
+        self.decay_towards(0.0, now - self.since);
+        self.since = now;
+    }
+
+    /// Record a transmission from `now` until `end`. Call
+    /// [`advance`](Self::advance) first.
+    pub fn start_tx(&mut self, now: SimTime, end: SimTime) {
+        self.since = self.since.max(now);
+        self.busy_until = end;
+    }
+
+    /// Average duty cycle (0 to 1) as of the last update.
+    pub fn duty(&self) -> f64 {
+        self.duty
+    }
+
+    /// TX power reduction in dB at the current duty cycle.
+    pub fn derating_db(&self) -> f64 {
+        self.config.derating_db(self.duty)
+    }
+
+    fn decay_towards(&mut self, target: f64, elapsed: SimTime) {
+        let tau = self.config.time_constant.as_secs_f64();
+        let weight = if tau > 0.0 { (-elapsed.as_secs_f64() / tau).exp() } else { 0.0 };
+        self.duty = target + (self.duty - target) * weight;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DeratingConfig {
+        DeratingConfig {
+            time_constant: SimTime::from_secs(60.0),
+            curve: vec![(0.1, 0.0), (0.3, 3.0), (0.5, 6.0)],
+        }
+    }
+
+    #[test]
+    fn test_derating_curve() {
+        let config = config();
+        assert_eq!(config.derating_db(0.0), 0.0);
+        assert_eq!(config.derating_db(0.1), 0.0);
+        assert!((config.derating_db(0.2) - 1.5).abs() < 1e-9);
+        assert!((config.derating_db(0.4) - 4.5).abs() < 1e-9);
+        assert_eq!(config.derating_db(0.9), 6.0);
+        assert_eq!(DeratingConfig { curve: Vec::new(), ..config }.derating_db(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_duty_heats_and_cools() {
+        let mut tracker = DutyTracker::new(config());
+
+        // Transmitting continuously for one time constant
+        tracker.advance(SimTime::from_secs(10.0));
+        tracker.start_tx(SimTime::from_secs(10.0), SimTime::from_secs(70.0));
+        tracker.advance(SimTime::from_secs(70.0));
+        let hot = 1.0 - (-1.0f64).exp();
+        assert!((tracker.duty() - hot).abs() < 1e-9);
+        assert_eq!(tracker.derating_db(), 6.0);
+
+        // Idle for another time constant
+        tracker.advance(SimTime::from_secs(130.0));
+        assert!((tracker.duty() - hot * (-1.0f64).exp()).abs() < 1e-9);
+
+        // Advancing part-way through a transmission counts only the part sent
+        let mut tracker = DutyTracker::new(config());
+        tracker.start_tx(SimTime::ZERO, SimTime::from_secs(60.0));
+        tracker.advance(SimTime::from_secs(30.0));
+        tracker.advance(SimTime::from_secs(60.0));
+        assert!((tracker.duty() - hot).abs() < 1e-9);
+    }
+}
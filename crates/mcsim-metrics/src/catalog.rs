@@ -0,0 +1,114 @@
+//! Machine-readable catalog of metric metadata.
+//!
+//! Dashboards and exporter configurations that hard-code metric names drift
+//! out of sync with the code as metrics are added or renamed. [`catalog`]
+//! lists every built-in metric from [`metric_defs::ALL`] plus any registered
+//! [custom metrics](crate::custom), and [`to_json`] / [`to_markdown`] render
+//! that list so such files can be generated instead:
+//!
+//! ```text
+//! mcsim metrics --format json > metrics.json
+//! ```
+
+use serde::Serialize;
+
+use crate::{custom, metric_defs, unit_name, MetricKind};
+
+/// Metadata for one metric in the catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MetricCatalogEntry {
+    /// Metric name.
+    pub name: String,
+    /// Kind of metric (counter, gauge or histogram).
+    pub kind: &'static str,
+    /// Unit of measurement, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<&'static str>,
+    /// Expected label keys.
+    pub labels: Vec<String>,
+    /// Human-readable description.
+    pub description: String,
+    /// Whether the metric was declared at runtime rather than built in.
+    pub custom: bool,
+}
+
+impl MetricCatalogEntry {
+    fn new(
+        name: &str,
+        kind: MetricKind,
+        unit: Option<metrics::Unit>,
+        labels: impl IntoIterator<Item = impl Into<String>>,
+        description: &str,
+        custom: bool,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: kind.as_str(),
+            unit: unit.map(|u| unit_name(Some(u))),
+            labels: labels.into_iter().map(Into::into).collect(),
+            description: description.to_string(),
+            custom,
+        }
+    }
+}
+
+/// All known metrics: built-ins in declaration order, then registered custom
+/// metrics by name.
+pub fn catalog() -> Vec<MetricCatalogEntry> {
+    let builtin = metric_defs::ALL.iter().map(|m| {
+        MetricCatalogEntry::new(m.name, m.kind, m.unit, m.labels.iter().copied(), m.description, false)
+    });
+    let registered = custom::all().into_iter().map(|m| {
+        MetricCatalogEntry::new(&m.name, m.kind, m.unit, m.labels, &m.description, true)
+    });
+    builtin.chain(registered).collect()
+}
+
+/// Render catalog entries as a pretty-printed JSON array.
+pub fn to_json(entries: &[MetricCatalogEntry]) -> String {
+    serde_json::to_string_pretty(entries).expect("catalog entries serialize")
+}
+
+/// Render catalog entries as a Markdown table.
+pub fn to_markdown(entries: &[MetricCatalogEntry]) -> String {
+    let mut out = String::from("| Name | Kind | Unit | Labels | Description |\n");
+    out.push_str("|------|------|------|--------|-------------|\n");
+    for entry in entries {
+        out.push_str(&format!(
+            "| `{}` | {} | {} | {} | {} |\n",
+            entry.name,
+            entry.kind,
+            entry.unit.unwrap_or(""),
+            entry.labels.join(", "),
+            entry.description.replace('|', "\\|"),
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_covers_builtin_metrics() {
+        let entries = catalog();
+        assert!(entries.len() >= metric_defs::ALL.len());
+        let tx = entries.iter().find(|e| e.name == metric_defs::RADIO_TX_AIRTIME.name).unwrap();
+        assert_eq!(tx.kind, "counter");
+        assert_eq!(tx.unit, Some("microseconds"));
+        assert!(tx.labels.iter().any(|l| l == "node"));
+        assert!(!tx.custom);
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&entries)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), entries.len());
+    }
+
+    #[test]
+    fn test_markdown_escapes_pipes() {
+        let entry = MetricCatalogEntry::new("a.b", MetricKind::Gauge, None, ["node"], "x | y", false);
+        let markdown = to_markdown(&[entry]);
+        assert_eq!(markdown.lines().count(), 3);
+        assert!(markdown.contains("| `a.b` | gauge |  | node | x \\| y |"));
+    }
+}
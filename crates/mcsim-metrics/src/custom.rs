@@ -0,0 +1,289 @@
+//! Custom metrics declared at runtime.
+//!
+//! Built-in metrics are compile-time [`Metric`](crate::Metric) constants. Custom
+//! metrics let a scenario (or an agent) declare experiment-specific counters,
+//! gauges and histograms by name without changing this crate. Once registered
+//! they are emitted through the same global recorder, so every exporter sees
+//! them exactly like built-in metrics.
+//!
+//! ```rust
+//! use mcsim_metrics::custom::{self, CustomMetric};
+//! use mcsim_metrics::MetricKind;
+//!
+//! custom::register(
+//!     CustomMetric::new("experiment.handshakes", MetricKind::Counter)
+//!         .with_description("Completed handshakes")
+//!         .with_labels(["node", "phase"]),
+//! )
+//! .unwrap();
+//!
+//! custom::counter("experiment.handshakes", &[("node", "Alice"), ("phase", "login")], 1).unwrap();
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use metrics::{describe_counter, describe_gauge, describe_histogram, Label, Unit};
+
+use crate::{metric_defs, MetricKind};
+
+/// Prefix reserved for built-in metrics.
+pub const RESERVED_PREFIX: &str = "mcsim.";
+
+/// Declaration of a custom metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomMetric {
+    /// Metric name (must not start with [`RESERVED_PREFIX`]).
+    pub name: String,
+    /// Kind of metric.
+    pub kind: MetricKind,
+    /// Human-readable description.
+    pub description: String,
+    /// Unit of measurement (optional).
+    pub unit: Option<Unit>,
+    /// Label keys that may be attached when emitting.
+    pub labels: Vec<String>,
+}
+
+impl CustomMetric {
+    /// Create a declaration with no description, unit or labels.
+    pub fn new(name: impl Into<String>, kind: MetricKind) -> Self {
+        CustomMetric {
+            name: name.into(),
+            kind,
+            description: String::new(),
+            unit: None,
+            labels: Vec::new(),
+        }
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Set the unit.
+    pub fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    /// Set the declared label keys.
+    pub fn with_labels<I, S>(mut self, labels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.labels = labels.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Check that the declaration is usable.
+    pub fn validate(&self) -> Result<(), CustomMetricError> {
+        if self.name.is_empty() {
+            return Err(CustomMetricError::InvalidName(self.name.clone()));
+        }
+        if self.name.starts_with(RESERVED_PREFIX)
+            || metric_defs::ALL.iter().any(|m| m.name == self.name)
+        {
+            return Err(CustomMetricError::ReservedName(self.name.clone()));
+        }
+        Ok(())
+    }
+
+    /// Register this metric's description with the metrics recorder.
+    pub fn describe(&self) {
+        let name = self.name.clone();
+        let description = self.description.clone();
+        match (self.kind, self.unit) {
+            (MetricKind::Counter, Some(unit)) => describe_counter!(name, unit, description),
+            (MetricKind::Counter, None) => describe_counter!(name, description),
+            (MetricKind::Gauge, Some(unit)) => describe_gauge!(name, unit, description),
+            (MetricKind::Gauge, None) => describe_gauge!(name, description),
+            (MetricKind::Histogram, Some(unit)) => describe_histogram!(name, unit, description),
+            (MetricKind::Histogram, None) => describe_histogram!(name, description),
+        }
+    }
+
+    fn to_labels(&self, labels: &[(&str, &str)]) -> Result<Vec<Label>, CustomMetricError> {
+        labels
+            .iter()
+            .map(|(key, value)| {
+                if self.labels.iter().any(|declared| declared == key) {
+                    Ok(Label::new(key.to_string(), value.to_string()))
+                } else {
+                    Err(CustomMetricError::UndeclaredLabel {
+                        metric: self.name.clone(),
+                        label: key.to_string(),
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+/// Errors from declaring or emitting custom metrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustomMetricError {
+    /// The metric name is empty.
+    InvalidName(String),
+    /// The metric name collides with a built-in metric.
+    ReservedName(String),
+    /// A metric with the same name but a different declaration is registered.
+    Conflict(String),
+    /// No custom metric with this name is registered.
+    Unknown(String),
+    /// The metric was emitted as a different kind than declared.
+    WrongKind {
+        /// Metric name.
+        metric: String,
+        /// Declared kind.
+        declared: MetricKind,
+    },
+    /// A label key was not declared for the metric.
+    UndeclaredLabel {
+        /// Metric name.
+        metric: String,
+        /// Offending label key.
+        label: String,
+    },
+}
+
+impl std::fmt::Display for CustomMetricError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CustomMetricError::InvalidName(name) => write!(f, "Invalid custom metric name '{}'", name),
+            CustomMetricError::ReservedName(name) => write!(
+                f,
+                "Custom metric '{}' collides with built-in metrics (the '{}' prefix is reserved)",
+                name, RESERVED_PREFIX
+            ),
+            CustomMetricError::Conflict(name) => {
+                write!(f, "Custom metric '{}' is already declared differently", name)
+            }
+            CustomMetricError::Unknown(name) => write!(f, "Custom metric '{}' is not declared", name),
+            CustomMetricError::WrongKind { metric, declared } => {
+                write!(f, "Custom metric '{}' is declared as a {}", metric, declared)
+            }
+            CustomMetricError::UndeclaredLabel { metric, label } => {
+                write!(f, "Label '{}' is not declared for custom metric '{}'", label, metric)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CustomMetricError {}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+static REGISTRY: RwLock<BTreeMap<String, CustomMetric>> = RwLock::new(BTreeMap::new());
+
+/// Declare a custom metric and describe it to the current recorder.
+///
+/// Registering an identical declaration again is a no-op, so scenarios that are
+/// loaded several times in one process don't fail.
+pub fn register(metric: CustomMetric) -> Result<(), CustomMetricError> {
+    metric.validate()?;
+    let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+    match registry.get(&metric.name) {
+        Some(existing) if *existing == metric => return Ok(()),
+        Some(_) => return Err(CustomMetricError::Conflict(metric.name)),
+        None => {}
+    }
+    metric.describe();
+    registry.insert(metric.name.clone(), metric);
+    Ok(())
+}
+
+/// Look up a registered custom metric.
+pub fn get(name: &str) -> Option<CustomMetric> {
+    REGISTRY.read().unwrap_or_else(|e| e.into_inner()).get(name).cloned()
+}
+
+/// All registered custom metrics, sorted by name.
+pub fn all() -> Vec<CustomMetric> {
+    REGISTRY.read().unwrap_or_else(|e| e.into_inner()).values().cloned().collect()
+}
+
+fn resolve(
+    name: &str,
+    kind: MetricKind,
+    labels: &[(&str, &str)],
+) -> Result<(String, Vec<Label>), CustomMetricError> {
+    let registry = REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+    let metric = registry
+        .get(name)
+        .ok_or_else(|| CustomMetricError::Unknown(name.to_string()))?;
+    if metric.kind != kind {
+        return Err(CustomMetricError::WrongKind {
+            metric: name.to_string(),
+            declared: metric.kind,
+        });
+    }
+    Ok((metric.name.clone(), metric.to_labels(labels)?))
+}
+
+/// Increment a declared custom counter.
+pub fn counter(name: &str, labels: &[(&str, &str)], value: u64) -> Result<(), CustomMetricError> {
+    let (name, labels) = resolve(name, MetricKind::Counter, labels)?;
+    metrics::counter!(name, labels).increment(value);
+    Ok(())
+}
+
+/// Set a declared custom gauge.
+pub fn gauge(name: &str, labels: &[(&str, &str)], value: f64) -> Result<(), CustomMetricError> {
+    let (name, labels) = resolve(name, MetricKind::Gauge, labels)?;
+    metrics::gauge!(name, labels).set(value);
+    Ok(())
+}
+
+/// Record a value in a declared custom histogram.
+pub fn histogram(name: &str, labels: &[(&str, &str)], value: f64) -> Result<(), CustomMetricError> {
+    let (name, labels) = resolve(name, MetricKind::Histogram, labels)?;
+    metrics::histogram!(name, labels).record(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserved_names_rejected() {
+        let builtin = CustomMetric::new(metric_defs::RADIO_TX_PACKETS.name, MetricKind::Counter);
+        assert!(matches!(register(builtin), Err(CustomMetricError::ReservedName(_))));
+        let prefixed = CustomMetric::new("mcsim.my_metric", MetricKind::Counter);
+        assert!(matches!(register(prefixed), Err(CustomMetricError::ReservedName(_))));
+    }
+
+    #[test]
+    fn test_register_and_emit() {
+        let metric = CustomMetric::new("test.custom.latency", MetricKind::Histogram)
+            .with_unit(Unit::Milliseconds)
+            .with_labels(["node"]);
+        register(metric.clone()).unwrap();
+        // Identical re-registration is allowed, conflicting is not
+        register(metric).unwrap();
+        assert!(matches!(
+            register(CustomMetric::new("test.custom.latency", MetricKind::Counter)),
+            Err(CustomMetricError::Conflict(_))
+        ));
+
+        histogram("test.custom.latency", &[("node", "Alice")], 12.5).unwrap();
+        assert!(matches!(
+            counter("test.custom.latency", &[], 1),
+            Err(CustomMetricError::WrongKind { .. })
+        ));
+        assert!(matches!(
+            histogram("test.custom.latency", &[("phase", "x")], 1.0),
+            Err(CustomMetricError::UndeclaredLabel { .. })
+        ));
+        assert!(matches!(
+            gauge("test.custom.missing", &[], 1.0),
+            Err(CustomMetricError::Unknown(_))
+        ));
+    }
+}
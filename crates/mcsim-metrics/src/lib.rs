@@ -40,6 +40,10 @@
 //! metrics::counter!(MY_COUNTER.name).increment(1);
 //! ```
 
+pub mod catalog;
+pub mod custom;
+
+pub use catalog::{catalog, MetricCatalogEntry};
 pub use metrics;
 
 use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
@@ -249,26 +253,31 @@ impl Metric {
 
     /// Returns the unit as a human-readable string.
     pub fn unit_str(&self) -> &'static str {
-        match self.unit {
-            Some(Unit::Count) => "count",
-            Some(Unit::Percent) => "percent",
-            Some(Unit::Seconds) => "seconds",
-            Some(Unit::Milliseconds) => "milliseconds",
-            Some(Unit::Microseconds) => "microseconds",
-            Some(Unit::Nanoseconds) => "nanoseconds",
-            Some(Unit::Tebibytes) => "tebibytes",
-            Some(Unit::Gibibytes) => "gibibytes",
-            Some(Unit::Mebibytes) => "mebibytes",
-            Some(Unit::Kibibytes) => "kibibytes",
-            Some(Unit::Bytes) => "bytes",
-            Some(Unit::TerabitsPerSecond) => "terabits/second",
-            Some(Unit::GigabitsPerSecond) => "gigabits/second",
-            Some(Unit::MegabitsPerSecond) => "megabits/second",
-            Some(Unit::KilobitsPerSecond) => "kilobits/second",
-            Some(Unit::BitsPerSecond) => "bits/second",
-            Some(Unit::CountPerSecond) => "count/second",
-            None => "",
-        }
+        unit_name(self.unit)
+    }
+}
+
+/// Human-readable name of a unit (empty for none).
+pub(crate) fn unit_name(unit: Option<Unit>) -> &'static str {
+    match unit {
+        Some(Unit::Count) => "count",
+        Some(Unit::Percent) => "percent",
+        Some(Unit::Seconds) => "seconds",
+        Some(Unit::Milliseconds) => "milliseconds",
+        Some(Unit::Microseconds) => "microseconds",
+        Some(Unit::Nanoseconds) => "nanoseconds",
+        Some(Unit::Tebibytes) => "tebibytes",
+        Some(Unit::Gibibytes) => "gibibytes",
+        Some(Unit::Mebibytes) => "mebibytes",
+        Some(Unit::Kibibytes) => "kibibytes",
+        Some(Unit::Bytes) => "bytes",
+        Some(Unit::TerabitsPerSecond) => "terabits/second",
+        Some(Unit::GigabitsPerSecond) => "gigabits/second",
+        Some(Unit::MegabitsPerSecond) => "megabits/second",
+        Some(Unit::KilobitsPerSecond) => "kilobits/second",
+        Some(Unit::BitsPerSecond) => "bits/second",
+        Some(Unit::CountPerSecond) => "count/second",
+        None => "",
     }
 }
 
@@ -349,6 +358,81 @@ pub mod metric_defs {
         .with_unit(Unit::Count)
         .with_labels(&["node", "node_type", "payload_type", "route_type", "payload_hash"]);
 
+    /// Packets delivered with bit errors (marginal SNR with the bit-error
+    /// channel enabled, or a fault injection rule).
+    /// 
+    /// Labels: node, node_type, payload_type, route_type, payload_hash
+    pub const RADIO_RX_CORRUPTED: Metric = Metric::counter("mcsim.radio.rx_corrupted")
+        .with_description("Packets delivered with bit errors due to marginal SNR or an injected fault")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type", "payload_type", "route_type", "payload_hash"]);
+
+    /// Receptions that overlapped interference from a jammer.
+    /// 
+    /// Labels: node, node_type, payload_type, route_type, payload_hash
+    pub const RADIO_RX_INTERFERED: Metric = Metric::counter("mcsim.radio.rx_interfered")
+        .with_description("Receptions whose SNR was reduced by interference")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type", "payload_type", "route_type", "payload_hash"]);
+
+    /// Packets heard on the radio's channel with another network's sync word.
+    /// They are never decoded, so carry no packet labels.
+    /// 
+    /// Labels: node, node_type
+    pub const RADIO_RX_FOREIGN_NETWORK: Metric = Metric::counter("mcsim.radio.rx_foreign_network")
+        .with_description("Co-channel packets dropped because they carried another network's sync word")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type"]);
+
+    /// Packets a fault injection rule dropped on their way to this radio.
+    /// They are never decoded, so carry no packet labels.
+    /// 
+    /// Labels: node, node_type
+    pub const RADIO_RX_FAULT_DROPPED: Metric = Metric::counter("mcsim.radio.rx_fault_dropped")
+        .with_description("Packets dropped on the link by a fault injection rule")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type"]);
+
+    /// TX power reduction from thermal derating in dB.
+    /// 
+    /// Labels: node, node_type
+    pub const RADIO_TX_DERATING: Metric = Metric::gauge("mcsim.radio.tx_derating_db")
+        .with_description("TX power reduction applied to the latest transmission by thermal derating in dB (nodes with a derating curve only)")
+        .with_labels(&["node", "node_type"]);
+
+    /// Transmissions that broke a regulatory duty-cycle or dwell-time limit.
+    /// 
+    /// Labels: node, node_type, limit, action
+    pub const RADIO_DUTY_CYCLE_VIOLATIONS: Metric = Metric::counter("mcsim.radio.duty_cycle_violations")
+        .with_description("Transmissions that broke a regulatory limit, by limit (duty_cycle or dwell) and what the radio did (transmitted, queued or dropped)")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type", "limit", "action"]);
+
+    /// Adverts the node's suppression policy skipped.
+    /// 
+    /// Labels: node, node_type, policy
+    pub const RADIO_ADVERTS_SUPPRESSED: Metric = Metric::counter("mcsim.radio.adverts_suppressed")
+        .with_description("Adverts the node originated that its suppression policy skipped")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type", "policy"]);
+
+    /// Airtime of the adverts the suppression policy skipped.
+    /// 
+    /// Labels: node, node_type, policy
+    pub const RADIO_SUPPRESSED_AIRTIME: Metric = Metric::counter("mcsim.radio.suppressed_airtime_us")
+        .with_description("Airtime in microseconds the skipped adverts would have taken")
+        .with_unit(Unit::Microseconds)
+        .with_labels(&["node", "node_type", "policy"]);
+
+    /// Transmissions whose firmware-reported airtime disagrees with the
+    /// computed time on air.
+    /// 
+    /// Labels: node, node_type
+    pub const RADIO_AIRTIME_MISMATCH: Metric = Metric::counter("mcsim.radio.airtime_mismatch")
+        .with_description("Transmissions whose firmware-reported airtime differs from the computed LoRa time on air beyond tolerance")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type"]);
+
     /// TX to RX turnaround time in microseconds.
     /// 
     /// Labels: node, node_type (no packet type - measured per state transition)
@@ -504,6 +588,15 @@ pub mod metric_defs {
     pub const FLOOD_COVERAGE: Metric = Metric::gauge("mcsim.flood.coverage")
         .with_description("Fraction of reachable nodes covered by flood");
 
+    /// Flood packets a node heard but did not forward because they had
+    /// reached its hop limit (`firmware/flood_max`).
+    ///
+    /// Labels: node, node_type, payload_type
+    pub const FLOOD_HOP_LIMIT_DROPPED: Metric = Metric::counter("mcsim.flood.hop_limit_dropped")
+        .with_description("Flood packets not forwarded because they reached the node's hop limit")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type", "payload_type"]);
+
     // Direct Message Delivery
 
     /// Path messages sent.
@@ -531,6 +624,32 @@ pub mod metric_defs {
         .with_description("Hop count for delivered path messages")
         .with_unit(Unit::Count);
 
+    // Message Paths
+
+    /// Hops on the path by which a node first received a message.
+    ///
+    /// Labels: node, node_type, payload_type, route_type
+    pub const MESSAGE_PATH_HOPS: Metric = Metric::histogram("mcsim.message.path_hops")
+        .with_description("Hops on the path by which a node first received a message")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type", "payload_type", "route_type"]);
+
+    /// Copies of a message a node received after the first.
+    ///
+    /// Labels: node, node_type, payload_type
+    pub const MESSAGE_DUPLICATES: Metric = Metric::counter("mcsim.message.duplicates")
+        .with_description("Copies of a message a node received after the first")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type", "payload_type"]);
+
+    /// Time from a node first receiving a message to retransmitting it.
+    ///
+    /// Labels: node, node_type
+    pub const MESSAGE_FORWARD_DELAY: Metric = Metric::histogram("mcsim.message.forward_delay_us")
+        .with_description("Time from a node first receiving a message to retransmitting it in microseconds")
+        .with_unit(Unit::Microseconds)
+        .with_labels(&["node", "node_type"]);
+
     // Timing
 
     /// Delay before transmission in microseconds.
@@ -558,6 +677,125 @@ pub mod metric_defs {
         .with_unit(Unit::Microseconds)
         .with_labels(&["node", "node_type"]);
 
+    // Room Server
+
+    /// Posts received by a room server.
+    /// 
+    /// Labels: node, node_type
+    pub const ROOM_POSTS: Metric = Metric::counter("mcsim.room.posts")
+        .with_description("Unique posts received by a room server")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type"]);
+
+    /// Fraction of posts still retained when a client reconnects after a delay.
+    /// 
+    /// Labels: node, node_type, reconnect_delay_s
+    pub const ROOM_POST_AVAILABILITY: Metric = Metric::gauge("mcsim.room.post_availability")
+        .with_description("Fraction of posts a client reconnecting this long after the post would still receive")
+        .with_labels(&["node", "node_type", "reconnect_delay_s"]);
+
+    /// Distinct clients that have sent a login to a room server.
+    /// 
+    /// Labels: node, node_type
+    pub const ROOM_CLIENTS: Metric = Metric::gauge("mcsim.room.clients")
+        .with_description("Distinct clients that have logged in to a room server")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type"]);
+
+    /// Successful room server logins by a room client agent.
+    pub const ROOM_LOGINS: Metric = Metric::counter("mcsim.room.logins")
+        .with_description("Room server logins accepted for a room client agent")
+        .with_unit(Unit::Count);
+
+    /// Room posts pushed to a room client agent by its room server.
+    pub const ROOM_MESSAGES_RECEIVED: Metric = Metric::counter("mcsim.room.messages_received")
+        .with_description("Room posts pushed to a room client agent by its room server")
+        .with_unit(Unit::Count);
+
+    // Companion Sync
+    //
+    // A companion queues received messages until its host app fetches them;
+    // a phone app that is away for long lets the queue grow.
+
+    /// Messages queued in the companion that the host app hasn't fetched yet.
+    pub const SYNC_BACKLOG: Metric = Metric::gauge("mcsim.sync.backlog")
+        .with_description("Messages queued in a companion that its host app hasn't fetched yet")
+        .with_unit(Unit::Count);
+
+    /// Messages the host app fetched from its companion.
+    pub const SYNC_FETCHED: Metric = Metric::counter("mcsim.sync.fetched")
+        .with_description("Messages a host app fetched from its companion's queue")
+        .with_unit(Unit::Count);
+
+    /// Time a message waited in the companion before the host app fetched it.
+    pub const SYNC_WAIT: Metric = Metric::histogram("mcsim.sync.wait_ms")
+        .with_description("Time a message waited in a companion's queue before its host app fetched it in milliseconds")
+        .with_unit(Unit::Milliseconds);
+
+    /// Messages announced by the companion that were gone when the app synced.
+    pub const SYNC_LOST: Metric = Metric::counter("mcsim.sync.lost")
+        .with_description("Messages a companion announced that were no longer queued when its host app synced (queue overflow)")
+        .with_unit(Unit::Count);
+
+    // Power
+
+    /// Remaining battery charge as a fraction of capacity.
+    /// 
+    /// Labels: node, node_type
+    pub const POWER_BATTERY_LEVEL: Metric = Metric::gauge("mcsim.power.battery_level")
+        .with_description("Remaining battery charge as a fraction of capacity (battery-powered nodes only)")
+        .with_labels(&["node", "node_type"]);
+
+    /// Charge drawn from the battery in milliamp-hours.
+    /// 
+    /// Labels: node, node_type
+    pub const POWER_CHARGE_USED: Metric = Metric::gauge("mcsim.power.charge_used_mah")
+        .with_description("Charge drawn from the battery since the start of the simulation in mAh")
+        .with_labels(&["node", "node_type"]);
+
+    /// Time spent in each radio power state in microseconds.
+    /// 
+    /// Labels: node, node_type, state
+    pub const POWER_STATE_TIME: Metric = Metric::counter("mcsim.power.state_time_us")
+        .with_description("Time the radio spent in each power state (tx, rx, idle) in microseconds")
+        .with_unit(Unit::Microseconds)
+        .with_labels(&["node", "node_type", "state"]);
+
+    /// Nodes whose battery ran out.
+    /// 
+    /// Labels: node, node_type
+    pub const POWER_DEPLETED: Metric = Metric::counter("mcsim.power.depleted")
+        .with_description("Battery-powered nodes that ran out of charge and powered off")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type"]);
+
+    // Hardware-in-the-loop bridges
+
+    /// Simulated packets a bridge handed to its hardware.
+    /// 
+    /// Labels: node, node_type
+    pub const BRIDGE_TO_HARDWARE: Metric = Metric::counter("mcsim.bridge.to_hardware")
+        .with_description("Simulated packets decodable at a bridge's position that it handed to the real hardware")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type"]);
+
+    /// Packets heard by a bridge's hardware and transmitted into the
+    /// simulation.
+    /// 
+    /// Labels: node, node_type
+    pub const BRIDGE_FROM_HARDWARE: Metric = Metric::counter("mcsim.bridge.from_hardware")
+        .with_description("Packets the real hardware heard that a bridge transmitted into the simulation")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type"]);
+
+    /// Malformed frames received from a bridge's hardware.
+    /// 
+    /// Labels: node, node_type
+    pub const BRIDGE_FRAME_ERRORS: Metric = Metric::counter("mcsim.bridge.frame_errors")
+        .with_description("Malformed frames a bridge received from the real hardware and discarded")
+        .with_unit(Unit::Count)
+        .with_labels(&["node", "node_type"]);
+
     /// Returns a slice of all defined metrics.
     pub const ALL: &[&Metric] = &[
         // Radio/PHY Layer
@@ -567,6 +805,15 @@ pub mod metric_defs {
         &RADIO_RX_PACKETS,
         &RADIO_RX_COLLIDED,
         &RADIO_RX_WEAK,
+        &RADIO_RX_CORRUPTED,
+        &RADIO_RX_INTERFERED,
+        &RADIO_RX_FOREIGN_NETWORK,
+        &RADIO_RX_FAULT_DROPPED,
+        &RADIO_TX_DERATING,
+        &RADIO_DUTY_CYCLE_VIOLATIONS,
+        &RADIO_ADVERTS_SUPPRESSED,
+        &RADIO_SUPPRESSED_AIRTIME,
+        &RADIO_AIRTIME_MISMATCH,
         &RADIO_TURNAROUND_TIME,
         &RADIO_ACTIVE_RECEPTIONS,
         &RADIO_TX_PACKET_SIZE,
@@ -591,18 +838,43 @@ pub mod metric_defs {
         &FLOOD_TIMES_HEARD,
         &FLOOD_PROPAGATION_TIME,
         &FLOOD_COVERAGE,
+        &FLOOD_HOP_LIMIT_DROPPED,
         // Direct Message Delivery
         &DIRECT_SENT,
         &DIRECT_DELIVERED,
         &DIRECT_FAILED,
         &DIRECT_DELIVERY_LATENCY,
         &DIRECT_HOPS,
+        // Message Paths
+        &MESSAGE_PATH_HOPS,
+        &MESSAGE_DUPLICATES,
+        &MESSAGE_FORWARD_DELAY,
         // Timing
         &TIMING_TX_DELAY,
         &TIMING_RX_PROCESS_DELAY,
         &TIMING_QUEUE_WAIT,
         // Simulation Performance
         &SIMULATION_STEP_TIME,
+        // Room Server
+        &ROOM_POSTS,
+        &ROOM_POST_AVAILABILITY,
+        &ROOM_CLIENTS,
+        &ROOM_LOGINS,
+        &ROOM_MESSAGES_RECEIVED,
+        // Companion Sync
+        &SYNC_BACKLOG,
+        &SYNC_FETCHED,
+        &SYNC_WAIT,
+        &SYNC_LOST,
+        // Power
+        &POWER_BATTERY_LEVEL,
+        &POWER_CHARGE_USED,
+        &POWER_STATE_TIME,
+        &POWER_DEPLETED,
+        // Hardware-in-the-loop bridges
+        &BRIDGE_TO_HARDWARE,
+        &BRIDGE_FROM_HARDWARE,
+        &BRIDGE_FRAME_ERRORS,
     ];
 }
 
@@ -825,8 +1097,8 @@ mod tests {
 
     #[test]
     fn test_all_metrics_count() {
-        // Verify we have all 36 metrics in the ALL slice
-        assert_eq!(metric_defs::ALL.len(), 36);
+        // Verify we have all 65 metrics in the ALL slice
+        assert_eq!(metric_defs::ALL.len(), 65);
     }
 
     #[test]
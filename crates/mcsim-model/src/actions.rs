@@ -0,0 +1,185 @@
+//! Group-targeted scenario actions.
+//!
+//! Instead of enumerating one action per node, a scenario can address a node
+//! group (as assigned by `metrics/groups`) and describe how each member picks
+//! its destination:
+//!
+//! ```yaml
+//! actions:
+//!   - at_s: 600
+//!     group: valley
+//!     send_dm:
+//!       strategy: nearest
+//!       target_type: repeater
+//! ```
+//!
+//! Only companions have agents, so only companion members of the group send.
+//! Candidate destinations are ordered nearest first when the simulation is
+//! built; the final choice (e.g. a random candidate) is made by the agent when
+//! the action fires.
+
+use mcsim_agents::DestinationStrategy;
+use mcsim_common::GeoCoord;
+use serde::{Deserialize, Serialize};
+
+use crate::ModelError;
+
+/// A scenario action applied to every member of a node group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupAction {
+    /// Simulation time (seconds) at which the action fires.
+    pub at_s: f64,
+    /// Group whose members perform the action.
+    pub group: String,
+    /// Direct message to send.
+    pub send_dm: SendDirectMessage,
+}
+
+/// Direct message sent by each member of a [`GroupAction`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SendDirectMessage {
+    /// How each sender picks its destination.
+    pub strategy: DestinationStrategy,
+    /// Only consider destinations with this firmware type (e.g. "repeater").
+    pub target_type: Option<String>,
+    /// Only consider destinations in this group.
+    pub target_group: Option<String>,
+    /// Message text (a default is generated if None).
+    pub text: Option<String>,
+}
+
+/// Node facts needed to resolve group actions.
+#[derive(Debug, Clone)]
+pub struct ActionNode {
+    /// Node name.
+    pub name: String,
+    /// Lowercase firmware type.
+    pub firmware_type: String,
+    /// Groups the node belongs to.
+    pub groups: Vec<String>,
+    /// Node location.
+    pub location: GeoCoord,
+}
+
+impl GroupAction {
+    /// Whether `node` is one of the senders of this action.
+    pub fn is_sender(&self, node: &ActionNode) -> bool {
+        node.firmware_type == "companion" && node.groups.contains(&self.group)
+    }
+
+    /// Candidate destination names for `sender`, nearest first.
+    pub fn candidates(&self, sender: &ActionNode, nodes: &[ActionNode]) -> Vec<String> {
+        let mut candidates: Vec<(f64, &ActionNode)> = nodes
+            .iter()
+            .filter(|node| node.name != sender.name)
+            .filter(|node| match &self.send_dm.target_type {
+                Some(t) => node.firmware_type == *t,
+                None => true,
+            })
+            .filter(|node| match &self.send_dm.target_group {
+                Some(g) => node.groups.contains(g),
+                None => true,
+            })
+            .map(|node| (sender.location.distance_to(&node.location), node))
+            .collect();
+        // Ties broken by name so the order is deterministic
+        candidates.sort_by(|(da, a), (db, b)| da.total_cmp(db).then_with(|| a.name.cmp(&b.name)));
+        candidates.into_iter().map(|(_, node)| node.name.clone()).collect()
+    }
+}
+
+// ============================================================================
+// YAML Schema Types (Internal)
+// ============================================================================
+
+/// Group action (YAML schema, internal).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct GroupActionYaml {
+    at_s: f64,
+    group: String,
+    send_dm: SendDirectMessageYaml,
+}
+
+/// Direct message of a group action (YAML schema, internal).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SendDirectMessageYaml {
+    /// "nearest" or "random".
+    strategy: String,
+    #[serde(default)]
+    target_type: Option<String>,
+    #[serde(default)]
+    target_group: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+impl GroupActionYaml {
+    pub(crate) fn resolve(&self) -> Result<GroupAction, ModelError> {
+        if self.at_s.is_nan() || self.at_s < 0.0 {
+            return Err(ModelError::InvalidConfig(format!(
+                "Action for group '{}': at_s must be non-negative",
+                self.group
+            )));
+        }
+        let strategy = self.send_dm.strategy.parse().map_err(|e| {
+            ModelError::InvalidConfig(format!("Action for group '{}': {}", self.group, e))
+        })?;
+        Ok(GroupAction {
+            at_s: self.at_s,
+            group: self.group.clone(),
+            send_dm: SendDirectMessage {
+                strategy,
+                target_type: self.send_dm.target_type.as_ref().map(|t| t.to_lowercase()),
+                target_group: self.send_dm.target_group.clone(),
+                text: self.send_dm.text.clone(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, firmware_type: &str, groups: &[&str], longitude: f64) -> ActionNode {
+        ActionNode {
+            name: name.to_string(),
+            firmware_type: firmware_type.to_string(),
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+            location: GeoCoord::new(47.0, longitude),
+        }
+    }
+
+    #[test]
+    fn test_candidates_nearest_first() {
+        let nodes = vec![
+            node("Alice", "companion", &["valley"], 0.0),
+            node("Bob", "companion", &["hill"], 0.001),
+            node("FarRpt", "repeater", &[], 0.1),
+            node("NearRpt", "repeater", &[], 0.01),
+        ];
+        let yaml: GroupActionYaml = serde_yaml::from_str(
+            "at_s: 600\ngroup: valley\nsend_dm:\n  strategy: nearest\n  target_type: Repeater\n",
+        )
+        .unwrap();
+        let action = yaml.resolve().unwrap();
+
+        assert!(action.is_sender(&nodes[0]));
+        assert!(!action.is_sender(&nodes[1]));
+        assert_eq!(action.candidates(&nodes[0], &nodes), vec!["NearRpt", "FarRpt"]);
+
+        let mut by_group = action.clone();
+        by_group.send_dm.target_type = None;
+        by_group.send_dm.target_group = Some("hill".to_string());
+        assert_eq!(by_group.candidates(&nodes[0], &nodes), vec!["Bob"]);
+    }
+
+    #[test]
+    fn test_invalid_strategy_rejected() {
+        let yaml: GroupActionYaml =
+            serde_yaml::from_str("at_s: 10\ngroup: valley\nsend_dm:\n  strategy: loudest\n").unwrap();
+        assert!(matches!(yaml.resolve(), Err(ModelError::InvalidConfig(_))));
+    }
+}
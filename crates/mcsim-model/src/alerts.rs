@@ -0,0 +1,223 @@
+//! Alert rules on metrics, evaluated while the simulation runs.
+//!
+//! Long experiments often go wrong early (a collision storm, a node that stops
+//! forwarding) and are only noticed after the run. An alert rule watches one
+//! metric and acts once its condition has held for a while:
+//!
+//! ```yaml
+//! alerts:
+//!   - name: collision_storm
+//!     metric: mcsim.radio.rx_collided
+//!     rate_above: 5        # per simulated second
+//!     for_s: 60
+//!     action: stop
+//!   - name: repeater_quiet
+//!     metric: mcsim.radio.tx_packets
+//!     node: Repeater1
+//!     rate_below: 0.01
+//!     for_s: 600
+//!     action: marker
+//! ```
+//!
+//! Exactly one of `rate_above`, `rate_below`, `value_above` or `value_below`
+//! is required. Rates are per simulated second and apply to counters; values
+//! are the current counter or gauge value. `action` is `warn` (the default),
+//! `marker` (warn and add an entry to the trace) or `stop` (warn and end the
+//! run early). Rules are evaluated every `metrics/alert_check_interval_s`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ModelError;
+
+/// A condition on a metric that triggers an action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    /// Rule name, shown when the alert fires.
+    pub name: String,
+    /// Metric to watch.
+    pub metric: String,
+    /// Only watch the metric for this node (all nodes are summed if None).
+    pub node: Option<String>,
+    /// Condition on the metric.
+    pub condition: AlertCondition,
+    /// Seconds the condition must hold before the alert fires.
+    pub for_s: f64,
+    /// What to do when the alert fires.
+    pub action: AlertAction,
+}
+
+/// Condition of an [`AlertRule`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertCondition {
+    /// Increase per simulated second is above the threshold.
+    RateAbove(f64),
+    /// Increase per simulated second is below the threshold.
+    RateBelow(f64),
+    /// Current value is above the threshold.
+    ValueAbove(f64),
+    /// Current value is below the threshold.
+    ValueBelow(f64),
+}
+
+impl AlertCondition {
+    /// Whether the condition is on the metric's rate rather than its value.
+    pub fn is_rate(&self) -> bool {
+        matches!(self, AlertCondition::RateAbove(_) | AlertCondition::RateBelow(_))
+    }
+
+    /// Whether `observed` (a rate or value, per [`is_rate`](Self::is_rate))
+    /// satisfies the condition.
+    pub fn holds(&self, observed: f64) -> bool {
+        match *self {
+            AlertCondition::RateAbove(threshold) | AlertCondition::ValueAbove(threshold) => observed > threshold,
+            AlertCondition::RateBelow(threshold) | AlertCondition::ValueBelow(threshold) => observed < threshold,
+        }
+    }
+}
+
+impl std::fmt::Display for AlertCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertCondition::RateAbove(t) => write!(f, "rate > {}/s", t),
+            AlertCondition::RateBelow(t) => write!(f, "rate < {}/s", t),
+            AlertCondition::ValueAbove(t) => write!(f, "value > {}", t),
+            AlertCondition::ValueBelow(t) => write!(f, "value < {}", t),
+        }
+    }
+}
+
+/// Action taken when an [`AlertRule`] fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertAction {
+    /// Log a warning.
+    Warn,
+    /// Log a warning and record a marker in the trace.
+    Marker,
+    /// Log a warning and end the run.
+    Stop,
+}
+
+impl std::str::FromStr for AlertAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "warn" => Ok(AlertAction::Warn),
+            "marker" => Ok(AlertAction::Marker),
+            "stop" => Ok(AlertAction::Stop),
+            other => Err(format!("unknown alert action '{}' (expected warn, marker or stop)", other)),
+        }
+    }
+}
+
+// ============================================================================
+// YAML Schema Types (Internal)
+// ============================================================================
+
+/// Alert rule (YAML schema, internal). Exactly one condition must be given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AlertRuleYaml {
+    name: String,
+    metric: String,
+    #[serde(default)]
+    node: Option<String>,
+    #[serde(default)]
+    rate_above: Option<f64>,
+    #[serde(default)]
+    rate_below: Option<f64>,
+    #[serde(default)]
+    value_above: Option<f64>,
+    #[serde(default)]
+    value_below: Option<f64>,
+    #[serde(default)]
+    for_s: f64,
+    /// "warn", "marker" or "stop".
+    #[serde(default)]
+    action: Option<String>,
+}
+
+impl AlertRuleYaml {
+    pub(crate) fn resolve(&self) -> Result<AlertRule, ModelError> {
+        let invalid = |reason: &str| ModelError::InvalidConfig(format!("Alert '{}': {}", self.name, reason));
+
+        let conditions = [
+            self.rate_above.map(AlertCondition::RateAbove),
+            self.rate_below.map(AlertCondition::RateBelow),
+            self.value_above.map(AlertCondition::ValueAbove),
+            self.value_below.map(AlertCondition::ValueBelow),
+        ];
+        let mut given = conditions.into_iter().flatten();
+        let condition = match (given.next(), given.next()) {
+            (Some(condition), None) => condition,
+            _ => {
+                return Err(invalid(
+                    "exactly one of rate_above, rate_below, value_above or value_below is required",
+                ))
+            }
+        };
+        if self.for_s.is_nan() || self.for_s < 0.0 {
+            return Err(invalid("for_s must be non-negative"));
+        }
+        let action = match &self.action {
+            Some(action) => action.parse().map_err(|e: String| invalid(&e))?,
+            None => AlertAction::Warn,
+        };
+
+        Ok(AlertRule {
+            name: self.name.clone(),
+            metric: self.metric.clone(),
+            node: self.node.clone(),
+            condition,
+            for_s: self.for_s,
+            action,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(yaml: &str) -> Result<AlertRule, ModelError> {
+        serde_yaml::from_str::<AlertRuleYaml>(yaml).unwrap().resolve()
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        let rule = resolve("name: storm\nmetric: mcsim.radio.rx_collided\nrate_above: 5\nfor_s: 60\naction: stop\n")
+            .unwrap();
+        assert_eq!(rule.condition, AlertCondition::RateAbove(5.0));
+        assert_eq!(rule.action, AlertAction::Stop);
+        assert_eq!(rule.for_s, 60.0);
+        assert!(rule.condition.holds(5.5));
+        assert!(!rule.condition.holds(5.0));
+
+        let rule = resolve("name: quiet\nmetric: m\nnode: R1\nvalue_below: 1\n").unwrap();
+        assert_eq!(rule.node.as_deref(), Some("R1"));
+        assert_eq!(rule.action, AlertAction::Warn);
+        assert!(!rule.condition.is_rate());
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        assert!(resolve("name: a\nmetric: m\n").is_err());
+        assert!(resolve("name: a\nmetric: m\nrate_above: 1\nvalue_above: 2\n").is_err());
+        assert!(resolve("name: a\nmetric: m\nrate_above: 1\naction: page\n").is_err());
+        assert!(resolve("name: a\nmetric: m\nrate_above: 1\nfor_s: -1\n").is_err());
+    }
+
+    #[test]
+    fn test_overlay_alerts() {
+        let topology = "nodes:\n  - name: Alice\n";
+        let overlay = "alerts:\n  - { name: storm, metric: m, rate_above: 1 }\n";
+        let model = crate::load_models_from_str(&[topology, overlay]).unwrap();
+        assert_eq!(model.alerts().len(), 1);
+
+        let unknown = "alerts:\n  - { name: quiet, metric: m, node: Bob, value_below: 1 }\n";
+        assert!(matches!(
+            crate::load_models_from_str(&[topology, unknown]),
+            Err(ModelError::NodeNotFound(name)) if name == "Bob"
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+//! Scenario outcome assertions, checked at the end of the run.
+//!
+//! Assertions turn a scenario into a regression test: the runner evaluates
+//! them once the simulation ends, reports each result and exits with a
+//! non-zero status if any failed.
+//!
+//! ```yaml
+//! assertions:
+//!   - name: alice_reaches_bob
+//!     delivered: { from: Alice, to: Bob, within_s: 30 }
+//!   - name: floods_reach_the_mesh
+//!     flood_coverage: { at_least: 0.9 }
+//!   - name: repeater_duty_cycle
+//!     duty_cycle: { node: Repeater1, at_most: 0.01, window_s: 3600 }
+//!   - name: no_collisions_at_bob
+//!     metric: { name: mcsim.radio.rx_collided, node: Bob, at_most: 0 }
+//! ```
+//!
+//! Exactly one check is required per assertion:
+//!
+//! - `delivered`: a direct message from `from` reached the app on `to`
+//!   within `within_s` seconds of being sent. Both must be companions.
+//! - `flood_coverage`: the mean fraction of nodes reached by flood packets.
+//! - `duty_cycle`: the node's transmit airtime as a fraction of any sliding
+//!   `window_s` window (default one hour) never exceeded `at_most`.
+//! - `metric`: the final value of a counter or gauge, optionally for one
+//!   node, is `at_least` and/or `at_most` the given bounds.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ModelError;
+
+/// Default sliding window of a duty cycle assertion, in seconds.
+pub const DEFAULT_DUTY_CYCLE_WINDOW_S: f64 = 3600.0;
+
+/// A named check on the outcome of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assertion {
+    /// Assertion name, shown in the report.
+    pub name: String,
+    /// What is checked.
+    pub check: AssertionCheck,
+}
+
+/// What an [`Assertion`] checks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssertionCheck {
+    /// A direct message from one companion reached another in time.
+    Delivered {
+        /// Sending node.
+        from: String,
+        /// Receiving node.
+        to: String,
+        /// Largest acceptable delivery latency in seconds.
+        within_s: f64,
+    },
+    /// Mean flood coverage is at least the given fraction.
+    FloodCoverage {
+        /// Lowest acceptable mean coverage (0-1).
+        at_least: f64,
+    },
+    /// A node's transmit duty cycle never exceeds the given fraction.
+    DutyCycle {
+        /// Transmitting node.
+        node: String,
+        /// Highest acceptable duty cycle (0-1).
+        at_most: f64,
+        /// Sliding window in seconds.
+        window_s: f64,
+    },
+    /// Final metric value lies within bounds.
+    Metric {
+        /// Metric name.
+        metric: String,
+        /// Only this node's value (all nodes are summed if None).
+        node: Option<String>,
+        /// Lowest acceptable value.
+        at_least: Option<f64>,
+        /// Highest acceptable value.
+        at_most: Option<f64>,
+    },
+}
+
+impl AssertionCheck {
+    /// Nodes the check refers to.
+    pub fn nodes(&self) -> Vec<&str> {
+        match self {
+            AssertionCheck::Delivered { from, to, .. } => vec![from, to],
+            AssertionCheck::FloodCoverage { .. } => Vec::new(),
+            AssertionCheck::DutyCycle { node, .. } => vec![node],
+            AssertionCheck::Metric { node, .. } => node.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for AssertionCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssertionCheck::Delivered { from, to, within_s } => {
+                write!(f, "DM {} -> {} delivered within {}s", from, to, within_s)
+            }
+            AssertionCheck::FloodCoverage { at_least } => {
+                write!(f, "flood coverage >= {:.1}%", at_least * 100.0)
+            }
+            AssertionCheck::DutyCycle { node, at_most, window_s } => {
+                write!(f, "{} duty cycle <= {:.2}% per {}s", node, at_most * 100.0, window_s)
+            }
+            AssertionCheck::Metric { metric, node, at_least, at_most } => {
+                write!(f, "{}", metric)?;
+                if let Some(node) = node {
+                    write!(f, " on {}", node)?;
+                }
+                if let Some(min) = at_least {
+                    write!(f, " >= {}", min)?;
+                }
+                if let Some(max) = at_most {
+                    write!(f, " <= {}", max)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// ============================================================================
+// YAML Schema Types (Internal)
+// ============================================================================
+
+/// Assertion (YAML schema, internal). Exactly one check must be given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AssertionYaml {
+    name: String,
+    #[serde(default)]
+    delivered: Option<DeliveredYaml>,
+    #[serde(default)]
+    flood_coverage: Option<FloodCoverageYaml>,
+    #[serde(default)]
+    duty_cycle: Option<DutyCycleYaml>,
+    #[serde(default)]
+    metric: Option<MetricYaml>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeliveredYaml {
+    from: String,
+    to: String,
+    within_s: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FloodCoverageYaml {
+    at_least: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DutyCycleYaml {
+    node: String,
+    at_most: f64,
+    #[serde(default)]
+    window_s: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MetricYaml {
+    name: String,
+    #[serde(default)]
+    node: Option<String>,
+    #[serde(default)]
+    at_least: Option<f64>,
+    #[serde(default)]
+    at_most: Option<f64>,
+}
+
+impl AssertionYaml {
+    pub(crate) fn resolve(&self) -> Result<Assertion, ModelError> {
+        let invalid = |reason: &str| ModelError::InvalidConfig(format!("Assertion '{}': {}", self.name, reason));
+        let fraction = |value: f64, field: &str| {
+            if (0.0..=1.0).contains(&value) {
+                Ok(value)
+            } else {
+                Err(invalid(&format!("{} must be a fraction between 0 and 1", field)))
+            }
+        };
+
+        let check = match (&self.delivered, &self.flood_coverage, &self.duty_cycle, &self.metric) {
+            (Some(d), None, None, None) => {
+                if d.within_s.is_nan() || d.within_s <= 0.0 {
+                    return Err(invalid("within_s must be positive"));
+                }
+                AssertionCheck::Delivered { from: d.from.clone(), to: d.to.clone(), within_s: d.within_s }
+            }
+            (None, Some(c), None, None) => AssertionCheck::FloodCoverage { at_least: fraction(c.at_least, "at_least")? },
+            (None, None, Some(d), None) => {
+                let window_s = d.window_s.unwrap_or(DEFAULT_DUTY_CYCLE_WINDOW_S);
+                if window_s.is_nan() || window_s <= 0.0 {
+                    return Err(invalid("window_s must be positive"));
+                }
+                AssertionCheck::DutyCycle { node: d.node.clone(), at_most: fraction(d.at_most, "at_most")?, window_s }
+            }
+            (None, None, None, Some(m)) => {
+                if m.at_least.is_none() && m.at_most.is_none() {
+                    return Err(invalid("metric needs at_least and/or at_most"));
+                }
+                AssertionCheck::Metric {
+                    metric: m.name.clone(),
+                    node: m.node.clone(),
+                    at_least: m.at_least,
+                    at_most: m.at_most,
+                }
+            }
+            _ => {
+                return Err(invalid(
+                    "exactly one of delivered, flood_coverage, duty_cycle or metric is required",
+                ))
+            }
+        };
+
+        Ok(Assertion { name: self.name.clone(), check })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(yaml: &str) -> Result<Assertion, ModelError> {
+        serde_yaml::from_str::<AssertionYaml>(yaml).unwrap().resolve()
+    }
+
+    #[test]
+    fn test_parse_assertions() {
+        let assertion = resolve("name: dm\ndelivered: { from: Alice, to: Bob, within_s: 30 }\n").unwrap();
+        assert_eq!(
+            assertion.check,
+            AssertionCheck::Delivered { from: "Alice".into(), to: "Bob".into(), within_s: 30.0 }
+        );
+        assert_eq!(assertion.check.nodes(), vec!["Alice", "Bob"]);
+
+        let assertion = resolve("name: duty\nduty_cycle: { node: R1, at_most: 0.01 }\n").unwrap();
+        assert_eq!(
+            assertion.check,
+            AssertionCheck::DutyCycle { node: "R1".into(), at_most: 0.01, window_s: DEFAULT_DUTY_CYCLE_WINDOW_S }
+        );
+        assert_eq!(assertion.check.to_string(), "R1 duty cycle <= 1.00% per 3600s");
+    }
+
+    #[test]
+    fn test_invalid_assertions_rejected() {
+        assert!(resolve("name: a\n").is_err());
+        assert!(resolve("name: a\nflood_coverage: { at_least: 90 }\n").is_err());
+        assert!(resolve("name: a\nmetric: { name: m }\n").is_err());
+        assert!(resolve("name: a\nflood_coverage: { at_least: 0.9 }\nmetric: { name: m, at_most: 1 }\n").is_err());
+    }
+
+    #[test]
+    fn test_overlay_assertions() {
+        let topology = "nodes:\n  - name: Alice\n";
+        let unknown = "assertions:\n  - { name: duty, duty_cycle: { node: Bob, at_most: 0.1 } }\n";
+        assert!(matches!(
+            crate::load_models_from_str(&[topology, unknown]),
+            Err(ModelError::NodeNotFound(name)) if name == "Bob"
+        ));
+    }
+}
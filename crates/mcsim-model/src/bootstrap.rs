@@ -0,0 +1,146 @@
+//! Address-book bootstrap: the contacts a companion starts with.
+//!
+//! A companion without an explicit `companion/contacts` list is given
+//! contacts at startup according to `companion/bootstrap`, typically set for
+//! the whole scenario in the `defaults` section:
+//!
+//! ```yaml
+//! defaults:
+//!   node:
+//!     companion:
+//!       bootstrap: neighbors
+//!       bootstrap_radius_km: 3.0
+//! ```
+//!
+//! - `full`: every other node, companions first (the default);
+//! - `neighbors`: the nodes within `companion/bootstrap_radius_km`, nearest
+//!   first;
+//! - `none`: no contacts, so they are learned from advertisements as on a
+//!   freshly deployed mesh.
+//!
+//! Every mode keeps at most `companion/auto_contacts_max` contacts. Contacts
+//! needed by scripted traffic and group actions are added regardless.
+
+use crate::actions::ActionNode;
+use crate::properties::{
+    ResolvedProperties, NodeScope, COMPANION_BOOTSTRAP, COMPANION_BOOTSTRAP_RADIUS_KM,
+};
+use crate::ModelError;
+
+/// How a companion's contact list is pre-populated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContactBootstrap {
+    /// Every other node.
+    Full,
+    /// Nodes within a distance.
+    Neighbors {
+        /// Maximum distance in kilometers.
+        radius_km: f64,
+    },
+    /// No contacts; discovery happens through advertisements.
+    None,
+}
+
+impl ContactBootstrap {
+    /// Read the bootstrap mode of the node named `node` from its properties.
+    pub fn from_properties(node: &str, props: &ResolvedProperties<NodeScope>) -> Result<Self, ModelError> {
+        let mode: String = props.get(&COMPANION_BOOTSTRAP);
+        match mode.to_lowercase().as_str() {
+            "full" => Ok(ContactBootstrap::Full),
+            "neighbors" => {
+                let radius_km: f64 = props.get(&COMPANION_BOOTSTRAP_RADIUS_KM);
+                if radius_km.is_nan() || radius_km < 0.0 {
+                    return Err(ModelError::InvalidConfig(format!(
+                        "Node '{}': companion/bootstrap_radius_km must be non-negative",
+                        node
+                    )));
+                }
+                Ok(ContactBootstrap::Neighbors { radius_km })
+            }
+            "none" => Ok(ContactBootstrap::None),
+            other => Err(ModelError::InvalidConfig(format!(
+                "Node '{}': unknown companion/bootstrap '{}' (expected 'full', 'neighbors' or 'none')",
+                node, other
+            ))),
+        }
+    }
+
+    /// Names of the contacts `node` starts with, out of `nodes`, keeping at
+    /// most `max`.
+    ///
+    /// `full` lists companions before other nodes, each sorted by name;
+    /// `neighbors` lists the nodes in range nearest first.
+    pub fn select(&self, node: &ActionNode, nodes: &[ActionNode], max: usize) -> Vec<String> {
+        let others = nodes.iter().filter(|n| n.name != node.name);
+        let mut selected: Vec<String> = match *self {
+            ContactBootstrap::Full => {
+                let mut others: Vec<&ActionNode> = others.collect();
+                others.sort_by(|a, b| {
+                    (a.firmware_type != "companion", &a.name).cmp(&(b.firmware_type != "companion", &b.name))
+                });
+                others.into_iter().map(|n| n.name.clone()).collect()
+            }
+            ContactBootstrap::Neighbors { radius_km } => {
+                let mut in_range: Vec<(f64, &ActionNode)> = others
+                    .map(|n| (node.location.distance_to(&n.location) / 1000.0, n))
+                    .filter(|(distance_km, _)| *distance_km <= radius_km)
+                    .collect();
+                in_range.sort_by(|(da, a), (db, b)| da.total_cmp(db).then(a.name.cmp(&b.name)));
+                in_range.into_iter().map(|(_, n)| n.name.clone()).collect()
+            }
+            ContactBootstrap::None => Vec::new(),
+        };
+        selected.truncate(max);
+        selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::GeoCoord;
+
+    fn node(name: &str, firmware_type: &str, latitude: f64) -> ActionNode {
+        ActionNode {
+            name: name.to_string(),
+            firmware_type: firmware_type.to_string(),
+            groups: Vec::new(),
+            location: GeoCoord { latitude, longitude: -122.0, altitude_m: None },
+        }
+    }
+
+    #[test]
+    fn test_select_contacts() {
+        // About 1.1 km per 0.01 degree of latitude
+        let nodes = [
+            node("Alice", "companion", 47.00),
+            node("R1", "repeater", 47.01),
+            node("Carol", "companion", 47.05),
+            node("Bob", "companion", 47.02),
+        ];
+        let alice = &nodes[0];
+
+        assert_eq!(ContactBootstrap::Full.select(alice, &nodes, 100), vec!["Bob", "Carol", "R1"]);
+        assert_eq!(ContactBootstrap::Full.select(alice, &nodes, 2), vec!["Bob", "Carol"]);
+        let neighbors = ContactBootstrap::Neighbors { radius_km: 3.0 };
+        assert_eq!(neighbors.select(alice, &nodes, 100), vec!["R1", "Bob"]);
+        assert!(ContactBootstrap::None.select(alice, &nodes, 100).is_empty());
+    }
+
+    #[test]
+    fn test_bootstrap_from_properties() {
+        let model = crate::load_models_from_str(&[
+            "defaults:\n  node:\n    companion: { bootstrap: neighbors, bootstrap_radius_km: 2.5 }\nnodes:\n  - name: A\n",
+        ])
+        .unwrap();
+        let props = model.nodes()["A"].properties();
+        assert_eq!(
+            ContactBootstrap::from_properties("A", props).unwrap(),
+            ContactBootstrap::Neighbors { radius_km: 2.5 }
+        );
+
+        let model = crate::load_models_from_str(&["nodes:\n  - name: A\n    companion: { bootstrap: gossip }\n"]).unwrap();
+        let props = model.nodes()["A"].properties();
+        assert!(matches!(ContactBootstrap::from_properties("A", props), Err(ModelError::InvalidConfig(_))));
+    }
+}
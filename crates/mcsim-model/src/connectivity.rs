@@ -0,0 +1,305 @@
+//! Startup connectivity analysis.
+//!
+//! Before a run starts, every edge's predicted SNR is compared against the
+//! receiver's demodulation threshold to find nodes that can't take part in the
+//! mesh at all: nodes nobody can hear, and nodes that can't hear anybody. Such
+//! a node would sit silent for the whole run, so it is reported up front along
+//! with its best candidate neighbor and how far that link falls short.
+//!
+//! The SNR used is the edge's mean SNR at 20 dBm shifted by the sender's
+//! `radio/tx_power_dbm`; fading (`link/snr_std_dev`) is ignored, so a link with
+//! a small positive margin may still be unreliable.
+
+use std::fmt;
+use std::str::FromStr;
+
+use mcsim_common::GeoCoord;
+use serde::Serialize;
+
+use crate::properties::{
+    LINK_MEAN_SNR_DB_AT20DBM, LOCATION_LATITUDE, LOCATION_LONGITUDE, RADIO_SNR_THRESHOLD_SF10_DB,
+    RADIO_SNR_THRESHOLD_SF11_DB, RADIO_SNR_THRESHOLD_SF12_DB, RADIO_SNR_THRESHOLD_SF7_DB,
+    RADIO_SNR_THRESHOLD_SF8_DB, RADIO_SNR_THRESHOLD_SF9_DB, RADIO_SPREADING_FACTOR,
+    RADIO_TX_POWER_DBM, SIMULATION_UNREACHABLE_NODES,
+};
+use crate::{Edge, Model, ModelError, Node};
+
+/// What to do with unreachable nodes found at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnreachablePolicy {
+    /// Report the nodes and continue.
+    #[default]
+    Warn,
+    /// Abort before the simulation starts.
+    Fail,
+    /// Skip the analysis.
+    Ignore,
+}
+
+impl UnreachablePolicy {
+    /// Read the policy from `simulation/unreachable_nodes`.
+    pub fn from_model(model: &Model) -> Result<Self, ModelError> {
+        model
+            .simulation_properties()
+            .get(&SIMULATION_UNREACHABLE_NODES)
+            .parse()
+            .map_err(ModelError::InvalidConfig)
+    }
+}
+
+impl FromStr for UnreachablePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "warn" => Ok(UnreachablePolicy::Warn),
+            "fail" => Ok(UnreachablePolicy::Fail),
+            "ignore" => Ok(UnreachablePolicy::Ignore),
+            other => Err(format!(
+                "Unknown unreachable_nodes policy '{}': expected warn, fail, or ignore",
+                other
+            )),
+        }
+    }
+}
+
+/// The closest candidate link partner of an unreachable node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Neighbor {
+    /// Neighbor node name.
+    pub name: String,
+    /// Great-circle distance in meters.
+    pub distance_m: f64,
+    /// Best SNR margin over the receiver's threshold in either direction, in dB.
+    /// `None` if the model has no edge between the two nodes.
+    pub margin_db: Option<f64>,
+}
+
+/// A node predicted to be cut off from the rest of the network.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnreachableNode {
+    /// Node name.
+    pub name: String,
+    /// Whether any other node is predicted to decode this node's transmissions.
+    pub heard_by_any: bool,
+    /// Whether this node is predicted to decode any other node's transmissions.
+    pub hears_any: bool,
+    /// Best candidate neighbor: the highest-margin link partner, or the
+    /// geographically nearest node if the node has no edges.
+    pub nearest: Option<Neighbor>,
+}
+
+/// Result of [`analyze_connectivity`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConnectivityReport {
+    /// Nodes that cannot send to or cannot receive from any other node.
+    pub unreachable: Vec<UnreachableNode>,
+}
+
+impl ConnectivityReport {
+    /// True if every node can both send and receive.
+    pub fn is_empty(&self) -> bool {
+        self.unreachable.is_empty()
+    }
+}
+
+impl fmt::Display for ConnectivityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} node(s) predicted unreachable from the rest of the network:",
+            self.unreachable.len()
+        )?;
+        for node in &self.unreachable {
+            let problem = match (node.heard_by_any, node.hears_any) {
+                (false, false) => "cannot send or receive",
+                (false, true) => "no node can hear it",
+                (true, false) => "cannot hear any node",
+                (true, true) => "reachable",
+            };
+            write!(f, "\n  {}: {}", node.name, problem)?;
+            match &node.nearest {
+                Some(Neighbor { name, distance_m, margin_db: Some(margin) }) => write!(
+                    f,
+                    "; best link {} ({:.2} km) at {:+.1} dB margin",
+                    name,
+                    distance_m / 1000.0,
+                    margin
+                )?,
+                Some(Neighbor { name, distance_m, margin_db: None }) => write!(
+                    f,
+                    "; no edges, nearest node {} ({:.2} km)",
+                    name,
+                    distance_m / 1000.0
+                )?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Find nodes whose predicted links leave them unable to send or receive.
+///
+/// A link is usable when its mean SNR, adjusted for the sender's TX power,
+/// meets the receiver's spreading-factor threshold. Models with fewer than two
+/// nodes have no network to reach and always produce an empty report.
+pub fn analyze_connectivity(model: &Model) -> ConnectivityReport {
+    let nodes = model.nodes();
+    if nodes.len() < 2 {
+        return ConnectivityReport::default();
+    }
+
+    let mut unreachable = Vec::new();
+    for (name, node) in nodes {
+        let mut heard_by_any = false;
+        let mut hears_any = false;
+        // (neighbor, best margin) over edges touching this node
+        let mut best: Option<(&str, f64)> = None;
+
+        for ((from, to), edge) in model.edges() {
+            let (neighbor, outbound) = if from == name {
+                (to, true)
+            } else if to == name {
+                (from, false)
+            } else {
+                continue;
+            };
+            let (Some(sender), Some(receiver)) = (nodes.get(from), nodes.get(to)) else {
+                continue;
+            };
+            let (snr_db, threshold_db) = link_budget(model, sender, receiver, edge);
+            let margin_db = snr_db - threshold_db;
+
+            if margin_db >= 0.0 {
+                if outbound {
+                    heard_by_any = true;
+                } else {
+                    hears_any = true;
+                }
+            }
+            if best.is_none_or(|(_, m)| margin_db > m) {
+                best = Some((neighbor.as_str(), margin_db));
+            }
+        }
+
+        if heard_by_any && hears_any {
+            continue;
+        }
+
+        let location = location_of(node);
+        let nearest = match best {
+            Some((neighbor, margin_db)) => Some(Neighbor {
+                name: neighbor.to_string(),
+                distance_m: location.distance_to(&location_of(&nodes[neighbor])),
+                margin_db: Some(margin_db),
+            }),
+            None => nodes
+                .iter()
+                .filter(|(other, _)| *other != name)
+                .map(|(other, n)| (other, location.distance_to(&location_of(n))))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(other, distance_m)| Neighbor {
+                    name: other.clone(),
+                    distance_m,
+                    margin_db: None,
+                }),
+        };
+
+        unreachable.push(UnreachableNode {
+            name: name.clone(),
+            heard_by_any,
+            hears_any,
+            nearest,
+        });
+    }
+
+    ConnectivityReport { unreachable }
+}
+
+/// Predicted mean SNR of an edge, adjusted for the sender's TX power, and
+/// the receiver's demodulation threshold, both in dB.
+pub(crate) fn link_budget(model: &Model, sender: &Node, receiver: &Node, edge: &Edge) -> (f64, f64) {
+    let sim_props = model.simulation_properties();
+    let tx_power_dbm = sender.properties().get(&RADIO_TX_POWER_DBM) as f64;
+    let snr_db = edge.properties().get(&LINK_MEAN_SNR_DB_AT20DBM) + (tx_power_dbm - 20.0);
+    let threshold_db = match receiver.properties().get(&RADIO_SPREADING_FACTOR) {
+        ..=7 => sim_props.get(&RADIO_SNR_THRESHOLD_SF7_DB),
+        8 => sim_props.get(&RADIO_SNR_THRESHOLD_SF8_DB),
+        9 => sim_props.get(&RADIO_SNR_THRESHOLD_SF9_DB),
+        10 => sim_props.get(&RADIO_SNR_THRESHOLD_SF10_DB),
+        11 => sim_props.get(&RADIO_SNR_THRESHOLD_SF11_DB),
+        _ => sim_props.get(&RADIO_SNR_THRESHOLD_SF12_DB),
+    };
+    (snr_db, threshold_db)
+}
+
+fn location_of(node: &Node) -> GeoCoord {
+    let props = node.properties();
+    GeoCoord::new(props.get(&LOCATION_LATITUDE), props.get(&LOCATION_LONGITUDE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_model_from_str;
+
+    const MODEL: &str = r#"
+defaults:
+  node:
+    radio:
+      spreading_factor: 7
+nodes:
+  - name: "A"
+    location: { lat: 47.60, lon: -122.40 }
+    firmware: { type: Repeater }
+  - name: "B"
+    location: { lat: 47.61, lon: -122.40 }
+    firmware: { type: Repeater }
+  - name: "C"
+    location: { lat: 47.70, lon: -122.40 }
+    firmware: { type: Repeater }
+    radio: { tx_power_dbm: 10 }
+  - name: "D"
+    location: { lat: 47.00, lon: -122.40 }
+    firmware: { type: Repeater }
+edges:
+  - { from: "A", to: "B", mean_snr_db_at20dbm: 10.0 }
+  - { from: "B", to: "A", mean_snr_db_at20dbm: 10.0 }
+  - { from: "B", to: "C", mean_snr_db_at20dbm: -5.0 }
+  - { from: "C", to: "B", mean_snr_db_at20dbm: -5.0 }
+"#;
+
+    #[test]
+    fn test_unreachable_nodes_reported() {
+        let model = load_model_from_str(MODEL).unwrap();
+        let report = analyze_connectivity(&model);
+        let names: Vec<&str> = report.unreachable.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["C", "D"]);
+
+        // C hears B (-5 dB vs -7.5 dB threshold) but its 10 dBm signal doesn't reach B
+        let c = &report.unreachable[0];
+        assert!(c.hears_any && !c.heard_by_any);
+        let nearest = c.nearest.as_ref().unwrap();
+        assert_eq!(nearest.name, "B");
+        assert!((nearest.margin_db.unwrap() - 2.5).abs() < 1e-9);
+
+        // D has no edges, so the geographically nearest node is reported
+        let d = &report.unreachable[1];
+        assert!(!d.hears_any && !d.heard_by_any);
+        assert_eq!(d.nearest.as_ref().unwrap().name, "A");
+        assert_eq!(d.nearest.as_ref().unwrap().margin_db, None);
+
+        let text = report.to_string();
+        assert!(text.contains("C: no node can hear it; best link B (10.01 km) at +2.5 dB margin"));
+        assert!(text.contains("D: cannot send or receive; no edges, nearest node A"));
+    }
+
+    #[test]
+    fn test_policy_parsing() {
+        let model = load_model_from_str(MODEL).unwrap();
+        assert_eq!(UnreachablePolicy::from_model(&model).unwrap(), UnreachablePolicy::Warn);
+        assert_eq!("FAIL".parse::<UnreachablePolicy>(), Ok(UnreachablePolicy::Fail));
+        assert!("abort".parse::<UnreachablePolicy>().is_err());
+    }
+}
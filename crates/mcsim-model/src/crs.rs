@@ -0,0 +1,321 @@
+//! Projected coordinate reference systems.
+//!
+//! Site surveys often deliver node positions as projected coordinates
+//! (eastings and northings in metres) rather than latitude and longitude.
+//! Instead of converting them by hand, a scenario names the projection with
+//! an EPSG code and gives each node `location/x_m` and `location/y_m`:
+//!
+//! ```yaml
+//! simulation:
+//!   location:
+//!     crs: EPSG:32610
+//! nodes:
+//!   - name: Hilltop
+//!     location: { x_m: 550214.3, y_m: 5272791.8, altitude_m: 120 }
+//! ```
+//!
+//! Projected positions are converted to WGS84 latitude and longitude when
+//! the model is loaded, so DEM lookups, ITM predictions and everything else
+//! that reads `location/latitude` and `location/longitude` sees geographic
+//! coordinates. Other coordinates in the scenario (mobility waypoints,
+//! outage areas) are always latitude and longitude.
+//!
+//! Supported systems are the UTM zones on WGS84 (EPSG:326xx north, 327xx
+//! south), NAD83 (EPSG:269xx) and ETRS89 (EPSG:258xx), and Web Mercator
+//! (EPSG:3857). NAD83 and ETRS89 are taken to coincide with WGS84; the
+//! datums differ by around a metre, well below what the propagation models
+//! resolve.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::properties::{ResolvedProperties, SimulationScope};
+use crate::{properties, ModelError, Node};
+
+/// WGS84 semi-major axis in metres.
+const WGS84_A: f64 = 6_378_137.0;
+
+/// WGS84 flattening.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// UTM scale factor on the central meridian.
+const UTM_K0: f64 = 0.9996;
+
+/// UTM false easting in metres.
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+
+/// UTM false northing in the southern hemisphere, in metres.
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+
+/// A coordinate reference system positions can be given in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crs {
+    /// Latitude and longitude in degrees (EPSG:4326).
+    Wgs84,
+    /// A UTM zone (1-60) on the WGS84 ellipsoid.
+    Utm {
+        /// EPSG code, kept for display.
+        epsg: u32,
+        /// Zone number.
+        zone: u8,
+        /// Northern (true) or southern hemisphere.
+        north: bool,
+    },
+    /// Spherical Web Mercator (EPSG:3857).
+    WebMercator,
+}
+
+impl Crs {
+    /// Look up a CRS by EPSG code.
+    pub fn from_epsg(code: u32) -> Option<Self> {
+        let utm = |zone: u32, north: bool| Crs::Utm { epsg: code, zone: zone as u8, north };
+        match code {
+            4326 => Some(Crs::Wgs84),
+            3857 => Some(Crs::WebMercator),
+            32601..=32660 => Some(utm(code - 32600, true)),
+            32701..=32760 => Some(utm(code - 32700, false)),
+            // NAD83 / UTM zones 1N-23N
+            26901..=26923 => Some(utm(code - 26900, true)),
+            // ETRS89 / UTM zones 28N-38N
+            25828..=25838 => Some(utm(code - 25800, true)),
+            _ => None,
+        }
+    }
+
+    /// EPSG code of the CRS.
+    pub fn epsg(&self) -> u32 {
+        match self {
+            Crs::Wgs84 => 4326,
+            Crs::Utm { epsg, .. } => *epsg,
+            Crs::WebMercator => 3857,
+        }
+    }
+
+    /// Whether positions are projected (in metres) rather than degrees.
+    pub fn is_projected(&self) -> bool {
+        !matches!(self, Crs::Wgs84)
+    }
+
+    /// Convert a position to WGS84 (latitude, longitude) in degrees. For
+    /// [`Crs::Wgs84`], `x` is the longitude and `y` the latitude.
+    pub fn to_wgs84(&self, x: f64, y: f64) -> (f64, f64) {
+        match *self {
+            Crs::Wgs84 => (y, x),
+            Crs::Utm { zone, north, .. } => {
+                let northing = if north { y } else { y - UTM_FALSE_NORTHING_SOUTH };
+                TransverseMercator::utm(zone).inverse(x - UTM_FALSE_EASTING, northing)
+            }
+            Crs::WebMercator => {
+                let lat = (y / WGS84_A).sinh().atan().to_degrees();
+                (lat, (x / WGS84_A).to_degrees())
+            }
+        }
+    }
+
+    /// Convert WGS84 latitude and longitude in degrees to a position in
+    /// this CRS, as (x, y).
+    pub fn from_wgs84(&self, lat: f64, lon: f64) -> (f64, f64) {
+        match *self {
+            Crs::Wgs84 => (lon, lat),
+            Crs::Utm { zone, north, .. } => {
+                let (x, y) = TransverseMercator::utm(zone).forward(lat, lon);
+                (x + UTM_FALSE_EASTING, if north { y } else { y + UTM_FALSE_NORTHING_SOUTH })
+            }
+            Crs::WebMercator => {
+                let y = lat.to_radians().tan().asinh() * WGS84_A;
+                (lon.to_radians() * WGS84_A, y)
+            }
+        }
+    }
+}
+
+impl FromStr for Crs {
+    type Err = ModelError;
+
+    /// Parse `EPSG:<code>` (or a bare code).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim();
+        let code = code
+            .get(..5)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("epsg:"))
+            .map_or(code, |_| &code[5..]);
+        code.parse()
+            .ok()
+            .and_then(Crs::from_epsg)
+            .ok_or_else(|| ModelError::InvalidConfig(format!("Unsupported coordinate reference system '{}'", s)))
+    }
+}
+
+impl fmt::Display for Crs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EPSG:{}", self.epsg())
+    }
+}
+
+/// Replace the projected positions of nodes with latitude and longitude.
+pub(crate) fn project_node_locations(
+    nodes: &mut BTreeMap<String, Node>,
+    simulation: &ResolvedProperties<SimulationScope>,
+) -> Result<(), ModelError> {
+    let crs: Crs = simulation.get(&properties::LOCATION_CRS).parse()?;
+    for node in nodes.values_mut() {
+        let x: Option<f64> = node.properties.get(&properties::LOCATION_X_M);
+        let y: Option<f64> = node.properties.get(&properties::LOCATION_Y_M);
+        let (x, y) = match (x, y) {
+            (None, None) => continue,
+            (Some(x), Some(y)) => (x, y),
+            _ => {
+                return Err(ModelError::InvalidConfig(format!(
+                    "Node '{}': location/x_m and location/y_m must be given together",
+                    node.name
+                )))
+            }
+        };
+        if !crs.is_projected() {
+            return Err(ModelError::InvalidConfig(format!(
+                "Node '{}' has a projected location but simulation location/crs is not set to a projected CRS",
+                node.name
+            )));
+        }
+        let (lat, lon) = crs.to_wgs84(x, y);
+        if !(-90.0..=90.0).contains(&lat) || !lat.is_finite() || !lon.is_finite() {
+            return Err(ModelError::InvalidConfig(format!(
+                "Node '{}': ({}, {}) is outside {}",
+                node.name, x, y, crs
+            )));
+        }
+        log::debug!("Node '{}': ({}, {}) in {} is {:.6}, {:.6}", node.name, x, y, crs, lat, lon);
+        node.properties
+            .set(&properties::LOCATION_LATITUDE, lat)
+            .and_then(|_| node.properties.set(&properties::LOCATION_LONGITUDE, lon))
+            .map_err(|e| ModelError::InvalidConfig(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Transverse Mercator projection of the WGS84 ellipsoid, by Krüger's
+/// series to third order in n (millimetre accuracy within a UTM zone).
+struct TransverseMercator {
+    /// Central meridian in degrees.
+    lon0: f64,
+    /// Scaled rectifying radius, k0 * A.
+    k0_a: f64,
+    n: f64,
+    alpha: [f64; 3],
+    beta: [f64; 3],
+    delta: [f64; 3],
+}
+
+impl TransverseMercator {
+    /// The projection of a UTM zone, without false easting or northing.
+    fn utm(zone: u8) -> Self {
+        let n = WGS84_F / (2.0 - WGS84_F);
+        let (n2, n3) = (n * n, n * n * n);
+        TransverseMercator {
+            lon0: f64::from(zone) * 6.0 - 183.0,
+            k0_a: UTM_K0 * WGS84_A / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0),
+            n,
+            alpha: [n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0, 13.0 * n2 / 48.0 - 3.0 * n3 / 5.0, 61.0 * n3 / 240.0],
+            beta: [n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0, n2 / 48.0 + n3 / 15.0, 17.0 * n3 / 480.0],
+            delta: [2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3, 7.0 * n2 / 3.0 - 8.0 * n3 / 5.0, 56.0 * n3 / 15.0],
+        }
+    }
+
+    /// Project (latitude, longitude) in degrees to (x, y) in metres.
+    fn forward(&self, lat: f64, lon: f64) -> (f64, f64) {
+        let phi = lat.to_radians();
+        let dlon = (lon - self.lon0).to_radians();
+        let c = 2.0 * self.n.sqrt() / (1.0 + self.n);
+        let t = (phi.sin().atanh() - c * (c * phi.sin()).atanh()).sinh();
+        let xi_p = t.atan2(dlon.cos());
+        let eta_p = (dlon.sin() / (1.0 + t * t).sqrt()).atanh();
+        let (mut xi, mut eta) = (xi_p, eta_p);
+        for (j, alpha) in self.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi += alpha * (k * xi_p).sin() * (k * eta_p).cosh();
+            eta += alpha * (k * xi_p).cos() * (k * eta_p).sinh();
+        }
+        (self.k0_a * eta, self.k0_a * xi)
+    }
+
+    /// Unproject (x, y) in metres to (latitude, longitude) in degrees.
+    fn inverse(&self, x: f64, y: f64) -> (f64, f64) {
+        let xi = y / self.k0_a;
+        let eta = x / self.k0_a;
+        let (mut xi_p, mut eta_p) = (xi, eta);
+        for (j, beta) in self.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_p -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_p -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+        let chi = (xi_p.sin() / eta_p.cosh()).asin();
+        let mut phi = chi;
+        for (j, delta) in self.delta.iter().enumerate() {
+            phi += delta * (2.0 * (j + 1) as f64 * chi).sin();
+        }
+        let dlon = eta_p.sinh().atan2(xi_p.cos());
+        (phi.to_degrees(), self.lon0 + dlon.to_degrees())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utm_conversion() {
+        let zone17: Crs = "EPSG:32617".parse().unwrap();
+        assert_eq!(zone17, Crs::Utm { epsg: 32617, zone: 17, north: true });
+        // CN Tower, 17T 630084 4833438
+        let (lat, lon) = zone17.to_wgs84(630_084.0, 4_833_438.0);
+        assert!((lat - 43.642_567).abs() < 2e-5, "{}", lat);
+        assert!((lon - -79.387_139).abs() < 2e-5, "{}", lon);
+
+        // Equator on the central meridian
+        let (x, y) = "32610".parse::<Crs>().unwrap().from_wgs84(0.0, -123.0);
+        assert!((x - 500_000.0).abs() < 1e-6 && y.abs() < 1e-6);
+
+        // Round trips, including the southern hemisphere and zone edges
+        for (epsg, lat, lon) in [(32610, 47.6062, -122.3321), (32756, -33.8568, 151.2153), (32633, 60.0, 17.9)] {
+            let crs = Crs::from_epsg(epsg).unwrap();
+            let (x, y) = crs.from_wgs84(lat, lon);
+            let (lat2, lon2) = crs.to_wgs84(x, y);
+            assert!((lat - lat2).abs() < 1e-8 && (lon - lon2).abs() < 1e-8, "{} {} {}", epsg, lat2, lon2);
+        }
+    }
+
+    #[test]
+    fn test_projected_node_locations() {
+        let model = crate::load_model_from_str(
+            "simulation:\n  location:\n    crs: EPSG:32617\nnodes:\n  - name: Tower\n    location: { easting_m: 630084, northing_m: 4833438, altitude_m: 553 }\n    firmware: { type: Repeater }\n  - name: Plain\n    location: { lat: 43.6, lon: -79.4 }\n    firmware: { type: Repeater }\n",
+        )
+        .unwrap();
+        let tower = model.nodes().get("Tower").unwrap().properties();
+        let lat: f64 = tower.get(&properties::LOCATION_LATITUDE);
+        let lon: f64 = tower.get(&properties::LOCATION_LONGITUDE);
+        assert!((lat - 43.642_567).abs() < 2e-5 && (lon - -79.387_139).abs() < 2e-5);
+        let plain: f64 = model.nodes().get("Plain").unwrap().properties().get(&properties::LOCATION_LATITUDE);
+        assert_eq!(plain, 43.6);
+
+        // Projected positions need a projected CRS and both coordinates
+        assert!(crate::load_model_from_str("nodes:\n  - name: A\n    location: { x_m: 1, y_m: 2 }\n").is_err());
+        assert!(crate::load_model_from_str(
+            "simulation:\n  location:\n    crs: EPSG:32617\nnodes:\n  - name: A\n    location: { x_m: 1 }\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_web_mercator_and_unsupported() {
+        let crs: Crs = "epsg:3857".parse().unwrap();
+        let (lat, lon) = crs.to_wgs84(-13_617_000.0, 6_041_000.0);
+        let (x, y) = crs.from_wgs84(lat, lon);
+        assert!((x - -13_617_000.0).abs() < 1e-6 && (y - 6_041_000.0).abs() < 1e-6);
+        assert!((lon - -122.33).abs() < 0.01 && (lat - 47.61).abs() < 0.01);
+
+        assert!(!Crs::Wgs84.is_projected());
+        assert!("EPSG:2193".parse::<Crs>().is_err());
+        assert!("UTM10".parse::<Crs>().is_err());
+    }
+}
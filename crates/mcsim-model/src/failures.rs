@@ -0,0 +1,115 @@
+//! Failure domains and scheduled domain failures.
+//!
+//! Nodes that fail together (sharing a mast, a power source or a site) are
+//! tagged with the same failure domain through the `failure/domains` node
+//! property. The `failures` section powers off every node of a domain at
+//! once:
+//!
+//! ```yaml
+//! nodes:
+//!   - name: Repeater1
+//!     failure:
+//!       domains: [north-mast, grid-a]
+//!
+//! failures:
+//!   - domain: north-mast
+//!     at_s: 3600
+//! ```
+//!
+//! A powered-off node stops transmitting and receiving for the rest of the
+//! run. `mcsim blast-radius` fails each domain in turn to quantify its
+//! impact on delivery.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{properties, Model, ModelError};
+
+/// Scheduled failure of a whole failure domain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainFailure {
+    /// Name of the failing domain.
+    pub domain: String,
+    /// Simulation time (seconds) at which its nodes power off.
+    pub at_s: f64,
+}
+
+/// Group the model's nodes by failure domain.
+///
+/// Returns the node names of each domain, both sorted by name.
+pub fn failure_domains(model: &Model) -> BTreeMap<String, Vec<String>> {
+    let mut domains: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (name, node) in model.nodes() {
+        let node_domains: Vec<String> = node.properties().get(&properties::FAILURE_DOMAINS);
+        for domain in node_domains {
+            domains.entry(domain).or_default().push(name.clone());
+        }
+    }
+    domains
+}
+
+// ============================================================================
+// YAML Schema Types (Internal)
+// ============================================================================
+
+/// Domain failure (YAML schema, internal).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DomainFailureYaml {
+    domain: String,
+    #[serde(default)]
+    at_s: f64,
+}
+
+impl DomainFailureYaml {
+    pub(crate) fn resolve(&self) -> Result<DomainFailure, ModelError> {
+        if self.at_s.is_nan() || self.at_s < 0.0 {
+            return Err(ModelError::InvalidConfig(format!(
+                "Failure of domain '{}': at_s must be non-negative",
+                self.domain
+            )));
+        }
+        Ok(DomainFailure { domain: self.domain.clone(), at_s: self.at_s })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGY: &str = "\
+nodes:
+  - name: R1
+    failure: { domains: [mast] }
+  - name: R2
+    failure: { domains: [mast, grid] }
+  - name: C1
+";
+
+    #[test]
+    fn test_failure_domains() {
+        let overlay = "failures:\n  - { domain: mast, at_s: 60 }\n";
+        let model = crate::load_models_from_str(&[TOPOLOGY, overlay]).unwrap();
+        assert_eq!(model.failures(), &[DomainFailure { domain: "mast".into(), at_s: 60.0 }]);
+
+        let domains = failure_domains(&model);
+        assert_eq!(domains.len(), 2);
+        assert_eq!(domains["mast"], vec!["R1", "R2"]);
+        assert_eq!(domains["grid"], vec!["R2"]);
+    }
+
+    #[test]
+    fn test_invalid_failures_rejected() {
+        for overlay in [
+            "failures:\n  - { domain: mast, at_s: -1 }\n",
+            "failures:\n  - { domain: unknown, at_s: 60 }\n",
+        ] {
+            assert!(
+                matches!(crate::load_models_from_str(&[TOPOLOGY, overlay]), Err(ModelError::InvalidConfig(_))),
+                "{}",
+                overlay
+            );
+        }
+    }
+}
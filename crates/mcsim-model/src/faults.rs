@@ -0,0 +1,172 @@
+//! Packet-level fault injection rules.
+//!
+//! The `faults` section drops or corrupts chosen packets on chosen links,
+//! for deterministic, targeted loss when testing retry and path-rebuild
+//! logic in firmware:
+//!
+//! ```yaml
+//! faults:
+//!   # Drop 20% of packets from A to B between t=60s and t=120s
+//!   - from: A
+//!     to: B
+//!     action: drop
+//!     probability: 0.2
+//!     at_s: 60
+//!     duration_s: 60
+//!   # Corrupt every 10th packet on the link between B and C, both ways
+//!   - from: B
+//!     to: C
+//!     bidirectional: true
+//!     action: corrupt
+//!     every: 10
+//! ```
+//!
+//! `from` and `to` name the transmitting and receiving nodes; leaving one
+//! out matches any node. Without `probability` or `every` every matching
+//! packet is hit, and without `duration_s` the rule applies for the rest of
+//! the run. See [`mcsim_lora::faults`] for how dropped and corrupted packets
+//! are received.
+
+use mcsim_lora::faults::FaultSelection;
+use mcsim_lora::InjectedFault;
+use serde::{Deserialize, Serialize};
+
+use crate::ModelError;
+
+/// A fault injection rule on packets between nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketFault {
+    /// Transmitting node, or any node.
+    pub from: Option<String>,
+    /// Receiving node, or any node.
+    pub to: Option<String>,
+    /// Whether the rule also applies from `to` to `from`.
+    pub bidirectional: bool,
+    /// What happens to a selected packet.
+    pub action: InjectedFault,
+    /// Which matching packets are selected.
+    pub selection: FaultSelection,
+    /// Simulation time (seconds) at which the rule starts applying.
+    pub at_s: f64,
+    /// How long it applies (seconds), or None for the rest of the run.
+    pub duration_s: Option<f64>,
+}
+
+impl PacketFault {
+    /// Names of the nodes the rule refers to.
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.from.iter().chain(&self.to).map(String::as_str)
+    }
+
+    /// Description of the link for messages (`A -> B`, `A <-> B`, `any -> B`).
+    pub fn link(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.from.as_deref().unwrap_or("any"),
+            if self.bidirectional { "<->" } else { "->" },
+            self.to.as_deref().unwrap_or("any")
+        )
+    }
+}
+
+// ============================================================================
+// YAML Schema Types (Internal)
+// ============================================================================
+
+/// Fault injection rule (YAML schema, internal).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PacketFaultYaml {
+    #[serde(default)]
+    from: Option<String>,
+    #[serde(default)]
+    to: Option<String>,
+    #[serde(default)]
+    bidirectional: bool,
+    action: InjectedFault,
+    #[serde(default)]
+    probability: Option<f64>,
+    #[serde(default)]
+    every: Option<u64>,
+    #[serde(default)]
+    at_s: f64,
+    #[serde(default)]
+    duration_s: Option<f64>,
+}
+
+impl PacketFaultYaml {
+    pub(crate) fn resolve(&self) -> Result<PacketFault, ModelError> {
+        let mut fault = PacketFault {
+            from: self.from.clone(),
+            to: self.to.clone(),
+            bidirectional: self.bidirectional,
+            action: self.action,
+            selection: FaultSelection::Probability(1.0),
+            at_s: self.at_s,
+            duration_s: self.duration_s,
+        };
+        let link = fault.link();
+        let invalid = |reason: &str| ModelError::InvalidConfig(format!("Fault on {}: {}", link, reason));
+        if self.at_s.is_nan() || self.at_s < 0.0 {
+            return Err(invalid("at_s must be non-negative"));
+        }
+        if self.duration_s.is_some_and(|d| d.is_nan() || d <= 0.0) {
+            return Err(invalid("duration_s must be positive"));
+        }
+        if self.from.is_some() && self.from == self.to {
+            return Err(invalid("from and to must be different nodes"));
+        }
+        fault.selection = match (self.probability, self.every) {
+            (Some(_), Some(_)) => return Err(invalid("give either probability or every, not both")),
+            (Some(p), None) if !(p > 0.0 && p <= 1.0) => return Err(invalid("probability must be in (0, 1]")),
+            (Some(p), None) => FaultSelection::Probability(p),
+            (None, Some(0)) => return Err(invalid("every must be at least 1")),
+            (None, Some(n)) => FaultSelection::Every(n),
+            (None, None) => FaultSelection::Probability(1.0),
+        };
+        Ok(fault)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGY: &str = "nodes:\n  - name: A\n  - name: B\n  - name: C\n";
+
+    #[test]
+    fn test_packet_faults() {
+        let overlay = "\
+faults:
+  - { from: A, to: B, action: drop, probability: 0.2, at_s: 60, duration_s: 60 }
+  - { from: B, to: C, bidirectional: true, action: corrupt, every: 10 }
+  - { to: C, action: drop }
+";
+        let model = crate::load_models_from_str(&[TOPOLOGY, overlay]).unwrap();
+        let faults = model.faults();
+        assert_eq!(faults.len(), 3);
+        assert_eq!(faults[0].selection, FaultSelection::Probability(0.2));
+        assert_eq!(faults[0].duration_s, Some(60.0));
+        assert_eq!(faults[1].action, InjectedFault::Corrupt);
+        assert_eq!(faults[1].selection, FaultSelection::Every(10));
+        assert_eq!(faults[1].link(), "B <-> C");
+        assert_eq!(faults[2].link(), "any -> C");
+        assert_eq!(faults[2].selection, FaultSelection::Probability(1.0));
+    }
+
+    #[test]
+    fn test_invalid_faults_rejected() {
+        for rule in [
+            "{ from: A, to: B, action: delay }",
+            "{ from: A, to: B, action: drop, probability: 0 }",
+            "{ from: A, to: B, action: drop, probability: 0.5, every: 2 }",
+            "{ from: A, to: B, action: drop, every: 0 }",
+            "{ from: A, to: A, action: drop }",
+            "{ from: A, to: B, action: drop, duration_s: 0 }",
+            "{ from: A, to: D, action: drop }",
+        ] {
+            let overlay = format!("faults:\n  - {}\n", rule);
+            assert!(crate::load_models_from_str(&[TOPOLOGY, &overlay]).is_err(), "{}", rule);
+        }
+    }
+}
@@ -5,6 +5,7 @@
 //! - [`KeyConfig`] - Configuration containing private and public key specifications
 //! - [`GeneratedKeypair`] - Result of key generation containing the actual key bytes
 //! - [`generate_keypair_with_spec`] - Function to generate keypairs based on specifications
+//! - [`AssignedKeys`] - Tracks assigned public keys to catch duplicates across nodes
 //!
 //! ## Key Specification Modes
 //!
@@ -15,6 +16,7 @@
 
 use crate::ModelError;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 
 /// Default maximum number of attempts to generate a keypair with a matching prefix.
 pub const DEFAULT_MAX_KEY_GENERATION_ATTEMPTS: u32 = 1_000_000;
@@ -295,6 +297,78 @@ pub fn generate_keypair_with_spec<R: rand::Rng>(
     generate_keypair(seed, key_config, node_name, None).map(|r| r.keypair)
 }
 
+/// Public keys already assigned to nodes.
+///
+/// The public key doubles as the node's [`NodeId`](mcsim_common::NodeId), so two
+/// nodes sharing a key (typically an exact key copy/pasted between YAML entries)
+/// would be indistinguishable on the mesh.
+#[derive(Debug, Clone, Default)]
+pub struct AssignedKeys {
+    owners: BTreeMap<[u8; 32], String>,
+}
+
+impl AssignedKeys {
+    /// Create an empty set of assigned keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name of the node that owns `public_key`, if it has been assigned.
+    pub fn owner(&self, public_key: &[u8; 32]) -> Option<&str> {
+        self.owners.get(public_key).map(|s| s.as_str())
+    }
+
+    /// Record `public_key` as belonging to `node_name`.
+    ///
+    /// Returns [`ModelError::DuplicateKey`] if another node already owns the key.
+    pub fn insert(&mut self, node_name: &str, public_key: [u8; 32]) -> Result<(), ModelError> {
+        if let Some(existing) = self.owner(&public_key) {
+            return Err(ModelError::DuplicateKey {
+                first: existing.to_string(),
+                second: node_name.to_string(),
+                public_key: hex::encode(&public_key[..8]),
+            });
+        }
+        self.owners.insert(public_key, node_name.to_string());
+        Ok(())
+    }
+
+    /// Generate a keypair for `node_name` and record it.
+    ///
+    /// If the generated public key is already owned by another node, this returns
+    /// [`ModelError::DuplicateKey`] unless `regenerate_duplicates` is set, in which
+    /// case a warning is logged and a random keypair is generated instead, giving
+    /// up with [`ModelError::DuplicateKey`] after
+    /// [`DEFAULT_MAX_KEY_GENERATION_ATTEMPTS`] collisions.
+    pub fn generate<R: rand::Rng>(
+        &mut self,
+        rng: &mut R,
+        key_config: &KeyConfig,
+        node_name: &str,
+        regenerate_duplicates: bool,
+    ) -> Result<GeneratedKeypair, ModelError> {
+        let mut keypair = generate_keypair_with_spec(rng, key_config, node_name)?;
+        if let Some(existing) = self.owner(&keypair.public_key) {
+            if !regenerate_duplicates {
+                return self.insert(node_name, keypair.public_key).map(|_| keypair);
+            }
+            log::warn!(
+                "Node '{}' has the same public key ({}) as node '{}'; generating a random key instead",
+                node_name,
+                hex::encode(&keypair.public_key[..8]),
+                existing
+            );
+            let mut attempts = 0;
+            while self.owner(&keypair.public_key).is_some() && attempts < DEFAULT_MAX_KEY_GENERATION_ATTEMPTS {
+                keypair = generate_keypair_with_spec(rng, &KeyConfig::default(), node_name)?;
+                attempts += 1;
+            }
+        }
+        self.insert(node_name, keypair.public_key)?;
+        Ok(keypair)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -516,6 +590,44 @@ mod tests {
         assert!(matches!(config.public_key, KeySpec::Random));
     }
 
+    #[test]
+    fn test_assigned_keys_rejects_duplicate_exact_key() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let hex_key = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+        let config = KeyConfig {
+            private_key: KeySpec::parse(hex_key).unwrap(),
+            public_key: KeySpec::Random,
+        };
+        let mut assigned = AssignedKeys::new();
+        assigned.generate(&mut rng, &config, "Alice", false).unwrap();
+
+        let err = assigned.generate(&mut rng, &config, "Bob", false).unwrap_err();
+        match err {
+            ModelError::DuplicateKey { first, second, .. } => {
+                assert_eq!(first, "Alice");
+                assert_eq!(second, "Bob");
+            }
+            other => panic!("Expected DuplicateKey, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_assigned_keys_regenerates_duplicate() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let hex_key = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+        let config = KeyConfig {
+            private_key: KeySpec::parse(hex_key).unwrap(),
+            public_key: KeySpec::Random,
+        };
+        let mut assigned = AssignedKeys::new();
+        let alice = assigned.generate(&mut rng, &config, "Alice", true).unwrap();
+        let bob = assigned.generate(&mut rng, &config, "Bob", true).unwrap();
+
+        assert_ne!(alice.public_key, bob.public_key);
+        assert_eq!(assigned.owner(&alice.public_key), Some("Alice"));
+        assert_eq!(assigned.owner(&bob.public_key), Some("Bob"));
+    }
+
     #[test]
     fn test_keyspec_parse_whitespace_handling() {
         // Should handle leading/trailing whitespace
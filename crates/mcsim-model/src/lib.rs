@@ -18,17 +18,48 @@
 //!
 //! Properties are resolved in order: built-in → defaults → explicit values.
 
+pub mod actions;
+pub mod alerts;
+pub mod assertions;
+pub mod bootstrap;
+pub mod connectivity;
+pub mod crs;
+pub mod failures;
+pub mod faults;
 pub mod keys;
+pub mod lifecycle;
+pub mod mobility;
+pub mod outages;
 pub mod properties;
-pub use keys::{generate_keypair, generate_keypair_with_spec, GeneratedKeypair, KeyConfig, KeygenResult, KeySpec, DEFAULT_MAX_KEY_GENERATION_ATTEMPTS};
+pub mod sweep;
+pub mod topology;
+pub mod traffic;
+pub mod validate;
+pub use actions::{ActionNode, GroupAction, SendDirectMessage};
+pub use alerts::{AlertAction, AlertCondition, AlertRule};
+pub use assertions::{Assertion, AssertionCheck};
+pub use bootstrap::ContactBootstrap;
+pub use failures::{failure_domains, DomainFailure};
+pub use faults::PacketFault;
+pub use crs::Crs;
+pub use connectivity::{analyze_connectivity, ConnectivityReport, Neighbor, UnreachableNode, UnreachablePolicy};
+pub use lifecycle::{LifecycleAction, LifecycleEvent};
+pub use mobility::NodeMobility;
+pub use outages::PowerOutage;
+pub use sweep::{SweepAxis, SweepPoint};
+pub use traffic::{TrafficMessage, TrafficRule, TrafficSource, TrafficTrigger};
+pub use topology::{analyze_topology, ArticulationPoint, NodeCentrality, TopologyReport};
+pub use validate::{predict_links, validate_simulation, FirmwareLibrary, LinkPrediction, ValidationReport};
+pub use keys::{generate_keypair, generate_keypair_with_spec, AssignedKeys, GeneratedKeypair, KeyConfig, KeygenResult, KeySpec, DEFAULT_MAX_KEY_GENERATION_ATTEMPTS};
 pub use properties::{
-    default_value, get_property_def, properties_by_scope, PropertyDef,
+    default_value, get_property_def, properties_by_scope, search_properties, PropertyDef,
     PropertyScope, PropertyValue, ResolvedProperties,
     UnresolvedProperties, NodeScope, EdgeScope, SimulationScope, ScopeMarker,
     PropertyType, PropertyBaseType, Property, FromPropertyValue,
     // Property constants
-    RADIO_FREQUENCY_HZ, RADIO_BANDWIDTH_HZ, RADIO_SPREADING_FACTOR, RADIO_CODING_RATE, RADIO_TX_POWER_DBM,
-    COMPANION_CHANNELS, COMPANION_CONTACTS, COMPANION_AUTO_CONTACTS_MAX,
+    RADIO_FREQUENCY_HZ, RADIO_BANDWIDTH_HZ, RADIO_SPREADING_FACTOR, RADIO_CODING_RATE, RADIO_TX_POWER_DBM, RADIO_SYNC_WORD,
+    RADIO_ANTENNA_GAIN_DBI, RADIO_ANTENNA_AZIMUTH_DEG, RADIO_ANTENNA_DOWNTILT_DEG, RADIO_ANTENNA_PATTERN,
+    COMPANION_CHANNELS, COMPANION_CONTACTS, COMPANION_AUTO_CONTACTS_MAX, COMPANION_BOOTSTRAP, COMPANION_BOOTSTRAP_RADIUS_KM,
     // Agent properties
     AGENT_DIRECT_ENABLED, AGENT_DIRECT_STARTUP_S, AGENT_DIRECT_STARTUP_JITTER_S, AGENT_DIRECT_TARGETS,
     AGENT_DIRECT_INTERVAL_S, AGENT_DIRECT_INTERVAL_JITTER_S, AGENT_DIRECT_ACK_TIMEOUT_S,
@@ -38,30 +69,45 @@ pub use properties::{
     AGENT_CHANNEL_INTERVAL_S, AGENT_CHANNEL_INTERVAL_JITTER_S,
     AGENT_CHANNEL_SESSION_MESSAGE_COUNT, AGENT_CHANNEL_SESSION_INTERVAL_S, AGENT_CHANNEL_SESSION_INTERVAL_JITTER_S,
     AGENT_CHANNEL_MESSAGE_COUNT, AGENT_CHANNEL_SHUTDOWN_S,
+    AGENT_PHONE_ENABLED, AGENT_PHONE_CONNECTED_S, AGENT_PHONE_CONNECTED_JITTER_S,
+    AGENT_PHONE_DISCONNECTED_S, AGENT_PHONE_DISCONNECTED_JITTER_S,
+    AGENT_PHONE_READ_RECEIPTS, AGENT_PHONE_READ_DELAY_S, AGENT_PHONE_READ_DELAY_JITTER_S,
+    AGENT_ROOM_TARGET, AGENT_ROOM_PASSWORD, AGENT_ROOM_LOGIN_S, AGENT_ROOM_POST_INTERVAL_S,
+    AGENT_ROOM_POST_INTERVAL_JITTER_S, AGENT_ROOM_POST_COUNT,
+    AGENT_GPS_ENABLED, AGENT_GPS_INTERVAL_S,
     // CLI properties
     CLI_PASSWORD, CLI_COMMANDS,
     // Agent config types
     AgentConfig, DirectMessageConfig, ChannelMessageConfig,
-    LINK_MEAN_SNR_DB_AT20DBM, LINK_SNR_STD_DEV, LINK_RSSI_DBM,
-    LOCATION_LATITUDE, LOCATION_LONGITUDE, LOCATION_ALTITUDE_M,
-    SIMULATION_DURATION_S, SIMULATION_SEED, SIMULATION_UART_BASE_PORT,
-    FIRMWARE_TYPE, FIRMWARE_UART_PORT, FIRMWARE_STARTUP_TIME_S, FIRMWARE_STARTUP_JITTER_S,
-    KEYS_PRIVATE_KEY, KEYS_PUBLIC_KEY,
-    METRICS_GROUPS, METRICS_WARMUP_S, ROOM_SERVER_ROOM_ID,
+    LINK_MEAN_SNR_DB_AT20DBM, LINK_SNR_STD_DEV, LINK_RSSI_DBM, LINK_FADING, LINK_RICIAN_K_DB,
+    LOCATION_LATITUDE, LOCATION_LONGITUDE, LOCATION_ALTITUDE_M, LOCATION_X_M, LOCATION_Y_M, LOCATION_CRS,
+    SIMULATION_DURATION_S, SIMULATION_SEED, SIMULATION_RNG_BACKEND, SIMULATION_UNREACHABLE_NODES, SIMULATION_UART_BASE_PORT,
+    FIRMWARE_TYPE, FIRMWARE_UART_PORT, FIRMWARE_UART_LATENCY_MS, FIRMWARE_UART_JITTER_MS,
+    FIRMWARE_UART_JITTER_DISTRIBUTION, FIRMWARE_STARTUP_TIME_S, FIRMWARE_STARTUP_JITTER_S, FIRMWARE_FLOOD_MAX,
+    KEYS_PRIVATE_KEY, KEYS_PUBLIC_KEY, KEYS_REGENERATE_DUPLICATES,
+    METRICS_GROUPS, FAILURE_DOMAINS, METRICS_WARMUP_S, METRICS_RECORD_DURING_WARMUP, METRICS_DISABLED_CATEGORIES, METRICS_ALERT_CHECK_INTERVAL_S,
+    ROOM_SERVER_ROOM_ID, ROOM_SERVER_MAX_POSTS, ROOM_SERVER_POST_TTL_S, ROOM_SERVER_RECONNECT_DELAYS_S,
     // Firmware simulation properties
     FIRMWARE_SPIN_DETECTION_THRESHOLD, FIRMWARE_IDLE_LOOPS_BEFORE_YIELD,
     FIRMWARE_LOG_SPIN_DETECTION, FIRMWARE_LOG_LOOP_ITERATIONS, FIRMWARE_INITIAL_RTC_SECS,
     // Predict-link properties
     PREDICT_FREQUENCY_MHZ, PREDICT_TX_POWER_DBM, PREDICT_SPREADING_FACTOR,
     PREDICT_DEM_DIR, PREDICT_ELEVATION_CACHE_DIR, PREDICT_ELEVATION_SOURCE, PREDICT_ELEVATION_ZOOM_LEVEL, PREDICT_TERRAIN_SAMPLES,
+    PREDICT_ELEVATION_OFFLINE, PREDICT_ELEVATION_OFFLINE_FALLBACK_M,
+    PREDICT_ANTENNA_HEIGHT_MODE,
     // Packet tracker properties
     PACKET_TRACKER_EVICTION_AGE_S,
     // Runner properties
-    RUNNER_WATCHDOG_TIMEOUT_S, RUNNER_PERIODIC_STATS_INTERVAL_S,
+    RUNNER_WATCHDOG_TIMEOUT_S, RUNNER_PERIODIC_STATS_INTERVAL_S, RUNNER_TIMEZONE,
+    RUNNER_MAX_ARTIFACT_MB,
 };
 
+use mcsim_common::rng::RngBackend;
 use mcsim_common::{EntityId, EntityRegistry, Event, EventPayload, GeoCoord, NodeId, SimTime};
 use mcsim_lora::{LinkModel, RadioParams};
+use mcsim_metrics::custom::CustomMetric;
+use mcsim_metrics::metrics::Unit;
+use mcsim_metrics::MetricKind;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -122,6 +168,17 @@ pub enum ModelError {
     /// Invalid key specification.
     #[error("Invalid key specification: {0}")]
     InvalidKeySpec(String),
+
+    /// Two nodes were assigned the same public key (and therefore the same NodeId).
+    #[error("Duplicate public key {public_key}... for nodes '{first}' and '{second}' (set keys/regenerate_duplicates to replace it with a random key)")]
+    DuplicateKey {
+        /// Node that was assigned the key first.
+        first: String,
+        /// Node that was assigned the same key.
+        second: String,
+        /// Hex prefix of the shared public key.
+        public_key: String,
+    },
 }
 
 
@@ -141,6 +198,28 @@ pub struct Model {
     edges: BTreeMap<(String, String), Edge>,
     /// Simulation properties.
     simulation: ResolvedProperties<SimulationScope>,
+    /// Scenario-defined custom metrics.
+    custom_metrics: Vec<CustomMetric>,
+    /// Scenario actions targeting node groups.
+    actions: Vec<GroupAction>,
+    /// Node movement, at most one entry per node.
+    mobility: Vec<NodeMobility>,
+    /// Alert rules evaluated during the run.
+    alerts: Vec<AlertRule>,
+    /// Scripted traffic sent by companions.
+    traffic: Vec<TrafficRule>,
+    /// Outcome assertions checked at the end of the run.
+    assertions: Vec<Assertion>,
+    /// Scheduled failure domain outages.
+    failures: Vec<DomainFailure>,
+    /// Scheduled power outages over an area.
+    outages: Vec<PowerOutage>,
+    /// Packet fault injection rules.
+    faults: Vec<PacketFault>,
+    /// Scheduled node lifecycle events.
+    lifecycle: Vec<LifecycleEvent>,
+    /// Properties swept by `mcsim sweep`.
+    sweep: Vec<SweepAxis>,
 }
 
 impl Model {
@@ -159,6 +238,68 @@ impl Model {
         &self.simulation
     }
 
+    /// Get the custom metrics declared by the scenario.
+    ///
+    /// These must be registered with [`mcsim_metrics::custom::register`] before
+    /// agents can emit them.
+    pub fn custom_metrics(&self) -> &[CustomMetric] {
+        &self.custom_metrics
+    }
+
+    /// Get the group-targeted scenario actions.
+    pub fn actions(&self) -> &[GroupAction] {
+        &self.actions
+    }
+
+    /// Get the mobile nodes and how they move.
+    pub fn mobility(&self) -> &[NodeMobility] {
+        &self.mobility
+    }
+
+    /// Get the alert rules on metrics, evaluated during the run.
+    pub fn alerts(&self) -> &[AlertRule] {
+        &self.alerts
+    }
+
+    /// Get the scripted traffic rules.
+    pub fn traffic(&self) -> &[TrafficRule] {
+        &self.traffic
+    }
+
+    /// Get the outcome assertions.
+    pub fn assertions(&self) -> &[Assertion] {
+        &self.assertions
+    }
+
+    /// Get the scheduled failure domain outages.
+    pub fn failures(&self) -> &[DomainFailure] {
+        &self.failures
+    }
+
+    /// Get the scheduled power outages over an area.
+    pub fn outages(&self) -> &[PowerOutage] {
+        &self.outages
+    }
+
+    /// Get the packet fault injection rules, in the order they are applied.
+    pub fn faults(&self) -> &[PacketFault] {
+        &self.faults
+    }
+
+    /// Get the scheduled node lifecycle events.
+    pub fn lifecycle(&self) -> &[LifecycleEvent] {
+        &self.lifecycle
+    }
+
+    /// Simulation time (seconds) at which a node joins the network, if it
+    /// isn't there from the start.
+    pub fn join_time_s(&self, node: &str) -> Option<f64> {
+        self.lifecycle
+            .iter()
+            .find(|e| e.node == node && e.action == LifecycleAction::Join)
+            .map(|e| e.at_s)
+    }
+
     /// Find a node by name.
     pub fn find_node(&self, name: &str) -> Option<&Node> {
         self.nodes.get(name)
@@ -243,6 +384,98 @@ struct SimulationModelYaml {
     /// Simulation-wide properties (not tied to nodes/edges).
     #[serde(default)]
     simulation: Option<UnresolvedProperties<SimulationScope>>,
+    /// Custom metric declarations.
+    #[serde(default)]
+    custom_metrics: Vec<CustomMetricYaml>,
+    /// Scenario actions targeting node groups.
+    #[serde(default)]
+    actions: Vec<actions::GroupActionYaml>,
+    /// Node movement.
+    #[serde(default)]
+    mobility: Vec<mobility::NodeMobilityYaml>,
+    /// Alert rules on metrics.
+    #[serde(default)]
+    alerts: Vec<alerts::AlertRuleYaml>,
+    /// Scripted traffic.
+    #[serde(default)]
+    traffic: Vec<traffic::TrafficRuleYaml>,
+    /// Outcome assertions.
+    #[serde(default)]
+    assertions: Vec<assertions::AssertionYaml>,
+    /// Failure domain outages.
+    #[serde(default)]
+    failures: Vec<failures::DomainFailureYaml>,
+    /// Power outages over an area.
+    #[serde(default)]
+    outages: Vec<outages::PowerOutageYaml>,
+    /// Packet fault injection rules.
+    #[serde(default)]
+    faults: Vec<faults::PacketFaultYaml>,
+    /// Node lifecycle events.
+    #[serde(default)]
+    lifecycle: Vec<lifecycle::LifecycleEventYaml>,
+    /// Parameter sweep.
+    #[serde(default)]
+    sweep: serde_yaml::Mapping,
+}
+
+/// Custom metric declaration (YAML schema, internal).
+///
+/// ## Example YAML
+///
+/// ```yaml
+/// custom_metrics:
+///   - name: experiment.handshakes
+///     kind: counter
+///     description: Completed handshakes
+///     unit: count
+///     labels: [node, phase]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CustomMetricYaml {
+    /// Metric name.
+    name: String,
+    /// Metric kind ("counter", "gauge" or "histogram").
+    kind: String,
+    /// Human-readable description.
+    #[serde(default)]
+    description: String,
+    /// Unit name as used by the `metrics` crate (e.g. "count", "milliseconds").
+    #[serde(default)]
+    unit: Option<String>,
+    /// Label keys that may be attached when emitting.
+    #[serde(default)]
+    labels: Vec<String>,
+}
+
+impl CustomMetricYaml {
+    fn resolve(&self) -> Result<CustomMetric, ModelError> {
+        let kind = match self.kind.to_lowercase().as_str() {
+            "counter" => MetricKind::Counter,
+            "gauge" => MetricKind::Gauge,
+            "histogram" => MetricKind::Histogram,
+            other => {
+                return Err(ModelError::InvalidConfig(format!(
+                    "Custom metric '{}': unknown kind '{}' (expected counter, gauge or histogram)",
+                    self.name, other
+                )))
+            }
+        };
+        let mut metric = CustomMetric::new(self.name.clone(), kind)
+            .with_description(self.description.clone())
+            .with_labels(self.labels.iter().cloned());
+        if let Some(unit) = &self.unit {
+            let unit = Unit::from_string(unit).ok_or_else(|| {
+                ModelError::InvalidConfig(format!("Custom metric '{}': unknown unit '{}'", self.name, unit))
+            })?;
+            metric = metric.with_unit(unit);
+        }
+        metric
+            .validate()
+            .map_err(|e| ModelError::InvalidConfig(e.to_string()))?;
+        Ok(metric)
+    }
 }
 
 /// Node configuration with dynamic properties (YAML schema, internal).
@@ -355,6 +588,17 @@ pub fn load_models_from_str(yaml_strs: &[&str]) -> Result<Model, ModelError> {
     let mut nodes: BTreeMap<String, Node> = BTreeMap::new();
     let mut edges = BTreeMap::new();
     let mut simulation: ResolvedProperties<SimulationScope> = ResolvedProperties::new();
+    let mut custom_metrics: BTreeMap<String, CustomMetric> = BTreeMap::new();
+    let mut group_actions: Vec<GroupAction> = Vec::new();
+    let mut node_mobility: BTreeMap<String, NodeMobility> = BTreeMap::new();
+    let mut alert_rules: Vec<AlertRule> = Vec::new();
+    let mut traffic_rules: Vec<TrafficRule> = Vec::new();
+    let mut outcome_assertions: Vec<Assertion> = Vec::new();
+    let mut domain_failures: Vec<DomainFailure> = Vec::new();
+    let mut power_outages: Vec<PowerOutage> = Vec::new();
+    let mut packet_faults: Vec<PacketFault> = Vec::new();
+    let mut lifecycle_events: Vec<LifecycleEvent> = Vec::new();
+    let mut sweep_axes: Vec<SweepAxis> = Vec::new();
 
     for yaml in yamls {
         // Merge nodes
@@ -367,6 +611,8 @@ pub fn load_models_from_str(yaml_strs: &[&str]) -> Result<Model, ModelError> {
 
                 // When we remove a node, we also remove any connected edges
                 edges.retain(|(from, to), _| from != &node.name && to != &node.name);
+                node_mobility.remove(&node.name);
+                traffic_rules.retain(|rule| rule.node != node.name);
             } else if let Some(existing) = nodes.get_mut(&node.name) {
                 // Node already exists - merge properties from the overlay
                 existing.properties.apply_unresolved(&node.properties);
@@ -424,13 +670,151 @@ pub fn load_models_from_str(yaml_strs: &[&str]) -> Result<Model, ModelError> {
         if let Some(sim_props) = yaml.simulation {
             simulation.apply_unresolved(&sim_props);
         }
+
+        // Merge custom metrics (later declarations replace earlier ones)
+        for metric in &yaml.custom_metrics {
+            let metric = metric.resolve()?;
+            custom_metrics.insert(metric.name.clone(), metric);
+        }
+
+        // Accumulate group actions
+        for action in &yaml.actions {
+            group_actions.push(action.resolve()?);
+        }
+
+        // Merge mobility (later entries for a node replace earlier ones)
+        for entry in &yaml.mobility {
+            let entry = entry.resolve()?;
+            node_mobility.insert(entry.node.clone(), entry);
+        }
+
+        // Merge alerts (a later rule replaces an earlier one of the same name)
+        for rule in &yaml.alerts {
+            let rule = rule.resolve()?;
+            match alert_rules.iter_mut().find(|r| r.name == rule.name) {
+                Some(existing) => *existing = rule,
+                None => alert_rules.push(rule),
+            }
+        }
+
+        // Accumulate traffic
+        for rule in &yaml.traffic {
+            traffic_rules.push(rule.resolve()?);
+        }
+
+        // Merge assertions (a later assertion replaces an earlier one of the same name)
+        for assertion in &yaml.assertions {
+            let assertion = assertion.resolve()?;
+            match outcome_assertions.iter_mut().find(|a| a.name == assertion.name) {
+                Some(existing) => *existing = assertion,
+                None => outcome_assertions.push(assertion),
+            }
+        }
+
+        // Accumulate domain failures
+        for failure in &yaml.failures {
+            domain_failures.push(failure.resolve()?);
+        }
+
+        // Accumulate power outages
+        for outage in &yaml.outages {
+            power_outages.push(outage.resolve()?);
+        }
+
+        // Accumulate fault injection rules
+        for fault in &yaml.faults {
+            packet_faults.push(fault.resolve()?);
+        }
+
+        // Accumulate lifecycle events
+        for event in &yaml.lifecycle {
+            lifecycle_events.push(event.resolve()?);
+        }
+
+        // Merge sweeps (a later file's values for a property replace earlier ones)
+        sweep::merge_axes(&mut sweep_axes, &yaml.sweep)?;
+    }
+
+    // Positions given in a projected CRS become latitude and longitude
+    crs::project_node_locations(&mut nodes, &simulation)?;
+
+    if let Some(name) = node_mobility.keys().find(|name| !nodes.contains_key(*name)) {
+        return Err(ModelError::NodeNotFound(name.clone()));
+    }
+    if let Some(name) = alert_rules.iter().filter_map(|r| r.node.as_ref()).find(|name| !nodes.contains_key(*name)) {
+        return Err(ModelError::NodeNotFound(name.clone()));
+    }
+    if let Some(name) = outcome_assertions
+        .iter()
+        .flat_map(|a| a.check.nodes())
+        .find(|name| !nodes.contains_key(*name))
+    {
+        return Err(ModelError::NodeNotFound(name.to_string()));
+    }
+    if let Some(name) = packet_faults.iter().flat_map(PacketFault::nodes).find(|name| !nodes.contains_key(*name)) {
+        return Err(ModelError::NodeNotFound(name.to_string()));
+    }
+    if let Some(event) = lifecycle_events.iter().find(|e| !nodes.contains_key(&e.node)) {
+        return Err(ModelError::NodeNotFound(event.node.clone()));
+    }
+    lifecycle::validate(&lifecycle_events)?;
+    for rule in &traffic_rules {
+        if !nodes.contains_key(&rule.node) {
+            return Err(ModelError::NodeNotFound(rule.node.clone()));
+        }
+        if let TrafficMessage::DirectMessage(to) = &rule.message {
+            if !nodes.contains_key(to) {
+                return Err(ModelError::NodeNotFound(to.clone()));
+            }
+        }
+        if let Some(TrafficSource::DirectMessage(from)) = rule.trigger.as_ref().map(|t| &t.source) {
+            if !nodes.contains_key(from) {
+                return Err(ModelError::NodeNotFound(from.clone()));
+            }
+        }
+        if let Some(metric) = &rule.metric {
+            if custom_metrics.get(metric).is_none_or(|m| m.kind != MetricKind::Counter) {
+                return Err(ModelError::InvalidConfig(format!(
+                    "Traffic for node '{}': metric '{}' is not a declared custom counter",
+                    rule.node, metric
+                )));
+            }
+        }
     }
 
-    Ok(Model {
+    let mut model = Model {
         nodes,
         edges,
-        simulation
-    })
+        simulation,
+        custom_metrics: custom_metrics.into_values().collect(),
+        actions: group_actions,
+        mobility: node_mobility.into_values().collect(),
+        alerts: alert_rules,
+        traffic: traffic_rules,
+        assertions: outcome_assertions,
+        failures: Vec::new(),
+        outages: Vec::new(),
+        faults: packet_faults,
+        lifecycle: lifecycle_events,
+        sweep: sweep_axes,
+    };
+
+    let domains = failures::failure_domains(&model);
+    if let Some(failure) = domain_failures.iter().find(|f| !domains.contains_key(&f.domain)) {
+        return Err(ModelError::InvalidConfig(format!(
+            "Failure of domain '{}': no node belongs to it",
+            failure.domain
+        )));
+    }
+    model.failures = domain_failures;
+    if let Some(outage) = power_outages.iter().find(|o| o.affected_nodes(&model).is_empty()) {
+        return Err(ModelError::InvalidConfig(format!(
+            "Outage '{}': no node lies within its area",
+            outage.name
+        )));
+    }
+    model.outages = power_outages;
+    Ok(model)
 }
 
 
@@ -459,22 +843,96 @@ pub struct NodeInfo {
     pub public_key: [u8; 32],
     /// Specific TCP port for UART connection (if specified in config).
     pub uart_port: Option<u16>,
+    /// Fixed latency added to the UART bridge in milliseconds.
+    pub uart_latency_ms: f64,
+    /// Jitter added to the UART bridge latency in milliseconds.
+    pub uart_jitter_ms: f64,
+    /// Distribution of the UART bridge jitter ("uniform", "normal", "exponential").
+    pub uart_jitter_distribution: String,
+    /// When the firmware boots (it ignores all events before then).
+    pub startup_time: SimTime,
+    /// Flood hop limit (for Repeaters and RoomServers, which forward floods).
+    pub flood_max: Option<u8>,
+    /// Post retention settings (for RoomServers).
+    pub room_retention: Option<RoomRetention>,
+    /// Name of the policy that may skip the node's adverts, if any.
+    pub advert_suppression: Option<String>,
+}
+
+impl Default for NodeInfo {
+    fn default() -> Self {
+        NodeInfo {
+            name: String::new(),
+            node_type: String::new(),
+            firmware_entity_id: 0,
+            radio_entity_id: 0,
+            agent_entity_id: None,
+            cli_agent_entity_id: None,
+            location: GeoCoord::new(0.0, 0.0),
+            public_key: [0; 32],
+            uart_port: None,
+            uart_latency_ms: 0.0,
+            uart_jitter_ms: 0.0,
+            uart_jitter_distribution: "uniform".to_string(),
+            startup_time: SimTime::ZERO,
+            flood_max: None,
+            room_retention: None,
+            advert_suppression: None,
+        }
+    }
+}
+
+impl NodeInfo {
+    /// A node with the given name, type and entity IDs, and defaults
+    /// otherwise (for test fixtures).
+    pub fn new(name: &str, node_type: &str, firmware_entity_id: u64, radio_entity_id: u64) -> Self {
+        NodeInfo {
+            name: name.to_string(),
+            node_type: node_type.to_string(),
+            firmware_entity_id,
+            radio_entity_id,
+            ..NodeInfo::default()
+        }
+    }
+}
+
+/// Post retention settings of a room server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomRetention {
+    /// Number of posts retained for clients that have not synced.
+    pub max_posts: usize,
+    /// Age (seconds) after which a post is no longer served, if any.
+    pub post_ttl_s: Option<f64>,
+    /// Client reconnect delays (seconds) at which availability is measured.
+    pub reconnect_delays_s: Vec<f64>,
 }
 
 /// Result of building a simulation from a model.
 pub struct BuiltSimulation {
     /// Entity registry with all entities.
     pub entities: EntityRegistry,
+    /// The Graph entity that routes transmissions.
+    pub graph_entity: EntityId,
     /// Link model.
     pub link_model: LinkModel,
     /// Initial events to seed the simulation.
     pub initial_events: Vec<Event>,
     /// Information about each node for display.
     pub node_infos: Vec<NodeInfo>,
+    /// Random number backend for the event loop.
+    pub rng_backend: RngBackend,
 }
 
 /// Build a simulation from a model.
 pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, ModelError> {
+    build(model, seed, true)
+}
+
+/// Build a simulation, optionally without loading firmware.
+///
+/// Without firmware every other entity, key and event is still created as
+/// for a run, so the model is checked as thoroughly as a run would check it.
+fn build(model: &Model, seed: u64, load_firmware: bool) -> Result<BuiltSimulation, ModelError> {
     use mcsim_firmware::{
         RepeaterFirmware, RepeaterConfig, CompanionFirmware, CompanionConfig, 
         RoomServerFirmware, RoomServerConfig, FirmwareConfig,
@@ -491,6 +949,7 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
 
     // RNG for generating node keys
     let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut assigned_keys = AssignedKeys::new();
 
     // Read firmware simulation parameters from model simulation properties
     let sim_props = model.simulation_properties();
@@ -501,8 +960,23 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
         log_loop_iterations: sim_props.get(&FIRMWARE_LOG_LOOP_ITERATIONS),
         initial_rtc_secs: sim_props.get(&FIRMWARE_INITIAL_RTC_SECS),
         startup_time_us: 0, // Default; overridden per-node based on node properties
+        clock_drift: None,
+        barometer: None,
     };
 
+    let rng_backend: RngBackend = sim_props
+        .get(&SIMULATION_RNG_BACKEND)
+        .parse()
+        .map_err(ModelError::InvalidConfig)?;
+    let regenerate_duplicate_keys = sim_props.get(&properties::KEYS_REGENERATE_DUPLICATES);
+
+    // Optional bit-error channel for marginal receptions
+    let bit_error_window_db: f64 = sim_props.get(&properties::RADIO_BIT_ERROR_WINDOW_DB);
+    let bit_errors_config = (bit_error_window_db > 0.0).then(|| mcsim_lora::BitErrorConfig {
+        window_db: bit_error_window_db,
+        max_bit_error_rate: sim_props.get(&properties::RADIO_BIT_ERROR_RATE_MAX),
+    });
+
     // Maps for entity ID allocation and name lookup
     let mut next_entity_id: u64 = 0;
     
@@ -524,11 +998,21 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
     
     // Collect node information for display
     let mut node_infos: Vec<NodeInfo> = Vec::new();
+    // Radio positions, for recomputing links of mobile nodes
+    let mut radio_positions: std::collections::BTreeMap<EntityId, mcsim_lora::mobility::RadioPosition> =
+        std::collections::BTreeMap::new();
 
     // Pre-pass: allocate entity IDs for radio, firmware, and agent (if present)
     for (_, node) in model.nodes() {
         let radio_id = EntityId::new(next_entity_id);
         next_entity_id += 1;
+
+        // Jammers and bridges have no firmware; their entity stands in for the radio
+        if is_jammer(node) || is_bridge(node) {
+            node_name_to_radio_id.insert(node.name.clone(), radio_id);
+            continue;
+        }
+
         let firmware_id = EntityId::new(next_entity_id);
         next_entity_id += 1;
 
@@ -564,6 +1048,71 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
     for (_, node) in model.nodes() {
         // Get pre-allocated entity IDs
         let radio_id = *node_name_to_radio_id.get(&node.name).unwrap();
+
+        if is_jammer(node) {
+            let config = jammer_config(node, graph_id)?;
+            let resolved = node.properties();
+            let start_s: f64 = resolved.get(&properties::JAMMER_START_S);
+            radio_positions.insert(
+                radio_id,
+                mcsim_lora::mobility::RadioPosition {
+                    position: GeoCoord {
+                        latitude: resolved.get(&properties::LOCATION_LATITUDE),
+                        longitude: resolved.get(&properties::LOCATION_LONGITUDE),
+                        altitude_m: resolved.get(&properties::LOCATION_ALTITUDE_M),
+                    },
+                    frequency_hz: config.frequency_hz,
+                },
+            );
+            entities.register(Box::new(mcsim_lora::jammer::Jammer::new(radio_id, config)));
+            initial_events.push(Event {
+                id: mcsim_common::EventId(event_id_counter),
+                time: SimTime::from_secs(start_s.max(0.0)),
+                source: radio_id,
+                targets: vec![radio_id],
+                payload: EventPayload::Timer { timer_id: mcsim_lora::jammer::TIMER_JAMMER_BURST },
+            });
+            event_id_counter += 1;
+            continue;
+        }
+
+        if is_bridge(node) {
+            let config = bridge_config(node, graph_id, sim_props)?;
+            let resolved = node.properties();
+            let position = GeoCoord {
+                latitude: resolved.get(&properties::LOCATION_LATITUDE),
+                longitude: resolved.get(&properties::LOCATION_LONGITUDE),
+                altitude_m: resolved.get(&properties::LOCATION_ALTITUDE_M),
+            };
+            radio_positions.insert(
+                radio_id,
+                mcsim_lora::mobility::RadioPosition { position, frequency_hz: config.params.frequency_hz },
+            );
+            let metric_labels = mcsim_metrics::MetricLabels::new(node.name.clone(), "bridge")
+                .with_groups(resolved.get(&properties::METRICS_GROUPS));
+            entities.register(Box::new(mcsim_lora::bridge::RadioBridge::new(radio_id, config, metric_labels)));
+            // The bridge's serial data is carried over its UART port
+            node_infos.push(NodeInfo {
+                name: node.name.clone(),
+                node_type: "Bridge".to_string(),
+                firmware_entity_id: radio_id.0,
+                radio_entity_id: radio_id.0,
+                agent_entity_id: None,
+                cli_agent_entity_id: None,
+                location: position,
+                public_key: [0; 32],
+                uart_port: resolved.get(&properties::FIRMWARE_UART_PORT),
+                uart_latency_ms: resolved.get(&properties::FIRMWARE_UART_LATENCY_MS),
+                uart_jitter_ms: resolved.get(&properties::FIRMWARE_UART_JITTER_MS),
+                uart_jitter_distribution: resolved.get(&properties::FIRMWARE_UART_JITTER_DISTRIBUTION),
+                startup_time: SimTime::ZERO,
+                flood_max: None,
+                room_retention: None,
+                advert_suppression: None,
+            });
+            continue;
+        }
+
         let firmware_id = *node_name_to_firmware_id.get(&node.name).unwrap();
         let agent_id = node_name_to_agent_id.get(&node.name).copied();
 
@@ -575,8 +1124,14 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
             public_key: KeySpec::parse(&resolved.get(&properties::KEYS_PUBLIC_KEY))?,
         };
 
-        // Generate node identity (public/private key pair) based on key config
-        let generated = generate_keypair_with_spec(&mut rng, &key_config, &node.name)?;
+        // Generate node identity (public/private key pair) based on key config,
+        // rejecting (or replacing) keys already assigned to another node
+        let generated = assigned_keys.generate(
+            &mut rng,
+            &key_config,
+            &node.name,
+            regenerate_duplicate_keys,
+        )?;
         let public_key = generated.public_key;
         let private_key = generated.private_key;
         let node_id = NodeId::from_bytes(public_key);
@@ -607,10 +1162,63 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
             longitude: resolved.get(&properties::LOCATION_LONGITUDE),
             altitude_m: resolved.get(&properties::LOCATION_ALTITUDE_M),
         };
+        radio_positions.insert(
+            radio_id,
+            mcsim_lora::mobility::RadioPosition { position, frequency_hz: radio_params.frequency_hz },
+        );
+        // Clock and carrier drift with the node's temperature
+        let (clock_drift, frequency_drift) = crystal_drifts(resolved, sim_props);
+
+        // Battery model, for nodes with a battery capacity
+        let battery_capacity_mah: f64 = resolved.get(&properties::POWER_BATTERY_CAPACITY_MAH);
+        let power_config = if battery_capacity_mah > 0.0 {
+            let initial_percent: f64 = resolved.get(&properties::POWER_BATTERY_INITIAL_PERCENT);
+            if !(0.0..=100.0).contains(&initial_percent) {
+                return Err(ModelError::InvalidConfig(format!(
+                    "Node '{}': power/battery_initial_percent must be 0-100, got {}",
+                    node.name, initial_percent
+                )));
+            }
+            Some(mcsim_lora::power::PowerConfig {
+                battery_capacity_mah,
+                initial_charge: initial_percent / 100.0,
+                tx_current_ma: resolved.get(&properties::POWER_TX_CURRENT_MA),
+                rx_current_ma: resolved.get(&properties::POWER_RX_CURRENT_MA),
+                idle_current_ma: resolved.get(&properties::POWER_IDLE_CURRENT_MA),
+            })
+        } else {
+            None
+        };
+        if power_config.is_some() {
+            // Start the radio's periodic battery updates
+            initial_events.push(Event {
+                id: mcsim_common::EventId(event_id_counter),
+                time: SimTime::ZERO,
+                source: radio_id,
+                targets: vec![radio_id],
+                payload: EventPayload::Timer { timer_id: mcsim_lora::BATTERY_UPDATE_TIMER_ID },
+            });
+            event_id_counter += 1;
+        }
+
+        let derating = derating_config(resolved, &node.name)?;
+        let regulatory = regulatory_config(resolved, &node.name, radio_params.frequency_hz)?;
+        let advert_suppression = advert_suppression_config(resolved, &node.name)?;
+        let advert_policy = advert_suppression.as_ref().map(|a| a.policy_name.clone());
+
         let radio_config = mcsim_lora::RadioConfig {
             params: radio_params,
             rx_to_tx_turnaround: SimTime::from_micros(100),
             tx_to_rx_turnaround: SimTime::from_micros(100),
+            preamble_symbols: sim_props.get(&properties::LORA_PREAMBLE_SYMBOLS),
+            bit_errors: bit_errors_config,
+            power: power_config,
+            derating,
+            regulatory,
+            advert_suppression,
+            adjacent_channel_rejection_db: sim_props.get(&properties::RADIO_ADJACENT_CHANNEL_REJECTION_DB),
+            frequency_drift,
+            sync_word: resolved.get(&RADIO_SYNC_WORD),
             graph_entity: graph_id,
         };
         
@@ -633,6 +1241,9 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
 
         // Get UART port if specified
         let uart_port = resolved.get(&properties::FIRMWARE_UART_PORT);
+        let uart_latency_ms: f64 = resolved.get(&properties::FIRMWARE_UART_LATENCY_MS);
+        let uart_jitter_ms: f64 = resolved.get(&properties::FIRMWARE_UART_JITTER_MS);
+        let uart_jitter_distribution: String = resolved.get(&properties::FIRMWARE_UART_JITTER_DISTRIBUTION);
 
         // Compute firmware startup time with jitter
         let startup_time_s: f64 = resolved.get(&properties::FIRMWARE_STARTUP_TIME_S);
@@ -642,15 +1253,29 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
         } else {
             0.0
         };
-        let firmware_startup_time = SimTime::from_secs((startup_time_s + jitter).max(0.0));
+        // A node that joins later boots then
+        let join_s = model.join_time_s(&node.name).unwrap_or(0.0);
+        let firmware_startup_time = SimTime::from_secs(join_s + (startup_time_s + jitter).max(0.0));
         node_name_to_firmware_startup_time.insert(node.name.clone(), firmware_startup_time);
 
         // Create node-specific firmware simulation params with startup time
         let node_firmware_sim_params = mcsim_firmware::FirmwareSimulationParams {
             startup_time_us: firmware_startup_time.as_micros(),
+            clock_drift,
+            barometer: barometer(&node.name, resolved, sim_props),
             ..firmware_sim_params.clone()
         };
 
+        let flood_max: u8 = resolved.get(&FIRMWARE_FLOOD_MAX);
+        if !(1..=mcsim_firmware::MAX_FLOOD_HOPS).contains(&flood_max) {
+            return Err(ModelError::InvalidConfig(format!(
+                "firmware/flood_max for node '{}' must be between 1 and {}, got {}",
+                node.name,
+                mcsim_firmware::MAX_FLOOD_HOPS,
+                flood_max
+            )));
+        }
+
         // Create firmware entity based on type
         match firmware_type.to_lowercase().as_str() {
             "repeater" => {
@@ -662,16 +1287,19 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
                         encryption_key: None,
                         rng_seed: node_rng_seed,
                     },
+                    flood_max: Some(flood_max),
                 };
-                let mut firmware = RepeaterFirmware::with_sim_params(firmware_id, fw_config, radio_id, node.name.clone(), &node_firmware_sim_params)?;
-                
-                // Attach CLI agent if one was allocated for this node
-                if let Some(cli_agent_id) = node_name_to_cli_agent_id.get(&node.name) {
-                    firmware.set_attached_cli_agent(*cli_agent_id);
+                if load_firmware {
+                    let mut firmware = RepeaterFirmware::with_sim_params(firmware_id, fw_config, radio_id, node.name.clone(), &node_firmware_sim_params)?;
+
+                    // Attach CLI agent if one was allocated for this node
+                    if let Some(cli_agent_id) = node_name_to_cli_agent_id.get(&node.name) {
+                        firmware.set_attached_cli_agent(*cli_agent_id);
+                    }
+
+                    entities.register(Box::new(firmware));
                 }
                 
-                entities.register(Box::new(firmware));
-                
                 // Add initial timer event to start the firmware (after startup delay)
                 initial_events.push(Event {
                     id: mcsim_common::EventId(event_id_counter),
@@ -694,6 +1322,13 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
                     location: position.clone(),
                     public_key,
                     uart_port,
+                    uart_latency_ms,
+                    uart_jitter_ms,
+                    uart_jitter_distribution,
+                    startup_time: firmware_startup_time,
+                    flood_max: Some(flood_max),
+                    room_retention: None,
+                    advert_suppression: advert_policy,
                 });
             }
             "companion" => {
@@ -706,8 +1341,10 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
                         rng_seed: node_rng_seed,
                     },
                 };
-                let firmware = CompanionFirmware::with_sim_params(firmware_id, fw_config, radio_id, agent_id, node.name.clone(), &node_firmware_sim_params)?;
-                entities.register(Box::new(firmware));
+                if load_firmware {
+                    let firmware = CompanionFirmware::with_sim_params(firmware_id, fw_config, radio_id, agent_id, node.name.clone(), &node_firmware_sim_params)?;
+                    entities.register(Box::new(firmware));
+                }
                 
                 // Add initial timer event (after startup delay)
                 initial_events.push(Event {
@@ -729,6 +1366,13 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
                     location: position.clone(),
                     public_key,
                     uart_port,
+                    uart_latency_ms,
+                    uart_jitter_ms,
+                    uart_jitter_distribution,
+                    startup_time: firmware_startup_time,
+                    flood_max: None,
+                    room_retention: None,
+                    advert_suppression: advert_policy,
                 });
             }
             "room_server" | "roomserver" => {
@@ -754,17 +1398,20 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
                         rng_seed: node_rng_seed,
                     },
                     room_id,
+                    flood_max: Some(flood_max),
                 };
                 
-                let mut firmware = RoomServerFirmware::with_sim_params(firmware_id, fw_config, radio_id, node.name.clone(), &node_firmware_sim_params)?;
-                
-                // Attach CLI agent if one was allocated for this node
-                if let Some(cli_agent_id) = node_name_to_cli_agent_id.get(&node.name) {
-                    firmware.set_attached_cli_agent(*cli_agent_id);
+                if load_firmware {
+                    let mut firmware = RoomServerFirmware::with_sim_params(firmware_id, fw_config, radio_id, node.name.clone(), &node_firmware_sim_params)?;
+
+                    // Attach CLI agent if one was allocated for this node
+                    if let Some(cli_agent_id) = node_name_to_cli_agent_id.get(&node.name) {
+                        firmware.set_attached_cli_agent(*cli_agent_id);
+                    }
+
+                    entities.register(Box::new(firmware));
                 }
                 
-                entities.register(Box::new(firmware));
-                
                 // Add initial timer event (after startup delay)
                 initial_events.push(Event {
                     id: mcsim_common::EventId(event_id_counter),
@@ -775,6 +1422,28 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
                 });
                 event_id_counter += 1;
                 
+                let max_posts: u32 = resolved.get(&ROOM_SERVER_MAX_POSTS);
+                let post_ttl_s: Option<f64> = resolved.get(&ROOM_SERVER_POST_TTL_S);
+                let reconnect_delays_s: Vec<f64> = resolved.get(&ROOM_SERVER_RECONNECT_DELAYS_S);
+                if max_posts == 0 {
+                    return Err(ModelError::InvalidConfig(format!(
+                        "room_server/max_posts for node '{}' must be at least 1",
+                        node.name
+                    )));
+                }
+                if post_ttl_s.is_some_and(|ttl| ttl.is_nan() || ttl <= 0.0) {
+                    return Err(ModelError::InvalidConfig(format!(
+                        "room_server/post_ttl_s for node '{}' must be positive",
+                        node.name
+                    )));
+                }
+                if reconnect_delays_s.iter().any(|d| d.is_nan() || *d < 0.0) {
+                    return Err(ModelError::InvalidConfig(format!(
+                        "room_server/reconnect_delays_s for node '{}' must be non-negative",
+                        node.name
+                    )));
+                }
+
                 let cli_agent_id = node_name_to_cli_agent_id.get(&node.name).copied();
                 node_infos.push(NodeInfo {
                     name: node.name.clone(),
@@ -786,6 +1455,17 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
                     location: position.clone(),
                     public_key,
                     uart_port,
+                    uart_latency_ms,
+                    uart_jitter_ms,
+                    uart_jitter_distribution,
+                    startup_time: firmware_startup_time,
+                    flood_max: Some(flood_max),
+                    room_retention: Some(RoomRetention {
+                        max_posts: max_posts as usize,
+                        post_ttl_s,
+                        reconnect_delays_s,
+                    }),
+                    advert_suppression: advert_policy,
                 });
             }
             _ => {
@@ -796,7 +1476,37 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
         }
     }
 
+    // Node facts for resolving group actions
+    let action_nodes: Vec<ActionNode> = model
+        .nodes()
+        .values()
+        .filter(|node| !is_jammer(node) && !is_bridge(node))
+        .map(|node| {
+            let props = node.properties();
+            ActionNode {
+                name: node.name.clone(),
+                firmware_type: node_name_to_firmware_type[&node.name].clone(),
+                groups: props.get(&properties::METRICS_GROUPS),
+                location: GeoCoord {
+                    latitude: props.get(&properties::LOCATION_LATITUDE),
+                    longitude: props.get(&properties::LOCATION_LONGITUDE),
+                    altitude_m: props.get(&properties::LOCATION_ALTITUDE_M),
+                },
+            }
+        })
+        .collect();
+
+    // Only companions have agents to send scripted traffic
+    if let Some(rule) = model.traffic().iter().find(|r| !node_name_to_agent_id.contains_key(&r.node)) {
+        return Err(ModelError::InvalidConfig(format!(
+            "Traffic for node '{}': only companions can send traffic",
+            rule.node
+        )));
+    }
+
     // Second pass: create agent entities and initial events
+    // Agents sharing their node's location get the Graph's position fixes
+    let mut gps_receivers: std::collections::BTreeMap<EntityId, Vec<EntityId>> = std::collections::BTreeMap::new();
     for (_, node_config) in &model.nodes {
         // Skip if no agent was allocated for this node
         let agent_id = match node_name_to_agent_id.get(&node_config.name) {
@@ -807,39 +1517,14 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
         let props = node_config.properties();
         let direct_enabled: bool = props.get(&AGENT_DIRECT_ENABLED);
         let channel_enabled: bool = props.get(&AGENT_CHANNEL_ENABLED);
+        let gps_enabled: bool = props.get(&AGENT_GPS_ENABLED);
         
         let firmware_id = *node_name_to_firmware_id.get(&node_config.name).unwrap();
         let node_id = *node_name_to_node_id.get(&node_config.name).unwrap();
 
         let auto_contacts_max: u32 = props.get(&COMPANION_AUTO_CONTACTS_MAX);
-
-        // Helper to build contact list when companion/contacts is null.
-        // We want as many repeaters/other nodes as possible (up to firmware capacity),
-        // but keep deterministic ordering: companions first, then others.
-        // Limit: auto_contacts_max to avoid exceeding firmware capacity.
-        let build_auto_contact_list = |exclude_self: &str| -> Vec<String> {
-            let mut companions: Vec<String> = Vec::new();
-            let mut others: Vec<String> = Vec::new();
-
-            for (name, fw_type) in &node_name_to_firmware_type {
-                if name == exclude_self {
-                    continue;
-                }
-                if fw_type == "companion" {
-                    companions.push(name.clone());
-                } else {
-                    others.push(name.clone());
-                }
-            }
-
-            companions.sort();
-            others.sort();
-
-            let mut result = companions;
-            result.extend(others);
-            result.truncate(auto_contacts_max as usize);
-            result
-        };
+        let bootstrap = ContactBootstrap::from_properties(&node_config.name, props)?;
+        let sender = action_nodes.iter().find(|n| n.name == node_config.name).unwrap();
 
         // Build contacts list from companion/contacts FIRST
         // These are node names that will be resolved to their public keys
@@ -864,17 +1549,87 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
                 .cloned()
                 .collect(),
             None => {
-                // If null, auto-populate contacts up to capacity (companions first, then others).
-                build_auto_contact_list(&node_config.name)
+                // If null, pre-populate contacts per companion/bootstrap, up to capacity.
+                bootstrap.select(sender, &action_nodes, auto_contacts_max as usize)
             }
         };
         
-        let contacts: Vec<mcsim_agents::ContactTarget> = resolved_contact_names.iter()
+        let mut contacts: Vec<mcsim_agents::ContactTarget> = resolved_contact_names.iter()
             .filter_map(|name| {
                 node_name_to_node_id.get(name).map(|node_id| make_contact(name, *node_id))
             })
             .collect();
 
+        // Expand group actions this node takes part in. Destinations must be
+        // contacts so the firmware can encrypt the message.
+        let mut scheduled = Vec::new();
+        for action in model.actions().iter().filter(|a| a.is_sender(sender)) {
+            let candidate_names = action.candidates(sender, &action_nodes);
+            if candidate_names.is_empty() {
+                log::warn!(
+                    "Node '{}': no destination for action on group '{}' at {}s",
+                    node_config.name, action.group, action.at_s
+                );
+                continue;
+            }
+            for name in &candidate_names {
+                if !contacts.iter().any(|c| &c.name == name) {
+                    contacts.push(make_contact(name, node_name_to_node_id[name]));
+                }
+            }
+            scheduled.push(mcsim_agents::ScheduledMessage {
+                at_s: action.at_s,
+                candidates: candidate_names.iter().map(|name| node_name_to_node_id[name]).collect(),
+                strategy: action.send_dm.strategy,
+                text: action.send_dm.text.clone(),
+            });
+        }
+
+        // Room server this node's agent joins; it must be a contact to log in
+        let room_target: Option<String> = props.get(&AGENT_ROOM_TARGET);
+        let room = match room_target {
+            Some(name) => {
+                if !matches!(node_name_to_firmware_type.get(&name).map(|s| s.as_str()), Some("room_server") | Some("roomserver")) {
+                    return Err(ModelError::InvalidConfig(format!(
+                        "Node '{}': agent/room/target '{}' is not a room server",
+                        node_config.name, name
+                    )));
+                }
+                if !contacts.iter().any(|c| c.name == name) {
+                    contacts.push(make_contact(&name, node_name_to_node_id[&name]));
+                }
+                Some(node_name_to_node_id[&name])
+            }
+            None => None,
+        };
+
+        // Scripted traffic from this node. DM destinations and trigger
+        // senders must be contacts and channels must be subscribed (added
+        // below).
+        let mut traffic = Vec::new();
+        let mut traffic_channels: Vec<String> = Vec::new();
+        for rule in model.traffic().iter().filter(|r| r.node == node_config.name) {
+            match &rule.message {
+                TrafficMessage::DirectMessage(to) if !contacts.iter().any(|c| &c.name == to) => {
+                    contacts.push(make_contact(to, node_name_to_node_id[to]));
+                }
+                TrafficMessage::Channel(name) if !traffic_channels.contains(name) => {
+                    traffic_channels.push(name.clone());
+                }
+                _ => {}
+            }
+            match rule.trigger.as_ref().map(|t| &t.source) {
+                Some(TrafficSource::DirectMessage(from)) if !contacts.iter().any(|c| &c.name == from) => {
+                    contacts.push(make_contact(from, node_name_to_node_id[from]));
+                }
+                Some(TrafficSource::Channel(name)) if !traffic_channels.contains(name) => {
+                    traffic_channels.push(name.clone());
+                }
+                _ => {}
+            }
+            traffic.push(rule.to_agent_rule(&node_name_to_node_id).expect("traffic nodes are validated at load"));
+        }
+
         // Build direct message config
         // If agent/direct/targets is specified, use those nodes
         // If agent/direct/targets is NULL, derive from contact list (companions only)
@@ -929,13 +1684,20 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
         
         // Companion channels (subscribe only) - exclude any that are already in targets
         let target_set: std::collections::HashSet<&String> = agent_channel_names.iter().collect();
-        let subscribe_only: Vec<mcsim_agents::ChannelTarget> = companion_channel_names
+        let mut subscribe_only: Vec<mcsim_agents::ChannelTarget> = companion_channel_names
             .unwrap_or_default()
             .into_iter()
             .filter(|name| !target_set.contains(name))
             .map(|name| mcsim_agents::ChannelTarget::from_name(name))
             .collect();
 
+        // Channels posted to by traffic rules
+        for name in traffic_channels {
+            if !channel_targets.iter().chain(&subscribe_only).any(|c| c.name == name) {
+                subscribe_only.push(mcsim_agents::ChannelTarget::from_name(name));
+            }
+        }
+
         let channel_config = mcsim_agents::ChannelMessageConfig {
             enabled: channel_enabled,
             startup_s: props.get(&AGENT_CHANNEL_STARTUP_S),
@@ -956,7 +1718,39 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
             direct: direct_config,
             channel: channel_config,
             contacts,
+            scheduled,
+            phone: mcsim_agents::PhoneAppConfig {
+                enabled: props.get(&AGENT_PHONE_ENABLED),
+                connected_s: props.get(&AGENT_PHONE_CONNECTED_S),
+                connected_jitter_s: props.get(&AGENT_PHONE_CONNECTED_JITTER_S),
+                disconnected_s: props.get(&AGENT_PHONE_DISCONNECTED_S),
+                disconnected_jitter_s: props.get(&AGENT_PHONE_DISCONNECTED_JITTER_S),
+                read_receipts: props.get(&AGENT_PHONE_READ_RECEIPTS),
+                read_delay_s: props.get(&AGENT_PHONE_READ_DELAY_S),
+                read_delay_jitter_s: props.get(&AGENT_PHONE_READ_DELAY_JITTER_S),
+            },
+            traffic,
+            room: mcsim_agents::RoomClientConfig {
+                room,
+                password: props.get(&AGENT_ROOM_PASSWORD),
+                login_s: props.get(&AGENT_ROOM_LOGIN_S),
+                post_interval_s: props.get(&AGENT_ROOM_POST_INTERVAL_S),
+                post_interval_jitter_s: props.get(&AGENT_ROOM_POST_INTERVAL_JITTER_S),
+                post_count: props.get(&AGENT_ROOM_POST_COUNT),
+            },
+            gps: mcsim_agents::GpsConfig {
+                enabled: gps_enabled,
+                position: Some(GeoCoord {
+                    latitude: props.get(&properties::LOCATION_LATITUDE),
+                    longitude: props.get(&properties::LOCATION_LONGITUDE),
+                    altitude_m: props.get(&properties::LOCATION_ALTITUDE_M),
+                }),
+                interval_s: props.get(&AGENT_GPS_INTERVAL_S),
+            },
         };
+        if gps_enabled {
+            gps_receivers.entry(node_name_to_radio_id[&node_config.name]).or_default().push(agent_id);
+        }
 
         let agent = mcsim_agents::Agent::new(agent_id, agent_config, node_id, firmware_id);
         entities.register(Box::new(agent));
@@ -1021,6 +1815,83 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
         event_id_counter += 1;
     }
 
+    // Power off the nodes of each failing domain
+    let domains = failures::failure_domains(model);
+    for failure in model.failures() {
+        for node_name in &domains[&failure.domain] {
+            let radio_id = node_name_to_radio_id[node_name];
+            initial_events.push(Event {
+                id: mcsim_common::EventId(event_id_counter),
+                time: SimTime::from_secs(failure.at_s),
+                source: radio_id,
+                targets: vec![radio_id],
+                payload: EventPayload::PowerOff(mcsim_common::PowerOffEvent {
+                    reason: format!("failure domain {}", failure.domain),
+                }),
+            });
+            event_id_counter += 1;
+        }
+    }
+
+    // Power off the nodes inside each outage area, and back on when it ends
+    for outage in model.outages() {
+        let reason = format!("outage {}", outage.name);
+        for node_name in outage.affected_nodes(model) {
+            let radio_id = node_name_to_radio_id[&node_name];
+            initial_events.push(Event {
+                id: mcsim_common::EventId(event_id_counter),
+                time: SimTime::from_secs(outage.at_s),
+                source: radio_id,
+                targets: vec![radio_id],
+                payload: EventPayload::PowerOff(mcsim_common::PowerOffEvent { reason: reason.clone() }),
+            });
+            event_id_counter += 1;
+            if let Some(restore_at_s) = outage.restore_at_s() {
+                initial_events.push(Event {
+                    id: mcsim_common::EventId(event_id_counter),
+                    time: SimTime::from_secs(restore_at_s),
+                    source: radio_id,
+                    targets: vec![radio_id],
+                    payload: EventPayload::PowerOn(mcsim_common::PowerOnEvent { reason: reason.clone() }),
+                });
+                event_id_counter += 1;
+            }
+        }
+    }
+
+    // Scheduled lifecycle events; nodes that join later are kept off until then
+    for event in model.lifecycle() {
+        let radio_id = node_name_to_radio_id[&event.node];
+        let reason = format!("lifecycle {}", event.action.name());
+        let mut schedule = |time: SimTime, payload: EventPayload| {
+            initial_events.push(Event {
+                id: mcsim_common::EventId(event_id_counter),
+                time,
+                source: radio_id,
+                targets: vec![radio_id],
+                payload,
+            });
+            event_id_counter += 1;
+        };
+        let at = SimTime::from_secs(event.at_s);
+        match event.action {
+            LifecycleAction::PowerOff | LifecycleAction::Leave => {
+                schedule(at, EventPayload::PowerOff(mcsim_common::PowerOffEvent { reason }));
+            }
+            LifecycleAction::PowerOn => {
+                schedule(at, EventPayload::PowerOn(mcsim_common::PowerOnEvent { reason }));
+            }
+            LifecycleAction::Reboot => {
+                schedule(at, EventPayload::Reboot(mcsim_common::RebootEvent { reason }));
+            }
+            LifecycleAction::Join => {
+                let waiting = "lifecycle not joined yet".to_string();
+                schedule(SimTime::ZERO, EventPayload::PowerOff(mcsim_common::PowerOffEvent { reason: waiting }));
+                schedule(at, EventPayload::PowerOn(mcsim_common::PowerOnEvent { reason }));
+            }
+        }
+    }
+
     // Fourth pass: populate link model from edges
     for (_,edge) in &model.edges {
         let from_radio = node_name_to_radio_id.get(&edge.from)
@@ -1034,19 +1905,369 @@ pub fn build_simulation(model: &Model, seed: u64) -> Result<BuiltSimulation, Mod
         let mean_snr: f64 = edge_props.get(&LINK_MEAN_SNR_DB_AT20DBM);
         let snr_std_dev: f64 = edge_props.get(&LINK_SNR_STD_DEV);
         let rssi_dbm: f64 = edge_props.get(&LINK_RSSI_DBM);
+        let fading_name: String = edge_props.get(&LINK_FADING);
+        let fading = match fading_name.to_lowercase().as_str() {
+            "none" => mcsim_lora::FadingModel::None,
+            "rayleigh" => mcsim_lora::FadingModel::Rayleigh,
+            "rician" => mcsim_lora::FadingModel::Rician {
+                k_factor_db: edge_props.get(&LINK_RICIAN_K_DB),
+            },
+            other => {
+                return Err(ModelError::InvalidConfig(format!(
+                    "Edge {} -> {}: unknown link/fading '{}' (expected none, rayleigh or rician)",
+                    edge.from, edge.to, other
+                )))
+            }
+        };
 
-        link_model.add_link(*from_radio, *to_radio, mean_snr, snr_std_dev, rssi_dbm);
+        link_model.add_edge(
+            *from_radio,
+            *to_radio,
+            mcsim_lora::LinkParams { mean_snr_db_at20dbm: mean_snr, snr_std_dev, rssi_dbm, fading },
+        );
     }
 
     // Create and register the Graph entity with the populated link model
-    let graph = mcsim_lora::Graph::new(graph_id, link_model.clone());
+    let mobile_nodes: Vec<_> = model
+        .mobility()
+        .iter()
+        .map(|entry| {
+            let radio = node_name_to_radio_id[&entry.node];
+            mcsim_lora::mobility::MobileNode::new(radio, radio_positions[&radio].position, entry.model.clone())
+        })
+        .collect();
+    let path_loss = mcsim_lora::mobility::PathLossModel {
+        exponent: sim_props.get(&properties::MOBILITY_PATH_LOSS_EXPONENT),
+        noise_floor_dbm: sim_props.get(&properties::RADIO_NOISE_FLOOR_DBM),
+        snr_std_dev: sim_props.get(&properties::MOBILITY_SNR_STD_DEV),
+        min_snr_db: sim_props.get(&properties::MOBILITY_MIN_SNR_DB),
+    };
+    let update_interval_s: f64 = sim_props.get(&properties::MOBILITY_UPDATE_INTERVAL_S);
+    let has_mobile_nodes = !mobile_nodes.is_empty();
+    if has_mobile_nodes && (update_interval_s.is_nan() || update_interval_s <= 0.0) {
+        return Err(ModelError::InvalidConfig(
+            "mobility/update_interval_s must be positive".to_string(),
+        ));
+    }
+    // Mobility is always attached, so nodes can also be moved while the simulation runs
+    let graph = mcsim_lora::Graph::new(graph_id, link_model.clone()).with_mobility(mcsim_lora::mobility::Mobility::new(
+        mobile_nodes,
+        radio_positions,
+        path_loss,
+        update_interval_s,
+    ));
+    if has_mobile_nodes {
+        // First update places mobile nodes before anything transmits
+        initial_events.push(Event {
+            id: mcsim_common::EventId(event_id_counter),
+            time: SimTime::ZERO,
+            source: graph_id,
+            targets: vec![graph_id],
+            payload: EventPayload::Timer { timer_id: mcsim_lora::TIMER_MOBILITY_UPDATE },
+        });
+    }
+    let fault_rules = model
+        .faults()
+        .iter()
+        .map(|fault| mcsim_lora::faults::FaultRule {
+            from: fault.from.as_ref().map(|name| node_name_to_radio_id[name]),
+            to: fault.to.as_ref().map(|name| node_name_to_radio_id[name]),
+            bidirectional: fault.bidirectional,
+            action: fault.action,
+            selection: fault.selection,
+            start: SimTime::from_secs(fault.at_s),
+            end: fault.duration_s.map(|duration| SimTime::from_secs(fault.at_s + duration)),
+        })
+        .collect();
+    let graph = graph
+        .with_faults(mcsim_lora::faults::PacketFaults::new(fault_rules))
+        .with_gps_receivers(gps_receivers);
     entities.register(Box::new(graph));
 
     Ok(BuiltSimulation {
         entities,
+        graph_entity: graph_id,
         link_model,
         initial_events,
         node_infos,
+        rng_backend,
+    })
+}
+
+/// Temperature-dependent drift of a node's clock and radio crystals.
+///
+/// A crystal drifts if the node has a temperature model or the crystal has
+/// an offset; without a temperature model it sits at its turnover
+/// temperature.
+fn crystal_drifts(
+    resolved: &ResolvedProperties<NodeScope>,
+    sim_props: &ResolvedProperties<SimulationScope>,
+) -> (Option<mcsim_common::crystal::CrystalDrift>, Option<mcsim_common::crystal::CrystalDrift>) {
+    use mcsim_common::crystal::{CrystalConfig, CrystalDrift, TemperatureProfile, CRYSTAL_TURNOVER_C};
+
+    let temperature = temperature_profile(resolved, sim_props);
+    let drift = |offset_ppm: f64, coefficient_ppm_per_c2: f64| {
+        (temperature.is_some() || offset_ppm != 0.0).then(|| CrystalDrift {
+            temperature: temperature.unwrap_or_else(|| TemperatureProfile::constant(CRYSTAL_TURNOVER_C)),
+            crystal: CrystalConfig { offset_ppm, coefficient_ppm_per_c2 },
+        })
+    };
+    (
+        drift(
+            resolved.get(&properties::CRYSTAL_CLOCK_OFFSET_PPM),
+            resolved.get(&properties::CRYSTAL_CLOCK_COEFFICIENT),
+        ),
+        drift(
+            resolved.get(&properties::CRYSTAL_RADIO_OFFSET_PPM),
+            resolved.get(&properties::CRYSTAL_RADIO_COEFFICIENT),
+        ),
+    )
+}
+
+/// The node's TX power derating curve, if it has one.
+fn derating_config(
+    resolved: &ResolvedProperties<NodeScope>,
+    node_name: &str,
+) -> Result<Option<mcsim_lora::thermal::DeratingConfig>, ModelError> {
+    let duty_percent: Vec<f64> = resolved.get(&properties::THERMAL_DUTY_PERCENT);
+    let derating_db: Vec<f64> = resolved.get(&properties::THERMAL_DERATING_DB);
+    let time_constant_s: f64 = resolved.get(&properties::THERMAL_TIME_CONSTANT_S);
+    if duty_percent.is_empty() && derating_db.is_empty() {
+        return Ok(None);
+    }
+    let invalid = |reason: &str| ModelError::InvalidConfig(format!("Node '{}': {}", node_name, reason));
+    if duty_percent.len() != derating_db.len() {
+        return Err(invalid("thermal/duty_percent and thermal/derating_db must have the same number of points"));
+    }
+    if duty_percent.iter().any(|d| !(0.0..=100.0).contains(d)) || duty_percent.windows(2).any(|w| w[1] < w[0]) {
+        return Err(invalid("thermal/duty_percent must be ascending values in 0-100"));
+    }
+    if derating_db.iter().any(|db| !db.is_finite() || *db < 0.0) {
+        return Err(invalid("thermal/derating_db must be non-negative"));
+    }
+    if !(time_constant_s > 0.0 && time_constant_s.is_finite()) {
+        return Err(invalid("thermal/time_constant_s must be positive"));
+    }
+    Ok(Some(mcsim_lora::thermal::DeratingConfig {
+        time_constant: SimTime::from_secs(time_constant_s),
+        curve: duty_percent.iter().map(|d| d / 100.0).zip(derating_db).collect(),
+    }))
+}
+
+/// The node's regulatory limits, if it has any.
+fn regulatory_config(
+    resolved: &ResolvedProperties<NodeScope>,
+    node_name: &str,
+    frequency_hz: u32,
+) -> Result<Option<mcsim_lora::regulatory::RegulatoryConfig>, ModelError> {
+    use mcsim_lora::regulatory::{Enforcement, Region, RegulatoryConfig};
+
+    let invalid = |reason: String| ModelError::InvalidConfig(format!("Node '{}': {}", node_name, reason));
+    let region_name: String = resolved.get(&properties::REGULATORY_REGION);
+    let region = match region_name.to_lowercase().as_str() {
+        "none" => None,
+        "eu868" => Some(Region::Eu868),
+        "us915" => Some(Region::Us915),
+        other => {
+            return Err(invalid(format!(
+                "unknown regulatory/region '{}' (expected none, eu868 or us915)",
+                other
+            )))
+        }
+    };
+    let duty_cycle_percent: Option<f64> = resolved.get(&properties::REGULATORY_DUTY_CYCLE_PERCENT);
+    let max_dwell_ms: Option<f64> = resolved.get(&properties::REGULATORY_MAX_DWELL_MS);
+    let duty_cycle_limit = duty_cycle_percent
+        .map(|p| p / 100.0)
+        .or_else(|| region.and_then(|r| r.duty_cycle_limit(frequency_hz)));
+    let max_dwell = max_dwell_ms
+        .map(|ms| SimTime::from_secs(ms / 1000.0))
+        .or_else(|| region.and_then(Region::max_dwell));
+    if duty_cycle_limit.is_none() && max_dwell.is_none() {
+        return Ok(None);
+    }
+    if duty_cycle_percent.is_some_and(|p| !(p > 0.0 && p <= 100.0)) {
+        return Err(invalid("regulatory/duty_cycle_percent must be in (0, 100]".to_string()));
+    }
+    if max_dwell_ms.is_some_and(|ms| !(ms > 0.0 && ms.is_finite())) {
+        return Err(invalid("regulatory/max_dwell_ms must be positive".to_string()));
+    }
+    let window_s: f64 = resolved.get(&properties::REGULATORY_WINDOW_S);
+    if !(window_s > 0.0 && window_s.is_finite()) {
+        return Err(invalid("regulatory/window_s must be positive".to_string()));
+    }
+    let enforcement_name: String = resolved.get(&properties::REGULATORY_ENFORCEMENT);
+    let enforcement = match enforcement_name.to_lowercase().as_str() {
+        "record" => Enforcement::Record,
+        "queue" => Enforcement::Queue,
+        "drop" => Enforcement::Drop,
+        other => {
+            return Err(invalid(format!(
+                "unknown regulatory/enforcement '{}' (expected record, queue or drop)",
+                other
+            )))
+        }
+    };
+    Ok(Some(RegulatoryConfig {
+        duty_cycle_limit,
+        window: SimTime::from_secs(window_s),
+        max_dwell,
+        enforcement,
+    }))
+}
+
+/// The node's advert suppression policy, if it has one.
+fn advert_suppression_config(
+    resolved: &ResolvedProperties<NodeScope>,
+    node_name: &str,
+) -> Result<Option<mcsim_lora::suppression::AdvertSuppression>, ModelError> {
+    use mcsim_lora::suppression::{self, AdvertSuppression, PolicyParams};
+
+    let invalid = |reason: String| ModelError::InvalidConfig(format!("Node '{}': {}", node_name, reason));
+    let policy_name: String = resolved.get(&properties::ADVERT_SUPPRESSION_POLICY);
+    if policy_name == "none" {
+        return Ok(None);
+    }
+    let max_utilization: f64 = resolved.get(&properties::ADVERT_SUPPRESSION_MAX_UTILIZATION);
+    if !(0.0..=1.0).contains(&max_utilization) {
+        return Err(invalid("advert_suppression/max_utilization must be in [0, 1]".to_string()));
+    }
+    let window_s: f64 = resolved.get(&properties::ADVERT_SUPPRESSION_WINDOW_S);
+    if !(window_s > 0.0 && window_s.is_finite()) {
+        return Err(invalid("advert_suppression/window_s must be positive".to_string()));
+    }
+    let max_queue_depth: u32 = resolved.get(&properties::ADVERT_SUPPRESSION_MAX_QUEUE_DEPTH);
+    let params = PolicyParams { max_utilization, max_queue_depth: max_queue_depth as usize };
+    let policy = suppression::policy(&policy_name, &params).ok_or_else(|| {
+        invalid(format!(
+            "unknown advert_suppression/policy '{}' (expected one of {})",
+            policy_name,
+            suppression::policy_names().join(", ")
+        ))
+    })?;
+    Ok(Some(AdvertSuppression {
+        policy_name,
+        policy,
+        window: SimTime::from_secs(window_s),
+        max_consecutive: resolved.get(&properties::ADVERT_SUPPRESSION_MAX_CONSECUTIVE),
+    }))
+}
+
+/// The node's daily temperature cycle, if it has a temperature model.
+fn temperature_profile(
+    resolved: &ResolvedProperties<NodeScope>,
+    sim_props: &ResolvedProperties<SimulationScope>,
+) -> Option<mcsim_common::crystal::TemperatureProfile> {
+    let mean_c: Option<f64> = resolved.get(&properties::ENVIRONMENT_TEMPERATURE_MEAN_C);
+    mean_c.map(|mean_c| mcsim_common::crystal::TemperatureProfile {
+        mean_c,
+        daily_swing_c: resolved.get(&properties::ENVIRONMENT_TEMPERATURE_SWING_C),
+        warmest_hour: resolved.get(&properties::ENVIRONMENT_WARMEST_HOUR),
+        start_hour: sim_props.get(&properties::ENVIRONMENT_START_HOUR),
+    })
+}
+
+/// The node's barometric pressure sensor, if it has one.
+fn barometer(
+    node_name: &str,
+    resolved: &ResolvedProperties<NodeScope>,
+    sim_props: &ResolvedProperties<SimulationScope>,
+) -> Option<mcsim_common::barometer::Barometer> {
+    if !resolved.get(&properties::SENSOR_BAROMETER) {
+        return None;
+    }
+    let altitude_m: Option<f64> = resolved.get(&properties::LOCATION_ALTITUDE_M);
+    if altitude_m.is_none() {
+        log::warn!(
+            "Node '{}' has a barometer but no location/altitude_m; reporting sea level",
+            node_name
+        );
+    }
+    Some(mcsim_common::barometer::Barometer {
+        altitude_m: altitude_m.unwrap_or(0.0),
+        sea_level_hpa: resolved.get(&properties::SENSOR_SEA_LEVEL_PRESSURE_HPA),
+        noise_std_hpa: resolved.get(&properties::SENSOR_PRESSURE_NOISE_HPA),
+        temperature: temperature_profile(resolved, sim_props),
+    })
+}
+
+/// Whether a node is an interference source rather than a MeshCore node.
+fn is_jammer(node: &Node) -> bool {
+    let firmware_type: String = node.properties().get(&FIRMWARE_TYPE);
+    firmware_type.eq_ignore_ascii_case("jammer")
+}
+
+/// True for the nodes that forward traffic: repeaters and room servers.
+fn is_relay(node: &Node) -> bool {
+    let firmware_type: String = node.properties().get(&FIRMWARE_TYPE);
+    matches!(firmware_type.to_lowercase().as_str(), "repeater" | "room_server" | "roomserver")
+}
+
+/// Build the configuration of a jammer node from its properties.
+fn jammer_config(node: &Node, graph_id: EntityId) -> Result<mcsim_lora::jammer::JammerConfig, ModelError> {
+    let resolved = node.properties();
+    let kind_name: String = resolved.get(&properties::JAMMER_KIND);
+    let kind = match kind_name.to_lowercase().as_str() {
+        "noise" => mcsim_lora::InterferenceKind::Noise,
+        "cw" => mcsim_lora::InterferenceKind::ContinuousWave,
+        other => {
+            return Err(ModelError::InvalidConfig(format!(
+                "Node '{}': unknown jammer/kind '{}' (expected noise or cw)",
+                node.name, other
+            )))
+        }
+    };
+    let on_time_s: f64 = resolved.get(&properties::JAMMER_ON_TIME_S);
+    let period_s: f64 = resolved.get(&properties::JAMMER_PERIOD_S);
+    if on_time_s.is_nan() || on_time_s <= 0.0 || period_s.is_nan() || period_s < on_time_s {
+        return Err(ModelError::InvalidConfig(format!(
+            "Node '{}': jammer/on_time_s must be positive and no longer than jammer/period_s (got {} and {})",
+            node.name, on_time_s, period_s
+        )));
+    }
+    let bandwidth_hz: Option<u32> = resolved.get(&properties::JAMMER_BANDWIDTH_HZ);
+    let stop_s: Option<f64> = resolved.get(&properties::JAMMER_STOP_S);
+    Ok(mcsim_lora::jammer::JammerConfig {
+        kind,
+        frequency_hz: resolved.get(&RADIO_FREQUENCY_HZ),
+        bandwidth_hz: bandwidth_hz.unwrap_or_else(|| resolved.get(&RADIO_BANDWIDTH_HZ)),
+        tx_power_dbm: resolved.get(&RADIO_TX_POWER_DBM),
+        on_time: SimTime::from_secs(on_time_s),
+        period: SimTime::from_secs(period_s),
+        stop_time: stop_s.map(|s| SimTime::from_secs(s.max(0.0))),
+        graph_entity: graph_id,
+    })
+}
+
+/// Whether a node is real hardware attached through a radio bridge.
+fn is_bridge(node: &Node) -> bool {
+    let firmware_type: String = node.properties().get(&FIRMWARE_TYPE);
+    firmware_type.eq_ignore_ascii_case("bridge")
+}
+
+/// Build the configuration of a bridge node from its properties.
+fn bridge_config(
+    node: &Node,
+    graph_id: EntityId,
+    sim_props: &ResolvedProperties<SimulationScope>,
+) -> Result<mcsim_lora::bridge::BridgeConfig, ModelError> {
+    let resolved = node.properties();
+    let protocol_name: String = resolved.get(&properties::BRIDGE_PROTOCOL);
+    let protocol = protocol_name
+        .parse()
+        .map_err(|e| ModelError::InvalidConfig(format!("Node '{}': bridge/protocol: {}", node.name, e)))?;
+    Ok(mcsim_lora::bridge::BridgeConfig {
+        params: RadioParams {
+            frequency_hz: resolved.get(&RADIO_FREQUENCY_HZ),
+            bandwidth_hz: resolved.get(&RADIO_BANDWIDTH_HZ),
+            spreading_factor: resolved.get(&RADIO_SPREADING_FACTOR),
+            coding_rate: resolved.get(&RADIO_CODING_RATE),
+            tx_power_dbm: resolved.get(&RADIO_TX_POWER_DBM),
+        },
+        sync_word: resolved.get(&RADIO_SYNC_WORD),
+        preamble_symbols: sim_props.get(&properties::LORA_PREAMBLE_SYMBOLS),
+        protocol,
+        graph_entity: graph_id,
     })
 }
 
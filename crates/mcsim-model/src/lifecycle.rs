@@ -0,0 +1,174 @@
+//! Scheduled node lifecycle events.
+//!
+//! The `lifecycle` section powers individual nodes off and on, reboots
+//! them, and has nodes join or leave the network part-way through a run:
+//!
+//! ```yaml
+//! lifecycle:
+//!   - { node: Repeater1, action: power_off, at_s: 3600 }
+//!   - { node: Repeater1, action: power_on, at_s: 5400 }
+//!   - { node: Repeater2, action: reboot, at_s: 600 }
+//!   - { node: LateComer, action: join, at_s: 1800 }
+//!   - { node: Traveller, action: leave, at_s: 7200 }
+//! ```
+//!
+//! - `power_off` and `power_on` cut and restore a node's power; as with
+//!   outages, the firmware resumes with the state it had.
+//! - `reboot` restarts the firmware from its configuration, dropping
+//!   whatever it held in RAM (routes, pending messages) and any packet the
+//!   radio was sending or receiving.
+//! - `join` keeps a node off until `at_s`, when its firmware first starts;
+//!   its agents start relative to then.
+//! - `leave` powers a node off for the rest of the run.
+//!
+//! A node joins and leaves at most once, and its other events must fall
+//! between the two.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ModelError;
+
+/// What happens to a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleAction {
+    /// Cut the node's power.
+    PowerOff,
+    /// Restore the node's power.
+    PowerOn,
+    /// Restart the node's firmware from scratch.
+    Reboot,
+    /// Start the node for the first time.
+    Join,
+    /// Power the node off for good.
+    Leave,
+}
+
+impl LifecycleAction {
+    /// Name of the action as written in the model.
+    pub fn name(self) -> &'static str {
+        match self {
+            LifecycleAction::PowerOff => "power_off",
+            LifecycleAction::PowerOn => "power_on",
+            LifecycleAction::Reboot => "reboot",
+            LifecycleAction::Join => "join",
+            LifecycleAction::Leave => "leave",
+        }
+    }
+}
+
+/// A scheduled lifecycle event of a node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifecycleEvent {
+    /// Name of the node.
+    pub node: String,
+    /// What happens to it.
+    pub action: LifecycleAction,
+    /// Simulation time (seconds) at which it happens.
+    pub at_s: f64,
+}
+
+/// Check that each node joins and leaves at most once, with its other
+/// events in between.
+pub(crate) fn validate(events: &[LifecycleEvent]) -> Result<(), ModelError> {
+    for event in events {
+        let times = |action: LifecycleAction| {
+            events
+                .iter()
+                .filter(move |e| e.node == event.node && e.action == action)
+                .map(|e| e.at_s)
+        };
+        let invalid = |reason: &str| {
+            ModelError::InvalidConfig(format!(
+                "Lifecycle of '{}': {} at {}s {}",
+                event.node,
+                event.action.name(),
+                event.at_s,
+                reason
+            ))
+        };
+        match event.action {
+            LifecycleAction::Join | LifecycleAction::Leave if times(event.action).count() > 1 => {
+                return Err(invalid("is scheduled more than once"));
+            }
+            LifecycleAction::Join => {}
+            _ if times(LifecycleAction::Join).any(|join| event.at_s < join) => {
+                return Err(invalid("is before the node joins"));
+            }
+            LifecycleAction::Leave => {}
+            _ if times(LifecycleAction::Leave).any(|leave| event.at_s > leave) => {
+                return Err(invalid("is after the node leaves"));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// YAML Schema Types (Internal)
+// ============================================================================
+
+/// Lifecycle event (YAML schema, internal).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LifecycleEventYaml {
+    node: String,
+    action: LifecycleAction,
+    #[serde(default)]
+    at_s: f64,
+}
+
+impl LifecycleEventYaml {
+    pub(crate) fn resolve(&self) -> Result<LifecycleEvent, ModelError> {
+        if self.at_s.is_nan() || self.at_s < 0.0 {
+            return Err(ModelError::InvalidConfig(format!(
+                "Lifecycle of '{}': at_s must be non-negative",
+                self.node
+            )));
+        }
+        Ok(LifecycleEvent { node: self.node.clone(), action: self.action, at_s: self.at_s })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGY: &str = "nodes:\n  - name: A\n  - name: B\n";
+
+    #[test]
+    fn test_lifecycle_events() {
+        let overlay = "\
+lifecycle:
+  - { node: A, action: join, at_s: 60 }
+  - { node: A, action: reboot, at_s: 120 }
+  - { node: A, action: leave, at_s: 600 }
+  - { node: B, action: power_off, at_s: 30 }
+  - { node: B, action: power_on, at_s: 90 }
+";
+        let model = crate::load_models_from_str(&[TOPOLOGY, overlay]).unwrap();
+        let lifecycle = model.lifecycle();
+        assert_eq!(lifecycle.len(), 5);
+        assert_eq!(lifecycle[0].action, LifecycleAction::Join);
+        assert_eq!(lifecycle[1].action, LifecycleAction::Reboot);
+        assert_eq!(lifecycle[4].at_s, 90.0);
+        assert_eq!(model.join_time_s("A"), Some(60.0));
+        assert_eq!(model.join_time_s("B"), None);
+    }
+
+    #[test]
+    fn test_invalid_lifecycle_rejected() {
+        for events in [
+            "[{ node: A, action: hibernate }]",
+            "[{ node: C, action: reboot }]",
+            "[{ node: A, action: reboot, at_s: -1 }]",
+            "[{ node: A, action: join, at_s: 10 }, { node: A, action: join, at_s: 20 }]",
+            "[{ node: A, action: join, at_s: 10 }, { node: A, action: reboot, at_s: 5 }]",
+            "[{ node: A, action: leave, at_s: 10 }, { node: A, action: power_on, at_s: 20 }]",
+        ] {
+            let overlay = format!("lifecycle: {}\n", events);
+            assert!(crate::load_models_from_str(&[TOPOLOGY, &overlay]).is_err(), "{}", events);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Scenario-defined node movement.
+//!
+//! Each entry of the `mobility` section makes one node mobile. Its links are
+//! then recomputed from its position during the run (see
+//! [`mcsim_lora::mobility`]), replacing any edges it has in the model:
+//!
+//! ```yaml
+//! mobility:
+//!   - node: Hiker
+//!     waypoints:
+//!       - { at_s: 0, lat: 47.5950, lon: -122.4050 }
+//!       - { at_s: 3600, lat: 47.6250, lon: -122.3550 }
+//!   - node: Car
+//!     constant_velocity: { speed_mps: 15, heading_deg: 45 }
+//!   - node: Walker
+//!     random_waypoint:
+//!       bounds: [47.59, -122.41, 47.63, -122.35]
+//!       speed_mps: [0.5, 1.5]
+//!       pause_s: 120
+//! ```
+//!
+//! Constant velocity and random waypoint movement start at the node's
+//! `location`.
+
+use mcsim_common::GeoCoord;
+use mcsim_lora::mobility::{MobilityModel, Waypoint};
+use serde::{Deserialize, Serialize};
+
+use crate::ModelError;
+
+/// Movement of one node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeMobility {
+    /// Name of the moving node.
+    pub node: String,
+    /// How it moves.
+    pub model: MobilityModel,
+}
+
+// ============================================================================
+// YAML Schema Types (Internal)
+// ============================================================================
+
+/// Mobility entry (YAML schema, internal). Exactly one model must be given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct NodeMobilityYaml {
+    node: String,
+    #[serde(default)]
+    waypoints: Option<Vec<WaypointYaml>>,
+    #[serde(default)]
+    constant_velocity: Option<ConstantVelocityYaml>,
+    #[serde(default)]
+    random_waypoint: Option<RandomWaypointYaml>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WaypointYaml {
+    at_s: f64,
+    lat: f64,
+    lon: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConstantVelocityYaml {
+    speed_mps: f64,
+    heading_deg: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RandomWaypointYaml {
+    /// min_lat, min_lon, max_lat, max_lon
+    bounds: [f64; 4],
+    /// Lowest and highest leg speed.
+    speed_mps: [f64; 2],
+    #[serde(default)]
+    pause_s: f64,
+}
+
+impl NodeMobilityYaml {
+    pub(crate) fn resolve(&self) -> Result<NodeMobility, ModelError> {
+        let invalid = |reason: &str| {
+            ModelError::InvalidConfig(format!("Mobility for node '{}': {}", self.node, reason))
+        };
+
+        let model = match (&self.waypoints, &self.constant_velocity, &self.random_waypoint) {
+            (Some(waypoints), None, None) => {
+                if waypoints.is_empty() {
+                    return Err(invalid("waypoints must not be empty"));
+                }
+                if waypoints.windows(2).any(|w| w[1].at_s <= w[0].at_s) {
+                    return Err(invalid("waypoint times must be strictly increasing"));
+                }
+                MobilityModel::Waypoints(
+                    waypoints
+                        .iter()
+                        .map(|w| Waypoint { at_s: w.at_s, position: GeoCoord::new(w.lat, w.lon) })
+                        .collect(),
+                )
+            }
+            (None, Some(cv), None) => {
+                if cv.speed_mps.is_nan() || cv.speed_mps < 0.0 {
+                    return Err(invalid("speed_mps must be non-negative"));
+                }
+                MobilityModel::ConstantVelocity { speed_mps: cv.speed_mps, heading_deg: cv.heading_deg }
+            }
+            (None, None, Some(rw)) => {
+                let [min_lat, min_lon, max_lat, max_lon] = rw.bounds;
+                let [min_speed_mps, max_speed_mps] = rw.speed_mps;
+                if min_lat > max_lat || min_lon > max_lon {
+                    return Err(invalid("bounds must be min_lat, min_lon, max_lat, max_lon"));
+                }
+                if min_speed_mps.is_nan() || min_speed_mps <= 0.0 || max_speed_mps < min_speed_mps {
+                    return Err(invalid("speed_mps must be [min, max] with 0 < min <= max"));
+                }
+                MobilityModel::RandomWaypoint {
+                    min_lat,
+                    min_lon,
+                    max_lat,
+                    max_lon,
+                    min_speed_mps,
+                    max_speed_mps,
+                    pause_s: rw.pause_s.max(0.0),
+                }
+            }
+            _ => {
+                return Err(invalid(
+                    "exactly one of waypoints, constant_velocity or random_waypoint is required",
+                ))
+            }
+        };
+
+        Ok(NodeMobility { node: self.node.clone(), model })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(yaml: &str) -> Result<NodeMobility, ModelError> {
+        serde_yaml::from_str::<NodeMobilityYaml>(yaml).unwrap().resolve()
+    }
+
+    #[test]
+    fn test_parse_models() {
+        let waypoints = resolve(
+            "node: Hiker\nwaypoints:\n  - { at_s: 0, lat: 47.0, lon: -122.0 }\n  - { at_s: 60, lat: 47.1, lon: -122.0 }\n",
+        )
+        .unwrap();
+        assert!(matches!(&waypoints.model, MobilityModel::Waypoints(w) if w.len() == 2));
+
+        let walker = resolve(
+            "node: Walker\nrandom_waypoint:\n  bounds: [47.0, -122.1, 47.1, -122.0]\n  speed_mps: [0.5, 1.5]\n",
+        )
+        .unwrap();
+        assert!(matches!(walker.model, MobilityModel::RandomWaypoint { max_speed_mps, pause_s, .. }
+            if max_speed_mps == 1.5 && pause_s == 0.0));
+    }
+
+    #[test]
+    fn test_overlay_mobility() {
+        let topology = "nodes:\n  - name: Alice\n  - name: Bob\n";
+        let overlay = "mobility:\n  - node: Alice\n    constant_velocity: { speed_mps: 1.2, heading_deg: 90 }\n";
+        let model = crate::load_models_from_str(&[topology, overlay]).unwrap();
+        assert_eq!(model.mobility().len(), 1);
+        assert_eq!(model.mobility()[0].node, "Alice");
+
+        let unknown = "mobility:\n  - node: Carol\n    constant_velocity: { speed_mps: 1, heading_deg: 0 }\n";
+        assert!(matches!(
+            crate::load_models_from_str(&[topology, unknown]),
+            Err(ModelError::NodeNotFound(name)) if name == "Carol"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_mobility_rejected() {
+        // No model, two models, unordered waypoints
+        assert!(resolve("node: A\n").is_err());
+        assert!(resolve(
+            "node: A\nconstant_velocity: { speed_mps: 1, heading_deg: 0 }\nwaypoints: [{ at_s: 0, lat: 0, lon: 0 }]\n"
+        )
+        .is_err());
+        assert!(resolve(
+            "node: A\nwaypoints:\n  - { at_s: 10, lat: 0, lon: 0 }\n  - { at_s: 5, lat: 1, lon: 1 }\n"
+        )
+        .is_err());
+    }
+}
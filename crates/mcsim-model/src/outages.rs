@@ -0,0 +1,165 @@
+//! Mains-power outages over a geographic area.
+//!
+//! The `outages` section powers off every node located inside a polygon for
+//! a time window, as a storm or a grid failure would, then restores power:
+//!
+//! ```yaml
+//! outages:
+//!   - name: storm
+//!     at_s: 3600
+//!     duration_s: 7200
+//!     area: [[47.60, -122.40], [47.70, -122.40], [47.70, -122.20], [47.60, -122.20]]
+//! ```
+//!
+//! `area` lists the polygon's vertices as `[latitude, longitude]` pairs;
+//! nodes are matched on their configured location. Without `duration_s`
+//! power is not restored for the rest of the run.
+//!
+//! Restored nodes resume with the state they had when power was cut, as
+//! firmware keeps its identity and contacts in flash. Battery-powered nodes
+//! whose battery ran out stay off.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{properties, Model, ModelError};
+
+/// A power outage over an area.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerOutage {
+    /// Name of the outage, for reports.
+    pub name: String,
+    /// Simulation time (seconds) at which power is cut.
+    pub at_s: f64,
+    /// How long power stays off (seconds), or None for the rest of the run.
+    pub duration_s: Option<f64>,
+    /// Polygon vertices as (latitude, longitude).
+    pub area: Vec<(f64, f64)>,
+}
+
+impl PowerOutage {
+    /// Simulation time (seconds) at which power is restored, if it is.
+    pub fn restore_at_s(&self) -> Option<f64> {
+        self.duration_s.map(|duration| self.at_s + duration)
+    }
+
+    /// Whether a coordinate lies inside the outage area, by the even-odd
+    /// rule.
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        let mut inside = false;
+        let n = self.area.len();
+        for i in 0..n {
+            let (y1, x1) = self.area[i];
+            let (y2, x2) = self.area[(i + 1) % n];
+            if (y1 > lat) != (y2 > lat) && lon < x1 + (lat - y1) * (x2 - x1) / (y2 - y1) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Names of the model's nodes inside the outage area, sorted.
+    pub fn affected_nodes(&self, model: &Model) -> Vec<String> {
+        model
+            .nodes()
+            .iter()
+            .filter(|(_, node)| {
+                let props = node.properties();
+                self.contains(props.get(&properties::LOCATION_LATITUDE), props.get(&properties::LOCATION_LONGITUDE))
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+// ============================================================================
+// YAML Schema Types (Internal)
+// ============================================================================
+
+/// Power outage (YAML schema, internal).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PowerOutageYaml {
+    name: String,
+    #[serde(default)]
+    at_s: f64,
+    #[serde(default)]
+    duration_s: Option<f64>,
+    area: Vec<[f64; 2]>,
+}
+
+impl PowerOutageYaml {
+    pub(crate) fn resolve(&self) -> Result<PowerOutage, ModelError> {
+        let invalid = |reason: &str| ModelError::InvalidConfig(format!("Outage '{}': {}", self.name, reason));
+        if self.at_s.is_nan() || self.at_s < 0.0 {
+            return Err(invalid("at_s must be non-negative"));
+        }
+        if self.duration_s.is_some_and(|d| d.is_nan() || d <= 0.0) {
+            return Err(invalid("duration_s must be positive"));
+        }
+        if self.area.len() < 3 {
+            return Err(invalid("area needs at least 3 vertices"));
+        }
+        if self
+            .area
+            .iter()
+            .any(|[lat, lon]| !(-90.0..=90.0).contains(lat) || !(-180.0..=180.0).contains(lon))
+        {
+            return Err(invalid("area vertices must be [latitude, longitude] in degrees"));
+        }
+        Ok(PowerOutage {
+            name: self.name.clone(),
+            at_s: self.at_s,
+            duration_s: self.duration_s,
+            area: self.area.iter().map(|&[lat, lon]| (lat, lon)).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGY: &str = "\
+nodes:
+  - name: R1
+    location: { latitude: 47.65, longitude: -122.30 }
+  - name: R2
+    location: { latitude: 47.80, longitude: -122.30 }
+  - name: C1
+    location: { latitude: 47.62, longitude: -122.25 }
+";
+
+    #[test]
+    fn test_outage_area() {
+        let overlay = "\
+outages:
+  - name: storm
+    at_s: 60
+    duration_s: 120
+    area: [[47.6, -122.4], [47.7, -122.4], [47.7, -122.2], [47.6, -122.2]]
+";
+        let model = crate::load_models_from_str(&[TOPOLOGY, overlay]).unwrap();
+        let outage = &model.outages()[0];
+        assert_eq!(outage.restore_at_s(), Some(180.0));
+        assert_eq!(outage.affected_nodes(&model), vec!["C1", "R1"]);
+        assert!(!outage.contains(47.65, -122.5));
+    }
+
+    #[test]
+    fn test_invalid_outages_rejected() {
+        for overlay in [
+            "outages:\n  - { name: a, at_s: -1, area: [[47.6, -122.4], [47.7, -122.4], [47.7, -122.2]] }\n",
+            "outages:\n  - { name: a, duration_s: 0, area: [[47.6, -122.4], [47.7, -122.4], [47.7, -122.2]] }\n",
+            "outages:\n  - { name: a, area: [[47.6, -122.4], [47.7, -122.4]] }\n",
+            "outages:\n  - { name: a, area: [[-122.4, 47.6], [-122.4, 47.7], [-122.2, 47.7]] }\n",
+            // No node inside
+            "outages:\n  - { name: a, area: [[10.0, 10.0], [10.1, 10.0], [10.1, 10.1]] }\n",
+        ] {
+            assert!(
+                matches!(crate::load_models_from_str(&[TOPOLOGY, overlay]), Err(ModelError::InvalidConfig(_))),
+                "{}",
+                overlay
+            );
+        }
+    }
+}
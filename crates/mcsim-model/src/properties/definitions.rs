@@ -25,6 +25,10 @@
 //!
 //! ### Node Properties
 //! - **Radio** - LoRa radio configuration (frequency, bandwidth, spreading factor, etc.)
+//! - **Power** - Battery capacity and current draw
+//! - **Jammer** - Interference sources
+//! - **Environment** - Daily temperature cycle
+//! - **Crystal** - Temperature-dependent clock and carrier error
 //! - **Keys** - Cryptographic key specifications
 //! - **Location** - Geographic coordinates
 //! - **Firmware** - Firmware type and UART configuration
@@ -36,7 +40,7 @@
 //! - **CLI** - Properties for nodes with CLIs (repeaters and room servers)
 //!
 //! ### Edge Properties
-//! - **Link** - Radio link characteristics (SNR, RSSI)
+//! - **Link** - Radio link characteristics (SNR, RSSI, fading)
 //!
 //! ### Simulation Properties
 //! - **Simulation** - Global simulation settings (duration, seed, ports)
@@ -81,6 +85,8 @@ pub const RADIO_CODING_RATE: Property<u8, NodeScope> = Property::new(
 );
 
 /// Transmit power in dBm.
+///
+/// Link SNR on edges leaving this node is offset by the difference from 20 dBm.
 pub const RADIO_TX_POWER_DBM: Property<i8, NodeScope> = Property::new(
     "radio/tx_power_dbm",
     "Transmit power in dBm",
@@ -88,6 +94,378 @@ pub const RADIO_TX_POWER_DBM: Property<i8, NodeScope> = Property::new(
 )
 .with_unit("dBm");
 
+/// LoRa sync word (network ID).
+///
+/// Co-channel packets with a different sync word are interference only.
+pub const RADIO_SYNC_WORD: Property<u8, NodeScope> = Property::new(
+    "radio/sync_word",
+    "LoRa sync word (network ID) the node sends with and listens for; co-channel packets with another sync word keep the channel busy and can collide but are never decoded (0x12 is MeshCore's)",
+    PropertyDefault::Integer(0x12),
+);
+
+/// Peak antenna gain in dBi.
+///
+/// Used by link prediction together with the antenna pattern and orientation.
+pub const RADIO_ANTENNA_GAIN_DBI: Property<f64, NodeScope> = Property::new(
+    "radio/antenna_gain_dbi",
+    "Peak antenna gain in dBi, applied along the antenna boresight",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("dBi");
+
+/// Antenna boresight azimuth in degrees clockwise from true north.
+pub const RADIO_ANTENNA_AZIMUTH_DEG: Property<f64, NodeScope> = Property::new(
+    "radio/antenna_azimuth_deg",
+    "Antenna boresight azimuth in degrees clockwise from true north (ignored for isotropic patterns)",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("deg");
+
+/// Antenna boresight downtilt in degrees below the horizon.
+pub const RADIO_ANTENNA_DOWNTILT_DEG: Property<f64, NodeScope> = Property::new(
+    "radio/antenna_downtilt_deg",
+    "Antenna boresight downtilt in degrees below the horizon (ignored for isotropic patterns)",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("deg");
+
+/// Antenna radiation pattern.
+pub const RADIO_ANTENNA_PATTERN: Property<String, NodeScope> = Property::new(
+    "radio/antenna_pattern",
+    "Antenna radiation pattern: 'isotropic', 'cosine:<h_beamwidth_deg>,<v_beamwidth_deg>[,<front_to_back_db>]', or 'file:<path>' for a CSV of 'az|el,angle_deg,gain_db' cuts",
+    PropertyDefault::String("isotropic"),
+);
+
+// ============================================================================
+// Power Properties (Node scope)
+// ============================================================================
+
+/// Battery capacity in mAh.
+///
+/// Enables the radio's power model; see `mcsim_lora::power`.
+pub const POWER_BATTERY_CAPACITY_MAH: Property<f64, NodeScope> = Property::new(
+    "power/battery_capacity_mah",
+    "Battery capacity in mAh. The node draws current for its radio activity and powers off when the battery is empty. 0 means mains powered (no battery model)",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("mAh");
+
+/// Initial battery charge as a percentage of capacity.
+pub const POWER_BATTERY_INITIAL_PERCENT: Property<f64, NodeScope> = Property::new(
+    "power/battery_initial_percent",
+    "Battery charge at the start of the simulation, as a percentage of capacity (0-100)",
+    PropertyDefault::Float(100.0),
+)
+.with_unit("%");
+
+/// Current while transmitting at 20 dBm.
+pub const POWER_TX_CURRENT_MA: Property<f64, NodeScope> = Property::new(
+    "power/tx_current_ma",
+    "Current drawn while transmitting at 20 dBm, in mA. The part above the receive current scales with the node's radio/tx_power_dbm",
+    PropertyDefault::Float(120.0),
+)
+.with_unit("mA");
+
+/// Current while receiving a packet.
+pub const POWER_RX_CURRENT_MA: Property<f64, NodeScope> = Property::new(
+    "power/rx_current_ma",
+    "Current drawn while receiving a packet, in mA",
+    PropertyDefault::Float(12.0),
+)
+.with_unit("mA");
+
+/// Current while idle.
+pub const POWER_IDLE_CURRENT_MA: Property<f64, NodeScope> = Property::new(
+    "power/idle_current_ma",
+    "Current drawn while listening with nothing on the air (radio and MCU baseline), in mA",
+    PropertyDefault::Float(8.0),
+)
+.with_unit("mA");
+
+// ============================================================================
+// Thermal Properties (Node scope)
+// ============================================================================
+
+/// Average TX duty cycles at which the derating curve is specified.
+///
+/// Enables the radio's thermal derating model; see `mcsim_lora::thermal`.
+pub const THERMAL_DUTY_PERCENT: Property<Vec<f64>, NodeScope> = Property::new(
+    "thermal/duty_percent",
+    "Average TX duty cycles (0-100, ascending) of the TX power derating curve; thermal/derating_db gives the power reduction at each. Empty means no derating",
+    PropertyDefault::Vec(&[]),
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).array())
+.with_unit("%");
+
+/// TX power reduction at each point of the derating curve.
+pub const THERMAL_DERATING_DB: Property<Vec<f64>, NodeScope> = Property::new(
+    "thermal/derating_db",
+    "TX power reduction in dB at each of thermal/duty_percent, interpolated linearly between them and held beyond the ends",
+    PropertyDefault::Vec(&[]),
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).array())
+.with_unit("dB");
+
+/// Time constant of the duty cycle average.
+pub const THERMAL_TIME_CONSTANT_S: Property<f64, NodeScope> = Property::new(
+    "thermal/time_constant_s",
+    "Time constant of the exponentially weighted TX duty cycle average, i.e. how quickly the radio heats up under traffic and cools down after it",
+    PropertyDefault::Float(300.0),
+)
+.with_unit("s");
+
+// ============================================================================
+// Regulatory Properties (Node scope)
+// ============================================================================
+
+/// Regulatory region whose band plan limits the radio.
+///
+/// Enables the radio's regulatory limits; see `mcsim_lora::regulatory`.
+pub const REGULATORY_REGION: Property<String, NodeScope> = Property::new(
+    "regulatory/region",
+    "Regulatory region whose limits apply to the radio: 'none', 'eu868' (duty-cycle limit of the sub-band radio/frequency_hz is in, e.g. 10% at 869.525 MHz) or 'us915' (400 ms dwell time)",
+    PropertyDefault::String("none"),
+);
+
+/// Duty-cycle limit, overriding the region's.
+pub const REGULATORY_DUTY_CYCLE_PERCENT: Property<Option<f64>, NodeScope> = Property::new(
+    "regulatory/duty_cycle_percent",
+    "Largest percentage of regulatory/window_s the radio may transmit, overriding the region's limit (null = the region's)",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).nullable())
+.with_unit("%");
+
+/// Dwell-time limit, overriding the region's.
+pub const REGULATORY_MAX_DWELL_MS: Property<Option<f64>, NodeScope> = Property::new(
+    "regulatory/max_dwell_ms",
+    "Longest single transmission allowed, overriding the region's limit (null = the region's)",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).nullable())
+.with_unit("ms");
+
+/// Window over which the duty cycle is evaluated.
+pub const REGULATORY_WINDOW_S: Property<f64, NodeScope> = Property::new(
+    "regulatory/window_s",
+    "Sliding window over which the duty-cycle limit is evaluated",
+    PropertyDefault::Float(3600.0),
+)
+.with_unit("s");
+
+/// What the radio does with a transmission that breaks a limit.
+pub const REGULATORY_ENFORCEMENT: Property<String, NodeScope> = Property::new(
+    "regulatory/enforcement",
+    "What the radio does with a transmission that breaks a limit: 'record' (send it and count the violation), 'queue' (hold it until the duty cycle allows it) or 'drop' (discard it)",
+    PropertyDefault::String("record"),
+);
+
+// ============================================================================
+// Advert Suppression Properties (Node scope)
+// ============================================================================
+
+/// Policy that may skip the node's own adverts.
+///
+/// See `mcsim_lora::suppression`.
+pub const ADVERT_SUPPRESSION_POLICY: Property<String, NodeScope> = Property::new(
+    "advert_suppression/policy",
+    "Policy deciding whether the node skips an advert it originates: 'none', 'utilization' (while the channel utilization exceeds advert_suppression/max_utilization), 'queue_depth' (while advert_suppression/max_queue_depth packets wait in the firmware's queue), 'adaptive' (either), or a policy registered by the experiment",
+    PropertyDefault::String("none"),
+);
+
+/// Channel utilization above which the channel counts as congested.
+pub const ADVERT_SUPPRESSION_MAX_UTILIZATION: Property<f64, NodeScope> = Property::new(
+    "advert_suppression/max_utilization",
+    "Fraction (0 to 1) of advert_suppression/window_s the channel may be busy before adverts are skipped",
+    PropertyDefault::Float(0.3),
+);
+
+/// Queued packets at which the node counts as backlogged.
+pub const ADVERT_SUPPRESSION_MAX_QUEUE_DEPTH: Property<u32, NodeScope> = Property::new(
+    "advert_suppression/max_queue_depth",
+    "Packets waiting in the firmware's outbound queue at which adverts are skipped (repeaters only)",
+    PropertyDefault::Integer(2),
+);
+
+/// Window over which the channel utilization is measured.
+pub const ADVERT_SUPPRESSION_WINDOW_S: Property<f64, NodeScope> = Property::new(
+    "advert_suppression/window_s",
+    "Sliding window over which the radio measures the channel utilization",
+    PropertyDefault::Float(60.0),
+)
+.with_unit("s");
+
+/// Most adverts skipped in a row.
+pub const ADVERT_SUPPRESSION_MAX_CONSECUTIVE: Property<u32, NodeScope> = Property::new(
+    "advert_suppression/max_consecutive",
+    "Most adverts skipped in a row; the next one is sent whatever the policy says, bounding discovery latency",
+    PropertyDefault::Integer(3),
+);
+
+// ============================================================================
+// Jammer Properties (Node scope)
+// ============================================================================
+
+/// Kind of interference a jammer emits.
+///
+/// Only used by nodes with `firmware/type: jammer`; see `mcsim_lora::jammer`.
+pub const JAMMER_KIND: Property<String, NodeScope> = Property::new(
+    "jammer/kind",
+    "Interference a jammer emits: 'noise' (wideband noise over jammer/bandwidth_hz) or 'cw' (an unmodulated carrier, attenuated by the receiver's spreading gain)",
+    PropertyDefault::String("noise"),
+);
+
+/// Bandwidth of a noise jammer.
+pub const JAMMER_BANDWIDTH_HZ: Property<Option<u32>, NodeScope> = Property::new(
+    "jammer/bandwidth_hz",
+    "Bandwidth of a noise jammer, centered on radio/frequency_hz (null = radio/bandwidth_hz)",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Integer).nullable())
+.with_unit("Hz");
+
+/// Length of each jammer burst.
+pub const JAMMER_ON_TIME_S: Property<f64, NodeScope> = Property::new(
+    "jammer/on_time_s",
+    "Length of each jammer burst",
+    PropertyDefault::Float(1.0),
+)
+.with_unit("s");
+
+/// Jammer burst period.
+pub const JAMMER_PERIOD_S: Property<f64, NodeScope> = Property::new(
+    "jammer/period_s",
+    "Time from the start of one jammer burst to the next. Equal to jammer/on_time_s for a continuous jammer",
+    PropertyDefault::Float(10.0),
+)
+.with_unit("s");
+
+/// When the jammer switches on.
+pub const JAMMER_START_S: Property<f64, NodeScope> = Property::new(
+    "jammer/start_s",
+    "Simulation time of the jammer's first burst",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("s");
+
+/// When the jammer switches off.
+pub const JAMMER_STOP_S: Property<Option<f64>, NodeScope> = Property::new(
+    "jammer/stop_s",
+    "Simulation time at which the jammer switches off for good (null = never)",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).nullable())
+.with_unit("s");
+
+// ============================================================================
+// Bridge Properties (Node scope)
+// ============================================================================
+
+/// Wire format spoken with hardware attached through a radio bridge.
+///
+/// Only used by nodes with `firmware/type: bridge`; see `mcsim_lora::bridge`.
+pub const BRIDGE_PROTOCOL: Property<String, NodeScope> = Property::new(
+    "bridge/protocol",
+    "Wire format spoken with the hardware over the node's UART port: 'json' (one JSON object per packet per line) or 'rs232' (MeshCore RS232 bridge frames)",
+    PropertyDefault::String("json"),
+);
+
+// ============================================================================
+// Environment Properties (Node scope)
+// ============================================================================
+
+/// Daily mean temperature at the node; enables crystal drift.
+///
+/// See `mcsim_common::crystal` for the temperature and crystal models.
+pub const ENVIRONMENT_TEMPERATURE_MEAN_C: Property<Option<f64>, NodeScope> = Property::new(
+    "environment/temperature_mean_c",
+    "Daily mean temperature at the node. When set, the node's clock and radio carrier drift with temperature as configured by the crystal/ properties (null = no temperature model; crystal offsets still apply)",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).nullable())
+.with_unit("°C");
+
+/// Daily temperature swing at the node.
+pub const ENVIRONMENT_TEMPERATURE_SWING_C: Property<f64, NodeScope> = Property::new(
+    "environment/temperature_swing_c",
+    "Difference between the daily high and low temperature at the node; temperature follows a sinusoid around environment/temperature_mean_c",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("°C");
+
+/// Hour of the daily high.
+pub const ENVIRONMENT_WARMEST_HOUR: Property<f64, NodeScope> = Property::new(
+    "environment/warmest_hour",
+    "Hour of the day (0-24) at which the node's temperature peaks",
+    PropertyDefault::Float(15.0),
+)
+.with_unit("h");
+
+// ============================================================================
+// Crystal Properties (Node scope)
+// ============================================================================
+
+/// Error of the clock crystal at its turnover temperature.
+pub const CRYSTAL_CLOCK_OFFSET_PPM: Property<f64, NodeScope> = Property::new(
+    "crystal/clock_offset_ppm",
+    "Frequency error of the node's clock crystal at 25 °C; positive values make the firmware's millisecond clock and RTC run fast",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("ppm");
+
+/// Temperature coefficient of the clock crystal.
+pub const CRYSTAL_CLOCK_COEFFICIENT: Property<f64, NodeScope> = Property::new(
+    "crystal/clock_coefficient_ppm_per_c2",
+    "How fast the clock crystal slows away from 25 °C: the error is offset - coefficient * (T - 25)^2. The default is a 32.768 kHz tuning-fork crystal",
+    PropertyDefault::Float(0.034),
+)
+.with_unit("ppm/°C²");
+
+/// Error of the radio crystal at its turnover temperature.
+pub const CRYSTAL_RADIO_OFFSET_PPM: Property<f64, NodeScope> = Property::new(
+    "crystal/radio_offset_ppm",
+    "Frequency error of the radio's reference crystal at 25 °C, which shifts the carrier; receivers lock onto packets offset by up to a quarter of the bandwidth",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("ppm");
+
+/// Temperature coefficient of the radio crystal.
+pub const CRYSTAL_RADIO_COEFFICIENT: Property<f64, NodeScope> = Property::new(
+    "crystal/radio_coefficient_ppm_per_c2",
+    "How fast the radio crystal's frequency falls away from 25 °C (see crystal/clock_coefficient_ppm_per_c2); about 0.01 for a plain crystal, 0.001 or less for a TCXO",
+    PropertyDefault::Float(0.01),
+)
+.with_unit("ppm/°C²");
+
+// ============================================================================
+// Sensor Properties (Node scope)
+// ============================================================================
+
+/// Whether the node has a barometric pressure sensor.
+///
+/// See `mcsim_common::barometer` for the pressure model.
+pub const SENSOR_BAROMETER: Property<bool, NodeScope> = Property::new(
+    "sensor/barometer",
+    "Whether the node has a barometric pressure sensor. Its pressure, the altitude derived from it and the temperature (environment/ properties) are included in telemetry for requesters with environment permission; the true pressure follows location/altitude_m",
+    PropertyDefault::Bool(false),
+);
+
+/// Noise of the barometric pressure sensor.
+pub const SENSOR_PRESSURE_NOISE_HPA: Property<f64, NodeScope> = Property::new(
+    "sensor/pressure_noise_hpa",
+    "Standard deviation of the barometer's pressure noise; 0.12 hPa (about 1 m of altitude) is typical of a BMP280",
+    PropertyDefault::Float(0.12),
+)
+.with_unit("hPa");
+
+/// Sea-level pressure of the weather at the node.
+pub const SENSOR_SEA_LEVEL_PRESSURE_HPA: Property<f64, NodeScope> = Property::new(
+    "sensor/sea_level_pressure_hpa",
+    "Sea-level air pressure the barometer's reading is derived from, and the reference it converts pressure back to altitude with",
+    PropertyDefault::Float(1013.25),
+)
+.with_unit("hPa");
+
 // ============================================================================
 // Keys Properties (Node scope)
 // ============================================================================
@@ -106,6 +484,13 @@ pub const KEYS_PUBLIC_KEY: Property<String, NodeScope> = Property::new(
     PropertyDefault::String("*"),
 );
 
+/// Replace duplicate node keys with random ones instead of failing.
+pub const KEYS_REGENERATE_DUPLICATES: Property<bool, SimulationScope> = Property::new(
+    "keys/regenerate_duplicates",
+    "When two nodes resolve to the same public key, log a warning and give the later node a random keypair instead of failing to build the simulation",
+    PropertyDefault::Bool(false),
+);
+
 // ============================================================================
 // Location Properties (Node scope)
 // ============================================================================
@@ -138,6 +523,26 @@ pub const LOCATION_ALTITUDE_M: Property<Option<f64>, NodeScope> = Property::new(
 .with_unit("m")
 .with_aliases(&["location/alt"]);
 
+/// Projected easting of the node (nullable).
+pub const LOCATION_X_M: Property<Option<f64>, NodeScope> = Property::new(
+    "location/x_m",
+    "Easting of the node in the scenario's projected coordinate reference system (simulation location/crs). When set with location/y_m, replaces location/latitude and location/longitude",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).nullable())
+.with_unit("m")
+.with_aliases(&["location/easting_m"]);
+
+/// Projected northing of the node (nullable).
+pub const LOCATION_Y_M: Property<Option<f64>, NodeScope> = Property::new(
+    "location/y_m",
+    "Northing of the node in the scenario's projected coordinate reference system (simulation location/crs). When set with location/x_m, replaces location/latitude and location/longitude",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).nullable())
+.with_unit("m")
+.with_aliases(&["location/northing_m"]);
+
 // ============================================================================
 // Firmware Properties (Node scope)
 // ============================================================================
@@ -145,7 +550,7 @@ pub const LOCATION_ALTITUDE_M: Property<Option<f64>, NodeScope> = Property::new(
 /// Firmware type of the node ("repeater", "companion", "roomserver").
 pub const FIRMWARE_TYPE: Property<String, NodeScope> = Property::new(
     "firmware/type",
-    "Firmware type of the node (\"repeater\", \"companion\", \"roomserver\")",
+    "Firmware type of the node (\"repeater\", \"companion\", \"roomserver\", \"jammer\" for an interference source without firmware, or \"bridge\" for real hardware attached through firmware/uart_port)",
     PropertyDefault::String("Repeater"),
 );
 
@@ -158,6 +563,29 @@ pub const FIRMWARE_UART_PORT: Property<Option<u16>, NodeScope> = Property::new(
 .with_type(PropertyType::new(PropertyBaseType::Integer).nullable())
 .with_unit("port");
 
+/// Fixed latency added to the UART TCP bridge in each direction.
+pub const FIRMWARE_UART_LATENCY_MS: Property<f64, NodeScope> = Property::new(
+    "firmware/uart_latency_ms",
+    "Fixed latency added to data crossing the UART TCP bridge in each direction (e.g. to emulate a Bluetooth link)",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("ms");
+
+/// Jitter added on top of the UART bridge latency.
+pub const FIRMWARE_UART_JITTER_MS: Property<f64, NodeScope> = Property::new(
+    "firmware/uart_jitter_ms",
+    "Random jitter added on top of uart_latency_ms; interpreted according to uart_jitter_distribution. Data is never reordered",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("ms");
+
+/// Distribution of the UART bridge jitter.
+pub const FIRMWARE_UART_JITTER_DISTRIBUTION: Property<String, NodeScope> = Property::new(
+    "firmware/uart_jitter_distribution",
+    "Distribution of uart_jitter_ms: 'uniform' (0 to jitter), 'normal' (jitter is the standard deviation) or 'exponential' (jitter is the mean)",
+    PropertyDefault::String("uniform"),
+);
+
 /// Startup delay time for the firmware in seconds.
 /// The firmware will not process any events (radio, serial, timer) until this time has passed.
 /// The actual startup time is: startup_time_s + random(-startup_time_jitter_s, +startup_time_jitter_s)
@@ -177,6 +605,15 @@ pub const FIRMWARE_STARTUP_JITTER_S: Property<f64, NodeScope> = Property::new(
 )
 .with_unit("s");
 
+/// Flood hop limit of repeaters and room servers (the firmware's `flood.max`).
+/// Flood packets that have already travelled this many hops are not forwarded.
+pub const FIRMWARE_FLOOD_MAX: Property<u8, NodeScope> = Property::new(
+    "firmware/flood_max",
+    "Flood packets that have already travelled this many hops are not forwarded by repeaters and room servers (firmware flood.max, 1-64)",
+    PropertyDefault::Integer(64),
+)
+.with_unit("hops");
+
 // ============================================================================
 // Companion Properties (Node scope)
 // ============================================================================
@@ -192,7 +629,7 @@ pub const COMPANION_CHANNELS: Property<Option<Vec<String>>, NodeScope> = Propert
 /// Contacts to add at startup.
 pub const COMPANION_CONTACTS: Property<Option<Vec<String>>, NodeScope> = Property::new(
     "companion/contacts",
-    "Contacts to add at startup (list of node names, resolved to public keys). If null, nodes must exchange advertisements",
+    "Contacts to add at startup (list of node names, resolved to public keys). If null, contacts are pre-populated per companion/bootstrap",
     PropertyDefault::Null,
 )
 .with_type(PropertyType::new(PropertyBaseType::String).array());
@@ -205,6 +642,21 @@ pub const COMPANION_AUTO_CONTACTS_MAX: Property<u32, NodeScope> = Property::new(
 )
 .with_unit("count");
 
+/// How contacts are pre-populated when companion/contacts is null.
+pub const COMPANION_BOOTSTRAP: Property<String, NodeScope> = Property::new(
+    "companion/bootstrap",
+    "How contacts are pre-populated when companion/contacts is null: 'full' (every other node), 'neighbors' (nodes within companion/bootstrap_radius_km) or 'none' (learned from advertisements)",
+    PropertyDefault::String("full"),
+);
+
+/// Range of the 'neighbors' contact bootstrap.
+pub const COMPANION_BOOTSTRAP_RADIUS_KM: Property<f64, NodeScope> = Property::new(
+    "companion/bootstrap_radius_km",
+    "Maximum distance of the contacts pre-populated by the 'neighbors' bootstrap",
+    PropertyDefault::Float(5.0),
+)
+.with_unit("km");
+
 // ============================================================================
 // Messaging Properties (Node scope)
 // ============================================================================
@@ -257,6 +709,35 @@ pub const ROOM_SERVER_ROOM_ID: Property<Option<String>, NodeScope> = Property::n
     PropertyDefault::Null,
 );
 
+/// Number of posts the room server keeps for clients that have not synced.
+pub const ROOM_SERVER_MAX_POSTS: Property<u32, NodeScope> = Property::new(
+    "room_server/max_posts",
+    "Number of posts retained for clients that have not synced yet; older posts are overwritten. The firmware buffer is fixed at build time (MAX_UNSYNCED_POSTS = 32), so this only affects retention measurements",
+    PropertyDefault::Integer(32),
+);
+
+/// Age after which a retained post is no longer served.
+pub const ROOM_SERVER_POST_TTL_S: Property<Option<f64>, NodeScope> = Property::new(
+    "room_server/post_ttl_s",
+    "Age after which a retained post is no longer served to reconnecting clients (null = posts never expire)",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).nullable())
+.with_unit("s");
+
+/// Client reconnect delays at which post availability is measured.
+pub const ROOM_SERVER_RECONNECT_DELAYS_S: Property<Vec<f64>, NodeScope> = Property::new(
+    "room_server/reconnect_delays_s",
+    "Client reconnect delays at which message availability is measured: the fraction of posts still retained when a client reconnects this long after the post arrived",
+    PropertyDefault::Vec(&[
+        PropertyDefault::Float(60.0),
+        PropertyDefault::Float(600.0),
+        PropertyDefault::Float(3600.0),
+    ]),
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).array())
+.with_unit("s");
+
 // ============================================================================
 // CLI Properties (Node scope)
 // ============================================================================
@@ -470,6 +951,140 @@ pub const AGENT_CHANNEL_SHUTDOWN_S: Property<Option<f64>, NodeScope> = Property:
 .with_type(PropertyType::new(PropertyBaseType::Float).nullable())
 .with_unit("s");
 
+// ============================================================================
+// Agent Phone App Properties (Node scope)
+// ============================================================================
+
+/// Connect and disconnect like a phone app instead of staying connected.
+pub const AGENT_PHONE_ENABLED: Property<bool, NodeScope> = Property::new(
+    "agent/phone/enabled",
+    "Connect and disconnect periodically like a phone app, syncing queued messages on each connect, instead of staying connected to the companion",
+    PropertyDefault::Bool(false),
+);
+
+/// Time the app stays connected.
+pub const AGENT_PHONE_CONNECTED_S: Property<f64, NodeScope> = Property::new(
+    "agent/phone/connected_s",
+    "Time the app stays connected before going to the background",
+    PropertyDefault::Float(300.0),
+)
+.with_unit("s");
+
+/// Standard deviation in the randomness of the connected time.
+pub const AGENT_PHONE_CONNECTED_JITTER_S: Property<f64, NodeScope> = Property::new(
+    "agent/phone/connected_jitter_s",
+    "Standard deviation in the randomness of the connected time",
+    PropertyDefault::Float(60.0),
+)
+.with_unit("s");
+
+/// Time the app spends disconnected.
+pub const AGENT_PHONE_DISCONNECTED_S: Property<f64, NodeScope> = Property::new(
+    "agent/phone/disconnected_s",
+    "Time the app spends disconnected in the background",
+    PropertyDefault::Float(1800.0),
+)
+.with_unit("s");
+
+/// Standard deviation in the randomness of the disconnected time.
+pub const AGENT_PHONE_DISCONNECTED_JITTER_S: Property<f64, NodeScope> = Property::new(
+    "agent/phone/disconnected_jitter_s",
+    "Standard deviation in the randomness of the disconnected time",
+    PropertyDefault::Float(600.0),
+)
+.with_unit("s");
+
+/// Answer received DMs with a read receipt.
+pub const AGENT_PHONE_READ_RECEIPTS: Property<bool, NodeScope> = Property::new(
+    "agent/phone/read_receipts",
+    "Answer each received DM with a read receipt DM once the user has read it",
+    PropertyDefault::Bool(false),
+);
+
+/// Time between receiving a DM and reading it.
+pub const AGENT_PHONE_READ_DELAY_S: Property<f64, NodeScope> = Property::new(
+    "agent/phone/read_delay_s",
+    "Time between the app receiving a DM and the user reading it",
+    PropertyDefault::Float(30.0),
+)
+.with_unit("s");
+
+/// Standard deviation in the randomness of the read delay.
+pub const AGENT_PHONE_READ_DELAY_JITTER_S: Property<f64, NodeScope> = Property::new(
+    "agent/phone/read_delay_jitter_s",
+    "Standard deviation in the randomness of the read delay",
+    PropertyDefault::Float(10.0),
+)
+.with_unit("s");
+
+// ============================================================================
+// Agent Room Client Properties (Node scope)
+// ============================================================================
+
+/// Room server the companion's agent joins as a client (null = none).
+pub const AGENT_ROOM_TARGET: Property<Option<String>, NodeScope> = Property::new(
+    "agent/room/target",
+    "Name of the room server node the agent logs in to and posts to (null = none)",
+    PropertyDefault::Null,
+);
+
+/// Guest password the agent logs in with.
+pub const AGENT_ROOM_PASSWORD: Property<String, NodeScope> = Property::new(
+    "agent/room/password",
+    "Guest password the agent logs in to the room server with",
+    PropertyDefault::String("hello"),
+);
+
+/// Delay after the agent is ready before it logs in to the room.
+pub const AGENT_ROOM_LOGIN_S: Property<f64, NodeScope> = Property::new(
+    "agent/room/login_s",
+    "Delay after the agent is ready before it logs in to the room server",
+    PropertyDefault::Float(30.0),
+)
+.with_unit("s");
+
+/// Interval between the agent's room posts.
+pub const AGENT_ROOM_POST_INTERVAL_S: Property<f64, NodeScope> = Property::new(
+    "agent/room/post_interval_s",
+    "Interval between the agent's posts to the room server",
+    PropertyDefault::Float(600.0),
+)
+.with_unit("s");
+
+/// Standard deviation in the randomness of the room post interval.
+pub const AGENT_ROOM_POST_INTERVAL_JITTER_S: Property<f64, NodeScope> = Property::new(
+    "agent/room/post_interval_jitter_s",
+    "Standard deviation in the randomness of the room post interval",
+    PropertyDefault::Float(120.0),
+)
+.with_unit("s");
+
+/// Posts before the agent only reads the room (null = unlimited).
+pub const AGENT_ROOM_POST_COUNT: Property<Option<u32>, NodeScope> = Property::new(
+    "agent/room/post_count",
+    "Posts the agent sends before it only reads the room (null = unlimited)",
+    PropertyDefault::Null,
+);
+
+// ============================================================================
+// Agent GPS Properties (Node scope)
+// ============================================================================
+
+/// Whether the agent shares the node's location with the companion.
+pub const AGENT_GPS_ENABLED: Property<bool, NodeScope> = Property::new(
+    "agent/gps/enabled",
+    "Whether the agent sets the companion's advert location from the node's position, updating it as the node moves",
+    PropertyDefault::Bool(false),
+);
+
+/// Shortest time between the agent's location updates.
+pub const AGENT_GPS_INTERVAL_S: Property<f64, NodeScope> = Property::new(
+    "agent/gps/interval_s",
+    "Shortest time between location updates the agent sends to the companion",
+    PropertyDefault::Float(60.0),
+)
+.with_unit("s");
+
 // ============================================================================
 // Metrics Properties (Node scope)
 // ============================================================================
@@ -482,6 +1097,18 @@ pub const METRICS_GROUPS: Property<Vec<String>, NodeScope> = Property::new(
 )
 .with_type(PropertyType::new(PropertyBaseType::String).array());
 
+// ============================================================================
+// Failure Properties (Node scope)
+// ============================================================================
+
+/// Failure domains this node belongs to.
+pub const FAILURE_DOMAINS: Property<Vec<String>, NodeScope> = Property::new(
+    "failure/domains",
+    "Failure domains this node belongs to, e.g. a shared mast or power source. Failing a domain powers off all of its nodes at once",
+    PropertyDefault::Vec(&[]),
+)
+.with_type(PropertyType::new(PropertyBaseType::String).array());
+
 // ============================================================================
 // Metrics Properties (Simulation scope)
 // ============================================================================
@@ -494,6 +1121,29 @@ pub const METRICS_WARMUP_S: Property<f64, SimulationScope> = Property::new(
 )
 .with_unit("s");
 
+/// Whether metrics are recorded during the warmup period.
+pub const METRICS_RECORD_DURING_WARMUP: Property<bool, SimulationScope> = Property::new(
+    "metrics/record_during_warmup",
+    "Record metrics during the warmup period. When false, recording is switched off until warmup ends, avoiding the recording overhead for discarded data",
+    PropertyDefault::Bool(true),
+);
+
+/// Metric name prefixes that are not recorded.
+pub const METRICS_DISABLED_CATEGORIES: Property<Vec<String>, SimulationScope> = Property::new(
+    "metrics/disabled_categories",
+    "Metric name prefixes to skip recording for (e.g. mcsim.radio, mcsim.timing)",
+    PropertyDefault::Vec(&[]),
+)
+.with_type(PropertyType::new(PropertyBaseType::String).array());
+
+/// Interval between evaluations of the scenario's alert rules.
+pub const METRICS_ALERT_CHECK_INTERVAL_S: Property<f64, SimulationScope> = Property::new(
+    "metrics/alert_check_interval_s",
+    "Simulation time between evaluations of the alert rules in the scenario's alerts section",
+    PropertyDefault::Float(5.0),
+)
+.with_unit("s");
+
 // ============================================================================
 // Link Properties (Edge scope)
 // ============================================================================
@@ -525,6 +1175,23 @@ pub const LINK_RSSI_DBM: Property<f64, EdgeScope> = Property::new(
 .with_unit("dBm")
 .with_aliases(&["rssi_dbm"]);
 
+/// Per-packet multipath fading model of the link.
+pub const LINK_FADING: Property<String, EdgeScope> = Property::new(
+    "link/fading",
+    "Per-packet multipath fading on top of link/snr_std_dev: 'none', 'rayleigh' (obstructed paths) or 'rician' (line of sight, see link/rician_k_db)",
+    PropertyDefault::String("none"),
+)
+.with_aliases(&["fading"]);
+
+/// Rician K-factor of a line-of-sight link.
+pub const LINK_RICIAN_K_DB: Property<f64, EdgeScope> = Property::new(
+    "link/rician_k_db",
+    "Ratio of direct to scattered power for link/fading: rician; larger values fade less",
+    PropertyDefault::Float(6.0),
+)
+.with_unit("dB")
+.with_aliases(&["rician_k_db"]);
+
 // ============================================================================
 // Simulation Properties (Simulation scope)
 // ============================================================================
@@ -544,6 +1211,33 @@ pub const SIMULATION_SEED: Property<i64, SimulationScope> = Property::new(
     PropertyDefault::Integer(0),
 );
 
+/// Coordinate reference system of projected node positions.
+pub const LOCATION_CRS: Property<String, SimulationScope> = Property::new(
+    "location/crs",
+    "EPSG code of the coordinate reference system node location/x_m and location/y_m are given in: UTM zones on WGS84 (EPSG:326xx, 327xx), NAD83 (EPSG:269xx) or ETRS89 (EPSG:258xx), or Web Mercator (EPSG:3857). Positions are converted to WGS84 when the model is loaded",
+    PropertyDefault::String("EPSG:4326"),
+);
+
+/// Random number backend.
+///
+/// "philox" gives each entity an independent counter-based stream, so draws
+/// don't depend on event interleaving; "chacha" shares one generator (legacy).
+pub const SIMULATION_RNG_BACKEND: Property<String, SimulationScope> = Property::new(
+    "simulation/rng_backend",
+    "Random number backend: philox (per-entity counter-based streams) or chacha (shared, legacy)",
+    PropertyDefault::String("philox"),
+);
+
+/// What to do when link analysis predicts a node cannot reach the network.
+///
+/// Checked before the simulation starts: "warn" prints a report and continues,
+/// "fail" aborts with the report, "ignore" skips the check.
+pub const SIMULATION_UNREACHABLE_NODES: Property<String, SimulationScope> = Property::new(
+    "simulation/unreachable_nodes",
+    "Action when nodes are predicted unreachable at startup: warn, fail, or ignore",
+    PropertyDefault::String("warn"),
+);
+
 /// Base TCP port for UART connections.
 pub const SIMULATION_UART_BASE_PORT: Property<u16, SimulationScope> = Property::new(
     "simulation/uart_base_port",
@@ -556,6 +1250,21 @@ pub const SIMULATION_UART_BASE_PORT: Property<u16, SimulationScope> = Property::
 // Radio Thresholds (Simulation scope)
 // ============================================================================
 
+/// Width of the bit-error window below the SNR threshold.
+pub const RADIO_BIT_ERROR_WINDOW_DB: Property<f64, SimulationScope> = Property::new(
+    "radio/bit_error_window_db",
+    "Packets whose SNR is at most this far below the demodulation threshold are delivered to firmware with flipped bits instead of being dropped (0 disables the bit-error channel)",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("dB");
+
+/// Per-bit error rate at the bottom of the bit-error window.
+pub const RADIO_BIT_ERROR_RATE_MAX: Property<f64, SimulationScope> = Property::new(
+    "radio/bit_error_rate_max",
+    "Per-bit error rate at the bottom of the bit-error window; scales linearly from zero at the SNR threshold (at least one bit is always flipped)",
+    PropertyDefault::Float(0.01),
+);
+
 /// Capture effect threshold - minimum SNR difference for capture.
 pub const RADIO_CAPTURE_EFFECT_THRESHOLD_DB: Property<f64, SimulationScope> = Property::new(
     "radio/capture_effect_threshold_db",
@@ -564,6 +1273,14 @@ pub const RADIO_CAPTURE_EFFECT_THRESHOLD_DB: Property<f64, SimulationScope> = Pr
 )
 .with_unit("dB");
 
+/// Rejection of packets on partially overlapping channels.
+pub const RADIO_ADJACENT_CHANNEL_REJECTION_DB: Property<f64, SimulationScope> = Property::new(
+    "radio/adjacent_channel_rejection_db",
+    "Attenuation of a packet whose channel partly overlaps the receiver's, on top of the power that falls outside the receiver's band; such packets are never decoded but can destroy co-channel packets (non-overlapping channels never interact)",
+    PropertyDefault::Float(10.0),
+)
+.with_unit("dB");
+
 /// LoRa noise floor at 250kHz bandwidth.
 pub const RADIO_NOISE_FLOOR_DBM: Property<f64, SimulationScope> = Property::new(
     "radio/noise_floor_dbm",
@@ -723,6 +1440,29 @@ pub const PREDICT_ELEVATION_SOURCE: Property<String, SimulationScope> = Property
     PropertyDefault::String("aws"),
 );
 
+/// Never download AWS elevation tiles.
+pub const PREDICT_ELEVATION_OFFLINE: Property<bool, SimulationScope> = Property::new(
+    "predict/terrain/offline",
+    "Never download AWS elevation tiles, for air-gapped machines: tiles must already be in the cache (see `mcsim prefetch-elevation`). A missing tile fails the prediction unless offline_fallback_m is set",
+    PropertyDefault::Bool(false),
+);
+
+/// Elevation of points on missing tiles in offline mode.
+pub const PREDICT_ELEVATION_OFFLINE_FALLBACK_M: Property<Option<f64>, SimulationScope> = Property::new(
+    "predict/terrain/offline_fallback_m",
+    "Elevation used for points on AWS tiles missing from the cache when offline (0 for a flat, sea-level earth). Setting it implies offline. If null, missing tiles are errors",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).nullable())
+.with_unit("m");
+
+/// Datum for antenna heights in link prediction.
+pub const PREDICT_ANTENNA_HEIGHT_MODE: Property<String, SimulationScope> = Property::new(
+    "predict/antenna/height_mode",
+    "Datum for antenna heights: 'agl' (above ground at the endpoint) or 'msl' (absolute elevation, converted to AGL using the DEM ground elevation)",
+    PropertyDefault::String("agl"),
+);
+
 /// Number of terrain samples along the path for link prediction.
 pub const PREDICT_TERRAIN_SAMPLES: Property<u32, SimulationScope> = Property::new(
     "predict/terrain/samples",
@@ -820,6 +1560,53 @@ pub const LORA_PREAMBLE_SYMBOLS: Property<u32, SimulationScope> = Property::new(
     PropertyDefault::Integer(8),
 );
 
+// ============================================================================
+// Environment (Simulation scope)
+// ============================================================================
+
+/// Time of day at which the simulation starts.
+pub const ENVIRONMENT_START_HOUR: Property<f64, SimulationScope> = Property::new(
+    "environment/start_hour",
+    "Hour of the day (0-24) at simulation time zero, which places nodes' daily temperature cycles",
+    PropertyDefault::Float(0.0),
+)
+.with_unit("h");
+
+// ============================================================================
+// Mobility (Simulation scope)
+// ============================================================================
+
+/// Interval between link updates for mobile nodes.
+pub const MOBILITY_UPDATE_INTERVAL_S: Property<f64, SimulationScope> = Property::new(
+    "mobility/update_interval_s",
+    "Interval between position and link updates of mobile nodes",
+    PropertyDefault::Float(10.0),
+)
+.with_unit("s");
+
+/// Path loss exponent for links of mobile nodes.
+pub const MOBILITY_PATH_LOSS_EXPONENT: Property<f64, SimulationScope> = Property::new(
+    "mobility/path_loss_exponent",
+    "Log-distance path loss exponent used to recompute links of mobile nodes (2.0 = free space)",
+    PropertyDefault::Float(3.0),
+);
+
+/// SNR standard deviation of links of mobile nodes.
+pub const MOBILITY_SNR_STD_DEV: Property<f64, SimulationScope> = Property::new(
+    "mobility/snr_std_dev",
+    "Standard deviation of SNR for links of mobile nodes",
+    PropertyDefault::Float(1.8),
+)
+.with_unit("dB");
+
+/// Weakest link of a mobile node that is kept.
+pub const MOBILITY_MIN_SNR_DB: Property<f64, SimulationScope> = Property::new(
+    "mobility/min_snr_db",
+    "Links of mobile nodes with a mean SNR (at 20 dBm) below this are removed",
+    PropertyDefault::Float(-25.0),
+)
+.with_unit("dB");
+
 // ============================================================================
 // Firmware Simulation (Simulation scope)
 // ============================================================================
@@ -881,6 +1668,23 @@ pub const RUNNER_PERIODIC_STATS_INTERVAL_S: Property<Option<u64>, SimulationScop
 .with_type(PropertyType::new(PropertyBaseType::Integer).nullable())
 .with_unit("s");
 
+/// Timezone of wall-clock timestamps in traces and reports.
+pub const RUNNER_TIMEZONE: Property<Option<String>, SimulationScope> = Property::new(
+    "runner/timezone",
+    "IANA timezone (e.g. America/Los_Angeles or UTC) in which traces and reports also show wall-clock datetimes, counted from firmware/initial_rtc_secs. If null, they show simulation time only",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::String).nullable());
+
+/// Cap on the combined size of a run's trace and capture files.
+pub const RUNNER_MAX_ARTIFACT_MB: Property<Option<f64>, SimulationScope> = Property::new(
+    "runner/max_artifact_mb",
+    "Cap on the combined size of the run's trace, serial capture, pcapng capture and Chrome trace files. Once reached, they are closed and the run continues with metrics only. If null, artifacts are unbounded",
+    PropertyDefault::Null,
+)
+.with_type(PropertyType::new(PropertyBaseType::Float).nullable())
+.with_unit("MB");
+
 // ============================================================================
 // Packet Tracker (Simulation scope)
 // ============================================================================
@@ -98,6 +98,24 @@ pub use definitions::{
     AGENT_CHANNEL_SESSION_INTERVAL_JITTER_S,
     AGENT_CHANNEL_MESSAGE_COUNT,
     AGENT_CHANNEL_SHUTDOWN_S,
+    AGENT_PHONE_ENABLED,
+    AGENT_PHONE_CONNECTED_S,
+    AGENT_PHONE_CONNECTED_JITTER_S,
+    AGENT_PHONE_DISCONNECTED_S,
+    AGENT_PHONE_DISCONNECTED_JITTER_S,
+    AGENT_PHONE_READ_RECEIPTS,
+    AGENT_PHONE_READ_DELAY_S,
+    AGENT_PHONE_READ_DELAY_JITTER_S,
+    AGENT_ROOM_TARGET,
+    AGENT_ROOM_PASSWORD,
+    AGENT_ROOM_LOGIN_S,
+    AGENT_ROOM_POST_INTERVAL_S,
+    AGENT_ROOM_POST_INTERVAL_JITTER_S,
+    AGENT_ROOM_POST_COUNT,
+    AGENT_GPS_ENABLED,
+    AGENT_GPS_INTERVAL_S,
+    // Bridge (Node scope)
+    BRIDGE_PROTOCOL,
     // CLI (Node scope)
     CLI_PASSWORD,
     CLI_COMMANDS,
@@ -107,11 +125,31 @@ pub use definitions::{
     COMPANION_CHANNELS,
     COMPANION_CONTACTS,
     COMPANION_AUTO_CONTACTS_MAX,
+    COMPANION_BOOTSTRAP,
+    COMPANION_BOOTSTRAP_RADIUS_KM,
+    // Crystal (Node scope)
+    CRYSTAL_CLOCK_COEFFICIENT,
+    CRYSTAL_CLOCK_OFFSET_PPM,
+    CRYSTAL_RADIO_COEFFICIENT,
+    CRYSTAL_RADIO_OFFSET_PPM,
+    // Environment
+    ENVIRONMENT_START_HOUR,
+    ENVIRONMENT_TEMPERATURE_MEAN_C,
+    ENVIRONMENT_TEMPERATURE_SWING_C,
+    ENVIRONMENT_WARMEST_HOUR,
+    // Sensor (Node scope)
+    SENSOR_BAROMETER,
+    SENSOR_PRESSURE_NOISE_HPA,
+    SENSOR_SEA_LEVEL_PRESSURE_HPA,
     // Firmware (Node scope)
     FIRMWARE_TYPE,
     FIRMWARE_UART_PORT,
+    FIRMWARE_UART_LATENCY_MS,
+    FIRMWARE_UART_JITTER_MS,
+    FIRMWARE_UART_JITTER_DISTRIBUTION,
     FIRMWARE_STARTUP_TIME_S,
     FIRMWARE_STARTUP_JITTER_S,
+    FIRMWARE_FLOOD_MAX,
     // Firmware Simulation (Simulation scope)
     FIRMWARE_SPIN_DETECTION_THRESHOLD,
     FIRMWARE_IDLE_LOOPS_BEFORE_YIELD,
@@ -128,11 +166,21 @@ pub use definitions::{
     ITM_GROUND_PERMITTIVITY,
     ITM_GROUND_CONDUCTIVITY,
     ITM_SURFACE_REFRACTIVITY,
+    // Jammer
+    JAMMER_BANDWIDTH_HZ,
+    JAMMER_KIND,
+    JAMMER_ON_TIME_S,
+    JAMMER_PERIOD_S,
+    JAMMER_START_S,
+    JAMMER_STOP_S,
     // Keys
     KEYS_PRIVATE_KEY,
     KEYS_PUBLIC_KEY,
+    KEYS_REGENERATE_DUPLICATES,
     // Link (Edge)
+    LINK_FADING,
     LINK_MEAN_SNR_DB_AT20DBM,
+    LINK_RICIAN_K_DB,
     LINK_RSSI_DBM,
     LINK_SNR_STD_DEV,
     // Link Quality Classification (Simulation scope)
@@ -141,10 +189,17 @@ pub use definitions::{
     LINK_MARGIN_MARGINAL_DB,
     // Location
     LOCATION_ALTITUDE_M,
+    LOCATION_X_M,
+    LOCATION_Y_M,
     LOCATION_LATITUDE,
     LOCATION_LONGITUDE,
     // LoRa PHY (Simulation scope)
     LORA_PREAMBLE_SYMBOLS,
+    // Mobility (Simulation scope)
+    MOBILITY_UPDATE_INTERVAL_S,
+    MOBILITY_PATH_LOSS_EXPONENT,
+    MOBILITY_SNR_STD_DEV,
+    MOBILITY_MIN_SNR_DB,
     // Messaging
     MESSAGING_DIRECT_ACK_TIMEOUT_PER_HOP_S,
     MESSAGING_DIRECT_ATTEMPTS,
@@ -153,8 +208,35 @@ pub use definitions::{
     MESSAGING_FLOOD_ATTEMPTS_NO_PATH,
     // Metrics (Node scope)
     METRICS_GROUPS,
+    // Failure (Node scope)
+    FAILURE_DOMAINS,
     // Metrics (Simulation scope)
     METRICS_WARMUP_S,
+    METRICS_RECORD_DURING_WARMUP,
+    METRICS_DISABLED_CATEGORIES,
+    METRICS_ALERT_CHECK_INTERVAL_S,
+    // Power (Node scope)
+    POWER_BATTERY_CAPACITY_MAH,
+    POWER_BATTERY_INITIAL_PERCENT,
+    POWER_IDLE_CURRENT_MA,
+    POWER_RX_CURRENT_MA,
+    POWER_TX_CURRENT_MA,
+    // Thermal (Node scope)
+    THERMAL_DUTY_PERCENT,
+    THERMAL_DERATING_DB,
+    THERMAL_TIME_CONSTANT_S,
+    // Regulatory (Node scope)
+    REGULATORY_REGION,
+    REGULATORY_DUTY_CYCLE_PERCENT,
+    REGULATORY_MAX_DWELL_MS,
+    REGULATORY_WINDOW_S,
+    REGULATORY_ENFORCEMENT,
+    // Advert suppression (Node scope)
+    ADVERT_SUPPRESSION_POLICY,
+    ADVERT_SUPPRESSION_MAX_UTILIZATION,
+    ADVERT_SUPPRESSION_MAX_QUEUE_DEPTH,
+    ADVERT_SUPPRESSION_WINDOW_S,
+    ADVERT_SUPPRESSION_MAX_CONSECUTIVE,
     // Predict-Link Parameters (Simulation scope)
     PREDICT_FREQUENCY_MHZ,
     PREDICT_TX_POWER_DBM,
@@ -162,16 +244,27 @@ pub use definitions::{
     PREDICT_DEM_DIR,
     PREDICT_ELEVATION_CACHE_DIR,
     PREDICT_ELEVATION_SOURCE,
+    PREDICT_ELEVATION_OFFLINE,
+    PREDICT_ELEVATION_OFFLINE_FALLBACK_M,
     PREDICT_ELEVATION_ZOOM_LEVEL,
     PREDICT_TERRAIN_SAMPLES,
+    PREDICT_ANTENNA_HEIGHT_MODE,
     // Radio (Node scope)
     RADIO_BANDWIDTH_HZ,
     RADIO_CODING_RATE,
     RADIO_FREQUENCY_HZ,
     RADIO_SPREADING_FACTOR,
     RADIO_TX_POWER_DBM,
+    RADIO_SYNC_WORD,
+    RADIO_ANTENNA_AZIMUTH_DEG,
+    RADIO_ANTENNA_DOWNTILT_DEG,
+    RADIO_ANTENNA_GAIN_DBI,
+    RADIO_ANTENNA_PATTERN,
     // Radio Thresholds (Simulation scope)
+    RADIO_BIT_ERROR_WINDOW_DB,
+    RADIO_BIT_ERROR_RATE_MAX,
     RADIO_CAPTURE_EFFECT_THRESHOLD_DB,
+    RADIO_ADJACENT_CHANNEL_REJECTION_DB,
     RADIO_NOISE_FLOOR_DBM,
     RADIO_RX_TO_TX_TURNAROUND_US,
     RADIO_TX_TO_RX_TURNAROUND_US,
@@ -184,21 +277,29 @@ pub use definitions::{
     RADIO_SNR_THRESHOLD_SF12_DB,
     // Room Server
     ROOM_SERVER_ROOM_ID,
+    ROOM_SERVER_MAX_POSTS,
+    ROOM_SERVER_POST_TTL_S,
+    ROOM_SERVER_RECONNECT_DELAYS_S,
     // Runner (Simulation scope)
     RUNNER_WATCHDOG_TIMEOUT_S,
     RUNNER_PERIODIC_STATS_INTERVAL_S,
+    RUNNER_TIMEZONE,
+    RUNNER_MAX_ARTIFACT_MB,
     // Packet Tracker (Simulation scope)
     PACKET_TRACKER_EVICTION_AGE_S,
     // Simulation
     SIMULATION_DURATION_S,
     SIMULATION_SEED,
+    SIMULATION_RNG_BACKEND,
+    LOCATION_CRS,
+    SIMULATION_UNREACHABLE_NODES,
     SIMULATION_UART_BASE_PORT,
 };
 
 // Re-export registry functions and types
 pub use registry::{
     default_value, get_property_def, is_known_property, known_namespaces, properties_by_namespace,
-    properties_by_scope, PropertySetError, ResolvedProperties, UnresolvedProperties,
+    properties_by_scope, search_properties, PropertySetError, ResolvedProperties, UnresolvedProperties,
     ALL_PROPERTIES,
 };
 
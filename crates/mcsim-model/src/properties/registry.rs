@@ -27,10 +27,62 @@ pub const ALL_PROPERTIES: &[&PropertyDef] = &[
     &RADIO_SPREADING_FACTOR.def,
     &RADIO_CODING_RATE.def,
     &RADIO_TX_POWER_DBM.def,
+    &RADIO_SYNC_WORD.def,
+    &RADIO_ANTENNA_GAIN_DBI.def,
+    &RADIO_ANTENNA_AZIMUTH_DEG.def,
+    &RADIO_ANTENNA_DOWNTILT_DEG.def,
+    &RADIO_ANTENNA_PATTERN.def,
+    // Power
+    &POWER_BATTERY_CAPACITY_MAH.def,
+    &POWER_BATTERY_INITIAL_PERCENT.def,
+    &POWER_TX_CURRENT_MA.def,
+    &POWER_RX_CURRENT_MA.def,
+    &POWER_IDLE_CURRENT_MA.def,
+    // Thermal
+    &THERMAL_DUTY_PERCENT.def,
+    &THERMAL_DERATING_DB.def,
+    &THERMAL_TIME_CONSTANT_S.def,
+    // Regulatory
+    &REGULATORY_REGION.def,
+    &REGULATORY_DUTY_CYCLE_PERCENT.def,
+    &REGULATORY_MAX_DWELL_MS.def,
+    &REGULATORY_WINDOW_S.def,
+    &REGULATORY_ENFORCEMENT.def,
+    // Advert suppression
+    &ADVERT_SUPPRESSION_POLICY.def,
+    &ADVERT_SUPPRESSION_MAX_UTILIZATION.def,
+    &ADVERT_SUPPRESSION_MAX_QUEUE_DEPTH.def,
+    &ADVERT_SUPPRESSION_WINDOW_S.def,
+    &ADVERT_SUPPRESSION_MAX_CONSECUTIVE.def,
+    // Jammer
+    &JAMMER_KIND.def,
+    &JAMMER_BANDWIDTH_HZ.def,
+    &JAMMER_ON_TIME_S.def,
+    &JAMMER_PERIOD_S.def,
+    &JAMMER_START_S.def,
+    &JAMMER_STOP_S.def,
+    // Bridge
+    &BRIDGE_PROTOCOL.def,
+    // Environment
+    &ENVIRONMENT_TEMPERATURE_MEAN_C.def,
+    &ENVIRONMENT_TEMPERATURE_SWING_C.def,
+    &ENVIRONMENT_WARMEST_HOUR.def,
+    &ENVIRONMENT_START_HOUR.def,
+    // Crystal
+    &CRYSTAL_CLOCK_OFFSET_PPM.def,
+    &CRYSTAL_CLOCK_COEFFICIENT.def,
+    &CRYSTAL_RADIO_OFFSET_PPM.def,
+    &CRYSTAL_RADIO_COEFFICIENT.def,
+    // Sensor
+    &SENSOR_BAROMETER.def,
+    &SENSOR_PRESSURE_NOISE_HPA.def,
+    &SENSOR_SEA_LEVEL_PRESSURE_HPA.def,
     // Companion
     &COMPANION_CHANNELS.def,
     &COMPANION_CONTACTS.def,
     &COMPANION_AUTO_CONTACTS_MAX.def,
+    &COMPANION_BOOTSTRAP.def,
+    &COMPANION_BOOTSTRAP_RADIUS_KM.def,
     // Messaging
     &MESSAGING_FLOOD_ACK_TIMEOUT_S.def,
     &MESSAGING_DIRECT_ACK_TIMEOUT_PER_HOP_S.def,
@@ -39,6 +91,9 @@ pub const ALL_PROPERTIES: &[&PropertyDef] = &[
     &MESSAGING_FLOOD_ATTEMPTS_NO_PATH.def,
     // Room Server
     &ROOM_SERVER_ROOM_ID.def,
+    &ROOM_SERVER_MAX_POSTS.def,
+    &ROOM_SERVER_POST_TTL_S.def,
+    &ROOM_SERVER_RECONNECT_DELAYS_S.def,
     // Agent Direct Message
     &AGENT_DIRECT_ENABLED.def,
     &AGENT_DIRECT_STARTUP_S.def,
@@ -64,35 +119,71 @@ pub const ALL_PROPERTIES: &[&PropertyDef] = &[
     &AGENT_CHANNEL_SESSION_INTERVAL_JITTER_S.def,
     &AGENT_CHANNEL_MESSAGE_COUNT.def,
     &AGENT_CHANNEL_SHUTDOWN_S.def,
+    &AGENT_PHONE_ENABLED.def,
+    &AGENT_PHONE_CONNECTED_S.def,
+    &AGENT_PHONE_CONNECTED_JITTER_S.def,
+    &AGENT_PHONE_DISCONNECTED_S.def,
+    &AGENT_PHONE_DISCONNECTED_JITTER_S.def,
+    &AGENT_PHONE_READ_RECEIPTS.def,
+    &AGENT_PHONE_READ_DELAY_S.def,
+    &AGENT_PHONE_READ_DELAY_JITTER_S.def,
+    &AGENT_ROOM_TARGET.def,
+    &AGENT_ROOM_PASSWORD.def,
+    &AGENT_ROOM_LOGIN_S.def,
+    &AGENT_ROOM_POST_INTERVAL_S.def,
+    &AGENT_ROOM_POST_INTERVAL_JITTER_S.def,
+    &AGENT_ROOM_POST_COUNT.def,
+    &AGENT_GPS_ENABLED.def,
+    &AGENT_GPS_INTERVAL_S.def,
     // Link
     &LINK_MEAN_SNR_DB_AT20DBM.def,
     &LINK_SNR_STD_DEV.def,
     &LINK_RSSI_DBM.def,
+    &LINK_FADING.def,
+    &LINK_RICIAN_K_DB.def,
     // Simulation
     &SIMULATION_DURATION_S.def,
     &SIMULATION_SEED.def,
+    &SIMULATION_RNG_BACKEND.def,
+    &LOCATION_CRS.def,
+    &SIMULATION_UNREACHABLE_NODES.def,
     &SIMULATION_UART_BASE_PORT.def,
     // Keys
     &KEYS_PRIVATE_KEY.def,
     &KEYS_PUBLIC_KEY.def,
+    &KEYS_REGENERATE_DUPLICATES.def,
     // Location
     &LOCATION_LATITUDE.def,
     &LOCATION_LONGITUDE.def,
     &LOCATION_ALTITUDE_M.def,
+    &LOCATION_X_M.def,
+    &LOCATION_Y_M.def,
     // Firmware (Node scope)
     &FIRMWARE_TYPE.def,
     &FIRMWARE_UART_PORT.def,
+    &FIRMWARE_UART_LATENCY_MS.def,
+    &FIRMWARE_UART_JITTER_MS.def,
+    &FIRMWARE_UART_JITTER_DISTRIBUTION.def,
     &FIRMWARE_STARTUP_TIME_S.def,
     &FIRMWARE_STARTUP_JITTER_S.def,
+    &FIRMWARE_FLOOD_MAX.def,
     // Metrics (Node scope)
     &METRICS_GROUPS.def,
+    // Failure (Node scope)
+    &FAILURE_DOMAINS.def,
     // Metrics (Simulation scope)
     &METRICS_WARMUP_S.def,
+    &METRICS_RECORD_DURING_WARMUP.def,
+    &METRICS_DISABLED_CATEGORIES.def,
+    &METRICS_ALERT_CHECK_INTERVAL_S.def,
     // CLI
     &CLI_PASSWORD.def,
     &CLI_COMMANDS.def,
     // Radio Thresholds (Simulation scope)
+    &RADIO_BIT_ERROR_WINDOW_DB.def,
+    &RADIO_BIT_ERROR_RATE_MAX.def,
     &RADIO_CAPTURE_EFFECT_THRESHOLD_DB.def,
+    &RADIO_ADJACENT_CHANNEL_REJECTION_DB.def,
     &RADIO_NOISE_FLOOR_DBM.def,
     &RADIO_RX_TO_TX_TURNAROUND_US.def,
     &RADIO_TX_TO_RX_TURNAROUND_US.def,
@@ -114,8 +205,11 @@ pub const ALL_PROPERTIES: &[&PropertyDef] = &[
     &PREDICT_DEM_DIR.def,
     &PREDICT_ELEVATION_CACHE_DIR.def,
     &PREDICT_ELEVATION_SOURCE.def,
+    &PREDICT_ELEVATION_OFFLINE.def,
+    &PREDICT_ELEVATION_OFFLINE_FALLBACK_M.def,
     &PREDICT_ELEVATION_ZOOM_LEVEL.def,
     &PREDICT_TERRAIN_SAMPLES.def,
+    &PREDICT_ANTENNA_HEIGHT_MODE.def,
     // ITM Prediction Parameters (Simulation scope)
     &ITM_MIN_DISTANCE_M.def,
     &ITM_TERRAIN_SAMPLES.def,
@@ -130,6 +224,11 @@ pub const ALL_PROPERTIES: &[&PropertyDef] = &[
     &COLOCATED_PATH_LOSS_DB.def,
     // LoRa PHY (Simulation scope)
     &LORA_PREAMBLE_SYMBOLS.def,
+    // Mobility (Simulation scope)
+    &MOBILITY_UPDATE_INTERVAL_S.def,
+    &MOBILITY_PATH_LOSS_EXPONENT.def,
+    &MOBILITY_SNR_STD_DEV.def,
+    &MOBILITY_MIN_SNR_DB.def,
     // Firmware Simulation (Simulation scope)
     &FIRMWARE_SPIN_DETECTION_THRESHOLD.def,
     &FIRMWARE_IDLE_LOOPS_BEFORE_YIELD.def,
@@ -139,6 +238,8 @@ pub const ALL_PROPERTIES: &[&PropertyDef] = &[
     // Runner (Simulation scope)
     &RUNNER_WATCHDOG_TIMEOUT_S.def,
     &RUNNER_PERIODIC_STATS_INTERVAL_S.def,
+    &RUNNER_TIMEZONE.def,
+    &RUNNER_MAX_ARTIFACT_MB.def,
 ];
 
 // ============================================================================
@@ -182,6 +283,23 @@ pub fn properties_by_namespace(namespace: &str) -> impl Iterator<Item = &'static
         .copied()
 }
 
+/// Find properties whose name or an alias contains `filter` (case-insensitive),
+/// optionally limited to one scope. An empty filter matches every property.
+pub fn search_properties(
+    filter: &str,
+    scope: Option<PropertyScope>,
+) -> impl Iterator<Item = &'static PropertyDef> + '_ {
+    let filter = filter.to_lowercase();
+    ALL_PROPERTIES
+        .iter()
+        .filter(move |p| scope.is_none_or(|s| p.scope == s))
+        .filter(move |p| {
+            p.name.to_lowercase().contains(&filter)
+                || p.aliases.iter().any(|a| a.to_lowercase().contains(&filter))
+        })
+        .copied()
+}
+
 /// Get the default value for a property by name.
 pub fn default_value(name: &str) -> Option<PropertyValue> {
     get_property_def(name).map(|p| p.default_value())
@@ -302,6 +420,12 @@ impl<S: ScopeMarker> ResolvedProperties<S> {
         Ok(())
     }
 
+    /// Set a property by definition, for values already checked against its
+    /// type (e.g. parsed from a sweep).
+    pub(crate) fn set_raw(&mut self, def: &'static PropertyDef, value: PropertyValue) {
+        self.values.insert(def, value);
+    }
+
     /// Get a property value with compile-time type safety.
     ///
     /// Returns the value converted to the property's declared type, or the default
@@ -432,7 +556,7 @@ impl<S: ScopeMarker> UnresolvedProperties<S> {
 }
 
 /// Describe the type of a PropertyValue for error messages.
-fn describe_value_type(value: &PropertyValue) -> String {
+pub(crate) fn describe_value_type(value: &PropertyValue) -> String {
     match value {
         PropertyValue::Integer(_) => "integer".to_string(),
         PropertyValue::Float(_) => "float".to_string(),
@@ -560,7 +684,7 @@ impl<S: ScopeMarker> Serialize for UnresolvedProperties<S> {
 }
 
 /// Convert a serde_yaml::Value to a PropertyValue.
-fn yaml_value_to_property(value: &serde_yaml::Value) -> Result<PropertyValue, PropertySetError> {
+pub(crate) fn yaml_value_to_property(value: &serde_yaml::Value) -> Result<PropertyValue, PropertySetError> {
     match value {
         serde_yaml::Value::Bool(b) => Ok(PropertyValue::Bool(*b)),
         serde_yaml::Value::Number(n) => {
@@ -604,6 +728,22 @@ mod tests {
     use super::super::types::{EdgeScope, NodeScope, SimulationScope};
     use super::*;
 
+    #[test]
+    fn test_search_properties() {
+        let radio: Vec<_> = search_properties("RADIO/", None).collect();
+        assert!(radio.iter().any(|p| p.name == "radio/frequency_hz"));
+        assert!(radio
+            .iter()
+            .all(|p| p.name.contains("radio/") || p.aliases.iter().any(|a| a.contains("radio/"))));
+
+        // Scope restricts the matches, aliases match too
+        assert!(search_properties("radio/", Some(PropertyScope::Edge)).next().is_none());
+        let lat: Vec<_> = search_properties("lat", Some(PropertyScope::Node)).collect();
+        assert!(lat.iter().any(|p| p.name == "location/latitude"));
+
+        assert_eq!(search_properties("", None).count(), ALL_PROPERTIES.len());
+    }
+
     #[test]
     fn test_typed_get() {
         let props: ResolvedProperties<NodeScope> = ResolvedProperties::new();
@@ -0,0 +1,220 @@
+//! Parameter sweeps.
+//!
+//! The `sweep` section lists values to try for one or more properties. `mcsim
+//! sweep` expands it into the cartesian product of those values and runs the
+//! scenario once per combination:
+//!
+//! ```yaml
+//! sweep:
+//!   radio.tx_power_dbm: [14, 17, 20]
+//!   radio/spreading_factor: [8, 10]
+//!   mobility:
+//!     path_loss_exponent: [2.7, 3.0]
+//! ```
+//!
+//! Keys are property names, written with `/` or `.` separators or as nested
+//! maps like elsewhere in the model. A node property is set on every node
+//! and an edge property on every edge, replacing their own values; a
+//! simulation property replaces the `simulation` section's value. Values are
+//! type-checked like any other property value when the model is loaded.
+//!
+//! When several files define a sweep, a later file's values for a property
+//! replace an earlier file's. `mcsim run` ignores the section and runs the
+//! scenario as configured, unless `--sweep-point` picks one combination.
+
+use std::fmt;
+
+use crate::properties::registry::{describe_value_type, get_property_def, yaml_value_to_property};
+use crate::properties::{PropertyDef, PropertyScope, PropertyValue};
+use crate::{Model, ModelError};
+
+/// Values tried for one property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepAxis {
+    /// The property swept.
+    pub property: &'static PropertyDef,
+    /// Its values, in the order given.
+    pub values: Vec<PropertyValue>,
+}
+
+/// One combination of swept values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepPoint {
+    /// Property and value, one per axis.
+    pub values: Vec<(&'static PropertyDef, PropertyValue)>,
+}
+
+impl SweepPoint {
+    /// Short form of the values, usable as a file name
+    /// (`radio.tx_power_dbm-14_radio.spreading_factor-8`).
+    pub fn slug(&self) -> String {
+        self.values
+            .iter()
+            .map(|(def, value)| format!("{}-{}", def.name.replace('/', "."), value))
+            .collect::<Vec<_>>()
+            .join("_")
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+            .collect()
+    }
+}
+
+impl fmt::Display for SweepPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (def, value)) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}={}", def.name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every combination of the axes' values, the first axis varying slowest.
+pub fn sweep_points(axes: &[SweepAxis]) -> Vec<SweepPoint> {
+    let mut points = vec![SweepPoint { values: Vec::new() }];
+    for axis in axes {
+        points = points
+            .into_iter()
+            .flat_map(|point| {
+                axis.values.iter().map(move |value| {
+                    let mut values = point.values.clone();
+                    values.push((axis.property, value.clone()));
+                    SweepPoint { values }
+                })
+            })
+            .collect();
+    }
+    points
+}
+
+/// Parse a `sweep` section, merging it into the axes of earlier files.
+pub(crate) fn merge_axes(axes: &mut Vec<SweepAxis>, section: &serde_yaml::Mapping) -> Result<(), ModelError> {
+    for (key, value) in section {
+        let key = key
+            .as_str()
+            .ok_or_else(|| ModelError::InvalidConfig("Sweep keys must be property names".to_string()))?;
+        collect_axis(axes, &key.replace('.', "/"), value)?;
+    }
+    Ok(())
+}
+
+fn collect_axis(axes: &mut Vec<SweepAxis>, name: &str, value: &serde_yaml::Value) -> Result<(), ModelError> {
+    let values = match value {
+        serde_yaml::Value::Mapping(map) => {
+            for (key, value) in map {
+                let key = key
+                    .as_str()
+                    .ok_or_else(|| ModelError::InvalidConfig(format!("Sweep of '{}': keys must be names", name)))?;
+                collect_axis(axes, &format!("{}/{}", name, key.replace('.', "/")), value)?;
+            }
+            return Ok(());
+        }
+        serde_yaml::Value::Sequence(values) if !values.is_empty() => values,
+        _ => {
+            return Err(ModelError::InvalidConfig(format!(
+                "Sweep of '{}': expected a non-empty list of values",
+                name
+            )))
+        }
+    };
+    let property = get_property_def(name).ok_or_else(|| {
+        ModelError::InvalidConfig(format!(
+            "Sweep of unknown property '{}'. Run \"mcsim properties\" for more details",
+            name
+        ))
+    })?;
+    let values = values
+        .iter()
+        .map(|value| {
+            let value = yaml_value_to_property(value)
+                .map_err(|e| ModelError::InvalidConfig(format!("Sweep of '{}': {}", property.name, e)))?;
+            if !property.value_type.matches(&value) {
+                return Err(ModelError::InvalidConfig(format!(
+                    "Sweep of '{}': expected {}, got {}",
+                    property.name,
+                    property.value_type,
+                    describe_value_type(&value)
+                )));
+            }
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    match axes.iter_mut().find(|axis| axis.property == property) {
+        Some(axis) => axis.values = values,
+        None => axes.push(SweepAxis { property, values }),
+    }
+    Ok(())
+}
+
+impl Model {
+    /// Get the swept properties, empty when the scenario has no sweep.
+    pub fn sweep(&self) -> &[SweepAxis] {
+        &self.sweep
+    }
+
+    /// Every combination of swept values (none without a sweep).
+    pub fn sweep_points(&self) -> Vec<SweepPoint> {
+        if self.sweep.is_empty() {
+            return Vec::new();
+        }
+        sweep_points(&self.sweep)
+    }
+
+    /// Set the properties of a sweep point on the whole model.
+    pub fn apply_sweep_point(&mut self, point: &SweepPoint) {
+        for (def, value) in &point.values {
+            match def.scope {
+                PropertyScope::Node => {
+                    for node in self.nodes.values_mut() {
+                        node.properties.set_raw(def, value.clone());
+                    }
+                }
+                PropertyScope::Edge => {
+                    for edge in self.edges.values_mut() {
+                        edge.properties.set_raw(def, value.clone());
+                    }
+                }
+                PropertyScope::Simulation => self.simulation.set_raw(def, value.clone()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::properties::{RADIO_SPREADING_FACTOR, RADIO_TX_POWER_DBM, SIMULATION_SEED};
+
+    #[test]
+    fn test_sweep_expands_and_applies() {
+        let base = "nodes:\n  - name: A\n    radio: { tx_power_dbm: 10 }\n  - name: B\nsweep:\n  radio.tx_power_dbm: [14, 17, 20]\n  radio:\n    spreading_factor: [8, 10]\n";
+        let overlay = "sweep:\n  radio/tx_power_dbm: [22, 14]\n  simulation/seed: [7]\n";
+        let mut model = crate::load_models_from_str(&[base, overlay]).unwrap();
+        assert_eq!(model.sweep().len(), 3);
+
+        let points = model.sweep_points();
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[1].to_string(), "radio/tx_power_dbm=22, radio/spreading_factor=10, simulation/seed=7");
+        assert_eq!(points[2].slug(), "radio.tx_power_dbm-14_radio.spreading_factor-8_simulation.seed-7");
+
+        // Every node takes the swept value, including one that set its own
+        model.apply_sweep_point(&points[1]);
+        for node in model.nodes().values() {
+            let power: i8 = node.properties().get(&RADIO_TX_POWER_DBM);
+            let sf: u8 = node.properties().get(&RADIO_SPREADING_FACTOR);
+            assert_eq!((power, sf), (22, 10));
+        }
+        let seed: i64 = model.simulation_properties().get(&SIMULATION_SEED);
+        assert_eq!(seed, 7);
+    }
+
+    #[test]
+    fn test_sweep_errors() {
+        assert!(crate::load_model_from_str("nodes: []\n").unwrap().sweep_points().is_empty());
+        for sweep in ["node_count: [10, 50]", "radio.tx_power_dbm: []", "radio.tx_power_dbm: 14", "radio.tx_power_dbm: [high]"] {
+            let yaml = format!("nodes: []\nsweep:\n  {}\n", sweep);
+            assert!(crate::load_model_from_str(&yaml).is_err(), "{}", sweep);
+        }
+    }
+}
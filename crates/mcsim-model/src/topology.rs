@@ -0,0 +1,389 @@
+//! Reachability and partition analysis of the predicted links.
+//!
+//! The nodes form a graph joined by two-way links: pairs whose predicted
+//! links (see [`predict_links`](crate::predict_links)) close in both
+//! directions, as a message and its acknowledgement need. Only relays
+//! (repeaters and room servers) forward traffic; companions are endpoints
+//! that a path can start or end at but never pass through. On that graph the
+//! analysis finds:
+//!
+//! - connected components: groups of nodes that can't reach each other at all,
+//! - articulation points: relays whose failure splits their component, the
+//!   single points of failure of the mesh,
+//! - betweenness centrality: the share of shortest paths between other nodes
+//!   that pass through a node, which ranks how critical a repeater's
+//!   placement is.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{LinkPrediction, Model, Node};
+
+/// Number of nodes listed as most central in the report's summary.
+const CENTRAL_NODES_SHOWN: usize = 5;
+
+/// Number of single points of failure listed in the report's summary.
+const ARTICULATION_POINTS_SHOWN: usize = 10;
+
+/// A node whose failure splits its component.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArticulationPoint {
+    /// Node name.
+    pub name: String,
+    /// Number of parts the rest of its component falls into without it.
+    pub splits_into: usize,
+}
+
+/// Betweenness centrality of a node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeCentrality {
+    /// Node name.
+    pub name: String,
+    /// Fraction of the shortest paths between pairs of other nodes that pass
+    /// through this node, from 0 to 1.
+    pub betweenness: f64,
+}
+
+/// Result of [`analyze_topology`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TopologyReport {
+    /// Groups of nodes that reach each other over two-way links, largest
+    /// first. An endpoint in range of relays of several groups is in each.
+    pub components: Vec<Vec<String>>,
+    /// Single points of failure, by node name.
+    pub articulation_points: Vec<ArticulationPoint>,
+    /// Every node, most central first.
+    pub centrality: Vec<NodeCentrality>,
+}
+
+impl TopologyReport {
+    /// True if every node can reach every other node.
+    pub fn is_connected(&self) -> bool {
+        self.components.len() <= 1
+    }
+}
+
+impl fmt::Display for TopologyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Topology: {} component(s) of two-way links", self.components.len())?;
+        if !self.is_connected() {
+            let sizes: Vec<String> = self.components.iter().map(|c| c.len().to_string()).collect();
+            write!(f, " (sizes {})", sizes.join(", "))?;
+        }
+        if self.articulation_points.is_empty() {
+            write!(f, "\n  no single points of failure")?;
+        } else {
+            let points: Vec<String> = self
+                .articulation_points
+                .iter()
+                .take(ARTICULATION_POINTS_SHOWN)
+                .map(|p| format!("{} (splits into {})", p.name, p.splits_into))
+                .collect();
+            write!(f, "\n  single points of failure: {}", points.join(", "))?;
+            let more = self.articulation_points.len().saturating_sub(ARTICULATION_POINTS_SHOWN);
+            if more > 0 {
+                write!(f, " and {} more", more)?;
+            }
+        }
+        let central: Vec<String> = self
+            .centrality
+            .iter()
+            .take(CENTRAL_NODES_SHOWN)
+            .filter(|c| c.betweenness > 0.0)
+            .map(|c| format!("{} {:.2}", c.name, c.betweenness))
+            .collect();
+        if !central.is_empty() {
+            write!(f, "\n  most central: {}", central.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Analyze the graph of two-way links among the model's nodes.
+///
+/// A link is usable when its predicted mean SNR meets the receiver's
+/// threshold. Jammers take no part in the mesh and are left out.
+pub fn analyze_topology(model: &Model, links: &[LinkPrediction]) -> TopologyReport {
+    let nodes: Vec<&Node> = model.nodes().values().filter(|node| !crate::is_jammer(node)).collect();
+    let names: Vec<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+    let relay: Vec<bool> = nodes.iter().map(|node| crate::is_relay(node)).collect();
+    let index: BTreeMap<&str, usize> = names.iter().enumerate().map(|(i, name)| (*name, i)).collect();
+
+    let usable: BTreeSet<(usize, usize)> = links
+        .iter()
+        .filter(|link| link.margin_db() >= 0.0)
+        .filter_map(|link| Some((*index.get(link.from.as_str())?, *index.get(link.to.as_str())?)))
+        .collect();
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+    for &(a, b) in &usable {
+        if a < b && usable.contains(&(b, a)) {
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        }
+    }
+
+    let name = |i: usize| names[i].to_string();
+    let groups = components(&adjacency, &relay, None);
+    let articulation_points = articulation_points(&adjacency, &relay, &groups)
+        .into_iter()
+        .map(|(i, splits_into)| ArticulationPoint { name: name(i), splits_into })
+        .collect();
+
+    let mut components: Vec<Vec<String>> = groups
+        .into_iter()
+        .map(|c| c.into_iter().map(name).collect())
+        .collect();
+    components.sort_by_key(|c| std::cmp::Reverse(c.len()));
+
+    let mut centrality: Vec<NodeCentrality> = betweenness(&adjacency, &relay)
+        .into_iter()
+        .enumerate()
+        .map(|(i, betweenness)| NodeCentrality { name: name(i), betweenness })
+        .collect();
+    centrality.sort_by(|a, b| b.betweenness.total_cmp(&a.betweenness));
+
+    TopologyReport { components, articulation_points, centrality }
+}
+
+/// Groups of nodes that reach each other, each sorted by node index,
+/// leaving out the `removed` node.
+///
+/// Relays joined by links form each group's backbone, and every endpoint in
+/// range of one of its relays belongs to the group. Endpoints out of range of
+/// every relay are grouped with the others they link to directly.
+fn components(adjacency: &[Vec<usize>], relay: &[bool], removed: Option<usize>) -> Vec<Vec<usize>> {
+    let present = |v: usize| Some(v) != removed;
+    let mut seen = vec![false; adjacency.len()];
+    let mut components = Vec::new();
+
+    for start in (0..adjacency.len()).filter(|&v| relay[v] && present(v)) {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut component = BTreeSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(v) = queue.pop_front() {
+            for &w in adjacency[v].iter().filter(|&&w| present(w)) {
+                if !relay[w] {
+                    component.insert(w);
+                } else if !seen[w] {
+                    seen[w] = true;
+                    component.insert(w);
+                    queue.push_back(w);
+                }
+            }
+        }
+        components.push(component.into_iter().collect());
+    }
+
+    let out_of_range =
+        |v: usize| present(v) && !relay[v] && adjacency[v].iter().all(|&w| !present(w) || !relay[w]);
+    for start in (0..adjacency.len()).filter(|&v| out_of_range(v)) {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut component = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(v) = queue.pop_front() {
+            for &w in &adjacency[v] {
+                if out_of_range(w) && !seen[w] {
+                    seen[w] = true;
+                    component.push(w);
+                    queue.push_back(w);
+                }
+            }
+        }
+        component.sort_unstable();
+        components.push(component);
+    }
+
+    components.sort_by_key(|c: &Vec<usize>| c[0]);
+    components
+}
+
+/// Relays whose failure splits their component, and the number of parts the
+/// rest of it falls into, by node index. Endpoints pass no traffic on, so
+/// they can't be points of failure.
+fn articulation_points(adjacency: &[Vec<usize>], relay: &[bool], groups: &[Vec<usize>]) -> Vec<(usize, usize)> {
+    (0..adjacency.len())
+        .filter(|&v| relay[v])
+        .filter_map(|v| {
+            let own = groups.iter().find(|c| c.binary_search(&v).is_ok())?;
+            // What's left of its component, split the way the rest now groups
+            let parts: BTreeSet<Vec<usize>> = components(adjacency, relay, Some(v))
+                .into_iter()
+                .map(|c| c.into_iter().filter(|w| own.binary_search(w).is_ok()).collect::<Vec<_>>())
+                .filter(|c| !c.is_empty())
+                .collect();
+            // An endpoint still in range of the rest also shows up in other
+            // groups; those don't split anything
+            let parts = parts
+                .iter()
+                .filter(|c| {
+                    !parts
+                        .iter()
+                        .any(|d| d.len() > c.len() && c.iter().all(|w| d.binary_search(w).is_ok()))
+                })
+                .count();
+            (parts >= 2).then_some((v, parts))
+        })
+        .collect()
+}
+
+/// Normalized betweenness centrality of each node (Brandes' algorithm for
+/// unweighted, undirected graphs), over paths that only pass through relays.
+fn betweenness(adjacency: &[Vec<usize>], relay: &[bool]) -> Vec<f64> {
+    let n = adjacency.len();
+    let mut centrality = vec![0.0; n];
+    for source in 0..n {
+        let mut stack = Vec::new();
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut paths = vec![0.0; n];
+        let mut distance: Vec<Option<usize>> = vec![None; n];
+        paths[source] = 1.0;
+        distance[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(v) = queue.pop_front() {
+            stack.push(v);
+            // Paths end at endpoints
+            if v != source && !relay[v] {
+                continue;
+            }
+            let next = distance[v].map(|d| d + 1);
+            for &w in &adjacency[v] {
+                if distance[w].is_none() {
+                    distance[w] = next;
+                    queue.push_back(w);
+                }
+                if distance[w] == next {
+                    paths[w] += paths[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+        let mut dependency = vec![0.0; n];
+        while let Some(w) = stack.pop() {
+            for &v in &predecessors[w] {
+                dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+            }
+            if w != source {
+                centrality[w] += dependency[w];
+            }
+        }
+    }
+    // Each pair was counted from both ends; scale by the number of pairs
+    // excluding the node itself
+    let pairs = if n > 2 { ((n - 1) * (n - 2)) as f64 } else { 1.0 };
+    centrality.iter().map(|c| c / pairs).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_model_from_str, predict_links};
+
+    // A - R1 - R2 - B in a line, with C hanging off R1 and D only heard one way
+    const MODEL: &str = r#"
+nodes:
+  - { name: "A", location: { lat: 47.60, lon: -122.40 }, firmware: { type: Companion } }
+  - { name: "R1", location: { lat: 47.61, lon: -122.40 }, firmware: { type: Repeater } }
+  - { name: "R2", location: { lat: 47.62, lon: -122.40 }, firmware: { type: Repeater } }
+  - { name: "B", location: { lat: 47.63, lon: -122.40 }, firmware: { type: Companion } }
+  - { name: "C", location: { lat: 47.61, lon: -122.41 }, firmware: { type: Companion } }
+  - { name: "D", location: { lat: 47.70, lon: -122.40 }, firmware: { type: Companion } }
+edges:
+  - { from: "A", to: "R1", mean_snr_db_at20dbm: 10.0 }
+  - { from: "R1", to: "A", mean_snr_db_at20dbm: 10.0 }
+  - { from: "R1", to: "R2", mean_snr_db_at20dbm: 10.0 }
+  - { from: "R2", to: "R1", mean_snr_db_at20dbm: 10.0 }
+  - { from: "R2", to: "B", mean_snr_db_at20dbm: 10.0 }
+  - { from: "B", to: "R2", mean_snr_db_at20dbm: 10.0 }
+  - { from: "C", to: "R1", mean_snr_db_at20dbm: 10.0 }
+  - { from: "R1", to: "C", mean_snr_db_at20dbm: 10.0 }
+  - { from: "D", to: "B", mean_snr_db_at20dbm: 10.0 }
+  - { from: "B", to: "D", mean_snr_db_at20dbm: -30.0 }
+"#;
+
+    #[test]
+    fn test_components_and_articulation_points() {
+        let model = load_model_from_str(MODEL).unwrap();
+        let report = analyze_topology(&model, &predict_links(&model));
+
+        // D's link to B is one-way, so D is on its own
+        assert_eq!(report.components, vec![vec!["A", "B", "C", "R1", "R2"], vec!["D"]]);
+        assert!(!report.is_connected());
+
+        // R1 separates A, C and the rest; R2 separates B
+        assert_eq!(
+            report.articulation_points,
+            vec![
+                ArticulationPoint { name: "R1".to_string(), splits_into: 3 },
+                ArticulationPoint { name: "R2".to_string(), splits_into: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_betweenness() {
+        let model = load_model_from_str(MODEL).unwrap();
+        let report = analyze_topology(&model, &predict_links(&model));
+
+        // R1 lies between A, C and {R2, B}: 5 of the 10 pairs of other nodes
+        assert_eq!(report.centrality[0].name, "R1");
+        assert!((report.centrality[0].betweenness - 0.5).abs() < 1e-9);
+        // R2 lies between B and {A, C, R1}
+        assert_eq!(report.centrality[1].name, "R2");
+        assert!((report.centrality[1].betweenness - 0.3).abs() < 1e-9);
+        assert!(report.centrality[2..].iter().all(|c| c.betweenness == 0.0));
+
+        let text = report.to_string();
+        assert!(text.contains("Topology: 2 component(s) of two-way links (sizes 5, 1)"));
+        assert!(text.contains("single points of failure: R1 (splits into 3), R2 (splits into 2)"));
+        assert!(text.contains("most central: R1 0.50, R2 0.30"));
+    }
+
+    #[test]
+    fn test_companions_do_not_relay() {
+        // R1 and R2 are only linked through the companion C
+        let model = load_model_from_str(
+            r#"
+nodes:
+  - { name: "A", location: { lat: 47.60, lon: -122.40 }, firmware: { type: Companion } }
+  - { name: "R1", location: { lat: 47.61, lon: -122.40 }, firmware: { type: Repeater } }
+  - { name: "C", location: { lat: 47.62, lon: -122.40 }, firmware: { type: Companion } }
+  - { name: "R2", location: { lat: 47.63, lon: -122.40 }, firmware: { type: Repeater } }
+  - { name: "B", location: { lat: 47.64, lon: -122.40 }, firmware: { type: Companion } }
+edges:
+  - { from: "A", to: "R1", mean_snr_db_at20dbm: 10.0 }
+  - { from: "R1", to: "A", mean_snr_db_at20dbm: 10.0 }
+  - { from: "R1", to: "C", mean_snr_db_at20dbm: 10.0 }
+  - { from: "C", to: "R1", mean_snr_db_at20dbm: 10.0 }
+  - { from: "C", to: "R2", mean_snr_db_at20dbm: 10.0 }
+  - { from: "R2", to: "C", mean_snr_db_at20dbm: 10.0 }
+  - { from: "R2", to: "B", mean_snr_db_at20dbm: 10.0 }
+  - { from: "B", to: "R2", mean_snr_db_at20dbm: 10.0 }
+"#,
+        )
+        .unwrap();
+        let report = analyze_topology(&model, &predict_links(&model));
+
+        // C reaches both repeaters but joins neither half to the other
+        assert_eq!(report.components, vec![vec!["A", "C", "R1"], vec!["B", "C", "R2"]]);
+        assert!(!report.is_connected());
+
+        // Each repeater alone joins its companions; C is no point of failure
+        assert_eq!(
+            report.articulation_points,
+            vec![
+                ArticulationPoint { name: "R1".to_string(), splits_into: 2 },
+                ArticulationPoint { name: "R2".to_string(), splits_into: 2 },
+            ]
+        );
+        let c = report.centrality.iter().find(|c| c.name == "C").unwrap();
+        assert_eq!(c.betweenness, 0.0);
+    }
+}
@@ -0,0 +1,354 @@
+//! Scripted application traffic.
+//!
+//! Each entry of the `traffic` section makes a companion's agent send a
+//! direct message or a channel post, once or on a repeating schedule:
+//!
+//! ```yaml
+//! traffic:
+//!   - node: Alice
+//!     send_dm: Bob
+//!     at_s: 30
+//!     text: "Are you there?"
+//!   - node: Alice
+//!     post_channel: "#ops"
+//!     at_s: 60
+//!     every_s: 300
+//!     jitter_s: 30
+//!     until_s: 7200
+//! ```
+//!
+//! Times are absolute simulation times. `every_s` repeats the message with
+//! `jitter_s` standard deviation per interval, until `count` messages have
+//! been sent or `until_s` has passed. A DM destination is added to the
+//! sender's contacts and a channel to its subscriptions, so the firmware can
+//! encrypt the message.
+//!
+//! A rule with `on_receive` sends in response to received messages instead
+//! of on a schedule, to model replies and forwarding:
+//!
+//! ```yaml
+//! traffic:
+//!   - node: Bob
+//!     send_dm: Alice
+//!     on_receive: { dm_from: Alice }
+//!     delay_s: 20
+//!     jitter_s: 10
+//!     text: "Yes, here"
+//!   - node: Carol
+//!     post_channel: "#ops"
+//!     on_receive: { channel: "#alerts", text_contains: "fire" }
+//! ```
+//!
+//! Each matching message (a DM from `dm_from`, or a post on `channel`, whose
+//! text contains `text_contains` if given) sends one message `delay_s` later,
+//! with `jitter_s` standard deviation on the delay. Messages received before
+//! `at_s` or after `until_s` trigger nothing, and `count` caps the replies.
+//! The trigger's sender is added to the contacts and its channel to the
+//! subscriptions, so the firmware can decrypt what triggers the rule.
+//!
+//! A rule with `metric` names a counter from the scenario's
+//! `custom_metrics`, incremented for each message the rule sends (with a
+//! `node` label if the counter declares one), so experiments can count
+//! their own traffic apart from the rest:
+//!
+//! ```yaml
+//! custom_metrics:
+//!   - { name: experiment.pings, kind: counter, labels: [node] }
+//! traffic:
+//!   - { node: Alice, send_dm: Bob, at_s: 30, every_s: 60, metric: experiment.pings }
+//! ```
+//!
+//! Traffic can be reloaded from the model files while a run is in progress
+//! (`mcsim run --watch-scripts`, or `reload` at the console), to iterate on
+//! agent behavior without restarting a long simulation. Only the `traffic`
+//! section is reloaded. Each companion's rules start over as if the run had
+//! begun with them: sent counts are reset, pending sends of the old rules
+//! are dropped, and timed rules are scheduled at their `at_s`, or at once if
+//! it has passed. Contacts and channels are only set up at startup, so a
+//! reloaded rule that needs a contact or channel the node didn't already
+//! have is dropped with a warning.
+
+use std::collections::BTreeMap;
+
+use mcsim_common::NodeId;
+use serde::{Deserialize, Serialize};
+
+use crate::ModelError;
+
+/// What a traffic rule sends.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrafficMessage {
+    /// Direct message to the named node.
+    DirectMessage(String),
+    /// Post to the named channel.
+    Channel(String),
+}
+
+/// Which received messages trigger a traffic rule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrafficSource {
+    /// Direct message from the named node.
+    DirectMessage(String),
+    /// Post on the named channel.
+    Channel(String),
+}
+
+/// Received message that makes a traffic rule send.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrafficTrigger {
+    /// Where the message comes from.
+    pub source: TrafficSource,
+    /// Only messages whose text contains this trigger the rule.
+    pub text_contains: Option<String>,
+}
+
+/// Scripted traffic sent by one node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrafficRule {
+    /// Name of the sending companion.
+    pub node: String,
+    /// What to send.
+    pub message: TrafficMessage,
+    /// Simulation time (seconds) of the first message, or from which
+    /// triggers are honored.
+    pub at_s: f64,
+    /// Interval between messages; sent once if None.
+    pub every_s: Option<f64>,
+    /// Standard deviation of the interval (or of the trigger delay).
+    pub jitter_s: f64,
+    /// Send in response to received messages instead of on a schedule.
+    pub trigger: Option<TrafficTrigger>,
+    /// Delay (seconds) between a trigger and the message it sends.
+    pub delay_s: f64,
+    /// Stop after this many messages.
+    pub count: Option<u32>,
+    /// Send nothing after this simulation time (seconds).
+    pub until_s: Option<f64>,
+    /// Message text (a default is generated if None).
+    pub text: Option<String>,
+    /// Custom counter incremented for each message sent.
+    pub metric: Option<String>,
+}
+
+impl TrafficRule {
+    /// The rule as the sender's agent runs it, with node names resolved to
+    /// IDs. Returns `None` if a node it names is not in `node_ids`.
+    pub fn to_agent_rule(&self, node_ids: &BTreeMap<String, NodeId>) -> Option<mcsim_agents::TrafficRule> {
+        let destination = match &self.message {
+            TrafficMessage::DirectMessage(to) => mcsim_agents::TrafficDestination::Direct(*node_ids.get(to)?),
+            TrafficMessage::Channel(name) => {
+                mcsim_agents::TrafficDestination::Channel(mcsim_agents::ChannelTarget::from_name(name.clone()))
+            }
+        };
+        let trigger = match &self.trigger {
+            Some(trigger) => {
+                let source = match &trigger.source {
+                    TrafficSource::DirectMessage(from) => mcsim_agents::TrafficSource::Direct(*node_ids.get(from)?),
+                    TrafficSource::Channel(name) => mcsim_agents::TrafficSource::Channel(name.clone()),
+                };
+                Some(mcsim_agents::TrafficTrigger { source, text_contains: trigger.text_contains.clone() })
+            }
+            None => None,
+        };
+        Some(mcsim_agents::TrafficRule {
+            destination,
+            at_s: self.at_s,
+            interval_s: self.every_s,
+            interval_jitter_s: self.jitter_s,
+            message_count: self.count,
+            until_s: self.until_s,
+            text: self.text.clone(),
+            trigger,
+            delay_s: self.delay_s,
+            metric: self.metric.clone(),
+        })
+    }
+}
+
+// ============================================================================
+// YAML Schema Types (Internal)
+// ============================================================================
+
+/// Traffic trigger (YAML schema, internal). Exactly one of `dm_from` and
+/// `channel` must be given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TrafficTriggerYaml {
+    #[serde(default)]
+    dm_from: Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    text_contains: Option<String>,
+}
+
+/// Traffic entry (YAML schema, internal). Exactly one of `send_dm` and
+/// `post_channel` must be given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TrafficRuleYaml {
+    node: String,
+    #[serde(default)]
+    send_dm: Option<String>,
+    #[serde(default)]
+    post_channel: Option<String>,
+    #[serde(default)]
+    at_s: f64,
+    #[serde(default)]
+    every_s: Option<f64>,
+    #[serde(default)]
+    jitter_s: f64,
+    #[serde(default)]
+    count: Option<u32>,
+    #[serde(default)]
+    until_s: Option<f64>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    on_receive: Option<TrafficTriggerYaml>,
+    #[serde(default)]
+    delay_s: f64,
+    #[serde(default)]
+    metric: Option<String>,
+}
+
+impl TrafficRuleYaml {
+    pub(crate) fn resolve(&self) -> Result<TrafficRule, ModelError> {
+        let invalid = |reason: &str| {
+            ModelError::InvalidConfig(format!("Traffic for node '{}': {}", self.node, reason))
+        };
+
+        let message = match (&self.send_dm, &self.post_channel) {
+            (Some(to), None) if *to == self.node => return Err(invalid("send_dm can't target the sender")),
+            (Some(to), None) => TrafficMessage::DirectMessage(to.clone()),
+            (None, Some(channel)) => TrafficMessage::Channel(channel.clone()),
+            _ => return Err(invalid("exactly one of send_dm or post_channel is required")),
+        };
+        if self.at_s.is_nan() || self.at_s < 0.0 {
+            return Err(invalid("at_s must be non-negative"));
+        }
+        if self.every_s.is_some_and(|every| every.is_nan() || every <= 0.0) {
+            return Err(invalid("every_s must be positive"));
+        }
+        if self.jitter_s.is_nan() || self.jitter_s < 0.0 {
+            return Err(invalid("jitter_s must be non-negative"));
+        }
+        let trigger = match &self.on_receive {
+            Some(on_receive) => {
+                let source = match (&on_receive.dm_from, &on_receive.channel) {
+                    (Some(from), None) if *from == self.node => {
+                        return Err(invalid("on_receive can't trigger on the sender's own DMs"))
+                    }
+                    (Some(from), None) => TrafficSource::DirectMessage(from.clone()),
+                    (None, Some(channel)) => TrafficSource::Channel(channel.clone()),
+                    _ => return Err(invalid("on_receive needs exactly one of dm_from or channel")),
+                };
+                if self.every_s.is_some() {
+                    return Err(invalid("every_s can't be combined with on_receive"));
+                }
+                Some(TrafficTrigger { source, text_contains: on_receive.text_contains.clone() })
+            }
+            None if self.delay_s != 0.0 => return Err(invalid("delay_s requires on_receive")),
+            None => None,
+        };
+        if self.delay_s.is_nan() || self.delay_s < 0.0 {
+            return Err(invalid("delay_s must be non-negative"));
+        }
+
+        Ok(TrafficRule {
+            node: self.node.clone(),
+            message,
+            at_s: self.at_s,
+            every_s: self.every_s,
+            jitter_s: self.jitter_s,
+            count: self.count,
+            until_s: self.until_s,
+            text: self.text.clone(),
+            trigger,
+            delay_s: self.delay_s,
+            metric: self.metric.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(yaml: &str) -> Result<TrafficRule, ModelError> {
+        serde_yaml::from_str::<TrafficRuleYaml>(yaml).unwrap().resolve()
+    }
+
+    #[test]
+    fn test_resolve_traffic() {
+        let rule = resolve("node: Alice\npost_channel: '#ops'\nat_s: 60\nevery_s: 300\njitter_s: 30\n").unwrap();
+        assert_eq!(rule.message, TrafficMessage::Channel("#ops".to_string()));
+        assert_eq!(rule.every_s, Some(300.0));
+        assert_eq!(rule.count, None);
+
+        let rule = resolve("node: Alice\nsend_dm: Bob\nat_s: 30\n").unwrap();
+        assert_eq!(rule.message, TrafficMessage::DirectMessage("Bob".to_string()));
+        assert_eq!(rule.every_s, None);
+        assert_eq!(rule.trigger, None);
+    }
+
+    #[test]
+    fn test_resolve_triggered_traffic() {
+        let rule = resolve("node: Bob\nsend_dm: Alice\non_receive: { dm_from: Alice }\ndelay_s: 20\n").unwrap();
+        assert_eq!(
+            rule.trigger,
+            Some(TrafficTrigger { source: TrafficSource::DirectMessage("Alice".to_string()), text_contains: None })
+        );
+        assert_eq!(rule.delay_s, 20.0);
+
+        let rule =
+            resolve("node: Carol\npost_channel: '#ops'\non_receive: { channel: '#alerts', text_contains: fire }\n")
+                .unwrap();
+        let trigger = rule.trigger.unwrap();
+        assert_eq!(trigger.source, TrafficSource::Channel("#alerts".to_string()));
+        assert_eq!(trigger.text_contains.as_deref(), Some("fire"));
+    }
+
+    #[test]
+    fn test_invalid_traffic_rejected() {
+        for yaml in [
+            "node: Alice\nat_s: 30\n",
+            "node: Alice\nsend_dm: Bob\npost_channel: Public\n",
+            "node: Alice\nsend_dm: Alice\n",
+            "node: Alice\nsend_dm: Bob\nevery_s: 0\n",
+            "node: Alice\nsend_dm: Bob\ndelay_s: 5\n",
+            "node: Alice\nsend_dm: Bob\non_receive: {}\n",
+            "node: Alice\nsend_dm: Bob\non_receive: { dm_from: Alice }\n",
+            "node: Alice\nsend_dm: Bob\non_receive: { dm_from: Bob }\nevery_s: 60\n",
+            "node: Alice\nsend_dm: Bob\non_receive: { channel: Public }\ndelay_s: -1\n",
+        ] {
+            assert!(matches!(resolve(yaml), Err(ModelError::InvalidConfig(_))), "{}", yaml);
+        }
+    }
+
+    #[test]
+    fn test_traffic_metric_must_be_declared_counter() {
+        let topology = "nodes:\n  - name: Alice\n  - name: Bob\n";
+        let traffic = "traffic:\n  - { node: Alice, send_dm: Bob, at_s: 10, metric: experiment.pings }\n";
+        let counter = "custom_metrics:\n  - { name: experiment.pings, kind: counter, labels: [node] }\n";
+        let model = crate::load_models_from_str(&[topology, counter, traffic]).unwrap();
+        assert_eq!(model.traffic()[0].metric.as_deref(), Some("experiment.pings"));
+
+        let gauge = "custom_metrics:\n  - { name: experiment.pings, kind: gauge }\n";
+        for models in [vec![topology, traffic], vec![topology, gauge, traffic]] {
+            assert!(matches!(crate::load_models_from_str(&models), Err(ModelError::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn test_non_ascii_names_and_text() {
+        let topology = "nodes:\n  - name: Café 📡\n  - name: 東京-Relay\n";
+        let overlay = "traffic:\n  - { node: Café 📡, send_dm: 東京-Relay, at_s: 10, text: 'Grüße 👋' }\n";
+        let model = crate::load_models_from_str(&[topology, overlay]).unwrap();
+        assert!(model.nodes().contains_key("東京-Relay"));
+        let rule = &model.traffic()[0];
+        assert_eq!(rule.node, "Café 📡");
+        assert_eq!(rule.message, TrafficMessage::DirectMessage("東京-Relay".to_string()));
+        assert_eq!(rule.text.as_deref(), Some("Grüße 👋"));
+    }
+}
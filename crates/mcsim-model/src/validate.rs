@@ -0,0 +1,260 @@
+//! Scenario validation without running it.
+//!
+//! [`validate_simulation`] builds the simulation as a run would, resolving
+//! every property, generating and checking node keys and creating radios,
+//! agents and initial events, but without loading any firmware. It then
+//! checks that the firmware libraries the nodes need can be found and
+//! predicts the margin of every link, so mistakes that would otherwise
+//! surface minutes into a run are reported up front.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use mcsim_firmware::dll::{find_dll_path, FirmwareType};
+
+use crate::connectivity::link_budget;
+use crate::topology::{analyze_topology, TopologyReport};
+use crate::{analyze_connectivity, ConnectivityReport, Model, ModelError, NodeInfo, UnreachablePolicy};
+
+/// A firmware library needed by the scenario.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirmwareLibrary {
+    /// Node type the library runs (Repeater, Companion, RoomServer).
+    pub node_type: String,
+    /// File name of the library.
+    pub library: String,
+    /// Nodes running it.
+    pub nodes: Vec<String>,
+    /// Where the library was found, `None` if it wasn't.
+    pub path: Option<PathBuf>,
+}
+
+/// Predicted quality of one directed link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkPrediction {
+    /// Sending node.
+    pub from: String,
+    /// Receiving node.
+    pub to: String,
+    /// Mean SNR adjusted for the sender's TX power, in dB.
+    pub snr_db: f64,
+    /// Demodulation threshold of the receiver's spreading factor, in dB.
+    pub threshold_db: f64,
+}
+
+impl LinkPrediction {
+    /// Margin of the mean SNR over the threshold, in dB.
+    pub fn margin_db(&self) -> f64 {
+        self.snr_db - self.threshold_db
+    }
+}
+
+/// Result of [`validate_simulation`].
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    /// Seed the node keys were generated with.
+    pub seed: u64,
+    /// The nodes as a run would create them.
+    pub node_infos: Vec<NodeInfo>,
+    /// Firmware libraries the nodes need.
+    pub firmware: Vec<FirmwareLibrary>,
+    /// Nodes sharing the first byte of their public key, by that byte.
+    /// Packet paths name nodes by this byte, so such nodes are ambiguous to
+    /// each other's neighbours.
+    pub hash_collisions: BTreeMap<u8, Vec<String>>,
+    /// Every edge of the model.
+    pub links: Vec<LinkPrediction>,
+    /// Partitions, single points of failure and centrality of the links.
+    pub topology: TopologyReport,
+    /// Nodes predicted unable to send or receive.
+    pub connectivity: ConnectivityReport,
+    /// What the run does with unreachable nodes.
+    pub unreachable_policy: UnreachablePolicy,
+}
+
+impl ValidationReport {
+    /// Problems that would stop the run from starting.
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = self
+            .firmware
+            .iter()
+            .filter(|lib| lib.path.is_none())
+            .map(|lib| format!("firmware library {} not found", lib.library))
+            .collect();
+        if self.unreachable_policy == UnreachablePolicy::Fail && !self.connectivity.is_empty() {
+            errors.push(self.connectivity.to_string());
+        }
+        errors
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut types: BTreeMap<&str, usize> = BTreeMap::new();
+        for info in &self.node_infos {
+            *types.entry(info.node_type.as_str()).or_default() += 1;
+        }
+        let types: Vec<String> = types.iter().map(|(t, n)| format!("{} {}", n, t)).collect();
+        writeln!(f, "Nodes: {} ({})", self.node_infos.len(), types.join(", "))?;
+
+        writeln!(f, "Firmware:")?;
+        for lib in &self.firmware {
+            match &lib.path {
+                Some(path) => writeln!(f, "  ✓ {} ({} node(s)): {}", lib.node_type, lib.nodes.len(), path.display())?,
+                None => writeln!(f, "  ✗ {} ({} node(s)): {} not found", lib.node_type, lib.nodes.len(), lib.library)?,
+            }
+        }
+
+        writeln!(f, "Keys: generated with seed {}", self.seed)?;
+        for (hash, nodes) in &self.hash_collisions {
+            writeln!(f, "  warning: {} share path hash {:02x}", nodes.join(", "), hash)?;
+        }
+
+        let weak: Vec<&LinkPrediction> = self.links.iter().filter(|l| l.margin_db() < 0.0).collect();
+        writeln!(f, "Links: {} predicted, {} below threshold", self.links.len(), weak.len())?;
+        for link in weak {
+            writeln!(
+                f,
+                "  {} -> {}: {:.1} dB SNR, {:+.1} dB margin",
+                link.from,
+                link.to,
+                link.snr_db,
+                link.margin_db()
+            )?;
+        }
+
+        writeln!(f, "{}", self.topology)?;
+
+        if self.connectivity.is_empty() {
+            write!(f, "Connectivity: every node can send and receive")
+        } else {
+            write!(f, "Connectivity: {}", self.connectivity)
+        }
+    }
+}
+
+/// Check that a model would build and run, without loading firmware.
+///
+/// Errors in the model itself (invalid properties, duplicate keys, unknown
+/// firmware types) are returned as errors. Missing firmware libraries and
+/// link predictions are reported; see [`ValidationReport::errors`].
+pub fn validate_simulation(model: &Model, seed: u64) -> Result<ValidationReport, ModelError> {
+    let unreachable_policy = UnreachablePolicy::from_model(model)?;
+    let built = crate::build(model, seed, false)?;
+
+    let mut firmware: Vec<FirmwareLibrary> = Vec::new();
+    let mut hashes: BTreeMap<u8, Vec<String>> = BTreeMap::new();
+    for info in &built.node_infos {
+        let firmware_type = match info.node_type.as_str() {
+            "Repeater" => FirmwareType::Repeater,
+            "Companion" => FirmwareType::Companion,
+            "RoomServer" => FirmwareType::RoomServer,
+            _ => continue,
+        };
+        match firmware.iter_mut().find(|lib| lib.node_type == info.node_type) {
+            Some(lib) => lib.nodes.push(info.name.clone()),
+            None => firmware.push(FirmwareLibrary {
+                node_type: info.node_type.clone(),
+                library: firmware_type.dll_name().to_string(),
+                nodes: vec![info.name.clone()],
+                path: find_dll_path(firmware_type).ok(),
+            }),
+        }
+        hashes.entry(info.public_key[0]).or_default().push(info.name.clone());
+    }
+    hashes.retain(|_, nodes| nodes.len() > 1);
+
+    let links = predict_links(model);
+    Ok(ValidationReport {
+        seed,
+        node_infos: built.node_infos,
+        firmware,
+        hash_collisions: hashes,
+        topology: analyze_topology(model, &links),
+        links,
+        connectivity: analyze_connectivity(model),
+        unreachable_policy,
+    })
+}
+
+/// Predict the mean SNR and margin of every edge of the model.
+pub fn predict_links(model: &Model) -> Vec<LinkPrediction> {
+    let nodes = model.nodes();
+    model
+        .edges()
+        .values()
+        .map(|edge| {
+            let (snr_db, threshold_db) = link_budget(model, &nodes[&edge.from], &nodes[&edge.to], edge);
+            LinkPrediction {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                snr_db,
+                threshold_db,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_model_from_str;
+
+    const MODEL: &str = r#"
+nodes:
+  - name: "Alice"
+    location: { lat: 47.60, lon: -122.40 }
+    firmware: { type: Companion }
+  - name: "Relay"
+    location: { lat: 47.61, lon: -122.40 }
+    firmware: { type: Repeater }
+    keys: { public_key: "ab*" }
+  - name: "Bob"
+    location: { lat: 47.62, lon: -122.40 }
+    firmware: { type: Companion }
+    keys: { public_key: "ab*" }
+edges:
+  - { from: "Alice", to: "Relay", mean_snr_db_at20dbm: 10.0 }
+  - { from: "Relay", to: "Alice", mean_snr_db_at20dbm: 10.0 }
+  - { from: "Relay", to: "Bob", mean_snr_db_at20dbm: 10.0 }
+  - { from: "Bob", to: "Relay", mean_snr_db_at20dbm: -30.0 }
+"#;
+
+    #[test]
+    fn test_validate_without_firmware() {
+        let model = load_model_from_str(MODEL).unwrap();
+        let report = validate_simulation(&model, 42).unwrap();
+
+        assert_eq!(report.node_infos.len(), 3);
+        let types: Vec<(&str, usize)> = report.firmware.iter().map(|l| (l.node_type.as_str(), l.nodes.len())).collect();
+        assert_eq!(types, vec![("Companion", 2), ("Repeater", 1)]);
+        assert_eq!(report.hash_collisions.get(&0xab), Some(&vec!["Bob".to_string(), "Relay".to_string()]));
+
+        let weak: Vec<(&str, &str)> = report
+            .links
+            .iter()
+            .filter(|l| l.margin_db() < 0.0)
+            .map(|l| (l.from.as_str(), l.to.as_str()))
+            .collect();
+        assert_eq!(weak, vec![("Bob", "Relay")]);
+        assert_eq!(report.connectivity.unreachable.len(), 1);
+        assert_eq!(report.connectivity.unreachable[0].name, "Bob");
+
+        let text = report.to_string();
+        assert!(text.contains("Nodes: 3 (2 Companion, 1 Repeater)"));
+        assert!(text.contains("warning: Bob, Relay share path hash ab"));
+        assert!(text.contains("Links: 4 predicted, 1 below threshold"));
+    }
+
+    #[test]
+    fn test_validate_reports_model_errors() {
+        let model = load_model_from_str(&MODEL.replace("type: Repeater", "type: Repeter")).unwrap();
+        let err = validate_simulation(&model, 42).unwrap_err();
+        assert!(err.to_string().contains("Unknown firmware_type 'Repeter'"));
+
+        let model = load_model_from_str(&format!("{}\nsimulation:\n  simulation/unreachable_nodes: fail\n", MODEL)).unwrap();
+        let report = validate_simulation(&model, 42).unwrap();
+        assert!(report.errors().iter().any(|e| e.contains("Bob")));
+    }
+}
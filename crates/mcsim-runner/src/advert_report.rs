@@ -0,0 +1,339 @@
+//! Discovery latency against advert airtime.
+//!
+//! Nodes with an advert suppression policy (see
+//! `mcsim_lora::suppression`) skip some of their adverts to save airtime,
+//! at the cost of neighbours discovering them later. This module records
+//! every advert a node originates, sent or skipped, and when each of its
+//! neighbours first heard one directly, so policies can be compared by
+//! running the same scenario with each.
+//!
+//! A neighbour is a node the link model predicts can decode the sender: the
+//! link's mean SNR at the sender's TX power clears the threshold of its
+//! spreading factor. Discovery latency is measured from the sender's
+//! firmware startup.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use mcsim_common::{Event, EventPayload, RadioParams, SimTime};
+use mcsim_lora::suppression::is_own_advert;
+use mcsim_lora::{calculate_snr_sensitivity, default_radio_params, LinkModel};
+use mcsim_model::NodeInfo;
+use serde::Serialize;
+
+/// Adverts and discovery of one node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeAdverts {
+    /// Node name.
+    pub node: String,
+    /// Suppression policy, if the node has one.
+    pub policy: Option<String>,
+    /// Adverts the node sent.
+    pub sent: u64,
+    /// Adverts its policy skipped.
+    pub suppressed: u64,
+    /// Airtime of the sent adverts in milliseconds.
+    pub airtime_ms: f64,
+    /// Airtime the skipped adverts would have taken in milliseconds.
+    pub airtime_saved_ms: f64,
+    /// Nodes predicted to hear the node.
+    pub neighbors: usize,
+    /// Neighbours that heard one of its adverts.
+    pub discovered_by: usize,
+    /// Mean time until a neighbour first heard it, in seconds.
+    pub mean_discovery_s: Option<f64>,
+}
+
+/// Result of [`AdvertTracker::report`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AdvertReport {
+    /// Every node, in model order.
+    pub nodes: Vec<NodeAdverts>,
+    /// Directed links between neighbours.
+    pub neighbor_links: usize,
+    /// Links over which the receiver heard an advert of the sender.
+    pub discovered_links: usize,
+    /// Mean discovery latency over the discovered links, in seconds.
+    pub mean_discovery_s: Option<f64>,
+    /// Longest discovery latency, in seconds.
+    pub max_discovery_s: Option<f64>,
+    /// Airtime of all sent adverts in milliseconds.
+    pub advert_airtime_ms: f64,
+    /// Airtime of all skipped adverts in milliseconds.
+    pub airtime_saved_ms: f64,
+}
+
+impl AdvertReport {
+    /// Fraction of the advert airtime the policies saved.
+    pub fn airtime_saved_ratio(&self) -> f64 {
+        let total = self.advert_airtime_ms + self.airtime_saved_ms;
+        if total > 0.0 {
+            self.airtime_saved_ms / total
+        } else {
+            0.0
+        }
+    }
+}
+
+fn fmt_secs(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.1}", v))
+}
+
+impl fmt::Display for AdvertReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<16} {:<12} {:>5} {:>5} {:>11} {:>11} {:>10} {:>10}",
+            "node", "policy", "sent", "skip", "airtime ms", "saved ms", "heard by", "latency s"
+        )?;
+        for node in &self.nodes {
+            writeln!(
+                f,
+                "{:<16} {:<12} {:>5} {:>5} {:>11.1} {:>11.1} {:>10} {:>10}",
+                node.node,
+                node.policy.as_deref().unwrap_or("none"),
+                node.sent,
+                node.suppressed,
+                node.airtime_ms,
+                node.airtime_saved_ms,
+                format!("{}/{}", node.discovered_by, node.neighbors),
+                fmt_secs(node.mean_discovery_s)
+            )?;
+        }
+        write!(
+            f,
+            "{}/{} neighbour links discovered, mean {} s, max {} s; {:.0}% of advert airtime saved",
+            self.discovered_links,
+            self.neighbor_links,
+            fmt_secs(self.mean_discovery_s),
+            fmt_secs(self.max_discovery_s),
+            self.airtime_saved_ratio() * 100.0
+        )
+    }
+}
+
+/// Advert activity of a node so far.
+#[derive(Debug, Clone)]
+struct NodeState {
+    name: String,
+    policy: Option<String>,
+    radio_id: u64,
+    startup_time: SimTime,
+    /// Transmitter settings, as last seen on air.
+    params: Option<RadioParams>,
+    sent: u64,
+    airtime_us: u64,
+    suppressed: u64,
+    saved_us: u64,
+}
+
+/// Records adverts and neighbour discovery during a run.
+#[derive(Debug, Clone)]
+pub struct AdvertTracker {
+    nodes: Vec<NodeState>,
+    /// Node index by radio entity ID.
+    by_radio: HashMap<u64, usize>,
+    /// Node index by firmware entity ID.
+    by_firmware: HashMap<u64, usize>,
+    /// When each (sender, receiver) node pair first heard an advert.
+    discovered: HashMap<(usize, usize), SimTime>,
+}
+
+impl AdvertTracker {
+    /// Track the nodes of a simulation.
+    pub fn new(nodes: &[NodeInfo]) -> Self {
+        let mut by_radio = HashMap::new();
+        let mut by_firmware = HashMap::new();
+        let nodes = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, info)| {
+                by_radio.insert(info.radio_entity_id, index);
+                by_firmware.insert(info.firmware_entity_id, index);
+                NodeState {
+                    name: info.name.clone(),
+                    policy: info.advert_suppression.clone(),
+                    radio_id: info.radio_entity_id,
+                    startup_time: info.startup_time,
+                    params: None,
+                    sent: 0,
+                    airtime_us: 0,
+                    suppressed: 0,
+                    saved_us: 0,
+                }
+            })
+            .collect();
+        Self { nodes, by_radio, by_firmware, discovered: HashMap::new() }
+    }
+
+    /// Record an event.
+    pub fn observe(&mut self, event: &Event) {
+        match &event.payload {
+            EventPayload::TransmitAir(tx) => {
+                let Some(&index) = self.by_radio.get(&tx.radio_id.0) else {
+                    return;
+                };
+                let node = &mut self.nodes[index];
+                node.params = Some(tx.params.clone());
+                if is_own_advert(&tx.packet) {
+                    node.sent += 1;
+                    node.airtime_us += (tx.end_time - event.time).as_micros();
+                }
+            }
+            EventPayload::AdvertSuppressed(skipped) => {
+                if let Some(&index) = self.by_radio.get(&skipped.radio_id.0) {
+                    self.nodes[index].suppressed += 1;
+                    self.nodes[index].saved_us += skipped.airtime.as_micros();
+                }
+            }
+            EventPayload::RadioRxPacket(rx)
+                if !rx.was_collided && !rx.was_weak_signal && !rx.was_corrupted && is_own_advert(&rx.packet) =>
+            {
+                let Some(&sender) = self.by_radio.get(&rx.source_radio_id.0) else {
+                    return;
+                };
+                for target in &event.targets {
+                    if let Some(&receiver) = self.by_firmware.get(&target.0) {
+                        self.discovered.entry((sender, receiver)).or_insert(event.time);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Summarize the adverts and discovery latency so far, with neighbours
+    /// taken from `link_model`.
+    pub fn report(&self, link_model: &LinkModel) -> AdvertReport {
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        let mut latencies = Vec::new();
+        let mut neighbor_links = 0;
+        for (sender, node) in self.nodes.iter().enumerate() {
+            let params = node.params.clone().unwrap_or_else(default_radio_params);
+            let threshold_db = calculate_snr_sensitivity(params.spreading_factor);
+            let tx_offset_db = params.tx_power_dbm as f64 - 20.0;
+            let mut neighbors = 0;
+            let mut node_latencies = Vec::new();
+            for (receiver, other) in self.nodes.iter().enumerate() {
+                let Some(link) = link_model.get_link(
+                    mcsim_common::EntityId::new(node.radio_id),
+                    mcsim_common::EntityId::new(other.radio_id),
+                ) else {
+                    continue;
+                };
+                if receiver == sender || link.mean_snr_db_at20dbm + tx_offset_db < threshold_db {
+                    continue;
+                }
+                neighbors += 1;
+                if let Some(&heard) = self.discovered.get(&(sender, receiver)) {
+                    node_latencies.push((heard - node.startup_time).as_secs_f64());
+                }
+            }
+            neighbor_links += neighbors;
+            nodes.push(NodeAdverts {
+                node: node.name.clone(),
+                policy: node.policy.clone(),
+                sent: node.sent,
+                suppressed: node.suppressed,
+                airtime_ms: node.airtime_us as f64 / 1000.0,
+                airtime_saved_ms: node.saved_us as f64 / 1000.0,
+                neighbors,
+                discovered_by: node_latencies.len(),
+                mean_discovery_s: mean(&node_latencies),
+            });
+            latencies.extend(node_latencies);
+        }
+        AdvertReport {
+            neighbor_links,
+            discovered_links: latencies.len(),
+            mean_discovery_s: mean(&latencies),
+            max_discovery_s: latencies.iter().copied().reduce(f64::max),
+            advert_airtime_ms: nodes.iter().map(|n| n.airtime_ms).sum(),
+            airtime_saved_ms: nodes.iter().map(|n| n.airtime_saved_ms).sum(),
+            nodes,
+        }
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{AdvertSuppressedEvent, EntityId, EventId, LoraPacket, RadioRxPacketEvent, TransmitAirEvent};
+    use meshcore_packet::{AdvertPayload, MeshCorePacket};
+
+    fn event(time_s: f64, targets: Vec<u64>, payload: EventPayload) -> Event {
+        Event {
+            id: EventId(0),
+            time: SimTime::from_secs(time_s),
+            source: EntityId::new(0),
+            targets: targets.into_iter().map(EntityId::new).collect(),
+            payload,
+        }
+    }
+
+    #[test]
+    fn test_discovery_latency_and_airtime() {
+        let advert = LoraPacket::new(MeshCorePacket::advert(AdvertPayload::new([0u8; 32], 1, [0u8; 64], "A")).encode());
+        let mut link_model = LinkModel::new();
+        for (from, to) in [(101, 102), (102, 101), (101, 103)] {
+            link_model.add_link(EntityId::new(from), EntityId::new(to), 5.0, 1.0, -100.0);
+        }
+        // Too weak to count as a neighbour
+        link_model.add_link(EntityId::new(103), EntityId::new(101), -30.0, 1.0, -100.0);
+
+        let nodes = [
+            NodeInfo { advert_suppression: Some("adaptive".to_string()), ..NodeInfo::new("A", "Repeater", 1, 101) },
+            NodeInfo::new("B", "Repeater", 2, 102),
+            NodeInfo::new("C", "Repeater", 3, 103),
+        ];
+        let mut tracker = AdvertTracker::new(&nodes);
+        tracker.observe(&event(
+            10.0,
+            vec![101],
+            EventPayload::AdvertSuppressed(AdvertSuppressedEvent {
+                radio_id: EntityId::new(101),
+                airtime: SimTime::from_millis(300),
+                policy: "adaptive".to_string(),
+                utilization: 0.5,
+                queue_depth: None,
+            }),
+        ));
+        tracker.observe(&event(
+            40.0,
+            vec![0],
+            EventPayload::TransmitAir(TransmitAirEvent {
+                radio_id: EntityId::new(101),
+                packet: advert.clone(),
+                params: default_radio_params(),
+                sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+                end_time: SimTime::from_secs(40.1),
+            }),
+        ));
+        let heard = |collided: bool| RadioRxPacketEvent {
+            packet: advert.clone(),
+            source_radio_id: EntityId::new(101),
+            snr_db: 5.0,
+            rssi_dbm: -100.0,
+            was_collided: collided,
+            was_weak_signal: false,
+            was_corrupted: false,
+            start_time: SimTime::from_secs(40.0),
+            end_time: SimTime::from_secs(40.1),
+        };
+        tracker.observe(&event(40.1, vec![3], EventPayload::RadioRxPacket(heard(true))));
+        tracker.observe(&event(40.1, vec![2], EventPayload::RadioRxPacket(heard(false))));
+
+        let report = tracker.report(&link_model);
+        let a = &report.nodes[0];
+        assert_eq!((a.sent, a.suppressed, a.neighbors, a.discovered_by), (1, 1, 2, 1));
+        assert!((a.airtime_ms - 100.0).abs() < 1e-6);
+        assert!((a.mean_discovery_s.unwrap() - 40.1).abs() < 1e-6);
+        assert_eq!((report.neighbor_links, report.discovered_links), (3, 1));
+        assert!((report.airtime_saved_ratio() - 0.75).abs() < 1e-9);
+        assert!(report.to_string().contains("1/3 neighbour links discovered"));
+    }
+}
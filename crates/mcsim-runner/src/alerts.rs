@@ -0,0 +1,195 @@
+//! In-run evaluation of the scenario's alert rules.
+//!
+//! An [`AlertMonitor`] polls the metrics recorder at a fixed simulation-time
+//! interval and evaluates each [`AlertRule`] (see [`mcsim_model::alerts`]).
+//! Rates are the increase since the previous check divided by the elapsed
+//! simulation time. A rule fires once its condition has held for `for_s`
+//! seconds and fires again only after the condition has cleared.
+//!
+//! Fired alerts are logged by the event loop; `marker` alerts also add an
+//! `ALERT` entry to the trace and `stop` alerts end the run early.
+
+use std::fmt;
+
+use mcsim_model::{AlertAction, AlertCondition, AlertRule};
+
+use crate::SimTime;
+
+/// An alert that fired during the run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiredAlert {
+    /// Name of the rule.
+    pub rule: String,
+    /// Watched metric.
+    pub metric: String,
+    /// Node the rule is restricted to, if any.
+    pub node: Option<String>,
+    /// The rule's condition.
+    pub condition: AlertCondition,
+    /// Observed rate or value at the check that fired.
+    pub observed: f64,
+    /// Simulation time (seconds) at which the alert fired.
+    pub at_s: f64,
+    /// What the event loop does about it.
+    pub action: AlertAction,
+}
+
+impl fmt::Display for FiredAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "alert '{}' at {:.1}s: {}", self.rule, self.at_s, self.metric)?;
+        if let Some(ref node) = self.node {
+            write!(f, " on {}", node)?;
+        }
+        let unit = if self.condition.is_rate() { "/s" } else { "" };
+        write!(f, " is {:.3}{} ({})", self.observed, unit, self.condition)
+    }
+}
+
+/// Evaluation state of one rule.
+#[derive(Debug, Clone)]
+struct RuleState {
+    rule: AlertRule,
+    /// Time and value at the previous check.
+    last: Option<(SimTime, f64)>,
+    /// Start of the current stretch in which the condition held.
+    holding_since: Option<SimTime>,
+    /// Whether the alert fired during the current stretch.
+    fired: bool,
+}
+
+/// Periodic evaluation of alert rules.
+#[derive(Debug, Clone)]
+pub struct AlertMonitor {
+    rules: Vec<RuleState>,
+    check_interval: SimTime,
+    next_check: SimTime,
+    fired: Vec<FiredAlert>,
+}
+
+impl AlertMonitor {
+    /// Evaluate `rules` every `check_interval` of simulation time.
+    pub fn new(rules: Vec<AlertRule>, check_interval: SimTime) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|rule| RuleState { rule, last: None, holding_since: None, fired: false })
+                .collect(),
+            check_interval,
+            next_check: SimTime::ZERO,
+            fired: Vec::new(),
+        }
+    }
+
+    /// Whether a check is due at `now`.
+    pub fn is_due(&self, now: SimTime) -> bool {
+        !self.rules.is_empty() && now >= self.next_check
+    }
+
+    /// Evaluate every rule at `now`, reading current metric values through
+    /// `value` (metric name, optional node). Returns the alerts that fired.
+    pub fn check<F>(&mut self, now: SimTime, mut value: F) -> Vec<FiredAlert>
+    where
+        F: FnMut(&str, Option<&str>) -> Option<f64>,
+    {
+        self.next_check = SimTime::from_micros(now.as_micros() + self.check_interval.as_micros().max(1));
+
+        let mut fired = Vec::new();
+        for state in &mut self.rules {
+            let rule = &state.rule;
+            // Metrics that haven't been recorded yet are zero
+            let current = value(&rule.metric, rule.node.as_deref()).unwrap_or(0.0);
+            let previous = state.last.replace((now, current));
+
+            let (observed, window_start) = if rule.condition.is_rate() {
+                let Some((then, before)) = previous else {
+                    continue;
+                };
+                let elapsed = now.as_secs_f64() - then.as_secs_f64();
+                if elapsed <= 0.0 {
+                    continue;
+                }
+                // A drop means the recorder was cleared (end of warmup)
+                ((current - before).max(0.0) / elapsed, then)
+            } else {
+                (current, now)
+            };
+
+            if !rule.condition.holds(observed) {
+                state.holding_since = None;
+                state.fired = false;
+                continue;
+            }
+            let since = *state.holding_since.get_or_insert(window_start);
+            if !state.fired && now.as_secs_f64() - since.as_secs_f64() >= rule.for_s {
+                state.fired = true;
+                fired.push(FiredAlert {
+                    rule: rule.name.clone(),
+                    metric: rule.metric.clone(),
+                    node: rule.node.clone(),
+                    condition: rule.condition,
+                    observed,
+                    at_s: now.as_secs_f64(),
+                    action: rule.action,
+                });
+            }
+        }
+        self.fired.extend(fired.iter().cloned());
+        fired
+    }
+
+    /// All alerts fired so far.
+    pub fn fired(&self) -> &[FiredAlert] {
+        &self.fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(condition: AlertCondition, for_s: f64) -> AlertRule {
+        AlertRule {
+            name: "storm".to_string(),
+            metric: "mcsim.radio.rx_collided".to_string(),
+            node: None,
+            condition,
+            for_s,
+            action: AlertAction::Stop,
+        }
+    }
+
+    #[test]
+    fn test_rate_must_hold_for_duration() {
+        let mut monitor = AlertMonitor::new(vec![rule(AlertCondition::RateAbove(5.0), 20.0)], SimTime::from_secs(10.0));
+        // 10/s from t=10 onwards
+        let counter = |t: f64| if t <= 10.0 { 0.0 } else { (t - 10.0) * 10.0 };
+
+        for t in [0.0, 10.0, 20.0] {
+            assert!(monitor.check(SimTime::from_secs(t), |_, _| Some(counter(t))).is_empty());
+        }
+        assert!(!monitor.is_due(SimTime::from_secs(25.0)));
+        let fired = monitor.check(SimTime::from_secs(30.0), |_, _| Some(counter(30.0)));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].observed, 10.0);
+        assert_eq!(fired[0].to_string(), "alert 'storm' at 30.0s: mcsim.radio.rx_collided is 10.000/s (rate > 5/s)");
+
+        // Fires once per stretch, re-arms after the condition clears
+        assert!(monitor.check(SimTime::from_secs(40.0), |_, _| Some(counter(40.0))).is_empty());
+        assert!(monitor.check(SimTime::from_secs(50.0), |_, _| Some(counter(40.0))).is_empty());
+        assert!(monitor.check(SimTime::from_secs(60.0), |_, _| Some(counter(40.0) + 100.0)).is_empty());
+        assert_eq!(monitor.check(SimTime::from_secs(70.0), |_, _| Some(counter(40.0) + 200.0)).len(), 1);
+        assert_eq!(monitor.fired().len(), 2);
+    }
+
+    #[test]
+    fn test_value_condition_and_missing_metric() {
+        let mut monitor = AlertMonitor::new(vec![rule(AlertCondition::ValueBelow(1.0), 0.0)], SimTime::from_secs(5.0));
+        let fired = monitor.check(SimTime::from_secs(0.0), |_, _| None);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].observed, 0.0);
+
+        let mut monitor = AlertMonitor::new(vec![rule(AlertCondition::ValueAbove(3.0), 0.0)], SimTime::from_secs(5.0));
+        assert!(monitor.check(SimTime::from_secs(0.0), |_, _| Some(3.0)).is_empty());
+        assert_eq!(monitor.check(SimTime::from_secs(5.0), |_, _| Some(4.0)).len(), 1);
+    }
+}
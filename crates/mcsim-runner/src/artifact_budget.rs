@@ -0,0 +1,141 @@
+//! Cap on the disk space of a run's artifacts.
+//!
+//! Batch sweeps run many simulations unattended, often side by side on one
+//! disk. A single run of a chatty scenario can write gigabytes of trace and
+//! capture data and fill the disk under every other run. An
+//! [`ArtifactBudget`], set by `runner/max_artifact_mb` or
+//! `mcsim run --max-artifact-size`, caps the combined size of:
+//!
+//! - the event trace (`--output`), charged as each entry is recorded;
+//! - the serial capture, pcapng capture and Chrome trace, written through
+//!   [`BudgetedWriter`]s that count their bytes.
+//!
+//! Once the cap is reached the event loop stops all of them after the current
+//! event and warns on stderr. The files end at a record boundary, so they stay
+//! readable. The run itself carries on: metrics, statistics and reports are
+//! unaffected, and `artifact_cap_reached_s` in the statistics records when
+//! tracing stopped.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Byte budget shared by the artifacts of one run.
+#[derive(Debug, Clone)]
+pub struct ArtifactBudget {
+    limit: u64,
+    used: Arc<AtomicU64>,
+}
+
+impl ArtifactBudget {
+    /// Budget of `limit` bytes.
+    pub fn new(limit: u64) -> Self {
+        ArtifactBudget {
+            limit,
+            used: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Budget of `mb` megabytes (10^6 bytes).
+    pub fn from_mb(mb: f64) -> Self {
+        Self::new((mb.max(0.0) * 1e6) as u64)
+    }
+
+    /// The cap in bytes.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Bytes written or committed so far.
+    pub fn used(&self) -> u64 {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Count `bytes` against the budget.
+    pub fn charge(&self, bytes: u64) {
+        self.used.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Whether the artifacts have reached the cap.
+    pub fn is_exhausted(&self) -> bool {
+        self.used() >= self.limit
+    }
+
+    /// Wrap `inner` so the bytes written through it count against the budget.
+    pub fn writer<W: Write>(&self, inner: W) -> BudgetedWriter<W> {
+        BudgetedWriter {
+            inner,
+            budget: self.clone(),
+        }
+    }
+}
+
+/// Writer that counts the bytes written through it against an
+/// [`ArtifactBudget`]. It never refuses a write; the event loop stops the
+/// artifact at a record boundary instead.
+pub struct BudgetedWriter<W> {
+    inner: W,
+    budget: ArtifactBudget,
+}
+
+impl<W: Write> Write for BudgetedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.budget.charge(written as u64);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Parse a size such as `500M`, `2G`, `750k` or plain bytes. Suffixes are
+/// decimal (k = 10^3, M = 10^6, G = 10^9) and may be followed by `B`.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let digits = s.trim_end_matches(['B', 'b']);
+    let (number, scale) = match digits.chars().last() {
+        Some('k' | 'K') => (&digits[..digits.len() - 1], 1e3),
+        Some('m' | 'M') => (&digits[..digits.len() - 1], 1e6),
+        Some('g' | 'G') => (&digits[..digits.len() - 1], 1e9),
+        Some('t' | 'T') => (&digits[..digits.len() - 1], 1e12),
+        _ => (digits, 1.0),
+    };
+    let value: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid size '{}' (e.g. 500M, 2G or plain bytes)", s))?;
+    if !value.is_finite() || value <= 0.0 {
+        return Err(format!("Size '{}' must be positive", s));
+    }
+    Ok((value * scale) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budgeted_writers_share_budget() {
+        let budget = ArtifactBudget::new(10);
+        let mut a = budget.writer(Vec::new());
+        let mut b = budget.writer(Vec::new());
+        a.write_all(b"hello").unwrap();
+        assert!(!budget.is_exhausted());
+        b.write_all(b"world").unwrap();
+        assert_eq!(budget.used(), 10);
+        assert!(budget.is_exhausted());
+        assert_eq!(a.inner, b"hello");
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("500M"), Ok(500_000_000));
+        assert_eq!(parse_size("1.5GB"), Ok(1_500_000_000));
+        assert_eq!(parse_size("750k"), Ok(750_000));
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("0").is_err());
+    }
+}
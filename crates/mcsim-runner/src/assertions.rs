@@ -0,0 +1,307 @@
+//! End-of-run evaluation of the scenario's outcome assertions.
+//!
+//! An [`AssertionMonitor`] watches the events the assertions depend on while
+//! the simulation runs (see [`mcsim_model::assertions`]):
+//!
+//! - Direct message delivery is observed at the app: the companion protocol
+//!   frames the receiving firmware sends to its agent are decoded, and each
+//!   received DM is matched to its sender by public key prefix. Its latency
+//!   is the receive time minus the timestamp the sender put in the message.
+//! - Transmit duty cycle is the largest airtime within any sliding window,
+//!   tracked from the node's transmissions.
+//!
+//! Flood coverage and metric assertions are read from the packet tracker and
+//! metrics recorder when the run ends. `mcsim run` prints the results and
+//! exits with [`EXIT_ASSERTION_FAILED`] if any assertion failed.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use mcsim_common::{Event, EventPayload};
+use mcsim_companion_protocol::{Message, ProtocolSession, Response, PUB_KEY_PREFIX_SIZE};
+use mcsim_model::{Assertion, AssertionCheck, NodeInfo};
+use serde::Serialize;
+
+use crate::SimTime;
+
+/// Process exit code of `mcsim run` when an assertion failed.
+pub const EXIT_ASSERTION_FAILED: i32 = 2;
+
+/// Outcome of one assertion.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssertionResult {
+    /// Assertion name.
+    pub name: String,
+    /// What was checked.
+    pub check: String,
+    /// Whether the assertion held.
+    pub passed: bool,
+    /// What was observed.
+    pub observed: String,
+}
+
+impl fmt::Display for AssertionResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = if self.passed { "✓" } else { "✗" };
+        write!(f, "{} {}: {} ({})", mark, self.name, self.check, self.observed)
+    }
+}
+
+/// DMs from one node received by another.
+#[derive(Debug, Clone, Copy, Default)]
+struct Deliveries {
+    count: u64,
+    best_latency_s: f64,
+}
+
+/// Transmissions of one node within the duty cycle window.
+#[derive(Debug, Clone)]
+struct DutyWindow {
+    radio_id: u64,
+    window_us: u64,
+    /// (start, end) of recent transmissions, oldest first.
+    transmissions: VecDeque<(u64, u64)>,
+    max_airtime_us: u64,
+}
+
+impl DutyWindow {
+    /// Add a transmission and update the largest airtime in a window ending
+    /// at its end (a sliding window's airtime peaks at the end of a
+    /// transmission).
+    fn record(&mut self, start_us: u64, end_us: u64) {
+        let window_start = end_us.saturating_sub(self.window_us);
+        self.transmissions.push_back((start_us, end_us));
+        while self.transmissions.front().is_some_and(|&(_, end)| end <= window_start) {
+            self.transmissions.pop_front();
+        }
+        let airtime: u64 = self
+            .transmissions
+            .iter()
+            .map(|&(start, end)| end - start.max(window_start))
+            .sum();
+        self.max_airtime_us = self.max_airtime_us.max(airtime);
+    }
+}
+
+/// A companion whose received DMs are watched.
+struct Receiver {
+    name: String,
+    session: ProtocolSession,
+}
+
+/// Observes a run and evaluates its assertions at the end.
+pub struct AssertionMonitor {
+    assertions: Vec<Assertion>,
+    /// Watched receivers, keyed by their agent's entity ID.
+    receivers: HashMap<u64, Receiver>,
+    /// Node names by public key prefix.
+    prefixes: HashMap<[u8; PUB_KEY_PREFIX_SIZE], String>,
+    /// Received DMs by (sender, receiver).
+    deliveries: HashMap<(String, String), Deliveries>,
+    /// One window per duty cycle assertion (None if the node has no radio).
+    duty: Vec<Option<DutyWindow>>,
+}
+
+impl AssertionMonitor {
+    /// Watch the events needed by `assertions` among `nodes`.
+    pub fn new(assertions: Vec<Assertion>, nodes: &[NodeInfo]) -> Self {
+        let find = |name: &str| nodes.iter().find(|n| n.name == name);
+
+        let mut receivers = HashMap::new();
+        let mut duty = Vec::new();
+        for assertion in &assertions {
+            match &assertion.check {
+                AssertionCheck::Delivered { to, .. } => {
+                    if let Some(agent_id) = find(to).and_then(|n| n.agent_entity_id) {
+                        receivers.entry(agent_id).or_insert_with(|| Receiver {
+                            name: to.clone(),
+                            session: ProtocolSession::new(),
+                        });
+                    }
+                }
+                AssertionCheck::DutyCycle { node, window_s, .. } => {
+                    duty.push(find(node).map(|n| DutyWindow {
+                        radio_id: n.radio_entity_id,
+                        window_us: SimTime::from_secs(*window_s).as_micros(),
+                        transmissions: VecDeque::new(),
+                        max_airtime_us: 0,
+                    }));
+                }
+                _ => {}
+            }
+        }
+
+        let prefixes = nodes
+            .iter()
+            .map(|n| {
+                let mut prefix = [0u8; PUB_KEY_PREFIX_SIZE];
+                prefix.copy_from_slice(&n.public_key[..PUB_KEY_PREFIX_SIZE]);
+                (prefix, n.name.clone())
+            })
+            .collect();
+
+        Self { assertions, receivers, prefixes, deliveries: HashMap::new(), duty }
+    }
+
+    /// Whether there is nothing to evaluate.
+    pub fn is_empty(&self) -> bool {
+        self.assertions.is_empty()
+    }
+
+    /// Observe a processed event.
+    pub fn observe(&mut self, event: &Event) {
+        match &event.payload {
+            EventPayload::TransmitAir(tx) => {
+                let (start, end) = (event.time.as_micros(), tx.end_time.as_micros());
+                for window in self.duty.iter_mut().flatten().filter(|w| w.radio_id == tx.radio_id.0) {
+                    window.record(start, end);
+                }
+            }
+            EventPayload::SerialTx(serial) => {
+                for target in &event.targets {
+                    let Some(receiver) = self.receivers.get_mut(&target.0) else {
+                        continue;
+                    };
+                    receiver.session.feed(&serial.data);
+                    loop {
+                        let msg = match receiver.session.try_decode() {
+                            Ok(Some(Message::Response(
+                                Response::ContactMessageV2(msg) | Response::ContactMessageV3(msg),
+                            ))) => msg,
+                            // Other responses, and frames this protocol version can't decode
+                            Ok(Some(_)) | Err(_) => continue,
+                            Ok(None) => break,
+                        };
+                        let Some(sender) = self.prefixes.get(msg.sender_prefix.as_bytes()) else {
+                            continue;
+                        };
+                        let latency_s = (event.time.as_secs_f64() - msg.timestamp as f64).max(0.0);
+                        let entry = self
+                            .deliveries
+                            .entry((sender.clone(), receiver.name.clone()))
+                            .or_insert(Deliveries { count: 0, best_latency_s: f64::INFINITY });
+                        entry.count += 1;
+                        entry.best_latency_s = entry.best_latency_s.min(latency_s);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Evaluate every assertion at the end of a run lasting `run_time`.
+    ///
+    /// `flood_coverage` is the mean coverage of flood packets (None if no
+    /// flood was sent) and `metric` reads a metric's final value (metric
+    /// name, optional node; None if not recorded).
+    pub fn evaluate<F>(&self, run_time: SimTime, flood_coverage: Option<f64>, metric: F) -> Vec<AssertionResult>
+    where
+        F: Fn(&str, Option<&str>) -> Option<f64>,
+    {
+        let mut duty = self.duty.iter();
+        self.assertions
+            .iter()
+            .map(|assertion| {
+                let (passed, observed) = match &assertion.check {
+                    AssertionCheck::Delivered { from, to, within_s } => {
+                        if !self.receivers.values().any(|r| &r.name == to) {
+                            (false, format!("{} is not a companion", to))
+                        } else {
+                            match self.deliveries.get(&(from.clone(), to.clone())) {
+                                Some(d) => (
+                                    d.best_latency_s <= *within_s,
+                                    format!("{} received, fastest after {:.1}s", d.count, d.best_latency_s),
+                                ),
+                                None => (false, "never delivered".to_string()),
+                            }
+                        }
+                    }
+                    AssertionCheck::FloodCoverage { at_least } => match flood_coverage {
+                        Some(coverage) => (coverage >= *at_least, format!("{:.1}%", coverage * 100.0)),
+                        None => (false, "no flood packets".to_string()),
+                    },
+                    AssertionCheck::DutyCycle { at_most, window_s, .. } => match duty.next() {
+                        Some(Some(window)) => {
+                            // Runs shorter than the window are measured over the run
+                            let span_s = window_s.min(run_time.as_secs_f64());
+                            let peak = if span_s > 0.0 {
+                                window.max_airtime_us as f64 / 1e6 / span_s
+                            } else {
+                                0.0
+                            };
+                            (peak <= *at_most, format!("peak {:.3}%", peak * 100.0))
+                        }
+                        _ => (false, "node has no radio".to_string()),
+                    },
+                    AssertionCheck::Metric { metric: name, node, at_least, at_most } => {
+                        match metric(name, node.as_deref()) {
+                            Some(value) => (
+                                at_least.is_none_or(|min| value >= min) && at_most.is_none_or(|max| value <= max),
+                                format!("{}", value),
+                            ),
+                            // Counters that never incremented were never recorded
+                            None => (
+                                at_least.is_none_or(|min| min <= 0.0) && at_most.is_none_or(|max| max >= 0.0),
+                                "not recorded (0)".to_string(),
+                            ),
+                        }
+                    }
+                };
+                AssertionResult {
+                    name: assertion.name.clone(),
+                    check: assertion.check.to_string(),
+                    passed,
+                    observed,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duty_window_peak() {
+        let mut window = DutyWindow {
+            radio_id: 1,
+            window_us: 10_000_000,
+            transmissions: VecDeque::new(),
+            max_airtime_us: 0,
+        };
+        window.record(0, 1_000_000);
+        window.record(5_000_000, 6_000_000);
+        assert_eq!(window.max_airtime_us, 2_000_000);
+
+        // The first transmission has left the window ending at 12s
+        window.record(11_000_000, 12_000_000);
+        assert_eq!(window.max_airtime_us, 2_000_000);
+        window.record(12_000_000, 13_500_000);
+        assert_eq!(window.max_airtime_us, 3_500_000);
+    }
+
+    #[test]
+    fn test_evaluate_coverage_and_metrics() {
+        let assertions = vec![
+            Assertion { name: "coverage".into(), check: AssertionCheck::FloodCoverage { at_least: 0.9 } },
+            Assertion {
+                name: "collisions".into(),
+                check: AssertionCheck::Metric {
+                    metric: "mcsim.radio.rx_collided".into(),
+                    node: None,
+                    at_least: None,
+                    at_most: Some(0.0),
+                },
+            },
+        ];
+        let monitor = AssertionMonitor::new(assertions, &[]);
+
+        let results = monitor.evaluate(SimTime::from_secs(60.0), Some(0.95), |_, _| None);
+        assert!(results.iter().all(|r| r.passed), "{:?}", results);
+
+        let results = monitor.evaluate(SimTime::from_secs(60.0), Some(0.5), |_, _| Some(3.0));
+        assert!(results.iter().all(|r| !r.passed), "{:?}", results);
+        assert_eq!(results[0].to_string(), "✗ coverage: flood coverage >= 90.0% (50.0%)");
+    }
+}
@@ -0,0 +1,412 @@
+//! Blast radius of failure domains.
+//!
+//! Nodes tagged with the same failure domain (see
+//! [`mcsim_model::failures`]) go down together. `mcsim blast-radius` runs a
+//! scenario once without failures and once per domain, powering off the
+//! domain's nodes at a fixed time, and compares how well the surviving
+//! nodes still reach each other afterwards.
+//!
+//! Delivery is measured on flood packets, which every node is expected to
+//! hear. A [`DeliveryTracker`] records the origin of each flood and which
+//! nodes received it; [`DeliveryTracker::summary`] then restricts both to
+//! the survivors and the floods sent after the failure:
+//!
+//! - coverage: the mean fraction of the other survivors reached by a flood;
+//! - routes: the (origin, receiver) pairs of survivors with at least one
+//!   flood delivered.
+//!
+//! A survivor is cut off when it heard floods from other survivors in the
+//! baseline run but none once the domain failed.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use mcsim_common::{Event, EventPayload};
+use mcsim_model::NodeInfo;
+use meshcore_packet::PayloadHash;
+use serde::Serialize;
+
+use crate::sla::PairDeliveries;
+use crate::SimTime;
+
+/// Where a flood came from and who heard it.
+#[derive(Debug, Clone)]
+struct FloodReach {
+    /// Radio entity ID of the first transmitter.
+    origin: u64,
+    /// Time of the first transmission in microseconds.
+    origin_time_us: u64,
+    /// Radio entity IDs of the nodes that received it intact, with the time
+    /// (microseconds) each first did.
+    receivers: HashMap<u64, u64>,
+}
+
+/// Records the origin and receivers of every flood packet in a run.
+pub struct DeliveryTracker {
+    /// Node names by radio entity ID.
+    names: HashMap<u64, String>,
+    /// Radio entity IDs by firmware entity ID.
+    firmware_to_radio: HashMap<u64, u64>,
+    floods: HashMap<PayloadHash, FloodReach>,
+}
+
+impl DeliveryTracker {
+    /// Track the floods exchanged by `nodes`.
+    pub fn new(nodes: &[NodeInfo]) -> Self {
+        Self {
+            names: nodes.iter().map(|n| (n.radio_entity_id, n.name.clone())).collect(),
+            firmware_to_radio: nodes.iter().map(|n| (n.firmware_entity_id, n.radio_entity_id)).collect(),
+            floods: HashMap::new(),
+        }
+    }
+
+    /// Observe a processed event.
+    pub fn observe(&mut self, event: &Event) {
+        match &event.payload {
+            EventPayload::TransmitAir(tx) => {
+                let Some(packet) = tx.packet.decoded().filter(|p| p.is_flood()) else {
+                    return;
+                };
+                // Repeaters retransmit the same payload; the first sender is the origin
+                self.floods.entry(packet.payload_hash_label()).or_insert_with(|| FloodReach {
+                    origin: tx.radio_id.0,
+                    origin_time_us: event.time.as_micros(),
+                    receivers: HashMap::new(),
+                });
+            }
+            EventPayload::RadioRxPacket(rx) if !rx.was_collided && !rx.was_corrupted => {
+                let Some(packet) = rx.packet.decoded().filter(|p| p.is_flood()) else {
+                    return;
+                };
+                let Some(flood) = self.floods.get_mut(&packet.payload_hash_label()) else {
+                    return;
+                };
+                for target in &event.targets {
+                    if let Some(&radio) = self.firmware_to_radio.get(&target.0) {
+                        flood.receivers.entry(radio).or_insert(event.time.as_micros());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Delivery among the nodes not in `failed`, over the floods first sent
+    /// at or after `since`.
+    pub fn summary(&self, since: SimTime, failed: &HashSet<String>) -> DeliverySummary {
+        self.summary_between(since, SimTime::from_micros(u64::MAX), failed)
+    }
+
+    /// Delivery among the nodes not in `failed`, over the floods first sent
+    /// at or after `since` and before `until`.
+    pub fn summary_between(&self, since: SimTime, until: SimTime, failed: &HashSet<String>) -> DeliverySummary {
+        let survivors: HashSet<u64> = self
+            .names
+            .iter()
+            .filter(|(_, name)| !failed.contains(*name))
+            .map(|(&radio, _)| radio)
+            .collect();
+        let others = survivors.len().saturating_sub(1);
+
+        let mut floods = 0u64;
+        let mut coverage_sum = 0.0;
+        let mut routes = BTreeSet::new();
+        for flood in self.floods.values() {
+            if flood.origin_time_us < since.as_micros()
+                || flood.origin_time_us >= until.as_micros()
+                || !survivors.contains(&flood.origin)
+            {
+                continue;
+            }
+            floods += 1;
+            let reached: Vec<u64> = flood
+                .receivers
+                .keys()
+                .copied()
+                .filter(|r| *r != flood.origin && survivors.contains(r))
+                .collect();
+            if others > 0 {
+                coverage_sum += reached.len() as f64 / others as f64;
+            }
+            for receiver in reached {
+                routes.insert((self.names[&flood.origin].clone(), self.names[&receiver].clone()));
+            }
+        }
+
+        DeliverySummary {
+            survivors: survivors.len(),
+            floods,
+            mean_coverage: (floods > 0).then(|| coverage_sum / floods as f64),
+            routes,
+        }
+    }
+
+    /// Send time of the first flood sent at or after `since` by another node
+    /// that reached `node`.
+    pub fn first_reached(&self, node: &str, since: SimTime) -> Option<SimTime> {
+        let (&radio, _) = self.names.iter().find(|(_, name)| *name == node)?;
+        self.floods
+            .values()
+            .filter(|flood| {
+                flood.origin != radio && flood.origin_time_us >= since.as_micros() && flood.receivers.contains_key(&radio)
+            })
+            .map(|flood| flood.origin_time_us)
+            .min()
+            .map(SimTime::from_micros)
+    }
+
+    /// Floods each node sent before `until`, and when each other node heard
+    /// them (see [`crate::sla`]). Pairs whose origin sent nothing are left out.
+    pub fn pair_deliveries(&self, until: SimTime) -> Vec<PairDeliveries> {
+        let mut pairs: BTreeMap<(&str, &str), PairDeliveries> = BTreeMap::new();
+        for flood in self.floods.values().filter(|f| f.origin_time_us < until.as_micros()) {
+            let origin = self.names[&flood.origin].as_str();
+            for (radio, receiver) in &self.names {
+                if *radio == flood.origin {
+                    continue;
+                }
+                let pair = pairs.entry((origin, receiver)).or_insert_with(|| PairDeliveries {
+                    from: origin.to_string(),
+                    to: receiver.clone(),
+                    floods: 0,
+                    latencies: Vec::new(),
+                });
+                pair.floods += 1;
+                if let Some(&heard_us) = flood.receivers.get(radio) {
+                    pair.latencies.push(SimTime::from_micros(heard_us.saturating_sub(flood.origin_time_us)));
+                }
+            }
+        }
+        pairs.into_values().collect()
+    }
+}
+
+/// Flood delivery among a set of surviving nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliverySummary {
+    /// Number of surviving nodes.
+    pub survivors: usize,
+    /// Floods sent by survivors.
+    pub floods: u64,
+    /// Mean fraction of the other survivors reached by a flood (None if no
+    /// flood was sent).
+    pub mean_coverage: Option<f64>,
+    /// (origin, receiver) pairs of survivors with a flood delivered.
+    pub routes: BTreeSet<(String, String)>,
+}
+
+impl DeliverySummary {
+    /// Survivors that received a flood from another survivor.
+    fn reached_nodes(&self) -> BTreeSet<&str> {
+        self.routes.iter().map(|(_, receiver)| receiver.as_str()).collect()
+    }
+}
+
+/// Delivery impact of one domain's failure.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DomainImpact {
+    /// Failure domain.
+    pub domain: String,
+    /// Nodes powered off with the domain.
+    pub failed_nodes: Vec<String>,
+    /// Mean flood coverage of the survivors without the failure.
+    pub baseline_coverage: Option<f64>,
+    /// Mean flood coverage of the survivors after the failure.
+    pub coverage: Option<f64>,
+    /// Survivor routes with a flood delivered without the failure.
+    pub baseline_routes: usize,
+    /// Survivor routes with a flood delivered after the failure.
+    pub routes: usize,
+    /// Survivors that heard other survivors without the failure but no one
+    /// after it.
+    pub cut_off: Vec<String>,
+}
+
+impl DomainImpact {
+    /// Compare the survivors' delivery after a domain failure with the
+    /// baseline run, both summarized over the same survivors.
+    pub fn new(domain: String, failed_nodes: Vec<String>, baseline: &DeliverySummary, failed: &DeliverySummary) -> Self {
+        let reached = failed.reached_nodes();
+        let cut_off = baseline
+            .reached_nodes()
+            .into_iter()
+            .filter(|node| !reached.contains(node))
+            .map(str::to_string)
+            .collect();
+        Self {
+            domain,
+            failed_nodes,
+            baseline_coverage: baseline.mean_coverage,
+            coverage: failed.mean_coverage,
+            baseline_routes: baseline.routes.len(),
+            routes: failed.routes.len(),
+            cut_off,
+        }
+    }
+
+    /// Drop in mean flood coverage, in fractions of the survivors.
+    pub fn coverage_loss(&self) -> f64 {
+        self.baseline_coverage.unwrap_or(0.0) - self.coverage.unwrap_or(0.0)
+    }
+
+    /// Survivor routes lost to the failure.
+    pub fn routes_lost(&self) -> usize {
+        self.baseline_routes.saturating_sub(self.routes)
+    }
+}
+
+/// Blast radius of every failure domain of a scenario.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlastRadiusReport {
+    /// Simulation time (seconds) at which each domain failed.
+    pub fail_at_s: f64,
+    /// Length of each run in seconds.
+    pub duration_s: f64,
+    /// One entry per domain, largest coverage loss first.
+    pub domains: Vec<DomainImpact>,
+}
+
+impl BlastRadiusReport {
+    /// Build a report, ordering the domains by decreasing impact.
+    pub fn new(fail_at: SimTime, duration: SimTime, mut domains: Vec<DomainImpact>) -> Self {
+        domains.sort_by(|a, b| {
+            b.coverage_loss()
+                .total_cmp(&a.coverage_loss())
+                .then(b.routes_lost().cmp(&a.routes_lost()))
+                .then(a.domain.cmp(&b.domain))
+        });
+        Self { fail_at_s: fail_at.as_secs_f64(), duration_s: duration.as_secs_f64(), domains }
+    }
+}
+
+fn percent(coverage: Option<f64>) -> String {
+    coverage.map_or("-".to_string(), |c| format!("{:.1}%", c * 100.0))
+}
+
+impl fmt::Display for BlastRadiusReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Blast radius (domains fail at {:.0}s, runs end at {:.0}s):",
+            self.fail_at_s, self.duration_s
+        )?;
+        writeln!(
+            f,
+            "  {:<20} {:>6} {:>10} {:>10} {:>12} {:>8}",
+            "domain", "nodes", "coverage", "baseline", "routes lost", "cut off"
+        )?;
+        for impact in &self.domains {
+            writeln!(
+                f,
+                "  {:<20} {:>6} {:>10} {:>10} {:>12} {:>8}",
+                impact.domain,
+                impact.failed_nodes.len(),
+                percent(impact.coverage),
+                percent(impact.baseline_coverage),
+                format!("{}/{}", impact.routes_lost(), impact.baseline_routes),
+                impact.cut_off.len()
+            )?;
+        }
+        for impact in self.domains.iter().filter(|i| !i.cut_off.is_empty()) {
+            writeln!(f, "  {} cuts off: {}", impact.domain, impact.cut_off.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(floods: &[(u64, u64, &[u64])]) -> DeliveryTracker {
+        DeliveryTracker {
+            names: (1..=4).map(|radio| (radio, format!("N{}", radio))).collect(),
+            firmware_to_radio: HashMap::new(),
+            floods: floods
+                .iter()
+                .enumerate()
+                .map(|(i, &(origin, origin_time_us, receivers))| {
+                    (
+                        PayloadHash::from(i as u64),
+                        FloodReach { origin, origin_time_us, receivers: receivers.iter().map(|&r| (r, origin_time_us + 1_000_000)).collect() },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_summary_restricted_to_survivors() {
+        let tracker = tracker(&[
+            // Before the failure: ignored
+            (1, 0, &[2, 3, 4]),
+            (1, 2_000_000, &[2, 3]),
+            (2, 3_000_000, &[1, 3, 4]),
+            // Sent by the failed node
+            (4, 4_000_000, &[1]),
+        ]);
+        let failed: HashSet<String> = ["N4".to_string()].into();
+        let summary = tracker.summary(SimTime::from_secs(1.0), &failed);
+        assert_eq!(summary.survivors, 3);
+        assert_eq!(summary.floods, 2);
+        assert_eq!(summary.mean_coverage, Some(1.0));
+        assert_eq!(summary.routes.len(), 4);
+    }
+
+    #[test]
+    fn test_summary_window_and_recovery() {
+        let tracker = tracker(&[
+            (1, 0, &[2, 3, 4]),
+            // During an outage of N4
+            (1, 2_000_000, &[2, 3]),
+            // After power returns
+            (2, 5_000_000, &[1, 3]),
+            (3, 7_000_000, &[1, 2, 4]),
+        ]);
+        let none = HashSet::new();
+        let during = tracker.summary_between(SimTime::from_secs(1.0), SimTime::from_secs(4.0), &none);
+        assert_eq!(during.floods, 1);
+        assert!((during.mean_coverage.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(tracker.first_reached("N4", SimTime::from_secs(4.0)), Some(SimTime::from_secs(7.0)));
+        assert_eq!(tracker.first_reached("N4", SimTime::from_secs(8.0)), None);
+        // A node's own floods don't count
+        assert_eq!(tracker.first_reached("N3", SimTime::from_secs(6.0)), None);
+    }
+
+    #[test]
+    fn test_domain_impact() {
+        let none = HashSet::new();
+        let failed: HashSet<String> = ["N2".to_string()].into();
+        let baseline = tracker(&[(1, 0, &[2, 3, 4]), (3, 0, &[1, 2])]).summary(SimTime::ZERO, &failed);
+        let after = tracker(&[(1, 0, &[2]), (3, 0, &[1])]).summary(SimTime::ZERO, &failed);
+        let impact = DomainImpact::new("mast".into(), vec!["N2".into()], &baseline, &after);
+        assert_eq!(impact.baseline_routes, 3);
+        assert_eq!(impact.routes, 1);
+        assert_eq!(impact.cut_off, vec!["N3", "N4"]);
+        assert!((impact.coverage_loss() - 0.5).abs() < 1e-9);
+
+        let other = DomainImpact::new("pole".into(), Vec::new(), &baseline, &baseline);
+        let report = BlastRadiusReport::new(SimTime::ZERO, SimTime::from_secs(60.0), vec![other, impact]);
+        assert_eq!(report.domains[0].domain, "mast");
+        assert!(report.to_string().contains("mast cuts off: N3, N4"));
+        assert_eq!(tracker(&[]).summary(SimTime::ZERO, &none).mean_coverage, None);
+    }
+
+    #[test]
+    fn test_pair_deliveries() {
+        let tracker = tracker(&[
+            (1, 0, &[2, 3]),
+            (1, 2_000_000, &[2]),
+            // Sent too late to count
+            (2, 9_000_000, &[1]),
+        ]);
+        let pairs = tracker.pair_deliveries(SimTime::from_secs(5.0));
+        assert_eq!(pairs.len(), 3);
+        let to = |name: &str| pairs.iter().find(|p| p.to == name).unwrap();
+        assert_eq!(to("N2").floods, 2);
+        assert_eq!(to("N2").latencies, vec![SimTime::from_secs(1.0); 2]);
+        assert_eq!(to("N3").latencies.len(), 1);
+        assert!(to("N4").latencies.is_empty());
+    }
+}
@@ -6,9 +6,11 @@
 
 use mcsim_link::{
     estimate_snr_with_threshold, load_dem, load_itm,
-    load_aws_elevation, predict_link_with_elevation,
-    ElevationSource, LinkPredictionConfig, LoraModulationParams, PredictionMethod,
+    load_aws_elevation, ElevationSource, LinkCache, OfflineMode, LinkPrediction, LinkPredictionConfig,
+    LinkPredictionError, LinkPredictionParams, LinkStatus, LoraModulationParams, MapFormat, MapLink,
+    MapNode, NetworkMap, PredictionMethod,
 };
+use mcsim_common::REFERENCE_TX_POWER_DBM;
 use mcsim_itm::Itm;
 use rayon::prelude::*;
 use serde::Deserialize;
@@ -189,6 +191,14 @@ pub struct BuildModelConfig {
     pub terrain_samples: usize,
     /// Number of hex characters to retain from public key prefix.
     pub public_key_prefix_len: usize,
+    /// Whether predicted links get terrain-selected per-packet fading.
+    pub fading: bool,
+    /// Whether link predictions are cached in the elevation cache directory.
+    pub link_cache: bool,
+    /// Whether AWS terrain tiles may be downloaded, or only cached ones used.
+    pub offline_mode: OfflineMode,
+    /// Network maps (KML or GeoJSON, by extension) to write with the model.
+    pub map_paths: Vec<std::path::PathBuf>,
     /// Verbose output.
     pub verbose: bool,
 }
@@ -210,6 +220,10 @@ impl Default for BuildModelConfig {
             antenna_height: 2.0,
             terrain_samples: 100,
             public_key_prefix_len: 2,
+            fading: false,
+            link_cache: true,
+            offline_mode: OfflineMode::Online,
+            map_paths: Vec::new(),
             verbose: false,
         }
     }
@@ -257,6 +271,10 @@ struct LinkData {
     terrain_delta_h_m: f64,
     /// The method used for path loss prediction.
     prediction_method: Option<PredictionMethod>,
+    /// Rician K-factor for a line-of-sight path, `None` if obstructed.
+    rician_k_factor_db: Option<f64>,
+    /// Link quality, from the margin of `mean_snr_db` above the SNR threshold.
+    status: LinkStatus,
 }
 
 /// Source of link estimation.
@@ -274,6 +292,11 @@ enum LinkSource {
 
 /// Build a simulation model from mesh node JSON data.
 pub fn build_model(config: &BuildModelConfig) -> Result<(), BuildModelError> {
+    // Reject unknown map formats before the slow part
+    for path in &config.map_paths {
+        MapFormat::from_path(path).map_err(map_error)?;
+    }
+
     // Load and parse the JSON file
     let json_content = std::fs::read_to_string(&config.input_path)?;
     let mesh_data: MeshNodesData = serde_json::from_str(&json_content)?;
@@ -315,7 +338,8 @@ pub fn build_model(config: &BuildModelConfig) -> Result<(), BuildModelError> {
                 config.zoom
             );
             let src = load_aws_elevation(&config.elevation_cache, config.zoom)
-                .map_err(|e| BuildModelError::DemError(e.to_string()))?;
+                .map_err(|e| BuildModelError::DemError(e.to_string()))?
+                .with_offline_mode(config.offline_mode);
             Arc::new(src)
         }
         "local_dem" => {
@@ -331,6 +355,20 @@ pub fn build_model(config: &BuildModelConfig) -> Result<(), BuildModelError> {
             )));
         }
     };
+
+    // Antenna altitude of each node, for barometer readings and the like
+    let node_altitudes: Vec<Option<f64>> = processed_nodes
+        .iter()
+        .map(|n| match elevation.get_elevation(n.lat, n.lon) {
+            Ok(ground_m) => Some(ground_m as f64 + config.antenna_height),
+            Err(e) => {
+                if config.verbose {
+                    eprintln!("No elevation for node '{}': {}", n.name, e);
+                }
+                None
+            }
+        })
+        .collect();
     
     eprintln!("Loading ITM library...");
     // Verify that the ITM library can be loaded (this will fail fast if the DLL is missing).
@@ -338,6 +376,24 @@ pub fn build_model(config: &BuildModelConfig) -> Result<(), BuildModelError> {
     let _itm = load_itm().map_err(|e| BuildModelError::ItmError(e.to_string()))?;
     drop(_itm); // We'll use thread-local instances instead
 
+    // Reuse the predictions of earlier builds over the same elevation data
+    let link_cache_path = config.link_cache.then(|| link_cache_path(config));
+    let link_cache = match &link_cache_path {
+        Some(path) => match LinkCache::load(path) {
+            Ok(cache) => {
+                if config.verbose {
+                    eprintln!("Loaded {} cached link predictions from {}", cache.len(), path.display());
+                }
+                cache
+            }
+            Err(e) => {
+                eprintln!("Warning: ignoring link prediction cache {}: {}", path.display(), e);
+                LinkCache::new()
+            }
+        },
+        None => LinkCache::new(),
+    };
+
     // Get SNR threshold for the spreading factor
     let snr_threshold = config.min_snr_threshold.unwrap_or_else(|| {
         LoraModulationParams::with_sf(config.spreading_factor).sensitivity_threshold_snr()
@@ -388,14 +444,14 @@ pub fn build_model(config: &BuildModelConfig) -> Result<(), BuildModelError> {
 
             // Use thread-local ITM instance for parallel access
             let eval_result = with_thread_itm(|itm| {
+                let predictor = LinkPredictor { elevation: &elevation, itm, cache: &link_cache };
                 let mut links = Vec::with_capacity(2);
                 
                 // Forward direction: from -> to
                 if let Ok(Some(link)) = evaluate_link(
                     from_node,
                     to_node,
-                    &elevation,
-                    itm,
+                    &predictor,
                     &zero_hop_data,
                     &zero_hop_key,
                     config,
@@ -408,8 +464,7 @@ pub fn build_model(config: &BuildModelConfig) -> Result<(), BuildModelError> {
                 if let Ok(Some(link)) = evaluate_link(
                     to_node,
                     from_node,
-                    &elevation,
-                    itm,
+                    &predictor,
                     &zero_hop_data,
                     &reverse_key,
                     config,
@@ -479,13 +534,20 @@ pub fn build_model(config: &BuildModelConfig) -> Result<(), BuildModelError> {
     
     // Get final download stats
     let download_info = if let Some(stats) = elevation.download_stats() {
-        if stats.tiles_downloaded > 0 {
+        let mut info = if stats.tiles_downloaded > 0 {
             format!(" | {} tiles, {:.1} MB downloaded", 
                 stats.tiles_downloaded,
                 stats.bytes_downloaded as f64 / 1_048_576.0)
         } else {
             String::new()
+        };
+        if stats.failed_downloads > 0 {
+            info.push_str(&format!(" | {} failed tile requests", stats.failed_downloads));
+        }
+        if stats.offline_fallbacks > 0 {
+            info.push_str(&format!(" | {} offline fallback lookups", stats.offline_fallbacks));
         }
+        info
     } else {
         String::new()
     };
@@ -493,12 +555,71 @@ pub fn build_model(config: &BuildModelConfig) -> Result<(), BuildModelError> {
     eprintln!("\r  [{:>6}/{:>6}] Done in {:.1}s! {} viable links, {:.0} links/s avg{}               ", 
         final_checked, total_pairs, elapsed.as_secs_f64(), final_viable, final_links_per_sec, download_info);
 
+    if let Some(path) = &link_cache_path {
+        let stats = link_cache.stats();
+        eprintln!("Link prediction cache: {} reused, {} computed", stats.hits, stats.misses);
+        // Predictions over fallback terrain would outlive the missing tiles
+        let fell_back = elevation.download_stats().is_some_and(|s| s.offline_fallbacks > 0);
+        if fell_back {
+            eprintln!("Not saving the link prediction cache: some links used the offline fallback elevation");
+        } else if stats.misses > 0 {
+            if let Err(e) = link_cache.save(path) {
+                eprintln!("Warning: failed to save link prediction cache {}: {}", path.display(), e);
+            }
+        }
+    }
+
     // Generate YAML output
-    generate_yaml(&processed_nodes, &links, config)?;
+    generate_yaml(&processed_nodes, &node_altitudes, &links, config)?;
+
+    if !config.map_paths.is_empty() {
+        let map = network_map(&processed_nodes, &node_altitudes, &links, snr_threshold);
+        for path in &config.map_paths {
+            map.write(path).map_err(map_error)?;
+            eprintln!("Network map written to: {}", path.display());
+        }
+    }
 
     Ok(())
 }
 
+/// Error of writing a network map.
+fn map_error(e: LinkPredictionError) -> BuildModelError {
+    match e {
+        LinkPredictionError::ConfigError(msg) => BuildModelError::ConfigError(msg),
+        other => BuildModelError::ConfigError(other.to_string()),
+    }
+}
+
+/// Map of the nodes and the links kept in the model.
+fn network_map(
+    nodes: &[ProcessedNode],
+    altitudes: &[Option<f64>],
+    links: &[LinkData],
+    snr_threshold: f64,
+) -> NetworkMap {
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (n.name.as_str(), i)).collect();
+    NetworkMap {
+        nodes: nodes
+            .iter()
+            .zip(altitudes)
+            .map(|(n, altitude)| MapNode { name: n.name.clone(), lat: n.lat, lon: n.lon, altitude_m: *altitude })
+            .collect(),
+        links: links
+            .iter()
+            .filter_map(|l| {
+                Some(MapLink {
+                    from: *index.get(l.from.as_str())?,
+                    to: *index.get(l.to.as_str())?,
+                    snr_db: l.mean_snr_db,
+                    link_margin_db: l.mean_snr_db - snr_threshold,
+                    status: l.status,
+                })
+            })
+            .collect(),
+    }
+}
+
 /// Process raw nodes into filtered, processed nodes.
 /// 
 /// When multiple nodes have the same name, only the most recently seen node
@@ -637,12 +758,34 @@ fn extract_zero_hop_data(
     zero_hop_map
 }
 
+/// Link prediction cache file for the configured elevation source, in the
+/// elevation cache directory.
+fn link_cache_path(config: &BuildModelConfig) -> std::path::PathBuf {
+    let source = match config.elevation_source.as_str() {
+        "aws" => format!("aws_z{}", config.zoom),
+        other => other.to_string(),
+    };
+    config.elevation_cache.join(format!("{}_{}", source, LinkCache::FILE_NAME))
+}
+
+/// Terrain, ITM instance and prediction cache used to predict links.
+struct LinkPredictor<'a> {
+    elevation: &'a ElevationSource,
+    itm: &'a Itm,
+    cache: &'a LinkCache,
+}
+
+impl LinkPredictor<'_> {
+    fn predict(&self, config: &LinkPredictionConfig) -> Result<LinkPrediction, LinkPredictionError> {
+        self.cache.predict(self.elevation, self.itm, config, &LinkPredictionParams::default())
+    }
+}
+
 /// Evaluate a potential link between two nodes.
 fn evaluate_link(
     from_node: &ProcessedNode,
     to_node: &ProcessedNode,
-    elevation: &ElevationSource,
-    itm: &mcsim_itm::Itm,
+    predictor: &LinkPredictor,
     zero_hop_data: &HashMap<String, ZeroHopData>,
     zero_hop_key: &str,
     config: &BuildModelConfig,
@@ -656,13 +799,15 @@ fn evaluate_link(
         to_lon: to_node.lon,
         from_height: config.antenna_height,
         to_height: config.antenna_height,
+        height_mode: mcsim_link::AntennaHeightMode::Agl,
         freq_mhz: 910.525,
         tx_power_dbm: config.tx_power_dbm,
         spreading_factor: config.spreading_factor,
         terrain_samples: config.terrain_samples,
+        ..Default::default()
     };
 
-    let prediction = match predict_link_with_elevation(elevation, itm, &pred_config) {
+    let prediction = match predictor.predict(&pred_config) {
         Ok(p) => p,
         Err(e) => {
             if config.verbose {
@@ -687,6 +832,7 @@ fn evaluate_link(
                     return Ok(Some(LinkData {
                         from: from_node.name.clone(),
                         to: to_node.name.clone(),
+                        status: LinkPredictionParams::default().classify_link(est_result.mean_snr - snr_threshold),
                         mean_snr_db: est_result.mean_snr,
                         snr_std_dev: est_result.std_dev,
                         source: LinkSource::Estimation,
@@ -694,6 +840,7 @@ fn evaluate_link(
                         distance_km: prediction.path.distance_km,
                         terrain_delta_h_m: prediction.terrain.delta_h,
                         prediction_method: Some(prediction.prediction_method),
+                        rician_k_factor_db: prediction.rician_k_factor_db(),
                     }));
                 }
                 Err(e) => {
@@ -715,6 +862,7 @@ fn evaluate_link(
     Ok(Some(LinkData {
         from: from_node.name.clone(),
         to: to_node.name.clone(),
+        status: LinkPredictionParams::default().classify_link(prediction.snr_db - snr_threshold),
         mean_snr_db: prediction.snr_db,
         snr_std_dev: prediction.snr_std_dev_db,
         source: LinkSource::Prediction,
@@ -722,6 +870,7 @@ fn evaluate_link(
         distance_km: prediction.path.distance_km,
         terrain_delta_h_m: prediction.terrain.delta_h,
         prediction_method: Some(prediction.prediction_method),
+        rician_k_factor_db: prediction.rician_k_factor_db(),
     }))
 }
 
@@ -764,6 +913,7 @@ fn escape_yaml_string(s: &str) -> String {
 /// Generate the YAML output.
 fn generate_yaml(
     nodes: &[ProcessedNode],
+    altitudes: &[Option<f64>],
     links: &[LinkData],
     config: &BuildModelConfig,
 ) -> Result<(), BuildModelError> {
@@ -782,7 +932,7 @@ fn generate_yaml(
 
     // Write nodes
     writeln!(output, "nodes:")?;
-    for node in nodes {
+    for (node, altitude_m) in nodes.iter().zip(altitudes) {
         // Generate the public key spec using configurable prefix length
         let prefix_len = config.public_key_prefix_len.min(node.public_key.len());
         let pub_key_prefix = &node.public_key[..prefix_len];
@@ -791,6 +941,9 @@ fn generate_yaml(
         writeln!(output, "    location:")?;
         writeln!(output, "      lat: {}", node.lat)?;
         writeln!(output, "      lon: {}", node.lon)?;
+        if let Some(altitude_m) = altitude_m {
+            writeln!(output, "      alt: {:.1}", altitude_m)?;
+        }
         writeln!(output, "    keys:")?;
         writeln!(output, "      private_key: \"*\"")?;
         writeln!(output, "      public_key: \"{}*\"", pub_key_prefix)?;
@@ -810,10 +963,18 @@ fn generate_yaml(
                 writeln!(output, "      type: Repeater")?;
             }
         }
+        if config.tx_power_dbm != REFERENCE_TX_POWER_DBM {
+            writeln!(output, "    radio:")?;
+            writeln!(output, "      tx_power_dbm: {}", config.tx_power_dbm)?;
+        }
         writeln!(output)?;
     }
 
     // Write edges
+    // Edge SNR values are written at the reference TX power; the nodes' own
+    // TX power is applied per direction at runtime.
+    let tx_power_offset_db = config.tx_power_dbm as f64 - REFERENCE_TX_POWER_DBM as f64;
+
     writeln!(output, "edges:")?;
     for link in links {
         // Write a comment about the source with distance and terrain info
@@ -841,8 +1002,18 @@ fn generate_yaml(
         
         writeln!(output, "  - from: {}", escape_yaml_string(&link.from))?;
         writeln!(output, "    to: {}", escape_yaml_string(&link.to))?;
-        writeln!(output, "    mean_snr_db_at20dbm: {:.1}", link.mean_snr_db)?;
+        writeln!(output, "    mean_snr_db_at20dbm: {:.1}", link.mean_snr_db - tx_power_offset_db)?;
         writeln!(output, "    snr_std_dev: {:.1}", link.snr_std_dev)?;
+        // Observed SNR spread already includes fading
+        if config.fading && link.source == LinkSource::Prediction {
+            match link.rician_k_factor_db {
+                Some(k_db) => {
+                    writeln!(output, "    fading: rician")?;
+                    writeln!(output, "    rician_k_db: {:.1}", k_db)?;
+                }
+                None => writeln!(output, "    fading: rayleigh")?,
+            }
+        }
         
         // If this link was estimated, include the predicted value as a comment
         if link.source == LinkSource::Estimation {
@@ -0,0 +1,627 @@
+//! Prediction-vs-simulation link calibration.
+//!
+//! Each edge of the model carries a predicted SNR distribution: a gaussian with
+//! the edge's `link/mean_snr_db_at20dbm` (shifted by the sender's TX power) and
+//! `link/snr_std_dev`. This module records what the radios actually saw on each
+//! directed link during a run and compares the two, so a scenario whose runtime
+//! behavior departs from the predictor's assumptions is caught instead of
+//! silently skewing results.
+//!
+//! For every link the report gives:
+//!
+//! - the predicted and observed SNR mean and standard deviation, from every
+//!   packet that reached the receiver while it was listening;
+//! - the predicted decode probability, `P(SNR >= threshold)` for the
+//!   spreading factor in use, next to the observed decode ratio of packets that
+//!   didn't collide;
+//! - the observed delivery ratio over all transmissions, which also counts
+//!   packets lost to collisions and to the receiver being busy transmitting.
+//!
+//! A link is flagged as drifting when it has enough samples and its SNR bias,
+//! spread ratio, or delivery shortfall exceeds the [`CalibrationTolerances`].
+//!
+//! Each link also gets a spreading factor recommendation: the lowest SF whose
+//! demodulation threshold the predicted mean SNR clears by the target margin,
+//! with the airtime of the sender's average packet at that SF. Counting the
+//! links each SF would close (see [`CalibrationReport::links_closed_at`])
+//! gives planners evidence for a network-wide setting.
+//!
+//! With the `planning` feature, [`CalibrationTracker::rx_logs`] also hands the
+//! per-link receive records to `mcsim_link`, which estimates each link's SNR
+//! distribution the way a deployed network would from its own logs (see
+//! [`EventLoop::snr_estimates`](crate::EventLoop::snr_estimates)). A packet
+//! counts as lost when it arrived below the threshold; collided packets are
+//! neither received nor lost, since their SNR says nothing about the link.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use mcsim_common::{EntityId, RadioParams, RadioRxPacketEvent};
+use mcsim_lora::{calculate_snr_sensitivity, calculate_time_on_air, default_radio_params, LinkModel};
+use serde::Serialize;
+
+/// Default link margin in dB a recommended spreading factor must leave.
+pub const DEFAULT_SF_MARGIN_DB: f64 = 5.0;
+
+/// Payload length airtime is quoted for when the sender never transmitted.
+const REFERENCE_PAYLOAD_LEN: usize = 40;
+
+/// Limits beyond which a link is reported as drifting from its prediction.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CalibrationTolerances {
+    /// Minimum number of received packets before a link is judged.
+    pub min_samples: u64,
+    /// Maximum absolute difference between observed and predicted mean SNR, in dB.
+    pub max_bias_db: f64,
+    /// Maximum ratio between observed and predicted SNR standard deviation
+    /// (either way round).
+    pub max_std_ratio: f64,
+    /// Maximum amount by which the observed delivery ratio may fall below the
+    /// predicted decode probability.
+    pub max_delivery_shortfall: f64,
+    /// Margin in dB above the demodulation threshold that the predicted mean
+    /// SNR must leave at a recommended spreading factor.
+    pub sf_margin_db: f64,
+}
+
+impl Default for CalibrationTolerances {
+    fn default() -> Self {
+        Self {
+            min_samples: 30,
+            max_bias_db: 1.0,
+            max_std_ratio: 1.5,
+            max_delivery_shortfall: 0.2,
+            sf_margin_db: DEFAULT_SF_MARGIN_DB,
+        }
+    }
+}
+
+/// Why a link was flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftReason {
+    /// Observed mean SNR is off the prediction by more than `max_bias_db`.
+    SnrBias,
+    /// Observed SNR spread doesn't match the predicted standard deviation.
+    SnrSpread,
+    /// Far fewer packets were delivered than the predictor expects.
+    DeliveryShortfall,
+}
+
+impl fmt::Display for DriftReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriftReason::SnrBias => write!(f, "SNR bias"),
+            DriftReason::SnrSpread => write!(f, "SNR spread"),
+            DriftReason::DeliveryShortfall => write!(f, "delivery shortfall"),
+        }
+    }
+}
+
+/// Predicted and observed behavior of one directed link.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkCalibration {
+    /// Transmitting node name.
+    pub from: String,
+    /// Receiving node name.
+    pub to: String,
+    /// Packets transmitted by the sender.
+    pub transmissions: u64,
+    /// Packets that reached the receiver while it was listening.
+    pub samples: u64,
+    /// Predicted mean SNR at the sender's TX power, in dB.
+    pub predicted_mean_snr_db: f64,
+    /// Predicted SNR standard deviation, in dB.
+    pub predicted_std_dev_db: f64,
+    /// Observed mean SNR, in dB.
+    pub observed_mean_snr_db: Option<f64>,
+    /// Observed SNR standard deviation, in dB.
+    pub observed_std_dev_db: Option<f64>,
+    /// Predicted probability that a packet clears the demodulation threshold.
+    pub predicted_decode_probability: f64,
+    /// Fraction of non-collided samples that cleared the threshold.
+    pub observed_decode_ratio: Option<f64>,
+    /// Fraction of samples lost to collisions.
+    pub collision_ratio: Option<f64>,
+    /// Fraction of transmissions delivered intact to the receiver.
+    pub delivery_ratio: Option<f64>,
+    /// Tolerances exceeded by this link; empty if it matches its prediction
+    /// or has too few samples to judge.
+    pub drift: Vec<DriftReason>,
+    /// Lowest spreading factor that leaves the target margin, or `None` if
+    /// even SF12 doesn't.
+    pub recommended_sf: Option<SfRecommendation>,
+}
+
+/// Lowest spreading factor that closes a link with the target margin.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SfRecommendation {
+    /// Recommended spreading factor.
+    pub spreading_factor: u8,
+    /// Predicted mean SNR above that SF's demodulation threshold, in dB.
+    pub margin_db: f64,
+    /// Airtime in milliseconds of the sender's average packet at that SF.
+    pub airtime_ms: f64,
+    /// That airtime relative to the sender's average packet at the spreading
+    /// factor it used (below 1 when a faster SF would do).
+    pub airtime_ratio: f64,
+}
+
+impl LinkCalibration {
+    /// Observed minus predicted mean SNR, in dB.
+    pub fn bias_db(&self) -> Option<f64> {
+        self.observed_mean_snr_db.map(|m| m - self.predicted_mean_snr_db)
+    }
+
+    /// Whether any tolerance was exceeded.
+    pub fn is_drifting(&self) -> bool {
+        !self.drift.is_empty()
+    }
+}
+
+/// Result of [`CalibrationTracker::report`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalibrationReport {
+    /// Tolerances the links were judged against.
+    pub tolerances: CalibrationTolerances,
+    /// Every link of the model, ordered by sender then receiver.
+    pub links: Vec<LinkCalibration>,
+}
+
+impl CalibrationReport {
+    /// Links that exceeded a tolerance.
+    pub fn drifting(&self) -> impl Iterator<Item = &LinkCalibration> {
+        self.links.iter().filter(|l| l.is_drifting())
+    }
+
+    /// Number of links a network-wide spreading factor would close with the
+    /// target margin (links whose recommended SF is at most `spreading_factor`).
+    pub fn links_closed_at(&self, spreading_factor: u8) -> usize {
+        self.links
+            .iter()
+            .filter(|l| l.recommended_sf.is_some_and(|r| r.spreading_factor <= spreading_factor))
+            .count()
+    }
+}
+
+fn fmt_opt(value: Option<f64>, precision: usize) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.*}", precision, v))
+}
+
+impl fmt::Display for CalibrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<16} {:<16} {:>6} {:>15} {:>15} {:>11} {:>6} {:>14}  drift",
+            "from", "to", "n", "pred snr/std", "obs snr/std", "pred/obs pd", "pdr", "sf (airtime)"
+        )?;
+        for link in &self.links {
+            let drift: Vec<String> = link.drift.iter().map(|r| r.to_string()).collect();
+            let sf = link.recommended_sf.map_or_else(
+                || "-".to_string(),
+                |r| format!("SF{} ({:.2}x)", r.spreading_factor, r.airtime_ratio),
+            );
+            writeln!(
+                f,
+                "{:<16} {:<16} {:>6} {:>8.1}/{:<6.1} {:>8}/{:<6} {:>5.2}/{:<5} {:>6} {:>14}  {}",
+                link.from,
+                link.to,
+                link.samples,
+                link.predicted_mean_snr_db,
+                link.predicted_std_dev_db,
+                fmt_opt(link.observed_mean_snr_db, 1),
+                fmt_opt(link.observed_std_dev_db, 1),
+                link.predicted_decode_probability,
+                fmt_opt(link.observed_decode_ratio, 2),
+                fmt_opt(link.delivery_ratio, 2),
+                sf,
+                drift.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Transmitter settings of a radio, as last seen on air.
+#[derive(Debug, Clone)]
+struct TxInfo {
+    transmissions: u64,
+    params: RadioParams,
+    payload_bytes: u64,
+}
+
+impl TxInfo {
+    /// Average payload length of the radio's packets.
+    fn mean_payload_len(&self) -> usize {
+        (self.payload_bytes as f64 / self.transmissions.max(1) as f64).round() as usize
+    }
+}
+
+/// Observations of one directed link.
+#[derive(Debug, Clone, Default)]
+struct LinkSamples {
+    count: u64,
+    snr_sum: f64,
+    snr_sum_sq: f64,
+    collided: u64,
+    /// Non-collided samples that cleared the threshold.
+    decoded: u64,
+    /// Samples delivered intact (not collided, corrupted or weak).
+    delivered: u64,
+    /// (end time in seconds, SNR, packets lost since the previous one) of
+    /// each delivered sample.
+    received: Vec<(f64, f64, u32)>,
+    /// Samples lost below the threshold since the last delivered one.
+    lost_since_received: u32,
+}
+
+/// Records per-link SNR observations during a run.
+#[derive(Debug, Default)]
+pub struct CalibrationTracker {
+    transmitters: HashMap<u64, TxInfo>,
+    /// Samples keyed by (sender radio ID, receiver radio ID).
+    links: HashMap<(u64, u64), LinkSamples>,
+}
+
+impl CalibrationTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a transmission of `payload_len` bytes by a radio.
+    pub fn track_transmit(&mut self, radio_id: u64, params: &RadioParams, payload_len: usize) {
+        let info = self.transmitters.entry(radio_id).or_insert(TxInfo {
+            transmissions: 0,
+            params: params.clone(),
+            payload_bytes: 0,
+        });
+        info.transmissions += 1;
+        info.params = params.clone();
+        info.payload_bytes += payload_len as u64;
+    }
+
+    /// Record a reception outcome at a radio.
+    pub fn track_reception(&mut self, rx_radio_id: u64, rx: &RadioRxPacketEvent) {
+        let link = self
+            .links
+            .entry((rx.source_radio_id.0, rx_radio_id))
+            .or_default();
+        link.count += 1;
+        link.snr_sum += rx.snr_db;
+        link.snr_sum_sq += rx.snr_db * rx.snr_db;
+        if rx.was_collided {
+            link.collided += 1;
+        } else {
+            // Corrupted packets fell below the threshold but were delivered
+            // with bit errors instead of dropped
+            if !rx.was_weak_signal && !rx.was_corrupted {
+                link.decoded += 1;
+                link.delivered += 1;
+                link.received
+                    .push((rx.end_time.as_secs_f64(), rx.snr_db, link.lost_since_received));
+                link.lost_since_received = 0;
+            } else {
+                link.lost_since_received += 1;
+            }
+        }
+    }
+
+    /// Receive logs of every link of the model that carried traffic, for
+    /// [`mcsim_link::estimate_link_snrs`], ordered by sender then receiver.
+    ///
+    /// `names` maps radio entity IDs to node names; links whose radios aren't
+    /// named are left out.
+    #[cfg(feature = "planning")]
+    pub fn rx_logs(&self, link_model: &LinkModel, names: &HashMap<u64, String>) -> Vec<mcsim_link::LinkRxLog> {
+        let mut by_name: BTreeMap<(&str, &str), mcsim_link::LinkRxLog> = BTreeMap::new();
+        for (&(from_id, to_id), samples) in &self.links {
+            let (Some(from), Some(to)) = (names.get(&from_id), names.get(&to_id)) else {
+                continue;
+            };
+            let Some(params) = link_model.get_link(EntityId::new(from_id), EntityId::new(to_id)) else {
+                continue;
+            };
+            let tx = self.transmitters.get(&from_id).map(|t| &t.params);
+            let log = mcsim_link::LinkRxLog {
+                from: from.clone(),
+                to: to.clone(),
+                tx_power_dbm: tx.map_or(20.0, |p| p.tx_power_dbm as f64),
+                threshold_db: calculate_snr_sensitivity(tx.map_or(7, |p| p.spreading_factor)),
+                records: samples
+                    .received
+                    .iter()
+                    .map(|&(time_s, snr_db, lost_before)| mcsim_link::LinkRxRecord { time_s, snr_db, lost_before })
+                    .collect(),
+                lost_after: samples.lost_since_received,
+                configured: Some(mcsim_link::ConfiguredLink {
+                    mean_snr_db_at20dbm: params.mean_snr_db_at20dbm,
+                    snr_std_dev: params.snr_std_dev,
+                }),
+            };
+            by_name.insert((from.as_str(), to.as_str()), log);
+        }
+        by_name.into_values().collect()
+    }
+
+    /// Compare the observations against the link model's predictions.
+    ///
+    /// `names` maps radio entity IDs to node names; links whose radios aren't
+    /// named are left out.
+    pub fn report(
+        &self,
+        link_model: &LinkModel,
+        names: &HashMap<u64, String>,
+        tolerances: CalibrationTolerances,
+    ) -> CalibrationReport {
+        // Order by name for a stable report
+        let mut by_name: BTreeMap<(&str, &str), LinkCalibration> = BTreeMap::new();
+        for (&from_id, from) in names {
+            for (&to_id, to) in names {
+                let Some(params) = link_model.get_link(EntityId::new(from_id), EntityId::new(to_id)) else {
+                    continue;
+                };
+                let tx = self.transmitters.get(&from_id);
+                let samples = self.links.get(&(from_id, to_id)).cloned().unwrap_or_default();
+                let link = calibrate_link(
+                    from,
+                    to,
+                    params.mean_snr_db_at20dbm,
+                    params.snr_std_dev,
+                    tx,
+                    &samples,
+                    &tolerances,
+                );
+                by_name.insert((from.as_str(), to.as_str()), link);
+            }
+        }
+        CalibrationReport {
+            tolerances,
+            links: by_name.into_values().collect(),
+        }
+    }
+}
+
+fn calibrate_link(
+    from: &str,
+    to: &str,
+    mean_snr_db_at20dbm: f64,
+    snr_std_dev: f64,
+    tx: Option<&TxInfo>,
+    samples: &LinkSamples,
+    tolerances: &CalibrationTolerances,
+) -> LinkCalibration {
+    let transmissions = tx.map_or(0, |t| t.transmissions);
+    let tx_offset_db = tx.map_or(0.0, |t| t.params.tx_power_dbm as f64 - 20.0);
+    let predicted_mean_snr_db = mean_snr_db_at20dbm + tx_offset_db;
+    let threshold_db = calculate_snr_sensitivity(tx.map_or(7, |t| t.params.spreading_factor));
+    let predicted_decode_probability =
+        exceedance_probability(threshold_db, predicted_mean_snr_db, snr_std_dev);
+
+    let n = samples.count as f64;
+    let (observed_mean_snr_db, observed_std_dev_db) = if samples.count > 0 {
+        let mean = samples.snr_sum / n;
+        let variance = (samples.snr_sum_sq / n - mean * mean).max(0.0);
+        (Some(mean), Some(variance.sqrt()))
+    } else {
+        (None, None)
+    };
+    let survived = samples.count - samples.collided;
+    let observed_decode_ratio = (survived > 0).then(|| samples.decoded as f64 / survived as f64);
+    let collision_ratio = (samples.count > 0).then(|| samples.collided as f64 / n);
+    let delivery_ratio =
+        (transmissions > 0).then(|| (samples.delivered as f64 / transmissions as f64).min(1.0));
+
+    let mut drift = Vec::new();
+    if samples.count >= tolerances.min_samples {
+        if let Some(mean) = observed_mean_snr_db {
+            if (mean - predicted_mean_snr_db).abs() > tolerances.max_bias_db {
+                drift.push(DriftReason::SnrBias);
+            }
+        }
+        if let Some(std) = observed_std_dev_db {
+            let spread_ok = if snr_std_dev > 0.0 {
+                let ratio = std / snr_std_dev;
+                ratio <= tolerances.max_std_ratio && ratio >= 1.0 / tolerances.max_std_ratio
+            } else {
+                std <= tolerances.max_bias_db
+            };
+            if !spread_ok {
+                drift.push(DriftReason::SnrSpread);
+            }
+        }
+        if delivery_ratio
+            .is_some_and(|pdr| predicted_decode_probability - pdr > tolerances.max_delivery_shortfall)
+        {
+            drift.push(DriftReason::DeliveryShortfall);
+        }
+    }
+
+    LinkCalibration {
+        from: from.to_string(),
+        to: to.to_string(),
+        transmissions,
+        samples: samples.count,
+        predicted_mean_snr_db,
+        predicted_std_dev_db: snr_std_dev,
+        observed_mean_snr_db,
+        observed_std_dev_db,
+        predicted_decode_probability,
+        observed_decode_ratio,
+        collision_ratio,
+        delivery_ratio,
+        drift,
+        recommended_sf: recommend_sf(predicted_mean_snr_db, tx, tolerances.sf_margin_db),
+    }
+}
+
+/// Lowest spreading factor at which `mean_snr_db` leaves `margin_db` above
+/// the demodulation threshold.
+fn recommend_sf(mean_snr_db: f64, tx: Option<&TxInfo>, margin_db: f64) -> Option<SfRecommendation> {
+    let (params, payload_len) = match tx {
+        Some(tx) => (tx.params.clone(), tx.mean_payload_len()),
+        None => (default_radio_params(), REFERENCE_PAYLOAD_LEN),
+    };
+    let spreading_factor = (7..=12).find(|&sf| mean_snr_db - calculate_snr_sensitivity(sf) >= margin_db)?;
+    let airtime_ms = |spreading_factor| {
+        let params = RadioParams { spreading_factor, ..params.clone() };
+        calculate_time_on_air(&params, payload_len).as_secs_f64() * 1000.0
+    };
+    let airtime = airtime_ms(spreading_factor);
+    Some(SfRecommendation {
+        spreading_factor,
+        margin_db: mean_snr_db - calculate_snr_sensitivity(spreading_factor),
+        airtime_ms: airtime,
+        airtime_ratio: airtime / airtime_ms(params.spreading_factor),
+    })
+}
+
+/// Probability that a gaussian `N(mean, std_dev)` sample is at least `threshold`.
+fn exceedance_probability(threshold: f64, mean: f64, std_dev: f64) -> f64 {
+    if std_dev <= 0.0 {
+        return if mean >= threshold { 1.0 } else { 0.0 };
+    }
+    0.5 * erfc((threshold - mean) / (std_dev * std::f64::consts::SQRT_2))
+}
+
+/// Complementary error function (Numerical Recipes `erfcc`, |error| < 1.2e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t * (-z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77)))))))))
+        .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{LoraPacket, SimTime};
+
+    fn rx(source: u64, snr_db: f64, was_collided: bool) -> RadioRxPacketEvent {
+        let threshold = calculate_snr_sensitivity(7);
+        RadioRxPacketEvent {
+            packet: LoraPacket::new(vec![0u8; 4]),
+            source_radio_id: EntityId::new(source),
+            snr_db,
+            rssi_dbm: -100.0,
+            was_collided,
+            was_weak_signal: !was_collided && snr_db < threshold,
+            was_corrupted: false,
+            start_time: SimTime::ZERO,
+            end_time: SimTime::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn test_exceedance_probability() {
+        assert!((exceedance_probability(0.0, 0.0, 2.0) - 0.5).abs() < 1e-6);
+        // One standard deviation above the threshold
+        assert!((exceedance_probability(-7.5, -5.5, 2.0) - 0.841_345).abs() < 1e-5);
+        assert_eq!(exceedance_probability(-7.5, -8.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_report_flags_drifting_links() {
+        let mut link_model = LinkModel::new();
+        link_model.add_link(EntityId::new(1), EntityId::new(2), 5.0, 1.0, -100.0);
+        link_model.add_link(EntityId::new(2), EntityId::new(1), 5.0, 1.0, -100.0);
+        let names: HashMap<u64, String> =
+            [(1, "A".to_string()), (2, "B".to_string())].into_iter().collect();
+
+        let mut tracker = CalibrationTracker::new();
+        for i in 0..40 {
+            // A → B matches its prediction
+            tracker.track_transmit(1, &default_radio_params(), 40);
+            let jitter = if i % 2 == 0 { 1.0 } else { -1.0 };
+            tracker.track_reception(2, &rx(1, 5.0 + jitter, false));
+
+            // B → A is 4 dB weaker than predicted and half its packets collide
+            tracker.track_transmit(2, &default_radio_params(), 40);
+            tracker.track_reception(1, &rx(2, 1.0 + jitter, i % 2 == 0));
+        }
+
+        let report = tracker.report(&link_model, &names, CalibrationTolerances::default());
+        assert_eq!(report.links.len(), 2);
+
+        let ab = &report.links[0];
+        assert_eq!((ab.from.as_str(), ab.to.as_str()), ("A", "B"));
+        assert_eq!(ab.samples, 40);
+        assert!(ab.drift.is_empty(), "unexpected drift: {:?}", ab.drift);
+        assert_eq!(ab.delivery_ratio, Some(1.0));
+        assert!(ab.predicted_decode_probability > 0.999);
+
+        let ba = &report.links[1];
+        assert!((ba.bias_db().unwrap() + 4.0).abs() < 1e-9);
+        assert_eq!(ba.collision_ratio, Some(0.5));
+        assert_eq!(ba.drift, vec![DriftReason::SnrBias, DriftReason::DeliveryShortfall]);
+        assert_eq!(report.drifting().count(), 1);
+        assert!(report.to_string().contains("SNR bias, delivery shortfall"));
+    }
+
+    #[cfg(feature = "planning")]
+    #[test]
+    fn test_rx_logs_count_lost_packets() {
+        let mut link_model = LinkModel::new();
+        link_model.add_link(EntityId::new(1), EntityId::new(2), -8.0, 2.0, -100.0);
+        let names: HashMap<u64, String> =
+            [(1, "A".to_string()), (2, "B".to_string())].into_iter().collect();
+
+        let mut tracker = CalibrationTracker::new();
+        tracker.track_transmit(1, &default_radio_params(), 40);
+        for (snr_db, collided) in [(-10.0, false), (-9.0, true), (-10.0, false), (-6.0, false), (-12.0, false)] {
+            tracker.track_reception(2, &rx(1, snr_db, collided));
+        }
+
+        let logs = tracker.rx_logs(&link_model, &names);
+        assert_eq!(logs.len(), 1);
+        let log = &logs[0];
+        assert_eq!((log.from.as_str(), log.to.as_str()), ("A", "B"));
+        // Two weak packets before the one at -6 dB, the collision not counted
+        let records: Vec<(f64, u32)> = log.records.iter().map(|r| (r.snr_db, r.lost_before)).collect();
+        assert_eq!(records, vec![(-6.0, 2)]);
+        assert_eq!(log.lost_after, 1);
+        assert_eq!(log.configured.unwrap().mean_snr_db_at20dbm, -8.0);
+    }
+
+    #[test]
+    fn test_sf_recommendation() {
+        let mut tx = TxInfo { transmissions: 0, params: default_radio_params(), payload_bytes: 0 };
+        tx.params.spreading_factor = 10;
+        tx.transmissions = 2;
+        tx.payload_bytes = 80;
+
+        // -4 dB clears SF9's -12.5 dB threshold by 8.5 dB, SF8's by only 6
+        let sf = recommend_sf(-4.0, Some(&tx), 7.0).unwrap();
+        assert_eq!(sf.spreading_factor, 9);
+        assert!((sf.margin_db - 8.5).abs() < 1e-9);
+        assert!(sf.airtime_ratio > 0.4 && sf.airtime_ratio < 0.6, "{}", sf.airtime_ratio);
+        let at_sf10 = calculate_time_on_air(&tx.params, 40).as_secs_f64() * 1000.0;
+        assert!((sf.airtime_ms / at_sf10 - sf.airtime_ratio).abs() < 1e-9);
+
+        // Strong links stay at SF7; links SF12 can't close get none
+        assert_eq!(recommend_sf(10.0, None, 5.0).unwrap().spreading_factor, 7);
+        assert!(recommend_sf(-17.0, Some(&tx), 5.0).is_none());
+
+        let mut link_model = LinkModel::new();
+        link_model.add_link(EntityId::new(1), EntityId::new(2), 5.0, 1.0, -100.0);
+        link_model.add_link(EntityId::new(2), EntityId::new(1), -10.0, 1.0, -100.0);
+        let names: HashMap<u64, String> =
+            [(1, "A".to_string()), (2, "B".to_string())].into_iter().collect();
+        let report = CalibrationTracker::new().report(&link_model, &names, CalibrationTolerances::default());
+        let sfs: Vec<u8> = report.links.iter().map(|l| l.recommended_sf.unwrap().spreading_factor).collect();
+        assert_eq!(sfs, vec![7, 10]);
+        assert_eq!((report.links_closed_at(7), report.links_closed_at(9), report.links_closed_at(10)), (1, 1, 2));
+    }
+}
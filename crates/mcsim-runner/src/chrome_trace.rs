@@ -0,0 +1,311 @@
+//! Chrome trace-event / Perfetto export of the event loop's timing.
+//!
+//! With `--perfetto FILE`, the runner writes a JSON trace that opens in
+//! [Perfetto](https://ui.perfetto.dev) or `chrome://tracing`, to see how
+//! the work of the nodes interleaves and where the loop stalls:
+//!
+//! ```text
+//! mcsim run model.yaml --duration 10m --perfetto trace.json
+//! ```
+//!
+//! The time axis is wall-clock time since the run started. Each node is a
+//! process with one track per entity (firmware, radio, agent, CLI agent),
+//! and an `event loop` process holds the dispatch of each event:
+//!
+//! - `dispatch` spans: one per event on the event loop, covering every
+//!   target's handling;
+//! - `firmware` and `entity` spans: one per event handled by a firmware
+//!   step or another entity, on that entity's track;
+//! - `radio` spans: from a transmission to the first event at or after its
+//!   end in simulation time, and instant markers for received packets.
+//!
+//! Every span carries the simulation time of its event in its arguments.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use mcsim_common::{EntityId, Event, EventPayload};
+use mcsim_model::NodeInfo;
+use serde_json::{json, Value};
+
+use crate::watchdog::describe_event_payload;
+use crate::SimTime;
+
+/// Process of the event loop's own track.
+const LOOP_PID: u32 = 0;
+
+/// Track IDs of a node's entities.
+const TID_FIRMWARE: u32 = 1;
+const TID_RADIO: u32 = 2;
+const TID_AGENT: u32 = 3;
+const TID_CLI_AGENT: u32 = 4;
+
+/// A transmission whose airtime hasn't ended yet.
+struct OpenTransmission {
+    pid: u32,
+    end_time: SimTime,
+    start_us: f64,
+    args: Value,
+}
+
+/// Streams trace events to a Chrome trace-event JSON array.
+pub struct ChromeTrace {
+    output: Box<dyn Write>,
+    started: Instant,
+    /// (process, track, is firmware) of each node entity.
+    tracks: HashMap<u64, (u32, u32, bool)>,
+    transmissions: Vec<OpenTransmission>,
+    events: u64,
+    /// First write error, reported by [`finish`](Self::finish).
+    error: Option<io::Error>,
+}
+
+impl ChromeTrace {
+    /// Start a trace of `nodes`, writing the process and track names.
+    pub fn new(output: Box<dyn Write>, nodes: &[NodeInfo]) -> io::Result<Self> {
+        let mut trace = Self {
+            output,
+            started: Instant::now(),
+            tracks: HashMap::new(),
+            transmissions: Vec::new(),
+            events: 0,
+            error: None,
+        };
+        trace.output.write_all(b"[\n")?;
+        trace.metadata("process_name", LOOP_PID, 0, "event loop");
+        trace.metadata("thread_name", LOOP_PID, 0, "dispatch");
+        for (index, node) in nodes.iter().enumerate() {
+            let pid = index as u32 + 1;
+            trace.metadata("process_name", pid, 0, &node.name);
+            let entities = [
+                (Some(node.firmware_entity_id), TID_FIRMWARE, "firmware"),
+                (Some(node.radio_entity_id), TID_RADIO, "radio"),
+                (node.agent_entity_id, TID_AGENT, "agent"),
+                (node.cli_agent_entity_id, TID_CLI_AGENT, "cli agent"),
+            ];
+            for (entity, tid, name) in entities {
+                if let Some(entity) = entity {
+                    trace.tracks.insert(entity, (pid, tid, tid == TID_FIRMWARE));
+                    trace.metadata("thread_name", pid, tid, name);
+                }
+            }
+        }
+        match trace.error.take() {
+            Some(e) => Err(e),
+            None => Ok(trace),
+        }
+    }
+
+    /// Number of trace events written.
+    pub fn events(&self) -> u64 {
+        self.events
+    }
+
+    /// Record one target's handling of `event`, which started at `start`
+    /// and took `elapsed`.
+    pub(crate) fn record_handling(&mut self, event: &Event, target: EntityId, start: Instant, elapsed: Duration) {
+        let (pid, tid, category) = match self.tracks.get(&target.0) {
+            Some(&(pid, tid, true)) => (pid, tid, "firmware"),
+            Some(&(pid, tid, false)) => (pid, tid, "entity"),
+            // Entities of no node (the radio graph) share a loop track
+            None => (LOOP_PID, 1, "entity"),
+        };
+        let (name, _) = describe_event_payload(&event.payload);
+        let entry = json!({
+            "name": name,
+            "cat": category,
+            "ph": "X",
+            "ts": self.wall_us(start),
+            "dur": elapsed.as_secs_f64() * 1e6,
+            "pid": pid,
+            "tid": tid,
+            "args": { "sim_time_s": event.time.as_secs_f64() },
+        });
+        self.write(&entry);
+    }
+
+    /// Record the dispatch of `event` to all its targets, which started at
+    /// `start` and took `elapsed`, and its effect on the radio tracks.
+    pub(crate) fn record_dispatch(&mut self, event: &Event, start: Instant, elapsed: Duration) {
+        let (name, details) = describe_event_payload(&event.payload);
+        let entry = json!({
+            "name": name,
+            "cat": "dispatch",
+            "ph": "X",
+            "ts": self.wall_us(start),
+            "dur": elapsed.as_secs_f64() * 1e6,
+            "pid": LOOP_PID,
+            "tid": 0,
+            "args": { "sim_time_s": event.time.as_secs_f64(), "details": details },
+        });
+        self.write(&entry);
+
+        // Close the transmissions that have ended in simulation time
+        let now_us = self.wall_us(Instant::now());
+        let (ended, open): (Vec<_>, Vec<_>) =
+            self.transmissions.drain(..).partition(|tx| tx.end_time <= event.time);
+        self.transmissions = open;
+        for tx in ended {
+            self.write(&json!({
+                "name": "TX",
+                "cat": "radio",
+                "ph": "X",
+                "ts": tx.start_us,
+                "dur": now_us - tx.start_us,
+                "pid": tx.pid,
+                "tid": TID_RADIO,
+                "args": tx.args,
+            }));
+        }
+
+        match &event.payload {
+            EventPayload::TransmitAir(tx) => {
+                if let Some(&(pid, _, _)) = self.tracks.get(&tx.radio_id.0) {
+                    self.transmissions.push(OpenTransmission {
+                        pid,
+                        end_time: tx.end_time,
+                        start_us: self.wall_us(start),
+                        args: json!({
+                            "sim_start_s": event.time.as_secs_f64(),
+                            "sim_end_s": tx.end_time.as_secs_f64(),
+                            "bytes": tx.packet.payload.len(),
+                        }),
+                    });
+                }
+            }
+            EventPayload::RadioRxPacket(rx) => {
+                let name = if rx.was_collided {
+                    "RX collided"
+                } else if rx.was_corrupted {
+                    "RX corrupted"
+                } else {
+                    "RX"
+                };
+                for target in &event.targets {
+                    let Some(&(pid, _, _)) = self.tracks.get(&target.0) else {
+                        continue;
+                    };
+                    let entry = json!({
+                        "name": name,
+                        "cat": "radio",
+                        "ph": "i",
+                        "s": "t",
+                        "ts": self.wall_us(start),
+                        "pid": pid,
+                        "tid": TID_RADIO,
+                        "args": { "sim_time_s": event.time.as_secs_f64(), "snr_db": rx.snr_db },
+                    });
+                    self.write(&entry);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Flush buffered output.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+
+    /// End the trace: close the JSON array and report the first write
+    /// error, if any. Traces cut short without this still load.
+    pub fn finish(mut self) -> io::Result<u64> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.output.write_all(b"\n]\n")?;
+        self.output.flush()?;
+        Ok(self.events)
+    }
+
+    fn wall_us(&self, at: Instant) -> f64 {
+        at.saturating_duration_since(self.started).as_secs_f64() * 1e6
+    }
+
+    fn metadata(&mut self, name: &str, pid: u32, tid: u32, value: &str) {
+        self.write(&json!({ "name": name, "ph": "M", "pid": pid, "tid": tid, "args": { "name": value } }));
+    }
+
+    fn write(&mut self, entry: &Value) {
+        if self.error.is_some() {
+            return;
+        }
+        let separator = if self.events == 0 { "" } else { ",\n" };
+        match write!(self.output, "{}{}", separator, entry) {
+            Ok(()) => self.events += 1,
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{EventId, LoraPacket, RadioParams, TransmitAirEvent};
+    use std::sync::{Arc, Mutex};
+
+    /// Writer that keeps its bytes readable after being boxed.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn event(time_s: f64, payload: EventPayload) -> Event {
+        Event {
+            id: EventId(0),
+            time: SimTime::from_secs(time_s),
+            source: EntityId::new(1),
+            targets: vec![EntityId::new(1)],
+            payload,
+        }
+    }
+
+    #[test]
+    fn test_trace_is_valid_json() {
+        let buf = SharedBuf::default();
+        let mut trace = ChromeTrace::new(Box::new(buf.clone()), &[NodeInfo::new("Alice", "Companion", 1, 2)]).unwrap();
+
+        let tx = event(
+            1.0,
+            EventPayload::TransmitAir(TransmitAirEvent {
+                radio_id: EntityId::new(2),
+                end_time: SimTime::from_secs(1.5),
+                packet: LoraPacket::from_bytes(vec![0; 10]),
+                params: RadioParams {
+                    frequency_hz: 910_525_000,
+                    bandwidth_hz: 62_500,
+                    spreading_factor: 7,
+                    coding_rate: 5,
+                    tx_power_dbm: 20,
+                },
+                sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+            }),
+        );
+        let start = Instant::now();
+        trace.record_handling(&tx, EntityId::new(1), start, Duration::from_micros(40));
+        trace.record_dispatch(&tx, start, Duration::from_micros(50));
+        let timer = event(2.0, EventPayload::Timer { timer_id: 0 });
+        trace.record_dispatch(&timer, Instant::now(), Duration::from_micros(5));
+        trace.finish().unwrap();
+
+        let bytes = buf.0.lock().unwrap().clone();
+        let entries: Vec<Value> = serde_json::from_slice(&bytes).unwrap();
+        let names: Vec<&str> = entries.iter().filter_map(|e| e["name"].as_str()).collect();
+        assert!(names.contains(&"process_name"));
+        let handled = entries.iter().find(|e| e["cat"] == "firmware").unwrap();
+        assert_eq!((handled["pid"].as_u64(), handled["tid"].as_u64()), (Some(1), Some(1)));
+        let tx = entries.iter().find(|e| e["name"] == "TX").unwrap();
+        assert_eq!(tx["tid"], TID_RADIO);
+        assert_eq!(tx["args"]["sim_end_s"], 1.5);
+    }
+}
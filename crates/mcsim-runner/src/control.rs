@@ -0,0 +1,495 @@
+//! Priority lane for control commands.
+//!
+//! Simulation events wait in the event queue in time order, and a busy run
+//! can have millions of them queued. Commands from whatever is controlling
+//! the run (pause, resume, stop, a status query) must not wait behind them.
+//! A [`ControlHandle`] sends commands on a channel of their own, which the
+//! [`EventLoop`](crate::EventLoop) drains before each event it processes, so
+//! a command is handled within one event's processing time however deep the
+//! queue is.
+//!
+//! ```no_run
+//! # fn example(event_loop: &mut mcsim_runner::EventLoop) {
+//! use std::time::Duration;
+//!
+//! let control = event_loop.control_handle();
+//! std::thread::spawn(move || {
+//!     control.pause();
+//!     if let Some(status) = control.status(Duration::from_secs(1)) {
+//!         println!("{} events queued", status.pending_events);
+//!     }
+//!     control.resume();
+//! });
+//! # }
+//! ```
+//!
+//! While paused the event loop blocks on the lane, still answering status
+//! queries and console commands, until it is resumed or stopped. The stop
+//! flag passed to the run methods is also honored while paused.
+//!
+//! Console commands are [`inspect`](crate::inspect) command lines executed
+//! against the running loop once a console is attached with
+//! [`EventLoop::set_console`](crate::EventLoop::set_console); this is what
+//! `mcsim run --interactive` is built on.
+//!
+//! [`ControlHandle::set_pacing`] changes how fast simulation time advances
+//! against the wall clock (see [`Pacing`]) from the next event on.
+//!
+//! Subscribers receive an [`EventNotice`] for every event processed after
+//! they subscribed; the [`control_server`](crate::control_server) streams
+//! them to external clients.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::time::{Duration, Instant};
+
+use mcsim_common::GeoCoord;
+use serde::Serialize;
+
+use crate::realtime::Pacing;
+use crate::SimTime;
+
+/// How often a paused event loop checks its stop flag.
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A command for a running event loop.
+#[derive(Debug)]
+pub enum ControlCommand {
+    /// Stop processing events until resumed.
+    Pause,
+    /// Continue after a pause.
+    Resume,
+    /// End the run, as if the stop flag had been set.
+    Stop,
+    /// Change how simulation time is paced against the wall clock.
+    Pace(Pacing),
+    /// Report the loop's current state on the given channel.
+    Status(Sender<ControlStatus>),
+    /// Execute a console command line and send its output on the given
+    /// channel.
+    Console(String, Sender<String>),
+    /// Report the state of every node on the given channel.
+    Nodes(Sender<Vec<NodeStatus>>),
+    /// Send a notice of every event processed from now on to the given
+    /// channel, until its receiver is dropped.
+    Subscribe(Sender<EventNotice>),
+    /// Turn metrics recording on or off, for one category (a metric name
+    /// prefix such as `mcsim.radio`) or for all metrics if None. Whether a
+    /// metrics recorder is attached is sent on the given channel.
+    Metrics(Option<String>, bool, Sender<bool>),
+}
+
+/// State of the event loop when a status command was handled.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ControlStatus {
+    /// Current simulation time.
+    pub sim_time: SimTime,
+    /// Events processed so far.
+    pub events_processed: u64,
+    /// Events waiting in the queue.
+    pub pending_events: usize,
+    /// Whether the loop is paused.
+    pub paused: bool,
+    /// How simulation time is paced against the wall clock.
+    pub pacing: Pacing,
+    /// Longest time a command waited on the lane before being handled, in
+    /// microseconds of wall-clock time.
+    pub max_command_latency_us: u64,
+}
+
+/// State of one node when a nodes command was handled.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeStatus {
+    /// Node name.
+    pub name: String,
+    /// Node type (Repeater, Companion, RoomServer).
+    pub node_type: String,
+    /// Current position.
+    pub location: GeoCoord,
+    /// Packets transmitted.
+    pub tx: u64,
+    /// Packets received intact.
+    pub rx: u64,
+    /// Packets that collided on reception.
+    pub collisions: u64,
+}
+
+/// A processed simulation event, as sent to subscribers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventNotice {
+    /// Time of the event.
+    pub sim_time: SimTime,
+    /// Event type name (e.g. `TransmitAir`, `SerialTx`).
+    pub event_type: String,
+    /// Short description of the payload.
+    pub details: String,
+    /// Node that sent the event (or the entity, if not a node's).
+    pub source: String,
+    /// Nodes or entities the event was delivered to.
+    pub targets: Vec<String>,
+    /// Serial data carried by the event, when it is printable text (a
+    /// node's console output or a command sent to it).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// A command stamped with the time it was sent.
+#[derive(Debug)]
+struct Envelope {
+    command: ControlCommand,
+    sent_at: Instant,
+}
+
+/// Sends commands to an event loop. Cheap to clone and safe to move to
+/// another thread.
+#[derive(Debug, Clone)]
+pub struct ControlHandle {
+    sender: Sender<Envelope>,
+}
+
+impl ControlHandle {
+    /// Send a command. Returns `false` if the event loop has been dropped.
+    pub fn send(&self, command: ControlCommand) -> bool {
+        self.sender
+            .send(Envelope { command, sent_at: Instant::now() })
+            .is_ok()
+    }
+
+    /// Pause the run.
+    pub fn pause(&self) -> bool {
+        self.send(ControlCommand::Pause)
+    }
+
+    /// Resume a paused run.
+    pub fn resume(&self) -> bool {
+        self.send(ControlCommand::Resume)
+    }
+
+    /// Stop the run.
+    pub fn stop(&self) -> bool {
+        self.send(ControlCommand::Stop)
+    }
+
+    /// Pace simulation time as `pacing` from the next event on.
+    pub fn set_pacing(&self, pacing: Pacing) -> bool {
+        self.send(ControlCommand::Pace(pacing))
+    }
+
+    /// Query the loop's state, waiting at most `timeout` for the answer.
+    ///
+    /// The loop only answers while it is running or paused; a query sent
+    /// between runs is answered when the next run starts.
+    pub fn status(&self, timeout: Duration) -> Option<ControlStatus> {
+        let (reply, answer) = mpsc::channel();
+        if !self.send(ControlCommand::Status(reply)) {
+            return None;
+        }
+        answer.recv_timeout(timeout).ok()
+    }
+
+    /// Execute a console command line, waiting at most `timeout` for its
+    /// output. Like [`status`](Self::status), only answered while a run is
+    /// in progress.
+    pub fn console(&self, line: &str, timeout: Duration) -> Option<String> {
+        let (reply, answer) = mpsc::channel();
+        if !self.send(ControlCommand::Console(line.to_string(), reply)) {
+            return None;
+        }
+        answer.recv_timeout(timeout).ok()
+    }
+
+    /// Query the state of every node, waiting at most `timeout` for the
+    /// answer. Like [`status`](Self::status), only answered while a run is
+    /// in progress.
+    pub fn nodes(&self, timeout: Duration) -> Option<Vec<NodeStatus>> {
+        let (reply, answer) = mpsc::channel();
+        if !self.send(ControlCommand::Nodes(reply)) {
+            return None;
+        }
+        answer.recv_timeout(timeout).ok()
+    }
+
+    /// Turn metrics recording on or off, for one `category` or for all
+    /// metrics, waiting at most `timeout` for the answer. Returns whether a
+    /// metrics recorder is attached, or None if the loop didn't answer.
+    pub fn set_metrics_recording(&self, category: Option<&str>, enabled: bool, timeout: Duration) -> Option<bool> {
+        let (reply, answer) = mpsc::channel();
+        if !self.send(ControlCommand::Metrics(category.map(str::to_string), enabled, reply)) {
+            return None;
+        }
+        answer.recv_timeout(timeout).ok()
+    }
+
+    /// Receive a notice of every event processed once the loop has handled
+    /// the subscription. Drop the receiver to unsubscribe.
+    pub fn subscribe(&self) -> Receiver<EventNotice> {
+        let (sender, receiver) = mpsc::channel();
+        self.send(ControlCommand::Subscribe(sender));
+        receiver
+    }
+}
+
+/// What the lane needs from the event loop it serves.
+pub(crate) trait LaneTarget {
+    /// The loop's state for status queries; the lane fills in its own
+    /// fields.
+    fn status(&self) -> ControlStatus;
+
+    /// Execute a console command line, returning its output.
+    fn console(&mut self, line: &str) -> String;
+
+    /// The state of every node.
+    fn nodes(&self) -> Vec<NodeStatus>;
+
+    /// Pace simulation time as `pacing` from now on.
+    fn set_pacing(&mut self, pacing: Pacing);
+
+    /// Turn metrics recording on or off, for one category or for all
+    /// metrics. Returns `false` if no metrics recorder is attached.
+    fn set_metrics_recording(&mut self, category: Option<&str>, enabled: bool) -> bool;
+}
+
+/// What the event loop should do after draining the lane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LaneOutcome {
+    /// Carry on processing events.
+    Continue,
+    /// Carry on after a pause; wall-clock pacing should restart.
+    Resumed,
+    /// End the run.
+    Stop,
+}
+
+/// Receiving end of the control lane, owned by the event loop.
+#[derive(Debug)]
+pub(crate) struct ControlLane {
+    receiver: Receiver<Envelope>,
+    /// Kept so new handles can be created at any time.
+    sender: Sender<Envelope>,
+    paused: bool,
+    max_latency: Duration,
+    /// Event subscribers.
+    subscribers: Vec<Sender<EventNotice>>,
+}
+
+impl ControlLane {
+    pub(crate) fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        ControlLane {
+            receiver,
+            sender,
+            paused: false,
+            max_latency: Duration::ZERO,
+            subscribers: Vec::new(),
+        }
+    }
+
+    pub(crate) fn handle(&self) -> ControlHandle {
+        ControlHandle { sender: self.sender.clone() }
+    }
+
+    /// Whether anyone subscribed to event notices.
+    pub(crate) fn has_subscribers(&self) -> bool {
+        !self.subscribers.is_empty()
+    }
+
+    /// Send `notice` to every subscriber, dropping those that hung up.
+    pub(crate) fn publish(&mut self, notice: EventNotice) {
+        self.subscribers.retain(|subscriber| subscriber.send(notice.clone()).is_ok());
+    }
+
+    /// Handle every pending command against `target`, blocking while
+    /// paused.
+    pub(crate) fn service<T: LaneTarget>(&mut self, target: &mut T, stop_flag: Option<&AtomicBool>) -> LaneOutcome {
+        let mut was_paused = false;
+        loop {
+            let envelope = if self.paused {
+                was_paused = true;
+                if stop_flag.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+                    return LaneOutcome::Stop;
+                }
+                match self.receiver.recv_timeout(PAUSE_POLL_INTERVAL) {
+                    Ok(envelope) => envelope,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    // Unreachable while the lane holds a sender
+                    Err(RecvTimeoutError::Disconnected) => return LaneOutcome::Continue,
+                }
+            } else {
+                match self.receiver.try_recv() {
+                    Ok(envelope) => envelope,
+                    Err(TryRecvError::Empty | TryRecvError::Disconnected) => break,
+                }
+            };
+
+            self.max_latency = self.max_latency.max(envelope.sent_at.elapsed());
+            match envelope.command {
+                ControlCommand::Pause => self.paused = true,
+                ControlCommand::Resume => self.paused = false,
+                ControlCommand::Stop => {
+                    self.paused = false;
+                    return LaneOutcome::Stop;
+                }
+                ControlCommand::Pace(pacing) => target.set_pacing(pacing),
+                ControlCommand::Status(reply) => {
+                    let _ = reply.send(ControlStatus {
+                        paused: self.paused,
+                        max_command_latency_us: self.max_latency.as_micros() as u64,
+                        ..target.status()
+                    });
+                }
+                ControlCommand::Console(line, reply) => {
+                    let _ = reply.send(target.console(&line));
+                }
+                ControlCommand::Nodes(reply) => {
+                    let _ = reply.send(target.nodes());
+                }
+                ControlCommand::Subscribe(subscriber) => self.subscribers.push(subscriber),
+                ControlCommand::Metrics(category, enabled, reply) => {
+                    let _ = reply.send(target.set_metrics_recording(category.as_deref(), enabled));
+                }
+            }
+        }
+
+        if was_paused {
+            LaneOutcome::Resumed
+        } else {
+            LaneOutcome::Continue
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Loop stand-in that echoes console lines.
+    #[derive(Default)]
+    struct Target {
+        pacing: Option<Pacing>,
+        metrics: Vec<(Option<String>, bool)>,
+    }
+
+    impl LaneTarget for Target {
+        fn status(&self) -> ControlStatus {
+            ControlStatus {
+                sim_time: SimTime::from_secs(5.0),
+                events_processed: 42,
+                pending_events: 1_000_000,
+                paused: false,
+                pacing: self.pacing.unwrap_or(Pacing::Fast),
+                max_command_latency_us: 0,
+            }
+        }
+
+        fn console(&mut self, line: &str) -> String {
+            format!("> {}", line)
+        }
+
+        fn nodes(&self) -> Vec<NodeStatus> {
+            Vec::new()
+        }
+
+        fn set_pacing(&mut self, pacing: Pacing) {
+            self.pacing = Some(pacing);
+        }
+
+        fn set_metrics_recording(&mut self, category: Option<&str>, enabled: bool) -> bool {
+            self.metrics.push((category.map(str::to_string), enabled));
+            true
+        }
+    }
+
+    #[test]
+    fn test_lane_without_commands_continues() {
+        let mut lane = ControlLane::new();
+        assert_eq!(lane.service(&mut Target::default(), None), LaneOutcome::Continue);
+        lane.handle().stop();
+        assert_eq!(lane.service(&mut Target::default(), None), LaneOutcome::Stop);
+    }
+
+    #[test]
+    fn test_pause_blocks_until_resumed() {
+        let mut lane = ControlLane::new();
+        let control = lane.handle();
+        control.pause();
+
+        let client = thread::spawn(move || {
+            let status = control.status(Duration::from_secs(5)).expect("status while paused");
+            let output = control.console("nodes", Duration::from_secs(5)).expect("console while paused");
+            control.resume();
+            (status, output)
+        });
+
+        // Blocks here, answering the queries, until the client resumes
+        assert_eq!(lane.service(&mut Target::default(), None), LaneOutcome::Resumed);
+        let (reported, output) = client.join().unwrap();
+        assert!(reported.paused);
+        assert_eq!(reported.pending_events, 1_000_000);
+        assert_eq!(output, "> nodes");
+    }
+
+    #[test]
+    fn test_subscribers_receive_notices() {
+        let mut lane = ControlLane::new();
+        let control = lane.handle();
+        let events = control.subscribe();
+        let dropped = control.subscribe();
+        drop(dropped);
+        assert_eq!(lane.service(&mut Target::default(), None), LaneOutcome::Continue);
+        assert!(lane.has_subscribers());
+
+        let notice = EventNotice {
+            sim_time: SimTime::from_secs(1.0),
+            event_type: "Timer".to_string(),
+            details: String::new(),
+            source: "Alice".to_string(),
+            targets: vec!["Alice".to_string()],
+            text: None,
+        };
+        lane.publish(notice.clone());
+        assert_eq!(lane.subscribers.len(), 1);
+        assert_eq!(events.try_recv().unwrap(), notice);
+
+        drop(events);
+        lane.publish(notice);
+        assert!(!lane.has_subscribers());
+    }
+
+    #[test]
+    fn test_pacing_reaches_target() {
+        let mut lane = ControlLane::new();
+        let control = lane.handle();
+        let mut target = Target::default();
+        control.set_pacing(Pacing::Scaled(10.0));
+        assert_eq!(lane.service(&mut target, None), LaneOutcome::Continue);
+        assert_eq!(target.pacing, Some(Pacing::Scaled(10.0)));
+    }
+
+    #[test]
+    fn test_metrics_recording_reaches_target() {
+        let mut lane = ControlLane::new();
+        let control = lane.handle();
+        let client = thread::spawn(move || {
+            let all = control.set_metrics_recording(None, false, Duration::from_secs(5));
+            let radio = control.set_metrics_recording(Some("mcsim.radio"), true, Duration::from_secs(5));
+            control.stop();
+            (all, radio)
+        });
+
+        let mut target = Target::default();
+        while lane.service(&mut target, None) != LaneOutcome::Stop {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(client.join().unwrap(), (Some(true), Some(true)));
+        assert_eq!(target.metrics, [(None, false), (Some("mcsim.radio".to_string()), true)]);
+    }
+
+    #[test]
+    fn test_stop_flag_ends_pause() {
+        let mut lane = ControlLane::new();
+        lane.handle().pause();
+        let stop_flag = AtomicBool::new(true);
+        assert_eq!(lane.service(&mut Target::default(), Some(&stop_flag)), LaneOutcome::Stop);
+    }
+}
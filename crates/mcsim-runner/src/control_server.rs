@@ -0,0 +1,309 @@
+//! JSON-RPC control API for external orchestration.
+//!
+//! With `--control-listen ADDR`, `mcsim run` accepts TCP connections and
+//! forwards their requests to the [`control`](crate::control) lane, so a
+//! test harness can drive a simulation without parsing logs. Each line sent
+//! is a JSON-RPC 2.0 request and each line received a response or, after
+//! `subscribe`, an event notification:
+//!
+//! ```text
+//! → {"jsonrpc":"2.0","id":1,"method":"subscribe","params":{"events":["SerialTx"]}}
+//! ← {"jsonrpc":"2.0","id":1,"result":true}
+//! → {"jsonrpc":"2.0","id":2,"method":"resume"}
+//! ← {"jsonrpc":"2.0","id":2,"result":true}
+//! ← {"jsonrpc":"2.0","method":"event","params":{"sim_time":1500000,"event_type":"SerialTx",...}}
+//! → {"jsonrpc":"2.0","id":3,"method":"send","params":{"node":"Repeater1","text":"neighbors"}}
+//! ```
+//!
+//! Methods:
+//!
+//! - `status`: the loop's [`ControlStatus`];
+//! - `pause`, `resume`, `stop`: hold, continue or end the run (`--control-wait`
+//!   holds it until a client resumes);
+//! - `pace` (`pacing`: `fast`, `realtime` or a multiplier such as `10x`):
+//!   change how simulation time advances against the wall clock; external
+//!   clients on the UART bridge want `realtime` so their timeouts behave;
+//! - `nodes`: every node's [`NodeStatus`](crate::NodeStatus);
+//! - `metrics` (`enabled`, optional `category`: a metric name prefix): turn
+//!   metrics recording on or off, returning whether a recorder is attached;
+//! - `console` (`line`): execute an [`inspect`](crate::inspect) command line
+//!   and return its output;
+//! - `send` (`node`, `text`): send a CLI command line to a node's serial
+//!   port, returning the console output;
+//! - `subscribe` (optional `events`: event type names): stream an `event`
+//!   notification per processed event until the connection closes.
+//!
+//! Each connection is served on its own thread.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+
+use crate::control::ControlHandle;
+use crate::realtime::Pacing;
+
+/// How long a request waits for the simulation to answer.
+pub(crate) const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// JSON-RPC error codes.
+pub(crate) const PARSE_ERROR: i64 = -32700;
+pub(crate) const INVALID_REQUEST: i64 = -32600;
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+pub(crate) const INVALID_PARAMS: i64 = -32602;
+/// The simulation did not answer in time (or has ended).
+pub(crate) const NO_ANSWER: i64 = -32000;
+
+/// A running control API endpoint.
+pub struct ControlServer {
+    local_addr: SocketAddr,
+}
+
+impl ControlServer {
+    /// Bind `addr` and forward requests to `control` from background
+    /// threads for the rest of the process.
+    pub fn start(addr: &str, control: ControlHandle) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        std::thread::Builder::new()
+            .name("control-server".to_string())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let control = control.clone();
+                    // A failed connection only affects that client
+                    let _ = std::thread::Builder::new()
+                        .name("control-client".to_string())
+                        .spawn(move || handle_connection(stream, control));
+                }
+            })?;
+        Ok(Self { local_addr })
+    }
+
+    /// Address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+/// Writes whole lines to a connection shared with the notification thread.
+#[derive(Clone)]
+pub(crate) struct LineWriter(pub(crate) Arc<Mutex<TcpStream>>);
+
+impl LineWriter {
+    pub(crate) fn send(&self, message: &Value) -> io::Result<()> {
+        let mut stream = self.0.lock();
+        writeln!(stream, "{}", message)?;
+        stream.flush()
+    }
+}
+
+fn handle_connection(stream: TcpStream, control: ControlHandle) -> io::Result<()> {
+    let writer = LineWriter(Arc::new(Mutex::new(stream.try_clone()?)));
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => handle_request(&request, &control, &writer),
+            Err(e) => error(Value::Null, PARSE_ERROR, &e.to_string()),
+        };
+        writer.send(&response)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Handle one request, returning its response.
+fn handle_request(request: &Value, control: &ControlHandle, writer: &LineWriter) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return error(id, INVALID_REQUEST, "missing method");
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let param = |name: &str| params.get(name).and_then(Value::as_str);
+
+    let result = match method {
+        "status" => control.status(REPLY_TIMEOUT).map(|status| json!(status)),
+        "pause" => Some(json!(control.pause())),
+        "resume" => Some(json!(control.resume())),
+        "stop" => Some(json!(control.stop())),
+        "pace" => {
+            let Some(pacing) = param("pacing").and_then(|pacing| pacing.parse::<Pacing>().ok()) else {
+                return error(id, INVALID_PARAMS, "pace needs a pacing: fast, realtime or a multiplier like 10x");
+            };
+            Some(json!(control.set_pacing(pacing)))
+        }
+        "nodes" => control.nodes(REPLY_TIMEOUT).map(|nodes| json!(nodes)),
+        "metrics" => {
+            let Some(enabled) = params.get("enabled").and_then(Value::as_bool) else {
+                return error(id, INVALID_PARAMS, "metrics needs enabled: true or false");
+            };
+            control.set_metrics_recording(param("category"), enabled, REPLY_TIMEOUT).map(Value::Bool)
+        }
+        "console" => {
+            let Some(line) = param("line") else {
+                return error(id, INVALID_PARAMS, "console needs a line");
+            };
+            control.console(line, REPLY_TIMEOUT).map(Value::String)
+        }
+        "send" => {
+            let (Some(node), Some(text)) = (param("node"), param("text")) else {
+                return error(id, INVALID_PARAMS, "send needs a node and a text");
+            };
+            control
+                .console(&format!("send {} {}", node, text), REPLY_TIMEOUT)
+                .map(Value::String)
+        }
+        "subscribe" => {
+            let filter: Option<Vec<String>> = match params.get("events") {
+                None | Some(Value::Null) => None,
+                Some(events) => match serde_json::from_value(events.clone()) {
+                    Ok(events) => Some(events),
+                    Err(_) => return error(id, INVALID_PARAMS, "events must be a list of event type names"),
+                },
+            };
+            let notices = control.subscribe();
+            let writer = writer.clone();
+            std::thread::spawn(move || {
+                for notice in notices {
+                    if filter.as_ref().is_some_and(|events| !events.contains(&notice.event_type)) {
+                        continue;
+                    }
+                    let message = json!({ "jsonrpc": "2.0", "method": "event", "params": notice });
+                    // Dropping the receiver unsubscribes once the client is gone
+                    if writer.send(&message).is_err() {
+                        break;
+                    }
+                }
+            });
+            Some(json!(true))
+        }
+        _ => return error(id, METHOD_NOT_FOUND, &format!("unknown method '{}'", method)),
+    };
+
+    match result {
+        Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        None => error(id, NO_ANSWER, "no answer from the simulation"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{ControlLane, ControlStatus, EventNotice, LaneOutcome, LaneTarget, NodeStatus};
+    use crate::SimTime;
+
+    /// Loop stand-in with one node.
+    struct Target {
+        pacing: Pacing,
+        metrics_enabled: bool,
+    }
+
+    impl LaneTarget for Target {
+        fn status(&self) -> ControlStatus {
+            ControlStatus {
+                sim_time: SimTime::from_secs(2.0),
+                events_processed: 7,
+                pending_events: 3,
+                paused: false,
+                pacing: self.pacing,
+                max_command_latency_us: 0,
+            }
+        }
+
+        fn console(&mut self, line: &str) -> String {
+            format!("> {}", line)
+        }
+
+        fn nodes(&self) -> Vec<NodeStatus> {
+            vec![NodeStatus {
+                name: "Alice".to_string(),
+                node_type: "Companion".to_string(),
+                location: mcsim_common::GeoCoord { latitude: 47.6, longitude: -122.3, altitude_m: None },
+                tx: 1,
+                rx: 2,
+                collisions: 0,
+            }]
+        }
+
+        fn set_pacing(&mut self, pacing: Pacing) {
+            self.pacing = pacing;
+        }
+
+        fn set_metrics_recording(&mut self, _category: Option<&str>, enabled: bool) -> bool {
+            self.metrics_enabled = enabled;
+            true
+        }
+    }
+
+    #[test]
+    fn test_json_rpc_requests() {
+        let mut lane = ControlLane::new();
+        let server = ControlServer::start("127.0.0.1:0", lane.handle()).unwrap();
+
+        let client = std::thread::spawn(move || {
+            let stream = TcpStream::connect(server.local_addr()).unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut lines = BufReader::new(stream).lines();
+            let mut call = |request: &str| -> Value {
+                writeln!(writer, "{}", request).unwrap();
+                serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap()
+            };
+
+            let paced = call(r#"{"jsonrpc":"2.0","id":0,"method":"pace","params":{"pacing":"10x"}}"#);
+            let bad_pacing = call(r#"{"jsonrpc":"2.0","id":0,"method":"pace","params":{"pacing":"slow"}}"#);
+            let metrics = call(r#"{"jsonrpc":"2.0","id":0,"method":"metrics","params":{"enabled":false}}"#);
+            let bad_metrics = call(r#"{"jsonrpc":"2.0","id":0,"method":"metrics","params":{"category":"mcsim"}}"#);
+            let status = call(r#"{"jsonrpc":"2.0","id":1,"method":"status"}"#);
+            let nodes = call(r#"{"jsonrpc":"2.0","id":2,"method":"nodes"}"#);
+            let sent = call(r#"{"jsonrpc":"2.0","id":3,"method":"send","params":{"node":"Alice","text":"ver"}}"#);
+            let unknown = call(r#"{"jsonrpc":"2.0","id":4,"method":"fly"}"#);
+            let invalid = call("not json");
+            let subscribed = call(r#"{"jsonrpc":"2.0","id":5,"method":"subscribe","params":{"events":["Timer"]}}"#);
+            call(r#"{"jsonrpc":"2.0","id":6,"method":"stop"}"#);
+            let event: Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+            (paced, bad_pacing, metrics, bad_metrics, status, nodes, sent, unknown, invalid, subscribed, event)
+        });
+
+        let mut target = Target { pacing: Pacing::Fast, metrics_enabled: true };
+        while lane.service(&mut target, None) != LaneOutcome::Stop {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let notice = |event_type: &str| EventNotice {
+            sim_time: SimTime::from_secs(2.0),
+            event_type: event_type.to_string(),
+            details: String::new(),
+            source: "Alice".to_string(),
+            targets: Vec::new(),
+            text: None,
+        };
+        lane.publish(notice("SerialTx"));
+        lane.publish(notice("Timer"));
+
+        let (paced, bad_pacing, metrics, bad_metrics, status, nodes, sent, unknown, invalid, subscribed, event) =
+            client.join().unwrap();
+        assert_eq!(paced["result"], true);
+        assert_eq!(bad_pacing["error"]["code"], INVALID_PARAMS);
+        assert_eq!(metrics["result"], true);
+        assert!(!target.metrics_enabled);
+        assert_eq!(bad_metrics["error"]["code"], INVALID_PARAMS);
+        assert_eq!(status["id"], 1);
+        assert_eq!(status["result"]["events_processed"], 7);
+        assert_eq!(status["result"]["pacing"], "10x");
+        assert_eq!(target.pacing, Pacing::Scaled(10.0));
+        assert_eq!(nodes["result"][0]["name"], "Alice");
+        assert_eq!(sent["result"], "> send Alice ver");
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(invalid["error"]["code"], PARSE_ERROR);
+        assert_eq!(subscribed["result"], true);
+        assert_eq!(event["method"], "event");
+        assert_eq!(event["params"]["event_type"], "Timer");
+    }
+}
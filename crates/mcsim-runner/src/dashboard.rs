@@ -0,0 +1,367 @@
+//! Live web dashboard of a running simulation.
+//!
+//! With `--dashboard ADDR`, `mcsim run` serves a page at `http://ADDR/`
+//! showing the nodes on a map, radio activity as it happens, per-node packet
+//! and message counts and the tail of each node's serial console output:
+//!
+//! ```text
+//! mcsim run model.yaml --realtime --dashboard 127.0.0.1:8080
+//! ```
+//!
+//! The page is updated over a websocket (`/ws`) that pushes a JSON
+//! [`DashboardSnapshot`] every half second; `/state` returns the same
+//! snapshot once, for scripts.
+//!
+//! The dashboard follows the run through the [`control`](crate::control)
+//! lane: a collector thread subscribes to the event stream and polls the
+//! loop's status and nodes, so the simulation never waits on a browser.
+//! Message counts are read from the metrics recorder, whose label filter
+//! must keep the `node` label of [`NODE_METRICS`].
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::{Html, IntoResponse, Json, Response};
+use axum::routing::get;
+use axum::Router;
+use mcsim_metrics::metric_defs;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::control::{ControlHandle, ControlStatus, EventNotice, NodeStatus};
+use crate::metrics_export::InMemoryRecorder;
+use crate::SimTime;
+
+/// The dashboard page.
+const PAGE: &str = include_str!("dashboard.html");
+
+/// How often the snapshot is refreshed and pushed to clients.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a status or nodes query waits for the simulation.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Radio events kept in the activity feed.
+const ACTIVITY_LEN: usize = 100;
+
+/// Serial output lines kept per node.
+const LOG_TAIL_LEN: usize = 40;
+
+/// Event types shown as radio activity.
+const RADIO_EVENTS: &[&str] = &["TransmitAir", "RadioRxPacket"];
+
+/// Metrics the dashboard reads per node: messages sent and received.
+pub const NODE_METRICS: &[&str] = &[metric_defs::MESSAGE_SENT.name, metric_defs::MESSAGE_DELIVERED.name];
+
+/// What the dashboard shows, as pushed to clients.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DashboardSnapshot {
+    /// Whether the simulation is still running.
+    pub running: bool,
+    /// State of the event loop at the last refresh.
+    pub status: Option<ControlStatus>,
+    /// Every node, in the order the simulation lists them.
+    pub nodes: Vec<NodeView>,
+    /// Recent radio events, oldest first.
+    pub activity: Vec<RadioActivity>,
+}
+
+/// One node on the dashboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeView {
+    /// Name, type, position and packet counts.
+    #[serde(flatten)]
+    pub status: NodeStatus,
+    /// Direct messages the node's agent sent.
+    pub messages_sent: u64,
+    /// Direct messages the node's agent received.
+    pub messages_received: u64,
+    /// When the node last started transmitting.
+    pub last_tx: Option<SimTime>,
+    /// Last lines of the node's serial console output.
+    pub log: Vec<String>,
+}
+
+/// A radio event in the activity feed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RadioActivity {
+    /// Time of the event.
+    pub sim_time: SimTime,
+    /// `TransmitAir` or `RadioRxPacket`.
+    pub event_type: String,
+    /// Node whose radio sent or received.
+    pub node: String,
+    /// Short description of the packet.
+    pub details: String,
+}
+
+/// Snapshot shared between the collector thread and the web server.
+type SharedSnapshot = Arc<Mutex<DashboardSnapshot>>;
+
+/// A running dashboard.
+pub struct Dashboard {
+    local_addr: SocketAddr,
+}
+
+impl Dashboard {
+    /// Bind `addr`, follow the simulation through `control` and serve the
+    /// dashboard from background threads for the rest of the process.
+    /// `recorder` supplies the per-node message counts.
+    pub fn start(addr: &str, control: ControlHandle, recorder: Option<Arc<InMemoryRecorder>>) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        listener.set_nonblocking(true)?;
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let snapshot = SharedSnapshot::default();
+
+        let events = control.subscribe();
+        let shared = snapshot.clone();
+        std::thread::Builder::new()
+            .name("dashboard-collector".to_string())
+            .spawn(move || {
+                let mut collector = Collector::default();
+                let mut running = true;
+                while running {
+                    let deadline = Instant::now() + REFRESH_INTERVAL;
+                    loop {
+                        match events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                            Ok(notice) => collector.observe(notice),
+                            Err(RecvTimeoutError::Timeout) => break,
+                            // The event loop has been dropped
+                            Err(RecvTimeoutError::Disconnected) => {
+                                running = false;
+                                break;
+                            }
+                        }
+                    }
+                    if running {
+                        collector.status = control.status(QUERY_TIMEOUT).or(collector.status.take());
+                        if let Some(nodes) = control.nodes(QUERY_TIMEOUT) {
+                            collector.nodes = nodes;
+                        }
+                    }
+                    let mut snapshot = collector.snapshot(recorder.as_deref());
+                    snapshot.running = running;
+                    *shared.lock() = snapshot;
+                }
+            })?;
+
+        std::thread::Builder::new()
+            .name("dashboard-server".to_string())
+            .spawn(move || {
+                runtime.block_on(async move {
+                    let app = Router::new()
+                        .route("/", get(|| async { Html(PAGE) }))
+                        .route("/state", get(state))
+                        .route("/ws", get(websocket))
+                        .with_state(snapshot);
+                    if let Ok(listener) = tokio::net::TcpListener::from_std(listener) {
+                        let _ = axum::serve(listener, app).await;
+                    }
+                })
+            })?;
+        Ok(Self { local_addr })
+    }
+
+    /// Address the dashboard is served on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+async fn state(State(snapshot): State<SharedSnapshot>) -> Json<DashboardSnapshot> {
+    Json(snapshot.lock().clone())
+}
+
+async fn websocket(upgrade: WebSocketUpgrade, State(snapshot): State<SharedSnapshot>) -> Response {
+    upgrade.on_upgrade(move |socket| push_snapshots(socket, snapshot)).into_response()
+}
+
+/// Send the snapshot to a websocket client every refresh until it goes away.
+async fn push_snapshots(mut socket: WebSocket, snapshot: SharedSnapshot) {
+    let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let Ok(json) = serde_json::to_string(&*snapshot.lock()) else {
+            return;
+        };
+        if socket.send(Message::Text(json.into())).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// What the collector has learned about the run so far.
+#[derive(Debug, Default)]
+struct Collector {
+    status: Option<ControlStatus>,
+    nodes: Vec<NodeStatus>,
+    last_tx: HashMap<String, SimTime>,
+    logs: HashMap<String, VecDeque<String>>,
+    activity: VecDeque<RadioActivity>,
+}
+
+impl Collector {
+    fn observe(&mut self, notice: EventNotice) {
+        if notice.event_type == "SerialTx" {
+            if let Some(text) = &notice.text {
+                let log = self.logs.entry(notice.source).or_default();
+                for line in text.lines().map(str::trim_end).filter(|line| !line.is_empty()) {
+                    if log.len() == LOG_TAIL_LEN {
+                        log.pop_front();
+                    }
+                    log.push_back(line.to_string());
+                }
+            }
+            return;
+        }
+        if !RADIO_EVENTS.contains(&notice.event_type.as_str()) {
+            return;
+        }
+        if notice.event_type == "TransmitAir" {
+            self.last_tx.insert(notice.source.clone(), notice.sim_time);
+        }
+        if self.activity.len() == ACTIVITY_LEN {
+            self.activity.pop_front();
+        }
+        self.activity.push_back(RadioActivity {
+            sim_time: notice.sim_time,
+            event_type: notice.event_type,
+            node: notice.source,
+            details: notice.details,
+        });
+    }
+
+    fn snapshot(&self, recorder: Option<&InMemoryRecorder>) -> DashboardSnapshot {
+        let count = |metric: &str, node: &str| {
+            recorder.and_then(|recorder| recorder.current_value(metric, Some(node))).unwrap_or(0.0) as u64
+        };
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|status| NodeView {
+                messages_sent: count(metric_defs::MESSAGE_SENT.name, &status.name),
+                messages_received: count(metric_defs::MESSAGE_DELIVERED.name, &status.name),
+                last_tx: self.last_tx.get(&status.name).copied(),
+                log: self.logs.get(&status.name).map(|log| log.iter().cloned().collect()).unwrap_or_default(),
+                status: status.clone(),
+            })
+            .collect();
+        DashboardSnapshot {
+            running: true,
+            status: self.status.clone(),
+            nodes,
+            activity: self.activity.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::control::{ControlLane, LaneOutcome, LaneTarget};
+    use crate::realtime::Pacing;
+
+    /// Loop stand-in with one node.
+    struct Target;
+
+    impl LaneTarget for Target {
+        fn status(&self) -> ControlStatus {
+            ControlStatus {
+                sim_time: SimTime::from_secs(3.0),
+                events_processed: 12,
+                pending_events: 4,
+                paused: false,
+                pacing: Pacing::Fast,
+                max_command_latency_us: 0,
+            }
+        }
+
+        fn console(&mut self, line: &str) -> String {
+            format!("> {}", line)
+        }
+
+        fn nodes(&self) -> Vec<NodeStatus> {
+            vec![NodeStatus {
+                name: "Repeater1".to_string(),
+                node_type: "Repeater".to_string(),
+                location: mcsim_common::GeoCoord { latitude: 47.6, longitude: -122.3, altitude_m: None },
+                tx: 1,
+                rx: 0,
+                collisions: 0,
+            }]
+        }
+
+        fn set_pacing(&mut self, _pacing: Pacing) {}
+
+        fn set_metrics_recording(&mut self, _category: Option<&str>, _enabled: bool) -> bool {
+            false
+        }
+    }
+
+    fn get_state(addr: SocketAddr) -> Value {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET /state HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn test_dashboard_follows_run() {
+        let mut lane = ControlLane::new();
+        let dashboard = Dashboard::start("127.0.0.1:0", lane.handle(), None).unwrap();
+        let notice = |event_type: &str, text: Option<&str>| EventNotice {
+            sim_time: SimTime::from_secs(2.0),
+            event_type: event_type.to_string(),
+            details: "pkt_len=20".to_string(),
+            source: "Repeater1".to_string(),
+            targets: Vec::new(),
+            text: text.map(str::to_string),
+        };
+
+        // The collector subscribes, then polls status and nodes
+        let started = Instant::now();
+        let mut published = false;
+        let state = loop {
+            assert_eq!(lane.service(&mut Target, None), LaneOutcome::Continue);
+            if !published && lane.has_subscribers() {
+                lane.publish(notice("TransmitAir", None));
+                lane.publish(notice("Timer", None));
+                lane.publish(notice("SerialTx", Some("ver\r\nMeshCore v1.9\r\n")));
+                published = true;
+            }
+            if published && started.elapsed() > REFRESH_INTERVAL * 3 {
+                let state = get_state(dashboard.local_addr());
+                if state["nodes"].as_array().is_some_and(|nodes| !nodes.is_empty()) {
+                    break state;
+                }
+            }
+            assert!(started.elapsed() < Duration::from_secs(10), "dashboard never refreshed");
+            std::thread::sleep(Duration::from_millis(5));
+        };
+
+        assert_eq!(state["running"], true);
+        assert_eq!(state["status"]["events_processed"], 12);
+        let node = &state["nodes"][0];
+        assert_eq!(node["name"], "Repeater1");
+        assert_eq!(node["tx"], 1);
+        assert_eq!(node["messages_sent"], 0);
+        assert_eq!(node["last_tx"], serde_json::to_value(SimTime::from_secs(2.0)).unwrap());
+        assert_eq!(node["log"], serde_json::json!(["ver", "MeshCore v1.9"]));
+        assert_eq!(state["activity"].as_array().unwrap().len(), 1);
+        assert_eq!(state["activity"][0]["event_type"], "TransmitAir");
+    }
+}
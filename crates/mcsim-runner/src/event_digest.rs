@@ -0,0 +1,144 @@
+//! Running digest of the processed event stream.
+//!
+//! An [`EventDigest`] hashes every processed event's time, source, targets
+//! and payload, so two runs can be checked for producing exactly the same
+//! event stream, and checkpoints locate the first point at which they
+//! diverge. `mcsim what-if` uses it to confirm that a run restored from a
+//! checkpoint continues as the original did.
+
+use mcsim_common::Event;
+use serde::Serialize;
+
+use crate::watchdog::describe_event_payload;
+
+/// Number of events between digest checkpoints.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 10_000;
+
+/// FNV-1a offset basis.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+/// FNV-1a prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Running hash of the processed event stream.
+///
+/// Event IDs are left out so that runs may number events differently;
+/// only what happened, when, and to whom counts. FNV-1a is used so digests
+/// are stable across builds and can be compared between binaries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EventDigest {
+    hash: u64,
+    events: u64,
+    checkpoint_interval: u64,
+    checkpoints: Vec<DigestCheckpoint>,
+}
+
+/// Digest of the event stream after a number of events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DigestCheckpoint {
+    /// Events processed.
+    pub events: u64,
+    /// Simulation time of the last of those events, in microseconds.
+    pub time_us: u64,
+    /// Digest after those events.
+    pub hash: u64,
+}
+
+impl EventDigest {
+    /// An empty digest recording a checkpoint every `checkpoint_interval`
+    /// events (0 for no checkpoints).
+    pub fn new(checkpoint_interval: u64) -> Self {
+        Self { hash: FNV_OFFSET, events: 0, checkpoint_interval, checkpoints: Vec::new() }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Add a processed event.
+    pub fn record(&mut self, event: &Event) {
+        let (kind, details) = describe_event_payload(&event.payload);
+        self.write(&event.time.as_micros().to_le_bytes());
+        self.write(&event.source.0.to_le_bytes());
+        for target in &event.targets {
+            self.write(&target.0.to_le_bytes());
+        }
+        self.write(kind.as_bytes());
+        self.write(details.as_bytes());
+        self.events += 1;
+        if self.checkpoint_interval > 0 && self.events.is_multiple_of(self.checkpoint_interval) {
+            self.checkpoints.push(DigestCheckpoint {
+                events: self.events,
+                time_us: event.time.as_micros(),
+                hash: self.hash,
+            });
+        }
+    }
+
+    /// Digest of all events recorded so far.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Number of events recorded.
+    pub fn events(&self) -> u64 {
+        self.events
+    }
+
+    /// Checkpoints recorded so far.
+    pub fn checkpoints(&self) -> &[DigestCheckpoint] {
+        &self.checkpoints
+    }
+
+    /// First checkpoint of `self` that differs from `other`, if the streams
+    /// diverge. When all shared checkpoints agree but the final digests
+    /// differ, the divergence is after the last shared checkpoint and `None`
+    /// is returned; compare [`hash`](Self::hash) to detect that.
+    pub fn first_divergence(&self, other: &EventDigest) -> Option<DigestCheckpoint> {
+        self.checkpoints
+            .iter()
+            .zip(&other.checkpoints)
+            .find(|(a, b)| a.hash != b.hash)
+            .map(|(a, _)| *a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{EntityId, EventId, EventPayload, SimTime};
+
+    fn timer(time_us: u64, timer_id: u64) -> Event {
+        Event {
+            id: EventId(time_us),
+            time: SimTime::from_micros(time_us),
+            source: EntityId::new(1),
+            targets: vec![EntityId::new(1)],
+            payload: EventPayload::Timer { timer_id },
+        }
+    }
+
+    #[test]
+    fn test_digest_locates_divergence() {
+        let mut a = EventDigest::new(2);
+        let mut b = EventDigest::new(2);
+        for i in 0..6 {
+            a.record(&timer(i * 10, 1));
+            b.record(&timer(i * 10, if i == 3 { 2 } else { 1 }));
+        }
+        assert_eq!(a.checkpoints().len(), 3);
+        assert_ne!(a.hash(), b.hash());
+        assert_eq!(b.first_divergence(&a).map(|c| c.events), Some(4));
+
+        // Event IDs don't count
+        let mut c = EventDigest::new(2);
+        for i in 0..6 {
+            let mut event = timer(i * 10, 1);
+            event.id = EventId(100 + i);
+            c.record(&event);
+        }
+        assert_eq!(c, a);
+    }
+}
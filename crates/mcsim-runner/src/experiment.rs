@@ -0,0 +1,415 @@
+//! Monte Carlo experiments.
+//!
+//! A single run shows one draw of the simulation's randomness: the airtime
+//! jitter, retransmit delays and link fading of one seed. `mcsim experiment`
+//! runs the same scenario N times, seed `S`, `S + 1`, ... so that run `i`
+//! can be reproduced on its own with `mcsim run --seed S+i`, and summarizes
+//! how each value varies across the runs.
+//!
+//! Every run is a separate `mcsim run` process, because the metrics
+//! recorder is global to a process; several can be in flight at once. A
+//! [`RunValues`] collects the numeric fields of the run's statistics (see
+//! [`crate::SimulationStats`]) together with the totals of its exported
+//! metrics, and an [`ExperimentReport`] reduces every value to a
+//! [`Summary`] of mean, standard deviation and percentiles. Values missing
+//! from some runs (a metric nothing recorded) are summarized over the runs
+//! that have them.
+//!
+//! `mcsim sweep` runs the combinations of a scenario's parameter sweep (see
+//! [`mcsim_model::sweep`]) the same way, all with one seed, and rolls the
+//! values of each combination up into a [`SweepReport`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::RunnerError;
+
+/// Derived value: fraction of sent messages that were acknowledged.
+pub const DELIVERY_RATIO: &str = "delivery_ratio";
+
+/// Derived value: number of scenario assertions that failed.
+pub const ASSERTIONS_FAILED: &str = "assertions_failed";
+
+/// Seed of each run of an experiment.
+pub fn run_seeds(seed: u64, runs: usize) -> Vec<u64> {
+    (0..runs as u64).map(|i| seed.wrapping_add(i)).collect()
+}
+
+/// Values measured by one run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunValues {
+    /// Index of the run, from 0.
+    pub run: usize,
+    /// Seed the run used.
+    pub seed: u64,
+    /// Statistics and metric totals by name.
+    pub values: BTreeMap<String, f64>,
+}
+
+impl RunValues {
+    /// Collect the values of a run from the statistics JSON it printed and,
+    /// if metrics were exported, the CSV it wrote.
+    pub fn parse(run: usize, seed: u64, stats_json: &str, metrics_csv: Option<&str>) -> Result<Self, RunnerError> {
+        let stats: serde_json::Value = serde_json::from_str(stats_json)?;
+        let serde_json::Value::Object(fields) = &stats else {
+            return Err(RunnerError::ConfigError("run statistics are not a JSON object".to_string()));
+        };
+        let mut values: BTreeMap<String, f64> = fields
+            .iter()
+            .filter_map(|(name, value)| Some((name.clone(), value.as_f64()?)))
+            .collect();
+        if let (Some(sent), Some(acked)) = (values.get("messages_sent"), values.get("messages_acked")) {
+            if *sent > 0.0 {
+                values.insert(DELIVERY_RATIO.to_string(), acked / sent);
+            }
+        }
+        if let Some(assertions) = stats.get("assertions").and_then(|a| a.as_array()) {
+            let failed = assertions
+                .iter()
+                .filter(|a| a.get("passed").and_then(|p| p.as_bool()) == Some(false))
+                .count();
+            values.insert(ASSERTIONS_FAILED.to_string(), failed as f64);
+        }
+        if let Some(csv) = metrics_csv {
+            values.extend(metric_totals(csv));
+        }
+        Ok(RunValues { run, seed, values })
+    }
+}
+
+/// Totals (the `/` row) of a metrics CSV written by `--metrics-output csv`.
+fn metric_totals(csv: &str) -> BTreeMap<String, f64> {
+    let mut lines = csv.lines();
+    let Some(header) = lines.next() else {
+        return BTreeMap::new();
+    };
+    let Some(totals) = lines.find(|line| line.split(',').next() == Some("/")) else {
+        return BTreeMap::new();
+    };
+    header
+        .split(',')
+        .zip(totals.split(','))
+        .skip(1)
+        .filter_map(|(name, value)| Some((name.to_string(), value.parse().ok()?)))
+        .collect()
+}
+
+/// A run that did not produce results.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunFailure {
+    /// Index of the run, from 0.
+    pub run: usize,
+    /// Seed the run used.
+    pub seed: u64,
+    /// Why it failed.
+    pub error: String,
+}
+
+/// Distribution of one value across the runs of an experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Summary {
+    /// Runs the value was measured in.
+    pub runs: usize,
+    /// Mean value.
+    pub mean: f64,
+    /// Sample standard deviation (0 for a single run).
+    pub std_dev: f64,
+    /// Minimum value.
+    pub min: f64,
+    /// 5th percentile.
+    pub p5: f64,
+    /// 50th percentile (median).
+    pub p50: f64,
+    /// 95th percentile.
+    pub p95: f64,
+    /// Maximum value.
+    pub max: f64,
+}
+
+impl Summary {
+    /// Summarize a non-empty set of values.
+    pub fn of(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let std_dev = if sorted.len() > 1 {
+            (sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        // Nearest-rank percentiles
+        let percentile = |p: f64| sorted[((n * p).ceil() as usize).clamp(1, sorted.len()) - 1];
+        Some(Summary {
+            runs: sorted.len(),
+            mean,
+            std_dev,
+            min: sorted[0],
+            p5: percentile(0.05),
+            p50: percentile(0.5),
+            p95: percentile(0.95),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Results of every run of an experiment and their summaries.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExperimentReport {
+    /// Seed of the first run.
+    pub seed: u64,
+    /// Simulated seconds per run.
+    pub duration_s: f64,
+    /// Summary of each value, by name.
+    pub summary: BTreeMap<String, Summary>,
+    /// Runs that completed, in run order.
+    pub runs: Vec<RunValues>,
+    /// Runs that failed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<RunFailure>,
+}
+
+impl ExperimentReport {
+    /// Summarize the completed runs.
+    pub fn new(seed: u64, duration_s: f64, mut runs: Vec<RunValues>, mut failures: Vec<RunFailure>) -> Self {
+        runs.sort_by_key(|r| r.run);
+        failures.sort_by_key(|f| f.run);
+        let mut samples: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+        for outcome in &runs {
+            for (name, value) in &outcome.values {
+                samples.entry(name).or_default().push(*value);
+            }
+        }
+        let summary = samples
+            .into_iter()
+            .filter_map(|(name, values)| Some((name.to_string(), Summary::of(&values)?)))
+            .collect();
+        ExperimentReport {
+            seed,
+            duration_s,
+            summary,
+            runs,
+            failures,
+        }
+    }
+
+    /// The summaries as CSV, one row per value.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("value,runs,mean,std_dev,min,p5,p50,p95,max\n");
+        for (name, s) in &self.summary {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                name, s.runs, s.mean, s.std_dev, s.min, s.p5, s.p50, s.p95, s.max
+            ));
+        }
+        csv
+    }
+}
+
+impl fmt::Display for ExperimentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Experiment: {} run(s) of {:.0}s from seed {}",
+            self.runs.len(),
+            self.duration_s,
+            self.seed
+        )?;
+        if !self.failures.is_empty() {
+            write!(f, ", {} failed", self.failures.len())?;
+        }
+        let width = self.summary.keys().map(String::len).max().unwrap_or(0);
+        write!(
+            f,
+            "\n  {:<width$} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            "value", "mean", "std dev", "p5", "p50", "p95"
+        )?;
+        for (name, s) in &self.summary {
+            write!(
+                f,
+                "\n  {:<width$} {:>12.3} {:>12.3} {:>12.3} {:>12.3} {:>12.3}",
+                name, s.mean, s.std_dev, s.p5, s.p50, s.p95
+            )?;
+        }
+        for failure in &self.failures {
+            write!(f, "\n  run {} (seed {}) failed: {}", failure.run, failure.seed, failure.error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Results of one combination of a parameter sweep.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SweepRun {
+    /// Index of the combination, from 0.
+    pub index: usize,
+    /// Swept property values, by property name.
+    pub parameters: BTreeMap<String, String>,
+    /// Directory the run's output was written to.
+    pub dir: String,
+    /// Statistics and metric totals by name; empty if the run failed.
+    pub values: BTreeMap<String, f64>,
+    /// Why the run failed, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Roll-up of every combination of a parameter sweep.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SweepReport {
+    /// Seed every combination ran with.
+    pub seed: u64,
+    /// Simulated seconds per run.
+    pub duration_s: f64,
+    /// Swept properties, in sweep order.
+    pub parameters: Vec<String>,
+    /// One entry per combination, in index order.
+    pub runs: Vec<SweepRun>,
+}
+
+impl SweepReport {
+    /// Roll up the runs of a sweep.
+    pub fn new(seed: u64, duration_s: f64, parameters: Vec<String>, mut runs: Vec<SweepRun>) -> Self {
+        runs.sort_by_key(|r| r.index);
+        SweepReport { seed, duration_s, parameters, runs }
+    }
+
+    /// The roll-up as CSV: one row per combination, with its swept values
+    /// followed by every value any run measured.
+    pub fn to_csv(&self) -> String {
+        let names: BTreeSet<&str> = self.runs.iter().flat_map(|r| r.values.keys().map(String::as_str)).collect();
+        let mut csv = String::from("run");
+        for name in self.parameters.iter().map(String::as_str).chain(names.iter().copied()) {
+            csv.push(',');
+            csv.push_str(name);
+        }
+        csv.push_str(",error\n");
+        for run in &self.runs {
+            csv.push_str(&run.index.to_string());
+            for parameter in &self.parameters {
+                csv.push(',');
+                csv.push_str(run.parameters.get(parameter).map_or("", String::as_str));
+            }
+            for name in &names {
+                csv.push(',');
+                if let Some(value) = run.values.get(*name) {
+                    csv.push_str(&value.to_string());
+                }
+            }
+            csv.push(',');
+            if let Some(error) = &run.error {
+                csv.push_str(&format!("\"{}\"", error.replace('"', "\"\"")));
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl fmt::Display for SweepReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Sweep: {} combination(s) of {} ({:.0}s, seed {})",
+            self.runs.len(),
+            self.parameters.join(", "),
+            self.duration_s,
+            self.seed
+        )?;
+        for run in &self.runs {
+            let parameters: Vec<String> = self
+                .parameters
+                .iter()
+                .filter_map(|p| Some(format!("{}={}", p, run.parameters.get(p)?)))
+                .collect();
+            write!(f, "\n  {:>3} {}: ", run.index, parameters.join(", "))?;
+            if let Some(error) = &run.error {
+                write!(f, "failed: {}", error)?;
+                continue;
+            }
+            let value = |name: &str| run.values.get(name).copied().unwrap_or(0.0);
+            match run.values.get(DELIVERY_RATIO) {
+                Some(ratio) => write!(f, "{:.1}% delivered", ratio * 100.0)?,
+                None => write!(f, "no messages")?,
+            }
+            write!(
+                f,
+                ", {:.0} transmitted, {:.0} collided",
+                value("packets_transmitted"),
+                value("packets_collided")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_experiment_summary() {
+        assert_eq!(run_seeds(u64::MAX, 2), vec![u64::MAX, 0]);
+
+        let stats = |acked: u64| {
+            format!(
+                r#"{{"total_events":100,"messages_sent":10,"messages_acked":{},"assertions":[{{"passed":false}}]}}"#,
+                acked
+            )
+        };
+        let csv = "path,mcsim.radio.rx_packets,mcsim.radio.tx_packets\n/,40,20\n/Alice,10,5\n";
+        let runs: Vec<RunValues> = (0..4)
+            .map(|i| RunValues::parse(i, i as u64, &stats(6 + i as u64), (i < 2).then_some(csv)).unwrap())
+            .collect();
+        assert_eq!(runs[0].values[DELIVERY_RATIO], 0.6);
+        assert_eq!(runs[0].values[ASSERTIONS_FAILED], 1.0);
+        assert_eq!(runs[0].values["mcsim.radio.rx_packets"], 40.0);
+
+        let failure = RunFailure { run: 4, seed: 4, error: "exit status 1".to_string() };
+        let report = ExperimentReport::new(0, 60.0, runs, vec![failure]);
+        let ratio = report.summary[DELIVERY_RATIO];
+        assert_eq!(ratio.runs, 4);
+        assert!((ratio.mean - 0.75).abs() < 1e-9);
+        assert!((ratio.std_dev - 0.129_099).abs() < 1e-6);
+        assert_eq!((ratio.min, ratio.p5, ratio.p50, ratio.p95, ratio.max), (0.6, 0.6, 0.7, 0.9, 0.9));
+
+        // Metrics only exported by some runs are summarized over those
+        assert_eq!(report.summary["mcsim.radio.tx_packets"].runs, 2);
+        assert_eq!(report.summary["total_events"].std_dev, 0.0);
+        assert!(report.to_csv().contains("\nmcsim.radio.rx_packets,2,40,0,40,40,40,40,40\n"));
+
+        assert!(RunValues::parse(0, 0, "[1]", None).is_err());
+    }
+
+    #[test]
+    fn test_sweep_report_csv() {
+        let run = |index: usize, power: &str, values: &[(&str, f64)], error: Option<&str>| SweepRun {
+            index,
+            parameters: [("radio/tx_power_dbm".to_string(), power.to_string())].into(),
+            dir: format!("out/{:03}", index),
+            values: values.iter().map(|(n, v)| (n.to_string(), *v)).collect(),
+            error: error.map(str::to_string),
+        };
+        let report = SweepReport::new(
+            1,
+            60.0,
+            vec!["radio/tx_power_dbm".to_string()],
+            vec![
+                run(1, "20", &[], Some("Error: \"boom\"")),
+                run(0, "14", &[(DELIVERY_RATIO, 0.5), ("packets_transmitted", 10.0)], None),
+            ],
+        );
+        assert_eq!(
+            report.to_csv(),
+            "run,radio/tx_power_dbm,delivery_ratio,packets_transmitted,error\n0,14,0.5,10,\n1,20,,,\"Error: \"\"boom\"\"\"\n"
+        );
+        let text = report.to_string();
+        assert!(text.contains("  0 radio/tx_power_dbm=14: 50.0% delivered, 10 transmitted, 0 collided"), "{}", text);
+        assert!(text.contains("failed: Error"));
+    }
+}
@@ -0,0 +1,381 @@
+//! Geographic heatmap of channel utilization.
+//!
+//! Combines node positions from the model, transmissions from a run's trace
+//! file (`run --output`) and a log-distance propagation kernel to estimate,
+//! for every cell of a grid laid over the network, the fraction of the run
+//! during which the channel was busy there. A cell counts as busy while any
+//! transmission arrives above the sender's detection level (noise floor plus
+//! the SNR threshold for its spreading factor); overlapping transmissions are
+//! counted once.
+//!
+//! The kernel ignores terrain, so it shows where airtime concentrates rather
+//! than exact coverage. The result is written as GeoJSON: one polygon per cell
+//! with `occupancy` and `saturated` properties, plus a point per node with its
+//! own transmit duty cycle.
+
+use std::collections::HashMap;
+
+use mcsim_common::GeoCoord;
+use mcsim_link::LinkPredictionParams;
+use mcsim_model::{Model, LOCATION_LATITUDE, LOCATION_LONGITUDE, RADIO_FREQUENCY_HZ, RADIO_SPREADING_FACTOR};
+use serde_json::{json, Value};
+
+use crate::RunnerError;
+
+/// Meters per degree of latitude.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Upper bound on grid size, to catch a cell size that is far too small.
+const MAX_CELLS: usize = 250_000;
+
+/// Grid and kernel settings.
+#[derive(Debug, Clone)]
+pub struct HeatmapConfig {
+    /// Cell edge length in meters.
+    pub cell_size_m: f64,
+    /// Distance the grid extends past the outermost nodes, in meters.
+    pub margin_m: f64,
+    /// Log-distance path loss exponent (2.0 is free space).
+    pub path_loss_exponent: f64,
+    /// Occupancy at or above which a cell is marked saturated.
+    pub saturation: f64,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            cell_size_m: 500.0,
+            margin_m: 2000.0,
+            path_loss_exponent: 3.0,
+            saturation: 0.3,
+        }
+    }
+}
+
+/// A transmitting node.
+#[derive(Debug, Clone)]
+pub struct HeatmapNode {
+    /// Node name, as it appears as the trace origin.
+    pub name: String,
+    /// Node position.
+    pub location: GeoCoord,
+    /// Carrier frequency in Hz.
+    pub frequency_hz: u32,
+    /// Lowest received power (dBm) at which its transmissions occupy the channel.
+    pub detect_threshold_dbm: f64,
+}
+
+impl HeatmapNode {
+    /// Build the node list from a model.
+    pub fn from_model(model: &Model) -> Vec<Self> {
+        let params = LinkPredictionParams::from_properties(model.simulation_properties());
+        model
+            .nodes()
+            .values()
+            .map(|node| {
+                let props = node.properties();
+                HeatmapNode {
+                    name: node.name.clone(),
+                    location: GeoCoord::new(props.get(&LOCATION_LATITUDE), props.get(&LOCATION_LONGITUDE)),
+                    frequency_hz: props.get(&RADIO_FREQUENCY_HZ),
+                    detect_threshold_dbm: params.noise_floor_dbm
+                        + params.snr_threshold_for_sf(props.get(&RADIO_SPREADING_FACTOR)),
+                }
+            })
+            .collect()
+    }
+}
+
+/// One transmission taken from a trace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transmission {
+    /// Sending node name.
+    pub node: String,
+    /// Start time in seconds.
+    pub start_s: f64,
+    /// End time in seconds.
+    pub end_s: f64,
+    /// Transmit power in dBm.
+    pub tx_power_dbm: f64,
+}
+
+/// Extract transmissions from a trace file's JSON array.
+///
+/// Only `PACKET` entries with direction `TX` are used.
+pub fn transmissions_from_trace(trace: &Value) -> Result<Vec<Transmission>, RunnerError> {
+    let entries = trace
+        .as_array()
+        .ok_or_else(|| RunnerError::ConfigError("Trace file must contain a JSON array".to_string()))?;
+    let mut transmissions = Vec::new();
+    for entry in entries {
+        if entry["type"] != "PACKET" || entry["direction"] != "TX" {
+            continue;
+        }
+        let field = |name: &str| {
+            entry[name].as_f64().ok_or_else(|| {
+                RunnerError::ConfigError(format!("Trace TX entry is missing '{}'", name))
+            })
+        };
+        let tx_power_dbm = entry["RSSI"]
+            .as_str()
+            .and_then(|s| s.trim_end_matches("dBm").trim().parse::<f64>().ok())
+            .ok_or_else(|| RunnerError::ConfigError("Trace TX entry has no transmit power".to_string()))?;
+        transmissions.push(Transmission {
+            node: entry["origin"].as_str().unwrap_or_default().to_string(),
+            start_s: field("packet_start_time_s")?,
+            end_s: field("packet_end_time_s")?,
+            tx_power_dbm,
+        });
+    }
+    Ok(transmissions)
+}
+
+/// Grid cell with its estimated channel occupancy.
+#[derive(Debug, Clone)]
+pub struct HeatmapCell {
+    /// South-west corner.
+    pub south_west: GeoCoord,
+    /// Fraction of the run the channel was busy here (0.0 to 1.0).
+    pub occupancy: f64,
+}
+
+/// Channel utilization over a geographic grid.
+#[derive(Debug, Clone)]
+pub struct ChannelHeatmap {
+    /// Cells in row-major order, south to north.
+    pub cells: Vec<HeatmapCell>,
+    /// Cell height in degrees of latitude.
+    pub lat_step: f64,
+    /// Cell width in degrees of longitude.
+    pub lon_step: f64,
+    /// Per-node transmit duty cycle.
+    pub node_duty_cycle: Vec<(String, GeoCoord, f64)>,
+    /// Occupancy at or above which a cell is saturated.
+    pub saturation: f64,
+}
+
+/// Log-distance path loss in dB, referenced to free space at 1 m.
+fn path_loss_db(distance_m: f64, frequency_hz: u32, exponent: f64) -> f64 {
+    let fspl_1m = 20.0 * (frequency_hz as f64 / 1e6).log10() - 27.55;
+    fspl_1m + 10.0 * exponent * distance_m.max(1.0).log10()
+}
+
+/// Compute the heatmap for a run of `duration_s` seconds.
+pub fn compute(
+    nodes: &[HeatmapNode],
+    transmissions: &[Transmission],
+    duration_s: f64,
+    config: &HeatmapConfig,
+) -> Result<ChannelHeatmap, RunnerError> {
+    if nodes.is_empty() || duration_s <= 0.0 || config.cell_size_m <= 0.0 {
+        return Err(RunnerError::ConfigError(
+            "Heatmap needs at least one node, a positive duration and a positive cell size".to_string(),
+        ));
+    }
+
+    // Grid bounds: node bounding box grown by the margin
+    let min_lat = nodes.iter().map(|n| n.location.latitude).fold(f64::INFINITY, f64::min);
+    let max_lat = nodes.iter().map(|n| n.location.latitude).fold(f64::NEG_INFINITY, f64::max);
+    let min_lon = nodes.iter().map(|n| n.location.longitude).fold(f64::INFINITY, f64::min);
+    let max_lon = nodes.iter().map(|n| n.location.longitude).fold(f64::NEG_INFINITY, f64::max);
+    let lat_step = config.cell_size_m / METERS_PER_DEGREE;
+    let lon_step = config.cell_size_m / (METERS_PER_DEGREE * ((min_lat + max_lat) / 2.0).to_radians().cos());
+    let lat_margin = config.margin_m / METERS_PER_DEGREE;
+    let lon_margin = lat_margin * lon_step / lat_step;
+    let south = min_lat - lat_margin;
+    let west = min_lon - lon_margin;
+    let rows = (((max_lat + lat_margin - south) / lat_step).ceil() as usize).max(1);
+    let cols = (((max_lon + lon_margin - west) / lon_step).ceil() as usize).max(1);
+    if rows * cols > MAX_CELLS {
+        return Err(RunnerError::ConfigError(format!(
+            "Heatmap grid of {}x{} cells is too large; increase the cell size",
+            rows, cols
+        )));
+    }
+
+    let node_index: HashMap<&str, usize> =
+        nodes.iter().enumerate().map(|(i, n)| (n.name.as_str(), i)).collect();
+    let mut sorted: Vec<(usize, &Transmission)> = transmissions
+        .iter()
+        .filter_map(|tx| node_index.get(tx.node.as_str()).map(|&i| (i, tx)))
+        .collect();
+    sorted.sort_by(|a, b| a.1.start_s.total_cmp(&b.1.start_s));
+
+    let mut cells = Vec::with_capacity(rows * cols);
+    for row in 0..rows {
+        for col in 0..cols {
+            let south_west = GeoCoord::new(south + row as f64 * lat_step, west + col as f64 * lon_step);
+            let center = GeoCoord::new(south_west.latitude + lat_step / 2.0, south_west.longitude + lon_step / 2.0);
+            // Path loss from each node to the cell center
+            let loss: Vec<f64> = nodes
+                .iter()
+                .map(|n| path_loss_db(n.location.distance_to(&center), n.frequency_hz, config.path_loss_exponent))
+                .collect();
+
+            // Union of busy intervals at this cell
+            let mut busy_s = 0.0;
+            let mut current: Option<(f64, f64)> = None;
+            for &(i, tx) in &sorted {
+                if tx.tx_power_dbm - loss[i] < nodes[i].detect_threshold_dbm {
+                    continue;
+                }
+                current = match current {
+                    Some((start, end)) if tx.start_s <= end => Some((start, end.max(tx.end_s))),
+                    Some((start, end)) => {
+                        busy_s += end - start;
+                        Some((tx.start_s, tx.end_s))
+                    }
+                    None => Some((tx.start_s, tx.end_s)),
+                };
+            }
+            if let Some((start, end)) = current {
+                busy_s += end - start;
+            }
+            cells.push(HeatmapCell {
+                south_west,
+                occupancy: (busy_s / duration_s).min(1.0),
+            });
+        }
+    }
+
+    let mut airtime = vec![0.0; nodes.len()];
+    for &(i, tx) in &sorted {
+        airtime[i] += tx.end_s - tx.start_s;
+    }
+    let node_duty_cycle = nodes
+        .iter()
+        .zip(airtime)
+        .map(|(n, t)| (n.name.clone(), n.location, t / duration_s))
+        .collect();
+
+    Ok(ChannelHeatmap {
+        cells,
+        lat_step,
+        lon_step,
+        node_duty_cycle,
+        saturation: config.saturation,
+    })
+}
+
+impl ChannelHeatmap {
+    /// Highest cell occupancy.
+    pub fn max_occupancy(&self) -> f64 {
+        self.cells.iter().map(|c| c.occupancy).fold(0.0, f64::max)
+    }
+
+    /// Number of cells at or above the saturation threshold.
+    pub fn saturated_cells(&self) -> usize {
+        self.cells.iter().filter(|c| c.occupancy >= self.saturation).count()
+    }
+
+    /// Render as a GeoJSON FeatureCollection.
+    ///
+    /// Cells where no transmission was detected are omitted.
+    pub fn to_geojson(&self) -> Value {
+        let mut features: Vec<Value> = self
+            .cells
+            .iter()
+            .filter(|c| c.occupancy > 0.0)
+            .map(|c| {
+                let (s, w) = (c.south_west.latitude, c.south_west.longitude);
+                let (n, e) = (s + self.lat_step, w + self.lon_step);
+                json!({
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Polygon",
+                        "coordinates": [[[w, s], [e, s], [e, n], [w, n], [w, s]]],
+                    },
+                    "properties": {
+                        "occupancy": c.occupancy,
+                        "saturated": c.occupancy >= self.saturation,
+                    },
+                })
+            })
+            .collect();
+        features.extend(self.node_duty_cycle.iter().map(|(name, location, duty)| {
+            json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": [location.longitude, location.latitude],
+                },
+                "properties": {
+                    "name": name,
+                    "tx_duty_cycle": duty,
+                },
+            })
+        }));
+        json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, longitude: f64) -> HeatmapNode {
+        HeatmapNode {
+            name: name.to_string(),
+            location: GeoCoord::new(47.0, longitude),
+            frequency_hz: 910_525_000,
+            detect_threshold_dbm: -127.5,
+        }
+    }
+
+    fn tx(node: &str, start_s: f64, end_s: f64) -> Transmission {
+        Transmission {
+            node: node.to_string(),
+            start_s,
+            end_s,
+            tx_power_dbm: 20.0,
+        }
+    }
+
+    #[test]
+    fn test_transmissions_from_trace() {
+        let trace = json!([
+            {"origin": "A", "type": "PACKET", "direction": "TX", "RSSI": "22 dBm",
+             "packet_start_time_s": 1.0, "packet_end_time_s": 1.5},
+            {"origin": "B", "type": "PACKET", "direction": "RX", "RSSI": "-90.0 dBm",
+             "packet_start_time_s": 1.0, "packet_end_time_s": 1.5},
+            {"origin": "A", "type": "TIMER", "timer_id": 3},
+        ]);
+        assert_eq!(transmissions_from_trace(&trace).unwrap(), vec![Transmission {
+            tx_power_dbm: 22.0,
+            ..tx("A", 1.0, 1.5)
+        }]);
+    }
+
+    #[test]
+    fn test_overlapping_airtime_counted_once() {
+        // Two nodes about 7.6 km apart; with a steep kernel each is only heard nearby
+        let nodes = vec![node("A", 0.0), node("B", 0.1)];
+        let config = HeatmapConfig {
+            cell_size_m: 1000.0,
+            margin_m: 0.0,
+            path_loss_exponent: 4.0,
+            saturation: 0.3,
+        };
+        let txs = vec![tx("A", 0.0, 2.0), tx("A", 1.0, 4.0), tx("B", 0.0, 1.0)];
+        let heatmap = compute(&nodes, &txs, 10.0, &config).unwrap();
+
+        let occupancy_near = |lon: f64| {
+            heatmap
+                .cells
+                .iter()
+                .find(|c| c.south_west.longitude <= lon && lon < c.south_west.longitude + heatmap.lon_step)
+                .unwrap()
+                .occupancy
+        };
+        // A's transmissions overlap: busy 0-4s
+        assert!((occupancy_near(0.0) - 0.4).abs() < 1e-9);
+        assert!((occupancy_near(0.1 - 1e-9) - 0.1).abs() < 1e-9);
+        assert_eq!(heatmap.saturated_cells(), heatmap.cells.iter().filter(|c| c.occupancy >= 0.3).count());
+        assert!(heatmap.saturated_cells() > 0);
+        assert!((heatmap.max_occupancy() - 0.4).abs() < 1e-9);
+        assert_eq!(heatmap.node_duty_cycle[0].2, 0.5);
+    }
+}
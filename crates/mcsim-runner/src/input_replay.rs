@@ -0,0 +1,310 @@
+//! Recording and deterministic replay of a run's external inputs.
+//!
+//! Given the same models and seed the simulation is deterministic; what makes
+//! a run unrepeatable are its inputs from outside: a random seed, model files
+//! edited since, and serial data typed into the UART bridge in realtime mode.
+//! `--record` captures all of these in a replay file:
+//!
+//! ```text
+//! mcsim run model.yaml --record session.json
+//! mcsim run --replay session.json
+//! ```
+//!
+//! Replaying loads the embedded models with the recorded seed and re-injects
+//! each serial input at the same point of the event sequence it entered the
+//! original run (after the same number of processed events, with the same
+//! event ID), as fast as possible rather than in real time. The replay stops
+//! after the recorded number of events and compares a digest of the processed
+//! event sequence against the recording, so a divergence is reported rather
+//! than silently producing a different run.
+//!
+//! Not to be confused with `mcsim replay`, which plays back a Rerun recording.
+
+use std::collections::VecDeque;
+use std::path::Path;
+
+use mcsim_common::{EntityId, Event, EventId, EventPayload, SerialRxEvent};
+use serde::{Deserialize, Serialize};
+
+use crate::{RunnerError, SimTime};
+
+/// Replay file format version.
+pub const REPLAY_FILE_VERSION: u32 = 1;
+
+/// A model file as it was when the run was recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedModel {
+    /// Path the model was loaded from.
+    pub path: String,
+    /// File contents.
+    pub content: String,
+}
+
+/// Serial data injected into a node from outside the simulation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerialInjection {
+    /// Number of events processed before the injection.
+    pub after_events: u64,
+    /// Simulation time of the injected event in microseconds.
+    pub time_us: u64,
+    /// ID of the injected event.
+    pub event_id: u64,
+    /// Firmware entity the data was sent to.
+    pub entity: u64,
+    /// Node name, for reading the file.
+    pub node: String,
+    /// Injected bytes (hex).
+    pub data_hex: String,
+}
+
+impl SerialInjection {
+    /// Record a `SerialRx` event injected into `node`.
+    pub fn from_event(event: &Event, node: &str, after_events: u64) -> Option<Self> {
+        let EventPayload::SerialRx(ref rx) = event.payload else {
+            return None;
+        };
+        Some(Self {
+            after_events,
+            time_us: event.time.as_micros(),
+            event_id: event.id.0,
+            entity: event.targets.first().map_or(event.source.0, |t| t.0),
+            node: node.to_string(),
+            data_hex: hex::encode(&rx.data),
+        })
+    }
+
+    /// The injected event, with the ID assigned by the replaying run.
+    pub fn to_event(&self, id: u64) -> Result<Event, RunnerError> {
+        let data = hex::decode(&self.data_hex).map_err(|e| {
+            RunnerError::ConfigError(format!("Invalid serial data for node '{}' in replay file: {}", self.node, e))
+        })?;
+        Ok(Event {
+            id: EventId(id),
+            time: SimTime::from_micros(self.time_us),
+            source: EntityId::new(self.entity),
+            targets: vec![EntityId::new(self.entity)],
+            payload: EventPayload::SerialRx(SerialRxEvent { data }),
+        })
+    }
+}
+
+/// What a recorded run did, for checking a replay against it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunOutcome {
+    /// Events processed.
+    pub events: u64,
+    /// Final simulation time in microseconds.
+    pub end_time_us: u64,
+    /// Digest of the processed event sequence (see [`EventDigest`]).
+    pub digest: u64,
+}
+
+/// Everything needed to reproduce a run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFile {
+    /// File format version.
+    pub version: u32,
+    /// Simulation seed.
+    pub seed: u64,
+    /// Requested duration in seconds (None for a realtime run).
+    pub duration_s: Option<f64>,
+    /// Model files in merge order.
+    pub models: Vec<RecordedModel>,
+    /// External serial inputs in the order they were injected.
+    pub injections: Vec<SerialInjection>,
+    /// Outcome of the recorded run.
+    pub outcome: RunOutcome,
+}
+
+impl ReplayFile {
+    /// Read a replay file.
+    pub fn load(path: &Path) -> Result<Self, RunnerError> {
+        let file: ReplayFile = serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(path)?))?;
+        if file.version != REPLAY_FILE_VERSION {
+            return Err(RunnerError::ConfigError(format!(
+                "Unsupported replay file version {} in {} (expected {})",
+                file.version,
+                path.display(),
+                REPLAY_FILE_VERSION
+            )));
+        }
+        Ok(file)
+    }
+
+    /// Write the replay file.
+    pub fn save(&self, path: &Path) -> Result<(), RunnerError> {
+        serde_json::to_writer_pretty(std::io::BufWriter::new(std::fs::File::create(path)?), self)?;
+        Ok(())
+    }
+
+    /// The recorded models, merged in order.
+    pub fn load_model(&self) -> Result<mcsim_model::Model, RunnerError> {
+        let contents: Vec<&str> = self.models.iter().map(|m| m.content.as_str()).collect();
+        Ok(mcsim_model::load_models_from_str(&contents)?)
+    }
+
+    /// Simulation time to run the replay to. A realtime run stopped at an
+    /// arbitrary point, so its replay runs past the recorded end time and
+    /// stops on the event count instead.
+    pub fn run_duration(&self) -> SimTime {
+        match self.duration_s {
+            Some(secs) => SimTime::from_secs(secs),
+            None => SimTime::from_micros(self.outcome.end_time_us + 1),
+        }
+    }
+}
+
+/// FNV-1a digest over the time, ID, source and targets of each processed
+/// event. Two runs with the same digest processed the same events in the same
+/// order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventDigest(u64);
+
+impl Default for EventDigest {
+    fn default() -> Self {
+        EventDigest(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl EventDigest {
+    /// Fold an event into the digest.
+    pub fn update(&mut self, event: &Event) {
+        let targets = event.targets.iter().map(|t| t.0);
+        for word in [event.time.as_micros(), event.id.0, event.source.0].into_iter().chain(targets) {
+            for byte in word.to_le_bytes() {
+                self.0 ^= byte as u64;
+                self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+
+    /// Current digest value.
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Input recording or replay state owned by the event loop.
+#[derive(Debug, Clone, Default)]
+pub struct InputLog {
+    recorded: Vec<SerialInjection>,
+    pending: VecDeque<SerialInjection>,
+    stop_after: Option<u64>,
+    digest: EventDigest,
+}
+
+impl InputLog {
+    /// Record injected inputs.
+    pub fn recording() -> Self {
+        Self::default()
+    }
+
+    /// Re-inject the inputs of a recorded run and stop after its event count.
+    pub fn replaying(file: &ReplayFile) -> Self {
+        Self {
+            pending: file.injections.iter().cloned().collect(),
+            stop_after: Some(file.outcome.events),
+            ..Self::default()
+        }
+    }
+
+    /// Note an input injected from outside the simulation.
+    pub fn record(&mut self, injection: SerialInjection) {
+        self.recorded.push(injection);
+    }
+
+    /// Next replayed input due after `events_processed` events.
+    pub fn next_due(&mut self, events_processed: u64) -> Option<SerialInjection> {
+        if self.pending.front()?.after_events <= events_processed {
+            self.pending.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Fold a processed event into the digest. Returns true once a replay
+    /// has processed as many events as the recorded run.
+    pub fn observe(&mut self, event: &Event, events_processed: u64) -> bool {
+        self.digest.update(event);
+        self.stop_after.is_some_and(|limit| events_processed >= limit)
+    }
+
+    /// Inputs recorded so far.
+    pub fn recorded(&self) -> &[SerialInjection] {
+        &self.recorded
+    }
+
+    /// Replayed inputs not injected yet.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Digest of the events processed so far.
+    pub fn digest(&self) -> u64 {
+        self.digest.value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serial_rx(time_us: u64, id: u64, entity: u64, data: &[u8]) -> Event {
+        Event {
+            id: EventId(id),
+            time: SimTime::from_micros(time_us),
+            source: EntityId::new(entity),
+            targets: vec![EntityId::new(entity)],
+            payload: EventPayload::SerialRx(SerialRxEvent { data: data.to_vec() }),
+        }
+    }
+
+    #[test]
+    fn test_injection_round_trip() {
+        let event = serial_rx(1_500, 42, 3, &[0x01, 0xff]);
+        let injection = SerialInjection::from_event(&event, "Alice", 17).unwrap();
+        assert_eq!(injection.data_hex, "01ff");
+        assert_eq!(injection.after_events, 17);
+
+        let replayed = injection.to_event(42).unwrap();
+        assert_eq!(replayed.time, event.time);
+        assert_eq!(replayed.targets, event.targets);
+        assert!(matches!(replayed.payload, EventPayload::SerialRx(ref rx) if rx.data == [0x01, 0xff]));
+
+        let timer = Event { payload: EventPayload::Timer { timer_id: 1 }, ..event };
+        assert!(SerialInjection::from_event(&timer, "Alice", 0).is_none());
+    }
+
+    #[test]
+    fn test_replay_schedule_and_digest() {
+        let injection = |after_events| SerialInjection::from_event(&serial_rx(0, 1, 1, b"x"), "A", after_events).unwrap();
+        let file = ReplayFile {
+            version: REPLAY_FILE_VERSION,
+            seed: 7,
+            duration_s: None,
+            models: vec![RecordedModel { path: "m.yaml".to_string(), content: "nodes: []\n".to_string() }],
+            injections: vec![injection(0), injection(2), injection(2)],
+            outcome: RunOutcome { events: 3, end_time_us: 10, digest: 0 },
+        };
+        let mut log = InputLog::replaying(&file);
+        assert!(log.next_due(0).is_some());
+        assert!(log.next_due(1).is_none());
+        assert!(log.next_due(2).is_some());
+        assert!(log.next_due(2).is_some());
+        assert_eq!(log.pending(), 0);
+        assert_eq!(file.run_duration(), SimTime::from_micros(11));
+
+        let event = serial_rx(5, 9, 1, b"x");
+        assert!(!log.observe(&event, 2));
+        assert!(log.observe(&event, 3));
+
+        // Order matters to the digest
+        let (a, b) = (serial_rx(5, 1, 1, b""), serial_rx(5, 2, 1, b""));
+        let (mut ab, mut ba) = (EventDigest::default(), EventDigest::default());
+        ab.update(&a);
+        ab.update(&b);
+        ba.update(&b);
+        ba.update(&a);
+        assert_ne!(ab, ba);
+    }
+}
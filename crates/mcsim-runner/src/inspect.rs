@@ -0,0 +1,651 @@
+//! Interactive inspection of a live simulation.
+//!
+//! The [`Inspector`] executes line-oriented commands against an
+//! [`EventLoop`], so a simulation can be stepped and queried from a terminal
+//! without writing a client:
+//!
+//! ```text
+//! mcsim> run 10m
+//! mcsim> nodes
+//! mcsim> node Alice
+//! mcsim> events 5
+//! mcsim> queue Repeater1
+//! mcsim> metrics mcsim.radio
+//! mcsim> metrics off mcsim.timing
+//! mcsim> send Repeater1 neighbors
+//! mcsim> move Alice 47.61 -122.33
+//! mcsim> links Alice
+//! ```
+//!
+//! `mcsim inspect` advances time with `step`, `run` and `until`. With
+//! `mcsim run --interactive` the simulation runs on its own and the console
+//! sends each line over the [`control`](crate::control) lane instead:
+//! `pause` and `resume` hold and release simulation time, `pace` sets how
+//! fast it advances against the wall clock, and the other
+//! commands are executed between events.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use mcsim_common::{Event, EventPayload, GeoCoord};
+use meshcore_packet::MeshCorePacket;
+
+use crate::metrics_export::InMemoryRecorder;
+use crate::realtime::Pacing;
+use crate::watchdog::describe_event_payload;
+use crate::{EventLoop, RunnerError, SimTime};
+
+/// Help text listing the available commands.
+pub const HELP: &str = "\
+Commands:
+  time                 Show the current simulation time and queue depth
+  step [N]             Process the next N events (default 1)
+  run <DURATION>       Advance simulation time by DURATION (e.g. 30, 90s, 10m, 1h)
+  until <TIME>         Advance to absolute simulation TIME
+  nodes                List nodes with position, TX/RX counts and last RX
+  node <NAME>          Show details for one node
+  events [N]           Show the next N pending events (default 10)
+  queue <NAME>         Show a repeater's outbound packet queue
+  metrics [PREFIX]     Show current metric values, optionally filtered by name prefix
+  metrics on|off [CATEGORY]
+                       Turn metrics recording on or off, for all metrics or one name prefix
+  send <NAME> <TEXT>   Send a CLI command line to a node's serial port
+  move <NAME> <LAT> <LON>
+                       Move a node; its links are recomputed from the path loss model
+  links [NAME]         Show radio links (mean SNR at 20 dBm, RSSI), optionally of one node
+  reload               Re-read the traffic scripts from the model files and restart the agents' rules
+  pause                Hold simulation time (run --interactive)
+  resume               Continue after a pause (run --interactive)
+  pace <PACING>        Pace simulation time: fast, realtime or a multiplier like 10x (run --interactive)
+  help                 Show this help
+  quit                 Exit";
+
+/// A parsed inspector command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InspectCommand {
+    /// Show the current time.
+    Time,
+    /// Process N events.
+    Step(u64),
+    /// Advance simulation time by a duration.
+    Run(SimTime),
+    /// Advance to an absolute simulation time.
+    Until(SimTime),
+    /// List nodes.
+    Nodes,
+    /// Show one node.
+    Node(String),
+    /// Show pending events.
+    Events(usize),
+    /// Show a node's outbound packet queue.
+    Queue(String),
+    /// Show metric values with an optional name prefix.
+    Metrics(Option<String>),
+    /// Turn metrics recording on or off, optionally for one category.
+    RecordMetrics(Option<String>, bool),
+    /// Send a command line to a node's serial port.
+    Send(String, String),
+    /// Move a node.
+    Move(String, GeoCoord),
+    /// Show radio links, optionally of one node.
+    Links(Option<String>),
+    /// Reload the agents' traffic scripts.
+    Reload,
+    /// Hold simulation time of a running simulation.
+    Pause,
+    /// Continue a paused simulation.
+    Resume,
+    /// Change how a running simulation is paced against the wall clock.
+    Pace(Pacing),
+    /// Show help.
+    Help,
+    /// Exit the inspector.
+    Quit,
+}
+
+/// Parse a time with an optional `s`, `m` or `h` suffix into a [`SimTime`].
+fn parse_time(s: &str) -> Result<SimTime, String> {
+    let (number, multiplier) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1.0),
+        Some((i, 'm')) => (&s[..i], 60.0),
+        Some((i, 'h')) => (&s[..i], 3600.0),
+        _ => (s, 1.0),
+    };
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid time '{}' (expected e.g. 30, 90s, 10m, 1h)", s))?;
+    if value < 0.0 {
+        return Err(format!("Time must be non-negative: '{}'", s));
+    }
+    Ok(SimTime::from_secs(value * multiplier))
+}
+
+impl std::str::FromStr for InspectCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("help");
+        let arg = words.next();
+        let count = |default| {
+            arg.map(|a| a.parse().map_err(|_| format!("Invalid count '{}'", a)))
+                .unwrap_or(Ok(default))
+        };
+        match command {
+            "time" | "t" => Ok(InspectCommand::Time),
+            "step" | "s" => Ok(InspectCommand::Step(count(1)?)),
+            "run" | "r" => {
+                let arg = arg.ok_or("Usage: run <DURATION>")?;
+                Ok(InspectCommand::Run(parse_time(arg)?))
+            }
+            "until" | "u" => {
+                let arg = arg.ok_or("Usage: until <TIME>")?;
+                Ok(InspectCommand::Until(parse_time(arg)?))
+            }
+            "nodes" | "n" => Ok(InspectCommand::Nodes),
+            "node" => {
+                let name = arg.ok_or("Usage: node <NAME>")?;
+                Ok(InspectCommand::Node(name.to_string()))
+            }
+            "events" | "e" => Ok(InspectCommand::Events(count(10)? as usize)),
+            "queue" => {
+                let name = arg.ok_or("Usage: queue <NAME>")?;
+                Ok(InspectCommand::Queue(name.to_string()))
+            }
+            "metrics" | "m" => match arg {
+                Some(state @ ("on" | "off")) => {
+                    Ok(InspectCommand::RecordMetrics(words.next().map(str::to_string), state == "on"))
+                }
+                _ => Ok(InspectCommand::Metrics(arg.map(str::to_string))),
+            },
+            "send" => {
+                let usage = "Usage: send <NAME> <TEXT>";
+                let name = arg.ok_or(usage)?;
+                // The text keeps its inner spacing
+                let text = line.trim_start()[command.len()..]
+                    .trim_start()
+                    .get(name.len()..)
+                    .map(str::trim)
+                    .filter(|text| !text.is_empty())
+                    .ok_or(usage)?;
+                Ok(InspectCommand::Send(name.to_string(), text.to_string()))
+            }
+            "move" => {
+                let usage = "Usage: move <NAME> <LAT> <LON>";
+                let name = arg.ok_or(usage)?;
+                let mut coord = || -> Result<f64, String> {
+                    let value = words.next().ok_or(usage)?;
+                    value.parse().map_err(|_| format!("Invalid coordinate '{}'", value))
+                };
+                let (latitude, longitude) = (coord()?, coord()?);
+                if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                    return Err(format!("Position out of range: {}, {}", latitude, longitude));
+                }
+                Ok(InspectCommand::Move(name.to_string(), GeoCoord::new(latitude, longitude)))
+            }
+            "links" | "l" => Ok(InspectCommand::Links(arg.map(str::to_string))),
+            "reload" => Ok(InspectCommand::Reload),
+            "pause" | "p" => Ok(InspectCommand::Pause),
+            "resume" | "continue" | "c" => Ok(InspectCommand::Resume),
+            "pace" => {
+                let arg = arg.ok_or("Usage: pace <fast|realtime|MULTIPLIER>")?;
+                Ok(InspectCommand::Pace(arg.parse()?))
+            }
+            "help" | "h" | "?" => Ok(InspectCommand::Help),
+            "quit" | "exit" | "q" => Ok(InspectCommand::Quit),
+            other => Err(format!("Unknown command '{}' (try 'help')", other)),
+        }
+    }
+}
+
+/// Executes [`InspectCommand`]s against a simulation.
+pub struct Inspector {
+    recorder: Option<Arc<InMemoryRecorder>>,
+    /// Entity ID to display name ("node" or "node/role").
+    entity_names: HashMap<u64, String>,
+}
+
+impl Inspector {
+    /// Create an inspector for `event_loop`'s nodes. `recorder` is used by
+    /// the `metrics` command.
+    pub fn new(event_loop: &EventLoop, recorder: Option<Arc<InMemoryRecorder>>) -> Self {
+        let mut entity_names = HashMap::new();
+        for info in event_loop.node_infos() {
+            entity_names.insert(info.firmware_entity_id, info.name.clone());
+            entity_names.insert(info.radio_entity_id, format!("{}/radio", info.name));
+            if let Some(id) = info.agent_entity_id {
+                entity_names.insert(id, format!("{}/agent", info.name));
+            }
+            if let Some(id) = info.cli_agent_entity_id {
+                entity_names.insert(id, format!("{}/cli", info.name));
+            }
+        }
+        Inspector {
+            recorder,
+            entity_names,
+        }
+    }
+
+    fn entity_name(&self, id: u64) -> String {
+        self.entity_names
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| format!("#{}", id))
+    }
+
+    /// Execute a command against `event_loop`, writing its output to `out`.
+    ///
+    /// Returns `false` when the inspector should exit.
+    pub fn execute(
+        &mut self,
+        event_loop: &mut EventLoop,
+        command: &InspectCommand,
+        out: &mut dyn Write,
+    ) -> Result<bool, RunnerError> {
+        match command {
+            InspectCommand::Time => self.print_time(event_loop, out)?,
+            InspectCommand::Step(n) => {
+                let mut processed = 0;
+                while processed < *n && event_loop.step()?.is_some() {
+                    processed += 1;
+                }
+                writeln!(out, "Processed {} event(s)", processed)?;
+                self.print_time(event_loop, out)?;
+            }
+            InspectCommand::Run(duration) => {
+                let target = event_loop.current_time() + *duration;
+                let processed = event_loop.run_until(target)?;
+                writeln!(out, "Processed {} event(s)", processed)?;
+                self.print_time(event_loop, out)?;
+            }
+            InspectCommand::Until(time) => {
+                let processed = event_loop.run_until(*time)?;
+                writeln!(out, "Processed {} event(s)", processed)?;
+                self.print_time(event_loop, out)?;
+            }
+            InspectCommand::Nodes => self.print_nodes(event_loop, out)?,
+            InspectCommand::Node(name) => self.print_node(event_loop, name, out)?,
+            InspectCommand::Events(n) => self.print_events(event_loop, *n, out)?,
+            InspectCommand::Queue(name) => self.print_queue(event_loop, name, out)?,
+            InspectCommand::Metrics(prefix) => self.print_metrics(prefix.as_deref(), out)?,
+            InspectCommand::RecordMetrics(category, enabled) => {
+                let state = if *enabled { "on" } else { "off" };
+                match (&self.recorder, category) {
+                    (None, _) => writeln!(out, "Metrics are not being recorded")?,
+                    (Some(recorder), Some(category)) => {
+                        recorder.set_category_enabled(category, *enabled);
+                        writeln!(out, "Recording of {} metrics {}", category, state)?;
+                    }
+                    (Some(recorder), None) => {
+                        recorder.set_enabled(*enabled);
+                        writeln!(out, "Metrics recording {}", state)?;
+                    }
+                }
+            }
+            InspectCommand::Send(name, text) => {
+                let mut data = text.clone().into_bytes();
+                data.push(b'\r');
+                if event_loop.send_serial(name, data) {
+                    writeln!(out, "Sent to {}; its serial output is shown as it arrives", name)?;
+                } else {
+                    writeln!(out, "No node named '{}'", name)?;
+                }
+            }
+            InspectCommand::Move(name, position) => {
+                if event_loop.move_node(name, *position) {
+                    writeln!(out, "Moved {} to {:.6}, {:.6}", name, position.latitude, position.longitude)?;
+                } else {
+                    writeln!(out, "No node named '{}'", name)?;
+                }
+            }
+            InspectCommand::Links(name) => self.print_links(event_loop, name.as_deref(), out)?,
+            InspectCommand::Reload => match event_loop.reload_scripts() {
+                Ok(rules) => writeln!(out, "Reloaded {} traffic rule(s); they take effect from the next event", rules)?,
+                Err(e) => writeln!(out, "Reload failed, keeping current rules: {}", e)?,
+            },
+            InspectCommand::Pause | InspectCommand::Resume => {
+                writeln!(out, "Only a running simulation (mcsim run --interactive) can be paused and resumed")?
+            }
+            InspectCommand::Pace(_) => writeln!(out, "Only a running simulation (mcsim run --interactive) can be paced")?,
+            InspectCommand::Help => writeln!(out, "{}", HELP)?,
+            InspectCommand::Quit => return Ok(false),
+        }
+        for line in event_loop.take_serial_echo() {
+            writeln!(out, "{}", line)?;
+        }
+        Ok(true)
+    }
+
+    /// Execute a command line sent to a running simulation. Time only
+    /// advances with the run, and pausing, resuming and quitting are up to
+    /// whoever holds the [`ControlHandle`](crate::ControlHandle).
+    pub(crate) fn execute_live(
+        &mut self,
+        event_loop: &mut EventLoop,
+        line: &str,
+        out: &mut dyn Write,
+    ) -> Result<(), RunnerError> {
+        match line.parse::<InspectCommand>() {
+            Ok(
+                InspectCommand::Step(_)
+                | InspectCommand::Run(_)
+                | InspectCommand::Until(_)
+                | InspectCommand::Pause
+                | InspectCommand::Resume
+                | InspectCommand::Quit,
+            ) => writeln!(out, "'{}' is not available while the simulation runs", line.trim())?,
+            Ok(command) => {
+                self.execute(event_loop, &command, out)?;
+            }
+            Err(e) => writeln!(out, "{}", e)?,
+        }
+        Ok(())
+    }
+
+    fn print_time(&self, event_loop: &EventLoop, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "t={:.3}s, {} events processed, {} pending",
+            event_loop.current_time().as_secs_f64(),
+            event_loop.stats().total_events,
+            event_loop.pending_event_count()
+        )
+    }
+
+    fn print_nodes(&self, event_loop: &EventLoop, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "{:<20} {:<11} {:>10} {:>11} {:>6} {:>6} {:>10}",
+            "NAME", "TYPE", "LAT", "LON", "TX", "RX", "LAST_RX"
+        )?;
+        for info in event_loop.node_infos() {
+            let stats = event_loop.node_stats().get(&info.radio_entity_id);
+            let last_rx = stats
+                .and_then(|s| s.last_rx.as_ref())
+                .map(|rx| format!("{:.3}s", rx.time_us as f64 / 1e6))
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                out,
+                "{:<20} {:<11} {:>10.5} {:>11.5} {:>6} {:>6} {:>10}",
+                info.name,
+                info.node_type,
+                info.location.latitude,
+                info.location.longitude,
+                stats.map_or(0, |s| s.tx),
+                stats.map_or(0, |s| s.rx),
+                last_rx
+            )?;
+        }
+        Ok(())
+    }
+
+    fn print_node(&self, event_loop: &EventLoop, name: &str, out: &mut dyn Write) -> std::io::Result<()> {
+        let Some(info) = event_loop.node_infos().iter().find(|i| i.name == name) else {
+            return writeln!(out, "No node named '{}'", name);
+        };
+        writeln!(out, "{} ({})", info.name, info.node_type)?;
+        writeln!(out, "  public key:  {}", hex::encode(&info.public_key[..8]))?;
+        write!(
+            out,
+            "  position:    {:.6}, {:.6}",
+            info.location.latitude, info.location.longitude
+        )?;
+        match info.location.altitude_m {
+            Some(alt) => writeln!(out, " ({:.1} m)", alt)?,
+            None => writeln!(out)?,
+        }
+        writeln!(
+            out,
+            "  entities:    firmware={} radio={} agent={:?} cli={:?}",
+            info.firmware_entity_id, info.radio_entity_id, info.agent_entity_id, info.cli_agent_entity_id
+        )?;
+        if let Some(stats) = event_loop.node_stats().get(&info.radio_entity_id) {
+            writeln!(
+                out,
+                "  packets:     tx={} rx={} collisions={}",
+                stats.tx, stats.rx, stats.collisions
+            )?;
+            match &stats.last_rx {
+                Some(rx) => writeln!(
+                    out,
+                    "  last rx:     t={:.3}s from {} snr={:.1}dB rssi={:.1}dBm",
+                    rx.time_us as f64 / 1e6,
+                    self.entity_name(rx.source_radio_id),
+                    rx.snr_db,
+                    rx.rssi_dbm
+                )?,
+                None => writeln!(out, "  last rx:     -")?,
+            }
+        }
+        Ok(())
+    }
+
+    fn print_events(&self, event_loop: &EventLoop, limit: usize, out: &mut dyn Write) -> std::io::Result<()> {
+        let pending = event_loop.pending_events(limit);
+        if pending.is_empty() {
+            return writeln!(out, "No pending events");
+        }
+        for event in pending {
+            let (event_type, details) = describe_event_payload(&event.payload);
+            let targets: Vec<String> = event.targets.iter().map(|t| self.entity_name(t.0)).collect();
+            writeln!(
+                out,
+                "{:>12.6}s  {:<18} {} -> [{}] {}",
+                event.time.as_secs_f64(),
+                event_type,
+                self.entity_name(event.source.0),
+                targets.join(", "),
+                details
+            )?;
+        }
+        Ok(())
+    }
+
+    fn print_queue(&self, event_loop: &EventLoop, name: &str, out: &mut dyn Write) -> std::io::Result<()> {
+        if event_loop.node_infos().iter().all(|i| i.name != name) {
+            return writeln!(out, "No node named '{}'", name);
+        }
+        let Some(queue) = event_loop.outbound_queue(name) else {
+            return writeln!(out, "{} has no inspectable outbound queue", name);
+        };
+        writeln!(out, "{}: {} queued, {} due", name, queue.packets.len(), queue.due)?;
+        for (index, packet) in queue.packets.iter().enumerate() {
+            writeln!(out, "  {:>3}  {}", index, describe_queued_packet(packet))?;
+        }
+        Ok(())
+    }
+
+    fn print_links(&self, event_loop: &EventLoop, name: Option<&str>, out: &mut dyn Write) -> std::io::Result<()> {
+        let radio = match name {
+            Some(name) => match event_loop.node_infos().iter().find(|i| i.name == name) {
+                Some(info) => Some(info.radio_entity_id),
+                None => return writeln!(out, "No node named '{}'", name),
+            },
+            None => None,
+        };
+        let links: Vec<_> = event_loop
+            .links()
+            .into_iter()
+            .filter(|link| radio.is_none_or(|r| link.from.0 == r || link.to.0 == r))
+            .collect();
+        if links.is_empty() {
+            return writeln!(out, "No links");
+        }
+        writeln!(out, "{:<24} {:<24} {:>9} {:>10}", "FROM", "TO", "SNR", "RSSI")?;
+        for link in links {
+            writeln!(
+                out,
+                "{:<24} {:<24} {:>7.1}dB {:>7.1}dBm",
+                self.entity_name(link.from.0),
+                self.entity_name(link.to.0),
+                link.mean_snr_db_at20dbm,
+                link.rssi_dbm
+            )?;
+        }
+        Ok(())
+    }
+
+    fn print_metrics(&self, prefix: Option<&str>, out: &mut dyn Write) -> std::io::Result<()> {
+        let Some(recorder) = &self.recorder else {
+            return writeln!(out, "Metrics recording is not enabled");
+        };
+        let snapshot = recorder.snapshot();
+        let matches = |name: &str| prefix.is_none_or(|p| name.starts_with(p));
+        for (name, value) in snapshot.counters.iter().filter(|(n, _)| matches(n)) {
+            writeln!(out, "{:<50} {}", name, value)?;
+        }
+        for (name, value) in snapshot.gauges.iter().filter(|(n, _)| matches(n)) {
+            writeln!(out, "{:<50} {:.3}", name, value)?;
+        }
+        for (name, h) in snapshot.histograms.iter().filter(|(n, _)| matches(n)) {
+            writeln!(
+                out,
+                "{:<50} count={} mean={:.3} p50={:.3} p99={:.3}",
+                name, h.count, h.mean, h.p50, h.p99
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Serial output of nodes sent console commands, split into lines for
+/// display.
+#[derive(Debug, Default)]
+pub(crate) struct SerialEcho {
+    /// Watched firmware entity IDs, with node name and unterminated output.
+    nodes: HashMap<u64, (String, String)>,
+    lines: Vec<String>,
+}
+
+impl SerialEcho {
+    /// Collect the serial output of a firmware entity.
+    pub(crate) fn watch(&mut self, firmware_entity_id: u64, name: String) {
+        self.nodes.entry(firmware_entity_id).or_insert((name, String::new()));
+    }
+
+    /// Collect the output carried by an event, if it is from a watched node.
+    /// Only the self-targeted copy of firmware output is used, as for the
+    /// UART bridge.
+    pub(crate) fn record(&mut self, event: &Event) {
+        let EventPayload::SerialTx(tx) = &event.payload else {
+            return;
+        };
+        if !event.targets.contains(&event.source) {
+            return;
+        }
+        let Some((name, partial)) = self.nodes.get_mut(&event.source.0) else {
+            return;
+        };
+        partial.push_str(&String::from_utf8_lossy(&tx.data));
+        while let Some(end) = partial.find(['\r', '\n']) {
+            let line: String = partial.drain(..=end).collect();
+            let line = line.trim();
+            if !line.is_empty() {
+                self.lines.push(format!("[{:.3}s] {}: {}", event.time.as_secs_f64(), name, line));
+            }
+        }
+    }
+
+    /// Lines collected since the last call.
+    pub(crate) fn take_lines(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.lines)
+    }
+}
+
+/// One-line summary of a raw queued packet: length, route, payload type,
+/// path length and payload hash.
+pub fn describe_queued_packet(data: &[u8]) -> String {
+    match MeshCorePacket::decode(data) {
+        Ok(packet) => format!(
+            "len={:<3} {:<16} {:<14} path={:<2} hash={}",
+            data.len(),
+            packet.route_type(),
+            packet.payload_type(),
+            packet.path_len(),
+            packet.payload_hash_label().as_label()
+        ),
+        Err(_) => format!("len={:<3} undecodable {}", data.len(), hex::encode(data)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!("step".parse(), Ok(InspectCommand::Step(1)));
+        assert_eq!("step 25".parse(), Ok(InspectCommand::Step(25)));
+        assert_eq!("run 10m".parse(), Ok(InspectCommand::Run(SimTime::from_secs(600.0))));
+        assert_eq!("until 90".parse(), Ok(InspectCommand::Until(SimTime::from_secs(90.0))));
+        assert_eq!("node Alice".parse(), Ok(InspectCommand::Node("Alice".to_string())));
+        assert_eq!("events".parse(), Ok(InspectCommand::Events(10)));
+        assert_eq!(
+            "metrics mcsim.radio".parse(),
+            Ok(InspectCommand::Metrics(Some("mcsim.radio".to_string())))
+        );
+        assert_eq!("queue Repeater1".parse(), Ok(InspectCommand::Queue("Repeater1".to_string())));
+        assert_eq!("q".parse(), Ok(InspectCommand::Quit));
+        assert!("queue".parse::<InspectCommand>().is_err());
+        assert!("run".parse::<InspectCommand>().is_err());
+        assert!("run soon".parse::<InspectCommand>().is_err());
+        assert!("teleport".parse::<InspectCommand>().is_err());
+
+        assert_eq!(
+            "send Repeater1  set  name R1 ".parse(),
+            Ok(InspectCommand::Send("Repeater1".to_string(), "set  name R1".to_string()))
+        );
+        assert_eq!(
+            "move Alice 47.61 -122.33".parse(),
+            Ok(InspectCommand::Move("Alice".to_string(), GeoCoord::new(47.61, -122.33)))
+        );
+        assert_eq!("metrics mcsim.radio".parse(), Ok(InspectCommand::Metrics(Some("mcsim.radio".to_string()))));
+        assert_eq!("metrics off".parse(), Ok(InspectCommand::RecordMetrics(None, false)));
+        assert_eq!(
+            "metrics on mcsim.timing".parse(),
+            Ok(InspectCommand::RecordMetrics(Some("mcsim.timing".to_string()), true))
+        );
+        assert_eq!("links".parse(), Ok(InspectCommand::Links(None)));
+        assert_eq!("reload".parse(), Ok(InspectCommand::Reload));
+        assert_eq!("pause".parse(), Ok(InspectCommand::Pause));
+        assert_eq!("continue".parse(), Ok(InspectCommand::Resume));
+        assert_eq!("pace 10x".parse(), Ok(InspectCommand::Pace(Pacing::Scaled(10.0))));
+        assert!("pace".parse::<InspectCommand>().is_err());
+        assert!("send Repeater1".parse::<InspectCommand>().is_err());
+        assert!("move Alice 47.61".parse::<InspectCommand>().is_err());
+        assert!("move Alice 147.61 0".parse::<InspectCommand>().is_err());
+    }
+
+    #[test]
+    fn test_serial_echo_lines() {
+        use mcsim_common::{EntityId, EventId, SerialTxEvent, SimTime};
+
+        let tx = |source: u64, target: u64, data: &[u8]| Event {
+            id: EventId(1),
+            time: SimTime::from_secs(2.5),
+            source: EntityId::new(source),
+            targets: vec![EntityId::new(target)],
+            payload: EventPayload::SerialTx(SerialTxEvent { data: data.to_vec() }),
+        };
+        let mut echo = SerialEcho::default();
+        echo.watch(1, "R1".to_string());
+        echo.record(&tx(1, 1, b"  -> v1."));
+        echo.record(&tx(1, 2, b"agent copy\r\n"));
+        echo.record(&tx(3, 3, b"unwatched\r\n"));
+        assert!(echo.take_lines().is_empty());
+        echo.record(&tx(1, 1, b"14\r\n"));
+        assert_eq!(echo.take_lines(), vec!["[2.500s] R1: -> v1.14".to_string()]);
+    }
+
+    #[test]
+    fn test_describe_queued_packet() {
+        let advert = meshcore_packet::AdvertPayload::new([0u8; 32], 1234567890, [0u8; 64], "Node");
+        let packet = MeshCorePacket::advert(advert);
+        let line = describe_queued_packet(&packet.encode());
+        assert!(line.contains("FLOOD"), "{}", line);
+        assert!(line.contains(&packet.payload_hash_label().as_label()), "{}", line);
+
+        assert!(describe_queued_packet(&[]).contains("undecodable"));
+    }
+}
@@ -24,34 +24,123 @@
 //! - Speed multiplier for faster/slower than real-time simulation
 //! - Catch-up logic when simulation falls behind wall clock
 //! - Drift tracking and warnings
-
+//!
+//! ## Control Commands
+//!
+//! Pause, resume, stop and status commands sent through a [`ControlHandle`]
+//! are handled ahead of queued simulation events, so they take effect
+//! promptly however backlogged the event queue is; see [`control`].
+//!
+//! ## Features
+//!
+//! The default `cli` feature builds the `mcsim` tool. Without default features
+//! the crate is only the simulation engine, free of the command line, TCP and
+//! terrain dependencies, for embedding in other services. Parts can be added
+//! back individually:
+//! - `bridges`: UART TCP bridges, [`control_server`], [`metrics_server`] and
+//!   the multi-tenant [`sim_server`] (otherwise [`EventLoop`] runs with no
+//!   UART manager)
+//! - `planning`: coverage [`heatmap`]s and per-link SNR estimates from the
+//!   run's receive records
+//! - `dashboard`: the live web [`dashboard`], with `bridges`
+//! - `sqlite`: the per-packet event database of [`packet_db`]
+//! - `rerun`: live visualization through [`RerunLogger`]
+
+pub mod advert_report;
+pub mod alerts;
+pub mod artifact_budget;
+pub mod assertions;
+pub mod blast_radius;
+pub mod calibration;
+pub mod chrome_trace;
+pub mod control;
+#[cfg(feature = "bridges")]
+pub mod control_server;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
+pub mod event_digest;
+pub mod experiment;
+#[cfg(feature = "planning")]
+pub mod heatmap;
+pub mod input_replay;
+pub mod inspect;
+pub mod message_paths;
 pub mod metric_spec;
 pub mod metrics_export;
+#[cfg(feature = "bridges")]
+pub mod metrics_server;
+pub mod minimize;
+pub mod mqtt_bridge;
+pub mod outages;
+pub mod packet_capture;
+pub mod packet_db;
 mod packet_tracker;
 pub mod parallel_step;
 pub mod realtime;
 pub mod rerun_blueprint;
 pub mod rerun_logger;
+pub mod room_retention;
+pub mod script_reload;
+pub mod serial_capture;
+#[cfg(feature = "bridges")]
+pub mod sim_server;
+pub mod sla;
+pub mod timeline;
+pub mod timer_jitter;
+pub mod trace_diff;
+pub mod trace_playback;
 pub mod uart_server;
+#[cfg(feature = "bridges")]
+mod uart_pty;
+pub mod wall_clock;
 pub mod watchdog;
+pub mod what_if;
 
+use alerts::{AlertMonitor, FiredAlert};
+use artifact_budget::ArtifactBudget;
+use assertions::{AssertionMonitor, AssertionResult};
+use blast_radius::{DeliverySummary, DeliveryTracker};
 use mcsim_common::entity_tracer::EntityTracer;
-use mcsim_common::{EntityId, Event, EventPayload, SimContext};
+use input_replay::{InputLog, SerialInjection};
+use mqtt_bridge::MqttFeed;
+use inspect::{Inspector, SerialEcho};
+use message_paths::{MessagePathReport, PathTracker};
+use mcsim_common::{EntityId, Event, EventPayload, GeoCoord, LinkQuality, NodeId, SimContext};
 pub use mcsim_common::SimTime;
-use mcsim_model::BuiltSimulation;
+use mcsim_model::{AlertAction, BuiltSimulation, PowerOutage, TopologyReport};
+use outages::OutageImpact;
+use sla::{SlaReport, SlaThresholds};
 use packet_tracker::PacketTracker;
+use calibration::{CalibrationReport, CalibrationTolerances, CalibrationTracker};
+use chrome_trace::ChromeTrace;
+use control::{ControlLane, LaneOutcome, LaneTarget};
+pub use control::{ControlCommand, ControlHandle, ControlStatus, EventNotice, NodeStatus};
+use room_retention::{RoomRetentionTracker, RoomState};
+use event_digest::EventDigest;
+use script_reload::ScriptReloader;
+use packet_capture::PacketCapture;
+use serial_capture::SerialCapture;
+use wall_clock::WallClock;
+use timeline::{Timeline, TimelineTracker};
+use advert_report::{AdvertReport, AdvertTracker};
+use timer_jitter::TimerJitter;
 pub use parallel_step::{ParallelStepConfig, FirmwareStepOutput};
-pub use realtime::{RealTimeConfig, RealTimePacer, RealTimePacerStats, PeriodicStats};
+pub use realtime::{Pacing, RealTimeConfig, RealTimePacer, RealTimePacerStats, PeriodicStats};
 pub use rerun_logger::RerunLogger;
 use serde::Serialize;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::io::Write;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use thiserror::Error;
-pub use uart_server::SyncUartManager;
+pub use uart_server::{JitterDistribution, SerialLatency, SyncUartManager};
 pub use watchdog::{Watchdog, WatchdogState, CurrentEventInfo};
+use watchdog::describe_event_payload;
+
+/// Longest a paced run sleeps at a time, so Ctrl-C, control commands and
+/// UART input are handled promptly on all platforms.
+const MAX_PACING_SLEEP: Duration = Duration::from_millis(10);
 
 // ============================================================================
 // Error Types
@@ -109,6 +198,22 @@ pub struct NodeStats {
     pub rx: u64,
     /// Packets that collided when received by this node.
     pub collisions: u64,
+    /// Most recent intact reception.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_rx: Option<LastRx>,
+}
+
+/// Details of a node's most recent intact reception.
+#[derive(Debug, Clone, Serialize)]
+pub struct LastRx {
+    /// Reception time in microseconds.
+    pub time_us: u64,
+    /// Radio entity ID of the sender.
+    pub source_radio_id: u64,
+    /// Signal-to-noise ratio in dB.
+    pub snr_db: f64,
+    /// Received signal strength in dBm.
+    pub rssi_dbm: f64,
 }
 
 /// Statistics collected during simulation.
@@ -130,6 +235,23 @@ pub struct SimulationStats {
     pub simulation_time_us: u64,
     /// Wall clock time in milliseconds.
     pub wall_time_ms: u64,
+    /// Outcome of the scenario's assertions, if it has any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<AssertionResult>,
+    /// Delivery around the scenario's power outages, if it has any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub outages: Vec<OutageImpact>,
+    /// Partitions, single points of failure and centrality of the network
+    /// predicted from its links.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology: Option<TopologyReport>,
+    /// Posts and clients of each room server, if the scenario has any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rooms: Vec<RoomState>,
+    /// Simulation time (seconds) at which the artifact size cap stopped
+    /// tracing, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact_cap_reached_s: Option<f64>,
 }
 
 // ============================================================================
@@ -229,6 +351,19 @@ pub struct MessageReceivedPayload {
     pub direction: String,
 }
 
+/// Payload for an alert marker.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertPayload {
+    /// Name of the alert rule.
+    pub rule: String,
+    /// Watched metric.
+    pub metric: String,
+    /// The rule's condition.
+    pub condition: String,
+    /// Observed rate or value when the alert fired.
+    pub observed: f64,
+}
+
 /// Payload types for different trace events.
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type")]
@@ -248,6 +383,9 @@ pub enum TracePayload {
     /// Message received.
     #[serde(rename = "MESSAGE")]
     MessageReceived(MessageReceivedPayload),
+    /// Alert rule fired.
+    #[serde(rename = "ALERT")]
+    Alert(AlertPayload),
 }
 
 /// A trace entry for output.
@@ -259,6 +397,10 @@ pub struct TraceEntry {
     pub origin_id: String,
     /// Timestamp (ISO 8601).
     pub timestamp: String,
+    /// Simulation time in seconds, when `timestamp` is a wall-clock datetime
+    /// (see [`wall_clock`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sim_time_s: Option<f64>,
     /// Event-specific payload (flattened into this object).
     #[serde(flatten)]
     pub payload: TracePayload,
@@ -267,7 +409,10 @@ pub struct TraceEntry {
 /// Trace recorder for outputting simulation events.
 pub struct TraceRecorder {
     output: Option<Box<dyn Write>>,
-    entries: Vec<TraceEntry>,
+    /// Entries serialized as they will appear in the output array.
+    entries: Vec<String>,
+    budget: Option<ArtifactBudget>,
+    stopped: bool,
 }
 
 impl TraceRecorder {
@@ -276,19 +421,57 @@ impl TraceRecorder {
         TraceRecorder {
             output,
             entries: Vec::new(),
+            budget: None,
+            stopped: false,
         }
     }
 
+    /// Charge the serialized size of each recorded entry to `budget`.
+    pub fn set_budget(&mut self, budget: ArtifactBudget) {
+        self.budget = Some(budget);
+    }
+
+    /// Stop recording. Entries recorded so far are still written by
+    /// [`TraceRecorder::flush`].
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
     /// Record an event.
     pub fn record(&mut self, entry: TraceEntry) {
-        self.entries.push(entry);
+        if self.stopped || self.output.is_none() {
+            return;
+        }
+        // Entries are indented one level inside the array
+        let json = serde_json::to_string_pretty(&entry).unwrap_or_default();
+        let mut indented = String::with_capacity(json.len() + 2 * json.lines().count());
+        for (i, line) in json.lines().enumerate() {
+            if i > 0 {
+                indented.push('\n');
+            }
+            indented.push_str("  ");
+            indented.push_str(line);
+        }
+        if let Some(budget) = &self.budget {
+            // Plus the separator
+            budget.charge(indented.len() as u64 + 2);
+        }
+        self.entries.push(indented);
     }
 
     /// Flush all entries to output.
     pub fn flush(&mut self) -> Result<(), RunnerError> {
         if let Some(ref mut output) = self.output {
-            let json = serde_json::to_string_pretty(&self.entries)?;
-            writeln!(output, "{}", json)?;
+            if self.entries.is_empty() {
+                writeln!(output, "[]")?;
+            } else {
+                write!(output, "[")?;
+                for (i, entry) in self.entries.iter().enumerate() {
+                    let separator = if i == 0 { "\n" } else { ",\n" };
+                    write!(output, "{}{}", separator, entry)?;
+                }
+                writeln!(output, "\n]")?;
+            }
         }
         Ok(())
     }
@@ -313,6 +496,8 @@ pub struct EventLoop {
     node_stats: HashMap<u64, NodeStats>,
     /// Mapping from firmware entity ID to radio entity ID.
     firmware_to_radio: HashMap<u64, u64>,
+    /// Flood hop limit of each forwarding node, by radio entity ID.
+    flood_max: HashMap<u64, u8>,
     /// Mapping from radio entity ID to node name.
     radio_to_name: HashMap<u64, String>,
     /// Mapping from entity ID to (node_name, node_type) for metrics labels.
@@ -334,10 +519,58 @@ pub struct EventLoop {
     parallel_config: ParallelStepConfig,
     /// Configuration for real-time mode.
     realtime_config: RealTimeConfig,
+    /// Pacing requested through [`set_pacing`](Self::set_pacing) or the
+    /// control lane; overrides the run method's default.
+    pacing: Option<Pacing>,
     /// Optional metrics recorder for collecting metrics and logging to Rerun.
     metrics_recorder: Option<Arc<metrics_export::InMemoryRecorder>>,
     /// Metric specs for Rerun visualization.
     rerun_metric_specs: Vec<metric_spec::MetricSpec>,
+    /// Room server post history for retention metrics.
+    room_retention: RoomRetentionTracker,
+    /// Optional time-ordered serial capture of selected nodes.
+    serial_capture: Option<SerialCapture>,
+    /// Optional pcapng capture of over-the-air packets.
+    packet_capture: Option<PacketCapture>,
+    /// Optional SQLite database of per-packet radio events.
+    #[cfg(feature = "sqlite")]
+    packet_db: Option<packet_db::PacketDatabase>,
+    /// Optional Chrome trace-event export of dispatch timing.
+    chrome_trace: Option<ChromeTrace>,
+    /// Observed per-link SNR for comparison against the link model.
+    calibration: CalibrationTracker,
+    /// Optional random perturbation of timer delivery.
+    timer_jitter: Option<TimerJitter>,
+    /// Optional alert rules evaluated against the metrics recorder.
+    alerts: Option<AlertMonitor>,
+    /// Optional scenario assertions, evaluated at the end of the run.
+    assertions: Option<AssertionMonitor>,
+    /// Optional per-node bring-up timeline.
+    timeline: Option<TimelineTracker>,
+    /// Optional record of adverts sent and skipped, and neighbour discovery.
+    adverts: Option<AdvertTracker>,
+    /// Optional flood delivery record for blast radius and outage reports.
+    delivery: Option<DeliveryTracker>,
+    /// Optional end-to-end path of every message.
+    message_paths: Option<PathTracker>,
+    /// Optional feed of the mesh traffic to an MQTT bridge.
+    mqtt: Option<MqttFeed>,
+    /// Optional recording or replay of external inputs.
+    input_log: Option<InputLog>,
+    /// Optional running hash of the processed events.
+    event_digest: Option<EventDigest>,
+    /// Optional priority lane for control commands.
+    control: Option<ControlLane>,
+    /// Optional console executing command lines sent on the control lane.
+    console: Option<Inspector>,
+    /// Optional source of agent scripts reloaded mid-run.
+    scripts: Option<ScriptReloader>,
+    /// Serial output of nodes sent commands with [`EventLoop::send_serial`].
+    serial_echo: SerialEcho,
+    /// Optional wall-clock rendering of trace and report timestamps.
+    wall_clock: Option<WallClock>,
+    /// Optional cap on the size of the trace and capture files.
+    artifact_budget: Option<ArtifactBudget>,
 }
 
 impl EventLoop {
@@ -351,6 +584,7 @@ impl EventLoop {
         entity_tracer: EntityTracer,
     ) -> Self {
         let mut event_queue = BinaryHeap::new();
+        let rng_backend = simulation.rng_backend;
 
         // Add initial events to queue
         for event in simulation.initial_events.iter().cloned() {
@@ -360,13 +594,18 @@ impl EventLoop {
         // Initialize per-node stats and build entity ID mappings
         let mut node_stats = HashMap::new();
         let mut firmware_to_radio = HashMap::new();
+        let mut flood_max = HashMap::new();
         let mut radio_to_name = HashMap::new();
         let mut entity_to_labels = HashMap::new();
         let mut firmware_entity_ids = std::collections::HashSet::new();
+        let mut room_retention = RoomRetentionTracker::new();
         for node_info in &simulation.node_infos {
             node_stats.insert(node_info.radio_entity_id, NodeStats::default());
             firmware_to_radio.insert(node_info.firmware_entity_id, node_info.radio_entity_id);
             radio_to_name.insert(node_info.radio_entity_id, node_info.name.clone());
+            if let Some(limit) = flood_hop_limit(node_info) {
+                flood_max.insert(node_info.radio_entity_id, limit);
+            }
             // Map firmware, radio, and agent entity IDs to labels for metrics and tracing
             let labels = (node_info.name.clone(), node_info.node_type.clone());
             entity_to_labels.insert(node_info.firmware_entity_id, labels.clone());
@@ -379,6 +618,14 @@ impl EventLoop {
                 entity_to_labels.insert(cli_agent_id, labels.clone());
             }
             firmware_entity_ids.insert(node_info.firmware_entity_id);
+            if let Some(retention) = &node_info.room_retention {
+                room_retention.add_room(
+                    node_info.firmware_entity_id,
+                    labels,
+                    &node_info.public_key,
+                    retention.clone(),
+                );
+            }
         }
 
         // Create packet tracker with total node count
@@ -388,11 +635,13 @@ impl EventLoop {
         EventLoop {
             event_queue,
             simulation,
-            context: SimContext::with_tracer(seed, entity_tracer.clone()),
+            context: SimContext::with_tracer(seed, entity_tracer.clone())
+                .with_rng_backend(rng_backend),
             trace: TraceRecorder::new(trace_output),
             stats: SimulationStats::default(),
             node_stats,
             firmware_to_radio,
+            flood_max,
             radio_to_name,
             entity_to_labels,
             firmware_entity_ids,
@@ -404,8 +653,32 @@ impl EventLoop {
             last_eviction_time_us: 0,
             parallel_config: ParallelStepConfig::default(),
             realtime_config: RealTimeConfig::default(),
+            pacing: None,
             metrics_recorder: None,
             rerun_metric_specs: Vec::new(),
+            room_retention,
+            serial_capture: None,
+            packet_capture: None,
+            #[cfg(feature = "sqlite")]
+            packet_db: None,
+            chrome_trace: None,
+            calibration: CalibrationTracker::new(),
+            timer_jitter: None,
+            alerts: None,
+            assertions: None,
+            timeline: None,
+            adverts: None,
+            delivery: None,
+            message_paths: None,
+            mqtt: None,
+            input_log: None,
+            event_digest: None,
+            control: None,
+            console: None,
+            scripts: None,
+            serial_echo: SerialEcho::default(),
+            wall_clock: None,
+            artifact_budget: None,
         }
     }
     
@@ -429,6 +702,20 @@ impl EventLoop {
         &self.realtime_config
     }
     
+    /// Pace simulation time against the wall clock. Timed runs otherwise
+    /// run as fast as possible and [`run_realtime`](Self::run_realtime) at
+    /// the real-time config's speed. A paced timed run also takes serial
+    /// input from UART bridge clients, like a realtime run.
+    pub fn set_pacing(&mut self, pacing: Pacing) {
+        self.pacing = Some(pacing);
+    }
+    
+    /// The pacing set with [`set_pacing`](Self::set_pacing) or by the
+    /// current run.
+    pub fn pacing(&self) -> Option<Pacing> {
+        self.pacing
+    }
+    
     /// Configure packet tracker eviction.
     ///
     /// When set, packets older than the specified age will be periodically
@@ -455,6 +742,506 @@ impl EventLoop {
         }
     }
     
+    /// Show wall-clock datetimes in traces and reports (see [`wall_clock`]).
+    pub fn set_wall_clock(&mut self, clock: WallClock) {
+        self.wall_clock = Some(clock);
+    }
+
+    /// Cap the combined size of the trace and capture files (see
+    /// [`artifact_budget`]). Captures must write through
+    /// [`ArtifactBudget::writer`] to be counted.
+    pub fn set_artifact_budget(&mut self, budget: ArtifactBudget) {
+        self.trace.set_budget(budget.clone());
+        self.artifact_budget = Some(budget);
+    }
+
+    /// Write serial traffic of the capture's nodes to a single time-ordered
+    /// stream (see [`serial_capture`]).
+    pub fn set_serial_capture(&mut self, capture: SerialCapture) {
+        self.serial_capture = Some(capture);
+    }
+
+    /// Write every over-the-air packet to a pcapng stream (see
+    /// [`packet_capture`]).
+    pub fn set_packet_capture(&mut self, capture: PacketCapture) {
+        self.packet_capture = Some(capture);
+    }
+
+    /// Write every radio TX, RX, delivery and drop to an SQLite database (see
+    /// [`packet_db`]).
+    #[cfg(feature = "sqlite")]
+    pub fn set_packet_database(&mut self, database: packet_db::PacketDatabase) {
+        self.packet_db = Some(database);
+    }
+
+    /// Number of rows written to the packet database, if enabled.
+    #[cfg(feature = "sqlite")]
+    pub fn packet_database_rows(&self) -> Option<u64> {
+        self.packet_db.as_ref().map(packet_db::PacketDatabase::rows)
+    }
+
+    /// Write the message paths to the packet database, if both are enabled,
+    /// and close it.
+    #[cfg(feature = "sqlite")]
+    pub fn finish_packet_database(&mut self) -> std::io::Result<()> {
+        let Some(mut database) = self.packet_db.take() else {
+            return Ok(());
+        };
+        if let Some(report) = self.message_path_report() {
+            database.write_message_paths(&report)?;
+        }
+        database.flush()
+    }
+
+    /// Write the timing of event dispatch, firmware steps and radio
+    /// activity as a Chrome trace (see [`chrome_trace`]).
+    pub fn set_chrome_trace(&mut self, trace: ChromeTrace) {
+        self.chrome_trace = Some(trace);
+    }
+
+    /// End the Chrome trace, if enabled, returning the number of trace
+    /// events written.
+    pub fn finish_chrome_trace(&mut self) -> std::io::Result<Option<u64>> {
+        self.chrome_trace.take().map(ChromeTrace::finish).transpose()
+    }
+
+    /// Compare the SNRs observed so far against the link model's predictions
+    /// (see [`calibration`]).
+    pub fn calibration_report(&self, tolerances: CalibrationTolerances) -> CalibrationReport {
+        self.calibration
+            .report(&self.simulation.link_model, &self.radio_to_name, tolerances)
+    }
+
+    /// Estimate every link's SNR distribution from the packets its receiver
+    /// got and lost so far, as a deployed network would from its logs, next
+    /// to the link model's parameters (see [`calibration`]).
+    #[cfg(feature = "planning")]
+    pub fn snr_estimates(&self) -> Vec<mcsim_link::LinkSnrEstimate> {
+        mcsim_link::estimate_link_snrs(&self.calibration.rx_logs(&self.simulation.link_model, &self.radio_to_name))
+    }
+
+    /// Deliver firmware timers with random jitter (see [`timer_jitter`]).
+    pub fn set_timer_jitter(&mut self, jitter: TimerJitter) {
+        self.timer_jitter = Some(jitter);
+    }
+
+    /// Hash every processed event into `digest` (see [`event_digest`]).
+    pub fn set_event_digest(&mut self, digest: EventDigest) {
+        self.event_digest = Some(digest);
+    }
+
+    /// Digest of the events processed so far, if enabled.
+    pub fn event_digest(&self) -> Option<&EventDigest> {
+        self.event_digest.as_ref()
+    }
+
+    /// Number of timer events shifted by the configured jitter.
+    pub fn timers_perturbed(&self) -> u64 {
+        self.timer_jitter.as_ref().map_or(0, TimerJitter::perturbed)
+    }
+
+    /// Handle for sending control commands to this loop (see [`control`]).
+    ///
+    /// Commands are handled ahead of queued simulation events by the `run_*`
+    /// methods. The lane is created on the first call; loops without one
+    /// don't check for commands.
+    pub fn control_handle(&mut self) -> ControlHandle {
+        self.control.get_or_insert_with(ControlLane::new).handle()
+    }
+
+    /// Execute console command lines sent on the control lane (see
+    /// [`ControlHandle::console`]) with `inspector`.
+    pub fn set_console(&mut self, inspector: Inspector) {
+        self.console = Some(inspector);
+    }
+
+    /// Reload agent scripts from `reloader`, on the console's `reload`
+    /// command and, if it watches its files, when they change (see
+    /// [`script_reload`]).
+    pub fn set_script_reloader(&mut self, reloader: ScriptReloader) {
+        self.scripts = Some(reloader);
+    }
+
+    /// Re-read the agent scripts and restart every agent's traffic rules,
+    /// returning the number of rules loaded.
+    pub fn reload_scripts(&mut self) -> Result<usize, RunnerError> {
+        let Some(reloader) = &self.scripts else {
+            return Err(RunnerError::ConfigError("No script source to reload from".to_string()));
+        };
+        let traffic = reloader.load()?;
+        Ok(self.reload_traffic(&traffic))
+    }
+
+    /// Replace the traffic rules of every companion's agent with those in
+    /// `traffic` (agents without rules there lose theirs), returning the
+    /// number of rules handed out.
+    pub fn reload_traffic(&mut self, traffic: &[mcsim_model::TrafficRule]) -> usize {
+        let node_ids: std::collections::BTreeMap<String, NodeId> = self
+            .simulation
+            .node_infos
+            .iter()
+            .map(|info| (info.name.clone(), NodeId::from_bytes(info.public_key)))
+            .collect();
+        let mut loaded = 0;
+        for info in &self.simulation.node_infos {
+            let Some(agent) = info.agent_entity_id else {
+                continue;
+            };
+            let rules: Vec<mcsim_agents::TrafficRule> = traffic
+                .iter()
+                .filter(|rule| rule.node == info.name)
+                .filter_map(|rule| rule.to_agent_rule(&node_ids))
+                .collect();
+            loaded += rules.len();
+            let agent = EntityId::new(agent);
+            self.event_queue.push(Event {
+                id: mcsim_common::EventId(self.context.next_event_id()),
+                time: self.context.time(),
+                source: agent,
+                targets: vec![agent],
+                payload: EventPayload::ReloadScript(mcsim_common::ReloadScriptEvent {
+                    script: mcsim_agents::encode_traffic_script(&rules),
+                }),
+            });
+        }
+        loaded
+    }
+
+    /// Handle pending control commands, blocking while paused.
+    fn service_control(&mut self, stop_flag: Option<&AtomicBool>) -> LaneOutcome {
+        if self.scripts.as_mut().is_some_and(ScriptReloader::changed) {
+            match self.reload_scripts() {
+                Ok(rules) => eprintln!("✓ Reloaded {} traffic rule(s) at {:.1}s", rules, self.context.time().as_secs_f64()),
+                Err(e) => eprintln!("⚠ Script reload failed, keeping current rules: {}", e),
+            }
+        }
+        let Some(mut lane) = self.control.take() else {
+            return LaneOutcome::Continue;
+        };
+        let outcome = lane.service(self, stop_flag);
+        self.control = Some(lane);
+        outcome
+    }
+
+    /// Bring `pacer` in line with the requested pacing, returning whether it
+    /// changed.
+    fn sync_pacer(&self, pacer: &mut RealTimePacer) -> bool {
+        match self.pacing {
+            Some(pacing) if pacer.config().pacing() != pacing => {
+                pacer.set_pacing(pacing, self.context.time());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// In a paced timed run, take input from UART clients and sleep while
+    /// the next event lies ahead of `pacer`. Returns whether it slept, in
+    /// which case the caller checks for control commands and tries again.
+    fn hold_for_pacer(&mut self, pacer: &RealTimePacer) -> bool {
+        if !pacer.config().enabled {
+            return false;
+        }
+        self.poll_uart_clients();
+        let Some(next_event) = self.event_queue.peek().map(|event| event.time) else {
+            return false;
+        };
+        match pacer.sleep_until_event(next_event) {
+            Some(wait) => {
+                std::thread::sleep(wait.min(MAX_PACING_SLEEP));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inject serial data received from UART bridge clients as `SerialRx`
+    /// events at the current time.
+    fn poll_uart_clients(&mut self) {
+        let Some(ref uart_mgr) = self.uart_manager else {
+            return;
+        };
+        for node_info in &self.simulation.node_infos {
+            if let Some(data) = uart_mgr.try_recv_from_client(node_info.firmware_entity_id) {
+                // Create a SerialRx event for this firmware entity
+                let current_sim = self.context.time();
+                let event = Event {
+                    id: mcsim_common::EventId(self.context.next_event_id()),
+                    time: current_sim,
+                    source: mcsim_common::EntityId::new(node_info.firmware_entity_id),
+                    targets: vec![mcsim_common::EntityId::new(node_info.firmware_entity_id)],
+                    payload: EventPayload::SerialRx(mcsim_common::SerialRxEvent { data }),
+                };
+                if let Some(ref mut log) = self.input_log {
+                    if let Some(injection) =
+                        SerialInjection::from_event(&event, &node_info.name, self.stats.total_events)
+                    {
+                        log.record(injection);
+                    }
+                }
+                self.event_queue.push(event);
+            }
+        }
+    }
+
+    /// Attach the metrics recorder, so control commands can turn recording
+    /// on and off.
+    pub fn set_metrics_recorder(&mut self, recorder: Arc<metrics_export::InMemoryRecorder>) {
+        self.metrics_recorder = Some(recorder);
+    }
+
+    /// Evaluate alert rules against `recorder` while running (see [`alerts`]).
+    pub fn set_alerts(&mut self, monitor: AlertMonitor, recorder: Arc<metrics_export::InMemoryRecorder>) {
+        self.alerts = Some(monitor);
+        self.metrics_recorder = Some(recorder);
+    }
+
+    /// Observe the run for the scenario's assertions (see [`assertions`]).
+    /// `recorder` supplies the values of metric assertions.
+    pub fn set_assertions(
+        &mut self,
+        monitor: AssertionMonitor,
+        recorder: Option<Arc<metrics_export::InMemoryRecorder>>,
+    ) {
+        self.assertions = Some(monitor);
+        if recorder.is_some() {
+            self.metrics_recorder = recorder;
+        }
+    }
+
+    /// Evaluate the scenario's assertions against the run so far.
+    pub fn evaluate_assertions(&self) -> Vec<AssertionResult> {
+        let Some(monitor) = self.assertions.as_ref() else {
+            return Vec::new();
+        };
+        let recorder = self.metrics_recorder.as_ref();
+        monitor.evaluate(
+            self.context.time(),
+            self.packet_tracker.mean_flood_coverage(),
+            |name, node| recorder.and_then(|r| r.current_value(name, node)),
+        )
+    }
+
+    /// Record each node's bring-up timeline (see [`timeline`]).
+    pub fn enable_timeline(&mut self) {
+        self.timeline = Some(TimelineTracker::new(&self.simulation.node_infos));
+    }
+
+    /// The bring-up timeline so far, if enabled.
+    pub fn timeline(&self) -> Option<Timeline> {
+        self.timeline.as_ref().map(|tracker| tracker.timeline(self.context.time()))
+    }
+
+    /// Record adverts and neighbour discovery (see [`advert_report`]).
+    pub fn enable_advert_report(&mut self) {
+        self.adverts = Some(AdvertTracker::new(&self.simulation.node_infos));
+    }
+
+    /// Discovery latency against advert airtime so far, if enabled.
+    pub fn advert_report(&self) -> Option<AdvertReport> {
+        self.adverts.as_ref().map(|tracker| tracker.report(&self.simulation.link_model))
+    }
+
+    /// Record the origin and receivers of every flood (see [`blast_radius`]).
+    pub fn enable_delivery_tracking(&mut self) {
+        self.delivery = Some(DeliveryTracker::new(&self.simulation.node_infos));
+    }
+
+    /// Follow every message across hops by its payload hash (see
+    /// [`message_paths`]).
+    pub fn enable_message_paths(&mut self) {
+        self.message_paths = Some(PathTracker::new(&self.simulation.node_infos));
+    }
+
+    /// Paths taken by the messages so far, if enabled.
+    pub fn message_path_report(&self) -> Option<MessagePathReport> {
+        self.message_paths.as_ref().map(PathTracker::report)
+    }
+
+    /// Publish the mesh traffic to an MQTT bridge (see [`mqtt_bridge`]).
+    pub fn set_mqtt_feed(&mut self, feed: MqttFeed) {
+        self.mqtt = Some(feed);
+    }
+
+    /// MQTT messages dropped because the broker fell behind, if publishing.
+    pub fn mqtt_dropped(&self) -> Option<u64> {
+        self.mqtt.as_ref().map(MqttFeed::dropped)
+    }
+
+    /// Flood delivery among the nodes not in `failed`, over the floods sent
+    /// since `since`, if tracking is enabled.
+    pub fn delivery_summary(&self, since: SimTime, failed: &HashSet<String>) -> Option<DeliverySummary> {
+        self.delivery.as_ref().map(|tracker| tracker.summary(since, failed))
+    }
+
+    /// Delivery before, during and after `outage`, which powered off
+    /// `nodes`, if tracking is enabled (see [`outages`]).
+    pub fn outage_impact(&self, outage: &PowerOutage, nodes: Vec<String>) -> Option<OutageImpact> {
+        self.delivery
+            .as_ref()
+            .map(|tracker| OutageImpact::measure(tracker, outage, nodes, self.context.time()))
+    }
+
+    /// SLA class of every node pair so far, if tracking is enabled (see
+    /// [`sla`]).
+    pub fn sla_report(&self, thresholds: SlaThresholds) -> Option<SlaReport> {
+        let until = self.context.time() - SimTime::from_secs(thresholds.max_latency_s);
+        self.delivery
+            .as_ref()
+            .map(|tracker| SlaReport::new(&tracker.pair_deliveries(until), thresholds))
+    }
+
+    /// Posts and clients of each room server so far.
+    pub fn room_states(&self) -> Vec<RoomState> {
+        self.room_retention.room_states()
+    }
+
+    /// Alerts fired so far.
+    pub fn fired_alerts(&self) -> &[FiredAlert] {
+        self.alerts.as_ref().map_or(&[], AlertMonitor::fired)
+    }
+
+    /// Record external inputs, or replay those of a recorded run (see
+    /// [`input_replay`]).
+    pub fn set_input_log(&mut self, log: InputLog) {
+        self.input_log = Some(log);
+    }
+
+    /// The input recording or replay state, if enabled.
+    pub fn input_log(&self) -> Option<&InputLog> {
+        self.input_log.as_ref()
+    }
+
+    /// Queue the replayed inputs due at the current point of the event
+    /// sequence, giving them the IDs they had in the recorded run.
+    fn inject_replayed_inputs(&mut self) -> Result<(), RunnerError> {
+        let Some(log) = self.input_log.as_mut() else {
+            return Ok(());
+        };
+        while let Some(injection) = log.next_due(self.stats.total_events) {
+            let id = self.context.next_event_id();
+            self.event_queue.push(injection.to_event(id)?);
+        }
+        Ok(())
+    }
+
+    /// Fold a processed event into the input log and queue any replayed
+    /// inputs now due. Returns true once a replay has processed as many
+    /// events as the recorded run.
+    fn log_inputs(&mut self, event: &Event) -> Result<bool, RunnerError> {
+        let Some(log) = self.input_log.as_mut() else {
+            return Ok(false);
+        };
+        let done = log.observe(event, self.stats.total_events);
+        self.inject_replayed_inputs()?;
+        Ok(done)
+    }
+
+    /// Evaluate alert rules if a check is due, acting on those that fire.
+    /// Returns true if an alert asked for the run to stop.
+    fn check_alerts(&mut self) -> bool {
+        let now = self.context.time();
+        let (Some(monitor), Some(recorder)) = (self.alerts.as_mut(), self.metrics_recorder.as_ref()) else {
+            return false;
+        };
+        if !monitor.is_due(now) {
+            return false;
+        }
+
+        let mut stop = false;
+        let when = self.wall_clock.map(|clock| format!(" [{}]", clock.format_short(now))).unwrap_or_default();
+        for alert in monitor.check(now, |name, node| recorder.current_value(name, node)) {
+            match alert.action {
+                AlertAction::Warn => eprintln!("⚠ {}{}", alert, when),
+                AlertAction::Marker => eprintln!("⚠ {}{} (marked in trace)", alert, when),
+                AlertAction::Stop => {
+                    eprintln!("⚠ {}{}, stopping run", alert, when);
+                    stop = true;
+                }
+            }
+            if alert.action != AlertAction::Warn {
+                self.record_alert(&alert);
+            }
+        }
+        stop
+    }
+
+    /// Move the events posted by the last dispatch into the queue.
+    fn queue_pending_events(&mut self) {
+        let now = self.context.time();
+        for mut new_event in self.context.take_pending_events() {
+            if let Some(ref mut jitter) = self.timer_jitter {
+                jitter.apply(&mut new_event, now);
+            }
+            self.event_queue.push(new_event);
+        }
+    }
+
+    /// Record an event's serial and over-the-air traffic to the captures, if
+    /// enabled.
+    fn capture_traffic(&mut self, event: &Event) -> Result<(), RunnerError> {
+        self.serial_echo.record(event);
+        if let Some(ref mut capture) = self.serial_capture {
+            capture.record(event)?;
+        }
+        if let Some(ref mut capture) = self.packet_capture {
+            capture.record(event)?;
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(ref mut database) = self.packet_db {
+            database.record(event)?;
+        }
+        self.enforce_artifact_budget()
+    }
+
+    /// Stop the trace and captures once they reach the artifact size cap.
+    /// Each is closed at a record boundary; the run continues without them.
+    fn enforce_artifact_budget(&mut self) -> Result<(), RunnerError> {
+        let Some(budget) = self.artifact_budget.as_ref().filter(|b| b.is_exhausted()) else {
+            return Ok(());
+        };
+        if self.stats.artifact_cap_reached_s.is_some() {
+            return Ok(());
+        }
+        let now = self.context.time();
+        eprintln!(
+            "Warning: artifacts reached the {:.1} MB cap at {:.1}s; stopped the trace and captures, metrics continue",
+            budget.limit() as f64 / 1e6,
+            now.as_secs_f64()
+        );
+        self.stats.artifact_cap_reached_s = Some(now.as_secs_f64());
+        self.trace.stop();
+        if let Some(mut capture) = self.serial_capture.take() {
+            capture.flush()?;
+        }
+        if let Some(mut capture) = self.packet_capture.take() {
+            capture.flush()?;
+        }
+        if let Some(trace) = self.chrome_trace.take() {
+            trace.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Flush the trace and captures at the end of a run.
+    fn flush_outputs(&mut self) -> Result<(), RunnerError> {
+        self.trace.flush()?;
+        if let Some(ref mut capture) = self.serial_capture {
+            capture.flush()?;
+        }
+        if let Some(ref mut capture) = self.packet_capture {
+            capture.flush()?;
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(ref mut database) = self.packet_db {
+            database.flush()?;
+        }
+        if let Some(ref mut trace) = self.chrome_trace {
+            trace.flush()?;
+        }
+        Ok(())
+    }
+    
     /// Configure metrics recording and Rerun visualization.
     ///
     /// This enables periodic logging of metrics snapshots to Rerun.
@@ -482,6 +1269,7 @@ impl EventLoop {
         &mut self,
         event: &Event,
     ) -> Result<(), mcsim_common::SimError> {
+        let dispatch_start = std::time::Instant::now();
         for target in &event.targets {
             if let Some(entity) = self.simulation.entities.get_mut(*target) {
                 self.context.set_source(*target);
@@ -490,7 +1278,10 @@ impl EventLoop {
                 let step_start = std::time::Instant::now();
                 entity.handle_event(event, &mut self.context)?;
                 let step_elapsed = step_start.elapsed();
-                
+                if let Some(trace) = self.chrome_trace.as_mut() {
+                    trace.record_handling(event, *target, step_start, step_elapsed);
+                }
+
                 // Record metric with labels if we have them
                 if let Some((name, node_type)) = self.entity_to_labels.get(&target.0) {
                     let labels = [
@@ -507,9 +1298,100 @@ impl EventLoop {
                 return Err(mcsim_common::SimError::EntityNotFound(*target));
             }
         }
+        if let Some(trace) = self.chrome_trace.as_mut() {
+            trace.record_dispatch(event, dispatch_start, dispatch_start.elapsed());
+        }
         Ok(())
     }
 
+    /// Process one event popped from the queue: dispatch it, queue the events
+    /// it produces and update statistics, tracing and visualization.
+    fn process_event(&mut self, event: &Event) -> Result<(), RunnerError> {
+        // Advance simulation time
+        self.context.set_time(event.time);
+
+        // Handle SerialTx events: forward to TCP clients AND dispatch to entity targets
+        if let EventPayload::SerialTx(serial_event) = &event.payload {
+            // Forward to TCP clients (for UART bridge) only if self-targeted
+            // This avoids double-sending when firmware sends to both self and agent
+            if event.targets.contains(&event.source) {
+                if let Some(ref uart_mgr) = self.uart_manager {
+                    uart_mgr.send_to_client(event.source.0, &serial_event.data);
+                }
+            }
+            // Continue to dispatch to entities (agents receive SerialTx from firmware)
+        }
+
+        // Dispatch event to target entities (with per-entity timing metrics)
+        self.dispatch_event_with_metrics(event)?;
+
+        // Collect new events
+        self.queue_pending_events();
+
+        // Update statistics
+        self.stats.total_events += 1;
+        self.update_stats(event);
+
+        // Record trace entry
+        self.record_trace(event);
+        self.capture_traffic(event)?;
+        if let Some(digest) = &mut self.event_digest {
+            digest.record(event);
+        }
+
+        // Log to rerun visualization
+        if let Some(ref mut rerun) = self.rerun_logger {
+            let _ = rerun.log_event(event);
+        }
+
+        // Periodically evict old packets to limit memory usage
+        self.maybe_evict_packets(self.context.time().as_micros());
+        Ok(())
+    }
+
+    /// Process the next pending event.
+    ///
+    /// Returns the time of the processed event, or `None` if the queue is empty.
+    /// Pending `SimulationEnd` markers are skipped.
+    pub fn step(&mut self) -> Result<Option<SimTime>, RunnerError> {
+        while let Some(event) = self.event_queue.pop() {
+            if matches!(event.payload, EventPayload::SimulationEnd) {
+                continue;
+            }
+            self.process_event(&event)?;
+            return Ok(Some(event.time));
+        }
+        Ok(None)
+    }
+
+    /// Process all events scheduled at or before `time`, then advance the
+    /// clock to `time`. Returns the number of events processed.
+    pub fn run_until(&mut self, time: SimTime) -> Result<u64, RunnerError> {
+        let mut processed = 0;
+        while self.event_queue.peek().is_some_and(|e| e.time <= time) {
+            if self.step()?.is_some() {
+                processed += 1;
+            }
+        }
+        if time > self.context.time() {
+            self.context.set_time(time);
+        }
+        Ok(processed)
+    }
+
+    /// Pending events in the order they will be processed (at most `limit`).
+    pub fn pending_events(&self, limit: usize) -> Vec<&Event> {
+        let mut events: Vec<&Event> = self.event_queue.iter().collect();
+        events.sort_by(|a, b| b.cmp(a));
+        events.truncate(limit);
+        events
+    }
+
+    /// Number of events waiting in the queue.
+    pub fn pending_event_count(&self) -> usize {
+        self.event_queue.len()
+    }
+
     /// Run the simulation for the specified duration.
     pub fn run(&mut self, duration: SimTime) -> Result<SimulationStats, RunnerError> {
         self.run_with_progress(duration, None, |_, _, _| {})
@@ -550,9 +1432,31 @@ impl EventLoop {
             targets: vec![],
             payload: EventPayload::SimulationEnd,
         });
+        self.inject_replayed_inputs()?;
+
+        let pacing = *self.pacing.get_or_insert(Pacing::Fast);
+        let mut pacer = RealTimePacer::new(self.realtime_config.clone().with_pacing(pacing), self.context.time());
 
         // Main event loop
-        while let Some(event) = self.event_queue.pop() {
+        loop {
+            // Control commands go ahead of queued events, including those
+            // the commands queue themselves
+            match self.service_control(stop_flag.as_deref()) {
+                LaneOutcome::Continue => {}
+                LaneOutcome::Resumed => pacer.restart(self.context.time()),
+                LaneOutcome::Stop => break,
+            }
+            self.sync_pacer(&mut pacer);
+            if self.hold_for_pacer(&pacer) {
+                if stop_flag.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+                    break;
+                }
+                continue;
+            }
+            let Some(event) = self.event_queue.pop() else {
+                break;
+            };
+
             // Check for stop flag
             if let Some(ref flag) = stop_flag {
                 if flag.load(Ordering::Relaxed) {
@@ -565,45 +1469,12 @@ impl EventLoop {
                 break;
             }
 
-            // Advance simulation time
-            self.context.set_time(event.time);
+            self.process_event(&event)?;
 
-            // Handle SerialTx events: forward to TCP clients AND dispatch to entity targets
-            if let EventPayload::SerialTx(serial_event) = &event.payload {
-                // Forward to TCP clients (for UART bridge) only if self-targeted
-                // This avoids double-sending when firmware sends to both self and agent
-                if event.targets.contains(&event.source) {
-                    if let Some(ref uart_mgr) = self.uart_manager {
-                        uart_mgr.send_to_client(event.source.0, &serial_event.data);
-                    }
-                }
-                // Continue to dispatch to entities (agents receive SerialTx from firmware)
-            }
-
-            // Dispatch event to target entities (with per-entity timing metrics)
-            self.dispatch_event_with_metrics(&event)?;
-
-            // Collect new events
-            let new_events = self.context.take_pending_events();
-            for new_event in new_events {
-                self.event_queue.push(new_event);
-            }
-
-            // Update statistics
-            self.stats.total_events += 1;
-            self.update_stats(&event);
-
-            // Record trace entry
-            self.record_trace(&event);
-
-            // Log to rerun visualization
-            if let Some(ref mut rerun) = self.rerun_logger {
-                let _ = rerun.log_event(&event);
+            if self.log_inputs(&event)? || self.check_alerts() {
+                break;
             }
 
-            // Periodically evict old packets to limit memory usage
-            self.maybe_evict_packets(self.context.time().as_micros());
-
             // Report progress periodically (time-based or event-count-based)
             let events_since_last = self.stats.total_events - last_progress_events;
             let should_report = last_progress.elapsed() >= progress_interval 
@@ -654,6 +1525,7 @@ impl EventLoop {
 
         // Emit packet tracking summaries
         self.packet_tracker.emit_flood_summaries();
+        self.room_retention.finish(self.context.time().as_micros());
 
         // Finalize stats
         self.stats.simulation_time_us = self.context.time().as_micros();
@@ -675,7 +1547,7 @@ impl EventLoop {
         on_progress(self, progress, true);
 
         // Flush trace
-        self.trace.flush()?;
+        self.flush_outputs()?;
 
         Ok(self.stats.clone())
     }
@@ -739,11 +1611,33 @@ impl EventLoop {
             payload: EventPayload::SimulationEnd,
         });
 
+        self.inject_replayed_inputs()?;
+
         // Track event number for break point
         let mut event_number: u64 = 0;
 
+        let pacing = *self.pacing.get_or_insert(Pacing::Fast);
+        let mut pacer = RealTimePacer::new(self.realtime_config.clone().with_pacing(pacing), self.context.time());
+
         // Main event loop
-        while let Some(event) = self.event_queue.pop() {
+        loop {
+            // Control commands go ahead of queued events, including those
+            // the commands queue themselves
+            match self.service_control(stop_flag.as_deref()) {
+                LaneOutcome::Continue => {}
+                LaneOutcome::Resumed => pacer.restart(self.context.time()),
+                LaneOutcome::Stop => break,
+            }
+            self.sync_pacer(&mut pacer);
+            if self.hold_for_pacer(&pacer) {
+                if stop_flag.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+                    break;
+                }
+                continue;
+            }
+            let Some(event) = self.event_queue.pop() else {
+                break;
+            };
             event_number += 1;
 
             // Check for stop flag
@@ -838,10 +1732,7 @@ impl EventLoop {
             }
 
             // Collect new events
-            let new_events = self.context.take_pending_events();
-            for new_event in new_events {
-                self.event_queue.push(new_event);
-            }
+            self.queue_pending_events();
 
             // Update statistics
             self.stats.total_events += 1;
@@ -849,6 +1740,10 @@ impl EventLoop {
 
             // Record trace entry
             self.record_trace(&event);
+            self.capture_traffic(&event)?;
+            if let Some(digest) = &mut self.event_digest {
+                digest.record(&event);
+            }
 
             // Log to rerun visualization
             if let Some(ref mut rerun) = self.rerun_logger {
@@ -858,6 +1753,10 @@ impl EventLoop {
             // Periodically evict old packets to limit memory usage
             self.maybe_evict_packets(self.context.time().as_micros());
 
+            if self.log_inputs(&event)? || self.check_alerts() {
+                break;
+            }
+
             // Report progress periodically
             let events_since_last = self.stats.total_events - last_progress_events;
             let should_report = last_progress.elapsed() >= progress_interval 
@@ -906,6 +1805,7 @@ impl EventLoop {
 
         // Emit packet tracking summaries
         self.packet_tracker.emit_flood_summaries();
+        self.room_retention.finish(self.context.time().as_micros());
 
         // Finalize stats
         self.stats.simulation_time_us = self.context.time().as_micros();
@@ -931,7 +1831,7 @@ impl EventLoop {
         on_progress(self, progress, true);
 
         // Flush trace
-        self.trace.flush()?;
+        self.flush_outputs()?;
 
         Ok(self.stats.clone())
     }
@@ -946,6 +1846,95 @@ impl EventLoop {
         &self.simulation.node_infos
     }
 
+    /// Snapshot of the packets a node's firmware has queued for transmission.
+    /// None for unknown nodes and for firmware without an inspectable queue
+    /// (only repeaters have one).
+    pub fn outbound_queue(&self, node: &str) -> Option<mcsim_common::OutboundQueue> {
+        let info = self.simulation.node_infos.iter().find(|info| info.name == node)?;
+        self.simulation.entities.get(EntityId::new(info.firmware_entity_id))?.outbound_queue()
+    }
+
+    /// Send `data` to a node's serial port at the current simulation time,
+    /// as a UART client would. The node's serial output is collected from
+    /// then on for the console. Returns `false` if there is no such node.
+    pub fn send_serial(&mut self, node: &str, data: Vec<u8>) -> bool {
+        let Some(info) = self.simulation.node_infos.iter().find(|info| info.name == node) else {
+            return false;
+        };
+        let firmware = EntityId::new(info.firmware_entity_id);
+        let event = Event {
+            id: mcsim_common::EventId(self.context.next_event_id()),
+            time: self.context.time(),
+            source: firmware,
+            targets: vec![firmware],
+            payload: EventPayload::SerialRx(mcsim_common::SerialRxEvent { data }),
+        };
+        if let Some(ref mut log) = self.input_log {
+            if let Some(injection) = SerialInjection::from_event(&event, &info.name, self.stats.total_events) {
+                log.record(injection);
+            }
+        }
+        self.serial_echo.watch(info.firmware_entity_id, info.name.clone());
+        self.event_queue.push(event);
+        true
+    }
+
+    /// Move a node to `position` at the current simulation time. Its links
+    /// are recomputed from the path loss model, as for a mobile node (see
+    /// [`mcsim_lora::mobility`]). Returns `false` if there is no such node.
+    pub fn move_node(&mut self, node: &str, position: GeoCoord) -> bool {
+        let graph = self.simulation.graph_entity;
+        let Some(info) = self.simulation.node_infos.iter_mut().find(|info| info.name == node) else {
+            return false;
+        };
+        info.location = position;
+        let event = Event {
+            id: mcsim_common::EventId(self.context.next_event_id()),
+            time: self.context.time(),
+            source: graph,
+            targets: vec![graph],
+            payload: EventPayload::MoveNode(mcsim_common::MoveNodeEvent {
+                radio_id: EntityId::new(info.radio_entity_id),
+                position,
+            }),
+        };
+        self.event_queue.push(event);
+        true
+    }
+
+    /// Power off a node at `at` for the rest of the run, as a failure of its
+    /// domain would. Returns false if there is no such node.
+    pub fn schedule_power_off(&mut self, node: &str, at: SimTime, reason: &str) -> bool {
+        let Some(info) = self.simulation.node_infos.iter().find(|info| info.name == node) else {
+            return false;
+        };
+        let radio = EntityId::new(info.radio_entity_id);
+        let event = Event {
+            id: mcsim_common::EventId(self.context.next_event_id()),
+            time: at.max(self.context.time()),
+            source: radio,
+            targets: vec![radio],
+            payload: EventPayload::PowerOff(mcsim_common::PowerOffEvent { reason: reason.to_string() }),
+        };
+        self.event_queue.push(event);
+        true
+    }
+
+    /// The current radio links, ordered by transmitter and receiver.
+    pub fn links(&self) -> Vec<LinkQuality> {
+        self.simulation
+            .entities
+            .get(self.simulation.graph_entity)
+            .and_then(|graph| graph.links())
+            .unwrap_or_default()
+    }
+
+    /// Serial output lines collected since the last call (see
+    /// [`send_serial`](Self::send_serial)).
+    pub(crate) fn take_serial_echo(&mut self) -> Vec<String> {
+        self.serial_echo.take_lines()
+    }
+
     /// Get current statistics.
     pub fn stats(&self) -> &SimulationStats {
         &self.stats
@@ -981,6 +1970,8 @@ impl EventLoop {
     /// - `enabled`: When false, runs as fast as possible
     ///
     /// Use [`set_realtime_config()`](Self::set_realtime_config) to configure before calling this method.
+    /// A pacing set with [`set_pacing()`](Self::set_pacing), or through the control lane while
+    /// running, takes precedence over the config's speed.
     pub fn run_realtime<F>(
         &mut self,
         stop_flag: Arc<AtomicBool>,
@@ -991,32 +1982,26 @@ impl EventLoop {
     {
         let start_wall = Instant::now();
         let start_sim = self.context.time();
-        let mut pacer = RealTimePacer::new(self.realtime_config.clone(), start_sim);
+        let pacing = *self.pacing.get_or_insert_with(|| self.realtime_config.pacing());
+        let mut pacer = RealTimePacer::new(self.realtime_config.clone().with_pacing(pacing), start_sim);
         
         let mut last_tick = Instant::now();
         let tick_interval = Duration::from_secs(1);
 
         while !stop_flag.load(Ordering::Relaxed) {
+            // Handle control commands while idle too
+            match self.service_control(Some(&stop_flag)) {
+                LaneOutcome::Continue => {}
+                LaneOutcome::Resumed => pacer.restart(self.context.time()),
+                LaneOutcome::Stop => break,
+            }
+            self.sync_pacer(&mut pacer);
+
             // Calculate target simulation time using the pacer (handles speed multiplier)
             let target_sim_time = pacer.target_sim_time();
 
             // Poll for incoming serial data from TCP clients and inject SerialRx events
-            if let Some(ref uart_mgr) = self.uart_manager {
-                for node_info in &self.simulation.node_infos {
-                    if let Some(data) = uart_mgr.try_recv_from_client(node_info.firmware_entity_id) {
-                        // Create a SerialRx event for this firmware entity
-                        let current_sim = self.context.time();
-                        let event = Event {
-                            id: mcsim_common::EventId(self.context.next_event_id()),
-                            time: current_sim,
-                            source: mcsim_common::EntityId::new(node_info.firmware_entity_id),
-                            targets: vec![mcsim_common::EntityId::new(node_info.firmware_entity_id)],
-                            payload: EventPayload::SerialRx(mcsim_common::SerialRxEvent { data }),
-                        };
-                        self.event_queue.push(event);
-                    }
-                }
-            }
+            self.poll_uart_clients();
 
             // Process all events up to target simulation time (catch-up logic)
             let mut events_this_tick = 0;
@@ -1025,6 +2010,24 @@ impl EventLoop {
                     break; // Event is in the future or stop requested
                 }
 
+                // Control commands go ahead of queued events
+                match self.service_control(Some(&stop_flag)) {
+                    LaneOutcome::Continue => {}
+                    LaneOutcome::Resumed => {
+                        // Don't catch up on the time spent paused
+                        pacer.restart(self.context.time());
+                        break;
+                    }
+                    LaneOutcome::Stop => {
+                        stop_flag.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                }
+                if self.sync_pacer(&mut pacer) {
+                    // The target time was computed at the old speed
+                    break;
+                }
+
                 let event = self.event_queue.pop().unwrap();
 
                 // Advance simulation time
@@ -1046,10 +2049,7 @@ impl EventLoop {
                 self.dispatch_event_with_metrics(&event)?;
 
                 // Collect new events
-                let new_events = self.context.take_pending_events();
-                for new_event in new_events {
-                    self.event_queue.push(new_event);
-                }
+                self.queue_pending_events();
 
                 // Update statistics
                 self.stats.total_events += 1;
@@ -1057,6 +2057,10 @@ impl EventLoop {
 
                 // Record trace entry
                 self.record_trace(&event);
+                self.capture_traffic(&event)?;
+                if let Some(digest) = &mut self.event_digest {
+                    digest.record(&event);
+                }
 
                 // Log to rerun visualization
                 if let Some(ref mut rerun) = self.rerun_logger {
@@ -1066,10 +2070,18 @@ impl EventLoop {
                 // Periodically evict old packets to limit memory usage
                 self.maybe_evict_packets(self.context.time().as_micros());
 
+                if self.log_inputs(&event)? || self.check_alerts() {
+                    stop_flag.store(true, Ordering::Relaxed);
+                    break;
+                }
+
                 events_this_tick += 1;
 
-                // Check stop flag periodically during heavy event processing
-                if events_this_tick % 1000 == 0 && stop_flag.load(Ordering::Relaxed) {
+                // Check stop flag periodically during heavy event processing,
+                // and come up for UART input and ticks when running flat out
+                if events_this_tick % 1000 == 0
+                    && (stop_flag.load(Ordering::Relaxed) || last_tick.elapsed() >= tick_interval)
+                {
                     break;
                 }
             }
@@ -1078,7 +2090,7 @@ impl EventLoop {
             if let Some(drift_ms) = pacer.check_lag_warning(self.context.time()) {
                 eprintln!(
                     "⚠ Simulation lagging by {}ms (speed: {}x)",
-                    drift_ms, self.realtime_config.speed_multiplier
+                    drift_ms, pacer.config().speed_multiplier
                 );
             }
 
@@ -1123,7 +2135,7 @@ impl EventLoop {
             // Calculate sleep time to prevent busy-waiting
             // If we have a next event, sleep until it should be processed
             // Otherwise, sleep for minimum duration
-            // Always cap at 10ms to ensure Ctrl-C responsiveness on all platforms
+            // Always cap at MAX_PACING_SLEEP to ensure Ctrl-C responsiveness
             let sleep_duration = if let Some(next_event) = self.event_queue.peek() {
                 pacer
                     .sleep_until_event(next_event.time)
@@ -1131,7 +2143,7 @@ impl EventLoop {
             } else {
                 pacer.min_sleep_duration()
             }
-            .min(Duration::from_millis(10));
+            .min(MAX_PACING_SLEEP);
 
             if sleep_duration > Duration::ZERO {
                 std::thread::sleep(sleep_duration);
@@ -1155,18 +2167,64 @@ impl EventLoop {
 
         // Emit packet tracking summaries
         self.packet_tracker.emit_flood_summaries();
+        self.room_retention.finish(self.context.time().as_micros());
 
         // Flush trace
-        self.trace.flush()?;
+        self.flush_outputs()?;
 
         Ok(self.stats.clone())
     }
 
     /// Update statistics based on event type.
+    /// Describe a processed event for control lane subscribers, naming
+    /// entities by their node.
+    fn event_notice(&self, event: &Event) -> EventNotice {
+        let name = |id: EntityId| {
+            self.entity_to_labels
+                .get(&id.0)
+                .map_or_else(|| format!("entity {}", id.0), |(name, _)| name.clone())
+        };
+        let (event_type, details) = describe_event_payload(&event.payload);
+        EventNotice {
+            sim_time: event.time,
+            event_type,
+            details,
+            source: name(event.source),
+            targets: event.targets.iter().copied().map(name).collect(),
+            text: serial_text(&event.payload),
+        }
+    }
+
     fn update_stats(&mut self, event: &Event) {
+        if let Some(monitor) = self.assertions.as_mut() {
+            monitor.observe(event);
+        }
+        if let Some(tracker) = self.timeline.as_mut() {
+            tracker.observe(event);
+        }
+        if let Some(tracker) = self.adverts.as_mut() {
+            tracker.observe(event);
+        }
+        if let Some(tracker) = self.delivery.as_mut() {
+            tracker.observe(event);
+        }
+        if let Some(tracker) = self.message_paths.as_mut() {
+            tracker.observe(event);
+        }
+        if let Some(feed) = self.mqtt.as_mut() {
+            feed.observe(event);
+        }
+        if self.control.as_ref().is_some_and(ControlLane::has_subscribers) {
+            let notice = self.event_notice(event);
+            if let Some(lane) = self.control.as_mut() {
+                lane.publish(notice);
+            }
+        }
         match &event.payload {
             EventPayload::TransmitAir(tx) => {
                 self.stats.packets_transmitted += 1;
+                self.calibration
+                    .track_transmit(tx.radio_id.0, &tx.params, tx.packet.payload.len());
 
                 // Track per-node TX
                 if let Some(stats) = self.node_stats.get_mut(&tx.radio_id.0) {
@@ -1196,26 +2254,51 @@ impl EventLoop {
                 for target in &event.targets {
                     // target is a firmware entity ID, map to radio entity ID
                     if let Some(&radio_id) = self.firmware_to_radio.get(&target.0) {
+                        self.calibration.track_reception(radio_id, rx);
                         if let Some(stats) = self.node_stats.get_mut(&radio_id) {
                             if rx.was_collided {
                                 stats.collisions += 1;
                             } else {
                                 stats.rx += 1;
+                                stats.last_rx = Some(LastRx {
+                                    time_us: event.time.as_micros(),
+                                    source_radio_id: rx.source_radio_id.0,
+                                    snr_db: rx.snr_db,
+                                    rssi_dbm: rx.rssi_dbm,
+                                });
                             }
                         }
 
-                        // Track packet reception for non-collided packets
-                        if !rx.was_collided {
+                        // Track packet reception for intact (non-collided, uncorrupted) packets
+                        if !rx.was_collided && !rx.was_corrupted {
                             if let Ok(packet) = meshcore_packet::MeshCorePacket::decode(&rx.packet.payload) {
                                 let receive_time = event.time.as_micros();
+                                self.room_retention.track_reception(target.0, &packet, receive_time);
 
                                 // Get receiver node name
                                 if let Some(node_name) = self.radio_to_name.get(&radio_id) {
-                                    self.packet_tracker.track_reception(
+                                    let first_heard = self.packet_tracker.track_reception(
                                         &packet,
                                         node_name,
                                         receive_time,
                                     );
+
+                                    // Forwarding nodes drop floods that have used up their hop limit
+                                    let limit = self.flood_max.get(&radio_id).copied();
+                                    if first_heard && flood_hops_exhausted(limit, &packet) {
+                                        if let Some((name, node_type)) = self.entity_to_labels.get(&radio_id) {
+                                            let labels = [
+                                                ("node", name.clone()),
+                                                ("node_type", node_type.clone()),
+                                                ("payload_type", packet.payload_type().as_label().to_string()),
+                                            ];
+                                            metrics::counter!(
+                                                mcsim_metrics::metric_defs::FLOOD_HOP_LIMIT_DROPPED.name,
+                                                &labels
+                                            )
+                                            .increment(1);
+                                        }
+                                    }
                                 }
                             }
                         }
@@ -1240,15 +2323,7 @@ impl EventLoop {
 
     /// Record a trace entry for an event.
     fn record_trace(&mut self, event: &Event) {
-        // Convert simulation time to ISO 8601 timestamp
-        // Using a base time of 2025-01-01T00:00:00Z for simulation start
-        let sim_secs = event.time.as_secs_f64();
-        let timestamp = format!(
-            "2025-01-01T{:02}:{:02}:{:06.3}Z",
-            (sim_secs / 3600.0) as u32 % 24,
-            (sim_secs / 60.0) as u32 % 60,
-            sim_secs % 60.0
-        );
+        let (timestamp, sim_time_s) = self.trace_time(event.time);
 
         // Look up node name from entity ID
         let origin = self.entity_to_labels
@@ -1282,6 +2357,8 @@ impl EventLoop {
                     "collided".to_string()
                 } else if rx.was_weak_signal {
                     "weak".to_string()
+                } else if rx.was_corrupted {
+                    "corrupted".to_string()
                 } else {
                     "ok".to_string()
                 };
@@ -1320,11 +2397,145 @@ impl EventLoop {
             origin,
             origin_id: format!("{}", event.source.0),
             timestamp,
+            sim_time_s,
             payload,
         };
 
         self.trace.record(entry);
     }
+
+    /// Record a trace marker for a fired alert.
+    fn record_alert(&mut self, alert: &FiredAlert) {
+        let firmware_id = alert.node.as_ref().and_then(|node| {
+            self.simulation.node_infos.iter().find(|info| info.name == *node).map(|info| info.firmware_entity_id)
+        });
+        let (timestamp, sim_time_s) = self.trace_time(SimTime::from_secs(alert.at_s));
+        let entry = TraceEntry {
+            origin: alert.node.clone().unwrap_or_else(|| "Simulation".to_string()),
+            origin_id: firmware_id.unwrap_or(0).to_string(),
+            timestamp,
+            sim_time_s,
+            payload: TracePayload::Alert(AlertPayload {
+                rule: alert.rule.clone(),
+                metric: alert.metric.clone(),
+                condition: alert.condition.to_string(),
+                observed: alert.observed,
+            }),
+        };
+        self.trace.record(entry);
+    }
+
+    /// Timestamp of a trace entry, with the simulation time when the
+    /// timestamp is a wall-clock datetime.
+    fn trace_time(&self, time: SimTime) -> (String, Option<f64>) {
+        match &self.wall_clock {
+            Some(clock) => (clock.format(time), Some(time.as_secs_f64())),
+            None => (trace_timestamp(time), None),
+        }
+    }
+}
+
+/// Flood hop limit of a node: its configured one or, for the roles that
+/// forward floods, the firmware's maximum.
+fn flood_hop_limit(node: &mcsim_model::NodeInfo) -> Option<u8> {
+    node.flood_max.or(match node.node_type.as_str() {
+        "Repeater" | "RoomServer" => Some(mcsim_firmware::MAX_FLOOD_HOPS),
+        _ => None,
+    })
+}
+
+/// Whether a flood heard by a node with hop limit `limit` has used up its
+/// hops, so the node won't forward it.
+fn flood_hops_exhausted(limit: Option<u8>, packet: &meshcore_packet::MeshCorePacket) -> bool {
+    packet.is_flood() && limit.is_some_and(|max| packet.path_len() >= max as usize)
+}
+
+/// ISO 8601 timestamp of a simulation time, counted from a base time of
+/// 2025-01-01T00:00:00Z.
+fn trace_timestamp(time: SimTime) -> String {
+    let sim_secs = time.as_secs_f64();
+    format!(
+        "2025-01-01T{:02}:{:02}:{:06.3}Z",
+        (sim_secs / 3600.0) as u32 % 24,
+        (sim_secs / 60.0) as u32 % 60,
+        sim_secs % 60.0
+    )
+}
+
+/// Serial data of a `SerialTx` or `SerialRx` event, if it is printable text.
+/// Companion protocol frames are binary and have none.
+fn serial_text(payload: &EventPayload) -> Option<String> {
+    let data = match payload {
+        EventPayload::SerialTx(e) => &e.data,
+        EventPayload::SerialRx(e) => &e.data,
+        _ => return None,
+    };
+    let text = std::str::from_utf8(data).ok()?;
+    if text.chars().any(|c| c.is_control() && !matches!(c, '\r' | '\n' | '\t')) {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+impl LaneTarget for EventLoop {
+    fn status(&self) -> ControlStatus {
+        ControlStatus {
+            sim_time: self.context.time(),
+            events_processed: self.stats.total_events,
+            pending_events: self.event_queue.len(),
+            paused: false,
+            pacing: self.pacing.unwrap_or(Pacing::Fast),
+            max_command_latency_us: 0,
+        }
+    }
+
+    fn console(&mut self, line: &str) -> String {
+        let Some(mut inspector) = self.console.take() else {
+            return "No console attached".to_string();
+        };
+        let mut out = Vec::new();
+        if let Err(e) = inspector.execute_live(self, line, &mut out) {
+            let _ = writeln!(out, "Error: {}", e);
+        }
+        self.console = Some(inspector);
+        String::from_utf8_lossy(&out).into_owned()
+    }
+
+    fn nodes(&self) -> Vec<NodeStatus> {
+        self.simulation
+            .node_infos
+            .iter()
+            .map(|info| {
+                let stats = self.node_stats.get(&info.radio_entity_id).cloned().unwrap_or_default();
+                NodeStatus {
+                    name: info.name.clone(),
+                    node_type: info.node_type.clone(),
+                    location: info.location,
+                    tx: stats.tx,
+                    rx: stats.rx,
+                    collisions: stats.collisions,
+                }
+            })
+            .collect()
+    }
+
+    fn set_pacing(&mut self, pacing: Pacing) {
+        if self.pacing != Some(pacing) {
+            eprintln!("⏱  Pacing set to {} at {:.1}s", pacing, self.context.time().as_secs_f64());
+        }
+        self.pacing = Some(pacing);
+    }
+
+    fn set_metrics_recording(&mut self, category: Option<&str>, enabled: bool) -> bool {
+        let Some(recorder) = self.metrics_recorder.as_ref() else {
+            return false;
+        };
+        match category {
+            Some(category) => recorder.set_category_enabled(category, enabled),
+            None => recorder.set_enabled(enabled),
+        }
+        true
+    }
 }
 
 /// Create a new event loop from a built simulation.
@@ -1344,3 +2555,82 @@ pub fn create_event_loop(
 
 // Re-export key types for convenience
 pub use mcsim_model::{build_simulation, load_model, load_model_from_str, BuiltSimulation as SimulationBuild, ModelLoader};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshcore_packet::{AdvertPayload, MeshCorePacket, PacketPayload, RouteType};
+
+    #[test]
+    fn test_flood_hop_limit() {
+        let advert = PacketPayload::Advert(AdvertPayload::new([0u8; 32], 1, [0u8; 64], "A"));
+        let mut flood = MeshCorePacket::new(RouteType::Flood, advert.clone());
+        flood.path = vec![0xAB; 3];
+
+        // A node's own limit, or the firmware maximum for forwarding roles
+        assert!(flood_hops_exhausted(Some(3), &flood));
+        assert!(!flood_hops_exhausted(Some(4), &flood));
+        assert!(!flood_hops_exhausted(Some(mcsim_firmware::MAX_FLOOD_HOPS), &flood));
+        flood.path = vec![0xAB; mcsim_firmware::MAX_FLOOD_HOPS as usize];
+        assert!(flood_hops_exhausted(Some(mcsim_firmware::MAX_FLOOD_HOPS), &flood));
+
+        // Companions don't forward, and direct packets aren't counted
+        assert!(!flood_hops_exhausted(None, &flood));
+        let mut direct = MeshCorePacket::new(RouteType::Direct, advert);
+        direct.path = vec![0xAB; 3];
+        assert!(!flood_hops_exhausted(Some(1), &direct));
+
+        let node = |node_type: &str, flood_max| mcsim_model::NodeInfo {
+            node_type: node_type.to_string(),
+            flood_max,
+            ..Default::default()
+        };
+        assert_eq!(flood_hop_limit(&node("Repeater", Some(3))), Some(3));
+        assert_eq!(flood_hop_limit(&node("RoomServer", None)), Some(mcsim_firmware::MAX_FLOOD_HOPS));
+        assert_eq!(flood_hop_limit(&node("Companion", None)), None);
+    }
+
+    /// Writer that keeps its bytes readable after being boxed.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace_recorder_output_and_budget() {
+        let timer = |timer_id| TraceEntry {
+            origin: "Alice".to_string(),
+            origin_id: "1".to_string(),
+            timestamp: trace_timestamp(SimTime::from_secs(1.0)),
+            sim_time_s: None,
+            payload: TracePayload::Timer(TimerPayload { timer_id }),
+        };
+        let buf = SharedBuf::default();
+        let budget = ArtifactBudget::new(1_000_000);
+        let mut trace = TraceRecorder::new(Some(Box::new(buf.clone())));
+        trace.set_budget(budget.clone());
+        trace.record(timer(1));
+        trace.record(timer(2));
+        trace.flush().unwrap();
+
+        // Same bytes as serializing the whole array at once
+        let output = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let expected = serde_json::to_string_pretty(&[timer(1), timer(2)]).unwrap() + "\n";
+        assert_eq!(output, expected);
+        // Entries are charged with a separator each, not the enclosing brackets
+        assert_eq!(budget.used() as usize + 3, output.len());
+
+        let buf = SharedBuf::default();
+        TraceRecorder::new(Some(Box::new(buf.clone()))).flush().unwrap();
+        assert_eq!(buf.0.lock().unwrap().as_slice(), b"[]\n");
+    }
+}
@@ -9,13 +9,31 @@ mod build_model;
 // Use modules and types from the library crate
 use mcsim_runner::metric_spec;
 use mcsim_runner::metrics_export;
-use mcsim_runner::realtime::RealTimeConfig;
+use mcsim_runner::realtime::{Pacing, RealTimeConfig};
 #[cfg(feature = "rerun")]
 use mcsim_runner::rerun_blueprint;
-use mcsim_runner::rerun_logger::{RerunLogger, VisLinkInfo, VisNodeInfo};
-use mcsim_runner::uart_server::SyncUartManager;
+use mcsim_runner::rerun_logger::{vis_links, RerunLogger, VisNodeInfo};
+use mcsim_runner::alerts::AlertMonitor;
+use mcsim_runner::artifact_budget::{parse_size, ArtifactBudget};
+use mcsim_runner::assertions::{AssertionMonitor, EXIT_ASSERTION_FAILED};
+use mcsim_runner::calibration::CalibrationTolerances;
+use mcsim_runner::sla::SlaThresholds;
+use mcsim_runner::inspect::{InspectCommand, Inspector, HELP};
+use mcsim_runner::input_replay::{InputLog, RecordedModel, ReplayFile, RunOutcome, REPLAY_FILE_VERSION};
+use mcsim_runner::chrome_trace::ChromeTrace;
+use mcsim_runner::control_server::ControlServer;
+#[cfg(feature = "dashboard")]
+use mcsim_runner::dashboard::Dashboard;
+#[cfg(feature = "mqtt")]
+use mcsim_runner::mqtt_bridge::{MqttBridge, MqttFeed};
+use mcsim_runner::metrics_server::MetricsServer;
+use mcsim_runner::packet_capture::PacketCapture;
+use mcsim_runner::script_reload::ScriptReloader;
+use mcsim_runner::serial_capture::SerialCapture;
+use mcsim_runner::wall_clock::WallClock;
+use mcsim_runner::uart_server::{SerialLatency, SyncUartManager};
 use mcsim_runner::watchdog::Watchdog;
-use mcsim_runner::{EventLoop, ProgressInfo, RunnerError, SimulationStats, SimTime};
+use mcsim_runner::{ControlHandle, EventLoop, ProgressInfo, RunnerError, SimulationStats, SimTime};
 
 use clap::{Parser, Subcommand, ValueEnum};
 use mcsim_common::entity_tracer::{EntityTracer, EntityTracerConfig};
@@ -33,6 +51,9 @@ use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 /// Default watchdog timeout in seconds (matches RUNNER_WATCHDOG_TIMEOUT_S property)
 pub const DEFAULT_WATCHDOG_TIMEOUT_S: u64 = 10;
 
+/// How long the interactive console waits for the simulation to answer.
+const CONSOLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 // ============================================================================
 // Duration Parsing
 // ============================================================================
@@ -113,6 +134,78 @@ pub enum MetricsOutputFormat {
     Csv,
 }
 
+/// Output format for the metric catalog.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum MetricsCatalogFormat {
+    /// Human-readable listing grouped by category.
+    #[default]
+    Text,
+    /// JSON array of metric metadata.
+    Json,
+    /// Markdown table.
+    Markdown,
+}
+
+/// Configuration for listing the metric catalog
+#[derive(Parser, Debug)]
+pub struct MetricsCatalogConfig {
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: MetricsCatalogFormat,
+
+    /// Output file (stdout if not specified)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Scope filter for property lookup.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum PropertyScopeArg {
+    /// Properties of individual nodes.
+    Node,
+    /// Properties of links between nodes.
+    Edge,
+    /// Simulation-wide properties.
+    Simulation,
+}
+
+impl From<PropertyScopeArg> for mcsim_model::PropertyScope {
+    fn from(scope: PropertyScopeArg) -> Self {
+        match scope {
+            PropertyScopeArg::Node => mcsim_model::PropertyScope::Node,
+            PropertyScopeArg::Edge => mcsim_model::PropertyScope::Edge,
+            PropertyScopeArg::Simulation => mcsim_model::PropertyScope::Simulation,
+        }
+    }
+}
+
+/// Output format for property lookup.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum PropertiesFormat {
+    /// Human-readable listing.
+    #[default]
+    Text,
+    /// JSON array of property metadata.
+    Json,
+}
+
+/// Configuration for listing properties
+#[derive(Parser, Debug)]
+pub struct PropertiesConfig {
+    /// Only list properties whose name or alias contains this text
+    /// (case-insensitive), e.g. `radio` or `radio/tx_`
+    #[arg(short, long)]
+    filter: Option<String>,
+
+    /// Only list properties of this scope
+    #[arg(long, value_enum)]
+    scope: Option<PropertyScopeArg>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: PropertiesFormat,
+}
+
 /// MCSim - MeshCore Network Simulator
 #[derive(Parser, Debug)]
 #[command(name = "mcsim")]
@@ -126,11 +219,12 @@ pub struct Cli {
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Run a simulation from a YAML model file
-    Run(RunnerConfig),
+    Run(Box<RunnerConfig>),
     /// List all available metrics with descriptions and labels
-    Metrics,
-    /// List all available properties with descriptions and defaults
-    Properties,
+    Metrics(MetricsCatalogConfig),
+    /// List available properties with their types, defaults, scopes and descriptions
+    #[command(visible_alias = "props")]
+    Properties(PropertiesConfig),
     /// Predict link quality between two geographic coordinates using DEM and ITM
     PredictLink(PredictLinkConfig),
     /// Estimate true SNR distribution from observed measurements
@@ -139,6 +233,511 @@ pub enum Commands {
     BuildModel(BuildModelConfig),
     /// Generate Ed25519 keypairs with optional public key prefix matching
     Keygen(KeygenConfig),
+    /// Play back a finished run: a saved rerun.io recording (from `run --rerun-save`)
+    /// or a trace (from `run --output`)
+    Replay(ReplayConfig),
+    /// Step a simulation interactively and query its live state
+    Inspect(InspectConfig),
+    /// Render a geographic heatmap of channel utilization from a run's trace
+    Heatmap(HeatmapConfig),
+    /// Align the trace streams of two nodes, or of one node in two runs, and show where they diverge
+    TraceDiff(TraceDiffConfig),
+    /// Compare a run against the same run with jittered firmware timers
+    TimerJitter(TimerJitterConfig),
+    /// Map predicted coverage of a transmitter over an area (GeoTIFF or PNG)
+    Coverage(CoverageMapConfig),
+    /// Fit per-region link prediction corrections from field measurements (CSV)
+    CalibrateLink(CalibrateLinkConfig),
+    /// Download the AWS terrain tiles for an area ahead of an offline run
+    PrefetchElevation(PrefetchElevationConfig),
+    /// Fail each failure domain in turn and report the delivery impact
+    BlastRadius(BlastRadiusConfig),
+    /// Branch what-if continuations from named checkpoints of one run and compare them
+    WhatIf(WhatIfConfig),
+    /// Shrink a failing scenario to a minimal one that fails the same way
+    Minimize(MinimizeConfig),
+    /// Run a scenario with many seeds and summarize how the results vary
+    Experiment(ExperimentConfig),
+    /// Run every combination of a scenario's parameter sweep and roll up the results
+    Sweep(SweepConfig),
+    /// Host simulations for several users, each with its own artifacts, control API and quotas
+    Serve(ServeConfig),
+}
+
+/// Configuration for coverage map generation
+#[derive(Parser, Debug)]
+#[command(allow_hyphen_values = true)]
+pub struct CoverageMapConfig {
+    /// Path(s) to YAML configuration file(s) for prediction properties.
+    /// Uses the standard model format - only the `simulation` section is read.
+    #[arg(short, long = "config", value_name = "FILE")]
+    pub configs: Vec<PathBuf>,
+
+    /// Latitude of the transmitter (degrees)
+    pub lat: f64,
+    /// Longitude of the transmitter (degrees)
+    pub lon: f64,
+
+    /// Area to map: min_lat,min_lon,max_lat,max_lon (default: the extent of --boundary)
+    #[arg(long, value_name = "BOX", required_unless_present = "boundary")]
+    pub bounds: Option<String>,
+    /// GeoJSON file with the polygon(s) to map; cells outside are skipped
+    #[arg(long, value_name = "FILE")]
+    pub boundary: Option<PathBuf>,
+    /// GeoJSON file with polygon(s) to leave out, e.g. water bodies (repeatable)
+    #[arg(long, value_name = "FILE")]
+    pub exclude: Vec<PathBuf>,
+    /// Cell size in meters
+    #[arg(long, default_value = "250")]
+    pub resolution: f64,
+    /// Output file: .tif/.tiff writes a GeoTIFF of SNR values, .png a colored image
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Height of the transmitter antenna above ground (meters)
+    #[arg(long, default_value = "2.0")]
+    pub height: f64,
+    /// Height of the receiver antenna above ground (meters)
+    #[arg(long, default_value = "2.0")]
+    pub rx_height: f64,
+    /// Frequency in MHz (overrides config file)
+    #[arg(long)]
+    pub freq: Option<f64>,
+    /// TX power in dBm (overrides config file)
+    #[arg(long)]
+    pub tx_power: Option<i8>,
+    /// Spreading factor (7-12) (overrides config file)
+    #[arg(long)]
+    pub sf: Option<u8>,
+    /// Number of terrain samples per path (overrides config file)
+    #[arg(long)]
+    pub samples: Option<usize>,
+    /// Elevation data source: 'aws' or 'local_dem' (overrides config file)
+    #[arg(long, value_name = "SOURCE")]
+    pub elevation_source: Option<String>,
+    /// DEM data directory for local USGS tiles (overrides config file)
+    #[arg(long)]
+    pub dem_dir: Option<PathBuf>,
+    /// Cache directory for AWS terrain tiles (overrides config file)
+    #[arg(long)]
+    pub elevation_cache: Option<PathBuf>,
+    /// Zoom level for AWS terrain tiles (overrides config file)
+    #[arg(long)]
+    pub zoom: Option<u8>,
+    /// Never download AWS terrain tiles: use only cached ones, and fail on a
+    /// missing tile unless --offline-elevation is set (overrides config file)
+    #[arg(long)]
+    pub offline: bool,
+    /// Elevation in meters for points on AWS tiles missing from the cache;
+    /// implies --offline (overrides config file)
+    #[arg(long, value_name = "METERS")]
+    pub offline_elevation: Option<f64>,
+    /// Link calibration (from `calibrate-link`) to correct predictions with
+    #[arg(long, value_name = "FILE")]
+    pub calibration: Option<PathBuf>,
+}
+
+/// Configuration for fitting a link calibration
+#[derive(Parser, Debug)]
+pub struct CalibrateLinkConfig {
+    /// CSV of field measurements with columns from_lat, from_lon, to_lat,
+    /// to_lon, sf and rssi_dbm and/or snr_db (optionally tx_power_dbm)
+    pub measurements: PathBuf,
+    /// Output JSON file for the calibration
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// Path(s) to YAML configuration file(s) for prediction properties.
+    /// Uses the standard model format - only the `simulation` section is read.
+    #[arg(short, long = "config", value_name = "FILE")]
+    pub configs: Vec<PathBuf>,
+    /// Size of the grid cells that get their own correction (degrees)
+    #[arg(long, default_value = "0.25")]
+    pub cell_size: f64,
+    /// Fewest measurements a cell needs for its own correction
+    #[arg(long, default_value = "5")]
+    pub min_samples: usize,
+
+    /// Height of the transmitting antenna above ground (meters)
+    #[arg(long, default_value = "2.0")]
+    pub from_height: f64,
+    /// Height of the receiving antenna above ground (meters)
+    #[arg(long, default_value = "2.0")]
+    pub to_height: f64,
+    /// Frequency in MHz (overrides config file)
+    #[arg(long)]
+    pub freq: Option<f64>,
+    /// TX power in dBm of rows without tx_power_dbm (overrides config file)
+    #[arg(long)]
+    pub tx_power: Option<i8>,
+    /// Number of terrain samples per path (overrides config file)
+    #[arg(long)]
+    pub samples: Option<usize>,
+    /// Elevation data source: 'aws' or 'local_dem' (overrides config file)
+    #[arg(long, value_name = "SOURCE")]
+    pub elevation_source: Option<String>,
+    /// DEM data directory for local USGS tiles (overrides config file)
+    #[arg(long)]
+    pub dem_dir: Option<PathBuf>,
+    /// Cache directory for AWS terrain tiles (overrides config file)
+    #[arg(long)]
+    pub elevation_cache: Option<PathBuf>,
+    /// Zoom level for AWS terrain tiles (overrides config file)
+    #[arg(long)]
+    pub zoom: Option<u8>,
+    /// Never download AWS terrain tiles: use only cached ones, and fail on a
+    /// missing tile unless --offline-elevation is set (overrides config file)
+    #[arg(long)]
+    pub offline: bool,
+    /// Elevation in meters for points on AWS tiles missing from the cache;
+    /// implies --offline (overrides config file)
+    #[arg(long, value_name = "METERS")]
+    pub offline_elevation: Option<f64>,
+}
+
+/// Configuration for bulk elevation tile download
+#[derive(Parser, Debug)]
+pub struct PrefetchElevationConfig {
+    /// YAML model file(s). The tiles covering their nodes are downloaded, and
+    /// the `simulation` section supplies the cache directory and zoom level.
+    pub models: Vec<PathBuf>,
+
+    /// Area to download: min_lat,min_lon,max_lat,max_lon (default: the extent of the models' nodes)
+    #[arg(long, value_name = "BOX", allow_hyphen_values = true, required_unless_present = "models")]
+    pub bounds: Option<String>,
+    /// Cache directory for AWS terrain tiles (overrides config file)
+    #[arg(long)]
+    pub elevation_cache: Option<PathBuf>,
+    /// Zoom level for AWS terrain tiles (overrides config file)
+    #[arg(long)]
+    pub zoom: Option<u8>,
+    /// Number of tiles to download in parallel
+    #[arg(short, long, default_value = "8")]
+    pub jobs: usize,
+}
+
+/// Configuration for the timer jitter stress test
+#[derive(Parser, Debug)]
+pub struct TimerJitterConfig {
+    /// Path(s) to YAML model file(s). Multiple files are merged in order.
+    #[arg(required = true)]
+    pub models: Vec<PathBuf>,
+
+    /// Simulation duration of each run.
+    /// Accepts plain seconds or units: 60, 60s, 10m, 2h, etc.
+    #[arg(short, long, value_parser = parse_duration)]
+    pub duration: f64,
+
+    /// Maximum timer jitter in milliseconds, applied in either direction
+    #[arg(long, default_value = "5")]
+    pub jitter: f64,
+
+    /// Number of jittered runs, each with a different jitter sequence
+    #[arg(long, default_value = "1")]
+    pub runs: u32,
+
+    /// Random seed shared by all runs (default: random)
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+}
+
+/// Configuration for the failure domain blast radius report
+#[derive(Parser, Debug)]
+pub struct BlastRadiusConfig {
+    /// Path(s) to YAML model file(s). Multiple files are merged in order.
+    #[arg(required = true)]
+    pub models: Vec<PathBuf>,
+
+    /// Simulation duration of each run.
+    /// Accepts plain seconds or units: 60, 60s, 10m, 2h, etc.
+    #[arg(short, long, value_parser = parse_duration)]
+    pub duration: f64,
+
+    /// When each domain fails; delivery is compared from then on.
+    /// Accepts plain seconds or units: 60, 60s, 10m, 2h, etc.
+    #[arg(long, value_parser = parse_duration)]
+    pub fail_at: f64,
+
+    /// Random seed shared by all runs (default: random)
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Write the full report as JSON to this file
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Configuration for what-if branches from named checkpoints
+#[derive(Parser, Debug)]
+pub struct WhatIfConfig {
+    /// Path(s) to YAML model file(s). Multiple files are merged in order.
+    #[arg(required = true)]
+    pub models: Vec<PathBuf>,
+
+    /// YAML plan of the checkpoints and the branches continuing from them
+    #[arg(long)]
+    pub plan: PathBuf,
+
+    /// Simulation duration of each branch.
+    /// Accepts plain seconds or units: 60, 60s, 10m, 2h, etc.
+    #[arg(short, long, value_parser = parse_duration)]
+    pub duration: f64,
+
+    /// Random seed shared by all branches (default: random)
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Write the full report as JSON to this file
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Configuration for shrinking a failing scenario
+#[derive(Parser, Debug)]
+pub struct MinimizeConfig {
+    /// Path(s) to YAML model file(s). Multiple files are merged in order.
+    #[arg(required = true)]
+    pub models: Vec<PathBuf>,
+
+    /// Simulation duration of the failing run.
+    /// Accepts plain seconds or units: 60, 60s, 10m, 2h, etc.
+    #[arg(short, long, value_parser = parse_duration)]
+    pub duration: f64,
+
+    /// Random seed of the failing run
+    #[arg(short, long)]
+    pub seed: u64,
+
+    /// Most runs to make while shrinking
+    #[arg(long, default_value = "200")]
+    pub max_runs: usize,
+
+    /// Directory to write the shrunk model files to
+    #[arg(short, long, default_value = "repro")]
+    pub output: PathBuf,
+}
+
+/// Configuration for Monte Carlo experiments
+#[derive(Parser, Debug)]
+pub struct ExperimentConfig {
+    /// Path(s) to YAML model file(s). Multiple files are merged in order.
+    #[arg(required = true)]
+    pub models: Vec<PathBuf>,
+
+    /// Simulation duration of each run.
+    /// Accepts plain seconds or units: 60, 60s, 10m, 2h, etc.
+    #[arg(short, long, value_parser = parse_duration)]
+    pub duration: f64,
+
+    /// Number of runs
+    #[arg(short, long, default_value = "10")]
+    pub runs: usize,
+
+    /// Seed of the first run; run i uses seed + i (default: random)
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Runs to execute in parallel, each in its own process
+    #[arg(short, long, default_value = "1")]
+    pub jobs: usize,
+
+    /// Metrics to summarize, as for `run --metric` (totals only).
+    /// Can be specified multiple times (default: all metrics).
+    #[arg(long = "metric", value_name = "SPEC")]
+    pub metric_specs: Vec<String>,
+
+    /// Write the report to this file: the summaries as CSV if it ends in
+    /// .csv, otherwise the summaries and every run's values as JSON
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Configuration for parameter sweeps
+#[derive(Parser, Debug)]
+pub struct SweepConfig {
+    /// Path(s) to YAML model file(s) with a `sweep` section. Multiple files
+    /// are merged in order.
+    #[arg(required = true)]
+    pub models: Vec<PathBuf>,
+
+    /// Simulation duration of each run.
+    /// Accepts plain seconds or units: 60, 60s, 10m, 2h, etc.
+    #[arg(short, long, value_parser = parse_duration)]
+    pub duration: f64,
+
+    /// Random seed shared by all runs (default: random)
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Runs to execute in parallel, each in its own process
+    #[arg(short, long, default_value = "1")]
+    pub jobs: usize,
+
+    /// Metrics to roll up, as for `run --metric` (totals only).
+    /// Can be specified multiple times (default: all metrics).
+    #[arg(long = "metric", value_name = "SPEC")]
+    pub metric_specs: Vec<String>,
+
+    /// Directory for the output of each run (one subdirectory per
+    /// combination) and the roll-up (summary.csv, summary.json)
+    #[arg(short, long)]
+    pub output_dir: PathBuf,
+}
+
+/// Configuration for the multi-tenant simulation server
+#[derive(Parser, Debug)]
+pub struct ServeConfig {
+    /// Address to accept JSON-RPC clients on. Addresses other than loopback
+    /// need --tenants
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:7900")]
+    pub listen: String,
+
+    /// YAML file mapping each tenant allowed to use the server to its token
+    /// (e.g. `alice: 8f3c...`); requests must then carry the tenant's token
+    #[arg(long, value_name = "FILE")]
+    pub tenants: Option<PathBuf>,
+
+    /// Directory clients' model file paths are read from; without it only
+    /// inline yaml models are accepted
+    #[arg(long, value_name = "DIR")]
+    pub models_dir: Option<PathBuf>,
+
+    /// Directory for the artifacts of each simulation (one subdirectory per
+    /// simulation)
+    #[arg(long, value_name = "DIR", default_value = "mcsim-server")]
+    pub data_dir: PathBuf,
+
+    /// Most simulations running at once
+    #[arg(long, value_name = "N")]
+    pub max_simulations: Option<usize>,
+
+    /// Most simulations running at once for one tenant
+    #[arg(long, value_name = "N")]
+    pub max_per_tenant: Option<usize>,
+
+    /// Longest simulated duration of a simulation; when set, realtime
+    /// simulations are refused. Accepts plain seconds or units: 60, 10m, 2h
+    #[arg(long, value_parser = parse_duration)]
+    pub max_duration: Option<f64>,
+
+    /// Longest wall-clock time a simulation may run before it is stopped.
+    /// Accepts plain seconds or units: 60, 10m, 2h
+    #[arg(long, value_parser = parse_duration)]
+    pub max_wall_time: Option<f64>,
+
+    /// Cap on the trace and capture files of each simulation (e.g. 500M)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_artifact_size: Option<u64>,
+
+    /// First UART port of the first simulation
+    #[arg(long, default_value = "10000")]
+    pub uart_base_port: u16,
+
+    /// UART ports reserved for each simulation (at least its node count)
+    #[arg(long, default_value = "100")]
+    pub uart_ports: u16,
+}
+
+/// Configuration for the channel utilization heatmap
+#[derive(Parser, Debug)]
+pub struct HeatmapConfig {
+    /// Path(s) to the YAML model file(s) the trace was recorded with.
+    #[arg(required = true)]
+    pub models: Vec<PathBuf>,
+
+    /// Trace file written by `run --output`
+    #[arg(long)]
+    pub trace: PathBuf,
+
+    /// Output GeoJSON file (stdout if not specified)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Run length in seconds used as the occupancy denominator
+    /// (default: end of the last packet in the trace).
+    /// Accepts plain seconds or units: 60, 60s, 10m, 2h, etc.
+    #[arg(short, long, value_parser = parse_duration)]
+    pub duration: Option<f64>,
+
+    /// Grid cell size in meters
+    #[arg(long, default_value = "500")]
+    pub cell_size: f64,
+
+    /// Distance the grid extends past the outermost nodes, in meters
+    #[arg(long, default_value = "2000")]
+    pub margin: f64,
+
+    /// Log-distance path loss exponent (2.0 = free space)
+    #[arg(long, default_value = "3.0")]
+    pub path_loss_exponent: f64,
+
+    /// Occupancy (0-1) at or above which a cell is marked saturated
+    #[arg(long, default_value = "0.3")]
+    pub saturation: f64,
+}
+
+/// Configuration for comparing the trace streams of two nodes
+#[derive(Parser, Debug)]
+pub struct TraceDiffConfig {
+    /// Trace file written by `run --output`
+    pub trace: PathBuf,
+
+    /// Second trace file, to compare the node across two runs
+    pub other_trace: Option<PathBuf>,
+
+    /// Node whose stream is compared
+    #[arg(long)]
+    pub node: String,
+
+    /// Node to compare it with (default: the same node in the second trace)
+    #[arg(long)]
+    pub with: Option<String>,
+
+    /// Compare only what happened, not which packets were involved
+    #[arg(long)]
+    pub ignore_payload: bool,
+
+    /// Events to look ahead in each stream when realigning after a difference
+    #[arg(long, default_value = "32")]
+    pub window: usize,
+
+    /// Aligned events to show from the first divergence
+    #[arg(long, default_value = "20")]
+    pub show: usize,
+
+    /// Write the full alignment as JSON to this file
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Configuration for the interactive state inspector
+#[derive(Parser, Debug)]
+pub struct InspectConfig {
+    /// Path(s) to YAML model file(s). Multiple files are merged in order.
+    #[arg(required = true)]
+    pub models: Vec<PathBuf>,
+
+    /// Random seed (default: random)
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+}
+
+/// Configuration for playing back a finished run
+#[derive(Parser, Debug)]
+pub struct ReplayConfig {
+    /// Path to the recording (.rrd) or trace file (JSON)
+    pub recording: PathBuf,
+
+    /// Model file(s) of the traced run, to place its nodes and links
+    #[arg(long = "model")]
+    pub models: Vec<PathBuf>,
+
+    /// Write the recording rebuilt from a trace here instead of opening it
+    #[arg(long)]
+    pub save: Option<PathBuf>,
+
+    /// Serve the recording to the web viewer instead of the native viewer
+    #[arg(long)]
+    pub web: bool,
 }
 
 /// Configuration for key generation
@@ -220,6 +819,29 @@ pub struct BuildModelConfig {
     #[arg(long, default_value = "2")]
     pub pubkey_prefix_len: usize,
 
+    /// Add per-packet fading to predicted links: Rician on line-of-sight paths, Rayleigh on obstructed ones
+    #[arg(long)]
+    pub fading: bool,
+
+    /// Recompute every link instead of reusing the predictions cached in the elevation cache directory
+    #[arg(long)]
+    pub no_link_cache: bool,
+
+    /// Never download AWS terrain tiles: use only cached ones, and fail on a
+    /// missing tile unless --offline-elevation is set
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Elevation in meters for points on AWS tiles missing from the cache; implies --offline
+    #[arg(long, value_name = "METERS")]
+    pub offline_elevation: Option<f64>,
+
+    /// Also write a map of the network (nodes, links colored by quality,
+    /// coverage outlines): .kml for Google Earth, .geojson/.json for GIS
+    /// tools. Repeat to write both.
+    #[arg(long = "map", value_name = "FILE")]
+    pub maps: Vec<PathBuf>,
+
     /// Verbose output
     #[arg(short, long)]
     pub verbose: bool,
@@ -277,12 +899,16 @@ pub struct PredictLinkConfig {
     pub to_lat: f64,
     /// Longitude of the receiver (degrees) - REQUIRED
     pub to_lon: f64,
-    /// Height of the transmitter above ground (meters, default: 2.0)
+    /// Height of the transmitter antenna (meters, default: 2.0)
     #[arg(long, default_value = "2.0")]
     pub from_height: f64,
-    /// Height of the receiver above ground (meters, default: 2.0)
+    /// Height of the receiver antenna (meters, default: 2.0)
     #[arg(long, default_value = "2.0")]
     pub to_height: f64,
+    /// How antenna heights are measured: 'agl' (above ground) or 'msl'
+    /// (absolute elevation above sea level) (overrides config file)
+    #[arg(long, value_name = "MODE")]
+    pub height_mode: Option<String>,
     /// Frequency in MHz (overrides config file)
     #[arg(long)]
     pub freq: Option<f64>,
@@ -292,6 +918,10 @@ pub struct PredictLinkConfig {
     /// TX power in dBm (overrides config file)
     #[arg(long)]
     pub tx_power: Option<i8>,
+    /// TX power of the receiving node in dBm, to also report the reverse
+    /// direction (default: same as --tx-power)
+    #[arg(long)]
+    pub reverse_tx_power: Option<i8>,
     /// Spreading factor (7-12) (overrides config file)
     #[arg(long)]
     pub sf: Option<u8>,
@@ -307,6 +937,21 @@ pub struct PredictLinkConfig {
     /// Zoom level for AWS terrain tiles (1-14, default: 12)
     #[arg(long)]
     pub zoom: Option<u8>,
+    /// Never download AWS terrain tiles: use only cached ones, and fail on a
+    /// missing tile unless --offline-elevation is set (overrides config file)
+    #[arg(long)]
+    pub offline: bool,
+    /// Elevation in meters for points on AWS tiles missing from the cache;
+    /// implies --offline (overrides config file)
+    #[arg(long, value_name = "METERS")]
+    pub offline_elevation: Option<f64>,
+    /// Directory of land-cover GeoTIFFs (e.g. ESA WorldCover) to add clutter
+    /// loss from forests and buildings
+    #[arg(long, value_name = "DIR")]
+    pub land_cover: Option<PathBuf>,
+    /// Link calibration (from `calibrate-link`) to correct the prediction with
+    #[arg(long, value_name = "FILE")]
+    pub calibration: Option<PathBuf>,
 }
 
 /// Resolved configuration with all required fields.
@@ -318,14 +963,19 @@ pub struct ResolvedPredictLinkConfig {
     pub to_lon: f64,
     pub from_height: f64,
     pub to_height: f64,
+    pub height_mode: mcsim_link::AntennaHeightMode,
     pub freq: f64,
     pub dem_dir: PathBuf,
     pub tx_power: i8,
+    pub reverse_tx_power: Option<i8>,
     pub sf: u8,
     pub samples: usize,
     pub elevation_source: String,  // "aws" or "local_dem"
     pub elevation_cache: PathBuf,
     pub zoom: u8,
+    pub offline_mode: mcsim_link::OfflineMode,
+    pub land_cover: Option<PathBuf>,
+    pub calibration: Option<PathBuf>,
 }
 
 impl PredictLinkConfig {
@@ -336,6 +986,7 @@ impl PredictLinkConfig {
             PREDICT_FREQUENCY_MHZ, PREDICT_TX_POWER_DBM, PREDICT_SPREADING_FACTOR,
             PREDICT_DEM_DIR, PREDICT_TERRAIN_SAMPLES,
             PREDICT_ELEVATION_CACHE_DIR, PREDICT_ELEVATION_SOURCE, PREDICT_ELEVATION_ZOOM_LEVEL,
+            PREDICT_ANTENNA_HEIGHT_MODE,
         };
         
         // Load simulation properties from YAML files (if any)
@@ -358,6 +1009,10 @@ impl PredictLinkConfig {
         let elevation_source = self.elevation_source.clone().unwrap_or_else(|| props.get::<String>(&PREDICT_ELEVATION_SOURCE));
         let elevation_cache = self.elevation_cache.clone().unwrap_or_else(|| PathBuf::from(props.get::<String>(&PREDICT_ELEVATION_CACHE_DIR)));
         let zoom = self.zoom.unwrap_or_else(|| props.get(&PREDICT_ELEVATION_ZOOM_LEVEL));
+        let height_mode = self.height_mode.clone()
+            .unwrap_or_else(|| props.get::<String>(&PREDICT_ANTENNA_HEIGHT_MODE))
+            .parse::<mcsim_link::AntennaHeightMode>()
+            .map_err(|e| RunnerError::ConfigError(format!("{}", e)))?;
         
         Ok(ResolvedPredictLinkConfig {
             from_lat: self.from_lat,
@@ -366,14 +1021,19 @@ impl PredictLinkConfig {
             to_lon: self.to_lon,
             from_height: self.from_height,
             to_height: self.to_height,
+            height_mode,
             freq,
             dem_dir,
             tx_power,
+            reverse_tx_power: self.reverse_tx_power,
             sf,
             samples,
             elevation_source,
             elevation_cache,
             zoom,
+            offline_mode: mcsim_link::resolve_offline_mode(&props, self.offline, self.offline_elevation),
+            land_cover: self.land_cover.clone(),
+            calibration: self.calibration.clone(),
         })
     }
 }
@@ -407,13 +1067,18 @@ pub struct EstimateSnrConfig {
     /// Output format: text or json (default: text)
     #[arg(long, default_value = "text")]
     pub format: String,
+
+    /// Also fit a two-component mixture and report it when the observations
+    /// are bimodal.
+    #[arg(long)]
+    pub mixture: bool,
 }
 
 /// Configuration for running a simulation
 #[derive(Parser, Debug)]
 pub struct RunnerConfig {
     /// Path(s) to YAML model file(s). Multiple files are merged in order (later overrides earlier).
-    #[arg(required = true)]
+    #[arg(required_unless_present = "replay")]
     pub models: Vec<PathBuf>,
 
     /// Simulation duration (omit for realtime mode).
@@ -433,11 +1098,23 @@ pub struct RunnerConfig {
     #[arg(short = 'p', long, default_value = "9000")]
     pub uart_base_port: u16,
 
+    /// Also expose each node's UART as a pseudo-terminal, linked in this
+    /// directory by node name (e.g. DIR/Alice -> /dev/pts/7), for host
+    /// applications that expect a serial device. Unix only
+    #[arg(long, value_name = "DIR")]
+    pub pty_dir: Option<PathBuf>,
+
     /// Enable rerun.io visualization (spawns viewer UI)
     /// Requires the 'rerun' feature to be enabled at compile time.
     #[arg(long)]
     pub rerun: bool,
 
+    /// Save the rerun.io visualization to a recording file (.rrd) instead of
+    /// spawning the viewer. Play it back later with `mcsim replay <FILE>`.
+    /// Requires the 'rerun' feature to be enabled at compile time.
+    #[arg(long, value_name = "FILE")]
+    pub rerun_save: Option<PathBuf>,
+
     /// Verbose output
     #[arg(short, long)]
     pub verbose: bool,
@@ -456,6 +1133,11 @@ pub struct RunnerConfig {
     #[arg(long)]
     pub metrics_file: Option<PathBuf>,
 
+    /// Serve live metrics in Prometheus format at http://ADDR/metrics while
+    /// the simulation runs (e.g. 0.0.0.0:9090).
+    #[arg(long, value_name = "ADDR")]
+    pub metrics_listen: Option<String>,
+
     /// Metric specifications for export. Can be specified multiple times.
     /// Format: <pattern>[/<breakdown1>[/<breakdown2>...]]
     /// Examples:
@@ -482,6 +1164,15 @@ pub struct RunnerConfig {
     #[arg(long, default_value = "100")]
     pub max_catchup_ms: u64,
 
+    /// Pace simulation time against the wall clock: fast (as fast as
+    /// possible), realtime, or a multiplier such as 10x or 0.5. Timed runs
+    /// default to fast, realtime mode to --speed. Use realtime when external
+    /// clients attach over the UART bridge so their timeouts behave; a paced
+    /// timed run takes their serial input too. Can be changed while running
+    /// with the `pace` control method or console command.
+    #[arg(long, value_name = "PACING", conflicts_with = "replay")]
+    pub pacing: Option<Pacing>,
+
     /// Break at a specific event number (for debugging slow events).
     /// When the event loop reaches this event, it will pause and print details.
     /// Use with the same --seed value to reproduce event sequences.
@@ -499,6 +1190,163 @@ pub struct RunnerConfig {
     /// Overrides the metrics/warmup_s property from the model.
     #[arg(long, value_parser = parse_duration)]
     pub metrics_warmup: Option<f64>,
+
+    /// Also show trace and report timestamps as wall-clock datetimes in this
+    /// IANA timezone (e.g. America/Los_Angeles or UTC), counted from
+    /// firmware/initial_rtc_secs. Overrides the runner/timezone property.
+    #[arg(long, value_name = "TZ")]
+    pub timezone: Option<String>,
+
+    /// Cap the combined size of the trace, serial capture, pcapng capture and
+    /// Chrome trace files (e.g. 500M or 2G). Once reached, they are closed and
+    /// the run continues with metrics only. Overrides runner/max_artifact_mb.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_artifact_size: Option<u64>,
+
+    /// Write the serial traffic of selected nodes to one time-ordered JSON
+    /// Lines file, annotated with node and direction.
+    #[arg(long, value_name = "FILE")]
+    pub serial_capture: Option<PathBuf>,
+
+    /// Comma-separated node names to include in --serial-capture (default: all nodes).
+    #[arg(long, value_name = "NODES", requires = "serial_capture")]
+    pub serial_capture_nodes: Option<String>,
+
+    /// Write every over-the-air packet (transmissions and receptions) to a
+    /// pcapng file for analysis in Wireshark.
+    #[arg(long, value_name = "FILE")]
+    pub pcap: Option<PathBuf>,
+
+    /// Write every radio transmission, reception, delivery to firmware and
+    /// drop to an SQLite database with indexed time, node, packet hash and
+    /// payload type columns, for SQL analysis of the run. Requires the
+    /// 'sqlite' feature.
+    #[arg(long, value_name = "FILE")]
+    pub packet_db: Option<PathBuf>,
+
+    /// Write the timing of event dispatch, firmware steps and radio TX/RX as
+    /// a Chrome trace-event JSON file, one process per node, for Perfetto
+    /// (ui.perfetto.dev) or chrome://tracing.
+    #[arg(long, value_name = "FILE")]
+    pub perfetto: Option<PathBuf>,
+
+    /// Write a JSON report comparing each link's predicted SNR distribution
+    /// with the SNRs observed during the run. Links that drift from their
+    /// prediction are also listed on stderr. Each link also gets the lowest
+    /// spreading factor that closes it and the resulting airtime.
+    #[arg(long, value_name = "FILE")]
+    pub calibration_report: Option<PathBuf>,
+
+    /// Link margin in dB above the demodulation threshold that a spreading
+    /// factor recommended in the calibration report must leave.
+    #[arg(long, value_name = "DB", default_value_t = mcsim_runner::calibration::DEFAULT_SF_MARGIN_DB, requires = "calibration_report")]
+    pub sf_margin: f64,
+
+    /// Write a CSV estimating each link's SNR distribution from the packets
+    /// its receiver got and lost during the run, next to the configured link
+    /// parameters.
+    #[arg(long, value_name = "FILE")]
+    pub snr_estimates: Option<PathBuf>,
+
+    /// Write each node's bring-up timeline (boot, first advert heard, first
+    /// contact, first message, outages) as JSON. An SVG Gantt chart of it is
+    /// written instead if FILE ends in `.svg`.
+    #[arg(long, value_name = "FILE")]
+    pub timeline: Option<PathBuf>,
+
+    /// Write a JSON report of the adverts each node sent and its suppression
+    /// policy skipped (see `advert_suppression/*`), with how long neighbours
+    /// took to hear one, and print a summary to stderr.
+    #[arg(long, value_name = "FILE")]
+    pub advert_report: Option<PathBuf>,
+
+    /// Follow every message across hops by its payload hash and write the
+    /// path each node first heard it by, with per-hop timing and duplicate
+    /// copies, as JSON; print a summary to stderr. With --packet-db the
+    /// paths also go to its `message_paths` table.
+    #[arg(long, value_name = "FILE")]
+    pub message_paths: Option<PathBuf>,
+
+    /// Classify every node pair by flood delivery against an SLA (met, best
+    /// effort, unreachable), print a summary and write the per-pair report
+    /// as JSON.
+    #[arg(long, value_name = "FILE")]
+    pub sla_report: Option<PathBuf>,
+
+    /// Percentage of a node's floods another node must hear in time for the
+    /// pair to meet the SLA.
+    #[arg(long, value_name = "PERCENT", default_value_t = mcsim_runner::sla::DEFAULT_SLA_DELIVERY * 100.0, requires = "sla_report")]
+    pub sla_delivery: f64,
+
+    /// Delivery deadline of the SLA in seconds.
+    #[arg(long, value_name = "SECONDS", default_value_t = mcsim_runner::sla::DEFAULT_SLA_LATENCY_S, requires = "sla_report")]
+    pub sla_latency: f64,
+
+    /// Read console commands from stdin while the simulation runs: pause and
+    /// resume simulation time, inspect nodes, send CLI commands to a node,
+    /// move nodes and show link qualities. Type `help` for the commands.
+    #[arg(long)]
+    pub interactive: bool,
+
+    /// Reload the `traffic` section of the model files whenever they change
+    /// on disk, restarting each companion's scripted traffic without
+    /// restarting the run. `reload` at the console does the same on demand.
+    #[arg(long)]
+    pub watch_scripts: bool,
+
+    /// Run one combination of the scenario's `sweep` section, by its index
+    /// from 0 (the order `mcsim sweep` runs them in).
+    #[arg(long, value_name = "INDEX")]
+    pub sweep_point: Option<usize>,
+
+    /// Accept JSON-RPC control requests on ADDR (e.g. 127.0.0.1:7800): pause,
+    /// resume and stop the run, query nodes, send CLI commands and subscribe
+    /// to the event stream. One JSON request per line.
+    #[arg(long, value_name = "ADDR")]
+    pub control_listen: Option<String>,
+
+    /// Hold the simulation paused until a control client resumes it.
+    #[arg(long, requires = "control_listen")]
+    pub control_wait: bool,
+
+    /// Serve a live web dashboard at http://ADDR/ (e.g. 127.0.0.1:8080): the
+    /// nodes on a map, radio activity, per-node message counts and serial
+    /// output. Requires the 'dashboard' feature.
+    #[arg(long, value_name = "ADDR")]
+    pub dashboard: Option<String>,
+
+    /// Publish decoded packets, adverts and delivered messages to the MQTT
+    /// broker at HOST[:PORT] (default port 1883), as gateways bridge real
+    /// meshes. Requires the 'mqtt' feature.
+    #[arg(long, value_name = "BROKER")]
+    pub mqtt: Option<String>,
+
+    /// Prefix of the MQTT topics (<PREFIX>/<node>/packets, ...)
+    #[arg(long, value_name = "PREFIX", default_value = mcsim_runner::mqtt_bridge::DEFAULT_TOPIC_PREFIX, requires = "mqtt")]
+    pub mqtt_topic: String,
+
+    /// Take CLI command lines for each node from <PREFIX>/<node>/command and
+    /// publish the output on <PREFIX>/<node>/response
+    #[arg(long, requires = "mqtt")]
+    pub mqtt_commands: bool,
+
+    /// Record the run's external inputs (seed, model files and serial data
+    /// injected over the UART bridge) to a replay file.
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Reproduce a run recorded with --record: same models, seed and inputs,
+    /// same event sequence. Runs as fast as possible and reports whether the
+    /// event sequence matched the recording.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["models", "seed", "duration"])]
+    pub replay: Option<PathBuf>,
+
+    /// Check the scenario without running it: resolve all properties, check
+    /// node keys, find the firmware libraries and predict the links, then
+    /// print a report. No firmware is loaded. Exits with an error if the run
+    /// could not start.
+    #[arg(long, visible_alias = "dry-run")]
+    pub validate: bool,
 }
 
 // ============================================================================
@@ -623,6 +1471,87 @@ fn print_summary_table(event_loop: &EventLoop) {
 // Main Entry Point
 // ============================================================================
 
+/// Declare the scenario's custom metrics, so traffic rules and agents can
+/// emit them. Call after the metrics recorder is installed, so it receives
+/// their descriptions.
+fn register_custom_metrics(model: &mcsim_model::Model) -> Result<(), RunnerError> {
+    for metric in model.custom_metrics() {
+        mcsim_metrics::custom::register(metric.clone()).map_err(|e| RunnerError::ConfigError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Load and merge the model files of a scenario and declare its custom
+/// metrics.
+fn load_scenario(models: &[PathBuf]) -> Result<mcsim_model::Model, RunnerError> {
+    let paths: Vec<&Path> = models.iter().map(|p| p.as_path()).collect();
+    let model = mcsim_model::load_models(&paths)?;
+    register_custom_metrics(&model)?;
+    Ok(model)
+}
+
+/// Load and merge the model(s) of a run, or of the replayed run, and apply
+/// its sweep point.
+fn load_run_model(config: &RunnerConfig, replay: Option<&ReplayFile>) -> Result<mcsim_model::Model, RunnerError> {
+    let mut model = if let Some(replay) = replay {
+        replay.load_model()?
+    } else if config.models.len() == 1 {
+        load_model(&config.models[0])?
+    } else {
+        let paths: Vec<&Path> = config.models.iter().map(|p| p.as_path()).collect();
+        mcsim_model::load_models(&paths)?
+    };
+
+    // One combination of the scenario's parameter sweep, as run by `mcsim sweep`
+    if let Some(index) = config.sweep_point {
+        let points = model.sweep_points();
+        let point = points.get(index).ok_or_else(|| {
+            RunnerError::ConfigError(format!(
+                "--sweep-point {} is out of range: the scenario's sweep has {} combination(s)",
+                index,
+                points.len()
+            ))
+        })?;
+        if config.verbose {
+            eprintln!("Sweep point {}: {}", index, point);
+        }
+        model.apply_sweep_point(point);
+    } else if !model.sweep().is_empty() && config.verbose {
+        eprintln!("Note: ignoring the scenario's sweep section; use `mcsim sweep` to run it");
+    }
+    Ok(model)
+}
+
+/// Check a run's scenario without starting it: build everything but the
+/// firmware, check the firmware libraries and UART settings, predict the
+/// links and print a report.
+fn validate_scenario(config: &RunnerConfig) -> Result<(), RunnerError> {
+    let replay = config.replay.as_deref().map(ReplayFile::load).transpose()?;
+    let model = load_run_model(config, replay.as_ref())?;
+
+    let seed = replay.as_ref().map(|r| r.seed).or(config.seed).unwrap_or_else(|| {
+        use rand::Rng;
+        rand::thread_rng().gen()
+    });
+    let report = mcsim_model::validate_simulation(&model, seed)?;
+    for node_info in &report.node_infos {
+        SerialLatency::from_millis(
+            node_info.uart_latency_ms,
+            node_info.uart_jitter_ms,
+            &node_info.uart_jitter_distribution,
+        )
+        .map_err(|e| RunnerError::ConfigError(format!("Node '{}': {}", node_info.name, e)))?;
+    }
+
+    eprintln!("{}", report);
+    let errors = report.errors();
+    if !errors.is_empty() {
+        return Err(RunnerError::ConfigError(errors.join("\n")));
+    }
+    eprintln!("✓ Scenario is valid");
+    Ok(())
+}
+
 /// Run a simulation with the given configuration.
 pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerError> {
     // Parse metric specs early - these are used for both label filtering and export
@@ -643,12 +1572,88 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
             .collect()
     };
 
-    // Install metrics recorder if metrics export is requested or rerun is enabled
-    let metrics_recorder = if config.metrics_output.is_some() || config.rerun {
+    if !(0.0..=100.0).contains(&config.sla_delivery) || config.sla_latency <= 0.0 {
+        return Err(RunnerError::ConfigError(format!(
+            "--sla-delivery must be 0-100 and --sla-latency positive, got {} and {}",
+            config.sla_delivery, config.sla_latency
+        )));
+    }
+
+    if config.watch_scripts && (config.record.is_some() || config.replay.is_some()) {
+        return Err(RunnerError::ConfigError(
+            "--watch-scripts can't be combined with --record or --replay: script reloads are not recorded".to_string(),
+        ));
+    }
+
+    if config.sweep_point.is_some() && (config.record.is_some() || config.replay.is_some()) {
+        return Err(RunnerError::ConfigError(
+            "--sweep-point can't be combined with --record or --replay: the swept values are not recorded".to_string(),
+        ));
+    }
+
+    // A replay takes its models, seed and duration from the replay file
+    let replay = config.replay.as_deref().map(ReplayFile::load).transpose()?;
+
+    // Keep the model files as loaded so a recording can be replayed after
+    // they change
+    let recorded_models = if config.record.is_some() {
+        config
+            .models
+            .iter()
+            .map(|path| {
+                Ok(RecordedModel {
+                    path: path.display().to_string(),
+                    content: std::fs::read_to_string(path)?,
+                })
+            })
+            .collect::<Result<Vec<_>, RunnerError>>()?
+    } else {
+        Vec::new()
+    };
+
+    let model = load_run_model(&config, replay.as_ref())?;
+
+    if config.verbose {
+        let files = replay.as_ref().map_or(config.models.len(), |r| r.models.len());
+        eprintln!("Loaded model with {} nodes from {} file(s)", model.nodes().len(), files);
+    }
+
+    // Install metrics recorder if metrics export or the metrics endpoint is
+    // requested, rerun is enabled or the scenario has alert rules or assertions
+    let rerun_enabled = config.rerun || config.rerun_save.is_some();
+    let metrics_recorder = if config.metrics_output.is_some()
+        || config.metrics_listen.is_some()
+        || rerun_enabled
+        || !model.alerts().is_empty()
+        || !model.assertions().is_empty()
+        || config.interactive
+        || config.control_listen.is_some()
+        || config.dashboard.is_some()
+    {
         let recorder = Arc::new(metrics_export::InMemoryRecorder::new());
         // Set label filter based on metric specs - this ensures we only track
-        // the labels that are requested in the specs, preventing unbounded memory growth
-        recorder.set_label_filter(metric_specs.clone());
+        // the labels that are requested in the specs, preventing unbounded memory growth.
+        // Alerts and assertions on a single node need that metric's node label.
+        let mut label_specs = metric_specs.clone();
+        let node_metrics = model
+            .alerts()
+            .iter()
+            .filter(|rule| rule.node.is_some())
+            .map(|rule| rule.metric.as_str())
+            .chain(model.assertions().iter().filter_map(|assertion| match &assertion.check {
+                mcsim_model::AssertionCheck::Metric { metric, node: Some(_), .. } => Some(metric.as_str()),
+                _ => None,
+            }));
+        #[cfg(feature = "dashboard")]
+        let node_metrics = node_metrics.chain(
+            config.dashboard.iter().flat_map(|_| mcsim_runner::dashboard::NODE_METRICS.iter().copied()),
+        );
+        for metric in node_metrics {
+            if let Ok(spec) = metric_spec::MetricSpec::parse(&format!("{}/node", metric)) {
+                label_specs.push(spec);
+            }
+        }
+        recorder.set_label_filter(label_specs);
         if let Err(e) = metrics::set_global_recorder(recorder.clone()) {
             eprintln!("Warning: Failed to set metrics recorder: {}", e);
             None
@@ -661,26 +1666,43 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
         None
     };
 
+    if let (Some(addr), Some(recorder)) = (&config.metrics_listen, &metrics_recorder) {
+        let server = MetricsServer::start(addr, recorder.clone())
+            .map_err(|e| RunnerError::ConfigError(format!("Cannot serve metrics on {}: {}", addr, e)))?;
+        eprintln!("✓ Serving metrics at http://{}/metrics", server.local_addr());
+    }
+
     // Create tokio runtime for async TCP handling
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()
         .expect("Failed to create tokio runtime");
 
-    // Load and merge model(s)
-    let model = if config.models.len() == 1 {
-        load_model(&config.models[0])?
-    } else {
-        let paths: Vec<&Path> = config.models.iter().map(|p| p.as_path()).collect();
-        mcsim_model::load_models(&paths)?
-    };
+    // Declare scenario-defined custom metrics so agents can emit them
+    register_custom_metrics(&model)?;
+
+    // Catch nodes that can't reach the network before spending time on a run
+    match mcsim_model::UnreachablePolicy::from_model(&model)? {
+        mcsim_model::UnreachablePolicy::Ignore => {}
+        policy => {
+            let report = mcsim_model::analyze_connectivity(&model);
+            if !report.is_empty() {
+                if policy == mcsim_model::UnreachablePolicy::Fail {
+                    return Err(RunnerError::ConfigError(report.to_string()));
+                }
+                eprintln!("Warning: {}", report);
+            }
+        }
+    }
 
+    // Partitions, single points of failure and central nodes of the predicted links
+    let topology = mcsim_model::analyze_topology(&model, &mcsim_model::predict_links(&model));
     if config.verbose {
-        eprintln!("Loaded model with {} nodes from {} file(s)", model.nodes().len(), config.models.len());
+        eprintln!("{}", topology);
     }
 
     // Generate seed if not provided
-    let seed = config.seed.unwrap_or_else(|| {
+    let seed = replay.as_ref().map(|r| r.seed).or(config.seed).unwrap_or_else(|| {
         use rand::Rng;
         rand::thread_rng().gen()
     });
@@ -711,17 +1733,33 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
     
     // Then register all nodes - explicit ports will be used, others assigned sequentially
     for node_info in &simulation.node_infos {
+        let latency = SerialLatency::from_millis(
+            node_info.uart_latency_ms,
+            node_info.uart_jitter_ms,
+            &node_info.uart_jitter_distribution,
+        )
+        .map_err(|e| RunnerError::ConfigError(format!("Node '{}': {}", node_info.name, e)))?;
         uart_manager.register_node(
             node_info.firmware_entity_id,
             node_info.name.clone(),
             node_info.node_type.clone(),
             &node_info.public_key,
             node_info.uart_port,
+            latency,
         );
     }
     
+    if let Some(ref dir) = config.pty_dir {
+        uart_manager.set_pty_dir(dir.clone());
+    }
+
     // Start the UART TCP listeners
     uart_manager.start()?;
+    for info in uart_manager.node_infos() {
+        if let Some(pty) = info.pty {
+            eprintln!("🔌 {} UART: {} -> {}", info.name, pty.link.display(), pty.device.display());
+        }
+    }
 
     // Set up trace output
     let trace_output: Option<Box<dyn Write>> = if let Some(ref path) = config.output {
@@ -730,6 +1768,107 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
         None
     };
 
+    // Render timestamps in local time if a timezone is set
+    let timezone = config
+        .timezone
+        .clone()
+        .or_else(|| model.simulation_properties().get(&mcsim_model::RUNNER_TIMEZONE));
+    let wall_clock = match timezone {
+        Some(timezone) => Some(WallClock::new(
+            model.simulation_properties().get(&mcsim_model::FIRMWARE_INITIAL_RTC_SECS),
+            &timezone,
+        )?),
+        None => None,
+    };
+
+    // Cap the size of the trace and capture files
+    let artifact_budget = config
+        .max_artifact_size
+        .map(ArtifactBudget::new)
+        .or_else(|| {
+            model
+                .simulation_properties()
+                .get::<Option<f64>>(&mcsim_model::RUNNER_MAX_ARTIFACT_MB)
+                .map(ArtifactBudget::from_mb)
+        });
+    let create_artifact = |path: &Path| -> Result<Box<dyn Write>, RunnerError> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        Ok(match &artifact_budget {
+            Some(budget) => Box::new(budget.writer(file)),
+            None => Box::new(file),
+        })
+    };
+
+    // Set up serial capture for the selected nodes
+    let serial_capture = if let Some(ref path) = config.serial_capture {
+        let mut capture = SerialCapture::new(create_artifact(path)?);
+        if let Some(clock) = wall_clock {
+            capture.set_wall_clock(clock);
+        }
+        let selected: Option<Vec<&str>> = config
+            .serial_capture_nodes
+            .as_deref()
+            .map(|names| names.split(',').map(str::trim).filter(|n| !n.is_empty()).collect());
+        if let Some(ref names) = selected {
+            if let Some(unknown) = names.iter().find(|n| simulation.node_infos.iter().all(|info| info.name != **n)) {
+                return Err(RunnerError::ConfigError(format!(
+                    "Unknown node '{}' in --serial-capture-nodes",
+                    unknown
+                )));
+            }
+        }
+        for info in &simulation.node_infos {
+            if selected.as_ref().is_none_or(|names| names.contains(&info.name.as_str())) {
+                capture.add_node(info.firmware_entity_id, info.name.clone());
+            }
+        }
+        if config.verbose {
+            eprintln!("Serial capture: {}", path.display());
+        }
+        Some(capture)
+    } else {
+        None
+    };
+
+    // Set up pcapng capture of over-the-air packets, one interface per radio
+    let packet_capture = if let Some(ref path) = config.pcap {
+        let radios: Vec<(u64, String)> = simulation
+            .node_infos
+            .iter()
+            .map(|info| (info.radio_entity_id, info.name.clone()))
+            .collect();
+        let capture = PacketCapture::new(create_artifact(path)?, &radios)?;
+        if config.verbose {
+            eprintln!("Packet capture: {}", path.display());
+        }
+        Some(capture)
+    } else {
+        None
+    };
+
+    // Set up the per-packet event database
+    #[cfg(feature = "sqlite")]
+    let packet_db = if let Some(ref path) = config.packet_db {
+        let radios: Vec<(u64, String, String)> = simulation
+            .node_infos
+            .iter()
+            .map(|info| (info.radio_entity_id, info.name.clone(), info.node_type.clone()))
+            .collect();
+        let database = mcsim_runner::packet_db::PacketDatabase::create(path, &radios)?;
+        if config.verbose {
+            eprintln!("Packet database: {}", path.display());
+        }
+        Some(database)
+    } else {
+        None
+    };
+    #[cfg(not(feature = "sqlite"))]
+    if config.packet_db.is_some() {
+        return Err(RunnerError::ConfigError(
+            "--packet-db requires mcsim to be built with the 'sqlite' feature".to_string(),
+        ));
+    }
+
     // Set up entity tracer if requested
     let entity_tracer = if let Some(ref trace_spec) = config.trace {
         let tracer_config = EntityTracerConfig::from_spec(trace_spec);
@@ -746,7 +1885,7 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
     };
 
     // Set up rerun visualization if enabled
-    let rerun_logger = if config.rerun {
+    let rerun_logger = if rerun_enabled {
         if config.verbose {
             eprintln!("Initializing rerun.io visualization...");
         }
@@ -762,31 +1901,16 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
             }
         }).collect();
         
-        // Build a name->location lookup for creating link info
-        let node_locations: std::collections::HashMap<String, mcsim_common::GeoCoord> = 
-            simulation.node_infos.iter()
-                .map(|n| (n.name.clone(), n.location.clone()))
-                .collect();
-        
         // Build link info from model edges
-        let vis_links: Vec<VisLinkInfo> = model.edges().iter().filter_map(|(_, edge)| {
-            let from_loc = node_locations.get(&edge.from)?;
-            let to_loc = node_locations.get(&edge.to)?;
-            // Get mean_snr_db_at20dbm from edge properties
-            let mean_snr: f64 = edge.properties()
-                .get(&mcsim_model::LINK_MEAN_SNR_DB_AT20DBM);
-            Some(VisLinkInfo {
-                from: edge.from.clone(),
-                to: edge.to.clone(),
-                from_location: from_loc.clone(),
-                to_location: to_loc.clone(),
-                mean_snr_db_at20dbm: mean_snr,
-            })
-        }).collect();
+        let vis_links = vis_links(&model, &vis_nodes);
         
-        match RerunLogger::new("MCSim", vis_nodes, vis_links) {
+        match RerunLogger::with_save_path("MCSim", vis_nodes, vis_links, config.rerun_save.as_deref()) {
             Ok(logger) => {
-                eprintln!("✓ Rerun viewer spawned");
+                if let Some(ref path) = config.rerun_save {
+                    eprintln!("✓ Rerun recording to {}", path.display());
+                } else {
+                    eprintln!("✓ Rerun viewer spawned");
+                }
                 
                 // Set up metrics visualization blueprint
                 #[cfg(feature = "rerun")]
@@ -819,6 +1943,46 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
         entity_tracer,
     );
 
+    if let Some(clock) = wall_clock {
+        event_loop.set_wall_clock(clock);
+    }
+    if let Some(budget) = artifact_budget.clone() {
+        if config.verbose {
+            eprintln!("Artifact size cap: {:.1} MB", budget.limit() as f64 / 1e6);
+        }
+        event_loop.set_artifact_budget(budget);
+    }
+    if let Some(capture) = serial_capture {
+        event_loop.set_serial_capture(capture);
+    }
+
+    if let Some(capture) = packet_capture {
+        event_loop.set_packet_capture(capture);
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(database) = packet_db {
+        event_loop.set_packet_database(database);
+    }
+
+    if let Some(ref path) = config.perfetto {
+        let output = create_artifact(path)?;
+        event_loop.set_chrome_trace(ChromeTrace::new(output, event_loop.node_infos())?);
+        if config.verbose {
+            eprintln!("Chrome trace: {}", path.display());
+        }
+    }
+
+    if let Some(ref replay) = replay {
+        event_loop.set_input_log(InputLog::replaying(replay));
+        eprintln!(
+            "↻ Replaying {} external input(s) over {} events",
+            replay.injections.len(),
+            replay.outcome.events
+        );
+    } else if config.record.is_some() {
+        event_loop.set_input_log(InputLog::recording());
+    }
+
     // Configure packet tracker eviction from model properties
     let eviction_age: Option<f64> = model.simulation_properties().get(&mcsim_model::PACKET_TRACKER_EVICTION_AGE_S);
     if eviction_age.is_some() {
@@ -829,7 +1993,7 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
     }
 
     // Configure metrics for Rerun visualization if both rerun and metrics are enabled
-    if config.rerun {
+    if rerun_enabled {
         if let Some(ref recorder) = metrics_recorder {
             // Use the already-parsed metric specs for rerun visualization
             event_loop.set_metrics_for_rerun(recorder.clone(), metric_specs.clone());
@@ -839,6 +2003,106 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
         }
     }
 
+    // Evaluate the scenario's alert rules while running
+    if let Some(ref recorder) = metrics_recorder {
+        if !model.alerts().is_empty() {
+            let interval: f64 = model.simulation_properties().get(&mcsim_model::METRICS_ALERT_CHECK_INTERVAL_S);
+            if interval <= 0.0 {
+                return Err(RunnerError::ConfigError(format!(
+                    "metrics/alert_check_interval_s must be positive, got {}",
+                    interval
+                )));
+            }
+            let monitor = AlertMonitor::new(model.alerts().to_vec(), SimTime::from_secs(interval));
+            event_loop.set_alerts(monitor, recorder.clone());
+            if config.verbose {
+                eprintln!("Alert rules: {} (checked every {:.1}s)", model.alerts().len(), interval);
+            }
+        }
+    }
+
+    if config.timeline.is_some() {
+        event_loop.enable_timeline();
+    }
+    if config.advert_report.is_some() {
+        event_loop.enable_advert_report();
+    }
+    if config.message_paths.is_some() || config.packet_db.is_some() || config.metrics_output.is_some() {
+        event_loop.enable_message_paths();
+    }
+
+    if config.interactive || config.control_listen.is_some() || config.mqtt_commands {
+        event_loop.set_console(Inspector::new(&event_loop, metrics_recorder.clone()));
+        if let Some(ref recorder) = metrics_recorder {
+            event_loop.set_metrics_recorder(recorder.clone());
+        }
+    }
+    if replay.is_none() && config.record.is_none() {
+        event_loop.set_script_reloader(ScriptReloader::new(config.models.clone(), config.watch_scripts));
+        if config.watch_scripts {
+            eprintln!("✓ Watching {} model file(s) for traffic script changes", config.models.len());
+        }
+    }
+    if let Some(pacing) = config.pacing {
+        event_loop.set_pacing(pacing);
+    }
+    if config.interactive {
+        spawn_console(event_loop.control_handle());
+    }
+    if let Some(addr) = &config.control_listen {
+        let control = event_loop.control_handle();
+        let server = ControlServer::start(addr, control.clone())
+            .map_err(|e| RunnerError::ConfigError(format!("Cannot listen for control requests on {}: {}", addr, e)))?;
+        eprintln!("✓ Accepting control requests on {}", server.local_addr());
+        if config.control_wait {
+            control.pause();
+            eprintln!("Waiting for a control client to resume the simulation...");
+        }
+    }
+    #[cfg(feature = "dashboard")]
+    if let Some(addr) = &config.dashboard {
+        let dashboard = Dashboard::start(addr, event_loop.control_handle(), metrics_recorder.clone())
+            .map_err(|e| RunnerError::ConfigError(format!("Cannot serve the dashboard on {}: {}", addr, e)))?;
+        eprintln!("✓ Serving the dashboard at http://{}/", dashboard.local_addr());
+    }
+    #[cfg(not(feature = "dashboard"))]
+    if config.dashboard.is_some() {
+        return Err(RunnerError::ConfigError(
+            "--dashboard requires mcsim to be built with the 'dashboard' feature".to_string(),
+        ));
+    }
+    #[cfg(feature = "mqtt")]
+    let mqtt_bridge = match &config.mqtt {
+        Some(broker) => {
+            let nodes: Vec<String> = event_loop.node_infos().iter().map(|info| info.name.clone()).collect();
+            let commands = config.mqtt_commands.then(|| event_loop.control_handle());
+            let bridge = MqttBridge::start(broker, &config.mqtt_topic, &nodes, commands)
+                .map_err(|e| RunnerError::ConfigError(format!("Cannot bridge to MQTT broker {}: {}", broker, e)))?;
+            let feed = MqttFeed::new(event_loop.node_infos(), &config.mqtt_topic, bridge.sender());
+            event_loop.set_mqtt_feed(feed);
+            eprintln!("✓ Publishing mesh traffic to MQTT broker {} under {}/", broker, config.mqtt_topic);
+            Some(bridge)
+        }
+        None => None,
+    };
+    #[cfg(not(feature = "mqtt"))]
+    if config.mqtt.is_some() {
+        return Err(RunnerError::ConfigError(
+            "--mqtt requires mcsim to be built with the 'mqtt' feature".to_string(),
+        ));
+    }
+
+    // Measure delivery around the scenario's power outages, or for the SLA report
+    if !model.outages().is_empty() || config.sla_report.is_some() {
+        event_loop.enable_delivery_tracking();
+    }
+
+    // Observe the run for the scenario's assertions
+    if !model.assertions().is_empty() {
+        let monitor = AssertionMonitor::new(model.assertions().to_vec(), event_loop.node_infos());
+        event_loop.set_assertions(monitor, metrics_recorder.clone());
+    }
+
     // Determine metrics warmup time (CLI overrides model property)
     let warmup_secs: f64 = config.metrics_warmup.unwrap_or_else(|| {
         model.simulation_properties().get(&mcsim_model::METRICS_WARMUP_S)
@@ -852,19 +2116,41 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
         None
     };
 
+    // Apply metrics recording toggles from the model. Recording is switched
+    // off during warmup if requested and re-enabled when warmup ends.
+    let record_during_warmup: bool = model.simulation_properties()
+        .get(&mcsim_model::METRICS_RECORD_DURING_WARMUP);
+    if let Some(ref recorder) = metrics_recorder {
+        for category in model.simulation_properties().get(&mcsim_model::METRICS_DISABLED_CATEGORIES) {
+            recorder.set_category_enabled(&category, false);
+        }
+        if warmup_time.is_some() && !record_during_warmup {
+            recorder.set_enabled(false);
+        }
+    }
+
     // Track whether warmup has completed (for clearing metrics)
     let warmup_cleared = std::cell::Cell::new(false);
 
-    // Run in either timed or realtime mode
-    let stats = if let Some(duration_secs) = config.duration {
+    // Run in either timed or realtime mode (a replay always runs timed)
+    let duration = match replay {
+        Some(ref replay) => Some(replay.run_duration()),
+        None => config.duration.map(SimTime::from_secs),
+    };
+    let mut stats = if let Some(duration) = duration {
         // Timed mode: run for specified duration
-        let duration = SimTime::from_secs(duration_secs);
+        let duration_secs = duration.as_secs_f64();
 
         if config.verbose {
             eprintln!("Running simulation for {} seconds...", duration_secs);
         }
 
-        eprintln!("⏱  Running timed simulation for {} seconds...", duration_secs);
+        match config.pacing {
+            Some(pacing @ Pacing::Scaled(_)) => {
+                eprintln!("⏱  Running timed simulation for {} seconds at {} pacing...", duration_secs, pacing)
+            }
+            _ => eprintln!("⏱  Running timed simulation for {} seconds...", duration_secs),
+        }
         
         // Create watchdog for monitoring slow events
         let watchdog = Watchdog::new(std::time::Duration::from_secs(config.watchdog_timeout));
@@ -885,6 +2171,7 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
                 if !warmup_cleared.get() && progress.sim_time >= warmup {
                     if let Some(ref recorder) = metrics_recorder {
                         recorder.clear();
+                        recorder.set_enabled(true);
                         if config.verbose {
                             eprintln!("  [WARMUP] Cleared metrics at {:.1}s (warmup period: {:.1}s)", 
                                 progress.sim_time.as_secs_f64(), warmup.as_secs_f64());
@@ -952,15 +2239,16 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
             .with_max_catchup_ms(config.max_catchup_ms)
             .with_periodic_stats_interval(periodic_stats_interval);
         event_loop.set_realtime_config(realtime_config);
+        let pacing = config.pacing.unwrap_or(Pacing::Scaled(config.speed));
 
         if config.verbose {
-            eprintln!("Starting realtime simulation at {}x speed...", config.speed);
+            eprintln!("Starting realtime simulation at {} pacing...", pacing);
         }
 
-        let speed_str = if (config.speed - 1.0).abs() < 0.001 {
-            String::from("real-time")
-        } else {
-            format!("{}x speed", config.speed)
+        let speed_str = match pacing {
+            Pacing::Fast => String::from("as-fast-as-possible"),
+            Pacing::Scaled(speed) if (speed - 1.0).abs() < 0.001 => String::from("real-time"),
+            Pacing::Scaled(speed) => format!("{}x speed", speed),
         };
         eprintln!("\n🚀 Running in {} mode. Press Ctrl+C to stop.", speed_str);
         
@@ -974,6 +2262,7 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
                 if !warmup_cleared.get() && event_loop.current_time() >= warmup {
                     if let Some(ref recorder) = metrics_recorder {
                         recorder.clear();
+                        recorder.set_enabled(true);
                         if config.verbose {
                             eprintln!("  [WARMUP] Cleared metrics at {:.1}s (warmup period: {:.1}s)", 
                                 event_loop.current_time().as_secs_f64(), warmup.as_secs_f64());
@@ -997,6 +2286,178 @@ pub fn run_simulation(config: RunnerConfig) -> Result<SimulationStats, RunnerErr
         eprintln!("  Messages sent: {}", stats.messages_sent);
         eprintln!("  Messages acked: {}", stats.messages_acked);
         eprintln!("  Wall time: {}ms", stats.wall_time_ms);
+        if !model.alerts().is_empty() {
+            eprintln!("  Alerts fired: {}", event_loop.fired_alerts().len());
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(rows) = event_loop.packet_database_rows() {
+            eprintln!("  Packet database rows: {}", rows);
+        }
+    }
+
+    #[cfg(feature = "mqtt")]
+    if let Some(bridge) = mqtt_bridge {
+        bridge.finish();
+        if let Some(dropped) = event_loop.mqtt_dropped().filter(|&dropped| dropped > 0) {
+            eprintln!("⚠ {} MQTT message(s) dropped: the broker fell behind", dropped);
+        }
+    }
+
+    stats.topology = Some(topology);
+
+    // Room server state
+    stats.rooms = event_loop.room_states();
+    if !stats.rooms.is_empty() {
+        eprintln!("Rooms:");
+        for room in &stats.rooms {
+            eprintln!("  {}: {} post(s), {} client(s)", room.name, room.posts, room.clients);
+        }
+    }
+
+    // Report how the network degraded and recovered around each outage
+    if !model.outages().is_empty() {
+        eprintln!("Outages:");
+        for outage in model.outages() {
+            if let Some(impact) = event_loop.outage_impact(outage, outage.affected_nodes(&model)) {
+                eprintln!("  {}", impact);
+                stats.outages.push(impact);
+            }
+        }
+    }
+
+    // Evaluate the scenario's assertions
+    if !model.assertions().is_empty() {
+        stats.assertions = event_loop.evaluate_assertions();
+        eprintln!("Assertions:");
+        for result in &stats.assertions {
+            eprintln!("  {}", result);
+        }
+        let failed = stats.assertions.iter().filter(|r| !r.passed).count();
+        if failed == 0 {
+            eprintln!("✓ All {} assertion(s) passed", stats.assertions.len());
+        } else {
+            eprintln!("✗ {} of {} assertion(s) failed", failed, stats.assertions.len());
+        }
+    }
+
+    // Check a replay against its recording, or write the recording
+    if let Some(log) = event_loop.input_log() {
+        let outcome = RunOutcome {
+            events: stats.total_events,
+            end_time_us: stats.simulation_time_us,
+            digest: log.digest(),
+        };
+        if let Some(ref replay) = replay {
+            if outcome.events == replay.outcome.events && outcome.digest == replay.outcome.digest {
+                eprintln!("✓ Replay matched the recording ({} events)", outcome.events);
+            } else {
+                eprintln!(
+                    "⚠ Replay diverged from the recording: {} events, digest {:016x} (recorded {} events, digest {:016x})",
+                    outcome.events, outcome.digest, replay.outcome.events, replay.outcome.digest
+                );
+            }
+        } else if let Some(ref path) = config.record {
+            let file = ReplayFile {
+                version: REPLAY_FILE_VERSION,
+                seed,
+                duration_s: config.duration,
+                models: recorded_models,
+                injections: log.recorded().to_vec(),
+                outcome,
+            };
+            file.save(path)?;
+            eprintln!("✓ Recorded {} external input(s) to {}", file.injections.len(), path.display());
+        }
+    }
+
+    if let Some(ref path) = config.calibration_report {
+        let report = event_loop.calibration_report(CalibrationTolerances {
+            sf_margin_db: config.sf_margin,
+            ..CalibrationTolerances::default()
+        });
+        let drifting: Vec<_> = report.drifting().collect();
+        if !drifting.is_empty() {
+            eprintln!("Warning: {} link(s) drifted from their predicted SNR:", drifting.len());
+            for link in drifting {
+                let reasons: Vec<String> = link.drift.iter().map(|r| r.to_string()).collect();
+                eprintln!("  {} -> {}: {}", link.from, link.to, reasons.join(", "));
+            }
+        }
+        if config.verbose && !report.links.is_empty() {
+            let closed: Vec<String> = (7..=12)
+                .map(|sf| format!("SF{} {}", sf, report.links_closed_at(sf)))
+                .collect();
+            eprintln!(
+                "Links closed with {:.1} dB margin (of {}): {}",
+                config.sf_margin,
+                report.links.len(),
+                closed.join(", ")
+            );
+        }
+        serde_json::to_writer_pretty(std::fs::File::create(path)?, &report)?;
+        if config.verbose {
+            eprintln!("Calibration report written to: {}", path.display());
+        }
+    }
+
+    if let Some(ref path) = config.snr_estimates {
+        let estimates = event_loop.snr_estimates();
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        mcsim_link::write_link_snr_csv(&estimates, &mut file)?;
+        file.flush()?;
+        if config.verbose {
+            eprintln!("SNR estimates of {} link(s) written to: {}", estimates.len(), path.display());
+        }
+    }
+
+    if let Some(ref path) = config.sla_report {
+        let thresholds = SlaThresholds {
+            min_delivery: config.sla_delivery / 100.0,
+            max_latency_s: config.sla_latency,
+        };
+        if let Some(report) = event_loop.sla_report(thresholds) {
+            eprintln!("{}", report);
+            serde_json::to_writer_pretty(std::fs::File::create(path)?, &report)?;
+            if config.verbose {
+                eprintln!("SLA report written to: {}", path.display());
+            }
+        }
+    }
+
+    if let (Some(path), Some(timeline)) = (&config.timeline, event_loop.timeline()) {
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg")) {
+            std::fs::write(path, timeline.to_svg())?;
+        } else {
+            serde_json::to_writer_pretty(std::fs::File::create(path)?, &timeline)?;
+        }
+        if config.verbose {
+            eprintln!("Timeline written to: {}", path.display());
+        }
+    }
+
+    if let (Some(path), Some(report)) = (&config.advert_report, event_loop.advert_report()) {
+        eprintln!("{}", report);
+        serde_json::to_writer_pretty(std::fs::File::create(path)?, &report)?;
+        if config.verbose {
+            eprintln!("Advert report written to: {}", path.display());
+        }
+    }
+
+    if let (Some(path), Some(report)) = (&config.message_paths, event_loop.message_path_report()) {
+        eprintln!("{}", report);
+        serde_json::to_writer_pretty(std::fs::File::create(path)?, &report)?;
+        if config.verbose {
+            eprintln!("Message paths written to: {}", path.display());
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    event_loop.finish_packet_database()?;
+
+    if let Some(events) = event_loop.finish_chrome_trace()? {
+        if let (true, Some(path)) = (config.verbose, &config.perfetto) {
+            eprintln!("Chrome trace written to: {} ({} trace events)", path.display(), events);
+        }
     }
 
     // Export metrics if requested
@@ -1070,6 +2531,9 @@ fn print_link_prediction(pred: &mcsim_link::LinkPrediction) {
         "  To:   ({:.6}, {:.6}) at {:.1}m AGL",
         pred.path.to_lat, pred.path.to_lon, pred.path.to_height
     );
+    if pred.path.height_mode != mcsim_link::AntennaHeightMode::Agl {
+        println!("  Antenna heights given as {}, converted to AGL using DEM ground elevation", pred.path.height_mode);
+    }
     println!("  Distance: {:.2} km", pred.path.distance_km);
     println!();
     println!("Terrain:");
@@ -1085,9 +2549,15 @@ fn print_link_prediction(pred: &mcsim_link::LinkPrediction) {
     println!("  Frequency: {:.1} MHz", pred.radio.freq_mhz);
     println!("  TX Power: {} dBm", pred.radio.tx_power_dbm);
     println!("  Noise Floor: {:.1} dBm (assumed)", pred.radio.noise_floor_dbm);
+    if pred.antenna_gain_db != 0.0 {
+        println!("  Antenna Gain: {:+.1} dB (both ends)", pred.antenna_gain_db);
+    }
     println!();
     println!("Path Loss ({}):", pred.prediction_method);
     println!("  Median:           {:.1} dB", pred.path_loss_db);
+    if pred.clutter_loss_db != 0.0 {
+        println!("  Clutter:          {:.1} dB (included)", pred.clutter_loss_db);
+    }
     if pred.itm_warnings != 0 {
         println!("  Warnings:         0x{:04x}", pred.itm_warnings);
     }
@@ -1107,16 +2577,40 @@ fn print_link_prediction(pred: &mcsim_link::LinkPrediction) {
     println!("  Status:           {}", pred.status);
 }
 
+/// Print the reverse-direction (receiver to transmitter) link assessment.
+///
+/// Path loss is reciprocal, so only the TX power differs between directions.
+fn print_reverse_link(pred: &mcsim_link::LinkPrediction, reverse_tx_power: i8) {
+    let margin = pred.link_margin_db_at_tx_power(reverse_tx_power);
+    println!();
+    println!("Reverse Link (TX Power {} dBm):", reverse_tx_power);
+    println!("  Mean SNR:         {:.1} dB", pred.snr_db_at_tx_power(reverse_tx_power));
+    println!("  Link Margin:      {:.1} dB (from median)", margin);
+    println!(
+        "  Asymmetry:        {:.1} dB",
+        pred.snr_db - pred.snr_db_at_tx_power(reverse_tx_power)
+    );
+}
+
 /// Predict link quality between two geographic coordinates using DEM and ITM.
 fn predict_link(config: PredictLinkConfig) -> Result<(), RunnerError> {
     use mcsim_link::{
         load_dem, load_itm, load_aws_elevation,
-        predict_link as do_predict, predict_link_with_elevation,
-        LinkPredictionConfig,
+        predict_link_with_params, predict_link_with_elevation_and_params,
+        ClutterModel, LandCoverMap, LinkCalibration, LinkPredictionConfig, LinkPredictionParams,
     };
 
     // Resolve the config (merge YAML files + CLI overrides)
     let config = config.resolve()?;
+    let params = LinkPredictionParams {
+        calibration: config
+            .calibration
+            .as_ref()
+            .map(LinkCalibration::load)
+            .transpose()
+            .map_err(|e| RunnerError::ConfigError(format!("{}", e)))?,
+        ..Default::default()
+    };
 
     // Load ITM library
     eprintln!("Loading ITM library...");
@@ -1132,10 +2626,12 @@ fn predict_link(config: PredictLinkConfig) -> Result<(), RunnerError> {
         to_lon: config.to_lon,
         from_height: config.from_height,
         to_height: config.to_height,
+        height_mode: config.height_mode,
         freq_mhz: config.freq,
         tx_power_dbm: config.tx_power,
         spreading_factor: config.sf,
         terrain_samples: config.samples,
+        ..Default::default()
     };
 
     eprintln!(
@@ -1144,7 +2640,7 @@ fn predict_link(config: PredictLinkConfig) -> Result<(), RunnerError> {
     );
 
     // Perform prediction using appropriate elevation source
-    let prediction = match config.elevation_source.as_str() {
+    let mut prediction = match config.elevation_source.as_str() {
         "aws" => {
             // Use AWS terrain tiles (fetched on demand and cached)
             eprintln!(
@@ -1154,11 +2650,12 @@ fn predict_link(config: PredictLinkConfig) -> Result<(), RunnerError> {
             );
             let elevation = load_aws_elevation(&config.elevation_cache, config.zoom).map_err(|e| {
                 RunnerError::ConfigError(format!("{}", e))
-            })?;
+            })?.with_offline_mode(config.offline_mode);
             eprintln!();
-            predict_link_with_elevation(&elevation, &itm, &pred_config).map_err(|e| {
-                RunnerError::ConfigError(format!("{}", e))
-            })?
+            let prediction = predict_link_with_elevation_and_params(&elevation, &itm, &pred_config, &params)
+                .map_err(|e| RunnerError::ConfigError(format!("{}", e)))?;
+            warn_offline_fallbacks(&elevation);
+            prediction
         }
         "local_dem" => {
             // Use local DEM files
@@ -1168,7 +2665,7 @@ fn predict_link(config: PredictLinkConfig) -> Result<(), RunnerError> {
             })?;
             eprintln!("DEM loaded");
             eprintln!();
-            do_predict(&dem, &itm, &pred_config).map_err(|e| {
+            predict_link_with_params(&dem, &itm, &pred_config, &params).map_err(|e| {
                 RunnerError::ConfigError(format!("{}", e))
             })?
         }
@@ -1180,15 +2677,390 @@ fn predict_link(config: PredictLinkConfig) -> Result<(), RunnerError> {
         }
     };
 
+    if let Some(dir) = &config.land_cover {
+        eprintln!("Loading land cover from {}...", dir.display());
+        let mut land_cover = LandCoverMap::new();
+        let tiles = land_cover.add_directory(dir).map_err(|e| {
+            RunnerError::ConfigError(format!("Failed to load land cover: {}", e))
+        })?;
+        if tiles == 0 {
+            eprintln!("Warning: no land-cover tiles in {}", dir.display());
+        }
+        ClutterModel::default().apply(&land_cover, &mut prediction, &params);
+    }
+
     // Print results
+    if let Some(calibration) = &params.calibration {
+        let correction = calibration.correction_for(&pred_config);
+        eprintln!(
+            "Field calibration: {:+.1} dB, std dev {:.1} dB (from {} measurements)",
+            correction.offset_db, correction.std_dev_db, correction.samples
+        );
+    }
     print_link_prediction(&prediction);
+    if let Some(reverse_tx_power) = config.reverse_tx_power {
+        print_reverse_link(&prediction, reverse_tx_power);
+    }
 
     Ok(())
 }
 
+/// Warn when missing AWS tiles were replaced by the offline fallback
+/// elevation, since the terrain in those predictions is not real.
+fn warn_offline_fallbacks(elevation: &mcsim_link::ElevationSource) {
+    if let Some(stats) = elevation.download_stats() {
+        if stats.offline_fallbacks > 0 {
+            eprintln!(
+                "Warning: {} elevation lookup(s) used the offline fallback elevation (tiles missing from the cache)",
+                stats.offline_fallbacks
+            );
+        }
+    }
+}
+
+/// Elevation source options shared by the prediction commands; unset ones
+/// come from the `simulation` properties.
+struct ElevationArgs {
+    source: Option<String>,
+    dem_dir: Option<PathBuf>,
+    cache: Option<PathBuf>,
+    zoom: Option<u8>,
+    offline: bool,
+    offline_elevation: Option<f64>,
+}
+
+/// Open the elevation source the options and properties select.
+fn load_elevation_source(
+    props: &mcsim_model::ResolvedProperties<mcsim_model::SimulationScope>,
+    args: ElevationArgs,
+) -> Result<mcsim_link::ElevationSource, RunnerError> {
+    use mcsim_link::{load_aws_elevation, load_dem, ElevationSource};
+    use mcsim_model::{
+        PREDICT_DEM_DIR, PREDICT_ELEVATION_CACHE_DIR, PREDICT_ELEVATION_SOURCE, PREDICT_ELEVATION_ZOOM_LEVEL,
+    };
+
+    let to_config_error = |e: mcsim_link::LinkPredictionError| RunnerError::ConfigError(e.to_string());
+    let source = args.source.unwrap_or_else(|| props.get::<String>(&PREDICT_ELEVATION_SOURCE));
+    match source.as_str() {
+        "aws" => {
+            let cache = args
+                .cache
+                .unwrap_or_else(|| PathBuf::from(props.get::<String>(&PREDICT_ELEVATION_CACHE_DIR)));
+            let zoom = args.zoom.unwrap_or_else(|| props.get(&PREDICT_ELEVATION_ZOOM_LEVEL));
+            eprintln!("Using AWS terrain tiles (cache: {}, zoom: {})...", cache.display(), zoom);
+            Ok(load_aws_elevation(&cache, zoom)
+                .map_err(to_config_error)?
+                .with_offline_mode(mcsim_link::resolve_offline_mode(props, args.offline, args.offline_elevation)))
+        }
+        "local_dem" => {
+            let dem_dir = args.dem_dir.unwrap_or_else(|| PathBuf::from(props.get::<String>(&PREDICT_DEM_DIR)));
+            eprintln!("Loading DEM data from {}...", dem_dir.display());
+            Ok(ElevationSource::from_local_dem(load_dem(&dem_dir).map_err(to_config_error)?))
+        }
+        other => Err(RunnerError::ConfigError(format!(
+            "Unknown elevation source '{}'. Use 'aws' or 'local_dem'.",
+            other
+        ))),
+    }
+}
+
+/// Compute and write a coverage map for one transmitter.
+fn coverage_command(config: CoverageMapConfig) -> Result<(), RunnerError> {
+    use mcsim_link::{
+        compute_coverage, BoundingBox, ColorRamp, CoverageConfig, CoverageRegion, LinkCalibration,
+        LinkPredictionConfig, LinkPredictionParams, Polygon,
+    };
+    use mcsim_model::{
+        load_models, ResolvedProperties, SimulationScope, PREDICT_FREQUENCY_MHZ, PREDICT_SPREADING_FACTOR,
+        PREDICT_TERRAIN_SAMPLES, PREDICT_TX_POWER_DBM,
+    };
+
+    let to_config_error = |e: mcsim_link::LinkPredictionError| RunnerError::ConfigError(e.to_string());
+    let props: ResolvedProperties<SimulationScope> = if config.configs.is_empty() {
+        ResolvedProperties::new()
+    } else {
+        let paths: Vec<&Path> = config.configs.iter().map(|p| p.as_path()).collect();
+        load_models(&paths)?.simulation_properties().clone()
+    };
+
+    let read_polygons = |path: &PathBuf| -> Result<Vec<Polygon>, RunnerError> {
+        Polygon::from_geojson(&std::fs::read_to_string(path)?)
+            .map_err(|e| RunnerError::ConfigError(format!("{}: {}", path.display(), e)))
+    };
+    let mut region = CoverageRegion::default();
+    if let Some(ref path) = config.boundary {
+        region.include = read_polygons(path)?;
+    }
+    for path in &config.exclude {
+        region.exclude.extend(read_polygons(path)?);
+    }
+    let bounds = match (&config.bounds, region.bounds()) {
+        (Some(spec), _) => BoundingBox::parse(spec).map_err(to_config_error)?,
+        (None, Some(bounds)) => bounds,
+        (None, None) => {
+            return Err(RunnerError::ConfigError("Coverage needs --bounds or --boundary".to_string()));
+        }
+    };
+
+    let params = LinkPredictionParams {
+        calibration: config.calibration.as_ref().map(LinkCalibration::load).transpose().map_err(to_config_error)?,
+        ..LinkPredictionParams::from_properties(&props)
+    };
+    let coverage = CoverageConfig {
+        link: LinkPredictionConfig {
+            from_lat: config.lat,
+            from_lon: config.lon,
+            from_height: config.height,
+            to_height: config.rx_height,
+            freq_mhz: config.freq.unwrap_or_else(|| props.get(&PREDICT_FREQUENCY_MHZ)),
+            tx_power_dbm: config.tx_power.unwrap_or_else(|| props.get(&PREDICT_TX_POWER_DBM)),
+            spreading_factor: config.sf.unwrap_or_else(|| props.get(&PREDICT_SPREADING_FACTOR)),
+            terrain_samples: config
+                .samples
+                .unwrap_or_else(|| props.get::<u32>(&PREDICT_TERRAIN_SAMPLES) as usize),
+            ..Default::default()
+        },
+        bounds,
+        resolution_m: config.resolution,
+        tx_siting: mcsim_itm::SitingCriteria::Careful,
+        region,
+    };
+
+    let elevation = load_elevation_source(
+        &props,
+        ElevationArgs {
+            source: config.elevation_source.clone(),
+            dem_dir: config.dem_dir.clone(),
+            cache: config.elevation_cache.clone(),
+            zoom: config.zoom,
+            offline: config.offline,
+            offline_elevation: config.offline_elevation,
+        },
+    )?;
+
+    let geotiff = match config.output.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase) {
+        Some(ext) if ext == "tif" || ext == "tiff" => true,
+        Some(ext) if ext == "png" => false,
+        _ => {
+            return Err(RunnerError::ConfigError(format!(
+                "Unsupported coverage output '{}': use .tif, .tiff or .png",
+                config.output.display()
+            )));
+        }
+    };
+
+    eprintln!("Computing coverage at {}m resolution...", config.resolution);
+    let map = compute_coverage(&elevation, &coverage, &params).map_err(to_config_error)?;
+    warn_offline_fallbacks(&elevation);
+    if config.boundary.is_some() || !config.exclude.is_empty() {
+        let in_region = (0..map.height)
+            .flat_map(|row| (0..map.width).map(move |col| (col, row)))
+            .filter(|&(col, row)| {
+                let (lat, lon) = map.cell_center(col, row);
+                coverage.region.contains(lat, lon)
+            })
+            .count();
+        eprintln!("{} of {} cells inside the region", in_region, map.width * map.height);
+    }
+    eprintln!(
+        "{}x{} cells, {:.1}% at or above the SF{} threshold ({:.1} dB)",
+        map.width,
+        map.height,
+        map.covered_fraction() * 100.0,
+        coverage.link.spreading_factor,
+        map.snr_threshold_db
+    );
+
+    let file = std::io::BufWriter::new(std::fs::File::create(&config.output)?);
+    if geotiff {
+        map.write_geotiff(file)?;
+    } else {
+        map.write_png(file, &ColorRamp::link_margin(map.snr_threshold_db))?;
+    }
+    eprintln!("Coverage map written to: {}", config.output.display());
+    Ok(())
+}
+
+/// Fit a link calibration from field measurements and save it.
+fn calibrate_link_command(config: CalibrateLinkConfig) -> Result<(), RunnerError> {
+    use mcsim_link::{load_itm, CalibrationFit, FieldMeasurement, LinkCalibration, LinkPredictionConfig, LinkPredictionParams};
+    use mcsim_model::{
+        load_models, ResolvedProperties, SimulationScope, PREDICT_FREQUENCY_MHZ, PREDICT_TERRAIN_SAMPLES,
+        PREDICT_TX_POWER_DBM,
+    };
+
+    let to_config_error = |e: mcsim_link::LinkPredictionError| RunnerError::ConfigError(e.to_string());
+    let props: ResolvedProperties<SimulationScope> = if config.configs.is_empty() {
+        ResolvedProperties::new()
+    } else {
+        let paths: Vec<&Path> = config.configs.iter().map(|p| p.as_path()).collect();
+        load_models(&paths)?.simulation_properties().clone()
+    };
+    let measurements = FieldMeasurement::from_csv(&std::fs::read_to_string(&config.measurements)?)
+        .map_err(|e| RunnerError::ConfigError(format!("{}: {}", config.measurements.display(), e)))?;
+    eprintln!("Read {} measurements from {}", measurements.len(), config.measurements.display());
+
+    let template = LinkPredictionConfig {
+        from_height: config.from_height,
+        to_height: config.to_height,
+        freq_mhz: config.freq.unwrap_or_else(|| props.get(&PREDICT_FREQUENCY_MHZ)),
+        tx_power_dbm: config.tx_power.unwrap_or_else(|| props.get(&PREDICT_TX_POWER_DBM)),
+        terrain_samples: config
+            .samples
+            .unwrap_or_else(|| props.get::<u32>(&PREDICT_TERRAIN_SAMPLES) as usize),
+        ..Default::default()
+    };
+    let elevation = load_elevation_source(
+        &props,
+        ElevationArgs {
+            source: config.elevation_source.clone(),
+            dem_dir: config.dem_dir.clone(),
+            cache: config.elevation_cache.clone(),
+            zoom: config.zoom,
+            offline: config.offline,
+            offline_elevation: config.offline_elevation,
+        },
+    )?;
+    let itm = load_itm().map_err(to_config_error)?;
+    let fit = CalibrationFit { cell_size_deg: config.cell_size, min_samples: config.min_samples };
+
+    eprintln!("Predicting {} measured links...", measurements.len());
+    let calibration = LinkCalibration::fit(
+        &elevation,
+        &itm,
+        &template,
+        &LinkPredictionParams::from_properties(&props),
+        &measurements,
+        &fit,
+    )
+    .map_err(to_config_error)?;
+    warn_offline_fallbacks(&elevation);
+    calibration.save(&config.output).map_err(to_config_error)?;
+
+    println!(
+        "Overall: {:+.1} dB, std dev {:.1} dB ({} measurements)",
+        calibration.global.offset_db, calibration.global.std_dev_db, calibration.global.samples
+    );
+    for region in &calibration.regions {
+        println!(
+            "  {:.3},{:.3},{:.3},{:.3}: {:+.1} dB, std dev {:.1} dB ({} measurements)",
+            region.bounds.min_lat,
+            region.bounds.min_lon,
+            region.bounds.max_lat,
+            region.bounds.max_lon,
+            region.correction.offset_db,
+            region.correction.std_dev_db,
+            region.correction.samples
+        );
+    }
+    eprintln!("Calibration written to {}", config.output.display());
+    Ok(())
+}
+
+/// Download every AWS terrain tile covering an area, so later link
+/// predictions read them from the cache instead of fetching them one by one.
+fn prefetch_elevation_command(config: PrefetchElevationConfig) -> Result<(), RunnerError> {
+    use mcsim_dem::{AwsTileFetcher, PrefetchOutcome, PrefetchProgress, TileCoord};
+    use mcsim_link::BoundingBox;
+    use mcsim_model::{
+        load_models, ResolvedProperties, SimulationScope, LOCATION_LATITUDE, LOCATION_LONGITUDE,
+        PREDICT_ELEVATION_CACHE_DIR, PREDICT_ELEVATION_ZOOM_LEVEL,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    let model = if config.models.is_empty() {
+        None
+    } else {
+        let paths: Vec<&Path> = config.models.iter().map(|p| p.as_path()).collect();
+        Some(load_models(&paths)?)
+    };
+    let props: ResolvedProperties<SimulationScope> =
+        model.as_ref().map_or_else(ResolvedProperties::new, |m| m.simulation_properties().clone());
+
+    let bounds = match (&config.bounds, &model) {
+        (Some(spec), _) => BoundingBox::parse(spec).map_err(|e| RunnerError::ConfigError(e.to_string()))?,
+        (None, Some(model)) => model
+            .nodes()
+            .values()
+            .map(|node| {
+                let props = node.properties();
+                (props.get::<f64>(&LOCATION_LATITUDE), props.get::<f64>(&LOCATION_LONGITUDE))
+            })
+            .fold(None, |bounds: Option<BoundingBox>, (lat, lon)| {
+                Some(match bounds {
+                    None => BoundingBox { min_lat: lat, min_lon: lon, max_lat: lat, max_lon: lon },
+                    Some(b) => BoundingBox {
+                        min_lat: b.min_lat.min(lat),
+                        min_lon: b.min_lon.min(lon),
+                        max_lat: b.max_lat.max(lat),
+                        max_lon: b.max_lon.max(lon),
+                    },
+                })
+            })
+            .ok_or_else(|| RunnerError::ConfigError("The model has no nodes; pass --bounds".to_string()))?,
+        (None, None) => {
+            return Err(RunnerError::ConfigError("Prefetch needs --bounds or a model".to_string()));
+        }
+    };
+
+    let cache = config
+        .elevation_cache
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(props.get::<String>(&PREDICT_ELEVATION_CACHE_DIR)));
+    let zoom = config.zoom.unwrap_or_else(|| props.get(&PREDICT_ELEVATION_ZOOM_LEVEL));
+    let to_config_error = |e: mcsim_dem::DemError| RunnerError::ConfigError(e.to_string());
+    let fetcher = AwsTileFetcher::with_zoom(&cache, zoom).map_err(to_config_error)?;
+    let tiles = TileCoord::covering(zoom, bounds.min_lat, bounds.max_lat, bounds.min_lon, bounds.max_lon)
+        .map_err(to_config_error)?;
+    if tiles.is_empty() {
+        return Err(RunnerError::ConfigError(
+            "Bounding box must be min_lat,min_lon,max_lat,max_lon with min below max".to_string(),
+        ));
+    }
+
+    eprintln!(
+        "Prefetching {} tile(s) at zoom {} into {} ({} parallel downloads)...",
+        tiles.len(),
+        zoom,
+        cache.display(),
+        config.jobs
+    );
+    let failures = AtomicUsize::new(0);
+    let progress = |p: &PrefetchProgress| {
+        if let PrefetchOutcome::Failed(reason) = &p.outcome {
+            failures.fetch_add(1, Ordering::Relaxed);
+            eprintln!("\r  {}/{}/{}: {}", p.coord.z, p.coord.x, p.coord.y, reason);
+        }
+        eprint!("\r  {}/{} tiles ({} failed)", p.completed, p.total, failures.load(Ordering::Relaxed));
+    };
+    let started = Instant::now();
+    let summary = fetcher.prefetch_tiles(&tiles, config.jobs, Some(&progress));
+    eprintln!();
+    eprintln!(
+        "{} downloaded ({:.1} MB), {} already cached, {} failed in {:.1}s",
+        summary.downloaded,
+        fetcher.download_stats().bytes_downloaded as f64 / 1e6,
+        summary.cached,
+        summary.failed.len(),
+        started.elapsed().as_secs_f64()
+    );
+
+    if !summary.failed.is_empty() {
+        return Err(RunnerError::Io(std::io::Error::other(format!(
+            "{} of {} tile(s) could not be downloaded; run the command again to retry them",
+            summary.failed.len(),
+            summary.total
+        ))));
+    }
+    Ok(())
+}
+
 /// Estimate true SNR distribution from observed measurements.
 fn estimate_snr_command(config: EstimateSnrConfig) -> Result<(), RunnerError> {
-    use mcsim_link::{estimate_snr, estimate_snr_with_threshold, LoraModulationParams};
+    use mcsim_link::{
+        estimate_snr, estimate_snr_mixture, estimate_snr_with_threshold, LoraModulationParams,
+    };
 
     if config.observations.is_empty() {
         return Err(RunnerError::ConfigError(
@@ -1210,9 +3082,18 @@ fn estimate_snr_command(config: EstimateSnrConfig) -> Result<(), RunnerError> {
     }
     .map_err(|e| RunnerError::ConfigError(format!("Estimation failed: {}", e)))?;
 
+    let mixture = if config.mixture {
+        Some(
+            estimate_snr_mixture(config.observations.clone(), result.threshold)
+                .map_err(|e| RunnerError::ConfigError(format!("Estimation failed: {}", e)))?,
+        )
+    } else {
+        None
+    };
+
     if config.format == "json" {
         // JSON output
-        let json = serde_json::json!({
+        let mut json = serde_json::json!({
             "mean_snr_db": result.mean_snr,
             "std_dev_db": result.std_dev,
             "threshold_db": result.threshold,
@@ -1221,6 +3102,24 @@ fn estimate_snr_command(config: EstimateSnrConfig) -> Result<(), RunnerError> {
             "reception_probability": result.reception_probability(),
             "link_margin_db": result.link_margin(),
         });
+        if let Some(mixture) = &mixture {
+            json["mixture"] = serde_json::json!({
+                "single_fit_poor": mixture.single_fit_poor(),
+                "bic_improvement": mixture.bic_improvement,
+                "mixing_weight": mixture.mixing_weight(),
+                "components": mixture.components.map(|components| {
+                    components
+                        .iter()
+                        .map(|c| serde_json::json!({
+                            "mean_snr_db": c.mean_snr,
+                            "std_dev_db": c.std_dev,
+                            "weight": c.weight,
+                        }))
+                        .collect::<Vec<_>>()
+                }),
+                "reception_probability": mixture.reception_probability(),
+            });
+        }
         println!("{}", serde_json::to_string_pretty(&json)?);
     } else {
         // Text output
@@ -1255,6 +3154,37 @@ fn estimate_snr_command(config: EstimateSnrConfig) -> Result<(), RunnerError> {
             "UNRELIABLE"
         };
         println!("  Status:           {}", status);
+
+        if let Some(mixture) = &mixture {
+            println!();
+            println!("Mixture Fit:");
+            match mixture.components {
+                Some([low, high]) => {
+                    println!(
+                        "  Low Mode:         {:.2} dB ± {:.2} dB ({:.0}%)",
+                        low.mean_snr,
+                        low.std_dev,
+                        low.weight * 100.0
+                    );
+                    println!(
+                        "  High Mode:        {:.2} dB ± {:.2} dB ({:.0}%)",
+                        high.mean_snr,
+                        high.std_dev,
+                        high.weight * 100.0
+                    );
+                    println!(
+                        "  Reception Prob:   {:.1}%",
+                        mixture.reception_probability() * 100.0
+                    );
+                    println!(
+                        "  Warning: observations are bimodal (BIC improvement {:.1}); \
+                         the single-normal estimate above is a poor fit",
+                        mixture.bic_improvement
+                    );
+                }
+                None => println!("  No second mode detected; the single-normal fit is adequate"),
+            }
+        }
     }
 
     Ok(())
@@ -1277,6 +3207,14 @@ fn build_model_command(config: BuildModelConfig) -> Result<(), RunnerError> {
         antenna_height: config.antenna_height,
         terrain_samples: config.terrain_samples,
         public_key_prefix_len: config.pubkey_prefix_len,
+        fading: config.fading,
+        link_cache: !config.no_link_cache,
+        offline_mode: mcsim_link::resolve_offline_mode(
+            &mcsim_model::ResolvedProperties::new(),
+            config.offline,
+            config.offline_elevation,
+        ),
+        map_paths: config.maps,
         verbose: config.verbose,
     };
 
@@ -1375,6 +3313,739 @@ fn keygen_command(config: KeygenConfig) -> Result<(), RunnerError> {
     Ok(())
 }
 
+/// Read console commands from stdin on a background thread and send them to
+/// the running simulation (`run --interactive`). Pausing, resuming and
+/// quitting go straight to the control lane; other commands are executed by
+/// the event loop between events.
+fn spawn_console(control: ControlHandle) {
+    use std::io::BufRead;
+
+    std::thread::spawn(move || {
+        eprintln!("Interactive console: type 'help' for commands, 'pause' to hold simulation time.");
+        let report = |action: &str| match control.status(CONSOLE_TIMEOUT) {
+            Some(status) => println!("{} at t={:.3}s", action, status.sim_time.as_secs_f64()),
+            None => println!("No answer from the simulation"),
+        };
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match line.parse::<InspectCommand>() {
+                Ok(InspectCommand::Pause) => {
+                    control.pause();
+                    report("Paused");
+                }
+                Ok(InspectCommand::Resume) => {
+                    control.resume();
+                    report("Resumed");
+                }
+                Ok(InspectCommand::Pace(pacing)) => {
+                    control.set_pacing(pacing);
+                    report(&format!("Pacing {}", pacing));
+                }
+                Ok(InspectCommand::Quit) => {
+                    control.stop();
+                    break;
+                }
+                Ok(InspectCommand::Help) => println!("{}", HELP),
+                Ok(_) => match control.console(line, CONSOLE_TIMEOUT) {
+                    Some(output) => print!("{}", output),
+                    None => println!("No answer from the simulation"),
+                },
+                Err(e) => println!("{}", e),
+            }
+        }
+    });
+}
+
+/// Play back a finished run in the rerun viewer. A trace is first rebuilt
+/// into a recording.
+fn replay_command(config: ReplayConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::trace_playback;
+
+    if !config.recording.exists() {
+        return Err(RunnerError::ConfigError(format!(
+            "Recording not found: {}",
+            config.recording.display()
+        )));
+    }
+
+    let recording = if config.recording.extension().is_some_and(|ext| ext == "rrd") {
+        config.recording.clone()
+    } else {
+        if config.models.is_empty() {
+            return Err(RunnerError::ConfigError(
+                "Replaying a trace requires the run's model file(s) (--model)".to_string(),
+            ));
+        }
+        let paths: Vec<&Path> = config.models.iter().map(|p| p.as_path()).collect();
+        let model = mcsim_model::load_models(&paths)?;
+        let trace: serde_json::Value =
+            serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(&config.recording)?))?;
+        let packets = trace_playback::packets_from_trace(&trace)?;
+
+        let nodes = trace_playback::playback_nodes(&model);
+        let links = vis_links(&model, &nodes);
+        let output = config.save.clone().unwrap_or_else(|| config.recording.with_extension("rrd"));
+        {
+            // The recording is flushed when the logger goes out of scope
+            let mut logger = RerunLogger::with_save_path("MCSim", nodes, links, Some(&output))
+                .map_err(|e| RunnerError::ConfigError(format!("Failed to create recording: {}", e)))?;
+            for packet in &packets {
+                logger
+                    .log_playback(packet)
+                    .map_err(|e| RunnerError::ConfigError(format!("Failed to record playback: {}", e)))?;
+            }
+        }
+        eprintln!("✓ Rebuilt {} packet events into {}", packets.len(), output.display());
+
+        if config.save.is_some() {
+            return Ok(());
+        }
+        output
+    };
+
+    eprintln!("Opening {} in the rerun viewer...", recording.display());
+    mcsim_runner::rerun_logger::open_recording(&recording, config.web)?;
+    Ok(())
+}
+
+fn inspect_command(config: InspectConfig) -> Result<(), RunnerError> {
+    use std::io::{BufRead, IsTerminal};
+
+    let recorder = Arc::new(metrics_export::InMemoryRecorder::new());
+    let recorder = match metrics::set_global_recorder(recorder.clone()) {
+        Ok(()) => {
+            mcsim_metrics::describe_metrics();
+            Some(recorder)
+        }
+        Err(e) => {
+            eprintln!("Warning: Failed to set metrics recorder: {}", e);
+            None
+        }
+    };
+
+    let model = load_scenario(&config.models)?;
+    let seed = config.seed.unwrap_or_else(|| {
+        use rand::Rng;
+        rand::thread_rng().gen()
+    });
+    let simulation = build_simulation(&model, seed)?;
+    let mut event_loop = mcsim_runner::create_event_loop(simulation, seed);
+    event_loop.set_script_reloader(ScriptReloader::new(config.models.clone(), false));
+    let mut inspector = Inspector::new(&event_loop, recorder);
+
+    let interactive = std::io::stdin().is_terminal();
+    if interactive {
+        eprintln!("Loaded {} nodes (seed {}). Type 'help' for commands.", model.nodes().len(), seed);
+    }
+    let stdout = std::io::stdout();
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        if interactive {
+            eprint!("mcsim> ");
+            std::io::stderr().flush()?;
+        }
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut out = stdout.lock();
+        match line.parse::<InspectCommand>() {
+            Ok(command) => {
+                if !inspector.execute(&mut event_loop, &command, &mut out)? {
+                    break;
+                }
+            }
+            Err(e) => writeln!(out, "{}", e)?,
+        }
+    }
+    Ok(())
+}
+
+fn heatmap_command(config: HeatmapConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::heatmap::{self, HeatmapNode};
+
+    let paths: Vec<&Path> = config.models.iter().map(|p| p.as_path()).collect();
+    let model = mcsim_model::load_models(&paths)?;
+    let trace: serde_json::Value = serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(&config.trace)?))?;
+    let transmissions = heatmap::transmissions_from_trace(&trace)?;
+    let duration_s = config
+        .duration
+        .unwrap_or_else(|| transmissions.iter().map(|tx| tx.end_s).fold(0.0, f64::max));
+
+    let heatmap = heatmap::compute(
+        &HeatmapNode::from_model(&model),
+        &transmissions,
+        duration_s,
+        &heatmap::HeatmapConfig {
+            cell_size_m: config.cell_size,
+            margin_m: config.margin,
+            path_loss_exponent: config.path_loss_exponent,
+            saturation: config.saturation,
+        },
+    )?;
+
+    eprintln!(
+        "{} transmissions over {:.1}s: peak occupancy {:.1}%, {} of {} cells saturated (>= {:.0}%)",
+        transmissions.len(),
+        duration_s,
+        heatmap.max_occupancy() * 100.0,
+        heatmap.saturated_cells(),
+        heatmap.cells.len(),
+        config.saturation * 100.0
+    );
+
+    let geojson = serde_json::to_string(&heatmap.to_geojson())?;
+    if let Some(ref path) = config.output {
+        std::fs::write(path, geojson)?;
+    } else {
+        println!("{}", geojson);
+    }
+    Ok(())
+}
+
+fn timer_jitter_command(config: TimerJitterConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::timer_jitter::{BehaviorSummary, TimerJitter};
+
+    let model = load_scenario(&config.models)?;
+    let seed = config.seed.unwrap_or_else(|| {
+        use rand::Rng;
+        rand::thread_rng().gen()
+    });
+    let duration = SimTime::from_secs(config.duration);
+    let max_jitter = SimTime::from_micros((config.jitter * 1000.0) as u64);
+
+    let run = |jitter: Option<TimerJitter>| -> Result<(BehaviorSummary, u64), RunnerError> {
+        let mut event_loop = mcsim_runner::create_event_loop(build_simulation(&model, seed)?, seed);
+        if let Some(jitter) = jitter {
+            event_loop.set_timer_jitter(jitter);
+        }
+        let stats = event_loop.run(duration)?;
+        Ok((BehaviorSummary::capture(&event_loop, &stats), event_loop.timers_perturbed()))
+    };
+
+    eprintln!("Baseline run (seed {}, {:.0}s)...", seed, config.duration);
+    let (baseline, _) = run(None)?;
+
+    let mut differing_runs = 0;
+    for i in 0..config.runs {
+        let (jittered, perturbed) = run(Some(TimerJitter::new(max_jitter, seed.wrapping_add(i as u64 + 1))))?;
+        let differences = jittered.diff(&baseline);
+        eprintln!(
+            "Jittered run {} (±{}ms, {} timers shifted): {} difference(s)",
+            i + 1,
+            config.jitter,
+            perturbed,
+            differences.len()
+        );
+        for difference in &differences {
+            println!("run {}: {}", i + 1, difference);
+        }
+        if !differences.is_empty() {
+            differing_runs += 1;
+        }
+    }
+
+    if differing_runs == 0 {
+        eprintln!("No behavioral differences from the deterministic run.");
+    } else {
+        eprintln!("{} of {} jittered run(s) differed from the deterministic run.", differing_runs, config.runs);
+    }
+    Ok(())
+}
+
+fn blast_radius_command(config: BlastRadiusConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::blast_radius::{BlastRadiusReport, DomainImpact};
+    use std::collections::HashSet;
+
+    let model = load_scenario(&config.models)?;
+    let domains = mcsim_model::failure_domains(&model);
+    if domains.is_empty() {
+        return Err(RunnerError::ConfigError(
+            "no node is tagged with a failure domain (failure/domains)".to_string(),
+        ));
+    }
+    if config.fail_at >= config.duration {
+        return Err(RunnerError::ConfigError("--fail-at must be before the end of the run".to_string()));
+    }
+    if !model.failures().is_empty() {
+        eprintln!("Note: the scenario's own failures apply to every run, including the baseline");
+    }
+    let seed = config.seed.unwrap_or_else(|| {
+        use rand::Rng;
+        rand::thread_rng().gen()
+    });
+    let duration = SimTime::from_secs(config.duration);
+    let fail_at = SimTime::from_secs(config.fail_at);
+
+    let run = |failed: &[String], reason: &str| -> Result<mcsim_runner::EventLoop, RunnerError> {
+        let mut event_loop = mcsim_runner::create_event_loop(build_simulation(&model, seed)?, seed);
+        event_loop.enable_delivery_tracking();
+        for node in failed {
+            event_loop.schedule_power_off(node, fail_at, reason);
+        }
+        event_loop.run(duration)?;
+        Ok(event_loop)
+    };
+
+    eprintln!("Baseline run (seed {}, {:.0}s)...", seed, config.duration);
+    let baseline = run(&[], "")?;
+
+    let mut impacts = Vec::new();
+    for (domain, nodes) in &domains {
+        eprintln!("Failing domain '{}' ({} node(s)) at {:.0}s...", domain, nodes.len(), config.fail_at);
+        let failed_run = run(nodes, &format!("failure domain {}", domain))?;
+        let failed: HashSet<String> = nodes.iter().cloned().collect();
+        let (Some(before), Some(after)) =
+            (baseline.delivery_summary(fail_at, &failed), failed_run.delivery_summary(fail_at, &failed))
+        else {
+            continue;
+        };
+        impacts.push(DomainImpact::new(domain.clone(), nodes.clone(), &before, &after));
+    }
+
+    let report = BlastRadiusReport::new(fail_at, duration, impacts);
+    println!("{}", report);
+    if let Some(path) = &config.output {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        eprintln!("Report written to {}", path.display());
+    }
+    Ok(())
+}
+
+fn what_if_command(config: WhatIfConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::event_digest::EventDigest;
+    use mcsim_runner::timer_jitter::BehaviorSummary;
+    use mcsim_runner::what_if::{BranchOutcome, WhatIfPlan, WhatIfReport};
+    use std::collections::HashSet;
+
+    let model = load_scenario(&config.models)?;
+    let duration = SimTime::from_secs(config.duration);
+    let plan = WhatIfPlan::load(&config.plan)?;
+    plan.validate(model.nodes().keys().map(String::as_str), duration)?;
+    let seed = config.seed.unwrap_or_else(|| {
+        use rand::Rng;
+        rand::thread_rng().gen()
+    });
+
+    let mut outcomes = Vec::new();
+    for branch in &plan.branches {
+        let Some(checkpoint) = plan.checkpoint(&branch.from) else {
+            continue;
+        };
+        eprintln!(
+            "Branch '{}' from checkpoint '{}' at {:.0}s (seed {}, {:.0}s)...",
+            branch.name, checkpoint.name, checkpoint.at_s, seed, config.duration
+        );
+        let mut event_loop = mcsim_runner::create_event_loop(build_simulation(&model, seed)?, seed);
+        event_loop.enable_delivery_tracking();
+        event_loop.set_event_digest(EventDigest::new(0));
+        event_loop.run_until(checkpoint.time())?;
+        let digest = event_loop.event_digest().map_or(0, EventDigest::hash);
+        let at_checkpoint = BehaviorSummary::capture(&event_loop, event_loop.stats());
+
+        branch.apply(&mut event_loop)?;
+        let stats = event_loop.run(duration)?;
+        let powered_off: HashSet<String> = branch.power_off.iter().cloned().collect();
+        let coverage = event_loop
+            .delivery_summary(checkpoint.time(), &powered_off)
+            .and_then(|summary| summary.mean_coverage);
+        outcomes.push(BranchOutcome::new(
+            branch,
+            digest,
+            &at_checkpoint,
+            BehaviorSummary::capture(&event_loop, &stats),
+            coverage,
+        ));
+    }
+
+    let report = WhatIfReport::new(seed, duration, outcomes);
+    println!("{}", report);
+    if let Some(path) = &config.output {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        eprintln!("Report written to {}", path.display());
+    }
+    Ok(())
+}
+
+fn trace_diff_command(config: TraceDiffConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::trace_diff::{self, DiffOptions, Stream, TraceDiff};
+
+    let read_trace = |path: &Path| -> Result<serde_json::Value, RunnerError> {
+        Ok(serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(path)?))?)
+    };
+    let trace = read_trace(&config.trace)?;
+    let other_trace = config.other_trace.as_deref().map(read_trace).transpose()?;
+    let other_node = match (&config.with, &other_trace) {
+        (Some(node), _) => node.clone(),
+        (None, Some(_)) => config.node.clone(),
+        (None, None) => {
+            return Err(RunnerError::ConfigError(
+                "Name a node to compare with (--with), or give a second trace to compare the node across runs".to_string(),
+            ))
+        }
+    };
+    let label = |path: &Path, node: &str| match &other_trace {
+        Some(_) => format!("{}:{}", path.display(), node),
+        None => node.to_string(),
+    };
+
+    let streams = [
+        (&trace, config.trace.as_path(), config.node.as_str()),
+        (other_trace.as_ref().unwrap_or(&trace), config.other_trace.as_deref().unwrap_or(&config.trace), other_node.as_str()),
+    ]
+    .map(|(trace, path, node)| -> Result<Stream, RunnerError> {
+        let steps = trace_diff::node_steps(trace, node)?;
+        if steps.is_empty() {
+            return Err(RunnerError::ConfigError(format!("No trace entries for '{}' in {}", node, path.display())));
+        }
+        Ok(Stream { label: label(path, node), steps })
+    });
+    let [a, b] = streams;
+    let options = DiffOptions { ignore_payload: config.ignore_payload, window: config.window };
+    let diff = TraceDiff::new(a?, b?, &options);
+
+    print!("{}", diff.report(3, config.show));
+    if let Some(path) = &config.output {
+        std::fs::write(path, serde_json::to_string_pretty(&diff)?)?;
+        eprintln!("Alignment written to {}", path.display());
+    }
+    Ok(())
+}
+
+fn minimize_command(config: MinimizeConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::minimize::{minimize, FailureSignature, Scenario};
+
+    let scenario = Scenario::load(&config.models, config.duration)?;
+    let exe = std::env::current_exe()?;
+    let work_dir = std::env::temp_dir().join(format!("mcsim-minimize-{}", std::process::id()));
+    // Each candidate is run in its own process, as the metrics recorder is global
+    let run = |scenario: &Scenario| -> Result<Option<FailureSignature>, RunnerError> {
+        let paths = scenario.write(&work_dir)?;
+        let output = std::process::Command::new(&exe)
+            .arg("run")
+            .args(&paths)
+            .arg("--duration")
+            .arg(scenario.duration_s.to_string())
+            .arg("--seed")
+            .arg(config.seed.to_string())
+            .output()?;
+        for path in paths {
+            std::fs::remove_file(path)?;
+        }
+        Ok(FailureSignature::from_run_output(&output))
+    };
+
+    eprintln!("Running the scenario ({})...", scenario.size());
+    let Some(signature) = run(&scenario)? else {
+        return Err(RunnerError::ConfigError(format!(
+            "The scenario doesn't fail with seed {} over {:.0}s",
+            config.seed, config.duration
+        )));
+    };
+    eprintln!("Failure: {}", signature);
+
+    let minimized = minimize(scenario, &signature, config.max_runs, |candidate| {
+        let reproduces = run(candidate).ok().flatten().as_ref() == Some(&signature);
+        eprintln!("{} {}", if reproduces { "✓" } else { "✗" }, candidate.size());
+        reproduces
+    });
+    let _ = std::fs::remove_dir(&work_dir);
+
+    let paths = minimized.scenario.write(&config.output)?;
+    println!("Minimal scenario: {} after {} run(s)", minimized.scenario.size(), minimized.runs);
+    if minimized.budget_exhausted {
+        println!("Run budget spent; it may shrink further with a larger --max-runs");
+    }
+    println!(
+        "Reproduce with: mcsim run {} --duration {} --seed {}",
+        paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(" "),
+        minimized.scenario.duration_s,
+        config.seed
+    );
+    Ok(())
+}
+
+/// `mcsim run` of a scenario in a child process, for commands that run it
+/// many times: the metrics recorder is global to a process, so each run
+/// needs its own.
+struct ChildRun<'a> {
+    exe: PathBuf,
+    models: &'a [PathBuf],
+    duration: f64,
+    metric_specs: Vec<String>,
+}
+
+impl<'a> ChildRun<'a> {
+    /// Validate the models and metric specs once, rather than in every run.
+    fn new(models: &'a [PathBuf], duration: f64, metric_specs: &[String]) -> Result<Self, RunnerError> {
+        for spec in metric_specs {
+            metric_spec::MetricSpec::parse(spec)
+                .map_err(|e| RunnerError::ConfigError(format!("Invalid metric spec '{}': {}", spec, e)))?;
+        }
+        let paths: Vec<&Path> = models.iter().map(|p| p.as_path()).collect();
+        mcsim_model::load_models(&paths)?;
+        Ok(ChildRun {
+            exe: std::env::current_exe()?,
+            models,
+            duration,
+            metric_specs: if metric_specs.is_empty() {
+                vec!["mcsim.*".to_string()]
+            } else {
+                metric_specs.to_vec()
+            },
+        })
+    }
+
+    /// Run with `seed` and any extra `run` arguments, writing metrics as CSV
+    /// to `metrics_file`. Returns the statistics JSON the run printed.
+    fn run(&self, seed: u64, extra_args: &[String], metrics_file: &Path) -> Result<String, String> {
+        let mut command = std::process::Command::new(&self.exe);
+        command
+            .arg("run")
+            .args(self.models)
+            .arg("--duration")
+            .arg(self.duration.to_string())
+            .arg("--seed")
+            .arg(seed.to_string())
+            .arg("--metrics-output")
+            .arg("csv")
+            .arg("--metrics-file")
+            .arg(metrics_file)
+            .args(extra_args);
+        for spec in &self.metric_specs {
+            command.arg("--metric").arg(spec);
+        }
+        let output = command.output().map_err(|e| e.to_string())?;
+        // A failed assertion still completes the run
+        if !output.status.success() && output.status.code() != Some(EXIT_ASSERTION_FAILED) {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(stderr.lines().last().map_or_else(|| output.status.to_string(), str::to_string));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Call `run` for every index in `0..count`, `jobs` at a time, returning
+/// the results in index order.
+fn run_jobs<T: Send>(jobs: usize, count: usize, run: impl Fn(usize) -> T + Sync) -> Vec<T> {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Mutex;
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(count));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(count) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= count {
+                    break;
+                }
+                let result = run(index);
+                let mut results = results.lock().unwrap();
+                results.push((index, result));
+                eprintln!("{} of {} run(s) done", results.len(), count);
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Write a report as CSV if the path ends in .csv, otherwise as JSON.
+fn write_report<T: serde::Serialize>(path: &Path, report: &T, csv: impl FnOnce() -> String) -> Result<(), RunnerError> {
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv")) {
+        std::fs::write(path, csv())?;
+    } else {
+        std::fs::write(path, serde_json::to_string_pretty(report)?)?;
+    }
+    eprintln!("Report written to {}", path.display());
+    Ok(())
+}
+
+fn experiment_command(config: ExperimentConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::experiment::{run_seeds, ExperimentReport, RunFailure, RunValues};
+
+    if config.runs == 0 || config.jobs == 0 {
+        return Err(RunnerError::ConfigError("--runs and --jobs must be at least 1".to_string()));
+    }
+    let child = ChildRun::new(&config.models, config.duration, &config.metric_specs)?;
+    let seed = config.seed.unwrap_or_else(|| {
+        use rand::Rng;
+        rand::thread_rng().gen()
+    });
+    let seeds = run_seeds(seed, config.runs);
+    let work_dir = std::env::temp_dir().join(format!("mcsim-experiment-{}", std::process::id()));
+    std::fs::create_dir_all(&work_dir)?;
+
+    eprintln!(
+        "Running {} run(s) of {:.0}s from seed {} ({} at a time)...",
+        config.runs, config.duration, seed, config.jobs
+    );
+    let results = run_jobs(config.jobs, config.runs, |index| {
+        let seed = seeds[index];
+        let metrics_file = work_dir.join(format!("run-{}.csv", index));
+        let stats = child.run(seed, &[], &metrics_file);
+        let metrics = std::fs::read_to_string(&metrics_file).ok();
+        let _ = std::fs::remove_file(&metrics_file);
+        stats
+            .and_then(|stats| RunValues::parse(index, seed, &stats, metrics.as_deref()).map_err(|e| e.to_string()))
+            .map_err(|error| {
+                eprintln!("Run {} (seed {}) failed: {}", index, seed, error);
+                RunFailure { run: index, seed, error }
+            })
+    });
+    let _ = std::fs::remove_dir(&work_dir);
+
+    let (runs, failures): (Vec<_>, Vec<_>) = results.into_iter().partition(Result::is_ok);
+    let runs: Vec<RunValues> = runs.into_iter().filter_map(Result::ok).collect();
+    if runs.is_empty() {
+        return Err(RunnerError::ConfigError("every run of the experiment failed".to_string()));
+    }
+    let failures = failures.into_iter().filter_map(Result::err).collect();
+    let report = ExperimentReport::new(seed, config.duration, runs, failures);
+    println!("{}", report);
+    if let Some(path) = &config.output {
+        write_report(path, &report, || report.to_csv())?;
+    }
+    Ok(())
+}
+
+fn sweep_command(config: SweepConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::experiment::{RunValues, SweepReport, SweepRun};
+
+    if config.jobs == 0 {
+        return Err(RunnerError::ConfigError("--jobs must be at least 1".to_string()));
+    }
+    let paths: Vec<&Path> = config.models.iter().map(|p| p.as_path()).collect();
+    let model = mcsim_model::load_models(&paths)?;
+    let points = model.sweep_points();
+    if points.is_empty() {
+        return Err(RunnerError::ConfigError("the scenario has no sweep section".to_string()));
+    }
+    let child = ChildRun::new(&config.models, config.duration, &config.metric_specs)?;
+    let seed = config.seed.unwrap_or_else(|| {
+        use rand::Rng;
+        rand::thread_rng().gen()
+    });
+    std::fs::create_dir_all(&config.output_dir)?;
+
+    eprintln!(
+        "Running {} combination(s) of {:.0}s with seed {} ({} at a time)...",
+        points.len(),
+        config.duration,
+        seed,
+        config.jobs
+    );
+    let runs = run_jobs(config.jobs, points.len(), |index| {
+        let point = &points[index];
+        let dir = config.output_dir.join(format!("{:03}-{}", index, point.slug()));
+        let result = std::fs::create_dir_all(&dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| child.run(seed, &["--sweep-point".to_string(), index.to_string()], &dir.join("metrics.csv")))
+            .and_then(|stats| {
+                std::fs::write(dir.join("stats.json"), &stats).map_err(|e| e.to_string())?;
+                let metrics = std::fs::read_to_string(dir.join("metrics.csv")).ok();
+                RunValues::parse(index, seed, &stats, metrics.as_deref()).map_err(|e| e.to_string())
+            });
+        if let Err(error) = &result {
+            eprintln!("Run {} ({}) failed: {}", index, point, error);
+        }
+        let (values, error) = match result {
+            Ok(run) => (run.values, None),
+            Err(error) => (Default::default(), Some(error)),
+        };
+        SweepRun {
+            index,
+            parameters: point.values.iter().map(|(def, value)| (def.name.to_string(), value.to_string())).collect(),
+            dir: dir.display().to_string(),
+            values,
+            error,
+        }
+    });
+
+    let parameters = model.sweep().iter().map(|axis| axis.property.name.to_string()).collect();
+    let report = SweepReport::new(seed, config.duration, parameters, runs);
+    println!("{}", report);
+    std::fs::write(config.output_dir.join("summary.csv"), report.to_csv())?;
+    std::fs::write(config.output_dir.join("summary.json"), serde_json::to_string_pretty(&report)?)?;
+    eprintln!("Summary written to {}", config.output_dir.join("summary.csv").display());
+    if report.runs.iter().all(|r| r.error.is_some()) {
+        return Err(RunnerError::ConfigError("every run of the sweep failed".to_string()));
+    }
+    Ok(())
+}
+
+fn serve_command(config: ServeConfig) -> Result<(), RunnerError> {
+    use mcsim_runner::sim_server::{ServerConfig, ServerQuotas, SimulationServer};
+    use std::net::ToSocketAddrs;
+
+    if config.uart_ports == 0 {
+        return Err(RunnerError::ConfigError("--uart-ports must be at least 1".to_string()));
+    }
+    let tenants: std::collections::BTreeMap<String, String> = match &config.tenants {
+        Some(path) => serde_yaml::from_str(&std::fs::read_to_string(path)?).map_err(|e| {
+            RunnerError::ConfigError(format!("Invalid tenants file {}: {}", path.display(), e))
+        })?,
+        None => Default::default(),
+    };
+    let loopback = config
+        .listen
+        .to_socket_addrs()
+        .is_ok_and(|mut addrs| addrs.all(|addr| addr.ip().is_loopback()));
+    if tenants.is_empty() && !loopback {
+        return Err(RunnerError::ConfigError(format!(
+            "Serving on {} lets anyone who can connect run simulations; pass --tenants with each tenant's token",
+            config.listen
+        )));
+    }
+    let server_config = ServerConfig {
+        exe: std::env::current_exe()?,
+        data_dir: config.data_dir.clone(),
+        quotas: ServerQuotas {
+            max_simulations: config.max_simulations,
+            max_per_tenant: config.max_per_tenant,
+            max_duration_s: config.max_duration,
+            max_wall_time_s: config.max_wall_time,
+            max_artifact_bytes: config.max_artifact_size,
+        },
+        uart_base_port: config.uart_base_port,
+        uart_ports_per_simulation: config.uart_ports,
+        tenants,
+        models_dir: config.models_dir.clone(),
+    };
+    let server = SimulationServer::start(&config.listen, server_config)
+        .map_err(|e| RunnerError::ConfigError(format!("Cannot serve on {}: {}", config.listen, e)))?;
+    eprintln!(
+        "✓ Serving simulations on {} (artifacts in {})",
+        server.local_addr(),
+        config.data_dir.display()
+    );
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let stop_flag_clone = stop_flag.clone();
+    ctrlc::set_handler(move || {
+        stop_flag_clone.store(true, Ordering::Relaxed);
+    })
+    .expect("Error setting Ctrl+C handler");
+    while !stop_flag.load(Ordering::Relaxed) {
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+
+    eprintln!("Stopping simulations...");
+    server.shutdown();
+    Ok(())
+}
+
 fn main() -> Result<(), RunnerError> {
     // Initialize tracing subscriber with RUST_LOG env filter
     // Default to "warn" level if RUST_LOG is not set
@@ -1389,20 +4060,30 @@ fn main() -> Result<(), RunnerError> {
     let cli = Cli::parse();
     
     match cli.command {
+        Commands::Run(config) if config.validate => {
+            validate_scenario(&config)?;
+        }
         Commands::Run(config) => {
             let metrics_output = config.metrics_output;
-            let stats = run_simulation(config)?;
+            let metrics_file = config.metrics_file.is_some();
+            let stats = run_simulation(*config)?;
 
             // Output stats as JSON to stdout only if not exporting metrics to stdout
-            if metrics_output.is_none() {
+            if metrics_output.is_none() || metrics_file {
                 println!("{}", serde_json::to_string_pretty(&stats)?);
             }
+
+            // A failed scenario assertion fails the run, so scenarios can be
+            // used as regression tests
+            if stats.assertions.iter().any(|r| !r.passed) {
+                std::process::exit(EXIT_ASSERTION_FAILED);
+            }
         }
-        Commands::Metrics => {
-            print_metrics_info();
+        Commands::Metrics(config) => {
+            metrics_command(config)?;
         }
-        Commands::Properties => {
-            print_properties_info();
+        Commands::Properties(config) => {
+            properties_command(config)?;
         }
         Commands::PredictLink(config) => {
             predict_link(config)?;
@@ -1416,11 +4097,81 @@ fn main() -> Result<(), RunnerError> {
         Commands::Keygen(config) => {
             keygen_command(config)?;
         }
+        Commands::Replay(config) => {
+            replay_command(config)?;
+        }
+        Commands::Inspect(config) => {
+            inspect_command(config)?;
+        }
+        Commands::Heatmap(config) => {
+            heatmap_command(config)?;
+        }
+        Commands::TraceDiff(config) => {
+            trace_diff_command(config)?;
+        }
+        Commands::TimerJitter(config) => {
+            timer_jitter_command(config)?;
+        }
+        Commands::BlastRadius(config) => {
+            blast_radius_command(config)?;
+        }
+        Commands::WhatIf(config) => {
+            what_if_command(config)?;
+        }
+        Commands::Minimize(config) => {
+            minimize_command(config)?;
+        }
+        Commands::Experiment(config) => {
+            experiment_command(config)?;
+        }
+        Commands::Sweep(config) => {
+            sweep_command(config)?;
+        }
+        Commands::Serve(config) => {
+            serve_command(config)?;
+        }
+        Commands::Coverage(config) => {
+            coverage_command(config)?;
+        }
+        Commands::CalibrateLink(config) => {
+            calibrate_link_command(config)?;
+        }
+        Commands::PrefetchElevation(config) => {
+            prefetch_elevation_command(config)?;
+        }
     }
 
     Ok(())
 }
 
+/// List the metric catalog in the requested format
+fn metrics_command(config: MetricsCatalogConfig) -> Result<(), RunnerError> {
+    use mcsim_metrics::catalog;
+
+    let rendered = match config.format {
+        MetricsCatalogFormat::Text if config.output.is_none() => {
+            print_metrics_info();
+            return Ok(());
+        }
+        MetricsCatalogFormat::Text => {
+            return Err(RunnerError::ConfigError(
+                "--output requires --format json or markdown".to_string(),
+            ));
+        }
+        MetricsCatalogFormat::Json => catalog::to_json(&catalog::catalog()),
+        MetricsCatalogFormat::Markdown => catalog::to_markdown(&catalog::catalog()),
+    };
+
+    match config.output {
+        Some(path) => {
+            std::fs::write(&path, rendered)?;
+            eprintln!("Wrote metric catalog to {}", path.display());
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
 /// Print information about all available metrics
 fn print_metrics_info() {
     use mcsim_metrics::metric_defs;
@@ -1476,6 +4227,60 @@ fn print_metrics_info() {
 }
 
 /// Print information about all available properties
+/// List properties, either the full guide or a filtered lookup
+fn properties_command(config: PropertiesConfig) -> Result<(), RunnerError> {
+    let scope = config.scope.map(mcsim_model::PropertyScope::from);
+    if config.filter.is_none() && scope.is_none() && matches!(config.format, PropertiesFormat::Text) {
+        print_properties_info();
+        return Ok(());
+    }
+
+    let filter = config.filter.as_deref().unwrap_or("");
+    let mut props: Vec<_> = mcsim_model::search_properties(filter, scope).collect();
+    props.sort_by_key(|p| (p.scope.to_string(), p.name));
+
+    match config.format {
+        PropertiesFormat::Json => {
+            let entries: Vec<serde_json::Value> = props
+                .iter()
+                .map(|p| {
+                    serde_json::json!({
+                        "name": p.name,
+                        "scope": p.scope.to_string(),
+                        "type": p.value_type.to_string(),
+                        "default": p.default.to_string(),
+                        "unit": p.unit,
+                        "aliases": p.aliases,
+                        "description": p.description,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&entries)?);
+        }
+        PropertiesFormat::Text => {
+            if props.is_empty() {
+                eprintln!("No properties match '{}'.", filter);
+                return Ok(());
+            }
+            for prop in &props {
+                println!("{} ({}, {})", prop.name, prop.value_type, prop.scope);
+                println!("    {}", prop.description);
+                print!("    Default: {}", prop.default);
+                if let Some(unit) = &prop.unit {
+                    print!(" {}", unit);
+                }
+                println!();
+                if !prop.aliases.is_empty() {
+                    println!("    Aliases: {}", prop.aliases.join(", "));
+                }
+                println!();
+            }
+            eprintln!("{} matching propert{}", props.len(), if props.len() == 1 { "y" } else { "ies" });
+        }
+    }
+    Ok(())
+}
+
 fn print_properties_info() {
     use mcsim_model::{properties_by_scope, PropertyScope};
     
@@ -1579,17 +4384,49 @@ mod tests {
             seed: Some(12345),
             output: None,
             uart_base_port: 9000,
+            pty_dir: None,
             rerun: false,
+            rerun_save: None,
             verbose: false,
             trace: None,
             metrics_output: None,
             metrics_file: None,
+            metrics_listen: None,
             metric_specs: vec![],
             speed: 1.0,
             max_catchup_ms: 100,
+            pacing: None,
             break_at_event: None,
             watchdog_timeout: DEFAULT_WATCHDOG_TIMEOUT_S,
             metrics_warmup: None,
+            timezone: None,
+            max_artifact_size: None,
+            serial_capture: None,
+            pcap: None,
+            packet_db: None,
+            perfetto: None,
+            serial_capture_nodes: None,
+            calibration_report: None,
+            snr_estimates: None,
+            sf_margin: 5.0,
+            timeline: None,
+            advert_report: None,
+            message_paths: None,
+            sla_report: None,
+            sla_delivery: 95.0,
+            sla_latency: 30.0,
+            interactive: false,
+            watch_scripts: false,
+            sweep_point: None,
+            control_listen: None,
+            control_wait: false,
+            dashboard: None,
+            mqtt: None,
+            mqtt_topic: mcsim_runner::mqtt_bridge::DEFAULT_TOPIC_PREFIX.to_string(),
+            mqtt_commands: false,
+            record: None,
+            replay: None,
+            validate: false,
         };
         assert_eq!(config.duration, Some(3600.0));
     }
@@ -1603,17 +4440,49 @@ mod tests {
             seed: Some(12345),
             output: None,
             uart_base_port: 9000,
+            pty_dir: None,
             rerun: false,
+            rerun_save: None,
             verbose: false,
             trace: None,
             metrics_output: None,
             metrics_file: None,
+            metrics_listen: None,
             metric_specs: vec![],
             speed: 1.0,
             max_catchup_ms: 100,
+            pacing: None,
             break_at_event: None,
             watchdog_timeout: DEFAULT_WATCHDOG_TIMEOUT_S,
             metrics_warmup: None,
+            timezone: None,
+            max_artifact_size: None,
+            serial_capture: None,
+            pcap: None,
+            packet_db: None,
+            perfetto: None,
+            serial_capture_nodes: None,
+            calibration_report: None,
+            snr_estimates: None,
+            sf_margin: 5.0,
+            timeline: None,
+            advert_report: None,
+            message_paths: None,
+            sla_report: None,
+            sla_delivery: 95.0,
+            sla_latency: 30.0,
+            interactive: false,
+            watch_scripts: false,
+            sweep_point: None,
+            control_listen: None,
+            control_wait: false,
+            dashboard: None,
+            mqtt: None,
+            mqtt_topic: mcsim_runner::mqtt_bridge::DEFAULT_TOPIC_PREFIX.to_string(),
+            mqtt_commands: false,
+            record: None,
+            replay: None,
+            validate: false,
         };
         assert!(config.duration.is_none());
     }
@@ -1627,17 +4496,49 @@ mod tests {
             seed: Some(12345),
             output: None,
             uart_base_port: 9000,
+            pty_dir: None,
             rerun: false,
+            rerun_save: None,
             verbose: false,
             trace: None,
             metrics_output: None,
             metrics_file: None,
+            metrics_listen: None,
             metric_specs: vec![],
             speed: 2.0,
             max_catchup_ms: 200,
+            pacing: None,
             break_at_event: None,
             watchdog_timeout: DEFAULT_WATCHDOG_TIMEOUT_S,
             metrics_warmup: None,
+            timezone: None,
+            max_artifact_size: None,
+            serial_capture: None,
+            pcap: None,
+            packet_db: None,
+            perfetto: None,
+            serial_capture_nodes: None,
+            calibration_report: None,
+            snr_estimates: None,
+            sf_margin: 5.0,
+            timeline: None,
+            advert_report: None,
+            message_paths: None,
+            sla_report: None,
+            sla_delivery: 95.0,
+            sla_latency: 30.0,
+            interactive: false,
+            watch_scripts: false,
+            sweep_point: None,
+            control_listen: None,
+            control_wait: false,
+            dashboard: None,
+            mqtt: None,
+            mqtt_topic: mcsim_runner::mqtt_bridge::DEFAULT_TOPIC_PREFIX.to_string(),
+            mqtt_commands: false,
+            record: None,
+            replay: None,
+            validate: false,
         };
         assert_eq!(config.speed, 2.0);
         assert_eq!(config.max_catchup_ms, 200);
@@ -1652,17 +4553,49 @@ mod tests {
             seed: Some(12345),
             output: None,
             uart_base_port: 9000,
+            pty_dir: None,
             rerun: false,
+            rerun_save: None,
             verbose: false,
             trace: None,
             metrics_output: Some(MetricsOutputFormat::Json),
             metrics_file: Some(PathBuf::from("metrics.json")),
+            metrics_listen: None,
             metric_specs: vec!["mcsim.radio.*/node".to_string()],
             speed: 1.0,
             max_catchup_ms: 100,
+            pacing: None,
             break_at_event: None,
             watchdog_timeout: DEFAULT_WATCHDOG_TIMEOUT_S,
             metrics_warmup: None,
+            timezone: None,
+            max_artifact_size: None,
+            serial_capture: None,
+            pcap: None,
+            packet_db: None,
+            perfetto: None,
+            serial_capture_nodes: None,
+            calibration_report: None,
+            snr_estimates: None,
+            sf_margin: 5.0,
+            timeline: None,
+            advert_report: None,
+            message_paths: None,
+            sla_report: None,
+            sla_delivery: 95.0,
+            sla_latency: 30.0,
+            interactive: false,
+            watch_scripts: false,
+            sweep_point: None,
+            control_listen: None,
+            control_wait: false,
+            dashboard: None,
+            mqtt: None,
+            mqtt_topic: mcsim_runner::mqtt_bridge::DEFAULT_TOPIC_PREFIX.to_string(),
+            mqtt_commands: false,
+            record: None,
+            replay: None,
+            validate: false,
         };
         assert!(config.metrics_output.is_some());
         assert!(config.metrics_file.is_some());
@@ -1680,17 +4613,49 @@ mod tests {
             seed: Some(12345),
             output: None,
             uart_base_port: 9000,
+            pty_dir: None,
             rerun: false,
+            rerun_save: None,
             verbose: false,
             trace: None,
             metrics_output: None,
             metrics_file: None,
+            metrics_listen: None,
             metric_specs: vec![],
             speed: 1.0,
             max_catchup_ms: 100,
+            pacing: None,
             break_at_event: None,
             watchdog_timeout: DEFAULT_WATCHDOG_TIMEOUT_S,
             metrics_warmup: None,
+            timezone: None,
+            max_artifact_size: None,
+            serial_capture: None,
+            pcap: None,
+            packet_db: None,
+            perfetto: None,
+            serial_capture_nodes: None,
+            calibration_report: None,
+            snr_estimates: None,
+            sf_margin: 5.0,
+            timeline: None,
+            advert_report: None,
+            message_paths: None,
+            sla_report: None,
+            sla_delivery: 95.0,
+            sla_latency: 30.0,
+            interactive: false,
+            watch_scripts: false,
+            sweep_point: None,
+            control_listen: None,
+            control_wait: false,
+            dashboard: None,
+            mqtt: None,
+            mqtt_topic: mcsim_runner::mqtt_bridge::DEFAULT_TOPIC_PREFIX.to_string(),
+            mqtt_commands: false,
+            record: None,
+            replay: None,
+            validate: false,
         };
         assert_eq!(config.models.len(), 2);
     }
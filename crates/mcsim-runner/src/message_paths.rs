@@ -0,0 +1,519 @@
+//! End-to-end paths of messages across hops.
+//!
+//! Repeaters retransmit a packet with the same payload, so its payload hash
+//! identifies a message across the whole mesh. A [`PathTracker`] follows
+//! every hash from its first transmission: each node that receives it
+//! intact records who it first heard it from and how many copies it heard,
+//! and each retransmission is attributed to the node that forwarded it.
+//! Walking the first-heard links back from a receiver to the origin gives
+//! the path the message actually took:
+//!
+//! ```text
+//! Alice > R1 > R2 > Bob
+//! ```
+//!
+//! with, for each hop, when the sender started transmitting and when the
+//! receiver finished hearing it. The time a forwarder held the message is
+//! the gap between hearing one hop and sending the next.
+//!
+//! The tracker emits `mcsim.message.*` metrics as the run goes;
+//! [`PathTracker::report`] summarizes the paths for `mcsim run
+//! --message-paths` and the `message_paths` table of the packet database.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use mcsim_common::{Event, EventPayload, LoraPacket};
+use mcsim_metrics::{metric_defs, metrics};
+use mcsim_model::NodeInfo;
+use serde::Serialize;
+
+/// The first intact copy of a message a node heard, and how many it heard.
+#[derive(Debug, Clone)]
+struct Reception {
+    /// Radio entity ID of the sender of the first copy.
+    from: u64,
+    /// When the sender started transmitting it, in microseconds.
+    sent_us: u64,
+    /// When the node finished receiving it, in microseconds.
+    heard_us: u64,
+    /// Intact copies heard.
+    copies: u32,
+}
+
+/// Everything seen of one payload hash.
+#[derive(Debug, Clone)]
+struct Message {
+    payload_type: &'static str,
+    route_type: &'static str,
+    /// Radio entity ID of the first transmitter.
+    origin: u64,
+    /// Time of the first transmission in microseconds.
+    sent_us: u64,
+    /// Transmissions by any node, retries and retransmissions included.
+    transmissions: u32,
+    /// First reception of each node other than the origin, by radio entity ID.
+    receptions: HashMap<u64, Reception>,
+    /// Time from first hearing to first retransmitting, per forwarder.
+    forward_delays_us: HashMap<u64, u64>,
+}
+
+impl Message {
+    fn new(packet: &LoraPacket, origin: u64, sent_us: u64) -> Self {
+        Self {
+            payload_type: packet.payload_type_label(),
+            route_type: packet.route_type_label(),
+            origin,
+            sent_us,
+            transmissions: 0,
+            receptions: HashMap::new(),
+            forward_delays_us: HashMap::new(),
+        }
+    }
+
+    /// Hops from the origin to `node` along the first-heard links, in order.
+    ///
+    /// A chain that reaches a node which never heard the message (it sent
+    /// the same payload on its own) starts there.
+    fn hops_to(&self, node: u64) -> Vec<(u64, u64, &Reception)> {
+        let mut hops = Vec::new();
+        let mut current = node;
+        while let Some(reception) = self.receptions.get(&current) {
+            hops.push((reception.from, current, reception));
+            current = reception.from;
+            // A node can't be on its own path; stop at a loop
+            if current == self.origin || hops.iter().any(|(_, to, _)| *to == current) {
+                break;
+            }
+        }
+        hops.reverse();
+        hops
+    }
+}
+
+/// Follows every message through the mesh by its payload hash.
+pub struct PathTracker {
+    /// Node name and type by radio entity ID.
+    nodes: HashMap<u64, (String, String)>,
+    messages: HashMap<String, Message>,
+}
+
+impl PathTracker {
+    /// Track the messages exchanged by `nodes`.
+    pub fn new(nodes: &[NodeInfo]) -> Self {
+        Self {
+            nodes: nodes
+                .iter()
+                .map(|n| (n.radio_entity_id, (n.name.clone(), n.node_type.clone())))
+                .collect(),
+            messages: HashMap::new(),
+        }
+    }
+
+    /// Observe a processed event.
+    pub fn observe(&mut self, event: &Event) {
+        let time_us = event.time.as_micros();
+        match &event.payload {
+            EventPayload::TransmitAir(tx) => {
+                let Some(hash) = packet_hash(&tx.packet) else {
+                    return;
+                };
+                let radio = tx.radio_id.0;
+                let message = self
+                    .messages
+                    .entry(hash)
+                    .or_insert_with(|| Message::new(&tx.packet, radio, time_us));
+                message.transmissions += 1;
+                let Some(reception) = message.receptions.get(&radio) else {
+                    return;
+                };
+                if message.forward_delays_us.contains_key(&radio) {
+                    return;
+                }
+                let delay_us = time_us.saturating_sub(reception.heard_us);
+                message.forward_delays_us.insert(radio, delay_us);
+                if let Some((name, node_type)) = self.nodes.get(&radio) {
+                    let labels = [("node", name.clone()), ("node_type", node_type.clone())];
+                    metrics::histogram!(metric_defs::MESSAGE_FORWARD_DELAY.name, &labels).record(delay_us as f64);
+                }
+            }
+            // Posted by the receiving radio to its firmware
+            EventPayload::RadioRxPacket(rx) if !rx.was_collided && !rx.was_weak_signal && !rx.was_corrupted => {
+                let Some(hash) = packet_hash(&rx.packet) else {
+                    return;
+                };
+                let radio = event.source.0;
+                let Some((name, node_type)) = self.nodes.get(&radio) else {
+                    return;
+                };
+                let sent_us = rx.start_time.as_micros();
+                // A copy that arrives before its transmission was seen came
+                // from outside the simulation (a hardware bridge)
+                let message = self
+                    .messages
+                    .entry(hash)
+                    .or_insert_with(|| Message::new(&rx.packet, rx.source_radio_id.0, sent_us));
+                if radio == message.origin {
+                    return;
+                }
+                let payload_type = message.payload_type;
+                if let Some(reception) = message.receptions.get_mut(&radio) {
+                    reception.copies += 1;
+                    let labels = [
+                        ("node", name.clone()),
+                        ("node_type", node_type.clone()),
+                        ("payload_type", payload_type.to_string()),
+                    ];
+                    metrics::counter!(metric_defs::MESSAGE_DUPLICATES.name, &labels).increment(1);
+                    return;
+                }
+                message.receptions.insert(
+                    radio,
+                    Reception { from: rx.source_radio_id.0, sent_us, heard_us: time_us, copies: 1 },
+                );
+                let labels = [
+                    ("node", name.clone()),
+                    ("node_type", node_type.clone()),
+                    ("payload_type", payload_type.to_string()),
+                    ("route_type", message.route_type.to_string()),
+                ];
+                metrics::histogram!(metric_defs::MESSAGE_PATH_HOPS.name, &labels)
+                    .record(message.hops_to(radio).len() as f64);
+            }
+            _ => {}
+        }
+    }
+
+    /// Paths of every message seen so far.
+    pub fn report(&self) -> MessagePathReport {
+        let name = |radio: u64| {
+            self.nodes
+                .get(&radio)
+                .map_or_else(|| format!("radio {}", radio), |(name, _)| name.clone())
+        };
+        let mut messages: Vec<MessagePath> = self
+            .messages
+            .iter()
+            .map(|(hash, message)| {
+                let mut deliveries: Vec<Delivery> = message
+                    .receptions
+                    .iter()
+                    .map(|(&node, reception)| Delivery {
+                        node: name(node),
+                        hops: message
+                            .hops_to(node)
+                            .into_iter()
+                            .map(|(from, to, r)| Hop {
+                                from: name(from),
+                                to: name(to),
+                                sent_us: r.sent_us,
+                                heard_us: r.heard_us,
+                            })
+                            .collect(),
+                        copies: reception.copies,
+                    })
+                    .collect();
+                deliveries.sort_by(|a, b| a.heard_us().cmp(&b.heard_us()).then_with(|| a.node.cmp(&b.node)));
+                let mut forward_delays_us: Vec<u64> = message.forward_delays_us.values().copied().collect();
+                forward_delays_us.sort_unstable();
+                MessagePath {
+                    packet_hash: hash.clone(),
+                    payload_type: message.payload_type.to_string(),
+                    route_type: message.route_type.to_string(),
+                    origin: name(message.origin),
+                    sent_us: message.sent_us,
+                    transmissions: message.transmissions,
+                    deliveries,
+                    forward_delays_us,
+                }
+            })
+            .collect();
+        messages.sort_by(|a, b| a.sent_us.cmp(&b.sent_us).then_with(|| a.packet_hash.cmp(&b.packet_hash)));
+        MessagePathReport::new(messages)
+    }
+}
+
+/// Hash identifying a message, if the packet decodes.
+fn packet_hash(packet: &LoraPacket) -> Option<String> {
+    packet.decoded().map(|p| p.payload_hash_label().as_label())
+}
+
+/// One hop of a message's path.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hop {
+    /// Node that transmitted the copy.
+    pub from: String,
+    /// Node that received it.
+    pub to: String,
+    /// When the transmission started, in microseconds.
+    pub sent_us: u64,
+    /// When the reception finished, in microseconds.
+    pub heard_us: u64,
+}
+
+/// How a message first reached a node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Delivery {
+    /// Receiving node.
+    pub node: String,
+    /// Hops from the origin to the node, in order.
+    pub hops: Vec<Hop>,
+    /// Intact copies the node heard, the first included.
+    pub copies: u32,
+}
+
+impl Delivery {
+    /// Node names from the origin to the receiver.
+    pub fn path(&self) -> Vec<&str> {
+        self.hops
+            .first()
+            .map(|hop| hop.from.as_str())
+            .into_iter()
+            .chain(self.hops.iter().map(|hop| hop.to.as_str()))
+            .collect()
+    }
+
+    /// When the node first heard the message, in microseconds.
+    pub fn heard_us(&self) -> u64 {
+        self.hops.last().map_or(0, |hop| hop.heard_us)
+    }
+}
+
+/// Where one message went.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MessagePath {
+    /// Payload hash, as in the trace and the packet database.
+    pub packet_hash: String,
+    /// Payload type label.
+    pub payload_type: String,
+    /// Route type label.
+    pub route_type: String,
+    /// Node that first transmitted it.
+    pub origin: String,
+    /// Time of the first transmission in microseconds.
+    pub sent_us: u64,
+    /// Transmissions by any node, retries and retransmissions included.
+    pub transmissions: u32,
+    /// Every node that heard it, earliest first.
+    pub deliveries: Vec<Delivery>,
+    /// Time each forwarder held it before retransmitting, in microseconds.
+    pub forward_delays_us: Vec<u64>,
+}
+
+impl MessagePath {
+    /// Copies heard beyond the first at each node.
+    pub fn duplicates(&self) -> u64 {
+        self.deliveries.iter().map(|d| u64::from(d.copies.saturating_sub(1))).sum()
+    }
+}
+
+/// Result of [`PathTracker::report`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MessagePathReport {
+    /// Messages heard by at least one other node.
+    pub delivered_messages: usize,
+    /// (message, receiver) pairs.
+    pub deliveries: usize,
+    /// Mean hops per delivery.
+    pub mean_hops: Option<f64>,
+    /// Longest path in hops.
+    pub max_hops: usize,
+    /// Mean time from the first transmission to a receiver hearing it, in
+    /// milliseconds.
+    pub mean_latency_ms: Option<f64>,
+    /// Mean time a forwarder held a message, in milliseconds.
+    pub mean_forward_delay_ms: Option<f64>,
+    /// Copies heard beyond the first, over all nodes.
+    pub duplicates: u64,
+    /// Every message, in order of first transmission.
+    pub messages: Vec<MessagePath>,
+}
+
+impl MessagePathReport {
+    fn new(messages: Vec<MessagePath>) -> Self {
+        let deliveries = || messages.iter().flat_map(|m| m.deliveries.iter().map(move |d| (m, d)));
+        let latencies_ms = deliveries().map(|(m, d)| d.heard_us().saturating_sub(m.sent_us) as f64 / 1000.0);
+        let forward_delays_ms = messages.iter().flat_map(|m| m.forward_delays_us.iter().map(|&d| d as f64 / 1000.0));
+        Self {
+            delivered_messages: messages.iter().filter(|m| !m.deliveries.is_empty()).count(),
+            deliveries: deliveries().count(),
+            mean_hops: mean(deliveries().map(|(_, d)| d.hops.len() as f64)),
+            max_hops: deliveries().map(|(_, d)| d.hops.len()).max().unwrap_or(0),
+            mean_latency_ms: mean(latencies_ms),
+            mean_forward_delay_ms: mean(forward_delays_ms),
+            duplicates: messages.iter().map(MessagePath::duplicates).sum(),
+            messages,
+        }
+    }
+
+    /// The delivery over the most hops, with its message.
+    pub fn longest(&self) -> Option<(&MessagePath, &Delivery)> {
+        self.messages
+            .iter()
+            .flat_map(|m| m.deliveries.iter().map(move |d| (m, d)))
+            .max_by_key(|(_, d)| d.hops.len())
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+fn fmt_ms(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.1} ms", v))
+}
+
+impl fmt::Display for MessagePathReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Message paths: {} of {} messages delivered, {} deliveries",
+            self.delivered_messages,
+            self.messages.len(),
+            self.deliveries
+        )?;
+        writeln!(
+            f,
+            "  hops: mean {}, max {}",
+            self.mean_hops.map_or_else(|| "-".to_string(), |h| format!("{:.2}", h)),
+            self.max_hops
+        )?;
+        writeln!(f, "  latency: mean {}", fmt_ms(self.mean_latency_ms))?;
+        writeln!(f, "  forward delay: mean {}", fmt_ms(self.mean_forward_delay_ms))?;
+        writeln!(f, "  duplicate copies: {}", self.duplicates)?;
+        if let Some((message, delivery)) = self.longest() {
+            writeln!(
+                f,
+                "  longest: {} ({} {}, {} hops)",
+                delivery.path().join(" > "),
+                message.payload_type,
+                message.packet_hash,
+                delivery.hops.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{EntityId, EventId, RadioParams, RadioRxPacketEvent, SimTime, TransmitAirEvent};
+
+    /// A flood text message with the given path bytes.
+    fn packet(path: &[u8]) -> LoraPacket {
+        let mut bytes = vec![0x09, path.len() as u8];
+        bytes.extend_from_slice(path);
+        bytes.extend_from_slice(&[0xaa, 0xbb, 0xcc, 0xdd]);
+        LoraPacket::new(bytes)
+    }
+
+    fn tx(radio: u64, time_ms: u64, packet: LoraPacket) -> Event {
+        Event {
+            id: EventId(0),
+            time: SimTime::from_millis(time_ms),
+            source: EntityId::new(radio),
+            targets: Vec::new(),
+            payload: EventPayload::TransmitAir(TransmitAirEvent {
+                radio_id: EntityId::new(radio),
+                packet,
+                params: RadioParams {
+                    frequency_hz: 910_525_000,
+                    bandwidth_hz: 62_500,
+                    spreading_factor: 7,
+                    coding_rate: 5,
+                    tx_power_dbm: 20,
+                },
+                sync_word: 0x12,
+                end_time: SimTime::from_millis(time_ms + 100),
+            }),
+        }
+    }
+
+    fn rx(receiver: u64, from: u64, sent_ms: u64, packet: LoraPacket, was_collided: bool) -> Event {
+        Event {
+            id: EventId(0),
+            time: SimTime::from_millis(sent_ms + 100),
+            source: EntityId::new(receiver),
+            targets: vec![EntityId::new(receiver + 100)],
+            payload: EventPayload::RadioRxPacket(RadioRxPacketEvent {
+                packet,
+                source_radio_id: EntityId::new(from),
+                snr_db: 5.0,
+                rssi_dbm: -100.0,
+                was_collided,
+                was_weak_signal: false,
+                was_corrupted: false,
+                start_time: SimTime::from_millis(sent_ms),
+                end_time: SimTime::from_millis(sent_ms + 100),
+            }),
+        }
+    }
+
+    fn tracker() -> PathTracker {
+        PathTracker {
+            nodes: (1..=4).map(|radio| (radio, (format!("N{}", radio), "Repeater".to_string()))).collect(),
+            messages: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_reconstructs_path() {
+        let mut tracker = tracker();
+        // N1 floods; N2 hears it and repeats; N3 hears N2's copy and repeats;
+        // N4 hears N3, then N2's copy arrives late at N4 and N1 hears its echo
+        tracker.observe(&tx(1, 0, packet(&[])));
+        tracker.observe(&rx(2, 1, 0, packet(&[]), false));
+        tracker.observe(&tx(2, 150, packet(&[0x02])));
+        tracker.observe(&rx(3, 2, 150, packet(&[0x02]), false));
+        tracker.observe(&rx(1, 2, 150, packet(&[0x02]), false));
+        tracker.observe(&tx(3, 400, packet(&[0x02, 0x03])));
+        tracker.observe(&rx(4, 3, 400, packet(&[0x02, 0x03]), false));
+        tracker.observe(&rx(4, 2, 450, packet(&[0x02]), false));
+        // Collided copies are not heard
+        tracker.observe(&rx(2, 3, 400, packet(&[0x02, 0x03]), true));
+
+        let report = tracker.report();
+        assert_eq!(report.messages.len(), 1);
+        let message = &report.messages[0];
+        assert_eq!((message.origin.as_str(), message.transmissions), ("N1", 3));
+        assert_eq!(message.deliveries.iter().map(|d| d.node.as_str()).collect::<Vec<_>>(), ["N2", "N3", "N4"]);
+
+        let n4 = &message.deliveries[2];
+        assert_eq!(n4.path(), ["N1", "N2", "N3", "N4"]);
+        assert_eq!(n4.copies, 2);
+        assert_eq!(
+            n4.hops.iter().map(|h| (h.sent_us / 1000, h.heard_us / 1000)).collect::<Vec<_>>(),
+            [(0, 100), (150, 250), (400, 500)]
+        );
+        assert_eq!(message.forward_delays_us, [50_000, 150_000]);
+        assert_eq!(message.duplicates(), 1);
+
+        assert_eq!(report.deliveries, 3);
+        assert_eq!(report.max_hops, 3);
+        assert!((report.mean_hops.unwrap() - 2.0).abs() < 1e-9);
+        assert!((report.mean_latency_ms.unwrap() - 850.0 / 3.0).abs() < 1e-9);
+        assert!(report.to_string().contains("longest: N1 > N2 > N3 > N4"));
+    }
+
+    #[test]
+    fn test_separate_messages_and_unknown_nodes() {
+        let mut tracker = tracker();
+        tracker.observe(&tx(1, 0, packet(&[])));
+        // A different payload is a different message
+        let mut other = packet(&[]).payload;
+        *other.last_mut().unwrap() = 0xee;
+        tracker.observe(&tx(2, 10, LoraPacket::new(other)));
+        // Receptions at radios outside the model are ignored
+        tracker.observe(&rx(9, 1, 0, packet(&[]), false));
+        // Undecodable packets are ignored
+        tracker.observe(&tx(3, 20, LoraPacket::from_bytes(vec![0xff])));
+
+        let report = tracker.report();
+        assert_eq!(report.messages.len(), 2);
+        assert_eq!(report.delivered_messages, 0);
+        assert_eq!((report.mean_hops, report.mean_latency_ms), (None, None));
+        assert!(report.longest().is_none());
+    }
+}
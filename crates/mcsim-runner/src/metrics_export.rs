@@ -20,7 +20,7 @@ use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, Share
 use parking_lot::RwLock;
 use std::collections::BTreeMap;
 use std::io::Write;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
 // ============================================================================
@@ -595,8 +595,15 @@ impl HistogramState {
 }
 
 /// Shared state for the in-memory recorder.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct RecorderState {
+    /// Whether recording is enabled at all.
+    enabled: AtomicBool,
+    /// Whether `disabled_categories` is non-empty, so registration can skip
+    /// the lock in the common case.
+    any_disabled: AtomicBool,
+    /// Metric name prefixes whose recording is disabled.
+    disabled_categories: RwLock<Vec<String>>,
     /// Counters keyed by full key string (metric name + labels).
     counters: RwLock<BTreeMap<String, Arc<CounterState>>>,
     /// Gauges keyed by full key string (metric name + labels).
@@ -830,7 +837,45 @@ fn key_metadata(key: &Key) -> KeyMetadata {
     }
 }
 
+/// Whether `name` equals `category` or lies under it as whole
+/// `.`-separated segments (`mcsim.dm` covers `mcsim.dm.sent` but not
+/// `mcsim.dmx`).
+fn in_category(name: &str, category: &str) -> bool {
+    name.strip_prefix(category)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+impl Default for RecorderState {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            any_disabled: AtomicBool::new(false),
+            disabled_categories: RwLock::new(Vec::new()),
+            counters: RwLock::default(),
+            gauges: RwLock::default(),
+            histograms: RwLock::default(),
+            key_metadata: RwLock::default(),
+            label_specs: RwLock::default(),
+        }
+    }
+}
+
 impl RecorderState {
+    /// Whether values for the named metric should currently be recorded.
+    fn is_recording(&self, name: &str) -> bool {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return false;
+        }
+        if !self.any_disabled.load(Ordering::Relaxed) {
+            return true;
+        }
+        !self
+            .disabled_categories
+            .read()
+            .iter()
+            .any(|category| in_category(name, category))
+    }
+
     /// Clear all recorded metrics.
     ///
     /// This resets all counters to 0, all gauges to 0, and clears all histogram samples.
@@ -924,6 +969,26 @@ impl RecorderState {
             .clone()
     }
 
+    /// Current value of a counter or gauge summed over its label sets,
+    /// optionally only those of one node.
+    fn current_value(&self, name: &str, node: Option<&str>) -> Option<f64> {
+        let metadata = self.key_metadata.read();
+        let matches = |key: &String| {
+            metadata
+                .get(key)
+                .is_some_and(|meta| meta.name == name && node.is_none_or(|n| meta.node() == Some(n)))
+        };
+
+        let mut value = None;
+        for (_, counter) in self.counters.read().iter().filter(|(key, _)| matches(key)) {
+            *value.get_or_insert(0.0) += counter.get() as f64;
+        }
+        for (_, gauge) in self.gauges.read().iter().filter(|(key, _)| matches(key)) {
+            *value.get_or_insert(0.0) += gauge.get();
+        }
+        value
+    }
+
     fn snapshot(&self) -> MetricsSnapshot {
         let timestamp = chrono::Utc::now().to_rfc3339();
         let metadata = self.key_metadata.read();
@@ -1438,6 +1503,16 @@ impl InMemoryRecorder {
         self.state.snapshot_with_specs(specs)
     }
 
+    /// Current value of a counter or gauge, summed over its label sets.
+    ///
+    /// With `node`, only label sets for that node are included (the `node`
+    /// label must be kept by the label filter). Returns `None` if nothing has
+    /// been recorded for the metric yet. Much cheaper than a snapshot, so it
+    /// can be polled while the simulation runs.
+    pub fn current_value(&self, name: &str, node: Option<&str>) -> Option<f64> {
+        self.state.current_value(name, node)
+    }
+
     /// Clear all recorded metrics.
     ///
     /// This is used for metrics warmup - clearing metrics after the warmup period
@@ -1445,6 +1520,46 @@ impl InMemoryRecorder {
     pub fn clear(&self) {
         self.state.clear();
     }
+
+    /// Enable or disable recording of all metrics.
+    ///
+    /// While disabled, metric updates are discarded without touching the
+    /// stored values, so a long warm-up can run without recording overhead.
+    /// Values recorded before disabling are kept.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.state.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether recording is globally enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.state.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable recording for a category of metrics.
+    ///
+    /// A category is a metric name prefix of whole `.`-separated segments,
+    /// such as `mcsim.radio` or `mcsim.timing`. Disabling a category has no
+    /// effect while recording is globally disabled.
+    pub fn set_category_enabled(&self, category: &str, enabled: bool) {
+        let mut disabled = self.state.disabled_categories.write();
+        disabled.retain(|c| c != category);
+        if !enabled {
+            disabled.push(category.to_string());
+        }
+        self.state
+            .any_disabled
+            .store(!disabled.is_empty(), Ordering::Relaxed);
+    }
+
+    /// Metric name prefixes that are currently disabled.
+    pub fn disabled_categories(&self) -> Vec<String> {
+        self.state.disabled_categories.read().clone()
+    }
+
+    /// Whether updates to the named metric are currently recorded.
+    pub fn is_recording(&self, name: &str) -> bool {
+        self.state.is_recording(name)
+    }
 }
 
 impl Default for InMemoryRecorder {
@@ -1528,16 +1643,25 @@ impl Recorder for InMemoryRecorder {
     }
 
     fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        if !self.state.is_recording(key.name()) {
+            return Counter::noop();
+        }
         let state = self.state.get_or_create_counter(key);
         Counter::from_arc(Arc::new(InMemoryCounter { state }))
     }
 
     fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        if !self.state.is_recording(key.name()) {
+            return Gauge::noop();
+        }
         let state = self.state.get_or_create_gauge(key);
         Gauge::from_arc(Arc::new(InMemoryGauge { state }))
     }
 
     fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        if !self.state.is_recording(key.name()) {
+            return Histogram::noop();
+        }
         let state = self.state.get_or_create_histogram(key);
         Histogram::from_arc(Arc::new(InMemoryHistogram { state }))
     }
@@ -1692,4 +1816,46 @@ mod tests {
             Some(&5)
         );
     }
+
+    #[test]
+    fn test_current_value() {
+        use metrics::Label;
+
+        let recorder = InMemoryRecorder::new();
+        for (node, count) in [("Node1", 4), ("Node2", 6)] {
+            let key = Key::from_parts("test.packets", vec![Label::new("node", node)]);
+            recorder.state.get_or_create_counter(&key).increment(count);
+        }
+
+        assert_eq!(recorder.current_value("test.packets", None), Some(10.0));
+        assert_eq!(recorder.current_value("test.packets", Some("Node2")), Some(6.0));
+        assert_eq!(recorder.current_value("test.packets", Some("Node3")), None);
+        assert_eq!(recorder.current_value("test.other", None), None);
+    }
+
+    #[test]
+    fn test_runtime_toggling() {
+        let recorder = InMemoryRecorder::new();
+        let metadata = Metadata::new(module_path!(), metrics::Level::INFO, None);
+        let radio_key = Key::from_static_name("mcsim.radio.tx_packets");
+        let dm_key = Key::from_static_name("mcsim.dm.sent");
+
+        recorder.set_enabled(false);
+        recorder.register_counter(&radio_key, &metadata).increment(5);
+        recorder.set_enabled(true);
+        recorder.register_counter(&radio_key, &metadata).increment(1);
+
+        recorder.set_category_enabled("mcsim.dm", false);
+        assert!(!recorder.is_recording("mcsim.dm.sent"));
+        assert!(!recorder.is_recording("mcsim.dm"));
+        assert!(recorder.is_recording("mcsim.dmx.sent"));
+        recorder.register_counter(&dm_key, &metadata).increment(3);
+        recorder.set_category_enabled("mcsim.dm", true);
+        assert!(recorder.disabled_categories().is_empty());
+        recorder.register_counter(&dm_key, &metadata).increment(2);
+
+        let snapshot = recorder.snapshot();
+        assert_eq!(snapshot.counters.get("mcsim.radio.tx_packets"), Some(&1));
+        assert_eq!(snapshot.counters.get("mcsim.dm.sent"), Some(&2));
+    }
 }
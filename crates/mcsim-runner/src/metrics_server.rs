@@ -0,0 +1,122 @@
+//! HTTP endpoint serving live metrics in Prometheus exposition format.
+//!
+//! With `--metrics-listen ADDR`, the runner serves the in-memory recorder's
+//! current values at `http://ADDR/metrics` while the simulation runs, so a
+//! long run can be scraped by Prometheus and watched in Grafana:
+//!
+//! ```text
+//! mcsim run model.yaml --duration 24h --metrics-listen 0.0.0.0:9090
+//! curl http://localhost:9090/metrics
+//! ```
+//!
+//! The output is the same as `--metrics-output prometheus`, taken at the
+//! moment of the scrape. Values are reset when the metrics warmup ends. The
+//! server runs on its own thread and handles one request at a time.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::metrics_export::{export_prometheus, InMemoryRecorder};
+
+/// Content type of the Prometheus text format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// How long a client may take to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A running metrics endpoint.
+pub struct MetricsServer {
+    local_addr: SocketAddr,
+}
+
+impl MetricsServer {
+    /// Bind `addr` and serve `recorder`'s metrics from a background thread
+    /// for the rest of the process.
+    pub fn start(addr: &str, recorder: Arc<InMemoryRecorder>) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        std::thread::Builder::new()
+            .name("metrics-server".to_string())
+            .spawn(move || {
+                // A failed request only affects that client
+                for stream in listener.incoming().flatten() {
+                    let _ = handle_connection(stream, &recorder);
+                }
+            })?;
+        Ok(Self { local_addr })
+    }
+
+    /// Address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+fn handle_connection(stream: TcpStream, recorder: &InMemoryRecorder) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip headers up to the blank line
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split('?').next().unwrap_or("");
+    match (method, path) {
+        ("GET", "/metrics") => {
+            let mut body = Vec::new();
+            export_prometheus(&recorder.snapshot(), &mut body)?;
+            respond(&stream, "200 OK", CONTENT_TYPE, &body)
+        }
+        ("GET", _) => respond(&stream, "404 Not Found", "text/plain", b"Metrics are served at /metrics\n"),
+        _ => respond(&stream, "405 Method Not Allowed", "text/plain", b"Only GET is supported\n"),
+    }
+}
+
+fn respond(mut stream: &TcpStream, status: &str, content_type: &str, body: &[u8]) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serves_prometheus_metrics() {
+        let recorder = Arc::new(InMemoryRecorder::new());
+        let server = MetricsServer::start("127.0.0.1:0", recorder.clone()).unwrap();
+        metrics::with_local_recorder(recorder.as_ref(), || {
+            metrics::counter!("test.scraped").increment(3);
+        });
+
+        let response = get(server.local_addr(), "/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.contains("# TYPE test_scraped counter"));
+        assert!(response.contains("test_scraped 3"));
+
+        assert!(get(server.local_addr(), "/").starts_with("HTTP/1.1 404"));
+    }
+}
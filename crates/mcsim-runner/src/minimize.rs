@@ -0,0 +1,598 @@
+//! Shrinking a failing scenario to a minimal reproduction.
+//!
+//! `mcsim minimize` takes a scenario whose run fails, either with failed
+//! assertions or with an error (such as a firmware error), and searches for
+//! a smaller scenario that still fails the same way under the same seed:
+//!
+//! 1. The shortest duration that still fails, by bisection (runs are
+//!    deterministic, so a failure at time `t` shows up in every run of at
+//!    least `t`).
+//! 2. Fewer nodes. Removing a node also removes its edges and every entry
+//!    of the other sections that names it (traffic, assertions, faults,
+//!    lifecycle events, ...).
+//! 3. Fewer `traffic` rules.
+//! 4. Fewer agents: a node's direct message and channel agents are
+//!    disabled.
+//!
+//! Removals are tried in halving chunks, so large scenarios shrink in few
+//! runs. The passes repeat until a round removes nothing or the run budget
+//! is spent. Candidates the model loader rejects (an outage left with no
+//! node in its area, say) are skipped without running them.
+//!
+//! A run reproduces the failure when its [`FailureSignature`] matches the
+//! original's: the same set of failed assertions, or the same error message
+//! with numbers masked, since entity IDs and times shift as the scenario
+//! shrinks.
+//!
+//! A `delivered` assertion also fails when its DM was never sent, so a
+//! shrunk scenario could match the signature only because it stopped
+//! sending. Candidates must therefore keep sending the DMs of the failed
+//! `delivered` assertions, and run long enough to judge them: a traffic
+//! rule of the sender that DMs the receiver, or a direct message agent that
+//! targets it, must start at least `within_s` before the end of the run. Triggered rules
+//! count as sending, since when they fire isn't known up front. The shrunk model files are written with the same names as the
+//! originals, ready to attach to a bug report.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use mcsim_model::{AssertionCheck, TrafficMessage};
+
+use crate::assertions::{AssertionResult, EXIT_ASSERTION_FAILED};
+use crate::RunnerError;
+
+/// How a run failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureSignature {
+    /// Names of the failed assertions.
+    Assertions(BTreeSet<String>),
+    /// Error message, with numbers masked.
+    Error(String),
+}
+
+impl FailureSignature {
+    /// Signature of a run whose assertions were evaluated, or None if they
+    /// all passed.
+    pub fn from_assertions(results: &[AssertionResult]) -> Option<Self> {
+        let failed: BTreeSet<String> = results.iter().filter(|r| !r.passed).map(|r| r.name.clone()).collect();
+        (!failed.is_empty()).then_some(FailureSignature::Assertions(failed))
+    }
+
+    /// Signature of a run that stopped with an error.
+    pub fn from_error(message: &str) -> Self {
+        let mut masked = String::with_capacity(message.len());
+        let mut in_number = false;
+        for c in message.trim().chars() {
+            if c.is_ascii_digit() {
+                if !in_number {
+                    masked.push('#');
+                }
+                in_number = true;
+            } else {
+                masked.push(c);
+                in_number = false;
+            }
+        }
+        FailureSignature::Error(masked)
+    }
+
+    /// Signature of an `mcsim run` process from its exit status and output,
+    /// or None if it succeeded.
+    pub fn from_run_output(output: &std::process::Output) -> Option<Self> {
+        if output.status.success() {
+            return None;
+        }
+        if output.status.code() == Some(EXIT_ASSERTION_FAILED) {
+            let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_default();
+            let failed: BTreeSet<String> = stats
+                .get("assertions")
+                .and_then(|a| a.as_array())
+                .into_iter()
+                .flatten()
+                .filter(|a| a.get("passed").and_then(|p| p.as_bool()) == Some(false))
+                .filter_map(|a| Some(a.get("name")?.as_str()?.to_string()))
+                .collect();
+            return Some(FailureSignature::Assertions(failed));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty())
+            .map_or_else(|| output.status.to_string(), str::to_string);
+        Some(FailureSignature::from_error(&message))
+    }
+}
+
+impl fmt::Display for FailureSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureSignature::Assertions(names) => {
+                write!(f, "failed assertions: {}", names.iter().cloned().collect::<Vec<_>>().join(", "))
+            }
+            FailureSignature::Error(message) => write!(f, "error: {}", message),
+        }
+    }
+}
+
+/// A DM that a failed `delivered` assertion is about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequiredSend {
+    /// Sending node.
+    pub from: String,
+    /// Receiving node.
+    pub to: String,
+    /// Time the DM is given to arrive, in seconds.
+    pub within_s: f64,
+}
+
+/// A model file of a scenario.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelFile {
+    /// File name.
+    pub name: String,
+    /// Parsed YAML.
+    pub yaml: Value,
+}
+
+/// A scenario being shrunk: its model files and run duration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    /// Model files, merged in order.
+    pub files: Vec<ModelFile>,
+    /// Run duration in seconds.
+    pub duration_s: f64,
+}
+
+impl Scenario {
+    /// Load the model files of a scenario.
+    pub fn load(paths: &[PathBuf], duration_s: f64) -> Result<Self, RunnerError> {
+        let mut files = Vec::new();
+        for path in paths {
+            let text = std::fs::read_to_string(path)?;
+            let yaml = serde_yaml::from_str(&text)
+                .map_err(|e| RunnerError::ConfigError(format!("Failed to parse {}: {}", path.display(), e)))?;
+            let name = path.file_name().map_or_else(|| "model.yaml".into(), |n| n.to_string_lossy().into_owned());
+            files.push(ModelFile { name, yaml });
+        }
+        // Files from different directories may share a name
+        for i in 1..files.len() {
+            if files[..i].iter().any(|f| f.name == files[i].name) {
+                files[i].name = format!("{}-{}", i, files[i].name);
+            }
+        }
+        let scenario = Scenario { files, duration_s };
+        scenario.model()?;
+        Ok(scenario)
+    }
+
+    /// Load the scenario's model.
+    pub fn model(&self) -> Result<mcsim_model::Model, RunnerError> {
+        let texts = self
+            .files
+            .iter()
+            .map(|f| serde_yaml::to_string(&f.yaml))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| RunnerError::ConfigError(e.to_string()))?;
+        let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+        Ok(mcsim_model::load_models_from_str(&texts)?)
+    }
+
+    /// Number of nodes and traffic rules, and the duration.
+    pub fn size(&self) -> ScenarioSize {
+        let model = self.model().ok();
+        ScenarioSize {
+            nodes: model.as_ref().map_or(0, |m| m.nodes().len()),
+            traffic_rules: model.as_ref().map_or(0, |m| m.traffic().len()),
+            duration_s: self.duration_s,
+        }
+    }
+
+    /// Write the model files into `dir`, returning their paths in order.
+    pub fn write(&self, dir: &Path) -> Result<Vec<PathBuf>, RunnerError> {
+        std::fs::create_dir_all(dir)?;
+        let mut paths = Vec::new();
+        for file in &self.files {
+            let path = dir.join(&file.name);
+            let text = serde_yaml::to_string(&file.yaml).map_err(|e| RunnerError::ConfigError(e.to_string()))?;
+            std::fs::write(&path, text)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// The DMs of the failed `delivered` assertions of `signature` that
+    /// the scenario sends.
+    pub fn required_sends(&self, signature: &FailureSignature) -> Vec<RequiredSend> {
+        let FailureSignature::Assertions(failed) = signature else {
+            return Vec::new();
+        };
+        let Ok(model) = self.model() else {
+            return Vec::new();
+        };
+        model
+            .assertions()
+            .iter()
+            .filter(|a| failed.contains(&a.name))
+            .filter_map(|a| match &a.check {
+                AssertionCheck::Delivered { from, to, within_s } => {
+                    Some(RequiredSend { from: from.clone(), to: to.clone(), within_s: *within_s })
+                }
+                _ => None,
+            })
+            .filter(|send| self.sends(&model, send))
+            .collect()
+    }
+
+    /// Whether the scenario sends `send` early enough to judge its delivery.
+    fn sends(&self, model: &mcsim_model::Model, send: &RequiredSend) -> bool {
+        use mcsim_model::properties::{AGENT_DIRECT_ENABLED, AGENT_DIRECT_STARTUP_S, AGENT_DIRECT_TARGETS};
+        let judged = |at_s: f64| at_s + send.within_s <= self.duration_s;
+        let by_traffic = model.traffic().iter().any(|rule| {
+            rule.node == send.from
+                && rule.message == TrafficMessage::DirectMessage(send.to.clone())
+                && (rule.trigger.is_some() || judged(rule.at_s))
+        });
+        let by_agent = model.nodes().get(&send.from).is_some_and(|node| {
+            let props = node.properties();
+            props.get(&AGENT_DIRECT_ENABLED)
+                && judged(props.get(&AGENT_DIRECT_STARTUP_S))
+                && props.get(&AGENT_DIRECT_TARGETS).is_some_and(|targets| targets.contains(&send.to))
+        });
+        by_traffic || by_agent
+    }
+
+    fn with_duration(&self, duration_s: f64) -> Scenario {
+        Scenario { files: self.files.clone(), duration_s }
+    }
+
+    /// Without the nodes and everything that names them.
+    fn without_nodes(&self, names: &[String]) -> Scenario {
+        let mut scenario = self.clone();
+        for file in &mut scenario.files {
+            let Value::Mapping(sections) = &mut file.yaml else {
+                continue;
+            };
+            for (key, section) in sections.iter_mut() {
+                let Value::Sequence(entries) = section else {
+                    continue;
+                };
+                if key.as_str() == Some("nodes") {
+                    entries.retain(|e| !names.iter().any(|n| e.get("name").and_then(Value::as_str) == Some(n)));
+                } else {
+                    entries.retain(|e| !names.iter().any(|n| mentions(e, n)));
+                }
+            }
+        }
+        scenario
+    }
+
+    /// Without the traffic rules.
+    fn without_traffic(&self, rules: &[Value]) -> Scenario {
+        let mut scenario = self.clone();
+        for file in &mut scenario.files {
+            if let Some(Value::Sequence(entries)) = file.yaml.get_mut("traffic") {
+                entries.retain(|e| !rules.contains(e));
+            }
+        }
+        scenario
+    }
+
+    /// With the direct message and channel agents of the nodes disabled.
+    fn without_agents(&self, names: &[String]) -> Scenario {
+        let mut scenario = self.clone();
+        for name in names {
+            // The last entry of a node overrides the earlier ones and the defaults
+            let entry = scenario.files.iter_mut().rev().find_map(|file| {
+                let Some(Value::Sequence(nodes)) = file.yaml.get_mut("nodes") else {
+                    return None;
+                };
+                nodes.iter_mut().find(|n| n.get("name").and_then(Value::as_str) == Some(name.as_str()))
+            });
+            let Some(Value::Mapping(node)) = entry else {
+                continue;
+            };
+            let agent = submapping(node, "agent");
+            for kind in ["direct", "channel"] {
+                submapping(agent, kind).insert("enabled".into(), false.into());
+            }
+        }
+        scenario
+    }
+
+    /// Traffic rules of all files.
+    fn traffic_rules(&self) -> Vec<Value> {
+        self.files
+            .iter()
+            .filter_map(|f| f.yaml.get("traffic").and_then(Value::as_sequence))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Nodes with a direct message or channel agent enabled.
+    fn nodes_with_agents(&self) -> Vec<String> {
+        use mcsim_model::properties::{AGENT_CHANNEL_ENABLED, AGENT_DIRECT_ENABLED};
+        let Ok(model) = self.model() else {
+            return Vec::new();
+        };
+        model
+            .nodes()
+            .values()
+            .filter(|n| n.properties().get(&AGENT_DIRECT_ENABLED) || n.properties().get(&AGENT_CHANNEL_ENABLED))
+            .map(|n| n.name.clone())
+            .collect()
+    }
+}
+
+/// Whether a YAML value contains the string `name` anywhere.
+fn mentions(value: &Value, name: &str) -> bool {
+    match value {
+        Value::String(s) => s == name,
+        Value::Sequence(values) => values.iter().any(|v| mentions(v, name)),
+        Value::Mapping(mapping) => mapping.values().any(|v| mentions(v, name)),
+        Value::Tagged(tagged) => mentions(&tagged.value, name),
+        _ => false,
+    }
+}
+
+/// The mapping under `key`, created if it's missing or not a mapping.
+fn submapping<'a>(mapping: &'a mut Mapping, key: &str) -> &'a mut Mapping {
+    let value = mapping.entry(key.into()).or_insert_with(|| Value::Mapping(Mapping::new()));
+    if !value.is_mapping() {
+        *value = Value::Mapping(Mapping::new());
+    }
+    value.as_mapping_mut().expect("just made a mapping")
+}
+
+/// Size of a scenario.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ScenarioSize {
+    /// Number of nodes.
+    pub nodes: usize,
+    /// Number of traffic rules.
+    pub traffic_rules: usize,
+    /// Run duration in seconds.
+    pub duration_s: f64,
+}
+
+impl fmt::Display for ScenarioSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} node(s), {} traffic rule(s), {:.0}s",
+            self.nodes, self.traffic_rules, self.duration_s
+        )
+    }
+}
+
+/// Result of shrinking a scenario.
+#[derive(Debug, Clone)]
+pub struct Minimized {
+    /// The smallest scenario found that still fails.
+    pub scenario: Scenario,
+    /// Runs made while shrinking.
+    pub runs: usize,
+    /// Whether shrinking stopped because the run budget was spent.
+    pub budget_exhausted: bool,
+}
+
+/// Shrink `scenario`, which is known to fail with `signature`. `reproduces`
+/// runs a candidate and tells whether it fails the same way; it is called at
+/// most `max_runs` times, and only for candidates that still send the
+/// [`RequiredSend`]s.
+pub fn minimize<F>(scenario: Scenario, signature: &FailureSignature, max_runs: usize, reproduces: F) -> Minimized
+where
+    F: FnMut(&Scenario) -> bool,
+{
+    let mut shrinker = Shrinker {
+        required: scenario.required_sends(signature),
+        reproduces,
+        runs: 0,
+        max_runs,
+    };
+    let mut scenario = scenario;
+    loop {
+        let before = scenario.clone();
+        scenario = shrinker.shrink_duration(scenario);
+        let nodes = scenario.model().map(|m| m.nodes().keys().cloned().collect()).unwrap_or_default();
+        scenario = shrinker.remove_chunks(scenario, nodes, Scenario::without_nodes);
+        let rules = scenario.traffic_rules();
+        scenario = shrinker.remove_chunks(scenario, rules, Scenario::without_traffic);
+        let agents = scenario.nodes_with_agents();
+        scenario = shrinker.remove_chunks(scenario, agents, Scenario::without_agents);
+        if scenario == before || shrinker.exhausted() {
+            break;
+        }
+    }
+    Minimized {
+        budget_exhausted: shrinker.exhausted(),
+        runs: shrinker.runs,
+        scenario,
+    }
+}
+
+struct Shrinker<F> {
+    required: Vec<RequiredSend>,
+    reproduces: F,
+    runs: usize,
+    max_runs: usize,
+}
+
+impl<F: FnMut(&Scenario) -> bool> Shrinker<F> {
+    fn exhausted(&self) -> bool {
+        self.runs >= self.max_runs
+    }
+
+    /// Whether a candidate is a valid scenario that still sends the
+    /// required DMs and still fails.
+    fn test(&mut self, candidate: &Scenario) -> bool {
+        if self.exhausted() {
+            return false;
+        }
+        let Ok(model) = candidate.model() else {
+            return false;
+        };
+        if !self.required.iter().all(|send| candidate.sends(&model, send)) {
+            return false;
+        }
+        self.runs += 1;
+        (self.reproduces)(candidate)
+    }
+
+    /// Bisect for the shortest failing duration, to the second.
+    fn shrink_duration(&mut self, scenario: Scenario) -> Scenario {
+        let (mut lo, mut hi) = (0.0, scenario.duration_s);
+        while hi - lo > 1.0 {
+            let mid = ((lo + hi) / 2.0_f64).ceil();
+            if mid >= hi {
+                break;
+            }
+            if self.test(&scenario.with_duration(mid)) {
+                hi = mid;
+            } else if self.exhausted() {
+                break;
+            } else {
+                lo = mid;
+            }
+        }
+        scenario.with_duration(hi)
+    }
+
+    /// Remove as many of `items` as possible, trying chunks of halving size.
+    fn remove_chunks<T: Clone>(
+        &mut self,
+        mut scenario: Scenario,
+        mut items: Vec<T>,
+        remove: impl Fn(&Scenario, &[T]) -> Scenario,
+    ) -> Scenario {
+        let mut chunk = items.len().div_ceil(2).max(1);
+        while !items.is_empty() && !self.exhausted() {
+            let mut start = 0;
+            while start < items.len() {
+                let end = (start + chunk).min(items.len());
+                let candidate = remove(&scenario, &items[start..end]);
+                if self.test(&candidate) {
+                    scenario = candidate;
+                    items.drain(start..end);
+                } else {
+                    start = end;
+                }
+            }
+            if chunk == 1 {
+                break;
+            }
+            chunk = chunk.div_ceil(2);
+        }
+        scenario
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario() -> Scenario {
+        let topology = "\
+nodes:
+  - name: A
+  - name: B
+  - name: C
+  - name: D
+edges:
+  - { from: A, to: B }
+  - { from: C, to: D }
+";
+        let overlay = "\
+nodes:
+  - name: A
+    agent: { direct: { enabled: true } }
+traffic:
+  - { node: A, send_dm: B, at_s: 10 }
+  - { node: C, send_dm: D, at_s: 20 }
+";
+        let files = [("topology.yaml", topology), ("overlay.yaml", overlay)]
+            .into_iter()
+            .map(|(name, text)| ModelFile {
+                name: name.to_string(),
+                yaml: serde_yaml::from_str(text).unwrap(),
+            })
+            .collect();
+        Scenario { files, duration_s: 600.0 }
+    }
+
+    #[test]
+    fn test_failure_signature() {
+        assert_eq!(
+            FailureSignature::from_error("Event handler error in entity EntityId(12) at 3.5s"),
+            FailureSignature::from_error("Event handler error in entity EntityId(7) at 41.25s")
+        );
+        assert_ne!(FailureSignature::from_error("timeout"), FailureSignature::from_error("overflow"));
+
+        let result = |name: &str, passed| AssertionResult {
+            name: name.to_string(),
+            check: String::new(),
+            passed,
+            observed: String::new(),
+        };
+        assert_eq!(FailureSignature::from_assertions(&[result("a", true)]), None);
+        let signature = FailureSignature::from_assertions(&[result("a", false), result("b", true)]).unwrap();
+        assert_eq!(signature.to_string(), "failed assertions: a");
+    }
+
+    #[test]
+    fn test_minimize_keeps_failure() {
+        // Fails when C sends to D and runs past 100s
+        let fails = |s: &Scenario| {
+            s.duration_s >= 100.0 && s.traffic_rules().iter().any(|r| r.get("node").and_then(Value::as_str) == Some("C"))
+        };
+        let signature = FailureSignature::Error("boom".to_string());
+        let minimized = minimize(scenario(), &signature, 100, fails);
+        assert!(!minimized.budget_exhausted);
+        let scenario = minimized.scenario;
+        assert_eq!(scenario.duration_s, 100.0);
+        let model = scenario.model().unwrap();
+        assert_eq!(model.nodes().keys().cloned().collect::<Vec<_>>(), ["C", "D"]);
+        assert_eq!(model.traffic().len(), 1);
+        assert!(scenario.nodes_with_agents().is_empty());
+        assert!(fails(&scenario));
+    }
+
+    #[test]
+    fn test_minimize_respects_budget() {
+        let minimized = minimize(scenario(), &FailureSignature::Error("boom".to_string()), 3, |_| true);
+        assert_eq!(minimized.runs, 3);
+        assert!(minimized.budget_exhausted);
+        assert!(minimized.scenario.model().is_ok());
+    }
+
+    #[test]
+    fn test_minimize_keeps_sending_failed_deliveries() {
+        let mut scenario = scenario();
+        let assertions = "assertions:\n  - { name: a_reaches_b, delivered: { from: A, to: B, within_s: 30 } }\n";
+        scenario.files.push(ModelFile {
+            name: "assertions.yaml".to_string(),
+            yaml: serde_yaml::from_str(assertions).unwrap(),
+        });
+        let signature = FailureSignature::Assertions(["a_reaches_b".to_string()].into());
+        assert_eq!(
+            scenario.required_sends(&signature),
+            [RequiredSend { from: "A".to_string(), to: "B".to_string(), within_s: 30.0 }]
+        );
+
+        // "Never delivered" also holds when A stops sending or the run ends
+        // first; neither may be mistaken for the original failure
+        let minimized = minimize(scenario, &signature, 100, |_| true);
+        let scenario = minimized.scenario;
+        assert_eq!(scenario.duration_s, 40.0);
+        let model = scenario.model().unwrap();
+        assert_eq!(model.nodes().keys().cloned().collect::<Vec<_>>(), ["A", "B"]);
+        assert_eq!(model.traffic().len(), 1);
+        assert_eq!(model.traffic()[0].node, "A");
+    }
+}
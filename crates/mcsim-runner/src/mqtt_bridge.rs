@@ -0,0 +1,537 @@
+//! MQTT bridge for simulated mesh traffic.
+//!
+//! With `--mqtt BROKER`, `mcsim run` publishes the mesh's traffic to an MQTT
+//! broker the way gateways bridge real MeshCore meshes, so existing
+//! dashboards and home-automation setups can follow a simulation:
+//!
+//! ```text
+//! mcsim run model.yaml --pacing realtime --mqtt localhost:1883 --mqtt-commands
+//! mosquitto_sub -t 'mcsim/#' -v
+//! ```
+//!
+//! Topics, under a prefix (`--mqtt-topic`, default `mcsim`), with one JSON
+//! object per message:
+//!
+//! - `<prefix>/<node>/packets`: every packet the node's radio sent (`tx`) or
+//!   received intact (`rx`, with SNR and RSSI), decoded;
+//! - `<prefix>/<node>/adverts`: adverts the node heard, with the advertised
+//!   name, key and position;
+//! - `<prefix>/<node>/messages`: direct and channel messages delivered to the
+//!   node's app (companions);
+//! - `<prefix>/status`: `online` while the run lasts, then `offline`
+//!   (retained, and the connection's last will).
+//!
+//! With `--mqtt-commands` the bridge also takes CLI command lines on
+//! `<prefix>/<node>/command`, sends them to the node's serial port as the
+//! console's `send` does, and publishes the output on
+//! `<prefix>/<node>/response`.
+//!
+//! Node names are used as topic levels with `/`, `+` and `#` replaced by `_`.
+//! An [`MqttFeed`] turns events into messages inside the event loop and
+//! hands them to the [`MqttBridge`] threads, so the simulation never waits on
+//! the broker; messages are dropped (and counted) if the broker falls behind.
+
+use std::collections::HashMap;
+use std::sync::mpsc::{SyncSender, TrySendError};
+
+use mcsim_common::{Event, EventPayload};
+use mcsim_companion_protocol::{Message, ProtocolSession, Response};
+use mcsim_model::NodeInfo;
+use meshcore_packet::{MeshCorePacket, PacketPayload};
+use serde::Serialize;
+
+/// Messages waiting for the broker before new ones are dropped.
+pub const QUEUE_LEN: usize = 4096;
+
+/// Default topic prefix.
+pub const DEFAULT_TOPIC_PREFIX: &str = "mcsim";
+
+/// A message for the broker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttMessage {
+    /// Topic to publish on.
+    pub topic: String,
+    /// Payload (JSON, or the bare status).
+    pub payload: String,
+    /// Whether the broker keeps it for later subscribers.
+    pub retain: bool,
+}
+
+/// Topic level for a node name.
+pub fn topic_segment(name: &str) -> String {
+    name.replace(['/', '+', '#'], "_")
+}
+
+/// A packet sent or received by a node.
+#[derive(Debug, Serialize)]
+struct PacketMessage<'a> {
+    sim_time_us: u64,
+    direction: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snr_db: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rssi_dbm: Option<f64>,
+    route: &'static str,
+    payload_type: &'static str,
+    path_len: usize,
+    hash: String,
+    len: usize,
+    raw: &'a str,
+}
+
+/// An advert heard by a node.
+#[derive(Debug, Serialize)]
+struct AdvertMessage {
+    sim_time_us: u64,
+    public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    longitude: Option<f64>,
+    advert_timestamp: u32,
+    snr_db: f64,
+}
+
+/// A message delivered to a node's app.
+#[derive(Debug, Serialize)]
+struct DeliveredMessage {
+    sim_time_us: u64,
+    kind: &'static str,
+    /// Public key prefix of the sender (direct messages).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<String>,
+    /// Channel index (channel messages).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel: Option<u8>,
+    sent_timestamp: u32,
+    text: String,
+}
+
+/// Turns processed events into MQTT messages for the bridge.
+pub struct MqttFeed {
+    prefix: String,
+    sender: SyncSender<MqttMessage>,
+    /// Topic level of each node, by index.
+    segments: Vec<String>,
+    by_firmware: HashMap<u64, usize>,
+    by_radio: HashMap<u64, usize>,
+    /// Node index and decoder of the firmware's output to the app, by agent
+    /// entity ID (companions only).
+    sessions: HashMap<u64, (usize, ProtocolSession)>,
+    dropped: u64,
+}
+
+impl MqttFeed {
+    /// Feed the traffic of `nodes` to `sender`, on topics under `prefix`.
+    pub fn new(nodes: &[NodeInfo], prefix: &str, sender: SyncSender<MqttMessage>) -> Self {
+        let mut by_firmware = HashMap::new();
+        let mut by_radio = HashMap::new();
+        let mut sessions = HashMap::new();
+        for (index, info) in nodes.iter().enumerate() {
+            by_firmware.insert(info.firmware_entity_id, index);
+            by_radio.insert(info.radio_entity_id, index);
+            if let Some(agent_id) = info.agent_entity_id {
+                sessions.insert(agent_id, (index, ProtocolSession::new()));
+            }
+        }
+        MqttFeed {
+            prefix: prefix.to_string(),
+            sender,
+            segments: nodes.iter().map(|info| topic_segment(&info.name)).collect(),
+            by_firmware,
+            by_radio,
+            sessions,
+            dropped: 0,
+        }
+    }
+
+    /// Messages dropped because the broker fell behind.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    fn publish<T: Serialize>(&mut self, node: usize, kind: &str, message: &T) {
+        let message = MqttMessage {
+            topic: format!("{}/{}/{}", self.prefix, self.segments[node], kind),
+            payload: serde_json::to_string(message).expect("MQTT messages serialize"),
+            retain: false,
+        };
+        match self.sender.try_send(message) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => self.dropped += 1,
+            // The bridge has shut down
+            Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+
+    /// Observe a processed event.
+    pub fn observe(&mut self, event: &Event) {
+        let sim_time_us = event.time.as_micros();
+        match &event.payload {
+            EventPayload::TransmitAir(tx) => {
+                let Some(&node) = self.by_radio.get(&tx.radio_id.0) else {
+                    return;
+                };
+                let Some(packet) = tx.packet.decoded() else {
+                    return;
+                };
+                let raw = hex::encode(&tx.packet.payload);
+                let message = packet_message(sim_time_us, "tx", None, packet, &raw);
+                self.publish(node, "packets", &message);
+            }
+            EventPayload::RadioRxPacket(rx) if !rx.was_collided && !rx.was_corrupted => {
+                let Some(packet) = rx.packet.decoded() else {
+                    return;
+                };
+                let raw = hex::encode(&rx.packet.payload);
+                for target in &event.targets {
+                    let Some(&node) = self.by_firmware.get(&target.0) else {
+                        continue;
+                    };
+                    let message = packet_message(sim_time_us, "rx", Some((rx.snr_db, rx.rssi_dbm)), packet, &raw);
+                    self.publish(node, "packets", &message);
+                    if let PacketPayload::Advert(advert) = &packet.payload {
+                        let message = AdvertMessage {
+                            sim_time_us,
+                            public_key: hex::encode(advert.public_key),
+                            name: advert.name.clone(),
+                            latitude: advert.latitude_f64(),
+                            longitude: advert.longitude_f64(),
+                            advert_timestamp: advert.timestamp,
+                            snr_db: rx.snr_db,
+                        };
+                        self.publish(node, "adverts", &message);
+                    }
+                }
+            }
+            EventPayload::SerialTx(serial) => {
+                for target in &event.targets {
+                    let mut delivered = Vec::new();
+                    let Some((node, session)) = self.sessions.get_mut(&target.0) else {
+                        continue;
+                    };
+                    session.feed(&serial.data);
+                    loop {
+                        match session.try_decode() {
+                            Ok(Some(Message::Response(
+                                Response::ContactMessageV2(msg) | Response::ContactMessageV3(msg),
+                            ))) => delivered.push(DeliveredMessage {
+                                sim_time_us,
+                                kind: "direct",
+                                from: Some(msg.sender_prefix.to_hex()),
+                                channel: None,
+                                sent_timestamp: msg.timestamp,
+                                text: msg.text,
+                            }),
+                            Ok(Some(Message::Response(
+                                Response::ChannelMessageV2(msg) | Response::ChannelMessageV3(msg),
+                            ))) => delivered.push(DeliveredMessage {
+                                sim_time_us,
+                                kind: "channel",
+                                from: None,
+                                channel: Some(msg.channel_idx),
+                                sent_timestamp: msg.timestamp,
+                                text: msg.text,
+                            }),
+                            Ok(Some(_)) | Err(_) => {}
+                            Ok(None) => break,
+                        }
+                    }
+                    let node = *node;
+                    for message in delivered {
+                        self.publish(node, "messages", &message);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn packet_message<'a>(
+    sim_time_us: u64,
+    direction: &'static str,
+    signal: Option<(f64, f64)>,
+    packet: &MeshCorePacket,
+    raw: &'a str,
+) -> PacketMessage<'a> {
+    PacketMessage {
+        sim_time_us,
+        direction,
+        snr_db: signal.map(|(snr, _)| snr),
+        rssi_dbm: signal.map(|(_, rssi)| rssi),
+        route: packet.route_type().as_label(),
+        payload_type: packet.payload_type().as_label(),
+        path_len: packet.path_len(),
+        hash: packet.payload_hash_hex(),
+        len: raw.len() / 2,
+        raw,
+    }
+}
+
+#[cfg(feature = "mqtt")]
+pub use client::MqttBridge;
+
+#[cfg(feature = "mqtt")]
+mod client {
+    use std::collections::HashMap;
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+
+    use rumqttc::{Client, Connection, Event, LastWill, MqttOptions, Packet, QoS};
+
+    use super::{topic_segment, MqttMessage, QUEUE_LEN};
+    use crate::control::ControlHandle;
+    use crate::control_server::REPLY_TIMEOUT;
+
+    /// Default MQTT port, when the broker address has none.
+    const DEFAULT_PORT: u16 = 1883;
+
+    /// How often the publisher checks whether the bridge is closing.
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Pause before reconnecting after a connection error.
+    const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+    /// Longest the bridge waits for its last messages to reach the broker.
+    const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// A connection to an MQTT broker, publishing an [`MqttFeed`](super::MqttFeed)'s
+    /// messages from background threads.
+    pub struct MqttBridge {
+        sender: SyncSender<MqttMessage>,
+        prefix: String,
+        closing: Arc<AtomicBool>,
+        publisher: JoinHandle<()>,
+        disconnected: Receiver<()>,
+    }
+
+    impl MqttBridge {
+        /// Connect to `broker` (`host` or `host:port`) and publish under
+        /// `prefix`. With `commands`, command lines for `nodes` are taken
+        /// from the broker and sent through `commands`' control lane.
+        pub fn start(
+            broker: &str,
+            prefix: &str,
+            nodes: &[String],
+            commands: Option<ControlHandle>,
+        ) -> io::Result<Self> {
+            let (host, port) = match broker.rsplit_once(':') {
+                Some((host, port)) => {
+                    let port = port.parse().map_err(|_| {
+                        io::Error::new(io::ErrorKind::InvalidInput, format!("invalid MQTT broker port '{}'", port))
+                    })?;
+                    (host, port)
+                }
+                None => (broker, DEFAULT_PORT),
+            };
+            let status_topic = format!("{}/status", prefix);
+            let mut options = MqttOptions::new(format!("mcsim-{}", std::process::id()), host, port);
+            options.set_keep_alive(Duration::from_secs(30));
+            options.set_last_will(LastWill::new(&status_topic, "offline", QoS::AtLeastOnce, true));
+            let (client, connection) = Client::new(options, QUEUE_LEN);
+
+            // Node name by command topic
+            let command_topics: HashMap<String, String> = match commands {
+                Some(_) => nodes
+                    .iter()
+                    .map(|name| (format!("{}/{}/command", prefix, topic_segment(name)), name.clone()))
+                    .collect(),
+                None => HashMap::new(),
+            };
+
+            let closing = Arc::new(AtomicBool::new(false));
+            let (sender, receiver) = mpsc::sync_channel(QUEUE_LEN);
+            let (done, disconnected) = mpsc::channel();
+            let connection_client = client.clone();
+            let connection_closing = closing.clone();
+            let responses = sender.clone();
+            let online = MqttMessage { topic: status_topic, payload: "online".to_string(), retain: true };
+            std::thread::Builder::new().name("mqtt-connection".to_string()).spawn(move || {
+                run_connection(
+                    connection,
+                    connection_client,
+                    online,
+                    command_topics,
+                    commands.map(|control| (control, responses)),
+                    connection_closing,
+                );
+                let _ = done.send(());
+            })?;
+            let publisher_closing = closing.clone();
+            let publisher = std::thread::Builder::new()
+                .name("mqtt-publisher".to_string())
+                .spawn(move || run_publisher(client, receiver, publisher_closing))?;
+            Ok(MqttBridge { sender, prefix: prefix.to_string(), closing, publisher, disconnected })
+        }
+
+        /// Sender for an [`MqttFeed`](super::MqttFeed).
+        pub fn sender(&self) -> SyncSender<MqttMessage> {
+            self.sender.clone()
+        }
+
+        /// Mark the run offline, publish what is still queued and disconnect.
+        pub fn finish(self) {
+            let offline = MqttMessage {
+                topic: format!("{}/status", self.prefix),
+                payload: "offline".to_string(),
+                retain: true,
+            };
+            let _ = self.sender.send(offline);
+            self.closing.store(true, Ordering::SeqCst);
+            let _ = self.publisher.join();
+            let _ = self.disconnected.recv_timeout(CLOSE_TIMEOUT);
+        }
+    }
+
+    /// Publish queued messages until the bridge closes and the queue is empty.
+    fn run_publisher(client: Client, receiver: Receiver<MqttMessage>, closing: Arc<AtomicBool>) {
+        loop {
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(message) => {
+                    let qos = if message.retain { QoS::AtLeastOnce } else { QoS::AtMostOnce };
+                    // Never block on a broker that is down or slow
+                    let _ = client.try_publish(message.topic, qos, message.retain, message.payload);
+                }
+                Err(RecvTimeoutError::Timeout) if closing.load(Ordering::SeqCst) => break,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        let _ = client.try_disconnect();
+    }
+
+    /// Drive the connection: announce the run and subscribe to command topics
+    /// on every (re)connection, and answer commands.
+    fn run_connection(
+        mut connection: Connection,
+        client: Client,
+        online: MqttMessage,
+        command_topics: HashMap<String, String>,
+        commands: Option<(ControlHandle, SyncSender<MqttMessage>)>,
+        closing: Arc<AtomicBool>,
+    ) {
+        let mut reported_error = false;
+        for notification in connection.iter() {
+            match notification {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    eprintln!("✓ Connected to the MQTT broker");
+                    reported_error = false;
+                    let _ = client.try_publish(&online.topic, QoS::AtLeastOnce, true, online.payload.clone());
+                    for topic in command_topics.keys() {
+                        let _ = client.try_subscribe(topic, QoS::AtMostOnce);
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    let (Some(node), Some((control, responses))) = (command_topics.get(&publish.topic), &commands)
+                    else {
+                        continue;
+                    };
+                    let line = String::from_utf8_lossy(&publish.payload).trim().to_string();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    // Answered off this thread, which must keep the connection alive
+                    let control = control.clone();
+                    let responses = responses.clone();
+                    let topic = format!("{}/response", publish.topic.trim_end_matches("/command"));
+                    let node = node.clone();
+                    std::thread::spawn(move || {
+                        let output = control
+                            .console(&format!("send {} {}", node, line), REPLY_TIMEOUT)
+                            .unwrap_or_else(|| "no answer from the simulation".to_string());
+                        let _ = responses.try_send(MqttMessage { topic, payload: output, retain: false });
+                    });
+                }
+                Ok(Event::Outgoing(rumqttc::Outgoing::Disconnect)) => break,
+                Ok(_) => {}
+                Err(_) if closing.load(Ordering::SeqCst) => break,
+                Err(e) => {
+                    if !reported_error {
+                        eprintln!("⚠ MQTT connection error: {} (retrying)", e);
+                        reported_error = true;
+                    }
+                    std::thread::sleep(RECONNECT_DELAY);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{EntityId, EventId, LoraPacket, RadioRxPacketEvent, SimTime};
+    use meshcore_packet::AdvertPayload;
+
+    fn received(packet: LoraPacket, target: u64, collided: bool) -> Event {
+        Event {
+            id: EventId(0),
+            time: SimTime::from_secs(2.0),
+            source: EntityId::new(1),
+            targets: vec![EntityId::new(target)],
+            payload: EventPayload::RadioRxPacket(RadioRxPacketEvent {
+                packet,
+                source_radio_id: EntityId::new(1),
+                snr_db: 6.5,
+                rssi_dbm: -101.0,
+                was_collided: collided,
+                was_weak_signal: false,
+                was_corrupted: false,
+                start_time: SimTime::from_secs(1.9),
+                end_time: SimTime::from_secs(2.0),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_topic_segment() {
+        assert_eq!(topic_segment("Alice"), "Alice");
+        assert_eq!(topic_segment("a/b+c#"), "a_b_c_");
+    }
+
+    #[test]
+    fn test_received_adverts_are_published() {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(16);
+        let nodes = [NodeInfo::new("Alice", "Repeater", 10, 11), NodeInfo::new("Bob/2", "Repeater", 20, 21)];
+        let mut feed = MqttFeed::new(&nodes, "sim", sender);
+
+        let advert = AdvertPayload::new([7; 32], 1234, [0; 64], "Carol").with_location(47.5, -122.25);
+        let packet = LoraPacket::new(MeshCorePacket::advert(advert).encode());
+        feed.observe(&received(packet.clone(), 20, false));
+        // Collided receptions are not published
+        feed.observe(&received(packet, 10, true));
+
+        let messages: Vec<MqttMessage> = receiver.try_iter().collect();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].topic, "sim/Bob_2/packets");
+        let rx: serde_json::Value = serde_json::from_str(&messages[0].payload).unwrap();
+        assert_eq!(rx["direction"], "rx");
+        assert_eq!(rx["payload_type"], "advert");
+        assert_eq!(rx["snr_db"], 6.5);
+
+        assert_eq!(messages[1].topic, "sim/Bob_2/adverts");
+        let advert: serde_json::Value = serde_json::from_str(&messages[1].payload).unwrap();
+        assert_eq!(advert["name"], "Carol");
+        assert_eq!(advert["latitude"], 47.5);
+        assert_eq!(advert["advert_timestamp"], 1234);
+    }
+
+    #[test]
+    fn test_full_queue_drops_messages() {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+        let mut feed = MqttFeed::new(&[NodeInfo::new("Alice", "Repeater", 10, 11)], "sim", sender);
+        let advert = AdvertPayload::new([7; 32], 1, [0; 64], "Carol");
+        let packet = LoraPacket::new(MeshCorePacket::advert(advert).encode());
+        feed.observe(&received(packet, 10, false));
+
+        assert_eq!(receiver.try_iter().count(), 1);
+        assert_eq!(feed.dropped(), 1);
+    }
+}
@@ -0,0 +1,116 @@
+//! Network degradation and recovery around power outages.
+//!
+//! A scenario's `outages` (see [`mcsim_model::outages`]) power off every
+//! node inside an area for a while. When a scenario has outages, `mcsim run`
+//! tracks flood delivery (see [`crate::blast_radius`]) and reports for each
+//! outage:
+//!
+//! - the mean flood coverage of all nodes before the outage;
+//! - the mean flood coverage of the nodes outside the area during it;
+//! - the mean flood coverage of all nodes once power is restored;
+//! - how long after restoration each restored node first heard a flood from
+//!   another node again.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use mcsim_model::PowerOutage;
+use serde::Serialize;
+
+use crate::blast_radius::DeliveryTracker;
+use crate::SimTime;
+
+/// Delivery before, during and after one power outage.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutageImpact {
+    /// Name of the outage.
+    pub name: String,
+    /// Nodes inside the outage area.
+    pub nodes: Vec<String>,
+    /// Time (seconds) power was cut.
+    pub at_s: f64,
+    /// Time (seconds) power was restored, if it was.
+    pub restore_at_s: Option<f64>,
+    /// Mean flood coverage of all nodes before the outage.
+    pub coverage_before: Option<f64>,
+    /// Mean flood coverage of the nodes outside the area during the outage.
+    pub coverage_during: Option<f64>,
+    /// Mean flood coverage of all nodes after power was restored.
+    pub coverage_after: Option<f64>,
+    /// Seconds from restoration until each restored node heard a flood
+    /// again, or None if it never did.
+    pub recovery_s: BTreeMap<String, Option<f64>>,
+}
+
+impl OutageImpact {
+    /// Measure the impact of `outage`, which powered off `nodes`, on a run
+    /// that ended at `end`.
+    pub fn measure(tracker: &DeliveryTracker, outage: &PowerOutage, nodes: Vec<String>, end: SimTime) -> Self {
+        let at = SimTime::from_secs(outage.at_s);
+        let restore_at = outage.restore_at_s().map(SimTime::from_secs).filter(|t| *t < end);
+        let all = HashSet::new();
+        let affected: HashSet<String> = nodes.iter().cloned().collect();
+
+        let recovery_s = match restore_at {
+            Some(restore_at) => nodes
+                .iter()
+                .map(|node| {
+                    let heard = tracker.first_reached(node, restore_at);
+                    (node.clone(), heard.map(|t| (t - restore_at).as_secs_f64()))
+                })
+                .collect(),
+            None => BTreeMap::new(),
+        };
+
+        Self {
+            name: outage.name.clone(),
+            at_s: outage.at_s,
+            restore_at_s: restore_at.map(|t| t.as_secs_f64()),
+            coverage_before: tracker.summary_between(SimTime::ZERO, at, &all).mean_coverage,
+            coverage_during: tracker
+                .summary_between(at, restore_at.unwrap_or(end), &affected)
+                .mean_coverage,
+            coverage_after: restore_at.and_then(|t| tracker.summary(t, &all).mean_coverage),
+            recovery_s,
+            nodes,
+        }
+    }
+
+    /// Number of restored nodes that heard a flood again.
+    pub fn recovered(&self) -> usize {
+        self.recovery_s.values().filter(|r| r.is_some()).count()
+    }
+}
+
+fn percent(coverage: Option<f64>) -> String {
+    coverage.map_or("-".to_string(), |c| format!("{:.1}%", c * 100.0))
+}
+
+impl fmt::Display for OutageImpact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} node(s) off at {:.0}s): coverage {} before, {} during",
+            self.name,
+            self.nodes.len(),
+            self.at_s,
+            percent(self.coverage_before),
+            percent(self.coverage_during)
+        )?;
+        let Some(restore_at_s) = self.restore_at_s else {
+            return write!(f, ", not restored");
+        };
+        write!(
+            f,
+            ", {} after restoration at {:.0}s; {}/{} node(s) recovered",
+            percent(self.coverage_after),
+            restore_at_s,
+            self.recovered(),
+            self.nodes.len()
+        )?;
+        if let Some(slowest) = self.recovery_s.values().flatten().copied().reduce(f64::max) {
+            write!(f, ", slowest after {:.0}s", slowest)?;
+        }
+        Ok(())
+    }
+}
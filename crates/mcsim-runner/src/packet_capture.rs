@@ -0,0 +1,295 @@
+//! PCAPNG capture of over-the-air packets.
+//!
+//! Writes every packet put on the air (`TransmitAir`) and every packet that
+//! reaches a radio (`ReceiveAir`) to a pcapng file for analysis in Wireshark:
+//!
+//! ```text
+//! mcsim run model.yaml --duration 10m --pcap air.pcapng
+//! ```
+//!
+//! Each node's radio is one interface, named after the node. Packet data is
+//! the raw MeshCore packet under link type [`LINKTYPE_MESHCORE`]
+//! (`LINKTYPE_USER0`); map it to a MeshCore dissector in Wireshark under
+//! *Preferences → Protocols → DLT_USER*. Transmissions are marked outbound and
+//! receptions inbound. Each packet carries a comment with its radio settings
+//! and, for receptions, the sender and the link's expected SNR and RSSI at the
+//! transmitter's power (the SNR actually sampled by the receiving radio varies
+//! around this mean):
+//!
+//! ```text
+//! rx from=Repeater1 freq_mhz=910.525 bw_khz=62.5 sf=7 cr=4/5 tx_power_dbm=20 snr_db=8.5 snr_std_dev_db=1.8 rssi_dbm=-105.0
+//! ```
+//!
+//! Timestamps count from 2025-01-01T00:00:00Z at simulation start, as in the
+//! JSON trace.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use mcsim_common::{Event, EventPayload, RadioParams};
+
+/// Link type of captured packets (`LINKTYPE_USER0`): raw MeshCore packets.
+pub const LINKTYPE_MESHCORE: u16 = 147;
+
+/// Unix time of simulation start (2025-01-01T00:00:00Z) in microseconds.
+const EPOCH_OFFSET_US: u64 = 1_735_689_600 * 1_000_000;
+
+const BLOCK_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const BLOCK_INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const BLOCK_ENHANCED_PACKET: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+const OPT_END: u16 = 0;
+const OPT_COMMENT: u16 = 1;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_IF_NAME: u16 = 2;
+const OPT_IF_DESCRIPTION: u16 = 3;
+const OPT_IF_TSRESOL: u16 = 9;
+const OPT_EPB_FLAGS: u16 = 2;
+
+const EPB_FLAG_INBOUND: u32 = 1;
+const EPB_FLAG_OUTBOUND: u32 = 2;
+
+/// Writes over-the-air packets to a pcapng stream.
+pub struct PacketCapture {
+    output: Box<dyn Write>,
+    /// Interface ID and node name of each radio entity.
+    radios: HashMap<u64, (u32, String)>,
+    packets: u64,
+}
+
+impl PacketCapture {
+    /// Start a capture of `radios` (radio entity ID, node name), writing the
+    /// section header and one interface per radio.
+    pub fn new(mut output: Box<dyn Write>, radios: &[(u64, String)]) -> io::Result<Self> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        // Section length not known in advance
+        body.extend_from_slice(&(-1i64).to_le_bytes());
+        push_option(&mut body, OPT_SHB_USERAPPL, b"mcsim");
+        push_option(&mut body, OPT_END, &[]);
+        write_block(&mut output, BLOCK_SECTION_HEADER, &body)?;
+
+        let mut interfaces = HashMap::new();
+        for (index, (radio, name)) in radios.iter().enumerate() {
+            let mut body = Vec::new();
+            body.extend_from_slice(&LINKTYPE_MESHCORE.to_le_bytes());
+            body.extend_from_slice(&0u16.to_le_bytes());
+            // No snapshot length limit
+            body.extend_from_slice(&0u32.to_le_bytes());
+            push_option(&mut body, OPT_IF_NAME, name.as_bytes());
+            push_option(&mut body, OPT_IF_DESCRIPTION, b"MeshCore LoRa radio");
+            // Microsecond timestamps
+            push_option(&mut body, OPT_IF_TSRESOL, &[6]);
+            push_option(&mut body, OPT_END, &[]);
+            write_block(&mut output, BLOCK_INTERFACE_DESCRIPTION, &body)?;
+            interfaces.insert(*radio, (index as u32, name.clone()));
+        }
+
+        Ok(PacketCapture { output, radios: interfaces, packets: 0 })
+    }
+
+    /// Write the packets carried by an event, if any.
+    pub fn record(&mut self, event: &Event) -> io::Result<()> {
+        match &event.payload {
+            EventPayload::TransmitAir(tx) => {
+                if let Some((interface, _)) = self.radios.get(&tx.radio_id.0) {
+                    let comment = format!("tx {}", radio_settings(&tx.params));
+                    let interface = *interface;
+                    self.write_packet(interface, event, &tx.packet.payload, EPB_FLAG_OUTBOUND, &comment)?;
+                }
+            }
+            EventPayload::ReceiveAir(rx) => {
+                let sender = self
+                    .radios
+                    .get(&rx.source_radio_id.0)
+                    .map_or_else(|| format!("entity:{}", rx.source_radio_id.0), |(_, name)| name.clone());
+                let offset_db = rx.params.tx_power_offset_db();
+                let comment = format!(
+                    "rx from={} {} snr_db={:.1} snr_std_dev_db={:.1} rssi_dbm={:.1}",
+                    sender,
+                    radio_settings(&rx.params),
+                    rx.mean_snr_db_at20dbm + offset_db,
+                    rx.snr_std_dev,
+                    rx.rssi_dbm + offset_db
+                );
+                for target in &event.targets {
+                    if let Some((interface, _)) = self.radios.get(&target.0) {
+                        let interface = *interface;
+                        self.write_packet(interface, event, &rx.packet.payload, EPB_FLAG_INBOUND, &comment)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Number of packets written.
+    pub fn packets(&self) -> u64 {
+        self.packets
+    }
+
+    /// Flush buffered output.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+
+    fn write_packet(&mut self, interface: u32, event: &Event, data: &[u8], flags: u32, comment: &str) -> io::Result<()> {
+        let timestamp = EPOCH_OFFSET_US + event.time.as_micros();
+        let mut body = Vec::new();
+        body.extend_from_slice(&interface.to_le_bytes());
+        body.extend_from_slice(&((timestamp >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(timestamp as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(data);
+        pad(&mut body);
+        push_option(&mut body, OPT_COMMENT, comment.as_bytes());
+        push_option(&mut body, OPT_EPB_FLAGS, &flags.to_le_bytes());
+        push_option(&mut body, OPT_END, &[]);
+        write_block(&mut self.output, BLOCK_ENHANCED_PACKET, &body)?;
+        self.packets += 1;
+        Ok(())
+    }
+}
+
+fn radio_settings(params: &RadioParams) -> String {
+    format!(
+        "freq_mhz={:.3} bw_khz={:.1} sf={} cr=4/{} tx_power_dbm={}",
+        params.frequency_hz as f64 / 1e6,
+        params.bandwidth_hz as f64 / 1e3,
+        params.spreading_factor,
+        params.coding_rate,
+        params.tx_power_dbm
+    )
+}
+
+/// Pad to a 32-bit boundary.
+fn pad(buf: &mut Vec<u8>) {
+    buf.resize(buf.len().next_multiple_of(4), 0);
+}
+
+fn push_option(buf: &mut Vec<u8>, code: u16, value: &[u8]) {
+    buf.extend_from_slice(&code.to_le_bytes());
+    buf.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    buf.extend_from_slice(value);
+    pad(buf);
+}
+
+/// Write a block: type, total length, body (already padded), total length.
+fn write_block(output: &mut dyn Write, block_type: u32, body: &[u8]) -> io::Result<()> {
+    let total = (body.len() + 12) as u32;
+    output.write_all(&block_type.to_le_bytes())?;
+    output.write_all(&total.to_le_bytes())?;
+    output.write_all(body)?;
+    output.write_all(&total.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{EntityId, EventId, LoraPacket, ReceiveAirEvent, SimTime, TransmitAirEvent};
+    use std::sync::{Arc, Mutex};
+
+    /// Writer that keeps its bytes readable after being boxed.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn params() -> RadioParams {
+        RadioParams {
+            frequency_hz: 910_525_000,
+            bandwidth_hz: 62_500,
+            spreading_factor: 7,
+            coding_rate: 5,
+            tx_power_dbm: 17,
+        }
+    }
+
+    /// Split a pcapng stream into (block type, body) pairs, checking lengths.
+    fn blocks(bytes: &[u8]) -> Vec<(u32, &[u8])> {
+        let word = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let mut blocks = Vec::new();
+        let mut at = 0;
+        while at < bytes.len() {
+            let total = word(at + 4) as usize;
+            assert_eq!(total % 4, 0);
+            assert_eq!(word(at + total - 4) as usize, total);
+            blocks.push((word(at), &bytes[at + 8..at + total - 4]));
+            at += total;
+        }
+        blocks
+    }
+
+    #[test]
+    fn test_capture_layout() {
+        let buf = SharedBuf::default();
+        let radios = vec![(10, "Alice".to_string()), (20, "Bob".to_string())];
+        let mut capture = PacketCapture::new(Box::new(buf.clone()), &radios).unwrap();
+
+        let packet = LoraPacket::new(vec![0x11, 0x22, 0x33]);
+        let tx = Event {
+            id: EventId(1),
+            time: SimTime::from_micros(2_000_000),
+            source: EntityId::new(10),
+            targets: vec![EntityId::new(1)],
+            payload: EventPayload::TransmitAir(TransmitAirEvent {
+                radio_id: EntityId::new(10),
+                packet: packet.clone(),
+                params: params(),
+                sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+                end_time: SimTime::from_micros(2_050_000),
+            }),
+        };
+        let rx = Event {
+            id: EventId(2),
+            targets: vec![EntityId::new(20)],
+            payload: EventPayload::ReceiveAir(ReceiveAirEvent {
+                source_radio_id: EntityId::new(10),
+                packet,
+                params: params(),
+                sync_word: mcsim_common::DEFAULT_SYNC_WORD,
+                end_time: SimTime::from_micros(2_050_000),
+                mean_snr_db_at20dbm: 10.0,
+                snr_std_dev: 1.8,
+                rssi_dbm: -100.0,
+                fading: Default::default(),
+                fault: None,
+            }),
+            ..tx.clone()
+        };
+        capture.record(&tx).unwrap();
+        capture.record(&rx).unwrap();
+        assert_eq!(capture.packets(), 2);
+
+        let bytes = buf.0.lock().unwrap().clone();
+        let blocks = blocks(&bytes);
+        let types: Vec<u32> = blocks.iter().map(|(t, _)| *t).collect();
+        assert_eq!(types, vec![BLOCK_SECTION_HEADER, 1, 1, 6, 6]);
+        assert_eq!(&blocks[1].1[..2], &LINKTYPE_MESHCORE.to_le_bytes());
+
+        // Received copy is on Bob's interface with the packet bytes and comment
+        let epb = blocks[4].1;
+        assert_eq!(&epb[..4], &1u32.to_le_bytes());
+        let timestamp = (u32::from_le_bytes(epb[4..8].try_into().unwrap()) as u64) << 32
+            | u32::from_le_bytes(epb[8..12].try_into().unwrap()) as u64;
+        assert_eq!(timestamp, EPOCH_OFFSET_US + 2_000_000);
+        assert_eq!(&epb[20..23], &[0x11, 0x22, 0x33]);
+        let text = String::from_utf8_lossy(epb);
+        assert!(text.contains("rx from=Alice freq_mhz=910.525 bw_khz=62.5 sf=7 cr=4/5 tx_power_dbm=17 snr_db=7.0"));
+        assert!(text.contains("rssi_dbm=-103.0"));
+    }
+}
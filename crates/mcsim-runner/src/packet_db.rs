@@ -0,0 +1,479 @@
+//! SQLite database of per-packet radio events.
+//!
+//! Writes one row per radio event to a `packet_events` table, so large runs
+//! can be analyzed with SQL instead of searching the trace:
+//!
+//! ```text
+//! mcsim run model.yaml --duration 1h --packet-db packets.sqlite
+//! sqlite3 packets.sqlite "SELECT node, drop_reason, COUNT(*) FROM packet_events
+//!                         WHERE kind = 'drop' GROUP BY node, drop_reason"
+//! ```
+//!
+//! Each packet appears as:
+//!
+//! - `tx`: a radio put it on the air (`node` is the sender),
+//! - `rx`: it reached a radio in range (`peer` is the sender; `snr_db` and
+//!   `rssi_dbm` are the link's expected values at the transmitter's power),
+//! - `delivery`: the receiving radio decoded it and handed it to the firmware,
+//!   with the SNR and RSSI it was received at,
+//! - `drop`: the receiving radio lost it; `drop_reason` is `collision`,
+//!   `weak_signal`, `corrupted` or `fault` (dropped by a fault injection rule).
+//!
+//! Time, node, packet hash and payload type are indexed. The `nodes` table
+//! lists each node's type. At the end of the run the `message_paths` table
+//! gets one row per message and node that heard it, with the path it first
+//! arrived by (see [`crate::message_paths`]), so
+//!
+//! ```text
+//! SELECT path, hops, heard_us - sent_us FROM message_paths WHERE packet_hash = '...'
+//! ```
+//!
+//! shows where a message went. Writing the database requires the `sqlite`
+//! feature; turning events into rows does not.
+
+use std::collections::HashMap;
+
+use mcsim_common::{Event, EventPayload, InjectedFault, LoraPacket};
+
+/// Kind of packet event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketEventKind {
+    /// Put on the air.
+    Tx,
+    /// Arrived at a radio.
+    Rx,
+    /// Decoded and handed to the firmware.
+    Delivery,
+    /// Lost by the receiving radio.
+    Drop,
+}
+
+impl PacketEventKind {
+    /// Name stored in the `kind` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PacketEventKind::Tx => "tx",
+            PacketEventKind::Rx => "rx",
+            PacketEventKind::Delivery => "delivery",
+            PacketEventKind::Drop => "drop",
+        }
+    }
+}
+
+/// One row of the `packet_events` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketRecord {
+    /// Simulation time in microseconds.
+    pub time_us: u64,
+    /// What happened to the packet.
+    pub kind: PacketEventKind,
+    /// Node whose radio the event happened at.
+    pub node: String,
+    /// Sending node, for events at a receiver.
+    pub peer: Option<String>,
+    /// Payload hash, as in the trace.
+    pub packet_hash: String,
+    /// Payload type, if the packet decodes.
+    pub payload_type: Option<String>,
+    /// SNR in dB, for events at a receiver.
+    pub snr_db: Option<f64>,
+    /// RSSI in dBm, for events at a receiver.
+    pub rssi_dbm: Option<f64>,
+    /// Why the packet was lost, for drops.
+    pub drop_reason: Option<&'static str>,
+    /// Raw packet.
+    pub packet: Vec<u8>,
+}
+
+/// Turns events into [`PacketRecord`]s.
+pub struct PacketRecorder {
+    /// Node name of each radio entity.
+    radios: HashMap<u64, String>,
+}
+
+impl PacketRecorder {
+    /// Record the events of `radios` (radio entity ID, node name).
+    pub fn new(radios: &[(u64, String)]) -> Self {
+        PacketRecorder { radios: radios.iter().cloned().collect() }
+    }
+
+    /// The rows for an event, if it concerns a packet at a known radio.
+    pub fn records(&self, event: &Event) -> Vec<PacketRecord> {
+        let time_us = event.time.as_micros();
+        match &event.payload {
+            EventPayload::TransmitAir(tx) => self
+                .radios
+                .get(&tx.radio_id.0)
+                .map(|node| record(time_us, PacketEventKind::Tx, node, None, &tx.packet))
+                .into_iter()
+                .collect(),
+            EventPayload::ReceiveAir(rx) => {
+                let peer = self.radios.get(&rx.source_radio_id.0);
+                let offset_db = rx.params.tx_power_offset_db();
+                event
+                    .targets
+                    .iter()
+                    .filter_map(|target| self.radios.get(&target.0))
+                    .map(|node| {
+                        let mut row = record(time_us, PacketEventKind::Rx, node, peer, &rx.packet);
+                        if rx.fault == Some(InjectedFault::Drop) {
+                            row.kind = PacketEventKind::Drop;
+                            row.drop_reason = Some("fault");
+                        } else {
+                            row.snr_db = Some(rx.mean_snr_db_at20dbm + offset_db);
+                            row.rssi_dbm = Some(rx.rssi_dbm + offset_db);
+                        }
+                        row
+                    })
+                    .collect()
+            }
+            EventPayload::RadioRxPacket(rx) => {
+                // Posted by the receiving radio to its firmware
+                let Some(node) = self.radios.get(&event.source.0) else {
+                    return Vec::new();
+                };
+                let drop_reason = if rx.was_collided {
+                    Some("collision")
+                } else if rx.was_weak_signal {
+                    Some("weak_signal")
+                } else if rx.was_corrupted {
+                    Some("corrupted")
+                } else {
+                    None
+                };
+                let kind = if drop_reason.is_some() { PacketEventKind::Drop } else { PacketEventKind::Delivery };
+                let mut row = record(time_us, kind, node, self.radios.get(&rx.source_radio_id.0), &rx.packet);
+                row.snr_db = Some(rx.snr_db);
+                row.rssi_dbm = Some(rx.rssi_dbm);
+                row.drop_reason = drop_reason;
+                vec![row]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn record(time_us: u64, kind: PacketEventKind, node: &str, peer: Option<&String>, packet: &LoraPacket) -> PacketRecord {
+    PacketRecord {
+        time_us,
+        kind,
+        node: node.to_string(),
+        peer: peer.cloned(),
+        packet_hash: packet.payload_hash_label(),
+        payload_type: packet.decoded().map(|p| p.payload_type().as_label().to_string()),
+        snr_db: None,
+        rssi_dbm: None,
+        drop_reason: None,
+        packet: packet.payload.clone(),
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use database::PacketDatabase;
+
+#[cfg(feature = "sqlite")]
+mod database {
+    use std::io;
+    use std::path::Path;
+
+    use mcsim_common::Event;
+    use rusqlite::{params, Connection};
+
+    use super::PacketRecorder;
+    use crate::message_paths::MessagePathReport;
+
+    /// Rows written per transaction.
+    const ROWS_PER_TRANSACTION: usize = 10_000;
+
+    const SCHEMA: &str = "
+        CREATE TABLE nodes (
+            name TEXT PRIMARY KEY,
+            node_type TEXT NOT NULL
+        );
+        CREATE TABLE packet_events (
+            id INTEGER PRIMARY KEY,
+            time_us INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            node TEXT NOT NULL,
+            peer TEXT,
+            packet_hash TEXT NOT NULL,
+            payload_type TEXT,
+            size INTEGER NOT NULL,
+            snr_db REAL,
+            rssi_dbm REAL,
+            drop_reason TEXT,
+            packet BLOB NOT NULL
+        );
+        CREATE INDEX packet_events_time ON packet_events (time_us);
+        CREATE INDEX packet_events_node ON packet_events (node, time_us);
+        CREATE INDEX packet_events_hash ON packet_events (packet_hash);
+        CREATE INDEX packet_events_payload_type ON packet_events (payload_type);
+        CREATE TABLE message_paths (
+            packet_hash TEXT NOT NULL,
+            payload_type TEXT NOT NULL,
+            origin TEXT NOT NULL,
+            node TEXT NOT NULL,
+            sent_us INTEGER NOT NULL,
+            heard_us INTEGER NOT NULL,
+            hops INTEGER NOT NULL,
+            copies INTEGER NOT NULL,
+            path TEXT NOT NULL
+        );
+        CREATE INDEX message_paths_hash ON message_paths (packet_hash);
+        CREATE INDEX message_paths_node ON message_paths (node);
+    ";
+
+    const INSERT: &str = "INSERT INTO packet_events
+        (time_us, kind, node, peer, packet_hash, payload_type, size, snr_db, rssi_dbm, drop_reason, packet)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)";
+
+    /// Writes packet events to an SQLite database.
+    pub struct PacketDatabase {
+        connection: Connection,
+        recorder: PacketRecorder,
+        /// Rows written in the open transaction.
+        pending: usize,
+        rows: u64,
+    }
+
+    impl PacketDatabase {
+        /// Create the database at `path`, replacing any existing file, for
+        /// `radios` (radio entity ID, node name, node type).
+        pub fn create(path: &Path, radios: &[(u64, String, String)]) -> io::Result<Self> {
+            match std::fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+            let connection = Connection::open(path).map_err(io::Error::other)?;
+            // The database is an output: a crash loses it either way
+            connection
+                .execute_batch("PRAGMA journal_mode = OFF; PRAGMA synchronous = OFF;")
+                .map_err(io::Error::other)?;
+            connection.execute_batch(SCHEMA).map_err(io::Error::other)?;
+            for (_, name, node_type) in radios {
+                connection
+                    .execute("INSERT INTO nodes (name, node_type) VALUES (?1, ?2)", params![name, node_type])
+                    .map_err(io::Error::other)?;
+            }
+            connection.execute_batch("BEGIN").map_err(io::Error::other)?;
+            let radios: Vec<(u64, String)> = radios.iter().map(|(id, name, _)| (*id, name.clone())).collect();
+            Ok(PacketDatabase { connection, recorder: PacketRecorder::new(&radios), pending: 0, rows: 0 })
+        }
+
+        /// Write the rows for an event, if any.
+        pub fn record(&mut self, event: &Event) -> io::Result<()> {
+            let records = self.recorder.records(event);
+            if records.is_empty() {
+                return Ok(());
+            }
+            let mut insert = self.connection.prepare_cached(INSERT).map_err(io::Error::other)?;
+            for r in &records {
+                insert
+                    .execute(params![
+                        r.time_us as i64,
+                        r.kind.as_str(),
+                        r.node,
+                        r.peer,
+                        r.packet_hash,
+                        r.payload_type,
+                        r.packet.len() as i64,
+                        r.snr_db,
+                        r.rssi_dbm,
+                        r.drop_reason,
+                        r.packet,
+                    ])
+                    .map_err(io::Error::other)?;
+            }
+            drop(insert);
+            self.rows += records.len() as u64;
+            self.pending += records.len();
+            if self.pending >= ROWS_PER_TRANSACTION {
+                self.flush()?;
+            }
+            Ok(())
+        }
+
+        /// Write a row per message and node that heard it to `message_paths`.
+        pub fn write_message_paths(&mut self, report: &MessagePathReport) -> io::Result<()> {
+            let mut insert = self
+                .connection
+                .prepare_cached(
+                    "INSERT INTO message_paths
+                     (packet_hash, payload_type, origin, node, sent_us, heard_us, hops, copies, path)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )
+                .map_err(io::Error::other)?;
+            for message in &report.messages {
+                for delivery in &message.deliveries {
+                    insert
+                        .execute(params![
+                            message.packet_hash,
+                            message.payload_type,
+                            message.origin,
+                            delivery.node,
+                            message.sent_us as i64,
+                            delivery.heard_us() as i64,
+                            delivery.hops.len() as i64,
+                            delivery.copies,
+                            delivery.path().join(" > "),
+                        ])
+                        .map_err(io::Error::other)?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Number of rows written.
+        pub fn rows(&self) -> u64 {
+            self.rows
+        }
+
+        /// Commit the rows written so far.
+        pub fn flush(&mut self) -> io::Result<()> {
+            self.connection.execute_batch("COMMIT; BEGIN").map_err(io::Error::other)?;
+            self.pending = 0;
+            Ok(())
+        }
+    }
+
+    impl Drop for PacketDatabase {
+        fn drop(&mut self) {
+            let _ = self.connection.execute_batch("COMMIT");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{EntityId, EventId, RadioParams, RadioRxPacketEvent, SimTime, TransmitAirEvent};
+
+    fn params() -> RadioParams {
+        RadioParams {
+            frequency_hz: 910_525_000,
+            bandwidth_hz: 62_500,
+            spreading_factor: 7,
+            coding_rate: 5,
+            tx_power_dbm: 20,
+        }
+    }
+
+    fn event(source: u64, targets: Vec<u64>, payload: EventPayload) -> Event {
+        Event {
+            id: EventId(0),
+            time: SimTime::from_millis(1500),
+            source: EntityId::new(source),
+            targets: targets.into_iter().map(EntityId::new).collect(),
+            payload,
+        }
+    }
+
+    fn rx(was_collided: bool) -> EventPayload {
+        EventPayload::RadioRxPacket(RadioRxPacketEvent {
+            packet: LoraPacket::new(vec![0x11, 0x00, 0xaa]),
+            source_radio_id: EntityId::new(1),
+            snr_db: 4.5,
+            rssi_dbm: -110.0,
+            was_collided,
+            was_weak_signal: false,
+            was_corrupted: false,
+            start_time: SimTime::from_millis(1400),
+            end_time: SimTime::from_millis(1500),
+        })
+    }
+
+    #[test]
+    fn test_records() {
+        let recorder = PacketRecorder::new(&[(1, "Alice".to_string()), (2, "Bob".to_string())]);
+
+        let tx = event(
+            1,
+            vec![0],
+            EventPayload::TransmitAir(TransmitAirEvent {
+                radio_id: EntityId::new(1),
+                packet: LoraPacket::new(vec![0x11, 0x00, 0xaa]),
+                params: params(),
+                sync_word: 0x12,
+                end_time: SimTime::from_millis(1600),
+            }),
+        );
+        let rows = recorder.records(&tx);
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].kind, rows[0].node.as_str(), rows[0].peer.as_ref()), (PacketEventKind::Tx, "Alice", None));
+        assert_eq!(rows[0].time_us, 1_500_000);
+        assert_eq!(rows[0].packet, vec![0x11, 0x00, 0xaa]);
+
+        let rows = recorder.records(&event(2, vec![3], rx(false)));
+        assert_eq!(rows[0].kind, PacketEventKind::Delivery);
+        assert_eq!((rows[0].node.as_str(), rows[0].peer.as_deref()), ("Bob", Some("Alice")));
+        assert_eq!((rows[0].snr_db, rows[0].drop_reason), (Some(4.5), None));
+
+        let rows = recorder.records(&event(2, vec![3], rx(true)));
+        assert_eq!((rows[0].kind, rows[0].drop_reason), (PacketEventKind::Drop, Some("collision")));
+
+        // Events at unknown radios and other events have no rows
+        assert!(recorder.records(&event(9, vec![3], rx(false))).is_empty());
+        assert!(recorder.records(&event(1, vec![1], EventPayload::Timer { timer_id: 0 })).is_empty());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_database() {
+        use crate::message_paths::{Delivery, Hop, MessagePath, MessagePathReport};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("packets.sqlite");
+        let radios = [(1, "Alice".to_string(), "Companion".to_string()), (2, "Bob".to_string(), "Repeater".to_string())];
+        let mut db = PacketDatabase::create(&path, &radios).unwrap();
+        db.record(&event(2, vec![3], rx(false))).unwrap();
+        db.record(&event(2, vec![3], rx(true))).unwrap();
+        assert_eq!(db.rows(), 2);
+
+        let hop = |from: &str, to: &str, sent_us, heard_us| Hop { from: from.into(), to: to.into(), sent_us, heard_us };
+        let report = MessagePathReport {
+            delivered_messages: 1,
+            deliveries: 1,
+            mean_hops: Some(2.0),
+            max_hops: 2,
+            mean_latency_ms: Some(300.0),
+            mean_forward_delay_ms: Some(100.0),
+            duplicates: 1,
+            messages: vec![MessagePath {
+                packet_hash: "ABCD".into(),
+                payload_type: "Advert".into(),
+                route_type: "Flood".into(),
+                origin: "Alice".into(),
+                sent_us: 0,
+                transmissions: 2,
+                deliveries: vec![Delivery {
+                    node: "Bob".into(),
+                    hops: vec![hop("Alice", "Carol", 0, 100_000), hop("Carol", "Bob", 200_000, 300_000)],
+                    copies: 2,
+                }],
+                forward_delays_us: vec![100_000],
+            }],
+        };
+        db.write_message_paths(&report).unwrap();
+        drop(db);
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        let drops: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM packet_events e JOIN nodes n ON n.name = e.node
+                 WHERE e.kind = 'drop' AND e.drop_reason = 'collision' AND n.node_type = 'Repeater'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(drops, 1);
+
+        let (path, latency_us): (String, i64) = connection
+            .query_row(
+                "SELECT path, heard_us - sent_us FROM message_paths WHERE packet_hash = 'ABCD' AND node = 'Bob'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((path.as_str(), latency_us), ("Alice > Carol > Bob", 300_000));
+    }
+}
@@ -77,6 +77,8 @@ pub struct PacketTracker {
     packets: HashMap<PayloadHash, PacketKind>,
     /// Total node count for coverage calculation.
     total_nodes: usize,
+    /// Flood packets evicted so far and the sum of their coverage.
+    evicted_floods: (u64, f64),
 }
 
 impl PacketTracker {
@@ -89,6 +91,7 @@ impl PacketTracker {
         Self {
             packets: HashMap::new(),
             total_nodes,
+            evicted_floods: (0, 0.0),
         }
     }
 
@@ -151,12 +154,15 @@ impl PacketTracker {
     /// * `packet` - The decoded MeshCore packet being received
     /// * `receiver` - Name of the receiving node
     /// * `receive_time` - Simulation time in microseconds when received
+    ///
+    /// Returns true if this is the first time `receiver` heard this flood
+    /// packet (repeats are dropped by the firmware as already seen).
     pub fn track_reception(
         &mut self,
         packet: &MeshCorePacket,
         receiver: &str,
         receive_time: u64,
-    ) {
+    ) -> bool {
         let payload_hash = packet.payload_hash_label();
         let route_type = packet.route_type();
         let payload_type = packet.payload_type();
@@ -179,12 +185,13 @@ impl PacketTracker {
                     last_activity,
                     ..
                 } => {
-                    nodes_reached.insert(receiver.to_string());
+                    let first_heard = nodes_reached.insert(receiver.to_string());
                     reception_times.insert(receiver.to_string(), receive_time);
                     *times_heard += 1;
                     *last_activity = receive_time;
 
                     metrics::counter!(metric_defs::PACKET_RX_FLOOD.name, &labels).increment(1);
+                    return first_heard;
                 }
                 PacketKind::Direct {
                     destination,
@@ -213,6 +220,7 @@ impl PacketTracker {
                 }
             }
         }
+        false
     }
 
     /// Emit summary metrics for completed flood packets.
@@ -253,6 +261,22 @@ impl PacketTracker {
         }
     }
 
+    /// Mean fraction of nodes reached by the flood packets seen so far,
+    /// including evicted ones. `None` if no flood packet was sent.
+    pub fn mean_flood_coverage(&self) -> Option<f64> {
+        if self.total_nodes == 0 {
+            return None;
+        }
+        let (mut count, mut sum) = self.evicted_floods;
+        for kind in self.packets.values() {
+            if let PacketKind::Flood { nodes_reached, .. } = kind {
+                count += 1;
+                sum += nodes_reached.len() as f64 / self.total_nodes as f64;
+            }
+        }
+        (count > 0).then(|| sum / count as f64)
+    }
+
     /// Mark a direct message as failed (timeout/delivery failure).
     ///
     /// # Arguments
@@ -338,7 +362,7 @@ impl PacketTracker {
     /// Emit summary metrics for a single packet.
     ///
     /// Called during eviction to ensure metrics are recorded before removal.
-    fn emit_packet_summary(&self, kind: &PacketKind) {
+    fn emit_packet_summary(&mut self, kind: &PacketKind) {
         match kind {
             PacketKind::Flood {
                 nodes_reached,
@@ -365,6 +389,8 @@ impl PacketTracker {
                 if self.total_nodes > 0 {
                     let coverage = nodes_reached.len() as f64 / self.total_nodes as f64;
                     metrics::gauge!(metric_defs::FLOOD_COVERAGE.name).set(coverage);
+                    self.evicted_floods.0 += 1;
+                    self.evicted_floods.1 += coverage;
                 }
             }
             PacketKind::Direct { delivered, .. } => {
@@ -410,9 +436,9 @@ mod tests {
         tracker.track_send(&packet, None, 1000);
 
         // Receive at multiple nodes
-        tracker.track_reception(&packet, "node1", 1500);
-        tracker.track_reception(&packet, "node2", 2000);
-        tracker.track_reception(&packet, "node1", 2500); // Same node, second reception
+        assert!(tracker.track_reception(&packet, "node1", 1500));
+        assert!(tracker.track_reception(&packet, "node2", 2000));
+        assert!(!tracker.track_reception(&packet, "node1", 2500)); // Same node, second reception
 
         // Verify tracking
         let tracked = tracker.get_packet(hash).unwrap();
@@ -427,6 +453,7 @@ mod tests {
         } else {
             panic!("Expected flood packet");
         }
+        assert_eq!(tracker.mean_flood_coverage(), Some(0.4));
     }
 
     #[test]
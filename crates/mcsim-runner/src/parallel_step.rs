@@ -114,7 +114,7 @@ pub fn process_firmware_results(
         match result.reason {
             YieldReason::RadioTxStart => {
                 // Firmware wants to transmit - send TX request to radio
-                if let Some((ref tx_data, _airtime_ms)) = result.radio_tx_data {
+                if let Some((ref tx_data, airtime_ms)) = result.radio_tx_data {
                     let event = Event {
                         id: mcsim_common::EventId(ctx.next_event_id()),
                         time: current_time,
@@ -122,6 +122,8 @@ pub fn process_firmware_results(
                         targets: vec![output.attached_radio],
                         payload: EventPayload::RadioTxRequest(mcsim_common::RadioTxRequestEvent {
                             packet: mcsim_common::LoraPacket::new(tx_data.clone()),
+                            reported_airtime_ms: Some(airtime_ms),
+                            queue_depth: None,
                         }),
                     };
                     new_events.push(event);
@@ -130,10 +132,9 @@ pub fn process_firmware_results(
             YieldReason::Idle => {
                 // Schedule wake timer if needed
                 if result.wake_millis > output.current_millis {
-                    let delay_us = (result.wake_millis - output.current_millis) * 1000;
                     let event = Event {
                         id: mcsim_common::EventId(ctx.next_event_id()),
-                        time: current_time + SimTime::from_micros(delay_us),
+                        time: current_time + result.wake_delay,
                         source: output.entity_id,
                         targets: vec![output.entity_id],
                         payload: EventPayload::Timer { timer_id: 1 },
@@ -149,7 +150,22 @@ pub fn process_firmware_results(
                     eprintln!("Firmware error for entity {:?}: {}", output.entity_id, msg);
                 }
             }
-            _ => {}
+            YieldReason::Reboot | YieldReason::PowerOff => {
+                // The firmware asked to restart or shut down; the radio goes
+                // down with it and hands the request back
+                let payload = if result.reason == YieldReason::PowerOff {
+                    EventPayload::PowerOff(mcsim_common::PowerOffEvent { reason: "firmware shutdown".to_string() })
+                } else {
+                    EventPayload::Reboot(mcsim_common::RebootEvent { reason: "firmware reboot".to_string() })
+                };
+                new_events.push(Event {
+                    id: mcsim_common::EventId(ctx.next_event_id()),
+                    time: current_time,
+                    source: output.entity_id,
+                    targets: vec![output.attached_radio],
+                    payload,
+                });
+            }
         }
         
         // Handle serial TX data if any
@@ -196,6 +212,7 @@ mod tests {
                 coding_rate: 5,
                 tx_power_dbm: 20,
             },
+            sync_word: mcsim_common::DEFAULT_SYNC_WORD,
             end_time: SimTime::from_millis(100),
         })));
     }
@@ -8,10 +8,68 @@
 //! - **Speed multiplier**: Run faster or slower than real-time
 //! - **Catch-up logic**: Detect and handle when simulation falls behind
 //! - **Drift tracking**: Monitor simulation vs wall clock drift
+//! - **Pacing modes**: [`Pacing`] selects as fast as possible, real time or
+//!   a scaled clock, and can be changed while a run is in progress
 
+use std::fmt;
+use std::str::FromStr;
 use std::time::{Duration, Instant};
 use crate::SimTime;
 
+/// How simulation time advances relative to the wall clock.
+///
+/// Parsed from `fast`, `realtime` or a multiplier such as `10x` or `0.5`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pacing {
+    /// Process events as fast as possible.
+    Fast,
+    /// Advance simulation time at this multiple of the wall clock
+    /// (1.0 = real time).
+    Scaled(f64),
+}
+
+impl Pacing {
+    /// Real-time pacing: one simulated second per wall-clock second.
+    pub const REALTIME: Pacing = Pacing::Scaled(1.0);
+}
+
+impl FromStr for Pacing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "fast" | "asap" => Ok(Pacing::Fast),
+            "realtime" | "real-time" => Ok(Pacing::REALTIME),
+            other => {
+                let number = other.strip_suffix('x').unwrap_or(other);
+                match number.parse::<f64>() {
+                    Ok(speed) if speed.is_finite() && speed > 0.0 => Ok(Pacing::Scaled(speed)),
+                    _ => Err(format!(
+                        "Invalid pacing '{}' (expected fast, realtime or a positive multiplier like 10x)",
+                        s
+                    )),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Pacing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pacing::Fast => write!(f, "fast"),
+            Pacing::Scaled(speed) if *speed == 1.0 => write!(f, "realtime"),
+            Pacing::Scaled(speed) => write!(f, "{}x", speed),
+        }
+    }
+}
+
+impl serde::Serialize for Pacing {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 /// Configuration for real-time simulation mode.
 #[derive(Debug, Clone)]
 pub struct RealTimeConfig {
@@ -72,6 +130,29 @@ impl RealTimeConfig {
         }
     }
     
+    /// Apply `pacing`: [`Pacing::Fast`] disables pacing, a scaled pacing
+    /// enables it at that speed.
+    pub fn with_pacing(mut self, pacing: Pacing) -> Self {
+        match pacing {
+            Pacing::Fast => self.enabled = false,
+            Pacing::Scaled(speed) => {
+                assert!(speed > 0.0, "Speed multiplier must be positive");
+                self.enabled = true;
+                self.speed_multiplier = speed;
+            }
+        }
+        self
+    }
+    
+    /// The pacing this config applies.
+    pub fn pacing(&self) -> Pacing {
+        if self.enabled {
+            Pacing::Scaled(self.speed_multiplier)
+        } else {
+            Pacing::Fast
+        }
+    }
+    
     /// Set the maximum catch-up time before warning.
     pub fn with_max_catchup_ms(mut self, max_catchup_ms: u64) -> Self {
         self.max_catchup_ms = max_catchup_ms;
@@ -126,6 +207,20 @@ impl RealTimePacer {
         }
     }
     
+    /// Restart pacing from `start_sim`, e.g. after a pause, so the time spent
+    /// paused isn't caught up on. Drift statistics are kept.
+    pub fn restart(&mut self, start_sim: SimTime) {
+        self.start_wall = Instant::now();
+        self.start_sim = start_sim;
+    }
+    
+    /// Switch to `pacing`, restarting from `now_sim` so the new speed applies
+    /// from the current simulation time on.
+    pub fn set_pacing(&mut self, pacing: Pacing, now_sim: SimTime) {
+        self.config = self.config.clone().with_pacing(pacing);
+        self.restart(now_sim);
+    }
+    
     /// Calculate the target simulation time based on elapsed wall clock time.
     /// Returns the simulation time that should have been reached by now.
     pub fn target_sim_time(&self) -> SimTime {
@@ -368,6 +463,46 @@ mod tests {
         assert!(!config.enabled);
     }
     
+    #[test]
+    fn test_pacing_parse_and_display() {
+        assert_eq!("fast".parse(), Ok(Pacing::Fast));
+        assert_eq!("realtime".parse(), Ok(Pacing::REALTIME));
+        assert_eq!("10x".parse(), Ok(Pacing::Scaled(10.0)));
+        assert_eq!("0.5".parse(), Ok(Pacing::Scaled(0.5)));
+        assert!("0x".parse::<Pacing>().is_err());
+        assert!("-2".parse::<Pacing>().is_err());
+        assert!("slow".parse::<Pacing>().is_err());
+        
+        for pacing in [Pacing::Fast, Pacing::REALTIME, Pacing::Scaled(10.0), Pacing::Scaled(0.5)] {
+            assert_eq!(pacing.to_string().parse(), Ok(pacing));
+        }
+        assert_eq!(Pacing::REALTIME.to_string(), "realtime");
+    }
+    
+    #[test]
+    fn test_config_pacing() {
+        let config = RealTimeConfig::default().with_pacing(Pacing::Fast);
+        assert!(!config.enabled);
+        assert_eq!(config.pacing(), Pacing::Fast);
+        
+        let config = config.with_pacing(Pacing::Scaled(10.0));
+        assert!(config.enabled);
+        assert_eq!(config.speed_multiplier, 10.0);
+        assert_eq!(config.pacing(), Pacing::Scaled(10.0));
+    }
+    
+    #[test]
+    fn test_set_pacing_restarts_from_current_time() {
+        let mut pacer = RealTimePacer::new(RealTimeConfig::disabled(), SimTime::ZERO);
+        assert!(pacer.sleep_until_event(SimTime::from_secs(100.0)).is_none());
+        
+        pacer.set_pacing(Pacing::REALTIME, SimTime::from_secs(50.0));
+        assert!(pacer.target_sim_time() >= SimTime::from_secs(50.0));
+        assert!(pacer.target_sim_time() < SimTime::from_secs(51.0));
+        let wait = pacer.sleep_until_event(SimTime::from_secs(60.0)).expect("paced");
+        assert!(wait > Duration::from_secs(9) && wait <= Duration::from_secs(10));
+    }
+    
     #[test]
     fn test_pacer_target_time() {
         let config = RealTimeConfig::with_speed(1.0);
@@ -8,6 +8,7 @@
 //! dependency for faster compile times during development.
 
 use mcsim_common::{Event, GeoCoord, SimTime};
+use mcsim_model::{Model, LINK_MEAN_SNR_DB_AT20DBM};
 
 /// Information about a node for visualization purposes.
 #[derive(Debug, Clone)]
@@ -39,6 +40,87 @@ pub struct VisLinkInfo {
     pub mean_snr_db_at20dbm: f64,
 }
 
+/// Build link info from the model's edges between the given nodes.
+pub fn vis_links(model: &Model, nodes: &[VisNodeInfo]) -> Vec<VisLinkInfo> {
+    let locations: std::collections::HashMap<&str, &GeoCoord> =
+        nodes.iter().map(|n| (n.name.as_str(), &n.location)).collect();
+    model
+        .edges()
+        .values()
+        .filter_map(|edge| {
+            Some(VisLinkInfo {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                from_location: (*locations.get(edge.from.as_str())?).clone(),
+                to_location: (*locations.get(edge.to.as_str())?).clone(),
+                mean_snr_db_at20dbm: edge.properties().get(&LINK_MEAN_SNR_DB_AT20DBM),
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Viewer helpers
+// ============================================================================
+
+/// Find the rerun executable in the following order:
+/// 1. In a "rerun" folder relative to the current executable
+/// 2. In a "rerun" folder relative to the current working directory
+/// 3. Return None to fall back to PATH lookup
+pub fn find_rerun_executable() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    const RERUN_EXE: &str = "rerun.exe";
+    #[cfg(not(target_os = "windows"))]
+    const RERUN_EXE: &str = "rerun";
+
+    // Try relative to current executable
+    if let Ok(exe_path) = std::env::current_exe() {
+        // Go up from target/release or target/debug to the workspace root
+        let mut path = exe_path;
+        for _ in 0..3 {
+            // Try up to 3 levels up (exe -> release/debug -> target -> workspace)
+            if let Some(parent) = path.parent() {
+                let rerun_path = parent.join("rerun").join(RERUN_EXE);
+                if rerun_path.exists() {
+                    eprintln!("Rerun: Found executable at {:?}", rerun_path);
+                    return Some(rerun_path.to_string_lossy().to_string());
+                }
+                path = parent.to_path_buf();
+            }
+        }
+    }
+
+    // Try relative to current working directory
+    if let Ok(cwd) = std::env::current_dir() {
+        let rerun_path = cwd.join("rerun").join(RERUN_EXE);
+        if rerun_path.exists() {
+            eprintln!("Rerun: Found executable at {:?}", rerun_path);
+            return Some(rerun_path.to_string_lossy().to_string());
+        }
+    }
+
+    eprintln!("Rerun: No local executable found, falling back to PATH lookup");
+    None
+}
+
+/// Open a saved recording (`.rrd`) in the Rerun viewer for playback.
+///
+/// The viewer's timeline provides scrubbing and playback over simulation
+/// time. With `web_viewer` set, the recording is served to a browser instead
+/// of opening the native viewer. Blocks until the viewer exits.
+pub fn open_recording(path: &std::path::Path, web_viewer: bool) -> std::io::Result<()> {
+    let exe = find_rerun_executable().unwrap_or_else(|| "rerun".to_string());
+    let mut cmd = std::process::Command::new(exe);
+    if web_viewer {
+        cmd.arg("--web-viewer");
+    }
+    let status = cmd.arg(path).status()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("rerun viewer exited with {}", status)));
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Real implementation (when rerun feature is enabled)
 // ============================================================================
@@ -47,6 +129,7 @@ pub struct VisLinkInfo {
 mod real_impl {
     use super::*;
     use crate::metric_spec::{CounterValue, GaugeValue, HistogramValue, MetricValue};
+    use crate::trace_playback::{PlaybackKind, PlaybackPacket, ReceptionStatus};
     use mcsim_common::EventPayload;
     use meshcore_packet::{MeshCorePacket, PacketPayload};
     use std::collections::HashMap;
@@ -183,18 +266,35 @@ mod real_impl {
             nodes: Vec<VisNodeInfo>,
             links: Vec<VisLinkInfo>,
         ) -> Result<Self, Box<dyn std::error::Error>> {
-            // Try to find rerun executable in the following order:
-            // 1. In a "rerun" folder relative to the current executable
-            // 2. In a "rerun" folder relative to the current working directory
-            // 3. Fall back to PATH lookup (default behavior)
-            let rerun_exe_path = Self::find_rerun_executable();
-
-            // Initialize rerun with spawn options
-            let spawn_opts = rerun::SpawnOptions {
-                executable_path: rerun_exe_path,
-                ..Default::default()
+            Self::with_save_path(app_name, nodes, links, None)
+        }
+
+        /// Create a new RerunLogger that either spawns the viewer or, when
+        /// `save_path` is set, writes the recording to a `.rrd` file for
+        /// later playback with [`open_recording`](super::open_recording).
+        pub fn with_save_path(
+            app_name: &str,
+            nodes: Vec<VisNodeInfo>,
+            links: Vec<VisLinkInfo>,
+            save_path: Option<&std::path::Path>,
+        ) -> Result<Self, Box<dyn std::error::Error>> {
+            let rec = match save_path {
+                Some(path) => rerun::RecordingStreamBuilder::new(app_name).save(path)?,
+                None => {
+                    // Try to find rerun executable in the following order:
+                    // 1. In a "rerun" folder relative to the current executable
+                    // 2. In a "rerun" folder relative to the current working directory
+                    // 3. Fall back to PATH lookup (default behavior)
+                    let rerun_exe_path = find_rerun_executable();
+
+                    // Initialize rerun with spawn options
+                    let spawn_opts = rerun::SpawnOptions {
+                        executable_path: rerun_exe_path,
+                        ..Default::default()
+                    };
+                    rerun::RecordingStreamBuilder::new(app_name).spawn_opts(&spawn_opts)?
+                }
             };
-            let rec = rerun::RecordingStreamBuilder::new(app_name).spawn_opts(&spawn_opts)?;
 
             let mut logger = RerunLogger {
                 rec,
@@ -237,46 +337,6 @@ mod real_impl {
             Ok(logger)
         }
 
-        /// Find the rerun executable in the following order:
-        /// 1. In a "rerun" folder relative to the current executable
-        /// 2. In a "rerun" folder relative to the current working directory
-        /// 3. Return None to fall back to PATH lookup
-        fn find_rerun_executable() -> Option<String> {
-            #[cfg(target_os = "windows")]
-            const RERUN_EXE: &str = "rerun.exe";
-            #[cfg(not(target_os = "windows"))]
-            const RERUN_EXE: &str = "rerun";
-
-            // Try relative to current executable
-            if let Ok(exe_path) = std::env::current_exe() {
-                // Go up from target/release or target/debug to the workspace root
-                let mut path = exe_path;
-                for _ in 0..3 {
-                    // Try up to 3 levels up (exe -> release/debug -> target -> workspace)
-                    if let Some(parent) = path.parent() {
-                        let rerun_path = parent.join("rerun").join(RERUN_EXE);
-                        if rerun_path.exists() {
-                            eprintln!("Rerun: Found executable at {:?}", rerun_path);
-                            return Some(rerun_path.to_string_lossy().to_string());
-                        }
-                        path = parent.to_path_buf();
-                    }
-                }
-            }
-
-            // Try relative to current working directory
-            if let Ok(cwd) = std::env::current_dir() {
-                let rerun_path = cwd.join("rerun").join(RERUN_EXE);
-                if rerun_path.exists() {
-                    eprintln!("Rerun: Found executable at {:?}", rerun_path);
-                    return Some(rerun_path.to_string_lossy().to_string());
-                }
-            }
-
-            eprintln!("Rerun: No local executable found, falling back to PATH lookup");
-            None
-        }
-
         /// Log the initial state (node positions on map, link lines).
         /// 
         /// Uses a layered visualization approach:
@@ -684,37 +744,13 @@ mod real_impl {
             event: &Event,
             tx_event: &mcsim_common::TransmitAirEvent,
         ) -> Result<(), Box<dyn std::error::Error>> {
-            let current_time_us = event.time.as_micros();
-            
             if let Some(node) = self.entity_to_node.get(&tx_event.radio_id.0) {
                 let node_name = node.name.clone();
-                let node_lat = node.location.latitude;
-                let node_lon = node.location.longitude;
-                
-                // Track this node's highlight expiry
-                self.highlighted_nodes.insert(node_name.clone(), current_time_us + HIGHLIGHT_DURATION_US);
-                
-                // Update the inner state circle to show TX (bright red)
-                self.rec.log(
-                    format!("map/nodes/state/{}", sanitize_for_entity_path(&node_name)),
-                    &rerun::GeoPoints::from_lat_lon([(node_lat, node_lon)])
-                        .with_colors([[255, 50, 50, 255]]) // Bright red for TX
-                        .with_radii([rerun::Radius::new_ui_points(6.0)]),
-                )?;
-
-                // Decode packet for detailed logging
-                let packet_decode = format_packet_decode(&tx_event.packet.payload);
-
-                // Log transmission info as text with decoded packet details
-                // Format: size | packet decode | radio params (for alignment)
-                self.rec.log(
-                    format!("radio/{}/tx_info", sanitize_for_entity_path(&node_name)),
-                    &rerun::TextLog::new(format!(
-                        "TX: {:3}B | {} | {}dBm",
-                        tx_event.packet.payload.len(),
-                        packet_decode,
-                        tx_event.params.tx_power_dbm
-                    )),
+                self.show_transmit(
+                    &node_name,
+                    event.time.as_micros(),
+                    &tx_event.packet.payload,
+                    tx_event.params.tx_power_dbm,
                 )?;
             }
 
@@ -727,65 +763,158 @@ mod real_impl {
             event: &Event,
             rx_event: &mcsim_common::RadioRxPacketEvent,
         ) -> Result<(), Box<dyn std::error::Error>> {
-            let current_time_us = event.time.as_micros();
-            
             // Find the transmitting node name from the source radio ID
             let tx_node_name = self.radio_id_to_name.get(&rx_event.source_radio_id.0).cloned();
-            
-            // Find which node received this
-            for target in &event.targets {
-                if let Some(node) = self.entity_to_node.get(&target.0) {
-                    // Determine status and color based on reception outcome
-                    let (status, color): (&str, [u8; 4]) = if rx_event.was_collided {
-                        ("COLLISION", [255, 140, 0, 255]) // Dark orange for collision
-                    } else if rx_event.was_weak_signal {
-                        ("WEAK", [255, 80, 80, 255]) // Red for weak signal
-                    } else {
-                        ("OK", [50, 255, 50, 255]) // Bright green for successful RX
-                    };
 
-                    // Clone data we need before mutable borrow
-                    let node_name = node.name.clone();
-                    let node_lat = node.location.latitude;
-                    let node_lon = node.location.longitude;
+            let status = if rx_event.was_collided {
+                ReceptionStatus::Collided
+            } else if rx_event.was_weak_signal {
+                ReceptionStatus::Weak
+            } else if rx_event.was_corrupted {
+                ReceptionStatus::Corrupted
+            } else {
+                ReceptionStatus::Ok
+            };
 
-                    // Track this node's highlight expiry
-                    self.highlighted_nodes.insert(node_name.clone(), current_time_us + HIGHLIGHT_DURATION_US);
+            // Find which node received this (only log for first matching target)
+            let receiver = event
+                .targets
+                .iter()
+                .find_map(|target| self.entity_to_node.get(&target.0))
+                .map(|node| node.name.clone());
+            if let Some(node_name) = receiver {
+                self.show_receive(
+                    &node_name,
+                    tx_node_name.as_deref(),
+                    event.time.as_micros(),
+                    &rx_event.packet.payload,
+                    rx_event.snr_db,
+                    rx_event.rssi_dbm,
+                    status,
+                )?;
+            }
 
-                    // Update the inner state circle to show RX state
-                    self.rec.log(
-                        format!("map/nodes/state/{}", sanitize_for_entity_path(&node_name)),
-                        &rerun::GeoPoints::from_lat_lon([(node_lat, node_lon)])
-                            .with_colors([color])
-                            .with_radii([rerun::Radius::new_ui_points(6.0)]),
-                    )?;
+            Ok(())
+        }
 
-                    // Decode packet for detailed logging
-                    let packet_decode = format_packet_decode(&rx_event.packet.payload);
+        /// Log a packet from a finished run's trace at its simulation time.
+        pub fn log_playback(&mut self, packet: &PlaybackPacket) -> Result<(), Box<dyn std::error::Error>> {
+            let current_time_us = packet.time.as_micros();
+            self.rec.set_time("sim_time", std::time::Duration::from_micros(current_time_us));
+            let _ = self.reset_expired_highlights(current_time_us);
 
-                    // Log reception info with decoded packet details
-                    // Format: size | packet decode | radio params (for alignment)
-                    self.rec.log(
-                        format!("radio/{}/rx_info", sanitize_for_entity_path(&node_name)),
-                        &rerun::TextLog::new(format!(
-                            "RX: {:3}B | {} | SNR:{:5.1}dB RSSI:{:6.1}dBm [{}]",
-                            rx_event.packet.payload.len(),
-                            packet_decode,
-                            rx_event.snr_db,
-                            rx_event.rssi_dbm,
-                            status
-                        )),
-                    )?;
+            match &packet.kind {
+                PlaybackKind::Transmit { tx_power_dbm } => {
+                    self.show_transmit(&packet.node, current_time_us, &packet.packet, *tx_power_dbm)
+                }
+                PlaybackKind::Receive { from, snr_db, rssi_dbm, status } => self.show_receive(
+                    &packet.node,
+                    from.as_deref(),
+                    current_time_us,
+                    &packet.packet,
+                    *snr_db,
+                    *rssi_dbm,
+                    *status,
+                ),
+            }
+        }
 
-                    // Highlight the link between transmitter and receiver (only for successful receptions)
-                    if let Some(ref tx_name) = tx_node_name {
-                        if !rx_event.was_collided && !rx_event.was_weak_signal {
-                            let _ = self.highlight_link(tx_name, &node_name, current_time_us);
-                        }
-                    }
+        /// Show a node transmitting a packet.
+        fn show_transmit(
+            &mut self,
+            node_name: &str,
+            current_time_us: u64,
+            payload: &[u8],
+            tx_power_dbm: f64,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let Some(pos) = self.node_positions.get(node_name).cloned() else {
+                return Ok(());
+            };
 
-                    break; // Only log for first matching target
-                }
+            // Track this node's highlight expiry
+            self.highlighted_nodes.insert(node_name.to_string(), current_time_us + HIGHLIGHT_DURATION_US);
+
+            // Update the inner state circle to show TX (bright red)
+            self.rec.log(
+                format!("map/nodes/state/{}", sanitize_for_entity_path(node_name)),
+                &rerun::GeoPoints::from_lat_lon([(pos.latitude, pos.longitude)])
+                    .with_colors([[255, 50, 50, 255]]) // Bright red for TX
+                    .with_radii([rerun::Radius::new_ui_points(6.0)]),
+            )?;
+
+            // Decode packet for detailed logging
+            let packet_decode = format_packet_decode(payload);
+
+            // Log transmission info as text with decoded packet details
+            // Format: size | packet decode | radio params (for alignment)
+            self.rec.log(
+                format!("radio/{}/tx_info", sanitize_for_entity_path(node_name)),
+                &rerun::TextLog::new(format!(
+                    "TX: {:3}B | {} | {}dBm",
+                    payload.len(),
+                    packet_decode,
+                    tx_power_dbm
+                )),
+            )?;
+
+            Ok(())
+        }
+
+        /// Show a node receiving a packet, highlighting the link it crossed.
+        #[allow(clippy::too_many_arguments)]
+        fn show_receive(
+            &mut self,
+            node_name: &str,
+            tx_node_name: Option<&str>,
+            current_time_us: u64,
+            payload: &[u8],
+            snr_db: f64,
+            rssi_dbm: f64,
+            status: ReceptionStatus,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let Some(pos) = self.node_positions.get(node_name).cloned() else {
+                return Ok(());
+            };
+
+            // Determine status and color based on reception outcome
+            let (label, color): (&str, [u8; 4]) = match status {
+                ReceptionStatus::Collided => ("COLLISION", [255, 140, 0, 255]), // Dark orange for collision
+                ReceptionStatus::Weak => ("WEAK", [255, 80, 80, 255]),          // Red for weak signal
+                ReceptionStatus::Corrupted => ("CORRUPT", [255, 215, 0, 255]),  // Gold for bit errors
+                ReceptionStatus::Ok => ("OK", [50, 255, 50, 255]),              // Bright green for successful RX
+            };
+
+            // Track this node's highlight expiry
+            self.highlighted_nodes.insert(node_name.to_string(), current_time_us + HIGHLIGHT_DURATION_US);
+
+            // Update the inner state circle to show RX state
+            self.rec.log(
+                format!("map/nodes/state/{}", sanitize_for_entity_path(node_name)),
+                &rerun::GeoPoints::from_lat_lon([(pos.latitude, pos.longitude)])
+                    .with_colors([color])
+                    .with_radii([rerun::Radius::new_ui_points(6.0)]),
+            )?;
+
+            // Decode packet for detailed logging
+            let packet_decode = format_packet_decode(payload);
+
+            // Log reception info with decoded packet details
+            // Format: size | packet decode | radio params (for alignment)
+            self.rec.log(
+                format!("radio/{}/rx_info", sanitize_for_entity_path(node_name)),
+                &rerun::TextLog::new(format!(
+                    "RX: {:3}B | {} | SNR:{:5.1}dB RSSI:{:6.1}dBm [{}]",
+                    payload.len(),
+                    packet_decode,
+                    snr_db,
+                    rssi_dbm,
+                    label
+                )),
+            )?;
+
+            // Highlight the link between transmitter and receiver (only for successful receptions)
+            if let (Some(tx_name), ReceptionStatus::Ok) = (tx_node_name, status) {
+                let _ = self.highlight_link(tx_name, node_name, current_time_us);
             }
 
             Ok(())
@@ -1055,7 +1184,7 @@ mod real_impl {
         if s.len() <= max_len {
             s.to_string()
         } else {
-            format!("{}...", &s[..max_len - 3])
+            format!("{}...", &s[..s.floor_char_boundary(max_len - 3)])
         }
     }
 }
@@ -1083,11 +1212,30 @@ mod stub_impl {
             Err("rerun feature is not enabled - build with --features rerun".into())
         }
 
+        /// Create a new RerunLogger with an optional save path (no-op stub).
+        /// Always returns an error since rerun is not enabled.
+        pub fn with_save_path(
+            _app_name: &str,
+            _nodes: Vec<VisNodeInfo>,
+            _links: Vec<VisLinkInfo>,
+            _save_path: Option<&std::path::Path>,
+        ) -> Result<Self, Box<dyn std::error::Error>> {
+            Err("rerun feature is not enabled - build with --features rerun".into())
+        }
+
         /// Log a simulation event (no-op).
         pub fn log_event(&mut self, _event: &Event) -> Result<(), Box<dyn std::error::Error>> {
             Ok(())
         }
 
+        /// Log a packet from a finished run's trace (no-op).
+        pub fn log_playback(
+            &mut self,
+            _packet: &crate::trace_playback::PlaybackPacket,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            Ok(())
+        }
+
         /// Log statistics update (no-op).
         pub fn log_stats(
             &self,
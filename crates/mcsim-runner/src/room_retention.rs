@@ -0,0 +1,249 @@
+//! Room server post retention measurements and room state.
+//!
+//! A room server keeps a bounded buffer of recent posts and replays them to
+//! clients when they reconnect. A client that stays away too long misses
+//! posts that were overwritten by newer ones or that expired. This module
+//! records when each room server receives a post and, at the end of the run,
+//! reports for each configured reconnect delay the fraction of posts that
+//! would still have been served to a client reconnecting that long after the
+//! post arrived.
+//!
+//! Posts are detected as intact text-message packets addressed to the room
+//! server (destination hash matches its public key), counted once per unique
+//! payload. The retention limits come from `room_server/max_posts` and
+//! `room_server/post_ttl_s`; the firmware's own buffer size is fixed at build
+//! time, so these settings describe the policy being evaluated rather than
+//! reconfiguring the firmware.
+//!
+//! The tracker also counts the distinct clients that send a login
+//! (anonymous request) to each room, so the runner can report every room's
+//! state — posts and clients — at the end of a run.
+
+use std::collections::{HashMap, HashSet};
+
+use meshcore_packet::{MeshCorePacket, PacketPayload, PayloadHash};
+use mcsim_metrics::{metric_defs, metrics};
+use mcsim_model::RoomRetention;
+use serde::Serialize;
+
+/// Posts and clients of one room server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoomState {
+    /// Room server node name.
+    pub name: String,
+    /// Unique posts the room received.
+    pub posts: usize,
+    /// Distinct clients that sent it a login.
+    pub clients: usize,
+}
+
+/// Post history of one room server.
+#[derive(Debug)]
+struct RoomHistory {
+    /// (node name, node type) used for metric labels.
+    labels: (String, String),
+    /// First byte of the room server's public key.
+    hash: u8,
+    retention: RoomRetention,
+    /// Arrival times of posts in microseconds, in arrival order.
+    post_times_us: Vec<u64>,
+    seen: HashSet<PayloadHash>,
+    /// Public keys of clients that sent a login.
+    clients: HashSet<[u8; 32]>,
+}
+
+/// Tracks posts received by room servers.
+#[derive(Debug, Default)]
+pub struct RoomRetentionTracker {
+    /// Room histories keyed by firmware entity ID.
+    rooms: HashMap<u64, RoomHistory>,
+}
+
+impl RoomRetentionTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a room server by firmware entity ID.
+    pub fn add_room(
+        &mut self,
+        firmware_entity_id: u64,
+        labels: (String, String),
+        public_key: &[u8; 32],
+        retention: RoomRetention,
+    ) {
+        self.rooms.insert(
+            firmware_entity_id,
+            RoomHistory {
+                labels,
+                hash: public_key[0],
+                retention,
+                post_times_us: Vec::new(),
+                seen: HashSet::new(),
+                clients: HashSet::new(),
+            },
+        );
+    }
+
+    /// Note an intact packet delivered to a firmware entity.
+    pub fn track_reception(&mut self, firmware_entity_id: u64, packet: &MeshCorePacket, time_us: u64) {
+        let Some(room) = self.rooms.get_mut(&firmware_entity_id) else {
+            return;
+        };
+        let labels = [("node", room.labels.0.clone()), ("node_type", room.labels.1.clone())];
+        match &packet.payload {
+            PacketPayload::TextMessage(msg) => {
+                if msg.header.dest_hash != room.hash || !room.seen.insert(packet.payload_hash_label()) {
+                    return;
+                }
+                room.post_times_us.push(time_us);
+                metrics::counter!(metric_defs::ROOM_POSTS.name, &labels).increment(1);
+            }
+            PacketPayload::AnonRequest(login) => {
+                if login.dest_hash != room.hash || !room.clients.insert(login.public_key) {
+                    return;
+                }
+                metrics::gauge!(metric_defs::ROOM_CLIENTS.name, &labels).set(room.clients.len() as f64);
+            }
+            _ => {}
+        }
+    }
+
+    /// State of every room server, sorted by name.
+    pub fn room_states(&self) -> Vec<RoomState> {
+        let mut states: Vec<RoomState> = self
+            .rooms
+            .values()
+            .map(|room| RoomState {
+                name: room.labels.0.clone(),
+                posts: room.post_times_us.len(),
+                clients: room.clients.len(),
+            })
+            .collect();
+        states.sort_by(|a, b| a.name.cmp(&b.name));
+        states
+    }
+
+    /// Emit availability gauges for every room server and reconnect delay.
+    ///
+    /// Posts whose reconnect time falls after `end_time_us` are not counted,
+    /// since the run never observed whether they were still retained.
+    pub fn finish(&self, end_time_us: u64) {
+        for room in self.rooms.values() {
+            for &delay_s in &room.retention.reconnect_delays_s {
+                let Some(availability) =
+                    availability(&room.post_times_us, &room.retention, delay_s, end_time_us)
+                else {
+                    continue;
+                };
+                let labels = [
+                    ("node", room.labels.0.clone()),
+                    ("node_type", room.labels.1.clone()),
+                    ("reconnect_delay_s", format!("{}", delay_s)),
+                ];
+                metrics::gauge!(metric_defs::ROOM_POST_AVAILABILITY.name, &labels).set(availability);
+            }
+        }
+    }
+}
+
+/// Fraction of posts still retained `delay_s` after they arrived.
+///
+/// A post is retained while fewer than `max_posts` newer posts have arrived
+/// and, if a TTL is set, while it is younger than the TTL. `post_times_us` must
+/// be sorted. Returns `None` if no post can be evaluated before `end_time_us`.
+pub fn availability(
+    post_times_us: &[u64],
+    retention: &RoomRetention,
+    delay_s: f64,
+    end_time_us: u64,
+) -> Option<f64> {
+    let delay_us = (delay_s * 1_000_000.0) as u64;
+    let expired = retention.post_ttl_s.is_some_and(|ttl| delay_s >= ttl);
+    let mut evaluated = 0usize;
+    let mut available = 0usize;
+    for (i, &posted) in post_times_us.iter().enumerate() {
+        let reconnect = posted.saturating_add(delay_us);
+        if reconnect > end_time_us {
+            break;
+        }
+        evaluated += 1;
+        let newer = post_times_us[i + 1..].partition_point(|&t| t <= reconnect);
+        if !expired && newer < retention.max_posts {
+            available += 1;
+        }
+    }
+    (evaluated > 0).then(|| available as f64 / evaluated as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retention(max_posts: usize, post_ttl_s: Option<f64>) -> RoomRetention {
+        RoomRetention {
+            max_posts,
+            post_ttl_s,
+            reconnect_delays_s: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_availability_limited_by_buffer() {
+        // One post per second for ten seconds
+        let posts: Vec<u64> = (0..10).map(|s| s * 1_000_000).collect();
+        let end = 100_000_000;
+
+        // A client back after 2.5s misses nothing with a 3-post buffer
+        assert_eq!(availability(&posts, &retention(3, None), 2.5, end), Some(1.0));
+        // With a 2-post buffer, every post followed by two more is overwritten
+        assert_eq!(availability(&posts, &retention(2, None), 2.5, end), Some(0.2));
+        // Long absences only catch the last max_posts posts
+        assert_eq!(availability(&posts, &retention(3, None), 60.0, end), Some(0.3));
+    }
+
+    #[test]
+    fn test_availability_ttl_and_censoring() {
+        let posts = vec![0, 1_000_000, 2_000_000];
+
+        assert_eq!(availability(&posts, &retention(32, Some(30.0)), 10.0, 60_000_000), Some(1.0));
+        assert_eq!(availability(&posts, &retention(32, Some(30.0)), 30.0, 60_000_000), Some(0.0));
+        // Only the first post can be checked before the run ends
+        assert_eq!(availability(&posts, &retention(1, None), 10.0, 10_500_000), Some(0.0));
+        assert_eq!(availability(&posts, &retention(1, None), 100.0, 60_000_000), None);
+    }
+
+    #[test]
+    fn test_room_state_counts_posts_and_clients() {
+        use meshcore_packet::{AnonRequestPayload, RouteType};
+
+        let mut tracker = RoomRetentionTracker::new();
+        tracker.add_room(7, ("Room".to_string(), "RoomServer".to_string()), &[0x12; 32], retention(32, None));
+        let login = |client: u8, dest_hash: u8| {
+            MeshCorePacket::new(
+                RouteType::Flood,
+                PacketPayload::AnonRequest(AnonRequestPayload {
+                    dest_hash,
+                    public_key: [client; 32],
+                    mac: 0,
+                    ciphertext: vec![1, 2, 3],
+                }),
+            )
+        };
+
+        tracker.track_reception(7, &login(1, 0x12), 1);
+        // The same client logging in again, and a login for another node
+        tracker.track_reception(7, &login(1, 0x12), 2);
+        tracker.track_reception(7, &login(2, 0x99), 3);
+        tracker.track_reception(7, &login(2, 0x12), 4);
+        let post = MeshCorePacket::text_message(0x12, 0x34, 0x5678, vec![1, 2, 3, 4]);
+        tracker.track_reception(7, &post, 5);
+        tracker.track_reception(7, &post, 6);
+
+        assert_eq!(
+            tracker.room_states(),
+            vec![RoomState { name: "Room".to_string(), posts: 1, clients: 2 }]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Hot reload of agent scripts.
+//!
+//! Agent behavior is scripted by the `traffic` section of the model (see
+//! [`mcsim_model::traffic`]). Iterating on it shouldn't mean restarting a
+//! long simulation of a large network, so a [`ScriptReloader`] re-reads the
+//! model files while the run is in progress and hands every companion's
+//! agent its new rules, either on demand (`reload` at the console) or, with
+//! `mcsim run --watch-scripts`, whenever a model file changes on disk.
+//!
+//! Only the `traffic` section is taken from the reloaded files; nodes,
+//! links and every other setting stay as they were built. A file that no
+//! longer parses leaves the running rules untouched.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use mcsim_model::{ModelError, TrafficRule};
+
+/// How often watched files are checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Model files agent scripts are reloaded from.
+#[derive(Debug)]
+pub struct ScriptReloader {
+    paths: Vec<PathBuf>,
+    watch: bool,
+    modified: Vec<Option<SystemTime>>,
+    last_check: Instant,
+}
+
+impl ScriptReloader {
+    /// Reload scripts from `paths` (merged like the model files of a run).
+    /// With `watch`, [`changed`](Self::changed) reports edits to them.
+    pub fn new(paths: Vec<PathBuf>, watch: bool) -> Self {
+        let modified = paths.iter().map(|p| modified(p)).collect();
+        ScriptReloader {
+            paths,
+            watch,
+            modified,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Whether a watched file changed since the last check. Files are
+    /// checked at most once a second; always false when not watching.
+    pub fn changed(&mut self) -> bool {
+        if !self.watch || self.last_check.elapsed() < WATCH_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+        let modified: Vec<Option<SystemTime>> = self.paths.iter().map(|p| modified(p)).collect();
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+
+    /// Read the traffic rules from the model files.
+    pub fn load(&self) -> Result<Vec<TrafficRule>, ModelError> {
+        let paths: Vec<&Path> = self.paths.iter().map(PathBuf::as_path).collect();
+        Ok(mcsim_model::load_models(&paths)?.traffic().to_vec())
+    }
+}
+
+/// Modification time of a file, if it can be read.
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reloader_reads_traffic() {
+        let path = std::env::temp_dir().join(format!("mcsim-script-reload-{}.yaml", std::process::id()));
+        let model = |text: &str| {
+            format!(
+                "nodes:\n  - name: Alice\n    location: {{ lat: 47.6, lon: -122.4 }}\n    firmware: {{ type: Companion }}\n  - name: Bob\n    location: {{ lat: 47.7, lon: -122.4 }}\n    firmware: {{ type: Companion }}\ntraffic:\n  - node: Alice\n    send_dm: Bob\n    text: \"{}\"\n",
+                text
+            )
+        };
+        std::fs::write(&path, model("one")).unwrap();
+        let mut reloader = ScriptReloader::new(vec![path.clone()], false);
+        assert!(!reloader.changed());
+        assert_eq!(reloader.load().unwrap()[0].text.as_deref(), Some("one"));
+
+        std::fs::write(&path, model("two")).unwrap();
+        assert_eq!(reloader.load().unwrap()[0].text.as_deref(), Some("two"));
+
+        std::fs::write(&path, "nodes: [").unwrap();
+        assert!(reloader.load().is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
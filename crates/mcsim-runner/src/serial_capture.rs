@@ -0,0 +1,201 @@
+//! Time-ordered serial capture across nodes.
+//!
+//! Writes every chunk of serial traffic for a selected set of nodes into a
+//! single JSON Lines file, one object per chunk, in simulation-time order.
+//! Because all nodes share one file, a multi-node exchange such as a companion
+//! logging into a room server or a path discovery reads top to bottom without
+//! merging per-node logs.
+//!
+//! Each line records the simulation time, the node, the direction relative to
+//! the node (`to_node` for host → firmware, `from_node` for firmware → host),
+//! the bytes as hex and a printable rendering for text protocols:
+//!
+//! ```json
+//! {"time_s":12.000100,"node":"Alice","direction":"to_node","len":2,"hex":"1601","text":".."}
+//! ```
+//!
+//! With a [`WallClock`], each line also has the local datetime as `wall_time`.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use mcsim_common::{Event, EventPayload};
+use serde::Serialize;
+
+use crate::wall_clock::WallClock;
+
+/// Direction of a serial chunk relative to the captured node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SerialDirection {
+    /// Host (TCP client or agent) to firmware.
+    ToNode,
+    /// Firmware to host.
+    FromNode,
+}
+
+/// One captured chunk of serial traffic.
+#[derive(Debug, Clone, Serialize)]
+pub struct SerialCaptureEntry<'a> {
+    /// Simulation time in seconds.
+    pub time_s: f64,
+    /// Local datetime, if a wall clock is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wall_time: Option<String>,
+    /// Node name.
+    pub node: &'a str,
+    /// Direction relative to the node.
+    pub direction: SerialDirection,
+    /// Number of bytes.
+    pub len: usize,
+    /// Bytes as lowercase hex.
+    pub hex: String,
+    /// Printable ASCII rendering, with other bytes shown as `.`.
+    pub text: String,
+}
+
+/// Writes serial traffic of selected firmware entities to one stream.
+pub struct SerialCapture {
+    /// Captured firmware entity IDs and their node names.
+    nodes: HashMap<u64, String>,
+    output: Box<dyn Write>,
+    wall_clock: Option<WallClock>,
+}
+
+impl SerialCapture {
+    /// Create a capture with no nodes selected.
+    pub fn new(output: Box<dyn Write>) -> Self {
+        SerialCapture {
+            nodes: HashMap::new(),
+            output,
+            wall_clock: None,
+        }
+    }
+
+    /// Add the local datetime of each chunk.
+    pub fn set_wall_clock(&mut self, clock: WallClock) {
+        self.wall_clock = Some(clock);
+    }
+
+    /// Capture serial traffic of a firmware entity.
+    pub fn add_node(&mut self, firmware_entity_id: u64, name: String) {
+        self.nodes.insert(firmware_entity_id, name);
+    }
+
+    /// Write the serial traffic carried by an event, if any.
+    ///
+    /// Firmware output is posted once to the firmware itself (for the UART
+    /// bridge) and again to any attached agent; only the self-targeted copy is
+    /// written so each chunk appears once.
+    pub fn record(&mut self, event: &Event) -> io::Result<()> {
+        match &event.payload {
+            EventPayload::SerialTx(tx) if event.targets.contains(&event.source) => {
+                if let Some(node) = self.nodes.get(&event.source.0) {
+                    let entry = entry(event, self.wall_clock.as_ref(), node, SerialDirection::FromNode, &tx.data);
+                    write_entry(&mut self.output, &entry)?;
+                }
+            }
+            EventPayload::SerialRx(rx) => {
+                for target in &event.targets {
+                    if let Some(node) = self.nodes.get(&target.0) {
+                        let entry = entry(event, self.wall_clock.as_ref(), node, SerialDirection::ToNode, &rx.data);
+                        write_entry(&mut self.output, &entry)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Flush buffered output.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+fn entry<'a>(
+    event: &Event,
+    wall_clock: Option<&WallClock>,
+    node: &'a str,
+    direction: SerialDirection,
+    data: &[u8],
+) -> SerialCaptureEntry<'a> {
+    SerialCaptureEntry {
+        time_s: event.time.as_secs_f64(),
+        wall_time: wall_clock.map(|clock| clock.format(event.time)),
+        node,
+        direction,
+        len: data.len(),
+        hex: hex::encode(data),
+        text: data
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect(),
+    }
+}
+
+fn write_entry(output: &mut dyn Write, entry: &SerialCaptureEntry<'_>) -> io::Result<()> {
+    serde_json::to_writer(&mut *output, entry)?;
+    output.write_all(b"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{EntityId, EventId, SerialRxEvent, SerialTxEvent, SimTime};
+    use std::sync::{Arc, Mutex};
+
+    /// Writer that keeps its bytes readable after being boxed.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn event(source: u64, targets: &[u64], payload: EventPayload) -> Event {
+        Event {
+            id: EventId(0),
+            time: SimTime::from_micros(1_500_000),
+            source: EntityId::new(source),
+            targets: targets.iter().map(|&t| EntityId::new(t)).collect(),
+            payload,
+        }
+    }
+
+    #[test]
+    fn test_captures_selected_nodes_once() {
+        let buf = SharedBuf::default();
+        let mut capture = SerialCapture::new(Box::new(buf.clone()));
+        capture.add_node(1, "Alice".to_string());
+
+        // Agent 5 writes to Alice's firmware
+        let rx = EventPayload::SerialRx(SerialRxEvent { data: b"ver\r".to_vec() });
+        capture.record(&event(5, &[1], rx)).unwrap();
+        // Alice's reply goes to itself and to its agent; only one copy is kept
+        let tx = || EventPayload::SerialTx(SerialTxEvent { data: vec![0x00, b'o', b'k'] });
+        capture.record(&event(1, &[1], tx())).unwrap();
+        capture.record(&event(1, &[5], tx())).unwrap();
+        // Node 2 isn't captured
+        capture.record(&event(2, &[2], tx())).unwrap();
+
+        let text = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> =
+            text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["direction"], "to_node");
+        assert_eq!(lines[0]["node"], "Alice");
+        assert_eq!(lines[0]["text"], "ver.");
+        assert_eq!(lines[1]["direction"], "from_node");
+        assert_eq!(lines[1]["hex"], "006f6b");
+        assert_eq!(lines[1]["time_s"], 1.5);
+    }
+}
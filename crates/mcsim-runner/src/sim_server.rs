@@ -0,0 +1,896 @@
+//! Multi-tenant simulation server.
+//!
+//! `mcsim serve` hosts simulations for several people on one machine, so a
+//! team can share a large host for interactive experiments rather than each
+//! running their own. Clients speak the same line-delimited JSON-RPC 2.0 as
+//! the [`control_server`](crate::control_server) and name their tenant in
+//! every request:
+//!
+//! ```text
+//! → {"jsonrpc":"2.0","id":1,"method":"create","params":{"tenant":"alice","models":["sea.yaml"],"duration":3600,"paused":true}}
+//! ← {"jsonrpc":"2.0","id":1,"result":{"id":"alice-1","state":"starting","uart_base_port":10000,...}}
+//! → {"jsonrpc":"2.0","id":2,"method":"resume","params":{"tenant":"alice","sim":"alice-1"}}
+//! ```
+//!
+//! Server methods:
+//!
+//! - `create` (`tenant`, `models`: model file paths in the models directory
+//!   and/or `yaml`: model documents, optional `duration` in seconds, `seed`,
+//!   `trace`, `paused`):
+//!   start a simulation and return its [`SimulationInfo`];
+//! - `list` (optional `tenant`): the simulations, running or not;
+//! - `info` (`tenant`, `sim`): one simulation;
+//! - `kill` (`tenant`, `sim`): stop a simulation, killing it if it doesn't
+//!   end within a few seconds;
+//! - `remove` (`tenant`, `sim`): delete an ended simulation and its artifacts;
+//! - `quotas`: the limits and the running simulations of each tenant.
+//!
+//! Any other method with `tenant` and `sim` goes to that simulation's own
+//! control API, so `status`, `pause`, `resume`, `nodes`, `console`, `send`
+//! and `subscribe` work as with `mcsim run --control-listen`. Event
+//! notifications carry the simulation's id in `params.sim`. A tenant can
+//! only reach its own simulations.
+//!
+//! With [`ServerConfig::tenants`] set, only those tenants are served and
+//! every request naming one must carry its `token`; `list` then needs a
+//! tenant too. Without them any tenant name is taken on trust, which only
+//! suits a loopback address or a trusted network. Model files are read
+//! from [`ServerConfig::models_dir`] and can't name paths outside it;
+//! without one, simulations are created from inline `yaml` only.
+//!
+//! Each simulation is a separate `mcsim run` process (the metrics recorder
+//! is global to a process) with a directory of its own under the data
+//! directory: the inline models, `stats.json`, `metrics.csv`, `run.log`
+//! (its stderr) and, with `trace`, `trace.json`. Simulations get disjoint
+//! blocks of UART ports. [`ServerQuotas`] limit how many run at once, in
+//! all and per tenant, their simulated duration, their wall-clock time and
+//! the size of their artifacts.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::control_server::{
+    error, LineWriter, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, NO_ANSWER, PARSE_ERROR, REPLY_TIMEOUT,
+};
+
+/// A quota does not allow the request.
+const QUOTA_EXCEEDED: i64 = -32001;
+/// No such simulation for the tenant.
+const UNKNOWN_SIMULATION: i64 = -32002;
+/// The simulation process could not be started.
+const LAUNCH_FAILED: i64 = -32003;
+/// The tenant is unknown or its token doesn't match.
+const UNAUTHORIZED: i64 = -32004;
+
+/// How often simulation processes are checked for exit and time limits.
+const MONITOR_INTERVAL: Duration = Duration::from_millis(200);
+/// How long a stopped simulation has to exit before it is killed.
+const STOP_GRACE: Duration = Duration::from_secs(5);
+/// Line a simulation logs once its control API is up, followed by the address.
+const CONTROL_READY: &str = "Accepting control requests on ";
+/// Longest tenant name.
+const MAX_TENANT_LEN: usize = 32;
+
+/// Limits on the simulations of a server. `None` is unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ServerQuotas {
+    /// Simulations running at once, across tenants.
+    pub max_simulations: Option<usize>,
+    /// Simulations running at once for one tenant.
+    pub max_per_tenant: Option<usize>,
+    /// Simulated duration of a simulation (seconds). When set, every
+    /// simulation needs a duration: realtime runs are refused.
+    pub max_duration_s: Option<f64>,
+    /// Wall-clock time a simulation may run before it is stopped (seconds).
+    pub max_wall_time_s: Option<f64>,
+    /// Combined size of a simulation's trace and capture files (bytes).
+    pub max_artifact_bytes: Option<u64>,
+}
+
+impl ServerQuotas {
+    /// Whether a simulation may start while `running` simulations run, of
+    /// which `running_for_tenant` are the requesting tenant's.
+    pub fn admit(
+        &self,
+        request: &SimulationRequest,
+        running: usize,
+        running_for_tenant: usize,
+    ) -> Result<(), String> {
+        if let Some(max) = self.max_simulations.filter(|&max| running >= max) {
+            return Err(format!("the server is running its limit of {} simulation(s)", max));
+        }
+        if let Some(max) = self.max_per_tenant.filter(|&max| running_for_tenant >= max) {
+            return Err(format!("tenant '{}' is running its limit of {} simulation(s)", request.tenant, max));
+        }
+        if let Some(max) = self.max_duration_s {
+            match request.duration_s {
+                None => return Err(format!("a duration of at most {}s is required", max)),
+                Some(duration) if duration > max => {
+                    return Err(format!("duration {}s is over the limit of {}s", duration, max));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Configuration of a simulation server.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// The `mcsim` executable simulations are run with.
+    pub exe: PathBuf,
+    /// Directory the simulations' artifacts are written under.
+    pub data_dir: PathBuf,
+    /// Limits on the simulations.
+    pub quotas: ServerQuotas,
+    /// First UART port of the first simulation.
+    pub uart_base_port: u16,
+    /// UART ports reserved for each simulation.
+    pub uart_ports_per_simulation: u16,
+    /// Token of each tenant allowed to use the server. Empty serves any
+    /// tenant without a token.
+    pub tenants: BTreeMap<String, String>,
+    /// Directory `create` reads model files from; paths that resolve outside
+    /// it, through `..` or symbolic links, are refused. None only accepts
+    /// inline model documents.
+    pub models_dir: Option<PathBuf>,
+}
+
+/// A request to start a simulation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationRequest {
+    /// Tenant starting it.
+    pub tenant: String,
+    /// Model files on the server, relative to its models directory.
+    pub models: Vec<PathBuf>,
+    /// Model YAML documents, merged after the files.
+    pub yaml: Vec<String>,
+    /// Simulated duration in seconds; None runs in realtime.
+    pub duration_s: Option<f64>,
+    /// Random seed.
+    pub seed: Option<u64>,
+    /// Whether to write an event trace.
+    pub trace: bool,
+    /// Whether to hold the simulation paused until a client resumes it.
+    pub paused: bool,
+}
+
+impl SimulationRequest {
+    /// Parse the params of a `create` request.
+    pub fn from_params(params: &Value) -> Result<Self, String> {
+        let tenant = tenant_param(params)?;
+        let strings = |name: &str| -> Result<Vec<String>, String> {
+            match params.get(name) {
+                None | Some(Value::Null) => Ok(Vec::new()),
+                Some(value) => {
+                    serde_json::from_value(value.clone()).map_err(|_| format!("{} must be a list of strings", name))
+                }
+            }
+        };
+        let models: Vec<PathBuf> = strings("models")?.into_iter().map(PathBuf::from).collect();
+        let yaml = strings("yaml")?;
+        if models.is_empty() && yaml.is_empty() {
+            return Err("create needs models or yaml".to_string());
+        }
+        let duration_s = match params.get("duration") {
+            None | Some(Value::Null) => None,
+            Some(value) => match value.as_f64() {
+                Some(duration) if duration > 0.0 => Some(duration),
+                _ => return Err("duration must be a positive number of seconds".to_string()),
+            },
+        };
+        let seed = match params.get("seed") {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(value.as_u64().ok_or("seed must be a non-negative integer")?),
+        };
+        let flag = |name: &str| params.get(name).and_then(Value::as_bool).unwrap_or(false);
+        Ok(SimulationRequest {
+            tenant,
+            models,
+            yaml,
+            duration_s,
+            seed,
+            trace: flag("trace"),
+            paused: flag("paused"),
+        })
+    }
+}
+
+/// The `tenant` param, checked to be safe as part of a file name.
+fn tenant_param(params: &Value) -> Result<String, String> {
+    let tenant = params.get("tenant").and_then(Value::as_str).ok_or("missing tenant")?;
+    let valid = !tenant.is_empty()
+        && tenant.len() <= MAX_TENANT_LEN
+        && tenant.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "tenant must be 1 to {} letters, digits, '-' or '_'",
+            MAX_TENANT_LEN
+        ));
+    }
+    Ok(tenant.to_string())
+}
+
+/// Whether a request's token is the tenant's, in time that doesn't depend
+/// on where they differ.
+fn token_matches(given: &str, expected: &str) -> bool {
+    let (given, expected) = (given.as_bytes(), expected.as_bytes());
+    let mut diff = given.len() ^ expected.len();
+    for (i, byte) in expected.iter().enumerate() {
+        diff |= (given.get(i).copied().unwrap_or(0) ^ byte) as usize;
+    }
+    diff == 0
+}
+
+/// Lifecycle of a simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulationState {
+    /// The process is starting; its control API isn't up yet.
+    Starting,
+    /// Accepting control requests.
+    Running,
+    /// Ended by itself with success.
+    Finished,
+    /// Ended by itself with an error (see the exit code and `run.log`).
+    Failed,
+    /// Ended by `kill` or a quota.
+    Stopped,
+}
+
+impl SimulationState {
+    /// Whether the process is still running.
+    pub fn is_active(self) -> bool {
+        matches!(self, SimulationState::Starting | SimulationState::Running)
+    }
+}
+
+/// What a client sees of a simulation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulationInfo {
+    /// Simulation id, `<tenant>-<n>`.
+    pub id: String,
+    /// Tenant that started it.
+    pub tenant: String,
+    pub state: SimulationState,
+    /// Directory of its artifacts.
+    pub dir: PathBuf,
+    /// Address of its control API, once up.
+    pub control_addr: Option<SocketAddr>,
+    /// First of its UART ports.
+    pub uart_base_port: u16,
+    /// Simulated duration in seconds; None in realtime.
+    pub duration_s: Option<f64>,
+    /// When it started, in seconds since the Unix epoch.
+    pub started_at: u64,
+    /// Exit code of the process once ended.
+    pub exit_code: Option<i32>,
+    /// Why it was stopped.
+    pub reason: Option<String>,
+}
+
+/// A simulation process and its bookkeeping.
+struct Simulation {
+    info: SimulationInfo,
+    child: Child,
+    /// Index of its block of UART ports.
+    slot: usize,
+    started: Instant,
+    stop_requested: Option<Instant>,
+}
+
+impl Simulation {
+    /// Ask the simulation to stop through its control API, or kill it if
+    /// that isn't up.
+    fn request_stop(&mut self, reason: &str) {
+        if self.stop_requested.is_some() {
+            return;
+        }
+        self.stop_requested = Some(Instant::now());
+        self.info.reason = Some(reason.to_string());
+        let stopped = self.info.control_addr.is_some_and(|addr| {
+            call(addr, &json!({ "jsonrpc": "2.0", "id": 0, "method": "stop" }))
+                .is_ok_and(|(reply, _)| reply.get("result").is_some())
+        });
+        if !stopped {
+            let _ = self.child.kill();
+        }
+    }
+}
+
+/// Simulations of a server.
+struct Registry {
+    config: ServerConfig,
+    simulations: Mutex<BTreeMap<String, Simulation>>,
+}
+
+impl Registry {
+    /// The tenant a request is made for, checked against its token when the
+    /// server has a list of tenants.
+    fn tenant(&self, params: &Value) -> Result<String, (i64, String)> {
+        let tenant = tenant_param(params).map_err(|e| (INVALID_PARAMS, e))?;
+        if self.config.tenants.is_empty() {
+            return Ok(tenant);
+        }
+        let token = params.get("token").and_then(Value::as_str).unwrap_or_default();
+        match self.config.tenants.get(&tenant) {
+            Some(expected) if token_matches(token, expected) => Ok(tenant),
+            _ => Err((UNAUTHORIZED, format!("unknown tenant '{}' or wrong token", tenant))),
+        }
+    }
+
+    /// Paths of a request's model files within the models directory.
+    fn model_paths(&self, models: &[PathBuf]) -> Result<Vec<PathBuf>, (i64, String)> {
+        if models.is_empty() {
+            return Ok(Vec::new());
+        }
+        let Some(dir) = &self.config.models_dir else {
+            return Err((INVALID_PARAMS, "this server only accepts inline yaml models".to_string()));
+        };
+        models
+            .iter()
+            .map(|path| {
+                let outside = || {
+                    let message = format!("model path '{}' is outside the models directory", path.display());
+                    (INVALID_PARAMS, message)
+                };
+                if !path.components().all(|c| matches!(c, std::path::Component::Normal(_))) {
+                    return Err(outside());
+                }
+                // Symbolic links inside the directory may still lead out of it
+                let resolved = dir
+                    .join(path)
+                    .canonicalize()
+                    .map_err(|e| (INVALID_PARAMS, format!("model '{}': {}", path.display(), e)))?;
+                if !resolved.starts_with(dir) {
+                    return Err(outside());
+                }
+                Ok(resolved)
+            })
+            .collect()
+    }
+
+    /// Start a simulation.
+    fn create(self: &Arc<Self>, mut request: SimulationRequest) -> Result<SimulationInfo, (i64, String)> {
+        request.models = self.model_paths(&request.models)?;
+        let mut simulations = self.simulations.lock();
+        let active: Vec<&Simulation> = simulations.values().filter(|s| s.info.state.is_active()).collect();
+        let for_tenant = active.iter().filter(|s| s.info.tenant == request.tenant).count();
+        self.config.quotas.admit(&request, active.len(), for_tenant).map_err(|e| (QUOTA_EXCEEDED, e))?;
+
+        let slot = (0..).find(|slot| !active.iter().any(|s| s.slot == *slot)).unwrap_or_default();
+        let uart_base_port = u16::try_from(
+            self.config.uart_base_port as usize + slot * self.config.uart_ports_per_simulation as usize,
+        )
+        .map_err(|_| (QUOTA_EXCEEDED, "no free block of UART ports".to_string()))?;
+
+        // Ids continue past the directories of earlier servers
+        let mut n = simulations.len() + 1;
+        let (id, dir) = loop {
+            let id = format!("{}-{}", request.tenant, n);
+            let dir = self.config.data_dir.join(&id);
+            if !simulations.contains_key(&id) && !dir.exists() {
+                break (id, dir);
+            }
+            n += 1;
+        };
+        let launch_failed = |e: io::Error| (LAUNCH_FAILED, format!("cannot start the simulation: {}", e));
+        fs::create_dir_all(&dir).map_err(launch_failed)?;
+        let child = self.spawn(&request, &dir, uart_base_port).inspect_err(|_| {
+            let _ = fs::remove_dir_all(&dir);
+        });
+        let mut child = child.map_err(launch_failed)?;
+
+        if let Some(stderr) = child.stderr.take() {
+            let registry = Arc::downgrade(self);
+            let (id, log) = (id.clone(), dir.join("run.log"));
+            std::thread::spawn(move || watch_log(registry, id, stderr, log));
+        }
+        let info = SimulationInfo {
+            id: id.clone(),
+            tenant: request.tenant,
+            state: SimulationState::Starting,
+            dir,
+            control_addr: None,
+            uart_base_port,
+            duration_s: request.duration_s,
+            started_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            exit_code: None,
+            reason: None,
+        };
+        simulations.insert(
+            id,
+            Simulation { info: info.clone(), child, slot, started: Instant::now(), stop_requested: None },
+        );
+        Ok(info)
+    }
+
+    /// Launch `mcsim run` for a request in its directory.
+    fn spawn(&self, request: &SimulationRequest, dir: &std::path::Path, uart_base_port: u16) -> io::Result<Child> {
+        // The simulation runs in its directory, so model paths are resolved first
+        let mut models = request.models.iter().map(std::path::absolute).collect::<io::Result<Vec<_>>>()?;
+        for (i, yaml) in request.yaml.iter().enumerate() {
+            let path = dir.join(format!("model-{}.yaml", i + 1));
+            fs::write(&path, yaml)?;
+            models.push(path);
+        }
+        let mut command = Command::new(&self.config.exe);
+        command
+            .arg("run")
+            .args(&models)
+            .arg("--uart-base-port")
+            .arg(uart_base_port.to_string())
+            .arg("--control-listen")
+            .arg("127.0.0.1:0")
+            .arg("--metrics-output")
+            .arg("csv")
+            .arg("--metrics-file")
+            .arg(dir.join("metrics.csv"))
+            .arg("--metric")
+            .arg("mcsim.*");
+        if let Some(duration) = request.duration_s {
+            command.arg("--duration").arg(duration.to_string());
+        }
+        if let Some(seed) = request.seed {
+            command.arg("--seed").arg(seed.to_string());
+        }
+        if request.trace {
+            command.arg("--output").arg(dir.join("trace.json"));
+        }
+        if request.paused {
+            command.arg("--control-wait");
+        }
+        if let Some(bytes) = self.config.quotas.max_artifact_bytes {
+            command.arg("--max-artifact-size").arg(bytes.to_string());
+        }
+        command
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .stdout(fs::File::create(dir.join("stats.json"))?)
+            .stderr(Stdio::piped())
+            .spawn()
+    }
+
+    /// Info of a simulation the tenant owns.
+    fn owned(&self, params: &Value) -> Result<SimulationInfo, (i64, String)> {
+        let tenant = self.tenant(params)?;
+        let id = params.get("sim").and_then(Value::as_str).ok_or((INVALID_PARAMS, "missing sim".to_string()))?;
+        self.simulations
+            .lock()
+            .get(id)
+            .filter(|s| s.info.tenant == tenant)
+            .map(|s| s.info.clone())
+            .ok_or_else(|| (UNKNOWN_SIMULATION, format!("tenant '{}' has no simulation '{}'", tenant, id)))
+    }
+
+    fn list(&self, tenant: Option<&str>) -> Vec<SimulationInfo> {
+        self.simulations
+            .lock()
+            .values()
+            .filter(|s| tenant.is_none_or(|t| s.info.tenant == t))
+            .map(|s| s.info.clone())
+            .collect()
+    }
+
+    /// Record exits and stop simulations over their wall-clock limit.
+    fn monitor(&self) {
+        let max_wall = self.config.quotas.max_wall_time_s.map(Duration::from_secs_f64);
+        for simulation in self.simulations.lock().values_mut() {
+            if !simulation.info.state.is_active() {
+                continue;
+            }
+            match simulation.child.try_wait() {
+                Ok(Some(status)) => {
+                    simulation.info.exit_code = status.code();
+                    simulation.info.state = if simulation.stop_requested.is_some() {
+                        SimulationState::Stopped
+                    } else if status.success() {
+                        SimulationState::Finished
+                    } else {
+                        SimulationState::Failed
+                    };
+                }
+                Ok(None) => {
+                    if max_wall.is_some_and(|max| simulation.started.elapsed() > max) {
+                        simulation.request_stop("wall-clock time limit");
+                    }
+                    if simulation.stop_requested.is_some_and(|at| at.elapsed() > STOP_GRACE) {
+                        let _ = simulation.child.kill();
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+/// Copy a simulation's stderr to its log, noting when its control API is up.
+fn watch_log(registry: Weak<Registry>, id: String, stderr: impl io::Read, log: PathBuf) {
+    let mut log = fs::File::create(log).ok();
+    for line in BufReader::new(stderr).lines() {
+        let Ok(line) = line else { break };
+        if let Some(log) = log.as_mut() {
+            let _ = writeln!(log, "{}", line);
+        }
+        let Some(addr) = line.split(CONTROL_READY).nth(1).and_then(|a| a.trim().parse().ok()) else {
+            continue;
+        };
+        let Some(registry) = registry.upgrade() else { break };
+        let mut simulations = registry.simulations.lock();
+        if let Some(simulation) = simulations.get_mut(&id) {
+            simulation.info.control_addr = Some(addr);
+            if simulation.info.state == SimulationState::Starting {
+                simulation.info.state = SimulationState::Running;
+            }
+        }
+    }
+}
+
+/// Send one request to a simulation's control API and read the response,
+/// returning the open connection for any notifications that follow.
+fn call(addr: SocketAddr, request: &Value) -> io::Result<(Value, BufReader<TcpStream>)> {
+    let mut stream = TcpStream::connect_timeout(&addr, REPLY_TIMEOUT)?;
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+    writeln!(stream, "{}", request)?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let reply = serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((reply, reader))
+}
+
+/// A running simulation server.
+pub struct SimulationServer {
+    local_addr: SocketAddr,
+    registry: Arc<Registry>,
+}
+
+impl SimulationServer {
+    /// Bind `addr` and serve clients from background threads for the rest
+    /// of the process.
+    pub fn start(addr: &str, mut config: ServerConfig) -> io::Result<Self> {
+        fs::create_dir_all(&config.data_dir)?;
+        // Model paths are checked against the resolved directory
+        if let Some(dir) = &mut config.models_dir {
+            *dir = dir.canonicalize()?;
+        }
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let registry = Arc::new(Registry { config, simulations: Mutex::new(BTreeMap::new()) });
+
+        let weak = Arc::downgrade(&registry);
+        std::thread::Builder::new().name("sim-monitor".to_string()).spawn(move || {
+            while let Some(registry) = weak.upgrade() {
+                registry.monitor();
+                drop(registry);
+                std::thread::sleep(MONITOR_INTERVAL);
+            }
+        })?;
+        let clients = registry.clone();
+        std::thread::Builder::new().name("sim-server".to_string()).spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let registry = clients.clone();
+                // A failed connection only affects that client
+                let _ = std::thread::Builder::new()
+                    .name("sim-client".to_string())
+                    .spawn(move || handle_connection(stream, registry));
+            }
+        })?;
+        Ok(Self { local_addr, registry })
+    }
+
+    /// Address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Every simulation, running or not.
+    pub fn simulations(&self) -> Vec<SimulationInfo> {
+        self.registry.list(None)
+    }
+
+    /// Stop every running simulation, killing those that don't end in time.
+    pub fn shutdown(&self) {
+        for simulation in self.registry.simulations.lock().values_mut() {
+            if simulation.info.state.is_active() {
+                simulation.request_stop("server shutdown");
+            }
+        }
+        let deadline = Instant::now() + STOP_GRACE + MONITOR_INTERVAL;
+        while Instant::now() < deadline && self.simulations().iter().any(|s| s.state.is_active()) {
+            std::thread::sleep(MONITOR_INTERVAL);
+        }
+        for simulation in self.registry.simulations.lock().values_mut() {
+            if simulation.info.state.is_active() {
+                let _ = simulation.child.kill();
+                let _ = simulation.child.wait();
+                simulation.info.state = SimulationState::Stopped;
+            }
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, registry: Arc<Registry>) -> io::Result<()> {
+    let writer = LineWriter(Arc::new(Mutex::new(stream.try_clone()?)));
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => handle_request(&request, &registry, &writer),
+            Err(e) => error(Value::Null, PARSE_ERROR, &e.to_string()),
+        };
+        writer.send(&response)?;
+    }
+    Ok(())
+}
+
+/// Handle one request, returning its response.
+fn handle_request(request: &Value, registry: &Arc<Registry>, writer: &LineWriter) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return error(id, INVALID_REQUEST, "missing method");
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "create" => registry
+            .tenant(&params)
+            .and_then(|_| SimulationRequest::from_params(&params).map_err(|e| (INVALID_PARAMS, e)))
+            .and_then(|request| registry.create(request))
+            .map(|info| json!(info)),
+        "list" if params.get("tenant").is_none() && !registry.config.tenants.is_empty() => {
+            Err((INVALID_PARAMS, "missing tenant".to_string()))
+        }
+        "list" => match params.get("tenant") {
+            None => Ok(json!(registry.list(None))),
+            Some(_) => registry.tenant(&params).map(|tenant| json!(registry.list(Some(&tenant)))),
+        },
+        "info" => registry.owned(&params).map(|info| json!(info)),
+        "kill" => registry.owned(&params).map(|info| {
+            if let Some(simulation) = registry.simulations.lock().get_mut(&info.id) {
+                if simulation.info.state.is_active() {
+                    simulation.request_stop("killed by client");
+                }
+            }
+            json!(true)
+        }),
+        "remove" => registry.owned(&params).and_then(|info| {
+            let mut simulations = registry.simulations.lock();
+            if simulations.get(&info.id).is_some_and(|s| s.info.state.is_active()) {
+                return Err((INVALID_PARAMS, format!("simulation '{}' is still running", info.id)));
+            }
+            simulations.remove(&info.id);
+            let _ = fs::remove_dir_all(&info.dir);
+            Ok(json!(true))
+        }),
+        "quotas" => {
+            let mut tenants: BTreeMap<String, usize> = BTreeMap::new();
+            for info in registry.list(None).into_iter().filter(|s| s.state.is_active()) {
+                *tenants.entry(info.tenant).or_default() += 1;
+            }
+            Ok(json!({ "quotas": registry.config.quotas, "running": tenants }))
+        }
+        _ if params.get("sim").is_some() => {
+            return registry.owned(&params).map_or_else(
+                |(code, message)| error(id.clone(), code, &message),
+                |info| forward(request, &info, writer),
+            );
+        }
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+    };
+
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error(id, code, &message),
+    }
+}
+
+/// Pass a request to a simulation's control API and return its response.
+/// After a `subscribe`, its notifications are relayed to the client,
+/// tagged with the simulation's id.
+fn forward(request: &Value, info: &SimulationInfo, writer: &LineWriter) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(addr) = info.control_addr.filter(|_| info.state.is_active()) else {
+        return error(id, NO_ANSWER, &format!("simulation '{}' is not accepting control requests", info.id));
+    };
+    let mut request = request.clone();
+    if let Some(params) = request.get_mut("params").and_then(Value::as_object_mut) {
+        params.remove("tenant");
+        params.remove("token");
+        params.remove("sim");
+    }
+    let (reply, reader) = match call(addr, &request) {
+        Ok(answer) => answer,
+        Err(e) => return error(id, NO_ANSWER, &format!("no answer from simulation '{}': {}", info.id, e)),
+    };
+
+    if request["method"] == "subscribe" && reply.get("result").is_some() {
+        let (writer, sim) = (writer.clone(), info.id.clone());
+        std::thread::spawn(move || {
+            let _ = reader.get_ref().set_read_timeout(None);
+            for line in reader.lines() {
+                let Ok(mut notice) = line.and_then(|l| serde_json::from_str::<Value>(&l).map_err(io::Error::other))
+                else {
+                    break;
+                };
+                if let Some(params) = notice.get_mut("params").and_then(Value::as_object_mut) {
+                    params.insert("sim".to_string(), json!(sim));
+                }
+                // Closing the simulation's connection unsubscribes once the client is gone
+                if writer.send(&notice).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    reply
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(tenant: &str, duration_s: Option<f64>) -> SimulationRequest {
+        SimulationRequest { tenant: tenant.to_string(), duration_s, ..Default::default() }
+    }
+
+    #[test]
+    fn test_quota_admission() {
+        let quotas = ServerQuotas {
+            max_simulations: Some(4),
+            max_per_tenant: Some(2),
+            max_duration_s: Some(3600.0),
+            ..Default::default()
+        };
+        assert!(quotas.admit(&request("alice", Some(600.0)), 3, 1).is_ok());
+        assert!(quotas.admit(&request("alice", Some(600.0)), 4, 0).unwrap_err().contains("server"));
+        assert!(quotas.admit(&request("alice", Some(600.0)), 2, 2).unwrap_err().contains("alice"));
+        assert!(quotas.admit(&request("alice", None), 0, 0).is_err());
+        assert!(quotas.admit(&request("alice", Some(7200.0)), 0, 0).is_err());
+        assert!(ServerQuotas::default().admit(&request("alice", None), 100, 100).is_ok());
+
+        let params = json!({ "tenant": "bob", "yaml": ["nodes: []"], "duration": 60, "seed": 7, "paused": true });
+        let parsed = SimulationRequest::from_params(&params).unwrap();
+        assert_eq!(parsed.duration_s, Some(60.0));
+        assert_eq!(parsed.seed, Some(7));
+        assert!(parsed.paused && !parsed.trace);
+        assert!(SimulationRequest::from_params(&json!({ "tenant": "../etc", "models": ["a.yaml"] })).is_err());
+        assert!(SimulationRequest::from_params(&json!({ "tenant": "bob" })).is_err());
+    }
+
+    #[test]
+    fn test_server_requests() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let config = ServerConfig {
+            exe: data_dir.path().join("no-such-mcsim"),
+            data_dir: data_dir.path().join("sims"),
+            quotas: ServerQuotas { max_per_tenant: Some(1), ..Default::default() },
+            uart_base_port: 10000,
+            uart_ports_per_simulation: 100,
+            tenants: BTreeMap::new(),
+            models_dir: Some(data_dir.path().to_path_buf()),
+        };
+        let server = SimulationServer::start("127.0.0.1:0", config).unwrap();
+
+        let stream = TcpStream::connect(server.local_addr()).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let mut call = |request: &str| -> Value {
+            writeln!(writer, "{}", request).unwrap();
+            serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap()
+        };
+
+        let create = r#"{"jsonrpc":"2.0","id":1,"method":"create","params":{"tenant":"alice","models":["a.yaml"]}}"#;
+        assert_eq!(call(create)["error"]["code"], LAUNCH_FAILED);
+        assert!(server.simulations().is_empty());
+        // The failed launch leaves no directory behind
+        assert_eq!(fs::read_dir(data_dir.path().join("sims")).unwrap().count(), 0);
+
+        let list = call(r#"{"jsonrpc":"2.0","id":2,"method":"list"}"#);
+        assert_eq!(list["result"], json!([]));
+        let quotas = call(r#"{"jsonrpc":"2.0","id":3,"method":"quotas"}"#);
+        assert_eq!(quotas["result"]["quotas"]["max_per_tenant"], 1);
+        let status = call(r#"{"jsonrpc":"2.0","id":4,"method":"status","params":{"tenant":"bob","sim":"alice-1"}}"#);
+        assert_eq!(status["error"]["code"], UNKNOWN_SIMULATION);
+        let invalid = call(r#"{"jsonrpc":"2.0","id":5,"method":"create","params":{"tenant":"a b","models":["a"]}}"#);
+        assert_eq!(invalid["error"]["code"], INVALID_PARAMS);
+        assert_eq!(call(r#"{"jsonrpc":"2.0","id":6,"method":"status"}"#)["error"]["code"], METHOD_NOT_FOUND);
+        let outside = r#"{"jsonrpc":"2.0","id":7,"method":"create","params":{"tenant":"alice","models":["../a.yaml"]}}"#;
+        assert_eq!(call(outside)["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_tenant_tokens_and_models_dir() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let config = ServerConfig {
+            exe: data_dir.path().join("no-such-mcsim"),
+            data_dir: data_dir.path().join("sims"),
+            quotas: ServerQuotas::default(),
+            uart_base_port: 10000,
+            uart_ports_per_simulation: 100,
+            tenants: BTreeMap::from([("alice".to_string(), "s3cret".to_string())]),
+            models_dir: None,
+        };
+        let server = SimulationServer::start("127.0.0.1:0", config).unwrap();
+
+        let stream = TcpStream::connect(server.local_addr()).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let mut call = |request: &str| -> Value {
+            writeln!(writer, "{}", request).unwrap();
+            serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap()
+        };
+
+        let no_token = r#"{"jsonrpc":"2.0","id":1,"method":"create","params":{"tenant":"alice","yaml":["nodes: []"]}}"#;
+        assert_eq!(call(no_token)["error"]["code"], UNAUTHORIZED);
+        let wrong = r#"{"jsonrpc":"2.0","id":2,"method":"list","params":{"tenant":"alice","token":"guess"}}"#;
+        assert_eq!(call(wrong)["error"]["code"], UNAUTHORIZED);
+        let unknown = r#"{"jsonrpc":"2.0","id":3,"method":"info","params":{"tenant":"bob","token":"s3cret","sim":"alice-1"}}"#;
+        assert_eq!(call(unknown)["error"]["code"], UNAUTHORIZED);
+        assert_eq!(call(r#"{"jsonrpc":"2.0","id":4,"method":"list"}"#)["error"]["code"], INVALID_PARAMS);
+        let list = call(r#"{"jsonrpc":"2.0","id":5,"method":"list","params":{"tenant":"alice","token":"s3cret"}}"#);
+        assert_eq!(list["result"], json!([]));
+
+        // Without a models directory only inline models are accepted
+        let files =
+            r#"{"jsonrpc":"2.0","id":6,"method":"create","params":{"tenant":"alice","token":"s3cret","models":["a.yaml"]}}"#;
+        assert_eq!(call(files)["error"]["code"], INVALID_PARAMS);
+        let inline =
+            r#"{"jsonrpc":"2.0","id":7,"method":"create","params":{"tenant":"alice","token":"s3cret","yaml":["nodes: []"]}}"#;
+        assert_eq!(call(inline)["error"]["code"], LAUNCH_FAILED);
+    }
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("s3cret", "s3cret"));
+        assert!(!token_matches("s3cres", "s3cret"));
+        assert!(!token_matches("s3cre", "s3cret"));
+        assert!(!token_matches("s3cret!", "s3cret"));
+        assert!(!token_matches("", "s3cret"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_model_paths_stay_in_models_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let models = root.path().join("models");
+        fs::create_dir_all(models.join("sea")).unwrap();
+        fs::write(models.join("sea/a.yaml"), "nodes: []").unwrap();
+        fs::write(root.path().join("secret.yaml"), "nodes: []").unwrap();
+        std::os::unix::fs::symlink(root.path().join("secret.yaml"), models.join("link.yaml")).unwrap();
+
+        let registry = Registry {
+            config: ServerConfig {
+                exe: PathBuf::new(),
+                data_dir: root.path().join("sims"),
+                quotas: ServerQuotas::default(),
+                uart_base_port: 10000,
+                uart_ports_per_simulation: 100,
+                tenants: BTreeMap::new(),
+                models_dir: Some(models.canonicalize().unwrap()),
+            },
+            simulations: Mutex::new(BTreeMap::new()),
+        };
+        let paths = registry.model_paths(&[PathBuf::from("sea/a.yaml")]).unwrap();
+        assert_eq!(paths, [models.join("sea/a.yaml").canonicalize().unwrap()]);
+
+        for path in ["link.yaml", "../secret.yaml", "missing.yaml"] {
+            assert_eq!(registry.model_paths(&[PathBuf::from(path)]).unwrap_err().0, INVALID_PARAMS, "{}", path);
+        }
+        let absolute = root.path().join("secret.yaml");
+        assert!(registry.model_paths(&[absolute]).is_err());
+    }
+}
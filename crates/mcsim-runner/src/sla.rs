@@ -0,0 +1,244 @@
+//! Delivery SLA classification of node pairs.
+//!
+//! Latency histograms say how the network performs on average; operators
+//! want to know which pairs of nodes they can count on. `mcsim run
+//! --sla-report` tracks flood delivery (see [`crate::blast_radius`]) and
+//! sorts every ordered pair of nodes (origin, receiver) into one of three
+//! classes:
+//!
+//! - **met**: at least `min_delivery` of the origin's floods reached the
+//!   receiver within `max_latency`;
+//! - **best effort**: some floods got through, but not enough in time;
+//! - **unreachable**: none did.
+//!
+//! Floods sent in the last `max_latency` of the run are not counted, since
+//! they had no chance to meet the deadline, and pairs whose origin sent no
+//! flood are left out.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::SimTime;
+
+/// Default fraction of floods that must arrive in time.
+pub const DEFAULT_SLA_DELIVERY: f64 = 0.95;
+
+/// Default delivery deadline in seconds.
+pub const DEFAULT_SLA_LATENCY_S: f64 = 30.0;
+
+/// Pairs listed per class in the text summary.
+const MAX_LISTED_PAIRS: usize = 10;
+
+/// What a pair must achieve to meet the SLA.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SlaThresholds {
+    /// Fraction (0-1) of the origin's floods that must reach the receiver.
+    pub min_delivery: f64,
+    /// Deadline in seconds from the origin's transmission.
+    pub max_latency_s: f64,
+}
+
+impl Default for SlaThresholds {
+    fn default() -> Self {
+        SlaThresholds {
+            min_delivery: DEFAULT_SLA_DELIVERY,
+            max_latency_s: DEFAULT_SLA_LATENCY_S,
+        }
+    }
+}
+
+/// Floods sent from one node and when another node heard them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairDeliveries {
+    /// Origin node.
+    pub from: String,
+    /// Receiving node.
+    pub to: String,
+    /// Floods the origin sent.
+    pub floods: u64,
+    /// Latency of each flood the receiver heard.
+    pub latencies: Vec<SimTime>,
+}
+
+/// SLA class of a node pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaClass {
+    /// Enough floods arrived in time.
+    Met,
+    /// Some floods arrived, but not enough in time.
+    BestEffort,
+    /// No flood arrived.
+    Unreachable,
+}
+
+impl fmt::Display for SlaClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlaClass::Met => write!(f, "met"),
+            SlaClass::BestEffort => write!(f, "best effort"),
+            SlaClass::Unreachable => write!(f, "unreachable"),
+        }
+    }
+}
+
+/// Delivery from one node to another, classified.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PairSla {
+    /// Origin node.
+    pub from: String,
+    /// Receiving node.
+    pub to: String,
+    /// Floods the origin sent.
+    pub floods: u64,
+    /// Fraction of them the receiver heard at all.
+    pub delivery: f64,
+    /// Fraction of them the receiver heard within the deadline.
+    pub on_time: f64,
+    /// 95th percentile latency in seconds of the floods heard.
+    pub p95_latency_s: Option<f64>,
+    /// SLA class.
+    pub class: SlaClass,
+}
+
+impl PairSla {
+    /// Classify one pair's deliveries.
+    pub fn classify(pair: &PairDeliveries, thresholds: &SlaThresholds) -> Self {
+        let floods = pair.floods.max(1) as f64;
+        let deadline = SimTime::from_secs(thresholds.max_latency_s);
+        let on_time = pair.latencies.iter().filter(|l| **l <= deadline).count() as f64 / floods;
+        let mut latencies = pair.latencies.clone();
+        latencies.sort();
+        let p95_latency_s = (!latencies.is_empty()).then(|| {
+            let index = ((latencies.len() as f64 * 0.95).ceil() as usize).clamp(1, latencies.len()) - 1;
+            latencies[index].as_secs_f64()
+        });
+        let class = if latencies.is_empty() {
+            SlaClass::Unreachable
+        } else if on_time >= thresholds.min_delivery {
+            SlaClass::Met
+        } else {
+            SlaClass::BestEffort
+        };
+        PairSla {
+            from: pair.from.clone(),
+            to: pair.to.clone(),
+            floods: pair.floods,
+            delivery: latencies.len() as f64 / floods,
+            on_time,
+            p95_latency_s,
+            class,
+        }
+    }
+}
+
+/// SLA classes of every node pair in a run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlaReport {
+    /// Thresholds the pairs were classified against.
+    pub thresholds: SlaThresholds,
+    /// One entry per pair, worst on-time delivery first.
+    pub pairs: Vec<PairSla>,
+}
+
+impl SlaReport {
+    /// Classify every pair, ordering them by on-time delivery.
+    pub fn new(pairs: &[PairDeliveries], thresholds: SlaThresholds) -> Self {
+        let mut pairs: Vec<PairSla> = pairs.iter().map(|p| PairSla::classify(p, &thresholds)).collect();
+        pairs.sort_by(|a, b| {
+            a.on_time
+                .total_cmp(&b.on_time)
+                .then(a.from.cmp(&b.from))
+                .then(a.to.cmp(&b.to))
+        });
+        SlaReport { thresholds, pairs }
+    }
+
+    /// Number of pairs in a class.
+    pub fn count(&self, class: SlaClass) -> usize {
+        self.pairs.iter().filter(|p| p.class == class).count()
+    }
+}
+
+impl fmt::Display for SlaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Delivery SLA ({:.0}% of floods within {:.0}s): {} met, {} best effort, {} unreachable of {} pair(s)",
+            self.thresholds.min_delivery * 100.0,
+            self.thresholds.max_latency_s,
+            self.count(SlaClass::Met),
+            self.count(SlaClass::BestEffort),
+            self.count(SlaClass::Unreachable),
+            self.pairs.len()
+        )?;
+        for class in [SlaClass::Unreachable, SlaClass::BestEffort] {
+            let pairs: Vec<&PairSla> = self.pairs.iter().filter(|p| p.class == class).collect();
+            for pair in pairs.iter().take(MAX_LISTED_PAIRS) {
+                write!(
+                    f,
+                    "\n  {:<12} {} -> {}: {:.1}% on time, {:.1}% delivered",
+                    class.to_string(),
+                    pair.from,
+                    pair.to,
+                    pair.on_time * 100.0,
+                    pair.delivery * 100.0
+                )?;
+                if let Some(p95) = pair.p95_latency_s {
+                    write!(f, ", p95 {:.1}s", p95)?;
+                }
+            }
+            if pairs.len() > MAX_LISTED_PAIRS {
+                write!(f, "\n  ... and {} more {} pair(s)", pairs.len() - MAX_LISTED_PAIRS, class)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(to: &str, floods: u64, latencies_s: &[f64]) -> PairDeliveries {
+        PairDeliveries {
+            from: "A".to_string(),
+            to: to.to_string(),
+            floods,
+            latencies: latencies_s.iter().map(|s| SimTime::from_secs(*s)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_sla_classes() {
+        let report = SlaReport::new(
+            &[
+                pair("met", 20, &[2.0; 19]),
+                // Every flood arrives, but three of them too late
+                pair("slow", 10, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 40.0, 50.0, 60.0]),
+                pair("gone", 10, &[]),
+            ],
+            SlaThresholds::default(),
+        );
+        assert_eq!(report.count(SlaClass::Met), 1);
+        assert_eq!(report.count(SlaClass::BestEffort), 1);
+        assert_eq!(report.count(SlaClass::Unreachable), 1);
+
+        // Worst first
+        assert_eq!(report.pairs[0].to, "gone");
+        let slow = &report.pairs[1];
+        assert_eq!(slow.class, SlaClass::BestEffort);
+        assert_eq!(slow.delivery, 1.0);
+        assert!((slow.on_time - 0.7).abs() < 1e-9);
+        assert_eq!(slow.p95_latency_s, Some(60.0));
+        assert_eq!(report.pairs[2].class, SlaClass::Met);
+
+        // Looser thresholds change the classes
+        let relaxed = SlaReport::new(
+            &[pair("slow", 10, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 40.0, 50.0, 60.0])],
+            SlaThresholds { min_delivery: 0.9, max_latency_s: 55.0 },
+        );
+        assert_eq!(relaxed.pairs[0].class, SlaClass::Met);
+    }
+}
@@ -0,0 +1,457 @@
+//! Per-node bring-up timeline.
+//!
+//! A [`TimelineTracker`] records, for every node, the milestones of network
+//! bring-up during a run:
+//!
+//! - `boot`: the firmware's startup time;
+//! - `first_advert_heard`: the node's radio first received an intact advert;
+//! - `first_contact`: the firmware first told the app about a contact's
+//!   advert (companions only);
+//! - `first_message`: the app first received a direct or channel message
+//!   (companions only);
+//!
+//! and the outages during which the node was down (its battery ran out or
+//! it lost power, e.g. with its failure domain).
+//!
+//! The resulting [`Timeline`] is written as JSON, or rendered as an SVG Gantt
+//! chart with one row per node.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use mcsim_common::{Event, EventPayload};
+use mcsim_companion_protocol::{Message, ProtocolSession, PushNotification, Response};
+use mcsim_model::NodeInfo;
+use meshcore_packet::PayloadType;
+use serde::Serialize;
+
+use crate::SimTime;
+
+/// An interval during which a node was down.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Outage {
+    /// Start of the outage in seconds.
+    pub start_s: f64,
+    /// End of the outage in seconds (None if the node was still down when
+    /// the run ended).
+    pub end_s: Option<f64>,
+    /// What caused it.
+    pub reason: String,
+}
+
+/// Bring-up milestones of one node. Times are in seconds of simulation time;
+/// None means the milestone was never reached.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeTimeline {
+    /// Node name.
+    pub node: String,
+    /// Node type (Repeater, Companion, RoomServer).
+    pub node_type: String,
+    /// Firmware startup.
+    pub boot_s: f64,
+    /// First intact advert received by the radio.
+    pub first_advert_heard_s: Option<f64>,
+    /// First contact advert reported to the app.
+    pub first_contact_s: Option<f64>,
+    /// First message received by the app.
+    pub first_message_s: Option<f64>,
+    /// Intervals during which the node was down.
+    pub outages: Vec<Outage>,
+}
+
+/// Bring-up timeline of every node in a run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Timeline {
+    /// Length of the run in seconds.
+    pub duration_s: f64,
+    /// One entry per node, in model order.
+    pub nodes: Vec<NodeTimeline>,
+}
+
+/// Layout of the SVG rendering, in pixels.
+const LABEL_WIDTH: f64 = 160.0;
+const PLOT_WIDTH: f64 = 800.0;
+const ROW_HEIGHT: f64 = 24.0;
+const BAR_HEIGHT: f64 = 12.0;
+const MARGIN: f64 = 10.0;
+const AXIS_HEIGHT: f64 = 30.0;
+const LEGEND_HEIGHT: f64 = 30.0;
+
+/// Colors of the SVG rendering.
+const BOOTING_COLOR: &str = "#d0d0d0";
+const UP_COLOR: &str = "#81c784";
+const OUTAGE_COLOR: &str = "#e53935";
+const ADVERT_COLOR: &str = "#1e88e5";
+const CONTACT_COLOR: &str = "#fb8c00";
+const MESSAGE_COLOR: &str = "#8e24aa";
+
+impl Timeline {
+    /// Render the timeline as an SVG Gantt chart: one row per node, shaded
+    /// while booting, up and down, with a marker per milestone.
+    pub fn to_svg(&self) -> String {
+        let duration = self.duration_s.max(f64::EPSILON);
+        let x = |t: f64| LABEL_WIDTH + t.clamp(0.0, duration) / duration * PLOT_WIDTH;
+        let plot_top = MARGIN;
+        let plot_height = self.nodes.len() as f64 * ROW_HEIGHT;
+        let width = LABEL_WIDTH + PLOT_WIDTH + 2.0 * MARGIN;
+        let height = plot_top + plot_height + AXIS_HEIGHT + LEGEND_HEIGHT + MARGIN;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+            w = width,
+            h = height
+        );
+        let _ = writeln!(svg, r#"<rect width="{}" height="{}" fill="white"/>"#, width, height);
+
+        for (row, node) in self.nodes.iter().enumerate() {
+            let top = plot_top + row as f64 * ROW_HEIGHT;
+            let bar_top = top + (ROW_HEIGHT - BAR_HEIGHT) / 2.0;
+            let middle = top + ROW_HEIGHT / 2.0;
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+                LABEL_WIDTH - 6.0,
+                middle,
+                escape(&node.node)
+            );
+
+            let mut bar = |start: f64, end: f64, color: &str, title: String| {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{:.1}" y="{}" width="{:.1}" height="{}" fill="{}"><title>{}</title></rect>"#,
+                    x(start),
+                    bar_top,
+                    (x(end) - x(start)).max(0.0),
+                    BAR_HEIGHT,
+                    color,
+                    escape(&title)
+                );
+            };
+            bar(0.0, node.boot_s, BOOTING_COLOR, format!("{}: booting", node.node));
+            bar(node.boot_s, duration, UP_COLOR, format!("{}: up", node.node));
+            for outage in &node.outages {
+                let end = outage.end_s.unwrap_or(duration);
+                bar(
+                    outage.start_s,
+                    end,
+                    OUTAGE_COLOR,
+                    format!("{}: down ({}) {} - {}", node.node, outage.reason, format_time(outage.start_s), format_time(end)),
+                );
+            }
+
+            let milestones = [
+                (node.first_advert_heard_s, ADVERT_COLOR, "first advert heard"),
+                (node.first_contact_s, CONTACT_COLOR, "first contact"),
+                (node.first_message_s, MESSAGE_COLOR, "first message"),
+            ];
+            for (time, color, label) in milestones {
+                if let Some(t) = time {
+                    let _ = writeln!(svg, "{}", marker(x(t), middle, color, &format!("{}: {} at {}", node.node, label, format_time(t))));
+                }
+            }
+        }
+
+        // Time axis
+        let axis_y = plot_top + plot_height + 4.0;
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="#444"/>"##,
+            LABEL_WIDTH,
+            axis_y,
+            LABEL_WIDTH + PLOT_WIDTH,
+            axis_y
+        );
+        let step = tick_step(duration);
+        let mut tick = 0.0;
+        while tick <= duration + step * 1e-9 {
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{x:.1}" y1="{}" x2="{x:.1}" y2="{}" stroke="#444"/><text x="{x:.1}" y="{}" text-anchor="middle">{}</text>"##,
+                axis_y,
+                axis_y + 4.0,
+                axis_y + 16.0,
+                format_time(tick),
+                x = x(tick)
+            );
+            tick += step;
+        }
+
+        // Legend
+        let legend_y = axis_y + AXIS_HEIGHT;
+        let mut legend_x = LABEL_WIDTH;
+        for (color, label) in [(BOOTING_COLOR, "booting"), (UP_COLOR, "up"), (OUTAGE_COLOR, "down")] {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="14" height="{}" fill="{}"/><text x="{}" y="{}" dominant-baseline="middle">{}</text>"#,
+                legend_x,
+                legend_y - BAR_HEIGHT / 2.0,
+                BAR_HEIGHT,
+                color,
+                legend_x + 18.0,
+                legend_y,
+                label
+            );
+            legend_x += 80.0;
+        }
+        for (color, label) in [
+            (ADVERT_COLOR, "first advert heard"),
+            (CONTACT_COLOR, "first contact"),
+            (MESSAGE_COLOR, "first message"),
+        ] {
+            let _ = writeln!(
+                svg,
+                r#"{}<text x="{}" y="{}" dominant-baseline="middle">{}</text>"#,
+                marker(legend_x + 6.0, legend_y, color, label),
+                legend_x + 16.0,
+                legend_y,
+                label
+            );
+            legend_x += 140.0;
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// A diamond marker centered on (x, y).
+fn marker(x: f64, y: f64, color: &str, title: &str) -> String {
+    let r = BAR_HEIGHT / 2.0 + 1.0;
+    format!(
+        r#"<polygon points="{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1}" fill="{}" stroke="white"><title>{}</title></polygon>"#,
+        x,
+        y - r,
+        x + r,
+        y,
+        x,
+        y + r,
+        x - r,
+        y,
+        color,
+        escape(title)
+    )
+}
+
+/// Axis tick spacing giving at most about ten ticks.
+fn tick_step(duration_s: f64) -> f64 {
+    const STEPS: [f64; 14] = [
+        1.0, 2.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0, 7200.0, 21600.0, 86400.0,
+    ];
+    STEPS
+        .iter()
+        .copied()
+        .find(|step| duration_s / step <= 10.0)
+        .unwrap_or_else(|| (duration_s / 10.0 / 86400.0).ceil() * 86400.0)
+}
+
+/// Compact time label: seconds, minutes or hours.
+fn format_time(seconds: f64) -> String {
+    if seconds < 120.0 {
+        format!("{}s", (seconds * 10.0).round() / 10.0)
+    } else if seconds < 7200.0 {
+        format!("{}m", (seconds / 6.0).round() / 10.0)
+    } else {
+        format!("{}h", (seconds / 360.0).round() / 10.0)
+    }
+}
+
+/// Escape text for use in SVG.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Milestones of one node as they are observed.
+struct NodeState {
+    timeline: NodeTimeline,
+    /// Decoder of the firmware's serial output to the app (companions only).
+    session: Option<ProtocolSession>,
+}
+
+/// Records each node's bring-up milestones while the simulation runs.
+pub struct TimelineTracker {
+    nodes: Vec<NodeState>,
+    /// Node index by firmware entity ID.
+    by_firmware: HashMap<u64, usize>,
+    /// Node index by agent entity ID.
+    by_agent: HashMap<u64, usize>,
+}
+
+impl TimelineTracker {
+    /// Track the nodes of a simulation.
+    pub fn new(nodes: &[NodeInfo]) -> Self {
+        let mut by_firmware = HashMap::new();
+        let mut by_agent = HashMap::new();
+        let nodes = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, info)| {
+                by_firmware.insert(info.firmware_entity_id, index);
+                if let Some(agent_id) = info.agent_entity_id {
+                    by_agent.insert(agent_id, index);
+                }
+                NodeState {
+                    timeline: NodeTimeline {
+                        node: info.name.clone(),
+                        node_type: info.node_type.clone(),
+                        boot_s: info.startup_time.as_secs_f64(),
+                        first_advert_heard_s: None,
+                        first_contact_s: None,
+                        first_message_s: None,
+                        outages: Vec::new(),
+                    },
+                    session: info.agent_entity_id.map(|_| ProtocolSession::new()),
+                }
+            })
+            .collect();
+        Self { nodes, by_firmware, by_agent }
+    }
+
+    /// Observe a processed event.
+    pub fn observe(&mut self, event: &Event) {
+        let now = event.time.as_secs_f64();
+        match &event.payload {
+            EventPayload::RadioRxPacket(rx) if !rx.was_collided && !rx.was_corrupted => {
+                let is_advert = rx.packet.decoded().is_some_and(|p| p.payload_type() == PayloadType::Advert);
+                if !is_advert {
+                    return;
+                }
+                for target in &event.targets {
+                    if let Some(&index) = self.by_firmware.get(&target.0) {
+                        self.nodes[index].timeline.first_advert_heard_s.get_or_insert(now);
+                    }
+                }
+            }
+            EventPayload::BatteryLevel(battery) => {
+                for target in &event.targets {
+                    let Some(&index) = self.by_firmware.get(&target.0) else {
+                        continue;
+                    };
+                    let outages = &mut self.nodes[index].timeline.outages;
+                    let ongoing = outages.last_mut().filter(|outage| outage.end_s.is_none());
+                    match (battery.depleted, ongoing) {
+                        (true, None) => outages.push(Outage {
+                            start_s: now,
+                            end_s: None,
+                            reason: "battery depleted".to_string(),
+                        }),
+                        (false, Some(outage)) => outage.end_s = Some(now),
+                        _ => {}
+                    }
+                }
+            }
+            EventPayload::PowerOff(power_off) => {
+                for target in &event.targets {
+                    let Some(&index) = self.by_firmware.get(&target.0) else {
+                        continue;
+                    };
+                    let outages = &mut self.nodes[index].timeline.outages;
+                    if outages.last().is_none_or(|outage| outage.end_s.is_some()) {
+                        outages.push(Outage { start_s: now, end_s: None, reason: power_off.reason.clone() });
+                    }
+                }
+            }
+            EventPayload::PowerOn(_) => {
+                for target in &event.targets {
+                    let Some(&index) = self.by_firmware.get(&target.0) else {
+                        continue;
+                    };
+                    let outages = &mut self.nodes[index].timeline.outages;
+                    if let Some(outage) = outages.last_mut().filter(|outage| outage.end_s.is_none()) {
+                        outage.end_s = Some(now);
+                    }
+                }
+            }
+            EventPayload::SerialTx(serial) => {
+                for target in &event.targets {
+                    let Some(&index) = self.by_agent.get(&target.0) else {
+                        continue;
+                    };
+                    let node = &mut self.nodes[index];
+                    let Some(session) = node.session.as_mut() else {
+                        continue;
+                    };
+                    session.feed(&serial.data);
+                    loop {
+                        match session.try_decode() {
+                            Ok(Some(Message::Push(PushNotification::Advert { .. } | PushNotification::NewAdvert(_)))) => {
+                                node.timeline.first_contact_s.get_or_insert(now);
+                            }
+                            Ok(Some(Message::Response(
+                                Response::ContactMessageV2(_)
+                                | Response::ContactMessageV3(_)
+                                | Response::ChannelMessageV2(_)
+                                | Response::ChannelMessageV3(_),
+                            ))) => {
+                                node.timeline.first_message_s.get_or_insert(now);
+                            }
+                            Ok(Some(_)) | Err(_) => {}
+                            Ok(None) => break,
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The timeline of a run that lasted `duration`.
+    pub fn timeline(&self, duration: SimTime) -> Timeline {
+        Timeline {
+            duration_s: duration.as_secs_f64(),
+            nodes: self.nodes.iter().map(|node| node.timeline.clone()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{BatteryLevelEvent, EntityId, EventId};
+
+    fn battery(time_s: f64, firmware_id: u64, depleted: bool) -> Event {
+        Event {
+            id: EventId(0),
+            time: SimTime::from_secs(time_s),
+            source: EntityId::new(firmware_id + 100),
+            targets: vec![EntityId::new(firmware_id)],
+            payload: EventPayload::BatteryLevel(BatteryLevelEvent { millivolts: 3000, depleted }),
+        }
+    }
+
+    #[test]
+    fn test_outages_recorded() {
+        let late = NodeInfo { startup_time: SimTime::from_secs(2.5), ..NodeInfo::new("R1", "Repeater", 1, 101) };
+        let mut tracker = TimelineTracker::new(&[late, NodeInfo::new("R2", "Repeater", 2, 102)]);
+        tracker.observe(&battery(100.0, 1, true));
+        tracker.observe(&battery(110.0, 1, true));
+        tracker.observe(&battery(150.0, 1, false));
+        tracker.observe(&battery(200.0, 1, true));
+
+        let timeline = tracker.timeline(SimTime::from_secs(300.0));
+        let r1 = &timeline.nodes[0];
+        assert_eq!(r1.boot_s, 2.5);
+        assert_eq!(r1.outages.len(), 2);
+        assert_eq!((r1.outages[0].start_s, r1.outages[0].end_s), (100.0, Some(150.0)));
+        assert_eq!((r1.outages[1].start_s, r1.outages[1].end_s), (200.0, None));
+        assert!(timeline.nodes[1].outages.is_empty());
+    }
+
+    #[test]
+    fn test_svg_rendering() {
+        let node = NodeInfo { startup_time: SimTime::from_secs(5.0), ..NodeInfo::new("R<1>", "Repeater", 1, 101) };
+        let mut timeline = TimelineTracker::new(&[node]).timeline(SimTime::from_secs(600.0));
+        timeline.nodes[0].first_advert_heard_s = Some(30.0);
+
+        let svg = timeline.to_svg();
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert!(svg.contains("R&lt;1&gt;"));
+        assert!(svg.contains("first advert heard at 30s"));
+        assert_eq!(tick_step(600.0), 60.0);
+        assert_eq!(format_time(5400.0), "90m");
+    }
+}
@@ -0,0 +1,216 @@
+//! Timer jitter stress testing.
+//!
+//! The simulator delivers firmware wake timers at exactly the requested time,
+//! which real hardware never does. Firmware logic that only works because of
+//! that precision goes unnoticed until it ships. [`TimerJitter`] shifts each
+//! `Timer` event by a random offset within `±max_jitter` (never into the past),
+//! and [`BehaviorSummary`] captures what a run did so a jittered run can be
+//! compared against the deterministic one:
+//!
+//! ```text
+//! mcsim timer-jitter model.yaml --duration 10m --jitter 5
+//! ```
+//!
+//! Per-node TX/RX/collision counts and message totals are compared. Some drift
+//! is expected from the changed schedule; large or one-sided differences
+//! (a node that stops transmitting, messages no longer acknowledged) point at
+//! timing-sensitive firmware logic.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use mcsim_common::{Event, EventPayload};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+
+use crate::{EventLoop, SimTime, SimulationStats};
+
+/// Random perturbation of timer event delivery.
+#[derive(Debug, Clone)]
+pub struct TimerJitter {
+    max_jitter_us: u64,
+    rng: ChaCha8Rng,
+    perturbed: u64,
+}
+
+impl TimerJitter {
+    /// Jitter timers by up to `max_jitter` either way, drawing offsets from a
+    /// generator seeded with `seed` so a jittered run is reproducible.
+    pub fn new(max_jitter: SimTime, seed: u64) -> Self {
+        Self {
+            max_jitter_us: max_jitter.as_micros(),
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            perturbed: 0,
+        }
+    }
+
+    /// Shift `event` if it is a timer, keeping it no earlier than `now`.
+    pub fn apply(&mut self, event: &mut Event, now: SimTime) {
+        if self.max_jitter_us == 0 || !matches!(event.payload, EventPayload::Timer { .. }) {
+            return;
+        }
+        let max = self.max_jitter_us as i64;
+        let offset = self.rng.gen_range(-max..=max);
+        let time = (event.time.as_micros() as i64 + offset).max(now.as_micros() as i64);
+        let time = SimTime::from_micros(time as u64);
+        if time != event.time {
+            event.time = time;
+            self.perturbed += 1;
+        }
+    }
+
+    /// Number of timer events shifted so far.
+    pub fn perturbed(&self) -> u64 {
+        self.perturbed
+    }
+}
+
+/// Observable activity of one node over a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct NodeBehavior {
+    /// Packets transmitted.
+    pub tx: u64,
+    /// Packets received.
+    pub rx: u64,
+    /// Receptions lost to collisions.
+    pub collisions: u64,
+}
+
+/// What a run did, for comparing runs of the same scenario.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BehaviorSummary {
+    /// Per-node activity, keyed by node name.
+    pub nodes: BTreeMap<String, NodeBehavior>,
+    /// Messages sent by agents.
+    pub messages_sent: u64,
+    /// Messages acknowledged.
+    pub messages_acked: u64,
+}
+
+impl BehaviorSummary {
+    /// Capture the outcome of a finished run.
+    pub fn capture(event_loop: &EventLoop, stats: &SimulationStats) -> Self {
+        let nodes = event_loop
+            .node_infos()
+            .iter()
+            .map(|info| {
+                let node = event_loop
+                    .node_stats()
+                    .get(&info.radio_entity_id)
+                    .map(|s| NodeBehavior { tx: s.tx, rx: s.rx, collisions: s.collisions })
+                    .unwrap_or_default();
+                (info.name.clone(), node)
+            })
+            .collect();
+        Self {
+            nodes,
+            messages_sent: stats.messages_sent,
+            messages_acked: stats.messages_acked,
+        }
+    }
+
+    /// Differences from a `baseline` run, in node name order.
+    pub fn diff(&self, baseline: &BehaviorSummary) -> Vec<BehaviorDifference> {
+        let mut differences = Vec::new();
+        let mut push = |subject: &str, counter: &'static str, baseline: u64, observed: u64| {
+            if baseline != observed {
+                differences.push(BehaviorDifference {
+                    subject: subject.to_string(),
+                    counter,
+                    baseline,
+                    observed,
+                });
+            }
+        };
+
+        let mut names: Vec<&String> = baseline.nodes.keys().chain(self.nodes.keys()).collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let before = baseline.nodes.get(name).copied().unwrap_or_default();
+            let after = self.nodes.get(name).copied().unwrap_or_default();
+            push(name, "tx", before.tx, after.tx);
+            push(name, "rx", before.rx, after.rx);
+            push(name, "collisions", before.collisions, after.collisions);
+        }
+        push("simulation", "messages_sent", baseline.messages_sent, self.messages_sent);
+        push("simulation", "messages_acked", baseline.messages_acked, self.messages_acked);
+        differences
+    }
+}
+
+/// A counter that differs between two runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BehaviorDifference {
+    /// Node name, or `simulation` for run-wide counters.
+    pub subject: String,
+    /// Counter name.
+    pub counter: &'static str,
+    /// Value in the baseline run.
+    pub baseline: u64,
+    /// Value in the compared run.
+    pub observed: u64,
+}
+
+impl fmt::Display for BehaviorDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {} -> {} ({:+})",
+            self.subject,
+            self.counter,
+            self.baseline,
+            self.observed,
+            self.observed as i64 - self.baseline as i64
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcsim_common::{EntityId, EventId};
+
+    fn event(time_us: u64, payload: EventPayload) -> Event {
+        Event {
+            id: EventId(1),
+            time: SimTime::from_micros(time_us),
+            source: EntityId::new(1),
+            targets: vec![EntityId::new(1)],
+            payload,
+        }
+    }
+
+    #[test]
+    fn test_jitter_only_shifts_timers_within_bounds() {
+        let mut jitter = TimerJitter::new(SimTime::from_millis(5), 7);
+        let now = SimTime::from_millis(998);
+        for _ in 0..100 {
+            let mut timer = event(1_000_000, EventPayload::Timer { timer_id: 1 });
+            jitter.apply(&mut timer, now);
+            assert!(timer.time >= now);
+            assert!(timer.time <= SimTime::from_millis(1005));
+        }
+        assert!(jitter.perturbed() > 0);
+
+        let mut end = event(1_000_000, EventPayload::SimulationEnd);
+        jitter.apply(&mut end, now);
+        assert_eq!(end.time, SimTime::from_micros(1_000_000));
+    }
+
+    #[test]
+    fn test_behavior_diff() {
+        let mut baseline = BehaviorSummary { messages_sent: 4, messages_acked: 4, ..Default::default() };
+        baseline.nodes.insert("Alice".to_string(), NodeBehavior { tx: 10, rx: 5, collisions: 0 });
+        let mut jittered = baseline.clone();
+        assert!(jittered.diff(&baseline).is_empty());
+
+        jittered.nodes.insert("Alice".to_string(), NodeBehavior { tx: 10, rx: 3, collisions: 0 });
+        jittered.messages_acked = 2;
+        let differences = jittered.diff(&baseline);
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0].to_string(), "Alice rx: 5 -> 3 (-2)");
+        assert_eq!(differences[1].subject, "simulation");
+    }
+}
@@ -0,0 +1,368 @@
+//! Differential tracing of two nodes.
+//!
+//! `mcsim trace-diff` lines up the trace stream (`run --output`) of one node
+//! with that of another node with the same role in the same run, or with the
+//! same node's stream in another run, and shows where their behavior
+//! diverges. Comparing a node across two runs with the same seed chases
+//! nondeterminism; comparing two repeaters that should behave alike shows a
+//! configuration mistake.
+//!
+//! Each trace entry of a node becomes a [`TraceStep`], keyed by what
+//! happened (packet direction, payload type, reception status, timer ID)
+//! and, unless payloads are ignored, the packet's payload hash. Times are
+//! left out of the key: the same events shifted in time align, and the
+//! report gives the largest shift. After a difference the streams realign at
+//! the nearest matching step within a look-ahead window.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::RunnerError;
+
+/// One trace entry of a node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraceStep {
+    /// Simulation time in seconds.
+    pub time_s: f64,
+    /// What happened, e.g. `TX Advert` or `RX TextMessage collided`.
+    pub event: String,
+    /// Payload hash of a packet, destination of a message, or rule of an
+    /// alert.
+    pub detail: Option<String>,
+}
+
+impl TraceStep {
+    /// Whether two steps are the same event.
+    fn matches(&self, other: &TraceStep, ignore_payload: bool) -> bool {
+        self.event == other.event && (ignore_payload || self.detail == other.detail)
+    }
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>12.3}s  {}", self.time_s, self.event)?;
+        if let Some(detail) = &self.detail {
+            write!(f, " [{}]", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Extract a node's steps from a trace file's JSON array, in order.
+pub fn node_steps(trace: &Value, node: &str) -> Result<Vec<TraceStep>, RunnerError> {
+    let entries = trace
+        .as_array()
+        .ok_or_else(|| RunnerError::ConfigError("Trace file must contain a JSON array".to_string()))?;
+    let mut steps = Vec::new();
+    let mut clock = TraceClock::default();
+    for entry in entries {
+        // Times are read from every entry, so the day count of timestamps
+        // follows the whole trace
+        let time_s = clock.time_s(entry);
+        if entry["origin"] != node {
+            continue;
+        }
+        let text = |name: &str| entry[name].as_str().map(str::to_string);
+        let kind = entry["type"].as_str().unwrap_or("UNKNOWN");
+        let direction = entry["direction"].as_str().unwrap_or_default();
+        let (event, detail) = match kind {
+            "PACKET" => {
+                let payload_type = entry["packet"]["header"]["payload_type"].as_str().unwrap_or("Unknown");
+                let mut event = format!("{} {}", direction, payload_type);
+                if let Some(status) = entry["reception_status"].as_str().filter(|&s| s != "ok") {
+                    event.push(' ');
+                    event.push_str(status);
+                }
+                (event, text("payload_hash"))
+            }
+            "TIMER" => (format!("TIMER {}", entry["timer_id"]), None),
+            "MESSAGE" => (format!("MESSAGE {}", direction), text("destination")),
+            "ALERT" => ("ALERT".to_string(), text("rule")),
+            other => (other.to_string(), None),
+        };
+        steps.push(TraceStep { time_s, event, detail });
+    }
+    Ok(steps)
+}
+
+/// Simulation time of trace entries.
+///
+/// Entries carry it as `sim_time_s` when timestamps are wall-clock
+/// datetimes. Otherwise the timestamp is the time of day from the trace's
+/// base time, so a run longer than a day is unwrapped by counting the times
+/// it goes backwards.
+#[derive(Debug, Default)]
+struct TraceClock {
+    days: u32,
+    last_s: f64,
+}
+
+impl TraceClock {
+    fn time_s(&mut self, entry: &Value) -> f64 {
+        if let Some(time_s) = entry["sim_time_s"].as_f64() {
+            return time_s;
+        }
+        let Some(of_day) = entry["timestamp"].as_str().and_then(time_of_day_s) else {
+            return self.last_s;
+        };
+        let mut time_s = self.days as f64 * 86_400.0 + of_day;
+        if time_s + 43_200.0 < self.last_s {
+            self.days += 1;
+            time_s += 86_400.0;
+        }
+        self.last_s = time_s;
+        time_s
+    }
+}
+
+/// Seconds since midnight of an ISO 8601 timestamp (`...THH:MM:SS.sssZ`).
+fn time_of_day_s(timestamp: &str) -> Option<f64> {
+    let (_, time) = timestamp.split_once('T')?;
+    let mut fields = time.trim_end_matches('Z').splitn(3, ':');
+    let hours: f64 = fields.next()?.parse().ok()?;
+    let minutes: f64 = fields.next()?.parse().ok()?;
+    let seconds: f64 = fields.next()?.parse().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// How a step of one stream lines up with the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Aligned {
+    /// The same event in both streams, by index.
+    Same {
+        /// Index in the first stream.
+        a: usize,
+        /// Index in the second stream.
+        b: usize,
+    },
+    /// An event only in the first stream.
+    OnlyA {
+        /// Index in the first stream.
+        a: usize,
+    },
+    /// An event only in the second stream.
+    OnlyB {
+        /// Index in the second stream.
+        b: usize,
+    },
+}
+
+/// Settings of a comparison.
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Compare only what happened, not which packet it was.
+    pub ignore_payload: bool,
+    /// Steps to look ahead in each stream when realigning after a
+    /// difference.
+    pub window: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self { ignore_payload: false, window: 32 }
+    }
+}
+
+/// A compared stream.
+#[derive(Debug, Clone, Serialize)]
+pub struct Stream {
+    /// Label of the stream, e.g. `Repeater1` or `run2.json:Repeater1`.
+    pub label: String,
+    /// The node's steps.
+    pub steps: Vec<TraceStep>,
+}
+
+/// Two aligned streams.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceDiff {
+    /// First stream.
+    pub a: Stream,
+    /// Second stream.
+    pub b: Stream,
+    /// Both streams' steps, lined up in order.
+    pub alignment: Vec<Aligned>,
+}
+
+impl TraceDiff {
+    /// Align two streams.
+    pub fn new(a: Stream, b: Stream, options: &DiffOptions) -> Self {
+        let alignment = align(&a.steps, &b.steps, options);
+        Self { a, b, alignment }
+    }
+
+    /// Position in the alignment of the first difference, if any.
+    pub fn first_divergence(&self) -> Option<usize> {
+        self.alignment.iter().position(|step| !matches!(step, Aligned::Same { .. }))
+    }
+
+    /// Number of steps the streams share.
+    pub fn matched(&self) -> usize {
+        self.alignment.iter().filter(|step| matches!(step, Aligned::Same { .. })).count()
+    }
+
+    /// Largest time shift between matching steps, in seconds (second stream
+    /// minus first).
+    pub fn max_time_shift_s(&self) -> f64 {
+        self.alignment
+            .iter()
+            .filter_map(|step| match *step {
+                Aligned::Same { a, b } => Some(self.b.steps[b].time_s - self.a.steps[a].time_s),
+                _ => None,
+            })
+            .fold(0.0, |max: f64, shift| if shift.abs() > max.abs() { shift } else { max })
+    }
+
+    /// Render the report, with `context` matching steps before the first
+    /// difference and up to `shown` steps from it.
+    pub fn report(&self, context: usize, shown: usize) -> String {
+        let mut out = String::new();
+        self.write_report(&mut out, context, shown).expect("writing to a String");
+        out
+    }
+
+    fn write_report(&self, out: &mut impl fmt::Write, context: usize, shown: usize) -> fmt::Result {
+        let matched = self.matched();
+        writeln!(out, "--- {} ({} events)", self.a.label, self.a.steps.len())?;
+        writeln!(out, "+++ {} ({} events)", self.b.label, self.b.steps.len())?;
+        writeln!(
+            out,
+            "{} matching, {} only in {}, {} only in {}; matching events shifted by up to {:+.3}s",
+            matched,
+            self.a.steps.len() - matched,
+            self.a.label,
+            self.b.steps.len() - matched,
+            self.b.label,
+            self.max_time_shift_s()
+        )?;
+        let Some(first) = self.first_divergence() else {
+            return writeln!(out, "No divergence: the streams are the same events in the same order");
+        };
+        writeln!(out)?;
+        writeln!(out, "First divergence after {} matching events:", first)?;
+        let end = (first + shown).min(self.alignment.len());
+        for step in &self.alignment[first.saturating_sub(context)..end] {
+            match *step {
+                Aligned::Same { a, b } => {
+                    let shift = self.b.steps[b].time_s - self.a.steps[a].time_s;
+                    write!(out, "  {}", self.a.steps[a])?;
+                    if shift != 0.0 {
+                        write!(out, " ({:+.3}s)", shift)?;
+                    }
+                    writeln!(out)?;
+                }
+                Aligned::OnlyA { a } => writeln!(out, "- {}", self.a.steps[a])?,
+                Aligned::OnlyB { b } => writeln!(out, "+ {}", self.b.steps[b])?,
+            }
+        }
+        if end < self.alignment.len() {
+            writeln!(out, "  ... {} more aligned steps", self.alignment.len() - end)?;
+        }
+        Ok(())
+    }
+}
+
+/// Line up two streams, realigning after each difference at the nearest
+/// pair of matching steps within the window.
+fn align(a: &[TraceStep], b: &[TraceStep], options: &DiffOptions) -> Vec<Aligned> {
+    let same = |i: usize, j: usize| a[i].matches(&b[j], options.ignore_payload);
+    let mut alignment = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if same(i, j) {
+            alignment.push(Aligned::Same { a: i, b: j });
+            i += 1;
+            j += 1;
+            continue;
+        }
+        // Nearest resync point first: fewest steps skipped in total
+        let window = options.window.max(1);
+        let resync = (1..2 * window).find_map(|skipped| {
+            (0..=skipped)
+                .map(|da| (da, skipped - da))
+                .filter(|&(da, db)| da < window && db < window && i + da < a.len() && j + db < b.len())
+                .find(|&(da, db)| same(i + da, j + db))
+        });
+        // Without one, the two steps differ and the streams move on together
+        let (da, db) = resync.unwrap_or((1, 1));
+        alignment.extend((i..i + da).map(|a| Aligned::OnlyA { a }));
+        alignment.extend((j..j + db).map(|b| Aligned::OnlyB { b }));
+        i += da;
+        j += db;
+    }
+    alignment.extend((i..a.len()).map(|a| Aligned::OnlyA { a }));
+    alignment.extend((j..b.len()).map(|b| Aligned::OnlyB { b }));
+    alignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn step(time_s: f64, event: &str, detail: &str) -> TraceStep {
+        TraceStep { time_s, event: event.to_string(), detail: Some(detail.to_string()) }
+    }
+
+    #[test]
+    fn test_node_steps_from_trace() {
+        let trace = json!([
+            {"origin": "A", "timestamp": "2025-01-01T23:59:59.000Z", "type": "TIMER", "timer_id": 3},
+            {"origin": "B", "timestamp": "2025-01-01T00:00:01.500Z", "type": "PACKET", "direction": "RX",
+             "reception_status": "collided", "payload_hash": "ab", "packet": {"header": {"payload_type": "Advert"}}},
+            {"origin": "A", "timestamp": "2025-01-01T00:00:02.000Z", "type": "PACKET", "direction": "TX",
+             "payload_hash": "cd", "packet": {"header": {"payload_type": "TextMessage"}}},
+            {"origin": "A", "timestamp": "ignored", "sim_time_s": 90000.5, "type": "MESSAGE", "direction": "TX",
+             "destination": "Bob"},
+        ]);
+        let steps = node_steps(&trace, "A").unwrap();
+        assert_eq!(steps, vec![
+            TraceStep { time_s: 86399.0, event: "TIMER 3".to_string(), detail: None },
+            step(86402.0, "TX TextMessage", "cd"),
+            step(90000.5, "MESSAGE TX", "Bob"),
+        ]);
+        assert_eq!(node_steps(&trace, "B").unwrap()[0].event, "RX Advert collided");
+        assert!(node_steps(&json!({}), "A").is_err());
+    }
+
+    #[test]
+    fn test_streams_realign_after_divergence() {
+        let stream = |label: &str, steps: Vec<TraceStep>| Stream { label: label.to_string(), steps };
+        let a = vec![
+            step(1.0, "TX Advert", "1"),
+            step(2.0, "RX Advert", "2"),
+            step(3.0, "TX Advert", "2"),
+            step(4.0, "RX Ack", "3"),
+        ];
+        let b = vec![
+            step(1.0, "TX Advert", "1"),
+            step(2.5, "RX Advert", "2"),
+            step(3.0, "RX Advert collided", "9"),
+            step(4.0, "RX Ack", "3"),
+        ];
+        let diff = TraceDiff::new(stream("A", a.clone()), stream("B", b.clone()), &DiffOptions::default());
+        assert_eq!(diff.alignment, vec![
+            Aligned::Same { a: 0, b: 0 },
+            Aligned::Same { a: 1, b: 1 },
+            Aligned::OnlyA { a: 2 },
+            Aligned::OnlyB { b: 2 },
+            Aligned::Same { a: 3, b: 3 },
+        ]);
+        assert_eq!(diff.first_divergence(), Some(2));
+        assert_eq!(diff.matched(), 3);
+        assert_eq!(diff.max_time_shift_s(), 0.5);
+        let report = diff.report(1, 10);
+        assert!(report.contains("First divergence after 2 matching events"));
+        assert!(report.contains("- "));
+        assert!(report.contains("RX Advert collided [9]"));
+
+        // Same kinds of events, different packets
+        let mut other = a.clone();
+        other[0].detail = Some("7".to_string());
+        let options = DiffOptions { ignore_payload: true, ..Default::default() };
+        assert_eq!(TraceDiff::new(stream("A", a.clone()), stream("B", other.clone()), &options).first_divergence(), None);
+        assert_eq!(TraceDiff::new(stream("A", a), stream("B", other), &DiffOptions::default()).first_divergence(), Some(0));
+    }
+}
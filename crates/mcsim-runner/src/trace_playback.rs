@@ -0,0 +1,232 @@
+//! Playback of a finished run from its trace.
+//!
+//! `mcsim replay` opens a Rerun recording saved with `run --rerun-save` as
+//! is. Given a trace written by `run --output` instead, it rebuilds the
+//! recording: the nodes and links are placed from the run's model files,
+//! and every packet transmission and reception in the trace is logged at
+//! its simulation time, as the live viewer shows them during a run. The
+//! finished run can then be scrubbed on the `sim_time` timeline without
+//! running it again:
+//!
+//! ```text
+//! mcsim run model.yaml --duration 1h --output trace.json
+//! mcsim replay trace.json --model model.yaml
+//! ```
+//!
+//! The trace doesn't name the transmitter of a reception, so receptions are
+//! matched to transmissions by payload hash and start time to highlight the
+//! link the packet crossed.
+
+use std::collections::HashMap;
+
+use mcsim_common::GeoCoord;
+use mcsim_model::{Model, FIRMWARE_TYPE, LOCATION_LATITUDE, LOCATION_LONGITUDE};
+use serde_json::Value;
+
+use crate::rerun_logger::VisNodeInfo;
+use crate::{RunnerError, SimTime};
+
+/// How a reception ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceptionStatus {
+    /// Received intact.
+    Ok,
+    /// Damaged by a collision.
+    Collided,
+    /// Too weak to decode.
+    Weak,
+    /// Received with bit errors.
+    Corrupted,
+}
+
+impl ReceptionStatus {
+    /// Parse the `reception_status` of a trace entry.
+    pub fn from_trace(status: &str) -> Option<Self> {
+        match status {
+            "ok" => Some(ReceptionStatus::Ok),
+            "collided" => Some(ReceptionStatus::Collided),
+            "weak" => Some(ReceptionStatus::Weak),
+            "corrupted" => Some(ReceptionStatus::Corrupted),
+            _ => None,
+        }
+    }
+}
+
+/// What happened to a packet at a node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlaybackKind {
+    /// The node transmitted it.
+    Transmit {
+        /// Transmit power in dBm.
+        tx_power_dbm: f64,
+    },
+    /// The node received it.
+    Receive {
+        /// Transmitting node, if its transmission is in the trace.
+        from: Option<String>,
+        /// Signal-to-noise ratio in dB.
+        snr_db: f64,
+        /// Received signal strength in dBm.
+        rssi_dbm: f64,
+        /// How the reception ended.
+        status: ReceptionStatus,
+    },
+}
+
+/// A packet transmission or reception taken from a trace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackPacket {
+    /// Node that transmitted or received.
+    pub node: String,
+    /// When the transmission started, or the reception ended.
+    pub time: SimTime,
+    /// Packet bytes.
+    pub packet: Vec<u8>,
+    /// Transmission or reception.
+    pub kind: PlaybackKind,
+}
+
+/// Parse a value such as `"-3.5 dB"` or `"20 dBm"`.
+fn parse_level(value: &Value) -> Option<f64> {
+    value.as_str()?.split_whitespace().next()?.parse().ok()
+}
+
+/// Extract the packet transmissions and receptions from a trace file's
+/// JSON array, in time order.
+pub fn packets_from_trace(trace: &Value) -> Result<Vec<PlaybackPacket>, RunnerError> {
+    let entries = trace
+        .as_array()
+        .ok_or_else(|| RunnerError::ConfigError("Trace file must contain a JSON array".to_string()))?;
+    let packets: Vec<&Value> = entries.iter().filter(|e| e["type"] == "PACKET").collect();
+
+    let invalid = |entry: &Value, field: &str| {
+        RunnerError::ConfigError(format!(
+            "Trace packet entry of '{}' is missing '{}'",
+            entry["origin"].as_str().unwrap_or_default(),
+            field
+        ))
+    };
+    let start_us = |entry: &Value| -> Result<u64, RunnerError> {
+        let start_s = entry["packet_start_time_s"].as_f64().ok_or_else(|| invalid(entry, "packet_start_time_s"))?;
+        Ok(SimTime::from_secs(start_s).as_micros())
+    };
+
+    // Transmitters by payload hash and start time, to match receptions
+    let mut transmitters: HashMap<(&str, u64), &str> = HashMap::new();
+    for entry in packets.iter().filter(|e| e["direction"] == "TX") {
+        let hash = entry["payload_hash"].as_str().unwrap_or_default();
+        transmitters.insert((hash, start_us(entry)?), entry["origin"].as_str().unwrap_or_default());
+    }
+
+    let mut result = Vec::with_capacity(packets.len());
+    for entry in packets {
+        let node = entry["origin"].as_str().ok_or_else(|| invalid(entry, "origin"))?.to_string();
+        let packet = entry["packet_hex"]
+            .as_str()
+            .and_then(|h| hex::decode(h).ok())
+            .ok_or_else(|| invalid(entry, "packet_hex"))?;
+        let (time_s, kind) = match entry["direction"].as_str() {
+            Some("TX") => (
+                entry["packet_start_time_s"].as_f64(),
+                PlaybackKind::Transmit {
+                    tx_power_dbm: parse_level(&entry["RSSI"]).ok_or_else(|| invalid(entry, "RSSI"))?,
+                },
+            ),
+            Some("RX") => {
+                let hash = entry["payload_hash"].as_str().unwrap_or_default();
+                (
+                    entry["packet_end_time_s"].as_f64(),
+                    PlaybackKind::Receive {
+                        from: transmitters.get(&(hash, start_us(entry)?)).map(|n| n.to_string()),
+                        snr_db: parse_level(&entry["SNR"]).ok_or_else(|| invalid(entry, "SNR"))?,
+                        rssi_dbm: parse_level(&entry["RSSI"]).ok_or_else(|| invalid(entry, "RSSI"))?,
+                        status: entry["reception_status"]
+                            .as_str()
+                            .and_then(ReceptionStatus::from_trace)
+                            .ok_or_else(|| invalid(entry, "reception_status"))?,
+                    },
+                )
+            }
+            _ => return Err(invalid(entry, "direction")),
+        };
+        let time_s = time_s.ok_or_else(|| invalid(entry, "packet time"))?;
+        result.push(PlaybackPacket { node, time: SimTime::from_secs(time_s), packet, kind });
+    }
+    result.sort_by_key(|p| p.time);
+    Ok(result)
+}
+
+/// The model's nodes, placed for the viewer. Playback has no simulation
+/// entities, so each node gets its index as both entity IDs.
+pub fn playback_nodes(model: &Model) -> Vec<VisNodeInfo> {
+    model
+        .nodes()
+        .values()
+        .enumerate()
+        .map(|(index, node)| {
+            let props = node.properties();
+            let firmware_type: String = props.get(&FIRMWARE_TYPE);
+            let node_type = match firmware_type.to_lowercase().as_str() {
+                "repeater" => "Repeater",
+                "companion" => "Companion",
+                "room_server" | "roomserver" => "RoomServer",
+                _ => "Unknown",
+            };
+            VisNodeInfo {
+                name: node.name.clone(),
+                node_type: node_type.to_string(),
+                firmware_entity_id: index as u64,
+                radio_entity_id: index as u64,
+                location: GeoCoord::new(props.get(&LOCATION_LATITUDE), props.get(&LOCATION_LONGITUDE)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_packets_from_trace() {
+        let trace = json!([
+            { "origin": "R1", "type": "PACKET", "direction": "RX", "SNR": "7.5 dB", "RSSI": "-95.0 dBm",
+              "payload_hash": "AB", "packet_hex": "0102", "reception_status": "ok",
+              "packet_start_time_s": 1.0, "packet_end_time_s": 1.25 },
+            { "origin": "Alice", "type": "PACKET", "direction": "TX", "RSSI": "20 dBm",
+              "payload_hash": "AB", "packet_hex": "0102",
+              "packet_start_time_s": 1.0, "packet_end_time_s": 1.25 },
+            { "origin": "R2", "type": "PACKET", "direction": "RX", "SNR": "-21.0 dB", "RSSI": "-130.0 dBm",
+              "payload_hash": "CD", "packet_hex": "03", "reception_status": "weak",
+              "packet_start_time_s": 2.0, "packet_end_time_s": 2.5 },
+            { "origin": "Alice", "type": "TIMER", "timer_id": 1 }
+        ]);
+        let packets = packets_from_trace(&trace).unwrap();
+        assert_eq!(packets.len(), 3);
+
+        assert_eq!(packets[0].node, "Alice");
+        assert_eq!(packets[0].time, SimTime::from_secs(1.0));
+        assert_eq!(packets[0].packet, [1, 2]);
+        assert_eq!(packets[0].kind, PlaybackKind::Transmit { tx_power_dbm: 20.0 });
+
+        // Receptions are shown when they end, with the matching transmitter
+        assert_eq!(packets[1].time, SimTime::from_secs(1.25));
+        assert_eq!(
+            packets[1].kind,
+            PlaybackKind::Receive {
+                from: Some("Alice".to_string()),
+                snr_db: 7.5,
+                rssi_dbm: -95.0,
+                status: ReceptionStatus::Ok
+            }
+        );
+        assert!(matches!(
+            packets[2].kind,
+            PlaybackKind::Receive { from: None, status: ReceptionStatus::Weak, .. }
+        ));
+
+        assert!(packets_from_trace(&json!({})).is_err());
+        assert!(packets_from_trace(&json!([{ "origin": "A", "type": "PACKET", "direction": "TX" }])).is_err());
+    }
+}
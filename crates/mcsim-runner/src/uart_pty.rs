@@ -0,0 +1,219 @@
+//! Pseudo-terminal exposure of node UARTs.
+//!
+//! Besides its TCP port, each node's UART can be exposed as a pseudo-terminal
+//! so host applications that only talk to serial devices (companion apps,
+//! `meshcore-cli`, terminal programs) attach unmodified. The terminal is put
+//! in raw mode and linked by node name under a directory, e.g.
+//! `/tmp/mcsim/Alice -> /dev/pts/7`. A client counts as connected while it
+//! holds the terminal open; data the node sends while nobody does is dropped,
+//! as on the TCP port.
+//!
+//! Pseudo-terminals are a Unix facility. Windows has no built-in virtual COM
+//! ports; pair a com0com port with the node's TCP port instead (e.g. with
+//! com0com's `com2tcp`).
+
+#[cfg(unix)]
+pub(crate) use unix::PtyMaster;
+
+use std::path::{Path, PathBuf};
+
+/// Path a node's terminal is linked at under `dir`.
+pub(crate) fn link_path(dir: &Path, node_name: &str) -> PathBuf {
+    dir.join(node_name.replace(['/', '\\'], "_"))
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::OpenOptions;
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::{Path, PathBuf};
+    use std::pin::Pin;
+    use std::sync::Mutex;
+    use std::task::{ready, Context, Poll};
+    use tokio::io::unix::AsyncFd;
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    /// `ptsname` returns a static buffer.
+    static PTSNAME_LOCK: Mutex<()> = Mutex::new(());
+
+    fn cvt(result: libc::c_int) -> io::Result<libc::c_int> {
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Master side of a pseudo-terminal, read and written by the runner.
+    pub(crate) struct PtyMaster {
+        fd: AsyncFd<OwnedFd>,
+        device: PathBuf,
+    }
+
+    impl PtyMaster {
+        /// Open a new pseudo-terminal in raw mode. Must be called within a
+        /// Tokio runtime.
+        pub(crate) fn open() -> io::Result<Self> {
+            // SAFETY: plain libc calls on a file descriptor we own
+            let fd = unsafe {
+                let fd = cvt(libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY))?;
+                OwnedFd::from_raw_fd(fd)
+            };
+            let raw = fd.as_raw_fd();
+            unsafe {
+                cvt(libc::grantpt(raw))?;
+                cvt(libc::unlockpt(raw))?;
+                let flags = cvt(libc::fcntl(raw, libc::F_GETFL))?;
+                cvt(libc::fcntl(raw, libc::F_SETFL, flags | libc::O_NONBLOCK))?;
+            }
+            let device = {
+                let _guard = PTSNAME_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+                // SAFETY: the returned string is copied before the lock is released
+                let name = unsafe { libc::ptsname(raw) };
+                if name.is_null() {
+                    return Err(io::Error::last_os_error());
+                }
+                let name = unsafe { std::ffi::CStr::from_ptr(name) };
+                PathBuf::from(name.to_string_lossy().into_owned())
+            };
+            set_raw_mode(&device)?;
+            Ok(PtyMaster { fd: AsyncFd::new(fd)?, device })
+        }
+
+        /// Path of the terminal clients open, e.g. `/dev/pts/7`.
+        pub(crate) fn device(&self) -> &Path {
+            &self.device
+        }
+
+        /// Whether a client holds the terminal open. The master reports a
+        /// hangup while nobody does.
+        pub(crate) fn is_open(&self) -> bool {
+            let mut pfd = libc::pollfd {
+                fd: self.fd.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: polls a single valid descriptor without blocking
+            let ready = unsafe { libc::poll(&mut pfd, 1, 0) };
+            ready >= 0 && pfd.revents & libc::POLLHUP == 0
+        }
+    }
+
+    /// Put the terminal in raw mode, so binary frames pass unchanged.
+    fn set_raw_mode(device: &Path) -> io::Result<()> {
+        let slave = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(device)?;
+        // SAFETY: termios is plain data filled in by tcgetattr
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            cvt(libc::tcgetattr(slave.as_raw_fd(), &mut termios))?;
+            libc::cfmakeraw(&mut termios);
+            cvt(libc::tcsetattr(slave.as_raw_fd(), libc::TCSANOW, &termios))?;
+        }
+        Ok(())
+    }
+
+    impl AsyncRead for PtyMaster {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            loop {
+                let mut guard = ready!(self.fd.poll_read_ready(cx))?;
+                let unfilled = buf.initialize_unfilled();
+                let result = guard.try_io(|fd| {
+                    // SAFETY: reads into the unfilled part of the buffer
+                    let n = unsafe { libc::read(fd.as_raw_fd(), unfilled.as_mut_ptr().cast(), unfilled.len()) };
+                    if n < 0 {
+                        Err(io::Error::last_os_error())
+                    } else {
+                        Ok(n as usize)
+                    }
+                });
+                match result {
+                    Ok(Ok(n)) => {
+                        buf.advance(n);
+                        return Poll::Ready(Ok(()));
+                    }
+                    // The client closed the terminal: end of stream
+                    Ok(Err(e)) if e.raw_os_error() == Some(libc::EIO) => return Poll::Ready(Ok(())),
+                    Ok(Err(e)) => return Poll::Ready(Err(e)),
+                    Err(_would_block) => continue,
+                }
+            }
+        }
+    }
+
+    impl AsyncWrite for PtyMaster {
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            loop {
+                let mut guard = ready!(self.fd.poll_write_ready(cx))?;
+                let result = guard.try_io(|fd| {
+                    // SAFETY: writes from a valid buffer
+                    let n = unsafe { libc::write(fd.as_raw_fd(), buf.as_ptr().cast(), buf.len()) };
+                    if n < 0 {
+                        Err(io::Error::last_os_error())
+                    } else {
+                        Ok(n as usize)
+                    }
+                });
+                match result {
+                    Ok(Ok(n)) => return Poll::Ready(Ok(n)),
+                    Ok(Err(e)) if e.raw_os_error() == Some(libc::EIO) => {
+                        return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()))
+                    }
+                    Ok(Err(e)) => return Poll::Ready(Err(e)),
+                    Err(_would_block) => continue,
+                }
+            }
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_link_path() {
+        assert_eq!(link_path(Path::new("/tmp/mcsim"), "Alice"), PathBuf::from("/tmp/mcsim/Alice"));
+        assert_eq!(link_path(Path::new("/tmp/mcsim"), "a/b"), PathBuf::from("/tmp/mcsim/a_b"));
+    }
+
+    #[tokio::test]
+    async fn test_pty_round_trip() {
+        let mut pty = PtyMaster::open().unwrap();
+        assert!(!pty.is_open());
+
+        let mut client = std::fs::OpenOptions::new().read(true).write(true).open(pty.device()).unwrap();
+        assert!(pty.is_open());
+
+        // Raw mode: bytes pass through without echo or newline translation
+        client.write_all(&[0x3c, 0x0a, 0x00, 0xff]).unwrap();
+        let mut buf = [0u8; 4];
+        pty.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x3c, 0x0a, 0x00, 0xff]);
+
+        pty.write_all(b"\n>ok").await.unwrap();
+        let mut buf = [0u8; 4];
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"\n>ok");
+
+        drop(client);
+        assert!(!pty.is_open());
+        let mut buf = [0u8; 4];
+        assert_eq!(pty.read(&mut buf).await.unwrap(), 0);
+    }
+}
@@ -3,17 +3,35 @@
 //! This module provides TCP connections that expose the UART interface of each
 //! firmware entity in the simulation. Each node gets its own TCP port that can
 //! be used to send/receive serial data to/from that node's UART.
-
-use std::collections::{HashMap, HashSet};
-use std::io;
-use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::mpsc;
-use tokio::sync::Mutex;
-use std::sync::RwLock;
+//!
+//! Each bridge can optionally add latency and jitter ([`SerialLatency`]) in both
+//! directions to emulate links such as Bluetooth, without ever reordering data.
+//!
+//! On Unix each UART can also be exposed as a pseudo-terminal
+//! ([`UartServer::set_pty_dir`]) for host applications that expect a serial
+//! device. A node accepts data from its TCP client and its terminal alike, and
+//! sends to whichever is connected.
+
+use rand::Rng;
+use std::str::FromStr;
+use std::time::Duration;
+#[cfg(feature = "bridges")]
+use {
+    rand::SeedableRng,
+    rand_chacha::ChaCha8Rng,
+    std::collections::{HashMap, HashSet, VecDeque},
+    std::io,
+    std::path::PathBuf,
+    std::sync::{Arc, RwLock},
+    tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    tokio::net::TcpListener,
+    tokio::sync::mpsc,
+    tokio::sync::Mutex,
+    tokio::time::Instant,
+};
 
 /// Shared state tracking which clients are connected.
+#[cfg(feature = "bridges")]
 type ConnectedClients = Arc<RwLock<HashSet<u64>>>;
 
 // ============================================================================
@@ -33,9 +51,144 @@ pub struct UartNodeInfo {
     pub entity_id: u64,
     /// Public key (first 6 bytes as hex string).
     pub public_key_prefix: String,
+    /// Latency applied to data crossing the bridge.
+    pub latency: SerialLatency,
+    /// Pseudo-terminal the UART is also exposed on, and the link to it.
+    pub pty: Option<PtyInfo>,
+}
+
+/// A UART's pseudo-terminal.
+#[derive(Debug, Clone)]
+pub struct PtyInfo {
+    /// Terminal device clients open, e.g. `/dev/pts/7`.
+    pub device: std::path::PathBuf,
+    /// Link to the device named after the node.
+    pub link: std::path::PathBuf,
+}
+
+/// Distribution used to sample serial bridge jitter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JitterDistribution {
+    /// Uniform between zero and the jitter value.
+    #[default]
+    Uniform,
+    /// Normal with the jitter value as standard deviation (clamped at zero total latency).
+    Normal,
+    /// Exponential with the jitter value as mean.
+    Exponential,
+}
+
+impl FromStr for JitterDistribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "uniform" => Ok(JitterDistribution::Uniform),
+            "normal" | "gaussian" => Ok(JitterDistribution::Normal),
+            "exponential" => Ok(JitterDistribution::Exponential),
+            other => Err(format!(
+                "Unknown jitter distribution '{}' (expected uniform, normal or exponential)",
+                other
+            )),
+        }
+    }
+}
+
+/// Latency and jitter applied to a serial bridge.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SerialLatency {
+    /// Fixed latency added to every chunk of data.
+    pub base: Duration,
+    /// Jitter magnitude (meaning depends on `distribution`).
+    pub jitter: Duration,
+    /// Distribution the jitter is sampled from.
+    pub distribution: JitterDistribution,
+}
+
+impl SerialLatency {
+    /// Build a latency profile from millisecond values and a distribution name.
+    pub fn from_millis(latency_ms: f64, jitter_ms: f64, distribution: &str) -> Result<Self, String> {
+        if !(latency_ms.is_finite() && latency_ms >= 0.0 && jitter_ms.is_finite() && jitter_ms >= 0.0) {
+            return Err(format!(
+                "UART latency and jitter must be non-negative (got {} ms latency, {} ms jitter)",
+                latency_ms, jitter_ms
+            ));
+        }
+        Ok(SerialLatency {
+            base: Duration::from_secs_f64(latency_ms / 1000.0),
+            jitter: Duration::from_secs_f64(jitter_ms / 1000.0),
+            distribution: distribution.parse()?,
+        })
+    }
+
+    /// Whether this profile adds no delay at all.
+    pub fn is_zero(&self) -> bool {
+        self.base.is_zero() && self.jitter.is_zero()
+    }
+
+    /// Sample the delay for one chunk of data.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Duration {
+        let base = self.base.as_secs_f64();
+        let jitter = self.jitter.as_secs_f64();
+        let delay = match self.distribution {
+            JitterDistribution::Uniform => base + jitter * rng.gen::<f64>(),
+            JitterDistribution::Normal => mcsim_lora::sample_gaussian(rng, base, jitter),
+            JitterDistribution::Exponential => {
+                let u: f64 = rng.gen();
+                base - jitter * (1.0 - u).ln()
+            }
+        };
+        Duration::from_secs_f64(delay.max(0.0))
+    }
+}
+
+/// Queue that releases data after a sampled delay, preserving order.
+#[cfg(feature = "bridges")]
+struct DelayLine {
+    latency: SerialLatency,
+    rng: ChaCha8Rng,
+    queue: VecDeque<(Instant, Vec<u8>)>,
+    last_release: Option<Instant>,
+}
+
+#[cfg(feature = "bridges")]
+impl DelayLine {
+    fn new(latency: SerialLatency, seed: u64) -> Self {
+        DelayLine {
+            latency,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            queue: VecDeque::new(),
+            last_release: None,
+        }
+    }
+
+    /// Queue data arriving at `now`. A chunk is never released before the one
+    /// queued ahead of it, so a serial stream is delayed but not reordered.
+    fn push(&mut self, now: Instant, data: Vec<u8>) {
+        let mut release = now + self.latency.sample(&mut self.rng);
+        if let Some(last) = self.last_release {
+            release = release.max(last);
+        }
+        self.last_release = Some(release);
+        self.queue.push_back((release, data));
+    }
+
+    /// Release time of the next queued chunk.
+    fn next_release(&self) -> Option<Instant> {
+        self.queue.front().map(|(release, _)| *release)
+    }
+
+    /// Pop the next chunk if it is due at `now`.
+    fn pop_due(&mut self, now: Instant) -> Option<Vec<u8>> {
+        match self.queue.front() {
+            Some((release, _)) if *release <= now => self.queue.pop_front().map(|(_, data)| data),
+            _ => None,
+        }
+    }
 }
 
 /// Message types for UART communication.
+#[cfg(feature = "bridges")]
 #[derive(Debug)]
 pub enum UartMessage {
     /// Data received from TCP client to be sent to firmware.
@@ -49,12 +202,15 @@ pub enum UartMessage {
 }
 
 /// Handle for sending data to a UART connection.
+#[cfg(feature = "bridges")]
 #[derive(Clone)]
 pub struct UartHandle {
     tx_sender: mpsc::Sender<Vec<u8>>,
+    pty_sender: Option<mpsc::Sender<Vec<u8>>>,
     rx_receiver: Arc<Mutex<mpsc::Receiver<Vec<u8>>>>,
 }
 
+#[cfg(feature = "bridges")]
 impl UartHandle {
     /// Send data to the connected TCP client (firmware TX -> TCP).
     pub async fn send(&self, data: &[u8]) -> Result<(), mpsc::error::SendError<Vec<u8>>> {
@@ -83,6 +239,7 @@ impl UartHandle {
 }
 
 /// A UART TCP server that manages connections for all nodes.
+#[cfg(feature = "bridges")]
 pub struct UartServer {
     /// Map from entity ID to UART handle.
     handles: HashMap<u64, UartHandle>,
@@ -96,8 +253,13 @@ pub struct UartServer {
     reserved_ports: HashSet<u16>,
     /// Shared tracking of connected clients.
     connected_clients: ConnectedClients,
+    /// Shared tracking of clients holding a pseudo-terminal open.
+    pty_clients: ConnectedClients,
+    /// Directory to link pseudo-terminals in, if they are exposed.
+    pty_dir: Option<PathBuf>,
 }
 
+#[cfg(feature = "bridges")]
 impl UartServer {
     /// Create a new UART server starting at the given base port.
     pub fn new(base_port: u16) -> Self {
@@ -108,6 +270,8 @@ impl UartServer {
             next_port: base_port,
             reserved_ports: HashSet::new(),
             connected_clients: Arc::new(RwLock::new(HashSet::new())),
+            pty_clients: Arc::new(RwLock::new(HashSet::new())),
+            pty_dir: None,
         }
     }
 
@@ -122,6 +286,12 @@ impl UartServer {
         self.reserved_ports.insert(port);
     }
 
+    /// Also expose each UART as a pseudo-terminal, linked in `dir` by node
+    /// name. Only supported on Unix; [`start`](Self::start) fails elsewhere.
+    pub fn set_pty_dir(&mut self, dir: PathBuf) {
+        self.pty_dir = Some(dir);
+    }
+
     /// Get the next available port for sequential allocation, skipping reserved ports.
     fn allocate_next_port(&mut self) -> u16 {
         while self.reserved_ports.contains(&self.next_port) {
@@ -136,6 +306,7 @@ impl UartServer {
     /// Register a node for UART service.
     /// If `requested_port` is Some, that port will be used (it should have been reserved first).
     /// If `requested_port` is None, a port will be allocated sequentially.
+    /// `latency` is applied to data in both directions.
     /// Returns the allocated port number.
     pub fn register_node(
         &mut self,
@@ -144,6 +315,7 @@ impl UartServer {
         node_type: String,
         public_key: &[u8; 32],
         requested_port: Option<u16>,
+        latency: SerialLatency,
     ) -> u16 {
         let port = match requested_port {
             Some(p) => {
@@ -163,20 +335,35 @@ impl UartServer {
             port,
             entity_id,
             public_key_prefix: hex::encode(&public_key[..6]),
+            latency,
+            pty: None,
         });
 
         port
     }
 
-    /// Start TCP listeners for all registered nodes.
+    /// Start TCP listeners (and pseudo-terminals) for all registered nodes.
     /// Returns handles for each entity.
     pub async fn start(&mut self) -> io::Result<()> {
-        for info in &self.node_infos {
+        if let Some(dir) = &self.pty_dir {
+            std::fs::create_dir_all(dir)?;
+        }
+        for info in &mut self.node_infos {
             let (tx_sender, tx_receiver) = mpsc::channel::<Vec<u8>>(256);
             let (rx_sender, rx_receiver) = mpsc::channel::<Vec<u8>>(256);
 
+            let pty_sender = match &self.pty_dir {
+                Some(dir) => {
+                    let (pty_sender, pty) = start_pty(dir, info, rx_sender.clone(), self.pty_clients.clone())?;
+                    info.pty = Some(pty);
+                    Some(pty_sender)
+                }
+                None => None,
+            };
+
             let handle = UartHandle {
                 tx_sender,
+                pty_sender,
                 rx_receiver: Arc::new(Mutex::new(rx_receiver)),
             };
 
@@ -186,9 +373,10 @@ impl UartServer {
             let port = info.port;
             let name = info.name.clone();
             let entity_id = info.entity_id;
+            let latency = info.latency;
             let connected_clients = self.connected_clients.clone();
             tokio::spawn(async move {
-                if let Err(e) = run_uart_listener(port, &name, entity_id, tx_receiver, rx_sender, connected_clients).await {
+                if let Err(e) = run_uart_listener(port, &name, entity_id, latency, tx_receiver, rx_sender, connected_clients).await {
                     eprintln!("UART listener error for {}: {}", name, e);
                 }
             });
@@ -217,6 +405,11 @@ impl UartServer {
         self.connected_clients.clone()
     }
 
+    /// Get the tracker of clients holding a pseudo-terminal open.
+    pub fn pty_clients(&self) -> ConnectedClients {
+        self.pty_clients.clone()
+    }
+
     /// Print the node table to stderr.
     pub fn print_node_table(&self) {
         eprintln!();
@@ -249,11 +442,97 @@ impl UartServer {
     }
 }
 
+#[cfg(feature = "bridges")]
+impl Drop for UartServer {
+    fn drop(&mut self) {
+        for pty in self.node_infos.iter().filter_map(|info| info.pty.as_ref()) {
+            let _ = std::fs::remove_file(&pty.link);
+        }
+    }
+}
+
+/// Open a node's pseudo-terminal, link it and serve it. Returns the sender
+/// for data to its client.
+#[cfg(all(feature = "bridges", unix))]
+fn start_pty(
+    dir: &std::path::Path,
+    info: &UartNodeInfo,
+    rx_sender: mpsc::Sender<Vec<u8>>,
+    pty_clients: ConnectedClients,
+) -> io::Result<(mpsc::Sender<Vec<u8>>, PtyInfo)> {
+    let pty = crate::uart_pty::PtyMaster::open()?;
+    let link = crate::uart_pty::link_path(dir, &info.name);
+    // Replace a link left behind by an earlier run
+    if std::fs::symlink_metadata(&link).is_ok_and(|m| m.file_type().is_symlink()) {
+        std::fs::remove_file(&link)?;
+    }
+    std::os::unix::fs::symlink(pty.device(), &link)?;
+    let pty_info = PtyInfo { device: pty.device().to_path_buf(), link };
+
+    let (tx_sender, tx_receiver) = mpsc::channel::<Vec<u8>>(256);
+    tokio::spawn(run_pty_link(pty, info.entity_id, info.latency, tx_receiver, rx_sender, pty_clients));
+    Ok((tx_sender, pty_info))
+}
+
+#[cfg(all(feature = "bridges", not(unix)))]
+fn start_pty(
+    _dir: &std::path::Path,
+    _info: &UartNodeInfo,
+    _rx_sender: mpsc::Sender<Vec<u8>>,
+    _pty_clients: ConnectedClients,
+) -> io::Result<(mpsc::Sender<Vec<u8>>, PtyInfo)> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "pseudo-terminals are only available on Unix; on Windows, pair a com0com virtual COM port with the node's TCP port",
+    ))
+}
+
+/// Serve a node's pseudo-terminal to whichever client opens it.
+#[cfg(all(feature = "bridges", unix))]
+async fn run_pty_link(
+    mut pty: crate::uart_pty::PtyMaster,
+    entity_id: u64,
+    latency: SerialLatency,
+    mut tx_receiver: mpsc::Receiver<Vec<u8>>,
+    rx_sender: mpsc::Sender<Vec<u8>>,
+    pty_clients: ConnectedClients,
+) {
+    loop {
+        // Wait for a client to open the terminal
+        while !pty.is_open() {
+            if rx_sender.is_closed() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        if let Ok(mut clients) = pty_clients.write() {
+            clients.insert(entity_id);
+        }
+
+        // The terminal closing ends the connection like a TCP disconnect
+        let _ = handle_uart_connection(&mut pty, &mut tx_receiver, &rx_sender, latency, entity_id).await;
+
+        if let Ok(mut clients) = pty_clients.write() {
+            clients.remove(&entity_id);
+        }
+        if rx_sender.is_closed() {
+            return;
+        }
+        // Wait for the client to have closed the terminal before polling again
+        while pty.is_open() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
+
 /// Run a TCP listener for a single UART.
+#[cfg(feature = "bridges")]
 async fn run_uart_listener(
     port: u16,
     _name: &str,
     entity_id: u64,
+    latency: SerialLatency,
     mut tx_receiver: mpsc::Receiver<Vec<u8>>,
     rx_sender: mpsc::Sender<Vec<u8>>,
     connected_clients: ConnectedClients,
@@ -275,6 +554,8 @@ async fn run_uart_listener(
             stream,
             &mut tx_receiver,
             &rx_sender,
+            latency,
+            entity_id,
         ).await;
 
         // Mark as disconnected
@@ -288,13 +569,20 @@ async fn run_uart_listener(
     }
 }
 
-/// Handle a single UART TCP connection.
-async fn handle_uart_connection(
-    mut stream: TcpStream,
+/// Handle a single UART connection (a TCP client or a pseudo-terminal).
+#[cfg(feature = "bridges")]
+async fn handle_uart_connection<S: AsyncRead + AsyncWrite>(
+    stream: S,
     tx_receiver: &mut mpsc::Receiver<Vec<u8>>,
     rx_sender: &mpsc::Sender<Vec<u8>>,
+    latency: SerialLatency,
+    seed: u64,
 ) -> io::Result<()> {
-    let (mut reader, mut writer) = stream.split();
+    if !latency.is_zero() {
+        return handle_delayed_uart_connection(stream, tx_receiver, rx_sender, latency, seed).await;
+    }
+
+    let (mut reader, mut writer) = tokio::io::split(stream);
     let mut read_buf = [0u8; 1024];
 
     loop {
@@ -333,11 +621,66 @@ async fn handle_uart_connection(
     }
 }
 
+/// Handle a UART connection with latency/jitter applied in both directions.
+#[cfg(feature = "bridges")]
+async fn handle_delayed_uart_connection<S: AsyncRead + AsyncWrite>(
+    stream: S,
+    tx_receiver: &mut mpsc::Receiver<Vec<u8>>,
+    rx_sender: &mpsc::Sender<Vec<u8>>,
+    latency: SerialLatency,
+    seed: u64,
+) -> io::Result<()> {
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let mut read_buf = [0u8; 1024];
+    // Separate streams so the two directions jitter independently
+    let mut to_firmware = DelayLine::new(latency, seed.wrapping_mul(2));
+    let mut to_client = DelayLine::new(latency, seed.wrapping_mul(2).wrapping_add(1));
+
+    loop {
+        let next_rx = to_firmware.next_release();
+        let next_tx = to_client.next_release();
+
+        tokio::select! {
+            // Read from TCP client -> queue for firmware RX
+            result = reader.read(&mut read_buf) => {
+                match result {
+                    Ok(0) => return Ok(()),
+                    Ok(n) => to_firmware.push(Instant::now(), read_buf[..n].to_vec()),
+                    Err(e) => return Err(e),
+                }
+            }
+
+            // Receive from firmware TX -> queue for TCP client
+            Some(data) = tx_receiver.recv() => {
+                to_client.push(Instant::now(), data);
+            }
+
+            // Deliver due data to firmware
+            _ = tokio::time::sleep_until(next_rx.unwrap_or_else(Instant::now)), if next_rx.is_some() => {
+                while let Some(data) = to_firmware.pop_due(Instant::now()) {
+                    if rx_sender.send(data).await.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+
+            // Deliver due data to TCP client
+            _ = tokio::time::sleep_until(next_tx.unwrap_or_else(Instant::now)), if next_tx.is_some() => {
+                while let Some(data) = to_client.pop_due(Instant::now()) {
+                    writer.write_all(&data).await?;
+                }
+                writer.flush().await?;
+            }
+        }
+    }
+}
+
 // ============================================================================
 // Synchronous API for use with non-async event loop
 // ============================================================================
 
 /// A synchronous wrapper around UartServer for use in the main event loop.
+#[cfg(feature = "bridges")]
 pub struct SyncUartManager {
     /// Runtime handle for spawning async tasks.
     runtime: tokio::runtime::Handle,
@@ -347,21 +690,35 @@ pub struct SyncUartManager {
     handles: HashMap<u64, UartHandle>,
     /// Shared connected clients tracker.
     connected_clients: ConnectedClients,
+    /// Shared tracker of clients holding a pseudo-terminal open.
+    pty_clients: ConnectedClients,
 }
 
+#[cfg(feature = "bridges")]
 impl SyncUartManager {
     /// Create a new synchronous UART manager.
     pub fn new(base_port: u16, runtime: tokio::runtime::Handle) -> Self {
         let server = UartServer::new(base_port);
         let connected_clients = server.connected_clients();
+        let pty_clients = server.pty_clients();
         SyncUartManager {
             runtime,
             server: Arc::new(Mutex::new(server)),
             handles: HashMap::new(),
             connected_clients,
+            pty_clients,
         }
     }
 
+    /// Also expose each UART as a pseudo-terminal, linked in `dir` by node
+    /// name (Unix only).
+    pub fn set_pty_dir(&mut self, dir: PathBuf) {
+        let server = self.server.clone();
+        self.runtime.block_on(async {
+            server.lock().await.set_pty_dir(dir);
+        });
+    }
+
     /// Reserve a specific port to prevent sequential allocation from using it.
     /// Call this before registering nodes to reserve explicitly assigned ports.
     pub fn reserve_port(&mut self, port: u16) {
@@ -375,6 +732,7 @@ impl SyncUartManager {
     /// Register a node for UART service (synchronous).
     /// If `requested_port` is Some, that port will be used.
     /// If `requested_port` is None, a port will be allocated sequentially.
+    /// `latency` is applied to data in both directions.
     pub fn register_node(
         &mut self,
         entity_id: u64,
@@ -382,12 +740,13 @@ impl SyncUartManager {
         node_type: String,
         public_key: &[u8; 32],
         requested_port: Option<u16>,
+        latency: SerialLatency,
     ) -> u16 {
         let server = self.server.clone();
         let public_key = *public_key;
         self.runtime.block_on(async {
             let mut server = server.lock().await;
-            server.register_node(entity_id, name, node_type, &public_key, requested_port)
+            server.register_node(entity_id, name, node_type, &public_key, requested_port, latency)
         })
     }
 
@@ -425,30 +784,19 @@ impl SyncUartManager {
         });
     }
 
-    /// Send data to a node's UART (firmware TX -> TCP).
-    /// Only sends if a client is connected, otherwise silently drops data.
+    /// Send data to a node's UART (firmware TX -> TCP and pseudo-terminal).
+    /// Only sends to connected clients, otherwise silently drops data.
     pub fn send_to_client(&self, entity_id: u64, data: &[u8]) {
-        // Only send if a client is actually connected
-        if !self.is_client_connected(entity_id) {
+        let Some(handle) = self.handles.get(&entity_id) else {
             return;
+        };
+        // Only send if a client is actually connected
+        if is_connected(&self.connected_clients, entity_id) {
+            forward(&handle.tx_sender, entity_id, data);
         }
-        
-        if let Some(handle) = self.handles.get(&entity_id) {
-            let tx_sender = handle.tx_sender.clone();
-            let data = data.to_vec();
-            
-            // Use try_send to avoid blocking - drop data if buffer is full
-            if let Err(e) = tx_sender.try_send(data) {
-                match e {
-                    mpsc::error::TrySendError::Full(_) => {
-                        // Buffer full even with client connected - drop data
-                        // This shouldn't happen often with a connected client
-                        eprintln!("[UART] TX buffer full for entity {} (client connected but slow)", entity_id);
-                    }
-                    mpsc::error::TrySendError::Closed(_) => {
-                        eprintln!("[UART] Channel closed for entity {}", entity_id);
-                    }
-                }
+        if let Some(pty_sender) = &handle.pty_sender {
+            if is_connected(&self.pty_clients, entity_id) {
+                forward(pty_sender, entity_id, data);
             }
         }
     }
@@ -467,8 +815,110 @@ impl SyncUartManager {
         })
     }
 
-    /// Check if a client is connected for the given entity.
+    /// Check if a client is connected for the given entity, over TCP or a
+    /// pseudo-terminal.
     pub fn is_client_connected(&self, entity_id: u64) -> bool {
-        self.connected_clients.read().map(|c| c.contains(&entity_id)).unwrap_or(false)
+        is_connected(&self.connected_clients, entity_id) || is_connected(&self.pty_clients, entity_id)
+    }
+}
+
+#[cfg(feature = "bridges")]
+fn is_connected(clients: &ConnectedClients, entity_id: u64) -> bool {
+    clients.read().map(|c| c.contains(&entity_id)).unwrap_or(false)
+}
+
+/// Queue data for a connected client without blocking.
+#[cfg(feature = "bridges")]
+fn forward(tx_sender: &mpsc::Sender<Vec<u8>>, entity_id: u64, data: &[u8]) {
+    // Use try_send to avoid blocking - drop data if buffer is full
+    if let Err(e) = tx_sender.try_send(data.to_vec()) {
+        match e {
+            mpsc::error::TrySendError::Full(_) => {
+                // Buffer full even with client connected - drop data
+                // This shouldn't happen often with a connected client
+                eprintln!("[UART] TX buffer full for entity {} (client connected but slow)", entity_id);
+            }
+            mpsc::error::TrySendError::Closed(_) => {
+                eprintln!("[UART] Channel closed for entity {}", entity_id);
+            }
+        }
+    }
+}
+
+/// Stand-in for the UART manager when the TCP bridges are not compiled in
+/// (the `bridges` feature). It can't be created, so an event loop never has
+/// one.
+#[cfg(not(feature = "bridges"))]
+pub struct SyncUartManager {
+    never: std::convert::Infallible,
+}
+
+#[cfg(not(feature = "bridges"))]
+impl SyncUartManager {
+    /// Send data to a node's UART (firmware TX -> TCP).
+    pub fn send_to_client(&self, _entity_id: u64, _data: &[u8]) {
+        match self.never {}
+    }
+
+    /// Try to receive data from a node's UART (TCP -> firmware RX).
+    pub fn try_recv_from_client(&self, _entity_id: u64) -> Option<Vec<u8>> {
+        match self.never {}
+    }
+
+    /// Check if a client is connected for the given entity.
+    pub fn is_client_connected(&self, _entity_id: u64) -> bool {
+        match self.never {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_serial_latency_from_millis() {
+        let latency = SerialLatency::from_millis(50.0, 20.0, "Normal").unwrap();
+        assert_eq!(latency.base, Duration::from_millis(50));
+        assert_eq!(latency.jitter, Duration::from_millis(20));
+        assert_eq!(latency.distribution, JitterDistribution::Normal);
+
+        assert!(SerialLatency::from_millis(0.0, 0.0, "uniform").unwrap().is_zero());
+        assert!(SerialLatency::from_millis(-1.0, 0.0, "uniform").is_err());
+        assert!(SerialLatency::from_millis(10.0, 5.0, "pareto").is_err());
+    }
+
+    #[test]
+    fn test_serial_latency_sample_bounds() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let uniform = SerialLatency::from_millis(10.0, 5.0, "uniform").unwrap();
+        let normal = SerialLatency::from_millis(1.0, 50.0, "normal").unwrap();
+        for _ in 0..1000 {
+            let d = uniform.sample(&mut rng);
+            assert!(d >= Duration::from_millis(10) && d <= Duration::from_millis(15));
+            // Normal jitter far larger than the base must never go negative
+            let _ = normal.sample(&mut rng);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bridges")]
+    fn test_delay_line_preserves_order() {
+        let latency = SerialLatency::from_millis(0.0, 100.0, "exponential").unwrap();
+        let mut line = DelayLine::new(latency, 1);
+        let start = Instant::now();
+        for i in 0..50u8 {
+            line.push(start + Duration::from_millis(i as u64), vec![i]);
+        }
+
+        let mut released = Vec::new();
+        let mut last = start;
+        while let Some(release) = line.next_release() {
+            assert!(release >= last);
+            last = release;
+            released.extend(line.pop_due(release).unwrap());
+        }
+        assert_eq!(released, (0..50u8).collect::<Vec<_>>());
     }
 }
@@ -0,0 +1,88 @@
+//! Wall-clock datetimes for simulation time.
+//!
+//! Simulation time counts from zero while node clocks start at
+//! `firmware/initial_rtc_secs`. A [`WallClock`] maps one onto the other in a
+//! timezone, so traces and reports can say when something happened in local
+//! time, which is how operators reason about diurnal behavior. It is enabled
+//! by `runner/timezone` or `mcsim run --timezone`:
+//!
+//! - trace entries carry the local datetime as their `timestamp` plus the
+//!   simulation time as `sim_time_s`;
+//! - serial capture lines gain a `wall_time` next to `time_s`;
+//! - alerts printed during the run are followed by the local datetime.
+
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::{RunnerError, SimTime};
+
+/// Maps simulation time to datetimes in a timezone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WallClock {
+    /// Datetime at simulation time zero.
+    epoch: DateTime<Utc>,
+    timezone: Tz,
+}
+
+impl WallClock {
+    /// Clock starting at Unix time `initial_rtc_secs` in the IANA timezone
+    /// named `timezone` (e.g. `America/Los_Angeles` or `UTC`).
+    pub fn new(initial_rtc_secs: u64, timezone: &str) -> Result<Self, RunnerError> {
+        let timezone: Tz = timezone.parse().map_err(|_| {
+            RunnerError::ConfigError(format!(
+                "Unknown timezone '{}' (expected an IANA name such as America/Los_Angeles, or UTC)",
+                timezone
+            ))
+        })?;
+        let epoch = i64::try_from(initial_rtc_secs)
+            .ok()
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .ok_or_else(|| {
+                RunnerError::ConfigError(format!("firmware/initial_rtc_secs {} is out of range", initial_rtc_secs))
+            })?;
+        Ok(Self { epoch, timezone })
+    }
+
+    /// The timezone.
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    /// Local datetime of a simulation time.
+    pub fn datetime(&self, time: SimTime) -> DateTime<Tz> {
+        let offset = chrono::Duration::microseconds(time.as_micros().min(i64::MAX as u64) as i64);
+        (self.epoch + offset).with_timezone(&self.timezone)
+    }
+
+    /// RFC 3339 datetime with milliseconds and UTC offset, e.g.
+    /// `2023-11-14T14:13:20.000-08:00`.
+    pub fn format(&self, time: SimTime) -> String {
+        self.datetime(time).format("%Y-%m-%dT%H:%M:%S%.3f%:z").to_string()
+    }
+
+    /// Short local datetime with the zone abbreviation, e.g.
+    /// `2023-11-14 14:13:20 PST`.
+    pub fn format_short(&self, time: SimTime) -> String {
+        self.datetime(time).format("%Y-%m-%d %H:%M:%S %Z").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wall_clock() {
+        // 2023-11-14T22:13:20Z
+        let clock = WallClock::new(1_700_000_000, "America/Los_Angeles").unwrap();
+        assert_eq!(clock.format(SimTime::ZERO), "2023-11-14T14:13:20.000-08:00");
+        assert_eq!(clock.format_short(SimTime::from_secs(3600.5)), "2023-11-14 15:13:20 PST");
+
+        // Daylight saving time applies by date
+        let summer = WallClock::new(1_688_000_000, "Europe/Berlin").unwrap();
+        assert!(summer.format(SimTime::ZERO).ends_with("+02:00"));
+
+        assert!(WallClock::new(0, "UTC").is_ok());
+        assert!(matches!(WallClock::new(0, "Mars/Olympus"), Err(RunnerError::ConfigError(_))));
+    }
+}
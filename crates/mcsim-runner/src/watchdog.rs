@@ -49,7 +49,7 @@ impl CurrentEventInfo {
 }
 
 /// Describe an event payload for logging.
-fn describe_event_payload(payload: &EventPayload) -> (String, String) {
+pub(crate) fn describe_event_payload(payload: &EventPayload) -> (String, String) {
     match payload {
         EventPayload::TransmitAir(e) => (
             "TransmitAir".to_string(),
@@ -67,6 +67,14 @@ fn describe_event_payload(payload: &EventPayload) -> (String, String) {
                 e.packet.payload.len()
             ),
         ),
+        EventPayload::TransmitInterference(e) => (
+            "TransmitInterference".to_string(),
+            format!("kind={:?}, freq={}Hz, end={:.3}s", e.kind, e.frequency_hz, e.end_time.as_secs_f64()),
+        ),
+        EventPayload::ReceiveInterference(e) => (
+            "ReceiveInterference".to_string(),
+            format!("src={}, kind={:?}, mean_snr={:.1}dB", e.source_id.0, e.kind, e.mean_snr_db_at20dbm),
+        ),
         EventPayload::RadioRxPacket(e) => (
             "RadioRxPacket".to_string(),
             format!("snr={:.1}dB, rssi={:.1}dBm, len={}, collided={}", e.snr_db, e.rssi_dbm, e.packet.payload.len(), e.was_collided),
@@ -75,6 +83,26 @@ fn describe_event_payload(payload: &EventPayload) -> (String, String) {
             "RadioStateChanged".to_string(),
             format!("{:?}", e.new_state),
         ),
+        EventPayload::ChannelActivity(e) => (
+            "ChannelActivity".to_string(),
+            format!("busy={}", e.busy),
+        ),
+        EventPayload::BatteryLevel(e) => (
+            "BatteryLevel".to_string(),
+            format!("mv={}, depleted={}", e.millivolts, e.depleted),
+        ),
+        EventPayload::PowerOff(e) => (
+            "PowerOff".to_string(),
+            format!("reason={}", e.reason),
+        ),
+        EventPayload::PowerOn(e) => (
+            "PowerOn".to_string(),
+            format!("reason={}", e.reason),
+        ),
+        EventPayload::Reboot(e) => (
+            "Reboot".to_string(),
+            format!("reason={}", e.reason),
+        ),
         EventPayload::RadioTxRequest(e) => (
             "RadioTxRequest".to_string(),
             format!("pkt_len={}", e.packet.payload.len()),
@@ -103,6 +131,22 @@ fn describe_event_payload(payload: &EventPayload) -> (String, String) {
             "Timer".to_string(),
             format!("timer_id={}", timer_id),
         ),
+        EventPayload::MoveNode(e) => (
+            "MoveNode".to_string(),
+            format!("radio={}, lat={:.6}, lon={:.6}", e.radio_id.0, e.position.latitude, e.position.longitude),
+        ),
+        EventPayload::AdvertSuppressed(e) => (
+            "AdvertSuppressed".to_string(),
+            format!("policy={}, airtime_us={}, utilization={:.3}", e.policy, e.airtime.as_micros(), e.utilization),
+        ),
+        EventPayload::PositionFix(e) => (
+            "PositionFix".to_string(),
+            format!("lat={:.6}, lon={:.6}", e.position.latitude, e.position.longitude),
+        ),
+        EventPayload::ReloadScript(e) => (
+            "ReloadScript".to_string(),
+            format!("script_len={}", e.script.len()),
+        ),
         EventPayload::SimulationEnd => (
             "SimulationEnd".to_string(),
             String::new(),
@@ -0,0 +1,407 @@
+//! Named checkpoints and what-if branches.
+//!
+//! `mcsim what-if` compares intervention strategies from an identical
+//! network state. A plan file names checkpoints, points in simulated time,
+//! and branches, each continuing from a checkpoint with its own changes:
+//!
+//! ```yaml
+//! checkpoints:
+//!   - name: congested
+//!     at_s: 3600
+//! branches:
+//!   - name: as-is
+//!     from: congested
+//!   - name: mast-down
+//!     from: congested
+//!     power_off: [Mast]
+//!   - name: low-power
+//!     from: congested
+//!     send:
+//!       Mast: ["set tx 14"]
+//!   - name: relocated
+//!     from: congested
+//!     move:
+//!       Mast: { lat: 47.61, lon: -122.33 }
+//!     traffic: [quiet.yaml]
+//! ```
+//!
+//! Firmware state lives in the native firmware libraries and can't be
+//! saved, so a checkpoint is not a stored snapshot: every branch rebuilds
+//! the scenario with the same seed and runs it up to the checkpoint, which
+//! reproduces the same state because runs are deterministic. A digest of
+//! the events processed up to the checkpoint (see
+//! [`EventDigest`](crate::event_digest::EventDigest)) confirms that
+//! all branches from a checkpoint started from the same state.
+//!
+//! At the checkpoint a branch powers off nodes, moves them, sends CLI
+//! commands to their serial ports and replaces agent traffic with the
+//! `traffic` section of other model files, then runs to the end. The report
+//! gives each branch's activity and flood coverage after its checkpoint,
+//! and how it differs from the first branch from the same checkpoint.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use mcsim_common::GeoCoord;
+use serde::{Deserialize, Serialize};
+
+use crate::script_reload::ScriptReloader;
+use crate::timer_jitter::{BehaviorDifference, BehaviorSummary};
+use crate::{EventLoop, RunnerError, SimTime};
+
+/// Checkpoints and the branches continuing from them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WhatIfPlan {
+    /// Named points in simulated time.
+    pub checkpoints: Vec<Checkpoint>,
+    /// Continuations, run in order.
+    pub branches: Vec<Branch>,
+}
+
+/// A named point in simulated time branches continue from.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Checkpoint {
+    /// Checkpoint name.
+    pub name: String,
+    /// Simulation time of the checkpoint in seconds.
+    pub at_s: f64,
+}
+
+impl Checkpoint {
+    /// Simulation time of the checkpoint.
+    pub fn time(&self) -> SimTime {
+        SimTime::from_secs(self.at_s)
+    }
+}
+
+/// A what-if continuation from a checkpoint.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Branch {
+    /// Branch name.
+    pub name: String,
+    /// Name of the checkpoint the branch continues from.
+    pub from: String,
+    /// Nodes powered off at the checkpoint.
+    #[serde(default)]
+    pub power_off: Vec<String>,
+    /// Nodes moved at the checkpoint.
+    #[serde(default, rename = "move")]
+    pub move_nodes: BTreeMap<String, Position>,
+    /// CLI commands sent to nodes' serial ports at the checkpoint, in order.
+    #[serde(default)]
+    pub send: BTreeMap<String, Vec<String>>,
+    /// Model files whose `traffic` section replaces the agents' traffic at
+    /// the checkpoint (merged in order).
+    #[serde(default)]
+    pub traffic: Vec<PathBuf>,
+}
+
+/// Position a node is moved to.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Position {
+    /// Latitude in degrees.
+    pub lat: f64,
+    /// Longitude in degrees.
+    pub lon: f64,
+    /// Altitude in meters.
+    #[serde(default)]
+    pub alt: Option<f64>,
+}
+
+impl WhatIfPlan {
+    /// Read a plan from a YAML file.
+    pub fn load(path: &Path) -> Result<Self, RunnerError> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_yaml(&text).map_err(|e| RunnerError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parse a plan from YAML.
+    pub fn from_yaml(text: &str) -> Result<Self, RunnerError> {
+        serde_yaml::from_str(text).map_err(|e| RunnerError::ConfigError(format!("Invalid what-if plan: {}", e)))
+    }
+
+    /// Check the plan against a scenario's nodes and the length of the run.
+    pub fn validate<'a>(&self, nodes: impl IntoIterator<Item = &'a str>, duration: SimTime) -> Result<(), RunnerError> {
+        let nodes: HashSet<&str> = nodes.into_iter().collect();
+        let invalid = |message: String| Err(RunnerError::ConfigError(message));
+        if self.branches.is_empty() {
+            return invalid("The what-if plan has no branches".to_string());
+        }
+
+        let mut names = HashSet::new();
+        for checkpoint in &self.checkpoints {
+            if !names.insert(checkpoint.name.as_str()) {
+                return invalid(format!("Checkpoint '{}' is defined twice", checkpoint.name));
+            }
+            if !(checkpoint.at_s >= 0.0 && checkpoint.time() < duration) {
+                return invalid(format!("Checkpoint '{}' must be within the run", checkpoint.name));
+            }
+        }
+
+        let mut names = HashSet::new();
+        for branch in &self.branches {
+            if !names.insert(branch.name.as_str()) {
+                return invalid(format!("Branch '{}' is defined twice", branch.name));
+            }
+            if self.checkpoint(&branch.from).is_none() {
+                return invalid(format!("Branch '{}': no checkpoint named '{}'", branch.name, branch.from));
+            }
+            if let Some(node) = branch.nodes().find(|node| !nodes.contains(node)) {
+                return invalid(format!("Branch '{}': unknown node '{}'", branch.name, node));
+            }
+            for (node, position) in &branch.move_nodes {
+                if !(-90.0..=90.0).contains(&position.lat) || !(-180.0..=180.0).contains(&position.lon) {
+                    return invalid(format!("Branch '{}': position of '{}' out of range", branch.name, node));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Find a checkpoint by name.
+    pub fn checkpoint(&self, name: &str) -> Option<&Checkpoint> {
+        self.checkpoints.iter().find(|c| c.name == name)
+    }
+}
+
+impl Branch {
+    /// Names of the nodes the branch changes.
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.power_off
+            .iter()
+            .chain(self.move_nodes.keys())
+            .chain(self.send.keys())
+            .map(String::as_str)
+    }
+
+    /// Apply the branch's changes at the event loop's current time.
+    pub fn apply(&self, event_loop: &mut EventLoop) -> Result<(), RunnerError> {
+        let now = event_loop.current_time();
+        let unknown = |node: &str| RunnerError::ConfigError(format!("Branch '{}': unknown node '{}'", self.name, node));
+        for node in &self.power_off {
+            if !event_loop.schedule_power_off(node, now, &format!("what-if branch {}", self.name)) {
+                return Err(unknown(node));
+            }
+        }
+        for (node, position) in &self.move_nodes {
+            let coord = match position.alt {
+                Some(alt) => GeoCoord::with_altitude(position.lat, position.lon, alt),
+                None => GeoCoord::new(position.lat, position.lon),
+            };
+            if !event_loop.move_node(node, coord) {
+                return Err(unknown(node));
+            }
+        }
+        for (node, commands) in &self.send {
+            for command in commands {
+                let mut data = command.clone().into_bytes();
+                data.push(b'\r');
+                if !event_loop.send_serial(node, data) {
+                    return Err(unknown(node));
+                }
+            }
+        }
+        if !self.traffic.is_empty() {
+            let traffic = ScriptReloader::new(self.traffic.clone(), false).load()?;
+            event_loop.reload_traffic(&traffic);
+        }
+        Ok(())
+    }
+}
+
+/// Activity of a branch after its checkpoint.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BranchOutcome {
+    /// Branch name.
+    pub branch: String,
+    /// Checkpoint the branch continued from.
+    pub checkpoint: String,
+    /// Digest of the events processed up to the checkpoint; equal for all
+    /// branches from the same checkpoint.
+    pub checkpoint_digest: String,
+    /// Packets transmitted after the checkpoint.
+    pub tx: u64,
+    /// Packets received after the checkpoint.
+    pub rx: u64,
+    /// Collisions after the checkpoint.
+    pub collisions: u64,
+    /// Messages sent by agents after the checkpoint.
+    pub messages_sent: u64,
+    /// Messages acknowledged after the checkpoint.
+    pub messages_acked: u64,
+    /// Mean flood coverage of the powered nodes after the checkpoint.
+    pub coverage: Option<f64>,
+    /// Differences from the first branch from the same checkpoint, over the
+    /// whole run.
+    pub differences: Vec<BehaviorDifference>,
+    /// Full-run activity, for comparing with other branches.
+    #[serde(skip)]
+    summary: BehaviorSummary,
+}
+
+impl BranchOutcome {
+    /// Summarize a branch from the activity at its checkpoint and at the end
+    /// of the run.
+    pub fn new(
+        branch: &Branch,
+        checkpoint_digest: u64,
+        at_checkpoint: &BehaviorSummary,
+        at_end: BehaviorSummary,
+        coverage: Option<f64>,
+    ) -> Self {
+        let total = |summary: &BehaviorSummary| {
+            summary.nodes.values().fold((0, 0, 0), |(tx, rx, collisions), node| {
+                (tx + node.tx, rx + node.rx, collisions + node.collisions)
+            })
+        };
+        let (tx_before, rx_before, collisions_before) = total(at_checkpoint);
+        let (tx, rx, collisions) = total(&at_end);
+        Self {
+            branch: branch.name.clone(),
+            checkpoint: branch.from.clone(),
+            checkpoint_digest: format!("{:016x}", checkpoint_digest),
+            tx: tx.saturating_sub(tx_before),
+            rx: rx.saturating_sub(rx_before),
+            collisions: collisions.saturating_sub(collisions_before),
+            messages_sent: at_end.messages_sent.saturating_sub(at_checkpoint.messages_sent),
+            messages_acked: at_end.messages_acked.saturating_sub(at_checkpoint.messages_acked),
+            coverage,
+            differences: Vec::new(),
+            summary: at_end,
+        }
+    }
+}
+
+/// Outcome of every branch of a what-if plan.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WhatIfReport {
+    /// Seed shared by all branches.
+    pub seed: u64,
+    /// Length of each run in seconds.
+    pub duration_s: f64,
+    /// One entry per branch, in plan order.
+    pub branches: Vec<BranchOutcome>,
+    /// Checkpoints whose branches did not start from the same state.
+    pub diverged_checkpoints: Vec<String>,
+}
+
+impl WhatIfReport {
+    /// Build a report, comparing each branch with the first branch from the
+    /// same checkpoint.
+    pub fn new(seed: u64, duration: SimTime, mut branches: Vec<BranchOutcome>) -> Self {
+        let mut diverged = BTreeSet::new();
+        for i in 0..branches.len() {
+            let Some(reference) = branches[..i].iter().find(|b| b.checkpoint == branches[i].checkpoint) else {
+                continue;
+            };
+            if reference.checkpoint_digest != branches[i].checkpoint_digest {
+                diverged.insert(reference.checkpoint.clone());
+            }
+            let differences = branches[i].summary.diff(&reference.summary);
+            branches[i].differences = differences;
+        }
+        Self {
+            seed,
+            duration_s: duration.as_secs_f64(),
+            branches,
+            diverged_checkpoints: diverged.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Display for WhatIfReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "What-if branches (seed {}, runs end at {:.0}s), activity after each checkpoint:", self.seed, self.duration_s)?;
+        writeln!(
+            f,
+            "  {:<20} {:<16} {:>8} {:>8} {:>6} {:>6} {:>6} {:>9} {:>6}",
+            "branch", "from", "tx", "rx", "coll", "sent", "acked", "coverage", "diffs"
+        )?;
+        for outcome in &self.branches {
+            writeln!(
+                f,
+                "  {:<20} {:<16} {:>8} {:>8} {:>6} {:>6} {:>6} {:>9} {:>6}",
+                outcome.branch,
+                outcome.checkpoint,
+                outcome.tx,
+                outcome.rx,
+                outcome.collisions,
+                outcome.messages_sent,
+                outcome.messages_acked,
+                outcome.coverage.map_or("-".to_string(), |c| format!("{:.1}%", c * 100.0)),
+                outcome.differences.len()
+            )?;
+        }
+        for checkpoint in &self.diverged_checkpoints {
+            writeln!(
+                f,
+                "  ⚠ Branches from '{}' did not start from the same state; the run is not deterministic",
+                checkpoint
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer_jitter::NodeBehavior;
+
+    const PLAN: &str = "\
+checkpoints:
+  - { name: busy, at_s: 60 }
+branches:
+  - { name: as-is, from: busy }
+  - name: mast-down
+    from: busy
+    power_off: [Mast]
+    send: { Base: [\"set tx 14\"] }
+  - { name: moved, from: busy, move: { Base: { lat: 47.6, lon: -122.3 } } }
+";
+
+    #[test]
+    fn test_plan_validation() {
+        let plan = WhatIfPlan::from_yaml(PLAN).unwrap();
+        let nodes = ["Mast", "Base"];
+        let duration = SimTime::from_secs(120.0);
+        plan.validate(nodes, duration).unwrap();
+        assert_eq!(plan.branches[1].nodes().collect::<Vec<_>>(), ["Mast", "Base"]);
+        assert_eq!(plan.branches[2].move_nodes["Base"].alt, None);
+
+        assert!(plan.validate(["Mast"], duration).is_err());
+        assert!(plan.validate(nodes, SimTime::from_secs(60.0)).is_err());
+        assert!(WhatIfPlan::from_yaml(&PLAN.replace("from: busy }", "from: idle }")).unwrap().validate(nodes, duration).is_err());
+        assert!(WhatIfPlan::from_yaml(&PLAN.replace("power_off", "power_down")).is_err());
+    }
+
+    #[test]
+    fn test_branches_compared_from_checkpoint() {
+        let plan = WhatIfPlan::from_yaml(PLAN).unwrap();
+        let summary = |tx: u64, sent: u64| BehaviorSummary {
+            nodes: [("Mast".to_string(), NodeBehavior { tx, rx: 2 * tx, collisions: 0 })].into(),
+            messages_sent: sent,
+            messages_acked: 0,
+        };
+        let at_checkpoint = summary(10, 3);
+        let outcomes = vec![
+            BranchOutcome::new(&plan.branches[0], 7, &at_checkpoint, summary(25, 6), Some(0.9)),
+            BranchOutcome::new(&plan.branches[1], 7, &at_checkpoint, summary(12, 6), Some(0.5)),
+            BranchOutcome::new(&plan.branches[2], 8, &at_checkpoint, summary(25, 6), None),
+        ];
+        let report = WhatIfReport::new(1, SimTime::from_secs(120.0), outcomes);
+        assert_eq!(report.branches[0].tx, 15);
+        assert_eq!(report.branches[1].tx, 2);
+        assert_eq!(report.branches[1].messages_sent, 3);
+        assert!(report.branches[0].differences.is_empty());
+        assert_eq!(report.branches[1].differences.len(), 2);
+        assert!(report.branches[2].differences.is_empty());
+        assert_eq!(report.diverged_checkpoints, ["busy"]);
+        assert!(report.to_string().contains("mast-down"));
+    }
+}
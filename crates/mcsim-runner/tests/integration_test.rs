@@ -132,6 +132,7 @@ fn test_lora_collision_detection() {
         start_time: SimTime::from_millis(100),
         end_time: SimTime::from_millis(600),
         frequency_hz: 906_000_000,
+        bandwidth_hz: 62_500,
         packet_id: 2,
     };
 
@@ -139,6 +140,7 @@ fn test_lora_collision_detection() {
         start_time: SimTime::from_millis(0),
         end_time: SimTime::from_millis(500),
         frequency_hz: 906_000_000,
+        bandwidth_hz: 62_500,
         packet_id: 1,
     }];
 
@@ -158,6 +160,7 @@ fn test_lora_no_collision() {
         start_time: SimTime::from_millis(1000),
         end_time: SimTime::from_millis(1500),
         frequency_hz: 906_000_000,
+        bandwidth_hz: 62_500,
         packet_id: 2,
     };
 
@@ -165,6 +168,7 @@ fn test_lora_no_collision() {
         start_time: SimTime::from_millis(0),
         end_time: SimTime::from_millis(500),
         frequency_hz: 906_000_000,
+        bandwidth_hz: 62_500,
         packet_id: 1,
     }];
 
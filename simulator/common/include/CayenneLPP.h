@@ -18,6 +18,7 @@
 #define LPP_RELATIVE_HUMIDITY   104     // 1 byte, 0.5% unsigned
 #define LPP_ACCELEROMETER       113     // 2 bytes per axis, 0.001G
 #define LPP_BAROMETRIC_PRESSURE 115     // 2 bytes 0.1 hPa unsigned
+#define LPP_ALTITUDE            121     // 2 bytes 1m signed
 #define LPP_GYROMETER           134     // 2 bytes per axis, 0.01 °/s
 #define LPP_GPS                 136     // 3 byte lon/lat 0.0001 °, 3 bytes alt 0.01m
 
@@ -119,6 +120,17 @@ public:
         return cursor_;
     }
     
+    // Add altitude
+    uint8_t addAltitude(uint8_t channel, float meters) {
+        if (cursor_ + 4 > maxsize_) return 0;
+        int16_t val = static_cast<int16_t>(meters);
+        buffer_[cursor_++] = channel;
+        buffer_[cursor_++] = LPP_ALTITUDE;
+        buffer_[cursor_++] = (val >> 8) & 0xFF;
+        buffer_[cursor_++] = val & 0xFF;
+        return cursor_;
+    }
+    
     // Add luminosity
     uint8_t addLuminosity(uint8_t channel, uint16_t lux) {
         if (cursor_ + 4 > maxsize_) return 0;
@@ -55,7 +55,10 @@ typedef struct {
     uint32_t idle_loops_before_yield;    // Idle loop count before yield
     uint8_t log_spin_detection;          // Enable debug logging for spin detection (bool as u8)
     uint8_t log_loop_iterations;         // Enable debug logging for loop iterations (bool as u8)
-    uint8_t _padding[2];                 // Alignment padding
+    
+    // Routing
+    uint8_t flood_max;                   // Max hops of flood packets still forwarded (0 = firmware default)
+    uint8_t _padding[1];                 // Alignment padding
     
     // Reserved for future use
     uint8_t _reserved[56];               // Reduced from 64 to account for new fields
@@ -170,6 +173,20 @@ SIM_API void sim_notify_tx_complete(SimNodeHandle node);
 // This is used to synchronize radio state between the coordinator and the DLL.
 SIM_API void sim_notify_state_change(SimNodeHandle node, uint32_t state_version);
 
+// Set whether the node's radio currently senses activity on the channel
+// (a reception in progress). Firmware sees this through isReceiving(), which
+// MeshCore uses for listen-before-talk / CAD before transmitting.
+SIM_API void sim_set_channel_busy(SimNodeHandle node, int busy);
+
+// Set the battery voltage the firmware reads through getBattMilliVolts(),
+// e.g. when reporting battery level to a companion app.
+SIM_API void sim_set_battery_mv(SimNodeHandle node, uint16_t millivolts);
+
+// Set the environment sensor reading (barometric pressure, the altitude
+// derived from it, and temperature). Once set, the node's sensor manager
+// includes it in telemetry for requesters with environment permission.
+SIM_API void sim_set_environment(SimNodeHandle node, float pressure_hpa, float altitude_m, float temperature_c);
+
 // ============================================================================
 // Query API
 // ============================================================================
@@ -180,6 +197,16 @@ SIM_API const char* sim_get_node_type(void);
 // Get the public key of the node (after creation)
 SIM_API void sim_get_public_key(SimNodeHandle node, uint8_t* out_key);
 
+// Snapshot the node's outbound packet queue (packets waiting to be sent or
+// retransmitted), in queue order. Each packet is written to `buffer` as a
+// 2-byte little-endian length followed by the raw packet. `due_count` receives
+// the number of packets whose scheduled send time has passed.
+// Returns the number of packets written (stopping when `buffer` is full), or
+// -1 if the node type has no inspectable queue.
+// Can only be called while the node is yielded.
+SIM_API int sim_get_outbound_queue(SimNodeHandle node, uint8_t* buffer,
+                                    size_t max_len, int* due_count);
+
 // ============================================================================
 // Filesystem API (for coordinator to pre-populate or inspect)
 // ============================================================================
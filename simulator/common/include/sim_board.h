@@ -29,9 +29,25 @@ public:
     
     // Configuration
     void setBatteryMilliVolts(uint16_t mv) { battery_mv_ = mv; }
+    void setEnvironment(float pressure_hpa, float altitude_m, float temperature_c) {
+        pressure_hpa_ = pressure_hpa;
+        altitude_m_ = altitude_m;
+        temperature_c_ = temperature_c;
+        has_environment_ = true;
+    }
+    
+    // Environment sensor reading (barometer), if the node has one
+    bool hasEnvironment() const { return has_environment_; }
+    float getPressureHpa() const { return pressure_hpa_; }
+    float getAltitudeMeters() const { return altitude_m_; }
+    float getTemperatureC() const { return temperature_c_; }
 
 private:
     uint16_t battery_mv_;
+    bool has_environment_;
+    float pressure_hpa_;
+    float altitude_m_;
+    float temperature_c_;
     std::atomic<bool> reboot_requested_;
     std::atomic<bool> poweroff_requested_;
 };
@@ -72,6 +72,13 @@ struct SimNodeImpl {
     virtual void loop() = 0;
     virtual const char* getNodeType() const = 0;
     
+    // Outbound queue snapshot for sim_get_outbound_queue(). Node types with
+    // an inspectable packet queue override this; -1 means no queue.
+    virtual int snapshotOutboundQueue(uint8_t* buffer, size_t max_len, int* due_count) {
+        (void)buffer; (void)max_len; (void)due_count;
+        return -1;
+    }
+    
     SimNodeImpl() = default;
     virtual ~SimNodeImpl() = default;
     
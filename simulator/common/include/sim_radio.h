@@ -20,6 +20,7 @@ static constexpr size_t RX_QUEUE_DEPTH = 4;
 // - RX packets are injected by the coordinator via sim_inject_radio_rx()
 // - TX packets are captured and reported back to the coordinator
 // - State changes are notified via sim_notify_state_change()
+// - Channel activity (receptions in progress) is set via sim_set_channel_busy()
 
 struct RxPacket {
     uint8_t data[256];
@@ -61,6 +62,7 @@ public:
     void injectRxPacket(const uint8_t* data, size_t len, float rssi, float snr);
     void notifyTxComplete();
     void notifyStateChange(uint32_t state_version);
+    void setChannelBusy(bool busy);
     
     // Check if there's a pending TX (for yield)
     bool hasPendingTx() const { return tx_pending_; }
@@ -112,4 +114,5 @@ private:
     
     // State
     bool recv_mode_;
+    bool channel_busy_;               // Coordinator-reported reception in progress
 };
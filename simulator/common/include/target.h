@@ -26,6 +26,7 @@ public:
     EnvironmentSensorManager() : SensorManager() {}
     bool begin() override { return true; }
     void loop() override {}
+    bool querySensors(uint8_t requester_permissions, CayenneLPP& telemetry) override;
 };
 
 // Global extern declarations using internal names
@@ -36,6 +37,18 @@ extern thread_local SimRadio _sim_radio_instance;
 extern thread_local SimRTCClock _sim_rtc_instance;
 extern thread_local EnvironmentSensorManager _sim_sensors_instance;
 
+// Reports the board's simulated barometer (set by sim_set_environment)
+inline bool EnvironmentSensorManager::querySensors(uint8_t requester_permissions, CayenneLPP& telemetry) {
+    if (!(requester_permissions & TELEM_PERM_ENVIRONMENT) || !_sim_board_instance.hasEnvironment()) {
+        return false;
+    }
+    node_altitude = _sim_board_instance.getAltitudeMeters();
+    telemetry.addTemperature(TELEM_CHANNEL_SELF, _sim_board_instance.getTemperatureC());
+    telemetry.addBarometricPressure(TELEM_CHANNEL_SELF, _sim_board_instance.getPressureHpa());
+    telemetry.addAltitude(TELEM_CHANNEL_SELF, _sim_board_instance.getAltitudeMeters());
+    return true;
+}
+
 // Radio helper functions (stubs)
 // Note: these use the macro names which get redirected to internal names
 inline bool radio_init() {
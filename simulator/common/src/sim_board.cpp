@@ -3,6 +3,10 @@
 
 SimBoard::SimBoard() 
     : battery_mv_(4200)
+    , has_environment_(false)
+    , pressure_hpa_(0)
+    , altitude_m_(0)
+    , temperature_c_(0)
     , reboot_requested_(false)
     , poweroff_requested_(false)
 {
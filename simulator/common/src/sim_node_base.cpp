@@ -99,11 +99,33 @@ SIM_API void sim_notify_state_change(SimNodeHandle node, uint32_t state_version)
     node->radio_ptr->notifyStateChange(state_version);
 }
 
+SIM_API void sim_set_channel_busy(SimNodeHandle node, int busy) {
+    if (!node || !node->radio_ptr) return;
+    node->radio_ptr->setChannelBusy(busy != 0);
+}
+
+SIM_API void sim_set_battery_mv(SimNodeHandle node, uint16_t millivolts) {
+    if (!node || !node->board_ptr) return;
+    node->board_ptr->setBatteryMilliVolts(millivolts);
+}
+
+SIM_API void sim_set_environment(SimNodeHandle node, float pressure_hpa, float altitude_m, float temperature_c) {
+    if (!node || !node->board_ptr) return;
+    node->board_ptr->setEnvironment(pressure_hpa, altitude_m, temperature_c);
+}
+
 SIM_API void sim_get_public_key(SimNodeHandle node, uint8_t* out_key) {
     if (!node || !out_key) return;
     memcpy(out_key, node->config.public_key, SIM_PUB_KEY_SIZE);
 }
 
+SIM_API int sim_get_outbound_queue(SimNodeHandle node, uint8_t* buffer,
+                                    size_t max_len, int* due_count) {
+    if (due_count) *due_count = 0;
+    if (!node || !buffer) return -1;
+    return node->snapshotOutboundQueue(buffer, max_len, due_count);
+}
+
 SIM_API int sim_fs_write(SimNodeHandle node, const char* path, 
                           const uint8_t* data, size_t len) {
     if (!node) return -1;
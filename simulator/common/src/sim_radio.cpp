@@ -23,6 +23,7 @@ SimRadio::SimRadio()
     , last_polled_version_(0)
     , poll_count_(0)
     , recv_mode_(false)
+    , channel_busy_(false)
 {
 }
 
@@ -201,10 +202,20 @@ bool SimRadio::isInRecvMode() const {
     return recv_mode_ && !tx_in_progress_;
 }
 
+void SimRadio::setChannelBusy(bool busy) {
+    if (busy != channel_busy_) {
+        channel_busy_ = busy;
+        state_version_++;  // State changed - channel activity
+    }
+}
+
 bool SimRadio::isReceiving() {
     checkForSpin();
-    // In simulation, we don't have a concept of "currently receiving"
-    // Return true if there are packets in the queue
+    // Busy while the coordinator reports a reception in progress on the
+    // channel, or while received packets are still queued
+    if (channel_busy_) {
+        return true;
+    }
     std::lock_guard<std::mutex> lock(rx_mutex_);
     return !rx_queue_.empty();
 }
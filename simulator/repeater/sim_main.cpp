@@ -34,6 +34,18 @@ thread_local EnvironmentSensorManager _sim_sensors_instance;
 
 // Note: g_sim_ctx is defined in Arduino.cpp (sim_common library)
 
+// ============================================================================
+// Repeater mesh with access to its packet manager
+// ============================================================================
+
+// The packet manager is owned by the Dispatcher and only reachable from a
+// subclass; the simulator needs it to snapshot the outbound queue.
+struct SimRepeaterMesh : public MyMesh {
+    using MyMesh::MyMesh;
+    
+    mesh::PacketManager* packetManager() { return _mgr; }
+};
+
 // ============================================================================
 // Repeater-specific SimNode implementation
 // ============================================================================
@@ -42,7 +54,7 @@ struct RepeaterSimNode : public SimNodeImpl {
     // Firmware objects
     SimRNG fast_rng;
     SimpleMeshTables tables;
-    std::unique_ptr<MyMesh> mesh;
+    std::unique_ptr<SimRepeaterMesh> mesh;
     
     // CLI command buffer (matches firmware's main.cpp)
     char command[160];
@@ -70,7 +82,7 @@ struct RepeaterSimNode : public SimNodeImpl {
         
         // Create the mesh instance using the global board/radio references
         // (which are redirected via macros to _sim_board_instance etc.)
-        mesh = std::make_unique<MyMesh>(
+        mesh = std::make_unique<SimRepeaterMesh>(
             _sim_board_instance,
             _sim_radio_instance,
             ctx.millis_clock,
@@ -96,6 +108,11 @@ struct RepeaterSimNode : public SimNodeImpl {
             prefs->node_name[sizeof(prefs->node_name) - 1] = '\0';
         }
         
+        // Set the flood hop limit from config (same as CLI "set flood.max")
+        if (config.flood_max != 0) {
+            mesh->getNodePrefs()->flood_max = config.flood_max;
+        }
+        
         // Reset command buffer
         command[0] = 0;
     }
@@ -143,6 +160,29 @@ struct RepeaterSimNode : public SimNodeImpl {
     const char* getNodeType() const override {
         return "repeater";
     }
+    
+    int snapshotOutboundQueue(uint8_t* buffer, size_t max_len, int* due_count) override {
+        if (!mesh) return 0;
+        mesh::PacketManager* mgr = mesh->packetManager();
+        int total = mgr->getOutboundCount(0xFFFFFFFF);
+        *due_count = mgr->getOutboundCount((uint32_t)ctx.current_millis);
+        
+        size_t offset = 0;
+        int written = 0;
+        uint8_t raw[MAX_TRANS_UNIT + 1];
+        for (int i = 0; i < total; i++) {
+            mesh::Packet* pkt = mgr->getOutboundByIdx(i);
+            if (!pkt) continue;
+            uint8_t len = pkt->writeTo(raw);
+            if (offset + 2 + len > max_len) break;
+            buffer[offset++] = len & 0xFF;
+            buffer[offset++] = 0;
+            memcpy(buffer + offset, raw, len);
+            offset += len;
+            written++;
+        }
+        return written;
+    }
 };
 
 // ============================================================================
@@ -96,6 +96,11 @@ struct RoomServerSimNode : public SimNodeImpl {
             prefs->node_name[sizeof(prefs->node_name) - 1] = '\0';
         }
         
+        // Set the flood hop limit from config (same as CLI "set flood.max")
+        if (config.flood_max != 0) {
+            mesh->getNodePrefs()->flood_max = config.flood_max;
+        }
+        
         // Reset CLI command buffer
         command[0] = 0;
     }